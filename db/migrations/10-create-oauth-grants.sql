@@ -0,0 +1,15 @@
+CREATE TABLE oauth_grants (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    org_id TEXT NOT NULL,
+    app_id TEXT NOT NULL,
+    scope TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    last_used_at INTEGER NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id),
+    FOREIGN KEY (org_id) REFERENCES orgs(id),
+    FOREIGN KEY (app_id) REFERENCES apps(id)
+) STRICT;
+
+CREATE INDEX idx_oauth_grants_user_id ON oauth_grants(user_id);
+CREATE UNIQUE INDEX idx_oauth_grants_user_id_org_id_app_id ON oauth_grants(user_id, org_id, app_id);
@@ -0,0 +1,12 @@
+CREATE TABLE user_emails (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    email TEXT NOT NULL,
+    verification_code TEXT NULL DEFAULT NULL,
+    verified_at INTEGER NULL DEFAULT NULL,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+) STRICT;
+
+CREATE UNIQUE INDEX idx_user_emails_email ON user_emails(email);
+CREATE INDEX idx_user_emails_user_id ON user_emails(user_id);
@@ -0,0 +1,10 @@
+CREATE TABLE notification_prefs (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    delivery TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+) STRICT;
+
+CREATE UNIQUE INDEX idx_notification_prefs_user_id ON notification_prefs(user_id);
@@ -0,0 +1,16 @@
+CREATE TABLE approvals (
+    id TEXT PRIMARY KEY,
+    action TEXT NOT NULL,
+    target_id TEXT NOT NULL,
+    requested_by TEXT NOT NULL,
+    status TEXT NOT NULL,
+    decided_by TEXT NULL,
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    decided_at INTEGER NULL,
+    FOREIGN KEY (requested_by) REFERENCES users(id),
+    FOREIGN KEY (decided_by) REFERENCES users(id)
+) STRICT;
+
+CREATE INDEX idx_approvals_status_expires_at ON approvals(status, expires_at);
+CREATE INDEX idx_approvals_action_target_id ON approvals(action, target_id);
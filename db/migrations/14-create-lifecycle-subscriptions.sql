@@ -0,0 +1,13 @@
+CREATE TABLE lifecycle_subscriptions (
+    id TEXT PRIMARY KEY,
+    app_id TEXT NOT NULL,
+    topic TEXT NOT NULL,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    FOREIGN KEY (app_id) REFERENCES apps(id)
+) STRICT;
+
+CREATE UNIQUE INDEX idx_lifecycle_subscriptions_app_id_topic ON lifecycle_subscriptions(app_id, topic);
+CREATE INDEX idx_lifecycle_subscriptions_topic ON lifecycle_subscriptions(topic);
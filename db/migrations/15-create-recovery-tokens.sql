@@ -0,0 +1,12 @@
+CREATE TABLE recovery_tokens (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    token_hash TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    used_at INTEGER NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+) STRICT;
+
+CREATE UNIQUE INDEX idx_recovery_tokens_token_hash ON recovery_tokens(token_hash);
+CREATE INDEX idx_recovery_tokens_user_id ON recovery_tokens(user_id);
@@ -0,0 +1,8 @@
+CREATE TABLE app_uri_checks (
+    app_id TEXT PRIMARY KEY,
+    redirect_uri TEXT NOT NULL,
+    status TEXT NOT NULL,
+    message TEXT NULL,
+    checked_at INTEGER NOT NULL,
+    FOREIGN KEY (app_id) REFERENCES apps(id)
+) STRICT;
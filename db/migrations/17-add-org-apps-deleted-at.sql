@@ -0,0 +1,3 @@
+ALTER TABLE org_apps ADD COLUMN deleted_at INTEGER DEFAULT NULL;
+
+CREATE INDEX idx_org_apps_deleted_at ON org_apps(deleted_at);
@@ -0,0 +1,14 @@
+ALTER TABLE users ADD COLUMN created_by TEXT DEFAULT NULL;
+ALTER TABLE users ADD COLUMN updated_by TEXT DEFAULT NULL;
+
+ALTER TABLE orgs ADD COLUMN created_by TEXT DEFAULT NULL;
+ALTER TABLE orgs ADD COLUMN updated_by TEXT DEFAULT NULL;
+
+ALTER TABLE apps ADD COLUMN created_by TEXT DEFAULT NULL;
+ALTER TABLE apps ADD COLUMN updated_by TEXT DEFAULT NULL;
+
+ALTER TABLE org_members ADD COLUMN created_by TEXT DEFAULT NULL;
+ALTER TABLE org_members ADD COLUMN updated_by TEXT DEFAULT NULL;
+
+ALTER TABLE org_apps ADD COLUMN created_by TEXT DEFAULT NULL;
+ALTER TABLE org_apps ADD COLUMN updated_by TEXT DEFAULT NULL;
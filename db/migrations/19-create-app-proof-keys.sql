@@ -0,0 +1,7 @@
+CREATE TABLE app_proof_keys (
+    app_id TEXT PRIMARY KEY,
+    key_id TEXT NOT NULL UNIQUE,
+    public_key TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (app_id) REFERENCES apps(id)
+) STRICT;
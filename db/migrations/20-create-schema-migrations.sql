@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS schema_migrations (
+    name TEXT PRIMARY KEY,
+    phase TEXT NOT NULL,
+    applied_at INTEGER NOT NULL
+);
@@ -0,0 +1,11 @@
+CREATE TABLE org_access_log (
+    org_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    day TEXT NOT NULL,
+    first_seen_at INTEGER NOT NULL,
+    last_seen_at INTEGER NOT NULL,
+    samples INTEGER NOT NULL DEFAULT 1,
+    PRIMARY KEY (org_id, user_id, day)
+) STRICT;
+
+CREATE INDEX idx_org_access_log_day ON org_access_log(day);
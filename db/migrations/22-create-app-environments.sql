@@ -0,0 +1,11 @@
+CREATE TABLE app_environments (
+    id TEXT PRIMARY KEY,
+    app_id TEXT NOT NULL,
+    label TEXT NOT NULL,
+    client_id TEXT NOT NULL UNIQUE,
+    client_secret TEXT NOT NULL,
+    redirect_uri TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    UNIQUE (app_id, label),
+    FOREIGN KEY (app_id) REFERENCES apps(id)
+) STRICT;
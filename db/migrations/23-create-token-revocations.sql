@@ -0,0 +1,6 @@
+CREATE TABLE token_revocations (
+    user_id TEXT PRIMARY KEY,
+    revoked_at INTEGER NOT NULL,
+    revoked_by TEXT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+) STRICT;
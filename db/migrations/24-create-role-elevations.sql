@@ -0,0 +1,20 @@
+CREATE TABLE role_elevations (
+    id TEXT PRIMARY KEY,
+    org_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    role TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    duration_mins INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    requested_by TEXT NOT NULL,
+    decided_by TEXT NULL,
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER NULL,
+    ended_at INTEGER NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id),
+    FOREIGN KEY (requested_by) REFERENCES users(id),
+    FOREIGN KEY (decided_by) REFERENCES users(id)
+) STRICT;
+
+CREATE INDEX idx_role_elevations_org_id_user_id ON role_elevations(org_id, user_id);
+CREATE INDEX idx_role_elevations_status_expires_at ON role_elevations(status, expires_at);
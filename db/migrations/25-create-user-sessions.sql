@@ -0,0 +1,13 @@
+CREATE TABLE user_sessions (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    user_agent TEXT NULL,
+    ip_address TEXT NULL,
+    created_at INTEGER NOT NULL,
+    last_used_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    revoked_at INTEGER NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+) STRICT;
+
+CREATE INDEX idx_user_sessions_user_id_expires_at ON user_sessions(user_id, expires_at);
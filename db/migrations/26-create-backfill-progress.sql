@@ -0,0 +1,8 @@
+CREATE TABLE backfill_progress (
+    name TEXT PRIMARY KEY,
+    last_id TEXT NULL,
+    rows_done INTEGER NOT NULL,
+    started_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    completed_at INTEGER NULL
+) STRICT;
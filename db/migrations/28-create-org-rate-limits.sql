@@ -0,0 +1,8 @@
+CREATE TABLE org_rate_limits (
+    org_id TEXT PRIMARY KEY,
+    requests_per_min INTEGER NOT NULL,
+    burst INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    updated_by TEXT NULL,
+    FOREIGN KEY (org_id) REFERENCES orgs(id)
+) STRICT;
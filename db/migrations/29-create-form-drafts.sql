@@ -0,0 +1,11 @@
+CREATE TABLE form_drafts (
+    user_id TEXT NOT NULL,
+    form_key TEXT NOT NULL,
+    data TEXT NOT NULL,
+    updated_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    PRIMARY KEY (user_id, form_key),
+    FOREIGN KEY (user_id) REFERENCES users(id)
+) STRICT;
+
+CREATE INDEX idx_form_drafts_expires_at ON form_drafts(expires_at);
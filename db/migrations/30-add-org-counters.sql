@@ -0,0 +1,27 @@
+ALTER TABLE orgs ADD COLUMN member_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE orgs ADD COLUMN app_count INTEGER NOT NULL DEFAULT 0;
+
+UPDATE orgs SET
+    member_count = (
+        SELECT COUNT(*)
+        FROM org_members
+        INNER JOIN users ON users.id = org_members.user_id
+        WHERE
+            org_members.org_id = orgs.id
+            AND users.deleted_at IS NULL
+    ),
+    app_count = (
+        SELECT COUNT(*)
+        FROM org_apps
+        INNER JOIN apps ON apps.id = org_apps.app_id
+        WHERE
+            org_apps.org_id = orgs.id
+            AND org_apps.deleted_at IS NULL
+            AND apps.deleted_at IS NULL
+    );
+
+CREATE TABLE stat_counters (
+    name TEXT PRIMARY KEY,
+    value INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+) STRICT;
@@ -0,0 +1,24 @@
+CREATE TABLE org_roles (
+    id TEXT PRIMARY KEY,
+    org_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    permissions TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    created_by TEXT NULL,
+    updated_by TEXT NULL,
+    UNIQUE (org_id, name),
+    FOREIGN KEY (org_id) REFERENCES orgs(id)
+) STRICT;
+
+CREATE TABLE org_member_roles (
+    org_member_id TEXT NOT NULL,
+    role_id TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (org_member_id, role_id),
+    FOREIGN KEY (org_member_id) REFERENCES org_members(id),
+    FOREIGN KEY (role_id) REFERENCES org_roles(id)
+) STRICT;
+
+CREATE INDEX idx_org_member_roles_role_id ON org_member_roles(role_id);
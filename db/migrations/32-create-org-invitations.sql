@@ -0,0 +1,17 @@
+CREATE TABLE org_invitations (
+    id TEXT PRIMARY KEY,
+    org_id TEXT NOT NULL,
+    email TEXT NOT NULL,
+    roles TEXT NOT NULL,
+    token_hash TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    accepted_at INTEGER NULL,
+    accepted_by TEXT NULL,
+    created_by TEXT NULL,
+    FOREIGN KEY (org_id) REFERENCES orgs(id)
+) STRICT;
+
+CREATE UNIQUE INDEX idx_org_invitations_token_hash ON org_invitations(token_hash);
+CREATE INDEX idx_org_invitations_org_id_email ON org_invitations(org_id, email);
@@ -0,0 +1,19 @@
+CREATE TABLE lifecycle_deliveries (
+    id TEXT PRIMARY KEY,
+    event_id TEXT NOT NULL,
+    subscription_id TEXT NOT NULL,
+    app_id TEXT NOT NULL,
+    topic TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    next_attempt_at INTEGER NOT NULL,
+    last_status INTEGER NULL,
+    last_error TEXT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    delivered_at INTEGER NULL
+) STRICT;
+
+CREATE INDEX idx_lifecycle_deliveries_status_next_attempt_at ON lifecycle_deliveries(status, next_attempt_at);
+CREATE INDEX idx_lifecycle_deliveries_app_id_created_at ON lifecycle_deliveries(app_id, created_at);
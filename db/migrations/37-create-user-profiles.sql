@@ -0,0 +1,11 @@
+CREATE TABLE user_profiles (
+    user_id TEXT PRIMARY KEY,
+    avatar_url TEXT DEFAULT NULL,
+    locale TEXT DEFAULT NULL,
+    timezone TEXT DEFAULT NULL,
+    phone TEXT DEFAULT NULL,
+    metadata TEXT NOT NULL DEFAULT '{}',
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+) STRICT;
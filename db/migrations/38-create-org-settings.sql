@@ -0,0 +1,9 @@
+CREATE TABLE org_settings (
+    org_id TEXT PRIMARY KEY,
+    logo_url TEXT DEFAULT NULL,
+    default_member_role TEXT NOT NULL,
+    allowed_email_domains TEXT NOT NULL DEFAULT '',
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    FOREIGN KEY (org_id) REFERENCES orgs(id)
+) STRICT;
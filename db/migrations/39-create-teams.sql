@@ -0,0 +1,24 @@
+CREATE TABLE teams (
+    id TEXT PRIMARY KEY,
+    org_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    roles TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    created_by TEXT NULL,
+    updated_by TEXT NULL,
+    UNIQUE (org_id, name),
+    FOREIGN KEY (org_id) REFERENCES orgs(id)
+) STRICT;
+
+CREATE TABLE team_members (
+    team_id TEXT NOT NULL,
+    org_member_id TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (team_id, org_member_id),
+    FOREIGN KEY (team_id) REFERENCES teams(id),
+    FOREIGN KEY (org_member_id) REFERENCES org_members(id)
+) STRICT;
+
+CREATE INDEX idx_team_members_org_member_id ON team_members(org_member_id);
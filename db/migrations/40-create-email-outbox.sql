@@ -0,0 +1,17 @@
+CREATE TABLE email_outbox (
+    id TEXT PRIMARY KEY,
+    org_id TEXT NULL,
+    recipient TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    text_body TEXT NOT NULL,
+    html_body TEXT NOT NULL,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    next_attempt_at INTEGER NOT NULL,
+    last_error TEXT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    sent_at INTEGER NULL
+) STRICT;
+
+CREATE INDEX idx_email_outbox_status_next_attempt_at ON email_outbox(status, next_attempt_at);
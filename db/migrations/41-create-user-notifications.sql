@@ -0,0 +1,13 @@
+CREATE TABLE user_notifications (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    title TEXT NOT NULL,
+    body TEXT NOT NULL,
+    link TEXT NULL,
+    read_at INTEGER NULL,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+) STRICT;
+
+CREATE INDEX idx_user_notifications_user_id_created_at ON user_notifications(user_id, created_at);
@@ -0,0 +1,15 @@
+CREATE TABLE idempotency_keys (
+    user_id TEXT NOT NULL,
+    route TEXT NOT NULL,
+    idempotency_key TEXT NOT NULL,
+    request_hash TEXT NOT NULL,
+    status_code INTEGER NULL,
+    content_type TEXT NULL,
+    body TEXT NULL,
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    PRIMARY KEY (user_id, route, idempotency_key),
+    FOREIGN KEY (user_id) REFERENCES users(id)
+) STRICT;
+
+CREATE INDEX idx_idempotency_keys_expires_at ON idempotency_keys(expires_at);
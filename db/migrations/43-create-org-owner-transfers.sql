@@ -0,0 +1,19 @@
+CREATE TABLE org_owner_transfers (
+    id TEXT PRIMARY KEY,
+    org_id TEXT NOT NULL,
+    from_user_id TEXT NOT NULL,
+    to_user_id TEXT NOT NULL,
+    token_hash TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    accepted_at INTEGER NULL,
+    cancelled_at INTEGER NULL,
+    created_by TEXT NULL,
+    FOREIGN KEY (org_id) REFERENCES orgs(id),
+    FOREIGN KEY (from_user_id) REFERENCES users(id),
+    FOREIGN KEY (to_user_id) REFERENCES users(id)
+) STRICT;
+
+CREATE UNIQUE INDEX idx_org_owner_transfers_token_hash ON org_owner_transfers(token_hash);
+CREATE INDEX idx_org_owner_transfers_org_id ON org_owner_transfers(org_id);
@@ -0,0 +1 @@
+ALTER TABLE apps ADD COLUMN extra_redirect_uris TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,2 @@
+ALTER TABLE apps ADD COLUMN scopes TEXT DEFAULT NULL;
+ALTER TABLE org_apps ADD COLUMN scopes TEXT DEFAULT NULL;
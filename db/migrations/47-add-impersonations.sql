@@ -0,0 +1,26 @@
+CREATE TABLE impersonations (
+    id TEXT PRIMARY KEY,
+    impersonator_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    org_id TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER NOT NULL,
+    ended_at INTEGER NULL,
+    FOREIGN KEY (impersonator_id) REFERENCES users(id),
+    FOREIGN KEY (user_id) REFERENCES users(id),
+    FOREIGN KEY (org_id) REFERENCES orgs(id)
+) STRICT;
+
+CREATE INDEX idx_impersonations_user_id_created_at ON impersonations(user_id, created_at);
+
+CREATE TABLE impersonation_actions (
+    id TEXT PRIMARY KEY,
+    impersonation_id TEXT NOT NULL,
+    method TEXT NOT NULL,
+    path TEXT NOT NULL,
+    status INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (impersonation_id) REFERENCES impersonations(id)
+) STRICT;
+
+CREATE INDEX idx_impersonation_actions_impersonation_id ON impersonation_actions(impersonation_id, created_at);
@@ -0,0 +1,15 @@
+CREATE TABLE user_exports (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    requested_by TEXT NULL,
+    format TEXT NOT NULL,
+    status TEXT NOT NULL,
+    content BLOB NULL,
+    size INTEGER NULL,
+    created_at INTEGER NOT NULL,
+    completed_at INTEGER NULL,
+    expires_at INTEGER NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+) STRICT;
+
+CREATE INDEX idx_user_exports_user_id_created_at ON user_exports(user_id, created_at);
@@ -0,0 +1 @@
+ALTER TABLE org_invitations ADD COLUMN email_id TEXT DEFAULT NULL;
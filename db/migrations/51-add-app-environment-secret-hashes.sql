@@ -0,0 +1,3 @@
+ALTER TABLE app_environments ADD COLUMN client_secret_hash TEXT DEFAULT NULL;
+ALTER TABLE app_environments ADD COLUMN previous_secret_hash TEXT DEFAULT NULL;
+ALTER TABLE app_environments ADD COLUMN previous_secret_expires_at INTEGER DEFAULT NULL;
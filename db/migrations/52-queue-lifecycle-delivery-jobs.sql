@@ -0,0 +1,23 @@
+INSERT INTO jobs (
+    id,
+    kind,
+    payload,
+    status,
+    attempts,
+    max_attempts,
+    run_at,
+    created_at,
+    updated_at
+)
+SELECT
+    'job_' || substr(id, 5),
+    'lifecycle.delivery',
+    '{"delivery_id":"' || id || '"}',
+    'queued',
+    attempts,
+    10,
+    next_attempt_at,
+    created_at,
+    updated_at
+FROM lifecycle_deliveries
+WHERE status = 'pending';
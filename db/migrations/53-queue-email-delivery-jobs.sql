@@ -0,0 +1,23 @@
+INSERT INTO jobs (
+    id,
+    kind,
+    payload,
+    status,
+    attempts,
+    max_attempts,
+    run_at,
+    created_at,
+    updated_at
+)
+SELECT
+    'job_' || substr(id, 5),
+    'email.delivery',
+    '{"email_id":"' || id || '"}',
+    'queued',
+    attempts,
+    8,
+    next_attempt_at,
+    created_at,
+    updated_at
+FROM email_outbox
+WHERE status = 'pending';
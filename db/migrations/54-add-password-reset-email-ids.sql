@@ -0,0 +1 @@
+ALTER TABLE password_resets ADD COLUMN email_id TEXT DEFAULT NULL;
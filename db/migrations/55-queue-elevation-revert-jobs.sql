@@ -0,0 +1,23 @@
+INSERT INTO jobs (
+    id,
+    kind,
+    payload,
+    status,
+    attempts,
+    max_attempts,
+    run_at,
+    created_at,
+    updated_at
+)
+SELECT
+    'job_' || substr(id, 5),
+    'org.elevation_revert',
+    '{"elevation_id":"' || id || '"}',
+    'queued',
+    0,
+    5,
+    expires_at,
+    created_at,
+    created_at
+FROM role_elevations
+WHERE status = 'active';
@@ -1,3 +1,6 @@
 import '../public/assets/js/site.js';
 import '../public/assets/js/nav.js';
 import '../public/assets/js/login.js';
+import '../public/assets/js/palette.js';
+import '../public/assets/js/drafts.js';
+import '../public/assets/js/admin-events.js';
@@ -0,0 +1,35 @@
+(function () {
+  if (window.X_ADMIN_EVENTS) {
+    return;
+  }
+
+  // Only superuser pages with live tables listen to changes
+  if (!document.querySelector('[data-admin-events]') || !window.EventSource || !window.htmx) {
+    return;
+  }
+
+  window.X_ADMIN_EVENTS = true;
+
+  const triggers = {
+    user: 'usersChanged',
+    org: 'orgsChanged',
+    org_member: 'orgMembersChanged',
+  };
+
+  const source = new EventSource('/events');
+
+  for (const [entity, name] of Object.entries(triggers)) {
+    source.addEventListener(entity, (e) => {
+      htmx.trigger(document.body, name, JSON.parse(e.data));
+    });
+  }
+
+  // Changes were missed, reload every table
+  source.addEventListener('resync', () => {
+    for (const name of Object.values(triggers)) {
+      htmx.trigger(document.body, name, {});
+    }
+  });
+
+  window.addEventListener('beforeunload', () => source.close());
+})();
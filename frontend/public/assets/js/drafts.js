@@ -0,0 +1,45 @@
+(function () {
+  if (!window.X_DRAFT_EVENTS) {
+    window.X_DRAFT_EVENTS = true;
+
+    document.addEventListener('click', (e) => {
+      const button = e.target.closest('[data-draft-restore]');
+      if (!button) {
+        return;
+      }
+
+      const prompt = button.closest('[data-draft]');
+      const form = button.closest('form');
+      if (!prompt || !form) {
+        return;
+      }
+
+      e.preventDefault();
+      restoreDraft(form, JSON.parse(prompt.dataset.draft));
+      prompt.remove();
+    });
+  }
+
+  // Fills the form with the draft values, hidden and secret fields are never part of a draft
+  function restoreDraft(form, data) {
+    for (const field of form.elements) {
+      if (!field.name || field.type === 'hidden' || field.type === 'password') {
+        continue;
+      }
+
+      const value = data[field.name];
+      const values = Array.isArray(value) ? value : value === undefined ? [] : [value];
+
+      if (field.type === 'checkbox' || field.type === 'radio') {
+        // Unchecked boxes are not submitted, so they are missing from the draft
+        field.checked = values.includes(field.value);
+      } else if (field.tagName === 'SELECT' && field.multiple) {
+        for (const option of field.options) {
+          option.selected = values.includes(option.value);
+        }
+      } else if (values.length > 0) {
+        field.value = values[0];
+      }
+    }
+  }
+})();
@@ -0,0 +1,92 @@
+(function () {
+  if (!window.X_PALETTE_EVENTS) {
+    window.X_PALETTE_EVENTS = true;
+
+    document.addEventListener('keydown', (e) => {
+      if ((e.ctrlKey || e.metaKey) && e.key.toLowerCase() === 'k') {
+        const container = document.getElementById('palette-container');
+        if (container) {
+          e.preventDefault();
+          openPalette(container);
+        }
+        return;
+      }
+
+      const palette = document.getElementById('command-palette');
+      if (!palette) {
+        return;
+      }
+
+      if (e.key === 'Escape') {
+        e.preventDefault();
+        closePalette();
+      } else if (e.key === 'ArrowDown') {
+        e.preventDefault();
+        moveSelection(1);
+      } else if (e.key === 'ArrowUp') {
+        e.preventDefault();
+        moveSelection(-1);
+      } else if (e.key === 'Enter') {
+        const active = palette.querySelector('[data-palette-item].is-active');
+        if (active) {
+          e.preventDefault();
+          window.location.href = active.getAttribute('href');
+        }
+      }
+    });
+
+    document.addEventListener('click', (e) => {
+      if (e.target.closest('[data-palette-close]')) {
+        closePalette();
+      }
+
+      if (e.target.closest('#btn-command-palette')) {
+        const container = document.getElementById('palette-container');
+        if (container) {
+          openPalette(container);
+        }
+      }
+    });
+
+    function openPalette(container) {
+      if (document.getElementById('command-palette')) {
+        focusInput();
+        return;
+      }
+
+      htmx
+        .ajax('GET', '/palette', { target: container, swap: 'innerHTML' })
+        .then(focusInput);
+    }
+
+    function closePalette() {
+      const container = document.getElementById('palette-container');
+      if (container) {
+        container.innerHTML = '';
+      }
+    }
+
+    function focusInput() {
+      const input = document.getElementById('command-palette-input');
+      if (input) {
+        input.focus();
+      }
+    }
+
+    function moveSelection(offset) {
+      const items = Array.from(
+        document.querySelectorAll('#command-palette [data-palette-item]'),
+      );
+      if (items.length === 0) {
+        return;
+      }
+
+      let index = items.findIndex((item) => item.classList.contains('is-active'));
+      items.forEach((item) => item.classList.remove('is-active'));
+
+      index = (index + offset + items.length) % items.length;
+      items[index].classList.add('is-active');
+      items[index].scrollIntoView({ block: 'nearest' });
+    }
+  }
+})();
@@ -2,8 +2,10 @@ use serde::Deserialize;
 use snafu::ResultExt;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::{env, fs};
 
+use crate::dto::PaginationLimits;
 use crate::error::{ManifestParseSnafu, ManifestReadSnafu};
 use crate::{Error, Result};
 
@@ -13,10 +15,41 @@ pub struct Config {
     pub db: DbConfig,
     pub superuser: SuperuserConfig,
     pub jwt_secret: String,
+    /// Auth and access tokens are signed with HS256 and `jwt_secret` when not set
+    pub jwt_signing: Option<JwtSigningConfig>,
+    pub token_denylist: TokenDenylistStore,
     pub frontend_dir: PathBuf,
     pub captcha_site_key: Option<String>,
     pub captcha_api_key: Option<String>,
     pub ga_tag_id: Option<String>,
+    pub token_claims: TokenClaimsConfig,
+    /// Minutes between background orphan scans, 0 disables the job
+    pub integrity_scan_mins: u64,
+    /// Hours between pending membership digests, 0 disables the job
+    pub notification_digest_hours: u64,
+    /// Hours a rotated app client secret keeps working, 0 cuts it off right away
+    pub app_secret_grace_hours: u64,
+    /// Minutes between logged memory samples, 0 disables the job
+    pub memory_sample_mins: u64,
+    /// Minutes between org and deployment counter reconciles, 0 disables the job
+    pub counter_reconcile_mins: u64,
+    /// Days of org access history to keep, 0 keeps it forever
+    pub org_access_retention_days: u64,
+    pub pagination: PaginationLimits,
+    pub approvals: ApprovalConfig,
+    pub org_rate_limit: OrgRateLimitConfig,
+    pub auth_rate_limit: AuthRateLimitConfig,
+    pub auth_cache: AuthCacheConfig,
+    /// Caches stay on each instance when not set
+    pub redis: Option<RedisConfig>,
+    pub region: RegionConfig,
+    /// Canaried routes keyed by their route pattern
+    pub route_rollouts: HashMap<String, RouteRollout>,
+    /// Probe verified redirect URIs with a HEAD request in the background
+    pub redirect_uri_probe: bool,
+    /// OpenID Connect is off when not set
+    pub oidc: Option<OidcConfig>,
+    pub mailer: MailerConfig,
     pub assets: AssetManifest,
 }
 
@@ -26,17 +59,392 @@ pub struct ServerConfig {
     pub https: bool,
 }
 
+/// OpenID Connect provider settings
+#[derive(Clone, Deserialize)]
+pub struct OidcConfig {
+    /// Public base URL of the deployment, used as `iss` and for the discovery URLs
+    pub issuer: String,
+    /// PKCS#8 PEM of the RSA key signing the ID tokens
+    pub signing_key_pem: String,
+}
+
+/// Outgoing email settings
+#[derive(Clone, Deserialize)]
+pub struct MailerConfig {
+    /// `From` of every message, ex: `Yaas <noreply@example.com>`
+    pub from: String,
+    /// Public base URL of the web UI, links in emails start with it
+    pub base_url: String,
+    pub transport: MailTransport,
+}
+
+/// Where outgoing emails go
+#[derive(Clone, Deserialize)]
+pub enum MailTransport {
+    /// Written to the log for operators to relay, for development
+    Log,
+    Smtp(SmtpConfig),
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub tls: SmtpTls,
+}
+
+/// How the SMTP connection is secured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum SmtpTls {
+    /// Upgrades a plain connection, usually on port 587
+    StartTls,
+    /// TLS from the start, usually on port 465
+    Tls,
+    /// Plain text, for a relay on the same host or network only
+    None,
+}
+
+impl MailerConfig {
+    fn from_env(server_address: &str, https: bool) -> Result<Self> {
+        let scheme = if https { "https" } else { "http" };
+        let base_url = optional_env("PUBLIC_URL")
+            .unwrap_or_else(|| format!("{}://{}", scheme, server_address))
+            .trim()
+            .trim_end_matches('/')
+            .to_string();
+
+        let transport = match optional_env("MAILER").as_deref() {
+            None | Some("log") => MailTransport::Log,
+            Some("smtp") => {
+                let tls = match optional_env("SMTP_TLS").as_deref() {
+                    None | Some("starttls") => SmtpTls::StartTls,
+                    Some("tls") => SmtpTls::Tls,
+                    Some("none") => SmtpTls::None,
+                    Some(other) => {
+                        return Err(Error::Config {
+                            msg: format!("SMTP_TLS must be starttls, tls or none: {}", other),
+                        });
+                    }
+                };
+                let default_port = if tls == SmtpTls::Tls { 465 } else { 587 };
+
+                MailTransport::Smtp(SmtpConfig {
+                    host: required_env("SMTP_HOST")?.trim().to_string(),
+                    port: optional_number_env("SMTP_PORT", default_port)?,
+                    username: optional_env("SMTP_USERNAME"),
+                    password: optional_env("SMTP_PASSWORD"),
+                    tls,
+                })
+            }
+            Some(other) => {
+                return Err(Error::Config {
+                    msg: format!("MAILER must be log or smtp: {}", other),
+                });
+            }
+        };
+
+        Ok(Self {
+            from: optional_env("MAIL_FROM")
+                .unwrap_or_else(|| "Yaas <noreply@localhost>".to_string()),
+            base_url,
+            transport,
+        })
+    }
+}
+
+/// Where the ids of individually revoked tokens are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum TokenDenylistStore {
+    /// Shared by every instance and kept across restarts
+    Database,
+    /// Per instance and lost on restart, for single instance deployments
+    Memory,
+    /// Shared through Redis, kept until the tokens expire
+    Redis,
+}
+
+/// Asymmetric signing of auth and access tokens
+#[derive(Clone, Deserialize)]
+pub struct JwtSigningConfig {
+    /// PKCS#8 PEM of the RSA or Ed25519 key signing new tokens
+    pub signing_key_pem: String,
+    /// Previous signing keys still accepted while their tokens expire
+    pub retired_keys: Vec<RetiredKeyConfig>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct RetiredKeyConfig {
+    pub key_pem: String,
+    /// Unix timestamp in seconds, tokens of the key are rejected from then on
+    pub accepted_until: i64,
+}
+
+impl JwtSigningConfig {
+    /// Parses `JWT_RETIRED_KEYS`, ex: `/keys/2025.pem=2026-11-01,/keys/2024.pem=2026-10-20`
+    ///
+    /// Each key is accepted until the start of its date in UTC.
+    pub fn parse_retired_keys(value: &str) -> Result<Vec<(PathBuf, i64)>> {
+        let mut keys = Vec::new();
+
+        for entry in value.split(',').map(|e| e.trim()).filter(|e| !e.is_empty()) {
+            let Some((path, date)) = entry.rsplit_once('=') else {
+                return Err(Error::Config {
+                    msg: format!("JWT_RETIRED_KEYS entry must be path=YYYY-MM-DD: {}", entry),
+                });
+            };
+
+            let Ok(date) = chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d") else {
+                return Err(Error::Config {
+                    msg: format!("JWT_RETIRED_KEYS contains an invalid date: {}", date),
+                });
+            };
+
+            let until = date
+                .and_hms_opt(0, 0, 0)
+                .expect("Midnight is a valid time")
+                .and_utc()
+                .timestamp();
+
+            keys.push((PathBuf::from(path.trim()), until));
+        }
+
+        Ok(keys)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct DbConfig {
     pub dir: PathBuf,
+    /// Read replica of the database, listings are read from it when set
+    pub read_path: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct SuperuserConfig {
-    /// Key used to set up the superuser account
+    /// Key used to set up the superuser account, generated at every boot
     pub setup_key: Option<String>,
 }
 
+/// Two-person rule for critical superuser operations
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApprovalConfig {
+    /// Critical operations wait for a second superuser when enabled
+    pub two_person_rule: bool,
+    /// Minutes a pending approval stays open
+    pub window_mins: u64,
+    /// Elevated org roles wait for a second member admin when enabled
+    pub elevation_approval: bool,
+}
+
+/// Default request budget of each org, orgs can override it
+#[derive(Debug, Clone, Deserialize)]
+pub struct OrgRateLimitConfig {
+    /// Sustained requests per minute, 0 disables org rate limits
+    pub requests_per_min: i64,
+    /// Requests allowed at once before the per minute rate applies
+    pub burst: i64,
+}
+
+/// Attempts allowed on the sign in and recovery forms within each window
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthRateLimitConfig {
+    /// Attempts per client IP, 0 disables the IP limit
+    pub per_ip: u64,
+    /// Attempts per email, 0 disables the email limit
+    pub per_email: u64,
+    pub window_secs: u64,
+}
+
+/// In-memory cache of the user, membership and permission lookups behind each request
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthCacheConfig {
+    /// Seconds a cached lookup is reused, 0 disables the cache
+    pub ttl_secs: u64,
+    /// Users kept in the cache at most
+    pub max_capacity: u64,
+}
+
+/// Redis shared by the instances of a deployment
+#[derive(Clone, Deserialize)]
+pub struct RedisConfig {
+    /// `redis://` or `rediss://` URL, may include the password
+    pub url: String,
+}
+
+/// Optional claims embedded into OAuth access tokens.
+///
+/// Core claims (subject, org, scope and expiry) are always included.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenClaimsConfig {
+    pub email: bool,
+    pub name: bool,
+    pub roles: bool,
+    pub permissions: bool,
+}
+
+impl Default for TokenClaimsConfig {
+    fn default() -> Self {
+        Self {
+            email: false,
+            name: false,
+            roles: true,
+            permissions: false,
+        }
+    }
+}
+
+impl TokenClaimsConfig {
+    /// Parses a comma separated list of claims, ex: `roles,email` or `none`
+    pub fn parse(value: &str) -> Result<Self> {
+        let mut config = Self {
+            email: false,
+            name: false,
+            roles: false,
+            permissions: false,
+        };
+
+        for claim in value.split(',').map(|c| c.trim()).filter(|c| !c.is_empty()) {
+            match claim {
+                "email" => config.email = true,
+                "name" => config.name = true,
+                "roles" => config.roles = true,
+                "permissions" => config.permissions = true,
+                "none" => {}
+                _ => {
+                    return Err(Error::Config {
+                        msg: format!("TOKEN_CLAIMS contains unknown claim: {}", claim),
+                    });
+                }
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// What happens to requests whose session lives in another region
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum RegionRouting {
+    /// Requests are served wherever they land
+    Off,
+    /// Requests are rejected with 421 Misdirected Request
+    Reject,
+    /// Requests are redirected to the home region, rejected when its URL is unknown
+    Redirect,
+}
+
+/// Region of this deployment, stamped into tokens for session stickiness
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegionConfig {
+    /// Region name, ex: `ap-southeast-1`, unset for single region deployments
+    pub name: Option<String>,
+    pub routing: RegionRouting,
+    /// Base URL of each region, used for redirects
+    pub urls: HashMap<String, String>,
+}
+
+impl RegionConfig {
+    /// Parses `REGION_URLS`, ex: `us-east-1=https://us.example.com,eu-west-1=https://eu.example.com`
+    pub fn parse_urls(value: &str) -> Result<HashMap<String, String>> {
+        let mut urls = HashMap::new();
+
+        for entry in value.split(',').map(|e| e.trim()).filter(|e| !e.is_empty()) {
+            let Some((region, url)) = entry.split_once('=') else {
+                return Err(Error::Config {
+                    msg: format!("REGION_URLS entry must be region=url: {}", entry),
+                });
+            };
+
+            let url = url.trim().trim_end_matches('/');
+            if url::Url::parse(url).is_err() {
+                return Err(Error::Config {
+                    msg: format!("REGION_URLS contains an invalid URL: {}", url),
+                });
+            }
+
+            urls.insert(region.trim().to_string(), url.to_string());
+        }
+
+        Ok(urls)
+    }
+}
+
+/// Hash the rollout cohort is picked by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum RolloutKey {
+    Org,
+    User,
+}
+
+/// Percentage rollout of a route's new implementation
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RouteRollout {
+    /// Share of orgs or users served the canary, 0 to 100
+    pub percent: u8,
+    pub key: RolloutKey,
+    /// Requests outside the cohort get a 404 instead of the stable variant
+    pub gated: bool,
+}
+
+impl RouteRollout {
+    /// Parses `ROUTE_ROLLOUTS`, ex: `/oauth/profile=25,/orgs/{org_id}/members=10:user:gate`
+    pub fn parse_all(value: &str) -> Result<HashMap<String, RouteRollout>> {
+        let mut rollouts = HashMap::new();
+
+        for entry in value.split(',').map(|e| e.trim()).filter(|e| !e.is_empty()) {
+            let Some((path, spec)) = entry.split_once('=') else {
+                return Err(Error::Config {
+                    msg: format!("ROUTE_ROLLOUTS entry must be path=percent: {}", entry),
+                });
+            };
+
+            let path = path.trim();
+            if !path.starts_with('/') {
+                return Err(Error::Config {
+                    msg: format!("ROUTE_ROLLOUTS path must start with /: {}", path),
+                });
+            }
+
+            let mut parts = spec.split(':').map(|p| p.trim());
+            let percent = parts
+                .next()
+                .and_then(|p| p.parse::<u8>().ok())
+                .filter(|p| *p <= 100)
+                .ok_or_else(|| Error::Config {
+                    msg: format!("ROUTE_ROLLOUTS percent must be 0 to 100: {}", entry),
+                })?;
+
+            let mut rollout = RouteRollout {
+                percent,
+                key: RolloutKey::Org,
+                gated: false,
+            };
+
+            for option in parts {
+                match option {
+                    "org" => rollout.key = RolloutKey::Org,
+                    "user" => rollout.key = RolloutKey::User,
+                    "gate" => rollout.gated = true,
+                    other => {
+                        return Err(Error::Config {
+                            msg: format!(
+                                "ROUTE_ROLLOUTS option must be org, user or gate: {}",
+                                other
+                            ),
+                        });
+                    }
+                }
+            }
+
+            rollouts.insert(path.to_string(), rollout);
+        }
+
+        Ok(rollouts)
+    }
+}
+
 #[derive(Deserialize)]
 struct BundleEntry {
     pub file: String,
@@ -44,6 +452,18 @@ struct BundleEntry {
 
 type BundleConfigMap = HashMap<String, BundleEntry>;
 
+/// Third party assets, versioned by their path
+pub const VENDOR_STYLES: &[&str] = &[
+    "/assets/vendors/fontawesome-free/6.6.0/css/all.min.css",
+    "/assets/vendors/bulma/1.0.2/css/bulma.min.css",
+];
+
+pub const VENDOR_SCRIPTS: &[&str] = &[
+    "/assets/vendors/htmx/2.0.2/js/htmx.min.js",
+    "/assets/vendors/alpinejs/3.14.9/alpinejs.min.js",
+];
+
+/// Fingerprinted bundle URLs read from the Vite manifest
 #[derive(Clone, Deserialize)]
 pub struct AssetManifest {
     pub main_css: String,
@@ -57,13 +477,13 @@ impl AssetManifest {
         let config_map = serde_json::from_str::<BundleConfigMap>(contents.as_str())
             .context(ManifestParseSnafu)?;
 
-        let main_css = config_map
-            .get("bundles/main.css")
-            .expect("main.css bundle is required");
+        let main_css = config_map.get("bundles/main.css").ok_or(Error::Config {
+            msg: "main.css bundle is required".to_string(),
+        })?;
 
-        let main_js = config_map
-            .get("bundles/main.js")
-            .expect("main.js bundle is required");
+        let main_js = config_map.get("bundles/main.js").ok_or(Error::Config {
+            msg: "main.js bundle is required".to_string(),
+        })?;
 
         Ok(AssetManifest {
             main_css: format!("/assets/bundles/{}", main_css.file),
@@ -72,6 +492,21 @@ impl AssetManifest {
     }
 }
 
+const DEFAULT_INTEGRITY_SCAN_MINS: u64 = 360;
+const DEFAULT_NOTIFICATION_DIGEST_HOURS: u64 = 24;
+const DEFAULT_APP_SECRET_GRACE_HOURS: u64 = 24;
+const DEFAULT_APPROVAL_WINDOW_MINS: u64 = 60;
+const DEFAULT_MEMORY_SAMPLE_MINS: u64 = 0;
+const DEFAULT_COUNTER_RECONCILE_MINS: u64 = 15;
+const DEFAULT_ORG_ACCESS_RETENTION_DAYS: u64 = 400;
+const DEFAULT_ORG_RATE_LIMIT_PER_MIN: i64 = 600;
+const DEFAULT_ORG_RATE_LIMIT_BURST: i64 = 100;
+const DEFAULT_AUTH_RATE_LIMIT_PER_IP: u64 = 30;
+const DEFAULT_AUTH_RATE_LIMIT_PER_EMAIL: u64 = 10;
+const DEFAULT_AUTH_RATE_LIMIT_WINDOW_SECS: u64 = 15 * 60;
+const DEFAULT_AUTH_CACHE_TTL_SECS: u64 = 10 * 60;
+const DEFAULT_AUTH_CACHE_MAX_CAPACITY: u64 = 10_000;
+
 impl Config {
     pub fn captcha_enabled(&self) -> bool {
         self.captcha_site_key.is_some() && self.captcha_api_key.is_some()
@@ -89,27 +524,267 @@ impl Config {
 
         let db_dir = PathBuf::from(required_env("DATABASE_DIR")?);
 
-        let assets = AssetManifest::build(&frontend_dir).expect("Asset manifest should be valid");
+        let db_read_path = match optional_env("DATABASE_READ_URL") {
+            Some(url) => Some(parse_database_read_url(&url)?),
+            None => None,
+        };
 
-        Ok(Config {
-            server: ServerConfig {
-                address: required_env("SERVER_ADDRESS")?,
-                https: required_env("HTTPS")? == "1",
+        let assets = AssetManifest::build(&frontend_dir)?;
+
+        let token_claims = match optional_env("TOKEN_CLAIMS") {
+            Some(value) => TokenClaimsConfig::parse(&value)?,
+            None => TokenClaimsConfig::default(),
+        };
+
+        let integrity_scan_mins =
+            optional_number_env("INTEGRITY_SCAN_MINS", DEFAULT_INTEGRITY_SCAN_MINS)?;
+
+        let notification_digest_hours = optional_number_env(
+            "NOTIFICATION_DIGEST_HOURS",
+            DEFAULT_NOTIFICATION_DIGEST_HOURS,
+        )?;
+        let app_secret_grace_hours =
+            optional_number_env("APP_SECRET_GRACE_HOURS", DEFAULT_APP_SECRET_GRACE_HOURS)?;
+
+        let memory_sample_mins =
+            optional_number_env("MEMORY_SAMPLE_MINS", DEFAULT_MEMORY_SAMPLE_MINS)?;
+        let counter_reconcile_mins =
+            optional_number_env("COUNTER_RECONCILE_MINS", DEFAULT_COUNTER_RECONCILE_MINS)?;
+
+        let org_access_retention_days = optional_number_env(
+            "ORG_ACCESS_RETENTION_DAYS",
+            DEFAULT_ORG_ACCESS_RETENTION_DAYS,
+        )?;
+
+        let pagination = build_pagination_limits()?;
+
+        let approval_window_mins =
+            optional_number_env("APPROVAL_WINDOW_MINS", DEFAULT_APPROVAL_WINDOW_MINS)?;
+
+        if approval_window_mins == 0 {
+            return Err(Error::Config {
+                msg: "APPROVAL_WINDOW_MINS must be at least 1.".to_string(),
+            });
+        }
+
+        let org_rate_limit = OrgRateLimitConfig {
+            requests_per_min: optional_number_env(
+                "ORG_RATE_LIMIT_PER_MIN",
+                DEFAULT_ORG_RATE_LIMIT_PER_MIN,
+            )?,
+            burst: optional_number_env("ORG_RATE_LIMIT_BURST", DEFAULT_ORG_RATE_LIMIT_BURST)?,
+        };
+
+        if org_rate_limit.requests_per_min < 0
+            || (org_rate_limit.requests_per_min > 0 && org_rate_limit.burst < 1)
+        {
+            return Err(Error::Config {
+                msg: "ORG_RATE_LIMIT_BURST must be at least 1 when ORG_RATE_LIMIT_PER_MIN is set."
+                    .to_string(),
+            });
+        }
+
+        let auth_rate_limit = AuthRateLimitConfig {
+            per_ip: optional_number_env("AUTH_RATE_LIMIT_PER_IP", DEFAULT_AUTH_RATE_LIMIT_PER_IP)?,
+            per_email: optional_number_env(
+                "AUTH_RATE_LIMIT_PER_EMAIL",
+                DEFAULT_AUTH_RATE_LIMIT_PER_EMAIL,
+            )?,
+            window_secs: optional_number_env(
+                "AUTH_RATE_LIMIT_WINDOW_SECS",
+                DEFAULT_AUTH_RATE_LIMIT_WINDOW_SECS,
+            )?,
+        };
+
+        if auth_rate_limit.window_secs == 0 {
+            return Err(Error::Config {
+                msg: "AUTH_RATE_LIMIT_WINDOW_SECS must be at least 1.".to_string(),
+            });
+        }
+
+        let auth_cache = AuthCacheConfig {
+            ttl_secs: optional_number_env("AUTH_CACHE_TTL_SECS", DEFAULT_AUTH_CACHE_TTL_SECS)?,
+            max_capacity: optional_number_env(
+                "AUTH_CACHE_MAX_CAPACITY",
+                DEFAULT_AUTH_CACHE_MAX_CAPACITY,
+            )?,
+        };
+
+        let region_routing = match optional_env("REGION_ROUTING").as_deref() {
+            None | Some("off") => RegionRouting::Off,
+            Some("reject") => RegionRouting::Reject,
+            Some("redirect") => RegionRouting::Redirect,
+            Some(other) => {
+                return Err(Error::Config {
+                    msg: format!("REGION_ROUTING must be off, reject or redirect: {}", other),
+                });
+            }
+        };
+
+        let region = RegionConfig {
+            name: optional_env("REGION"),
+            routing: region_routing,
+            urls: match optional_env("REGION_URLS") {
+                Some(value) => RegionConfig::parse_urls(&value)?,
+                None => HashMap::new(),
             },
-            db: DbConfig { dir: db_dir },
-            superuser: SuperuserConfig {
-                setup_key: env::var("SUPERUSER_SETUP_KEY").ok(),
+        };
+
+        if region.name.is_none() && region.routing != RegionRouting::Off {
+            return Err(Error::Config {
+                msg: "REGION is required when REGION_ROUTING is set.".to_string(),
+            });
+        }
+
+        let token_denylist = match optional_env("TOKEN_DENYLIST").as_deref() {
+            None | Some("database") => TokenDenylistStore::Database,
+            Some("memory") => TokenDenylistStore::Memory,
+            Some("redis") => TokenDenylistStore::Redis,
+            Some(other) => {
+                return Err(Error::Config {
+                    msg: format!(
+                        "TOKEN_DENYLIST must be database, memory or redis: {}",
+                        other
+                    ),
+                });
+            }
+        };
+
+        let redis = optional_env("REDIS_URL").map(|url| RedisConfig { url });
+
+        if token_denylist == TokenDenylistStore::Redis && redis.is_none() {
+            return Err(Error::Config {
+                msg: "REDIS_URL is required when TOKEN_DENYLIST is redis.".to_string(),
+            });
+        }
+
+        let oidc = match (
+            optional_env("OIDC_ISSUER"),
+            optional_env("OIDC_SIGNING_KEY_FILE"),
+        ) {
+            (None, None) => None,
+            (Some(issuer), Some(key_file)) => Some(OidcConfig {
+                issuer: issuer.trim().trim_end_matches('/').to_string(),
+                signing_key_pem: read_key_file(
+                    "OIDC_SIGNING_KEY_FILE",
+                    Path::new(key_file.trim()),
+                )?,
+            }),
+            _ => {
+                return Err(Error::Config {
+                    msg: "OIDC_ISSUER and OIDC_SIGNING_KEY_FILE must be set together.".to_string(),
+                });
+            }
+        };
+
+        let jwt_signing = match (
+            optional_env("JWT_SIGNING_KEY_FILE"),
+            optional_env("JWT_RETIRED_KEYS"),
+        ) {
+            (None, None) => None,
+            (Some(key_file), retired) => {
+                let mut retired_keys = Vec::new();
+                for (path, accepted_until) in
+                    JwtSigningConfig::parse_retired_keys(retired.as_deref().unwrap_or_default())?
+                {
+                    retired_keys.push(RetiredKeyConfig {
+                        key_pem: read_key_file("JWT_RETIRED_KEYS", &path)?,
+                        accepted_until,
+                    });
+                }
+
+                Some(JwtSigningConfig {
+                    signing_key_pem: read_key_file(
+                        "JWT_SIGNING_KEY_FILE",
+                        Path::new(key_file.trim()),
+                    )?,
+                    retired_keys,
+                })
+            }
+            (None, Some(_)) => {
+                return Err(Error::Config {
+                    msg: "JWT_RETIRED_KEYS requires JWT_SIGNING_KEY_FILE.".to_string(),
+                });
+            }
+        };
+
+        let server = ServerConfig {
+            address: required_env("SERVER_ADDRESS")?,
+            https: required_env("HTTPS")? == "1",
+        };
+        let mailer = MailerConfig::from_env(&server.address, server.https)?;
+
+        Ok(Config {
+            server,
+            db: DbConfig {
+                dir: db_dir,
+                read_path: db_read_path,
             },
+            superuser: SuperuserConfig { setup_key: None },
             jwt_secret: required_env("JWT_SECRET")?,
+            jwt_signing,
+            token_denylist,
             frontend_dir,
             captcha_site_key: optional_env("CAPTCHA_SITE_KEY"),
             captcha_api_key: optional_env("CAPTCHA_API_KEY"),
             ga_tag_id: optional_env("GA_TAG_ID"),
+            token_claims,
+            integrity_scan_mins,
+            notification_digest_hours,
+            app_secret_grace_hours,
+            memory_sample_mins,
+            counter_reconcile_mins,
+            org_access_retention_days,
+            pagination,
+            approvals: ApprovalConfig {
+                two_person_rule: optional_env("TWO_PERSON_RULE").as_deref() == Some("1"),
+                window_mins: approval_window_mins,
+                elevation_approval: optional_env("ELEVATION_APPROVAL").as_deref() == Some("1"),
+            },
+            org_rate_limit,
+            auth_rate_limit,
+            auth_cache,
+            redis,
+            region,
+            route_rollouts: match optional_env("ROUTE_ROLLOUTS") {
+                Some(value) => RouteRollout::parse_all(&value)?,
+                None => HashMap::new(),
+            },
+            redirect_uri_probe: optional_env("REDIRECT_URI_PROBE").as_deref() == Some("1"),
+            oidc,
+            mailer,
             assets,
         })
     }
 }
 
+/// Local replica file as a plain path or a `file:` URL, ex: a LiteFS mount
+fn parse_database_read_url(url: &str) -> Result<PathBuf> {
+    let path = url
+        .strip_prefix("file://")
+        .or_else(|| url.strip_prefix("file:"));
+    let path = match path {
+        Some(path) => path,
+        None if !url.contains("://") => url,
+        None => {
+            return Err(Error::Config {
+                msg: format!(
+                    "DATABASE_READ_URL must be a local file path or file: URL: {}",
+                    url
+                ),
+            });
+        }
+    };
+
+    if path.is_empty() {
+        return Err(Error::Config {
+            msg: "DATABASE_READ_URL must not be empty.".to_string(),
+        });
+    }
+
+    Ok(PathBuf::from(path))
+}
+
 fn required_env(name: &str) -> Result<String> {
     match env::var(name) {
         Ok(val) => {
@@ -126,9 +801,146 @@ fn required_env(name: &str) -> Result<String> {
     }
 }
 
+fn read_key_file(name: &str, path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| Error::Config {
+        msg: format!("Unable to read {} {}: {}", name, path.display(), e),
+    })
+}
+
 fn optional_env(name: &str) -> Option<String> {
     match env::var(name) {
         Ok(val) if !val.trim().is_empty() => Some(val),
         _ => None,
     }
 }
+
+fn optional_number_env<T: FromStr>(name: &str, default: T) -> Result<T> {
+    match optional_env(name) {
+        Some(val) => val.trim().parse::<T>().map_err(|_| Error::Config {
+            msg: format!("{} must be a non-negative number.", name),
+        }),
+        None => Ok(default),
+    }
+}
+
+fn build_pagination_limits() -> Result<PaginationLimits> {
+    let defaults = PaginationLimits::default();
+    let limits = PaginationLimits {
+        min_per_page: optional_number_env("PAGINATION_MIN_PER_PAGE", defaults.min_per_page)?,
+        max_per_page: optional_number_env("PAGINATION_MAX_PER_PAGE", defaults.max_per_page)?,
+        max_page: optional_number_env("PAGINATION_MAX_PAGE", defaults.max_page)?,
+    };
+
+    if limits.min_per_page < 1 || limits.max_per_page < limits.min_per_page {
+        return Err(Error::Config {
+            msg: "PAGINATION_MAX_PER_PAGE must be at least PAGINATION_MIN_PER_PAGE, which must be at least 1."
+                .to_string(),
+        });
+    }
+
+    if limits.max_page < 1 {
+        return Err(Error::Config {
+            msg: "PAGINATION_MAX_PAGE must be at least 1.".to_string(),
+        });
+    }
+
+    Ok(limits)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{
+        JwtSigningConfig, RegionConfig, RolloutKey, RouteRollout, TokenClaimsConfig,
+        parse_database_read_url,
+    };
+
+    #[test]
+    fn test_database_read_url_parse() {
+        let expected = PathBuf::from("/litefs/yaas.db");
+        assert_eq!(
+            parse_database_read_url("/litefs/yaas.db").expect("path"),
+            expected
+        );
+        assert_eq!(
+            parse_database_read_url("file:/litefs/yaas.db").expect("file url"),
+            expected
+        );
+        assert_eq!(
+            parse_database_read_url("file:///litefs/yaas.db").expect("file url"),
+            expected
+        );
+        assert!(parse_database_read_url("libsql://replica.example.com").is_err());
+        assert!(parse_database_read_url("file:").is_err());
+    }
+
+    #[test]
+    fn test_token_claims_parse() {
+        let config = TokenClaimsConfig::parse("email, permissions").expect("should parse");
+        assert!(config.email);
+        assert!(config.permissions);
+        assert!(!config.name);
+        assert!(!config.roles);
+    }
+
+    #[test]
+    fn test_token_claims_parse_rejects_unknown() {
+        assert!(TokenClaimsConfig::parse("roles,phone").is_err());
+    }
+
+    #[test]
+    fn test_retired_keys_parse() {
+        let keys = JwtSigningConfig::parse_retired_keys(
+            "/keys/old.pem=2026-11-01, /keys/older.pem=2026-10-20",
+        )
+        .expect("should parse");
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].0.to_str(), Some("/keys/old.pem"));
+        assert_eq!(keys[0].1, 1_793_491_200);
+
+        assert!(JwtSigningConfig::parse_retired_keys("/keys/old.pem").is_err());
+        assert!(JwtSigningConfig::parse_retired_keys("/keys/old.pem=next week").is_err());
+    }
+
+    #[test]
+    fn test_region_urls_parse() {
+        let urls = RegionConfig::parse_urls(
+            "us-east-1=https://us.example.com/, eu-west-1=https://eu.example.com",
+        )
+        .expect("should parse");
+        assert_eq!(urls["us-east-1"], "https://us.example.com");
+        assert_eq!(urls["eu-west-1"], "https://eu.example.com");
+
+        assert!(RegionConfig::parse_urls("us-east-1").is_err());
+        assert!(RegionConfig::parse_urls("us-east-1=not a url").is_err());
+    }
+
+    #[test]
+    fn test_route_rollouts_parse() {
+        let rollouts =
+            RouteRollout::parse_all("/oauth/profile=25, /orgs/{org_id}/members=10:user:gate")
+                .expect("should parse");
+        assert_eq!(
+            rollouts["/oauth/profile"],
+            RouteRollout {
+                percent: 25,
+                key: RolloutKey::Org,
+                gated: false,
+            }
+        );
+        assert_eq!(
+            rollouts["/orgs/{org_id}/members"],
+            RouteRollout {
+                percent: 10,
+                key: RolloutKey::User,
+                gated: true,
+            }
+        );
+
+        assert!(RouteRollout::parse_all("/oauth/profile").is_err());
+        assert!(RouteRollout::parse_all("/oauth/profile=101").is_err());
+        assert!(RouteRollout::parse_all("oauth/profile=10").is_err());
+        assert!(RouteRollout::parse_all("/oauth/profile=10:team").is_err());
+    }
+}
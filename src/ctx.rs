@@ -1,3 +1,5 @@
+use std::future::Future;
+
 use crate::dto::{Actor, ActorDto};
 
 #[derive(Clone)]
@@ -16,4 +18,54 @@ impl Ctx {
         }
         None
     }
+
+    pub fn audit(&self) -> AuditCtx {
+        AuditCtx {
+            actor_id: self.actor().map(|actor| actor.id.clone()),
+        }
+    }
+}
+
+tokio::task_local! {
+    static AUDIT_CTX: AuditCtx;
+}
+
+/// Acting principal passed to every mutating repo call.
+///
+/// Stamped into the `created_by` and `updated_by` columns. The auth
+/// middlewares scope it to the request so services do not have to thread
+/// it through their signatures.
+#[derive(Clone, Debug, Default)]
+pub struct AuditCtx {
+    pub actor_id: Option<String>,
+}
+
+impl AuditCtx {
+    /// Runs the future with this context as the acting principal
+    pub async fn scope<F: Future>(self, f: F) -> F::Output {
+        AUDIT_CTX.scope(self, f).await
+    }
+
+    /// Context of the current request, anonymous outside of one
+    pub fn current() -> Self {
+        AUDIT_CTX.try_with(|ctx| ctx.clone()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AuditCtx;
+
+    #[tokio::test]
+    async fn audit_ctx_is_scoped_to_the_future() {
+        assert!(AuditCtx::current().actor_id.is_none());
+
+        let ctx = AuditCtx {
+            actor_id: Some("usr_actor".to_string()),
+        };
+        let inside = ctx.scope(async { AuditCtx::current().actor_id }).await;
+
+        assert_eq!(inside.as_deref(), Some("usr_actor"));
+        assert!(AuditCtx::current().actor_id.is_none());
+    }
 }
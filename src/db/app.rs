@@ -1,16 +1,31 @@
 use snafu::ResultExt;
+use tracing::instrument;
 use turso::{Connection, Row};
 
 use crate::Result;
+use crate::ctx::AuditCtx;
+use crate::db::read_pool::ReadPool;
+use crate::db::soft_delete::{DeletedScope, SoftDelete};
 use crate::db::turso_decode::{
-    FromTursoRow, collect_count, collect_row, collect_rows, row_integer, row_text,
+    FromTursoRow, collect_row, collect_rows, opt_row_integer, opt_row_text, row_integer, row_text,
 };
-use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::db::versioned::{NEXT_VERSION_SQL, Versioned};
+use crate::dto::{APP_SCOPES, APP_SORT, Paginated, PaginationLimits, PaginationParams};
 use crate::dto::{AppDto, ListAppsParamsDto, NewAppDto, UpdateAppDto};
-use crate::dto::{Paginated, PaginationParams};
-use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu, JsonParseSnafu, JsonSerializeSnafu};
 use crate::utils::{IdPrefix, generate_id};
 
+/// Apps without declared scopes may request every app scope
+fn default_app_scopes() -> Vec<String> {
+    APP_SCOPES.iter().map(|s| s.to_string()).collect()
+}
+
+/// Stored space separated like the scope strings of codes and grants
+fn stored_scopes(scopes: &[String]) -> String {
+    scopes.join(" ")
+}
+
 pub struct App {
     pub id: String,
     pub name: String,
@@ -20,6 +35,10 @@ pub struct App {
     pub created_at: i64,
     pub updated_at: i64,
     pub deleted_at: Option<i64>,
+    pub created_by: Option<String>,
+    pub updated_by: Option<String>,
+    pub client_secret_hash: Option<String>,
+    pub scopes: Vec<String>,
 }
 
 impl From<App> for AppDto {
@@ -29,44 +48,95 @@ impl From<App> for AppDto {
             name: app.name,
             client_id: app.client_id,
             client_secret: app.client_secret,
+            redirect_uris: vec![app.redirect_uri.clone()],
             redirect_uri: app.redirect_uri,
+            scopes: app.scopes,
             created_at: app.created_at,
             updated_at: app.updated_at,
+            deleted_at: app.deleted_at,
+            created_by: app.created_by,
+            updated_by: app.updated_by,
+            client_secret_hash: app.client_secret_hash,
+            previous_secret_hash: None,
+            previous_secret_expires_at: None,
         }
     }
 }
 
 impl FromTursoRow for AppDto {
     fn from_row(row: &Row) -> Result<Self> {
+        let redirect_uri = row_text(row, 4)?;
+        let extra_redirect_uris: Vec<String> =
+            serde_json::from_str(&row_text(row, 13)?).context(JsonParseSnafu)?;
+
+        let mut redirect_uris = vec![redirect_uri.clone()];
+        redirect_uris.extend(extra_redirect_uris);
+
+        let scopes = match opt_row_text(row, 14)? {
+            Some(scopes) => scopes.split(' ').map(|s| s.to_string()).collect(),
+            None => default_app_scopes(),
+        };
+
         Ok(Self {
             id: row_text(row, 0)?,
             name: row_text(row, 1)?,
             client_id: row_text(row, 2)?,
             client_secret: row_text(row, 3)?,
-            redirect_uri: row_text(row, 4)?,
+            redirect_uri,
+            redirect_uris,
+            scopes,
             created_at: row_integer(row, 5)?,
             updated_at: row_integer(row, 6)?,
+            deleted_at: opt_row_integer(row, 7)?,
+            created_by: opt_row_text(row, 8)?,
+            updated_by: opt_row_text(row, 9)?,
+            client_secret_hash: opt_row_text(row, 10)?,
+            previous_secret_hash: opt_row_text(row, 11)?,
+            previous_secret_expires_at: opt_row_integer(row, 12)?,
+        })
+    }
+}
+
+/// An app still holding its client secret in plain text
+pub struct PlainAppSecret {
+    pub id: String,
+    pub client_secret: String,
+}
+
+impl FromTursoRow for PlainAppSecret {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            client_secret: row_text(row, 1)?,
         })
     }
 }
 
 pub struct AppRepo {
     db_pool: Connection,
+    read_pool: ReadPool,
+    pagination: PaginationLimits,
 }
 
 impl AppRepo {
-    pub fn new(db_pool: Connection) -> Self {
-        Self { db_pool }
+    pub fn new(db_pool: Connection, read_pool: ReadPool, pagination: PaginationLimits) -> Self {
+        Self {
+            db_pool,
+            read_pool,
+            pagination,
+        }
     }
 
-    async fn listing_count(&self, params: ListAppsParamsDto) -> Result<i64> {
-        let mut query = r#"
+    async fn listing_count(&self, params: ListAppsParamsDto, scope: DeletedScope) -> Result<i64> {
+        let mut query = format!(
+            r#"
             SELECT COUNT(*) AS total_count
             FROM apps
             WHERE
-                deleted_at IS NULL
-        "#
-        .to_string();
+                {}
+            "#,
+            scope.condition("deleted_at")
+        );
 
         let mut q_params = new_query_params();
 
@@ -78,13 +148,22 @@ impl AppRepo {
             q_params.push(text_param(":keyword", pattern));
         }
 
-        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
-        let row_result = stmt.query_row(q_params).await;
-        collect_count(row_result)
+        self.read_pool.count(&query, q_params).await
     }
 
+    #[instrument(level = "debug", name = "db.app.list", skip_all)]
     pub async fn list(&self, params: ListAppsParamsDto) -> Result<Paginated<AppDto>> {
-        let mut query = r#"
+        self.list_scoped(params, DeletedScope::Active).await
+    }
+
+    #[instrument(level = "debug", name = "db.app.list_scoped", skip_all)]
+    pub async fn list_scoped(
+        &self,
+        params: ListAppsParamsDto,
+        scope: DeletedScope,
+    ) -> Result<Paginated<AppDto>> {
+        let mut query = format!(
+            r#"
             SELECT
                 id,
                 name,
@@ -92,12 +171,21 @@ impl AppRepo {
                 client_secret,
                 redirect_uri,
                 created_at,
-                updated_at
+                updated_at,
+                deleted_at,
+                created_by,
+                updated_by,
+                client_secret_hash,
+                previous_secret_hash,
+                previous_secret_expires_at,
+                extra_redirect_uris,
+                scopes
             FROM apps
             WHERE
-                deleted_at IS NULL
-        "#
-        .to_string();
+                {}
+            "#,
+            scope.condition("deleted_at")
+        );
 
         let mut q_params = new_query_params();
         let count_params = params.clone();
@@ -110,9 +198,14 @@ impl AppRepo {
             q_params.push(text_param(":keyword", pattern));
         }
 
-        let total_records = self.listing_count(count_params).await?;
+        let total_records = self.listing_count(count_params, scope).await?;
 
-        let pagination = PaginationParams::new(total_records, params.page, params.per_page, None);
+        let pagination = PaginationParams::new(
+            total_records,
+            params.page,
+            params.per_page,
+            &self.pagination,
+        );
 
         // Do not query if we already know there are no records
         if pagination.total_pages == 0 {
@@ -124,14 +217,13 @@ impl AppRepo {
             ));
         }
 
-        query.push_str(" ORDER BY name ASC LIMIT :limit OFFSET :offset");
+        query.push_str(&APP_SORT.order_by(params.sort_by.as_deref(), params.sort_dir.as_deref()));
+        query.push_str(" LIMIT :limit OFFSET :offset");
 
         q_params.push(integer_param(":limit", pagination.per_page as i64));
         q_params.push(integer_param(":offset", pagination.offset));
 
-        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
-        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
-        let items: Vec<AppDto> = collect_rows(&mut rows).await?;
+        let items: Vec<AppDto> = self.read_pool.rows(&query, q_params).await?;
 
         Ok(Paginated::new(
             items,
@@ -141,7 +233,14 @@ impl AppRepo {
         ))
     }
 
-    pub async fn create(&self, data: NewAppDto) -> Result<AppDto> {
+    /// Only the hash of the client secret is stored, the caller hands out the plain secret
+    #[instrument(level = "debug", name = "db.app.create", skip_all)]
+    pub async fn create(
+        &self,
+        audit: &AuditCtx,
+        data: NewAppDto,
+        client_secret_hash: String,
+    ) -> Result<AppDto> {
         let query = r#"
             INSERT INTO apps
             (
@@ -149,38 +248,53 @@ impl AppRepo {
                 name,
                 client_id,
                 client_secret,
+                client_secret_hash,
                 redirect_uri,
+                scopes,
                 created_at,
                 updated_at,
-                deleted_at
+                deleted_at,
+                created_by,
+                updated_by
             )
             VALUES
             (
                 :id,
                 :name,
                 :client_id,
-                :client_secret,
+                '',
+                :client_secret_hash,
                 :redirect_uri,
+                :scopes,
                 :created_at,
                 :updated_at,
-                NULL
+                NULL,
+                :created_by,
+                :created_by
             )
         "#;
 
         let id = generate_id(IdPrefix::App);
         let today = chrono::Utc::now().timestamp_millis();
         let client_id = generate_id(IdPrefix::ClientId);
-        let client_secret = generate_id(IdPrefix::ClientSecret);
 
         let mut q_params = new_query_params();
 
         q_params.push(text_param(":name", data.name.clone()));
         q_params.push(text_param(":id", id.clone()));
         q_params.push(text_param(":client_id", client_id.clone()));
-        q_params.push(text_param(":client_secret", client_secret.clone()));
+        q_params.push(text_param(
+            ":client_secret_hash",
+            client_secret_hash.clone(),
+        ));
         q_params.push(text_param(":redirect_uri", data.redirect_uri.clone()));
+        q_params.push(opt_text_param(
+            ":scopes",
+            data.scopes.as_deref().map(stored_scopes),
+        ));
         q_params.push(integer_param(":created_at", today));
         q_params.push(integer_param(":updated_at", today));
+        q_params.push(opt_text_param(":created_by", audit.actor_id.clone()));
 
         let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
         let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
@@ -190,18 +304,29 @@ impl AppRepo {
             id,
             name: data.name,
             client_id,
-            client_secret,
+            client_secret: String::new(),
             redirect_uri: data.redirect_uri,
             created_at: today,
             updated_at: today,
             deleted_at: None,
+            created_by: audit.actor_id.clone(),
+            updated_by: audit.actor_id.clone(),
+            client_secret_hash: Some(client_secret_hash),
+            scopes: data.scopes.unwrap_or_else(default_app_scopes),
         };
 
         Ok(app.into())
     }
 
+    #[instrument(level = "debug", name = "db.app.get", skip_all)]
     pub async fn get(&self, id: String) -> Result<Option<AppDto>> {
-        let query = r#"
+        self.get_scoped(id, DeletedScope::Active).await
+    }
+
+    #[instrument(level = "debug", name = "db.app.get_scoped", skip_all)]
+    pub async fn get_scoped(&self, id: String, scope: DeletedScope) -> Result<Option<AppDto>> {
+        let query = format!(
+            r#"
             SELECT
                 id,
                 name,
@@ -209,13 +334,23 @@ impl AppRepo {
                 client_secret,
                 redirect_uri,
                 created_at,
-                updated_at
+                updated_at,
+                deleted_at,
+                created_by,
+                updated_by,
+                client_secret_hash,
+                previous_secret_hash,
+                previous_secret_expires_at,
+                extra_redirect_uris,
+                scopes
             FROM apps
             WHERE
-                deleted_at IS NULL
+                {}
                 AND id = :id
             LIMIT 1
-        "#;
+            "#,
+            scope.condition("deleted_at")
+        );
 
         let mut q_params = new_query_params();
         q_params.push(text_param(":id", id));
@@ -226,6 +361,7 @@ impl AppRepo {
         Ok(dto)
     }
 
+    #[instrument(level = "debug", name = "db.app.find_by_client_id", skip_all)]
     pub async fn find_by_client_id(&self, client_id: String) -> Result<Option<AppDto>> {
         let query = r#"
             SELECT
@@ -235,7 +371,15 @@ impl AppRepo {
                 client_secret,
                 redirect_uri,
                 created_at,
-                updated_at
+                updated_at,
+                deleted_at,
+                created_by,
+                updated_by,
+                client_secret_hash,
+                previous_secret_hash,
+                previous_secret_expires_at,
+                extra_redirect_uris,
+                scopes
             FROM apps
             WHERE
                 deleted_at IS NULL
@@ -252,9 +396,10 @@ impl AppRepo {
         Ok(dto)
     }
 
-    pub async fn update(&self, id: String, data: UpdateAppDto) -> Result<bool> {
+    #[instrument(level = "debug", name = "db.app.update", skip_all)]
+    pub async fn update(&self, audit: &AuditCtx, id: String, data: UpdateAppDto) -> Result<bool> {
         // Do not allow empty update
-        if data.name.is_none() && data.redirect_uri.is_none() {
+        if data.name.is_none() && data.redirect_uri.is_none() && data.scopes.is_none() {
             return Ok(false);
         }
 
@@ -272,9 +417,16 @@ impl AppRepo {
             q_params.push(text_param(":redirect_uri", redirect_uri));
         }
 
+        if let Some(scopes) = data.scopes {
+            set_parts.push("scopes = :scopes");
+            q_params.push(text_param(":scopes", stored_scopes(&scopes)));
+        }
+
         let updated_at = chrono::Utc::now().timestamp_millis();
-        set_parts.push("updated_at = :updated_at");
+        set_parts.push(NEXT_VERSION_SQL);
         q_params.push(integer_param(":updated_at", updated_at));
+        set_parts.push("updated_by = :updated_by");
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
 
         query.push_str(&set_parts.join(", "));
         query.push_str(" WHERE id = :id AND deleted_at IS NULL");
@@ -285,51 +437,173 @@ impl AppRepo {
         Ok(affected > 0)
     }
 
-    pub async fn regenerate_secret(&self, id: String) -> Result<bool> {
+    /// Stores the redirect URIs registered on top of the main one
+    #[instrument(level = "debug", name = "db.app.set_extra_redirect_uris", skip_all)]
+    pub async fn set_extra_redirect_uris(
+        &self,
+        audit: &AuditCtx,
+        id: String,
+        redirect_uris: &[String],
+    ) -> Result<bool> {
+        let query = format!(
+            r#"
+            UPDATE apps
+            SET
+                extra_redirect_uris = :extra_redirect_uris,
+                {},
+                updated_by = :updated_by
+            WHERE
+                id = :id
+                AND deleted_at IS NULL
+            "#,
+            NEXT_VERSION_SQL
+        );
+
+        let encoded = serde_json::to_string(redirect_uris).context(JsonSerializeSnafu)?;
+        let updated_at = chrono::Utc::now().timestamp_millis();
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":extra_redirect_uris", encoded));
+        q_params.push(integer_param(":updated_at", updated_at));
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(affected > 0)
+    }
+
+    /// Replaces both the client ID and the secret, the old secret stops working right away.
+    ///
+    /// Returns the new client ID when the app exists.
+    #[instrument(level = "debug", name = "db.app.regenerate_secret", skip_all)]
+    pub async fn regenerate_secret(
+        &self,
+        audit: &AuditCtx,
+        id: String,
+        client_secret_hash: String,
+    ) -> Result<Option<String>> {
         let query = r#"
             UPDATE apps
             SET
                 client_id = :client_id,
-                client_secret = :client_secret,
-                updated_at = :updated_at
+                client_secret = '',
+                client_secret_hash = :client_secret_hash,
+                previous_secret_hash = NULL,
+                previous_secret_expires_at = NULL,
+                updated_at = :updated_at,
+                updated_by = :updated_by
             WHERE
                 id = :id
                 AND deleted_at IS NULL
         "#;
 
         let client_id = generate_id(IdPrefix::ClientId);
-        let client_secret = generate_id(IdPrefix::ClientSecret);
         let updated_at = chrono::Utc::now().timestamp_millis();
 
         let mut q_params = new_query_params();
-        q_params.push(text_param(":client_id", client_id));
-        q_params.push(text_param(":client_secret", client_secret));
+        q_params.push(text_param(":client_id", client_id.clone()));
+        q_params.push(text_param(":client_secret_hash", client_secret_hash));
         q_params.push(integer_param(":updated_at", updated_at));
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
         q_params.push(text_param(":id", id));
 
         let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
         let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
-        Ok(affected > 0)
+        Ok((affected > 0).then_some(client_id))
     }
 
-    pub async fn delete(&self, id: String) -> Result<bool> {
+    /// Swaps in a new secret while the current one stays valid until `previous_expires_at`.
+    #[instrument(level = "debug", name = "db.app.rotate_secret", skip_all)]
+    pub async fn rotate_secret(
+        &self,
+        audit: &AuditCtx,
+        id: String,
+        client_secret_hash: String,
+        previous_expires_at: i64,
+    ) -> Result<bool> {
         let query = r#"
             UPDATE apps
             SET
-                deleted_at = :deleted_at
+                previous_secret_hash = client_secret_hash,
+                previous_secret_expires_at = :previous_expires_at,
+                client_secret = '',
+                client_secret_hash = :client_secret_hash,
+                updated_at = :updated_at,
+                updated_by = :updated_by
             WHERE
                 id = :id
                 AND deleted_at IS NULL
         "#;
 
-        let deleted_at = chrono::Utc::now().timestamp_millis();
+        let updated_at = chrono::Utc::now().timestamp_millis();
 
         let mut q_params = new_query_params();
-        q_params.push(integer_param(":deleted_at", deleted_at));
+        q_params.push(integer_param(":previous_expires_at", previous_expires_at));
+        q_params.push(text_param(":client_secret_hash", client_secret_hash));
+        q_params.push(integer_param(":updated_at", updated_at));
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
         q_params.push(text_param(":id", id));
 
         let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
         let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
         Ok(affected > 0)
     }
+
+    /// Apps created before secrets were hashed
+    #[instrument(level = "debug", name = "db.app.list_plain_secrets", skip_all)]
+    pub async fn list_plain_secrets(&self) -> Result<Vec<PlainAppSecret>> {
+        let query = r#"
+            SELECT id, client_secret
+            FROM apps
+            WHERE
+                client_secret != ''
+                AND client_secret_hash IS NULL
+        "#;
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt
+            .query(new_query_params())
+            .await
+            .context(DbStatementSnafu)?;
+
+        collect_rows(&mut rows).await
+    }
+
+    /// Stores the hash of a legacy plain secret and clears the plain value
+    #[instrument(level = "debug", name = "db.app.set_secret_hash", skip_all)]
+    pub async fn set_secret_hash(&self, id: String, client_secret_hash: String) -> Result<bool> {
+        let query = r#"
+            UPDATE apps
+            SET
+                client_secret = '',
+                client_secret_hash = :client_secret_hash
+            WHERE
+                id = :id
+                AND client_secret_hash IS NULL
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":client_secret_hash", client_secret_hash));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(affected > 0)
+    }
+
+    #[instrument(level = "debug", name = "db.app.delete", skip_all)]
+    pub async fn delete(&self, audit: &AuditCtx, id: String) -> Result<bool> {
+        self.soft_delete(audit, id).await
+    }
 }
+
+impl SoftDelete for AppRepo {
+    const TABLE: &'static str = "apps";
+
+    fn connection(&self) -> &Connection {
+        &self.db_pool
+    }
+}
+
+impl Versioned for AppRepo {}
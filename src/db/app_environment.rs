@@ -0,0 +1,305 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::app::PlainAppSecret;
+use crate::db::turso_decode::{
+    FromTursoRow, collect_row, collect_rows, opt_row_integer, opt_row_text, row_integer, row_text,
+};
+use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::dto::AppEnvironmentDto;
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+
+impl FromTursoRow for AppEnvironmentDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            app_id: row_text(row, 1)?,
+            label: row_text(row, 2)?,
+            client_id: row_text(row, 3)?,
+            client_secret: "".to_string(),
+            redirect_uri: row_text(row, 4)?,
+            created_at: row_integer(row, 5)?,
+            client_secret_hash: opt_row_text(row, 6)?,
+            previous_secret_hash: opt_row_text(row, 7)?,
+            previous_secret_expires_at: opt_row_integer(row, 8)?,
+        })
+    }
+}
+
+const APP_ENVIRONMENT_COLUMNS: &str = r#"
+    app_environments.id,
+    app_environments.app_id,
+    app_environments.label,
+    app_environments.client_id,
+    app_environments.redirect_uri,
+    app_environments.created_at,
+    app_environments.client_secret_hash,
+    app_environments.previous_secret_hash,
+    app_environments.previous_secret_expires_at
+"#;
+
+pub struct AppEnvironmentRepo {
+    db_pool: Connection,
+}
+
+impl AppEnvironmentRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.app_environment.list", skip_all)]
+    pub async fn list(&self, app_id: String) -> Result<Vec<AppEnvironmentDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM app_environments
+            WHERE
+                app_id = :app_id
+            ORDER BY label ASC
+        "#,
+            APP_ENVIRONMENT_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":app_id", app_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<AppEnvironmentDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.app_environment.get", skip_all)]
+    pub async fn get(&self, app_id: String, id: String) -> Result<Option<AppEnvironmentDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM app_environments
+            WHERE
+                app_id = :app_id
+                AND id = :id
+            LIMIT 1
+        "#,
+            APP_ENVIRONMENT_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":app_id", app_id));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<AppEnvironmentDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.app_environment.find_by_label", skip_all)]
+    pub async fn find_by_label(
+        &self,
+        app_id: String,
+        label: String,
+    ) -> Result<Option<AppEnvironmentDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM app_environments
+            WHERE
+                app_id = :app_id
+                AND label = :label
+            LIMIT 1
+        "#,
+            APP_ENVIRONMENT_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":app_id", app_id));
+        q_params.push(text_param(":label", label));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<AppEnvironmentDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Environments of deleted apps are ignored
+    #[instrument(
+        level = "debug",
+        name = "db.app_environment.find_by_client_id",
+        skip_all
+    )]
+    pub async fn find_by_client_id(&self, client_id: String) -> Result<Option<AppEnvironmentDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM app_environments
+            INNER JOIN apps ON apps.id = app_environments.app_id
+            WHERE
+                apps.deleted_at IS NULL
+                AND app_environments.client_id = :client_id
+            LIMIT 1
+        "#,
+            APP_ENVIRONMENT_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":client_id", client_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<AppEnvironmentDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Only the hash of the environment's secret is stored
+    #[instrument(level = "debug", name = "db.app_environment.create", skip_all)]
+    pub async fn create(
+        &self,
+        data: AppEnvironmentDto,
+        client_secret_hash: String,
+    ) -> Result<AppEnvironmentDto> {
+        let query = r#"
+            INSERT INTO app_environments
+            (
+                id,
+                app_id,
+                label,
+                client_id,
+                client_secret,
+                client_secret_hash,
+                redirect_uri,
+                created_at
+            )
+            VALUES
+            (
+                :id,
+                :app_id,
+                :label,
+                :client_id,
+                '',
+                :client_secret_hash,
+                :redirect_uri,
+                :created_at
+            )
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", data.id.clone()));
+        q_params.push(text_param(":app_id", data.app_id.clone()));
+        q_params.push(text_param(":label", data.label.clone()));
+        q_params.push(text_param(":client_id", data.client_id.clone()));
+        q_params.push(text_param(
+            ":client_secret_hash",
+            client_secret_hash.clone(),
+        ));
+        q_params.push(text_param(":redirect_uri", data.redirect_uri.clone()));
+        q_params.push(integer_param(":created_at", data.created_at));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(AppEnvironmentDto {
+            client_secret_hash: Some(client_secret_hash),
+            ..data
+        })
+    }
+
+    /// Swaps in a new secret hash, the current one keeps working until `previous_expires_at`
+    #[instrument(level = "debug", name = "db.app_environment.rotate_secret", skip_all)]
+    pub async fn rotate_secret(
+        &self,
+        app_id: String,
+        id: String,
+        client_secret_hash: String,
+        previous_expires_at: i64,
+    ) -> Result<bool> {
+        let query = r#"
+            UPDATE app_environments
+            SET
+                previous_secret_hash = client_secret_hash,
+                previous_secret_expires_at = :previous_expires_at,
+                client_secret = '',
+                client_secret_hash = :client_secret_hash
+            WHERE
+                app_id = :app_id
+                AND id = :id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":previous_expires_at", previous_expires_at));
+        q_params.push(text_param(":client_secret_hash", client_secret_hash));
+        q_params.push(text_param(":app_id", app_id));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(affected > 0)
+    }
+
+    /// Environments created before secrets were hashed
+    #[instrument(
+        level = "debug",
+        name = "db.app_environment.list_plain_secrets",
+        skip_all
+    )]
+    pub async fn list_plain_secrets(&self) -> Result<Vec<PlainAppSecret>> {
+        let query = r#"
+            SELECT id, client_secret
+            FROM app_environments
+            WHERE
+                client_secret != ''
+                AND client_secret_hash IS NULL
+        "#;
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt
+            .query(new_query_params())
+            .await
+            .context(DbStatementSnafu)?;
+
+        collect_rows(&mut rows).await
+    }
+
+    /// Stores the hash of a legacy plain secret and clears the plain value
+    #[instrument(level = "debug", name = "db.app_environment.set_secret_hash", skip_all)]
+    pub async fn set_secret_hash(&self, id: String, client_secret_hash: String) -> Result<bool> {
+        let query = r#"
+            UPDATE app_environments
+            SET
+                client_secret = '',
+                client_secret_hash = :client_secret_hash
+            WHERE
+                id = :id
+                AND client_secret_hash IS NULL
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":client_secret_hash", client_secret_hash));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(affected > 0)
+    }
+
+    /// Returns false when the environment does not belong to the app
+    #[instrument(level = "debug", name = "db.app_environment.delete", skip_all)]
+    pub async fn delete(&self, app_id: String, id: String) -> Result<bool> {
+        let query = r#"
+            DELETE FROM app_environments
+            WHERE
+                app_id = :app_id
+                AND id = :id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":app_id", app_id));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+}
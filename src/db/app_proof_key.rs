@@ -0,0 +1,129 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{FromTursoRow, collect_row, row_integer, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::dto::AppProofKeyDto;
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+
+impl FromTursoRow for AppProofKeyDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            app_id: row_text(row, 0)?,
+            key_id: row_text(row, 1)?,
+            public_key: row_text(row, 2)?,
+            created_at: row_integer(row, 3)?,
+        })
+    }
+}
+
+pub struct AppProofKeyRepo {
+    db_pool: Connection,
+}
+
+impl AppProofKeyRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.app_proof_key.find", skip_all)]
+    pub async fn find(&self, app_id: String) -> Result<Option<AppProofKeyDto>> {
+        let query = r#"
+            SELECT
+                app_id,
+                key_id,
+                public_key,
+                created_at
+            FROM app_proof_keys
+            WHERE
+                app_id = :app_id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":app_id", app_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<AppProofKeyDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.app_proof_key.find_by_key_id", skip_all)]
+    pub async fn find_by_key_id(&self, key_id: String) -> Result<Option<AppProofKeyDto>> {
+        let query = r#"
+            SELECT
+                app_id,
+                key_id,
+                public_key,
+                created_at
+            FROM app_proof_keys
+            WHERE
+                key_id = :key_id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":key_id", key_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<AppProofKeyDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Registers the app's key, replacing the previous one
+    #[instrument(level = "debug", name = "db.app_proof_key.upsert", skip_all)]
+    pub async fn upsert(&self, data: AppProofKeyDto) -> Result<AppProofKeyDto> {
+        let query = r#"
+            INSERT INTO app_proof_keys
+            (
+                app_id,
+                key_id,
+                public_key,
+                created_at
+            )
+            VALUES
+            (
+                :app_id,
+                :key_id,
+                :public_key,
+                :created_at
+            )
+            ON CONFLICT (app_id) DO UPDATE SET
+                key_id = excluded.key_id,
+                public_key = excluded.public_key,
+                created_at = excluded.created_at
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":app_id", data.app_id.clone()));
+        q_params.push(text_param(":key_id", data.key_id.clone()));
+        q_params.push(text_param(":public_key", data.public_key.clone()));
+        q_params.push(integer_param(":created_at", data.created_at));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(data)
+    }
+
+    #[instrument(level = "debug", name = "db.app_proof_key.delete", skip_all)]
+    pub async fn delete(&self, app_id: String) -> Result<()> {
+        let query = r#"
+            DELETE FROM app_proof_keys
+            WHERE
+                app_id = :app_id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":app_id", app_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{FromTursoRow, collect_row, opt_row_text, row_integer, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::dto::{AppUriCheckDto, AppUriCheckStatus};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+
+impl FromTursoRow for AppUriCheckDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        let status = row_text(row, 2)?;
+
+        Ok(Self {
+            app_id: row_text(row, 0)?,
+            redirect_uri: row_text(row, 1)?,
+            status: AppUriCheckStatus::try_from(status.as_str())?,
+            message: opt_row_text(row, 3)?,
+            checked_at: row_integer(row, 4)?,
+        })
+    }
+}
+
+pub struct AppUriCheckRepo {
+    db_pool: Connection,
+}
+
+impl AppUriCheckRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.app_uri_check.find", skip_all)]
+    pub async fn find(&self, app_id: String) -> Result<Option<AppUriCheckDto>> {
+        let query = r#"
+            SELECT
+                app_id,
+                redirect_uri,
+                status,
+                message,
+                checked_at
+            FROM app_uri_checks
+            WHERE
+                app_id = :app_id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":app_id", app_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<AppUriCheckDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Records the latest check, replacing the previous one
+    #[instrument(level = "debug", name = "db.app_uri_check.upsert", skip_all)]
+    pub async fn upsert(&self, data: AppUriCheckDto) -> Result<AppUriCheckDto> {
+        let query = r#"
+            INSERT INTO app_uri_checks
+            (
+                app_id,
+                redirect_uri,
+                status,
+                message,
+                checked_at
+            )
+            VALUES
+            (
+                :app_id,
+                :redirect_uri,
+                :status,
+                :message,
+                :checked_at
+            )
+            ON CONFLICT (app_id) DO UPDATE SET
+                redirect_uri = excluded.redirect_uri,
+                status = excluded.status,
+                message = excluded.message,
+                checked_at = excluded.checked_at
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":app_id", data.app_id.clone()));
+        q_params.push(text_param(":redirect_uri", data.redirect_uri.clone()));
+        q_params.push(text_param(":status", data.status.to_string()));
+        q_params.push(opt_text_param(":message", data.message.clone()));
+        q_params.push(integer_param(":checked_at", data.checked_at));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(data)
+    }
+
+    /// Stores a probe result.
+    ///
+    /// Ignored when the app was re-verified with another URI in the meantime.
+    #[instrument(level = "debug", name = "db.app_uri_check.record_probe", skip_all)]
+    pub async fn record_probe(&self, data: AppUriCheckDto) -> Result<bool> {
+        let query = r#"
+            UPDATE app_uri_checks
+            SET
+                status = :status,
+                message = :message,
+                checked_at = :checked_at
+            WHERE
+                app_id = :app_id
+                AND redirect_uri = :redirect_uri
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":status", data.status.to_string()));
+        q_params.push(opt_text_param(":message", data.message));
+        q_params.push(integer_param(":checked_at", data.checked_at));
+        q_params.push(text_param(":app_id", data.app_id));
+        q_params.push(text_param(":redirect_uri", data.redirect_uri));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+}
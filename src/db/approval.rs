@@ -0,0 +1,227 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{
+    FromTursoRow, collect_row, collect_rows, opt_row_integer, opt_row_text, row_integer, row_text,
+};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::dto::{ApprovalAction, ApprovalDto, ApprovalStatus, NewApprovalDto};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for ApprovalDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            action: ApprovalAction::try_from(row_text(row, 1)?.as_str())?,
+            target_id: row_text(row, 2)?,
+            requested_by: row_text(row, 3)?,
+            status: ApprovalStatus::try_from(row_text(row, 4)?.as_str())?,
+            decided_by: opt_row_text(row, 5)?,
+            created_at: row_integer(row, 6)?,
+            expires_at: row_integer(row, 7)?,
+            decided_at: opt_row_integer(row, 8)?,
+        })
+    }
+}
+
+pub struct ApprovalRepo {
+    db_pool: Connection,
+}
+
+impl ApprovalRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    /// Most recent approvals first, including decided ones for the audit trail
+    #[instrument(level = "debug", name = "db.approval.list", skip_all)]
+    pub async fn list(&self, limit: i64) -> Result<Vec<ApprovalDto>> {
+        let query = r#"
+            SELECT
+                id,
+                action,
+                target_id,
+                requested_by,
+                status,
+                decided_by,
+                created_at,
+                expires_at,
+                decided_at
+            FROM approvals
+            ORDER BY created_at DESC
+            LIMIT :limit
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":limit", limit));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<ApprovalDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.approval.get", skip_all)]
+    pub async fn get(&self, id: String) -> Result<Option<ApprovalDto>> {
+        let query = r#"
+            SELECT
+                id,
+                action,
+                target_id,
+                requested_by,
+                status,
+                decided_by,
+                created_at,
+                expires_at,
+                decided_at
+            FROM approvals
+            WHERE
+                id = :id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<ApprovalDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Pending and unexpired request for the same action and target
+    #[instrument(level = "debug", name = "db.approval.find_pending", skip_all)]
+    pub async fn find_pending(
+        &self,
+        action: ApprovalAction,
+        target_id: String,
+        now: i64,
+    ) -> Result<Option<ApprovalDto>> {
+        let query = r#"
+            SELECT
+                id,
+                action,
+                target_id,
+                requested_by,
+                status,
+                decided_by,
+                created_at,
+                expires_at,
+                decided_at
+            FROM approvals
+            WHERE
+                action = :action
+                AND target_id = :target_id
+                AND status = 'pending'
+                AND expires_at > :now
+            ORDER BY created_at DESC
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":action", action.to_string()));
+        q_params.push(text_param(":target_id", target_id));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<ApprovalDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.approval.create", skip_all)]
+    pub async fn create(&self, data: NewApprovalDto) -> Result<ApprovalDto> {
+        let query = r#"
+            INSERT INTO approvals
+            (
+                id,
+                action,
+                target_id,
+                requested_by,
+                status,
+                decided_by,
+                created_at,
+                expires_at,
+                decided_at
+            )
+            VALUES
+            (
+                :id,
+                :action,
+                :target_id,
+                :requested_by,
+                :status,
+                NULL,
+                :created_at,
+                :expires_at,
+                NULL
+            )
+        "#;
+
+        let id = generate_id(IdPrefix::Approval);
+        let created_at = chrono::Utc::now().timestamp_millis();
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id.clone()));
+        q_params.push(text_param(":action", data.action.to_string()));
+        q_params.push(text_param(":target_id", data.target_id.clone()));
+        q_params.push(text_param(":requested_by", data.requested_by.clone()));
+        q_params.push(text_param(":status", ApprovalStatus::Pending.to_string()));
+        q_params.push(integer_param(":created_at", created_at));
+        q_params.push(integer_param(":expires_at", data.expires_at));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        assert!(affected > 0, "Must insert a new approval row");
+
+        Ok(ApprovalDto {
+            id,
+            action: data.action,
+            target_id: data.target_id,
+            requested_by: data.requested_by,
+            status: ApprovalStatus::Pending,
+            decided_by: None,
+            created_at,
+            expires_at: data.expires_at,
+            decided_at: None,
+        })
+    }
+
+    /// Moves a pending approval to its final status.
+    ///
+    /// Returns false when someone else already decided it.
+    #[instrument(level = "debug", name = "db.approval.decide", skip_all)]
+    pub async fn decide(
+        &self,
+        id: String,
+        status: ApprovalStatus,
+        decided_by: Option<String>,
+    ) -> Result<bool> {
+        let query = r#"
+            UPDATE approvals
+            SET
+                status = :status,
+                decided_by = :decided_by,
+                decided_at = :decided_at
+            WHERE
+                id = :id
+                AND status = 'pending'
+        "#;
+
+        let decided_at = chrono::Utc::now().timestamp_millis();
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":status", status.to_string()));
+        q_params.push(opt_text_param(":decided_by", decided_by));
+        q_params.push(integer_param(":decided_at", decided_at));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+}
@@ -0,0 +1,212 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::Backfill;
+use crate::db::turso_decode::{
+    FromTursoRow, collect_row, collect_rows, opt_row_integer, opt_row_text, row_integer, row_text,
+};
+use crate::db::turso_params::{
+    integer_param, new_query_params, opt_integer_param, opt_text_param, text_param,
+};
+use crate::dto::BackfillProgressDto;
+use crate::error::{DbPrepareSnafu, DbStatementSnafu, DbTransactionSnafu};
+
+struct IdRow {
+    id: String,
+}
+
+impl FromTursoRow for IdRow {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+        })
+    }
+}
+
+impl FromTursoRow for BackfillProgressDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            name: row_text(row, 0)?,
+            last_id: opt_row_text(row, 1)?,
+            rows_done: row_integer(row, 2)?,
+            started_at: row_integer(row, 3)?,
+            updated_at: row_integer(row, 4)?,
+            completed_at: opt_row_integer(row, 5)?,
+        })
+    }
+}
+
+pub struct BackfillRepo {
+    db_pool: Connection,
+}
+
+impl BackfillRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.backfill.get_progress", skip_all)]
+    pub async fn get_progress(&self, name: String) -> Result<Option<BackfillProgressDto>> {
+        let query = r#"
+            SELECT
+                name,
+                last_id,
+                rows_done,
+                started_at,
+                updated_at,
+                completed_at
+            FROM backfill_progress
+            WHERE
+                name = :name
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":name", name));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<BackfillProgressDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Ids of the next rows that still need the backfill
+    #[instrument(level = "debug", name = "db.backfill.next_batch", skip_all)]
+    pub async fn next_batch(
+        &self,
+        backfill: &Backfill,
+        after_id: Option<String>,
+        limit: i64,
+    ) -> Result<Vec<String>> {
+        let query = format!(
+            r#"
+            SELECT
+                id
+            FROM {table}
+            WHERE
+                id > :after_id
+                AND ({pending})
+            ORDER BY id ASC
+            LIMIT :limit
+        "#,
+            table = backfill.table,
+            pending = backfill.pending,
+        );
+
+        let mut q_params = new_query_params();
+        // Ids are never empty, so an empty string starts from the first row
+        q_params.push(text_param(":after_id", after_id.unwrap_or_default()));
+        q_params.push(integer_param(":limit", limit));
+
+        let mut stmt = self.db_pool.prepare(&query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<IdRow> = collect_rows(&mut rows).await?;
+        Ok(items.into_iter().map(|item| item.id).collect())
+    }
+
+    /// Updates the batch and moves the progress past it in a single transaction.
+    ///
+    /// Rows changed since the batch was selected are skipped by `pending`.
+    /// Returns the number of rows updated.
+    #[instrument(level = "debug", name = "db.backfill.apply_batch", skip_all)]
+    pub async fn apply_batch(
+        &self,
+        backfill: &Backfill,
+        ids: &[String],
+        progress: &BackfillProgressDto,
+    ) -> Result<i64> {
+        let placeholders: Vec<String> = (0..ids.len()).map(|i| format!(":id_{}", i)).collect();
+        let query = format!(
+            r#"
+            UPDATE {table}
+            SET
+                {set}
+            WHERE
+                id IN ({ids})
+                AND ({pending})
+        "#,
+            table = backfill.table,
+            set = backfill.set,
+            ids = placeholders.join(", "),
+            pending = backfill.pending,
+        );
+
+        let mut q_params = new_query_params();
+        for (placeholder, id) in placeholders.iter().zip(ids.iter()) {
+            q_params.push(text_param(placeholder, id.clone()));
+        }
+
+        let mut conn = self.db_pool.clone();
+        let tx = conn.transaction().await.context(DbTransactionSnafu)?;
+
+        let mut stmt = tx.prepare(&query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)? as i64;
+
+        let progress = BackfillProgressDto {
+            last_id: ids.last().cloned().or(progress.last_id.clone()),
+            rows_done: progress.rows_done + affected,
+            ..progress.clone()
+        };
+        let (query, q_params) = upsert_progress_query(&progress);
+        let mut stmt = tx.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        tx.commit().await.context(DbTransactionSnafu)?;
+
+        Ok(affected)
+    }
+
+    #[instrument(level = "debug", name = "db.backfill.save_progress", skip_all)]
+    pub async fn save_progress(&self, progress: &BackfillProgressDto) -> Result<()> {
+        let (query, q_params) = upsert_progress_query(progress);
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(())
+    }
+}
+
+fn upsert_progress_query(
+    progress: &BackfillProgressDto,
+) -> (&'static str, Vec<(String, turso::Value)>) {
+    let query = r#"
+        INSERT INTO backfill_progress
+        (
+            name,
+            last_id,
+            rows_done,
+            started_at,
+            updated_at,
+            completed_at
+        )
+        VALUES
+        (
+            :name,
+            :last_id,
+            :rows_done,
+            :started_at,
+            :updated_at,
+            :completed_at
+        )
+        ON CONFLICT (name) DO UPDATE SET
+            last_id = excluded.last_id,
+            rows_done = excluded.rows_done,
+            started_at = excluded.started_at,
+            updated_at = excluded.updated_at,
+            completed_at = excluded.completed_at
+    "#;
+
+    let mut q_params = new_query_params();
+    q_params.push(text_param(":name", progress.name.clone()));
+    q_params.push(opt_text_param(":last_id", progress.last_id.clone()));
+    q_params.push(integer_param(":rows_done", progress.rows_done));
+    q_params.push(integer_param(":started_at", progress.started_at));
+    q_params.push(integer_param(
+        ":updated_at",
+        chrono::Utc::now().timestamp_millis(),
+    ));
+    q_params.push(opt_integer_param(":completed_at", progress.completed_at));
+
+    (query, q_params)
+}
@@ -0,0 +1,17 @@
+/// A data backfill for a derived column, run in batches by `yaas backfill`.
+///
+/// Rows matching `pending` get `set` applied, walking the table by `id`.
+/// Both are SQL fragments compiled into the binary, never user input.
+#[derive(Clone, Copy)]
+pub struct Backfill {
+    pub name: &'static str,
+    pub table: &'static str,
+    /// Condition of the rows that still need the backfill, ie: `email_normalized IS NULL`
+    pub pending: &'static str,
+    /// Assignments applied to those rows, ie: `email_normalized = LOWER(email)`
+    pub set: &'static str,
+}
+
+/// Backfills that can be run, add one alongside the migration of its column.
+/// Keep entries until every deployment has completed them.
+pub const BACKFILLS: &[Backfill] = &[];
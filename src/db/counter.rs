@@ -0,0 +1,174 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{FromTursoRow, collect_rows, row_integer, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::dto::StatCounterDto;
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+
+/// Members listed on the org, same rules as the member listing
+const ORG_MEMBER_COUNT: &str = r#"
+    SELECT COUNT(*)
+    FROM org_members
+    INNER JOIN users ON users.id = org_members.user_id
+    WHERE
+        org_members.org_id = orgs.id
+        AND users.deleted_at IS NULL
+"#;
+
+/// Apps linked to the org, same rules as the org app listing
+const ORG_APP_COUNT: &str = r#"
+    SELECT COUNT(*)
+    FROM org_apps
+    INNER JOIN apps ON apps.id = org_apps.app_id
+    WHERE
+        org_apps.org_id = orgs.id
+        AND org_apps.deleted_at IS NULL
+        AND apps.deleted_at IS NULL
+"#;
+
+/// Deployment wide counters and the query computing each
+const STAT_QUERIES: &[(&str, &str)] = &[
+    (
+        "users",
+        "SELECT COUNT(*) FROM users WHERE deleted_at IS NULL",
+    ),
+    (
+        "active_users",
+        "SELECT COUNT(*) FROM users WHERE deleted_at IS NULL AND status = 'active'",
+    ),
+    ("orgs", "SELECT COUNT(*) FROM orgs WHERE deleted_at IS NULL"),
+    (
+        "active_orgs",
+        "SELECT COUNT(*) FROM orgs WHERE deleted_at IS NULL AND status = 'active'",
+    ),
+    ("apps", "SELECT COUNT(*) FROM apps WHERE deleted_at IS NULL"),
+    (
+        "org_members",
+        "SELECT COALESCE(SUM(member_count), 0) FROM orgs WHERE deleted_at IS NULL",
+    ),
+];
+
+impl FromTursoRow for StatCounterDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            name: row_text(row, 0)?,
+            value: row_integer(row, 1)?,
+            updated_at: row_integer(row, 2)?,
+        })
+    }
+}
+
+/// Denormalized counters, the org columns and the `stat_counters` table
+pub struct CounterRepo {
+    db_pool: Connection,
+}
+
+impl CounterRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    /// Recounts the members and apps of one org
+    #[instrument(level = "debug", name = "db.counter.refresh_org", skip_all)]
+    pub async fn refresh_org(&self, org_id: String) -> Result<bool> {
+        let query = format!(
+            r#"
+            UPDATE orgs SET
+                member_count = ({}),
+                app_count = ({})
+            WHERE
+                id = :org_id
+            "#,
+            ORG_MEMBER_COUNT, ORG_APP_COUNT
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(affected > 0)
+    }
+
+    /// Recounts every org, returns how many had drifted
+    #[instrument(level = "debug", name = "db.counter.reconcile_orgs", skip_all)]
+    pub async fn reconcile_orgs(&self) -> Result<u64> {
+        let query = format!(
+            r#"
+            UPDATE orgs SET
+                member_count = ({members}),
+                app_count = ({apps})
+            WHERE
+                member_count != ({members})
+                OR app_count != ({apps})
+            "#,
+            members = ORG_MEMBER_COUNT,
+            apps = ORG_APP_COUNT
+        );
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt
+            .execute(new_query_params())
+            .await
+            .context(DbStatementSnafu)?;
+        Ok(affected)
+    }
+
+    /// Recomputes the deployment wide counters, run after `reconcile_orgs`
+    #[instrument(level = "debug", name = "db.counter.refresh_stats", skip_all)]
+    pub async fn refresh_stats(&self, now: i64) -> Result<()> {
+        for (name, count_query) in STAT_QUERIES.iter() {
+            let query = format!(
+                r#"
+                INSERT INTO stat_counters
+                (
+                    name,
+                    value,
+                    updated_at
+                )
+                VALUES
+                (
+                    :name,
+                    ({}),
+                    :updated_at
+                )
+                ON CONFLICT (name) DO UPDATE SET
+                    value = excluded.value,
+                    updated_at = excluded.updated_at
+                "#,
+                count_query
+            );
+
+            let mut q_params = new_query_params();
+            q_params.push(text_param(":name", name.to_string()));
+            q_params.push(integer_param(":updated_at", now));
+
+            let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+            let _ = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "db.counter.list_stats", skip_all)]
+    pub async fn list_stats(&self) -> Result<Vec<StatCounterDto>> {
+        let query = r#"
+            SELECT
+                name,
+                value,
+                updated_at
+            FROM stat_counters
+            ORDER BY name ASC
+        "#;
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt
+            .query(new_query_params())
+            .await
+            .context(DbStatementSnafu)?;
+        collect_rows(&mut rows).await
+    }
+}
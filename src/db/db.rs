@@ -1,17 +1,37 @@
 use std::path::Path;
 
 use snafu::ResultExt;
-use turso::{Builder, Connection};
+use tracing::error;
+use turso::{Builder, Connection, Database};
 
+use crate::db::read_pool::ReadPool;
+use crate::db::unit_of_work::UnitOfWork;
 use crate::db::{
-    app::AppRepo, oauth_code::OauthCodeRepo, org::OrgRepo, org_app::OrgAppRepo,
-    org_member::OrgMemberRepo, password::PasswordRepo, superuser::SuperuserRepo, user::UserRepo,
+    app::AppRepo, app_environment::AppEnvironmentRepo, app_proof_key::AppProofKeyRepo,
+    app_uri_check::AppUriCheckRepo, approval::ApprovalRepo, backfill::BackfillRepo,
+    counter::CounterRepo, email_outbox::EmailOutboxRepo, form_draft::FormDraftRepo,
+    idempotency_key::IdempotencyKeyRepo, impersonation::ImpersonationRepo,
+    integrity::IntegrityRepo, job::JobRepo, lifecycle::LifecycleRepo,
+    lifecycle_delivery::LifecycleDeliveryRepo, notification::NotificationRepo,
+    oauth_code::OauthCodeRepo, oauth_grant::OauthGrantRepo, org::OrgRepo,
+    org_access::OrgAccessRepo, org_app::OrgAppRepo, org_invitation::OrgInvitationRepo,
+    org_member::OrgMemberRepo, org_owner_transfer::OrgOwnerTransferRepo,
+    org_rate_limit::OrgRateLimitRepo, org_role::OrgRoleRepo, org_setting::OrgSettingRepo,
+    org_transfer::OrgTransferRepo, password::PasswordRepo, password_reset::PasswordResetRepo,
+    recovery::RecoveryTokenRepo, revoked_token::RevokedTokenRepo,
+    role_elevation::RoleElevationRepo, schema::SchemaRepo, suggestion::SuggestionRepo,
+    superuser::SuperuserRepo, team::TeamRepo, token_revocation::TokenRevocationRepo,
+    user::UserRepo, user_email::UserEmailRepo, user_erasure::UserErasureRepo,
+    user_export::UserExportRepo, user_notification::UserNotificationRepo,
+    user_profile::UserProfileRepo, user_session::UserSessionRepo,
 };
+use crate::dto::PaginationLimits;
 use crate::error::{DbBuilderSnafu, DbConnectSnafu};
 
 use crate::Result;
 
-pub async fn create_db_pool(filename: &Path) -> Result<Connection> {
+/// Opens the database and its shared connection
+pub async fn open_database(filename: &Path) -> Result<(Database, Connection)> {
     let db = Builder::new_local(filename.to_str().expect("DB path is required"))
         .build()
         .await
@@ -23,30 +43,170 @@ pub async fn create_db_pool(filename: &Path) -> Result<Connection> {
         .await
         .context(DbConnectSnafu)?;
 
-    Ok(conn)
+    Ok((db, conn))
 }
 
 pub struct DbMapper {
     pub apps: AppRepo,
+    pub app_environments: AppEnvironmentRepo,
+    pub app_proof_keys: AppProofKeyRepo,
+    pub app_uri_checks: AppUriCheckRepo,
+    pub approvals: ApprovalRepo,
+    pub backfills: BackfillRepo,
+    pub counters: CounterRepo,
+    pub email_outbox: EmailOutboxRepo,
+    pub form_drafts: FormDraftRepo,
+    pub idempotency_keys: IdempotencyKeyRepo,
+    pub impersonations: ImpersonationRepo,
+    pub integrity: IntegrityRepo,
+    pub jobs: JobRepo,
+    pub lifecycle: LifecycleRepo,
+    pub lifecycle_deliveries: LifecycleDeliveryRepo,
+    pub notifications: NotificationRepo,
     pub oauth_codes: OauthCodeRepo,
+    pub oauth_grants: OauthGrantRepo,
     pub orgs: OrgRepo,
+    pub org_access: OrgAccessRepo,
     pub org_apps: OrgAppRepo,
+    pub org_invitations: OrgInvitationRepo,
     pub org_members: OrgMemberRepo,
+    pub org_owner_transfers: OrgOwnerTransferRepo,
+    pub org_rate_limits: OrgRateLimitRepo,
+    pub org_roles: OrgRoleRepo,
+    pub org_settings: OrgSettingRepo,
+    pub org_transfers: OrgTransferRepo,
     pub passwords: PasswordRepo,
+    pub password_resets: PasswordResetRepo,
+    pub recovery_tokens: RecoveryTokenRepo,
+    pub role_elevations: RoleElevationRepo,
+    pub schema: SchemaRepo,
+    pub suggestions: SuggestionRepo,
     pub superusers: SuperuserRepo,
+    pub teams: TeamRepo,
+    pub token_revocations: TokenRevocationRepo,
+    pub revoked_tokens: RevokedTokenRepo,
     pub users: UserRepo,
+    pub user_emails: UserEmailRepo,
+    pub user_erasures: UserErasureRepo,
+    pub user_exports: UserExportRepo,
+    pub user_notifications: UserNotificationRepo,
+    pub user_profiles: UserProfileRepo,
+    pub user_sessions: UserSessionRepo,
+    database: Database,
+    pagination: PaginationLimits,
 }
 
-pub async fn create_db_mapper(filename: &Path) -> Result<DbMapper> {
-    let pool = create_db_pool(filename).await?;
-    Ok(DbMapper {
-        apps: AppRepo::new(pool.clone()),
+impl DbMapper {
+    /// Starts a unit of work on a connection of its own, see [`UnitOfWork`]
+    pub async fn begin(&self) -> Result<UnitOfWork> {
+        let conn = self.database.connect().context(DbConnectSnafu)?;
+        let read_pool = ReadPool::new(conn.clone(), None);
+        let db = new_db_mapper(
+            self.database.clone(),
+            conn.clone(),
+            read_pool,
+            &self.pagination,
+        );
+        UnitOfWork::begin(conn, db).await
+    }
+}
+
+/// Opens a read replica, running without one when it cannot be opened
+async fn create_replica_pool(filename: &Path) -> Option<Connection> {
+    let opened = async {
+        let db = Builder::new_local(filename.to_str().expect("DB path is required"))
+            .build()
+            .await
+            .context(DbBuilderSnafu)?;
+        db.connect().context(DbConnectSnafu)
+    };
+
+    match opened.await {
+        Ok(conn) => Some(conn),
+        Err(e) => {
+            error!(
+                "Read replica is unavailable, reading from the primary: {}",
+                e
+            );
+            None
+        }
+    }
+}
+
+pub async fn create_db_mapper(filename: &Path, pagination: &PaginationLimits) -> Result<DbMapper> {
+    create_db_mapper_with_replica(filename, None, pagination).await
+}
+
+/// Listings read from the replica when given, everything else uses the primary
+pub async fn create_db_mapper_with_replica(
+    filename: &Path,
+    replica: Option<&Path>,
+    pagination: &PaginationLimits,
+) -> Result<DbMapper> {
+    let (database, pool) = open_database(filename).await?;
+    let replica = match replica {
+        Some(replica) => create_replica_pool(replica).await,
+        None => None,
+    };
+    let read_pool = ReadPool::new(pool.clone(), replica);
+
+    Ok(new_db_mapper(database, pool, read_pool, pagination))
+}
+
+/// Binds every repo to the given connection
+fn new_db_mapper(
+    database: Database,
+    pool: Connection,
+    read_pool: ReadPool,
+    pagination: &PaginationLimits,
+) -> DbMapper {
+    DbMapper {
+        apps: AppRepo::new(pool.clone(), read_pool.clone(), pagination.clone()),
+        app_environments: AppEnvironmentRepo::new(pool.clone()),
+        app_proof_keys: AppProofKeyRepo::new(pool.clone()),
+        app_uri_checks: AppUriCheckRepo::new(pool.clone()),
+        approvals: ApprovalRepo::new(pool.clone()),
+        backfills: BackfillRepo::new(pool.clone()),
+        counters: CounterRepo::new(pool.clone()),
+        email_outbox: EmailOutboxRepo::new(pool.clone()),
+        form_drafts: FormDraftRepo::new(pool.clone()),
+        idempotency_keys: IdempotencyKeyRepo::new(pool.clone()),
+        impersonations: ImpersonationRepo::new(pool.clone()),
+        integrity: IntegrityRepo::new(pool.clone()),
+        jobs: JobRepo::new(pool.clone()),
+        lifecycle: LifecycleRepo::new(pool.clone()),
+        lifecycle_deliveries: LifecycleDeliveryRepo::new(pool.clone()),
+        notifications: NotificationRepo::new(pool.clone()),
         oauth_codes: OauthCodeRepo::new(pool.clone()),
-        orgs: OrgRepo::new(pool.clone()),
-        org_apps: OrgAppRepo::new(pool.clone()),
-        org_members: OrgMemberRepo::new(pool.clone()),
+        oauth_grants: OauthGrantRepo::new(pool.clone()),
+        orgs: OrgRepo::new(pool.clone(), read_pool.clone(), pagination.clone()),
+        org_access: OrgAccessRepo::new(pool.clone()),
+        org_apps: OrgAppRepo::new(pool.clone(), read_pool.clone(), pagination.clone()),
+        org_invitations: OrgInvitationRepo::new(pool.clone()),
+        org_members: OrgMemberRepo::new(pool.clone(), read_pool.clone(), pagination.clone()),
+        org_owner_transfers: OrgOwnerTransferRepo::new(pool.clone()),
+        org_rate_limits: OrgRateLimitRepo::new(pool.clone()),
+        org_roles: OrgRoleRepo::new(pool.clone()),
+        org_settings: OrgSettingRepo::new(pool.clone()),
+        org_transfers: OrgTransferRepo::new(pool.clone()),
         passwords: PasswordRepo::new(pool.clone()),
+        password_resets: PasswordResetRepo::new(pool.clone()),
+        recovery_tokens: RecoveryTokenRepo::new(pool.clone()),
+        role_elevations: RoleElevationRepo::new(pool.clone()),
+        schema: SchemaRepo::new(pool.clone()),
+        suggestions: SuggestionRepo::new(pool.clone()),
         superusers: SuperuserRepo::new(pool.clone()),
-        users: UserRepo::new(pool),
-    })
+        teams: TeamRepo::new(pool.clone()),
+        token_revocations: TokenRevocationRepo::new(pool.clone()),
+        revoked_tokens: RevokedTokenRepo::new(pool.clone()),
+        users: UserRepo::new(pool.clone(), read_pool.clone(), pagination.clone()),
+        user_emails: UserEmailRepo::new(pool.clone()),
+        user_erasures: UserErasureRepo::new(pool.clone()),
+        user_exports: UserExportRepo::new(pool.clone()),
+        user_notifications: UserNotificationRepo::new(pool.clone()),
+        user_profiles: UserProfileRepo::new(pool.clone()),
+        user_sessions: UserSessionRepo::new(pool),
+        database,
+        pagination: pagination.clone(),
+    }
 }
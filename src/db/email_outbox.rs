@@ -0,0 +1,248 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{
+    FromTursoRow, collect_row, collect_rows, opt_row_integer, opt_row_text, row_integer, row_text,
+};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::dto::{DELIVERY_DELIVERED, DELIVERY_PENDING, EmailDto, NewEmailDto};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for EmailDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            org_id: opt_row_text(row, 1)?,
+            recipient: row_text(row, 2)?,
+            subject: row_text(row, 3)?,
+            text_body: row_text(row, 4)?,
+            html_body: row_text(row, 5)?,
+            status: row_text(row, 6)?,
+            attempts: row_integer(row, 7)?,
+            next_attempt_at: row_integer(row, 8)?,
+            last_error: opt_row_text(row, 9)?,
+            created_at: row_integer(row, 10)?,
+            updated_at: row_integer(row, 11)?,
+            sent_at: opt_row_integer(row, 12)?,
+        })
+    }
+}
+
+const EMAIL_COLUMNS: &str = r#"
+    id,
+    org_id,
+    recipient,
+    subject,
+    text_body,
+    html_body,
+    status,
+    attempts,
+    next_attempt_at,
+    last_error,
+    created_at,
+    updated_at,
+    sent_at
+"#;
+
+pub struct EmailOutboxRepo {
+    db_pool: Connection,
+}
+
+impl EmailOutboxRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    /// Latest emails, optionally only those with the status
+    #[instrument(level = "debug", name = "db.email_outbox.list", skip_all)]
+    pub async fn list(&self, status: Option<String>, limit: i64) -> Result<Vec<EmailDto>> {
+        let mut query = format!(
+            r#"
+            SELECT {}
+            FROM email_outbox
+        "#,
+            EMAIL_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+
+        if let Some(status) = status {
+            query.push_str(" WHERE status = :status");
+            q_params.push(text_param(":status", status));
+        }
+
+        query.push_str(" ORDER BY created_at DESC, id DESC LIMIT :limit");
+        q_params.push(integer_param(":limit", limit));
+
+        let mut stmt = self
+            .db_pool
+            .prepare(query.as_str())
+            .await
+            .context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<EmailDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.email_outbox.get", skip_all)]
+    pub async fn get(&self, id: String) -> Result<Option<EmailDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM email_outbox
+            WHERE
+                id = :id
+            LIMIT 1
+        "#,
+            EMAIL_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<EmailDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Queues the email, due right away
+    #[instrument(level = "debug", name = "db.email_outbox.create", skip_all)]
+    pub async fn create(&self, data: NewEmailDto, now: i64) -> Result<EmailDto> {
+        let query = r#"
+            INSERT INTO email_outbox
+            (
+                id,
+                org_id,
+                recipient,
+                subject,
+                text_body,
+                html_body,
+                status,
+                attempts,
+                next_attempt_at,
+                created_at,
+                updated_at
+            )
+            VALUES
+            (
+                :id,
+                :org_id,
+                :recipient,
+                :subject,
+                :text_body,
+                :html_body,
+                :status,
+                0,
+                :next_attempt_at,
+                :created_at,
+                :updated_at
+            )
+        "#;
+
+        let email = EmailDto {
+            id: generate_id(IdPrefix::Email),
+            org_id: data.org_id,
+            recipient: data.recipient,
+            subject: data.subject,
+            text_body: data.text_body,
+            html_body: data.html_body,
+            status: DELIVERY_PENDING.to_string(),
+            attempts: 0,
+            next_attempt_at: now,
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+            sent_at: None,
+        };
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", email.id.clone()));
+        q_params.push(opt_text_param(":org_id", email.org_id.clone()));
+        q_params.push(text_param(":recipient", email.recipient.clone()));
+        q_params.push(text_param(":subject", email.subject.clone()));
+        q_params.push(text_param(":text_body", email.text_body.clone()));
+        q_params.push(text_param(":html_body", email.html_body.clone()));
+        q_params.push(text_param(":status", email.status.clone()));
+        q_params.push(integer_param(":next_attempt_at", now));
+        q_params.push(integer_param(":created_at", now));
+        q_params.push(integer_param(":updated_at", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        assert!(affected > 0, "Must insert an email outbox row");
+
+        Ok(email)
+    }
+
+    #[instrument(level = "debug", name = "db.email_outbox.mark_sent", skip_all)]
+    pub async fn mark_sent(&self, id: String, attempts: i64, now: i64) -> Result<()> {
+        let query = r#"
+            UPDATE email_outbox
+            SET
+                status = :status,
+                attempts = :attempts,
+                text_body = '',
+                html_body = '',
+                last_error = NULL,
+                sent_at = :now,
+                updated_at = :now
+            WHERE
+                id = :id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+        q_params.push(text_param(":status", DELIVERY_DELIVERED.to_string()));
+        q_params.push(integer_param(":attempts", attempts));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let _ = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(())
+    }
+
+    /// Records a failed attempt, either scheduling the next one or giving up
+    /// with the `failed` status, which also clears the bodies
+    #[instrument(level = "debug", name = "db.email_outbox.record_failure", skip_all)]
+    pub async fn record_failure(
+        &self,
+        id: String,
+        status: &str,
+        attempts: i64,
+        next_attempt_at: i64,
+        last_error: String,
+        now: i64,
+    ) -> Result<()> {
+        let query = r#"
+            UPDATE email_outbox
+            SET
+                status = :status,
+                attempts = :attempts,
+                next_attempt_at = :next_attempt_at,
+                text_body = CASE WHEN :status = 'pending' THEN text_body ELSE '' END,
+                html_body = CASE WHEN :status = 'pending' THEN html_body ELSE '' END,
+                last_error = :last_error,
+                updated_at = :now
+            WHERE
+                id = :id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+        q_params.push(text_param(":status", status.to_string()));
+        q_params.push(integer_param(":attempts", attempts));
+        q_params.push(integer_param(":next_attempt_at", next_attempt_at));
+        q_params.push(text_param(":last_error", last_error));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let _ = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(())
+    }
+}
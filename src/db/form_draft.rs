@@ -0,0 +1,141 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{FromTursoRow, collect_row, row_integer, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::dto::FormDraftDto;
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+
+impl FromTursoRow for FormDraftDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            user_id: row_text(row, 0)?,
+            form_key: row_text(row, 1)?,
+            data: row_text(row, 2)?,
+            updated_at: row_integer(row, 3)?,
+            expires_at: row_integer(row, 4)?,
+        })
+    }
+}
+
+pub struct FormDraftRepo {
+    db_pool: Connection,
+}
+
+impl FormDraftRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    /// Draft of the form, expired drafts are left out
+    #[instrument(level = "debug", name = "db.form_draft.get", skip_all)]
+    pub async fn get(
+        &self,
+        user_id: String,
+        form_key: String,
+        now: i64,
+    ) -> Result<Option<FormDraftDto>> {
+        let query = r#"
+            SELECT
+                user_id,
+                form_key,
+                data,
+                updated_at,
+                expires_at
+            FROM form_drafts
+            WHERE
+                user_id = :user_id
+                AND form_key = :form_key
+                AND expires_at > :now
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(text_param(":form_key", form_key));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<FormDraftDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Saves the draft, keeping a single row per user and form
+    #[instrument(level = "debug", name = "db.form_draft.upsert", skip_all)]
+    pub async fn upsert(&self, data: FormDraftDto) -> Result<FormDraftDto> {
+        let query = r#"
+            INSERT INTO form_drafts
+            (
+                user_id,
+                form_key,
+                data,
+                updated_at,
+                expires_at
+            )
+            VALUES
+            (
+                :user_id,
+                :form_key,
+                :data,
+                :updated_at,
+                :expires_at
+            )
+            ON CONFLICT (user_id, form_key) DO UPDATE SET
+                data = excluded.data,
+                updated_at = excluded.updated_at,
+                expires_at = excluded.expires_at
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", data.user_id.clone()));
+        q_params.push(text_param(":form_key", data.form_key.clone()));
+        q_params.push(text_param(":data", data.data.clone()));
+        q_params.push(integer_param(":updated_at", data.updated_at));
+        q_params.push(integer_param(":expires_at", data.expires_at));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(data)
+    }
+
+    #[instrument(level = "debug", name = "db.form_draft.delete", skip_all)]
+    pub async fn delete(&self, user_id: String, form_key: String) -> Result<bool> {
+        let query = r#"
+            DELETE FROM form_drafts
+            WHERE
+                user_id = :user_id
+                AND form_key = :form_key
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(text_param(":form_key", form_key));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+
+    /// Removes drafts past their expiry, returns how many were removed
+    #[instrument(level = "debug", name = "db.form_draft.delete_expired", skip_all)]
+    pub async fn delete_expired(&self, now: i64) -> Result<u64> {
+        let query = r#"
+            DELETE FROM form_drafts
+            WHERE
+                expires_at <= :now
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected)
+    }
+}
@@ -0,0 +1,230 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{
+    FromTursoRow, collect_row, opt_row_integer, opt_row_text, row_integer, row_text,
+};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::dto::{IdempotencyKeyDto, IdempotentResponseDto};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+
+impl FromTursoRow for IdempotencyKeyDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            user_id: row_text(row, 0)?,
+            route: row_text(row, 1)?,
+            idempotency_key: row_text(row, 2)?,
+            request_hash: row_text(row, 3)?,
+            status_code: opt_row_integer(row, 4)?,
+            content_type: opt_row_text(row, 5)?,
+            body: opt_row_text(row, 6)?,
+            response_hash: opt_row_text(row, 7)?,
+            created_at: row_integer(row, 8)?,
+            expires_at: row_integer(row, 9)?,
+        })
+    }
+}
+
+pub struct IdempotencyKeyRepo {
+    db_pool: Connection,
+}
+
+impl IdempotencyKeyRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    /// Key of the actor on the route, expired keys are left out
+    #[instrument(level = "debug", name = "db.idempotency_key.get", skip_all)]
+    pub async fn get(
+        &self,
+        user_id: String,
+        route: String,
+        idempotency_key: String,
+        now: i64,
+    ) -> Result<Option<IdempotencyKeyDto>> {
+        let query = r#"
+            SELECT
+                user_id,
+                route,
+                idempotency_key,
+                request_hash,
+                status_code,
+                content_type,
+                body,
+                response_hash,
+                created_at,
+                expires_at
+            FROM idempotency_keys
+            WHERE
+                user_id = :user_id
+                AND route = :route
+                AND idempotency_key = :idempotency_key
+                AND expires_at > :now
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(text_param(":route", route));
+        q_params.push(text_param(":idempotency_key", idempotency_key));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<IdempotencyKeyDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Claims the key for a new request.
+    ///
+    /// Returns false when the key is already taken, an expired key is
+    /// replaced.
+    #[instrument(level = "debug", name = "db.idempotency_key.reserve", skip_all)]
+    pub async fn reserve(&self, data: IdempotencyKeyDto) -> Result<bool> {
+        let delete_query = r#"
+            DELETE FROM idempotency_keys
+            WHERE
+                user_id = :user_id
+                AND route = :route
+                AND idempotency_key = :idempotency_key
+                AND expires_at <= :now
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", data.user_id.clone()));
+        q_params.push(text_param(":route", data.route.clone()));
+        q_params.push(text_param(":idempotency_key", data.idempotency_key.clone()));
+        q_params.push(integer_param(":now", data.created_at));
+
+        let mut stmt = self
+            .db_pool
+            .prepare(delete_query)
+            .await
+            .context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        let query = r#"
+            INSERT INTO idempotency_keys
+            (
+                user_id,
+                route,
+                idempotency_key,
+                request_hash,
+                created_at,
+                expires_at
+            )
+            VALUES
+            (
+                :user_id,
+                :route,
+                :idempotency_key,
+                :request_hash,
+                :created_at,
+                :expires_at
+            )
+            ON CONFLICT (user_id, route, idempotency_key) DO NOTHING
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", data.user_id));
+        q_params.push(text_param(":route", data.route));
+        q_params.push(text_param(":idempotency_key", data.idempotency_key));
+        q_params.push(text_param(":request_hash", data.request_hash));
+        q_params.push(integer_param(":created_at", data.created_at));
+        q_params.push(integer_param(":expires_at", data.expires_at));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+
+    /// Stores the response of the request that reserved the key, kept
+    /// until `expires_at`
+    #[instrument(level = "debug", name = "db.idempotency_key.complete", skip_all)]
+    pub async fn complete(
+        &self,
+        user_id: String,
+        route: String,
+        idempotency_key: String,
+        response: IdempotentResponseDto,
+        expires_at: i64,
+    ) -> Result<()> {
+        let query = r#"
+            UPDATE idempotency_keys
+            SET
+                status_code = :status_code,
+                content_type = :content_type,
+                body = :body,
+                response_hash = :response_hash,
+                expires_at = :expires_at
+            WHERE
+                user_id = :user_id
+                AND route = :route
+                AND idempotency_key = :idempotency_key
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":status_code", response.status_code));
+        q_params.push(opt_text_param(":content_type", response.content_type));
+        q_params.push(opt_text_param(":body", response.body));
+        q_params.push(text_param(":response_hash", response.response_hash));
+        q_params.push(integer_param(":expires_at", expires_at));
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(text_param(":route", route));
+        q_params.push(text_param(":idempotency_key", idempotency_key));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(())
+    }
+
+    /// Frees the key so the request can be sent again
+    #[instrument(level = "debug", name = "db.idempotency_key.delete", skip_all)]
+    pub async fn delete(
+        &self,
+        user_id: String,
+        route: String,
+        idempotency_key: String,
+    ) -> Result<bool> {
+        let query = r#"
+            DELETE FROM idempotency_keys
+            WHERE
+                user_id = :user_id
+                AND route = :route
+                AND idempotency_key = :idempotency_key
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(text_param(":route", route));
+        q_params.push(text_param(":idempotency_key", idempotency_key));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+
+    /// Removes keys past their expiry, returns how many were removed
+    #[instrument(level = "debug", name = "db.idempotency_key.delete_expired", skip_all)]
+    pub async fn delete_expired(&self, now: i64) -> Result<u64> {
+        let query = r#"
+            DELETE FROM idempotency_keys
+            WHERE
+                expires_at <= :now
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected)
+    }
+}
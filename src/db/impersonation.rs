@@ -0,0 +1,261 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{
+    FromTursoRow, collect_row, collect_rows, opt_row_integer, row_integer, row_text,
+};
+use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::dto::{ImpersonationActionDto, ImpersonationDto};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for ImpersonationDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            impersonator_id: row_text(row, 1)?,
+            user_id: row_text(row, 2)?,
+            org_id: row_text(row, 3)?,
+            created_at: row_integer(row, 4)?,
+            expires_at: row_integer(row, 5)?,
+            ended_at: opt_row_integer(row, 6)?,
+        })
+    }
+}
+
+impl FromTursoRow for ImpersonationActionDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            impersonation_id: row_text(row, 1)?,
+            method: row_text(row, 2)?,
+            path: row_text(row, 3)?,
+            status: row_integer(row, 4)?,
+            created_at: row_integer(row, 5)?,
+        })
+    }
+}
+
+const IMPERSONATION_COLUMNS: &str = r#"
+    id,
+    impersonator_id,
+    user_id,
+    org_id,
+    created_at,
+    expires_at,
+    ended_at
+"#;
+
+pub struct ImpersonationRepo {
+    db_pool: Connection,
+}
+
+impl ImpersonationRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.impersonation.get", skip_all)]
+    pub async fn get(&self, id: String) -> Result<Option<ImpersonationDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM impersonations
+            WHERE id = :id
+            LIMIT 1
+        "#,
+            IMPERSONATION_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<ImpersonationDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Latest impersonations of the user, ended ones included
+    #[instrument(level = "debug", name = "db.impersonation.list_by_user", skip_all)]
+    pub async fn list_by_user(&self, user_id: String, limit: i64) -> Result<Vec<ImpersonationDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM impersonations
+            WHERE user_id = :user_id
+            ORDER BY created_at DESC
+            LIMIT :limit
+        "#,
+            IMPERSONATION_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(integer_param(":limit", limit));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        collect_rows(&mut rows).await
+    }
+
+    #[instrument(level = "debug", name = "db.impersonation.create", skip_all)]
+    pub async fn create(
+        &self,
+        impersonator_id: String,
+        user_id: String,
+        org_id: String,
+        expires_at: i64,
+    ) -> Result<ImpersonationDto> {
+        let query = r#"
+            INSERT INTO impersonations
+            (
+                id,
+                impersonator_id,
+                user_id,
+                org_id,
+                created_at,
+                expires_at,
+                ended_at
+            )
+            VALUES
+            (
+                :id,
+                :impersonator_id,
+                :user_id,
+                :org_id,
+                :created_at,
+                :expires_at,
+                NULL
+            )
+        "#;
+
+        let id = generate_id(IdPrefix::Impersonation);
+        let today = chrono::Utc::now().timestamp_millis();
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id.clone()));
+        q_params.push(text_param(":impersonator_id", impersonator_id.clone()));
+        q_params.push(text_param(":user_id", user_id.clone()));
+        q_params.push(text_param(":org_id", org_id.clone()));
+        q_params.push(integer_param(":created_at", today));
+        q_params.push(integer_param(":expires_at", expires_at));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        assert!(affected > 0, "Must insert a new impersonation row");
+
+        Ok(ImpersonationDto {
+            id,
+            impersonator_id,
+            user_id,
+            org_id,
+            created_at: today,
+            expires_at,
+            ended_at: None,
+        })
+    }
+
+    /// Ends a running impersonation, returns false when it already ended
+    #[instrument(level = "debug", name = "db.impersonation.end", skip_all)]
+    pub async fn end(&self, id: String, now: i64) -> Result<bool> {
+        let query = r#"
+            UPDATE impersonations
+            SET ended_at = :now
+            WHERE
+                id = :id
+                AND ended_at IS NULL
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":now", now));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+
+    #[instrument(level = "debug", name = "db.impersonation.record_action", skip_all)]
+    pub async fn record_action(
+        &self,
+        impersonation_id: String,
+        method: String,
+        path: String,
+        status: i64,
+    ) -> Result<()> {
+        let query = r#"
+            INSERT INTO impersonation_actions
+            (
+                id,
+                impersonation_id,
+                method,
+                path,
+                status,
+                created_at
+            )
+            VALUES
+            (
+                :id,
+                :impersonation_id,
+                :method,
+                :path,
+                :status,
+                :created_at
+            )
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(
+            ":id",
+            generate_id(IdPrefix::ImpersonationAction),
+        ));
+        q_params.push(text_param(":impersonation_id", impersonation_id));
+        q_params.push(text_param(":method", method));
+        q_params.push(text_param(":path", path));
+        q_params.push(integer_param(":status", status));
+        q_params.push(integer_param(
+            ":created_at",
+            chrono::Utc::now().timestamp_millis(),
+        ));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        assert!(affected > 0, "Must insert a new impersonation action row");
+
+        Ok(())
+    }
+
+    /// Requests made during the impersonation, oldest first
+    #[instrument(level = "debug", name = "db.impersonation.list_actions", skip_all)]
+    pub async fn list_actions(
+        &self,
+        impersonation_id: String,
+        limit: i64,
+    ) -> Result<Vec<ImpersonationActionDto>> {
+        let query = r#"
+            SELECT
+                id,
+                impersonation_id,
+                method,
+                path,
+                status,
+                created_at
+            FROM impersonation_actions
+            WHERE impersonation_id = :impersonation_id
+            ORDER BY created_at ASC
+            LIMIT :limit
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":impersonation_id", impersonation_id));
+        q_params.push(integer_param(":limit", limit));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        collect_rows(&mut rows).await
+    }
+}
@@ -0,0 +1,181 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::Connection;
+
+use crate::Result;
+use crate::db::turso_decode::collect_count;
+use crate::db::turso_params::new_query_params;
+use crate::dto::OrphanCountDto;
+use crate::error::{DbPrepareSnafu, DbStatementSnafu, DbTransactionSnafu};
+
+struct OrphanRule {
+    kind: &'static str,
+    table: &'static str,
+    condition: &'static str,
+}
+
+/// Rows left behind by soft deletes or partially failed writes
+const ORPHAN_RULES: &[OrphanRule] = &[
+    OrphanRule {
+        kind: "org_members.deleted_user",
+        table: "org_members",
+        condition: "user_id NOT IN (SELECT id FROM users WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "org_members.deleted_org",
+        table: "org_members",
+        condition: "org_id NOT IN (SELECT id FROM orgs WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "org_apps.deleted_app",
+        table: "org_apps",
+        condition: "app_id NOT IN (SELECT id FROM apps WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "org_apps.deleted_org",
+        table: "org_apps",
+        condition: "org_id NOT IN (SELECT id FROM orgs WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "oauth_grants.deleted_user",
+        table: "oauth_grants",
+        condition: "user_id NOT IN (SELECT id FROM users WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "oauth_grants.deleted_org",
+        table: "oauth_grants",
+        condition: "org_id NOT IN (SELECT id FROM orgs WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "oauth_grants.deleted_app",
+        table: "oauth_grants",
+        condition: "app_id NOT IN (SELECT id FROM apps WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "user_emails.deleted_user",
+        table: "user_emails",
+        condition: "user_id NOT IN (SELECT id FROM users WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "notification_prefs.deleted_user",
+        table: "notification_prefs",
+        condition: "user_id NOT IN (SELECT id FROM users WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "lifecycle_subscriptions.deleted_app",
+        table: "lifecycle_subscriptions",
+        condition: "app_id NOT IN (SELECT id FROM apps WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "recovery_tokens.deleted_user",
+        table: "recovery_tokens",
+        condition: "user_id NOT IN (SELECT id FROM users WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "org_rate_limits.deleted_org",
+        table: "org_rate_limits",
+        condition: "org_id NOT IN (SELECT id FROM orgs WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "form_drafts.deleted_user",
+        table: "form_drafts",
+        condition: "user_id NOT IN (SELECT id FROM users WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "idempotency_keys.deleted_user",
+        table: "idempotency_keys",
+        condition: "user_id NOT IN (SELECT id FROM users WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "password_resets.deleted_user",
+        table: "password_resets",
+        condition: "user_id NOT IN (SELECT id FROM users WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "app_uri_checks.deleted_app",
+        table: "app_uri_checks",
+        condition: "app_id NOT IN (SELECT id FROM apps WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "app_proof_keys.deleted_app",
+        table: "app_proof_keys",
+        condition: "app_id NOT IN (SELECT id FROM apps WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "app_environments.deleted_app",
+        table: "app_environments",
+        condition: "app_id NOT IN (SELECT id FROM apps WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "user_sessions.deleted_user",
+        table: "user_sessions",
+        condition: "user_id NOT IN (SELECT id FROM users WHERE deleted_at IS NULL)",
+    },
+    OrphanRule {
+        kind: "user_notifications.deleted_user",
+        table: "user_notifications",
+        condition: "user_id NOT IN (SELECT id FROM users WHERE deleted_at IS NULL)",
+    },
+];
+
+pub struct IntegrityRepo {
+    db_pool: Connection,
+}
+
+impl IntegrityRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.integrity.count_orphans", skip_all)]
+    pub async fn count_orphans(&self) -> Result<Vec<OrphanCountDto>> {
+        let mut items = Vec::with_capacity(ORPHAN_RULES.len());
+
+        for rule in ORPHAN_RULES.iter() {
+            let query = format!(
+                "SELECT COUNT(*) AS total_count FROM {} WHERE {}",
+                rule.table, rule.condition
+            );
+
+            let mut stmt = self.db_pool.prepare(&query).await.context(DbPrepareSnafu)?;
+            let row_result = stmt.query_row(new_query_params()).await;
+
+            items.push(OrphanCountDto {
+                kind: rule.kind.to_string(),
+                count: collect_count(row_result)?,
+            });
+        }
+
+        Ok(items)
+    }
+
+    /// Deletes orphans in a single transaction.
+    /// Conditions are re-evaluated on delete so rows fixed by concurrent
+    /// writes since the last scan are left untouched.
+    #[instrument(level = "debug", name = "db.integrity.delete_orphans", skip_all)]
+    pub async fn delete_orphans(&self) -> Result<Vec<OrphanCountDto>> {
+        let mut items = Vec::with_capacity(ORPHAN_RULES.len());
+
+        let mut conn = self.db_pool.clone();
+        let tx = conn.transaction().await.context(DbTransactionSnafu)?;
+
+        for rule in ORPHAN_RULES.iter() {
+            let query = format!("DELETE FROM {} WHERE {}", rule.table, rule.condition);
+
+            let mut stmt = tx.prepare(&query).await.context(DbPrepareSnafu)?;
+            let affected = stmt
+                .execute(new_query_params())
+                .await
+                .context(DbStatementSnafu)?;
+
+            items.push(OrphanCountDto {
+                kind: rule.kind.to_string(),
+                count: affected as i64,
+            });
+        }
+
+        tx.commit().await.context(DbTransactionSnafu)?;
+
+        Ok(items)
+    }
+}
@@ -0,0 +1,356 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{
+    FromTursoRow, collect_row, collect_rows, opt_row_integer, opt_row_text, row_integer, row_text,
+};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::dto::{JobDto, JobKind, JobStatus};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for JobDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            kind: JobKind::try_from(row_text(row, 1)?.as_str())?,
+            payload: row_text(row, 2)?,
+            status: JobStatus::try_from(row_text(row, 3)?.as_str())?,
+            attempts: row_integer(row, 4)?,
+            max_attempts: row_integer(row, 5)?,
+            run_at: row_integer(row, 6)?,
+            locked_until: opt_row_integer(row, 7)?,
+            last_error: opt_row_text(row, 8)?,
+            created_at: row_integer(row, 9)?,
+            updated_at: row_integer(row, 10)?,
+            finished_at: opt_row_integer(row, 11)?,
+        })
+    }
+}
+
+const JOB_COLUMNS: &str = r#"
+    id,
+    kind,
+    payload,
+    status,
+    attempts,
+    max_attempts,
+    run_at,
+    locked_until,
+    last_error,
+    created_at,
+    updated_at,
+    finished_at
+"#;
+
+pub struct JobRepo {
+    db_pool: Connection,
+}
+
+impl JobRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    /// Latest jobs, optionally of one status, newest first
+    #[instrument(level = "debug", name = "db.job.list", skip_all)]
+    pub async fn list(&self, status: Option<JobStatus>, limit: i64) -> Result<Vec<JobDto>> {
+        let mut query = format!(
+            r#"
+            SELECT {}
+            FROM jobs
+        "#,
+            JOB_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+
+        if let Some(status) = status {
+            query.push_str(" WHERE status = :status");
+            q_params.push(text_param(":status", status.to_string()));
+        }
+
+        query.push_str(" ORDER BY created_at DESC, id DESC LIMIT :limit");
+        q_params.push(integer_param(":limit", limit));
+
+        let mut stmt = self
+            .db_pool
+            .prepare(query.as_str())
+            .await
+            .context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<JobDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.job.get", skip_all)]
+    pub async fn get(&self, id: String) -> Result<Option<JobDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM jobs
+            WHERE
+                id = :id
+            LIMIT 1
+        "#,
+            JOB_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<JobDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.job.enqueue", skip_all)]
+    pub async fn enqueue(
+        &self,
+        kind: JobKind,
+        payload: String,
+        max_attempts: i64,
+        run_at: i64,
+    ) -> Result<JobDto> {
+        let query = r#"
+            INSERT INTO jobs
+            (
+                id,
+                kind,
+                payload,
+                status,
+                attempts,
+                max_attempts,
+                run_at,
+                created_at,
+                updated_at
+            )
+            VALUES
+            (
+                :id,
+                :kind,
+                :payload,
+                :status,
+                0,
+                :max_attempts,
+                :run_at,
+                :created_at,
+                :updated_at
+            )
+        "#;
+
+        let today = chrono::Utc::now().timestamp_millis();
+        let job = JobDto {
+            id: generate_id(IdPrefix::Job),
+            kind,
+            payload,
+            status: JobStatus::Queued,
+            attempts: 0,
+            max_attempts,
+            run_at,
+            locked_until: None,
+            last_error: None,
+            created_at: today,
+            updated_at: today,
+            finished_at: None,
+        };
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", job.id.clone()));
+        q_params.push(text_param(":kind", job.kind.to_string()));
+        q_params.push(text_param(":payload", job.payload.clone()));
+        q_params.push(text_param(":status", job.status.to_string()));
+        q_params.push(integer_param(":max_attempts", max_attempts));
+        q_params.push(integer_param(":run_at", run_at));
+        q_params.push(integer_param(":created_at", today));
+        q_params.push(integer_param(":updated_at", today));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        assert!(affected > 0, "Must insert a job row");
+
+        Ok(job)
+    }
+
+    /// Claims the oldest due job: a queued job whose time has come, or a
+    /// running job whose lock expired because its worker went away.
+    ///
+    /// The claimed job is `running` and locked until `locked_until`.
+    #[instrument(level = "debug", name = "db.job.claim_next", skip_all)]
+    pub async fn claim_next(&self, now: i64, locked_until: i64) -> Result<Option<JobDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM jobs
+            WHERE
+                (status = 'queued' AND run_at <= :now)
+                OR (status = 'running' AND locked_until <= :now)
+            ORDER BY run_at ASC, id ASC
+            LIMIT 1
+        "#,
+            JOB_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let job: Option<JobDto> = collect_row(row_result)?;
+
+        let Some(job) = job else {
+            return Ok(None);
+        };
+
+        // Only one worker wins when the same job is picked twice
+        let query = r#"
+            UPDATE jobs
+            SET
+                status = 'running',
+                attempts = attempts + 1,
+                locked_until = :locked_until,
+                updated_at = :now
+            WHERE
+                id = :id
+                AND status = :status
+                AND attempts = :attempts
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", job.id.clone()));
+        q_params.push(text_param(":status", job.status.to_string()));
+        q_params.push(integer_param(":attempts", job.attempts));
+        q_params.push(integer_param(":locked_until", locked_until));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        if affected == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(JobDto {
+            status: JobStatus::Running,
+            attempts: job.attempts + 1,
+            locked_until: Some(locked_until),
+            updated_at: now,
+            ..job
+        }))
+    }
+
+    #[instrument(level = "debug", name = "db.job.succeed", skip_all)]
+    pub async fn succeed(&self, id: String, now: i64) -> Result<()> {
+        let query = r#"
+            UPDATE jobs
+            SET
+                status = 'succeeded',
+                locked_until = NULL,
+                last_error = NULL,
+                updated_at = :now,
+                finished_at = :now
+            WHERE
+                id = :id
+                AND status = 'running'
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let _ = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(())
+    }
+
+    /// Puts a failed job back in the queue to run again at `run_at`
+    #[instrument(level = "debug", name = "db.job.retry_later", skip_all)]
+    pub async fn retry_later(
+        &self,
+        id: String,
+        run_at: i64,
+        error: String,
+        now: i64,
+    ) -> Result<()> {
+        let query = r#"
+            UPDATE jobs
+            SET
+                status = 'queued',
+                run_at = :run_at,
+                locked_until = NULL,
+                last_error = :last_error,
+                updated_at = :now
+            WHERE
+                id = :id
+                AND status = 'running'
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+        q_params.push(integer_param(":run_at", run_at));
+        q_params.push(text_param(":last_error", error));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let _ = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(())
+    }
+
+    /// Moves a job that ran out of attempts to the dead letters
+    #[instrument(level = "debug", name = "db.job.bury", skip_all)]
+    pub async fn bury(&self, id: String, error: Option<String>, now: i64) -> Result<()> {
+        let query = r#"
+            UPDATE jobs
+            SET
+                status = 'dead',
+                locked_until = NULL,
+                last_error = :last_error,
+                updated_at = :now,
+                finished_at = :now
+            WHERE
+                id = :id
+                AND status = 'running'
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+        q_params.push(opt_text_param(":last_error", error));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let _ = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(())
+    }
+
+    /// Gives a dead job a fresh set of attempts, due right away
+    #[instrument(level = "debug", name = "db.job.revive", skip_all)]
+    pub async fn revive(&self, id: String, now: i64) -> Result<bool> {
+        let query = r#"
+            UPDATE jobs
+            SET
+                status = 'queued',
+                attempts = 0,
+                run_at = :now,
+                finished_at = NULL,
+                updated_at = :now
+            WHERE
+                id = :id
+                AND status = 'dead'
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+}
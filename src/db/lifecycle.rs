@@ -0,0 +1,219 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{FromTursoRow, collect_row, collect_rows, row_integer, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::dto::{LifecycleSubscriptionDto, LifecycleTopic};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for LifecycleSubscriptionDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            app_id: row_text(row, 1)?,
+            topic: LifecycleTopic::try_from(row_text(row, 2)?.as_str())?,
+            url: row_text(row, 3)?,
+            secret: row_text(row, 4)?,
+            created_at: row_integer(row, 5)?,
+            updated_at: row_integer(row, 6)?,
+        })
+    }
+}
+
+pub struct LifecycleRepo {
+    db_pool: Connection,
+}
+
+impl LifecycleRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.lifecycle.list_by_app", skip_all)]
+    pub async fn list_by_app(&self, app_id: String) -> Result<Vec<LifecycleSubscriptionDto>> {
+        let query = r#"
+            SELECT
+                id,
+                app_id,
+                topic,
+                url,
+                secret,
+                created_at,
+                updated_at
+            FROM lifecycle_subscriptions
+            WHERE
+                app_id = :app_id
+            ORDER BY topic ASC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":app_id", app_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<LifecycleSubscriptionDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    /// Subscribers of the topic among live apps.
+    ///
+    /// With an org, only apps linked to that org are returned.
+    #[instrument(level = "debug", name = "db.lifecycle.list_subscribers", skip_all)]
+    pub async fn list_subscribers(
+        &self,
+        topic: LifecycleTopic,
+        org_id: Option<String>,
+    ) -> Result<Vec<LifecycleSubscriptionDto>> {
+        let mut query = r#"
+            SELECT
+                lifecycle_subscriptions.id,
+                lifecycle_subscriptions.app_id,
+                lifecycle_subscriptions.topic,
+                lifecycle_subscriptions.url,
+                lifecycle_subscriptions.secret,
+                lifecycle_subscriptions.created_at,
+                lifecycle_subscriptions.updated_at
+            FROM lifecycle_subscriptions
+            INNER JOIN apps ON apps.id = lifecycle_subscriptions.app_id
+            WHERE
+                lifecycle_subscriptions.topic = :topic
+                AND apps.deleted_at IS NULL
+        "#
+        .to_string();
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":topic", topic.to_string()));
+
+        if let Some(org_id) = org_id {
+            query.push_str(
+                " AND lifecycle_subscriptions.app_id IN (SELECT app_id FROM org_apps WHERE org_id = :org_id AND deleted_at IS NULL)",
+            );
+            q_params.push(text_param(":org_id", org_id));
+        }
+
+        let mut stmt = self
+            .db_pool
+            .prepare(query.as_str())
+            .await
+            .context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<LifecycleSubscriptionDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.lifecycle.get", skip_all)]
+    pub async fn get(
+        &self,
+        app_id: String,
+        id: String,
+    ) -> Result<Option<LifecycleSubscriptionDto>> {
+        let query = r#"
+            SELECT
+                id,
+                app_id,
+                topic,
+                url,
+                secret,
+                created_at,
+                updated_at
+            FROM lifecycle_subscriptions
+            WHERE
+                id = :id
+                AND app_id = :app_id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+        q_params.push(text_param(":app_id", app_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<LifecycleSubscriptionDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Creates the topic subscription or points the existing one to a new url.
+    /// The signing secret is kept when updating.
+    #[instrument(level = "debug", name = "db.lifecycle.upsert", skip_all)]
+    pub async fn upsert(
+        &self,
+        app_id: String,
+        topic: LifecycleTopic,
+        url: String,
+    ) -> Result<LifecycleSubscriptionDto> {
+        let query = r#"
+            INSERT INTO lifecycle_subscriptions
+            (
+                id,
+                app_id,
+                topic,
+                url,
+                secret,
+                created_at,
+                updated_at
+            )
+            VALUES
+            (
+                :id,
+                :app_id,
+                :topic,
+                :url,
+                :secret,
+                :created_at,
+                :updated_at
+            )
+            ON CONFLICT (app_id, topic) DO UPDATE SET
+                url = excluded.url,
+                updated_at = excluded.updated_at
+        "#;
+
+        let today = chrono::Utc::now().timestamp_millis();
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(
+            ":id",
+            generate_id(IdPrefix::LifecycleSubscription),
+        ));
+        q_params.push(text_param(":app_id", app_id.clone()));
+        q_params.push(text_param(":topic", topic.to_string()));
+        q_params.push(text_param(":url", url));
+        q_params.push(text_param(":secret", generate_id(IdPrefix::SigningSecret)));
+        q_params.push(integer_param(":created_at", today));
+        q_params.push(integer_param(":updated_at", today));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        assert!(affected > 0, "Must upsert a lifecycle subscription row");
+
+        let items = self.list_by_app(app_id).await?;
+        let subscription = items
+            .into_iter()
+            .find(|item| item.topic == topic)
+            .expect("Upserted lifecycle subscription must exist");
+
+        Ok(subscription)
+    }
+
+    #[instrument(level = "debug", name = "db.lifecycle.delete", skip_all)]
+    pub async fn delete(&self, app_id: String, id: String) -> Result<bool> {
+        let query = r#"
+            DELETE FROM lifecycle_subscriptions
+            WHERE
+                id = :id
+                AND app_id = :app_id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+        q_params.push(text_param(":app_id", app_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+}
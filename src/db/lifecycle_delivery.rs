@@ -0,0 +1,279 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{
+    FromTursoRow, collect_row, collect_rows, opt_row_integer, opt_row_text, row_integer, row_text,
+};
+use crate::db::turso_params::{
+    integer_param, new_query_params, opt_integer_param, opt_text_param, text_param,
+};
+use crate::dto::{
+    DELIVERY_DELIVERED, DELIVERY_PENDING, LifecycleDeliveryDto, LifecycleSubscriptionDto,
+    LifecycleTopic,
+};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for LifecycleDeliveryDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            event_id: row_text(row, 1)?,
+            subscription_id: row_text(row, 2)?,
+            app_id: row_text(row, 3)?,
+            topic: LifecycleTopic::try_from(row_text(row, 4)?.as_str())?,
+            payload: row_text(row, 5)?,
+            status: row_text(row, 6)?,
+            attempts: row_integer(row, 7)?,
+            next_attempt_at: row_integer(row, 8)?,
+            last_status: opt_row_integer(row, 9)?,
+            last_error: opt_row_text(row, 10)?,
+            created_at: row_integer(row, 11)?,
+            updated_at: row_integer(row, 12)?,
+            delivered_at: opt_row_integer(row, 13)?,
+        })
+    }
+}
+
+const DELIVERY_COLUMNS: &str = r#"
+    id,
+    event_id,
+    subscription_id,
+    app_id,
+    topic,
+    payload,
+    status,
+    attempts,
+    next_attempt_at,
+    last_status,
+    last_error,
+    created_at,
+    updated_at,
+    delivered_at
+"#;
+
+pub struct LifecycleDeliveryRepo {
+    db_pool: Connection,
+}
+
+impl LifecycleDeliveryRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    /// Latest deliveries of the app, newest first
+    #[instrument(level = "debug", name = "db.lifecycle_delivery.list_by_app", skip_all)]
+    pub async fn list_by_app(
+        &self,
+        app_id: String,
+        limit: i64,
+    ) -> Result<Vec<LifecycleDeliveryDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM lifecycle_deliveries
+            WHERE
+                app_id = :app_id
+            ORDER BY created_at DESC, id DESC
+            LIMIT :limit
+        "#,
+            DELIVERY_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":app_id", app_id));
+        q_params.push(integer_param(":limit", limit));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<LifecycleDeliveryDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.lifecycle_delivery.get", skip_all)]
+    pub async fn get(&self, id: String) -> Result<Option<LifecycleDeliveryDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM lifecycle_deliveries
+            WHERE
+                id = :id
+            LIMIT 1
+        "#,
+            DELIVERY_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<LifecycleDeliveryDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Queues the event for the subscription, due right away
+    #[instrument(level = "debug", name = "db.lifecycle_delivery.create", skip_all)]
+    pub async fn create(
+        &self,
+        subscription: &LifecycleSubscriptionDto,
+        event_id: String,
+        payload: String,
+        now: i64,
+    ) -> Result<LifecycleDeliveryDto> {
+        let query = r#"
+            INSERT INTO lifecycle_deliveries
+            (
+                id,
+                event_id,
+                subscription_id,
+                app_id,
+                topic,
+                payload,
+                status,
+                attempts,
+                next_attempt_at,
+                created_at,
+                updated_at
+            )
+            VALUES
+            (
+                :id,
+                :event_id,
+                :subscription_id,
+                :app_id,
+                :topic,
+                :payload,
+                :status,
+                0,
+                :next_attempt_at,
+                :created_at,
+                :updated_at
+            )
+        "#;
+
+        let delivery = LifecycleDeliveryDto {
+            id: generate_id(IdPrefix::LifecycleDelivery),
+            event_id,
+            subscription_id: subscription.id.clone(),
+            app_id: subscription.app_id.clone(),
+            topic: subscription.topic,
+            payload,
+            status: DELIVERY_PENDING.to_string(),
+            attempts: 0,
+            next_attempt_at: now,
+            last_status: None,
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+            delivered_at: None,
+        };
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", delivery.id.clone()));
+        q_params.push(text_param(":event_id", delivery.event_id.clone()));
+        q_params.push(text_param(
+            ":subscription_id",
+            delivery.subscription_id.clone(),
+        ));
+        q_params.push(text_param(":app_id", delivery.app_id.clone()));
+        q_params.push(text_param(":topic", delivery.topic.to_string()));
+        q_params.push(text_param(":payload", delivery.payload.clone()));
+        q_params.push(text_param(":status", delivery.status.clone()));
+        q_params.push(integer_param(":next_attempt_at", now));
+        q_params.push(integer_param(":created_at", now));
+        q_params.push(integer_param(":updated_at", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        assert!(affected > 0, "Must insert a lifecycle delivery row");
+
+        Ok(delivery)
+    }
+
+    #[instrument(
+        level = "debug",
+        name = "db.lifecycle_delivery.mark_delivered",
+        skip_all
+    )]
+    pub async fn mark_delivered(
+        &self,
+        id: String,
+        attempts: i64,
+        last_status: i64,
+        now: i64,
+    ) -> Result<()> {
+        let query = r#"
+            UPDATE lifecycle_deliveries
+            SET
+                status = :status,
+                attempts = :attempts,
+                last_status = :last_status,
+                last_error = NULL,
+                delivered_at = :now,
+                updated_at = :now
+            WHERE
+                id = :id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+        q_params.push(text_param(":status", DELIVERY_DELIVERED.to_string()));
+        q_params.push(integer_param(":attempts", attempts));
+        q_params.push(integer_param(":last_status", last_status));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let _ = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(())
+    }
+
+    /// Records a failed attempt, either scheduling the next one
+    /// or giving up with the `failed` status
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(
+        level = "debug",
+        name = "db.lifecycle_delivery.record_failure",
+        skip_all
+    )]
+    pub async fn record_failure(
+        &self,
+        id: String,
+        status: &str,
+        attempts: i64,
+        next_attempt_at: i64,
+        last_status: Option<i64>,
+        last_error: Option<String>,
+        now: i64,
+    ) -> Result<()> {
+        let query = r#"
+            UPDATE lifecycle_deliveries
+            SET
+                status = :status,
+                attempts = :attempts,
+                next_attempt_at = :next_attempt_at,
+                last_status = :last_status,
+                last_error = :last_error,
+                updated_at = :now
+            WHERE
+                id = :id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+        q_params.push(text_param(":status", status.to_string()));
+        q_params.push(integer_param(":attempts", attempts));
+        q_params.push(integer_param(":next_attempt_at", next_attempt_at));
+        q_params.push(opt_integer_param(":last_status", last_status));
+        q_params.push(opt_text_param(":last_error", last_error));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let _ = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+/// A migration file embedded into the binary
+#[derive(Clone, Copy)]
+pub struct Migration {
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+macro_rules! migration {
+    ($name:literal) => {
+        Migration {
+            name: $name,
+            sql: include_str!(concat!("../../db/migrations/", $name)),
+        }
+    };
+}
+
+/// Schema migrations in the order they must be applied.
+/// `01-enable-mvcc.sql` is left out since the connection setup enables MVCC.
+pub const MIGRATIONS: &[Migration] = &[
+    migration!("02-create-users.sql"),
+    migration!("03-create-passwords.sql"),
+    migration!("04-create-orgs.sql"),
+    migration!("05-create-org-members.sql"),
+    migration!("06-create-apps.sql"),
+    migration!("07-create-org-apps.sql"),
+    migration!("08-create-oauth-codes.sql"),
+    migration!("09-create-superusers.sql"),
+    migration!("10-create-oauth-grants.sql"),
+    migration!("11-create-user-emails.sql"),
+    migration!("12-create-notification-prefs.sql"),
+    migration!("13-create-approvals.sql"),
+    migration!("14-create-lifecycle-subscriptions.sql"),
+    migration!("15-create-recovery-tokens.sql"),
+    migration!("16-create-app-uri-checks.sql"),
+    migration!("17-add-org-apps-deleted-at.sql"),
+    migration!("18-add-audit-columns.sql"),
+    migration!("19-create-app-proof-keys.sql"),
+    migration!("20-create-schema-migrations.sql"),
+    migration!("21-create-org-access-log.sql"),
+    migration!("22-create-app-environments.sql"),
+    migration!("23-create-token-revocations.sql"),
+    migration!("24-create-role-elevations.sql"),
+    migration!("25-create-user-sessions.sql"),
+    migration!("26-create-backfill-progress.sql"),
+    migration!("27-create-password-resets.sql"),
+    migration!("28-create-org-rate-limits.sql"),
+    migration!("29-create-form-drafts.sql"),
+    migration!("30-add-org-counters.sql"),
+    migration!("31-create-org-roles.sql"),
+    migration!("32-create-org-invitations.sql"),
+    migration!("33-create-lifecycle-deliveries.sql"),
+    migration!("34-create-jobs.sql"),
+    migration!("35-add-oauth-codes-nonce.sql"),
+    migration!("36-create-revoked-tokens.sql"),
+    migration!("37-create-user-profiles.sql"),
+    migration!("38-create-org-settings.sql"),
+    migration!("39-create-teams.sql"),
+    migration!("40-create-email-outbox.sql"),
+    migration!("41-create-user-notifications.sql"),
+    migration!("42-create-idempotency-keys.sql"),
+    migration!("43-create-org-owner-transfers.sql"),
+    migration!("44-add-app-secret-hashes.sql"),
+    migration!("45-add-app-redirect-uris.sql"),
+    migration!("46-add-app-scopes.sql"),
+    migration!("47-add-impersonations.sql"),
+    migration!("48-add-user-exports.sql"),
+    migration!("49-add-org-invitation-email-ids.sql"),
+    migration!("50-add-idempotency-response-hashes.sql"),
+    migration!("51-add-app-environment-secret-hashes.sql"),
+    migration!("52-queue-lifecycle-delivery-jobs.sql"),
+    migration!("53-queue-email-delivery-jobs.sql"),
+    migration!("54-add-password-reset-email-ids.sql"),
+    migration!("55-queue-elevation-revert-jobs.sql"),
+];
+
+/// Creates the table that tracks applied migrations
+pub const MIGRATIONS_TABLE_SQL: &str =
+    include_str!("../../db/migrations/20-create-schema-migrations.sql");
+
+/// Names of the tables created by the migrations
+pub fn migration_tables() -> Vec<String> {
+    MIGRATIONS
+        .iter()
+        .flat_map(|migration| migration.sql.split(';'))
+        .filter_map(|stmt| {
+            let stmt = stmt.trim();
+            let rest = stmt.strip_prefix("CREATE TABLE ")?;
+            let rest = rest.strip_prefix("IF NOT EXISTS ").unwrap_or(rest);
+            rest.split(|c: char| c.is_whitespace() || c == '(')
+                .next()
+                .map(|name| name.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::migration_tables;
+
+    #[test]
+    fn test_migration_tables() {
+        let tables = migration_tables();
+        assert_eq!(tables.first().map(|t| t.as_str()), Some("users"));
+        assert!(tables.contains(&"org_members".to_string()));
+        assert!(tables.contains(&"oauth_grants".to_string()));
+    }
+}
@@ -1,13 +1,61 @@
 mod app;
+mod app_environment;
+mod app_proof_key;
+mod app_uri_check;
+mod approval;
+mod backfill;
+mod backfills;
+mod counter;
+#[allow(clippy::module_inception)]
 mod db;
+mod email_outbox;
+mod form_draft;
+mod idempotency_key;
+mod impersonation;
+mod integrity;
+mod job;
+mod lifecycle;
+mod lifecycle_delivery;
+mod migrations;
+mod notification;
 mod oauth_code;
+mod oauth_grant;
 mod org;
+mod org_access;
 mod org_app;
+mod org_invitation;
 mod org_member;
+mod org_owner_transfer;
+mod org_rate_limit;
+mod org_role;
+mod org_setting;
+mod org_transfer;
 mod password;
+mod password_reset;
+mod read_pool;
+mod recovery;
+mod revoked_token;
+mod role_elevation;
+mod schema;
+mod soft_delete;
+mod suggestion;
 mod superuser;
+mod team;
+mod token_revocation;
 mod turso_decode;
 mod turso_params;
+mod unit_of_work;
 mod user;
+mod user_email;
+mod user_erasure;
+mod user_export;
+mod user_notification;
+mod user_profile;
+mod user_session;
+mod versioned;
 
-pub use db::{DbMapper, create_db_mapper};
+pub use backfills::{BACKFILLS, Backfill};
+pub use db::{DbMapper, create_db_mapper, create_db_mapper_with_replica};
+pub use migrations::{MIGRATIONS, Migration, migration_tables};
+pub use soft_delete::{DeletedScope, SoftDelete};
+pub use versioned::Versioned;
@@ -0,0 +1,172 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{FromTursoRow, collect_row, collect_rows, row_integer, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::dto::{NotificationDelivery, NotificationPrefDto, PendingMembersRowDto};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for NotificationPrefDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            user_id: row_text(row, 0)?,
+            delivery: NotificationDelivery::try_from(row_text(row, 1)?.as_str())?,
+        })
+    }
+}
+
+impl FromTursoRow for PendingMembersRowDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            admin_id: row_text(row, 0)?,
+            admin_email: row_text(row, 1)?,
+            delivery: NotificationDelivery::try_from(row_text(row, 2)?.as_str())?,
+            org_id: row_text(row, 3)?,
+            org_name: row_text(row, 4)?,
+            pending_count: row_integer(row, 5)?,
+        })
+    }
+}
+
+/// Active org admins of live orgs with their delivery preference and
+/// the number of inactive memberships waiting in the org
+const PENDING_MEMBERS_QUERY: &str = r#"
+    SELECT
+        admins.user_id,
+        users.email,
+        COALESCE(notification_prefs.delivery, 'digest') AS delivery,
+        orgs.id,
+        orgs.name,
+        (
+            SELECT COUNT(*)
+            FROM org_members pending
+            WHERE
+                pending.org_id = orgs.id
+                AND pending.status = 'inactive'
+        ) AS pending_count
+    FROM org_members admins
+    INNER JOIN users ON users.id = admins.user_id
+    INNER JOIN orgs ON orgs.id = admins.org_id
+    LEFT JOIN notification_prefs ON notification_prefs.user_id = admins.user_id
+    WHERE
+        admins.status = 'active'
+        AND (',' || admins.roles || ',') LIKE '%,OrgAdmin,%'
+        AND users.deleted_at IS NULL
+        AND users.status = 'active'
+        AND orgs.deleted_at IS NULL
+"#;
+
+pub struct NotificationRepo {
+    db_pool: Connection,
+}
+
+impl NotificationRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.notification.get_pref", skip_all)]
+    pub async fn get_pref(&self, user_id: String) -> Result<Option<NotificationPrefDto>> {
+        let query = r#"
+            SELECT
+                user_id,
+                delivery
+            FROM notification_prefs
+            WHERE
+                user_id = :user_id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<NotificationPrefDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.notification.upsert_pref", skip_all)]
+    pub async fn upsert_pref(&self, data: NotificationPrefDto) -> Result<NotificationPrefDto> {
+        let today = chrono::Utc::now().timestamp_millis();
+
+        let query = r#"
+            INSERT INTO notification_prefs
+            (
+                id,
+                user_id,
+                delivery,
+                created_at,
+                updated_at
+            )
+            VALUES
+            (
+                :id,
+                :user_id,
+                :delivery,
+                :created_at,
+                :updated_at
+            )
+            ON CONFLICT (user_id) DO UPDATE SET
+                delivery = excluded.delivery,
+                updated_at = excluded.updated_at
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", generate_id(IdPrefix::NotificationPref)));
+        q_params.push(text_param(":user_id", data.user_id.clone()));
+        q_params.push(text_param(":delivery", data.delivery.to_string()));
+        q_params.push(integer_param(":created_at", today));
+        q_params.push(integer_param(":updated_at", today));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(data)
+    }
+
+    /// Pending membership counts for every org admin, across all orgs
+    #[instrument(
+        level = "debug",
+        name = "db.notification.list_pending_members",
+        skip_all
+    )]
+    pub async fn list_pending_members(&self) -> Result<Vec<PendingMembersRowDto>> {
+        let query = format!(
+            "{} ORDER BY admins.user_id ASC, orgs.name ASC",
+            PENDING_MEMBERS_QUERY
+        );
+
+        let mut stmt = self.db_pool.prepare(&query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt
+            .query(new_query_params())
+            .await
+            .context(DbStatementSnafu)?;
+        let items: Vec<PendingMembersRowDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    /// Pending membership counts for the admins of a single org
+    #[instrument(
+        level = "debug",
+        name = "db.notification.list_org_pending_members",
+        skip_all
+    )]
+    pub async fn list_org_pending_members(
+        &self,
+        org_id: String,
+    ) -> Result<Vec<PendingMembersRowDto>> {
+        let query = format!("{} AND orgs.id = :org_id", PENDING_MEMBERS_QUERY);
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+
+        let mut stmt = self.db_pool.prepare(&query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<PendingMembersRowDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+}
@@ -1,13 +1,19 @@
 use snafu::ResultExt;
+use tracing::instrument;
 use turso::{Connection, Row};
 
 use crate::Result;
-use crate::db::turso_decode::{FromTursoRow, collect_row, collect_rows, row_integer, row_text};
-use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::db::turso_decode::{
+    FromTursoRow, collect_row, collect_rows, opt_row_text, row_integer, row_text,
+};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
 use crate::dto::{NewOauthCodeDto, OauthCodeDto};
 use crate::error::{DbPrepareSnafu, DbStatementSnafu};
 use crate::utils::{IdPrefix, generate_id};
 
+/// Authorization codes are exchanged right after the redirect
+const OAUTH_CODE_TTL_MINS: i64 = 10;
+
 impl FromTursoRow for OauthCodeDto {
     fn from_row(row: &Row) -> Result<Self> {
         Ok(Self {
@@ -21,6 +27,7 @@ impl FromTursoRow for OauthCodeDto {
             user_id: row_text(row, 7)?,
             created_at: row_integer(row, 8)?,
             expires_at: row_integer(row, 9)?,
+            nonce: opt_row_text(row, 10)?,
         })
     }
 }
@@ -34,6 +41,7 @@ impl OauthCodeRepo {
         Self { db_pool }
     }
 
+    #[instrument(level = "debug", name = "db.oauth_code.list_by_user", skip_all)]
     pub async fn list_by_user(&self, user_id: String) -> Result<Vec<OauthCodeDto>> {
         let query = r#"
             SELECT
@@ -46,7 +54,8 @@ impl OauthCodeRepo {
                 org_id,
                 user_id,
                 created_at,
-                expires_at
+                expires_at,
+                nonce
             FROM oauth_codes
             WHERE
                 user_id = :user_id
@@ -66,6 +75,7 @@ impl OauthCodeRepo {
         Ok(items)
     }
 
+    #[instrument(level = "debug", name = "db.oauth_code.create", skip_all)]
     pub async fn create(&self, data: NewOauthCodeDto) -> Result<OauthCodeDto> {
         let query = r#"
             INSERT INTO oauth_codes
@@ -79,7 +89,8 @@ impl OauthCodeRepo {
                 org_id,
                 user_id,
                 created_at,
-                expires_at
+                expires_at,
+                nonce
             )
             VALUES
             (
@@ -92,13 +103,15 @@ impl OauthCodeRepo {
                 :org_id,
                 :user_id,
                 :created_at,
-                :expires_at
+                :expires_at,
+                :nonce
             )
         "#;
 
         let id = generate_id(IdPrefix::OauthCode);
         let created_at = chrono::Utc::now().timestamp_millis();
-        let expires_at = created_at + chrono::Duration::days(7).num_milliseconds();
+        let expires_at =
+            created_at + chrono::Duration::minutes(OAUTH_CODE_TTL_MINS).num_milliseconds();
 
         let mut q_params = new_query_params();
         q_params.push(text_param(":id", id.clone()));
@@ -111,6 +124,7 @@ impl OauthCodeRepo {
         q_params.push(text_param(":user_id", data.user_id.clone()));
         q_params.push(integer_param(":created_at", created_at));
         q_params.push(integer_param(":expires_at", expires_at));
+        q_params.push(opt_text_param(":nonce", data.nonce.clone()));
 
         let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
         let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
@@ -127,9 +141,11 @@ impl OauthCodeRepo {
             user_id: data.user_id,
             created_at,
             expires_at,
+            nonce: data.nonce,
         })
     }
 
+    #[instrument(level = "debug", name = "db.oauth_code.get", skip_all)]
     pub async fn get(&self, id: String) -> Result<Option<OauthCodeDto>> {
         let query = r#"
             SELECT
@@ -142,7 +158,8 @@ impl OauthCodeRepo {
                 org_id,
                 user_id,
                 created_at,
-                expires_at
+                expires_at,
+                nonce
             FROM oauth_codes
             WHERE
                 id = :id
@@ -162,6 +179,7 @@ impl OauthCodeRepo {
         Ok(dto)
     }
 
+    #[instrument(level = "debug", name = "db.oauth_code.find_by_code", skip_all)]
     pub async fn find_by_code(&self, code: &str) -> Result<Option<OauthCodeDto>> {
         let query = r#"
             SELECT
@@ -174,7 +192,8 @@ impl OauthCodeRepo {
                 org_id,
                 user_id,
                 created_at,
-                expires_at
+                expires_at,
+                nonce
             FROM oauth_codes
             WHERE
                 code = :code
@@ -194,22 +213,29 @@ impl OauthCodeRepo {
         Ok(dto)
     }
 
-    pub async fn delete(&self, id: String) -> Result<()> {
+    /// Deletes the code, returns false when it was already used or expired
+    #[instrument(level = "debug", name = "db.oauth_code.consume", skip_all)]
+    pub async fn consume(&self, id: String) -> Result<bool> {
         let query = r#"
             DELETE FROM oauth_codes
             WHERE
                 id = :id
+                AND expires_at > :now
         "#;
 
+        let now = chrono::Utc::now().timestamp_millis();
+
         let mut q_params = new_query_params();
         q_params.push(text_param(":id", id));
+        q_params.push(integer_param(":now", now));
 
         let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
-        let _ = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
 
-        Ok(())
+        Ok(affected > 0)
     }
 
+    #[instrument(level = "debug", name = "db.oauth_code.delete_expired", skip_all)]
     pub async fn delete_expired(&self) -> Result<()> {
         let query = r#"
             DELETE FROM oauth_codes
@@ -227,4 +253,21 @@ impl OauthCodeRepo {
 
         Ok(())
     }
+
+    /// Drops the codes not exchanged yet for the org, returns how many
+    #[instrument(level = "debug", name = "db.oauth_code.delete_for_org", skip_all)]
+    pub async fn delete_for_org(&self, org_id: String) -> Result<u64> {
+        let query = r#"
+            DELETE FROM oauth_codes
+            WHERE
+                org_id = :org_id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(affected)
+    }
 }
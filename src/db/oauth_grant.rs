@@ -0,0 +1,270 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{FromTursoRow, collect_row, collect_rows, row_integer, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::dto::{AuthorizedAppDto, NewOauthGrantDto, OauthGrantDto};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for OauthGrantDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            user_id: row_text(row, 1)?,
+            org_id: row_text(row, 2)?,
+            app_id: row_text(row, 3)?,
+            scope: row_text(row, 4)?,
+            created_at: row_integer(row, 5)?,
+            last_used_at: row_integer(row, 6)?,
+        })
+    }
+}
+
+impl FromTursoRow for AuthorizedAppDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            app_id: row_text(row, 0)?,
+            app_name: row_text(row, 1)?,
+            org_id: row_text(row, 2)?,
+            org_name: row_text(row, 3)?,
+            scope: row_text(row, 4)?,
+            created_at: row_integer(row, 5)?,
+            last_used_at: row_integer(row, 6)?,
+        })
+    }
+}
+
+pub struct OauthGrantRepo {
+    db_pool: Connection,
+}
+
+impl OauthGrantRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(
+        level = "debug",
+        name = "db.oauth_grant.list_authorized_apps",
+        skip_all
+    )]
+    pub async fn list_authorized_apps(&self, user_id: String) -> Result<Vec<AuthorizedAppDto>> {
+        let query = r#"
+            SELECT
+                oauth_grants.app_id,
+                apps.name AS app_name,
+                oauth_grants.org_id,
+                orgs.name AS org_name,
+                oauth_grants.scope,
+                oauth_grants.created_at,
+                oauth_grants.last_used_at
+            FROM oauth_grants
+            INNER JOIN apps ON apps.id = oauth_grants.app_id
+            INNER JOIN orgs ON orgs.id = oauth_grants.org_id
+            WHERE
+                oauth_grants.user_id = :user_id
+                AND apps.deleted_at IS NULL
+            ORDER BY oauth_grants.last_used_at DESC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<AuthorizedAppDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.oauth_grant.get", skip_all)]
+    pub async fn get(&self, id: String) -> Result<Option<OauthGrantDto>> {
+        let query = r#"
+            SELECT
+                id,
+                user_id,
+                org_id,
+                app_id,
+                scope,
+                created_at,
+                last_used_at
+            FROM oauth_grants
+            WHERE
+                id = :id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<OauthGrantDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.oauth_grant.find_grant", skip_all)]
+    pub async fn find_grant(
+        &self,
+        user_id: String,
+        org_id: String,
+        app_id: String,
+    ) -> Result<Option<OauthGrantDto>> {
+        let query = r#"
+            SELECT
+                id,
+                user_id,
+                org_id,
+                app_id,
+                scope,
+                created_at,
+                last_used_at
+            FROM oauth_grants
+            WHERE
+                user_id = :user_id
+                AND org_id = :org_id
+                AND app_id = :app_id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(text_param(":org_id", org_id));
+        q_params.push(text_param(":app_id", app_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<OauthGrantDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Creates the grant or refreshes the scope and usage of an existing one
+    #[instrument(level = "debug", name = "db.oauth_grant.upsert", skip_all)]
+    pub async fn upsert(&self, data: NewOauthGrantDto) -> Result<OauthGrantDto> {
+        let now = chrono::Utc::now().timestamp_millis();
+
+        let existing = self
+            .find_grant(
+                data.user_id.clone(),
+                data.org_id.clone(),
+                data.app_id.clone(),
+            )
+            .await?;
+
+        if let Some(grant) = existing {
+            let query = r#"
+                UPDATE oauth_grants
+                SET
+                    scope = :scope,
+                    last_used_at = :last_used_at
+                WHERE
+                    id = :id
+            "#;
+
+            let mut q_params = new_query_params();
+            q_params.push(text_param(":scope", data.scope.clone()));
+            q_params.push(integer_param(":last_used_at", now));
+            q_params.push(text_param(":id", grant.id.clone()));
+
+            let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+            stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+            return Ok(OauthGrantDto {
+                scope: data.scope,
+                last_used_at: now,
+                ..grant
+            });
+        }
+
+        let query = r#"
+            INSERT INTO oauth_grants
+            (
+                id,
+                user_id,
+                org_id,
+                app_id,
+                scope,
+                created_at,
+                last_used_at
+            )
+            VALUES
+            (
+                :id,
+                :user_id,
+                :org_id,
+                :app_id,
+                :scope,
+                :created_at,
+                :last_used_at
+            )
+        "#;
+
+        let id = generate_id(IdPrefix::OauthGrant);
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id.clone()));
+        q_params.push(text_param(":user_id", data.user_id.clone()));
+        q_params.push(text_param(":org_id", data.org_id.clone()));
+        q_params.push(text_param(":app_id", data.app_id.clone()));
+        q_params.push(text_param(":scope", data.scope.clone()));
+        q_params.push(integer_param(":created_at", now));
+        q_params.push(integer_param(":last_used_at", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        assert!(affected > 0, "Must insert a new row");
+
+        Ok(OauthGrantDto {
+            id,
+            user_id: data.user_id,
+            org_id: data.org_id,
+            app_id: data.app_id,
+            scope: data.scope,
+            created_at: now,
+            last_used_at: now,
+        })
+    }
+
+    #[instrument(level = "debug", name = "db.oauth_grant.touch", skip_all)]
+    pub async fn touch(&self, id: String) -> Result<()> {
+        let query = r#"
+            UPDATE oauth_grants
+            SET
+                last_used_at = :last_used_at
+            WHERE
+                id = :id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(
+            ":last_used_at",
+            chrono::Utc::now().timestamp_millis(),
+        ));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(())
+    }
+
+    /// Removes all grants of the user for the app, across orgs
+    #[instrument(level = "debug", name = "db.oauth_grant.delete_by_app", skip_all)]
+    pub async fn delete_by_app(&self, user_id: String, app_id: String) -> Result<bool> {
+        let query = r#"
+            DELETE FROM oauth_grants
+            WHERE
+                user_id = :user_id
+                AND app_id = :app_id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(text_param(":app_id", app_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(affected > 0)
+    }
+}
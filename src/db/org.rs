@@ -1,19 +1,75 @@
 use snafu::ResultExt;
+use tracing::instrument;
 use turso::{Connection, Row};
 
 use crate::Result;
+use crate::ctx::AuditCtx;
+use crate::db::read_pool::ReadPool;
+use crate::db::soft_delete::{DeletedScope, SoftDelete};
 use crate::db::turso_decode::{
-    FromTursoRow, collect_count, collect_row, collect_rows, opt_row_text, row_integer, row_text,
+    FromTursoRow, collect_row, collect_rows, opt_row_integer, opt_row_text, row_integer, row_text,
 };
-use crate::db::turso_params::{integer_param, new_query_params, text_param};
-use crate::dto::{
-    ListOrgOwnerSuggestionsParamsDto, ListOrgsParamsDto, NewOrgDto, OrgDto, OrgOwnerSuggestionDto,
-    UpdateOrgDto,
-};
-use crate::dto::{Paginated, PaginationParams};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::db::versioned::{NEXT_VERSION_SQL, Versioned};
+use crate::dto::ORG_SORT;
+use crate::dto::{Cursor, CursorPage, Paginated, PaginationLimits, PaginationParams};
+use crate::dto::{ListOrgsParamsDto, NewOrgDto, OrgDto, UpdateOrgDto};
 use crate::error::{DbPrepareSnafu, DbStatementSnafu, DbTransactionSnafu};
 use crate::utils::{IdPrefix, generate_id};
 
+const OWNER_MEMBER_QUERY: &str = r#"
+    INSERT INTO org_members
+    (
+        id,
+        org_id,
+        user_id,
+        roles,
+        status,
+        created_at,
+        updated_at,
+        created_by,
+        updated_by
+    )
+    VALUES
+    (
+        :id,
+        :org_id,
+        :user_id,
+        :roles,
+        :status,
+        :created_at,
+        :updated_at,
+        :created_by,
+        :created_by
+    )
+"#;
+
+struct OwnerMembershipRow {
+    id: String,
+    roles: String,
+}
+
+struct OrgIdRow {
+    id: String,
+}
+
+impl FromTursoRow for OrgIdRow {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+        })
+    }
+}
+
+impl FromTursoRow for OwnerMembershipRow {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            roles: row_text(row, 1)?,
+        })
+    }
+}
+
 impl FromTursoRow for OrgDto {
     fn from_row(row: &Row) -> Result<Self> {
         Ok(Self {
@@ -25,38 +81,42 @@ impl FromTursoRow for OrgDto {
             owner_name: opt_row_text(row, 5)?,
             created_at: row_integer(row, 6)?,
             updated_at: row_integer(row, 7)?,
-        })
-    }
-}
-
-impl FromTursoRow for OrgOwnerSuggestionDto {
-    fn from_row(row: &Row) -> Result<Self> {
-        Ok(Self {
-            id: row_text(row, 0)?,
-            email: row_text(row, 1)?,
-            name: row_text(row, 2)?,
+            deleted_at: opt_row_integer(row, 8)?,
+            created_by: opt_row_text(row, 9)?,
+            updated_by: opt_row_text(row, 10)?,
+            member_count: row_integer(row, 11)?,
+            app_count: row_integer(row, 12)?,
+            pending_transfer: None,
         })
     }
 }
 
 pub struct OrgRepo {
     db_pool: Connection,
+    read_pool: ReadPool,
+    pagination: PaginationLimits,
 }
 
 impl OrgRepo {
-    pub fn new(db_pool: Connection) -> Self {
-        Self { db_pool }
+    pub fn new(db_pool: Connection, read_pool: ReadPool, pagination: PaginationLimits) -> Self {
+        Self {
+            db_pool,
+            read_pool,
+            pagination,
+        }
     }
 
-    async fn listing_count(&self, params: ListOrgsParamsDto) -> Result<i64> {
-        let mut query = r#"
+    async fn listing_count(&self, params: ListOrgsParamsDto, scope: DeletedScope) -> Result<i64> {
+        let mut query = format!(
+            r#"
             SELECT COUNT(*) AS total_count
             FROM orgs
             LEFT JOIN users ON users.id = orgs.owner_id
             WHERE
-                orgs.deleted_at IS NULL
-        "#
-        .to_string();
+                {}
+            "#,
+            scope.condition("orgs.deleted_at")
+        );
 
         let mut q_params = new_query_params();
 
@@ -68,13 +128,22 @@ impl OrgRepo {
             q_params.push(text_param(":keyword", pattern));
         }
 
-        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
-        let row_result = stmt.query_row(q_params).await;
-        collect_count(row_result)
+        self.read_pool.count(&query, q_params).await
     }
 
+    #[instrument(level = "debug", name = "db.org.list", skip_all)]
     pub async fn list(&self, params: ListOrgsParamsDto) -> Result<Paginated<OrgDto>> {
-        let mut query = r#"
+        self.list_scoped(params, DeletedScope::Active).await
+    }
+
+    #[instrument(level = "debug", name = "db.org.list_scoped", skip_all)]
+    pub async fn list_scoped(
+        &self,
+        params: ListOrgsParamsDto,
+        scope: DeletedScope,
+    ) -> Result<Paginated<OrgDto>> {
+        let mut query = format!(
+            r#"
             SELECT
                 orgs.id,
                 orgs.name,
@@ -83,13 +152,19 @@ impl OrgRepo {
                 users.email AS owner_email,
                 users.name AS owner_name,
                 orgs.created_at,
-                orgs.updated_at
+                orgs.updated_at,
+                orgs.deleted_at,
+                orgs.created_by,
+                orgs.updated_by,
+                orgs.member_count,
+                orgs.app_count
             FROM orgs
             LEFT JOIN users ON users.id = orgs.owner_id
             WHERE
-                orgs.deleted_at IS NULL
-        "#
-        .to_string();
+                {}
+            "#,
+            scope.condition("orgs.deleted_at")
+        );
 
         let mut q_params = new_query_params();
 
@@ -101,8 +176,13 @@ impl OrgRepo {
             q_params.push(text_param(":keyword", pattern));
         }
 
-        let total_records = self.listing_count(params.clone()).await?;
-        let pagination = PaginationParams::new(total_records, params.page, params.per_page, None);
+        let total_records = self.listing_count(params.clone(), scope).await?;
+        let pagination = PaginationParams::new(
+            total_records,
+            params.page,
+            params.per_page,
+            &self.pagination,
+        );
 
         if pagination.total_pages == 0 {
             return Ok(Paginated::new(
@@ -113,13 +193,12 @@ impl OrgRepo {
             ));
         }
 
-        query.push_str(" ORDER BY orgs.name ASC LIMIT :limit OFFSET :offset");
+        query.push_str(&ORG_SORT.order_by(params.sort_by.as_deref(), params.sort_dir.as_deref()));
+        query.push_str(" LIMIT :limit OFFSET :offset");
         q_params.push(integer_param(":limit", pagination.per_page as i64));
         q_params.push(integer_param(":offset", pagination.offset));
 
-        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
-        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
-        let items: Vec<OrgDto> = collect_rows(&mut rows).await?;
+        let items: Vec<OrgDto> = self.read_pool.rows(&query, q_params).await?;
 
         Ok(Paginated::new(
             items,
@@ -129,103 +208,72 @@ impl OrgRepo {
         ))
     }
 
-    async fn list_owner_suggestions_count(
-        &self,
-        params: ListOrgOwnerSuggestionsParamsDto,
-    ) -> Result<i64> {
-        let mut query = r#"
-            SELECT COUNT(*) AS total_count
-            FROM users
-            LEFT JOIN superusers ON superusers.id = users.id
-            WHERE
-                superusers.id IS NULL
-                AND users.deleted_at IS NULL
-        "#
-        .to_string();
-
-        let mut q_params = new_query_params();
-
-        if let Some(keyword) = params.keyword
-            && !keyword.is_empty()
-        {
-            query.push_str(" AND (users.name LIKE :keyword OR users.email LIKE :keyword)");
-            let pattern = format!("%{}%", keyword);
-            q_params.push(text_param(":keyword", pattern));
-        }
-
-        if let Some(exclude_user_id) = params.exclude_id {
-            query.push_str(" AND users.id <> :exclude_id");
-            q_params.push(text_param(":exclude_id", exclude_user_id.to_string()));
-        }
-
-        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
-        let row_result = stmt.query_row(q_params).await;
-        collect_count(row_result)
-    }
-
-    pub async fn list_owner_suggestions(
+    /// Keyset listing ordered by name then id, nothing is counted
+    #[instrument(level = "debug", name = "db.org.list_cursor", skip_all)]
+    pub async fn list_cursor(
         &self,
-        params: ListOrgOwnerSuggestionsParamsDto,
-    ) -> Result<Paginated<OrgOwnerSuggestionDto>> {
-        let mut query = r#"
+        params: ListOrgsParamsDto,
+        scope: DeletedScope,
+    ) -> Result<CursorPage<OrgDto>> {
+        let mut query = format!(
+            r#"
             SELECT
-                users.id,
-                users.email,
-                users.name
-            FROM users
-            LEFT JOIN superusers ON superusers.id = users.id
+                orgs.id,
+                orgs.name,
+                orgs.status,
+                orgs.owner_id,
+                users.email AS owner_email,
+                users.name AS owner_name,
+                orgs.created_at,
+                orgs.updated_at,
+                orgs.deleted_at,
+                orgs.created_by,
+                orgs.updated_by,
+                orgs.member_count,
+                orgs.app_count
+            FROM orgs
+            LEFT JOIN users ON users.id = orgs.owner_id
             WHERE
-                superusers.id IS NULL
-                AND users.deleted_at IS NULL
-        "#
-        .to_string();
+                {}
+            "#,
+            scope.condition("orgs.deleted_at")
+        );
 
         let mut q_params = new_query_params();
 
-        if let Some(keyword) = params.keyword.clone()
+        if let Some(keyword) = params.keyword
             && !keyword.is_empty()
         {
-            query.push_str(" AND (users.name LIKE :keyword OR users.email LIKE :keyword)");
+            query.push_str(" AND (orgs.name LIKE :keyword OR users.email LIKE :keyword)");
             let pattern = format!("%{}%", keyword);
             q_params.push(text_param(":keyword", pattern));
         }
 
-        if let Some(exclude_user_id) = params.exclude_id.as_ref() {
-            query.push_str(" AND users.id <> :exclude_id");
-            q_params.push(text_param(":exclude_id", exclude_user_id.to_string()));
-        }
-
-        let total_records = self.list_owner_suggestions_count(params.clone()).await?;
-        let pagination = PaginationParams::new(total_records, params.page, params.per_page, None);
-
-        if pagination.total_pages == 0 {
-            return Ok(Paginated::new(
-                Vec::new(),
-                pagination.page,
-                pagination.per_page,
-                pagination.total_records,
-            ));
+        if let Some(cursor) = params.cursor.as_deref() {
+            let cursor = Cursor::decode(cursor)?;
+            query.push_str(" AND (orgs.name > :key OR (orgs.name = :key AND orgs.id > :id))");
+            q_params.push(text_param(":key", cursor.key));
+            q_params.push(text_param(":id", cursor.id));
         }
 
-        query.push_str(" ORDER BY users.email ASC LIMIT :limit OFFSET :offset");
-        q_params.push(integer_param(":limit", pagination.per_page as i64));
-        q_params.push(integer_param(":offset", pagination.offset));
+        let limit = self.pagination.cursor_limit(params.limit);
+        query.push_str(" ORDER BY orgs.name ASC, orgs.id ASC LIMIT :limit");
+        q_params.push(integer_param(":limit", limit as i64 + 1));
 
-        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
-        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
-        let items: Vec<OrgOwnerSuggestionDto> = collect_rows(&mut rows).await?;
+        let items: Vec<OrgDto> = self.read_pool.rows(&query, q_params).await?;
 
-        Ok(Paginated::new(
-            items,
-            pagination.page,
-            pagination.per_page,
-            pagination.total_records,
-        ))
+        Ok(CursorPage::new(items, limit, |org| {
+            Cursor::new(&org.name, &org.id)
+        }))
     }
 
-    pub async fn create(&self, data: NewOrgDto) -> Result<OrgDto> {
+    /// Inserts the org counting its owner as the first member.
+    ///
+    /// The owner membership is not inserted here, callers create it in the
+    /// same unit of work.
+    #[instrument(level = "debug", name = "db.org.create", skip_all)]
+    pub async fn create(&self, audit: &AuditCtx, data: NewOrgDto) -> Result<OrgDto> {
         let org_id = generate_id(IdPrefix::Org);
-        let member_id = generate_id(IdPrefix::OrgMember);
         let today = chrono::Utc::now().timestamp_millis();
 
         let org_query = r#"
@@ -237,7 +285,10 @@ impl OrgRepo {
                 owner_id,
                 created_at,
                 updated_at,
-                deleted_at
+                deleted_at,
+                created_by,
+                updated_by,
+                member_count
             )
             VALUES
             (
@@ -247,7 +298,10 @@ impl OrgRepo {
                 :owner_id,
                 :created_at,
                 :updated_at,
-                NULL
+                NULL,
+                :created_by,
+                :created_by,
+                1
             )
         "#;
 
@@ -258,58 +312,19 @@ impl OrgRepo {
         org_params.push(text_param(":owner_id", data.owner_id.clone()));
         org_params.push(integer_param(":created_at", today));
         org_params.push(integer_param(":updated_at", today));
+        org_params.push(opt_text_param(":created_by", audit.actor_id.clone()));
 
-        let member_query = r#"
-            INSERT INTO org_members
-            (
-                id,
-                org_id,
-                user_id,
-                roles,
-                status,
-                created_at,
-                updated_at
-            )
-            VALUES
-            (
-                :id,
-                :org_id,
-                :user_id,
-                :roles,
-                :status,
-                :created_at,
-                :updated_at
-            )
-        "#;
-
-        let mut member_params = new_query_params();
-        member_params.push(text_param(":id", member_id));
-        member_params.push(text_param(":org_id", org_id.clone()));
-        member_params.push(text_param(":user_id", data.owner_id.clone()));
-        member_params.push(text_param(":roles", "OrgAdmin".to_string()));
-        member_params.push(text_param(":status", "active".to_string()));
-        member_params.push(integer_param(":created_at", today));
-        member_params.push(integer_param(":updated_at", today));
-
-        let mut conn = self.db_pool.clone();
-        let tx = conn.transaction().await.context(DbTransactionSnafu)?;
-
-        let mut org_stmt = tx.prepare(org_query).await.context(DbPrepareSnafu)?;
+        let mut org_stmt = self
+            .db_pool
+            .prepare(org_query)
+            .await
+            .context(DbPrepareSnafu)?;
         let org_affected = org_stmt
             .execute(org_params)
             .await
             .context(DbStatementSnafu)?;
         assert!(org_affected > 0, "Must insert a new org row");
 
-        let mut member_stmt = tx.prepare(member_query).await.context(DbPrepareSnafu)?;
-        let member_affected = member_stmt
-            .execute(member_params)
-            .await
-            .context(DbStatementSnafu)?;
-        assert!(member_affected > 0, "Must insert a new org member row");
-
-        tx.commit().await.context(DbTransactionSnafu)?;
-
         Ok(OrgDto {
             id: org_id,
             name: data.name,
@@ -319,11 +334,69 @@ impl OrgRepo {
             owner_name: None,
             created_at: today,
             updated_at: today,
+            deleted_at: None,
+            created_by: audit.actor_id.clone(),
+            updated_by: audit.actor_id.clone(),
+            // The owner is the first member
+            member_count: 1,
+            app_count: 0,
+            pending_transfer: None,
         })
     }
 
+    #[instrument(level = "debug", name = "db.org.get", skip_all)]
     pub async fn get(&self, id: String) -> Result<Option<OrgDto>> {
-        let query = r#"
+        self.get_scoped(id, DeletedScope::Active).await
+    }
+
+    /// Active orgs with the given ids, in no particular order
+    #[instrument(level = "debug", name = "db.org.list_by_ids", skip_all)]
+    pub async fn list_by_ids(&self, ids: &[String]) -> Result<Vec<OrgDto>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let placeholders: Vec<String> = (0..ids.len()).map(|i| format!(":id_{}", i)).collect();
+        let query = format!(
+            r#"
+            SELECT
+                orgs.id,
+                orgs.name,
+                orgs.status,
+                orgs.owner_id,
+                users.email AS owner_email,
+                users.name AS owner_name,
+                orgs.created_at,
+                orgs.updated_at,
+                orgs.deleted_at,
+                orgs.created_by,
+                orgs.updated_by,
+                orgs.member_count,
+                orgs.app_count
+            FROM orgs
+            LEFT JOIN users ON users.id = orgs.owner_id
+            WHERE
+                orgs.deleted_at IS NULL
+                AND orgs.id IN ({})
+            "#,
+            placeholders.join(", ")
+        );
+
+        let mut q_params = new_query_params();
+        for (placeholder, id) in placeholders.iter().zip(ids.iter()) {
+            q_params.push(text_param(placeholder, id.clone()));
+        }
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<OrgDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.org.get_scoped", skip_all)]
+    pub async fn get_scoped(&self, id: String, scope: DeletedScope) -> Result<Option<OrgDto>> {
+        let query = format!(
+            r#"
             SELECT
                 orgs.id,
                 orgs.name,
@@ -332,14 +405,21 @@ impl OrgRepo {
                 users.email AS owner_email,
                 users.name AS owner_name,
                 orgs.created_at,
-                orgs.updated_at
+                orgs.updated_at,
+                orgs.deleted_at,
+                orgs.created_by,
+                orgs.updated_by,
+                orgs.member_count,
+                orgs.app_count
             FROM orgs
             LEFT JOIN users ON users.id = orgs.owner_id
             WHERE
                 orgs.id = :id
-                AND orgs.deleted_at IS NULL
+                AND {}
             LIMIT 1
-        "#;
+            "#,
+            scope.condition("orgs.deleted_at")
+        );
 
         let mut q_params = new_query_params();
         q_params.push(text_param(":id", id));
@@ -350,7 +430,8 @@ impl OrgRepo {
         Ok(dto)
     }
 
-    pub async fn update(&self, id: String, data: UpdateOrgDto) -> Result<bool> {
+    #[instrument(level = "debug", name = "db.org.update", skip_all)]
+    pub async fn update(&self, audit: &AuditCtx, id: String, data: UpdateOrgDto) -> Result<bool> {
         if data.status.is_none() && data.name.is_none() && data.owner_id.is_none() {
             return Ok(false);
         }
@@ -375,8 +456,10 @@ impl OrgRepo {
         }
 
         let updated_at = chrono::Utc::now().timestamp_millis();
-        set_parts.push("updated_at = :updated_at");
+        set_parts.push(NEXT_VERSION_SQL);
         q_params.push(integer_param(":updated_at", updated_at));
+        set_parts.push("updated_by = :updated_by");
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
 
         query.push_str(&set_parts.join(", "));
         query.push_str(" WHERE id = :id AND deleted_at IS NULL");
@@ -388,28 +471,188 @@ impl OrgRepo {
         Ok(affected > 0)
     }
 
-    pub async fn delete(&self, id: String) -> Result<bool> {
-        let query = r#"
+    /// Transfers ownership and ensures the new owner is an active OrgAdmin member
+    #[instrument(level = "debug", name = "db.org.update_owner", skip_all)]
+    pub async fn update_owner(
+        &self,
+        audit: &AuditCtx,
+        id: String,
+        owner_id: String,
+    ) -> Result<bool> {
+        let today = chrono::Utc::now().timestamp_millis();
+
+        let org_query = r#"
             UPDATE orgs
             SET
-                deleted_at = :deleted_at
+                owner_id = :owner_id,
+                updated_at = :updated_at,
+                updated_by = :updated_by
             WHERE
                 id = :id
                 AND deleted_at IS NULL
         "#;
 
-        let deleted_at = chrono::Utc::now().timestamp_millis();
+        let mut org_params = new_query_params();
+        org_params.push(text_param(":owner_id", owner_id.clone()));
+        org_params.push(integer_param(":updated_at", today));
+        org_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
+        org_params.push(text_param(":id", id.clone()));
+
+        let find_query = r#"
+            SELECT
+                id,
+                roles
+            FROM org_members
+            WHERE
+                org_id = :org_id
+                AND user_id = :user_id
+            LIMIT 1
+        "#;
+
+        let mut find_params = new_query_params();
+        find_params.push(text_param(":org_id", id.clone()));
+        find_params.push(text_param(":user_id", owner_id.clone()));
+
+        let mut conn = self.db_pool.clone();
+        let tx = conn.transaction().await.context(DbTransactionSnafu)?;
+
+        let mut org_stmt = tx.prepare(org_query).await.context(DbPrepareSnafu)?;
+        let org_affected = org_stmt
+            .execute(org_params)
+            .await
+            .context(DbStatementSnafu)?;
+
+        if org_affected == 0 {
+            tx.rollback().await.context(DbTransactionSnafu)?;
+            return Ok(false);
+        }
+
+        let mut find_stmt = tx.prepare(find_query).await.context(DbPrepareSnafu)?;
+        let row_result = find_stmt.query_row(find_params).await;
+        let membership: Option<OwnerMembershipRow> = collect_row(row_result)?;
+
+        match membership {
+            Some(membership) => {
+                // Keep existing roles but make sure the owner can administer the org
+                let mut roles: Vec<&str> = membership
+                    .roles
+                    .split(',')
+                    .filter(|r| !r.is_empty())
+                    .collect();
+                if !roles.contains(&"OrgAdmin") {
+                    roles.push("OrgAdmin");
+                }
+
+                let member_query = r#"
+                    UPDATE org_members
+                    SET
+                        roles = :roles,
+                        status = :status,
+                        updated_at = :updated_at,
+                        updated_by = :updated_by
+                    WHERE
+                        id = :id
+                "#;
+
+                let mut member_params = new_query_params();
+                member_params.push(text_param(":roles", roles.join(",")));
+                member_params.push(text_param(":status", "active".to_string()));
+                member_params.push(integer_param(":updated_at", today));
+                member_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
+                member_params.push(text_param(":id", membership.id));
+
+                let mut member_stmt = tx.prepare(member_query).await.context(DbPrepareSnafu)?;
+                member_stmt
+                    .execute(member_params)
+                    .await
+                    .context(DbStatementSnafu)?;
+            }
+            None => {
+                let mut member_params = new_query_params();
+                member_params.push(text_param(":id", generate_id(IdPrefix::OrgMember)));
+                member_params.push(text_param(":org_id", id));
+                member_params.push(text_param(":user_id", owner_id));
+                member_params.push(text_param(":roles", "OrgAdmin".to_string()));
+                member_params.push(text_param(":status", "active".to_string()));
+                member_params.push(integer_param(":created_at", today));
+                member_params.push(integer_param(":updated_at", today));
+                member_params.push(opt_text_param(":created_by", audit.actor_id.clone()));
+
+                let mut member_stmt = tx
+                    .prepare(OWNER_MEMBER_QUERY)
+                    .await
+                    .context(DbPrepareSnafu)?;
+                let member_affected = member_stmt
+                    .execute(member_params)
+                    .await
+                    .context(DbStatementSnafu)?;
+                assert!(member_affected > 0, "Must insert a new org member row");
+            }
+        }
+
+        tx.commit().await.context(DbTransactionSnafu)?;
+
+        Ok(true)
+    }
+
+    #[instrument(level = "debug", name = "db.org.delete", skip_all)]
+    pub async fn delete(&self, audit: &AuditCtx, id: String) -> Result<bool> {
+        self.soft_delete(audit, id).await
+    }
+
+    /// Ids of the active orgs owned by the user
+    #[instrument(level = "debug", name = "db.org.list_owned_ids", skip_all)]
+    pub async fn list_owned_ids(&self, owner_id: String) -> Result<Vec<String>> {
+        let query = r#"
+            SELECT id
+            FROM orgs
+            WHERE
+                owner_id = :owner_id
+                AND deleted_at IS NULL
+            ORDER BY created_at ASC, id ASC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":owner_id", owner_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<OrgIdRow> = collect_rows(&mut rows).await?;
+        Ok(items.into_iter().map(|row| row.id).collect())
+    }
+
+    /// Hands the org to a user who already administers it.
+    ///
+    /// Unlike `update_owner` the membership is left alone, so this can join a
+    /// unit of work.
+    #[instrument(level = "debug", name = "db.org.set_owner", skip_all)]
+    pub async fn set_owner(&self, audit: &AuditCtx, id: String, owner_id: String) -> Result<bool> {
+        let query = r#"
+            UPDATE orgs
+            SET
+                owner_id = :owner_id,
+                updated_at = :updated_at,
+                updated_by = :updated_by
+            WHERE
+                id = :id
+                AND deleted_at IS NULL
+        "#;
 
         let mut q_params = new_query_params();
-        q_params.push(integer_param(":deleted_at", deleted_at));
+        q_params.push(text_param(":owner_id", owner_id));
+        q_params.push(integer_param(
+            ":updated_at",
+            chrono::Utc::now().timestamp_millis(),
+        ));
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
         q_params.push(text_param(":id", id));
 
         let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
         let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
-
         Ok(affected > 0)
     }
 
+    #[instrument(level = "debug", name = "db.org.test_read", skip_all)]
     pub async fn test_read(&self) -> Result<()> {
         let query = r#"
             SELECT
@@ -420,7 +663,12 @@ impl OrgRepo {
                 users.email AS owner_email,
                 users.name AS owner_name,
                 orgs.created_at,
-                orgs.updated_at
+                orgs.updated_at,
+                orgs.deleted_at,
+                orgs.created_by,
+                orgs.updated_by,
+                orgs.member_count,
+                orgs.app_count
             FROM orgs
             LEFT JOIN users ON users.id = orgs.owner_id
             WHERE
@@ -435,3 +683,13 @@ impl OrgRepo {
         Ok(())
     }
 }
+
+impl SoftDelete for OrgRepo {
+    const TABLE: &'static str = "orgs";
+
+    fn connection(&self) -> &Connection {
+        &self.db_pool
+    }
+}
+
+impl Versioned for OrgRepo {}
@@ -0,0 +1,159 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{FromTursoRow, collect_rows, opt_row_text, row_integer, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::dto::OrgAccessDto;
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+
+impl FromTursoRow for OrgAccessDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            org_id: row_text(row, 0)?,
+            user_id: row_text(row, 1)?,
+            day: row_text(row, 2)?,
+            email: opt_row_text(row, 3)?,
+            name: opt_row_text(row, 4)?,
+            first_seen_at: row_integer(row, 5)?,
+            last_seen_at: row_integer(row, 6)?,
+            samples: row_integer(row, 7)?,
+        })
+    }
+}
+
+pub struct OrgAccessRepo {
+    db_pool: Connection,
+}
+
+impl OrgAccessRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    /// Records a sampled access, one row per org, user and day
+    #[instrument(level = "debug", name = "db.org_access.touch", skip_all)]
+    pub async fn touch(
+        &self,
+        org_id: String,
+        user_id: String,
+        day: String,
+        now: i64,
+    ) -> Result<()> {
+        let query = r#"
+            INSERT INTO org_access_log
+            (
+                org_id,
+                user_id,
+                day,
+                first_seen_at,
+                last_seen_at,
+                samples
+            )
+            VALUES
+            (
+                :org_id,
+                :user_id,
+                :day,
+                :now,
+                :now,
+                1
+            )
+            ON CONFLICT (org_id, user_id, day) DO UPDATE SET
+                last_seen_at = excluded.last_seen_at,
+                samples = org_access_log.samples + 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(text_param(":day", day));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(())
+    }
+
+    /// Access rows of an org between two days, inclusive.
+    ///
+    /// Users are looked up for convenience but rows of removed users are kept.
+    #[instrument(level = "debug", name = "db.org_access.list_range", skip_all)]
+    pub async fn list_range(
+        &self,
+        org_id: String,
+        from_day: String,
+        to_day: String,
+    ) -> Result<Vec<OrgAccessDto>> {
+        let query = r#"
+            SELECT
+                org_access_log.org_id,
+                org_access_log.user_id,
+                org_access_log.day,
+                users.email,
+                users.name,
+                org_access_log.first_seen_at,
+                org_access_log.last_seen_at,
+                org_access_log.samples
+            FROM org_access_log
+            LEFT JOIN users ON users.id = org_access_log.user_id
+            WHERE
+                org_access_log.org_id = :org_id
+                AND org_access_log.day >= :from_day
+                AND org_access_log.day <= :to_day
+            ORDER BY org_access_log.day ASC, org_access_log.first_seen_at ASC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+        q_params.push(text_param(":from_day", from_day));
+        q_params.push(text_param(":to_day", to_day));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        collect_rows(&mut rows).await
+    }
+
+    /// Access rows of a user across every org, oldest first
+    #[instrument(level = "debug", name = "db.org_access.list_by_user", skip_all)]
+    pub async fn list_by_user(&self, user_id: String) -> Result<Vec<OrgAccessDto>> {
+        let query = r#"
+            SELECT
+                org_access_log.org_id,
+                org_access_log.user_id,
+                org_access_log.day,
+                users.email,
+                users.name,
+                org_access_log.first_seen_at,
+                org_access_log.last_seen_at,
+                org_access_log.samples
+            FROM org_access_log
+            LEFT JOIN users ON users.id = org_access_log.user_id
+            WHERE org_access_log.user_id = :user_id
+            ORDER BY org_access_log.day ASC, org_access_log.first_seen_at ASC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        collect_rows(&mut rows).await
+    }
+
+    /// Removes rows older than the given day, returns how many were removed
+    #[instrument(level = "debug", name = "db.org_access.delete_before", skip_all)]
+    pub async fn delete_before(&self, day: String) -> Result<u64> {
+        let query = "DELETE FROM org_access_log WHERE day < :day";
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":day", day));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected)
+    }
+}
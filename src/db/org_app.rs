@@ -1,13 +1,17 @@
 use snafu::ResultExt;
+use tracing::instrument;
 use turso::{Connection, Row};
 
 use crate::Result;
+use crate::ctx::AuditCtx;
+use crate::db::read_pool::ReadPool;
+use crate::db::soft_delete::{DeletedScope, SoftDelete};
 use crate::db::turso_decode::{
     FromTursoRow, collect_count, collect_row, collect_rows, opt_row_text, row_integer, row_text,
 };
-use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
 use crate::dto::{ListOrgAppsParamsDto, NewOrgAppDto, OrgAppDto, OrgAppSuggestionDto};
-use crate::dto::{Paginated, PaginationParams};
+use crate::dto::{ORG_APP_SORT, Paginated, PaginationLimits, PaginationParams};
 use crate::error::{DbPrepareSnafu, DbStatementSnafu};
 use crate::utils::{IdPrefix, generate_id};
 
@@ -19,6 +23,8 @@ impl FromTursoRow for OrgAppDto {
             app_id: row_text(row, 2)?,
             app_name: opt_row_text(row, 3)?,
             created_at: row_integer(row, 4)?,
+            scopes: opt_row_text(row, 5)?
+                .map(|scopes| scopes.split(' ').map(|s| s.to_string()).collect()),
         })
     }
 }
@@ -34,13 +40,20 @@ impl FromTursoRow for OrgAppSuggestionDto {
 
 pub struct OrgAppRepo {
     db_pool: Connection,
+    read_pool: ReadPool,
+    pagination: PaginationLimits,
 }
 
 impl OrgAppRepo {
-    pub fn new(db_pool: Connection) -> Self {
-        Self { db_pool }
+    pub fn new(db_pool: Connection, read_pool: ReadPool, pagination: PaginationLimits) -> Self {
+        Self {
+            db_pool,
+            read_pool,
+            pagination,
+        }
     }
 
+    #[instrument(level = "debug", name = "db.org_app.listing_count", skip_all)]
     pub async fn listing_count(&self, org_id: String, params: ListOrgAppsParamsDto) -> Result<i64> {
         let mut query = r#"
             SELECT COUNT(*) AS total_count
@@ -48,6 +61,7 @@ impl OrgAppRepo {
             LEFT JOIN apps ON apps.id = org_apps.app_id
             WHERE
                 org_apps.org_id = :org_id
+                AND org_apps.deleted_at IS NULL
                 AND apps.deleted_at IS NULL
         "#
         .to_string();
@@ -63,11 +77,10 @@ impl OrgAppRepo {
             q_params.push(text_param(":keyword", pattern));
         }
 
-        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
-        let row_result = stmt.query_row(q_params).await;
-        collect_count(row_result)
+        self.read_pool.count(&query, q_params).await
     }
 
+    #[instrument(level = "debug", name = "db.org_app.list", skip_all)]
     pub async fn list(
         &self,
         org_id: String,
@@ -79,11 +92,13 @@ impl OrgAppRepo {
                 org_apps.org_id,
                 org_apps.app_id,
                 apps.name,
-                org_apps.created_at
+                org_apps.created_at,
+                org_apps.scopes
             FROM org_apps
             LEFT JOIN apps ON apps.id = org_apps.app_id
             WHERE
                 org_apps.org_id = :org_id
+                AND org_apps.deleted_at IS NULL
                 AND apps.deleted_at IS NULL
         "#
         .to_string();
@@ -100,7 +115,12 @@ impl OrgAppRepo {
         }
 
         let total_records = self.listing_count(org_id, params.clone()).await?;
-        let pagination = PaginationParams::new(total_records, params.page, params.per_page, None);
+        let pagination = PaginationParams::new(
+            total_records,
+            params.page,
+            params.per_page,
+            &self.pagination,
+        );
 
         if pagination.total_pages == 0 {
             return Ok(Paginated::new(
@@ -111,13 +131,14 @@ impl OrgAppRepo {
             ));
         }
 
-        query.push_str(" ORDER BY apps.name ASC LIMIT :limit OFFSET :offset");
+        query.push_str(
+            &ORG_APP_SORT.order_by(params.sort_by.as_deref(), params.sort_dir.as_deref()),
+        );
+        query.push_str(" LIMIT :limit OFFSET :offset");
         q_params.push(integer_param(":limit", pagination.per_page as i64));
         q_params.push(integer_param(":offset", pagination.offset));
 
-        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
-        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
-        let items: Vec<OrgAppDto> = collect_rows(&mut rows).await?;
+        let items: Vec<OrgAppDto> = self.read_pool.rows(&query, q_params).await?;
 
         Ok(Paginated::new(
             items,
@@ -138,6 +159,7 @@ impl OrgAppRepo {
             LEFT JOIN org_apps
                 ON org_apps.app_id = apps.id
                 AND org_apps.org_id = :org_id
+                AND org_apps.deleted_at IS NULL
             WHERE
                 org_apps.app_id IS NULL
                 AND apps.deleted_at IS NULL
@@ -160,6 +182,7 @@ impl OrgAppRepo {
         collect_count(row_result)
     }
 
+    #[instrument(level = "debug", name = "db.org_app.list_app_suggestions", skip_all)]
     pub async fn list_app_suggestions(
         &self,
         org_id: String,
@@ -173,6 +196,7 @@ impl OrgAppRepo {
             LEFT JOIN org_apps
                 ON org_apps.app_id = apps.id
                 AND org_apps.org_id = :org_id
+                AND org_apps.deleted_at IS NULL
             WHERE
                 org_apps.app_id IS NULL
                 AND apps.deleted_at IS NULL
@@ -194,7 +218,12 @@ impl OrgAppRepo {
             .list_app_suggestions_count(org_id, params.clone())
             .await?;
 
-        let pagination = PaginationParams::new(total_records, params.page, params.per_page, None);
+        let pagination = PaginationParams::new(
+            total_records,
+            params.page,
+            params.per_page,
+            &self.pagination,
+        );
 
         if pagination.total_pages == 0 {
             return Ok(Paginated::new(
@@ -221,21 +250,47 @@ impl OrgAppRepo {
         ))
     }
 
-    pub async fn create(&self, org_id: String, data: NewOrgAppDto) -> Result<OrgAppDto> {
+    /// Links the app, restoring a previously removed link when there is one
+    #[instrument(level = "debug", name = "db.org_app.create", skip_all)]
+    pub async fn create(
+        &self,
+        audit: &AuditCtx,
+        org_id: String,
+        data: NewOrgAppDto,
+    ) -> Result<OrgAppDto> {
+        if let Some(link) = self
+            .find_app_scoped(
+                org_id.clone(),
+                data.app_id.clone(),
+                DeletedScope::OnlyDeleted,
+            )
+            .await?
+            && self.restore(audit, link.id.clone()).await?
+        {
+            return Ok(OrgAppDto {
+                app_name: None,
+                ..link
+            });
+        }
+
         let query = r#"
             INSERT INTO org_apps
             (
                 id,
                 org_id,
                 app_id,
-                created_at
+                created_at,
+                created_by,
+                updated_by
             )
             VALUES
             (
                 :id,
                 :org_id,
                 :app_id,
-                :created_at
+                :created_at,
+                :created_by,
+                :created_by
             )
         "#;
 
@@ -247,6 +302,7 @@ impl OrgAppRepo {
         q_params.push(text_param(":org_id", org_id.clone()));
         q_params.push(text_param(":app_id", data.app_id.clone()));
         q_params.push(integer_param(":created_at", created_at));
+        q_params.push(opt_text_param(":created_by", audit.actor_id.clone()));
 
         let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
         let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
@@ -257,10 +313,40 @@ impl OrgAppRepo {
             org_id,
             app_id: data.app_id,
             app_name: None,
+            scopes: None,
             created_at,
         })
     }
 
+    /// Narrows the scopes the link grants, `None` lifts the restriction
+    #[instrument(level = "debug", name = "db.org_app.set_scopes", skip_all)]
+    pub async fn set_scopes(
+        &self,
+        audit: &AuditCtx,
+        id: String,
+        scopes: Option<Vec<String>>,
+    ) -> Result<bool> {
+        let query = r#"
+            UPDATE org_apps
+            SET
+                scopes = :scopes,
+                updated_by = :updated_by
+            WHERE
+                id = :id
+                AND deleted_at IS NULL
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(opt_text_param(":scopes", scopes.map(|s| s.join(" "))));
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(affected > 0)
+    }
+
+    #[instrument(level = "debug", name = "db.org_app.get", skip_all)]
     pub async fn get(&self, id: String) -> Result<Option<OrgAppDto>> {
         let query = r#"
             SELECT
@@ -268,11 +354,13 @@ impl OrgAppRepo {
                 org_apps.org_id,
                 org_apps.app_id,
                 apps.name,
-                org_apps.created_at
+                org_apps.created_at,
+                org_apps.scopes
             FROM org_apps
             LEFT JOIN apps ON apps.id = org_apps.app_id
             WHERE
                 org_apps.id = :id
+                AND org_apps.deleted_at IS NULL
                 AND apps.deleted_at IS NULL
             LIMIT 1
         "#;
@@ -286,22 +374,39 @@ impl OrgAppRepo {
         Ok(dto)
     }
 
+    #[instrument(level = "debug", name = "db.org_app.find_app", skip_all)]
     pub async fn find_app(&self, org_id: String, app_id: String) -> Result<Option<OrgAppDto>> {
-        let query = r#"
+        self.find_app_scoped(org_id, app_id, DeletedScope::Active)
+            .await
+    }
+
+    #[instrument(level = "debug", name = "db.org_app.find_app_scoped", skip_all)]
+    pub async fn find_app_scoped(
+        &self,
+        org_id: String,
+        app_id: String,
+        scope: DeletedScope,
+    ) -> Result<Option<OrgAppDto>> {
+        let query = format!(
+            r#"
             SELECT
                 org_apps.id,
                 org_apps.org_id,
                 org_apps.app_id,
                 apps.name,
-                org_apps.created_at
+                org_apps.created_at,
+                org_apps.scopes
             FROM org_apps
             LEFT JOIN apps ON apps.id = org_apps.app_id
             WHERE
                 org_apps.org_id = :org_id
                 AND org_apps.app_id = :app_id
+                AND {}
                 AND apps.deleted_at IS NULL
             LIMIT 1
-        "#;
+            "#,
+            scope.condition("org_apps.deleted_at")
+        );
 
         let mut q_params = new_query_params();
         q_params.push(text_param(":org_id", org_id));
@@ -313,19 +418,43 @@ impl OrgAppRepo {
         Ok(dto)
     }
 
-    pub async fn delete(&self, id: String) -> Result<()> {
+    #[instrument(level = "debug", name = "db.org_app.delete", skip_all)]
+    pub async fn delete(&self, audit: &AuditCtx, id: String) -> Result<()> {
+        let _ = self.soft_delete(audit, id).await?;
+        Ok(())
+    }
+
+    /// Soft deletes every app link of the org, returns how many
+    #[instrument(level = "debug", name = "db.org_app.delete_for_org", skip_all)]
+    pub async fn delete_for_org(&self, audit: &AuditCtx, org_id: String) -> Result<u64> {
         let query = r#"
-            DELETE FROM org_apps
+            UPDATE org_apps
+            SET
+                deleted_at = :deleted_at,
+                updated_by = :updated_by
             WHERE
-                id = :id
+                org_id = :org_id
+                AND deleted_at IS NULL
         "#;
 
         let mut q_params = new_query_params();
-        q_params.push(text_param(":id", id));
+        q_params.push(integer_param(
+            ":deleted_at",
+            chrono::Utc::now().timestamp_millis(),
+        ));
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
+        q_params.push(text_param(":org_id", org_id));
 
         let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
-        let _ = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(affected)
+    }
+}
 
-        Ok(())
+impl SoftDelete for OrgAppRepo {
+    const TABLE: &'static str = "org_apps";
+
+    fn connection(&self) -> &Connection {
+        &self.db_pool
     }
 }
@@ -0,0 +1,521 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::ctx::AuditCtx;
+use crate::db::turso_decode::{
+    FromTursoRow, collect_row, collect_rows, opt_row_integer, opt_row_text, row_integer, row_text,
+};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::dto::{
+    InvitationAccount, NewOrgInvitationDto, OrgInvitationDto, OrgMemberDto, UserDto, to_roles,
+};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu, DbTransactionSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for OrgInvitationDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        let roles: Vec<String> = row_text(row, 3)?
+            .split(',')
+            .filter(|r| !r.is_empty())
+            .map(|r| r.to_string())
+            .collect();
+
+        Ok(Self {
+            id: row_text(row, 0)?,
+            org_id: row_text(row, 1)?,
+            email: row_text(row, 2)?,
+            roles: to_roles(&roles)?,
+            token_hash: row_text(row, 4)?,
+            created_at: row_integer(row, 5)?,
+            updated_at: row_integer(row, 6)?,
+            expires_at: row_integer(row, 7)?,
+            accepted_at: opt_row_integer(row, 8)?,
+            accepted_by: opt_row_text(row, 9)?,
+            created_by: opt_row_text(row, 10)?,
+            email_id: opt_row_text(row, 11)?,
+            delivery_status: opt_row_text(row, 12)?,
+        })
+    }
+}
+
+const INVITATION_COLUMNS: &str = r#"
+    id,
+    org_id,
+    email,
+    roles,
+    token_hash,
+    created_at,
+    updated_at,
+    expires_at,
+    accepted_at,
+    accepted_by,
+    created_by,
+    email_id,
+    (
+        SELECT status
+        FROM email_outbox
+        WHERE email_outbox.id = org_invitations.email_id
+    ) AS delivery_status
+"#;
+
+pub struct OrgInvitationRepo {
+    db_pool: Connection,
+}
+
+impl OrgInvitationRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    /// Invitations of the org, newest first
+    #[instrument(level = "debug", name = "db.org_invitation.list", skip_all)]
+    pub async fn list(&self, org_id: String) -> Result<Vec<OrgInvitationDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM org_invitations
+            WHERE
+                org_id = :org_id
+            ORDER BY created_at DESC, id DESC
+        "#,
+            INVITATION_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<OrgInvitationDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.org_invitation.get", skip_all)]
+    pub async fn get(&self, org_id: String, id: String) -> Result<Option<OrgInvitationDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM org_invitations
+            WHERE
+                org_id = :org_id
+                AND id = :id
+            LIMIT 1
+        "#,
+            INVITATION_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<OrgInvitationDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Invitation of the email that can still be accepted
+    #[instrument(level = "debug", name = "db.org_invitation.find_pending", skip_all)]
+    pub async fn find_pending(
+        &self,
+        org_id: String,
+        email: String,
+        now: i64,
+    ) -> Result<Option<OrgInvitationDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM org_invitations
+            WHERE
+                org_id = :org_id
+                AND email = :email
+                AND accepted_at IS NULL
+                AND expires_at > :now
+            LIMIT 1
+        "#,
+            INVITATION_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+        q_params.push(text_param(":email", email));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<OrgInvitationDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.org_invitation.find_by_hash", skip_all)]
+    pub async fn find_by_hash(&self, token_hash: String) -> Result<Option<OrgInvitationDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM org_invitations
+            WHERE
+                token_hash = :token_hash
+            LIMIT 1
+        "#,
+            INVITATION_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":token_hash", token_hash));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<OrgInvitationDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.org_invitation.create", skip_all)]
+    pub async fn create(
+        &self,
+        audit: &AuditCtx,
+        org_id: String,
+        data: NewOrgInvitationDto,
+        token_hash: String,
+        expires_at: i64,
+    ) -> Result<OrgInvitationDto> {
+        let query = r#"
+            INSERT INTO org_invitations
+            (
+                id,
+                org_id,
+                email,
+                roles,
+                token_hash,
+                created_at,
+                updated_at,
+                expires_at,
+                accepted_at,
+                accepted_by,
+                created_by
+            )
+            VALUES
+            (
+                :id,
+                :org_id,
+                :email,
+                :roles,
+                :token_hash,
+                :created_at,
+                :updated_at,
+                :expires_at,
+                NULL,
+                NULL,
+                :created_by
+            )
+        "#;
+
+        let id = generate_id(IdPrefix::OrgInvitation);
+        let today = chrono::Utc::now().timestamp_millis();
+        let roles = to_roles(&data.roles)?;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id.clone()));
+        q_params.push(text_param(":org_id", org_id.clone()));
+        q_params.push(text_param(":email", data.email.clone()));
+        q_params.push(text_param(":roles", data.roles.join(",")));
+        q_params.push(text_param(":token_hash", token_hash.clone()));
+        q_params.push(integer_param(":created_at", today));
+        q_params.push(integer_param(":updated_at", today));
+        q_params.push(integer_param(":expires_at", expires_at));
+        q_params.push(opt_text_param(":created_by", audit.actor_id.clone()));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        assert!(affected > 0, "Must insert a new invitation row");
+
+        Ok(OrgInvitationDto {
+            id,
+            org_id,
+            email: data.email,
+            roles,
+            token_hash,
+            created_at: today,
+            updated_at: today,
+            expires_at,
+            accepted_at: None,
+            accepted_by: None,
+            created_by: audit.actor_id.clone(),
+            email_id: None,
+            delivery_status: None,
+        })
+    }
+
+    /// Points the invitation to the email carrying its latest link
+    #[instrument(level = "debug", name = "db.org_invitation.set_email", skip_all)]
+    pub async fn set_email(&self, id: String, email_id: String) -> Result<()> {
+        let query = r#"
+            UPDATE org_invitations
+            SET
+                email_id = :email_id
+            WHERE
+                id = :id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":email_id", email_id));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let _ = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(())
+    }
+
+    /// Replaces the token and expiry of an invitation that was not accepted yet.
+    ///
+    /// The previous link stops working.
+    #[instrument(level = "debug", name = "db.org_invitation.reissue", skip_all)]
+    pub async fn reissue(&self, id: String, token_hash: String, expires_at: i64) -> Result<bool> {
+        let query = r#"
+            UPDATE org_invitations
+            SET
+                token_hash = :token_hash,
+                expires_at = :expires_at,
+                updated_at = :updated_at
+            WHERE
+                id = :id
+                AND accepted_at IS NULL
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":token_hash", token_hash));
+        q_params.push(integer_param(":expires_at", expires_at));
+        q_params.push(integer_param(
+            ":updated_at",
+            chrono::Utc::now().timestamp_millis(),
+        ));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+
+    /// Expires a pending invitation right away
+    #[instrument(level = "debug", name = "db.org_invitation.expire", skip_all)]
+    pub async fn expire(&self, id: String, now: i64) -> Result<bool> {
+        let query = r#"
+            UPDATE org_invitations
+            SET
+                expires_at = :now,
+                updated_at = :now
+            WHERE
+                id = :id
+                AND accepted_at IS NULL
+                AND expires_at > :now
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":now", now));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+
+    /// Marks the invitation accepted and creates the account, when new, and the
+    /// active membership in a single transaction.
+    ///
+    /// Returns nothing when the invitation was already accepted or has expired.
+    #[instrument(level = "debug", name = "db.org_invitation.accept", skip_all)]
+    pub async fn accept(
+        &self,
+        invitation: &OrgInvitationDto,
+        account: InvitationAccount,
+        now: i64,
+    ) -> Result<Option<(UserDto, OrgMemberDto)>> {
+        let accept_query = r#"
+            UPDATE org_invitations
+            SET
+                accepted_at = :now,
+                accepted_by = :user_id,
+                updated_at = :now
+            WHERE
+                id = :id
+                AND accepted_at IS NULL
+                AND expires_at > :now
+        "#;
+
+        let user_query = r#"
+            INSERT INTO users
+            (
+                id,
+                email,
+                name,
+                status,
+                created_at,
+                updated_at,
+                deleted_at,
+                created_by,
+                updated_by
+            )
+            VALUES
+            (
+                :id,
+                :email,
+                :name,
+                :status,
+                :created_at,
+                :updated_at,
+                NULL,
+                :created_by,
+                :created_by
+            )
+        "#;
+
+        let passwd_query = r#"
+            INSERT INTO passwords
+            (
+                id,
+                password,
+                created_at,
+                updated_at
+            )
+            VALUES
+            (
+                :id,
+                :password,
+                :created_at,
+                :updated_at
+            )
+        "#;
+
+        let member_query = r#"
+            INSERT INTO org_members
+            (
+                id,
+                org_id,
+                user_id,
+                roles,
+                status,
+                created_at,
+                updated_at,
+                created_by,
+                updated_by
+            )
+            VALUES
+            (
+                :id,
+                :org_id,
+                :user_id,
+                :roles,
+                :status,
+                :created_at,
+                :updated_at,
+                :created_by,
+                :created_by
+            )
+        "#;
+
+        // Invitees are the audit actor of their own account and membership
+        let (user, new_password) = match account {
+            InvitationAccount::New(new_user) => (
+                UserDto {
+                    id: generate_id(IdPrefix::User),
+                    email: new_user.email,
+                    name: new_user.name,
+                    status: "active".to_string(),
+                    created_at: now,
+                    updated_at: now,
+                    deleted_at: None,
+                },
+                Some(new_user.password),
+            ),
+            InvitationAccount::Existing(user) => (user, None),
+        };
+        let audit = AuditCtx {
+            actor_id: Some(user.id.clone()),
+        };
+
+        let mut conn = self.db_pool.clone();
+        let tx = conn.transaction().await.context(DbTransactionSnafu)?;
+
+        let mut accept_params = new_query_params();
+        accept_params.push(integer_param(":now", now));
+        accept_params.push(text_param(":user_id", user.id.clone()));
+        accept_params.push(text_param(":id", invitation.id.clone()));
+
+        let mut accept_stmt = tx.prepare(accept_query).await.context(DbPrepareSnafu)?;
+        let accepted = accept_stmt
+            .execute(accept_params)
+            .await
+            .context(DbStatementSnafu)?;
+
+        if accepted == 0 {
+            tx.rollback().await.context(DbTransactionSnafu)?;
+            return Ok(None);
+        }
+
+        if let Some(password) = new_password {
+            let mut user_params = new_query_params();
+            user_params.push(text_param(":id", user.id.clone()));
+            user_params.push(text_param(":email", user.email.clone()));
+            user_params.push(text_param(":name", user.name.clone()));
+            user_params.push(text_param(":status", user.status.clone()));
+            user_params.push(integer_param(":created_at", now));
+            user_params.push(integer_param(":updated_at", now));
+            user_params.push(opt_text_param(":created_by", audit.actor_id.clone()));
+
+            let mut user_stmt = tx.prepare(user_query).await.context(DbPrepareSnafu)?;
+            user_stmt
+                .execute(user_params)
+                .await
+                .context(DbStatementSnafu)?;
+
+            let mut password_params = new_query_params();
+            password_params.push(text_param(":id", user.id.clone()));
+            password_params.push(text_param(":password", password));
+            password_params.push(integer_param(":created_at", now));
+            password_params.push(integer_param(":updated_at", now));
+
+            let mut password_stmt = tx.prepare(passwd_query).await.context(DbPrepareSnafu)?;
+            password_stmt
+                .execute(password_params)
+                .await
+                .context(DbStatementSnafu)?;
+        }
+
+        let member = OrgMemberDto {
+            id: generate_id(IdPrefix::OrgMember),
+            org_id: invitation.org_id.clone(),
+            user_id: user.id.clone(),
+            member_email: Some(user.email.clone()),
+            member_name: Some(user.name.clone()),
+            roles: invitation.roles.clone(),
+            status: "active".to_string(),
+            created_at: now,
+            updated_at: now,
+        };
+        let roles: Vec<String> = member.roles.iter().map(|r| r.to_string()).collect();
+
+        let mut member_params = new_query_params();
+        member_params.push(text_param(":id", member.id.clone()));
+        member_params.push(text_param(":org_id", member.org_id.clone()));
+        member_params.push(text_param(":user_id", member.user_id.clone()));
+        member_params.push(text_param(":roles", roles.join(",")));
+        member_params.push(text_param(":status", member.status.clone()));
+        member_params.push(integer_param(":created_at", now));
+        member_params.push(integer_param(":updated_at", now));
+        member_params.push(opt_text_param(":created_by", audit.actor_id.clone()));
+
+        let mut member_stmt = tx.prepare(member_query).await.context(DbPrepareSnafu)?;
+        member_stmt
+            .execute(member_params)
+            .await
+            .context(DbStatementSnafu)?;
+
+        tx.commit().await.context(DbTransactionSnafu)?;
+
+        Ok(Some((user, member)))
+    }
+}
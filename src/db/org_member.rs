@@ -1,18 +1,23 @@
 use snafu::ResultExt;
+use tracing::instrument;
 use turso::{Connection, Row};
 
 use crate::Result;
+use crate::ctx::AuditCtx;
+use crate::db::read_pool::ReadPool;
 use crate::db::turso_decode::{
     FromTursoRow, collect_count, collect_row, collect_rows, opt_row_text, row_integer, row_text,
 };
-use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
 use crate::dto::{
-    ListOrgMembersParamsDto, NewOrgMemberDto, OrgMemberDto, OrgMemberSuggestionDto,
-    OrgMembershipDto, UpdateOrgMemberDto,
+    Cursor, CursorPage, ListingParamsDto, ORG_MEMBER_SORT, Paginated, PaginationLimits,
+    PaginationParams,
+};
+use crate::dto::{
+    ListOrgMembersParamsDto, NewOrgMemberDto, OrgMemberDto, OrgMembershipDto, UpdateOrgMemberDto,
 };
-use crate::dto::{ListingParamsDto, Paginated, PaginationParams};
 use crate::dto::{Role, to_roles};
-use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu, DbTransactionSnafu};
 use crate::utils::{IdPrefix, generate_id};
 
 pub struct OrgMemberWithName {
@@ -55,6 +60,44 @@ impl TryFrom<OrgMemberWithName> for OrgMemberDto {
     }
 }
 
+struct OrgMemberRolesRow {
+    roles: String,
+}
+
+impl FromTursoRow for OrgMemberRolesRow {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            roles: row_text(row, 0)?,
+        })
+    }
+}
+
+struct MembershipKeyRow {
+    org_id: String,
+    user_id: String,
+}
+
+impl FromTursoRow for MembershipKeyRow {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            org_id: row_text(row, 0)?,
+            user_id: row_text(row, 1)?,
+        })
+    }
+}
+
+struct UserIdRow {
+    user_id: String,
+}
+
+impl FromTursoRow for UserIdRow {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            user_id: row_text(row, 0)?,
+        })
+    }
+}
+
 impl FromTursoRow for OrgMemberWithName {
     fn from_row(row: &Row) -> Result<Self> {
         Ok(Self {
@@ -107,25 +150,22 @@ impl FromTursoRow for OrgMembership {
     }
 }
 
-impl FromTursoRow for OrgMemberSuggestionDto {
-    fn from_row(row: &Row) -> Result<Self> {
-        Ok(Self {
-            id: row_text(row, 0)?,
-            email: row_text(row, 1)?,
-            name: row_text(row, 2)?,
-        })
-    }
-}
-
 pub struct OrgMemberRepo {
     db_pool: Connection,
+    read_pool: ReadPool,
+    pagination: PaginationLimits,
 }
 
 impl OrgMemberRepo {
-    pub fn new(db_pool: Connection) -> Self {
-        Self { db_pool }
+    pub fn new(db_pool: Connection, read_pool: ReadPool, pagination: PaginationLimits) -> Self {
+        Self {
+            db_pool,
+            read_pool,
+            pagination,
+        }
     }
 
+    #[instrument(level = "debug", name = "db.org_member.listing_count", skip_all)]
     pub async fn listing_count(
         &self,
         org_id: String,
@@ -152,11 +192,10 @@ impl OrgMemberRepo {
             q_params.push(text_param(":keyword", pattern));
         }
 
-        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
-        let row_result = stmt.query_row(q_params).await;
-        collect_count(row_result)
+        self.read_pool.count(&query, q_params).await
     }
 
+    #[instrument(level = "debug", name = "db.org_member.list", skip_all)]
     pub async fn list(
         &self,
         org_id: String,
@@ -193,7 +232,12 @@ impl OrgMemberRepo {
         }
 
         let total_records = self.listing_count(org_id, params.clone()).await?;
-        let pagination = PaginationParams::new(total_records, params.page, params.per_page, None);
+        let pagination = PaginationParams::new(
+            total_records,
+            params.page,
+            params.per_page,
+            &self.pagination,
+        );
 
         if pagination.total_pages == 0 {
             return Ok(Paginated::new(
@@ -204,13 +248,14 @@ impl OrgMemberRepo {
             ));
         }
 
-        query.push_str(" ORDER BY users.email ASC LIMIT :limit OFFSET :offset");
+        query.push_str(
+            &ORG_MEMBER_SORT.order_by(params.sort_by.as_deref(), params.sort_dir.as_deref()),
+        );
+        query.push_str(" LIMIT :limit OFFSET :offset");
         q_params.push(integer_param(":limit", pagination.per_page as i64));
         q_params.push(integer_param(":offset", pagination.offset));
 
-        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
-        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
-        let items: Vec<OrgMemberWithName> = collect_rows(&mut rows).await?;
+        let items: Vec<OrgMemberWithName> = self.read_pool.rows(&query, q_params).await?;
 
         let items: std::result::Result<Vec<OrgMemberDto>, String> =
             items.into_iter().map(|x| x.try_into()).collect();
@@ -226,6 +271,106 @@ impl OrgMemberRepo {
         }
     }
 
+    /// Keyset listing ordered by member email then membership id, nothing is counted
+    #[instrument(level = "debug", name = "db.org_member.list_cursor", skip_all)]
+    pub async fn list_cursor(
+        &self,
+        org_id: String,
+        params: ListOrgMembersParamsDto,
+    ) -> Result<CursorPage<OrgMemberDto>> {
+        let mut query = r#"
+            SELECT
+                org_members.id,
+                org_members.org_id,
+                org_members.user_id,
+                users.email,
+                users.name,
+                org_members.roles,
+                org_members.status,
+                org_members.created_at,
+                org_members.updated_at
+            FROM org_members
+            LEFT JOIN users ON users.id = org_members.user_id
+            WHERE
+                org_members.org_id = :org_id
+                AND users.deleted_at IS NULL
+        "#
+        .to_string();
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+
+        if let Some(keyword) = params.keyword
+            && !keyword.is_empty()
+        {
+            query.push_str(" AND (users.name LIKE :keyword OR users.email LIKE :keyword)");
+            let pattern = format!("%{}%", keyword);
+            q_params.push(text_param(":keyword", pattern));
+        }
+
+        if let Some(cursor) = params.cursor.as_deref() {
+            let cursor = Cursor::decode(cursor)?;
+            query.push_str(
+                " AND (users.email > :key OR (users.email = :key AND org_members.id > :id))",
+            );
+            q_params.push(text_param(":key", cursor.key));
+            q_params.push(text_param(":id", cursor.id));
+        }
+
+        let limit = self.pagination.cursor_limit(params.limit);
+        query.push_str(" ORDER BY users.email ASC, org_members.id ASC LIMIT :limit");
+        q_params.push(integer_param(":limit", limit as i64 + 1));
+
+        let items: Vec<OrgMemberWithName> = self.read_pool.rows(&query, q_params).await?;
+
+        let items: std::result::Result<Vec<OrgMemberDto>, String> =
+            items.into_iter().map(|x| x.try_into()).collect();
+
+        match items {
+            Ok(list) => Ok(CursorPage::new(list, limit, |member| {
+                Cursor::new(
+                    member.member_email.as_deref().unwrap_or_default(),
+                    &member.id,
+                )
+            })),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Distinct roles assigned to members of the org
+    #[instrument(level = "debug", name = "db.org_member.list_assigned_roles", skip_all)]
+    pub async fn list_assigned_roles(&self, org_id: String) -> Result<Vec<Role>> {
+        let query = r#"
+            SELECT DISTINCT roles
+            FROM org_members
+            WHERE
+                org_id = :org_id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<OrgMemberRolesRow> = collect_rows(&mut rows).await?;
+
+        let mut names: Vec<String> = Vec::new();
+        for item in items.iter() {
+            for role in item.roles.split(',').filter(|r| !r.is_empty()) {
+                if !names.iter().any(|n| n == role) {
+                    names.push(role.to_string());
+                }
+            }
+        }
+
+        to_roles(&names)
+    }
+
+    #[instrument(
+        level = "debug",
+        name = "db.org_member.list_memberships_count",
+        skip_all
+    )]
     pub async fn list_memberships_count(&self, user_id: String) -> Result<i64> {
         let query = r#"
             SELECT COUNT(*) AS total_count
@@ -246,6 +391,7 @@ impl OrgMemberRepo {
         collect_count(row_result)
     }
 
+    #[instrument(level = "debug", name = "db.org_member.list_memberships", skip_all)]
     pub async fn list_memberships(
         &self,
         user_id: String,
@@ -269,7 +415,12 @@ impl OrgMemberRepo {
         "#;
 
         let total_records = self.list_memberships_count(user_id.clone()).await?;
-        let pagination = PaginationParams::new(total_records, params.page, params.per_page, None);
+        let pagination = PaginationParams::new(
+            total_records,
+            params.page,
+            params.per_page,
+            &self.pagination,
+        );
 
         if pagination.total_pages == 0 {
             return Ok(Paginated::new(
@@ -303,7 +454,13 @@ impl OrgMemberRepo {
         }
     }
 
-    pub async fn create(&self, org_id: String, data: NewOrgMemberDto) -> Result<OrgMemberDto> {
+    #[instrument(level = "debug", name = "db.org_member.create", skip_all)]
+    pub async fn create(
+        &self,
+        audit: &AuditCtx,
+        org_id: String,
+        data: NewOrgMemberDto,
+    ) -> Result<OrgMemberDto> {
         let query = r#"
             INSERT INTO org_members
             (
@@ -313,7 +470,9 @@ impl OrgMemberRepo {
                 roles,
                 status,
                 created_at,
-                updated_at
+                updated_at,
+                created_by,
+                updated_by
             )
             VALUES
             (
@@ -323,7 +482,9 @@ impl OrgMemberRepo {
                 :roles,
                 :status,
                 :created_at,
-                :updated_at
+                :updated_at,
+                :created_by,
+                :created_by
             )
         "#;
 
@@ -339,6 +500,7 @@ impl OrgMemberRepo {
         q_params.push(text_param(":status", data.status.clone()));
         q_params.push(integer_param(":created_at", today));
         q_params.push(integer_param(":updated_at", today));
+        q_params.push(opt_text_param(":created_by", audit.actor_id.clone()));
 
         let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
         let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
@@ -361,6 +523,94 @@ impl OrgMemberRepo {
         })
     }
 
+    /// Inserts the new members and updates the existing ones in a single transaction.
+    ///
+    /// `updates` pairs the id of the existing membership with its new roles and status.
+    #[instrument(level = "debug", name = "db.org_member.bulk_assign", skip_all)]
+    pub async fn bulk_assign(
+        &self,
+        audit: &AuditCtx,
+        org_id: String,
+        inserts: Vec<NewOrgMemberDto>,
+        updates: Vec<(String, NewOrgMemberDto)>,
+    ) -> Result<()> {
+        let insert_query = r#"
+            INSERT INTO org_members
+            (
+                id,
+                org_id,
+                user_id,
+                roles,
+                status,
+                created_at,
+                updated_at,
+                created_by,
+                updated_by
+            )
+            VALUES
+            (
+                :id,
+                :org_id,
+                :user_id,
+                :roles,
+                :status,
+                :created_at,
+                :updated_at,
+                :created_by,
+                :created_by
+            )
+        "#;
+
+        let update_query = r#"
+            UPDATE org_members
+            SET
+                roles = :roles,
+                status = :status,
+                updated_at = :updated_at,
+                updated_by = :updated_by
+            WHERE
+                id = :id
+                AND org_id = :org_id
+        "#;
+
+        let today = chrono::Utc::now().timestamp_millis();
+        let mut conn = self.db_pool.clone();
+        let tx = conn.transaction().await.context(DbTransactionSnafu)?;
+
+        for data in inserts.into_iter() {
+            let mut q_params = new_query_params();
+            q_params.push(text_param(":id", generate_id(IdPrefix::OrgMember)));
+            q_params.push(text_param(":org_id", org_id.clone()));
+            q_params.push(text_param(":user_id", data.user_id));
+            q_params.push(text_param(":roles", data.roles.join(",")));
+            q_params.push(text_param(":status", data.status));
+            q_params.push(integer_param(":created_at", today));
+            q_params.push(integer_param(":updated_at", today));
+            q_params.push(opt_text_param(":created_by", audit.actor_id.clone()));
+
+            let mut stmt = tx.prepare(insert_query).await.context(DbPrepareSnafu)?;
+            stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        }
+
+        for (id, data) in updates.into_iter() {
+            let mut q_params = new_query_params();
+            q_params.push(text_param(":id", id));
+            q_params.push(text_param(":org_id", org_id.clone()));
+            q_params.push(text_param(":roles", data.roles.join(",")));
+            q_params.push(text_param(":status", data.status));
+            q_params.push(integer_param(":updated_at", today));
+            q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
+
+            let mut stmt = tx.prepare(update_query).await.context(DbPrepareSnafu)?;
+            stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        }
+
+        tx.commit().await.context(DbTransactionSnafu)?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "db.org_member.get", skip_all)]
     pub async fn get(&self, id: String) -> Result<Option<OrgMemberDto>> {
         let query = r#"
             SELECT
@@ -397,6 +647,7 @@ impl OrgMemberRepo {
         }
     }
 
+    #[instrument(level = "debug", name = "db.org_member.find_member", skip_all)]
     pub async fn find_member(
         &self,
         org_id: String,
@@ -439,105 +690,13 @@ impl OrgMemberRepo {
         }
     }
 
-    async fn list_member_suggestions_count(
-        &self,
-        org_id: String,
-        params: ListOrgMembersParamsDto,
-    ) -> Result<i64> {
-        let mut query = r#"
-            SELECT COUNT(*) AS total_count
-            FROM users
-            LEFT JOIN org_members
-                ON org_members.user_id = users.id
-                AND org_members.org_id = :org_id
-            LEFT JOIN superusers ON superusers.id = users.id
-            WHERE
-                org_members.user_id IS NULL
-                AND superusers.id IS NULL
-                AND users.deleted_at IS NULL
-        "#
-        .to_string();
-
-        let mut q_params = new_query_params();
-        q_params.push(text_param(":org_id", org_id));
-
-        if let Some(keyword) = params.keyword
-            && !keyword.is_empty()
-        {
-            query.push_str(" AND (users.name LIKE :keyword OR users.email LIKE :keyword)");
-            let pattern = format!("%{}%", keyword);
-            q_params.push(text_param(":keyword", pattern));
-        }
-
-        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
-        let row_result = stmt.query_row(q_params).await;
-        collect_count(row_result)
-    }
-
-    pub async fn list_member_suggestions(
+    #[instrument(level = "debug", name = "db.org_member.update", skip_all)]
+    pub async fn update(
         &self,
-        org_id: String,
-        params: ListOrgMembersParamsDto,
-    ) -> Result<Paginated<OrgMemberSuggestionDto>> {
-        let mut query = r#"
-            SELECT
-                users.id,
-                users.email,
-                users.name
-            FROM users
-            LEFT JOIN org_members
-                ON org_members.user_id = users.id
-                AND org_members.org_id = :org_id
-            LEFT JOIN superusers ON superusers.id = users.id
-            WHERE
-                org_members.user_id IS NULL
-                AND superusers.id IS NULL
-                AND users.deleted_at IS NULL
-        "#
-        .to_string();
-
-        let mut q_params = new_query_params();
-        q_params.push(text_param(":org_id", org_id.clone()));
-
-        if let Some(keyword) = params.keyword.clone()
-            && !keyword.is_empty()
-        {
-            query.push_str(" AND (users.name LIKE :keyword OR users.email LIKE :keyword)");
-            let pattern = format!("%{}%", keyword);
-            q_params.push(text_param(":keyword", pattern));
-        }
-
-        let total_records = self
-            .list_member_suggestions_count(org_id, params.clone())
-            .await?;
-        let pagination = PaginationParams::new(total_records, params.page, params.per_page, None);
-
-        if pagination.total_pages == 0 {
-            return Ok(Paginated::new(
-                Vec::new(),
-                pagination.page,
-                pagination.per_page,
-                pagination.total_records,
-            ));
-        }
-
-        query.push_str(" ORDER BY users.email ASC LIMIT :limit OFFSET :offset");
-        q_params.push(integer_param(":limit", pagination.per_page as i64));
-        q_params.push(integer_param(":offset", pagination.offset));
-
-        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
-        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
-        let items: Vec<OrgMemberSuggestionDto> = collect_rows(&mut rows).await?;
-
-        Ok(Paginated::new(
-            items,
-            pagination.page,
-            pagination.per_page,
-            pagination.total_records,
-        ))
-    }
-
-    pub async fn update(&self, id: String, data: UpdateOrgMemberDto) -> Result<bool> {
+        audit: &AuditCtx,
+        id: String,
+        data: UpdateOrgMemberDto,
+    ) -> Result<bool> {
         if data.status.is_none() && data.roles.is_none() {
             return Ok(false);
         }
@@ -559,6 +718,8 @@ impl OrgMemberRepo {
         let updated_at = chrono::Utc::now().timestamp_millis();
         set_parts.push("updated_at = :updated_at");
         q_params.push(integer_param(":updated_at", updated_at));
+        set_parts.push("updated_by = :updated_by");
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
 
         query.push_str(&set_parts.join(", "));
         query.push_str(" WHERE id = :id");
@@ -569,19 +730,144 @@ impl OrgMemberRepo {
         Ok(affected > 0)
     }
 
+    /// Removes the membership along with its custom roles and teams
+    #[instrument(level = "debug", name = "db.org_member.delete", skip_all)]
     pub async fn delete(&self, id: String) -> Result<()> {
+        let roles_query = r#"
+            DELETE FROM org_member_roles
+            WHERE
+                org_member_id = :org_member_id
+        "#;
+
+        let teams_query = r#"
+            DELETE FROM team_members
+            WHERE
+                org_member_id = :org_member_id
+        "#;
+
         let query = r#"
             DELETE FROM org_members
             WHERE
                 id = :id
         "#;
 
+        let mut roles_params = new_query_params();
+        roles_params.push(text_param(":org_member_id", id.clone()));
+
+        let mut teams_params = new_query_params();
+        teams_params.push(text_param(":org_member_id", id.clone()));
+
         let mut q_params = new_query_params();
         q_params.push(text_param(":id", id));
 
-        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut conn = self.db_pool.clone();
+        let tx = conn.transaction().await.context(DbTransactionSnafu)?;
+
+        let mut roles_stmt = tx.prepare(roles_query).await.context(DbPrepareSnafu)?;
+        let _ = roles_stmt
+            .execute(roles_params)
+            .await
+            .context(DbStatementSnafu)?;
+
+        let mut teams_stmt = tx.prepare(teams_query).await.context(DbPrepareSnafu)?;
+        let _ = teams_stmt
+            .execute(teams_params)
+            .await
+            .context(DbStatementSnafu)?;
+
+        let mut stmt = tx.prepare(query).await.context(DbPrepareSnafu)?;
         let _ = stmt.execute(q_params).await.context(DbStatementSnafu)?;
 
+        tx.commit().await.context(DbTransactionSnafu)?;
+
         Ok(())
     }
+
+    /// Removes every membership of the user along with its custom roles and
+    /// teams, returns the ids of the orgs the user was a member of.
+    ///
+    /// Runs without its own transaction so it can join a unit of work.
+    #[instrument(level = "debug", name = "db.org_member.delete_for_user", skip_all)]
+    pub async fn delete_for_user(&self, user_id: String) -> Result<Vec<String>> {
+        let keys = self.delete_matching("user_id", user_id).await?;
+        Ok(keys.into_iter().map(|key| key.org_id).collect())
+    }
+
+    /// Removes every membership of the org along with its custom roles and
+    /// teams, returns the ids of the users who were members.
+    ///
+    /// Runs without its own transaction so it can join a unit of work.
+    #[instrument(level = "debug", name = "db.org_member.delete_for_org", skip_all)]
+    pub async fn delete_for_org(&self, org_id: String) -> Result<Vec<String>> {
+        let keys = self.delete_matching("org_id", org_id).await?;
+        Ok(keys.into_iter().map(|key| key.user_id).collect())
+    }
+
+    /// Deletes the memberships where `column` matches the value
+    async fn delete_matching(&self, column: &str, value: String) -> Result<Vec<MembershipKeyRow>> {
+        let keys_query = format!(
+            "SELECT org_id, user_id FROM org_members WHERE {} = :value",
+            column
+        );
+        let roles_query = format!(
+            "DELETE FROM org_member_roles WHERE org_member_id IN (SELECT id FROM org_members WHERE {} = :value)",
+            column
+        );
+        let teams_query = format!(
+            "DELETE FROM team_members WHERE org_member_id IN (SELECT id FROM org_members WHERE {} = :value)",
+            column
+        );
+        let query = format!("DELETE FROM org_members WHERE {} = :value", column);
+
+        let mut keys_stmt = self
+            .db_pool
+            .prepare(&keys_query)
+            .await
+            .context(DbPrepareSnafu)?;
+        let mut rows = keys_stmt
+            .query(vec![text_param(":value", value.clone())])
+            .await
+            .context(DbStatementSnafu)?;
+        let keys: Vec<MembershipKeyRow> = collect_rows(&mut rows).await?;
+
+        for query in [roles_query, teams_query, query] {
+            let mut stmt = self.db_pool.prepare(&query).await.context(DbPrepareSnafu)?;
+            let _ = stmt
+                .execute(vec![text_param(":value", value.clone())])
+                .await
+                .context(DbStatementSnafu)?;
+        }
+
+        Ok(keys)
+    }
+
+    /// Longest standing active admin of the org other than the given user,
+    /// the one who takes the org over when its owner is deleted
+    #[instrument(level = "debug", name = "db.org_member.find_successor", skip_all)]
+    pub async fn find_successor(&self, org_id: String, user_id: String) -> Result<Option<String>> {
+        let query = r#"
+            SELECT
+                org_members.user_id
+            FROM org_members
+            INNER JOIN users ON users.id = org_members.user_id
+            WHERE
+                org_members.org_id = :org_id
+                AND org_members.user_id != :user_id
+                AND org_members.status = 'active'
+                AND (',' || org_members.roles || ',') LIKE '%,OrgAdmin,%'
+                AND users.status = 'active'
+                AND users.deleted_at IS NULL
+            ORDER BY org_members.created_at ASC, org_members.id ASC
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let row: Option<UserIdRow> = collect_row(row_result)?;
+        Ok(row.map(|row| row.user_id))
+    }
 }
@@ -0,0 +1,245 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::ctx::AuditCtx;
+use crate::db::turso_decode::{
+    FromTursoRow, collect_row, opt_row_integer, opt_row_text, row_integer, row_text,
+};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::dto::OrgOwnerTransferDto;
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for OrgOwnerTransferDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            org_id: row_text(row, 1)?,
+            from_user_id: row_text(row, 2)?,
+            to_user_id: row_text(row, 3)?,
+            token_hash: row_text(row, 4)?,
+            created_at: row_integer(row, 5)?,
+            updated_at: row_integer(row, 6)?,
+            expires_at: row_integer(row, 7)?,
+            accepted_at: opt_row_integer(row, 8)?,
+            cancelled_at: opt_row_integer(row, 9)?,
+            created_by: opt_row_text(row, 10)?,
+            to_email: opt_row_text(row, 11)?,
+        })
+    }
+}
+
+const TRANSFER_COLUMNS: &str = r#"
+    t.id,
+    t.org_id,
+    t.from_user_id,
+    t.to_user_id,
+    t.token_hash,
+    t.created_at,
+    t.updated_at,
+    t.expires_at,
+    t.accepted_at,
+    t.cancelled_at,
+    t.created_by,
+    u.email
+"#;
+
+pub struct OrgOwnerTransferRepo {
+    db_pool: Connection,
+}
+
+impl OrgOwnerTransferRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    /// Transfer of the org still waiting for the new owner
+    #[instrument(level = "debug", name = "db.org_owner_transfer.find_pending", skip_all)]
+    pub async fn find_pending(
+        &self,
+        org_id: String,
+        now: i64,
+    ) -> Result<Option<OrgOwnerTransferDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM org_owner_transfers t
+            LEFT JOIN users u ON u.id = t.to_user_id
+            WHERE
+                t.org_id = :org_id
+                AND t.accepted_at IS NULL
+                AND t.cancelled_at IS NULL
+                AND t.expires_at > :now
+            ORDER BY t.created_at DESC
+            LIMIT 1
+        "#,
+            TRANSFER_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<OrgOwnerTransferDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.org_owner_transfer.find_by_hash", skip_all)]
+    pub async fn find_by_hash(&self, token_hash: String) -> Result<Option<OrgOwnerTransferDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM org_owner_transfers t
+            LEFT JOIN users u ON u.id = t.to_user_id
+            WHERE
+                t.token_hash = :token_hash
+            LIMIT 1
+        "#,
+            TRANSFER_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":token_hash", token_hash));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<OrgOwnerTransferDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.org_owner_transfer.create", skip_all)]
+    pub async fn create(
+        &self,
+        audit: &AuditCtx,
+        org_id: String,
+        from_user_id: String,
+        to_user_id: String,
+        token_hash: String,
+        expires_at: i64,
+    ) -> Result<OrgOwnerTransferDto> {
+        let query = r#"
+            INSERT INTO org_owner_transfers
+            (
+                id,
+                org_id,
+                from_user_id,
+                to_user_id,
+                token_hash,
+                created_at,
+                updated_at,
+                expires_at,
+                accepted_at,
+                cancelled_at,
+                created_by
+            )
+            VALUES
+            (
+                :id,
+                :org_id,
+                :from_user_id,
+                :to_user_id,
+                :token_hash,
+                :created_at,
+                :updated_at,
+                :expires_at,
+                NULL,
+                NULL,
+                :created_by
+            )
+        "#;
+
+        let id = generate_id(IdPrefix::OwnerTransfer);
+        let today = chrono::Utc::now().timestamp_millis();
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id.clone()));
+        q_params.push(text_param(":org_id", org_id.clone()));
+        q_params.push(text_param(":from_user_id", from_user_id.clone()));
+        q_params.push(text_param(":to_user_id", to_user_id.clone()));
+        q_params.push(text_param(":token_hash", token_hash.clone()));
+        q_params.push(integer_param(":created_at", today));
+        q_params.push(integer_param(":updated_at", today));
+        q_params.push(integer_param(":expires_at", expires_at));
+        q_params.push(opt_text_param(":created_by", audit.actor_id.clone()));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        assert!(affected > 0, "Must insert a new owner transfer row");
+
+        Ok(OrgOwnerTransferDto {
+            id,
+            org_id,
+            from_user_id,
+            to_user_id,
+            to_email: None,
+            token_hash,
+            created_at: today,
+            updated_at: today,
+            expires_at,
+            accepted_at: None,
+            cancelled_at: None,
+            created_by: audit.actor_id.clone(),
+        })
+    }
+
+    /// Cancels the pending transfers of the org, the links stop working
+    #[instrument(
+        level = "debug",
+        name = "db.org_owner_transfer.cancel_for_org",
+        skip_all
+    )]
+    pub async fn cancel_for_org(&self, org_id: String, now: i64) -> Result<u64> {
+        let query = r#"
+            UPDATE org_owner_transfers
+            SET
+                cancelled_at = :now,
+                updated_at = :now
+            WHERE
+                org_id = :org_id
+                AND accepted_at IS NULL
+                AND cancelled_at IS NULL
+                AND expires_at > :now
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":now", now));
+        q_params.push(text_param(":org_id", org_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected)
+    }
+
+    /// Marks a pending transfer accepted.
+    ///
+    /// Returns false when it was already accepted, cancelled or has expired.
+    /// Has no transaction of its own so it can run inside a unit of work.
+    #[instrument(level = "debug", name = "db.org_owner_transfer.accept", skip_all)]
+    pub async fn accept(&self, id: String, now: i64) -> Result<bool> {
+        let query = r#"
+            UPDATE org_owner_transfers
+            SET
+                accepted_at = :now,
+                updated_at = :now
+            WHERE
+                id = :id
+                AND accepted_at IS NULL
+                AND cancelled_at IS NULL
+                AND expires_at > :now
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":now", now));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+}
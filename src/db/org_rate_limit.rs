@@ -0,0 +1,113 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{FromTursoRow, collect_row, opt_row_text, row_integer, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::dto::OrgRateLimitDto;
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+
+impl FromTursoRow for OrgRateLimitDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            org_id: row_text(row, 0)?,
+            requests_per_min: row_integer(row, 1)?,
+            burst: row_integer(row, 2)?,
+            updated_at: row_integer(row, 3)?,
+            updated_by: opt_row_text(row, 4)?,
+        })
+    }
+}
+
+pub struct OrgRateLimitRepo {
+    db_pool: Connection,
+}
+
+impl OrgRateLimitRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.org_rate_limit.get", skip_all)]
+    pub async fn get(&self, org_id: String) -> Result<Option<OrgRateLimitDto>> {
+        let query = r#"
+            SELECT
+                org_id,
+                requests_per_min,
+                burst,
+                updated_at,
+                updated_by
+            FROM org_rate_limits
+            WHERE
+                org_id = :org_id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<OrgRateLimitDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Sets the org's budget, keeping a single row per org
+    #[instrument(level = "debug", name = "db.org_rate_limit.upsert", skip_all)]
+    pub async fn upsert(&self, data: OrgRateLimitDto) -> Result<OrgRateLimitDto> {
+        let query = r#"
+            INSERT INTO org_rate_limits
+            (
+                org_id,
+                requests_per_min,
+                burst,
+                updated_at,
+                updated_by
+            )
+            VALUES
+            (
+                :org_id,
+                :requests_per_min,
+                :burst,
+                :updated_at,
+                :updated_by
+            )
+            ON CONFLICT (org_id) DO UPDATE SET
+                requests_per_min = excluded.requests_per_min,
+                burst = excluded.burst,
+                updated_at = excluded.updated_at,
+                updated_by = excluded.updated_by
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", data.org_id.clone()));
+        q_params.push(integer_param(":requests_per_min", data.requests_per_min));
+        q_params.push(integer_param(":burst", data.burst));
+        q_params.push(integer_param(":updated_at", data.updated_at));
+        q_params.push(opt_text_param(":updated_by", data.updated_by.clone()));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(data)
+    }
+
+    /// Removes the override so the org falls back to the deployment defaults
+    #[instrument(level = "debug", name = "db.org_rate_limit.delete", skip_all)]
+    pub async fn delete(&self, org_id: String) -> Result<bool> {
+        let query = r#"
+            DELETE FROM org_rate_limits
+            WHERE
+                org_id = :org_id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+}
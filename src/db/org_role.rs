@@ -0,0 +1,369 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::ctx::AuditCtx;
+use crate::db::turso_decode::{FromTursoRow, collect_row, collect_rows, row_integer, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::dto::{NewOrgRoleDto, OrgRoleDto, UpdateOrgRoleDto, to_permissions};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu, DbTransactionSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for OrgRoleDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        let permissions: Vec<String> = row_text(row, 4)?
+            .split(',')
+            .filter(|p| !p.is_empty())
+            .map(|p| p.to_string())
+            .collect();
+
+        Ok(Self {
+            id: row_text(row, 0)?,
+            org_id: row_text(row, 1)?,
+            name: row_text(row, 2)?,
+            description: row_text(row, 3)?,
+            permissions: to_permissions(&permissions)?,
+            created_at: row_integer(row, 5)?,
+            updated_at: row_integer(row, 6)?,
+        })
+    }
+}
+
+pub struct OrgRoleRepo {
+    db_pool: Connection,
+}
+
+impl OrgRoleRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.org_role.list", skip_all)]
+    pub async fn list(&self, org_id: String) -> Result<Vec<OrgRoleDto>> {
+        let query = r#"
+            SELECT
+                id,
+                org_id,
+                name,
+                description,
+                permissions,
+                created_at,
+                updated_at
+            FROM org_roles
+            WHERE
+                org_id = :org_id
+            ORDER BY name ASC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<OrgRoleDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.org_role.get", skip_all)]
+    pub async fn get(&self, org_id: String, id: String) -> Result<Option<OrgRoleDto>> {
+        let query = r#"
+            SELECT
+                id,
+                org_id,
+                name,
+                description,
+                permissions,
+                created_at,
+                updated_at
+            FROM org_roles
+            WHERE
+                org_id = :org_id
+                AND id = :id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<OrgRoleDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Names are unique within the org regardless of case
+    #[instrument(level = "debug", name = "db.org_role.find_by_name", skip_all)]
+    pub async fn find_by_name(&self, org_id: String, name: String) -> Result<Option<OrgRoleDto>> {
+        let query = r#"
+            SELECT
+                id,
+                org_id,
+                name,
+                description,
+                permissions,
+                created_at,
+                updated_at
+            FROM org_roles
+            WHERE
+                org_id = :org_id
+                AND LOWER(name) = LOWER(:name)
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+        q_params.push(text_param(":name", name));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<OrgRoleDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Custom roles assigned to the user's membership in the org
+    #[instrument(level = "debug", name = "db.org_role.list_by_member", skip_all)]
+    pub async fn list_by_member(&self, org_id: String, user_id: String) -> Result<Vec<OrgRoleDto>> {
+        let query = r#"
+            SELECT
+                org_roles.id,
+                org_roles.org_id,
+                org_roles.name,
+                org_roles.description,
+                org_roles.permissions,
+                org_roles.created_at,
+                org_roles.updated_at
+            FROM org_member_roles
+            INNER JOIN org_members ON org_members.id = org_member_roles.org_member_id
+            INNER JOIN org_roles ON org_roles.id = org_member_roles.role_id
+            WHERE
+                org_members.org_id = :org_id
+                AND org_members.user_id = :user_id
+            ORDER BY org_roles.name ASC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<OrgRoleDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.org_role.create", skip_all)]
+    pub async fn create(
+        &self,
+        audit: &AuditCtx,
+        org_id: String,
+        data: NewOrgRoleDto,
+    ) -> Result<OrgRoleDto> {
+        let query = r#"
+            INSERT INTO org_roles
+            (
+                id,
+                org_id,
+                name,
+                description,
+                permissions,
+                created_at,
+                updated_at,
+                created_by,
+                updated_by
+            )
+            VALUES
+            (
+                :id,
+                :org_id,
+                :name,
+                :description,
+                :permissions,
+                :created_at,
+                :updated_at,
+                :created_by,
+                :updated_by
+            )
+        "#;
+
+        let today = chrono::Utc::now().timestamp_millis();
+        let id = generate_id(IdPrefix::OrgRole);
+        let permissions = to_permissions(&data.permissions)?;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id.clone()));
+        q_params.push(text_param(":org_id", org_id.clone()));
+        q_params.push(text_param(":name", data.name.clone()));
+        q_params.push(text_param(":description", data.description.clone()));
+        q_params.push(text_param(":permissions", data.permissions.join(",")));
+        q_params.push(integer_param(":created_at", today));
+        q_params.push(integer_param(":updated_at", today));
+        q_params.push(opt_text_param(":created_by", audit.actor_id.clone()));
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(OrgRoleDto {
+            id,
+            org_id,
+            name: data.name,
+            description: data.description,
+            permissions,
+            created_at: today,
+            updated_at: today,
+        })
+    }
+
+    #[instrument(level = "debug", name = "db.org_role.update", skip_all)]
+    pub async fn update(
+        &self,
+        audit: &AuditCtx,
+        id: String,
+        data: UpdateOrgRoleDto,
+    ) -> Result<bool> {
+        if data.name.is_none() && data.description.is_none() && data.permissions.is_none() {
+            return Ok(false);
+        }
+
+        let mut query = "UPDATE org_roles SET ".to_string();
+        let mut set_parts: Vec<&str> = Vec::new();
+        let mut q_params = new_query_params();
+
+        if let Some(name) = data.name {
+            set_parts.push("name = :name");
+            q_params.push(text_param(":name", name));
+        }
+
+        if let Some(description) = data.description {
+            set_parts.push("description = :description");
+            q_params.push(text_param(":description", description));
+        }
+
+        if let Some(permissions) = data.permissions {
+            set_parts.push("permissions = :permissions");
+            q_params.push(text_param(":permissions", permissions.join(",")));
+        }
+
+        let updated_at = chrono::Utc::now().timestamp_millis();
+        set_parts.push("updated_at = :updated_at");
+        q_params.push(integer_param(":updated_at", updated_at));
+        set_parts.push("updated_by = :updated_by");
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
+
+        query.push_str(&set_parts.join(", "));
+        query.push_str(" WHERE id = :id");
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(affected > 0)
+    }
+
+    /// Removes the role along with its assignments
+    #[instrument(level = "debug", name = "db.org_role.delete", skip_all)]
+    pub async fn delete(&self, id: String) -> Result<bool> {
+        let assignments_query = r#"
+            DELETE FROM org_member_roles
+            WHERE
+                role_id = :role_id
+        "#;
+
+        let role_query = r#"
+            DELETE FROM org_roles
+            WHERE
+                id = :id
+        "#;
+
+        let mut assignments_params = new_query_params();
+        assignments_params.push(text_param(":role_id", id.clone()));
+
+        let mut role_params = new_query_params();
+        role_params.push(text_param(":id", id));
+
+        let mut conn = self.db_pool.clone();
+        let tx = conn.transaction().await.context(DbTransactionSnafu)?;
+
+        let mut assignments_stmt = tx
+            .prepare(assignments_query)
+            .await
+            .context(DbPrepareSnafu)?;
+        let _ = assignments_stmt
+            .execute(assignments_params)
+            .await
+            .context(DbStatementSnafu)?;
+
+        let mut role_stmt = tx.prepare(role_query).await.context(DbPrepareSnafu)?;
+        let affected = role_stmt
+            .execute(role_params)
+            .await
+            .context(DbStatementSnafu)?;
+
+        if affected == 0 {
+            tx.rollback().await.context(DbTransactionSnafu)?;
+            return Ok(false);
+        }
+
+        tx.commit().await.context(DbTransactionSnafu)?;
+
+        Ok(true)
+    }
+
+    /// Replaces the custom roles of the membership
+    #[instrument(level = "debug", name = "db.org_role.set_member_roles", skip_all)]
+    pub async fn set_member_roles(
+        &self,
+        org_member_id: String,
+        role_ids: Vec<String>,
+    ) -> Result<()> {
+        let clear_query = r#"
+            DELETE FROM org_member_roles
+            WHERE
+                org_member_id = :org_member_id
+        "#;
+
+        let insert_query = r#"
+            INSERT INTO org_member_roles
+            (
+                org_member_id,
+                role_id,
+                created_at
+            )
+            VALUES
+            (
+                :org_member_id,
+                :role_id,
+                :created_at
+            )
+        "#;
+
+        let today = chrono::Utc::now().timestamp_millis();
+
+        let mut conn = self.db_pool.clone();
+        let tx = conn.transaction().await.context(DbTransactionSnafu)?;
+
+        let mut clear_params = new_query_params();
+        clear_params.push(text_param(":org_member_id", org_member_id.clone()));
+
+        let mut clear_stmt = tx.prepare(clear_query).await.context(DbPrepareSnafu)?;
+        let _ = clear_stmt
+            .execute(clear_params)
+            .await
+            .context(DbStatementSnafu)?;
+
+        for role_id in role_ids.into_iter() {
+            let mut q_params = new_query_params();
+            q_params.push(text_param(":org_member_id", org_member_id.clone()));
+            q_params.push(text_param(":role_id", role_id));
+            q_params.push(integer_param(":created_at", today));
+
+            let mut stmt = tx.prepare(insert_query).await.context(DbPrepareSnafu)?;
+            let _ = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        }
+
+        tx.commit().await.context(DbTransactionSnafu)?;
+
+        Ok(())
+    }
+}
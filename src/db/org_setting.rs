@@ -0,0 +1,109 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{FromTursoRow, collect_row, opt_row_text, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::dto::OrgSettingsDto;
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+
+impl FromTursoRow for OrgSettingsDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        let domains = row_text(row, 3)?;
+
+        Ok(Self {
+            org_id: row_text(row, 0)?,
+            logo_url: opt_row_text(row, 1)?,
+            default_member_role: row_text(row, 2)?,
+            allowed_email_domains: domains
+                .split(',')
+                .filter(|domain| !domain.is_empty())
+                .map(|domain| domain.to_string())
+                .collect(),
+        })
+    }
+}
+
+pub struct OrgSettingRepo {
+    db_pool: Connection,
+}
+
+impl OrgSettingRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.org_setting.get", skip_all)]
+    pub async fn get(&self, org_id: String) -> Result<Option<OrgSettingsDto>> {
+        let query = r#"
+            SELECT
+                org_id,
+                logo_url,
+                default_member_role,
+                allowed_email_domains
+            FROM org_settings
+            WHERE
+                org_id = :org_id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<OrgSettingsDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.org_setting.upsert", skip_all)]
+    pub async fn upsert(&self, data: OrgSettingsDto) -> Result<OrgSettingsDto> {
+        let today = chrono::Utc::now().timestamp_millis();
+
+        let query = r#"
+            INSERT INTO org_settings
+            (
+                org_id,
+                logo_url,
+                default_member_role,
+                allowed_email_domains,
+                created_at,
+                updated_at
+            )
+            VALUES
+            (
+                :org_id,
+                :logo_url,
+                :default_member_role,
+                :allowed_email_domains,
+                :created_at,
+                :updated_at
+            )
+            ON CONFLICT (org_id) DO UPDATE SET
+                logo_url = excluded.logo_url,
+                default_member_role = excluded.default_member_role,
+                allowed_email_domains = excluded.allowed_email_domains,
+                updated_at = excluded.updated_at
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", data.org_id.clone()));
+        q_params.push(opt_text_param(":logo_url", data.logo_url.clone()));
+        q_params.push(text_param(
+            ":default_member_role",
+            data.default_member_role.clone(),
+        ));
+        q_params.push(text_param(
+            ":allowed_email_domains",
+            data.allowed_email_domains.join(","),
+        ));
+        q_params.push(integer_param(":created_at", today));
+        q_params.push(integer_param(":updated_at", today));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(data)
+    }
+}
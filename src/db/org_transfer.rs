@@ -0,0 +1,644 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::ctx::AuditCtx;
+use crate::db::turso_decode::{FromTursoRow, collect_row, collect_rows, opt_row_text, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::dto::{
+    IdMappingDto, ImportAction, NamedRecordDto, OrgArchiveAppDto, OrgArchiveMemberDto,
+    OrgImportPlanDto, OrgImportResultDto,
+};
+use crate::error::{
+    DbPrepareSnafu, DbStatementSnafu, DbTransactionSnafu, JsonParseSnafu, JsonSerializeSnafu,
+};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for OrgArchiveMemberDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        let roles_raw = row_text(row, 1)?;
+        let roles: Vec<String> = roles_raw
+            .split(',')
+            .filter(|role| !role.is_empty())
+            .map(|role| role.to_string())
+            .collect();
+
+        Ok(Self {
+            email: row_text(row, 0)?,
+            roles,
+            status: row_text(row, 2)?,
+            custom_roles: Vec::new(),
+        })
+    }
+}
+
+fn split_scopes(scopes: Option<String>) -> Option<Vec<String>> {
+    scopes.map(|scopes| scopes.split(' ').map(|s| s.to_string()).collect())
+}
+
+impl FromTursoRow for OrgArchiveAppDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        let extra_redirect_uris: Vec<String> =
+            serde_json::from_str(&row_text(row, 3)?).context(JsonParseSnafu)?;
+
+        Ok(Self {
+            id: row_text(row, 0)?,
+            name: row_text(row, 1)?,
+            redirect_uri: row_text(row, 2)?,
+            extra_redirect_uris,
+            scopes: split_scopes(opt_row_text(row, 4)?),
+            org_scopes: split_scopes(opt_row_text(row, 5)?),
+        })
+    }
+}
+
+/// Custom role held by a member, keyed by the member email
+struct MemberCustomRole {
+    email: String,
+    role_name: String,
+}
+
+impl FromTursoRow for MemberCustomRole {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            email: row_text(row, 0)?,
+            role_name: row_text(row, 1)?,
+        })
+    }
+}
+
+impl FromTursoRow for NamedRecordDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            name: row_text(row, 1)?,
+        })
+    }
+}
+
+pub struct OrgTransferRepo {
+    db_pool: Connection,
+}
+
+impl OrgTransferRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    /// Members of the org with their email as the portable reference
+    #[instrument(level = "debug", name = "db.org_transfer.export_members", skip_all)]
+    pub async fn export_members(&self, org_id: String) -> Result<Vec<OrgArchiveMemberDto>> {
+        let query = r#"
+            SELECT
+                users.email,
+                org_members.roles,
+                org_members.status
+            FROM org_members
+            INNER JOIN users ON users.id = org_members.user_id
+            WHERE
+                org_members.org_id = :org_id
+                AND users.deleted_at IS NULL
+            ORDER BY users.email ASC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id.clone()));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let mut items: Vec<OrgArchiveMemberDto> = collect_rows(&mut rows).await?;
+
+        let roles_query = r#"
+            SELECT
+                users.email,
+                org_roles.name
+            FROM org_member_roles
+            INNER JOIN org_members ON org_members.id = org_member_roles.org_member_id
+            INNER JOIN users ON users.id = org_members.user_id
+            INNER JOIN org_roles ON org_roles.id = org_member_roles.role_id
+            WHERE
+                org_members.org_id = :org_id
+                AND users.deleted_at IS NULL
+            ORDER BY users.email ASC, org_roles.name ASC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+
+        let mut stmt = self
+            .db_pool
+            .prepare(roles_query)
+            .await
+            .context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let custom_roles: Vec<MemberCustomRole> = collect_rows(&mut rows).await?;
+
+        for custom_role in custom_roles.into_iter() {
+            if let Some(item) = items
+                .iter_mut()
+                .find(|item| item.email == custom_role.email)
+            {
+                item.custom_roles.push(custom_role.role_name);
+            }
+        }
+
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.org_transfer.export_apps", skip_all)]
+    pub async fn export_apps(&self, org_id: String) -> Result<Vec<OrgArchiveAppDto>> {
+        let query = r#"
+            SELECT
+                apps.id,
+                apps.name,
+                apps.redirect_uri,
+                apps.extra_redirect_uris,
+                apps.scopes,
+                org_apps.scopes
+            FROM org_apps
+            INNER JOIN apps ON apps.id = org_apps.app_id
+            WHERE
+                org_apps.org_id = :org_id
+                AND org_apps.deleted_at IS NULL
+                AND apps.deleted_at IS NULL
+            ORDER BY apps.name ASC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<OrgArchiveAppDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.org_transfer.find_org_by_name", skip_all)]
+    pub async fn find_org_by_name(&self, name: String) -> Result<Option<NamedRecordDto>> {
+        let query = r#"
+            SELECT
+                id,
+                name
+            FROM orgs
+            WHERE
+                name = :name
+                AND deleted_at IS NULL
+            ORDER BY created_at ASC
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":name", name));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<NamedRecordDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.org_transfer.find_app_by_name", skip_all)]
+    pub async fn find_app_by_name(&self, name: String) -> Result<Option<NamedRecordDto>> {
+        let query = r#"
+            SELECT
+                id,
+                name
+            FROM apps
+            WHERE
+                name = :name
+                AND deleted_at IS NULL
+            ORDER BY created_at ASC
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":name", name));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<NamedRecordDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Applies a resolved import plan in a single transaction.
+    ///
+    /// New ids are generated here and returned as source to target mappings.
+    #[instrument(level = "debug", name = "db.org_transfer.import", skip_all)]
+    pub async fn import(
+        &self,
+        audit: &AuditCtx,
+        plan: &OrgImportPlanDto,
+    ) -> Result<OrgImportResultDto> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let actor_id = audit.actor_id.clone();
+
+        let mut conn = self.db_pool.clone();
+        let tx = conn.transaction().await.context(DbTransactionSnafu)?;
+
+        let org_id = match (plan.org.action, plan.org.target_id.clone()) {
+            (ImportAction::Create, _) => {
+                let org_query = r#"
+                    INSERT INTO orgs
+                    (
+                        id,
+                        name,
+                        status,
+                        owner_id,
+                        created_at,
+                        updated_at,
+                        deleted_at,
+                        created_by,
+                        updated_by
+                    )
+                    VALUES
+                    (
+                        :id,
+                        :name,
+                        :status,
+                        :owner_id,
+                        :created_at,
+                        :updated_at,
+                        NULL,
+                        :created_by,
+                        :created_by
+                    )
+                "#;
+
+                let org_id = generate_id(IdPrefix::Org);
+
+                let mut q_params = new_query_params();
+                q_params.push(text_param(":id", org_id.clone()));
+                q_params.push(text_param(":name", plan.org.name.clone()));
+                q_params.push(text_param(":status", plan.org.status.clone()));
+                q_params.push(opt_text_param(":owner_id", plan.org.owner_id.clone()));
+                q_params.push(integer_param(":created_at", now));
+                q_params.push(integer_param(":updated_at", now));
+                q_params.push(opt_text_param(":created_by", actor_id.clone()));
+
+                let mut stmt = tx.prepare(org_query).await.context(DbPrepareSnafu)?;
+                stmt.execute(q_params).await.context(DbStatementSnafu)?;
+                org_id
+            }
+            (_, Some(target_id)) => target_id,
+            (_, None) => {
+                tx.rollback().await.context(DbTransactionSnafu)?;
+                return Err("Import plan must have a target org".into());
+            }
+        };
+
+        if let Some(settings) = plan.settings.as_ref()
+            && settings.action == ImportAction::Create
+        {
+            let settings_query = r#"
+                INSERT INTO org_settings
+                (
+                    org_id,
+                    logo_url,
+                    default_member_role,
+                    allowed_email_domains,
+                    created_at,
+                    updated_at
+                )
+                VALUES
+                (
+                    :org_id,
+                    :logo_url,
+                    :default_member_role,
+                    :allowed_email_domains,
+                    :created_at,
+                    :updated_at
+                )
+                ON CONFLICT (org_id) DO NOTHING
+            "#;
+
+            let mut q_params = new_query_params();
+            q_params.push(text_param(":org_id", org_id.clone()));
+            q_params.push(opt_text_param(":logo_url", settings.logo_url.clone()));
+            q_params.push(text_param(
+                ":default_member_role",
+                settings.default_member_role.clone(),
+            ));
+            q_params.push(text_param(
+                ":allowed_email_domains",
+                settings.allowed_email_domains.join(","),
+            ));
+            q_params.push(integer_param(":created_at", now));
+            q_params.push(integer_param(":updated_at", now));
+
+            let mut stmt = tx.prepare(settings_query).await.context(DbPrepareSnafu)?;
+            stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        }
+
+        // Role names are resolved to the ids the member links point to
+        let mut role_ids: Vec<(String, String)> = Vec::with_capacity(plan.roles.len());
+        let mut roles_created: usize = 0;
+
+        for role in plan.roles.iter() {
+            let role_id = match (role.action, role.target_id.clone()) {
+                (ImportAction::Create, _) => {
+                    let role_query = r#"
+                        INSERT INTO org_roles
+                        (
+                            id,
+                            org_id,
+                            name,
+                            description,
+                            permissions,
+                            created_at,
+                            updated_at,
+                            created_by,
+                            updated_by
+                        )
+                        VALUES
+                        (
+                            :id,
+                            :org_id,
+                            :name,
+                            :description,
+                            :permissions,
+                            :created_at,
+                            :updated_at,
+                            :created_by,
+                            :created_by
+                        )
+                    "#;
+
+                    let role_id = generate_id(IdPrefix::OrgRole);
+
+                    let mut q_params = new_query_params();
+                    q_params.push(text_param(":id", role_id.clone()));
+                    q_params.push(text_param(":org_id", org_id.clone()));
+                    q_params.push(text_param(":name", role.name.clone()));
+                    q_params.push(text_param(":description", role.description.clone()));
+                    q_params.push(text_param(":permissions", role.permissions.join(",")));
+                    q_params.push(integer_param(":created_at", now));
+                    q_params.push(integer_param(":updated_at", now));
+                    q_params.push(opt_text_param(":created_by", actor_id.clone()));
+
+                    let mut stmt = tx.prepare(role_query).await.context(DbPrepareSnafu)?;
+                    stmt.execute(q_params).await.context(DbStatementSnafu)?;
+                    roles_created += 1;
+                    role_id
+                }
+                (_, Some(target_id)) => target_id,
+                (_, None) => continue,
+            };
+
+            role_ids.push((role.name.clone(), role_id));
+        }
+
+        let mut app_mappings: Vec<IdMappingDto> = Vec::with_capacity(plan.apps.len());
+
+        for app in plan.apps.iter() {
+            let extra_redirect_uris =
+                serde_json::to_string(&app.extra_redirect_uris).context(JsonSerializeSnafu)?;
+
+            let app_id = match (app.action, app.target_id.clone()) {
+                (ImportAction::Create, _) => {
+                    let app_query = r#"
+                        INSERT INTO apps
+                        (
+                            id,
+                            name,
+                            client_id,
+                            client_secret,
+                            redirect_uri,
+                            extra_redirect_uris,
+                            scopes,
+                            created_at,
+                            updated_at,
+                            deleted_at,
+                            created_by,
+                            updated_by
+                        )
+                        VALUES
+                        (
+                            :id,
+                            :name,
+                            :client_id,
+                            '',
+                            :redirect_uri,
+                            :extra_redirect_uris,
+                            :scopes,
+                            :created_at,
+                            :updated_at,
+                            NULL,
+                            :created_by,
+                            :created_by
+                        )
+                    "#;
+
+                    let app_id = generate_id(IdPrefix::App);
+
+                    let mut q_params = new_query_params();
+                    q_params.push(text_param(":id", app_id.clone()));
+                    q_params.push(text_param(":name", app.name.clone()));
+                    q_params.push(text_param(":client_id", generate_id(IdPrefix::ClientId)));
+                    q_params.push(text_param(":redirect_uri", app.redirect_uri.clone()));
+                    q_params.push(text_param(":extra_redirect_uris", extra_redirect_uris));
+                    q_params.push(opt_text_param(
+                        ":scopes",
+                        app.scopes.as_ref().map(|scopes| scopes.join(" ")),
+                    ));
+                    q_params.push(integer_param(":created_at", now));
+                    q_params.push(integer_param(":updated_at", now));
+                    q_params.push(opt_text_param(":created_by", actor_id.clone()));
+
+                    let mut stmt = tx.prepare(app_query).await.context(DbPrepareSnafu)?;
+                    stmt.execute(q_params).await.context(DbStatementSnafu)?;
+                    app_id
+                }
+                (ImportAction::Update, Some(target_id)) => {
+                    let app_query = r#"
+                        UPDATE apps
+                        SET
+                            redirect_uri = :redirect_uri,
+                            extra_redirect_uris = :extra_redirect_uris,
+                            updated_at = :updated_at,
+                            updated_by = :updated_by
+                        WHERE
+                            id = :id
+                            AND deleted_at IS NULL
+                    "#;
+
+                    let mut q_params = new_query_params();
+                    q_params.push(text_param(":redirect_uri", app.redirect_uri.clone()));
+                    q_params.push(text_param(":extra_redirect_uris", extra_redirect_uris));
+                    q_params.push(integer_param(":updated_at", now));
+                    q_params.push(opt_text_param(":updated_by", actor_id.clone()));
+                    q_params.push(text_param(":id", target_id.clone()));
+
+                    let mut stmt = tx.prepare(app_query).await.context(DbPrepareSnafu)?;
+                    stmt.execute(q_params).await.context(DbStatementSnafu)?;
+                    target_id
+                }
+                (_, Some(target_id)) => target_id,
+                (_, None) => continue,
+            };
+
+            if app.link {
+                let link_query = r#"
+                    INSERT INTO org_apps
+                    (
+                        id,
+                        org_id,
+                        app_id,
+                        scopes,
+                        created_at,
+                        created_by,
+                        updated_by
+                    )
+                    VALUES
+                    (
+                        :id,
+                        :org_id,
+                        :app_id,
+                        :scopes,
+                        :created_at,
+                        :created_by,
+                        :created_by
+                    )
+                    ON CONFLICT (org_id, app_id) DO UPDATE SET
+                        deleted_at = NULL,
+                        scopes = :scopes,
+                        updated_by = :created_by
+                "#;
+
+                let mut q_params = new_query_params();
+                q_params.push(text_param(":id", generate_id(IdPrefix::OrgApp)));
+                q_params.push(text_param(":org_id", org_id.clone()));
+                q_params.push(text_param(":app_id", app_id.clone()));
+                q_params.push(opt_text_param(
+                    ":scopes",
+                    app.org_scopes.as_ref().map(|scopes| scopes.join(" ")),
+                ));
+                q_params.push(integer_param(":created_at", now));
+                q_params.push(opt_text_param(":created_by", actor_id.clone()));
+
+                let mut stmt = tx.prepare(link_query).await.context(DbPrepareSnafu)?;
+                stmt.execute(q_params).await.context(DbStatementSnafu)?;
+            }
+
+            app_mappings.push(IdMappingDto {
+                source_id: app.source_id.clone(),
+                target_id: app_id,
+            });
+        }
+
+        let mut members_created: usize = 0;
+        let mut members_updated: usize = 0;
+
+        for member in plan.members.iter() {
+            let member_id = match (member.action, &member.user_id, &member.member_id) {
+                (ImportAction::Create, Some(user_id), _) => {
+                    let member_query = r#"
+                        INSERT INTO org_members
+                        (
+                            id,
+                            org_id,
+                            user_id,
+                            roles,
+                            status,
+                            created_at,
+                            updated_at,
+                            created_by,
+                            updated_by
+                        )
+                        VALUES
+                        (
+                            :id,
+                            :org_id,
+                            :user_id,
+                            :roles,
+                            :status,
+                            :created_at,
+                            :updated_at,
+                            :created_by,
+                            :created_by
+                        )
+                    "#;
+
+                    let member_id = generate_id(IdPrefix::OrgMember);
+
+                    let mut q_params = new_query_params();
+                    q_params.push(text_param(":id", member_id.clone()));
+                    q_params.push(text_param(":org_id", org_id.clone()));
+                    q_params.push(text_param(":user_id", user_id.clone()));
+                    q_params.push(text_param(":roles", member.roles.join(",")));
+                    q_params.push(text_param(":status", member.status.clone()));
+                    q_params.push(integer_param(":created_at", now));
+                    q_params.push(integer_param(":updated_at", now));
+                    q_params.push(opt_text_param(":created_by", actor_id.clone()));
+
+                    let mut stmt = tx.prepare(member_query).await.context(DbPrepareSnafu)?;
+                    stmt.execute(q_params).await.context(DbStatementSnafu)?;
+                    members_created += 1;
+                    member_id
+                }
+                (ImportAction::Update, _, Some(member_id)) => {
+                    let member_query = r#"
+                        UPDATE org_members
+                        SET
+                            roles = :roles,
+                            updated_at = :updated_at,
+                            updated_by = :updated_by
+                        WHERE
+                            id = :id
+                    "#;
+
+                    let mut q_params = new_query_params();
+                    q_params.push(text_param(":roles", member.roles.join(",")));
+                    q_params.push(integer_param(":updated_at", now));
+                    q_params.push(opt_text_param(":updated_by", actor_id.clone()));
+                    q_params.push(text_param(":id", member_id.clone()));
+
+                    let mut stmt = tx.prepare(member_query).await.context(DbPrepareSnafu)?;
+                    stmt.execute(q_params).await.context(DbStatementSnafu)?;
+                    members_updated += 1;
+                    member_id.clone()
+                }
+                _ => continue,
+            };
+
+            for role_name in member.custom_roles.iter() {
+                let Some((_, role_id)) = role_ids.iter().find(|(name, _)| name == role_name) else {
+                    continue;
+                };
+
+                let link_query = r#"
+                    INSERT INTO org_member_roles
+                    (
+                        org_member_id,
+                        role_id,
+                        created_at
+                    )
+                    VALUES
+                    (
+                        :org_member_id,
+                        :role_id,
+                        :created_at
+                    )
+                    ON CONFLICT (org_member_id, role_id) DO NOTHING
+                "#;
+
+                let mut q_params = new_query_params();
+                q_params.push(text_param(":org_member_id", member_id.clone()));
+                q_params.push(text_param(":role_id", role_id.clone()));
+                q_params.push(integer_param(":created_at", now));
+
+                let mut stmt = tx.prepare(link_query).await.context(DbPrepareSnafu)?;
+                stmt.execute(q_params).await.context(DbStatementSnafu)?;
+            }
+        }
+
+        tx.commit().await.context(DbTransactionSnafu)?;
+
+        Ok(OrgImportResultDto {
+            org_id,
+            apps: app_mappings,
+            roles_created,
+            members_created,
+            members_updated,
+        })
+    }
+}
@@ -1,4 +1,5 @@
 use snafu::ResultExt;
+use tracing::instrument;
 use turso::{Connection, Row};
 
 use crate::Result;
@@ -27,6 +28,7 @@ impl PasswordRepo {
         Self { db_pool }
     }
 
+    #[instrument(level = "debug", name = "db.password.create", skip_all)]
     pub async fn create(&self, user_id: String, data: NewPasswordDto) -> Result<()> {
         let query = r#"
             INSERT INTO passwords
@@ -59,6 +61,7 @@ impl PasswordRepo {
         Ok(())
     }
 
+    #[instrument(level = "debug", name = "db.password.get", skip_all)]
     pub async fn get(&self, user_id: String) -> Result<Option<PasswordDto>> {
         let query = r#"
             SELECT
@@ -81,6 +84,7 @@ impl PasswordRepo {
         Ok(dto)
     }
 
+    #[instrument(level = "debug", name = "db.password.update", skip_all)]
     pub async fn update(&self, user_id: String, data: NewPasswordDto) -> Result<bool> {
         let query = r#"
             UPDATE passwords
@@ -104,6 +108,7 @@ impl PasswordRepo {
         Ok(affected > 0)
     }
 
+    #[instrument(level = "debug", name = "db.password.delete", skip_all)]
     pub async fn delete(&self, user_id: String) -> Result<()> {
         let query = r#"
             DELETE FROM passwords
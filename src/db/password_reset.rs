@@ -0,0 +1,214 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{
+    FromTursoRow, collect_row, opt_row_integer, opt_row_text, row_integer, row_text,
+};
+use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::dto::{NewPasswordResetDto, PasswordResetDto};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for PasswordResetDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            user_id: row_text(row, 1)?,
+            token_hash: row_text(row, 2)?,
+            created_at: row_integer(row, 3)?,
+            expires_at: row_integer(row, 4)?,
+            used_at: opt_row_integer(row, 5)?,
+            email_id: opt_row_text(row, 6)?,
+            delivery_status: opt_row_text(row, 7)?,
+        })
+    }
+}
+
+const PASSWORD_RESET_COLUMNS: &str = r#"
+    id,
+    user_id,
+    token_hash,
+    created_at,
+    expires_at,
+    used_at,
+    email_id,
+    (
+        SELECT status
+        FROM email_outbox
+        WHERE email_outbox.id = password_resets.email_id
+    ) AS delivery_status
+"#;
+
+pub struct PasswordResetRepo {
+    db_pool: Connection,
+}
+
+impl PasswordResetRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.password_reset.find_by_hash", skip_all)]
+    pub async fn find_by_hash(&self, token_hash: String) -> Result<Option<PasswordResetDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM password_resets
+            WHERE
+                token_hash = :token_hash
+            LIMIT 1
+        "#,
+            PASSWORD_RESET_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":token_hash", token_hash));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<PasswordResetDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.password_reset.create", skip_all)]
+    pub async fn create(&self, data: NewPasswordResetDto) -> Result<PasswordResetDto> {
+        let query = r#"
+            INSERT INTO password_resets
+            (
+                id,
+                user_id,
+                token_hash,
+                created_at,
+                expires_at,
+                used_at
+            )
+            VALUES
+            (
+                :id,
+                :user_id,
+                :token_hash,
+                :created_at,
+                :expires_at,
+                NULL
+            )
+        "#;
+
+        let id = generate_id(IdPrefix::PasswordReset);
+        let created_at = chrono::Utc::now().timestamp_millis();
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id.clone()));
+        q_params.push(text_param(":user_id", data.user_id.clone()));
+        q_params.push(text_param(":token_hash", data.token_hash.clone()));
+        q_params.push(integer_param(":created_at", created_at));
+        q_params.push(integer_param(":expires_at", data.expires_at));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        assert!(affected > 0, "Must insert a new password reset row");
+
+        Ok(PasswordResetDto {
+            id,
+            user_id: data.user_id,
+            token_hash: data.token_hash,
+            created_at,
+            expires_at: data.expires_at,
+            used_at: None,
+            email_id: None,
+            delivery_status: None,
+        })
+    }
+
+    /// Newest reset issued to the user, used or not
+    #[instrument(level = "debug", name = "db.password_reset.find_latest", skip_all)]
+    pub async fn find_latest(&self, user_id: String) -> Result<Option<PasswordResetDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM password_resets
+            WHERE
+                user_id = :user_id
+            ORDER BY created_at DESC, id DESC
+            LIMIT 1
+        "#,
+            PASSWORD_RESET_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<PasswordResetDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.password_reset.set_email", skip_all)]
+    pub async fn set_email(&self, id: String, email_id: String) -> Result<()> {
+        let query = r#"
+            UPDATE password_resets
+            SET
+                email_id = :email_id
+            WHERE
+                id = :id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":email_id", email_id));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let _ = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(())
+    }
+
+    /// Marks the token as used.
+    ///
+    /// Returns false when it was already used or has expired.
+    #[instrument(level = "debug", name = "db.password_reset.consume", skip_all)]
+    pub async fn consume(&self, id: String, now: i64) -> Result<bool> {
+        let query = r#"
+            UPDATE password_resets
+            SET
+                used_at = :used_at
+            WHERE
+                id = :id
+                AND used_at IS NULL
+                AND expires_at > :used_at
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":used_at", now));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+
+    /// Burns every unused token of the user so only the newest one works
+    #[instrument(level = "debug", name = "db.password_reset.revoke_for_user", skip_all)]
+    pub async fn revoke_for_user(&self, user_id: String, now: i64) -> Result<u64> {
+        let query = r#"
+            UPDATE password_resets
+            SET
+                used_at = :used_at
+            WHERE
+                user_id = :user_id
+                AND used_at IS NULL
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":used_at", now));
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected)
+    }
+}
@@ -0,0 +1,155 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use chrono::Utc;
+use snafu::ResultExt;
+use tracing::warn;
+use turso::{Connection, Value};
+
+use crate::db::turso_decode::{FromTursoRow, collect_count, collect_rows};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::{Error, Result};
+
+/// Reads stay on the primary for this long after the replica failed
+const REPLICA_RETRY_MILLIS: i64 = 30_000;
+
+/// Runs listing queries on the read replica when there is one.
+///
+/// Reads go to the primary while the replica fails. Single row lookups stay
+/// on the primary since services read rows back right after writing them.
+#[derive(Clone)]
+pub struct ReadPool {
+    primary: Connection,
+    replica: Option<Connection>,
+    replica_down_until: Arc<AtomicI64>,
+}
+
+impl ReadPool {
+    pub fn new(primary: Connection, replica: Option<Connection>) -> Self {
+        Self {
+            primary,
+            replica,
+            replica_down_until: Arc::new(AtomicI64::new(0)),
+        }
+    }
+
+    fn replica(&self) -> Option<&Connection> {
+        let replica = self.replica.as_ref()?;
+        let now = Utc::now().timestamp_millis();
+        (self.replica_down_until.load(Ordering::Relaxed) <= now).then_some(replica)
+    }
+
+    fn replica_failed(&self, e: &Error) {
+        let retry_at = Utc::now().timestamp_millis() + REPLICA_RETRY_MILLIS;
+        self.replica_down_until.store(retry_at, Ordering::Relaxed);
+        warn!("db.replica_failed, reading from the primary: {}", e);
+    }
+
+    pub async fn rows<T: FromTursoRow>(
+        &self,
+        query: &str,
+        params: Vec<(String, Value)>,
+    ) -> Result<Vec<T>> {
+        if let Some(replica) = self.replica() {
+            match rows_on(replica, query, params.clone()).await {
+                Ok(items) => return Ok(items),
+                Err(e) => self.replica_failed(&e),
+            }
+        }
+        rows_on(&self.primary, query, params).await
+    }
+
+    pub async fn count(&self, query: &str, params: Vec<(String, Value)>) -> Result<i64> {
+        if let Some(replica) = self.replica() {
+            match count_on(replica, query, params.clone()).await {
+                Ok(count) => return Ok(count),
+                Err(e) => self.replica_failed(&e),
+            }
+        }
+        count_on(&self.primary, query, params).await
+    }
+}
+
+async fn rows_on<T: FromTursoRow>(
+    conn: &Connection,
+    query: &str,
+    params: Vec<(String, Value)>,
+) -> Result<Vec<T>> {
+    let mut stmt = conn.prepare(query).await.context(DbPrepareSnafu)?;
+    let mut rows = stmt.query(params).await.context(DbStatementSnafu)?;
+    collect_rows(&mut rows).await
+}
+
+async fn count_on(conn: &Connection, query: &str, params: Vec<(String, Value)>) -> Result<i64> {
+    let mut stmt = conn.prepare(query).await.context(DbPrepareSnafu)?;
+    let row_result = stmt.query_row(params).await;
+    collect_count(row_result)
+}
+
+#[cfg(test)]
+mod tests {
+    use turso::Connection;
+
+    use crate::db::db::open_database;
+    use crate::db::turso_params::{new_query_params, text_param};
+    use crate::utils::{IdPrefix, generate_id};
+
+    use super::ReadPool;
+
+    async fn db_with_users(name: &str, emails: &[&str]) -> Connection {
+        let dir = std::env::temp_dir().join("yaas").join("test").join(format!(
+            "read_pool_{}-{}",
+            name,
+            generate_id(IdPrefix::User)
+        ));
+        std::fs::create_dir_all(&dir).expect("db dir");
+
+        let (_, conn) = open_database(&dir.join("yaas.db")).await.expect("db");
+        if emails.is_empty() {
+            return conn;
+        }
+
+        conn.execute("CREATE TABLE users (email TEXT NOT NULL)", ())
+            .await
+            .expect("create table");
+        for email in emails {
+            conn.execute(
+                "INSERT INTO users (email) VALUES (:email)",
+                vec![text_param(":email", email.to_string())],
+            )
+            .await
+            .expect("insert");
+        }
+        conn
+    }
+
+    #[tokio::test]
+    async fn reads_go_to_the_replica_then_fall_back_to_the_primary() {
+        let primary = db_with_users("primary", &["primary@example.com"]).await;
+        let replica = db_with_users("replica", &["replica@example.com", "b@example.com"]).await;
+        let pool = ReadPool::new(primary.clone(), Some(replica));
+
+        let count = pool
+            .count(
+                "SELECT COUNT(*) AS total_count FROM users",
+                new_query_params(),
+            )
+            .await
+            .expect("count");
+        assert_eq!(count, 2);
+
+        // A replica without the table fails like one that is down
+        let broken = db_with_users("broken", &[]).await;
+        let pool = ReadPool::new(primary, Some(broken));
+
+        let count = pool
+            .count(
+                "SELECT COUNT(*) AS total_count FROM users",
+                new_query_params(),
+            )
+            .await
+            .expect("count");
+        assert_eq!(count, 1);
+        assert!(pool.replica().is_none(), "replica is skipped for a while");
+    }
+}
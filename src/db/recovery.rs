@@ -0,0 +1,152 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{FromTursoRow, collect_row, opt_row_integer, row_integer, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::dto::{NewRecoveryTokenDto, RecoveryTokenDto};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for RecoveryTokenDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            user_id: row_text(row, 1)?,
+            token_hash: row_text(row, 2)?,
+            created_at: row_integer(row, 3)?,
+            expires_at: row_integer(row, 4)?,
+            used_at: opt_row_integer(row, 5)?,
+        })
+    }
+}
+
+pub struct RecoveryTokenRepo {
+    db_pool: Connection,
+}
+
+impl RecoveryTokenRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.recovery.find_by_hash", skip_all)]
+    pub async fn find_by_hash(&self, token_hash: String) -> Result<Option<RecoveryTokenDto>> {
+        let query = r#"
+            SELECT
+                id,
+                user_id,
+                token_hash,
+                created_at,
+                expires_at,
+                used_at
+            FROM recovery_tokens
+            WHERE
+                token_hash = :token_hash
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":token_hash", token_hash));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<RecoveryTokenDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.recovery.create", skip_all)]
+    pub async fn create(&self, data: NewRecoveryTokenDto) -> Result<RecoveryTokenDto> {
+        let query = r#"
+            INSERT INTO recovery_tokens
+            (
+                id,
+                user_id,
+                token_hash,
+                created_at,
+                expires_at,
+                used_at
+            )
+            VALUES
+            (
+                :id,
+                :user_id,
+                :token_hash,
+                :created_at,
+                :expires_at,
+                NULL
+            )
+        "#;
+
+        let id = generate_id(IdPrefix::RecoveryToken);
+        let created_at = chrono::Utc::now().timestamp_millis();
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id.clone()));
+        q_params.push(text_param(":user_id", data.user_id.clone()));
+        q_params.push(text_param(":token_hash", data.token_hash.clone()));
+        q_params.push(integer_param(":created_at", created_at));
+        q_params.push(integer_param(":expires_at", data.expires_at));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        assert!(affected > 0, "Must insert a new recovery token row");
+
+        Ok(RecoveryTokenDto {
+            id,
+            user_id: data.user_id,
+            token_hash: data.token_hash,
+            created_at,
+            expires_at: data.expires_at,
+            used_at: None,
+        })
+    }
+
+    /// Marks the token as used.
+    ///
+    /// Returns false when it was already used or has expired.
+    #[instrument(level = "debug", name = "db.recovery.consume", skip_all)]
+    pub async fn consume(&self, id: String, now: i64) -> Result<bool> {
+        let query = r#"
+            UPDATE recovery_tokens
+            SET
+                used_at = :used_at
+            WHERE
+                id = :id
+                AND used_at IS NULL
+                AND expires_at > :used_at
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":used_at", now));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+
+    /// Burns every unused token of the user so only the newest one works
+    #[instrument(level = "debug", name = "db.recovery.revoke_for_user", skip_all)]
+    pub async fn revoke_for_user(&self, user_id: String, now: i64) -> Result<u64> {
+        let query = r#"
+            UPDATE recovery_tokens
+            SET
+                used_at = :used_at
+            WHERE
+                user_id = :user_id
+                AND used_at IS NULL
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":used_at", now));
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected)
+    }
+}
@@ -0,0 +1,104 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{FromTursoRow, collect_row, row_integer, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::dto::RevokedTokenDto;
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+
+impl FromTursoRow for RevokedTokenDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            jti: row_text(row, 0)?,
+            user_id: row_text(row, 1)?,
+            expires_at: row_integer(row, 2)?,
+            created_at: row_integer(row, 3)?,
+        })
+    }
+}
+
+pub struct RevokedTokenRepo {
+    db_pool: Connection,
+}
+
+impl RevokedTokenRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.revoked_token.find", skip_all)]
+    pub async fn find(&self, jti: String) -> Result<Option<RevokedTokenDto>> {
+        let query = r#"
+            SELECT
+                jti,
+                user_id,
+                expires_at,
+                created_at
+            FROM revoked_tokens
+            WHERE
+                jti = :jti
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":jti", jti));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<RevokedTokenDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Revoking the same token twice keeps the first row
+    #[instrument(level = "debug", name = "db.revoked_token.insert", skip_all)]
+    pub async fn insert(&self, data: RevokedTokenDto) -> Result<RevokedTokenDto> {
+        let query = r#"
+            INSERT INTO revoked_tokens
+            (
+                jti,
+                user_id,
+                expires_at,
+                created_at
+            )
+            VALUES
+            (
+                :jti,
+                :user_id,
+                :expires_at,
+                :created_at
+            )
+            ON CONFLICT (jti) DO NOTHING
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":jti", data.jti.clone()));
+        q_params.push(text_param(":user_id", data.user_id.clone()));
+        q_params.push(integer_param(":expires_at", data.expires_at));
+        q_params.push(integer_param(":created_at", data.created_at));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(data)
+    }
+
+    /// Expired tokens fail verification on their own, their rows are no longer needed
+    #[instrument(level = "debug", name = "db.revoked_token.delete_expired", skip_all)]
+    pub async fn delete_expired(&self, now: i64) -> Result<u64> {
+        let query = r#"
+            DELETE FROM revoked_tokens
+            WHERE
+                expires_at <= :now
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected)
+    }
+}
@@ -0,0 +1,317 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::db::turso_decode::{
+    FromTursoRow, collect_row, collect_rows, opt_row_integer, opt_row_text, row_integer, row_text,
+};
+use crate::db::turso_params::{
+    integer_param, new_query_params, opt_integer_param, opt_text_param, text_param,
+};
+use crate::dto::{ElevationStatus, Role, RoleElevationDto};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::{Error, Result};
+
+impl FromTursoRow for RoleElevationDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        let role = row_text(row, 3)?;
+
+        Ok(Self {
+            id: row_text(row, 0)?,
+            org_id: row_text(row, 1)?,
+            user_id: row_text(row, 2)?,
+            role: Role::try_from(role.as_str()).map_err(|msg| Error::Validation { msg })?,
+            reason: row_text(row, 4)?,
+            duration_mins: row_integer(row, 5)?,
+            status: ElevationStatus::try_from(row_text(row, 6)?.as_str())?,
+            requested_by: row_text(row, 7)?,
+            decided_by: opt_row_text(row, 8)?,
+            created_at: row_integer(row, 9)?,
+            expires_at: opt_row_integer(row, 10)?,
+            ended_at: opt_row_integer(row, 11)?,
+        })
+    }
+}
+
+pub struct RoleElevationRepo {
+    db_pool: Connection,
+}
+
+impl RoleElevationRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    /// Most recent elevations of the member first, including ended ones
+    #[instrument(level = "debug", name = "db.role_elevation.list_by_member", skip_all)]
+    pub async fn list_by_member(
+        &self,
+        org_id: String,
+        user_id: String,
+        limit: i64,
+    ) -> Result<Vec<RoleElevationDto>> {
+        let query = r#"
+            SELECT
+                id,
+                org_id,
+                user_id,
+                role,
+                reason,
+                duration_mins,
+                status,
+                requested_by,
+                decided_by,
+                created_at,
+                expires_at,
+                ended_at
+            FROM role_elevations
+            WHERE
+                org_id = :org_id
+                AND user_id = :user_id
+            ORDER BY created_at DESC
+            LIMIT :limit
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(integer_param(":limit", limit));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<RoleElevationDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    /// Active elevations in the org that have not expired yet
+    #[instrument(
+        level = "debug",
+        name = "db.role_elevation.list_active_by_org",
+        skip_all
+    )]
+    pub async fn list_active_by_org(
+        &self,
+        org_id: String,
+        now: i64,
+    ) -> Result<Vec<RoleElevationDto>> {
+        let query = r#"
+            SELECT
+                id,
+                org_id,
+                user_id,
+                role,
+                reason,
+                duration_mins,
+                status,
+                requested_by,
+                decided_by,
+                created_at,
+                expires_at,
+                ended_at
+            FROM role_elevations
+            WHERE
+                org_id = :org_id
+                AND status = 'active'
+                AND expires_at > :now
+            ORDER BY expires_at ASC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<RoleElevationDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.role_elevation.get", skip_all)]
+    pub async fn get(&self, id: String) -> Result<Option<RoleElevationDto>> {
+        let query = r#"
+            SELECT
+                id,
+                org_id,
+                user_id,
+                role,
+                reason,
+                duration_mins,
+                status,
+                requested_by,
+                decided_by,
+                created_at,
+                expires_at,
+                ended_at
+            FROM role_elevations
+            WHERE
+                id = :id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<RoleElevationDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Pending or active elevation of the member to the same role
+    #[instrument(level = "debug", name = "db.role_elevation.find_open", skip_all)]
+    pub async fn find_open(
+        &self,
+        org_id: String,
+        user_id: String,
+        role: Role,
+    ) -> Result<Option<RoleElevationDto>> {
+        let query = r#"
+            SELECT
+                id,
+                org_id,
+                user_id,
+                role,
+                reason,
+                duration_mins,
+                status,
+                requested_by,
+                decided_by,
+                created_at,
+                expires_at,
+                ended_at
+            FROM role_elevations
+            WHERE
+                org_id = :org_id
+                AND user_id = :user_id
+                AND role = :role
+                AND status IN ('pending', 'active')
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(text_param(":role", role.to_string()));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<RoleElevationDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.role_elevation.create", skip_all)]
+    pub async fn create(&self, data: RoleElevationDto) -> Result<RoleElevationDto> {
+        let query = r#"
+            INSERT INTO role_elevations
+            (
+                id,
+                org_id,
+                user_id,
+                role,
+                reason,
+                duration_mins,
+                status,
+                requested_by,
+                decided_by,
+                created_at,
+                expires_at,
+                ended_at
+            )
+            VALUES
+            (
+                :id,
+                :org_id,
+                :user_id,
+                :role,
+                :reason,
+                :duration_mins,
+                :status,
+                :requested_by,
+                :decided_by,
+                :created_at,
+                :expires_at,
+                :ended_at
+            )
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", data.id.clone()));
+        q_params.push(text_param(":org_id", data.org_id.clone()));
+        q_params.push(text_param(":user_id", data.user_id.clone()));
+        q_params.push(text_param(":role", data.role.to_string()));
+        q_params.push(text_param(":reason", data.reason.clone()));
+        q_params.push(integer_param(":duration_mins", data.duration_mins));
+        q_params.push(text_param(":status", data.status.to_string()));
+        q_params.push(text_param(":requested_by", data.requested_by.clone()));
+        q_params.push(opt_text_param(":decided_by", data.decided_by.clone()));
+        q_params.push(integer_param(":created_at", data.created_at));
+        q_params.push(opt_integer_param(":expires_at", data.expires_at));
+        q_params.push(opt_integer_param(":ended_at", data.ended_at));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(data)
+    }
+
+    /// Starts a pending elevation.
+    /// Returns false when someone else already decided it.
+    #[instrument(level = "debug", name = "db.role_elevation.activate", skip_all)]
+    pub async fn activate(&self, id: String, decided_by: String, expires_at: i64) -> Result<bool> {
+        let query = r#"
+            UPDATE role_elevations
+            SET
+                status = 'active',
+                decided_by = :decided_by,
+                expires_at = :expires_at
+            WHERE
+                id = :id
+                AND status = 'pending'
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":decided_by", decided_by));
+        q_params.push(integer_param(":expires_at", expires_at));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+
+    /// Moves the elevation out of `from`, returns false when it already left it
+    #[instrument(level = "debug", name = "db.role_elevation.end", skip_all)]
+    pub async fn end(
+        &self,
+        id: String,
+        from: ElevationStatus,
+        to: ElevationStatus,
+        decided_by: Option<String>,
+    ) -> Result<bool> {
+        let query = r#"
+            UPDATE role_elevations
+            SET
+                status = :to_status,
+                decided_by = COALESCE(:decided_by, decided_by),
+                ended_at = :ended_at
+            WHERE
+                id = :id
+                AND status = :from_status
+        "#;
+
+        let ended_at = chrono::Utc::now().timestamp_millis();
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":to_status", to.to_string()));
+        q_params.push(opt_text_param(":decided_by", decided_by));
+        q_params.push(integer_param(":ended_at", ended_at));
+        q_params.push(text_param(":id", id));
+        q_params.push(text_param(":from_status", from.to_string()));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+}
@@ -0,0 +1,139 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::migrations::MIGRATIONS_TABLE_SQL;
+use crate::db::turso_decode::{FromTursoRow, collect_rows, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::dto::MigrationPhase;
+use crate::error::{DbPrepareSnafu, DbStatementSnafu, DbTransactionSnafu};
+
+struct TableNameRow {
+    name: String,
+}
+
+impl FromTursoRow for TableNameRow {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            name: row_text(row, 0)?,
+        })
+    }
+}
+
+pub struct SchemaRepo {
+    db_pool: Connection,
+}
+
+impl SchemaRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.schema.list_tables", skip_all)]
+    pub async fn list_tables(&self) -> Result<Vec<String>> {
+        let query = r#"
+            SELECT
+                name
+            FROM sqlite_schema
+            WHERE
+                type = 'table'
+            ORDER BY name ASC
+        "#;
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt
+            .query(new_query_params())
+            .await
+            .context(DbStatementSnafu)?;
+        let items: Vec<TableNameRow> = collect_rows(&mut rows).await?;
+        Ok(items.into_iter().map(|item| item.name).collect())
+    }
+
+    #[instrument(level = "debug", name = "db.schema.ensure_migrations_table", skip_all)]
+    pub async fn ensure_migrations_table(&self) -> Result<()> {
+        let mut stmt = self
+            .db_pool
+            .prepare(MIGRATIONS_TABLE_SQL)
+            .await
+            .context(DbPrepareSnafu)?;
+        stmt.execute(new_query_params())
+            .await
+            .context(DbStatementSnafu)?;
+        Ok(())
+    }
+
+    /// Names of the applied migrations, oldest first
+    #[instrument(level = "debug", name = "db.schema.list_applied_migrations", skip_all)]
+    pub async fn list_applied_migrations(&self) -> Result<Vec<String>> {
+        let query = r#"
+            SELECT
+                name
+            FROM schema_migrations
+            ORDER BY name ASC
+        "#;
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt
+            .query(new_query_params())
+            .await
+            .context(DbStatementSnafu)?;
+        let items: Vec<TableNameRow> = collect_rows(&mut rows).await?;
+        Ok(items.into_iter().map(|item| item.name).collect())
+    }
+
+    /// Runs the migration statements and records it in a single transaction
+    #[instrument(level = "debug", name = "db.schema.apply_migration", skip_all)]
+    pub async fn apply_migration(
+        &self,
+        name: &str,
+        phase: MigrationPhase,
+        sql: Option<&str>,
+    ) -> Result<()> {
+        let mut conn = self.db_pool.clone();
+        let tx = conn.transaction().await.context(DbTransactionSnafu)?;
+
+        let statements = sql
+            .unwrap_or_default()
+            .split(';')
+            .map(|stmt| stmt.trim())
+            .filter(|stmt| !stmt.is_empty());
+
+        for statement in statements {
+            let mut stmt = tx.prepare(statement).await.context(DbPrepareSnafu)?;
+            stmt.execute(new_query_params())
+                .await
+                .context(DbStatementSnafu)?;
+        }
+
+        let query = r#"
+            INSERT INTO schema_migrations
+            (
+                name,
+                phase,
+                applied_at
+            )
+            VALUES
+            (
+                :name,
+                :phase,
+                :applied_at
+            )
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":name", name.to_string()));
+        q_params.push(text_param(":phase", phase.to_string()));
+        q_params.push(integer_param(
+            ":applied_at",
+            chrono::Utc::now().timestamp_millis(),
+        ));
+
+        let mut stmt = tx.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        tx.commit().await.context(DbTransactionSnafu)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use snafu::ResultExt;
+use turso::Connection;
+
+use crate::Result;
+use crate::ctx::AuditCtx;
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+
+/// Which rows a query sees with respect to `deleted_at`.
+///
+/// Repos default to `Active`, only admin endpoints opt into the others.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DeletedScope {
+    #[default]
+    Active,
+    WithDeleted,
+    OnlyDeleted,
+}
+
+impl DeletedScope {
+    pub fn include_deleted(include: bool) -> Self {
+        if include {
+            Self::WithDeleted
+        } else {
+            Self::Active
+        }
+    }
+
+    /// Condition on a `deleted_at` column, ex: `orgs.deleted_at`
+    pub fn condition(&self, column: &str) -> String {
+        match self {
+            Self::Active => format!("{} IS NULL", column),
+            Self::WithDeleted => "1 = 1".to_string(),
+            Self::OnlyDeleted => format!("{} IS NOT NULL", column),
+        }
+    }
+}
+
+pub fn soft_delete_query(table: &str) -> String {
+    format!(
+        "UPDATE {} SET deleted_at = :deleted_at, updated_by = :updated_by WHERE id = :id AND deleted_at IS NULL",
+        table
+    )
+}
+
+pub fn restore_query(table: &str) -> String {
+    format!(
+        "UPDATE {} SET deleted_at = NULL, updated_by = :updated_by WHERE id = :id AND deleted_at IS NOT NULL",
+        table
+    )
+}
+
+/// Repos over a table with an `id` key and a nullable `deleted_at` column.
+///
+/// Deleting only stamps `deleted_at`, restoring clears it. Both record the
+/// acting principal in `updated_by`. Each repo keeps its reads scoped to
+/// active rows unless given another `DeletedScope`.
+pub trait SoftDelete {
+    const TABLE: &'static str;
+
+    fn connection(&self) -> &Connection;
+
+    /// Returns false when the row does not exist or is already deleted
+    async fn soft_delete(&self, audit: &AuditCtx, id: String) -> Result<bool> {
+        let query = soft_delete_query(Self::TABLE);
+        let deleted_at = chrono::Utc::now().timestamp_millis();
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":deleted_at", deleted_at));
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self
+            .connection()
+            .prepare(&query)
+            .await
+            .context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+
+    /// Returns false when the row does not exist or is not deleted
+    async fn restore(&self, audit: &AuditCtx, id: String) -> Result<bool> {
+        let query = restore_query(Self::TABLE);
+
+        let mut q_params = new_query_params();
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self
+            .connection()
+            .prepare(&query)
+            .await
+            .context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeletedScope;
+
+    #[test]
+    fn test_deleted_scope_condition() {
+        assert_eq!(
+            DeletedScope::default().condition("orgs.deleted_at"),
+            "orgs.deleted_at IS NULL"
+        );
+        assert_eq!(
+            DeletedScope::include_deleted(true).condition("deleted_at"),
+            "1 = 1"
+        );
+        assert_eq!(
+            DeletedScope::OnlyDeleted.condition("deleted_at"),
+            "deleted_at IS NOT NULL"
+        );
+    }
+}
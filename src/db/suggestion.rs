@@ -0,0 +1,119 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{FromTursoRow, collect_rows, row_integer, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::dto::{SuggestionBufDto, SuggestionParamsDto, UserSuggestionDto};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+
+impl FromTursoRow for UserSuggestionDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            email: row_text(row, 1)?,
+            name: row_text(row, 2)?,
+            active: row_text(row, 3)? == "active",
+            memberships: row_integer(row, 4)?,
+        })
+    }
+}
+
+pub struct SuggestionRepo {
+    db_pool: Connection,
+}
+
+impl SuggestionRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    /// Ranked user suggestions, superusers are never suggested.
+    ///
+    /// When `org_id` is given, current members of that org are left out.
+    #[instrument(level = "debug", name = "db.suggestion.users", skip_all)]
+    pub async fn users(
+        &self,
+        org_id: Option<String>,
+        params: SuggestionParamsDto,
+        limit: usize,
+    ) -> Result<SuggestionBufDto> {
+        let keyword = params.normalized_keyword();
+
+        let match_rank = match keyword {
+            Some(_) => {
+                "CASE WHEN users.email LIKE :prefix OR users.name LIKE :prefix THEN 0 ELSE 1 END"
+            }
+            None => "0",
+        };
+
+        let mut query = format!(
+            r#"
+            SELECT
+                users.id,
+                users.email,
+                users.name,
+                users.status,
+                (
+                    SELECT COUNT(*)
+                    FROM org_members AS memberships
+                    WHERE memberships.user_id = users.id
+                ) AS memberships,
+                {} AS match_rank
+            FROM users
+            LEFT JOIN superusers ON superusers.id = users.id
+            "#,
+            match_rank
+        );
+
+        let mut q_params = new_query_params();
+
+        if let Some(org_id) = org_id {
+            query.push_str(
+                r#"
+            LEFT JOIN org_members
+                ON org_members.user_id = users.id
+                AND org_members.org_id = :org_id
+            WHERE
+                org_members.user_id IS NULL
+                AND "#,
+            );
+            q_params.push(text_param(":org_id", org_id));
+        } else {
+            query.push_str(" WHERE ");
+        }
+
+        query.push_str("superusers.id IS NULL AND users.deleted_at IS NULL");
+
+        if let Some(keyword) = keyword {
+            query.push_str(" AND (users.name LIKE :keyword OR users.email LIKE :keyword)");
+            q_params.push(text_param(":keyword", format!("%{}%", keyword)));
+            q_params.push(text_param(":prefix", format!("{}%", keyword)));
+        }
+
+        if let Some(exclude_id) = params.exclude_id {
+            query.push_str(" AND users.id <> :exclude_id");
+            q_params.push(text_param(":exclude_id", exclude_id));
+        }
+
+        query.push_str(
+            r#"
+            ORDER BY
+                match_rank ASC,
+                CASE WHEN users.status = 'active' THEN 0 ELSE 1 END ASC,
+                users.email ASC
+            LIMIT :limit
+            "#,
+        );
+
+        // One extra row tells whether there are more matches
+        q_params.push(integer_param(":limit", limit as i64 + 1));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<UserSuggestionDto> = collect_rows(&mut rows).await?;
+
+        Ok(SuggestionBufDto::new(items, limit))
+    }
+}
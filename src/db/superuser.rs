@@ -1,12 +1,13 @@
 use snafu::ResultExt;
+use tracing::instrument;
 use turso::{Connection, Row};
 
-use crate::Result;
 use crate::db::turso_decode::{FromTursoRow, collect_row, collect_rows, row_integer, row_text};
 use crate::db::turso_params::{integer_param, new_query_params, text_param};
 use crate::dto::{NewPasswordDto, NewUserDto, SuperuserDto};
 use crate::error::{DbPrepareSnafu, DbStatementSnafu, DbTransactionSnafu};
 use crate::utils::{IdPrefix, generate_id};
+use crate::{Error, Result};
 
 impl FromTursoRow for SuperuserDto {
     fn from_row(row: &Row) -> Result<Self> {
@@ -26,6 +27,7 @@ impl SuperuserRepo {
         Self { db_pool }
     }
 
+    #[instrument(level = "debug", name = "db.superuser.setup", skip_all)]
     pub async fn setup(
         &self,
         new_user: NewUserDto,
@@ -97,6 +99,7 @@ impl SuperuserRepo {
                 name,
                 status,
                 owner_id,
+                member_count,
                 created_at,
                 updated_at,
                 deleted_at
@@ -107,6 +110,7 @@ impl SuperuserRepo {
                 :name,
                 :status,
                 :owner_id,
+                1,
                 :created_at,
                 :updated_at,
                 NULL
@@ -216,6 +220,7 @@ impl SuperuserRepo {
         })
     }
 
+    #[instrument(level = "debug", name = "db.superuser.list", skip_all)]
     pub async fn list(&self) -> Result<Vec<SuperuserDto>> {
         let query = r#"
             SELECT
@@ -234,6 +239,31 @@ impl SuperuserRepo {
         Ok(items)
     }
 
+    /// Superusers who can still sign in, deactivated and deleted accounts left out
+    #[instrument(level = "debug", name = "db.superuser.list_active", skip_all)]
+    pub async fn list_active(&self) -> Result<Vec<SuperuserDto>> {
+        let query = r#"
+            SELECT
+                superusers.id,
+                superusers.created_at
+            FROM superusers
+            INNER JOIN users ON users.id = superusers.id
+            WHERE
+                users.status = 'active'
+                AND users.deleted_at IS NULL
+            ORDER BY superusers.created_at ASC
+        "#;
+
+        let q_params = new_query_params();
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<SuperuserDto> = collect_rows(&mut rows).await?;
+
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.superuser.create", skip_all)]
     pub async fn create(&self, user_id: String) -> Result<SuperuserDto> {
         let query = r#"
             INSERT INTO superusers
@@ -264,6 +294,110 @@ impl SuperuserRepo {
         })
     }
 
+    /// Promotes an existing user into a superuser.
+    ///
+    /// Adds the superuser record and a Superuser membership in the org
+    /// created during setup. Returns None when there is no such org.
+    #[instrument(level = "debug", name = "db.superuser.grant", skip_all)]
+    pub async fn grant(&self, user_id: String) -> Result<Option<SuperuserDto>> {
+        let org_query = r#"
+            SELECT org_members.org_id
+            FROM org_members
+            INNER JOIN superusers ON superusers.id = org_members.user_id
+            WHERE
+                (',' || org_members.roles || ',') LIKE '%,Superuser,%'
+            ORDER BY superusers.created_at ASC
+            LIMIT 1
+        "#;
+
+        let superuser_query = r#"
+            INSERT INTO superusers
+            (
+                id,
+                created_at
+            )
+            VALUES
+            (
+                :id,
+                :created_at
+            )
+        "#;
+
+        let org_member_query = r#"
+            INSERT INTO org_members
+            (
+                id,
+                org_id,
+                user_id,
+                roles,
+                status,
+                created_at,
+                updated_at
+            )
+            VALUES
+            (
+                :id,
+                :org_id,
+                :user_id,
+                :roles,
+                :status,
+                :created_at,
+                :updated_at
+            )
+        "#;
+
+        let created_at = chrono::Utc::now().timestamp_millis();
+
+        let mut conn = self.db_pool.clone();
+        let tx = conn.transaction().await.context(DbTransactionSnafu)?;
+
+        let mut org_stmt = tx.prepare(org_query).await.context(DbPrepareSnafu)?;
+        let org_row = org_stmt.query_row(new_query_params()).await;
+        let org_id = match org_row {
+            Ok(row) => row_text(&row, 0)?,
+            Err(turso::Error::QueryReturnedNoRows) => {
+                tx.rollback().await.context(DbTransactionSnafu)?;
+                return Ok(None);
+            }
+            Err(err) => return Err(Error::DbRow { source: err }),
+        };
+
+        let mut superuser_params = new_query_params();
+        superuser_params.push(text_param(":id", user_id.clone()));
+        superuser_params.push(integer_param(":created_at", created_at));
+
+        let mut superuser_stmt = tx.prepare(superuser_query).await.context(DbPrepareSnafu)?;
+        let superuser_affected = superuser_stmt
+            .execute(superuser_params)
+            .await
+            .context(DbStatementSnafu)?;
+        assert!(superuser_affected > 0, "Must insert a new superuser row");
+
+        let mut org_member_params = new_query_params();
+        org_member_params.push(text_param(":id", generate_id(IdPrefix::OrgMember)));
+        org_member_params.push(text_param(":org_id", org_id));
+        org_member_params.push(text_param(":user_id", user_id.clone()));
+        org_member_params.push(text_param(":roles", "Superuser".to_string()));
+        org_member_params.push(text_param(":status", "active".to_string()));
+        org_member_params.push(integer_param(":created_at", created_at));
+        org_member_params.push(integer_param(":updated_at", created_at));
+
+        let mut org_member_stmt = tx.prepare(org_member_query).await.context(DbPrepareSnafu)?;
+        let org_member_affected = org_member_stmt
+            .execute(org_member_params)
+            .await
+            .context(DbStatementSnafu)?;
+        assert!(org_member_affected > 0, "Must insert a new org member row");
+
+        tx.commit().await.context(DbTransactionSnafu)?;
+
+        Ok(Some(SuperuserDto {
+            id: user_id,
+            created_at,
+        }))
+    }
+
+    #[instrument(level = "debug", name = "db.superuser.get", skip_all)]
     pub async fn get(&self, id: String) -> Result<Option<SuperuserDto>> {
         let query = r#"
             SELECT
@@ -0,0 +1,413 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::ctx::AuditCtx;
+use crate::db::turso_decode::{
+    FromTursoRow, collect_row, collect_rows, opt_row_text, row_integer, row_text,
+};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::dto::{NewTeamDto, TeamDto, TeamMemberDto, UpdateTeamDto, to_roles};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu, DbTransactionSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for TeamDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        let roles: Vec<String> = row_text(row, 4)?
+            .split(',')
+            .filter(|r| !r.is_empty())
+            .map(|r| r.to_string())
+            .collect();
+
+        Ok(Self {
+            id: row_text(row, 0)?,
+            org_id: row_text(row, 1)?,
+            name: row_text(row, 2)?,
+            description: row_text(row, 3)?,
+            roles: to_roles(&roles)?,
+            member_count: row_integer(row, 5)?,
+            created_at: row_integer(row, 6)?,
+            updated_at: row_integer(row, 7)?,
+        })
+    }
+}
+
+impl FromTursoRow for TeamMemberDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            team_id: row_text(row, 0)?,
+            user_id: row_text(row, 1)?,
+            member_name: opt_row_text(row, 2)?,
+            member_email: opt_row_text(row, 3)?,
+            created_at: row_integer(row, 4)?,
+        })
+    }
+}
+
+pub struct TeamRepo {
+    db_pool: Connection,
+}
+
+impl TeamRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.team.list", skip_all)]
+    pub async fn list(&self, org_id: String) -> Result<Vec<TeamDto>> {
+        let query = r#"
+            SELECT
+                id,
+                org_id,
+                name,
+                description,
+                roles,
+                (
+                    SELECT COUNT(*) FROM team_members
+                    WHERE team_members.team_id = teams.id
+                ) AS member_count,
+                created_at,
+                updated_at
+            FROM teams
+            WHERE
+                org_id = :org_id
+            ORDER BY name ASC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<TeamDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.team.get", skip_all)]
+    pub async fn get(&self, org_id: String, id: String) -> Result<Option<TeamDto>> {
+        let query = r#"
+            SELECT
+                id,
+                org_id,
+                name,
+                description,
+                roles,
+                (
+                    SELECT COUNT(*) FROM team_members
+                    WHERE team_members.team_id = teams.id
+                ) AS member_count,
+                created_at,
+                updated_at
+            FROM teams
+            WHERE
+                org_id = :org_id
+                AND id = :id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<TeamDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Names are unique within the org regardless of case
+    #[instrument(level = "debug", name = "db.team.find_by_name", skip_all)]
+    pub async fn find_by_name(&self, org_id: String, name: String) -> Result<Option<TeamDto>> {
+        let query = r#"
+            SELECT
+                id,
+                org_id,
+                name,
+                description,
+                roles,
+                (
+                    SELECT COUNT(*) FROM team_members
+                    WHERE team_members.team_id = teams.id
+                ) AS member_count,
+                created_at,
+                updated_at
+            FROM teams
+            WHERE
+                org_id = :org_id
+                AND LOWER(name) = LOWER(:name)
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+        q_params.push(text_param(":name", name));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<TeamDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Teams the user's membership in the org belongs to
+    #[instrument(level = "debug", name = "db.team.list_by_member", skip_all)]
+    pub async fn list_by_member(&self, org_id: String, user_id: String) -> Result<Vec<TeamDto>> {
+        let query = r#"
+            SELECT
+                teams.id,
+                teams.org_id,
+                teams.name,
+                teams.description,
+                teams.roles,
+                (
+                    SELECT COUNT(*) FROM team_members AS counted
+                    WHERE counted.team_id = teams.id
+                ) AS member_count,
+                teams.created_at,
+                teams.updated_at
+            FROM team_members
+            INNER JOIN org_members ON org_members.id = team_members.org_member_id
+            INNER JOIN teams ON teams.id = team_members.team_id
+            WHERE
+                org_members.org_id = :org_id
+                AND org_members.user_id = :user_id
+            ORDER BY teams.name ASC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":org_id", org_id));
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<TeamDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.team.create", skip_all)]
+    pub async fn create(
+        &self,
+        audit: &AuditCtx,
+        org_id: String,
+        data: NewTeamDto,
+    ) -> Result<TeamDto> {
+        let query = r#"
+            INSERT INTO teams
+            (
+                id,
+                org_id,
+                name,
+                description,
+                roles,
+                created_at,
+                updated_at,
+                created_by,
+                updated_by
+            )
+            VALUES
+            (
+                :id,
+                :org_id,
+                :name,
+                :description,
+                :roles,
+                :created_at,
+                :updated_at,
+                :created_by,
+                :updated_by
+            )
+        "#;
+
+        let today = chrono::Utc::now().timestamp_millis();
+        let id = generate_id(IdPrefix::Team);
+        let roles = to_roles(&data.roles)?;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id.clone()));
+        q_params.push(text_param(":org_id", org_id.clone()));
+        q_params.push(text_param(":name", data.name.clone()));
+        q_params.push(text_param(":description", data.description.clone()));
+        q_params.push(text_param(":roles", data.roles.join(",")));
+        q_params.push(integer_param(":created_at", today));
+        q_params.push(integer_param(":updated_at", today));
+        q_params.push(opt_text_param(":created_by", audit.actor_id.clone()));
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(TeamDto {
+            id,
+            org_id,
+            name: data.name,
+            description: data.description,
+            roles,
+            member_count: 0,
+            created_at: today,
+            updated_at: today,
+        })
+    }
+
+    #[instrument(level = "debug", name = "db.team.update", skip_all)]
+    pub async fn update(&self, audit: &AuditCtx, id: String, data: UpdateTeamDto) -> Result<bool> {
+        if data.name.is_none() && data.description.is_none() && data.roles.is_none() {
+            return Ok(false);
+        }
+
+        let mut query = "UPDATE teams SET ".to_string();
+        let mut set_parts: Vec<&str> = Vec::new();
+        let mut q_params = new_query_params();
+
+        if let Some(name) = data.name {
+            set_parts.push("name = :name");
+            q_params.push(text_param(":name", name));
+        }
+
+        if let Some(description) = data.description {
+            set_parts.push("description = :description");
+            q_params.push(text_param(":description", description));
+        }
+
+        if let Some(roles) = data.roles {
+            set_parts.push("roles = :roles");
+            q_params.push(text_param(":roles", roles.join(",")));
+        }
+
+        let updated_at = chrono::Utc::now().timestamp_millis();
+        set_parts.push("updated_at = :updated_at");
+        q_params.push(integer_param(":updated_at", updated_at));
+        set_parts.push("updated_by = :updated_by");
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
+
+        query.push_str(&set_parts.join(", "));
+        query.push_str(" WHERE id = :id");
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(affected > 0)
+    }
+
+    /// Removes the team along with its memberships
+    #[instrument(level = "debug", name = "db.team.delete", skip_all)]
+    pub async fn delete(&self, id: String) -> Result<bool> {
+        let members_query = r#"
+            DELETE FROM team_members
+            WHERE
+                team_id = :team_id
+        "#;
+
+        let team_query = r#"
+            DELETE FROM teams
+            WHERE
+                id = :id
+        "#;
+
+        let mut members_params = new_query_params();
+        members_params.push(text_param(":team_id", id.clone()));
+
+        let mut team_params = new_query_params();
+        team_params.push(text_param(":id", id));
+
+        let mut conn = self.db_pool.clone();
+        let tx = conn.transaction().await.context(DbTransactionSnafu)?;
+
+        let mut members_stmt = tx.prepare(members_query).await.context(DbPrepareSnafu)?;
+        let _ = members_stmt
+            .execute(members_params)
+            .await
+            .context(DbStatementSnafu)?;
+
+        let mut team_stmt = tx.prepare(team_query).await.context(DbPrepareSnafu)?;
+        let affected = team_stmt
+            .execute(team_params)
+            .await
+            .context(DbStatementSnafu)?;
+
+        if affected == 0 {
+            tx.rollback().await.context(DbTransactionSnafu)?;
+            return Ok(false);
+        }
+
+        tx.commit().await.context(DbTransactionSnafu)?;
+
+        Ok(true)
+    }
+
+    #[instrument(level = "debug", name = "db.team.list_members", skip_all)]
+    pub async fn list_members(&self, team_id: String) -> Result<Vec<TeamMemberDto>> {
+        let query = r#"
+            SELECT
+                team_members.team_id,
+                org_members.user_id,
+                users.name,
+                users.email,
+                team_members.created_at
+            FROM team_members
+            INNER JOIN org_members ON org_members.id = team_members.org_member_id
+            LEFT JOIN users ON users.id = org_members.user_id
+            WHERE
+                team_members.team_id = :team_id
+            ORDER BY users.name ASC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":team_id", team_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<TeamMemberDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    /// Adding a member twice keeps the first membership
+    #[instrument(level = "debug", name = "db.team.add_member", skip_all)]
+    pub async fn add_member(&self, team_id: String, org_member_id: String) -> Result<()> {
+        let query = r#"
+            INSERT INTO team_members
+            (
+                team_id,
+                org_member_id,
+                created_at
+            )
+            VALUES
+            (
+                :team_id,
+                :org_member_id,
+                :created_at
+            )
+            ON CONFLICT (team_id, org_member_id) DO NOTHING
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":team_id", team_id));
+        q_params.push(text_param(":org_member_id", org_member_id));
+        q_params.push(integer_param(
+            ":created_at",
+            chrono::Utc::now().timestamp_millis(),
+        ));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "db.team.remove_member", skip_all)]
+    pub async fn remove_member(&self, team_id: String, org_member_id: String) -> Result<bool> {
+        let query = r#"
+            DELETE FROM team_members
+            WHERE
+                team_id = :team_id
+                AND org_member_id = :org_member_id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":team_id", team_id));
+        q_params.push(text_param(":org_member_id", org_member_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(affected > 0)
+    }
+}
@@ -0,0 +1,83 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{FromTursoRow, collect_row, opt_row_text, row_integer, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::dto::TokenRevocationDto;
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+
+impl FromTursoRow for TokenRevocationDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            user_id: row_text(row, 0)?,
+            revoked_at: row_integer(row, 1)?,
+            revoked_by: opt_row_text(row, 2)?,
+        })
+    }
+}
+
+pub struct TokenRevocationRepo {
+    db_pool: Connection,
+}
+
+impl TokenRevocationRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.token_revocation.find", skip_all)]
+    pub async fn find(&self, user_id: String) -> Result<Option<TokenRevocationDto>> {
+        let query = r#"
+            SELECT
+                user_id,
+                revoked_at,
+                revoked_by
+            FROM token_revocations
+            WHERE
+                user_id = :user_id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<TokenRevocationDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Moves the user's revocation cutoff, keeping a single row per user
+    #[instrument(level = "debug", name = "db.token_revocation.upsert", skip_all)]
+    pub async fn upsert(&self, data: TokenRevocationDto) -> Result<TokenRevocationDto> {
+        let query = r#"
+            INSERT INTO token_revocations
+            (
+                user_id,
+                revoked_at,
+                revoked_by
+            )
+            VALUES
+            (
+                :user_id,
+                :revoked_at,
+                :revoked_by
+            )
+            ON CONFLICT (user_id) DO UPDATE SET
+                revoked_at = excluded.revoked_at,
+                revoked_by = excluded.revoked_by
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", data.user_id.clone()));
+        q_params.push(integer_param(":revoked_at", data.revoked_at));
+        q_params.push(opt_text_param(":revoked_by", data.revoked_by.clone()));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(data)
+    }
+}
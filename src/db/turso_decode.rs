@@ -75,6 +75,19 @@ pub fn opt_row_text(row: &Row, idx: usize) -> Result<Option<String>> {
     }
 }
 
+pub fn opt_row_blob(row: &Row, idx: usize) -> Result<Option<Vec<u8>>> {
+    let value = row.get_value(idx).context(DbValueSnafu)?;
+
+    match value {
+        Value::Null => Ok(None),
+        _ => value
+            .as_blob()
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| format!("Expected nullable blob value at column index {idx}").into()),
+    }
+}
+
 #[allow(dead_code)]
 pub fn opt_row_integer(row: &Row, idx: usize) -> Result<Option<i64>> {
     let value = row.get_value(idx).context(DbValueSnafu)?;
@@ -11,3 +11,21 @@ pub fn text_param(key: &str, value: String) -> (String, Value) {
 pub fn integer_param(key: &str, value: i64) -> (String, Value) {
     (key.to_string(), Value::Integer(value))
 }
+
+pub fn opt_text_param(key: &str, value: Option<String>) -> (String, Value) {
+    match value {
+        Some(value) => text_param(key, value),
+        None => (key.to_string(), Value::Null),
+    }
+}
+
+pub fn opt_integer_param(key: &str, value: Option<i64>) -> (String, Value) {
+    match value {
+        Some(value) => integer_param(key, value),
+        None => (key.to_string(), Value::Null),
+    }
+}
+
+pub fn blob_param(key: &str, value: Vec<u8>) -> (String, Value) {
+    (key.to_string(), Value::Blob(value))
+}
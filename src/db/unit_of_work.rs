@@ -0,0 +1,187 @@
+use std::ops::Deref;
+
+use snafu::ResultExt;
+use tracing::error;
+use turso::Connection;
+
+use crate::Result;
+use crate::db::DbMapper;
+use crate::error::DbTransactionSnafu;
+
+/// Runs several repo calls as a single transaction.
+///
+/// The unit of work opens a connection of its own and derefs to a mapper
+/// whose repos run on it, so only calls made through the unit of work join
+/// the transaction. Calls made through the shared mapper meanwhile are not
+/// part of it. A write to a row another open unit of work wrote fails.
+/// Repo methods that open their own transaction cannot be called inside one.
+/// Dropping the unit of work without finishing it closes its connection,
+/// discarding the transaction.
+pub struct UnitOfWork {
+    conn: Connection,
+    db: DbMapper,
+}
+
+impl UnitOfWork {
+    pub(super) async fn begin(conn: Connection, db: DbMapper) -> Result<Self> {
+        conn.execute("BEGIN CONCURRENT", ())
+            .await
+            .context(DbTransactionSnafu)?;
+        Ok(Self { conn, db })
+    }
+
+    /// Commits when the work succeeded, otherwise rolls back and returns its error
+    pub async fn finish<T>(self, result: Result<T>) -> Result<T> {
+        match result {
+            Ok(value) => {
+                self.conn
+                    .execute("COMMIT", ())
+                    .await
+                    .context(DbTransactionSnafu)?;
+                Ok(value)
+            }
+            Err(e) => {
+                // A write conflict already ended the transaction
+                let aborted = self.conn.is_autocommit().unwrap_or(false);
+                if !aborted && let Err(rollback) = self.conn.execute("ROLLBACK", ()).await {
+                    error!("Unit of work rollback failed: {}", rollback);
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Deref for UnitOfWork {
+    type Target = DbMapper;
+
+    fn deref(&self) -> &DbMapper {
+        &self.db
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+    use crate::ctx::AuditCtx;
+    use crate::dto::{NewPasswordDto, NewUserDto};
+    use crate::test::TestCtx;
+
+    #[tokio::test]
+    async fn failed_work_leaves_no_partial_rows() {
+        let ctx = TestCtx::new("unit_of_work").await.expect("test ctx");
+        let db = &ctx.state.db;
+
+        let uow = db.begin().await.expect("begin");
+        let work = async {
+            let user = uow
+                .users
+                .create(
+                    &AuditCtx::default(),
+                    NewUserDto {
+                        email: "uow.rollback@example.com".to_string(),
+                        name: "Rollback".to_string(),
+                    },
+                )
+                .await?;
+            uow.passwords
+                .create(
+                    user.id.clone(),
+                    NewPasswordDto {
+                        password: "hashed".to_string(),
+                    },
+                )
+                .await?;
+            Err::<String, Error>(Error::Validation {
+                msg: "Stop here".to_string(),
+            })
+        };
+        let result = work.await;
+        let result = uow.finish(result).await;
+        assert!(result.is_err());
+
+        let found = db
+            .users
+            .find_by_email("uow.rollback@example.com".to_string())
+            .await
+            .expect("find");
+        assert!(found.is_none(), "user insert is rolled back");
+
+        let uow = db.begin().await.expect("begin");
+        let work = async {
+            uow.users
+                .create(
+                    &AuditCtx::default(),
+                    NewUserDto {
+                        email: "uow.commit@example.com".to_string(),
+                        name: "Commit".to_string(),
+                    },
+                )
+                .await
+        };
+        let result = work.await;
+        let user = uow.finish(result).await.expect("committed");
+
+        let found = db.users.get(user.id).await.expect("get");
+        assert!(found.is_some());
+    }
+
+    fn new_user(email: &str) -> NewUserDto {
+        NewUserDto {
+            email: email.to_string(),
+            name: "Concurrent".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_units_of_work_are_isolated() {
+        let ctx = TestCtx::new("unit_of_work_concurrent")
+            .await
+            .expect("test ctx");
+        let db = &ctx.state.db;
+        let audit = AuditCtx::default();
+
+        // Both are open at once, each on its own connection
+        let failing = db.begin().await.expect("begin failing");
+        let passing = db.begin().await.expect("begin passing");
+
+        let failed = async {
+            failing
+                .users
+                .create(&audit, new_user("uow.failing@example.com"))
+                .await?;
+            tokio::task::yield_now().await;
+            Err::<(), Error>(Error::Validation {
+                msg: "Stop here".to_string(),
+            })
+        };
+        let passed = async {
+            tokio::task::yield_now().await;
+            passing
+                .users
+                .create(&audit, new_user("uow.passing@example.com"))
+                .await
+        };
+        let (failed, passed) = tokio::join!(failed, passed);
+
+        // Writes outside of a unit of work do not join either of them
+        let outside = db
+            .users
+            .create(&audit, new_user("uow.outside@example.com"))
+            .await
+            .expect("outside write");
+
+        let (failed, passed) = tokio::join!(failing.finish(failed), passing.finish(passed));
+        assert!(failed.is_err());
+        let passed = passed.expect("committed");
+
+        let found = db
+            .users
+            .find_by_email("uow.failing@example.com".to_string())
+            .await
+            .expect("find");
+        assert!(found.is_none(), "failed unit of work is rolled back");
+        assert!(db.users.get(passed.id).await.expect("get").is_some());
+        assert!(db.users.get(outside.id).await.expect("get").is_some());
+    }
+}
@@ -1,13 +1,19 @@
 use snafu::ResultExt;
+use tracing::instrument;
 use turso::{Connection, Row};
 
 use crate::Result;
+use crate::ctx::AuditCtx;
+use crate::db::read_pool::ReadPool;
+use crate::db::soft_delete::{DeletedScope, SoftDelete};
 use crate::db::turso_decode::{
-    FromTursoRow, collect_count, collect_row, collect_rows, row_integer, row_text,
+    FromTursoRow, collect_row, collect_rows, opt_row_integer, row_integer, row_text,
 };
-use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::db::versioned::{NEXT_VERSION_SQL, Versioned};
+use crate::dto::USER_SORT;
+use crate::dto::{Cursor, CursorPage, Paginated, PaginationLimits, PaginationParams};
 use crate::dto::{ListUsersParamsDto, NewUserDto, NewUserWithPasswordDto, UpdateUserDto, UserDto};
-use crate::dto::{Paginated, PaginationParams};
 use crate::error::{DbPrepareSnafu, DbStatementSnafu, DbTransactionSnafu};
 use crate::utils::{IdPrefix, generate_id};
 
@@ -31,6 +37,7 @@ impl From<User> for UserDto {
             status: user.status,
             created_at: user.created_at,
             updated_at: user.updated_at,
+            deleted_at: user.deleted_at,
         }
     }
 }
@@ -44,27 +51,36 @@ impl FromTursoRow for UserDto {
             status: row_text(row, 3)?,
             created_at: row_integer(row, 4)?,
             updated_at: row_integer(row, 5)?,
+            deleted_at: opt_row_integer(row, 6)?,
         })
     }
 }
 
 pub struct UserRepo {
     db_pool: Connection,
+    read_pool: ReadPool,
+    pagination: PaginationLimits,
 }
 
 impl UserRepo {
-    pub fn new(db_pool: Connection) -> Self {
-        Self { db_pool }
+    pub fn new(db_pool: Connection, read_pool: ReadPool, pagination: PaginationLimits) -> Self {
+        Self {
+            db_pool,
+            read_pool,
+            pagination,
+        }
     }
 
-    async fn listing_count(&self, params: ListUsersParamsDto) -> Result<i64> {
-        let mut query = r#"
+    async fn listing_count(&self, params: ListUsersParamsDto, scope: DeletedScope) -> Result<i64> {
+        let mut query = format!(
+            r#"
             SELECT COUNT(*) AS total_count
             FROM users
             WHERE
-                deleted_at IS NULL
-        "#
-        .to_string();
+                {}
+            "#,
+            scope.condition("deleted_at")
+        );
 
         let mut q_params = new_query_params();
 
@@ -76,25 +92,36 @@ impl UserRepo {
             q_params.push(text_param(":keyword", pattern));
         }
 
-        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
-        let row_result = stmt.query_row(q_params).await;
-        collect_count(row_result)
+        self.read_pool.count(&query, q_params).await
     }
 
+    #[instrument(level = "debug", name = "db.user.list", skip_all)]
     pub async fn list(&self, params: ListUsersParamsDto) -> Result<Paginated<UserDto>> {
-        let mut query = r#"
+        self.list_scoped(params, DeletedScope::Active).await
+    }
+
+    #[instrument(level = "debug", name = "db.user.list_scoped", skip_all)]
+    pub async fn list_scoped(
+        &self,
+        params: ListUsersParamsDto,
+        scope: DeletedScope,
+    ) -> Result<Paginated<UserDto>> {
+        let mut query = format!(
+            r#"
             SELECT
                 id,
                 email,
                 name,
                 status,
                 created_at,
-                updated_at
+                updated_at,
+                deleted_at
             FROM users
             WHERE
-                deleted_at IS NULL
-        "#
-        .to_string();
+                {}
+            "#,
+            scope.condition("deleted_at")
+        );
 
         let mut q_params = new_query_params();
         let count_params = params.clone();
@@ -107,8 +134,13 @@ impl UserRepo {
             q_params.push(text_param(":keyword", pattern));
         }
 
-        let total_records = self.listing_count(count_params).await?;
-        let pagination = PaginationParams::new(total_records, params.page, params.per_page, None);
+        let total_records = self.listing_count(count_params, scope).await?;
+        let pagination = PaginationParams::new(
+            total_records,
+            params.page,
+            params.per_page,
+            &self.pagination,
+        );
 
         if pagination.total_pages == 0 {
             return Ok(Paginated::new(
@@ -119,13 +151,12 @@ impl UserRepo {
             ));
         }
 
-        query.push_str(" ORDER BY email ASC LIMIT :limit OFFSET :offset");
+        query.push_str(&USER_SORT.order_by(params.sort_by.as_deref(), params.sort_dir.as_deref()));
+        query.push_str(" LIMIT :limit OFFSET :offset");
         q_params.push(integer_param(":limit", pagination.per_page as i64));
         q_params.push(integer_param(":offset", pagination.offset));
 
-        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
-        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
-        let items: Vec<UserDto> = collect_rows(&mut rows).await?;
+        let items: Vec<UserDto> = self.read_pool.rows(&query, q_params).await?;
 
         Ok(Paginated::new(
             items,
@@ -135,7 +166,60 @@ impl UserRepo {
         ))
     }
 
-    pub async fn create(&self, data: NewUserDto) -> Result<UserDto> {
+    /// Keyset listing ordered by email then id, nothing is counted
+    #[instrument(level = "debug", name = "db.user.list_cursor", skip_all)]
+    pub async fn list_cursor(
+        &self,
+        params: ListUsersParamsDto,
+        scope: DeletedScope,
+    ) -> Result<CursorPage<UserDto>> {
+        let mut query = format!(
+            r#"
+            SELECT
+                id,
+                email,
+                name,
+                status,
+                created_at,
+                updated_at,
+                deleted_at
+            FROM users
+            WHERE
+                {}
+            "#,
+            scope.condition("deleted_at")
+        );
+
+        let mut q_params = new_query_params();
+
+        if let Some(keyword) = params.keyword
+            && !keyword.is_empty()
+        {
+            query.push_str(" AND (email LIKE :keyword OR name LIKE :keyword)");
+            let pattern = format!("%{}%", keyword);
+            q_params.push(text_param(":keyword", pattern));
+        }
+
+        if let Some(cursor) = params.cursor.as_deref() {
+            let cursor = Cursor::decode(cursor)?;
+            query.push_str(" AND (email > :key OR (email = :key AND id > :id))");
+            q_params.push(text_param(":key", cursor.key));
+            q_params.push(text_param(":id", cursor.id));
+        }
+
+        let limit = self.pagination.cursor_limit(params.limit);
+        query.push_str(" ORDER BY email ASC, id ASC LIMIT :limit");
+        q_params.push(integer_param(":limit", limit as i64 + 1));
+
+        let items: Vec<UserDto> = self.read_pool.rows(&query, q_params).await?;
+
+        Ok(CursorPage::new(items, limit, |user| {
+            Cursor::new(&user.email, &user.id)
+        }))
+    }
+
+    #[instrument(level = "debug", name = "db.user.create", skip_all)]
+    pub async fn create(&self, audit: &AuditCtx, data: NewUserDto) -> Result<UserDto> {
         let query = r#"
             INSERT INTO users
             (
@@ -145,7 +229,9 @@ impl UserRepo {
                 status,
                 created_at,
                 updated_at,
-                deleted_at
+                deleted_at,
+                created_by,
+                updated_by
             )
             VALUES
             (
@@ -155,7 +241,9 @@ impl UserRepo {
                 :status,
                 :created_at,
                 :updated_at,
-                NULL
+                NULL,
+                :created_by,
+                :created_by
             )
         "#;
 
@@ -170,6 +258,7 @@ impl UserRepo {
         q_params.push(text_param(":status", status.clone()));
         q_params.push(integer_param(":created_at", today));
         q_params.push(integer_param(":updated_at", today));
+        q_params.push(opt_text_param(":created_by", audit.actor_id.clone()));
 
         let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
         let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
@@ -182,12 +271,18 @@ impl UserRepo {
             status,
             created_at: today,
             updated_at: today,
+            deleted_at: None,
         };
 
         Ok(user)
     }
 
-    pub async fn create_with_password(&self, new_user: NewUserWithPasswordDto) -> Result<UserDto> {
+    #[instrument(level = "debug", name = "db.user.create_with_password", skip_all)]
+    pub async fn create_with_password(
+        &self,
+        audit: &AuditCtx,
+        new_user: NewUserWithPasswordDto,
+    ) -> Result<UserDto> {
         let user_id = generate_id(IdPrefix::User);
         let status = "active".to_string();
         let today = chrono::Utc::now().timestamp_millis();
@@ -201,7 +296,9 @@ impl UserRepo {
                 status,
                 created_at,
                 updated_at,
-                deleted_at
+                deleted_at,
+                created_by,
+                updated_by
             )
             VALUES
             (
@@ -211,7 +308,9 @@ impl UserRepo {
                 :status,
                 :created_at,
                 :updated_at,
-                NULL
+                NULL,
+                :created_by,
+                :created_by
             )
         "#;
 
@@ -222,6 +321,7 @@ impl UserRepo {
         user_params.push(text_param(":status", status.clone()));
         user_params.push(integer_param(":created_at", today));
         user_params.push(integer_param(":updated_at", today));
+        user_params.push(opt_text_param(":created_by", audit.actor_id.clone()));
 
         let mut conn = self.db_pool.clone();
         let tx = conn.transaction().await.context(DbTransactionSnafu)?;
@@ -276,24 +376,72 @@ impl UserRepo {
             status,
             created_at: today,
             updated_at: today,
+            deleted_at: None,
         })
     }
 
+    #[instrument(level = "debug", name = "db.user.get", skip_all)]
     pub async fn get(&self, id: String) -> Result<Option<UserDto>> {
-        let query = r#"
+        self.get_scoped(id, DeletedScope::Active).await
+    }
+
+    /// Active users with the given ids, in no particular order
+    #[instrument(level = "debug", name = "db.user.list_by_ids", skip_all)]
+    pub async fn list_by_ids(&self, ids: &[String]) -> Result<Vec<UserDto>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let placeholders: Vec<String> = (0..ids.len()).map(|i| format!(":id_{}", i)).collect();
+        let query = format!(
+            r#"
             SELECT
                 id,
                 email,
                 name,
                 status,
                 created_at,
-                updated_at
+                updated_at,
+                deleted_at
             FROM users
             WHERE
                 deleted_at IS NULL
+                AND id IN ({})
+            "#,
+            placeholders.join(", ")
+        );
+
+        let mut q_params = new_query_params();
+        for (placeholder, id) in placeholders.iter().zip(ids.iter()) {
+            q_params.push(text_param(placeholder, id.clone()));
+        }
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<UserDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.user.get_scoped", skip_all)]
+    pub async fn get_scoped(&self, id: String, scope: DeletedScope) -> Result<Option<UserDto>> {
+        let query = format!(
+            r#"
+            SELECT
+                id,
+                email,
+                name,
+                status,
+                created_at,
+                updated_at,
+                deleted_at
+            FROM users
+            WHERE
+                {}
                 AND id = :id
             LIMIT 1
-        "#;
+            "#,
+            scope.condition("deleted_at")
+        );
 
         let mut q_params = new_query_params();
         q_params.push(text_param(":id", id));
@@ -304,6 +452,7 @@ impl UserRepo {
         Ok(dto)
     }
 
+    #[instrument(level = "debug", name = "db.user.find_by_email", skip_all)]
     pub async fn find_by_email(&self, email: String) -> Result<Option<UserDto>> {
         let query = r#"
             SELECT
@@ -312,7 +461,8 @@ impl UserRepo {
                 name,
                 status,
                 created_at,
-                updated_at
+                updated_at,
+                deleted_at
             FROM users
             WHERE
                 deleted_at IS NULL
@@ -329,7 +479,8 @@ impl UserRepo {
         Ok(dto)
     }
 
-    pub async fn update(&self, id: String, data: UpdateUserDto) -> Result<bool> {
+    #[instrument(level = "debug", name = "db.user.update", skip_all)]
+    pub async fn update(&self, audit: &AuditCtx, id: String, data: UpdateUserDto) -> Result<bool> {
         if data.status.is_none() && data.name.is_none() {
             return Ok(false);
         }
@@ -349,8 +500,10 @@ impl UserRepo {
         }
 
         let updated_at = chrono::Utc::now().timestamp_millis();
-        set_parts.push("updated_at = :updated_at");
+        set_parts.push(NEXT_VERSION_SQL);
         q_params.push(integer_param(":updated_at", updated_at));
+        set_parts.push("updated_by = :updated_by");
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
 
         query.push_str(&set_parts.join(", "));
         query.push_str(" WHERE id = :id AND deleted_at IS NULL");
@@ -361,23 +514,57 @@ impl UserRepo {
         Ok(affected > 0)
     }
 
-    pub async fn delete(&self, id: String) -> Result<bool> {
-        let query = r#"
+    /// Replaces the email and name with tombstones and deactivates the user,
+    /// deleted users included
+    #[instrument(level = "debug", name = "db.user.anonymize", skip_all)]
+    pub async fn anonymize(
+        &self,
+        audit: &AuditCtx,
+        id: String,
+        email: String,
+        name: String,
+    ) -> Result<bool> {
+        let query = format!(
+            r#"
             UPDATE users
             SET
-                deleted_at = :deleted_at
-            WHERE
-                id = :id
-                AND deleted_at IS NULL
-        "#;
+                email = :email,
+                name = :name,
+                status = 'inactive',
+                {},
+                updated_by = :updated_by
+            WHERE id = :id
+        "#,
+            NEXT_VERSION_SQL
+        );
 
-        let deleted_at = chrono::Utc::now().timestamp_millis();
         let mut q_params = new_query_params();
-        q_params.push(integer_param(":deleted_at", deleted_at));
+        q_params.push(text_param(":email", email));
+        q_params.push(text_param(":name", name));
+        q_params.push(integer_param(
+            ":updated_at",
+            chrono::Utc::now().timestamp_millis(),
+        ));
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
         q_params.push(text_param(":id", id));
 
         let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
         let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
         Ok(affected > 0)
     }
+
+    #[instrument(level = "debug", name = "db.user.delete", skip_all)]
+    pub async fn delete(&self, audit: &AuditCtx, id: String) -> Result<bool> {
+        self.soft_delete(audit, id).await
+    }
 }
+
+impl SoftDelete for UserRepo {
+    const TABLE: &'static str = "users";
+
+    fn connection(&self) -> &Connection {
+        &self.db_pool
+    }
+}
+
+impl Versioned for UserRepo {}
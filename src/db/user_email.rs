@@ -0,0 +1,300 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{
+    FromTursoRow, collect_count, collect_row, collect_rows, opt_row_integer, opt_row_text,
+    row_integer, row_text,
+};
+use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::dto::{NewUserEmailDto, UserEmailDto};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu, DbTransactionSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for UserEmailDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            user_id: row_text(row, 1)?,
+            email: row_text(row, 2)?,
+            verification_code: opt_row_text(row, 3)?,
+            verified_at: opt_row_integer(row, 4)?,
+            created_at: row_integer(row, 5)?,
+        })
+    }
+}
+
+pub struct UserEmailRepo {
+    db_pool: Connection,
+}
+
+impl UserEmailRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.user_email.list_by_user", skip_all)]
+    pub async fn list_by_user(&self, user_id: String) -> Result<Vec<UserEmailDto>> {
+        let query = r#"
+            SELECT
+                id,
+                user_id,
+                email,
+                verification_code,
+                verified_at,
+                created_at
+            FROM user_emails
+            WHERE
+                user_id = :user_id
+            ORDER BY email ASC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<UserEmailDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.user_email.count_by_user", skip_all)]
+    pub async fn count_by_user(&self, user_id: String) -> Result<i64> {
+        let query = r#"
+            SELECT COUNT(*) AS total_count
+            FROM user_emails
+            WHERE
+                user_id = :user_id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        collect_count(row_result)
+    }
+
+    #[instrument(level = "debug", name = "db.user_email.get", skip_all)]
+    pub async fn get(&self, user_id: String, id: String) -> Result<Option<UserEmailDto>> {
+        let query = r#"
+            SELECT
+                id,
+                user_id,
+                email,
+                verification_code,
+                verified_at,
+                created_at
+            FROM user_emails
+            WHERE
+                id = :id
+                AND user_id = :user_id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<UserEmailDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Finds the email regardless of owner or verification state
+    #[instrument(level = "debug", name = "db.user_email.find_by_email", skip_all)]
+    pub async fn find_by_email(&self, email: String) -> Result<Option<UserEmailDto>> {
+        let query = r#"
+            SELECT
+                id,
+                user_id,
+                email,
+                verification_code,
+                verified_at,
+                created_at
+            FROM user_emails
+            WHERE
+                email = :email
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":email", email));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<UserEmailDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.user_email.create", skip_all)]
+    pub async fn create(
+        &self,
+        user_id: String,
+        data: NewUserEmailDto,
+        verification_code: String,
+    ) -> Result<UserEmailDto> {
+        let query = r#"
+            INSERT INTO user_emails
+            (
+                id,
+                user_id,
+                email,
+                verification_code,
+                verified_at,
+                created_at
+            )
+            VALUES
+            (
+                :id,
+                :user_id,
+                :email,
+                :verification_code,
+                NULL,
+                :created_at
+            )
+        "#;
+
+        let id = generate_id(IdPrefix::UserEmail);
+        let today = chrono::Utc::now().timestamp_millis();
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id.clone()));
+        q_params.push(text_param(":user_id", user_id.clone()));
+        q_params.push(text_param(":email", data.email.clone()));
+        q_params.push(text_param(":verification_code", verification_code.clone()));
+        q_params.push(integer_param(":created_at", today));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        assert!(affected > 0, "Must insert a new row");
+
+        Ok(UserEmailDto {
+            id,
+            user_id,
+            email: data.email,
+            verification_code: Some(verification_code),
+            verified_at: None,
+            created_at: today,
+        })
+    }
+
+    #[instrument(level = "debug", name = "db.user_email.mark_verified", skip_all)]
+    pub async fn mark_verified(&self, id: String) -> Result<bool> {
+        let query = r#"
+            UPDATE user_emails
+            SET
+                verification_code = NULL,
+                verified_at = :verified_at
+            WHERE
+                id = :id
+                AND verified_at IS NULL
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(
+            ":verified_at",
+            chrono::Utc::now().timestamp_millis(),
+        ));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(affected > 0)
+    }
+
+    #[instrument(level = "debug", name = "db.user_email.delete", skip_all)]
+    pub async fn delete(&self, user_id: String, id: String) -> Result<bool> {
+        let query = r#"
+            DELETE FROM user_emails
+            WHERE
+                id = :id
+                AND user_id = :user_id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(affected > 0)
+    }
+
+    /// Swaps the user's primary email with a verified secondary email
+    #[instrument(level = "debug", name = "db.user_email.promote", skip_all)]
+    pub async fn promote(
+        &self,
+        user_id: String,
+        id: String,
+        primary_email: String,
+        secondary_email: String,
+    ) -> Result<bool> {
+        let today = chrono::Utc::now().timestamp_millis();
+
+        let email_query = r#"
+            UPDATE user_emails
+            SET
+                email = :email
+            WHERE
+                id = :id
+                AND user_id = :user_id
+                AND email = :current_email
+                AND verified_at IS NOT NULL
+        "#;
+
+        let mut email_params = new_query_params();
+        email_params.push(text_param(":email", primary_email.clone()));
+        email_params.push(text_param(":id", id));
+        email_params.push(text_param(":user_id", user_id.clone()));
+        email_params.push(text_param(":current_email", secondary_email.clone()));
+
+        let user_query = r#"
+            UPDATE users
+            SET
+                email = :email,
+                updated_at = :updated_at
+            WHERE
+                id = :id
+                AND email = :current_email
+                AND deleted_at IS NULL
+        "#;
+
+        let mut user_params = new_query_params();
+        user_params.push(text_param(":email", secondary_email));
+        user_params.push(integer_param(":updated_at", today));
+        user_params.push(text_param(":id", user_id));
+        user_params.push(text_param(":current_email", primary_email));
+
+        let mut conn = self.db_pool.clone();
+        let tx = conn.transaction().await.context(DbTransactionSnafu)?;
+
+        let mut email_stmt = tx.prepare(email_query).await.context(DbPrepareSnafu)?;
+        let email_affected = email_stmt
+            .execute(email_params)
+            .await
+            .context(DbStatementSnafu)?;
+
+        if email_affected == 0 {
+            tx.rollback().await.context(DbTransactionSnafu)?;
+            return Ok(false);
+        }
+
+        let mut user_stmt = tx.prepare(user_query).await.context(DbPrepareSnafu)?;
+        let user_affected = user_stmt
+            .execute(user_params)
+            .await
+            .context(DbStatementSnafu)?;
+
+        if user_affected == 0 {
+            tx.rollback().await.context(DbTransactionSnafu)?;
+            return Ok(false);
+        }
+
+        tx.commit().await.context(DbTransactionSnafu)?;
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,149 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::Connection;
+
+use crate::Result;
+use crate::db::turso_params::{integer_param, new_query_params, text_param};
+use crate::dto::{DELIVERY_FAILED, DELIVERY_PENDING};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+
+/// Rows keyed by the user that hold credentials or personal data.
+///
+/// Memberships, the org access log, impersonations, approvals and role
+/// elevations are history other records point to and are kept. So are token
+/// revocations, they keep rejecting tokens issued before the erasure.
+const USER_DATA_TABLES: [(&str, &str); 13] = [
+    ("passwords", "id"),
+    ("user_emails", "user_id"),
+    ("user_sessions", "user_id"),
+    ("user_profiles", "user_id"),
+    ("notification_prefs", "user_id"),
+    ("user_notifications", "user_id"),
+    ("oauth_grants", "user_id"),
+    ("oauth_codes", "user_id"),
+    ("recovery_tokens", "user_id"),
+    ("password_resets", "user_id"),
+    ("form_drafts", "user_id"),
+    ("idempotency_keys", "user_id"),
+    ("user_exports", "user_id"),
+];
+
+/// Recorded on the lifecycle deliveries cancelled by an erasure
+const ERASED_DELIVERY_ERROR: &str = "Cancelled, the user was erased";
+
+pub struct UserErasureRepo {
+    db_pool: Connection,
+}
+
+impl UserErasureRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    /// Removes the credentials and personal data of the user, returns the rows removed
+    #[instrument(level = "debug", name = "db.user_erasure.purge_user_data", skip_all)]
+    pub async fn purge_user_data(&self, user_id: String) -> Result<u64> {
+        let mut removed = 0;
+
+        for (table, column) in USER_DATA_TABLES.iter() {
+            let query = format!("DELETE FROM {} WHERE {} = :user_id", table, column);
+
+            let mut q_params = new_query_params();
+            q_params.push(text_param(":user_id", user_id.clone()));
+
+            let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+            removed += stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        }
+
+        Ok(removed)
+    }
+
+    /// Drops queued emails to the address and points invitations sent to it to
+    /// the tombstone, pending invitations expire
+    #[instrument(level = "debug", name = "db.user_erasure.scrub_email", skip_all)]
+    pub async fn scrub_email(&self, email: String, tombstone: String, now: i64) -> Result<()> {
+        let outbox_query = r#"
+            DELETE FROM email_outbox
+            WHERE recipient = :email
+        "#;
+
+        let mut outbox_params = new_query_params();
+        outbox_params.push(text_param(":email", email.clone()));
+
+        let mut stmt = self
+            .db_pool
+            .prepare(outbox_query)
+            .await
+            .context(DbPrepareSnafu)?;
+        stmt.execute(outbox_params)
+            .await
+            .context(DbStatementSnafu)?;
+
+        let invitations_query = r#"
+            UPDATE org_invitations
+            SET
+                email = :tombstone,
+                expires_at = CASE
+                    WHEN accepted_at IS NULL AND expires_at > :now THEN :now
+                    ELSE expires_at
+                END,
+                updated_at = :now
+            WHERE email = :email
+        "#;
+
+        let mut invitations_params = new_query_params();
+        invitations_params.push(text_param(":tombstone", tombstone));
+        invitations_params.push(integer_param(":now", now));
+        invitations_params.push(text_param(":email", email));
+
+        let mut stmt = self
+            .db_pool
+            .prepare(invitations_query)
+            .await
+            .context(DbPrepareSnafu)?;
+        stmt.execute(invitations_params)
+            .await
+            .context(DbStatementSnafu)?;
+
+        Ok(())
+    }
+
+    /// Puts the tombstone in the lifecycle payloads about the user and cancels
+    /// the deliveries not sent yet, returns the deliveries scrubbed
+    #[instrument(
+        level = "debug",
+        name = "db.user_erasure.scrub_lifecycle_deliveries",
+        skip_all
+    )]
+    pub async fn scrub_lifecycle_deliveries(
+        &self,
+        user_id: String,
+        tombstone: String,
+        name: String,
+        now: i64,
+    ) -> Result<u64> {
+        let query = r#"
+            UPDATE lifecycle_deliveries
+            SET
+                payload = json_set(payload, '$.user.email', :tombstone, '$.user.name', :name),
+                status = CASE WHEN status = :pending THEN :failed ELSE status END,
+                last_error = CASE WHEN status = :pending THEN :cancelled ELSE last_error END,
+                updated_at = :now
+            WHERE json_extract(payload, '$.external_ids.yaas_user_id') = :user_id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":tombstone", tombstone));
+        q_params.push(text_param(":name", name));
+        q_params.push(text_param(":pending", DELIVERY_PENDING.to_string()));
+        q_params.push(text_param(":failed", DELIVERY_FAILED.to_string()));
+        q_params.push(text_param(":cancelled", ERASED_DELIVERY_ERROR.to_string()));
+        q_params.push(integer_param(":now", now));
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let scrubbed = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(scrubbed)
+    }
+}
@@ -0,0 +1,261 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{
+    FromTursoRow, collect_row, opt_row_blob, opt_row_integer, opt_row_text, row_integer, row_text,
+};
+use crate::db::turso_params::{
+    blob_param, integer_param, new_query_params, opt_text_param, text_param,
+};
+use crate::dto::{UserExportDto, UserExportFormat, UserExportStatus};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for UserExportDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            user_id: row_text(row, 1)?,
+            requested_by: opt_row_text(row, 2)?,
+            format: UserExportFormat::try_from(row_text(row, 3)?.as_str())?,
+            status: UserExportStatus::try_from(row_text(row, 4)?.as_str())?,
+            size: opt_row_integer(row, 5)?,
+            created_at: row_integer(row, 6)?,
+            completed_at: opt_row_integer(row, 7)?,
+            expires_at: opt_row_integer(row, 8)?,
+            download_url: None,
+        })
+    }
+}
+
+struct UserExportContent {
+    content: Option<Vec<u8>>,
+}
+
+impl FromTursoRow for UserExportContent {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            content: opt_row_blob(row, 0)?,
+        })
+    }
+}
+
+const USER_EXPORT_COLUMNS: &str = r#"
+    id,
+    user_id,
+    requested_by,
+    format,
+    status,
+    size,
+    created_at,
+    completed_at,
+    expires_at
+"#;
+
+pub struct UserExportRepo {
+    db_pool: Connection,
+}
+
+impl UserExportRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.user_export.get", skip_all)]
+    pub async fn get(&self, id: String) -> Result<Option<UserExportDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM user_exports
+            WHERE id = :id
+            LIMIT 1
+        "#,
+            USER_EXPORT_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<UserExportDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Latest export of the user in the given format
+    #[instrument(level = "debug", name = "db.user_export.find_latest", skip_all)]
+    pub async fn find_latest(
+        &self,
+        user_id: String,
+        format: UserExportFormat,
+    ) -> Result<Option<UserExportDto>> {
+        let query = format!(
+            r#"
+            SELECT {}
+            FROM user_exports
+            WHERE
+                user_id = :user_id
+                AND format = :format
+            ORDER BY created_at DESC
+            LIMIT 1
+        "#,
+            USER_EXPORT_COLUMNS
+        );
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(text_param(":format", format.to_string()));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<UserExportDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    /// Archive of a ready export
+    #[instrument(level = "debug", name = "db.user_export.get_content", skip_all)]
+    pub async fn get_content(&self, id: String) -> Result<Option<Vec<u8>>> {
+        let query = r#"
+            SELECT content
+            FROM user_exports
+            WHERE id = :id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let row: Option<UserExportContent> = collect_row(row_result)?;
+        Ok(row.and_then(|row| row.content))
+    }
+
+    #[instrument(level = "debug", name = "db.user_export.create", skip_all)]
+    pub async fn create(
+        &self,
+        user_id: String,
+        requested_by: Option<String>,
+        format: UserExportFormat,
+    ) -> Result<UserExportDto> {
+        let query = r#"
+            INSERT INTO user_exports
+            (
+                id,
+                user_id,
+                requested_by,
+                format,
+                status,
+                content,
+                size,
+                created_at,
+                completed_at,
+                expires_at
+            )
+            VALUES
+            (
+                :id,
+                :user_id,
+                :requested_by,
+                :format,
+                :status,
+                NULL,
+                NULL,
+                :created_at,
+                NULL,
+                NULL
+            )
+        "#;
+
+        let id = generate_id(IdPrefix::UserExport);
+        let today = chrono::Utc::now().timestamp_millis();
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id.clone()));
+        q_params.push(text_param(":user_id", user_id.clone()));
+        q_params.push(opt_text_param(":requested_by", requested_by.clone()));
+        q_params.push(text_param(":format", format.to_string()));
+        q_params.push(text_param(":status", UserExportStatus::Pending.to_string()));
+        q_params.push(integer_param(":created_at", today));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        assert!(affected > 0, "Must insert a new user export row");
+
+        Ok(UserExportDto {
+            id,
+            user_id,
+            requested_by,
+            format,
+            status: UserExportStatus::Pending,
+            size: None,
+            created_at: today,
+            completed_at: None,
+            expires_at: None,
+            download_url: None,
+        })
+    }
+
+    /// Stores the archive of a pending export, returns false when it is gone
+    #[instrument(level = "debug", name = "db.user_export.complete", skip_all)]
+    pub async fn complete(
+        &self,
+        id: String,
+        content: Vec<u8>,
+        now: i64,
+        expires_at: i64,
+    ) -> Result<bool> {
+        let query = r#"
+            UPDATE user_exports
+            SET
+                status = :status,
+                content = :content,
+                size = :size,
+                completed_at = :now,
+                expires_at = :expires_at
+            WHERE id = :id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":status", UserExportStatus::Ready.to_string()));
+        q_params.push(integer_param(":size", content.len() as i64));
+        q_params.push(blob_param(":content", content));
+        q_params.push(integer_param(":now", now));
+        q_params.push(integer_param(":expires_at", expires_at));
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+
+    /// Removes the earlier exports of the user in the format, only the latest one is kept
+    #[instrument(level = "debug", name = "db.user_export.delete_others", skip_all)]
+    pub async fn delete_others(
+        &self,
+        user_id: String,
+        format: UserExportFormat,
+        keep_id: String,
+    ) -> Result<u64> {
+        let query = r#"
+            DELETE FROM user_exports
+            WHERE
+                user_id = :user_id
+                AND format = :format
+                AND id != :keep_id
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(text_param(":format", format.to_string()));
+        q_params.push(text_param(":keep_id", keep_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected)
+    }
+}
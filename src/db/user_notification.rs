@@ -0,0 +1,189 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{
+    FromTursoRow, collect_count, collect_rows, opt_row_integer, opt_row_text, row_integer, row_text,
+};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::dto::{NewUserNotificationDto, UserNotificationDto, UserNotificationKind};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+use crate::utils::{IdPrefix, generate_id};
+
+impl FromTursoRow for UserNotificationDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            user_id: row_text(row, 1)?,
+            kind: UserNotificationKind::try_from(row_text(row, 2)?.as_str())?,
+            title: row_text(row, 3)?,
+            body: row_text(row, 4)?,
+            link: opt_row_text(row, 5)?,
+            read_at: opt_row_integer(row, 6)?,
+            created_at: row_integer(row, 7)?,
+        })
+    }
+}
+
+pub struct UserNotificationRepo {
+    db_pool: Connection,
+}
+
+impl UserNotificationRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    /// Latest notifications of the user, newest first
+    #[instrument(level = "debug", name = "db.user_notification.list", skip_all)]
+    pub async fn list(&self, user_id: String, limit: i64) -> Result<Vec<UserNotificationDto>> {
+        let query = r#"
+            SELECT
+                id,
+                user_id,
+                kind,
+                title,
+                body,
+                link,
+                read_at,
+                created_at
+            FROM user_notifications
+            WHERE
+                user_id = :user_id
+            ORDER BY created_at DESC, id DESC
+            LIMIT :limit
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(integer_param(":limit", limit));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<UserNotificationDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    #[instrument(level = "debug", name = "db.user_notification.count_unread", skip_all)]
+    pub async fn count_unread(&self, user_id: String) -> Result<i64> {
+        let query = r#"
+            SELECT COUNT(*) AS total_count
+            FROM user_notifications
+            WHERE
+                user_id = :user_id
+                AND read_at IS NULL
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        collect_count(row_result)
+    }
+
+    #[instrument(level = "debug", name = "db.user_notification.create", skip_all)]
+    pub async fn create(
+        &self,
+        data: NewUserNotificationDto,
+        now: i64,
+    ) -> Result<UserNotificationDto> {
+        let query = r#"
+            INSERT INTO user_notifications
+            (
+                id,
+                user_id,
+                kind,
+                title,
+                body,
+                link,
+                created_at
+            )
+            VALUES
+            (
+                :id,
+                :user_id,
+                :kind,
+                :title,
+                :body,
+                :link,
+                :created_at
+            )
+        "#;
+
+        let notification = UserNotificationDto {
+            id: generate_id(IdPrefix::UserNotification),
+            user_id: data.user_id,
+            kind: data.kind,
+            title: data.title,
+            body: data.body,
+            link: data.link,
+            read_at: None,
+            created_at: now,
+        };
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", notification.id.clone()));
+        q_params.push(text_param(":user_id", notification.user_id.clone()));
+        q_params.push(text_param(":kind", notification.kind.to_string()));
+        q_params.push(text_param(":title", notification.title.clone()));
+        q_params.push(text_param(":body", notification.body.clone()));
+        q_params.push(opt_text_param(":link", notification.link.clone()));
+        q_params.push(integer_param(":created_at", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        assert!(affected > 0, "Must insert a user notification row");
+
+        Ok(notification)
+    }
+
+    /// Marks one unread notification of the user as read.
+    ///
+    /// Returns false when there is no such unread notification.
+    #[instrument(level = "debug", name = "db.user_notification.mark_read", skip_all)]
+    pub async fn mark_read(&self, user_id: String, id: String, now: i64) -> Result<bool> {
+        let query = r#"
+            UPDATE user_notifications
+            SET
+                read_at = :now
+            WHERE
+                id = :id
+                AND user_id = :user_id
+                AND read_at IS NULL
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+
+    /// Marks every unread notification of the user as read, returns how many
+    #[instrument(level = "debug", name = "db.user_notification.mark_all_read", skip_all)]
+    pub async fn mark_all_read(&self, user_id: String, now: i64) -> Result<u64> {
+        let query = r#"
+            UPDATE user_notifications
+            SET
+                read_at = :now
+            WHERE
+                user_id = :user_id
+                AND read_at IS NULL
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected)
+    }
+}
@@ -0,0 +1,114 @@
+use serde_json::{Map, Value};
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{FromTursoRow, collect_row, opt_row_text, row_text};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::dto::UserProfileDto;
+use crate::error::{DbPrepareSnafu, DbStatementSnafu, JsonParseSnafu, JsonSerializeSnafu};
+
+impl FromTursoRow for UserProfileDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        let metadata: Map<String, Value> =
+            serde_json::from_str(&row_text(row, 5)?).context(JsonParseSnafu)?;
+
+        Ok(Self {
+            user_id: row_text(row, 0)?,
+            avatar_url: opt_row_text(row, 1)?,
+            locale: opt_row_text(row, 2)?,
+            timezone: opt_row_text(row, 3)?,
+            phone: opt_row_text(row, 4)?,
+            metadata,
+        })
+    }
+}
+
+pub struct UserProfileRepo {
+    db_pool: Connection,
+}
+
+impl UserProfileRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    #[instrument(level = "debug", name = "db.user_profile.get", skip_all)]
+    pub async fn get(&self, user_id: String) -> Result<Option<UserProfileDto>> {
+        let query = r#"
+            SELECT
+                user_id,
+                avatar_url,
+                locale,
+                timezone,
+                phone,
+                metadata
+            FROM user_profiles
+            WHERE
+                user_id = :user_id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<UserProfileDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.user_profile.upsert", skip_all)]
+    pub async fn upsert(&self, data: UserProfileDto) -> Result<UserProfileDto> {
+        let today = chrono::Utc::now().timestamp_millis();
+        let metadata = serde_json::to_string(&data.metadata).context(JsonSerializeSnafu)?;
+
+        let query = r#"
+            INSERT INTO user_profiles
+            (
+                user_id,
+                avatar_url,
+                locale,
+                timezone,
+                phone,
+                metadata,
+                created_at,
+                updated_at
+            )
+            VALUES
+            (
+                :user_id,
+                :avatar_url,
+                :locale,
+                :timezone,
+                :phone,
+                :metadata,
+                :created_at,
+                :updated_at
+            )
+            ON CONFLICT (user_id) DO UPDATE SET
+                avatar_url = excluded.avatar_url,
+                locale = excluded.locale,
+                timezone = excluded.timezone,
+                phone = excluded.phone,
+                metadata = excluded.metadata,
+                updated_at = excluded.updated_at
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", data.user_id.clone()));
+        q_params.push(opt_text_param(":avatar_url", data.avatar_url.clone()));
+        q_params.push(opt_text_param(":locale", data.locale.clone()));
+        q_params.push(opt_text_param(":timezone", data.timezone.clone()));
+        q_params.push(opt_text_param(":phone", data.phone.clone()));
+        q_params.push(text_param(":metadata", metadata));
+        q_params.push(integer_param(":created_at", today));
+        q_params.push(integer_param(":updated_at", today));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(data)
+    }
+}
@@ -0,0 +1,211 @@
+use snafu::ResultExt;
+use tracing::instrument;
+use turso::{Connection, Row};
+
+use crate::Result;
+use crate::db::turso_decode::{
+    FromTursoRow, collect_row, collect_rows, opt_row_integer, opt_row_text, row_integer, row_text,
+};
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::dto::UserSessionDto;
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+
+/// Sessions are touched at most once per minute
+const TOUCH_INTERVAL_MS: i64 = 60 * 1000;
+
+impl FromTursoRow for UserSessionDto {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            id: row_text(row, 0)?,
+            user_id: row_text(row, 1)?,
+            user_agent: opt_row_text(row, 2)?,
+            ip_address: opt_row_text(row, 3)?,
+            created_at: row_integer(row, 4)?,
+            last_used_at: row_integer(row, 5)?,
+            expires_at: row_integer(row, 6)?,
+            revoked_at: opt_row_integer(row, 7)?,
+            current: false,
+        })
+    }
+}
+
+pub struct UserSessionRepo {
+    db_pool: Connection,
+}
+
+impl UserSessionRepo {
+    pub fn new(db_pool: Connection) -> Self {
+        Self { db_pool }
+    }
+
+    /// Sessions of the user that are neither revoked nor expired, recently used first
+    #[instrument(level = "debug", name = "db.user_session.list_active", skip_all)]
+    pub async fn list_active(&self, user_id: String, now: i64) -> Result<Vec<UserSessionDto>> {
+        let query = r#"
+            SELECT
+                id,
+                user_id,
+                user_agent,
+                ip_address,
+                created_at,
+                last_used_at,
+                expires_at,
+                revoked_at
+            FROM user_sessions
+            WHERE
+                user_id = :user_id
+                AND revoked_at IS NULL
+                AND expires_at > :now
+            ORDER BY last_used_at DESC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+        q_params.push(integer_param(":now", now));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        let items: Vec<UserSessionDto> = collect_rows(&mut rows).await?;
+        Ok(items)
+    }
+
+    /// Every session of the user, revoked and expired ones included, newest first
+    #[instrument(level = "debug", name = "db.user_session.list_by_user", skip_all)]
+    pub async fn list_by_user(&self, user_id: String) -> Result<Vec<UserSessionDto>> {
+        let query = r#"
+            SELECT
+                id,
+                user_id,
+                user_agent,
+                ip_address,
+                created_at,
+                last_used_at,
+                expires_at,
+                revoked_at
+            FROM user_sessions
+            WHERE user_id = :user_id
+            ORDER BY created_at DESC
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let mut rows = stmt.query(q_params).await.context(DbStatementSnafu)?;
+        collect_rows(&mut rows).await
+    }
+
+    #[instrument(level = "debug", name = "db.user_session.get", skip_all)]
+    pub async fn get(&self, id: String) -> Result<Option<UserSessionDto>> {
+        let query = r#"
+            SELECT
+                id,
+                user_id,
+                user_agent,
+                ip_address,
+                created_at,
+                last_used_at,
+                expires_at,
+                revoked_at
+            FROM user_sessions
+            WHERE
+                id = :id
+            LIMIT 1
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let row_result = stmt.query_row(q_params).await;
+        let dto: Option<UserSessionDto> = collect_row(row_result)?;
+        Ok(dto)
+    }
+
+    #[instrument(level = "debug", name = "db.user_session.create", skip_all)]
+    pub async fn create(&self, data: UserSessionDto) -> Result<UserSessionDto> {
+        let query = r#"
+            INSERT INTO user_sessions
+            (
+                id,
+                user_id,
+                user_agent,
+                ip_address,
+                created_at,
+                last_used_at,
+                expires_at
+            )
+            VALUES
+            (
+                :id,
+                :user_id,
+                :user_agent,
+                :ip_address,
+                :created_at,
+                :last_used_at,
+                :expires_at
+            )
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(text_param(":id", data.id.clone()));
+        q_params.push(text_param(":user_id", data.user_id.clone()));
+        q_params.push(opt_text_param(":user_agent", data.user_agent.clone()));
+        q_params.push(opt_text_param(":ip_address", data.ip_address.clone()));
+        q_params.push(integer_param(":created_at", data.created_at));
+        q_params.push(integer_param(":last_used_at", data.last_used_at));
+        q_params.push(integer_param(":expires_at", data.expires_at));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(data)
+    }
+
+    /// Records the session as used, skipped when it was used within the last minute
+    #[instrument(level = "debug", name = "db.user_session.touch", skip_all)]
+    pub async fn touch(&self, id: String, now: i64) -> Result<()> {
+        let query = r#"
+            UPDATE user_sessions
+            SET
+                last_used_at = :now
+            WHERE
+                id = :id
+                AND last_used_at <= :stale_before
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":now", now));
+        q_params.push(text_param(":id", id));
+        q_params.push(integer_param(":stale_before", now - TOUCH_INTERVAL_MS));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        stmt.execute(q_params).await.context(DbStatementSnafu)?;
+        Ok(())
+    }
+
+    /// Revokes one of the user's sessions, returns false when there was none to revoke
+    #[instrument(level = "debug", name = "db.user_session.revoke", skip_all)]
+    pub async fn revoke(&self, user_id: String, id: String, now: i64) -> Result<bool> {
+        let query = r#"
+            UPDATE user_sessions
+            SET
+                revoked_at = :now
+            WHERE
+                id = :id
+                AND user_id = :user_id
+                AND revoked_at IS NULL
+                AND expires_at > :now
+        "#;
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":now", now));
+        q_params.push(text_param(":id", id));
+        q_params.push(text_param(":user_id", user_id));
+
+        let mut stmt = self.db_pool.prepare(query).await.context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+}
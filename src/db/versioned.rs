@@ -0,0 +1,45 @@
+use snafu::ResultExt;
+
+use crate::Result;
+use crate::ctx::AuditCtx;
+use crate::db::soft_delete::SoftDelete;
+use crate::db::turso_params::{integer_param, new_query_params, opt_text_param, text_param};
+use crate::error::{DbPrepareSnafu, DbStatementSnafu};
+
+/// Moves `updated_at` forward even when two writes land in the same millisecond
+pub const NEXT_VERSION_SQL: &str = "updated_at = MAX(:updated_at, updated_at + 1)";
+
+pub fn claim_version_query(table: &str) -> String {
+    format!(
+        "UPDATE {} SET {}, updated_by = :updated_by WHERE id = :id AND updated_at = :version AND deleted_at IS NULL",
+        table, NEXT_VERSION_SQL
+    )
+}
+
+/// Repos whose rows use `updated_at` as their version.
+///
+/// A client that edits a row it read earlier first claims the version it
+/// saw. The claim is a compare-and-swap, it fails once anyone else wrote to
+/// the row in between, and the edit is rejected instead of overwriting theirs.
+pub trait Versioned: SoftDelete {
+    /// Returns false when the row is gone or no longer at `version`
+    async fn claim_version(&self, audit: &AuditCtx, id: String, version: i64) -> Result<bool> {
+        let query = claim_version_query(Self::TABLE);
+        let updated_at = chrono::Utc::now().timestamp_millis();
+
+        let mut q_params = new_query_params();
+        q_params.push(integer_param(":updated_at", updated_at));
+        q_params.push(opt_text_param(":updated_by", audit.actor_id.clone()));
+        q_params.push(text_param(":id", id));
+        q_params.push(integer_param(":version", version));
+
+        let mut stmt = self
+            .connection()
+            .prepare(&query)
+            .await
+            .context(DbPrepareSnafu)?;
+        let affected = stmt.execute(q_params).await.context(DbStatementSnafu)?;
+
+        Ok(affected > 0)
+    }
+}
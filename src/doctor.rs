@@ -0,0 +1,411 @@
+use chrono::{DateTime, Utc};
+use reqwest::ClientBuilder;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::db::{create_db_mapper, migration_tables};
+use crate::services::token::{create_csrf_token_svc, verify_csrf_token};
+use crate::utils::{IdPrefix, generate_id};
+
+const DEFAULT_TIME_URL: &str = "https://www.google.com";
+const CLOCK_SKEW_WARN_SECS: i64 = 30;
+const CLOCK_SKEW_FAIL_SECS: i64 = 300;
+const MIN_JWT_SECRET_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindingStatus {
+    Pass,
+    Skip,
+    Warn,
+    Fail,
+}
+
+impl core::fmt::Display for FindingStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            FindingStatus::Pass => write!(f, "PASS"),
+            FindingStatus::Skip => write!(f, "SKIP"),
+            FindingStatus::Warn => write!(f, "WARN"),
+            FindingStatus::Fail => write!(f, "FAIL"),
+        }
+    }
+}
+
+pub struct Finding {
+    pub check: &'static str,
+    pub status: FindingStatus,
+    pub message: String,
+}
+
+impl Finding {
+    fn new(check: &'static str, status: FindingStatus, message: impl Into<String>) -> Self {
+        Self {
+            check,
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+/// Runs all checks, prints the findings and returns the process exit code.
+///
+/// Exit codes: `0` all checks passed, `1` at least one check failed,
+/// `2` no failures but at least one warning.
+pub async fn run_doctor() -> i32 {
+    let findings = collect_findings().await;
+
+    for finding in findings.iter() {
+        println!(
+            "[{}] {}: {}",
+            finding.status, finding.check, finding.message
+        );
+    }
+
+    exit_code(&findings)
+}
+
+pub fn exit_code(findings: &[Finding]) -> i32 {
+    if findings.iter().any(|f| f.status == FindingStatus::Fail) {
+        return 1;
+    }
+    if findings.iter().any(|f| f.status == FindingStatus::Warn) {
+        return 2;
+    }
+    0
+}
+
+async fn collect_findings() -> Vec<Finding> {
+    let mut findings: Vec<Finding> = Vec::new();
+
+    let config = match Config::build() {
+        Ok(config) => {
+            findings.push(Finding::new(
+                "config",
+                FindingStatus::Pass,
+                "Configuration is valid",
+            ));
+            config
+        }
+        Err(e) => {
+            findings.push(Finding::new(
+                "config",
+                FindingStatus::Fail,
+                format!("{}. Fix the environment variables and run again.", e),
+            ));
+            return findings;
+        }
+    };
+
+    findings.extend(check_database(&config).await);
+    findings.push(check_writable_dir("temp_dir", &std::env::temp_dir()));
+    findings.push(check_writable_dir(
+        "database_dir",
+        &config.db.dir.join("default"),
+    ));
+    findings.push(check_jwt_secret(&config.jwt_secret));
+    findings.push(check_smtp().await);
+    findings.push(check_clock_skew().await);
+
+    findings
+}
+
+async fn check_database(config: &Config) -> Vec<Finding> {
+    let db_file = config.db.dir.join("default").join("yaas.db");
+
+    if !db_file.exists() {
+        return vec![Finding::new(
+            "database",
+            FindingStatus::Fail,
+            format!(
+                "{} does not exist. Create it by running `yaas migrate`.",
+                db_file.display()
+            ),
+        )];
+    }
+
+    let db = match create_db_mapper(db_file.as_path(), &config.pagination).await {
+        Ok(db) => db,
+        Err(e) => {
+            return vec![Finding::new(
+                "database",
+                FindingStatus::Fail,
+                format!("Unable to open {}: {}", db_file.display(), e),
+            )];
+        }
+    };
+
+    let mut findings = Vec::new();
+
+    match db.orgs.test_read().await {
+        Ok(_) => findings.push(Finding::new(
+            "database",
+            FindingStatus::Pass,
+            format!("Connected to {}", db_file.display()),
+        )),
+        Err(e) => findings.push(Finding::new(
+            "database",
+            FindingStatus::Fail,
+            format!("Unable to read from {}: {}", db_file.display(), e),
+        )),
+    }
+
+    match db.schema.list_tables().await {
+        Ok(tables) => {
+            let missing: Vec<String> = migration_tables()
+                .into_iter()
+                .filter(|t| !tables.contains(t))
+                .collect();
+
+            if missing.is_empty() {
+                findings.push(Finding::new(
+                    "migrations",
+                    FindingStatus::Pass,
+                    "All migration tables exist",
+                ));
+            } else {
+                findings.push(Finding::new(
+                    "migrations",
+                    FindingStatus::Fail,
+                    format!(
+                        "Missing tables: {}. Apply the pending migrations with `yaas migrate`.",
+                        missing.join(", ")
+                    ),
+                ));
+            }
+        }
+        Err(e) => findings.push(Finding::new(
+            "migrations",
+            FindingStatus::Fail,
+            format!("Unable to list tables: {}", e),
+        )),
+    }
+
+    findings
+}
+
+fn check_writable_dir(check: &'static str, dir: &Path) -> Finding {
+    let probe = dir.join(format!(".doctor-{}", generate_id(IdPrefix::Password)));
+
+    match fs::write(&probe, b"ok") {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            Finding::new(
+                check,
+                FindingStatus::Pass,
+                format!("{} is writable", dir.display()),
+            )
+        }
+        Err(e) => Finding::new(
+            check,
+            FindingStatus::Fail,
+            format!(
+                "{} is not writable: {}. Check the directory ownership and permissions.",
+                dir.display(),
+                e
+            ),
+        ),
+    }
+}
+
+pub fn check_jwt_secret(secret: &str) -> Finding {
+    let roundtrip =
+        create_csrf_token_svc("doctor", secret).and_then(|token| verify_csrf_token(&token, secret));
+
+    match roundtrip {
+        Ok(subject) if subject == "doctor" => {
+            if secret.len() < MIN_JWT_SECRET_LEN {
+                Finding::new(
+                    "jwt_secret",
+                    FindingStatus::Warn,
+                    format!(
+                        "JWT_SECRET is shorter than {} bytes. Use a longer random value.",
+                        MIN_JWT_SECRET_LEN
+                    ),
+                )
+            } else {
+                Finding::new(
+                    "jwt_secret",
+                    FindingStatus::Pass,
+                    "Tokens can be signed and verified",
+                )
+            }
+        }
+        _ => Finding::new(
+            "jwt_secret",
+            FindingStatus::Fail,
+            "Unable to sign and verify tokens with JWT_SECRET",
+        ),
+    }
+}
+
+async fn check_smtp() -> Finding {
+    if std::env::var("MAILER").as_deref() != Ok("smtp") {
+        return Finding::new(
+            "smtp",
+            FindingStatus::Skip,
+            "MAILER is not smtp, emails are written to the log",
+        );
+    }
+
+    let Ok(host) = std::env::var("SMTP_HOST") else {
+        return Finding::new(
+            "smtp",
+            FindingStatus::Fail,
+            "SMTP_HOST is required when MAILER is smtp",
+        );
+    };
+
+    let default_port = match std::env::var("SMTP_TLS").as_deref() {
+        Ok("tls") => "465",
+        _ => "587",
+    };
+    let port = std::env::var("SMTP_PORT").unwrap_or_else(|_| default_port.to_string());
+    let address = format!("{}:{}", host, port);
+
+    let connect = tokio::time::timeout(
+        Duration::from_secs(5),
+        tokio::net::TcpStream::connect(&address),
+    )
+    .await;
+
+    match connect {
+        Ok(Ok(_)) => Finding::new(
+            "smtp",
+            FindingStatus::Pass,
+            format!("{} is reachable", address),
+        ),
+        Ok(Err(e)) => Finding::new(
+            "smtp",
+            FindingStatus::Fail,
+            format!("Unable to connect to {}: {}", address, e),
+        ),
+        Err(_) => Finding::new(
+            "smtp",
+            FindingStatus::Fail,
+            format!("Timed out connecting to {}. Check firewall rules.", address),
+        ),
+    }
+}
+
+async fn check_clock_skew() -> Finding {
+    let url = std::env::var("DOCTOR_TIME_URL").unwrap_or_else(|_| DEFAULT_TIME_URL.to_string());
+
+    let Ok(client) = ClientBuilder::new().timeout(Duration::from_secs(5)).build() else {
+        return Finding::new("clock", FindingStatus::Warn, "Unable to create HTTP client");
+    };
+
+    let remote = match client.head(&url).send().await {
+        Ok(res) => res
+            .headers()
+            .get("Date")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| DateTime::parse_from_rfc2822(value).ok()),
+        Err(e) => {
+            return Finding::new(
+                "clock",
+                FindingStatus::Warn,
+                format!("Unable to reach {} to compare clocks: {}", url, e),
+            );
+        }
+    };
+
+    let Some(remote) = remote else {
+        return Finding::new(
+            "clock",
+            FindingStatus::Warn,
+            format!("{} did not return a valid Date header", url),
+        );
+    };
+
+    clock_skew_finding((Utc::now() - remote.with_timezone(&Utc)).num_seconds())
+}
+
+pub fn clock_skew_finding(skew_secs: i64) -> Finding {
+    let skew = skew_secs.abs();
+
+    if skew >= CLOCK_SKEW_FAIL_SECS {
+        Finding::new(
+            "clock",
+            FindingStatus::Fail,
+            format!(
+                "Clock is off by {}s. Token expiry will misbehave, enable NTP sync.",
+                skew
+            ),
+        )
+    } else if skew >= CLOCK_SKEW_WARN_SECS {
+        Finding::new(
+            "clock",
+            FindingStatus::Warn,
+            format!("Clock is off by {}s. Consider enabling NTP sync.", skew),
+        )
+    } else {
+        Finding::new(
+            "clock",
+            FindingStatus::Pass,
+            format!("Clock skew is {}s", skew),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_code() {
+        let findings = vec![
+            Finding::new("a", FindingStatus::Pass, ""),
+            Finding::new("b", FindingStatus::Skip, ""),
+        ];
+        assert_eq!(exit_code(&findings), 0);
+
+        let findings = vec![
+            Finding::new("a", FindingStatus::Warn, ""),
+            Finding::new("b", FindingStatus::Pass, ""),
+        ];
+        assert_eq!(exit_code(&findings), 2);
+
+        let findings = vec![
+            Finding::new("a", FindingStatus::Warn, ""),
+            Finding::new("b", FindingStatus::Fail, ""),
+        ];
+        assert_eq!(exit_code(&findings), 1);
+    }
+
+    #[test]
+    fn test_check_jwt_secret() {
+        assert_eq!(check_jwt_secret("short").status, FindingStatus::Warn);
+        assert_eq!(
+            check_jwt_secret("a-sufficiently-long-secret-for-signing-tokens").status,
+            FindingStatus::Pass
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migration_tables_exist_after_migrations() {
+        let ctx = crate::test::TestCtx::new("doctor_migrations")
+            .await
+            .expect("test ctx");
+
+        let tables = ctx
+            .state
+            .db
+            .schema
+            .list_tables()
+            .await
+            .expect("tables should be listed");
+
+        for table in migration_tables() {
+            assert!(tables.contains(&table), "{} should exist", table);
+        }
+    }
+
+    #[test]
+    fn test_clock_skew_finding() {
+        assert_eq!(clock_skew_finding(3).status, FindingStatus::Pass);
+        assert_eq!(clock_skew_finding(-45).status, FindingStatus::Warn);
+        assert_eq!(clock_skew_finding(600).status, FindingStatus::Fail);
+    }
+}
@@ -2,7 +2,12 @@ use serde::{Deserialize, Serialize};
 use validator::Validate;
 
 use crate::dto::UserDto;
-use crate::dto::{Permission, Role, Scope, roles_permissions, to_permissions};
+use crate::dto::{
+    PERMISSION_MASK_VERSION, Permission, PermissionMask, Role, Scope, scopes_permission_mask,
+    to_permissions,
+};
+
+use crate::utils::{Redact, SparseFields, redacted_debug};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ActorDto {
@@ -13,6 +18,34 @@ pub struct ActorDto {
     pub user: UserDto,
     pub roles: Vec<Role>,
     pub permissions: Vec<Permission>,
+    /// Region that issued the token and the region its session lives in
+    pub region: Option<String>,
+    pub home_region: Option<String>,
+
+    /// Superuser acting as the user, see `ImpersonationDto`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonator_id: Option<String>,
+    #[serde(skip)]
+    pub impersonation_id: Option<String>,
+
+    /// Same permissions as a mask for the per request checks
+    #[serde(skip)]
+    pub permission_mask: u64,
+}
+
+impl SparseFields for ActorDto {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "org_id",
+        "org_count",
+        "scopes",
+        "user",
+        "roles",
+        "permissions",
+        "region",
+        "home_region",
+        "impersonator_id",
+    ];
 }
 
 #[derive(Clone)]
@@ -22,19 +55,93 @@ pub struct ActorPayloadDto {
     pub org_count: i32,
     pub roles: Vec<Role>,
     pub scopes: Vec<Scope>,
+    /// OAuth grant the token was issued under, if any
+    pub grant_id: Option<String>,
+    /// App proof key the token is bound to, if any
+    pub proof_key_id: Option<String>,
+    /// Permissions the token was issued with, if any
+    pub permission_mask: Option<PermissionMask>,
+    /// Unix timestamp the token was issued at, 0 when unknown
+    pub issued_at: i64,
+    /// Login session the token belongs to, if any
+    pub session_id: Option<String>,
+    /// Region that issued the token, if the deployment has one
+    pub region: Option<String>,
+    /// Region the session lives in, requests are routed back to it
+    pub home_region: Option<String>,
+    /// Unique id (`jti`) of a verified token, new tokens are given one when created
+    pub token_id: Option<String>,
+    /// Org app link of a client credentials token, `id` is then the app id
+    pub org_app_id: Option<String>,
+    /// Superuser acting as the user and the impersonation the token belongs to
+    pub impersonator_id: Option<String>,
+    pub impersonation_id: Option<String>,
+}
+
+impl ActorPayloadDto {
+    /// Whether the token roles must be reloaded from the membership.
+    ///
+    /// Tokens carrying roles without a current mask were issued before the
+    /// role definitions changed.
+    pub fn needs_role_refresh(&self) -> bool {
+        match &self.permission_mask {
+            Some(mask) => !mask.is_current(),
+            None => !self.roles.is_empty(),
+        }
+    }
+}
+
+/// Server-to-server app acting on an org it is linked to, there is no user
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AppActorDto {
+    pub app_id: String,
+    pub org_app_id: String,
+    pub org_id: String,
+    pub scopes: Vec<Scope>,
+    /// Derived from the org app link, see `ORG_APP_ROLES`
+    pub roles: Vec<Role>,
+    pub permissions: Vec<Permission>,
+
+    #[serde(skip)]
+    pub permission_mask: u64,
+}
+
+/// Database lookups behind a user actor, cached per user.
+///
+/// The actor itself is rebuilt from each token since tokens of the same user
+/// differ in scopes and permission masks.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AuthLookupDto {
+    pub org_id: String,
+    pub user: UserDto,
+    /// Current membership roles, loaded for tokens issued before the role definitions changed
+    pub member_roles: Option<Vec<Role>>,
+    /// Permissions of the member's custom org roles and teams, loaded for session tokens
+    pub custom_permissions: Option<Vec<Permission>>,
 }
 
+/// Roles an org app link grants to the app's client credentials tokens
+pub const ORG_APP_ROLES: [Role; 1] = [Role::OrgViewer];
+
+/// Acting principal, either a user or, for client credentials tokens, an app
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Actor {
     pub actor: Option<ActorDto>,
+    pub app: Option<AppActorDto>,
 }
 
 impl Actor {
+    /// User actor, the token scopes narrow the permissions of its roles
     pub fn new(payload: ActorPayloadDto, user: UserDto) -> Self {
-        let permissions: Vec<Permission> = roles_permissions(&payload.roles).into_iter().collect();
+        let mask = match payload.permission_mask {
+            Some(mask) if mask.is_current() => mask,
+            _ => PermissionMask::from_roles(&payload.roles),
+        }
+        .intersection(scopes_permission_mask(&payload.scopes));
 
         // Convert to string to allow sorting
-        let mut permissions: Vec<String> = permissions.iter().map(|p| p.to_string()).collect();
+        let mut permissions: Vec<String> =
+            mask.permissions().iter().map(|p| p.to_string()).collect();
         permissions.sort();
 
         // Convert again to Permission enum
@@ -42,6 +149,7 @@ impl Actor {
             to_permissions(&permissions).expect("Permissions should convert back to enum");
 
         Actor {
+            app: None,
             actor: Some(ActorDto {
                 id: payload.id,
                 org_id: payload.org_id,
@@ -50,32 +158,104 @@ impl Actor {
                 user,
                 roles: payload.roles,
                 permissions,
+                region: payload.region,
+                home_region: payload.home_region,
+                impersonator_id: payload.impersonator_id,
+                impersonation_id: payload.impersonation_id,
+                permission_mask: mask.bits,
             }),
         }
     }
 
-    pub fn has_auth_scope(&self) -> bool {
-        self.has_scope(Scope::Auth)
+    /// App actor of a client credentials token, permissions come from the org app link
+    pub fn for_app(payload: ActorPayloadDto, org_app_id: String) -> Self {
+        let mask = PermissionMask::from_roles(&ORG_APP_ROLES)
+            .intersection(scopes_permission_mask(&payload.scopes));
+
+        let mut permissions: Vec<String> =
+            mask.permissions().iter().map(|p| p.to_string()).collect();
+        permissions.sort();
+
+        let permissions: Vec<Permission> =
+            to_permissions(&permissions).expect("Permissions should convert back to enum");
+
+        Actor {
+            actor: None,
+            app: Some(AppActorDto {
+                app_id: payload.id,
+                org_app_id,
+                org_id: payload.org_id,
+                scopes: payload.scopes,
+                roles: ORG_APP_ROLES.to_vec(),
+                permissions,
+                permission_mask: mask.bits,
+            }),
+        }
     }
 
-    pub fn has_vault_scope(&self) -> bool {
-        self.has_scope(Scope::Vault)
+    /// Adds the permissions granted by the member's custom org roles
+    pub fn with_custom_permissions(self, custom: &[Permission]) -> Self {
+        let Some(mut actor) = self.actor else {
+            return self;
+        };
+        if custom.is_empty() {
+            return Actor {
+                actor: Some(actor),
+                app: None,
+            };
+        }
+
+        let mask = PermissionMask {
+            version: PERMISSION_MASK_VERSION,
+            bits: actor.permission_mask,
+        }
+        .union(PermissionMask::from_permissions(custom));
+
+        let mut permissions: Vec<String> =
+            mask.permissions().iter().map(|p| p.to_string()).collect();
+        permissions.sort();
+
+        actor.permissions =
+            to_permissions(&permissions).expect("Permissions should convert back to enum");
+        actor.permission_mask = mask.bits;
+
+        Actor {
+            actor: Some(actor),
+            app: None,
+        }
+    }
+
+    pub fn has_auth_scope(&self) -> bool {
+        self.has_scope(Scope::Auth)
     }
 
     pub fn has_scope(&self, scope: Scope) -> bool {
-        match &self.actor {
-            Some(actor) => actor.scopes.contains(&scope),
-            None => false,
+        match (&self.actor, &self.app) {
+            (Some(actor), _) => actor.scopes.contains(&scope),
+            (None, Some(app)) => app.scopes.contains(&scope),
+            (None, None) => false,
         }
     }
 
     pub fn has_permissions(&self, permissions: &[Permission]) -> bool {
-        match &self.actor {
-            Some(actor) => permissions
-                .iter()
-                .all(|permission| actor.permissions.contains(permission)),
-            None => false,
+        let bits = match (&self.actor, &self.app) {
+            (Some(actor), _) => actor.permission_mask,
+            (None, Some(app)) => app.permission_mask,
+            (None, None) => return false,
+        };
+
+        PermissionMask {
+            version: PERMISSION_MASK_VERSION,
+            bits,
         }
+        .contains(PermissionMask::from_permissions(permissions))
+    }
+
+    /// Superuser acting as the user, if the token was issued for an impersonation
+    pub fn impersonator_id(&self) -> Option<&str> {
+        self.actor
+            .as_ref()
+            .and_then(|actor| actor.impersonator_id.as_deref())
     }
 
     pub fn is_system_admin(&self) -> bool {
@@ -86,9 +266,15 @@ impl Actor {
     }
 
     pub fn member_of(&self, org_id: &str) -> bool {
-        match &self.actor {
-            Some(actor) => actor.org_id == org_id,
-            None => false,
+        self.org_id() == Some(org_id)
+    }
+
+    /// Org the user is signed in to or the app is linked to
+    pub fn org_id(&self) -> Option<&str> {
+        match (&self.actor, &self.app) {
+            (Some(actor), _) => Some(actor.org_id.as_str()),
+            (None, Some(app)) => Some(app.org_id.as_str()),
+            (None, None) => None,
         }
     }
 }
@@ -96,7 +282,10 @@ impl Actor {
 impl Default for Actor {
     /// Empty actor for unauthenticated requests
     fn default() -> Self {
-        Actor { actor: None }
+        Actor {
+            actor: None,
+            app: None,
+        }
     }
 }
 
@@ -110,6 +299,12 @@ pub struct CredentialsDto {
     pub password: String,
 }
 
+impl Redact for CredentialsDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["password"];
+}
+
+redacted_debug!(CredentialsDto);
+
 #[derive(Deserialize, Serialize, Validate)]
 pub struct SwitchAuthContextDto {
     pub org_id: String,
@@ -123,6 +318,12 @@ pub struct AuthResponseDto {
     pub org_count: i32,
 }
 
+impl Redact for AuthResponseDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["token"];
+}
+
+redacted_debug!(AuthResponseDto);
+
 #[cfg(test)]
 mod tests {
     use crate::utils::{IdPrefix, datetime_now_millis, generate_id};
@@ -147,6 +348,17 @@ mod tests {
                 org_count: 1,
                 roles: vec![Role::OrgViewer],
                 scopes: vec![Scope::Auth],
+                grant_id: None,
+                proof_key_id: None,
+                permission_mask: None,
+                issued_at: 0,
+                session_id: None,
+                region: None,
+                home_region: None,
+                token_id: None,
+                org_app_id: None,
+                impersonator_id: None,
+                impersonation_id: None,
             },
             UserDto {
                 id: user_id,
@@ -155,6 +367,7 @@ mod tests {
                 status: "active".to_string(),
                 created_at: today,
                 updated_at: today,
+                deleted_at: None,
             },
         );
         assert!(actor.has_auth_scope());
@@ -172,6 +385,17 @@ mod tests {
                 org_count: 1,
                 roles: vec![Role::Superuser],
                 scopes: vec![Scope::Auth],
+                grant_id: None,
+                proof_key_id: None,
+                permission_mask: None,
+                issued_at: 0,
+                session_id: None,
+                region: None,
+                home_region: None,
+                token_id: None,
+                org_app_id: None,
+                impersonator_id: None,
+                impersonation_id: None,
             },
             UserDto {
                 id: user_id,
@@ -180,6 +404,7 @@ mod tests {
                 status: "active".to_string(),
                 created_at: today,
                 updated_at: today,
+                deleted_at: None,
             },
         );
         assert!(actor.has_auth_scope());
@@ -197,6 +422,17 @@ mod tests {
                 org_count: 1,
                 roles: vec![Role::OrgViewer],
                 scopes: vec![Scope::Auth],
+                grant_id: None,
+                proof_key_id: None,
+                permission_mask: None,
+                issued_at: 0,
+                session_id: None,
+                region: None,
+                home_region: None,
+                token_id: None,
+                org_app_id: None,
+                impersonator_id: None,
+                impersonation_id: None,
             },
             UserDto {
                 id: user_id,
@@ -205,6 +441,7 @@ mod tests {
                 status: "active".to_string(),
                 created_at: today,
                 updated_at: today,
+                deleted_at: None,
             },
         );
 
@@ -223,6 +460,17 @@ mod tests {
                 org_count: 1,
                 roles: vec![Role::OrgViewer],
                 scopes: vec![Scope::Auth],
+                grant_id: None,
+                proof_key_id: None,
+                permission_mask: None,
+                issued_at: 0,
+                session_id: None,
+                region: None,
+                home_region: None,
+                token_id: None,
+                org_app_id: None,
+                impersonator_id: None,
+                impersonation_id: None,
             },
             UserDto {
                 id: user_id,
@@ -231,10 +479,54 @@ mod tests {
                 status: "active".to_string(),
                 created_at: today,
                 updated_at: today,
+                deleted_at: None,
             },
         );
 
         let required = vec![Permission::UsersDelete, Permission::UsersCreate];
         assert!(!actor.has_permissions(&required));
     }
+
+    #[test]
+    fn test_custom_permissions_extend_role_permissions() {
+        let today = datetime_now_millis();
+        let user_id = generate_id(IdPrefix::User);
+        let actor = Actor::new(
+            ActorPayloadDto {
+                id: user_id.clone(),
+                org_id: generate_id(IdPrefix::Org),
+                org_count: 1,
+                roles: vec![Role::OrgViewer],
+                scopes: vec![Scope::Auth],
+                grant_id: None,
+                proof_key_id: None,
+                permission_mask: None,
+                issued_at: 0,
+                session_id: None,
+                region: None,
+                home_region: None,
+                token_id: None,
+                org_app_id: None,
+                impersonator_id: None,
+                impersonation_id: None,
+            },
+            UserDto {
+                id: user_id,
+                email: "test@example.com".to_string(),
+                name: "test".to_string(),
+                status: "active".to_string(),
+                created_at: today,
+                updated_at: today,
+                deleted_at: None,
+            },
+        );
+        assert!(!actor.has_permissions(&[Permission::OrgMembersEdit]));
+
+        let actor = actor.with_custom_permissions(&[Permission::OrgMembersEdit]);
+        assert!(actor.has_permissions(&[Permission::OrgMembersEdit, Permission::OrgsView]));
+
+        let dto = actor.actor.expect("actor");
+        assert!(dto.permissions.contains(&Permission::OrgMembersEdit));
+        assert_eq!(dto.roles, vec![Role::OrgViewer]);
+    }
 }
@@ -0,0 +1,550 @@
+use crate::dto::{
+    AppDto, ListAppsParamsDto, ListOrgMembersParamsDto, ListOrgsParamsDto, ListUsersParamsDto,
+    NewAppDto, NewOrgDto, NewOrgMemberDto, NewUserWithPasswordDto, OrgDto, OrgMemberDto,
+    PaginatedMeta, UpdateAppDto, UpdateOrgDto, UpdateOrgMemberDto, UpdateUserDto, UserDto,
+};
+
+/// Messages of the gRPC admin services, described in proto/admin.proto
+pub mod rpc {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Empty {}
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ListRequest {
+        #[prost(int32, optional, tag = "1")]
+        pub page: Option<i32>,
+        #[prost(int32, optional, tag = "2")]
+        pub per_page: Option<i32>,
+        #[prost(string, optional, tag = "3")]
+        pub keyword: Option<String>,
+        #[prost(string, optional, tag = "4")]
+        pub sort_by: Option<String>,
+        #[prost(string, optional, tag = "5")]
+        pub sort_dir: Option<String>,
+        #[prost(bool, tag = "6")]
+        pub include_deleted: bool,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct PageMeta {
+        #[prost(int32, tag = "1")]
+        pub page: i32,
+        #[prost(int32, tag = "2")]
+        pub per_page: i32,
+        #[prost(int64, tag = "3")]
+        pub total_records: i64,
+        #[prost(int64, tag = "4")]
+        pub total_pages: i64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct GetRequest {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(bool, tag = "2")]
+        pub include_deleted: bool,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Scopes {
+        #[prost(string, repeated, tag = "1")]
+        pub scopes: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Roles {
+        #[prost(string, repeated, tag = "1")]
+        pub roles: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct User {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(string, tag = "2")]
+        pub email: String,
+        #[prost(string, tag = "3")]
+        pub name: String,
+        #[prost(string, tag = "4")]
+        pub status: String,
+        #[prost(int64, tag = "5")]
+        pub created_at: i64,
+        #[prost(int64, tag = "6")]
+        pub updated_at: i64,
+        #[prost(int64, optional, tag = "7")]
+        pub deleted_at: Option<i64>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ListUsersResponse {
+        #[prost(message, optional, tag = "1")]
+        pub meta: Option<PageMeta>,
+        #[prost(message, repeated, tag = "2")]
+        pub users: Vec<User>,
+    }
+
+    /// Debug leaves out the password
+    #[derive(Clone, PartialEq, prost::Message)]
+    #[prost(skip_debug)]
+    pub struct CreateUserRequest {
+        #[prost(string, tag = "1")]
+        pub email: String,
+        #[prost(string, tag = "2")]
+        pub name: String,
+        #[prost(string, tag = "3")]
+        pub password: String,
+    }
+
+    impl core::fmt::Debug for CreateUserRequest {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.debug_struct("CreateUserRequest")
+                .field("email", &self.email)
+                .field("name", &self.name)
+                .field("password", &"[redacted]")
+                .finish()
+        }
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct UpdateUserRequest {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(int64, optional, tag = "2")]
+        pub version: Option<i64>,
+        #[prost(bool, tag = "3")]
+        pub any_version: bool,
+        #[prost(string, optional, tag = "4")]
+        pub name: Option<String>,
+        #[prost(string, optional, tag = "5")]
+        pub status: Option<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct UpdateUserResponse {
+        #[prost(message, optional, tag = "1")]
+        pub user: Option<User>,
+        #[prost(string, repeated, tag = "2")]
+        pub changed_fields: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Org {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(string, tag = "2")]
+        pub name: String,
+        #[prost(string, tag = "3")]
+        pub status: String,
+        #[prost(string, optional, tag = "4")]
+        pub owner_id: Option<String>,
+        #[prost(string, optional, tag = "5")]
+        pub owner_email: Option<String>,
+        #[prost(string, optional, tag = "6")]
+        pub owner_name: Option<String>,
+        #[prost(int64, tag = "7")]
+        pub member_count: i64,
+        #[prost(int64, tag = "8")]
+        pub app_count: i64,
+        #[prost(int64, tag = "9")]
+        pub created_at: i64,
+        #[prost(int64, tag = "10")]
+        pub updated_at: i64,
+        #[prost(int64, optional, tag = "11")]
+        pub deleted_at: Option<i64>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ListOrgsResponse {
+        #[prost(message, optional, tag = "1")]
+        pub meta: Option<PageMeta>,
+        #[prost(message, repeated, tag = "2")]
+        pub orgs: Vec<Org>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CreateOrgRequest {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(string, tag = "2")]
+        pub owner_id: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct UpdateOrgRequest {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(int64, optional, tag = "2")]
+        pub version: Option<i64>,
+        #[prost(bool, tag = "3")]
+        pub any_version: bool,
+        #[prost(string, optional, tag = "4")]
+        pub name: Option<String>,
+        #[prost(string, optional, tag = "5")]
+        pub status: Option<String>,
+        #[prost(string, optional, tag = "6")]
+        pub owner_id: Option<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct UpdateOrgResponse {
+        #[prost(message, optional, tag = "1")]
+        pub org: Option<Org>,
+        #[prost(string, repeated, tag = "2")]
+        pub changed_fields: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct App {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(string, tag = "2")]
+        pub name: String,
+        #[prost(string, tag = "3")]
+        pub client_id: String,
+        #[prost(string, tag = "4")]
+        pub client_secret: String,
+        #[prost(string, tag = "5")]
+        pub redirect_uri: String,
+        #[prost(string, repeated, tag = "6")]
+        pub redirect_uris: Vec<String>,
+        #[prost(string, repeated, tag = "7")]
+        pub scopes: Vec<String>,
+        #[prost(int64, tag = "8")]
+        pub created_at: i64,
+        #[prost(int64, tag = "9")]
+        pub updated_at: i64,
+        #[prost(int64, optional, tag = "10")]
+        pub deleted_at: Option<i64>,
+        #[prost(int64, optional, tag = "11")]
+        pub previous_secret_expires_at: Option<i64>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ListAppsResponse {
+        #[prost(message, optional, tag = "1")]
+        pub meta: Option<PageMeta>,
+        #[prost(message, repeated, tag = "2")]
+        pub apps: Vec<App>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CreateAppRequest {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(string, tag = "2")]
+        pub redirect_uri: String,
+        #[prost(message, optional, tag = "3")]
+        pub scopes: Option<Scopes>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct UpdateAppRequest {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(int64, optional, tag = "2")]
+        pub version: Option<i64>,
+        #[prost(bool, tag = "3")]
+        pub any_version: bool,
+        #[prost(string, optional, tag = "4")]
+        pub name: Option<String>,
+        #[prost(string, optional, tag = "5")]
+        pub redirect_uri: Option<String>,
+        #[prost(message, optional, tag = "6")]
+        pub scopes: Option<Scopes>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct UpdateAppResponse {
+        #[prost(message, optional, tag = "1")]
+        pub app: Option<App>,
+        #[prost(string, repeated, tag = "2")]
+        pub changed_fields: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct OrgMember {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(string, tag = "2")]
+        pub org_id: String,
+        #[prost(string, tag = "3")]
+        pub user_id: String,
+        #[prost(string, optional, tag = "4")]
+        pub member_email: Option<String>,
+        #[prost(string, optional, tag = "5")]
+        pub member_name: Option<String>,
+        #[prost(string, repeated, tag = "6")]
+        pub roles: Vec<String>,
+        #[prost(string, tag = "7")]
+        pub status: String,
+        #[prost(int64, tag = "8")]
+        pub created_at: i64,
+        #[prost(int64, tag = "9")]
+        pub updated_at: i64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ListOrgMembersRequest {
+        #[prost(string, tag = "1")]
+        pub org_id: String,
+        #[prost(int32, optional, tag = "2")]
+        pub page: Option<i32>,
+        #[prost(int32, optional, tag = "3")]
+        pub per_page: Option<i32>,
+        #[prost(string, optional, tag = "4")]
+        pub keyword: Option<String>,
+        #[prost(string, optional, tag = "5")]
+        pub sort_by: Option<String>,
+        #[prost(string, optional, tag = "6")]
+        pub sort_dir: Option<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ListOrgMembersResponse {
+        #[prost(message, optional, tag = "1")]
+        pub meta: Option<PageMeta>,
+        #[prost(message, repeated, tag = "2")]
+        pub members: Vec<OrgMember>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct OrgMemberRequest {
+        #[prost(string, tag = "1")]
+        pub org_id: String,
+        #[prost(string, tag = "2")]
+        pub user_id: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CreateOrgMemberRequest {
+        #[prost(string, tag = "1")]
+        pub org_id: String,
+        #[prost(string, tag = "2")]
+        pub user_id: String,
+        #[prost(string, repeated, tag = "3")]
+        pub roles: Vec<String>,
+        #[prost(string, tag = "4")]
+        pub status: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct UpdateOrgMemberRequest {
+        #[prost(string, tag = "1")]
+        pub org_id: String,
+        #[prost(string, tag = "2")]
+        pub user_id: String,
+        #[prost(message, optional, tag = "3")]
+        pub roles: Option<Roles>,
+        #[prost(string, optional, tag = "4")]
+        pub status: Option<String>,
+    }
+}
+
+impl From<PaginatedMeta> for rpc::PageMeta {
+    fn from(meta: PaginatedMeta) -> Self {
+        Self {
+            page: meta.page,
+            per_page: meta.per_page,
+            total_records: meta.total_records,
+            total_pages: meta.total_pages,
+        }
+    }
+}
+
+impl From<UserDto> for rpc::User {
+    fn from(user: UserDto) -> Self {
+        Self {
+            id: user.id,
+            email: user.email,
+            name: user.name,
+            status: user.status,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+            deleted_at: user.deleted_at,
+        }
+    }
+}
+
+impl From<OrgDto> for rpc::Org {
+    fn from(org: OrgDto) -> Self {
+        Self {
+            id: org.id,
+            name: org.name,
+            status: org.status,
+            owner_id: org.owner_id,
+            owner_email: org.owner_email,
+            owner_name: org.owner_name,
+            member_count: org.member_count,
+            app_count: org.app_count,
+            created_at: org.created_at,
+            updated_at: org.updated_at,
+            deleted_at: org.deleted_at,
+        }
+    }
+}
+
+impl From<AppDto> for rpc::App {
+    fn from(app: AppDto) -> Self {
+        Self {
+            id: app.id,
+            name: app.name,
+            client_id: app.client_id,
+            client_secret: app.client_secret,
+            redirect_uri: app.redirect_uri,
+            redirect_uris: app.redirect_uris,
+            scopes: app.scopes,
+            created_at: app.created_at,
+            updated_at: app.updated_at,
+            deleted_at: app.deleted_at,
+            previous_secret_expires_at: app.previous_secret_expires_at,
+        }
+    }
+}
+
+impl From<OrgMemberDto> for rpc::OrgMember {
+    fn from(member: OrgMemberDto) -> Self {
+        Self {
+            id: member.id,
+            org_id: member.org_id,
+            user_id: member.user_id,
+            member_email: member.member_email,
+            member_name: member.member_name,
+            roles: member.roles.iter().map(|role| role.to_string()).collect(),
+            status: member.status,
+            created_at: member.created_at,
+            updated_at: member.updated_at,
+        }
+    }
+}
+
+impl From<rpc::ListRequest> for ListUsersParamsDto {
+    fn from(req: rpc::ListRequest) -> Self {
+        Self {
+            page: req.page,
+            per_page: req.per_page,
+            keyword: req.keyword,
+            sort_by: req.sort_by,
+            sort_dir: req.sort_dir,
+            cursor: None,
+            limit: None,
+        }
+    }
+}
+
+impl From<rpc::ListRequest> for ListOrgsParamsDto {
+    fn from(req: rpc::ListRequest) -> Self {
+        Self {
+            page: req.page,
+            per_page: req.per_page,
+            keyword: req.keyword,
+            sort_by: req.sort_by,
+            sort_dir: req.sort_dir,
+            cursor: None,
+            limit: None,
+        }
+    }
+}
+
+impl From<rpc::ListRequest> for ListAppsParamsDto {
+    fn from(req: rpc::ListRequest) -> Self {
+        Self {
+            page: req.page,
+            per_page: req.per_page,
+            keyword: req.keyword,
+            sort_by: req.sort_by,
+            sort_dir: req.sort_dir,
+        }
+    }
+}
+
+impl From<rpc::ListOrgMembersRequest> for ListOrgMembersParamsDto {
+    fn from(req: rpc::ListOrgMembersRequest) -> Self {
+        Self {
+            page: req.page,
+            per_page: req.per_page,
+            keyword: req.keyword,
+            sort_by: req.sort_by,
+            sort_dir: req.sort_dir,
+            cursor: None,
+            limit: None,
+            next: None,
+        }
+    }
+}
+
+impl From<rpc::CreateUserRequest> for NewUserWithPasswordDto {
+    fn from(req: rpc::CreateUserRequest) -> Self {
+        Self {
+            email: req.email,
+            name: req.name,
+            password: req.password,
+        }
+    }
+}
+
+impl From<rpc::UpdateUserRequest> for UpdateUserDto {
+    fn from(req: rpc::UpdateUserRequest) -> Self {
+        Self {
+            name: req.name,
+            status: req.status,
+        }
+    }
+}
+
+impl From<rpc::CreateOrgRequest> for NewOrgDto {
+    fn from(req: rpc::CreateOrgRequest) -> Self {
+        Self {
+            name: req.name,
+            owner_id: req.owner_id,
+        }
+    }
+}
+
+impl From<rpc::UpdateOrgRequest> for UpdateOrgDto {
+    fn from(req: rpc::UpdateOrgRequest) -> Self {
+        Self {
+            name: req.name,
+            status: req.status,
+            owner_id: req.owner_id,
+        }
+    }
+}
+
+impl From<rpc::CreateAppRequest> for NewAppDto {
+    fn from(req: rpc::CreateAppRequest) -> Self {
+        Self {
+            name: req.name,
+            redirect_uri: req.redirect_uri,
+            scopes: req.scopes.map(|scopes| scopes.scopes),
+        }
+    }
+}
+
+impl From<rpc::UpdateAppRequest> for UpdateAppDto {
+    fn from(req: rpc::UpdateAppRequest) -> Self {
+        Self {
+            name: req.name,
+            redirect_uri: req.redirect_uri,
+            scopes: req.scopes.map(|scopes| scopes.scopes),
+        }
+    }
+}
+
+impl From<rpc::CreateOrgMemberRequest> for NewOrgMemberDto {
+    fn from(req: rpc::CreateOrgMemberRequest) -> Self {
+        Self {
+            user_id: req.user_id,
+            roles: req.roles,
+            status: req.status,
+        }
+    }
+}
+
+impl From<rpc::UpdateOrgMemberRequest> for UpdateOrgMemberDto {
+    fn from(req: rpc::UpdateOrgMemberRequest) -> Self {
+        Self {
+            roles: req.roles.map(|roles| roles.roles),
+            status: req.status,
+        }
+    }
+}
@@ -3,15 +3,97 @@ use serde::{Deserialize, Serialize};
 use urlencoding::encode;
 use validator::Validate;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+use crate::dto::sort_query;
+use crate::utils::{Redact, SparseFields, accepted_secret_hashes, redacted_debug};
+use crate::validators;
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct AppDto {
     pub id: String,
     pub name: String,
     pub client_id: String,
+
+    /// Only filled in right after the secret is issued, the database keeps a hash
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub client_secret: String,
+
+    /// Main redirect URI, used when probing and as the default for new environments
     pub redirect_uri: String,
+
+    /// Every registered redirect URI, the main one first
+    #[serde(default)]
+    pub redirect_uris: Vec<String>,
+
+    /// Scopes the app may request, every app scope unless narrowed
+    #[serde(default)]
+    pub scopes: Vec<String>,
+
     pub created_at: i64,
     pub updated_at: i64,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<i64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<String>,
+
+    #[serde(skip)]
+    pub client_secret_hash: Option<String>,
+
+    #[serde(skip)]
+    pub previous_secret_hash: Option<String>,
+
+    /// When the secret replaced by the last rotation stops working
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_secret_expires_at: Option<i64>,
+}
+
+impl AppDto {
+    /// Redirect URIs registered on top of the main one
+    pub fn extra_redirect_uris(&self) -> Vec<String> {
+        self.redirect_uris
+            .iter()
+            .filter(|uri| **uri != self.redirect_uri)
+            .cloned()
+            .collect()
+    }
+
+    /// Hashes of the secrets accepted right now, the rotated out one included while in grace
+    pub fn accepted_secret_hashes(&self, now: i64) -> Vec<String> {
+        accepted_secret_hashes(
+            self.client_secret_hash.as_ref(),
+            self.previous_secret_hash.as_ref(),
+            self.previous_secret_expires_at,
+            now,
+        )
+    }
+}
+
+impl Redact for AppDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["client_secret"];
+}
+
+redacted_debug!(AppDto);
+
+impl SparseFields for AppDto {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "name",
+        "client_id",
+        "client_secret",
+        "redirect_uri",
+        "redirect_uris",
+        "scopes",
+        "created_at",
+        "updated_at",
+        "deleted_at",
+        "created_by",
+        "updated_by",
+        "previous_secret_expires_at",
+    ];
 }
 
 #[derive(Clone, Serialize, Deserialize, Validate)]
@@ -22,6 +104,18 @@ pub struct NewAppDto {
     #[validate(length(min = 1, max = 250))]
     #[validate(url)]
     pub redirect_uri: String,
+
+    /// Leave out to allow every app scope
+    #[serde(default)]
+    #[validate(custom(function = "validators::app_scopes"))]
+    pub scopes: Option<Vec<String>>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct AppRedirectUriDto {
+    #[validate(length(min = 1, max = 250))]
+    #[validate(url)]
+    pub redirect_uri: String,
 }
 
 #[derive(Clone, Serialize, Deserialize, Validate)]
@@ -32,24 +126,36 @@ pub struct UpdateAppDto {
     #[validate(length(min = 1, max = 250))]
     #[validate(url)]
     pub redirect_uri: Option<String>,
+
+    #[serde(default)]
+    #[validate(custom(function = "validators::app_scopes"))]
+    pub scopes: Option<Vec<String>>,
 }
 
 #[derive(Clone, Deserialize, Validate)]
 pub struct ListAppsParamsDto {
-    #[validate(range(min = 1, max = 1000))]
+    #[validate(range(min = 1))]
     pub page: Option<i32>,
 
-    #[validate(range(min = 1, max = 50))]
+    #[validate(range(min = 1))]
     pub per_page: Option<i32>,
 
     #[validate(length(min = 0, max = 50))]
     pub keyword: Option<String>,
+
+    #[validate(custom(function = "validators::app_sort"))]
+    pub sort_by: Option<String>,
+
+    #[validate(custom(function = "validators::sort_dir"))]
+    pub sort_dir: Option<String>,
 }
 
 impl Default for ListAppsParamsDto {
     fn default() -> Self {
         Self {
             keyword: None,
+            sort_by: None,
+            sort_dir: None,
             page: Some(1),
             per_page: Some(10),
         }
@@ -69,10 +175,11 @@ impl fmt::Display for ListAppsParamsDto {
 
         write!(
             f,
-            "page={}&per_page={}&keyword={}",
+            "page={}&per_page={}&keyword={}{}",
             page,
             per_page,
-            encode(keyword)
+            encode(keyword),
+            sort_query(self.sort_by.as_deref(), self.sort_dir.as_deref())
         )
     }
 }
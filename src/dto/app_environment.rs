@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::dto::AppDto;
+use crate::utils::{Redact, SparseFields, accepted_secret_hashes, redacted_debug};
+use crate::validators;
+
+/// Separate credentials for one deployment of an app, ie: staging.
+///
+/// Each environment gets its own client_id so the OAuth flow picks the
+/// environment from the client_id alone, the app's own credentials keep
+/// working as the default environment.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AppEnvironmentDto {
+    pub id: String,
+    pub app_id: String,
+    pub label: String,
+    pub client_id: String,
+
+    /// Only filled in right after the secret is issued, the database keeps a hash
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub client_secret: String,
+
+    pub redirect_uri: String,
+    pub created_at: i64,
+
+    #[serde(skip)]
+    pub client_secret_hash: Option<String>,
+
+    #[serde(skip)]
+    pub previous_secret_hash: Option<String>,
+
+    /// When the secret replaced by the last rotation stops working
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_secret_expires_at: Option<i64>,
+}
+
+impl AppEnvironmentDto {
+    /// Hashes of the secrets accepted right now, the rotated out one included while in grace
+    pub fn accepted_secret_hashes(&self, now: i64) -> Vec<String> {
+        accepted_secret_hashes(
+            self.client_secret_hash.as_ref(),
+            self.previous_secret_hash.as_ref(),
+            self.previous_secret_expires_at,
+            now,
+        )
+    }
+}
+
+impl Redact for AppEnvironmentDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["client_secret"];
+}
+
+redacted_debug!(AppEnvironmentDto);
+
+impl SparseFields for AppEnvironmentDto {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "app_id",
+        "label",
+        "client_id",
+        "redirect_uri",
+        "created_at",
+        "previous_secret_expires_at",
+    ];
+}
+
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct NewAppEnvironmentDto {
+    #[validate(length(min = 1, max = 30))]
+    #[validate(custom(function = "validators::sluggable"))]
+    pub label: String,
+
+    #[validate(length(min = 1, max = 250))]
+    #[validate(url)]
+    pub redirect_uri: String,
+}
+
+/// Credentials an OAuth client_id resolves to
+#[derive(Clone)]
+pub struct OauthClientDto {
+    pub app: AppDto,
+
+    /// Environment label, `None` for the app's own credentials
+    pub environment: Option<String>,
+
+    /// Hashes of every client secret currently accepted for this client_id
+    pub secret_hashes: Vec<String>,
+    pub redirect_uris: Vec<String>,
+}
+
+impl OauthClientDto {
+    /// Compared in constant time, this is the client secret check
+    #[allow(deprecated)]
+    pub fn accepts_secret_hash(&self, hash: &str) -> bool {
+        self.secret_hashes.iter().any(|accepted| {
+            ring::constant_time::verify_slices_are_equal(accepted.as_bytes(), hash.as_bytes())
+                .is_ok()
+        })
+    }
+}
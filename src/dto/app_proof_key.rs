@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Request header carrying the proof of possession for bound tokens
+pub const TOKEN_PROOF_HEADER: &str = "Yaas-Proof";
+
+/// Ed25519 public key an app registered to bind its access tokens to.
+///
+/// Once registered, tokens issued to the app carry the key id and are only
+/// accepted along with a request signed by the matching private key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AppProofKeyDto {
+    pub app_id: String,
+    pub key_id: String,
+    pub public_key: String,
+    pub created_at: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct NewAppProofKeyDto {
+    /// Raw 32 byte Ed25519 public key, base64url encoded without padding
+    #[validate(length(min = 43, max = 44))]
+    pub public_key: String,
+}
+
+/// Parts of an API request needed to check a bound token
+#[derive(Clone, Debug)]
+pub struct TokenProofDto {
+    pub header: Option<String>,
+    pub method: String,
+    pub path: String,
+}
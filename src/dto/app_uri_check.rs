@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AppUriCheckStatus {
+    /// Syntax passed, reachability was not probed (yet)
+    Valid,
+    Reachable,
+    Unreachable,
+}
+
+impl TryFrom<&str> for AppUriCheckStatus {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "valid" => Ok(Self::Valid),
+            "reachable" => Ok(Self::Reachable),
+            "unreachable" => Ok(Self::Unreachable),
+            _ => Err(Error::Validation {
+                msg: format!("Invalid redirect URI check status: {}", value),
+            }),
+        }
+    }
+}
+
+impl core::fmt::Display for AppUriCheckStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Valid => write!(f, "valid"),
+            Self::Reachable => write!(f, "reachable"),
+            Self::Unreachable => write!(f, "unreachable"),
+        }
+    }
+}
+
+/// Last verification result of an app's redirect URI
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AppUriCheckDto {
+    pub app_id: String,
+    pub redirect_uri: String,
+    pub status: AppUriCheckStatus,
+    pub message: Option<String>,
+    pub checked_at: i64,
+}
+
+impl AppUriCheckDto {
+    /// Checks are tied to the URI they verified, an edited URI is unverified
+    pub fn is_current(&self, redirect_uri: &str) -> bool {
+        self.redirect_uri == redirect_uri
+    }
+}
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Superuser operations that need a second superuser to sign off
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ApprovalAction {
+    DeleteOrg,
+    GrantSuperuser,
+}
+
+impl TryFrom<&str> for ApprovalAction {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "delete_org" => Ok(Self::DeleteOrg),
+            "grant_superuser" => Ok(Self::GrantSuperuser),
+            _ => Err(Error::Validation {
+                msg: format!("Invalid approval action: {}", value),
+            }),
+        }
+    }
+}
+
+impl core::fmt::Display for ApprovalAction {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::DeleteOrg => write!(f, "delete_org"),
+            Self::GrantSuperuser => write!(f, "grant_superuser"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Expired,
+}
+
+impl TryFrom<&str> for ApprovalStatus {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "pending" => Ok(Self::Pending),
+            "approved" => Ok(Self::Approved),
+            "rejected" => Ok(Self::Rejected),
+            "expired" => Ok(Self::Expired),
+            _ => Err(Error::Validation {
+                msg: format!("Invalid approval status: {}", value),
+            }),
+        }
+    }
+}
+
+impl core::fmt::Display for ApprovalStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Pending => write!(f, "pending"),
+            Self::Approved => write!(f, "approved"),
+            Self::Rejected => write!(f, "rejected"),
+            Self::Expired => write!(f, "expired"),
+        }
+    }
+}
+
+/// Request to run a critical operation, kept as its audit trail
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ApprovalDto {
+    pub id: String,
+    pub action: ApprovalAction,
+    pub target_id: String,
+    pub requested_by: String,
+    pub status: ApprovalStatus,
+    pub decided_by: Option<String>,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub decided_at: Option<i64>,
+}
+
+impl ApprovalDto {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at <= now
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NewApprovalDto {
+    pub action: ApprovalAction,
+    pub target_id: String,
+    pub requested_by: String,
+    pub expires_at: i64,
+}
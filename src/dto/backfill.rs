@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+/// How far a backfill got, rows are walked in `id` order
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BackfillProgressDto {
+    pub name: String,
+
+    /// Last row handled, the next batch starts after it
+    pub last_id: Option<String>,
+    pub rows_done: i64,
+    pub started_at: i64,
+    pub updated_at: i64,
+    pub completed_at: Option<i64>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BackfillOptionsDto {
+    /// Rows updated per transaction
+    pub batch_size: i64,
+    /// Pause between batches to leave room for regular writes
+    pub pause_ms: u64,
+    /// Stop after this many batches, the next run resumes from there
+    pub max_batches: Option<i64>,
+    /// Start over from the first row, even when completed
+    pub restart: bool,
+}
+
+impl Default for BackfillOptionsDto {
+    fn default() -> Self {
+        Self {
+            batch_size: 500,
+            pause_ms: 100,
+            max_batches: None,
+            restart: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BackfillReportDto {
+    pub name: String,
+    pub batches: i64,
+    pub rows: i64,
+    pub completed: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BackfillStatusDto {
+    pub name: String,
+    pub table: String,
+    pub progress: Option<BackfillProgressDto>,
+}
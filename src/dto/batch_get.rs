@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Maximum number of ids accepted by a batch get
+pub const MAX_BATCH_GET_IDS: u64 = 100;
+
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct BatchGetDto {
+    #[validate(length(min = 1, max = MAX_BATCH_GET_IDS))]
+    pub ids: Vec<String>,
+}
+
+/// Records of a batch get in the order of the requested ids, with the ids not found
+#[derive(Clone, Serialize)]
+pub struct BatchGetResultDto<T> {
+    pub items: Vec<T>,
+    pub missing_ids: Vec<String>,
+}
+
+impl<T> BatchGetResultDto<T> {
+    /// Orders the found records as requested, repeated ids are answered once
+    pub fn new(ids: &[String], found: Vec<T>, id_of: impl Fn(&T) -> &str) -> Self {
+        let mut found: HashMap<String, T> = found
+            .into_iter()
+            .map(|item| (id_of(&item).to_string(), item))
+            .collect();
+
+        let mut items = Vec::new();
+        let mut missing_ids: Vec<String> = Vec::new();
+        for id in ids {
+            match found.remove(id) {
+                Some(item) => items.push(item),
+                None if !missing_ids.contains(id)
+                    && !items.iter().any(|item| id_of(item) == id) =>
+                {
+                    missing_ids.push(id.clone())
+                }
+                None => {}
+            }
+        }
+
+        Self { items, missing_ids }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BatchGetResultDto;
+
+    #[test]
+    fn batch_results_follow_the_requested_order() {
+        let ids: Vec<String> = ["b", "x", "a", "b", "x"]
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        let found = vec!["a".to_string(), "b".to_string()];
+
+        let result = BatchGetResultDto::new(&ids, found, |item| item.as_str());
+        assert_eq!(result.items, vec!["b", "a"]);
+        assert_eq!(result.missing_ids, vec!["x"]);
+    }
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of items accepted in a single bulk request
+pub const MAX_BULK_ITEMS: usize = 50;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BulkItemResultDto {
+    pub id: String,
+    pub label: String,
+    pub success: bool,
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct BulkResultDto {
+    pub items: Vec<BulkItemResultDto>,
+}
+
+impl BulkResultDto {
+    pub fn succeeded(&self) -> usize {
+        self.items.iter().filter(|item| item.success).count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.items.iter().filter(|item| !item.success).count()
+    }
+
+    pub fn push_success(&mut self, id: &str, label: &str) {
+        self.items.push(BulkItemResultDto {
+            id: id.to_string(),
+            label: label.to_string(),
+            success: true,
+            message: None,
+        });
+    }
+
+    pub fn push_failure(&mut self, id: &str, label: &str, message: String) {
+        self.items.push(BulkItemResultDto {
+            id: id.to_string(),
+            label: label.to_string(),
+            success: false,
+            message: Some(message),
+        });
+    }
+}
@@ -0,0 +1,116 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+use crate::dto::{AppDto, OrgDto, UserDto};
+
+/// Bookkeeping fields that change on every write
+const IGNORED_FIELDS: [&str; 2] = ["updated_at", "updated_by"];
+
+/// Entity returned by an update along with the fields the update changed.
+///
+/// An empty list means the update was a no-op.
+#[derive(Clone, Debug, Serialize)]
+pub struct UpdatedDto<T> {
+    #[serde(flatten)]
+    pub data: T,
+    pub changed_fields: Vec<String>,
+}
+
+impl<T: Serialize> UpdatedDto<T> {
+    pub fn new(before: &T, after: T) -> Self {
+        Self {
+            changed_fields: changed_fields(before, &after),
+            data: after,
+        }
+    }
+}
+
+/// Records updated with optimistic concurrency, `updated_at` is their version
+pub trait HasVersion {
+    fn version(&self) -> i64;
+}
+
+impl HasVersion for UserDto {
+    fn version(&self) -> i64 {
+        self.updated_at
+    }
+}
+
+impl HasVersion for OrgDto {
+    fn version(&self) -> i64 {
+        self.updated_at
+    }
+}
+
+impl HasVersion for AppDto {
+    fn version(&self) -> i64 {
+        self.updated_at
+    }
+}
+
+/// Sorted names of the top level fields that differ between two versions
+pub fn changed_fields<T: Serialize>(before: &T, after: &T) -> Vec<String> {
+    let (Ok(Value::Object(before)), Ok(Value::Object(after))) =
+        (serde_json::to_value(before), serde_json::to_value(after))
+    else {
+        return Vec::new();
+    };
+
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+
+    keys.into_iter()
+        .filter(|key| !IGNORED_FIELDS.contains(&key.as_str()))
+        .filter(|key| before.get(*key) != after.get(*key))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UpdatedDto, changed_fields};
+    use crate::dto::UserDto;
+
+    fn user(name: &str, status: &str, updated_at: i64) -> UserDto {
+        UserDto {
+            id: "usr_1".to_string(),
+            email: "jane@example.com".to_string(),
+            name: name.to_string(),
+            status: status.to_string(),
+            created_at: 1,
+            updated_at,
+            deleted_at: None,
+        }
+    }
+
+    #[test]
+    fn test_changed_fields() {
+        let before = user("Jane", "active", 1);
+
+        assert!(changed_fields(&before, &user("Jane", "active", 2)).is_empty());
+        assert_eq!(
+            changed_fields(&before, &user("Janet", "inactive", 2)),
+            vec!["name".to_string(), "status".to_string()]
+        );
+
+        // Fields skipped when empty still count as changed
+        let deleted = UserDto {
+            deleted_at: Some(3),
+            ..before.clone()
+        };
+        assert_eq!(
+            changed_fields(&before, &deleted),
+            vec!["deleted_at".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_updated_dto_flattens_entity() {
+        let before = user("Jane", "active", 1);
+        let updated = UpdatedDto::new(&before, user("Janet", "active", 2));
+
+        let value = serde_json::to_value(&updated).unwrap();
+        assert_eq!(value["name"], "Janet");
+        assert_eq!(value["changed_fields"], serde_json::json!(["name"]));
+    }
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utils::SparseFields;
+
+/// One email queued for delivery, kept as the mail outbox.
+///
+/// Uses the lifecycle delivery statuses: `pending`, `delivered` or `failed`.
+/// Bodies are cleared once delivered or given up as they can carry links.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EmailDto {
+    pub id: String,
+    pub org_id: Option<String>,
+    pub recipient: String,
+    pub subject: String,
+
+    #[serde(skip_serializing)]
+    pub text_body: String,
+
+    #[serde(skip_serializing)]
+    pub html_body: String,
+
+    pub status: String,
+    pub attempts: i64,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub sent_at: Option<i64>,
+}
+
+impl SparseFields for EmailDto {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "org_id",
+        "recipient",
+        "subject",
+        "status",
+        "attempts",
+        "next_attempt_at",
+        "last_error",
+        "created_at",
+        "updated_at",
+        "sent_at",
+    ];
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ListEmailsParamsDto {
+    pub status: Option<String>,
+}
+
+/// Payload of the `email.delivery` job
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EmailDeliveryJobDto {
+    pub email_id: String,
+}
+
+/// Rendered message ready to be queued
+#[derive(Clone)]
+pub struct NewEmailDto {
+    pub org_id: Option<String>,
+    pub recipient: String,
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: String,
+}
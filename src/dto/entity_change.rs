@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+/// Kind of record an admin table lists
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangedEntity {
+    User,
+    Org,
+    OrgMember,
+}
+
+impl core::fmt::Display for ChangedEntity {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::User => write!(f, "user"),
+            Self::Org => write!(f, "org"),
+            Self::OrgMember => write!(f, "org_member"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// A user, org or membership was written, sent to admins watching `/events`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EntityChangeDto {
+    pub entity: ChangedEntity,
+    pub action: ChangeAction,
+    pub id: String,
+    /// Org of the membership, or the org itself
+    pub org_id: Option<String>,
+}
+
+impl EntityChangeDto {
+    pub fn user(action: ChangeAction, user_id: &str) -> Self {
+        Self {
+            entity: ChangedEntity::User,
+            action,
+            id: user_id.to_string(),
+            org_id: None,
+        }
+    }
+
+    pub fn org(action: ChangeAction, org_id: &str) -> Self {
+        Self {
+            entity: ChangedEntity::Org,
+            action,
+            id: org_id.to_string(),
+            org_id: Some(org_id.to_string()),
+        }
+    }
+
+    pub fn org_member(action: ChangeAction, org_id: &str, user_id: &str) -> Self {
+        Self {
+            entity: ChangedEntity::OrgMember,
+            action,
+            id: user_id.to_string(),
+            org_id: Some(org_id.to_string()),
+        }
+    }
+}
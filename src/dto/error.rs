@@ -6,4 +6,17 @@ pub struct ErrorMessageDto {
     pub message: String,
     pub error: String,
     pub error_code: Option<String>,
+    /// Id of the failed request to quote when reporting the error
+    pub request_id: Option<String>,
+}
+
+/// Error body of an update made against an old version, with the record as it is now
+#[derive(Clone, Serialize)]
+pub struct VersionConflictDto<T> {
+    pub status_code: u16,
+    pub message: String,
+    pub error: String,
+    pub error_code: Option<String>,
+    pub request_id: Option<String>,
+    pub current: T,
 }
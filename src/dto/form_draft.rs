@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// Unsubmitted values of a long form, one per user and form
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FormDraftDto {
+    pub user_id: String,
+    pub form_key: String,
+    /// JSON object of field names to values
+    pub data: String,
+    pub updated_at: i64,
+    pub expires_at: i64,
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// Mutating API request made with an `Idempotency-Key`, one per actor, route and key.
+///
+/// The response is empty while the first request is still running, its body
+/// is left out when it held a secret shown once.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IdempotencyKeyDto {
+    pub user_id: String,
+    pub route: String,
+    pub idempotency_key: String,
+    /// SHA-256 of the request body, retries must send the same body
+    pub request_hash: String,
+    pub status_code: Option<i64>,
+    pub content_type: Option<String>,
+    pub body: Option<String>,
+    /// SHA-256 of the response body, kept even when the body is not
+    pub response_hash: Option<String>,
+    pub created_at: i64,
+    pub expires_at: i64,
+}
+
+/// First response of a request, replayed on retries
+#[derive(Clone, Debug)]
+pub struct IdempotentResponseDto {
+    pub status_code: i64,
+    pub content_type: Option<String>,
+    /// None when the response held a secret, retries are refused instead
+    pub body: Option<String>,
+    pub response_hash: String,
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{Redact, redacted_debug};
+
+/// Superuser signed in as another user, for support.
+///
+/// Tokens issued for it stop working once it ends or expires, whichever
+/// comes first.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImpersonationDto {
+    pub id: String,
+    pub impersonator_id: String,
+    pub user_id: String,
+    pub org_id: String,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub ended_at: Option<i64>,
+}
+
+impl ImpersonationDto {
+    pub fn is_active(&self, now: i64) -> bool {
+        self.ended_at.is_none() && self.expires_at > now
+    }
+}
+
+/// Request made while impersonating, kept as the audit trail
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImpersonationActionDto {
+    pub id: String,
+    pub impersonation_id: String,
+    pub method: String,
+    pub path: String,
+    pub status: i64,
+    pub created_at: i64,
+}
+
+/// Impersonation with the requests made during it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImpersonationLogDto {
+    #[serde(flatten)]
+    pub impersonation: ImpersonationDto,
+    pub actions: Vec<ImpersonationActionDto>,
+}
+
+/// Token acting as the user, only returned when the impersonation starts
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ImpersonationTokenDto {
+    pub token: String,
+    pub impersonation: ImpersonationDto,
+}
+
+impl Redact for ImpersonationTokenDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["token"];
+}
+
+redacted_debug!(ImpersonationTokenDto);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_impersonation_is_active() {
+        let mut impersonation = ImpersonationDto {
+            id: "imp_1".to_string(),
+            impersonator_id: "usr_1".to_string(),
+            user_id: "usr_2".to_string(),
+            org_id: "org_1".to_string(),
+            created_at: 1000,
+            expires_at: 2000,
+            ended_at: None,
+        };
+        assert!(impersonation.is_active(1500));
+        assert!(!impersonation.is_active(2000));
+
+        impersonation.ended_at = Some(1200);
+        assert!(!impersonation.is_active(1500));
+    }
+}
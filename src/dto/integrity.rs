@@ -0,0 +1,20 @@
+use serde::Serialize;
+
+/// Number of orphaned rows found (or removed) for one kind of orphan
+#[derive(Clone, Serialize)]
+pub struct OrphanCountDto {
+    pub kind: String,
+    pub count: i64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct IntegrityReportDto {
+    pub items: Vec<OrphanCountDto>,
+    pub repaired: bool,
+}
+
+impl IntegrityReportDto {
+    pub fn total(&self) -> i64 {
+        self.items.iter().map(|item| item.count).sum()
+    }
+}
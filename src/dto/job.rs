@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utils::SparseFields;
+use crate::{Error, Result};
+
+/// Work the background job worker knows how to run
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum JobKind {
+    #[serde(rename = "app.redirect_uri_probe")]
+    RedirectUriProbe,
+    #[serde(rename = "user.export")]
+    UserExport,
+    #[serde(rename = "lifecycle.delivery")]
+    LifecycleDelivery,
+    #[serde(rename = "email.delivery")]
+    EmailDelivery,
+    #[serde(rename = "org.elevation_revert")]
+    ElevationRevert,
+}
+
+impl TryFrom<&str> for JobKind {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "app.redirect_uri_probe" => Ok(Self::RedirectUriProbe),
+            "user.export" => Ok(Self::UserExport),
+            "lifecycle.delivery" => Ok(Self::LifecycleDelivery),
+            "email.delivery" => Ok(Self::EmailDelivery),
+            "org.elevation_revert" => Ok(Self::ElevationRevert),
+            _ => Err(Error::Validation {
+                msg: format!("Invalid job kind: {}", value),
+            }),
+        }
+    }
+}
+
+impl core::fmt::Display for JobKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::RedirectUriProbe => write!(f, "app.redirect_uri_probe"),
+            Self::UserExport => write!(f, "user.export"),
+            Self::LifecycleDelivery => write!(f, "lifecycle.delivery"),
+            Self::EmailDelivery => write!(f, "email.delivery"),
+            Self::ElevationRevert => write!(f, "org.elevation_revert"),
+        }
+    }
+}
+
+/// Job state machine:
+///
+/// - `queued` -> `running` when a worker claims it
+/// - `running` -> `succeeded` when the handler passes
+/// - `running` -> `queued` with a later `run_at` when the handler fails and attempts are left
+/// - `running` -> `dead` when the last attempt fails, until an admin retries it
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Dead,
+}
+
+impl TryFrom<&str> for JobStatus {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "queued" => Ok(Self::Queued),
+            "running" => Ok(Self::Running),
+            "succeeded" => Ok(Self::Succeeded),
+            "dead" => Ok(Self::Dead),
+            _ => Err(Error::Validation {
+                msg: format!("Invalid job status: {}", value),
+            }),
+        }
+    }
+}
+
+impl core::fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Queued => write!(f, "queued"),
+            Self::Running => write!(f, "running"),
+            Self::Succeeded => write!(f, "succeeded"),
+            Self::Dead => write!(f, "dead"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JobDto {
+    pub id: String,
+    pub kind: JobKind,
+
+    /// JSON input of the job handler
+    pub payload: String,
+    pub status: JobStatus,
+    pub attempts: i64,
+    pub max_attempts: i64,
+    pub run_at: i64,
+
+    /// A running job whose lock expired was abandoned and is claimed again
+    pub locked_until: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub finished_at: Option<i64>,
+}
+
+impl SparseFields for JobDto {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "kind",
+        "payload",
+        "status",
+        "attempts",
+        "max_attempts",
+        "run_at",
+        "locked_until",
+        "last_error",
+        "created_at",
+        "updated_at",
+        "finished_at",
+    ];
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ListJobsParamsDto {
+    pub status: Option<String>,
+}
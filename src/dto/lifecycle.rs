@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::utils::SparseFields;
+use crate::{Error, Result};
+
+/// Version of the lifecycle event payload, bumped on breaking changes
+pub const LIFECYCLE_SCHEMA_VERSION: i32 = 1;
+
+/// User lifecycle topics downstream apps can subscribe to
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LifecycleTopic {
+    #[serde(rename = "user.provisioned")]
+    UserProvisioned,
+    #[serde(rename = "user.deactivated")]
+    UserDeactivated,
+    #[serde(rename = "membership.granted")]
+    MembershipGranted,
+    #[serde(rename = "membership.revoked")]
+    MembershipRevoked,
+}
+
+pub const LIFECYCLE_TOPICS: [LifecycleTopic; 4] = [
+    LifecycleTopic::UserProvisioned,
+    LifecycleTopic::UserDeactivated,
+    LifecycleTopic::MembershipGranted,
+    LifecycleTopic::MembershipRevoked,
+];
+
+impl LifecycleTopic {
+    /// Membership topics only go to apps linked to the org
+    pub fn is_membership(&self) -> bool {
+        matches!(self, Self::MembershipGranted | Self::MembershipRevoked)
+    }
+}
+
+impl TryFrom<&str> for LifecycleTopic {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "user.provisioned" => Ok(Self::UserProvisioned),
+            "user.deactivated" => Ok(Self::UserDeactivated),
+            "membership.granted" => Ok(Self::MembershipGranted),
+            "membership.revoked" => Ok(Self::MembershipRevoked),
+            _ => Err(Error::Validation {
+                msg: format!("Invalid lifecycle topic: {}", value),
+            }),
+        }
+    }
+}
+
+impl core::fmt::Display for LifecycleTopic {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::UserProvisioned => write!(f, "user.provisioned"),
+            Self::UserDeactivated => write!(f, "user.deactivated"),
+            Self::MembershipGranted => write!(f, "membership.granted"),
+            Self::MembershipRevoked => write!(f, "membership.revoked"),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LifecycleSubscriptionDto {
+    pub id: String,
+    pub app_id: String,
+    pub topic: LifecycleTopic,
+    pub url: String,
+
+    #[serde(skip_serializing)]
+    pub secret: String,
+
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl SparseFields for LifecycleSubscriptionDto {
+    const FIELDS: &'static [&'static str] =
+        &["id", "app_id", "topic", "url", "created_at", "updated_at"];
+}
+
+/// A subscription as returned once to its creator, with the signing secret
+#[derive(Clone, Serialize)]
+pub struct LifecycleSubscriptionSecretDto {
+    #[serde(flatten)]
+    pub subscription: LifecycleSubscriptionDto,
+    pub secret: String,
+}
+
+impl From<LifecycleSubscriptionDto> for LifecycleSubscriptionSecretDto {
+    fn from(subscription: LifecycleSubscriptionDto) -> Self {
+        let secret = subscription.secret.clone();
+        Self {
+            subscription,
+            secret,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct NewLifecycleSubscriptionDto {
+    #[validate(length(min = 1, max = 50))]
+    pub topic: String,
+
+    #[validate(length(min = 1, max = 250))]
+    #[validate(url)]
+    pub url: String,
+}
+
+pub const DELIVERY_PENDING: &str = "pending";
+pub const DELIVERY_DELIVERED: &str = "delivered";
+pub const DELIVERY_FAILED: &str = "failed";
+
+/// One event queued for one subscription, kept as the delivery outbox
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LifecycleDeliveryDto {
+    pub id: String,
+    pub event_id: String,
+    pub subscription_id: String,
+    pub app_id: String,
+    pub topic: LifecycleTopic,
+
+    #[serde(skip_serializing)]
+    pub payload: String,
+
+    /// One of `pending`, `delivered` or `failed`
+    pub status: String,
+    pub attempts: i64,
+    pub next_attempt_at: i64,
+    pub last_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub delivered_at: Option<i64>,
+}
+
+impl SparseFields for LifecycleDeliveryDto {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "event_id",
+        "subscription_id",
+        "app_id",
+        "topic",
+        "status",
+        "attempts",
+        "next_attempt_at",
+        "last_status",
+        "last_error",
+        "created_at",
+        "updated_at",
+        "delivered_at",
+    ];
+}
+
+/// Payload of the `lifecycle.delivery` job
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LifecycleDeliveryJobDto {
+    pub delivery_id: String,
+}
+
+/// Stable identifiers downstream apps can key their mirrored records on
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LifecycleExternalIdsDto {
+    pub yaas_user_id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub yaas_org_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LifecycleUserDto {
+    pub email: String,
+    pub name: String,
+    pub status: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LifecycleMembershipDto {
+    pub org_id: String,
+    pub roles: Vec<String>,
+    pub status: String,
+}
+
+/// Payload posted to lifecycle subscribers
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LifecycleEventDto {
+    pub id: String,
+    pub topic: LifecycleTopic,
+    pub schema_version: i32,
+    pub occurred_at: i64,
+    pub external_ids: LifecycleExternalIdsDto,
+    pub user: LifecycleUserDto,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub membership: Option<LifecycleMembershipDto>,
+
+    /// Fields changed by the update that triggered the event, if any
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changed_fields: Vec<String>,
+}
+
+/// JSON Schema of one event payload, for consumers generating their types
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EventSchemaDto {
+    pub event: String,
+    pub version: i32,
+    pub schema: serde_json::Value,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EventSchemasDto {
+    pub schemas: Vec<EventSchemaDto>,
+}
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utils::AllocCounters;
+
+/// Allocator totals since the process started
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AllocatorStatsDto {
+    pub allocations: u64,
+    pub deallocations: u64,
+    pub allocated_bytes: u64,
+    pub live_bytes: u64,
+    pub peak_bytes: u64,
+}
+
+impl From<AllocCounters> for AllocatorStatsDto {
+    fn from(counters: AllocCounters) -> Self {
+        Self {
+            allocations: counters.allocations,
+            deallocations: counters.deallocations,
+            allocated_bytes: counters.allocated_bytes,
+            live_bytes: counters.live_bytes,
+            peak_bytes: counters.peak_bytes,
+        }
+    }
+}
+
+/// Process memory snapshot.
+///
+/// `rss_bytes` is only known on Linux, `allocator` only when the server is
+/// built with the `alloc-stats` feature.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MemoryStatsDto {
+    pub rss_bytes: Option<u64>,
+    pub allocator: Option<AllocatorStatsDto>,
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+/// Expand/contract phase of a schema migration.
+///
+/// Expand migrations only add to the schema and are safe while older servers
+/// still run. Contract migrations (`NN-contract-*.sql`) remove what the new
+/// code no longer reads and run once every server is upgraded.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MigrationPhase {
+    Expand,
+    Contract,
+}
+
+impl MigrationPhase {
+    pub fn from_name(name: &str) -> Self {
+        let rest = name.trim_start_matches(|c: char| c.is_ascii_digit());
+        match rest.starts_with("-contract-") {
+            true => Self::Contract,
+            false => Self::Expand,
+        }
+    }
+}
+
+impl core::fmt::Display for MigrationPhase {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Expand => write!(f, "expand"),
+            Self::Contract => write!(f, "contract"),
+        }
+    }
+}
+
+/// An unsafe statement found by the migration linter
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MigrationLintDto {
+    pub migration: String,
+    pub rule: String,
+    pub statement: String,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MigrateOptionsDto {
+    /// Apply migrations even when the linter flags them
+    pub allow_unsafe: bool,
+    /// Also apply contract phase migrations
+    pub contract: bool,
+    /// Record every migration as applied without running it
+    pub baseline: bool,
+    /// Report pending migrations and lints without applying anything
+    pub check: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MigrateReportDto {
+    pub baselined: Vec<String>,
+    pub applied: Vec<String>,
+    pub pending: Vec<String>,
+    pub lints: Vec<MigrationLintDto>,
+    /// Why the run stopped before applying every pending migration
+    pub blocked: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MigrationPhase;
+
+    #[test]
+    fn test_migration_phase_from_name() {
+        assert_eq!(
+            MigrationPhase::from_name("18-add-audit-columns.sql"),
+            MigrationPhase::Expand
+        );
+        assert_eq!(
+            MigrationPhase::from_name("21-contract-drop-legacy.sql"),
+            MigrationPhase::Contract
+        );
+        assert_eq!(
+            MigrationPhase::from_name("22-add-contract-notes.sql"),
+            MigrationPhase::Expand
+        );
+    }
+}
@@ -1,29 +1,123 @@
 mod actor;
+mod admin_rpc;
 mod app;
+mod app_environment;
+mod app_proof_key;
+mod app_uri_check;
+mod approval;
+mod backfill;
+mod batch_get;
+mod bulk;
+mod changes;
+mod email;
+mod entity_change;
 mod error;
+mod form_draft;
+mod idempotency_key;
+mod impersonation;
+mod integrity;
+mod job;
+mod lifecycle;
+mod memory;
+mod migration;
+mod notification;
 mod oauth;
 mod oauth_client;
 mod oauth_code;
+mod oauth_grant;
+mod oidc;
 mod org;
+mod org_access;
 mod org_app;
+mod org_invitation;
 mod org_member;
+mod org_owner_transfer;
+mod org_rate_limit;
+mod org_role;
+mod org_setting;
+mod org_transfer;
 mod pagination;
+mod palette;
 mod password;
+mod password_reset;
+mod permission_matrix;
+mod recovery;
 mod role;
+mod role_elevation;
+mod scopes;
+mod sorting;
+mod stat_counter;
+mod suggestion;
 mod superuser;
+mod team;
+mod token_revocation;
 mod user;
+mod user_email;
+mod user_erasure;
+mod user_export;
+mod user_import;
+mod user_notification;
+mod user_profile;
+mod user_session;
 
 pub use actor::*;
+pub use admin_rpc::*;
 pub use app::*;
+pub use app_environment::*;
+pub use app_proof_key::*;
+pub use app_uri_check::*;
+pub use approval::*;
+pub use backfill::*;
+pub use batch_get::*;
+pub use bulk::*;
+pub use changes::*;
+pub use email::*;
+pub use entity_change::*;
 pub use error::*;
+pub use form_draft::*;
+pub use idempotency_key::*;
+pub use impersonation::*;
+pub use integrity::*;
+pub use job::*;
+pub use lifecycle::*;
+pub use memory::*;
+pub use migration::*;
+pub use notification::*;
 pub use oauth::*;
 pub use oauth_client::*;
 pub use oauth_code::*;
+pub use oauth_grant::*;
+pub use oidc::*;
 pub use org::*;
+pub use org_access::*;
 pub use org_app::*;
+pub use org_invitation::*;
 pub use org_member::*;
+pub use org_owner_transfer::*;
+pub use org_rate_limit::*;
+pub use org_role::*;
+pub use org_setting::*;
+pub use org_transfer::*;
 pub use pagination::*;
+pub use palette::*;
 pub use password::*;
+pub use password_reset::*;
+pub use permission_matrix::*;
+pub use recovery::*;
 pub use role::*;
+pub use role_elevation::*;
+pub use scopes::*;
+pub use sorting::*;
+pub use stat_counter::*;
+pub use suggestion::*;
 pub use superuser::*;
+pub use team::*;
+pub use token_revocation::*;
 pub use user::*;
+pub use user_email::*;
+pub use user_erasure::*;
+pub use user_export::*;
+pub use user_import::*;
+pub use user_notification::*;
+pub use user_profile::*;
+pub use user_session::*;
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// How an org admin wants to hear about pending memberships
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum NotificationDelivery {
+    Instant,
+    #[default]
+    Digest,
+    Off,
+}
+
+impl TryFrom<&str> for NotificationDelivery {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "instant" => Ok(Self::Instant),
+            "digest" => Ok(Self::Digest),
+            "off" => Ok(Self::Off),
+            _ => Err(Error::Validation {
+                msg: format!("Invalid notification delivery: {}", value),
+            }),
+        }
+    }
+}
+
+impl core::fmt::Display for NotificationDelivery {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Instant => write!(f, "instant"),
+            Self::Digest => write!(f, "digest"),
+            Self::Off => write!(f, "off"),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NotificationPrefDto {
+    pub user_id: String,
+    pub delivery: NotificationDelivery,
+}
+
+/// Org admin with the number of pending memberships in one of their orgs
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PendingMembersRowDto {
+    pub admin_id: String,
+    pub admin_email: String,
+    pub delivery: NotificationDelivery,
+    pub org_id: String,
+    pub org_name: String,
+    pub pending_count: i64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OrgPendingSummaryDto {
+    pub org_id: String,
+    pub org_name: String,
+    pub pending_count: i64,
+}
+
+/// Digest sent to one org admin, covering all of their orgs
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AdminDigestDto {
+    pub admin_id: String,
+    pub admin_email: String,
+    pub orgs: Vec<OrgPendingSummaryDto>,
+}
@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
+use crate::dto::Scope;
+use crate::utils::{Redact, redacted_debug};
+use crate::validators;
+
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct OauthAuthorizeDto {
     #[validate(length(equal = 36))]
@@ -15,6 +19,21 @@ pub struct OauthAuthorizeDto {
 
     #[validate(length(min = 1, max = 250))]
     pub state: String,
+
+    /// OpenID Connect clients send it to match the ID token to the request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, max = 250))]
+    pub nonce: Option<String>,
+}
+
+/// Consent screen of an authorize request
+#[derive(Clone, Debug)]
+pub struct OauthConsentDto {
+    pub app_name: String,
+    pub org_name: String,
+    pub scopes: Vec<Scope>,
+    /// False when an earlier approval already covers every scope
+    pub required: bool,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -23,8 +42,13 @@ pub struct OauthAuthorizationCodeDto {
     pub state: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[derive(Clone, Serialize, Deserialize, Validate)]
 pub struct OauthTokenRequestDto {
+    /// Required, always `authorization_code`, see
+    /// `OauthClientCredentialsRequestDto` for `client_credentials`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grant_type: Option<String>,
+
     #[validate(length(equal = 36))]
     pub client_id: String,
 
@@ -42,9 +66,132 @@ pub struct OauthTokenRequestDto {
     pub redirect_uri: String,
 }
 
+impl Redact for OauthTokenRequestDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["client_secret", "code"];
+}
+
+redacted_debug!(OauthTokenRequestDto);
+
+/// Token request of a server-to-server app, see RFC 6749 section 4.4
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct OauthClientCredentialsRequestDto {
+    /// Always `client_credentials`
+    pub grant_type: String,
+
+    #[validate(length(equal = 36))]
+    pub client_id: String,
+
+    #[validate(length(equal = 36))]
+    pub client_secret: String,
+
+    /// Org the app is linked to, the token acts on it
+    #[validate(custom(function = "validators::prefixed_uuid"))]
+    pub org_id: String,
+
+    /// `oauth`, the default when left out, or `org.read`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, max = 250))]
+    pub scope: Option<String>,
+}
+
+impl Redact for OauthClientCredentialsRequestDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["client_secret"];
+}
+
+redacted_debug!(OauthClientCredentialsRequestDto);
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct OauthTokenResponseDto {
     pub access_token: String,
     pub scope: String,
     pub token_type: String,
+
+    /// Set when the token is bound to the app's proof key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+
+    /// Signed OpenID Connect ID token, for the `openid` scope
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+}
+
+impl Redact for OauthTokenResponseDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["access_token", "id_token"];
+}
+
+redacted_debug!(OauthTokenResponseDto);
+
+/// Token introspection request of a resource server, see RFC 7662
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct OauthIntrospectRequestDto {
+    #[validate(length(equal = 36))]
+    pub client_id: String,
+
+    #[validate(length(equal = 36))]
+    pub client_secret: String,
+
+    #[validate(length(min = 1, max = 4096))]
+    pub token: String,
+
+    /// Accepted for compatibility, only access tokens are issued
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_type_hint: Option<String>,
+}
+
+impl Redact for OauthIntrospectRequestDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["client_secret", "token"];
+}
+
+redacted_debug!(OauthIntrospectRequestDto);
+
+/// Token revocation request of an app, see RFC 7009
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct OauthRevokeRequestDto {
+    #[validate(length(equal = 36))]
+    pub client_id: String,
+
+    #[validate(length(equal = 36))]
+    pub client_secret: String,
+
+    #[validate(length(min = 1, max = 4096))]
+    pub token: String,
+
+    /// Accepted for compatibility, only access tokens are issued
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_type_hint: Option<String>,
+}
+
+impl Redact for OauthRevokeRequestDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["client_secret", "token"];
+}
+
+redacted_debug!(OauthRevokeRequestDto);
+
+/// Introspection result, inactive tokens only carry `active`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct OauthIntrospectionDto {
+    pub active: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+
+    /// User id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org_id: Option<String>,
+
+    /// Unix timestamps in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
 }
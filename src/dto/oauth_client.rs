@@ -14,4 +14,8 @@ pub struct OauthClientLookupDto {
 #[derive(Clone, Serialize, Deserialize)]
 pub struct OauthClientAppDto {
     pub name: String,
+
+    /// Environment label when the client_id belongs to an app environment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
 }
@@ -13,6 +13,9 @@ pub struct OauthCodeDto {
     pub user_id: String,
     pub created_at: i64,
     pub expires_at: i64,
+
+    /// OpenID Connect nonce, echoed in the ID token
+    pub nonce: Option<String>,
 }
 
 #[derive(Clone, Deserialize, Validate)]
@@ -33,4 +36,7 @@ pub struct NewOauthCodeDto {
     pub app_id: String,
     pub org_id: String,
     pub user_id: String,
+
+    #[validate(length(min = 1, max = 250))]
+    pub nonce: Option<String>,
 }
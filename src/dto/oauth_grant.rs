@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// Record of a user authorizing an app within an org
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OauthGrantDto {
+    pub id: String,
+    pub user_id: String,
+    pub org_id: String,
+    pub app_id: String,
+    pub scope: String,
+    pub created_at: i64,
+    pub last_used_at: i64,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct NewOauthGrantDto {
+    pub user_id: String,
+    pub org_id: String,
+    pub app_id: String,
+    pub scope: String,
+}
+
+/// Grant details shown to the user on the connected apps listing
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AuthorizedAppDto {
+    pub app_id: String,
+    pub app_name: String,
+    pub org_id: String,
+    pub org_name: String,
+    pub scope: String,
+    pub created_at: i64,
+    pub last_used_at: i64,
+}
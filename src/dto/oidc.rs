@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+use crate::dto::UserDto;
+
+/// OpenID Provider metadata served at `/.well-known/openid-configuration`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OpenidConfigurationDto {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub userinfo_endpoint: String,
+    pub jwks_uri: String,
+    pub response_types_supported: Vec<String>,
+    pub grant_types_supported: Vec<String>,
+    pub subject_types_supported: Vec<String>,
+    pub id_token_signing_alg_values_supported: Vec<String>,
+    pub scopes_supported: Vec<String>,
+    pub token_endpoint_auth_methods_supported: Vec<String>,
+    pub claims_supported: Vec<String>,
+}
+
+/// Public half of a token signing key, RSA keys carry `n` and `e`, Ed25519 keys `crv` and `x`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JwkDto {
+    pub kty: String,
+
+    #[serde(rename = "use")]
+    pub key_use: String,
+    pub alg: String,
+    pub kid: String,
+
+    /// Base64url encoded modulus
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+
+    /// Base64url encoded public exponent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+
+    /// Base64url encoded public key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JwksDto {
+    pub keys: Vec<JwkDto>,
+}
+
+/// Standard claims of the signed in user
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserinfoDto {
+    pub sub: String,
+    pub name: String,
+    pub email: String,
+
+    /// Seconds since the epoch, as the OIDC spec requires
+    pub updated_at: i64,
+}
+
+impl From<UserDto> for UserinfoDto {
+    fn from(user: UserDto) -> Self {
+        Self {
+            sub: user.id,
+            name: user.name,
+            email: user.email,
+            updated_at: user.updated_at / 1000,
+        }
+    }
+}
@@ -3,6 +3,10 @@ use serde::{Deserialize, Serialize};
 use urlencoding::encode;
 use validator::Validate;
 
+use crate::dto::{OrgOwnerTransferDto, sort_query};
+use crate::utils::SparseFields;
+use crate::validators;
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct OrgDto {
     pub id: String,
@@ -13,13 +17,45 @@ pub struct OrgDto {
     pub owner_name: Option<String>,
     pub updated_at: i64,
     pub created_at: i64,
+
+    /// Kept up to date on writes and reconciled in the background
+    #[serde(default)]
+    pub member_count: i64,
+
+    #[serde(default)]
+    pub app_count: i64,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<i64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<String>,
+
+    /// Ownership transfer waiting for the new owner, only loaded on single org reads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_transfer: Option<OrgOwnerTransferDto>,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
-pub struct OrgOwnerSuggestionDto {
-    pub id: String,
-    pub email: String,
-    pub name: String,
+impl SparseFields for OrgDto {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "name",
+        "status",
+        "owner_id",
+        "owner_email",
+        "owner_name",
+        "updated_at",
+        "created_at",
+        "member_count",
+        "app_count",
+        "deleted_at",
+        "created_by",
+        "updated_by",
+        "pending_transfer",
+    ];
 }
 
 #[derive(Clone, Serialize, Deserialize, Validate)]
@@ -27,6 +63,7 @@ pub struct NewOrgDto {
     #[validate(length(min = 1, max = 100))]
     pub name: String,
 
+    #[validate(custom(function = "validators::prefixed_uuid"))]
     pub owner_id: String,
 }
 
@@ -38,78 +75,57 @@ pub struct UpdateOrgDto {
     #[validate(length(min = 1, max = 200))]
     pub status: Option<String>,
 
+    #[validate(custom(function = "validators::prefixed_uuid"))]
     pub owner_id: Option<String>,
 }
 
 #[derive(Clone, Deserialize, Validate)]
 pub struct ListOrgsParamsDto {
-    #[validate(range(min = 1, max = 1000))]
+    #[validate(range(min = 1))]
     pub page: Option<i32>,
 
-    #[validate(range(min = 1, max = 50))]
+    #[validate(range(min = 1))]
     pub per_page: Option<i32>,
 
     #[validate(length(min = 0, max = 50))]
     pub keyword: Option<String>,
-}
-
-impl Default for ListOrgsParamsDto {
-    fn default() -> Self {
-        Self {
-            keyword: None,
-            page: Some(1),
-            per_page: Some(10),
-        }
-    }
-}
-
-impl fmt::Display for ListOrgsParamsDto {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // Ideally, we want an empty string if all fields are None
-        if self.keyword.is_none() && self.page.is_none() && self.per_page.is_none() {
-            return write!(f, "");
-        }
 
-        let keyword = self.keyword.as_deref().unwrap_or("");
-        let page = self.page.unwrap_or(1);
-        let per_page = self.per_page.unwrap_or(10);
+    #[validate(custom(function = "validators::org_sort"))]
+    pub sort_by: Option<String>,
 
-        write!(
-            f,
-            "page={}&per_page={}&keyword={}",
-            page,
-            per_page,
-            encode(keyword)
-        )
-    }
-}
+    #[validate(custom(function = "validators::sort_dir"))]
+    pub sort_dir: Option<String>,
 
-#[derive(Clone, Deserialize, Validate)]
-pub struct ListOrgOwnerSuggestionsParamsDto {
-    #[validate(range(min = 1, max = 1000))]
-    pub page: Option<i32>,
-
-    #[validate(range(min = 1, max = 50))]
-    pub per_page: Option<i32>,
-
-    #[validate(length(min = 0, max = 50))]
-    pub keyword: Option<String>,
+    /// Opaque `next_cursor` of the previous page, switches to keyset pagination
+    #[validate(length(min = 1, max = 500))]
+    pub cursor: Option<String>,
 
-    pub exclude_id: Option<String>,
+    #[validate(range(min = 1))]
+    pub limit: Option<i32>,
 }
 
-impl Default for ListOrgOwnerSuggestionsParamsDto {
+impl Default for ListOrgsParamsDto {
     fn default() -> Self {
         Self {
             keyword: None,
+            sort_by: None,
+            sort_dir: None,
             page: Some(1),
             per_page: Some(10),
-            exclude_id: None,
+            cursor: None,
+            limit: None,
         }
     }
 }
 
-impl fmt::Display for ListOrgOwnerSuggestionsParamsDto {
+impl ListOrgsParamsDto {
+    /// Keyset pagination is used once a cursor or a limit is passed
+    pub fn uses_cursor(&self) -> bool {
+        self.cursor.is_some() || self.limit.is_some()
+    }
+}
+
+impl fmt::Display for ListOrgsParamsDto {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // Ideally, we want an empty string if all fields are None
         if self.keyword.is_none() && self.page.is_none() && self.per_page.is_none() {
@@ -119,10 +135,6 @@ impl fmt::Display for ListOrgOwnerSuggestionsParamsDto {
         let keyword = self.keyword.as_deref().unwrap_or("");
         let page = self.page.unwrap_or(1);
         let per_page = self.per_page.unwrap_or(10);
-        let exclude_id_str = match self.exclude_id.as_ref() {
-            Some(id) => format!("&exclude_id={}", id),
-            None => "".to_string(),
-        };
 
         write!(
             f,
@@ -130,7 +142,7 @@ impl fmt::Display for ListOrgOwnerSuggestionsParamsDto {
             page,
             per_page,
             encode(keyword),
-            exclude_id_str
+            sort_query(self.sort_by.as_deref(), self.sort_dir.as_deref())
         )
     }
 }
@@ -0,0 +1,90 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use snafu::ensure;
+
+use crate::Result;
+use crate::error::ValidationSnafu;
+
+/// Longest date range a single access log export may cover
+pub const ORG_ACCESS_EXPORT_MAX_DAYS: i64 = 366;
+
+/// Distinct access of a user within an org context on a given UTC day.
+///
+/// Requests are sampled, `samples` counts the sampling windows the user was
+/// seen in rather than individual requests.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrgAccessDto {
+    pub org_id: String,
+    pub user_id: String,
+    pub day: String,
+    pub email: Option<String>,
+    pub name: Option<String>,
+    pub first_seen_at: i64,
+    pub last_seen_at: i64,
+    pub samples: i64,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct OrgAccessExportParamsDto {
+    /// First day to export, `YYYY-MM-DD`
+    pub from: String,
+    /// Last day to export, inclusive
+    pub to: String,
+}
+
+impl OrgAccessExportParamsDto {
+    /// Validated `(from, to)` days
+    pub fn range(&self) -> Result<(NaiveDate, NaiveDate)> {
+        let parse = |value: &str| {
+            NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| {
+                crate::Error::Validation {
+                    msg: format!("Invalid date: {}. Use YYYY-MM-DD.", value),
+                }
+            })
+        };
+
+        let from = parse(&self.from)?;
+        let to = parse(&self.to)?;
+
+        ensure!(
+            from <= to,
+            ValidationSnafu {
+                msg: "from must not be after to".to_string(),
+            }
+        );
+        ensure!(
+            (to - from).num_days() < ORG_ACCESS_EXPORT_MAX_DAYS,
+            ValidationSnafu {
+                msg: format!(
+                    "Date range must not exceed {} days",
+                    ORG_ACCESS_EXPORT_MAX_DAYS
+                ),
+            }
+        );
+
+        Ok((from, to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OrgAccessExportParamsDto;
+
+    fn params(from: &str, to: &str) -> OrgAccessExportParamsDto {
+        OrgAccessExportParamsDto {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_export_range() {
+        let (from, to) = params("2026-03-01", "2026-03-31").range().expect("range");
+        assert_eq!(from.to_string(), "2026-03-01");
+        assert_eq!(to.to_string(), "2026-03-31");
+
+        assert!(params("2026-03-31", "2026-03-01").range().is_err());
+        assert!(params("2026-03", "2026-03-31").range().is_err());
+        assert!(params("2025-01-01", "2026-03-31").range().is_err());
+    }
+}
@@ -3,12 +3,20 @@ use serde::{Deserialize, Serialize};
 use urlencoding::encode;
 use validator::Validate;
 
+use crate::dto::sort_query;
+use crate::validators;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct OrgAppDto {
     pub id: String,
     pub org_id: String,
     pub app_id: String,
     pub app_name: Option<String>,
+
+    /// Scopes the org allows for the app, `None` keeps every scope the app declares
+    #[serde(default)]
+    pub scopes: Option<Vec<String>>,
+
     pub created_at: i64,
 }
 
@@ -20,25 +28,41 @@ pub struct OrgAppSuggestionDto {
 
 #[derive(Clone, Serialize, Deserialize, Validate)]
 pub struct NewOrgAppDto {
+    #[validate(custom(function = "validators::prefixed_uuid"))]
     pub app_id: String,
 }
 
+/// Narrows the scopes an org app link grants, `null` lifts the restriction
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateOrgAppDto {
+    #[validate(custom(function = "validators::app_scopes"))]
+    pub scopes: Option<Vec<String>>,
+}
+
 #[derive(Clone, Deserialize, Validate)]
 pub struct ListOrgAppsParamsDto {
-    #[validate(range(min = 1, max = 1000))]
+    #[validate(range(min = 1))]
     pub page: Option<i32>,
 
-    #[validate(range(min = 1, max = 50))]
+    #[validate(range(min = 1))]
     pub per_page: Option<i32>,
 
     #[validate(length(min = 0, max = 50))]
     pub keyword: Option<String>,
+
+    #[validate(custom(function = "validators::org_app_sort"))]
+    pub sort_by: Option<String>,
+
+    #[validate(custom(function = "validators::sort_dir"))]
+    pub sort_dir: Option<String>,
 }
 
 impl Default for ListOrgAppsParamsDto {
     fn default() -> Self {
         Self {
             keyword: None,
+            sort_by: None,
+            sort_dir: None,
             page: Some(1),
             per_page: Some(10),
         }
@@ -58,10 +82,11 @@ impl fmt::Display for ListOrgAppsParamsDto {
 
         write!(
             f,
-            "page={}&per_page={}&keyword={}",
+            "page={}&per_page={}&keyword={}{}",
             page,
             per_page,
-            encode(keyword)
+            encode(keyword),
+            sort_query(self.sort_by.as_deref(), self.sort_dir.as_deref())
         )
     }
 }
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::dto::{NewUserWithPasswordDto, Role, UserDto};
+use crate::utils::SparseFields;
+use crate::validators;
+
+/// Invitation to join an org sent to an email address.
+///
+/// Only the hash of the acceptance token is stored, the plain token is part
+/// of the link sent to the invitee.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrgInvitationDto {
+    pub id: String,
+    pub org_id: String,
+    pub email: String,
+    pub roles: Vec<Role>,
+
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub expires_at: i64,
+    pub accepted_at: Option<i64>,
+    pub accepted_by: Option<String>,
+    pub created_by: Option<String>,
+
+    /// Outbox email carrying the latest link
+    pub email_id: Option<String>,
+
+    /// Status of that email, `pending`, `delivered` or `failed`
+    pub delivery_status: Option<String>,
+}
+
+impl SparseFields for OrgInvitationDto {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "org_id",
+        "email",
+        "roles",
+        "created_at",
+        "updated_at",
+        "expires_at",
+        "accepted_at",
+        "accepted_by",
+        "created_by",
+        "email_id",
+        "delivery_status",
+    ];
+}
+
+impl OrgInvitationDto {
+    pub fn is_usable(&self, now: i64) -> bool {
+        self.accepted_at.is_none() && self.expires_at > now
+    }
+
+    pub fn status(&self, now: i64) -> &'static str {
+        match (self.accepted_at, self.expires_at > now) {
+            (Some(_), _) => "accepted",
+            (None, true) => "pending",
+            (None, false) => "expired",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct NewOrgInvitationDto {
+    #[validate(email)]
+    #[validate(length(min = 1, max = 250))]
+    pub email: String,
+
+    /// The org's default member role when left out
+    #[serde(default)]
+    #[validate(length(min = 1))]
+    #[validate(custom(function = "validators::roles"))]
+    pub roles: Vec<String>,
+}
+
+/// Account that accepts an invitation, new accounts come with a hashed password
+pub enum InvitationAccount {
+    New(NewUserWithPasswordDto),
+    Existing(UserDto),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invitation_status() {
+        let mut invitation = OrgInvitationDto {
+            id: "inv_1".to_string(),
+            org_id: "org_1".to_string(),
+            email: "invitee@example.com".to_string(),
+            roles: vec![Role::OrgViewer],
+            token_hash: "hash".to_string(),
+            created_at: 100,
+            updated_at: 100,
+            expires_at: 200,
+            accepted_at: None,
+            accepted_by: None,
+            created_by: None,
+            email_id: None,
+            delivery_status: None,
+        };
+
+        assert_eq!(invitation.status(150), "pending");
+        assert!(invitation.is_usable(150));
+        assert_eq!(invitation.status(200), "expired");
+        assert!(!invitation.is_usable(200));
+
+        invitation.accepted_at = Some(150);
+        assert_eq!(invitation.status(150), "accepted");
+        assert!(!invitation.is_usable(150));
+    }
+}
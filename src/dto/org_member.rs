@@ -3,7 +3,8 @@ use serde::{Deserialize, Serialize};
 use urlencoding::encode;
 use validator::Validate;
 
-use crate::dto::Role;
+use crate::dto::{Role, sort_query};
+use crate::utils::SparseFields;
 use crate::validators;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +20,20 @@ pub struct OrgMemberDto {
     pub updated_at: i64,
 }
 
+impl SparseFields for OrgMemberDto {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "org_id",
+        "user_id",
+        "member_email",
+        "member_name",
+        "roles",
+        "status",
+        "created_at",
+        "updated_at",
+    ];
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct OrgMembershipDto {
     pub org_id: String,
@@ -27,17 +42,13 @@ pub struct OrgMembershipDto {
     pub roles: Vec<Role>,
 }
 
-#[derive(Clone, Serialize, Deserialize)]
-pub struct OrgMemberSuggestionDto {
-    pub id: String,
-    pub email: String,
-    pub name: String,
-}
-
 #[derive(Clone, Serialize, Deserialize, Validate)]
 pub struct NewOrgMemberDto {
+    #[validate(custom(function = "validators::prefixed_uuid"))]
     pub user_id: String,
 
+    /// The org's default member role when left out
+    #[serde(default)]
     #[validate(custom(function = "validators::roles"))]
     pub roles: Vec<String>,
 
@@ -45,6 +56,12 @@ pub struct NewOrgMemberDto {
     pub status: String,
 }
 
+/// Memberships to assign in one go, existing members get the new roles and status
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BulkOrgMembersDto {
+    pub members: Vec<NewOrgMemberDto>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct UpdateOrgMemberDto {
     #[validate(custom(function = "validators::roles"))]
@@ -56,15 +73,28 @@ pub struct UpdateOrgMemberDto {
 
 #[derive(Clone, Debug, Deserialize, Validate)]
 pub struct ListOrgMembersParamsDto {
-    #[validate(range(min = 1, max = 1000))]
+    #[validate(range(min = 1))]
     pub page: Option<i32>,
 
-    #[validate(range(min = 1, max = 50))]
+    #[validate(range(min = 1))]
     pub per_page: Option<i32>,
 
     #[validate(length(min = 0, max = 50))]
     pub keyword: Option<String>,
 
+    #[validate(custom(function = "validators::org_member_sort"))]
+    pub sort_by: Option<String>,
+
+    #[validate(custom(function = "validators::sort_dir"))]
+    pub sort_dir: Option<String>,
+
+    /// Opaque `next_cursor` of the previous page, switches to keyset pagination
+    #[validate(length(min = 1, max = 500))]
+    pub cursor: Option<String>,
+
+    #[validate(range(min = 1))]
+    pub limit: Option<i32>,
+
     pub next: Option<String>,
 }
 
@@ -72,13 +102,24 @@ impl Default for ListOrgMembersParamsDto {
     fn default() -> Self {
         Self {
             keyword: None,
+            sort_by: None,
+            sort_dir: None,
             page: Some(1),
             per_page: Some(10),
+            cursor: None,
+            limit: None,
             next: None,
         }
     }
 }
 
+impl ListOrgMembersParamsDto {
+    /// Keyset pagination is used once a cursor or a limit is passed
+    pub fn uses_cursor(&self) -> bool {
+        self.cursor.is_some() || self.limit.is_some()
+    }
+}
+
 impl fmt::Display for ListOrgMembersParamsDto {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // Ideally, we want an empty string if all fields are None
@@ -97,11 +138,12 @@ impl fmt::Display for ListOrgMembersParamsDto {
 
         write!(
             f,
-            "page={}&per_page={}&keyword={}&next={}",
+            "page={}&per_page={}&keyword={}&next={}{}",
             page,
             per_page,
             encode(keyword),
-            encode(next)
+            encode(next),
+            sort_query(self.sort_by.as_deref(), self.sort_dir.as_deref())
         )
     }
 }
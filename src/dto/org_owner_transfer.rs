@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::validators;
+
+/// Handover of an org to one of its members, done once the member accepts.
+///
+/// Only the hash of the acceptance token is stored, the plain token is part
+/// of the link sent to the new owner.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrgOwnerTransferDto {
+    pub id: String,
+    pub org_id: String,
+    pub from_user_id: String,
+    pub to_user_id: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_email: Option<String>,
+
+    #[serde(skip_serializing, default)]
+    pub token_hash: String,
+
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub expires_at: i64,
+    pub accepted_at: Option<i64>,
+    pub cancelled_at: Option<i64>,
+    pub created_by: Option<String>,
+}
+
+impl OrgOwnerTransferDto {
+    pub fn is_usable(&self, now: i64) -> bool {
+        self.status(now) == "pending"
+    }
+
+    pub fn status(&self, now: i64) -> &'static str {
+        match (self.accepted_at, self.cancelled_at, self.expires_at > now) {
+            (Some(_), _, _) => "accepted",
+            (None, Some(_), _) => "cancelled",
+            (None, None, true) => "pending",
+            (None, None, false) => "expired",
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct NewOrgOwnerTransferDto {
+    /// Member who becomes the owner once they accept
+    #[validate(custom(function = "validators::prefixed_uuid"))]
+    pub user_id: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_status() {
+        let mut transfer = OrgOwnerTransferDto {
+            id: "otr_1".to_string(),
+            org_id: "org_1".to_string(),
+            from_user_id: "usr_1".to_string(),
+            to_user_id: "usr_2".to_string(),
+            to_email: None,
+            token_hash: "hash".to_string(),
+            created_at: 100,
+            updated_at: 100,
+            expires_at: 200,
+            accepted_at: None,
+            cancelled_at: None,
+            created_by: None,
+        };
+
+        assert_eq!(transfer.status(150), "pending");
+        assert!(transfer.is_usable(150));
+        assert_eq!(transfer.status(200), "expired");
+
+        transfer.cancelled_at = Some(150);
+        assert_eq!(transfer.status(150), "cancelled");
+        assert!(!transfer.is_usable(150));
+
+        transfer.accepted_at = Some(150);
+        assert_eq!(transfer.status(150), "accepted");
+    }
+}
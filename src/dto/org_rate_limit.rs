@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Request budget of an org, overrides the deployment defaults
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrgRateLimitDto {
+    pub org_id: String,
+    pub requests_per_min: i64,
+    pub burst: i64,
+    pub updated_at: i64,
+    pub updated_by: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateOrgRateLimitDto {
+    #[validate(range(min = 1, max = 100000))]
+    pub requests_per_min: i64,
+
+    #[validate(range(min = 1, max = 10000))]
+    pub burst: i64,
+}
+
+/// Budget in effect for an org and what is left of it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrgRateUsageDto {
+    pub org_id: String,
+    /// False when neither the org nor the deployment sets a budget
+    pub enabled: bool,
+    pub requests_per_min: i64,
+    pub burst: i64,
+    /// `org` when the org overrides the deployment defaults, `default` otherwise
+    pub source: String,
+    /// Requests that can be made right now
+    pub remaining: i64,
+}
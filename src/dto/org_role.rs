@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::dto::Permission;
+use crate::utils::SparseFields;
+use crate::validators;
+
+/// Role defined by an org on top of the built-in roles.
+///
+/// Members holding it gain its permissions next to those of their built-in roles.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrgRoleDto {
+    pub id: String,
+    pub org_id: String,
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<Permission>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl SparseFields for OrgRoleDto {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "org_id",
+        "name",
+        "description",
+        "permissions",
+        "created_at",
+        "updated_at",
+    ];
+}
+
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct NewOrgRoleDto {
+    #[validate(length(min = 1, max = 50))]
+    #[validate(custom(function = "validators::anyname"))]
+    pub name: String,
+
+    #[validate(length(max = 250))]
+    #[serde(default)]
+    pub description: String,
+
+    #[validate(length(min = 1))]
+    #[validate(custom(function = "validators::custom_role_permissions"))]
+    pub permissions: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateOrgRoleDto {
+    #[validate(length(min = 1, max = 50))]
+    #[validate(custom(function = "validators::anyname"))]
+    pub name: Option<String>,
+
+    #[validate(length(max = 250))]
+    pub description: Option<String>,
+
+    #[validate(length(min = 1))]
+    #[validate(custom(function = "validators::custom_role_permissions"))]
+    pub permissions: Option<Vec<String>>,
+}
+
+/// Custom roles of a member, replaces the previous assignment
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OrgMemberRolesDto {
+    pub role_ids: Vec<String>,
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::dto::Role;
+use crate::validators;
+
+/// Role given to new members and invitees when none is picked
+pub const DEFAULT_MEMBER_ROLE: Role = Role::OrgViewer;
+
+/// Most email domains an org may restrict its members to
+pub const MAX_ALLOWED_EMAIL_DOMAINS: u64 = 20;
+
+/// Settings and branding of an org, the defaults apply until saved
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrgSettingsDto {
+    pub org_id: String,
+    pub logo_url: Option<String>,
+    pub default_member_role: String,
+
+    /// Members must have an email in one of these domains, any email when empty
+    pub allowed_email_domains: Vec<String>,
+}
+
+impl OrgSettingsDto {
+    pub fn defaults(org_id: &str) -> Self {
+        Self {
+            org_id: org_id.to_string(),
+            logo_url: None,
+            default_member_role: DEFAULT_MEMBER_ROLE.to_string(),
+            allowed_email_domains: Vec::new(),
+        }
+    }
+
+    /// Whether the email may belong to a member, subdomains are not included
+    pub fn allows_email(&self, email: &str) -> bool {
+        if self.allowed_email_domains.is_empty() {
+            return true;
+        }
+
+        let domain = email
+            .rsplit_once('@')
+            .map(|(_, domain)| domain.trim().to_lowercase())
+            .unwrap_or_default();
+
+        self.allowed_email_domains.contains(&domain)
+    }
+}
+
+/// Partial update, fields left out are kept and an empty logo URL clears it
+#[derive(Clone, Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateOrgSettingsDto {
+    #[validate(length(max = 500))]
+    #[validate(custom(function = "validators::https_url"))]
+    pub logo_url: Option<String>,
+
+    #[validate(custom(function = "validators::member_role"))]
+    pub default_member_role: Option<String>,
+
+    #[validate(length(max = MAX_ALLOWED_EMAIL_DOMAINS))]
+    #[validate(custom(function = "validators::email_domains"))]
+    pub allowed_email_domains: Option<Vec<String>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OrgSettingsDto;
+
+    #[test]
+    fn test_allows_email() {
+        let mut settings = OrgSettingsDto::defaults("org_1");
+        assert!(settings.allows_email("anyone@anywhere.com"));
+
+        settings.allowed_email_domains = vec!["example.com".to_string()];
+        assert!(settings.allows_email("jane@example.com"));
+        assert!(settings.allows_email("Jane@EXAMPLE.com"));
+        assert!(!settings.allows_email("jane@mail.example.com"));
+        assert!(!settings.allows_email("jane@example.com.evil.io"));
+        assert!(!settings.allows_email("no-domain"));
+    }
+}
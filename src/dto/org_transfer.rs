@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Bumped whenever the archive layout changes in a non additive way
+pub const ORG_ARCHIVE_FORMAT_VERSION: i32 = 1;
+
+/// Portable copy of an org, ids are only kept as references for remapping
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrgArchiveDto {
+    pub format_version: i32,
+    pub exported_at: i64,
+    pub org: OrgArchiveOrgDto,
+    pub members: Vec<OrgArchiveMemberDto>,
+    pub apps: Vec<OrgArchiveAppDto>,
+
+    /// Missing in archives exported before settings were carried over
+    #[serde(default)]
+    pub settings: Option<OrgArchiveSettingsDto>,
+
+    #[serde(default)]
+    pub roles: Vec<OrgArchiveRoleDto>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrgArchiveOrgDto {
+    pub id: String,
+    pub name: String,
+    pub status: String,
+    pub owner_email: Option<String>,
+}
+
+/// Members are referenced by email since user ids differ between environments
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrgArchiveMemberDto {
+    pub email: String,
+    pub roles: Vec<String>,
+    pub status: String,
+
+    /// Names of the custom org roles held, listed in the archive roles
+    #[serde(default)]
+    pub custom_roles: Vec<String>,
+}
+
+/// Client credentials are never exported, imported apps get fresh ones
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrgArchiveAppDto {
+    pub id: String,
+    pub name: String,
+    pub redirect_uri: String,
+
+    #[serde(default)]
+    pub extra_redirect_uris: Vec<String>,
+
+    /// `None` lets the app request every app scope
+    #[serde(default)]
+    pub scopes: Option<Vec<String>>,
+
+    /// Scopes the org link narrows the app to, `None` for no restriction
+    #[serde(default)]
+    pub org_scopes: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrgArchiveSettingsDto {
+    pub logo_url: Option<String>,
+    pub default_member_role: String,
+    pub allowed_email_domains: Vec<String>,
+}
+
+/// Custom org roles are referenced by name, role ids differ between environments
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrgArchiveRoleDto {
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<String>,
+}
+
+/// Existing record matched by name in the target environment
+#[derive(Clone, Debug, Serialize)]
+pub struct NamedRecordDto {
+    pub id: String,
+    pub name: String,
+}
+
+/// What to do when a record already exists in the target environment
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ImportConflictStrategy {
+    /// Leave existing records untouched, an existing org aborts the import
+    Skip,
+    /// Reuse existing records and fill in what is missing
+    Merge,
+    /// Create renamed copies next to the existing records
+    Rename,
+}
+
+impl TryFrom<&str> for ImportConflictStrategy {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "skip" => Ok(Self::Skip),
+            "merge" => Ok(Self::Merge),
+            "rename" => Ok(Self::Rename),
+            _ => Err(Error::Validation {
+                msg: format!(
+                    "Invalid conflict strategy: {}. Use skip, merge or rename.",
+                    value
+                ),
+            }),
+        }
+    }
+}
+
+impl core::fmt::Display for ImportConflictStrategy {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Skip => write!(f, "skip"),
+            Self::Merge => write!(f, "merge"),
+            Self::Rename => write!(f, "rename"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    Create,
+    Update,
+    Reuse,
+    Skip,
+}
+
+impl core::fmt::Display for ImportAction {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Create => write!(f, "create"),
+            Self::Update => write!(f, "update"),
+            Self::Reuse => write!(f, "reuse"),
+            Self::Skip => write!(f, "skip"),
+        }
+    }
+}
+
+/// Resolved org step, `target_id` is only known for existing orgs
+#[derive(Clone, Debug, Serialize)]
+pub struct OrgImportOrgPlanDto {
+    pub action: ImportAction,
+    pub target_id: Option<String>,
+    pub name: String,
+    pub status: String,
+    pub owner_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OrgImportAppPlanDto {
+    pub source_id: String,
+    pub action: ImportAction,
+    pub target_id: Option<String>,
+    pub name: String,
+    pub redirect_uri: String,
+    pub extra_redirect_uris: Vec<String>,
+
+    /// Only applied to created apps, existing apps keep their scopes
+    pub scopes: Option<Vec<String>>,
+
+    /// Only applied when the link is created
+    pub org_scopes: Option<Vec<String>>,
+    pub link: bool,
+}
+
+/// Settings are only written when the target org has none yet
+#[derive(Clone, Debug, Serialize)]
+pub struct OrgImportSettingsPlanDto {
+    pub action: ImportAction,
+    pub logo_url: Option<String>,
+    pub default_member_role: String,
+    pub allowed_email_domains: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OrgImportRolePlanDto {
+    pub action: ImportAction,
+    pub target_id: Option<String>,
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OrgImportMemberPlanDto {
+    pub email: String,
+    pub action: ImportAction,
+    pub user_id: Option<String>,
+    pub member_id: Option<String>,
+    pub roles: Vec<String>,
+    pub custom_roles: Vec<String>,
+    pub status: String,
+    pub note: Option<String>,
+}
+
+/// Everything the import will do, doubles as the dry-run preview
+#[derive(Clone, Debug, Serialize)]
+pub struct OrgImportPlanDto {
+    pub strategy: ImportConflictStrategy,
+    pub org: OrgImportOrgPlanDto,
+    pub settings: Option<OrgImportSettingsPlanDto>,
+    pub roles: Vec<OrgImportRolePlanDto>,
+    pub apps: Vec<OrgImportAppPlanDto>,
+    pub members: Vec<OrgImportMemberPlanDto>,
+}
+
+/// Source id to target id pairs produced by the import
+#[derive(Clone, Debug, Serialize)]
+pub struct IdMappingDto {
+    pub source_id: String,
+    pub target_id: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OrgImportResultDto {
+    pub org_id: String,
+    pub apps: Vec<IdMappingDto>,
+    pub roles_created: usize,
+    pub members_created: usize,
+    pub members_updated: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OrgImportReportDto {
+    pub dry_run: bool,
+    pub plan: OrgImportPlanDto,
+    pub result: Option<OrgImportResultDto>,
+}
@@ -1,7 +1,10 @@
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
 use core::fmt;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
+use crate::{Error, Result};
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PaginatedMeta {
     pub page: i32,
@@ -52,23 +55,20 @@ impl PaginationParams {
         total_records: i64,
         page_param: Option<i32>,
         per_page_param: Option<i32>,
-        max_per_page_param: Option<i32>,
+        limits: &PaginationLimits,
     ) -> Self {
         let mut page: i32 = 1;
-        let max_per_page: i32 = max_per_page_param.unwrap_or(50);
-        let mut per_page: i32 = max_per_page;
         let mut offset: i64 = 0;
 
-        if let Some(per_page_param) = per_page_param
-            && per_page_param > 0
-            && per_page_param <= max_per_page
-        {
-            per_page = per_page_param;
-        }
+        let per_page = match per_page_param {
+            Some(value) => value.clamp(limits.min_per_page, limits.max_per_page),
+            None => limits.max_per_page,
+        };
 
         let total_pages: i64 = (total_records as f64 / per_page as f64).ceil() as i64;
 
         if let Some(p) = page_param {
+            let p = p.min(limits.max_page);
             let p64 = p as i64;
             if p64 > 0 && p64 <= total_pages {
                 page = p;
@@ -85,12 +85,110 @@ impl PaginationParams {
     }
 }
 
+/// Bounds applied to every paginated listing.
+///
+/// Requested values outside these bounds are clamped instead of rejected.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PaginationLimits {
+    pub min_per_page: i32,
+    pub max_per_page: i32,
+    pub max_page: i32,
+}
+
+impl PaginationLimits {
+    /// Page size of a cursor listing, same bounds and default as `per_page`
+    pub fn cursor_limit(&self, limit: Option<i32>) -> i32 {
+        match limit {
+            Some(value) => value.clamp(self.min_per_page, self.max_per_page),
+            None => self.max_per_page,
+        }
+    }
+}
+
+/// Position of the last row of a page in a listing ordered by (sort key, id).
+///
+/// Clients only see the encoded form and pass it back unchanged.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub key: String,
+    pub id: String,
+}
+
+impl Cursor {
+    pub fn new(key: &str, id: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            id: id.to_string(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("cursor should serialize");
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    /// Rejects values that were not produced by `encode`
+    pub fn decode(value: &str) -> Result<Self> {
+        URL_SAFE_NO_PAD
+            .decode(value)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or_else(|| Error::Validation {
+                msg: "Cursor is invalid".to_string(),
+            })
+    }
+}
+
+/// Keyset page, without totals so large tables are never counted.
+///
+/// `next_cursor` is `None` on the last page.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CursorPage<T> {
+    pub data: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+impl<T> CursorPage<T> {
+    /// Builds the page from up to `limit + 1` rows, the extra row only signals more data
+    pub fn new(mut rows: Vec<T>, limit: i32, cursor_of: impl Fn(&T) -> Cursor) -> Self {
+        let limit = limit.max(0) as usize;
+        let has_more = rows.len() > limit;
+        rows.truncate(limit);
+
+        let next_cursor = match (has_more, rows.last()) {
+            (true, Some(last)) => Some(cursor_of(last).encode()),
+            _ => None,
+        };
+
+        Self {
+            data: rows,
+            next_cursor,
+        }
+    }
+}
+
+/// Request limits published for API clients
+#[derive(Clone, Debug, Serialize)]
+pub struct LimitsDto {
+    pub pagination: PaginationLimits,
+}
+
+impl Default for PaginationLimits {
+    fn default() -> Self {
+        Self {
+            min_per_page: 1,
+            max_per_page: 50,
+            max_page: 1000,
+        }
+    }
+}
+
 #[derive(Clone, Deserialize, Validate)]
 pub struct ListingParamsDto {
-    #[validate(range(min = 1, max = 1000))]
+    #[validate(range(min = 1))]
     pub page: Option<i32>,
 
-    #[validate(range(min = 1, max = 50))]
+    #[validate(range(min = 1))]
     pub per_page: Option<i32>,
 }
 
@@ -116,3 +214,65 @@ impl fmt::Display for ListingParamsDto {
         write!(f, "page={}&per_page={}", page, per_page,)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_per_page_is_clamped_to_limits() {
+        let limits = PaginationLimits::default();
+
+        let params = PaginationParams::new(500, Some(1), Some(100000), &limits);
+        assert_eq!(params.per_page, 50);
+        assert_eq!(params.total_pages, 10);
+
+        let params = PaginationParams::new(500, Some(1), Some(0), &limits);
+        assert_eq!(params.per_page, 1);
+
+        let params = PaginationParams::new(500, Some(1), None, &limits);
+        assert_eq!(params.per_page, 50);
+
+        let params = PaginationParams::new(500, Some(1), Some(50), &limits);
+        assert_eq!(params.per_page, 50);
+    }
+
+    #[test]
+    fn test_page_is_capped_to_max_page() {
+        let limits = PaginationLimits {
+            min_per_page: 1,
+            max_per_page: 10,
+            max_page: 3,
+        };
+
+        let params = PaginationParams::new(100, Some(50), Some(10), &limits);
+        assert_eq!(params.page, 3);
+        assert_eq!(params.offset, 20);
+
+        let params = PaginationParams::new(100, Some(3), Some(10), &limits);
+        assert_eq!(params.page, 3);
+
+        // Pages past the last one fall back to the first page
+        let params = PaginationParams::new(15, Some(3), Some(10), &limits);
+        assert_eq!(params.page, 1);
+        assert_eq!(params.offset, 0);
+    }
+
+    #[test]
+    fn test_cursor_page_from_extra_row() {
+        let rows = vec![("a", "1"), ("b", "2"), ("b", "3")];
+        let page = CursorPage::new(rows, 2, |(key, id)| Cursor::new(key, id));
+        assert_eq!(page.data.len(), 2);
+
+        let cursor = page.next_cursor.expect("more rows");
+        assert_eq!(Cursor::decode(&cursor).ok(), Some(Cursor::new("b", "2")));
+        assert!(Cursor::decode("not a cursor").is_err());
+
+        let page = CursorPage::new(vec![("a", "1")], 2, |(key, id)| Cursor::new(key, id));
+        assert!(page.next_cursor.is_none());
+
+        let limits = PaginationLimits::default();
+        assert_eq!(limits.cursor_limit(None), 50);
+        assert_eq!(limits.cursor_limit(Some(0)), 1);
+    }
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Clone, Default, Deserialize, Validate)]
+pub struct PaletteParamsDto {
+    #[validate(length(min = 0, max = 50))]
+    pub keyword: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PaletteItemDto {
+    pub kind: String,
+    pub label: String,
+    pub description: String,
+    pub url: String,
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
+use crate::utils::{Redact, redacted_debug};
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct PasswordDto {
     pub id: String,
@@ -9,12 +11,24 @@ pub struct PasswordDto {
     pub updated_at: i64,
 }
 
+impl Redact for PasswordDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["password"];
+}
+
+redacted_debug!(PasswordDto);
+
 #[derive(Clone, Serialize, Deserialize, Validate)]
 pub struct NewPasswordDto {
     #[validate(length(min = 8, max = 60))]
     pub password: String,
 }
 
+impl Redact for NewPasswordDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["password"];
+}
+
+redacted_debug!(NewPasswordDto);
+
 #[derive(Clone, Serialize, Deserialize, Validate)]
 pub struct ChangeCurrentPasswordDto {
     #[validate(length(min = 8, max = 60))]
@@ -23,3 +37,9 @@ pub struct ChangeCurrentPasswordDto {
     #[validate(length(min = 8, max = 60))]
     pub new_password: String,
 }
+
+impl Redact for ChangeCurrentPasswordDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["current_password", "new_password"];
+}
+
+redacted_debug!(ChangeCurrentPasswordDto);
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+/// Single-use forgot password token, only the hash of the secret is stored
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PasswordResetDto {
+    pub id: String,
+    pub user_id: String,
+
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub used_at: Option<i64>,
+
+    /// Outbox email carrying the link
+    pub email_id: Option<String>,
+
+    /// Status of that email, `pending`, `delivered` or `failed`
+    pub delivery_status: Option<String>,
+}
+
+impl PasswordResetDto {
+    pub fn is_usable(&self, now: i64) -> bool {
+        self.used_at.is_none() && self.expires_at > now
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NewPasswordResetDto {
+    pub user_id: String,
+    pub token_hash: String,
+    pub expires_at: i64,
+}
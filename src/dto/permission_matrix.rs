@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::dto::{Permission, Role};
+
+#[derive(Clone, Default, Deserialize, Validate)]
+pub struct PermissionMatrixParamsDto {
+    #[validate(length(min = 1, max = 10))]
+    pub format: Option<String>,
+
+    #[validate(length(equal = 36))]
+    pub org_id: Option<String>,
+}
+
+/// Permissions of a built-in role, or of a custom org role when `custom` is set
+#[derive(Clone, Serialize)]
+pub struct RolePermissionsDto {
+    pub role: String,
+    pub custom: bool,
+    pub permissions: Vec<Permission>,
+}
+
+/// A permission along with the roles that grant it
+#[derive(Clone, Serialize)]
+pub struct EffectivePermissionDto {
+    pub permission: Permission,
+    pub sources: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct MemberPermissionsDto {
+    pub org_id: String,
+    pub user_id: String,
+    pub roles: Vec<Role>,
+
+    /// Names of the custom org roles held by the member
+    pub custom_roles: Vec<String>,
+
+    /// Names of the teams the member belongs to
+    pub teams: Vec<String>,
+
+    /// Permissions only apply when both the membership and the org are active
+    pub active: bool,
+    pub permissions: Vec<EffectivePermissionDto>,
+}
+
+/// Effective permissions of a member before and after a role change
+#[derive(Clone, Serialize)]
+pub struct MemberPermissionImpactDto {
+    pub user_id: String,
+    pub member_email: Option<String>,
+    pub before: Vec<Permission>,
+    pub after: Vec<Permission>,
+    pub gained: Vec<Permission>,
+    pub lost: Vec<Permission>,
+}
+
+impl MemberPermissionImpactDto {
+    pub fn has_changes(&self) -> bool {
+        !self.gained.is_empty() || !self.lost.is_empty()
+    }
+}
+
+/// Members of an org affected by a role change
+#[derive(Clone, Serialize)]
+pub struct PermissionImpactDto {
+    pub org_id: String,
+    pub members: Vec<MemberPermissionImpactDto>,
+}
+
+impl PermissionImpactDto {
+    pub fn has_changes(&self) -> bool {
+        self.members.iter().any(|member| member.has_changes())
+    }
+}
+
+/// Label and help text of a permission, from `Permission::label` and `Permission::description`
+#[derive(Clone, Serialize)]
+pub struct PermissionInfoDto {
+    pub permission: Permission,
+    pub label: String,
+    pub description: String,
+}
+
+/// Label and help text of a role along with the permissions it grants
+#[derive(Clone, Serialize)]
+pub struct RoleInfoDto {
+    pub role: Role,
+    pub label: String,
+    pub description: String,
+    pub permissions: Vec<Permission>,
+}
+
+/// Help text for the permission tooltips and role pickers
+#[derive(Clone, Serialize)]
+pub struct PermissionHelpDto {
+    pub permissions: Vec<PermissionInfoDto>,
+    pub roles: Vec<RoleInfoDto>,
+}
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+/// One-time break-glass token, only the hash of the secret is stored
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecoveryTokenDto {
+    pub id: String,
+    pub user_id: String,
+
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub used_at: Option<i64>,
+}
+
+impl RecoveryTokenDto {
+    pub fn is_usable(&self, now: i64) -> bool {
+        self.used_at.is_none() && self.expires_at > now
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NewRecoveryTokenDto {
+    pub user_id: String,
+    pub token_hash: String,
+    pub expires_at: i64,
+}
@@ -13,6 +13,14 @@ pub enum Role {
     OrgViewer,
 }
 
+/// All built-in roles, from the most to the least privileged
+pub const ALL_ROLES: [Role; 4] = [
+    Role::Superuser,
+    Role::OrgAdmin,
+    Role::OrgEditor,
+    Role::OrgViewer,
+];
+
 #[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub enum Permission {
     UsersCreate,
@@ -66,6 +74,8 @@ pub enum Permission {
     FilesList,
     FilesView,
     FilesManage,
+
+    UsersErase,
 }
 
 #[derive(PartialEq, Eq, Hash, Debug, Clone)]
@@ -73,6 +83,10 @@ pub enum Scope {
     Auth,
     Vault,
     Oauth,
+    /// OpenID Connect, adds an ID token to the token exchange
+    Openid,
+    /// Read only access to the org, its members and apps
+    OrgRead,
 }
 
 impl TryFrom<&str> for Role {
@@ -100,6 +114,28 @@ impl core::fmt::Display for Role {
     }
 }
 
+impl Role {
+    /// Human label shown in the role pickers
+    pub fn label(&self) -> &'static str {
+        match self {
+            Role::Superuser => "Superuser",
+            Role::OrgAdmin => "Admin",
+            Role::OrgEditor => "Editor",
+            Role::OrgViewer => "Viewer",
+        }
+    }
+
+    /// One sentence on what the role is for, shown as help text
+    pub fn description(&self) -> &'static str {
+        match self {
+            Role::Superuser => "Manages every user, org and app of the deployment.",
+            Role::OrgAdmin => "Manages the org, its members and its apps.",
+            Role::OrgEditor => "Works with the org's files and sees its members and apps.",
+            Role::OrgViewer => "Read only access to the org's files, members and apps.",
+        }
+    }
+}
+
 pub fn to_roles(list: &[String]) -> Result<Vec<Role>> {
     let mut roles: Vec<Role> = Vec::with_capacity(list.len());
     let mut errors: Vec<String> = Vec::with_capacity(list.len());
@@ -129,6 +165,8 @@ impl TryFrom<&str> for Scope {
             "auth" => Ok(Scope::Auth),
             "vault" => Ok(Scope::Vault),
             "oauth" => Ok(Scope::Oauth),
+            "openid" => Ok(Scope::Openid),
+            "org.read" => Ok(Scope::OrgRead),
             _ => Err(format!("Invalid scope: {value}")),
         }
     }
@@ -148,6 +186,8 @@ impl core::fmt::Display for Scope {
             Scope::Auth => write!(f, "auth"),
             Scope::Vault => write!(f, "vault"),
             Scope::Oauth => write!(f, "oauth"),
+            Scope::Openid => write!(f, "openid"),
+            Scope::OrgRead => write!(f, "org.read"),
         }
     }
 }
@@ -248,6 +288,7 @@ impl TryFrom<&str> for Permission {
             "files.list" => Ok(Permission::FilesList),
             "files.view" => Ok(Permission::FilesView),
             "files.manage" => Ok(Permission::FilesManage),
+            "users.erase" => Ok(Permission::UsersErase),
 
             _ => Err(format!("Invalid permission: {value}")),
         }
@@ -316,6 +357,7 @@ impl core::fmt::Display for Permission {
             Permission::FilesList => write!(f, "files.list"),
             Permission::FilesView => write!(f, "files.view"),
             Permission::FilesManage => write!(f, "files.manage"),
+            Permission::UsersErase => write!(f, "users.erase"),
         }
     }
 }
@@ -371,6 +413,7 @@ pub fn role_permissions(role: &Role) -> Vec<Permission> {
             Permission::UsersList,
             Permission::UsersView,
             Permission::UsersManage,
+            Permission::UsersErase,
             Permission::AppsCreate,
             Permission::AppsEdit,
             Permission::AppsDelete,
@@ -476,6 +519,263 @@ pub fn roles_permissions(roles: &[Role]) -> Vec<Permission> {
     permissions.into_iter().collect()
 }
 
+/// Permissions an org's custom roles may grant.
+///
+/// Limited to what the org admin role grants so custom roles never reach
+/// beyond the org.
+pub fn custom_role_permissions() -> Vec<Permission> {
+    role_permissions(&Role::OrgAdmin)
+}
+
+/// Bumped whenever `role_permissions` or `ALL_PERMISSIONS` change, tokens
+/// carrying an older mask fall back to the roles stored in the database
+pub const PERMISSION_MASK_VERSION: u32 = 2;
+
+/// Bit positions of the permission mask, only ever append to this list
+pub const ALL_PERMISSIONS: [Permission; 45] = [
+    Permission::UsersCreate,
+    Permission::UsersEdit,
+    Permission::UsersDelete,
+    Permission::UsersList,
+    Permission::UsersView,
+    Permission::UsersManage,
+    Permission::AppsCreate,
+    Permission::AppsEdit,
+    Permission::AppsDelete,
+    Permission::AppsList,
+    Permission::AppsView,
+    Permission::AppsManage,
+    Permission::OrgsCreate,
+    Permission::OrgsEdit,
+    Permission::OrgsDelete,
+    Permission::OrgsList,
+    Permission::OrgsView,
+    Permission::OrgsManage,
+    Permission::OrgMembersCreate,
+    Permission::OrgMembersEdit,
+    Permission::OrgMembersDelete,
+    Permission::OrgMembersList,
+    Permission::OrgMembersView,
+    Permission::OrgMembersManage,
+    Permission::OrgAppsCreate,
+    Permission::OrgAppsEdit,
+    Permission::OrgAppsDelete,
+    Permission::OrgAppsList,
+    Permission::OrgAppsView,
+    Permission::OrgAppsManage,
+    Permission::BucketsEdit,
+    Permission::BucketsView,
+    Permission::DirsCreate,
+    Permission::DirsEdit,
+    Permission::DirsDelete,
+    Permission::DirsList,
+    Permission::DirsView,
+    Permission::DirsManage,
+    Permission::FilesCreate,
+    Permission::FilesEdit,
+    Permission::FilesDelete,
+    Permission::FilesList,
+    Permission::FilesView,
+    Permission::FilesManage,
+    Permission::UsersErase,
+];
+
+impl Permission {
+    /// Human label shown in the UI, ex: `Edit org members`
+    pub fn label(&self) -> &'static str {
+        self.metadata().0
+    }
+
+    /// One sentence on what the permission allows, shown as help text
+    pub fn description(&self) -> &'static str {
+        self.metadata().1
+    }
+
+    fn metadata(&self) -> (&'static str, &'static str) {
+        match self {
+            Permission::UsersCreate => ("Create users", "Add new user accounts."),
+            Permission::UsersEdit => (
+                "Edit users",
+                "Change the details of existing user accounts.",
+            ),
+            Permission::UsersDelete => ("Delete users", "Remove user accounts."),
+            Permission::UsersList => ("List users", "Search and browse user accounts."),
+            Permission::UsersView => ("View users", "Open the details of user accounts."),
+            Permission::UsersManage => (
+                "Manage users",
+                "Full control over user accounts, including actions not covered by the other permissions.",
+            ),
+            Permission::AppsCreate => ("Create apps", "Add new OAuth apps."),
+            Permission::AppsEdit => ("Edit apps", "Change the details of existing OAuth apps."),
+            Permission::AppsDelete => ("Delete apps", "Remove OAuth apps."),
+            Permission::AppsList => ("List apps", "Search and browse OAuth apps."),
+            Permission::AppsView => ("View apps", "Open the details of OAuth apps."),
+            Permission::AppsManage => (
+                "Manage apps",
+                "Full control over OAuth apps, including actions not covered by the other permissions.",
+            ),
+            Permission::OrgsCreate => ("Create orgs", "Add new orgs."),
+            Permission::OrgsEdit => ("Edit orgs", "Change the details of existing orgs."),
+            Permission::OrgsDelete => ("Delete orgs", "Remove orgs."),
+            Permission::OrgsList => ("List orgs", "Search and browse orgs."),
+            Permission::OrgsView => ("View orgs", "Open the details of orgs."),
+            Permission::OrgsManage => (
+                "Manage orgs",
+                "Full control over orgs, including actions not covered by the other permissions.",
+            ),
+            Permission::OrgMembersCreate => ("Create org members", "Add new members of the org."),
+            Permission::OrgMembersEdit => (
+                "Edit org members",
+                "Change the details of existing members of the org.",
+            ),
+            Permission::OrgMembersDelete => ("Delete org members", "Remove members of the org."),
+            Permission::OrgMembersList => {
+                ("List org members", "Search and browse members of the org.")
+            }
+            Permission::OrgMembersView => (
+                "View org members",
+                "Open the details of members of the org.",
+            ),
+            Permission::OrgMembersManage => (
+                "Manage org members",
+                "Full control over members of the org, including actions not covered by the other permissions.",
+            ),
+            Permission::OrgAppsCreate => ("Create org apps", "Add new apps linked to the org."),
+            Permission::OrgAppsEdit => (
+                "Edit org apps",
+                "Change the details of existing apps linked to the org.",
+            ),
+            Permission::OrgAppsDelete => ("Delete org apps", "Remove apps linked to the org."),
+            Permission::OrgAppsList => {
+                ("List org apps", "Search and browse apps linked to the org.")
+            }
+            Permission::OrgAppsView => (
+                "View org apps",
+                "Open the details of apps linked to the org.",
+            ),
+            Permission::OrgAppsManage => (
+                "Manage org apps",
+                "Full control over apps linked to the org, including actions not covered by the other permissions.",
+            ),
+            Permission::BucketsEdit => ("Edit buckets", "Change the settings of storage buckets."),
+            Permission::BucketsView => ("View buckets", "Open the details of storage buckets."),
+            Permission::DirsCreate => ("Create directories", "Add new stored directories."),
+            Permission::DirsEdit => (
+                "Edit directories",
+                "Change the details of existing stored directories.",
+            ),
+            Permission::DirsDelete => ("Delete directories", "Remove stored directories."),
+            Permission::DirsList => ("List directories", "Search and browse stored directories."),
+            Permission::DirsView => (
+                "View directories",
+                "Open the details of stored directories.",
+            ),
+            Permission::DirsManage => (
+                "Manage directories",
+                "Full control over stored directories, including actions not covered by the other permissions.",
+            ),
+            Permission::FilesCreate => ("Create files", "Add new stored files."),
+            Permission::FilesEdit => ("Edit files", "Change the details of existing stored files."),
+            Permission::FilesDelete => ("Delete files", "Remove stored files."),
+            Permission::FilesList => ("List files", "Search and browse stored files."),
+            Permission::FilesView => ("View files", "Open the details of stored files."),
+            Permission::FilesManage => (
+                "Manage files",
+                "Full control over stored files, including actions not covered by the other permissions.",
+            ),
+            Permission::UsersErase => (
+                "Erase users",
+                "Irreversibly anonymize the personal data of user accounts.",
+            ),
+        }
+    }
+
+    /// Single bit of the permission in a `PermissionMask`
+    pub fn bit(&self) -> u64 {
+        let index = ALL_PERMISSIONS
+            .iter()
+            .position(|permission| permission == self)
+            .expect("Every permission must have a bit");
+        1 << index
+    }
+}
+
+/// Permissions of a set of roles packed into a single integer.
+///
+/// Carried in tokens as `<version>.<hex bits>` so requests can check
+/// permissions without expanding role strings.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct PermissionMask {
+    pub version: u32,
+    pub bits: u64,
+}
+
+impl PermissionMask {
+    pub fn from_permissions(permissions: &[Permission]) -> Self {
+        Self {
+            version: PERMISSION_MASK_VERSION,
+            bits: permissions.iter().fold(0, |bits, p| bits | p.bit()),
+        }
+    }
+
+    pub fn from_roles(roles: &[Role]) -> Self {
+        Self::from_permissions(&roles_permissions(roles))
+    }
+
+    /// Whether the mask was built from the current role definitions
+    pub fn is_current(&self) -> bool {
+        self.version == PERMISSION_MASK_VERSION
+    }
+
+    pub fn contains(&self, other: PermissionMask) -> bool {
+        self.bits & other.bits == other.bits
+    }
+
+    /// Mask granting the permissions of both masks
+    pub fn union(&self, other: PermissionMask) -> Self {
+        Self {
+            version: self.version,
+            bits: self.bits | other.bits,
+        }
+    }
+
+    /// Mask granting only the permissions found in both masks
+    pub fn intersection(&self, other: PermissionMask) -> Self {
+        Self {
+            version: self.version,
+            bits: self.bits & other.bits,
+        }
+    }
+
+    pub fn permissions(&self) -> Vec<Permission> {
+        ALL_PERMISSIONS
+            .iter()
+            .filter(|permission| self.bits & permission.bit() != 0)
+            .cloned()
+            .collect()
+    }
+}
+
+impl core::fmt::Display for PermissionMask {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}.{:x}", self.version, self.bits)
+    }
+}
+
+impl TryFrom<&str> for PermissionMask {
+    type Error = String;
+
+    fn try_from(value: &str) -> core::result::Result<Self, Self::Error> {
+        let invalid = || format!("Invalid permission mask: {value}");
+        let (version, bits) = value.split_once('.').ok_or_else(invalid)?;
+
+        Ok(Self {
+            version: version.parse().map_err(|_| invalid())?,
+            bits: u64::from_str_radix(bits, 16).map_err(|_| invalid())?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -560,6 +860,8 @@ mod tests {
         assert_eq!(Scope::Auth.to_string(), "auth");
         assert_eq!(Scope::Vault.to_string(), "vault");
         assert_eq!(Scope::Oauth.to_string(), "oauth");
+        assert_eq!(Scope::Openid.to_string(), "openid");
+        assert_eq!(Scope::OrgRead.to_string(), "org.read");
     }
 
     #[test]
@@ -579,4 +881,81 @@ mod tests {
         let permission: Permission = serde_json::from_str("\"org_members.view\"").unwrap();
         assert_eq!(permission, Permission::OrgMembersView);
     }
+
+    #[test]
+    fn test_all_roles_and_permissions_round_trip_json() {
+        for role in ALL_ROLES.iter() {
+            let json = serde_json::to_string(role).unwrap();
+            assert_eq!(json, format!("\"{}\"", role));
+            assert_eq!(&serde_json::from_str::<Role>(&json).unwrap(), role);
+        }
+
+        for permission in ALL_PERMISSIONS.iter() {
+            let json = serde_json::to_string(permission).unwrap();
+            assert_eq!(json, format!("\"{}\"", permission));
+            assert_eq!(
+                &serde_json::from_str::<Permission>(&json).unwrap(),
+                permission
+            );
+        }
+    }
+
+    #[test]
+    fn test_all_permissions_have_unique_bits() {
+        let mask = PermissionMask::from_permissions(&ALL_PERMISSIONS);
+        assert_eq!(mask.bits.count_ones() as usize, ALL_PERMISSIONS.len());
+        assert_eq!(mask.permissions(), ALL_PERMISSIONS.to_vec());
+    }
+
+    #[test]
+    fn test_permission_mask_round_trip() {
+        let mask = PermissionMask::from_roles(&[Role::OrgViewer]);
+        let parsed = PermissionMask::try_from(mask.to_string().as_str()).unwrap();
+        assert_eq!(parsed, mask);
+        assert!(parsed.is_current());
+        assert!(PermissionMask::try_from("1.xyz").is_err());
+        assert!(PermissionMask::try_from("12").is_err());
+
+        let required = PermissionMask::from_permissions(&[Permission::OrgsView]);
+        assert!(mask.contains(required));
+        let required = PermissionMask::from_permissions(&[Permission::OrgsEdit]);
+        assert!(!mask.contains(required));
+    }
+
+    #[test]
+    fn test_custom_permissions_merge_with_roles() {
+        let viewer = PermissionMask::from_roles(&[Role::OrgViewer]);
+        let mask = viewer.union(PermissionMask::from_permissions(&[
+            Permission::OrgMembersEdit,
+            Permission::OrgsView,
+        ]));
+
+        assert!(mask.contains(viewer));
+        assert!(mask.contains(PermissionMask::from_permissions(&[
+            Permission::OrgMembersEdit
+        ])));
+        assert_eq!(mask.bits.count_ones(), viewer.bits.count_ones() + 1);
+
+        assert!(!custom_role_permissions().contains(&Permission::UsersCreate));
+        assert!(custom_role_permissions().contains(&Permission::FilesManage));
+    }
+
+    /// Fails when role definitions change, update the pinned masks and bump
+    /// `PERMISSION_MASK_VERSION` so issued tokens are re-evaluated
+    #[test]
+    fn test_permission_mask_version_matches_role_definitions() {
+        let masks: Vec<String> = ALL_ROLES
+            .iter()
+            .map(|role| PermissionMask::from_roles(std::slice::from_ref(role)).to_string())
+            .collect();
+        assert_eq!(
+            masks,
+            vec![
+                "2.10003fffffff",
+                "2.fffd8ffe018",
+                "2.7df98618018",
+                "2.61898618010"
+            ]
+        );
+    }
 }
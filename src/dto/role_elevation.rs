@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::dto::Role;
+use crate::{Error, Result};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ElevationStatus {
+    Pending,
+    Active,
+    Reverted,
+    Rejected,
+}
+
+impl TryFrom<&str> for ElevationStatus {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "pending" => Ok(Self::Pending),
+            "active" => Ok(Self::Active),
+            "reverted" => Ok(Self::Reverted),
+            "rejected" => Ok(Self::Rejected),
+            _ => Err(Error::Validation {
+                msg: format!("Invalid elevation status: {}", value),
+            }),
+        }
+    }
+}
+
+impl core::fmt::Display for ElevationStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Pending => write!(f, "pending"),
+            Self::Active => write!(f, "active"),
+            Self::Reverted => write!(f, "reverted"),
+            Self::Rejected => write!(f, "rejected"),
+        }
+    }
+}
+
+/// Org role granted to a member for a limited time, kept as its audit trail
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RoleElevationDto {
+    pub id: String,
+    pub org_id: String,
+    pub user_id: String,
+    pub role: Role,
+    pub reason: String,
+    pub duration_mins: i64,
+    pub status: ElevationStatus,
+    pub requested_by: String,
+    pub decided_by: Option<String>,
+    pub created_at: i64,
+
+    /// Set once the elevation is active
+    pub expires_at: Option<i64>,
+
+    /// Set when the elevation is reverted or rejected
+    pub ended_at: Option<i64>,
+}
+
+impl RoleElevationDto {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// Payload of the `org.elevation_revert` job, due when the elevation expires
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ElevationRevertJobDto {
+    pub elevation_id: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct NewRoleElevationDto {
+    pub role: String,
+
+    #[validate(length(min = 1, max = 250))]
+    pub reason: String,
+
+    /// Up to a day, longer access should be a regular role change
+    #[validate(range(min = 15, max = 1440))]
+    pub duration_mins: i64,
+}
@@ -0,0 +1,120 @@
+use crate::Result;
+use crate::dto::{ALL_PERMISSIONS, Permission, PermissionMask, Scope, to_scopes};
+
+/// Scopes apps may request with the authorization code flow
+pub const APP_SCOPES: [Scope; 4] = [Scope::Auth, Scope::Oauth, Scope::Openid, Scope::OrgRead];
+
+/// Scopes of client credentials tokens, there is no user to act for
+pub const CLIENT_CREDENTIALS_SCOPES: [Scope; 2] = [Scope::Oauth, Scope::OrgRead];
+
+/// Permissions a token with the scope may use.
+///
+/// Policies are enforced with the intersection of these and the permissions
+/// of the actor's roles, a scope never grants more than the roles do.
+pub fn scope_permissions(scope: &Scope) -> Vec<Permission> {
+    match scope {
+        // Sessions and the catch-all app scope act with the full roles
+        Scope::Auth | Scope::Oauth => ALL_PERMISSIONS.to_vec(),
+        Scope::Vault => vec![
+            Permission::BucketsEdit,
+            Permission::BucketsView,
+            Permission::DirsCreate,
+            Permission::DirsEdit,
+            Permission::DirsDelete,
+            Permission::DirsList,
+            Permission::DirsView,
+            Permission::DirsManage,
+            Permission::FilesCreate,
+            Permission::FilesEdit,
+            Permission::FilesDelete,
+            Permission::FilesList,
+            Permission::FilesView,
+            Permission::FilesManage,
+        ],
+        Scope::OrgRead => vec![
+            Permission::OrgsList,
+            Permission::OrgsView,
+            Permission::OrgMembersList,
+            Permission::OrgMembersView,
+            Permission::OrgAppsList,
+            Permission::OrgAppsView,
+        ],
+        // Only proves the sign in
+        Scope::Openid => Vec::new(),
+    }
+}
+
+/// Shown to the user on the consent screen
+pub fn scope_description(scope: &Scope) -> &'static str {
+    match scope {
+        Scope::Auth => "Act on your behalf with all the permissions of your roles",
+        Scope::Oauth => "Use the app with all the permissions of your roles",
+        Scope::Vault => "Manage buckets, directories and files",
+        Scope::OrgRead => "View the org, its members and apps",
+        Scope::Openid => "Confirm your identity, name and email",
+    }
+}
+
+/// Permissions granted by any of the scopes
+pub fn scopes_permission_mask(scopes: &[Scope]) -> PermissionMask {
+    let permissions: Vec<Permission> = scopes.iter().flat_map(scope_permissions).collect();
+    PermissionMask::from_permissions(&permissions)
+}
+
+/// Parses a space separated scope string, ex: `openid org.read`
+pub fn parse_scopes(value: &str) -> Result<Vec<Scope>> {
+    let list: Vec<String> = value
+        .split(' ')
+        .filter(|scope| !scope.is_empty())
+        .map(|scope| scope.to_string())
+        .collect();
+
+    to_scopes(&list)
+}
+
+/// Canonical scope string persisted with codes, grants and tokens, duplicates removed
+pub fn join_scopes(scopes: &[Scope]) -> String {
+    let mut unique: Vec<String> = Vec::with_capacity(scopes.len());
+    for scope in scopes {
+        let scope = scope.to_string();
+        if !unique.contains(&scope) {
+            unique.push(scope);
+        }
+    }
+    unique.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::{Permission, PermissionMask, Role, Scope};
+
+    use super::{join_scopes, parse_scopes, scopes_permission_mask};
+
+    #[test]
+    fn scopes_narrow_the_role_permissions() {
+        let admin = PermissionMask::from_roles(&[Role::OrgAdmin]);
+
+        let full = admin.intersection(scopes_permission_mask(&[Scope::Oauth]));
+        assert_eq!(full, admin);
+
+        let read = admin.intersection(scopes_permission_mask(&[Scope::OrgRead]));
+        assert!(read.contains(PermissionMask::from_permissions(&[
+            Permission::OrgMembersView
+        ])));
+        assert!(!read.contains(PermissionMask::from_permissions(&[
+            Permission::OrgMembersEdit
+        ])));
+
+        let none = admin.intersection(scopes_permission_mask(&[Scope::Openid]));
+        assert!(none.permissions().is_empty());
+    }
+
+    #[test]
+    fn scope_strings_round_trip() {
+        let scopes = parse_scopes("openid  org.read openid").expect("valid scopes");
+        assert_eq!(scopes, vec![Scope::Openid, Scope::OrgRead, Scope::Openid]);
+        assert_eq!(join_scopes(&scopes), "openid org.read");
+
+        assert!(parse_scopes("org.write").is_err());
+    }
+}
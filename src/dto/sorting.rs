@@ -0,0 +1,118 @@
+use serde::Serialize;
+
+/// Sortable fields of a listing, each mapped to the SQL column it orders by.
+///
+/// The first field is the default order, ties are broken by the id column.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct SortSpec {
+    pub columns: &'static [(&'static str, &'static str)],
+    pub id_column: &'static str,
+}
+
+pub const USER_SORT: SortSpec = SortSpec {
+    columns: &[
+        ("email", "email"),
+        ("name", "name"),
+        ("status", "status"),
+        ("created_at", "created_at"),
+        ("updated_at", "updated_at"),
+    ],
+    id_column: "id",
+};
+
+pub const ORG_SORT: SortSpec = SortSpec {
+    columns: &[
+        ("name", "orgs.name"),
+        ("owner_email", "users.email"),
+        ("status", "orgs.status"),
+        ("member_count", "orgs.member_count"),
+        ("app_count", "orgs.app_count"),
+        ("created_at", "orgs.created_at"),
+        ("updated_at", "orgs.updated_at"),
+    ],
+    id_column: "orgs.id",
+};
+
+pub const ORG_MEMBER_SORT: SortSpec = SortSpec {
+    columns: &[
+        ("email", "users.email"),
+        ("name", "users.name"),
+        ("status", "org_members.status"),
+        ("created_at", "org_members.created_at"),
+        ("updated_at", "org_members.updated_at"),
+    ],
+    id_column: "org_members.id",
+};
+
+pub const APP_SORT: SortSpec = SortSpec {
+    columns: &[
+        ("name", "name"),
+        ("created_at", "created_at"),
+        ("updated_at", "updated_at"),
+    ],
+    id_column: "id",
+};
+
+pub const ORG_APP_SORT: SortSpec = SortSpec {
+    columns: &[("name", "apps.name"), ("created_at", "org_apps.created_at")],
+    id_column: "org_apps.id",
+};
+
+impl SortSpec {
+    pub fn default_field(&self) -> &'static str {
+        self.columns[0].0
+    }
+
+    pub fn column(&self, field: &str) -> Option<&'static str> {
+        self.columns
+            .iter()
+            .find(|(name, _)| *name == field)
+            .map(|(_, column)| *column)
+    }
+
+    /// `ORDER BY` clause, fields are validated beforehand so unknown ones use the default
+    pub fn order_by(&self, sort_by: Option<&str>, sort_dir: Option<&str>) -> String {
+        let column = sort_by
+            .and_then(|field| self.column(field))
+            .unwrap_or(self.columns[0].1);
+        let dir = match sort_dir {
+            Some("desc") => "DESC",
+            _ => "ASC",
+        };
+
+        format!(" ORDER BY {} {}, {} {}", column, dir, self.id_column, dir)
+    }
+}
+
+/// Query string suffix that keeps the sort across pagination links
+pub fn sort_query(sort_by: Option<&str>, sort_dir: Option<&str>) -> String {
+    let mut query = String::new();
+    if let Some(sort_by) = sort_by {
+        query.push_str(&format!("&sort_by={}", urlencoding::encode(sort_by)));
+    }
+    if let Some(sort_dir) = sort_dir {
+        query.push_str(&format!("&sort_dir={}", urlencoding::encode(sort_dir)));
+    }
+    query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_by_uses_mapped_column_and_id_tiebreak() {
+        assert_eq!(
+            ORG_SORT.order_by(Some("member_count"), Some("desc")),
+            " ORDER BY orgs.member_count DESC, orgs.id DESC"
+        );
+        assert_eq!(
+            USER_SORT.order_by(None, None),
+            " ORDER BY email ASC, id ASC"
+        );
+        assert_eq!(
+            USER_SORT.order_by(Some("password"), Some("sideways")),
+            " ORDER BY email ASC, id ASC"
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+/// Deployment wide counter, recomputed in the background
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StatCounterDto {
+    pub name: String,
+    pub value: i64,
+    pub updated_at: i64,
+}
+
+/// Totals for the stats endpoint, read from the stored counters
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct StatsDto {
+    pub users: i64,
+    pub active_users: i64,
+    pub orgs: i64,
+    pub active_orgs: i64,
+    pub apps: i64,
+    pub org_members: i64,
+
+    /// When the counters were last recomputed, None before the first run
+    pub updated_at: Option<i64>,
+}
+
+impl From<Vec<StatCounterDto>> for StatsDto {
+    fn from(counters: Vec<StatCounterDto>) -> Self {
+        let mut stats = StatsDto::default();
+
+        for counter in counters.into_iter() {
+            match counter.name.as_str() {
+                "users" => stats.users = counter.value,
+                "active_users" => stats.active_users = counter.value,
+                "orgs" => stats.orgs = counter.value,
+                "active_orgs" => stats.active_orgs = counter.value,
+                "apps" => stats.apps = counter.value,
+                "org_members" => stats.org_members = counter.value,
+                _ => continue,
+            }
+
+            stats.updated_at = stats.updated_at.max(Some(counter.updated_at));
+        }
+
+        stats
+    }
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Type-ahead widgets only ever show a handful of matches
+pub const SUGGESTION_LIMIT: usize = 10;
+
+#[derive(Clone, Default, Deserialize, Validate)]
+pub struct SuggestionParamsDto {
+    #[validate(length(min = 0, max = 50))]
+    pub keyword: Option<String>,
+
+    pub exclude_id: Option<String>,
+}
+
+impl SuggestionParamsDto {
+    /// Trimmed, lowercased keyword, `None` when blank
+    pub fn normalized_keyword(&self) -> Option<String> {
+        self.keyword
+            .as_deref()
+            .map(|keyword| keyword.trim().to_lowercase())
+            .filter(|keyword| !keyword.is_empty())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserSuggestionDto {
+    pub id: String,
+    pub email: String,
+    pub name: String,
+    pub active: bool,
+
+    /// Number of orgs the user already belongs to
+    pub memberships: i64,
+}
+
+/// Ranked suggestions without pagination or counts.
+///
+/// Prefix matches come first, then infix matches, active users ahead of
+/// inactive ones within each group.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SuggestionBufDto {
+    pub items: Vec<UserSuggestionDto>,
+    pub has_more: bool,
+}
+
+impl SuggestionBufDto {
+    /// Builds the buffer from up to `limit + 1` rows
+    pub fn new(mut items: Vec<UserSuggestionDto>, limit: usize) -> Self {
+        let has_more = items.len() > limit;
+        items.truncate(limit);
+        Self { items, has_more }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SuggestionBufDto, SuggestionParamsDto, UserSuggestionDto};
+
+    fn suggestion(id: &str) -> UserSuggestionDto {
+        UserSuggestionDto {
+            id: id.to_string(),
+            email: format!("{}@example.com", id),
+            name: id.to_string(),
+            active: true,
+            memberships: 0,
+        }
+    }
+
+    #[test]
+    fn test_suggestion_buf_truncates_extra_row() {
+        let buf = SuggestionBufDto::new(vec![suggestion("a"), suggestion("b"), suggestion("c")], 2);
+        assert_eq!(buf.items.len(), 2);
+        assert!(buf.has_more);
+
+        let buf = SuggestionBufDto::new(vec![suggestion("a")], 2);
+        assert_eq!(buf.items.len(), 1);
+        assert!(!buf.has_more);
+    }
+
+    #[test]
+    fn test_normalized_keyword() {
+        let params = SuggestionParamsDto {
+            keyword: Some("  Alice ".to_string()),
+            exclude_id: None,
+        };
+        assert_eq!(params.normalized_keyword().as_deref(), Some("alice"));
+
+        let params = SuggestionParamsDto {
+            keyword: Some("   ".to_string()),
+            exclude_id: None,
+        };
+        assert!(params.normalized_keyword().is_none());
+    }
+}
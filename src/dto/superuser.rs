@@ -1,12 +1,20 @@
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
+use crate::utils::{Redact, redacted_debug};
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SuperuserDto {
     pub id: String,
     pub created_at: i64,
 }
 
+/// Response of `GET /setup/status`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SetupStatusDto {
+    pub setup_required: bool,
+}
+
 #[derive(Clone, Serialize, Deserialize, Validate)]
 pub struct SetupBodyDto {
     #[validate(length(equal = 36))]
@@ -19,3 +27,9 @@ pub struct SetupBodyDto {
     #[validate(length(min = 8, max = 60))]
     pub password: String,
 }
+
+impl Redact for SetupBodyDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["setup_key", "password"];
+}
+
+redacted_debug!(SetupBodyDto);
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::dto::Role;
+use crate::utils::SparseFields;
+use crate::validators;
+
+/// Group of org members sharing built-in roles.
+///
+/// Members of the team gain the permissions of its roles next to those of their own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamDto {
+    pub id: String,
+    pub org_id: String,
+    pub name: String,
+    pub description: String,
+    pub roles: Vec<Role>,
+    pub member_count: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl SparseFields for TeamDto {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "org_id",
+        "name",
+        "description",
+        "roles",
+        "member_count",
+        "created_at",
+        "updated_at",
+    ];
+}
+
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct NewTeamDto {
+    #[validate(length(min = 1, max = 50))]
+    #[validate(custom(function = "validators::anyname"))]
+    pub name: String,
+
+    #[validate(length(max = 250))]
+    #[serde(default)]
+    pub description: String,
+
+    /// A team without roles only groups its members
+    #[validate(custom(function = "validators::member_roles"))]
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct UpdateTeamDto {
+    #[validate(length(min = 1, max = 50))]
+    #[validate(custom(function = "validators::anyname"))]
+    pub name: Option<String>,
+
+    #[validate(length(max = 250))]
+    pub description: Option<String>,
+
+    #[validate(custom(function = "validators::member_roles"))]
+    pub roles: Option<Vec<String>>,
+}
+
+/// Org member belonging to a team
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeamMemberDto {
+    pub team_id: String,
+    pub user_id: String,
+    pub member_name: Option<String>,
+    pub member_email: Option<String>,
+    pub created_at: i64,
+}
+
+/// Adds an org member to the team
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NewTeamMemberDto {
+    pub user_id: String,
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// Tokens of the user issued at or before `revoked_at` are rejected
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TokenRevocationDto {
+    pub user_id: String,
+    pub revoked_at: i64,
+
+    /// User that forced the logout, `None` when done from the command line
+    pub revoked_by: Option<String>,
+}
+
+/// Single token rejected until it would have expired anyway
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RevokedTokenDto {
+    pub jti: String,
+    pub user_id: String,
+    pub expires_at: i64,
+    pub created_at: i64,
+}
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use urlencoding::encode;
 use validator::Validate;
 
+use crate::dto::sort_query;
+use crate::utils::{Redact, SparseFields, redacted_debug};
 use crate::validators;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -13,6 +15,21 @@ pub struct UserDto {
     pub status: String,
     pub created_at: i64,
     pub updated_at: i64,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<i64>,
+}
+
+impl SparseFields for UserDto {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "email",
+        "name",
+        "status",
+        "created_at",
+        "updated_at",
+        "deleted_at",
+    ];
 }
 
 #[derive(Clone, Deserialize, Validate)]
@@ -38,6 +55,12 @@ pub struct NewUserWithPasswordDto {
     pub password: String,
 }
 
+impl Redact for NewUserWithPasswordDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["password"];
+}
+
+redacted_debug!(NewUserWithPasswordDto);
+
 #[derive(Clone, Serialize, Deserialize, Validate)]
 pub struct UpdateUserDto {
     #[validate(length(min = 1, max = 100))]
@@ -49,26 +72,50 @@ pub struct UpdateUserDto {
 
 #[derive(Clone, Deserialize, Validate)]
 pub struct ListUsersParamsDto {
-    #[validate(range(min = 1, max = 1000))]
+    #[validate(range(min = 1))]
     pub page: Option<i32>,
 
-    #[validate(range(min = 1, max = 50))]
+    #[validate(range(min = 1))]
     pub per_page: Option<i32>,
 
     #[validate(length(min = 0, max = 50))]
     pub keyword: Option<String>,
+
+    #[validate(custom(function = "validators::user_sort"))]
+    pub sort_by: Option<String>,
+
+    #[validate(custom(function = "validators::sort_dir"))]
+    pub sort_dir: Option<String>,
+
+    /// Opaque `next_cursor` of the previous page, switches to keyset pagination
+    #[validate(length(min = 1, max = 500))]
+    pub cursor: Option<String>,
+
+    #[validate(range(min = 1))]
+    pub limit: Option<i32>,
 }
 
 impl Default for ListUsersParamsDto {
     fn default() -> Self {
         Self {
             keyword: None,
+            sort_by: None,
+            sort_dir: None,
             page: Some(1),
             per_page: Some(10),
+            cursor: None,
+            limit: None,
         }
     }
 }
 
+impl ListUsersParamsDto {
+    /// Keyset pagination is used once a cursor or a limit is passed
+    pub fn uses_cursor(&self) -> bool {
+        self.cursor.is_some() || self.limit.is_some()
+    }
+}
+
 impl fmt::Display for ListUsersParamsDto {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // Ideally, we want an empty string if all fields are None
@@ -82,10 +129,11 @@ impl fmt::Display for ListUsersParamsDto {
 
         write!(
             f,
-            "page={}&per_page={}&keyword={}",
+            "page={}&per_page={}&keyword={}{}",
             page,
             per_page,
-            encode(keyword)
+            encode(keyword),
+            sort_query(self.sort_by.as_deref(), self.sort_dir.as_deref())
         )
     }
 }
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Secondary email linked to a user account.
+///
+/// The primary email stays on the user record.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UserEmailDto {
+    pub id: String,
+    pub user_id: String,
+    pub email: String,
+
+    #[serde(skip_serializing)]
+    pub verification_code: Option<String>,
+
+    pub verified_at: Option<i64>,
+    pub created_at: i64,
+}
+
+impl UserEmailDto {
+    pub fn is_verified(&self) -> bool {
+        self.verified_at.is_some()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct NewUserEmailDto {
+    #[validate(email)]
+    #[validate(length(min = 1, max = 250))]
+    pub email: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Validate)]
+pub struct VerifyUserEmailDto {
+    #[validate(length(min = 1, max = 50))]
+    pub code: String,
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+use crate::dto::UserDto;
+use crate::utils::{Redact, redacted_debug};
+
+/// Token confirming the erasure of a user, asked for right before erasing
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ErasureConfirmationDto {
+    pub user_id: String,
+    pub confirmation_token: String,
+    pub expires_at: i64,
+}
+
+impl Redact for ErasureConfirmationDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["confirmation_token"];
+}
+
+redacted_debug!(ErasureConfirmationDto);
+
+#[derive(Clone, Deserialize)]
+pub struct AnonymizeUserDto {
+    pub confirmation_token: String,
+}
+
+/// Tombstone left in place of the erased user
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AnonymizedUserDto {
+    pub user: UserDto,
+    pub erased_at: i64,
+
+    /// Credential and personal data rows removed
+    pub rows_removed: u64,
+}
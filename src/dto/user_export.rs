@@ -0,0 +1,679 @@
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt};
+
+use crate::dto::{
+    ImpersonationActionDto, ImpersonationDto, ImpersonationLogDto, OrgAccessDto, OrgMembershipDto,
+    UserDto, UserProfileDto, UserSessionDto, to_roles,
+};
+use crate::error::{JsonParseSnafu, ValidationSnafu};
+use crate::{Error, Result};
+
+/// Encoding of a user data export archive
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserExportFormat {
+    Json,
+    Protobuf,
+}
+
+impl UserExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Protobuf => "application/x-protobuf",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Protobuf => "pb",
+        }
+    }
+}
+
+impl TryFrom<&str> for UserExportFormat {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "json" => Ok(Self::Json),
+            "protobuf" => Ok(Self::Protobuf),
+            _ => Err(Error::Validation {
+                msg: format!("Invalid export format: {}", value),
+            }),
+        }
+    }
+}
+
+impl core::fmt::Display for UserExportFormat {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Json => write!(f, "json"),
+            Self::Protobuf => write!(f, "protobuf"),
+        }
+    }
+}
+
+/// Export state machine: `pending` until the job assembled the archive, then `ready`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserExportStatus {
+    Pending,
+    Ready,
+}
+
+impl TryFrom<&str> for UserExportStatus {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "pending" => Ok(Self::Pending),
+            "ready" => Ok(Self::Ready),
+            _ => Err(Error::Validation {
+                msg: format!("Invalid export status: {}", value),
+            }),
+        }
+    }
+}
+
+impl core::fmt::Display for UserExportStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Pending => write!(f, "pending"),
+            Self::Ready => write!(f, "ready"),
+        }
+    }
+}
+
+/// Data export of a user, the archive itself is only served by the download link
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserExportDto {
+    pub id: String,
+    pub user_id: String,
+    pub requested_by: Option<String>,
+    pub format: UserExportFormat,
+    pub status: UserExportStatus,
+
+    /// Archive size in bytes once ready
+    pub size: Option<i64>,
+    pub created_at: i64,
+    pub completed_at: Option<i64>,
+    pub expires_at: Option<i64>,
+
+    /// Signed link valid for an hour, fetch the export again for a fresh one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
+}
+
+impl UserExportDto {
+    /// Ready and not yet expired
+    pub fn is_downloadable(&self, now: i64) -> bool {
+        self.status == UserExportStatus::Ready && self.expires_at.is_some_and(|at| at > now)
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct UserExportParamsDto {
+    /// `json` (default) or `protobuf`
+    pub format: Option<String>,
+}
+
+impl UserExportParamsDto {
+    pub fn format(&self) -> Result<UserExportFormat> {
+        match self.format.as_deref() {
+            Some(value) => UserExportFormat::try_from(value.trim()),
+            None => Ok(UserExportFormat::Json),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct UserExportDownloadParamsDto {
+    pub token: String,
+}
+
+/// Payload of the `user.export` job
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserExportJobDto {
+    pub export_id: String,
+}
+
+/// Archive file served by the download link
+pub struct UserExportFileDto {
+    pub filename: String,
+    pub content_type: &'static str,
+    pub content: Vec<u8>,
+}
+
+/// Everything yaas keeps about a user.
+///
+/// The access log and impersonations are the audit events involving the user.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UserExportArchiveDto {
+    pub exported_at: i64,
+    pub user: UserDto,
+    pub profile: UserProfileDto,
+    pub memberships: Vec<OrgMembershipDto>,
+    pub sessions: Vec<UserSessionDto>,
+    pub access_log: Vec<OrgAccessDto>,
+    pub impersonations: Vec<ImpersonationLogDto>,
+}
+
+/// Protobuf messages of the archive, see `proto/user_export.proto`
+pub mod proto {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct UserExport {
+        #[prost(int64, tag = "1")]
+        pub exported_at: i64,
+        #[prost(message, optional, tag = "2")]
+        pub user: Option<User>,
+        #[prost(message, optional, tag = "3")]
+        pub profile: Option<Profile>,
+        #[prost(message, repeated, tag = "4")]
+        pub memberships: Vec<Membership>,
+        #[prost(message, repeated, tag = "5")]
+        pub sessions: Vec<Session>,
+        #[prost(message, repeated, tag = "6")]
+        pub access_log: Vec<Access>,
+        #[prost(message, repeated, tag = "7")]
+        pub impersonations: Vec<Impersonation>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct User {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(string, tag = "2")]
+        pub email: String,
+        #[prost(string, tag = "3")]
+        pub name: String,
+        #[prost(string, tag = "4")]
+        pub status: String,
+        #[prost(int64, tag = "5")]
+        pub created_at: i64,
+        #[prost(int64, tag = "6")]
+        pub updated_at: i64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Profile {
+        #[prost(string, optional, tag = "1")]
+        pub avatar_url: Option<String>,
+        #[prost(string, optional, tag = "2")]
+        pub locale: Option<String>,
+        #[prost(string, optional, tag = "3")]
+        pub timezone: Option<String>,
+        #[prost(string, optional, tag = "4")]
+        pub phone: Option<String>,
+        /// The metadata object as compact JSON
+        #[prost(string, tag = "5")]
+        pub metadata_json: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Membership {
+        #[prost(string, tag = "1")]
+        pub org_id: String,
+        #[prost(string, tag = "2")]
+        pub org_name: String,
+        #[prost(string, repeated, tag = "3")]
+        pub roles: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Session {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(string, optional, tag = "2")]
+        pub user_agent: Option<String>,
+        #[prost(string, optional, tag = "3")]
+        pub ip_address: Option<String>,
+        #[prost(int64, tag = "4")]
+        pub created_at: i64,
+        #[prost(int64, tag = "5")]
+        pub last_used_at: i64,
+        #[prost(int64, tag = "6")]
+        pub expires_at: i64,
+        #[prost(int64, optional, tag = "7")]
+        pub revoked_at: Option<i64>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Access {
+        #[prost(string, tag = "1")]
+        pub org_id: String,
+        #[prost(string, tag = "2")]
+        pub day: String,
+        #[prost(int64, tag = "3")]
+        pub first_seen_at: i64,
+        #[prost(int64, tag = "4")]
+        pub last_seen_at: i64,
+        #[prost(int64, tag = "5")]
+        pub samples: i64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Impersonation {
+        #[prost(string, tag = "1")]
+        pub id: String,
+        #[prost(string, tag = "2")]
+        pub impersonator_id: String,
+        #[prost(string, tag = "3")]
+        pub org_id: String,
+        #[prost(int64, tag = "4")]
+        pub created_at: i64,
+        #[prost(int64, tag = "5")]
+        pub expires_at: i64,
+        #[prost(int64, optional, tag = "6")]
+        pub ended_at: Option<i64>,
+        #[prost(message, repeated, tag = "7")]
+        pub actions: Vec<ImpersonationAction>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ImpersonationAction {
+        #[prost(string, tag = "1")]
+        pub method: String,
+        #[prost(string, tag = "2")]
+        pub path: String,
+        #[prost(int64, tag = "3")]
+        pub status: i64,
+        #[prost(int64, tag = "4")]
+        pub created_at: i64,
+    }
+}
+
+impl From<&UserExportArchiveDto> for proto::UserExport {
+    fn from(archive: &UserExportArchiveDto) -> Self {
+        let user = &archive.user;
+        let profile = &archive.profile;
+
+        Self {
+            exported_at: archive.exported_at,
+            user: Some(proto::User {
+                id: user.id.clone(),
+                email: user.email.clone(),
+                name: user.name.clone(),
+                status: user.status.clone(),
+                created_at: user.created_at,
+                updated_at: user.updated_at,
+            }),
+            profile: Some(proto::Profile {
+                avatar_url: profile.avatar_url.clone(),
+                locale: profile.locale.clone(),
+                timezone: profile.timezone.clone(),
+                phone: profile.phone.clone(),
+                metadata_json: serde_json::Value::Object(profile.metadata.clone()).to_string(),
+            }),
+            memberships: archive
+                .memberships
+                .iter()
+                .map(|membership| proto::Membership {
+                    org_id: membership.org_id.clone(),
+                    org_name: membership.org_name.clone(),
+                    roles: membership.roles.iter().map(|r| r.to_string()).collect(),
+                })
+                .collect(),
+            sessions: archive
+                .sessions
+                .iter()
+                .map(|session| proto::Session {
+                    id: session.id.clone(),
+                    user_agent: session.user_agent.clone(),
+                    ip_address: session.ip_address.clone(),
+                    created_at: session.created_at,
+                    last_used_at: session.last_used_at,
+                    expires_at: session.expires_at,
+                    revoked_at: session.revoked_at,
+                })
+                .collect(),
+            access_log: archive
+                .access_log
+                .iter()
+                .map(|access| proto::Access {
+                    org_id: access.org_id.clone(),
+                    day: access.day.clone(),
+                    first_seen_at: access.first_seen_at,
+                    last_seen_at: access.last_seen_at,
+                    samples: access.samples,
+                })
+                .collect(),
+            impersonations: archive
+                .impersonations
+                .iter()
+                .map(|log| proto::Impersonation {
+                    id: log.impersonation.id.clone(),
+                    impersonator_id: log.impersonation.impersonator_id.clone(),
+                    org_id: log.impersonation.org_id.clone(),
+                    created_at: log.impersonation.created_at,
+                    expires_at: log.impersonation.expires_at,
+                    ended_at: log.impersonation.ended_at,
+                    actions: log
+                        .actions
+                        .iter()
+                        .map(|action| proto::ImpersonationAction {
+                            method: action.method.clone(),
+                            path: action.path.clone(),
+                            status: action.status,
+                            created_at: action.created_at,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Reads a protobuf archive back, fields the messages do not carry are left empty.
+///
+/// Those are the user's `deleted_at`, the `current` flag of sessions, the email and
+/// name of access log rows and the ids of impersonation actions. User ids come from
+/// the archived user.
+impl TryFrom<proto::UserExport> for UserExportArchiveDto {
+    type Error = Error;
+
+    fn try_from(archive: proto::UserExport) -> Result<Self> {
+        let user = archive.user.context(ValidationSnafu {
+            msg: "Protobuf export has no user".to_string(),
+        })?;
+        let profile = archive.profile.unwrap_or_default();
+        let metadata: serde_json::Value = match profile.metadata_json.as_str() {
+            "" => serde_json::Value::Object(serde_json::Map::new()),
+            value => serde_json::from_str(value).context(JsonParseSnafu)?,
+        };
+        let serde_json::Value::Object(metadata) = metadata else {
+            return Err(Error::Validation {
+                msg: "Protobuf export profile metadata is not an object".to_string(),
+            });
+        };
+
+        let mut memberships: Vec<OrgMembershipDto> = Vec::with_capacity(archive.memberships.len());
+        for membership in archive.memberships.into_iter() {
+            memberships.push(OrgMembershipDto {
+                org_id: membership.org_id,
+                org_name: membership.org_name,
+                user_id: user.id.clone(),
+                roles: to_roles(&membership.roles)?,
+            });
+        }
+
+        Ok(Self {
+            exported_at: archive.exported_at,
+            profile: UserProfileDto {
+                user_id: user.id.clone(),
+                avatar_url: profile.avatar_url,
+                locale: profile.locale,
+                timezone: profile.timezone,
+                phone: profile.phone,
+                metadata,
+            },
+            memberships,
+            sessions: archive
+                .sessions
+                .into_iter()
+                .map(|session| UserSessionDto {
+                    id: session.id,
+                    user_id: user.id.clone(),
+                    user_agent: session.user_agent,
+                    ip_address: session.ip_address,
+                    created_at: session.created_at,
+                    last_used_at: session.last_used_at,
+                    expires_at: session.expires_at,
+                    revoked_at: session.revoked_at,
+                    current: false,
+                })
+                .collect(),
+            access_log: archive
+                .access_log
+                .into_iter()
+                .map(|access| OrgAccessDto {
+                    org_id: access.org_id,
+                    user_id: user.id.clone(),
+                    day: access.day,
+                    email: None,
+                    name: None,
+                    first_seen_at: access.first_seen_at,
+                    last_seen_at: access.last_seen_at,
+                    samples: access.samples,
+                })
+                .collect(),
+            impersonations: archive
+                .impersonations
+                .into_iter()
+                .map(|log| ImpersonationLogDto {
+                    actions: log
+                        .actions
+                        .into_iter()
+                        .map(|action| ImpersonationActionDto {
+                            id: String::new(),
+                            impersonation_id: log.id.clone(),
+                            method: action.method,
+                            path: action.path,
+                            status: action.status,
+                            created_at: action.created_at,
+                        })
+                        .collect(),
+                    impersonation: ImpersonationDto {
+                        id: log.id,
+                        impersonator_id: log.impersonator_id,
+                        user_id: user.id.clone(),
+                        org_id: log.org_id,
+                        created_at: log.created_at,
+                        expires_at: log.expires_at,
+                        ended_at: log.ended_at,
+                    },
+                })
+                .collect(),
+            user: UserDto {
+                id: user.id,
+                email: user.email,
+                name: user.name,
+                status: user.status,
+                created_at: user.created_at,
+                updated_at: user.updated_at,
+                deleted_at: None,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::dto::{ALL_ROLES, Role};
+
+    /// JSON fields of the archive the protobuf messages do not carry
+    const NOT_IN_PROTOBUF: &[&str] = &[
+        "/user/deleted_at",
+        "/sessions/*/current",
+        "/access_log/*/email",
+        "/access_log/*/name",
+        "/impersonations/*/actions/*/id",
+    ];
+
+    fn sample_archive() -> UserExportArchiveDto {
+        let user_id = "usr_0195a3a0-0000-7000-8000-000000000001".to_string();
+        let impersonation_id = "imp_0195a3a0-0000-7000-8000-000000000009".to_string();
+
+        UserExportArchiveDto {
+            exported_at: 1_760_000_000_123,
+            user: UserDto {
+                id: user_id.clone(),
+                email: "export@example.com".to_string(),
+                name: "Export User".to_string(),
+                status: "active".to_string(),
+                created_at: 1_700_000_000_000,
+                updated_at: 1_700_000_500_000,
+                deleted_at: Some(1_750_000_000_000),
+            },
+            profile: UserProfileDto {
+                user_id: user_id.clone(),
+                avatar_url: Some("https://cdn.example.com/me.png".to_string()),
+                locale: Some("en-US".to_string()),
+                timezone: None,
+                phone: Some("+15550100".to_string()),
+                metadata: json!({ "team": "blue", "level": 3, "tags": ["a", "b"] })
+                    .as_object()
+                    .cloned()
+                    .unwrap(),
+            },
+            memberships: vec![
+                OrgMembershipDto {
+                    org_id: "org_1".to_string(),
+                    org_name: "Every Role Org".to_string(),
+                    user_id: user_id.clone(),
+                    roles: ALL_ROLES.to_vec(),
+                },
+                OrgMembershipDto {
+                    org_id: "org_2".to_string(),
+                    org_name: "Viewer Org".to_string(),
+                    user_id: user_id.clone(),
+                    roles: vec![Role::OrgViewer],
+                },
+            ],
+            sessions: vec![UserSessionDto {
+                id: "ses_1".to_string(),
+                user_id: user_id.clone(),
+                user_agent: Some("Mozilla/5.0".to_string()),
+                ip_address: None,
+                created_at: 1_700_000_000_000,
+                last_used_at: 1_700_000_100_000,
+                expires_at: 1_700_086_400_000,
+                revoked_at: Some(1_700_000_200_000),
+                current: true,
+            }],
+            access_log: vec![OrgAccessDto {
+                org_id: "org_1".to_string(),
+                user_id: user_id.clone(),
+                day: "2026-03-01".to_string(),
+                email: Some("export@example.com".to_string()),
+                name: Some("Export User".to_string()),
+                first_seen_at: 1_700_000_000_000,
+                last_seen_at: 1_700_000_900_000,
+                samples: 4,
+            }],
+            impersonations: vec![ImpersonationLogDto {
+                impersonation: ImpersonationDto {
+                    id: impersonation_id.clone(),
+                    impersonator_id: "usr_root".to_string(),
+                    user_id,
+                    org_id: "org_1".to_string(),
+                    created_at: 1_700_000_000_000,
+                    expires_at: 1_700_001_800_000,
+                    ended_at: None,
+                },
+                actions: vec![ImpersonationActionDto {
+                    id: "ima_1".to_string(),
+                    impersonation_id,
+                    method: "PATCH".to_string(),
+                    path: "/user/profile".to_string(),
+                    status: 200,
+                    created_at: 1_700_000_060_000,
+                }],
+            }],
+        }
+    }
+
+    /// Removes the field at the path, `*` matches every item of an array
+    fn remove_path(value: &mut Value, path: &[&str]) {
+        let [head, rest @ ..] = path else {
+            return;
+        };
+
+        if *head == "*" {
+            for item in value.as_array_mut().into_iter().flatten() {
+                remove_path(item, rest);
+            }
+        } else if rest.is_empty() {
+            if let Some(object) = value.as_object_mut() {
+                object.remove(*head);
+            }
+        } else if let Some(child) = value.get_mut(*head) {
+            remove_path(child, rest);
+        }
+    }
+
+    fn json_without_skipped(archive: &UserExportArchiveDto) -> Value {
+        let mut value = serde_json::to_value(archive).unwrap();
+        for path in NOT_IN_PROTOBUF.iter() {
+            let parts: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+            remove_path(&mut value, &parts);
+        }
+        value
+    }
+
+    #[test]
+    fn test_user_export_round_trips_through_protobuf() {
+        let archive = sample_archive();
+
+        let message = proto::UserExport::from(&archive);
+        let bytes = message.encode_to_vec();
+        let decoded = proto::UserExport::decode(bytes.as_slice()).unwrap();
+        assert_eq!(decoded, message);
+
+        let restored = UserExportArchiveDto::try_from(decoded).unwrap();
+        assert_eq!(
+            json_without_skipped(&restored),
+            json_without_skipped(&archive)
+        );
+
+        // Every role keeps its JSON name through the protobuf strings
+        let restored_json = serde_json::to_value(&restored).unwrap();
+        let roles: Vec<String> = ALL_ROLES.iter().map(|role| role.to_string()).collect();
+        assert_eq!(restored_json["memberships"][0]["roles"], json!(roles));
+        assert_eq!(restored.memberships[0].roles, ALL_ROLES.to_vec());
+    }
+
+    #[test]
+    fn test_user_export_json_round_trip_matches_protobuf() {
+        let archive = sample_archive();
+
+        let json = serde_json::to_string(&archive).unwrap();
+        let from_json: UserExportArchiveDto = serde_json::from_str(&json).unwrap();
+        let bytes = proto::UserExport::from(&archive).encode_to_vec();
+        let from_protobuf =
+            UserExportArchiveDto::try_from(proto::UserExport::decode(bytes.as_slice()).unwrap())
+                .unwrap();
+
+        // Both encodings agree on every field the protobuf messages carry
+        assert_eq!(
+            json_without_skipped(&from_protobuf),
+            json_without_skipped(&from_json)
+        );
+        assert_eq!(
+            proto::UserExport::from(&from_json),
+            proto::UserExport::from(&from_protobuf)
+        );
+    }
+
+    #[test]
+    fn test_user_export_protobuf_rejects_unknown_roles() {
+        let mut message = proto::UserExport::from(&sample_archive());
+        message.memberships[0].roles.push("OrgOwner".to_string());
+        assert!(UserExportArchiveDto::try_from(message).is_err());
+
+        let mut message = proto::UserExport::from(&sample_archive());
+        message.user = None;
+        assert!(UserExportArchiveDto::try_from(message).is_err());
+    }
+
+    #[test]
+    fn test_user_export_format() {
+        let params = UserExportParamsDto::default();
+        assert_eq!(params.format().unwrap(), UserExportFormat::Json);
+
+        let params = UserExportParamsDto {
+            format: Some("protobuf".to_string()),
+        };
+        assert_eq!(params.format().unwrap(), UserExportFormat::Protobuf);
+
+        let params = UserExportParamsDto {
+            format: Some("xml".to_string()),
+        };
+        assert!(params.format().is_err());
+    }
+}
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{Redact, redacted_debug};
+
+/// Maximum number of rows accepted in a single user import
+pub const MAX_IMPORT_USERS: usize = 1000;
+
+/// A user to create from an import file, the password is optional
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UserImportRowDto {
+    pub email: String,
+    pub name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+impl Redact for UserImportRowDto {
+    const SENSITIVE_FIELDS: &'static [&'static str] = &["password"];
+}
+
+redacted_debug!(UserImportRowDto);
+
+/// Outcome of a single row, rows are numbered from 1 without the CSV header
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserImportRowResultDto {
+    pub row: usize,
+    pub email: String,
+    pub user_id: Option<String>,
+    pub errors: Vec<String>,
+}
+
+/// Summary of an import, nothing is created when any row fails
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserImportResultDto {
+    pub imported: bool,
+    pub total: usize,
+    pub created: usize,
+    pub failed: usize,
+    pub rows: Vec<UserImportRowResultDto>,
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Account event a user is told about in the app
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserNotificationKind {
+    MembershipAdded,
+    RoleChanged,
+    PasswordChanged,
+}
+
+impl TryFrom<&str> for UserNotificationKind {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value {
+            "membership_added" => Ok(Self::MembershipAdded),
+            "role_changed" => Ok(Self::RoleChanged),
+            "password_changed" => Ok(Self::PasswordChanged),
+            _ => Err(Error::Validation {
+                msg: format!("Invalid notification kind: {}", value),
+            }),
+        }
+    }
+}
+
+impl core::fmt::Display for UserNotificationKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::MembershipAdded => write!(f, "membership_added"),
+            Self::RoleChanged => write!(f, "role_changed"),
+            Self::PasswordChanged => write!(f, "password_changed"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserNotificationDto {
+    pub id: String,
+    pub user_id: String,
+    pub kind: UserNotificationKind,
+    pub title: String,
+    pub body: String,
+    /// Page to open for details
+    pub link: Option<String>,
+    pub read_at: Option<i64>,
+    pub created_at: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct NewUserNotificationDto {
+    pub user_id: String,
+    pub kind: UserNotificationKind,
+    pub title: String,
+    pub body: String,
+    pub link: Option<String>,
+}
+
+/// Latest notifications of the user with the number not read yet
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserNotificationsDto {
+    pub unread_count: i64,
+    pub notifications: Vec<UserNotificationDto>,
+}
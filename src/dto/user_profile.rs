@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use validator::Validate;
+
+use crate::validators;
+
+/// Largest metadata object kept for a user, counted as compact JSON
+pub const USER_PROFILE_METADATA_MAX_BYTES: usize = 4096;
+
+/// Most top level keys the metadata object may have
+pub const USER_PROFILE_METADATA_MAX_KEYS: usize = 50;
+
+/// Profile attributes of a user, everything is optional
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct UserProfileDto {
+    pub user_id: String,
+    pub avatar_url: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub phone: Option<String>,
+
+    /// Free-form JSON object for the apps, never interpreted by yaas
+    pub metadata: Map<String, Value>,
+}
+
+/// Partial update, fields left out are kept and empty strings clear them.
+///
+/// Metadata keys are merged into the existing object, keys set to `null` are removed.
+#[derive(Clone, Deserialize, Validate)]
+pub struct UpdateUserProfileDto {
+    #[validate(length(max = 500))]
+    #[validate(custom(function = "validators::https_url"))]
+    pub avatar_url: Option<String>,
+
+    #[validate(custom(function = "validators::locale"))]
+    pub locale: Option<String>,
+
+    #[validate(custom(function = "validators::timezone"))]
+    pub timezone: Option<String>,
+
+    #[validate(custom(function = "validators::phone"))]
+    pub phone: Option<String>,
+
+    #[validate(custom(function = "validators::profile_metadata"))]
+    pub metadata: Option<Map<String, Value>>,
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// Signed in session behind the tokens issued at login
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserSessionDto {
+    pub id: String,
+    pub user_id: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: i64,
+    pub last_used_at: i64,
+    pub expires_at: i64,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<i64>,
+
+    /// Whether the listing was requested with this session's token
+    #[serde(default)]
+    pub current: bool,
+}
+
+impl UserSessionDto {
+    pub fn is_active(&self, now: i64) -> bool {
+        self.revoked_at.is_none() && self.expires_at > now
+    }
+}
+
+/// Device details recorded when a session starts
+#[derive(Clone, Debug, Default)]
+pub struct SessionClientDto {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
@@ -71,9 +71,15 @@ pub enum Error {
     #[snafu(display("{}", msg))]
     NotFound { msg: String },
 
+    #[snafu(display("{}", msg))]
+    Conflict { msg: String },
+
     #[snafu(display("Invalid auth token"))]
     InvalidAuthToken,
 
+    #[snafu(display("Invalid token proof: {}", msg))]
+    InvalidTokenProof { msg: String },
+
     #[snafu(display("Insufficient auth scope"))]
     InsufficientAuthScope,
 
@@ -134,6 +140,12 @@ pub enum Error {
     #[snafu(display("{}", source))]
     HttpResponseBytes { source: reqwest::Error },
 
+    #[snafu(display("Unable to send email: {}", msg))]
+    Mail { msg: String },
+
+    #[snafu(display("Redis error: {}", source))]
+    Redis { source: redis::RedisError },
+
     #[snafu(display("{}", source))]
     Base64Decode { source: base64::DecodeError },
 
@@ -143,6 +155,9 @@ pub enum Error {
     #[snafu(display("Failed to serialize JSON: {}", source))]
     JsonSerialize { source: serde_json::Error },
 
+    #[snafu(display("Failed to parse JSON: {}", source))]
+    JsonParse { source: serde_json::Error },
+
     #[snafu(display("OAuth redirect_uri mismatch"))]
     RedirectUriMistmatch,
 
@@ -155,6 +170,9 @@ pub enum Error {
     #[snafu(display("OAuth code invalid"))]
     OauthCodeInvalid,
 
+    #[snafu(display("Unsupported grant_type"))]
+    UnsupportedGrantType,
+
     #[snafu(display("OAuth scopes invalid"))]
     OauthInvalidScopes,
 
@@ -173,6 +191,13 @@ pub enum Error {
     #[snafu(display("Client not found"))]
     ClientNotFound,
 
+    /// Failure while the client and redirect_uri were still being verified
+    #[snafu(display("{}", source))]
+    UnverifiedClient {
+        #[snafu(source(from(Error, Box::new)))]
+        source: Box<Error>,
+    },
+
     #[snafu(display("Stale form data. Refresh the page and try again."))]
     CsrfToken,
 
@@ -188,6 +213,31 @@ pub enum Error {
     #[snafu(display("Too many requests. Please try again later."))]
     RateLimitExceeded,
 
+    #[snafu(display("Session belongs to region {}, send requests there.", region))]
+    RegionMismatch { region: String },
+
+    #[snafu(display("{}", msg))]
+    ApprovalRequired { msg: String },
+
+    #[snafu(display("A request with this Idempotency-Key is still in progress."))]
+    IdempotencyKeyInProgress,
+
+    #[snafu(display("This Idempotency-Key was already used with a different request."))]
+    IdempotencyKeyReused,
+
+    #[snafu(display(
+        "The request with this Idempotency-Key already succeeded and returned a secret, which is only shown once."
+    ))]
+    IdempotencyKeyNotReplayable,
+
+    #[snafu(display(
+        "Send the version of the record, its updated_at, in an If-Match header or the version field."
+    ))]
+    VersionRequired,
+
+    #[snafu(display("The record was changed by someone else. Reload it and try again."))]
+    VersionMismatch,
+
     #[snafu(display("{}", msg))]
     Whatever { msg: String },
 }
@@ -216,7 +266,9 @@ impl From<&Error> for StatusCode {
             Error::Forbidden { .. } => StatusCode::FORBIDDEN,
             Error::JsonRejection { .. } => StatusCode::BAD_REQUEST,
             Error::NotFound { .. } => StatusCode::NOT_FOUND,
+            Error::Conflict { .. } => StatusCode::CONFLICT,
             Error::InvalidAuthToken => StatusCode::UNAUTHORIZED,
+            Error::InvalidTokenProof { .. } => StatusCode::UNAUTHORIZED,
             Error::InsufficientAuthScope => StatusCode::UNAUTHORIZED,
             Error::NoAuthToken => StatusCode::UNAUTHORIZED,
             Error::InvalidClient => StatusCode::UNAUTHORIZED,
@@ -229,6 +281,7 @@ impl From<&Error> for StatusCode {
             Error::AppNotRegistered => StatusCode::UNAUTHORIZED,
             Error::OauthStateMismatch => StatusCode::UNAUTHORIZED,
             Error::OauthCodeInvalid => StatusCode::UNAUTHORIZED,
+            Error::UnsupportedGrantType => StatusCode::BAD_REQUEST,
             Error::OauthInvalidScopes => StatusCode::UNAUTHORIZED,
             Error::UserNotFound => StatusCode::NOT_FOUND,
             Error::AppNotFound => StatusCode::NOT_FOUND,
@@ -241,15 +294,66 @@ impl From<&Error> for StatusCode {
             Error::LoginRequired => StatusCode::UNAUTHORIZED,
             Error::FileNotFound => StatusCode::NOT_FOUND,
             Error::ClientNotFound => StatusCode::NOT_FOUND,
+            Error::UnverifiedClient { source } => StatusCode::from(source.as_ref()),
             Error::CsrfToken => StatusCode::BAD_REQUEST,
             Error::InvalidOauthToken => StatusCode::UNAUTHORIZED,
             Error::Oauth { .. } => StatusCode::UNAUTHORIZED,
             Error::RateLimitExceeded => StatusCode::TOO_MANY_REQUESTS,
+            Error::RegionMismatch { .. } => StatusCode::MISDIRECTED_REQUEST,
+            Error::ApprovalRequired { .. } => StatusCode::ACCEPTED,
+            Error::IdempotencyKeyInProgress => StatusCode::CONFLICT,
+            Error::IdempotencyKeyReused => StatusCode::UNPROCESSABLE_ENTITY,
+            Error::IdempotencyKeyNotReplayable => StatusCode::CONFLICT,
+            Error::VersionRequired => StatusCode::PRECONDITION_REQUIRED,
+            Error::VersionMismatch => StatusCode::CONFLICT,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 }
 
+/// gRPC status of the admin services, follows the HTTP status of the admin API
+impl From<Error> for tonic::Status {
+    fn from(err: Error) -> Self {
+        let code = match StatusCode::from(&err) {
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => {
+                tonic::Code::InvalidArgument
+            }
+            StatusCode::UNAUTHORIZED => tonic::Code::Unauthenticated,
+            StatusCode::FORBIDDEN => tonic::Code::PermissionDenied,
+            StatusCode::NOT_FOUND => tonic::Code::NotFound,
+            StatusCode::CONFLICT => tonic::Code::Aborted,
+            StatusCode::ACCEPTED | StatusCode::PRECONDITION_REQUIRED => {
+                tonic::Code::FailedPrecondition
+            }
+            StatusCode::TOO_MANY_REQUESTS => tonic::Code::ResourceExhausted,
+            _ => tonic::Code::Internal,
+        };
+        tonic::Status::new(code, err.to_string())
+    }
+}
+
+impl Error {
+    /// RFC 6749 error code for authorization endpoint failures.
+    ///
+    /// Returns `None` when the client or redirect_uri cannot be trusted, in
+    /// which case the user must not be redirected back to the client. This
+    /// covers any failure raised before both were verified. Stale consent
+    /// forms are not reported either, the user can submit again.
+    pub fn oauth_error_code(&self) -> Option<&'static str> {
+        match self {
+            Error::InvalidClient | Error::ClientNotFound | Error::RedirectUriMistmatch => None,
+            Error::UnverifiedClient { .. } => None,
+            Error::CsrfToken => None,
+            Error::Validation { .. } | Error::BadRequest { .. } => Some("invalid_request"),
+            Error::InvalidScopes { .. } | Error::OauthInvalidScopes => Some("invalid_scope"),
+            Error::AppNotRegistered => Some("unauthorized_client"),
+            Error::UnsupportedGrantType => Some("unsupported_grant_type"),
+            err if StatusCode::from(err).is_server_error() => Some("server_error"),
+            _ => Some("access_denied"),
+        }
+    }
+}
+
 // Allow errors to be rendered as response
 impl IntoResponse for Error {
     fn into_response(self) -> Response<Body> {
@@ -271,6 +375,7 @@ impl IntoResponse for Error {
             status_code,
             title,
             message,
+            request_id: None,
         });
 
         res
@@ -282,6 +387,8 @@ pub struct ErrorInfo {
     pub status_code: StatusCode,
     pub title: String,
     pub message: String,
+    /// Set by the response mappers from the `X-Request-Id` header
+    pub request_id: Option<String>,
 }
 
 impl From<&Error> for ErrorInfo {
@@ -295,6 +402,7 @@ impl From<&Error> for ErrorInfo {
                 .expect("status_code must be valid")
                 .to_string(),
             message: msg,
+            request_id: None,
         }
     }
 }
@@ -1,6 +1,7 @@
 mod config;
 mod ctx;
 mod db;
+mod doctor;
 mod dto;
 mod error;
 mod models;
@@ -20,7 +21,24 @@ use config::Config;
 // Re-exports
 pub use error::{Error, Result};
 
-use crate::run::run;
+use crate::doctor::run_doctor;
+use crate::dto::BackfillOptionsDto;
+use crate::dto::ImportConflictStrategy;
+use crate::dto::MigrateOptionsDto;
+use crate::run::{
+    run, run_admin_token, run_backfill, run_break_glass, run_create_superuser, run_deactivate_user,
+    run_gc, run_list_orgs, run_migrate, run_org_export, run_org_import, run_permissions_export,
+    run_reset_password,
+};
+use crate::services::recovery::RECOVERY_TOKEN_TTL_MINS;
+
+#[cfg(not(feature = "alloc-stats"))]
+#[global_allocator]
+static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static GLOBAL: utils::CountingAlloc = utils::CountingAlloc;
 
 #[tokio::main]
 async fn main() {
@@ -32,19 +50,341 @@ async fn main() {
         });
     }
 
-    tracing_subscriber::fmt()
-        .with_max_level(max_log)
-        .with_target(false)
-        .compact()
-        .init();
+    let tracer_provider = utils::init_tracing(max_log).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    });
+
+    let result = run_command().await;
 
-    if let Err(e) = run_command().await {
+    // Flush the spans still waiting in the batch
+    if let Some(provider) = tracer_provider {
+        let _ = provider.shutdown();
+    }
+
+    if let Err(e) = result {
         eprintln!("Application error: {e}");
         process::exit(1);
     }
 }
 
 async fn run_command() -> Result<()> {
-    let config = Config::build()?;
-    run(config).await
+    match std::env::args().nth(1).as_deref() {
+        Some("doctor") => {
+            let code = run_doctor().await;
+            process::exit(code);
+        }
+        Some("gc") => {
+            let repair = std::env::args().skip(2).any(|arg| arg == "--repair");
+            let config = Config::build()?;
+            let code = run_gc(config, repair).await?;
+            process::exit(code);
+        }
+        Some("migrate") => {
+            let args: Vec<String> = std::env::args().skip(2).collect();
+            let options = parse_migrate_args(&args)?;
+            let config = Config::build()?;
+            let code = run_migrate(config, options).await?;
+            process::exit(code);
+        }
+        Some("backfill") => {
+            let args: Vec<String> = std::env::args().skip(2).collect();
+            let (name, options) = parse_backfill_args(&args)?;
+            let config = Config::build()?;
+            let code = run_backfill(config, name.as_deref(), options).await?;
+            process::exit(code);
+        }
+        Some("break-glass") => {
+            let args: Vec<String> = std::env::args().skip(2).collect();
+            let (email, ttl_mins) = parse_break_glass_args(&args)?;
+            let config = Config::build()?;
+            let code = run_break_glass(config, &email, ttl_mins).await?;
+            process::exit(code);
+        }
+        Some("admin-token") => {
+            let email = std::env::args().nth(2).ok_or_else(|| Error::Config {
+                msg: "Usage: yaas admin-token <email>".to_string(),
+            })?;
+            let config = Config::build()?;
+            let code = run_admin_token(config, &email).await?;
+            process::exit(code);
+        }
+        Some("org-export") => {
+            let args: Vec<String> = std::env::args().skip(2).collect();
+            let (org_id, out) = parse_org_export_args(&args)?;
+            let config = Config::build()?;
+            let code = run_org_export(config, &org_id, out.as_deref()).await?;
+            process::exit(code);
+        }
+        Some("org-import") => {
+            let args: Vec<String> = std::env::args().skip(2).collect();
+            let (file, strategy, dry_run) = parse_org_import_args(&args)?;
+            let config = Config::build()?;
+            let code = run_org_import(config, &file, strategy, dry_run).await?;
+            process::exit(code);
+        }
+        Some("permissions-export") => {
+            let args: Vec<String> = std::env::args().skip(2).collect();
+            let (org_id, format, out) = parse_permissions_export_args(&args)?;
+            let config = Config::build()?;
+            let code =
+                run_permissions_export(config, org_id.as_deref(), &format, out.as_deref()).await?;
+            process::exit(code);
+        }
+        Some("create-superuser") => {
+            let args: Vec<String> = std::env::args().skip(2).collect();
+            let (email, password_stdin) = parse_password_args("create-superuser", &args)?;
+            let config = Config::build()?;
+            let code = run_create_superuser(config, &email, password_stdin).await?;
+            process::exit(code);
+        }
+        Some("reset-password") => {
+            let args: Vec<String> = std::env::args().skip(2).collect();
+            let (email, password_stdin) = parse_password_args("reset-password", &args)?;
+            let config = Config::build()?;
+            let code = run_reset_password(config, &email, password_stdin).await?;
+            process::exit(code);
+        }
+        Some("deactivate-user") => {
+            let email = std::env::args().nth(2).ok_or_else(|| Error::Config {
+                msg: "Usage: yaas deactivate-user <email>".to_string(),
+            })?;
+            let config = Config::build()?;
+            let code = run_deactivate_user(config, &email).await?;
+            process::exit(code);
+        }
+        Some("list-orgs") => {
+            let args: Vec<String> = std::env::args().skip(2).collect();
+            let keyword = parse_list_orgs_args(&args)?;
+            let config = Config::build()?;
+            let code = run_list_orgs(config, keyword).await?;
+            process::exit(code);
+        }
+        Some(cmd) => Err(Error::Config {
+            msg: format!(
+                "Unknown command: {}. Available commands: doctor, gc, migrate, backfill, break-glass, admin-token, create-superuser, reset-password, deactivate-user, list-orgs, org-export, org-import, permissions-export",
+                cmd
+            ),
+        }),
+        None => {
+            let config = Config::build()?;
+            run(config).await
+        }
+    }
+}
+
+/// Parses `break-glass <email> [--ttl-mins N]`
+fn parse_break_glass_args(args: &[String]) -> Result<(String, i64)> {
+    let usage = || Error::Config {
+        msg: "Usage: yaas break-glass <email> [--ttl-mins N]".to_string(),
+    };
+
+    let mut email: Option<String> = None;
+    let mut ttl_mins = RECOVERY_TOKEN_TTL_MINS;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--ttl-mins" => {
+                let value = iter.next().ok_or_else(usage)?;
+                ttl_mins = value.parse::<i64>().map_err(|_| usage())?;
+            }
+            value if email.is_none() && !value.starts_with("--") => {
+                email = Some(value.to_string());
+            }
+            _ => return Err(usage()),
+        }
+    }
+
+    Ok((email.ok_or_else(usage)?, ttl_mins))
+}
+
+/// Parses `<command> <email> [--password-stdin]`
+fn parse_password_args(command: &str, args: &[String]) -> Result<(String, bool)> {
+    let usage = || Error::Config {
+        msg: format!("Usage: yaas {} <email> [--password-stdin]", command),
+    };
+
+    let mut email: Option<String> = None;
+    let mut password_stdin = false;
+
+    for arg in args.iter() {
+        match arg.as_str() {
+            "--password-stdin" => password_stdin = true,
+            value if email.is_none() && !value.starts_with("--") => {
+                email = Some(value.to_string());
+            }
+            _ => return Err(usage()),
+        }
+    }
+
+    Ok((email.ok_or_else(usage)?, password_stdin))
+}
+
+/// Parses `list-orgs [--keyword TEXT]`
+fn parse_list_orgs_args(args: &[String]) -> Result<Option<String>> {
+    let usage = || Error::Config {
+        msg: "Usage: yaas list-orgs [--keyword TEXT]".to_string(),
+    };
+
+    let mut keyword: Option<String> = None;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--keyword" => {
+                keyword = Some(iter.next().ok_or_else(usage)?.to_string());
+            }
+            _ => return Err(usage()),
+        }
+    }
+
+    Ok(keyword)
+}
+
+/// Parses `migrate [--allow-unsafe] [--contract] [--baseline] [--check]`
+fn parse_migrate_args(args: &[String]) -> Result<MigrateOptionsDto> {
+    let mut options = MigrateOptionsDto::default();
+
+    for arg in args.iter() {
+        match arg.as_str() {
+            "--allow-unsafe" => options.allow_unsafe = true,
+            "--contract" => options.contract = true,
+            "--baseline" => options.baseline = true,
+            "--check" => options.check = true,
+            _ => {
+                return Err(Error::Config {
+                    msg: "Usage: yaas migrate [--allow-unsafe] [--contract] [--baseline] [--check]"
+                        .to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(options)
+}
+
+/// Parses `backfill [<name>] [--batch-size N] [--pause-ms N] [--max-batches N] [--restart]`
+fn parse_backfill_args(args: &[String]) -> Result<(Option<String>, BackfillOptionsDto)> {
+    let usage = || {
+        Error::Config {
+        msg: "Usage: yaas backfill [<name>] [--batch-size N] [--pause-ms N] [--max-batches N] [--restart]"
+            .to_string(),
+    }
+    };
+
+    let mut name: Option<String> = None;
+    let mut options = BackfillOptionsDto::default();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--batch-size" => {
+                options.batch_size = iter.next().and_then(|v| v.parse().ok()).ok_or_else(usage)?;
+            }
+            "--pause-ms" => {
+                options.pause_ms = iter.next().and_then(|v| v.parse().ok()).ok_or_else(usage)?;
+            }
+            "--max-batches" => {
+                let value = iter.next().and_then(|v| v.parse().ok()).ok_or_else(usage)?;
+                options.max_batches = Some(value);
+            }
+            "--restart" => options.restart = true,
+            value if name.is_none() && !value.starts_with("--") => {
+                name = Some(value.to_string());
+            }
+            _ => return Err(usage()),
+        }
+    }
+
+    Ok((name, options))
+}
+
+/// Parses `org-export <org_id> [--out FILE]`
+fn parse_org_export_args(args: &[String]) -> Result<(String, Option<String>)> {
+    let usage = || Error::Config {
+        msg: "Usage: yaas org-export <org_id> [--out FILE]".to_string(),
+    };
+
+    let mut org_id: Option<String> = None;
+    let mut out: Option<String> = None;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--out" => {
+                out = Some(iter.next().ok_or_else(usage)?.to_string());
+            }
+            value if org_id.is_none() && !value.starts_with("--") => {
+                org_id = Some(value.to_string());
+            }
+            _ => return Err(usage()),
+        }
+    }
+
+    Ok((org_id.ok_or_else(usage)?, out))
+}
+
+/// Parses `permissions-export [--org ORG_ID] [--format json|csv] [--out FILE]`
+fn parse_permissions_export_args(
+    args: &[String],
+) -> Result<(Option<String>, String, Option<String>)> {
+    let usage = || Error::Config {
+        msg: "Usage: yaas permissions-export [--org ORG_ID] [--format json|csv] [--out FILE]"
+            .to_string(),
+    };
+
+    let mut org_id: Option<String> = None;
+    let mut format = "json".to_string();
+    let mut out: Option<String> = None;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--org" => {
+                org_id = Some(iter.next().ok_or_else(usage)?.to_string());
+            }
+            "--format" => {
+                let value = iter.next().ok_or_else(usage)?;
+                if value != "json" && value != "csv" {
+                    return Err(usage());
+                }
+                format = value.to_string();
+            }
+            "--out" => {
+                out = Some(iter.next().ok_or_else(usage)?.to_string());
+            }
+            _ => return Err(usage()),
+        }
+    }
+
+    Ok((org_id, format, out))
+}
+
+/// Parses `org-import <file> [--strategy skip|merge|rename] [--dry-run]`
+fn parse_org_import_args(args: &[String]) -> Result<(String, ImportConflictStrategy, bool)> {
+    let usage = || Error::Config {
+        msg: "Usage: yaas org-import <file> [--strategy skip|merge|rename] [--dry-run]".to_string(),
+    };
+
+    let mut file: Option<String> = None;
+    let mut strategy = ImportConflictStrategy::Skip;
+    let mut dry_run = false;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--strategy" => {
+                let value = iter.next().ok_or_else(usage)?;
+                strategy = ImportConflictStrategy::try_from(value.as_str())?;
+            }
+            "--dry-run" => dry_run = true,
+            value if file.is_none() && !value.starts_with("--") => {
+                file = Some(value.to_string());
+            }
+            _ => return Err(usage()),
+        }
+    }
+
+    Ok((file.ok_or_else(usage)?, strategy, dry_run))
 }
@@ -0,0 +1,70 @@
+use crate::{Error, Result};
+
+/// Bulk status form submitted from listing widgets.
+///
+/// Selected rows are submitted as repeated `ids` fields which the default
+/// form extractor cannot map into a `Vec`, hence parsing from raw pairs.
+#[derive(Clone)]
+pub struct BulkStatusFormData {
+    pub token: String,
+    pub status: String,
+    pub ids: Vec<String>,
+}
+
+impl TryFrom<Vec<(String, String)>> for BulkStatusFormData {
+    type Error = Error;
+
+    fn try_from(pairs: Vec<(String, String)>) -> Result<Self> {
+        let mut token: Option<String> = None;
+        let mut status: Option<String> = None;
+        let mut ids: Vec<String> = Vec::new();
+
+        for (key, value) in pairs.into_iter() {
+            match key.as_str() {
+                "token" => token = Some(value),
+                "status" => status = Some(value),
+                "ids" if !value.is_empty() && !ids.contains(&value) => ids.push(value),
+                _ => {}
+            }
+        }
+
+        let Some(token) = token else {
+            return Err(Error::CsrfToken);
+        };
+
+        let Some(status) = status else {
+            return Err(Error::Validation {
+                msg: "Status is required".to_string(),
+            });
+        };
+
+        Ok(BulkStatusFormData { token, status, ids })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bulk_status_form_from_pairs() {
+        let pairs = vec![
+            ("token".to_string(), "abc".to_string()),
+            ("ids".to_string(), "usr_1".to_string()),
+            ("ids".to_string(), "usr_2".to_string()),
+            ("ids".to_string(), "usr_1".to_string()),
+            ("status".to_string(), "inactive".to_string()),
+        ];
+
+        let form = BulkStatusFormData::try_from(pairs).expect("form should parse");
+        assert_eq!(form.token, "abc");
+        assert_eq!(form.status, "inactive");
+        assert_eq!(form.ids, vec!["usr_1".to_string(), "usr_2".to_string()]);
+    }
+
+    #[test]
+    fn test_bulk_status_form_requires_status() {
+        let pairs = vec![("token".to_string(), "abc".to_string())];
+        assert!(BulkStatusFormData::try_from(pairs).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use crate::dto::{Actor, Permission};
+
+/// Call to action rendered below an empty state message
+#[derive(Clone)]
+pub struct EmptyStateAction {
+    pub label: String,
+    pub url: String,
+}
+
+/// Content for the shared empty-state / zero-results widget
+#[derive(Clone)]
+pub struct EmptyState {
+    pub title: String,
+    pub message: String,
+    pub action: Option<EmptyStateAction>,
+}
+
+impl EmptyState {
+    fn build(
+        keyword: Option<&str>,
+        resource: &str,
+        create: Option<EmptyStateAction>,
+        has_permission: bool,
+    ) -> Self {
+        let searching = keyword.map(|k| !k.trim().is_empty()).unwrap_or(false);
+
+        let (title, message) = if searching {
+            (
+                "No match".to_string(),
+                format!("There are no {} matching your search criteria.", resource),
+            )
+        } else {
+            (
+                format!("No {} yet", resource),
+                format!("There are no {} to show yet.", resource),
+            )
+        };
+
+        EmptyState {
+            title,
+            message,
+            action: if has_permission { create } else { None },
+        }
+    }
+
+    pub fn users(actor: &Actor, keyword: Option<&str>) -> Self {
+        Self::build(
+            keyword,
+            "users",
+            Some(EmptyStateAction {
+                label: "Create a user".to_string(),
+                url: "/users/new".to_string(),
+            }),
+            actor.has_permissions(&[Permission::UsersCreate]),
+        )
+    }
+
+    pub fn orgs(actor: &Actor, keyword: Option<&str>) -> Self {
+        Self::build(
+            keyword,
+            "orgs",
+            Some(EmptyStateAction {
+                label: "Create an org".to_string(),
+                url: "/orgs/new".to_string(),
+            }),
+            actor.has_permissions(&[Permission::OrgsCreate]),
+        )
+    }
+
+    pub fn apps(actor: &Actor, keyword: Option<&str>) -> Self {
+        Self::build(
+            keyword,
+            "apps",
+            Some(EmptyStateAction {
+                label: "Register an app".to_string(),
+                url: "/apps/new".to_string(),
+            }),
+            actor.has_permissions(&[Permission::AppsCreate]),
+        )
+    }
+
+    pub fn org_members(actor: &Actor, org_id: &str, keyword: Option<&str>) -> Self {
+        Self::build(
+            keyword,
+            "members",
+            Some(EmptyStateAction {
+                label: "Add a member".to_string(),
+                url: format!("/orgs/{}/members/new", org_id),
+            }),
+            actor.has_permissions(&[Permission::OrgMembersCreate]),
+        )
+    }
+
+    pub fn org_apps(actor: &Actor, org_id: &str, keyword: Option<&str>) -> Self {
+        Self::build(
+            keyword,
+            "org apps",
+            Some(EmptyStateAction {
+                label: "Add an app".to_string(),
+                url: format!("/orgs/{}/apps/new", org_id),
+            }),
+            actor.has_permissions(&[Permission::OrgAppsCreate]),
+        )
+    }
+
+    /// Empty state for suggestion lists where there is nothing to create inline
+    pub fn suggestions(resource: &str, keyword: Option<&str>) -> Self {
+        Self::build(keyword, resource, None, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto::{ActorPayloadDto, Role, Scope, UserDto};
+
+    fn create_actor(roles: Vec<Role>) -> Actor {
+        Actor::new(
+            ActorPayloadDto {
+                id: "usr_test".to_string(),
+                org_id: "org_test".to_string(),
+                org_count: 1,
+                roles,
+                scopes: vec![Scope::Auth],
+                grant_id: None,
+                proof_key_id: None,
+                permission_mask: None,
+                issued_at: 0,
+                session_id: None,
+                region: None,
+                home_region: None,
+                token_id: None,
+                org_app_id: None,
+                impersonator_id: None,
+                impersonation_id: None,
+            },
+            UserDto {
+                id: "usr_test".to_string(),
+                email: "test@example.com".to_string(),
+                name: "test".to_string(),
+                status: "active".to_string(),
+                created_at: 0,
+                updated_at: 0,
+                deleted_at: None,
+            },
+        )
+    }
+
+    #[test]
+    fn test_empty_state_with_keyword_is_no_match() {
+        let actor = create_actor(vec![Role::Superuser]);
+        let state = EmptyState::users(&actor, Some("john"));
+        assert_eq!(state.title, "No match");
+        assert_eq!(
+            state.message,
+            "There are no users matching your search criteria."
+        );
+    }
+
+    #[test]
+    fn test_empty_state_without_keyword_is_empty_collection() {
+        let actor = create_actor(vec![Role::Superuser]);
+        let state = EmptyState::orgs(&actor, Some("  "));
+        assert_eq!(state.title, "No orgs yet");
+        assert!(state.action.is_some());
+    }
+
+    #[test]
+    fn test_empty_state_action_requires_permission() {
+        let actor = create_actor(vec![Role::OrgViewer]);
+        let state = EmptyState::org_members(&actor, "org_test", None);
+        assert!(state.action.is_none());
+
+        let actor = create_actor(vec![Role::OrgAdmin]);
+        let state = EmptyState::org_members(&actor, "org_test", None);
+        let action = state.action.expect("action should exist");
+        assert_eq!(action.url, "/orgs/org_test/members/new");
+    }
+
+    #[test]
+    fn test_suggestions_empty_state_has_no_action() {
+        let state = EmptyState::suggestions("apps", None);
+        assert!(state.action.is_none());
+    }
+}
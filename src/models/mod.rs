@@ -1,20 +1,26 @@
+mod bulk;
 mod csp;
+mod empty_state;
 mod login;
 pub mod options;
 mod pagination;
 mod params;
 mod pref;
 mod setup;
+mod sorting;
 mod template;
 mod tokens;
 mod view;
 
+pub use bulk::*;
 pub use csp::*;
+pub use empty_state::*;
 pub use login::*;
 pub use pagination::*;
 pub use params::*;
 pub use pref::*;
 pub use setup::*;
+pub use sorting::*;
 pub use template::*;
 pub use tokens::*;
 pub use view::*;
@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Deserialize, Serialize)]
-pub struct SelectOption {
+pub struct CheckboxOption {
     pub value: String,
     pub label: String,
+    pub checked: bool,
+
+    /// Shown under the option and as its tooltip
+    pub help: Option<String>,
 }
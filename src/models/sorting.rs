@@ -0,0 +1,134 @@
+use serde::Serialize;
+use urlencoding::encode;
+
+use crate::dto::SortSpec;
+
+/// Column header of a listing table, links to the listing sorted by its field
+#[derive(Clone, Serialize)]
+pub struct SortHeader {
+    pub label: String,
+    pub fetch_url: String,
+    pub landing_url: String,
+    pub target: String,
+
+    /// `asc` or `desc` when the listing is currently sorted by this column
+    pub direction: Option<&'static str>,
+}
+
+/// Builds the sortable headers of a listing table.
+///
+/// Clicking the active column flips its direction, other columns start ascending.
+#[derive(Clone, Serialize)]
+pub struct SortLinks {
+    fetch_url: String,
+    landing_url: String,
+    suffix: String,
+    target: String,
+    sort_by: &'static str,
+    descending: bool,
+}
+
+impl SortLinks {
+    pub fn new(
+        spec: &SortSpec,
+        sort_by: Option<&str>,
+        sort_dir: Option<&str>,
+        fetch_url: &str,
+        landing_url: &str,
+        keyword: Option<&str>,
+        target: &str,
+    ) -> Self {
+        let sort_by = sort_by
+            .and_then(|field| spec.columns.iter().find(|(name, _)| *name == field))
+            .map(|(name, _)| *name)
+            .unwrap_or(spec.default_field());
+
+        Self {
+            fetch_url: fetch_url.to_string(),
+            landing_url: landing_url.to_string(),
+            suffix: keyword
+                .map(|keyword| format!("&keyword={}", encode(keyword)))
+                .unwrap_or_default(),
+            target: target.to_string(),
+            sort_by,
+            descending: sort_dir == Some("desc"),
+        }
+    }
+
+    pub fn header(&self, field: &str, label: &str) -> SortHeader {
+        let active = field == self.sort_by;
+        let next_dir = if active && !self.descending {
+            "desc"
+        } else {
+            "asc"
+        };
+        let query = format!(
+            "?page=1&sort_by={}&sort_dir={}{}",
+            encode(field),
+            next_dir,
+            self.suffix
+        );
+
+        SortHeader {
+            label: label.to_string(),
+            fetch_url: format!("{}{}", self.fetch_url, query),
+            landing_url: format!("{}{}", self.landing_url, query),
+            target: self.target.clone(),
+            direction: match (active, self.descending) {
+                (false, _) => None,
+                (true, false) => Some("asc"),
+                (true, true) => Some("desc"),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto::USER_SORT;
+
+    #[test]
+    fn test_active_header_flips_direction() {
+        let links = SortLinks::new(
+            &USER_SORT,
+            None,
+            None,
+            "/users/search",
+            "/users",
+            Some("jo"),
+            ".album-items",
+        );
+
+        let email = links.header("email", "Email");
+        assert_eq!(email.direction, Some("asc"));
+        assert_eq!(
+            email.fetch_url,
+            "/users/search?page=1&sort_by=email&sort_dir=desc&keyword=jo"
+        );
+
+        let name = links.header("name", "Name");
+        assert_eq!(name.direction, None);
+        assert_eq!(
+            name.landing_url,
+            "/users?page=1&sort_by=name&sort_dir=asc&keyword=jo"
+        );
+
+        let links = SortLinks::new(
+            &USER_SORT,
+            Some("name"),
+            Some("desc"),
+            "/users/search",
+            "/users",
+            None,
+            ".album-items",
+        );
+        assert_eq!(links.header("name", "Name").direction, Some("desc"));
+        assert!(
+            links
+                .header("name", "Name")
+                .fetch_url
+                .ends_with("sort_dir=asc")
+        );
+    }
+}
@@ -1,4 +1,6 @@
+use crate::config::{VENDOR_SCRIPTS, VENDOR_STYLES};
 use crate::run::AppState;
+use crate::services::token::create_csrf_token_svc;
 
 use super::Pref;
 use crate::dto::Actor;
@@ -15,6 +17,7 @@ pub struct TemplateData {
     pub ga_tag_id: Option<String>,
     pub actor: Actor,
     pub is_system_admin: bool,
+    pub stop_impersonation_token: Option<String>,
 }
 
 impl TemplateData {
@@ -22,9 +25,22 @@ impl TemplateData {
         let config = state.config.clone();
         let is_system_admin = actor.is_system_admin();
 
-        // Add main CSS and JS by default
-        let styles: Vec<String> = vec![state.config.assets.main_css.clone()];
-        let scripts: Vec<String> = vec![state.config.assets.main_js.clone()];
+        // Every page shows a way out while impersonating
+        let stop_impersonation_token = actor
+            .impersonator_id()
+            .and_then(|_| create_csrf_token_svc("stop_impersonating", &config.jwt_secret).ok());
+
+        // Vendor assets first, then the main CSS and JS bundles
+        let styles: Vec<String> = VENDOR_STYLES
+            .iter()
+            .map(|url| url.to_string())
+            .chain([state.config.assets.main_css.clone()])
+            .collect();
+        let scripts: Vec<String> = VENDOR_SCRIPTS
+            .iter()
+            .map(|url| url.to_string())
+            .chain([state.config.assets.main_js.clone()])
+            .collect();
 
         TemplateData {
             nonce,
@@ -37,6 +53,7 @@ impl TemplateData {
             ga_tag_id: config.ga_tag_id.clone(),
             actor,
             is_system_admin,
+            stop_impersonation_token,
         }
     }
 }
@@ -1,7 +1,12 @@
 use chrono::{DateTime, Utc};
 
 use crate::dto::Role;
-use crate::dto::{AppDto, OrgAppDto, OrgDto, OrgMemberDto, UserDto};
+use crate::dto::{
+    AppDto, ApprovalDto, ApprovalStatus, AuthorizedAppDto, DELIVERY_FAILED, ElevationStatus,
+    FormDraftDto, LifecycleSubscriptionDto, OrgAppDto, OrgDto, OrgInvitationDto, OrgMemberDto,
+    OrgOwnerTransferDto, RoleElevationDto, UserDto, UserEmailDto, UserNotificationDto,
+    UserSessionDto,
+};
 
 fn to_ymd(millis: i64) -> String {
     match DateTime::<Utc>::from_timestamp_millis(millis) {
@@ -10,6 +15,13 @@ fn to_ymd(millis: i64) -> String {
     }
 }
 
+fn to_ymd_hm(millis: i64) -> String {
+    match DateTime::<Utc>::from_timestamp_millis(millis) {
+        Some(datetime) => datetime.format("%Y-%m-%d %H:%M UTC").to_string(),
+        None => String::new(),
+    }
+}
+
 #[derive(Clone)]
 pub struct UserView {
     pub id: String,
@@ -71,6 +83,8 @@ pub struct OrgView {
     pub owner_email: Option<String>,
     #[allow(dead_code)]
     pub owner_name: Option<String>,
+    pub member_count: i64,
+    pub app_count: i64,
     pub updated_at: String,
     pub created_at: String,
 }
@@ -84,6 +98,8 @@ impl From<OrgDto> for OrgView {
             owner_id: org.owner_id,
             owner_email: org.owner_email,
             owner_name: org.owner_name,
+            member_count: org.member_count,
+            app_count: org.app_count,
             updated_at: to_ymd(org.updated_at),
             created_at: to_ymd(org.created_at),
         }
@@ -103,6 +119,15 @@ pub struct OrgMemberView {
     pub status: String,
     pub created_at: String,
     pub updated_at: String,
+    /// Set while the member holds an elevated role
+    pub elevated_until: Option<String>,
+}
+
+impl OrgMemberView {
+    pub fn with_elevated_until(mut self, expires_at: Option<i64>) -> Self {
+        self.elevated_until = expires_at.map(to_ymd_hm);
+        self
+    }
 }
 
 impl From<OrgMemberDto> for OrgMemberView {
@@ -117,6 +142,7 @@ impl From<OrgMemberDto> for OrgMemberView {
             status: member.status,
             created_at: to_ymd(member.created_at),
             updated_at: to_ymd(member.updated_at),
+            elevated_until: None,
         }
     }
 }
@@ -142,3 +168,253 @@ impl From<OrgAppDto> for OrgAppView {
         }
     }
 }
+
+#[derive(Clone)]
+pub struct AuthorizedAppView {
+    pub app_id: String,
+    pub app_name: String,
+    pub org_name: String,
+    pub scope: String,
+    pub created_at: String,
+    pub last_used_at: String,
+}
+
+impl From<AuthorizedAppDto> for AuthorizedAppView {
+    fn from(app: AuthorizedAppDto) -> Self {
+        AuthorizedAppView {
+            app_id: app.app_id,
+            app_name: app.app_name,
+            org_name: app.org_name,
+            scope: app.scope,
+            created_at: to_ymd(app.created_at),
+            last_used_at: to_ymd(app.last_used_at),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct UserEmailView {
+    pub id: String,
+    pub email: String,
+    pub verified: bool,
+    pub created_at: String,
+}
+
+impl From<UserEmailDto> for UserEmailView {
+    fn from(email: UserEmailDto) -> Self {
+        UserEmailView {
+            verified: email.is_verified(),
+            id: email.id,
+            email: email.email,
+            created_at: to_ymd(email.created_at),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ApprovalView {
+    pub id: String,
+    pub action: String,
+    pub target_id: String,
+    pub requested_by: String,
+    pub status: String,
+    pub decided_by: String,
+    pub created_at: String,
+    pub expires_at: String,
+    /// Still waiting for a decision within its window
+    pub open: bool,
+}
+
+impl From<ApprovalDto> for ApprovalView {
+    fn from(approval: ApprovalDto) -> Self {
+        let now = Utc::now().timestamp_millis();
+        let open = approval.status == ApprovalStatus::Pending && !approval.is_expired(now);
+
+        // Show lapsed requests as expired even before anyone touched them
+        let status = if approval.status == ApprovalStatus::Pending && !open {
+            ApprovalStatus::Expired
+        } else {
+            approval.status
+        };
+
+        ApprovalView {
+            id: approval.id,
+            action: approval.action.to_string(),
+            target_id: approval.target_id,
+            requested_by: approval.requested_by,
+            status: status.to_string(),
+            decided_by: approval.decided_by.unwrap_or_default(),
+            created_at: to_ymd_hm(approval.created_at),
+            expires_at: to_ymd_hm(approval.expires_at),
+            open,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RoleElevationView {
+    pub id: String,
+    pub role: String,
+    pub reason: String,
+    pub status: String,
+    pub duration_mins: i64,
+    pub requested_by: String,
+    pub decided_by: String,
+    pub created_at: String,
+    pub expires_at: String,
+    pub pending: bool,
+    pub active: bool,
+}
+
+impl From<RoleElevationDto> for RoleElevationView {
+    fn from(elevation: RoleElevationDto) -> Self {
+        RoleElevationView {
+            pending: elevation.status == ElevationStatus::Pending,
+            active: elevation.status == ElevationStatus::Active,
+            id: elevation.id,
+            role: elevation.role.to_string(),
+            reason: elevation.reason,
+            status: elevation.status.to_string(),
+            duration_mins: elevation.duration_mins,
+            requested_by: elevation.requested_by,
+            decided_by: elevation.decided_by.unwrap_or_default(),
+            created_at: to_ymd_hm(elevation.created_at),
+            expires_at: elevation.expires_at.map(to_ymd_hm).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct OrgInvitationView {
+    pub id: String,
+    pub email: String,
+    pub roles: String,
+    pub status: String,
+    pub created_at: String,
+    pub expires_at: String,
+    pub accepted_at: String,
+    pub pending: bool,
+    pub accepted: bool,
+    /// The outbox gave up on the latest email, resending issues a new one
+    pub email_failed: bool,
+}
+
+impl From<OrgInvitationDto> for OrgInvitationView {
+    fn from(invitation: OrgInvitationDto) -> Self {
+        let now = Utc::now().timestamp_millis();
+        let roles: Vec<&str> = invitation.roles.iter().map(|role| role.label()).collect();
+
+        OrgInvitationView {
+            status: invitation.status(now).to_string(),
+            pending: invitation.is_usable(now),
+            accepted: invitation.accepted_at.is_some(),
+            email_failed: invitation.delivery_status.as_deref() == Some(DELIVERY_FAILED),
+            id: invitation.id,
+            email: invitation.email,
+            roles: roles.join(", "),
+            created_at: to_ymd_hm(invitation.created_at),
+            expires_at: to_ymd_hm(invitation.expires_at),
+            accepted_at: invitation.accepted_at.map(to_ymd_hm).unwrap_or_default(),
+        }
+    }
+}
+
+/// Ownership transfer waiting for the new owner to accept
+#[derive(Clone)]
+pub struct OwnerTransferView {
+    pub email: String,
+    pub created_at: String,
+    pub expires_at: String,
+}
+
+impl From<OrgOwnerTransferDto> for OwnerTransferView {
+    fn from(transfer: OrgOwnerTransferDto) -> Self {
+        OwnerTransferView {
+            email: transfer.to_email.unwrap_or(transfer.to_user_id),
+            created_at: to_ymd_hm(transfer.created_at),
+            expires_at: to_ymd_hm(transfer.expires_at),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct UserSessionView {
+    pub id: String,
+    pub device: String,
+    pub ip_address: String,
+    pub created_at: String,
+    pub last_used_at: String,
+    pub current: bool,
+}
+
+impl From<UserSessionDto> for UserSessionView {
+    fn from(session: UserSessionDto) -> Self {
+        UserSessionView {
+            id: session.id,
+            device: session
+                .user_agent
+                .unwrap_or_else(|| "Unknown device".to_string()),
+            ip_address: session.ip_address.unwrap_or_default(),
+            created_at: to_ymd_hm(session.created_at),
+            last_used_at: to_ymd_hm(session.last_used_at),
+            current: session.current,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct FormDraftView {
+    pub form_key: String,
+    pub data: String,
+    pub updated_at: String,
+}
+
+impl From<FormDraftDto> for FormDraftView {
+    fn from(draft: FormDraftDto) -> Self {
+        FormDraftView {
+            form_key: draft.form_key,
+            data: draft.data,
+            updated_at: to_ymd_hm(draft.updated_at),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct UserNotificationView {
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    pub link: Option<String>,
+    pub unread: bool,
+    pub created_at: String,
+}
+
+impl From<UserNotificationDto> for UserNotificationView {
+    fn from(notification: UserNotificationDto) -> Self {
+        UserNotificationView {
+            id: notification.id,
+            title: notification.title,
+            body: notification.body,
+            link: notification.link,
+            unread: notification.read_at.is_none(),
+            created_at: to_ymd_hm(notification.created_at),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LifecycleSubscriptionView {
+    pub id: String,
+    pub url: String,
+    pub secret: String,
+}
+
+impl From<LifecycleSubscriptionDto> for LifecycleSubscriptionView {
+    fn from(subscription: LifecycleSubscriptionDto) -> Self {
+        LifecycleSubscriptionView {
+            id: subscription.id,
+            url: subscription.url,
+            secret: subscription.secret,
+        }
+    }
+}
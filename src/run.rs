@@ -1,28 +1,80 @@
-use axum::Router;
 use axum::extract::FromRef;
+use axum::{Router, middleware};
 use moka::sync::Cache;
 use reqwest::{Client, ClientBuilder};
+use snafu::ResultExt;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::net::TcpListener;
+use tokio::sync::watch;
 use tower_cookies::CookieManagerLayer;
-use tower_http::trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::{DefaultOnResponse, TraceLayer};
 use tracing::{Level, info};
 
 use crate::Result;
 use crate::config::{Config, SuperuserConfig};
-use crate::db::{DbMapper, create_db_mapper};
-use crate::dto::Actor;
+use crate::db::{BACKFILLS, DbMapper, MIGRATIONS, create_db_mapper, create_db_mapper_with_replica};
+use crate::dto::{
+    AuthLookupDto, BackfillOptionsDto, ImportConflictStrategy, ListOrgsParamsDto,
+    MigrateOptionsDto, SuggestionBufDto,
+};
+use crate::error::{IoSnafu, JsonSerializeSnafu};
+use crate::services::admin_events::AdminEvents;
+use crate::services::app_environments::hash_plain_environment_secrets_svc;
+use crate::services::apps::hash_plain_app_secrets_svc;
+use crate::services::auth::{issue_superuser_token_svc, new_auth_cache};
+use crate::services::auth_rate_limits::AuthRateLimiter;
+use crate::services::backfills::{find_backfill, list_backfills_svc, run_backfill_svc};
+use crate::services::counters::counter_reconcile_job;
+use crate::services::drafts::draft_cleanup_job;
+use crate::services::idempotency::idempotency_cleanup_job;
+use crate::services::integrity::{integrity_scan_job, integrity_scan_svc};
+use crate::services::jobs::job_worker;
+use crate::services::mailer::Mailer;
+use crate::services::memory::memory_sample_job;
+use crate::services::migrations::migrate_svc;
+use crate::services::notifications::notification_digest_job;
+use crate::services::oidc::OidcProvider;
+use crate::services::org_access::{ORG_ACCESS_SAMPLE_MINS, org_access_retention_job};
+use crate::services::org_rate_limits::OrgRateLimiter;
+use crate::services::org_transfer::{export_org_svc, import_org_svc, parse_org_archive};
+use crate::services::password::{generate_password, reset_user_password_svc};
+use crate::services::permissions::{permission_matrix_svc, permission_matrix_to_csv};
+use crate::services::recovery::issue_recovery_token_svc;
+use crate::services::revocations::TokenDenylist;
+use crate::services::setup::{create_superuser_svc, log_setup_key, setup_required_svc};
+use crate::services::shared_cache::SharedCache;
+use crate::services::token::TokenKeys;
+use crate::services::users::deactivate_user_svc;
 use crate::utils::{IdPrefix, generate_id};
-use crate::web::all_routes;
+use crate::web::{RedactedMakeSpan, all_routes, request_log_middleware};
 
 #[derive(Clone, FromRef)]
 pub struct AppState {
     pub config: Arc<Config>,
     pub db: Arc<DbMapper>,
     pub client: Client,
-    pub auth_cache: Cache<String, Actor>,
+    pub auth_cache: Cache<String, AuthLookupDto>,
+    pub suggestion_cache: Cache<String, Arc<SuggestionBufDto>>,
+    /// Org access already recorded within the current sampling window
+    pub access_log_cache: Cache<String, ()>,
+    /// Token revocation cutoff per user, 0 when never revoked
+    pub revocation_cache: Cache<String, i64>,
+    /// Individually revoked tokens, in memory, in the database or in Redis
+    pub token_denylist: TokenDenylist,
+    /// Redis when `REDIS_URL` is set, shared by the instances of the deployment
+    pub shared_cache: Arc<SharedCache>,
+    pub org_rate_limiter: OrgRateLimiter,
+    pub auth_rate_limiter: AuthRateLimiter,
+    /// Signs ID tokens when OpenID Connect is configured
+    pub oidc: Option<Arc<OidcProvider>>,
+    pub token_keys: Arc<TokenKeys>,
+    /// Sends the queued emails, selected with `MAILER`
+    pub mailer: Arc<Mailer>,
+    /// Entity changes streamed to admins
+    pub admin_events: AdminEvents,
 }
 
 pub async fn run(config: Config) -> Result<()> {
@@ -30,7 +82,12 @@ pub async fn run(config: Config) -> Result<()> {
     let frontend_dir = config.frontend_dir.clone();
     let db_file = config.db.dir.join("default").join("yaas.db");
 
-    let mapper = create_db_mapper(db_file.as_path()).await?;
+    let mapper = create_db_mapper_with_replica(
+        db_file.as_path(),
+        config.db.read_path.as_deref(),
+        &config.pagination,
+    )
+    .await?;
 
     let db = Arc::new(mapper);
 
@@ -39,30 +96,103 @@ pub async fn run(config: Config) -> Result<()> {
         .build()
         .expect("HTTP Client is required");
 
-    let auth_cache = Cache::builder()
+    let auth_cache = new_auth_cache(&config.auth_cache);
+
+    // Type-ahead results only need to survive a burst of keystrokes
+    let suggestion_cache = Cache::builder()
+        .time_to_live(Duration::from_secs(30))
+        .max_capacity(500)
+        .build();
+
+    let access_log_cache = Cache::builder()
+        .time_to_live(Duration::from_secs(ORG_ACCESS_SAMPLE_MINS * 60))
+        .max_capacity(10_000)
+        .build();
+
+    // Force logouts update the cache directly, the TTL only bounds its size
+    let revocation_cache = Cache::builder()
         .time_to_live(Duration::from_secs(10 * 60))
-        .time_to_idle(Duration::from_secs(60))
-        .max_capacity(100)
+        .max_capacity(10_000)
         .build();
 
     // Check for superusers
     let config = init_superuser(config, db.clone()).await?;
 
+    let oidc = match &config.oidc {
+        Some(oidc) => Some(Arc::new(OidcProvider::new(oidc)?)),
+        None => None,
+    };
+    let token_keys = Arc::new(TokenKeys::new(&config)?);
+    let token_denylist = TokenDenylist::new(config.token_denylist);
+    let shared_cache = Arc::new(SharedCache::connect(config.redis.as_ref()).await?);
+    let mailer = Arc::new(Mailer::new(&config.mailer)?);
+
+    if config.integrity_scan_mins > 0 {
+        let interval = Duration::from_secs(config.integrity_scan_mins * 60);
+        tokio::spawn(integrity_scan_job(db.clone(), interval));
+    }
+
+    if config.notification_digest_hours > 0 {
+        let interval = Duration::from_secs(config.notification_digest_hours * 60 * 60);
+        tokio::spawn(notification_digest_job(db.clone(), interval));
+    }
+
+    if config.memory_sample_mins > 0 {
+        let interval = Duration::from_secs(config.memory_sample_mins * 60);
+        tokio::spawn(memory_sample_job(interval));
+    }
+
+    if config.counter_reconcile_mins > 0 {
+        let interval = Duration::from_secs(config.counter_reconcile_mins * 60);
+        tokio::spawn(counter_reconcile_job(db.clone(), interval));
+    }
+
+    if config.org_access_retention_days > 0 {
+        tokio::spawn(org_access_retention_job(
+            db.clone(),
+            config.org_access_retention_days,
+        ));
+    }
+
     let state = AppState {
         config: Arc::new(config),
         db,
         client,
         auth_cache,
+        suggestion_cache,
+        access_log_cache,
+        revocation_cache,
+        token_denylist,
+        shared_cache,
+        org_rate_limiter: OrgRateLimiter::default(),
+        auth_rate_limiter: AuthRateLimiter::default(),
+        oidc,
+        token_keys,
+        mailer,
+        admin_events: AdminEvents::default(),
     };
 
+    // Apps from before secrets were hashed keep working with their existing secret
+    hash_plain_app_secrets_svc(&state).await?;
+    hash_plain_environment_secrets_svc(&state).await?;
+
+    tokio::spawn(draft_cleanup_job(state.db.clone()));
+    tokio::spawn(idempotency_cleanup_job(state.db.clone()));
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let job_worker_handle = tokio::spawn(job_worker(state.clone(), shutdown_rx));
+
     let routes_all = Router::new()
         .merge(all_routes(state, &frontend_dir))
         .layer(CookieManagerLayer::new())
+        .layer(middleware::from_fn(request_log_middleware))
+        .layer(PropagateRequestIdLayer::x_request_id())
         .layer(
             TraceLayer::new_for_http()
-                .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
+                .make_span_with(RedactedMakeSpan)
                 .on_response(DefaultOnResponse::new().level(Level::INFO)),
-        );
+        )
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid));
 
     // Setup the server
     info!("HTTP Server runnung on {}", server_address);
@@ -80,9 +210,371 @@ pub async fn run(config: Config) -> Result<()> {
 
     info!("HTTP Server stopped");
 
+    // Let the job worker finish the job at hand
+    let _ = shutdown_tx.send(true);
+    let _ = job_worker_handle.await;
+
     Ok(())
 }
 
+/// Reports orphaned records, removing them when `repair` is set.
+///
+/// Returns exit code 2 when orphans were found but left in place.
+pub async fn run_gc(config: Config, repair: bool) -> Result<i32> {
+    let db_file = config.db.dir.join("default").join("yaas.db");
+    let db = create_db_mapper(db_file.as_path(), &config.pagination).await?;
+
+    let report = integrity_scan_svc(&db, repair).await?;
+
+    for item in report.items.iter() {
+        println!("{:<28} {}", item.kind, item.count);
+    }
+
+    let total = report.total();
+    match (repair, total) {
+        (_, 0) => println!("No orphaned records found."),
+        (true, _) => println!("Removed {} orphaned record(s).", total),
+        (false, _) => println!(
+            "Found {} orphaned record(s). Run with --repair to remove them.",
+            total
+        ),
+    }
+
+    Ok(if !repair && total > 0 { 2 } else { 0 })
+}
+
+/// Applies pending schema migrations.
+///
+/// Returns exit code 2 when a lint or a contract migration stopped the run.
+pub async fn run_migrate(config: Config, options: MigrateOptionsDto) -> Result<i32> {
+    // Migrating an empty DATABASE_DIR creates the database
+    let db_dir = config.db.dir.join("default");
+    std::fs::create_dir_all(&db_dir).context(IoSnafu)?;
+    let db_file = db_dir.join("yaas.db");
+    let db = create_db_mapper(db_file.as_path(), &config.pagination).await?;
+
+    let report = migrate_svc(&db, MIGRATIONS, options).await?;
+
+    for name in report.baselined.iter() {
+        println!("{:<10} {}", "baseline", name);
+    }
+    for name in report.applied.iter() {
+        println!("{:<10} {}", "applied", name);
+    }
+    for lint in report.lints.iter() {
+        println!(
+            "{:<10} {} [{}] {}",
+            "unsafe", lint.migration, lint.rule, lint.statement
+        );
+    }
+    for name in report.pending.iter() {
+        println!("{:<10} {}", "pending", name);
+    }
+
+    match &report.blocked {
+        Some(reason) => println!("{}", reason),
+        None if options.check => println!("Check only, nothing was applied."),
+        None if report.applied.is_empty() && report.baselined.is_empty() => {
+            println!("Schema is up to date.")
+        }
+        None => {}
+    }
+
+    Ok(if report.blocked.is_some() { 2 } else { 0 })
+}
+
+/// Runs a registered backfill, or lists them with their progress without a name.
+///
+/// Returns exit code 2 when the run stopped before the backfill completed.
+pub async fn run_backfill(
+    config: Config,
+    name: Option<&str>,
+    options: BackfillOptionsDto,
+) -> Result<i32> {
+    let db_file = config.db.dir.join("default").join("yaas.db");
+    let db = create_db_mapper(db_file.as_path(), &config.pagination).await?;
+
+    let Some(name) = name else {
+        let statuses = list_backfills_svc(&db, BACKFILLS).await?;
+        if statuses.is_empty() {
+            println!("No backfills are registered.");
+        }
+        for status in statuses.iter() {
+            let state = match &status.progress {
+                Some(progress) if progress.completed_at.is_some() => "completed",
+                Some(_) => "partial",
+                None => "pending",
+            };
+            let rows_done = status.progress.as_ref().map(|p| p.rows_done).unwrap_or(0);
+            println!(
+                "{:<10} {} ({}, {} row(s) done)",
+                state, status.name, status.table, rows_done
+            );
+        }
+        return Ok(0);
+    };
+
+    let backfill = find_backfill(BACKFILLS, name)?;
+    let report = run_backfill_svc(&db, backfill, options).await?;
+
+    match (report.completed, report.batches) {
+        (true, 0) => println!("{} is already completed.", report.name),
+        (true, _) => println!(
+            "{} completed, updated {} row(s) in {} batch(es).",
+            report.name, report.rows, report.batches
+        ),
+        (false, _) => println!(
+            "{} stopped after {} batch(es) and {} row(s). Run it again to resume.",
+            report.name, report.batches, report.rows
+        ),
+    }
+
+    Ok(if report.completed { 0 } else { 2 })
+}
+
+pub async fn run_break_glass(config: Config, email: &str, ttl_mins: i64) -> Result<i32> {
+    let db_file = config.db.dir.join("default").join("yaas.db");
+    let db = create_db_mapper(db_file.as_path(), &config.pagination).await?;
+
+    let issued = issue_recovery_token_svc(&db, email, ttl_mins).await?;
+    let scheme = if config.server.https { "https" } else { "http" };
+
+    println!("Recovery token issued for {}.", email.trim());
+    let expires_at = chrono::DateTime::from_timestamp_millis(issued.recovery.expires_at)
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_default();
+    println!(
+        "It can be used once and expires in {} minute(s), at {}.",
+        ttl_mins, expires_at
+    );
+    println!("All superusers have been notified.");
+    println!();
+    println!(
+        "{}://{}/recover?token={}",
+        scheme, config.server.address, issued.token
+    );
+
+    Ok(0)
+}
+
+pub async fn run_admin_token(config: Config, email: &str) -> Result<i32> {
+    let db_file = config.db.dir.join("default").join("yaas.db");
+    let db = create_db_mapper(db_file.as_path(), &config.pagination).await?;
+
+    let keys = TokenKeys::new(&config)?;
+    let token = issue_superuser_token_svc(&db, &keys, email).await?;
+    println!("{}", token);
+
+    Ok(0)
+}
+
+/// Creates a superuser, printing a generated password unless one is read from stdin
+pub async fn run_create_superuser(
+    config: Config,
+    email: &str,
+    password_stdin: bool,
+) -> Result<i32> {
+    let db_file = config.db.dir.join("default").join("yaas.db");
+    let db = create_db_mapper(db_file.as_path(), &config.pagination).await?;
+
+    let password = read_or_generate_password(password_stdin)?;
+    let superuser = create_superuser_svc(&db, email, &password).await?;
+
+    println!("Superuser {} created for {}.", superuser.id, email.trim());
+    if !password_stdin {
+        println!("Password: {}", password);
+    }
+
+    Ok(0)
+}
+
+/// Sets a new password and revokes the user's tokens
+pub async fn run_reset_password(config: Config, email: &str, password_stdin: bool) -> Result<i32> {
+    let db_file = config.db.dir.join("default").join("yaas.db");
+    let db = create_db_mapper(db_file.as_path(), &config.pagination).await?;
+
+    let password = read_or_generate_password(password_stdin)?;
+    let user = reset_user_password_svc(&db, email, &password).await?;
+
+    println!(
+        "Password reset for {}, existing sessions were revoked.",
+        user.email
+    );
+    if !password_stdin {
+        println!("Password: {}", password);
+    }
+    if user.status != "active" {
+        println!(
+            "The user is {}, activate them to let them sign in.",
+            user.status
+        );
+    }
+
+    Ok(0)
+}
+
+pub async fn run_deactivate_user(config: Config, email: &str) -> Result<i32> {
+    let db_file = config.db.dir.join("default").join("yaas.db");
+    let db = create_db_mapper(db_file.as_path(), &config.pagination).await?;
+
+    let user = deactivate_user_svc(&db, email).await?;
+    println!(
+        "Deactivated {}, existing sessions were revoked.",
+        user.email
+    );
+
+    Ok(0)
+}
+
+pub async fn run_list_orgs(config: Config, keyword: Option<String>) -> Result<i32> {
+    let db_file = config.db.dir.join("default").join("yaas.db");
+    let db = create_db_mapper(db_file.as_path(), &config.pagination).await?;
+
+    println!(
+        "{:<30} {:<10} {:>8} {:>6}  name",
+        "id", "status", "members", "apps"
+    );
+
+    let mut page = 1;
+    loop {
+        let params = ListOrgsParamsDto {
+            page: Some(page),
+            per_page: Some(config.pagination.max_per_page),
+            keyword: keyword.clone(),
+            ..Default::default()
+        };
+        let orgs = db.orgs.list(params).await?;
+
+        for org in orgs.data.iter() {
+            println!(
+                "{:<30} {:<10} {:>8} {:>6}  {}",
+                org.id, org.status, org.member_count, org.app_count, org.name
+            );
+        }
+
+        if page as i64 >= orgs.meta.total_pages {
+            break;
+        }
+        page += 1;
+    }
+
+    Ok(0)
+}
+
+/// Reads the first line of stdin so the password stays out of the shell history
+fn read_or_generate_password(password_stdin: bool) -> Result<String> {
+    if !password_stdin {
+        return generate_password();
+    }
+
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line).context(IoSnafu)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+pub async fn run_org_export(config: Config, org_id: &str, out: Option<&str>) -> Result<i32> {
+    let db_file = config.db.dir.join("default").join("yaas.db");
+    let db = create_db_mapper(db_file.as_path(), &config.pagination).await?;
+
+    let archive = export_org_svc(&db, org_id).await?;
+    let contents = serde_json::to_string_pretty(&archive).context(JsonSerializeSnafu)?;
+
+    match out {
+        Some(path) => {
+            std::fs::write(path, contents).context(IoSnafu)?;
+            eprintln!(
+                "Exported org {} with {} member(s) and {} app(s) to {}.",
+                archive.org.name,
+                archive.members.len(),
+                archive.apps.len(),
+                path
+            );
+        }
+        None => println!("{}", contents),
+    }
+
+    Ok(0)
+}
+
+/// Prints the role to permission matrix as JSON or CSV, or writes it to a file
+pub async fn run_permissions_export(
+    config: Config,
+    org_id: Option<&str>,
+    format: &str,
+    out: Option<&str>,
+) -> Result<i32> {
+    let db_file = config.db.dir.join("default").join("yaas.db");
+    let db = create_db_mapper(db_file.as_path(), &config.pagination).await?;
+
+    let matrix = permission_matrix_svc(&db, org_id).await?;
+    let contents = match format {
+        "csv" => permission_matrix_to_csv(&matrix),
+        _ => serde_json::to_string_pretty(&matrix).context(JsonSerializeSnafu)?,
+    };
+
+    match out {
+        Some(path) => {
+            std::fs::write(path, contents).context(IoSnafu)?;
+            eprintln!("Exported {} role(s) to {}.", matrix.len(), path);
+        }
+        None => print!("{}", contents),
+    }
+
+    Ok(0)
+}
+
+pub async fn run_org_import(
+    config: Config,
+    file: &str,
+    strategy: ImportConflictStrategy,
+    dry_run: bool,
+) -> Result<i32> {
+    let db_file = config.db.dir.join("default").join("yaas.db");
+    let db = create_db_mapper(db_file.as_path(), &config.pagination).await?;
+
+    let contents = std::fs::read_to_string(file).context(IoSnafu)?;
+    let archive = parse_org_archive(&contents)?;
+    let report = import_org_svc(&db, &archive, strategy, dry_run).await?;
+    let plan = &report.plan;
+
+    println!("{:<8} {:<8} {}", "org", plan.org.action, plan.org.name);
+    if let Some(settings) = plan.settings.as_ref() {
+        println!("{:<8} {:<8} {}", "settings", settings.action, plan.org.name);
+    }
+    for role in plan.roles.iter() {
+        println!("{:<8} {:<8} {}", "role", role.action, role.name);
+    }
+    for app in plan.apps.iter() {
+        let link = if app.link { " (link)" } else { "" };
+        println!("{:<8} {:<8} {}{}", "app", app.action, app.name, link);
+    }
+    for member in plan.members.iter() {
+        let note = member
+            .note
+            .as_deref()
+            .map(|note| format!(" ({})", note))
+            .unwrap_or_default();
+        println!(
+            "{:<8} {:<8} {}{}",
+            "member", member.action, member.email, note
+        );
+    }
+    println!();
+
+    match report.result {
+        Some(result) => {
+            println!("Imported into org {}.", result.org_id);
+            for mapping in result.apps.iter() {
+                println!("app {} -> {}", mapping.source_id, mapping.target_id);
+            }
+        }
+        None if dry_run => println!("Dry run, nothing was written."),
+        None => println!("Org already exists, nothing was imported."),
+    }
+
+    Ok(0)
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         tokio::signal::ctrl_c()
@@ -108,14 +600,14 @@ async fn shutdown_signal() {
 }
 
 async fn init_superuser(mut config: Config, db: Arc<DbMapper>) -> Result<Config> {
-    let superusers = db.superusers.list().await?;
-    if superusers.is_empty() {
-        let setup_key = generate_id(IdPrefix::SuperuserKey);
-        info!("Superuser setup key: {}", setup_key);
+    // A fresh key every boot, setup may be re-run later if every superuser
+    // account is lost
+    config.superuser = SuperuserConfig {
+        setup_key: Some(generate_id(IdPrefix::SuperuserKey)),
+    };
 
-        config.superuser = SuperuserConfig {
-            setup_key: Some(setup_key),
-        };
+    if setup_required_svc(&db).await? {
+        log_setup_key(&config);
     }
 
     Ok(config)
@@ -0,0 +1,119 @@
+use tokio::sync::broadcast;
+use tracing::debug;
+
+use crate::dto::EntityChangeDto;
+use crate::run::AppState;
+
+/// Changes kept for slow subscribers before they start missing some
+const ADMIN_EVENTS_CAPACITY: usize = 256;
+
+/// Fans out entity changes to the admins watching `/events`.
+///
+/// Only changes made by this instance are seen, other instances have
+/// their own feed.
+#[derive(Clone)]
+pub struct AdminEvents {
+    sender: broadcast::Sender<EntityChangeDto>,
+}
+
+impl Default for AdminEvents {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(ADMIN_EVENTS_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl AdminEvents {
+    pub fn subscribe(&self) -> broadcast::Receiver<EntityChangeDto> {
+        self.sender.subscribe()
+    }
+
+    /// Sends the change to every subscriber, if any
+    pub fn publish(&self, change: EntityChangeDto) {
+        if let Ok(count) = self.sender.send(change) {
+            debug!(subscribers = count, "admin_events.published");
+        }
+    }
+}
+
+pub fn publish_change(state: &AppState, change: EntityChangeDto) {
+    state.admin_events.publish(change);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::{ChangeAction, ChangedEntity, EntityChangeDto, NewOrgMemberDto};
+    use crate::services::org_members::{create_org_member_svc, delete_org_member_svc};
+    use crate::test::TestCtx;
+
+    use super::AdminEvents;
+
+    #[tokio::test]
+    async fn subscribers_receive_published_changes() {
+        let events = AdminEvents::default();
+
+        // Nobody is listening yet
+        events.publish(EntityChangeDto::user(ChangeAction::Created, "usr_1"));
+
+        let mut receiver = events.subscribe();
+        events.publish(EntityChangeDto::org_member(
+            ChangeAction::Updated,
+            "org_1",
+            "usr_2",
+        ));
+
+        let change = receiver.recv().await.expect("change");
+        assert_eq!(change.entity, ChangedEntity::OrgMember);
+        assert_eq!(change.action, ChangeAction::Updated);
+        assert_eq!(change.id, "usr_2");
+        assert_eq!(change.org_id.as_deref(), Some("org_1"));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn member_changes_are_published() {
+        let ctx = TestCtx::new("admin_events_members")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Owner User",
+                "events.owner@example.com",
+                "password123",
+                "Events Org",
+            )
+            .await
+            .expect("auth fixture");
+        let user = ctx
+            .seed_user_with_password("Member User", "events.member@example.com", "password123")
+            .await
+            .expect("member user");
+
+        let mut receiver = ctx.state.admin_events.subscribe();
+
+        let member = create_org_member_svc(
+            &ctx.state,
+            &fixture.org.id,
+            NewOrgMemberDto {
+                user_id: user.id.clone(),
+                roles: vec!["OrgViewer".to_string()],
+                status: "active".to_string(),
+            },
+        )
+        .await
+        .expect("create member");
+        delete_org_member_svc(&ctx.state, &member.id)
+            .await
+            .expect("delete member");
+
+        let created = receiver.recv().await.expect("created");
+        assert_eq!(created.entity, ChangedEntity::OrgMember);
+        assert_eq!(created.action, ChangeAction::Created);
+        assert_eq!(created.id, user.id);
+        assert_eq!(created.org_id.as_deref(), Some(fixture.org.id.as_str()));
+
+        let deleted = receiver.recv().await.expect("deleted");
+        assert_eq!(deleted.action, ChangeAction::Deleted);
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,509 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ensure};
+use tracing::{info, instrument};
+
+use crate::Result;
+use crate::dto::{AppEnvironmentDto, NewAppEnvironmentDto, OauthClientDto};
+use crate::error::{AppNotFoundSnafu, CsrfTokenSnafu, NotFoundSnafu, ValidationSnafu};
+use crate::run::AppState;
+use crate::services::token::verify_csrf_token;
+use crate::utils::{IdPrefix, generate_id, hash_secret};
+use crate::validators::validate_payload;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct AppEnvironmentFormData {
+    pub token: String,
+    pub label: String,
+    pub redirect_uri: String,
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn list_app_environments_svc(
+    state: &AppState,
+    app_id: &str,
+) -> Result<Vec<AppEnvironmentDto>> {
+    state.db.app_environments.list(app_id.to_string()).await
+}
+
+/// Issues a client_id and secret for a new environment of the app.
+///
+/// The returned environment carries the plain secret once.
+#[instrument(level = "debug", skip_all)]
+pub async fn create_app_environment_svc(
+    state: &AppState,
+    app_id: &str,
+    data: NewAppEnvironmentDto,
+) -> Result<AppEnvironmentDto> {
+    validate_payload(&data)?;
+
+    let _ = state
+        .db
+        .apps
+        .get(app_id.to_string())
+        .await?
+        .context(AppNotFoundSnafu)?;
+
+    let existing = state
+        .db
+        .app_environments
+        .find_by_label(app_id.to_string(), data.label.clone())
+        .await?;
+
+    ensure!(
+        existing.is_none(),
+        ValidationSnafu {
+            msg: "Environment label already exists".to_string(),
+        }
+    );
+
+    let client_secret = generate_id(IdPrefix::ClientSecret);
+    let mut environment = state
+        .db
+        .app_environments
+        .create(
+            AppEnvironmentDto {
+                id: generate_id(IdPrefix::AppEnvironment),
+                app_id: app_id.to_string(),
+                label: data.label,
+                client_id: generate_id(IdPrefix::ClientId),
+                client_secret: "".to_string(),
+                redirect_uri: data.redirect_uri,
+                created_at: Utc::now().timestamp_millis(),
+                client_secret_hash: None,
+                previous_secret_hash: None,
+                previous_secret_expires_at: None,
+            },
+            hash_secret(&client_secret),
+        )
+        .await?;
+
+    // The only time the plain secret is seen
+    environment.client_secret = client_secret;
+    Ok(environment)
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn create_app_environment_web_svc(
+    state: &AppState,
+    app_id: &str,
+    form: AppEnvironmentFormData,
+) -> Result<AppEnvironmentDto> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == app_id, CsrfTokenSnafu);
+
+    create_app_environment_svc(
+        state,
+        app_id,
+        NewAppEnvironmentDto {
+            label: form.label,
+            redirect_uri: form.redirect_uri,
+        },
+    )
+    .await
+}
+
+/// Issues a new secret for the environment while the current one keeps
+/// working for the grace period, same as an app's own secret.
+///
+/// The returned environment carries the plain secret once.
+#[instrument(level = "debug", skip_all)]
+pub async fn rotate_app_environment_secret_svc(
+    state: &AppState,
+    app_id: &str,
+    environment_id: &str,
+) -> Result<AppEnvironmentDto> {
+    let client_secret = generate_id(IdPrefix::ClientSecret);
+    let grace_ms = (state.config.app_secret_grace_hours as i64) * 60 * 60 * 1000;
+    let previous_expires_at = Utc::now().timestamp_millis() + grace_ms;
+
+    let rotated = state
+        .db
+        .app_environments
+        .rotate_secret(
+            app_id.to_string(),
+            environment_id.to_string(),
+            hash_secret(&client_secret),
+            previous_expires_at,
+        )
+        .await?;
+
+    ensure!(
+        rotated,
+        NotFoundSnafu {
+            msg: "App environment not found".to_string()
+        }
+    );
+    info!(
+        app_id = app_id,
+        environment_id = environment_id,
+        "app_environment.secret_rotated"
+    );
+
+    let mut environment = state
+        .db
+        .app_environments
+        .get(app_id.to_string(), environment_id.to_string())
+        .await?
+        .context(NotFoundSnafu {
+            msg: "App environment not found".to_string(),
+        })?;
+
+    environment.client_secret = client_secret;
+    Ok(environment)
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn rotate_app_environment_secret_web_svc(
+    state: &AppState,
+    app_id: &str,
+    environment_id: &str,
+    csrf_token: &str,
+) -> Result<AppEnvironmentDto> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == app_id, CsrfTokenSnafu);
+
+    rotate_app_environment_secret_svc(state, app_id, environment_id).await
+}
+
+/// Hashes client secrets of environments created before secrets were stored hashed.
+///
+/// Runs on startup with the app secrets, environments already hashed are left alone.
+#[instrument(level = "debug", skip_all)]
+pub async fn hash_plain_environment_secrets_svc(state: &AppState) -> Result<usize> {
+    let plain = state.db.app_environments.list_plain_secrets().await?;
+    let mut hashed = 0;
+
+    for environment in plain.into_iter() {
+        if state
+            .db
+            .app_environments
+            .set_secret_hash(environment.id, hash_secret(&environment.client_secret))
+            .await?
+        {
+            hashed += 1;
+        }
+    }
+
+    if hashed > 0 {
+        info!(count = hashed, "app_environment.secrets_hashed");
+    }
+
+    Ok(hashed)
+}
+
+/// Removes the environment, its client_id stops working right away
+#[instrument(level = "debug", skip_all)]
+pub async fn delete_app_environment_svc(
+    state: &AppState,
+    app_id: &str,
+    environment_id: &str,
+) -> Result<()> {
+    let deleted = state
+        .db
+        .app_environments
+        .delete(app_id.to_string(), environment_id.to_string())
+        .await?;
+
+    ensure!(
+        deleted,
+        NotFoundSnafu {
+            msg: "App environment not found".to_string()
+        }
+    );
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn delete_app_environment_web_svc(
+    state: &AppState,
+    app_id: &str,
+    environment_id: &str,
+    csrf_token: &str,
+) -> Result<()> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == app_id, CsrfTokenSnafu);
+
+    delete_app_environment_svc(state, app_id, environment_id).await
+}
+
+/// Finds the credentials behind an OAuth client_id.
+///
+/// The app's own client_id is checked first, then the environments.
+#[instrument(level = "debug", skip_all)]
+pub async fn resolve_oauth_client_svc(
+    state: &AppState,
+    client_id: &str,
+) -> Result<Option<OauthClientDto>> {
+    if let Some(app) = state
+        .db
+        .apps
+        .find_by_client_id(client_id.to_string())
+        .await?
+    {
+        let now = chrono::Utc::now().timestamp_millis();
+
+        return Ok(Some(OauthClientDto {
+            secret_hashes: app.accepted_secret_hashes(now),
+            redirect_uris: app.redirect_uris.clone(),
+            environment: None,
+            app,
+        }));
+    }
+
+    let Some(environment) = state
+        .db
+        .app_environments
+        .find_by_client_id(client_id.to_string())
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let app = state.db.apps.get(environment.app_id.clone()).await?;
+    let now = Utc::now().timestamp_millis();
+
+    Ok(app.map(|app| OauthClientDto {
+        app,
+        secret_hashes: environment.accepted_secret_hashes(now),
+        environment: Some(environment.label),
+        redirect_uris: vec![environment.redirect_uri],
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::{NewAppEnvironmentDto, OauthAuthorizeDto, OauthTokenRequestDto, Scope};
+    use crate::services::oauth::{
+        create_authorization_code_svc, exchange_code_for_access_token_svc,
+    };
+    use crate::test::TestCtx;
+
+    use crate::utils::hash_secret;
+
+    use super::{
+        create_app_environment_svc, delete_app_environment_svc, resolve_oauth_client_svc,
+        rotate_app_environment_secret_svc,
+    };
+
+    #[tokio::test]
+    async fn environment_credentials_select_the_environment() {
+        let ctx = TestCtx::new("app_environments_oauth")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "Env User",
+                "env.user@example.com",
+                "password123",
+                "Env Org",
+                "Env App",
+                "https://prod.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+
+        let staging = create_app_environment_svc(
+            &ctx.state,
+            &fixture.app.id,
+            NewAppEnvironmentDto {
+                label: "staging".to_string(),
+                redirect_uri: "https://staging.example.com/callback".to_string(),
+            },
+        )
+        .await
+        .expect("environment should be created");
+        assert_eq!(staging.client_id.len(), 36);
+        assert_ne!(staging.client_secret, fixture.app.client_secret);
+
+        let duplicate = create_app_environment_svc(
+            &ctx.state,
+            &fixture.app.id,
+            NewAppEnvironmentDto {
+                label: "staging".to_string(),
+                redirect_uri: "https://staging.example.com/callback".to_string(),
+            },
+        )
+        .await;
+        assert!(duplicate.is_err(), "labels are unique per app");
+
+        let client = resolve_oauth_client_svc(&ctx.state, &staging.client_id)
+            .await
+            .expect("resolve")
+            .expect("client");
+        assert_eq!(client.app.id, fixture.app.id);
+        assert_eq!(client.environment.as_deref(), Some("staging"));
+
+        let auth_ctx = fixture.auth.to_ctx(vec![Scope::Auth]);
+        let authorize = |redirect_uri: &str| OauthAuthorizeDto {
+            client_id: staging.client_id.clone(),
+            redirect_uri: redirect_uri.to_string(),
+            scope: "auth".to_string(),
+            state: "state-1".to_string(),
+            nonce: None,
+        };
+
+        // The app's redirect URI does not apply to the environment
+        let mismatch = create_authorization_code_svc(
+            &ctx.state,
+            &auth_ctx,
+            &authorize("https://prod.example.com/callback"),
+        )
+        .await;
+        assert!(mismatch.is_err());
+
+        let code = create_authorization_code_svc(
+            &ctx.state,
+            &auth_ctx,
+            &authorize("https://staging.example.com/callback"),
+        )
+        .await
+        .expect("authorization code");
+
+        let mut request = OauthTokenRequestDto {
+            grant_type: Some("authorization_code".to_string()),
+            client_id: staging.client_id.clone(),
+            client_secret: fixture.app.client_secret.clone(),
+            code: code.code,
+            state: code.state,
+            redirect_uri: "https://staging.example.com/callback".to_string(),
+        };
+
+        // The app secret does not unlock the environment client_id
+        let wrong_secret = exchange_code_for_access_token_svc(&ctx.state, &request).await;
+        assert!(wrong_secret.is_err());
+
+        // Nor can the app credentials redeem a staging code
+        request.client_id = fixture.app.client_id.clone();
+        let wrong_client = exchange_code_for_access_token_svc(&ctx.state, &request).await;
+        assert!(wrong_client.is_err());
+
+        request.client_id = staging.client_id.clone();
+        request.client_secret = staging.client_secret.clone();
+        let token = exchange_code_for_access_token_svc(&ctx.state, &request)
+            .await
+            .expect("token exchange");
+        assert!(!token.access_token.is_empty());
+
+        delete_app_environment_svc(&ctx.state, &fixture.app.id, &staging.id)
+            .await
+            .expect("delete");
+        let client = resolve_oauth_client_svc(&ctx.state, &staging.client_id)
+            .await
+            .expect("resolve");
+        assert!(client.is_none());
+    }
+
+    #[tokio::test]
+    async fn rotated_environment_secret_keeps_the_previous_one_during_grace() {
+        let ctx = TestCtx::new("app_environments_rotate")
+            .await
+            .expect("test ctx");
+        let app = ctx
+            .seed_app("Env App", "https://prod.example.com/callback")
+            .await
+            .expect("seed app");
+
+        let staging = create_app_environment_svc(
+            &ctx.state,
+            &app.id,
+            NewAppEnvironmentDto {
+                label: "staging".to_string(),
+                redirect_uri: "https://staging.example.com/callback".to_string(),
+            },
+        )
+        .await
+        .expect("environment should be created");
+        assert_eq!(staging.client_secret.len(), 36);
+
+        // Only the hash is kept, listing never returns the secret
+        let listed = ctx
+            .state
+            .db
+            .app_environments
+            .list(app.id.clone())
+            .await
+            .expect("list");
+        assert!(listed[0].client_secret.is_empty());
+        assert_eq!(
+            listed[0].client_secret_hash,
+            Some(hash_secret(&staging.client_secret))
+        );
+
+        let rotated = rotate_app_environment_secret_svc(&ctx.state, &app.id, &staging.id)
+            .await
+            .expect("rotate should pass");
+        assert_eq!(rotated.client_id, staging.client_id);
+        assert_ne!(rotated.client_secret, staging.client_secret);
+
+        let client = resolve_oauth_client_svc(&ctx.state, &staging.client_id)
+            .await
+            .expect("resolve")
+            .expect("client");
+        assert!(client.accepts_secret_hash(&hash_secret(&rotated.client_secret)));
+        assert!(client.accepts_secret_hash(&hash_secret(&staging.client_secret)));
+
+        // Past the grace period, only the new secret is accepted
+        let expires_at = rotated
+            .previous_secret_expires_at
+            .expect("grace should be set");
+        let accepted = rotated.accepted_secret_hashes(expires_at + 1);
+        assert_eq!(accepted, vec![hash_secret(&rotated.client_secret)]);
+
+        let missing = rotate_app_environment_secret_svc(&ctx.state, &app.id, "env_missing").await;
+        assert!(missing.is_err());
+    }
+
+    #[tokio::test]
+    async fn client_secret_check_follows_the_rotation_grace() {
+        let ctx = TestCtx::new("app_environments_secret_check")
+            .await
+            .expect("test ctx");
+        let app = ctx
+            .seed_app("Env App", "https://prod.example.com/callback")
+            .await
+            .expect("seed app");
+
+        let staging = create_app_environment_svc(
+            &ctx.state,
+            &app.id,
+            NewAppEnvironmentDto {
+                label: "staging".to_string(),
+                redirect_uri: "https://staging.example.com/callback".to_string(),
+            },
+        )
+        .await
+        .expect("environment should be created");
+
+        let client = resolve_oauth_client_svc(&ctx.state, &staging.client_id)
+            .await
+            .expect("resolve")
+            .expect("client");
+        assert!(client.accepts_secret_hash(&hash_secret(&staging.client_secret)));
+        assert!(!client.accepts_secret_hash(&hash_secret("not-the-secret")));
+        assert!(!client.accepts_secret_hash(""));
+
+        let rotated = rotate_app_environment_secret_svc(&ctx.state, &app.id, &staging.id)
+            .await
+            .expect("rotate should pass");
+        let expires_at = rotated
+            .previous_secret_expires_at
+            .expect("grace should be set");
+
+        // Within the grace period both secrets pass
+        let mut client = resolve_oauth_client_svc(&ctx.state, &staging.client_id)
+            .await
+            .expect("resolve")
+            .expect("client");
+        client.secret_hashes = rotated.accepted_secret_hashes(expires_at - 1);
+        assert!(client.accepts_secret_hash(&hash_secret(&rotated.client_secret)));
+        assert!(client.accepts_secret_hash(&hash_secret(&staging.client_secret)));
+
+        // Once the grace expires the rotated out secret is rejected
+        client.secret_hashes = rotated.accepted_secret_hashes(expires_at);
+        assert!(client.accepts_secret_hash(&hash_secret(&rotated.client_secret)));
+        assert!(!client.accepts_secret_hash(&hash_secret(&staging.client_secret)));
+    }
+}
@@ -0,0 +1,456 @@
+use snafu::{OptionExt, ensure};
+use tracing::{info, instrument};
+
+use crate::dto::{ApprovalAction, ApprovalDto, ApprovalStatus, NewApprovalDto};
+use crate::error::{CsrfTokenSnafu, ForbiddenSnafu, NotFoundSnafu, ValidationSnafu};
+use crate::run::AppState;
+use crate::services::orgs::{delete_org_svc, ensure_org_deletable_svc};
+use crate::services::token::verify_csrf_token;
+use crate::services::users::{ensure_can_grant_superuser_svc, grant_superuser_svc};
+use crate::{Error, Result};
+
+/// Number of approvals shown in the audit listing
+const APPROVALS_LIST_LIMIT: i64 = 50;
+
+#[instrument(level = "debug", skip_all)]
+pub async fn list_approvals_svc(state: &AppState) -> Result<Vec<ApprovalDto>> {
+    state.db.approvals.list(APPROVALS_LIST_LIMIT).await
+}
+
+/// Records a critical operation for a second superuser to approve.
+///
+/// An open request for the same action and target is reused.
+#[instrument(level = "debug", skip_all)]
+pub async fn request_approval_svc(
+    state: &AppState,
+    action: ApprovalAction,
+    target_id: &str,
+    actor_id: &str,
+) -> Result<ApprovalDto> {
+    ensure_superuser(state, actor_id).await?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+
+    if let Some(existing) = state
+        .db
+        .approvals
+        .find_pending(action, target_id.to_string(), now)
+        .await?
+    {
+        return Ok(existing);
+    }
+
+    let window_millis = (state.config.approvals.window_mins * 60 * 1000) as i64;
+
+    let approval = state
+        .db
+        .approvals
+        .create(NewApprovalDto {
+            action,
+            target_id: target_id.to_string(),
+            requested_by: actor_id.to_string(),
+            expires_at: now + window_millis,
+        })
+        .await?;
+
+    info!(
+        approval_id = approval.id.as_str(),
+        action = approval.action.to_string(),
+        target_id = approval.target_id.as_str(),
+        requested_by = approval.requested_by.as_str(),
+        "approval.requested"
+    );
+
+    Ok(approval)
+}
+
+/// Error returned to the requester while the operation waits for approval
+pub fn approval_required_error(approval: &ApprovalDto) -> Error {
+    Error::ApprovalRequired {
+        msg: format!(
+            "This operation needs a second superuser. Approval request {} is pending.",
+            approval.id
+        ),
+    }
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn approve_svc(
+    state: &AppState,
+    approval_id: &str,
+    actor_id: &str,
+) -> Result<ApprovalDto> {
+    let approval = get_pending_approval(state, approval_id, actor_id).await?;
+
+    ensure!(
+        approval.requested_by != actor_id,
+        ForbiddenSnafu {
+            msg: "A second superuser must approve this request".to_string()
+        }
+    );
+
+    // Do not approve something that can no longer run
+    ensure_action_allowed(state, &approval).await?;
+
+    let decided = state
+        .db
+        .approvals
+        .decide(
+            approval.id.clone(),
+            ApprovalStatus::Approved,
+            Some(actor_id.to_string()),
+        )
+        .await?;
+
+    ensure!(
+        decided,
+        ValidationSnafu {
+            msg: "Approval request was already decided".to_string()
+        }
+    );
+
+    info!(
+        approval_id = approval.id.as_str(),
+        action = approval.action.to_string(),
+        target_id = approval.target_id.as_str(),
+        requested_by = approval.requested_by.as_str(),
+        decided_by = actor_id,
+        "approval.approved"
+    );
+
+    execute_action(state, &approval).await?;
+
+    info!(
+        approval_id = approval.id.as_str(),
+        action = approval.action.to_string(),
+        target_id = approval.target_id.as_str(),
+        "approval.executed"
+    );
+
+    get_approval(state, approval_id).await
+}
+
+/// Any superuser can reject, including the requester to withdraw it
+#[instrument(level = "debug", skip_all)]
+pub async fn reject_svc(
+    state: &AppState,
+    approval_id: &str,
+    actor_id: &str,
+) -> Result<ApprovalDto> {
+    let approval = get_pending_approval(state, approval_id, actor_id).await?;
+
+    let decided = state
+        .db
+        .approvals
+        .decide(
+            approval.id.clone(),
+            ApprovalStatus::Rejected,
+            Some(actor_id.to_string()),
+        )
+        .await?;
+
+    ensure!(
+        decided,
+        ValidationSnafu {
+            msg: "Approval request was already decided".to_string()
+        }
+    );
+
+    info!(
+        approval_id = approval.id.as_str(),
+        action = approval.action.to_string(),
+        target_id = approval.target_id.as_str(),
+        requested_by = approval.requested_by.as_str(),
+        decided_by = actor_id,
+        "approval.rejected"
+    );
+
+    get_approval(state, approval_id).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn approve_web_svc(
+    state: &AppState,
+    approval_id: &str,
+    actor_id: &str,
+    csrf_token: &str,
+) -> Result<ApprovalDto> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == approval_id, CsrfTokenSnafu);
+
+    approve_svc(state, approval_id, actor_id).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn reject_web_svc(
+    state: &AppState,
+    approval_id: &str,
+    actor_id: &str,
+    csrf_token: &str,
+) -> Result<ApprovalDto> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == approval_id, CsrfTokenSnafu);
+
+    reject_svc(state, approval_id, actor_id).await
+}
+
+async fn ensure_superuser(state: &AppState, actor_id: &str) -> Result<()> {
+    let superuser = state.db.superusers.get(actor_id.to_string()).await?;
+
+    ensure!(
+        superuser.is_some(),
+        ForbiddenSnafu {
+            msg: "Only superusers can handle approvals".to_string()
+        }
+    );
+
+    Ok(())
+}
+
+async fn get_approval(state: &AppState, approval_id: &str) -> Result<ApprovalDto> {
+    state
+        .db
+        .approvals
+        .get(approval_id.to_string())
+        .await?
+        .context(NotFoundSnafu {
+            msg: "Approval request not found".to_string(),
+        })
+}
+
+/// Loads the approval and makes sure it can still be decided.
+/// Requests found past their window are closed as expired.
+async fn get_pending_approval(
+    state: &AppState,
+    approval_id: &str,
+    actor_id: &str,
+) -> Result<ApprovalDto> {
+    ensure_superuser(state, actor_id).await?;
+
+    let approval = get_approval(state, approval_id).await?;
+
+    ensure!(
+        approval.status == ApprovalStatus::Pending,
+        ValidationSnafu {
+            msg: format!("Approval request is already {}", approval.status)
+        }
+    );
+
+    let now = chrono::Utc::now().timestamp_millis();
+
+    if approval.is_expired(now) {
+        let expired = state
+            .db
+            .approvals
+            .decide(approval.id.clone(), ApprovalStatus::Expired, None)
+            .await?;
+
+        if expired {
+            info!(
+                approval_id = approval.id.as_str(),
+                action = approval.action.to_string(),
+                target_id = approval.target_id.as_str(),
+                "approval.expired"
+            );
+        }
+
+        return Err(Error::Validation {
+            msg: "Approval request has expired".to_string(),
+        });
+    }
+
+    Ok(approval)
+}
+
+async fn ensure_action_allowed(state: &AppState, approval: &ApprovalDto) -> Result<()> {
+    match approval.action {
+        ApprovalAction::DeleteOrg => ensure_org_deletable_svc(state, &approval.target_id).await,
+        ApprovalAction::GrantSuperuser => {
+            ensure_can_grant_superuser_svc(state, &approval.target_id).await
+        }
+    }
+}
+
+async fn execute_action(state: &AppState, approval: &ApprovalDto) -> Result<()> {
+    match approval.action {
+        ApprovalAction::DeleteOrg => {
+            delete_org_svc(state, &approval.target_id).await?;
+        }
+        ApprovalAction::GrantSuperuser => {
+            grant_superuser_svc(state, &approval.target_id).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::Error;
+    use crate::dto::{ApprovalAction, ApprovalStatus, ListOrgMembersParamsDto};
+    use crate::services::orgs::{delete_org_web_svc, get_org_svc};
+    use crate::services::token::create_csrf_token_svc;
+    use crate::services::users::grant_superuser_web_svc;
+    use crate::test::TestCtx;
+
+    use super::{approve_web_svc, reject_svc, request_approval_svc};
+
+    fn enable_two_person_rule(ctx: &mut TestCtx) {
+        Arc::make_mut(&mut ctx.state.config)
+            .approvals
+            .two_person_rule = true;
+    }
+
+    #[tokio::test]
+    async fn delete_org_with_members_waits_for_second_superuser() {
+        let mut ctx = TestCtx::new("approvals_delete_org")
+            .await
+            .expect("test ctx");
+        enable_two_person_rule(&mut ctx);
+
+        let requester = ctx
+            .seed_superuser("root@example.com")
+            .await
+            .expect("requester");
+        let approver = ctx
+            .seed_superuser("root2@example.com")
+            .await
+            .expect("approver");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Owner User",
+                "approval.owner@example.com",
+                "password123",
+                "Doomed Org",
+            )
+            .await
+            .expect("auth fixture");
+
+        let csrf = create_csrf_token_svc(&fixture.org.id, &ctx.state.config.jwt_secret)
+            .expect("csrf token should be generated");
+        let result = delete_org_web_svc(&ctx.state, &requester.id, &fixture.org.id, &csrf).await;
+        assert!(matches!(result, Err(Error::ApprovalRequired { .. })));
+
+        // Asking again reuses the pending request
+        let approval = request_approval_svc(
+            &ctx.state,
+            ApprovalAction::DeleteOrg,
+            &fixture.org.id,
+            &requester.id,
+        )
+        .await
+        .expect("pending approval");
+        let pending = ctx.state.db.approvals.list(10).await.expect("approvals");
+        assert_eq!(pending.len(), 1);
+
+        let token = create_csrf_token_svc(&approval.id, &ctx.state.config.jwt_secret)
+            .expect("csrf token should be generated");
+
+        let own = approve_web_svc(&ctx.state, &approval.id, &requester.id, &token).await;
+        let err = own.err().expect("requester cannot approve");
+        assert_eq!(
+            err.to_string(),
+            "A second superuser must approve this request"
+        );
+
+        let decided = approve_web_svc(&ctx.state, &approval.id, &approver.id, &token)
+            .await
+            .expect("approval should pass");
+        assert_eq!(decided.status, ApprovalStatus::Approved);
+        assert_eq!(decided.decided_by, Some(approver.id.clone()));
+
+        let org = get_org_svc(&ctx.state, &fixture.org.id)
+            .await
+            .expect("query should pass");
+        assert!(org.is_none());
+
+        let members = ctx
+            .state
+            .db
+            .org_members
+            .listing_count(fixture.org.id.clone(), ListOrgMembersParamsDto::default())
+            .await
+            .expect("count should pass");
+        assert_eq!(members, 0);
+
+        // Decided requests cannot be replayed
+        let again = approve_web_svc(&ctx.state, &approval.id, &approver.id, &token).await;
+        assert!(again.is_err());
+    }
+
+    #[tokio::test]
+    async fn grant_superuser_can_be_rejected_or_expire() {
+        let mut ctx = TestCtx::new("approvals_grant").await.expect("test ctx");
+        enable_two_person_rule(&mut ctx);
+
+        let requester = ctx
+            .seed_superuser("root@example.com")
+            .await
+            .expect("requester");
+        let approver = ctx
+            .seed_superuser("root2@example.com")
+            .await
+            .expect("approver");
+        let user = ctx
+            .seed_user_with_password("Candidate", "candidate@example.com", "password123")
+            .await
+            .expect("candidate");
+
+        let csrf = create_csrf_token_svc(&user.id, &ctx.state.config.jwt_secret)
+            .expect("csrf token should be generated");
+        let result = grant_superuser_web_svc(&ctx.state, &requester.id, &user.id, &csrf).await;
+        assert!(matches!(result, Err(Error::ApprovalRequired { .. })));
+
+        let approval = request_approval_svc(
+            &ctx.state,
+            ApprovalAction::GrantSuperuser,
+            &user.id,
+            &requester.id,
+        )
+        .await
+        .expect("pending approval");
+
+        let rejected = reject_svc(&ctx.state, &approval.id, &approver.id)
+            .await
+            .expect("rejection should pass");
+        assert_eq!(rejected.status, ApprovalStatus::Rejected);
+
+        let superuser = ctx
+            .state
+            .db
+            .superusers
+            .get(user.id.clone())
+            .await
+            .expect("query should pass");
+        assert!(superuser.is_none());
+
+        // A fresh request that outlived its window cannot be approved
+        Arc::make_mut(&mut ctx.state.config).approvals.window_mins = 0;
+        let stale = request_approval_svc(
+            &ctx.state,
+            ApprovalAction::GrantSuperuser,
+            &user.id,
+            &requester.id,
+        )
+        .await
+        .expect("stale approval");
+
+        let token = create_csrf_token_svc(&stale.id, &ctx.state.config.jwt_secret)
+            .expect("csrf token should be generated");
+        let result = approve_web_svc(&ctx.state, &stale.id, &approver.id, &token).await;
+        let err = result.err().expect("expired approval should fail");
+        assert_eq!(err.to_string(), "Approval request has expired");
+
+        let expired = ctx
+            .state
+            .db
+            .approvals
+            .get(stale.id.clone())
+            .await
+            .expect("query should pass")
+            .expect("approval should exist");
+        assert_eq!(expired.status, ApprovalStatus::Expired);
+    }
+}
@@ -1,11 +1,27 @@
+use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use snafu::ensure;
+use snafu::{OptionExt, ensure};
+use tracing::{info, instrument};
 
+use crate::ctx::AuditCtx;
+use crate::db::{DeletedScope, SoftDelete, Versioned};
 use crate::dto::Paginated;
-use crate::dto::{AppDto, ListAppsParamsDto, NewAppDto, UpdateAppDto};
-use crate::error::CsrfTokenSnafu;
+use crate::dto::{
+    AppDto, AppRedirectUriDto, AppUriCheckDto, AppUriCheckStatus, JobKind, ListAppsParamsDto,
+    NewAppDto, UpdateAppDto, UpdatedDto,
+};
+use crate::error::{
+    AppNotFoundSnafu, CsrfTokenSnafu, NotFoundSnafu, ValidationSnafu, VersionMismatchSnafu,
+    WhateverSnafu,
+};
 use crate::run::AppState;
+use crate::services::jobs::enqueue_job_svc;
 use crate::services::token::verify_csrf_token;
+use crate::utils::{
+    IdPrefix, ListingAllocGuard, check_redirect_uri, generate_id, hash_secret,
+    is_loopback_redirect_uri,
+};
+use crate::validators::validate_payload;
 use crate::{Error, Result};
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -13,6 +29,9 @@ pub struct NewAppFormData {
     pub name: String,
     pub redirect_uri: String,
     pub token: String,
+
+    /// Opt-in strict redirect URI verification
+    pub verify: Option<String>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -20,41 +39,176 @@ pub struct UpdateAppFormData {
     pub token: String,
     pub name: String,
     pub redirect_uri: String,
+
+    /// Additional redirect URIs, one per line
+    #[serde(default)]
+    pub extra_redirect_uris: String,
+
+    /// Scopes the app may request, separated by spaces
+    #[serde(default)]
+    pub scopes: String,
+
+    pub verify: Option<String>,
 }
 
+/// Most redirect URIs an app can register, the main one included
+pub const MAX_REDIRECT_URIS: usize = 10;
+
+#[instrument(level = "debug", skip_all)]
 pub async fn list_apps_svc(
     state: &AppState,
     params: ListAppsParamsDto,
 ) -> Result<Paginated<AppDto>> {
+    let _guard = ListingAllocGuard::new("apps");
     state.db.apps.list(params).await
 }
 
+/// Admin listing that can also see soft deleted apps
+#[instrument(level = "debug", skip_all)]
+pub async fn list_apps_scoped_svc(
+    state: &AppState,
+    params: ListAppsParamsDto,
+    scope: DeletedScope,
+) -> Result<Paginated<AppDto>> {
+    let _guard = ListingAllocGuard::new("apps");
+    state.db.apps.list_scoped(params, scope).await
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn create_app_svc(state: &AppState, data: NewAppDto) -> Result<AppDto> {
-    state.db.apps.create(data).await
+    validate_payload(&data)?;
+
+    let client_secret = generate_id(IdPrefix::ClientSecret);
+    let mut app = state
+        .db
+        .apps
+        .create(&AuditCtx::current(), data, hash_secret(&client_secret))
+        .await?;
+
+    // The only time the plain secret is seen
+    app.client_secret = client_secret;
+    Ok(app)
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn create_app_web_svc(state: &AppState, form: NewAppFormData) -> Result<AppDto> {
     let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
     ensure!(csrf_result == "new_app", CsrfTokenSnafu);
 
-    create_app_svc(
+    if form.verify.is_some() {
+        ensure_strict_redirect_uri(&form.redirect_uri)?;
+    }
+
+    let app = create_app_svc(
         state,
         NewAppDto {
             name: form.name,
             redirect_uri: form.redirect_uri,
+            scopes: None,
         },
     )
-    .await
+    .await?;
+
+    if form.verify.is_some() {
+        verify_app_redirect_uri_svc(state, &app).await?;
+    }
+
+    Ok(app)
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn get_app_svc(state: &AppState, id: &str) -> Result<Option<AppDto>> {
     state.db.apps.get(id.to_string()).await
 }
 
+#[instrument(level = "debug", skip_all)]
+pub async fn get_app_scoped_svc(
+    state: &AppState,
+    id: &str,
+    scope: DeletedScope,
+) -> Result<Option<AppDto>> {
+    state.db.apps.get_scoped(id.to_string(), scope).await
+}
+
+/// Brings back a soft deleted app with its existing client credentials
+#[instrument(level = "debug", skip_all)]
+pub async fn restore_app_svc(state: &AppState, id: &str) -> Result<AppDto> {
+    let Some(_) = get_app_scoped_svc(state, id, DeletedScope::OnlyDeleted).await? else {
+        return Err(Error::AppNotFound);
+    };
+
+    state
+        .db
+        .apps
+        .restore(&AuditCtx::current(), id.to_string())
+        .await?;
+    info!(app_id = id, "app.restored");
+
+    let Some(restored) = get_app_svc(state, id).await? else {
+        return Err(Error::AppNotFound);
+    };
+
+    Ok(restored)
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn update_app_svc(state: &AppState, id: &str, data: UpdateAppDto) -> Result<bool> {
-    state.db.apps.update(id.to_string(), data).await
+    validate_payload(&data)?;
+
+    // A new main redirect URI must not also be listed as an additional one
+    if let Some(redirect_uri) = data.redirect_uri.as_deref()
+        && let Some(app) = get_app_svc(state, id).await?
+    {
+        let extras = app.extra_redirect_uris();
+        if extras.iter().any(|uri| uri == redirect_uri) {
+            let remaining: Vec<String> = extras
+                .into_iter()
+                .filter(|uri| uri != redirect_uri)
+                .collect();
+            state
+                .db
+                .apps
+                .set_extra_redirect_uris(&AuditCtx::current(), id.to_string(), &remaining)
+                .await?;
+        }
+    }
+
+    state
+        .db
+        .apps
+        .update(&AuditCtx::current(), id.to_string(), data)
+        .await
+}
+
+/// Updates the app and reports which fields actually changed.
+///
+/// With a version, the update only goes through while the app is still at it.
+#[instrument(level = "debug", skip_all)]
+pub async fn update_app_tracked_svc(
+    state: &AppState,
+    id: &str,
+    data: UpdateAppDto,
+    version: Option<i64>,
+) -> Result<UpdatedDto<AppDto>> {
+    let before = get_app_svc(state, id).await?.context(AppNotFoundSnafu)?;
+
+    if let Some(version) = version {
+        validate_payload(&data)?;
+        let claimed = state
+            .db
+            .apps
+            .claim_version(&AuditCtx::current(), id.to_string(), version)
+            .await?;
+        ensure!(claimed, VersionMismatchSnafu);
+    }
+
+    update_app_svc(state, id, data).await?;
+
+    let after = get_app_svc(state, id).await?.context(AppNotFoundSnafu)?;
+    Ok(UpdatedDto::new(&before, after))
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn update_app_web_svc(
     state: &AppState,
     app_id: &str,
@@ -63,45 +217,368 @@ pub async fn update_app_web_svc(
     let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
     ensure!(csrf_result == app_id, CsrfTokenSnafu);
 
+    if form.verify.is_some() {
+        ensure_strict_redirect_uri(&form.redirect_uri)?;
+    }
+
+    let extras = check_extra_redirect_uris(
+        &form.redirect_uri,
+        form.extra_redirect_uris
+            .lines()
+            .map(|line| line.to_string()),
+    )?;
+
+    // A blank field keeps the scopes as they are
+    let scopes: Vec<String> = form.scopes.split_whitespace().map(String::from).collect();
+    let scopes = (!scopes.is_empty()).then_some(scopes);
+
     update_app_svc(
         state,
         app_id,
         UpdateAppDto {
             name: Some(form.name),
             redirect_uri: Some(form.redirect_uri),
+            scopes,
         },
     )
     .await?;
 
+    state
+        .db
+        .apps
+        .set_extra_redirect_uris(&AuditCtx::current(), app_id.to_string(), &extras)
+        .await?;
+
     // Fetch the updated app to return
     let Some(updated_app) = get_app_svc(state, app_id).await? else {
         return Err(Error::AppNotFound);
     };
 
+    if form.verify.is_some() {
+        verify_app_redirect_uri_svc(state, &updated_app).await?;
+    }
+
     Ok(updated_app)
 }
 
-pub async fn regenerate_app_secret_svc(state: &AppState, id: &str) -> Result<bool> {
-    state.db.apps.regenerate_secret(id.to_string()).await
+fn ensure_strict_redirect_uri(redirect_uri: &str) -> Result<()> {
+    check_redirect_uri(redirect_uri).map_err(|msg| Error::Validation { msg })
+}
+
+/// Cleans up additional redirect URIs, each one must pass the strict check.
+///
+/// Blank lines, duplicates and the main redirect URI are dropped.
+pub fn check_extra_redirect_uris(
+    redirect_uri: &str,
+    uris: impl Iterator<Item = String>,
+) -> Result<Vec<String>> {
+    let mut extras: Vec<String> = Vec::new();
+
+    for uri in uris {
+        let uri = uri.trim().to_string();
+        if uri.is_empty() || uri == redirect_uri || extras.contains(&uri) {
+            continue;
+        }
+
+        validate_payload(&AppRedirectUriDto {
+            redirect_uri: uri.clone(),
+        })?;
+        ensure_strict_redirect_uri(&uri)?;
+        extras.push(uri);
+    }
+
+    ensure!(
+        extras.len() < MAX_REDIRECT_URIS,
+        ValidationSnafu {
+            msg: format!(
+                "An app can have at most {} redirect URIs",
+                MAX_REDIRECT_URIS
+            )
+        }
+    );
+
+    Ok(extras)
+}
+
+/// Registers one more redirect URI for the app.
+#[instrument(level = "debug", skip_all)]
+pub async fn add_app_redirect_uri_svc(
+    state: &AppState,
+    app_id: &str,
+    data: AppRedirectUriDto,
+) -> Result<AppDto> {
+    validate_payload(&data)?;
+
+    let app = get_app_svc(state, app_id)
+        .await?
+        .context(AppNotFoundSnafu)?;
+    ensure!(
+        !app.redirect_uris.contains(&data.redirect_uri),
+        ValidationSnafu {
+            msg: "Redirect URI is already registered".to_string()
+        }
+    );
+
+    let mut uris = app.extra_redirect_uris();
+    uris.push(data.redirect_uri);
+    let extras = check_extra_redirect_uris(&app.redirect_uri, uris.into_iter())?;
+
+    state
+        .db
+        .apps
+        .set_extra_redirect_uris(&AuditCtx::current(), app_id.to_string(), &extras)
+        .await?;
+
+    get_app_svc(state, app_id).await?.context(AppNotFoundSnafu)
+}
+
+/// Removes an additional redirect URI, the main one can only be changed.
+#[instrument(level = "debug", skip_all)]
+pub async fn remove_app_redirect_uri_svc(
+    state: &AppState,
+    app_id: &str,
+    redirect_uri: &str,
+) -> Result<AppDto> {
+    let app = get_app_svc(state, app_id)
+        .await?
+        .context(AppNotFoundSnafu)?;
+    ensure!(
+        app.redirect_uri != redirect_uri,
+        ValidationSnafu {
+            msg: "The main redirect URI cannot be removed, change it instead".to_string()
+        }
+    );
+
+    let extras = app.extra_redirect_uris();
+    ensure!(
+        extras.iter().any(|uri| uri == redirect_uri),
+        NotFoundSnafu {
+            msg: "Redirect URI not found".to_string()
+        }
+    );
+
+    let remaining: Vec<String> = extras
+        .into_iter()
+        .filter(|uri| uri != redirect_uri)
+        .collect();
+    state
+        .db
+        .apps
+        .set_extra_redirect_uris(&AuditCtx::current(), app_id.to_string(), &remaining)
+        .await?;
+
+    get_app_svc(state, app_id).await?.context(AppNotFoundSnafu)
+}
+
+/// Verifies the app's redirect URI and records the result.
+///
+/// A strict syntax failure is returned as a validation error. When probing
+/// is enabled, non-loopback URIs are also probed by a background job and the
+/// recorded status is updated once the probe completes.
+#[instrument(level = "debug", skip_all)]
+pub async fn verify_app_redirect_uri_svc(state: &AppState, app: &AppDto) -> Result<AppUriCheckDto> {
+    ensure_strict_redirect_uri(&app.redirect_uri)?;
+
+    let check = state
+        .db
+        .app_uri_checks
+        .upsert(AppUriCheckDto {
+            app_id: app.id.clone(),
+            redirect_uri: app.redirect_uri.clone(),
+            status: AppUriCheckStatus::Valid,
+            message: None,
+            checked_at: chrono::Utc::now().timestamp_millis(),
+        })
+        .await?;
+
+    if state.config.redirect_uri_probe && !is_loopback_redirect_uri(&app.redirect_uri) {
+        enqueue_job_svc(&state.db, JobKind::RedirectUriProbe, &check).await?;
+    }
+
+    Ok(check)
+}
+
+/// Latest check of the app's current redirect URI, if any
+#[instrument(level = "debug", skip_all)]
+pub async fn get_app_uri_check_svc(
+    state: &AppState,
+    app: &AppDto,
+) -> Result<Option<AppUriCheckDto>> {
+    let check = state.db.app_uri_checks.find(app.id.clone()).await?;
+    Ok(check.filter(|check| check.is_current(&app.redirect_uri)))
 }
 
+/// Any HTTP answer counts as reachable except server errors.
+///
+/// Redirect URIs usually reject requests without an authorization code,
+/// what matters is that DNS, TCP and TLS all succeed.
+#[instrument(level = "debug", skip_all)]
+pub async fn probe_redirect_uri(
+    client: &Client,
+    redirect_uri: &str,
+) -> (AppUriCheckStatus, Option<String>) {
+    match client.head(redirect_uri).send().await {
+        Ok(res) if res.status().is_server_error() => (
+            AppUriCheckStatus::Unreachable,
+            Some(format!("HTTP {}", res.status().as_u16())),
+        ),
+        Ok(res) => (
+            AppUriCheckStatus::Reachable,
+            Some(format!("HTTP {}", res.status().as_u16())),
+        ),
+        Err(err) => (AppUriCheckStatus::Unreachable, Some(err.to_string())),
+    }
+}
+
+/// Probes the URI of a verified check and stores the result.
+///
+/// Runs as a background job, an unreachable URI fails the job so it is
+/// probed again later. Checks replaced by a newer URI are left alone.
+#[instrument(level = "debug", skip_all)]
+pub async fn run_redirect_uri_probe(state: &AppState, check: AppUriCheckDto) -> Result<()> {
+    let (status, message) = probe_redirect_uri(&state.client, &check.redirect_uri).await;
+    let app_id = check.app_id.clone();
+
+    let recorded = state
+        .db
+        .app_uri_checks
+        .record_probe(AppUriCheckDto {
+            status,
+            message: message.clone(),
+            checked_at: chrono::Utc::now().timestamp_millis(),
+            ..check
+        })
+        .await?;
+
+    if !recorded {
+        return Ok(());
+    }
+
+    info!(
+        app_id = app_id.as_str(),
+        status = status.to_string(),
+        "app.redirect_uri_probed"
+    );
+
+    ensure!(
+        status != AppUriCheckStatus::Unreachable,
+        WhateverSnafu {
+            msg: format!("Redirect URI unreachable: {}", message.unwrap_or_default())
+        }
+    );
+
+    Ok(())
+}
+
+/// Issues a new client ID and secret, the old pair stops working right away.
+///
+/// The returned app carries the plain secret, it cannot be read back later.
+#[instrument(level = "debug", skip_all)]
+pub async fn regenerate_app_secret_svc(state: &AppState, id: &str) -> Result<AppDto> {
+    let client_secret = generate_id(IdPrefix::ClientSecret);
+    let regenerated = state
+        .db
+        .apps
+        .regenerate_secret(
+            &AuditCtx::current(),
+            id.to_string(),
+            hash_secret(&client_secret),
+        )
+        .await?;
+    ensure!(regenerated.is_some(), AppNotFoundSnafu);
+
+    let mut app = get_app_svc(state, id).await?.context(AppNotFoundSnafu)?;
+    app.client_secret = client_secret;
+    Ok(app)
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn regenerate_app_secret_web_svc(
     state: &AppState,
     app_id: &str,
     csrf_token: &str,
-) -> Result<()> {
+) -> Result<AppDto> {
     let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
     ensure!(csrf_result == app_id, CsrfTokenSnafu);
 
-    regenerate_app_secret_svc(state, app_id).await?;
+    regenerate_app_secret_svc(state, app_id).await
+}
+
+/// Issues a new client secret while the current one keeps working for the grace period.
+///
+/// The client ID stays the same. The returned app carries the plain secret once.
+#[instrument(level = "debug", skip_all)]
+pub async fn rotate_app_secret_svc(state: &AppState, id: &str) -> Result<AppDto> {
+    let client_secret = generate_id(IdPrefix::ClientSecret);
+    let grace_ms = (state.config.app_secret_grace_hours as i64) * 60 * 60 * 1000;
+    let previous_expires_at = chrono::Utc::now().timestamp_millis() + grace_ms;
 
-    Ok(())
+    let rotated = state
+        .db
+        .apps
+        .rotate_secret(
+            &AuditCtx::current(),
+            id.to_string(),
+            hash_secret(&client_secret),
+            previous_expires_at,
+        )
+        .await?;
+    ensure!(rotated, AppNotFoundSnafu);
+    info!(app_id = id, "app.secret_rotated");
+
+    let mut app = get_app_svc(state, id).await?.context(AppNotFoundSnafu)?;
+    app.client_secret = client_secret;
+    Ok(app)
 }
 
+#[instrument(level = "debug", skip_all)]
+pub async fn rotate_app_secret_web_svc(
+    state: &AppState,
+    app_id: &str,
+    csrf_token: &str,
+) -> Result<AppDto> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == app_id, CsrfTokenSnafu);
+
+    rotate_app_secret_svc(state, app_id).await
+}
+
+/// Hashes client secrets of apps created before secrets were stored hashed.
+///
+/// Runs on startup, apps already hashed are left alone.
+#[instrument(level = "debug", skip_all)]
+pub async fn hash_plain_app_secrets_svc(state: &AppState) -> Result<usize> {
+    let plain = state.db.apps.list_plain_secrets().await?;
+    let mut hashed = 0;
+
+    for app in plain.into_iter() {
+        if state
+            .db
+            .apps
+            .set_secret_hash(app.id, hash_secret(&app.client_secret))
+            .await?
+        {
+            hashed += 1;
+        }
+    }
+
+    if hashed > 0 {
+        info!(count = hashed, "app.secrets_hashed");
+    }
+
+    Ok(hashed)
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn delete_app_svc(state: &AppState, id: &str) -> Result<bool> {
-    state.db.apps.delete(id.to_string()).await
+    state
+        .db
+        .apps
+        .delete(&AuditCtx::current(), id.to_string())
+        .await
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn delete_app_web_svc(state: &AppState, app_id: &str, csrf_token: &str) -> Result<()> {
     let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
     ensure!(csrf_result == app_id, CsrfTokenSnafu);
@@ -113,11 +590,18 @@ pub async fn delete_app_web_svc(state: &AppState, app_id: &str, csrf_token: &str
 
 #[cfg(test)]
 mod tests {
-    use crate::dto::{ListAppsParamsDto, NewAppDto, UpdateAppDto};
+    use crate::ctx::AuditCtx;
+    use crate::dto::{
+        AppRedirectUriDto, AppUriCheckStatus, ListAppsParamsDto, NewAppDto, UpdateAppDto,
+    };
+    use crate::services::token::create_csrf_token_svc;
     use crate::test::TestCtx;
+    use crate::utils::hash_secret;
 
     use super::{
-        create_app_svc, delete_app_svc, get_app_svc, list_apps_svc, regenerate_app_secret_svc,
+        NewAppFormData, add_app_redirect_uri_svc, create_app_svc, create_app_web_svc,
+        delete_app_svc, get_app_svc, get_app_uri_check_svc, list_apps_svc, probe_redirect_uri,
+        regenerate_app_secret_svc, remove_app_redirect_uri_svc, rotate_app_secret_svc,
         update_app_svc,
     };
 
@@ -130,6 +614,7 @@ mod tests {
             NewAppDto {
                 name: "Photos".to_string(),
                 redirect_uri: "https://photos.example.com/oauth/callback".to_string(),
+                scopes: None,
             },
         )
         .await
@@ -145,6 +630,57 @@ mod tests {
         assert!(!app.client_secret.is_empty());
     }
 
+    #[tokio::test]
+    async fn app_writes_are_stamped_with_the_acting_principal() {
+        let ctx = TestCtx::new("apps_audit_columns").await.expect("test ctx");
+        let creator = AuditCtx {
+            actor_id: Some("usr_creator".to_string()),
+        };
+        let editor = AuditCtx {
+            actor_id: Some("usr_editor".to_string()),
+        };
+
+        let app = creator
+            .scope(create_app_svc(
+                &ctx.state,
+                NewAppDto {
+                    name: "Audited".to_string(),
+                    redirect_uri: "https://audited.example.com/oauth/callback".to_string(),
+                    scopes: None,
+                },
+            ))
+            .await
+            .expect("app should be created");
+        assert_eq!(app.created_by.as_deref(), Some("usr_creator"));
+
+        editor
+            .scope(update_app_svc(
+                &ctx.state,
+                &app.id,
+                UpdateAppDto {
+                    name: Some("Audited v2".to_string()),
+                    redirect_uri: None,
+                    scopes: None,
+                },
+            ))
+            .await
+            .expect("app should be updated");
+
+        let stored = get_app_svc(&ctx.state, &app.id)
+            .await
+            .expect("get app")
+            .expect("app exists");
+        assert_eq!(stored.created_by.as_deref(), Some("usr_creator"));
+        assert_eq!(stored.updated_by.as_deref(), Some("usr_editor"));
+
+        // Outside of a request nobody is stamped
+        let anonymous = ctx
+            .seed_app("Seeded", "https://seeded.example.com/oauth/callback")
+            .await
+            .expect("seed app");
+        assert!(anonymous.created_by.is_none());
+    }
+
     #[tokio::test]
     async fn get_app_svc_returns_existing_app() {
         let ctx = TestCtx::new("apps_get_existing").await.expect("test ctx");
@@ -189,6 +725,8 @@ mod tests {
                 page: Some(1),
                 per_page: Some(10),
                 keyword: None,
+                sort_by: Some("name".to_string()),
+                sort_dir: Some("desc".to_string()),
             },
         )
         .await
@@ -196,6 +734,8 @@ mod tests {
 
         assert_eq!(apps.meta.total_records, 2);
         assert_eq!(apps.data.len(), 2);
+        assert_eq!(apps.data[0].name, "App Two");
+        assert_eq!(apps.data[1].name, "App One");
     }
 
     #[tokio::test]
@@ -212,6 +752,7 @@ mod tests {
             UpdateAppDto {
                 name: Some("Calendar Pro".to_string()),
                 redirect_uri: Some("https://calendar.example.com/oauth/new-callback".to_string()),
+                scopes: None,
             },
         )
         .await
@@ -241,6 +782,7 @@ mod tests {
             UpdateAppDto {
                 name: Some("Nope".to_string()),
                 redirect_uri: Some("https://none.example.com/callback".to_string()),
+                scopes: None,
             },
         )
         .await
@@ -265,15 +807,107 @@ mod tests {
         let regenerated = regenerate_app_secret_svc(&ctx.state, &app.id)
             .await
             .expect("regenerate should pass");
-        assert!(regenerated);
+
+        assert_ne!(regenerated.client_id, old_client_id);
+        assert_ne!(regenerated.client_secret, old_client_secret);
 
         let reloaded = get_app_svc(&ctx.state, &app.id)
             .await
             .expect("get should pass")
             .expect("app should exist");
 
-        assert_ne!(reloaded.client_id, old_client_id);
-        assert_ne!(reloaded.client_secret, old_client_secret);
+        assert_eq!(reloaded.client_id, regenerated.client_id);
+        assert!(reloaded.client_secret.is_empty());
+        assert_eq!(
+            reloaded.accepted_secret_hashes(chrono::Utc::now().timestamp_millis()),
+            vec![hash_secret(&regenerated.client_secret)]
+        );
+    }
+
+    #[tokio::test]
+    async fn rotate_app_secret_svc_keeps_previous_secret_during_grace() {
+        let ctx = TestCtx::new("apps_rotate_secret").await.expect("test ctx");
+        let app = ctx
+            .seed_app("Drive", "https://drive.example.com/oauth/callback")
+            .await
+            .expect("seed app");
+
+        let rotated = rotate_app_secret_svc(&ctx.state, &app.id)
+            .await
+            .expect("rotate should pass");
+
+        assert_eq!(rotated.client_id, app.client_id);
+        assert_ne!(rotated.client_secret, app.client_secret);
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let expires_at = rotated
+            .previous_secret_expires_at
+            .expect("grace should be set");
+        assert!(expires_at > now);
+
+        let accepted = rotated.accepted_secret_hashes(now);
+        assert!(accepted.contains(&hash_secret(&rotated.client_secret)));
+        assert!(accepted.contains(&hash_secret(&app.client_secret)));
+
+        // Past the grace period, only the new secret is accepted
+        let accepted = rotated.accepted_secret_hashes(expires_at + 1);
+        assert_eq!(accepted, vec![hash_secret(&rotated.client_secret)]);
+    }
+
+    #[tokio::test]
+    async fn app_redirect_uris_can_be_added_and_removed() {
+        let ctx = TestCtx::new("apps_redirect_uris").await.expect("test ctx");
+        let app = ctx
+            .seed_app("Drive", "https://drive.example.com/oauth/callback")
+            .await
+            .expect("seed app");
+        assert_eq!(app.redirect_uris, vec![app.redirect_uri.clone()]);
+
+        let extra = "https://staging.drive.example.com/oauth/callback".to_string();
+        let updated = add_app_redirect_uri_svc(
+            &ctx.state,
+            &app.id,
+            AppRedirectUriDto {
+                redirect_uri: extra.clone(),
+            },
+        )
+        .await
+        .expect("add should pass");
+        assert_eq!(
+            updated.redirect_uris,
+            vec![app.redirect_uri.clone(), extra.clone()]
+        );
+
+        let duplicate = add_app_redirect_uri_svc(
+            &ctx.state,
+            &app.id,
+            AppRedirectUriDto {
+                redirect_uri: extra.clone(),
+            },
+        )
+        .await;
+        assert!(duplicate.is_err(), "duplicates should be rejected");
+
+        let insecure = add_app_redirect_uri_svc(
+            &ctx.state,
+            &app.id,
+            AppRedirectUriDto {
+                redirect_uri: "http://drive.example.com/oauth/callback".to_string(),
+            },
+        )
+        .await;
+        assert!(
+            insecure.is_err(),
+            "http outside localhost should be rejected"
+        );
+
+        let main = remove_app_redirect_uri_svc(&ctx.state, &app.id, &app.redirect_uri).await;
+        assert!(main.is_err(), "the main redirect URI cannot be removed");
+
+        let updated = remove_app_redirect_uri_svc(&ctx.state, &app.id, &extra)
+            .await
+            .expect("remove should pass");
+        assert_eq!(updated.redirect_uris, vec![app.redirect_uri]);
     }
 
     #[tokio::test]
@@ -294,4 +928,102 @@ mod tests {
             .expect("get should pass");
         assert!(reloaded.is_none());
     }
+
+    #[tokio::test]
+    async fn create_app_web_svc_verifies_redirect_uri_on_request() {
+        let ctx = TestCtx::new("apps_verify_uri").await.expect("test ctx");
+        let token =
+            create_csrf_token_svc("new_app", &ctx.state.config.jwt_secret).expect("csrf token");
+
+        let rejected = create_app_web_svc(
+            &ctx.state,
+            NewAppFormData {
+                name: "Plain".to_string(),
+                redirect_uri: "http://plain.example.com/callback".to_string(),
+                token: token.clone(),
+                verify: Some("1".to_string()),
+            },
+        )
+        .await;
+        assert!(rejected.is_err(), "http outside localhost must be rejected");
+
+        let listed = list_apps_svc(&ctx.state, ListAppsParamsDto::default())
+            .await
+            .expect("list should pass");
+        assert_eq!(
+            listed.meta.total_records, 0,
+            "rejected app must not be saved"
+        );
+
+        let app = create_app_web_svc(
+            &ctx.state,
+            NewAppFormData {
+                name: "Secure".to_string(),
+                redirect_uri: "https://secure.example.com/callback".to_string(),
+                token,
+                verify: Some("1".to_string()),
+            },
+        )
+        .await
+        .expect("verified app should be created");
+
+        let check = get_app_uri_check_svc(&ctx.state, &app)
+            .await
+            .expect("get check should pass")
+            .expect("check should be recorded");
+        assert_eq!(check.status, AppUriCheckStatus::Valid);
+
+        update_app_svc(
+            &ctx.state,
+            &app.id,
+            UpdateAppDto {
+                name: None,
+                redirect_uri: Some("https://secure.example.com/other".to_string()),
+                scopes: None,
+            },
+        )
+        .await
+        .expect("update should pass");
+
+        let updated = get_app_svc(&ctx.state, &app.id)
+            .await
+            .expect("get should pass")
+            .expect("app should exist");
+        let stale = get_app_uri_check_svc(&ctx.state, &updated)
+            .await
+            .expect("get check should pass");
+        assert!(stale.is_none(), "edited URI must show as unverified");
+    }
+
+    #[tokio::test]
+    async fn probe_redirect_uri_reports_reachability() {
+        let ctx = TestCtx::new("apps_probe_uri").await.expect("test ctx");
+
+        let app = axum::Router::new().route("/callback", axum::routing::get(|| async { "ok" }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener should bind");
+        let addr = listener.local_addr().expect("local addr");
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        let (status, message) =
+            probe_redirect_uri(&ctx.state.client, &format!("http://{}/callback", addr)).await;
+        assert_eq!(status, AppUriCheckStatus::Reachable);
+        assert_eq!(message.as_deref(), Some("HTTP 200"));
+
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener should bind");
+        let closed_addr = closed.local_addr().expect("local addr");
+        drop(closed);
+
+        let (status, _) = probe_redirect_uri(
+            &ctx.state.client,
+            &format!("http://{}/callback", closed_addr),
+        )
+        .await;
+        assert_eq!(status, AppUriCheckStatus::Unreachable);
+    }
 }
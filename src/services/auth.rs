@@ -1,28 +1,42 @@
+use std::time::Duration;
+
+use moka::sync::Cache;
 use serde::{Deserialize, Serialize};
 use snafu::{OptionExt, ensure};
+use tracing::{instrument, warn};
 
+use crate::config::AuthCacheConfig;
+use crate::db::DbMapper;
 use crate::dto::{
-    Actor, ActorPayloadDto, AuthResponseDto, CredentialsDto, ListingParamsDto, Scope,
-    SwitchAuthContextDto,
+    Actor, ActorPayloadDto, AuthLookupDto, AuthResponseDto, CredentialsDto, ListingParamsDto, Role,
+    Scope, SessionClientDto, SwitchAuthContextDto, TokenProofDto,
 };
 use crate::error::{
     ForbiddenSnafu, InactiveUserSnafu, InvalidClientSnafu, InvalidPasswordSnafu, UserNoOrgSnafu,
-    UserNotFoundSnafu, WhateverSnafu,
+    UserNotFoundSnafu, ValidationSnafu, WhateverSnafu,
 };
+use crate::services::impersonations::verify_impersonation_svc;
+use crate::services::oauth_grants::verify_oauth_grant_svc;
+use crate::services::org_access::record_org_access;
+use crate::services::org_apps::verify_org_app_link_svc;
+use crate::services::org_roles::custom_permissions_svc;
 use crate::services::password::verify_password;
-use crate::services::token::{create_auth_token, verify_auth_token};
+use crate::services::proof_keys::verify_token_proof_svc;
+use crate::services::revocations::{verify_not_denied_svc, verify_not_revoked_svc};
+use crate::services::sessions::{start_session_svc, verify_session_svc};
+use crate::services::teams::team_permissions_svc;
+use crate::services::token::{TokenKeys, create_auth_token, verify_auth_token};
+use crate::services::user_emails::find_user_by_login_email_svc;
 use crate::{Result, run::AppState};
 
+#[instrument(level = "debug", skip_all)]
 pub async fn authenticate(
     state: &AppState,
     credentials: &CredentialsDto,
+    client: SessionClientDto,
 ) -> Result<AuthResponseDto> {
-    // Validate user
-    let user = state
-        .db
-        .users
-        .find_by_email(credentials.email.clone())
-        .await?;
+    // Validate user, any verified email of the user can be used
+    let user = find_user_by_login_email_svc(state, &credentials.email).await?;
 
     let user = user.context(InvalidPasswordSnafu)?;
 
@@ -58,6 +72,8 @@ pub async fn authenticate(
 
     ensure!(org_listing.meta.total_records > 0, UserNoOrgSnafu);
 
+    let session = start_session_svc(&state.db, &user_id, client).await?;
+
     // Select the first org, just let the user switch in the frontend
     let org_id = org_listing.data[0].org_id.clone();
     let actor = ActorPayloadDto {
@@ -66,9 +82,20 @@ pub async fn authenticate(
         org_count: org_listing.meta.total_records as i32,
         roles: org_listing.data[0].roles.clone(),
         scopes: vec![Scope::Auth],
+        grant_id: None,
+        proof_key_id: None,
+        permission_mask: None,
+        issued_at: 0,
+        session_id: Some(session.id),
+        region: state.config.region.name.clone(),
+        home_region: state.config.region.name.clone(),
+        token_id: None,
+        org_app_id: None,
+        impersonator_id: None,
+        impersonation_id: None,
     };
 
-    let token = create_auth_token(&actor, &state.config.jwt_secret)?;
+    let token = create_auth_token(&actor, &state.token_keys)?;
 
     Ok(AuthResponseDto {
         user,
@@ -78,31 +105,256 @@ pub async fn authenticate(
     })
 }
 
+/// Issues a session token for a superuser without a password, for the admin API.
+///
+/// Only callable from the CLI since it needs direct access to the database.
+#[instrument(level = "debug", skip_all)]
+pub async fn issue_superuser_token_svc(
+    db: &DbMapper,
+    keys: &TokenKeys,
+    email: &str,
+) -> Result<String> {
+    let user = db
+        .users
+        .find_by_email(email.trim().to_string())
+        .await?
+        .context(UserNotFoundSnafu)?;
+
+    ensure!(&user.status == "active", InactiveUserSnafu);
+
+    let superuser = db.superusers.get(user.id.clone()).await?;
+    ensure!(
+        superuser.is_some(),
+        ValidationSnafu {
+            msg: format!("{} is not a superuser", user.email),
+        }
+    );
+
+    let memberships = db
+        .org_members
+        .list_memberships(
+            user.id.clone(),
+            ListingParamsDto {
+                page: Some(1),
+                per_page: Some(10),
+            },
+        )
+        .await?;
+
+    let membership = memberships
+        .data
+        .iter()
+        .find(|membership| membership.roles.contains(&Role::Superuser))
+        .context(UserNoOrgSnafu)?;
+
+    let session = start_session_svc(
+        db,
+        &user.id,
+        SessionClientDto {
+            user_agent: Some("yaas admin-token".to_string()),
+            ip_address: None,
+        },
+    )
+    .await?;
+
+    let actor = ActorPayloadDto {
+        id: user.id.clone(),
+        org_id: membership.org_id.clone(),
+        org_count: memberships.meta.total_records as i32,
+        roles: membership.roles.clone(),
+        scopes: vec![Scope::Auth],
+        grant_id: None,
+        proof_key_id: None,
+        permission_mask: None,
+        issued_at: 0,
+        session_id: Some(session.id),
+        // Admin API tokens are not tied to a region
+        region: None,
+        home_region: None,
+        token_id: None,
+        org_app_id: None,
+        impersonator_id: None,
+        impersonation_id: None,
+    };
+
+    let token = create_auth_token(&actor, keys)?;
+
+    warn!(user_id = user.id, email = user.email, "admin_token.issued");
+
+    Ok(token)
+}
+
+/// Validates a token presented without a proof of possession, e.g. from the cookie.
+///
+/// Tokens bound to an app's proof key are rejected.
+#[instrument(level = "debug", skip_all)]
 pub async fn authenticate_token_svc(state: &AppState, token: &str) -> Result<Actor> {
-    let actor_payload = verify_auth_token(token, &state.config.jwt_secret)?;
+    authenticate_bound_token_svc(state, token, None).await
+}
+
+/// Validates a bearer token, bound tokens must come with a valid request proof
+#[instrument(level = "debug", skip_all)]
+pub async fn authenticate_bound_token_svc(
+    state: &AppState,
+    token: &str,
+    proof: Option<&TokenProofDto>,
+) -> Result<Actor> {
+    let mut actor_payload = verify_auth_token(token, &state.token_keys)?;
     let user_id = actor_payload.id.clone();
+
+    verify_not_revoked_svc(state, &actor_payload).await?;
+    verify_not_denied_svc(state, &actor_payload).await?;
+
+    if let Some(session_id) = actor_payload.session_id.as_deref() {
+        verify_session_svc(state, session_id, &user_id).await?;
+    }
+
+    // Impersonation tokens stop working once the impersonation ends
+    verify_impersonation_svc(state, &actor_payload).await?;
+
+    if let Some(key_id) = actor_payload.proof_key_id.as_deref() {
+        verify_token_proof_svc(state, key_id, token, proof).await?;
+    }
+
+    // OAuth tokens are only valid while the user's grant for the app exists
+    if let Some(grant_id) = actor_payload.grant_id.as_ref() {
+        verify_oauth_grant_svc(state, grant_id).await?;
+    }
+
+    // Client credentials tokens act for an org app link, there is no user to load
+    if let Some(org_app_id) = actor_payload.org_app_id.clone() {
+        let app_id = actor_payload.id.as_str();
+        verify_org_app_link_svc(state, &org_app_id, app_id, &actor_payload.org_id).await?;
+        return Ok(Actor::for_app(actor_payload, org_app_id));
+    }
     let org_id = actor_payload.org_id.clone();
 
-    // If found in cache, return right away
-    if let Some(cached_actor) = state.auth_cache.get(&user_id) {
-        return Ok(cached_actor.clone());
+    // Lookups cached for another org are reloaded
+    let cached = cached_auth_lookup(state, &user_id)
+        .await
+        .filter(|lookup| lookup.org_id == org_id);
+    let mut changed = cached.is_none();
+
+    let mut lookup = match cached {
+        Some(lookup) => lookup,
+        None => {
+            // Validate org
+            let org = state.db.orgs.get(org_id.clone()).await?;
+            let _ = org.context(InvalidClientSnafu)?;
+
+            let user = state.db.users.get(user_id.clone()).await?;
+            let user = user.context(UserNotFoundSnafu)?;
+
+            AuthLookupDto {
+                org_id: org_id.clone(),
+                user,
+                member_roles: None,
+                custom_permissions: None,
+            }
+        }
+    };
+
+    // Roles were defined differently when the token was issued
+    if actor_payload.needs_role_refresh() {
+        if lookup.member_roles.is_none() {
+            let membership = state
+                .db
+                .org_members
+                .find_member(org_id.clone(), user_id.clone())
+                .await?;
+
+            let membership = membership.context(ForbiddenSnafu {
+                msg: "User must be a member of the org".to_string(),
+            })?;
+
+            lookup.member_roles = Some(membership.roles);
+            changed = true;
+        }
+
+        actor_payload.roles = lookup.member_roles.clone().unwrap_or_default();
+        actor_payload.permission_mask = None;
     }
 
-    // Validate org
-    let org = state.db.orgs.get(org_id).await?;
-    let _ = org.context(InvalidClientSnafu)?;
+    // Custom org roles and team roles only extend session tokens, OAuth tokens keep the roles they were issued with
+    let custom_permissions = match actor_payload.scopes.contains(&Scope::Auth) {
+        true => {
+            if lookup.custom_permissions.is_none() {
+                let mut permissions = custom_permissions_svc(state, &org_id, &user_id).await?;
+                permissions.extend(team_permissions_svc(state, &org_id, &user_id).await?);
+                lookup.custom_permissions = Some(permissions);
+                changed = true;
+            }
+            lookup.custom_permissions.clone().unwrap_or_default()
+        }
+        false => Vec::new(),
+    };
 
-    let user = state.db.users.get(user_id.clone()).await?;
-    let user = user.context(UserNotFoundSnafu)?;
+    let actor =
+        Actor::new(actor_payload, lookup.user.clone()).with_custom_permissions(&custom_permissions);
 
-    let actor = Actor::new(actor_payload, user.clone());
+    record_org_access(state, &org_id, &user_id).await;
 
-    // Store to cache
-    state.auth_cache.insert(user_id, actor.clone());
+    if changed && state.config.auth_cache.ttl_secs > 0 {
+        store_auth_lookup(state, user_id, lookup).await;
+    }
 
     Ok(actor)
 }
 
+fn auth_lookup_key(user_id: &str) -> String {
+    format!("auth:{}", user_id)
+}
+
+/// Lookups behind the user's actor, from Redis when the cache is shared
+async fn cached_auth_lookup(state: &AppState, user_id: &str) -> Option<AuthLookupDto> {
+    if !state.shared_cache.is_redis() {
+        return state.auth_cache.get(user_id);
+    }
+
+    let value = state.shared_cache.get(&auth_lookup_key(user_id)).await?;
+    serde_json::from_str(&value).ok()
+}
+
+async fn store_auth_lookup(state: &AppState, user_id: String, lookup: AuthLookupDto) {
+    if !state.shared_cache.is_redis() {
+        state.auth_cache.insert(user_id, lookup);
+        return;
+    }
+
+    let Ok(value) = serde_json::to_string(&lookup) else {
+        return;
+    };
+    let ttl = Duration::from_secs(state.config.auth_cache.ttl_secs);
+    state
+        .shared_cache
+        .set(&auth_lookup_key(&user_id), value, ttl)
+        .await;
+}
+
+/// Drops the cached lookups of the user, on every instance when the cache is shared
+pub async fn invalidate_auth_cache(state: &AppState, user_id: &str) {
+    state.auth_cache.invalidate(user_id);
+    if state.shared_cache.is_redis() {
+        state.shared_cache.delete(&auth_lookup_key(user_id)).await;
+    }
+}
+
+/// Drops the cached lookups of every user
+pub async fn invalidate_all_auth_cache(state: &AppState) {
+    state.auth_cache.invalidate_all();
+    if state.shared_cache.is_redis() {
+        state.shared_cache.delete_prefix(&auth_lookup_key("")).await;
+    }
+}
+
+/// Cache of the lookups behind user actors, see `AuthCacheConfig`
+pub fn new_auth_cache(config: &AuthCacheConfig) -> Cache<String, AuthLookupDto> {
+    Cache::builder()
+        .time_to_live(Duration::from_secs(config.ttl_secs.max(1)))
+        .max_capacity(config.max_capacity)
+        .build()
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct SwitchAuthContextFormData {
     pub token: String,
@@ -118,9 +370,12 @@ pub struct SwitchAuthContextParams {
     pub next: String,
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn switch_auth_context_svc(
     state: &AppState,
     user_id: &str,
+    session_id: Option<&str>,
+    home_region: Option<&str>,
     payload: SwitchAuthContextDto,
 ) -> Result<AuthResponseDto> {
     let user_id = user_id.to_owned();
@@ -153,9 +408,23 @@ pub async fn switch_auth_context_svc(
         org_count: org_count as i32,
         roles: membership.roles,
         scopes: vec![Scope::Auth],
+        grant_id: None,
+        proof_key_id: None,
+        permission_mask: None,
+        issued_at: 0,
+        session_id: session_id.map(|id| id.to_string()),
+        region: state.config.region.name.clone(),
+        // The session stays in the region it started in
+        home_region: home_region
+            .map(|region| region.to_string())
+            .or_else(|| state.config.region.name.clone()),
+        token_id: None,
+        org_app_id: None,
+        impersonator_id: None,
+        impersonation_id: None,
     };
 
-    let token = create_auth_token(&actor, &state.config.jwt_secret)?;
+    let token = create_auth_token(&actor, &state.token_keys)?;
 
     Ok(AuthResponseDto {
         user,
@@ -167,10 +436,14 @@ pub async fn switch_auth_context_svc(
 
 #[cfg(test)]
 mod tests {
-    use crate::dto::CredentialsDto;
+    use crate::dto::{
+        CredentialsDto, NewOrgDto, SessionClientDto, SwitchAuthContextDto, UpdateUserDto,
+    };
+    use crate::services::orgs::create_org_svc;
+    use crate::services::users::update_user_svc;
     use crate::test::TestCtx;
 
-    use super::{authenticate, authenticate_token_svc};
+    use super::{authenticate, authenticate_token_svc, switch_auth_context_svc};
 
     #[tokio::test]
     async fn authenticate_svc_accepts_valid_credentials() {
@@ -194,6 +467,7 @@ mod tests {
                 email: fixture.email.clone(),
                 password: fixture.password.clone(),
             },
+            SessionClientDto::default(),
         )
         .await
         .expect("authentication should pass");
@@ -226,11 +500,12 @@ mod tests {
                 email: fixture.email,
                 password: "wrongpassword".to_string(),
             },
+            SessionClientDto::default(),
         )
         .await;
 
         assert!(result.is_err(), "authentication should fail");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "Invalid username or password");
     }
 
@@ -244,11 +519,12 @@ mod tests {
                 email: "unknown@example.com".to_string(),
                 password: "password123".to_string(),
             },
+            SessionClientDto::default(),
         )
         .await;
 
         assert!(result.is_err(), "authentication should fail");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "Invalid username or password");
     }
 
@@ -272,6 +548,7 @@ mod tests {
                 email: fixture.email,
                 password: fixture.password,
             },
+            SessionClientDto::default(),
         )
         .await
         .expect("authentication should pass");
@@ -296,4 +573,83 @@ mod tests {
         let err = result.err().expect("error should exist");
         assert_eq!(err.to_string(), "Invalid auth token");
     }
+
+    #[tokio::test]
+    async fn cached_lookups_follow_the_token_org_and_user_changes() {
+        let ctx = TestCtx::new("auth_cached_lookups").await.expect("test ctx");
+
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Auth User",
+                "auth.cache@example.com",
+                "password123",
+                "Auth Org",
+            )
+            .await
+            .expect("auth fixture");
+        let other_org = create_org_svc(
+            &ctx.state,
+            NewOrgDto {
+                name: "Other Org".to_string(),
+                owner_id: fixture.user.id.clone(),
+            },
+        )
+        .await
+        .expect("other org");
+
+        let auth = authenticate(
+            &ctx.state,
+            &CredentialsDto {
+                email: fixture.email,
+                password: fixture.password,
+            },
+            SessionClientDto::default(),
+        )
+        .await
+        .expect("authentication should pass");
+
+        authenticate_token_svc(&ctx.state, &auth.token)
+            .await
+            .expect("token should be valid");
+        assert!(ctx.state.auth_cache.contains_key(&fixture.user.id));
+
+        // Tokens of the same user for another org are not served the cached org
+        let switched = switch_auth_context_svc(
+            &ctx.state,
+            &fixture.user.id,
+            None,
+            None,
+            SwitchAuthContextDto {
+                org_id: other_org.id.clone(),
+            },
+        )
+        .await
+        .expect("switch org");
+        let actor = authenticate_token_svc(&ctx.state, &switched.token)
+            .await
+            .expect("token should be valid");
+        assert_eq!(actor.actor.expect("actor data").org_id, other_org.id);
+
+        let actor = authenticate_token_svc(&ctx.state, &auth.token)
+            .await
+            .expect("token should be valid");
+        assert_eq!(actor.actor.expect("actor data").org_id, fixture.org.id);
+
+        update_user_svc(
+            &ctx.state,
+            &fixture.user.id,
+            UpdateUserDto {
+                name: Some("Renamed User".to_string()),
+                status: None,
+            },
+        )
+        .await
+        .expect("update user");
+        assert!(!ctx.state.auth_cache.contains_key(&fixture.user.id));
+
+        let actor = authenticate_token_svc(&ctx.state, &auth.token)
+            .await
+            .expect("token should be valid");
+        assert_eq!(actor.actor.expect("actor data").user.name, "Renamed User");
+    }
 }
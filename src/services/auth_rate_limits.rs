@@ -0,0 +1,174 @@
+use moka::sync::Cache;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::run::AppState;
+
+/// Attempts counted for one key since the window started
+#[derive(Clone, Copy, Debug)]
+struct AttemptWindow {
+    started_at: Instant,
+    attempts: u64,
+}
+
+impl AttemptWindow {
+    fn new(now: Instant) -> Self {
+        Self {
+            started_at: now,
+            attempts: 0,
+        }
+    }
+
+    /// Counts the attempt, returns the seconds left in the window when the
+    /// limit is already used up
+    fn take(&mut self, limit: u64, window: Duration, now: Instant) -> Option<u64> {
+        let elapsed = now.saturating_duration_since(self.started_at);
+        if elapsed >= window {
+            *self = Self::new(now);
+        }
+
+        if self.attempts >= limit {
+            let left = window.saturating_sub(now.saturating_duration_since(self.started_at));
+            return Some(left.as_secs().max(1));
+        }
+
+        self.attempts += 1;
+        None
+    }
+}
+
+/// Outcome of an attempt on the sign in and recovery forms
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AuthRateDecision {
+    pub allowed: bool,
+    /// Seconds until the next attempt is accepted, 0 when allowed
+    pub retry_after_secs: u64,
+}
+
+/// Attempt counters per client IP and per email, kept in memory on each instance
+#[derive(Clone)]
+pub struct AuthRateLimiter {
+    windows: Cache<String, Arc<Mutex<AttemptWindow>>>,
+}
+
+impl Default for AuthRateLimiter {
+    fn default() -> Self {
+        Self {
+            // Idle counters are past their window long before they are evicted
+            windows: Cache::builder()
+                .time_to_idle(Duration::from_secs(2 * 60 * 60))
+                .max_capacity(100_000)
+                .build(),
+        }
+    }
+}
+
+impl AuthRateLimiter {
+    fn take(&self, key: String, limit: u64, window: Duration, now: Instant) -> Option<u64> {
+        let counter = self
+            .windows
+            .get_with(key, || Arc::new(Mutex::new(AttemptWindow::new(now))));
+
+        counter
+            .lock()
+            .expect("Auth attempt lock must not be poisoned")
+            .take(limit, window, now)
+    }
+}
+
+/// Counts an attempt against the client IP, then against the email.
+///
+/// Either limit being used up rejects the attempt. Counters are shared
+/// through Redis when it is configured.
+pub async fn take_auth_attempt_svc(
+    state: &AppState,
+    ip_address: Option<&str>,
+    email: Option<&str>,
+) -> AuthRateDecision {
+    let config = &state.config.auth_rate_limit;
+    let window = Duration::from_secs(config.window_secs);
+    let now = Instant::now();
+
+    let mut checks: Vec<(String, u64)> = Vec::new();
+    if let Some(ip) = ip_address.filter(|_| config.per_ip > 0) {
+        checks.push((format!("ip:{}", ip), config.per_ip));
+    }
+    if let Some(email) = email.filter(|_| config.per_email > 0) {
+        checks.push((
+            format!("email:{}", email.trim().to_lowercase()),
+            config.per_email,
+        ));
+    }
+
+    for (key, limit) in checks.into_iter() {
+        let rejected = match state.shared_cache.is_redis() {
+            true => take_shared(state, &key, limit, window).await,
+            false => state
+                .auth_rate_limiter
+                .take(key.clone(), limit, window, now),
+        };
+
+        if let Some(retry_after_secs) = rejected {
+            warn!(key = key, "auth.rate_limited");
+            return AuthRateDecision {
+                allowed: false,
+                retry_after_secs,
+            };
+        }
+    }
+
+    AuthRateDecision {
+        allowed: true,
+        retry_after_secs: 0,
+    }
+}
+
+/// Same as `AuthRateLimiter::take` with the counter in the shared cache,
+/// attempts over the limit are counted too
+async fn take_shared(state: &AppState, key: &str, limit: u64, window: Duration) -> Option<u64> {
+    let (attempts, left) = state
+        .shared_cache
+        .incr(&format!("auth_attempts:{}", key), window)
+        .await;
+
+    match attempts > limit {
+        true => Some(left.as_secs().max(1)),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{AttemptWindow, AuthRateLimiter};
+
+    #[test]
+    fn attempts_reset_with_the_window() {
+        let window = Duration::from_secs(60);
+        let start = Instant::now();
+        let mut counter = AttemptWindow::new(start);
+
+        assert_eq!(counter.take(2, window, start), None);
+        assert_eq!(counter.take(2, window, start), None);
+        assert_eq!(
+            counter.take(2, window, start + Duration::from_secs(15)),
+            Some(45)
+        );
+
+        let next = start + Duration::from_secs(60);
+        assert_eq!(counter.take(2, window, next), None);
+    }
+
+    #[test]
+    fn keys_are_counted_separately() {
+        let limiter = AuthRateLimiter::default();
+        let window = Duration::from_secs(60);
+        let now = Instant::now();
+
+        assert_eq!(limiter.take("ip:1".to_string(), 1, window, now), None);
+        assert!(limiter.take("ip:1".to_string(), 1, window, now).is_some());
+        assert_eq!(limiter.take("ip:2".to_string(), 1, window, now), None);
+    }
+}
@@ -0,0 +1,198 @@
+use chrono::Utc;
+use snafu::{OptionExt, ensure};
+use std::time::Duration;
+use tracing::{info, instrument};
+
+use crate::Result;
+use crate::db::{Backfill, DbMapper};
+use crate::dto::{BackfillOptionsDto, BackfillProgressDto, BackfillReportDto, BackfillStatusDto};
+use crate::error::{NotFoundSnafu, ValidationSnafu};
+
+/// Largest batch allowed, keeps each transaction short
+const MAX_BATCH_SIZE: i64 = 5000;
+
+pub fn find_backfill<'a>(backfills: &'a [Backfill], name: &str) -> Result<&'a Backfill> {
+    backfills
+        .iter()
+        .find(|backfill| backfill.name == name)
+        .context(NotFoundSnafu {
+            msg: format!("Unknown backfill: {}", name),
+        })
+}
+
+/// Registered backfills with how far each one got
+#[instrument(level = "debug", skip_all)]
+pub async fn list_backfills_svc(
+    db: &DbMapper,
+    backfills: &[Backfill],
+) -> Result<Vec<BackfillStatusDto>> {
+    let mut items = Vec::with_capacity(backfills.len());
+
+    for backfill in backfills.iter() {
+        let progress = db.backfills.get_progress(backfill.name.to_string()).await?;
+        items.push(BackfillStatusDto {
+            name: backfill.name.to_string(),
+            table: backfill.table.to_string(),
+            progress,
+        });
+    }
+
+    Ok(items)
+}
+
+/// Runs the backfill in batches, resuming after the last completed batch.
+///
+/// Each batch commits with its progress, so an interrupted run loses at most
+/// the batch in flight. Completed backfills are not run again unless restarted.
+#[instrument(level = "debug", skip_all)]
+pub async fn run_backfill_svc(
+    db: &DbMapper,
+    backfill: &Backfill,
+    options: BackfillOptionsDto,
+) -> Result<BackfillReportDto> {
+    ensure!(
+        (1..=MAX_BATCH_SIZE).contains(&options.batch_size),
+        ValidationSnafu {
+            msg: format!("Batch size must be between 1 and {}", MAX_BATCH_SIZE),
+        }
+    );
+
+    let now = Utc::now().timestamp_millis();
+    let saved = match options.restart {
+        true => None,
+        false => db.backfills.get_progress(backfill.name.to_string()).await?,
+    };
+
+    let mut progress = saved.unwrap_or(BackfillProgressDto {
+        name: backfill.name.to_string(),
+        last_id: None,
+        rows_done: 0,
+        started_at: now,
+        updated_at: now,
+        completed_at: None,
+    });
+
+    let mut report = BackfillReportDto {
+        name: backfill.name.to_string(),
+        ..Default::default()
+    };
+
+    if progress.completed_at.is_some() {
+        report.completed = true;
+        return Ok(report);
+    }
+
+    while options
+        .max_batches
+        .is_none_or(|max_batches| report.batches < max_batches)
+    {
+        let ids = db
+            .backfills
+            .next_batch(backfill, progress.last_id.clone(), options.batch_size)
+            .await?;
+
+        if ids.is_empty() {
+            progress.completed_at = Some(Utc::now().timestamp_millis());
+            db.backfills.save_progress(&progress).await?;
+            report.completed = true;
+            break;
+        }
+
+        let rows = db.backfills.apply_batch(backfill, &ids, &progress).await?;
+        progress.last_id = ids.last().cloned();
+        progress.rows_done += rows;
+        report.batches += 1;
+        report.rows += rows;
+
+        info!(
+            backfill = backfill.name,
+            batch = report.batches,
+            rows = rows,
+            rows_done = progress.rows_done,
+            last_id = progress.last_id,
+            "backfill.batch"
+        );
+
+        if options.pause_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(options.pause_ms)).await;
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::Backfill;
+    use crate::dto::BackfillOptionsDto;
+    use crate::test::TestCtx;
+
+    use super::{list_backfills_svc, run_backfill_svc};
+
+    const TRIM_NAMES: Backfill = Backfill {
+        name: "trim-user-names",
+        table: "users",
+        pending: "name <> TRIM(name)",
+        set: "name = TRIM(name)",
+    };
+
+    #[tokio::test]
+    async fn backfill_resumes_where_it_stopped() {
+        let ctx = TestCtx::new("backfill_resume").await.expect("test ctx");
+        for i in 0..5 {
+            ctx.seed_user_with_password(
+                &format!("  User {}  ", i),
+                &format!("user{}@example.com", i),
+                "password123",
+            )
+            .await
+            .expect("user");
+        }
+
+        let options = BackfillOptionsDto {
+            batch_size: 2,
+            pause_ms: 0,
+            max_batches: Some(1),
+            restart: false,
+        };
+        let first = run_backfill_svc(&ctx.state.db, &TRIM_NAMES, options)
+            .await
+            .expect("first run");
+        assert_eq!((first.batches, first.rows, first.completed), (1, 2, false));
+
+        let rest = run_backfill_svc(
+            &ctx.state.db,
+            &TRIM_NAMES,
+            BackfillOptionsDto {
+                max_batches: None,
+                ..options
+            },
+        )
+        .await
+        .expect("second run");
+        assert_eq!((rest.rows, rest.completed), (3, true));
+
+        let statuses = list_backfills_svc(&ctx.state.db, &[TRIM_NAMES])
+            .await
+            .expect("status");
+        let progress = statuses[0].progress.as_ref().expect("progress");
+        assert_eq!(progress.rows_done, 5);
+        assert!(progress.completed_at.is_some());
+
+        let users = ctx
+            .state
+            .db
+            .users
+            .find_by_email("user3@example.com".to_string())
+            .await
+            .expect("find")
+            .expect("user");
+        assert_eq!(users.name, "User 3");
+
+        // Completed backfills are skipped
+        let again = run_backfill_svc(&ctx.state.db, &TRIM_NAMES, options)
+            .await
+            .expect("third run");
+        assert_eq!((again.batches, again.completed), (0, true));
+    }
+}
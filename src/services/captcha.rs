@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
+use tracing::instrument;
 
 use crate::{
     Result,
@@ -82,6 +83,7 @@ struct CaptchaErrorResponse {
     error: CaptchaError,
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn validate_catpcha(state: &AppState, response: &str) -> Result<()> {
     let site_key = state
         .config
@@ -0,0 +1,160 @@
+use chrono::Utc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, instrument, warn};
+
+use crate::Result;
+use crate::db::DbMapper;
+use crate::dto::StatsDto;
+use crate::run::AppState;
+
+/// Recounts the org after its members or apps change.
+///
+/// Failures only leave the counters stale until the next reconcile run.
+#[instrument(level = "debug", skip_all)]
+pub async fn refresh_org_counters(db: &DbMapper, org_id: &str) {
+    if let Err(e) = db.counters.refresh_org(org_id.to_string()).await {
+        warn!(org_id = org_id, "Org counter refresh failed: {}", e);
+    }
+}
+
+/// Fixes drifted org counters and recomputes the deployment totals,
+/// returns the number of orgs that had drifted
+#[instrument(level = "debug", skip_all)]
+pub async fn reconcile_counters_svc(db: &DbMapper) -> Result<u64> {
+    let drifted = db.counters.reconcile_orgs().await?;
+    db.counters
+        .refresh_stats(Utc::now().timestamp_millis())
+        .await?;
+    Ok(drifted)
+}
+
+/// Reconciles the counters on startup and then on every interval.
+///
+/// Catches changes made without a refresh, ie: deleted users and apps.
+pub async fn counter_reconcile_job(db: Arc<DbMapper>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        match reconcile_counters_svc(&db).await {
+            Ok(0) => {}
+            Ok(drifted) => info!(drifted = drifted, "Org counters reconciled"),
+            Err(e) => error!("Counter reconcile failed: {}", e),
+        }
+    }
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn stats_svc(state: &AppState) -> Result<StatsDto> {
+    let counters = state.db.counters.list_stats().await?;
+    Ok(StatsDto::from(counters))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ctx::AuditCtx;
+    use crate::dto::NewOrgMemberDto;
+    use crate::services::org_members::create_org_member_svc;
+    use crate::services::orgs::get_org_svc;
+    use crate::services::users::delete_user_svc;
+    use crate::test::TestCtx;
+
+    use super::{reconcile_counters_svc, stats_svc};
+
+    #[tokio::test]
+    async fn counters_follow_writes_and_reconcile_drift() {
+        let ctx = TestCtx::new("counters_reconcile").await.expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "Counter Owner",
+                "counter.owner@example.com",
+                "password123",
+                "Counter Org",
+                "Counter App",
+                "https://counter.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+        let member = ctx
+            .seed_user_with_password(
+                "Counter Member",
+                "counter.member@example.com",
+                "password123",
+            )
+            .await
+            .expect("member");
+        let org_id = fixture.auth.org.id.clone();
+
+        create_org_member_svc(
+            &ctx.state,
+            &org_id,
+            NewOrgMemberDto {
+                user_id: member.id.clone(),
+                roles: vec!["OrgViewer".to_string()],
+                status: "active".to_string(),
+            },
+        )
+        .await
+        .expect("member created");
+
+        let org = get_org_svc(&ctx.state, &org_id)
+            .await
+            .expect("org query")
+            .expect("org");
+        assert_eq!(org.member_count, 2);
+        assert_eq!(org.app_count, 1);
+
+        // Deleting a user refreshes the counters of its orgs
+        delete_user_svc(&ctx.state, &member.id)
+            .await
+            .expect("user deleted");
+        let org = get_org_svc(&ctx.state, &org_id)
+            .await
+            .expect("org query")
+            .expect("org");
+        assert_eq!(org.member_count, 1);
+
+        // Drift from writes that skip the refresh is caught by the reconcile
+        let drifted_member = ctx
+            .seed_user_with_password("Drift Member", "counter.drift@example.com", "password123")
+            .await
+            .expect("drift member");
+        create_org_member_svc(
+            &ctx.state,
+            &org_id,
+            NewOrgMemberDto {
+                user_id: drifted_member.id.clone(),
+                roles: vec!["OrgViewer".to_string()],
+                status: "active".to_string(),
+            },
+        )
+        .await
+        .expect("member created");
+        ctx.state
+            .db
+            .users
+            .delete(&AuditCtx::default(), drifted_member.id.clone())
+            .await
+            .expect("user deleted");
+        let drifted = reconcile_counters_svc(&ctx.state.db)
+            .await
+            .expect("reconcile");
+        assert_eq!(drifted, 1);
+
+        let org = get_org_svc(&ctx.state, &org_id)
+            .await
+            .expect("org query")
+            .expect("org");
+        assert_eq!(org.member_count, 1);
+
+        let stats = stats_svc(&ctx.state).await.expect("stats");
+        assert_eq!(stats.users, 1);
+        assert_eq!(stats.orgs, 1);
+        assert_eq!(stats.apps, 1);
+        assert_eq!(stats.org_members, 1);
+        assert!(stats.updated_at.is_some());
+    }
+}
@@ -0,0 +1,208 @@
+use chrono::Utc;
+use serde_json::{Map, Value};
+use snafu::{ResultExt, ensure};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, instrument};
+
+use crate::Result;
+use crate::db::DbMapper;
+use crate::dto::FormDraftDto;
+use crate::error::{JsonSerializeSnafu, ValidationSnafu};
+use crate::run::AppState;
+
+/// Drafts not saved again within this window are dropped
+pub const DRAFT_TTL_HOURS: i64 = 7 * 24;
+
+/// Largest draft kept, long forms are still well below it
+const MAX_DRAFT_BYTES: usize = 16 * 1024;
+
+/// Fields never written to a draft: the CSRF token and anything secret
+fn is_draft_field(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name != "token" && !name.contains("password") && !name.contains("secret")
+}
+
+/// Form keys name the form and its record, ie: `org-edit-org_...`
+pub fn valid_form_key(form_key: &str) -> bool {
+    !form_key.is_empty()
+        && form_key.len() <= 100
+        && form_key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Field values as a JSON object, repeated fields become arrays
+fn draft_data(fields: Vec<(String, String)>) -> Map<String, Value> {
+    let mut data = Map::new();
+
+    for (name, value) in fields.into_iter() {
+        if !is_draft_field(&name) {
+            continue;
+        }
+
+        match data.get_mut(&name) {
+            Some(Value::Array(values)) => values.push(Value::String(value)),
+            Some(existing) => {
+                let first = existing.take();
+                *existing = Value::Array(vec![first, Value::String(value)]);
+            }
+            None => {
+                data.insert(name, Value::String(value));
+            }
+        }
+    }
+
+    data
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn get_draft_svc(
+    state: &AppState,
+    user_id: &str,
+    form_key: &str,
+) -> Result<Option<FormDraftDto>> {
+    if !valid_form_key(form_key) {
+        return Ok(None);
+    }
+
+    let now = Utc::now().timestamp_millis();
+    state
+        .db
+        .form_drafts
+        .get(user_id.to_string(), form_key.to_string(), now)
+        .await
+}
+
+/// Saves the current values of the form, each save moves the expiry
+#[instrument(level = "debug", skip_all)]
+pub async fn save_draft_svc(
+    state: &AppState,
+    user_id: &str,
+    form_key: &str,
+    fields: Vec<(String, String)>,
+) -> Result<FormDraftDto> {
+    ensure!(
+        valid_form_key(form_key),
+        ValidationSnafu {
+            msg: "Invalid form key."
+        }
+    );
+
+    let data = serde_json::to_string(&draft_data(fields)).context(JsonSerializeSnafu)?;
+    ensure!(
+        data.len() <= MAX_DRAFT_BYTES,
+        ValidationSnafu {
+            msg: "Draft is too large to save."
+        }
+    );
+
+    let now = Utc::now().timestamp_millis();
+    state
+        .db
+        .form_drafts
+        .upsert(FormDraftDto {
+            user_id: user_id.to_string(),
+            form_key: form_key.to_string(),
+            data,
+            updated_at: now,
+            expires_at: now + DRAFT_TTL_HOURS * 60 * 60 * 1000,
+        })
+        .await
+}
+
+/// Drops the draft once the form is submitted or the user discards it
+#[instrument(level = "debug", skip_all)]
+pub async fn discard_draft_svc(state: &AppState, user_id: &str, form_key: &str) -> Result<bool> {
+    if !valid_form_key(form_key) {
+        return Ok(false);
+    }
+
+    state
+        .db
+        .form_drafts
+        .delete(user_id.to_string(), form_key.to_string())
+        .await
+}
+
+/// Removes expired drafts every hour
+pub async fn draft_cleanup_job(db: Arc<DbMapper>) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(60 * 60));
+
+    loop {
+        ticker.tick().await;
+
+        match db
+            .form_drafts
+            .delete_expired(Utc::now().timestamp_millis())
+            .await
+        {
+            Ok(0) => {}
+            Ok(removed) => info!(removed = removed, "Expired form drafts removed"),
+            Err(e) => error!("Form draft cleanup failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use crate::test::TestCtx;
+
+    use super::{discard_draft_svc, get_draft_svc, save_draft_svc};
+
+    fn fields(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn draft_keeps_values_but_not_secrets() {
+        let ctx = TestCtx::new("form_drafts").await.expect("test ctx");
+        let user = ctx
+            .seed_user_with_password("Jane", "jane@example.com", "password123")
+            .await
+            .expect("user");
+
+        save_draft_svc(
+            &ctx.state,
+            &user.id,
+            "org-edit-org_1",
+            fields(&[
+                ("token", "csrf"),
+                ("name", "Acme"),
+                ("roles", "OrgAdmin"),
+                ("roles", "OrgViewer"),
+                ("client_secret", "hidden"),
+            ]),
+        )
+        .await
+        .expect("save");
+
+        let draft = get_draft_svc(&ctx.state, &user.id, "org-edit-org_1")
+            .await
+            .expect("get")
+            .expect("draft");
+        let data: Value = serde_json::from_str(&draft.data).expect("json");
+        assert_eq!(
+            data,
+            json!({ "name": "Acme", "roles": ["OrgAdmin", "OrgViewer"] })
+        );
+
+        let invalid = save_draft_svc(&ctx.state, &user.id, "../org", Vec::new()).await;
+        assert!(invalid.is_err());
+
+        assert!(
+            discard_draft_svc(&ctx.state, &user.id, "org-edit-org_1")
+                .await
+                .expect("discard")
+        );
+        let gone = get_draft_svc(&ctx.state, &user.id, "org-edit-org_1")
+            .await
+            .expect("get");
+        assert!(gone.is_none());
+    }
+}
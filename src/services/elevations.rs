@@ -0,0 +1,697 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ensure};
+use tracing::{info, instrument, warn};
+
+use crate::ctx::AuditCtx;
+use crate::dto::{
+    ElevationRevertJobDto, ElevationStatus, JobKind, NewRoleElevationDto, OrgMemberDto, Role,
+    RoleElevationDto, UpdateOrgMemberDto,
+};
+use crate::error::{CsrfTokenSnafu, ForbiddenSnafu, NotFoundSnafu, ValidationSnafu};
+use crate::run::AppState;
+use crate::services::auth::invalidate_auth_cache;
+use crate::services::jobs::schedule_job_svc;
+use crate::services::revocations::revoke_user_tokens_svc;
+use crate::services::token::verify_csrf_token;
+use crate::utils::{IdPrefix, generate_id};
+use crate::validators::validate_payload;
+use crate::{Error, Result};
+
+/// Number of elevations shown on the member page
+const ELEVATIONS_LIST_LIMIT: i64 = 20;
+
+/// Roles that can be granted for a limited time, superuser is never elevated
+const ELEVATABLE_ROLES: [Role; 3] = [Role::OrgAdmin, Role::OrgEditor, Role::OrgViewer];
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct RoleElevationFormData {
+    pub token: String,
+    pub role: String,
+    pub reason: String,
+    pub duration_mins: i64,
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn list_member_elevations_svc(
+    state: &AppState,
+    org_id: &str,
+    user_id: &str,
+) -> Result<Vec<RoleElevationDto>> {
+    state
+        .db
+        .role_elevations
+        .list_by_member(
+            org_id.to_string(),
+            user_id.to_string(),
+            ELEVATIONS_LIST_LIMIT,
+        )
+        .await
+}
+
+/// Latest expiry of the running elevations in the org, keyed by user_id
+#[instrument(level = "debug", skip_all)]
+pub async fn elevated_until_svc(state: &AppState, org_id: &str) -> Result<HashMap<String, i64>> {
+    let now = Utc::now().timestamp_millis();
+    let elevations = state
+        .db
+        .role_elevations
+        .list_active_by_org(org_id.to_string(), now)
+        .await?;
+
+    let mut until: HashMap<String, i64> = HashMap::new();
+    for elevation in elevations.into_iter() {
+        let Some(expires_at) = elevation.expires_at else {
+            continue;
+        };
+        let entry = until.entry(elevation.user_id).or_insert(expires_at);
+        *entry = (*entry).max(expires_at);
+    }
+
+    Ok(until)
+}
+
+/// Grants an extra org role to the member until the duration runs out.
+///
+/// With `ELEVATION_APPROVAL=1` the elevation stays pending until a different
+/// member admin approves it.
+#[instrument(level = "debug", skip_all)]
+pub async fn request_elevation_svc(
+    state: &AppState,
+    actor_id: &str,
+    org_id: &str,
+    user_id: &str,
+    data: NewRoleElevationDto,
+) -> Result<RoleElevationDto> {
+    validate_payload(&data)?;
+
+    let role = Role::try_from(data.role.as_str()).map_err(|msg| Error::Validation { msg })?;
+    ensure!(
+        ELEVATABLE_ROLES.contains(&role),
+        ValidationSnafu {
+            msg: format!("{} cannot be granted for a limited time", role),
+        }
+    );
+
+    let member = find_elevatable_member(state, org_id, user_id, &role).await?;
+
+    let open = state
+        .db
+        .role_elevations
+        .find_open(org_id.to_string(), user_id.to_string(), role.clone())
+        .await?;
+
+    ensure!(
+        open.is_none(),
+        ValidationSnafu {
+            msg: format!("An elevation to {} is already open for the member", role),
+        }
+    );
+
+    let elevation = state
+        .db
+        .role_elevations
+        .create(RoleElevationDto {
+            id: generate_id(IdPrefix::RoleElevation),
+            org_id: org_id.to_string(),
+            user_id: member.user_id.clone(),
+            role,
+            reason: data.reason.trim().to_string(),
+            duration_mins: data.duration_mins,
+            status: ElevationStatus::Pending,
+            requested_by: actor_id.to_string(),
+            decided_by: None,
+            created_at: Utc::now().timestamp_millis(),
+            expires_at: None,
+            ended_at: None,
+        })
+        .await?;
+
+    info!(
+        elevation_id = elevation.id.as_str(),
+        org_id = elevation.org_id.as_str(),
+        user_id = elevation.user_id.as_str(),
+        role = elevation.role.to_string(),
+        duration_mins = elevation.duration_mins,
+        requested_by = elevation.requested_by.as_str(),
+        reason = elevation.reason.as_str(),
+        "elevation.requested"
+    );
+
+    if state.config.approvals.elevation_approval {
+        return Ok(elevation);
+    }
+
+    activate_elevation(state, elevation, actor_id).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn approve_elevation_svc(
+    state: &AppState,
+    actor_id: &str,
+    org_id: &str,
+    elevation_id: &str,
+) -> Result<RoleElevationDto> {
+    let elevation = get_elevation(state, org_id, elevation_id).await?;
+
+    ensure!(
+        elevation.status == ElevationStatus::Pending,
+        ValidationSnafu {
+            msg: format!("Elevation is already {}", elevation.status),
+        }
+    );
+
+    ensure!(
+        elevation.requested_by != actor_id,
+        ForbiddenSnafu {
+            msg: "A second member admin must approve this elevation".to_string()
+        }
+    );
+
+    // The member may have gained the role or left since the request
+    let _ = find_elevatable_member(state, org_id, &elevation.user_id, &elevation.role).await?;
+
+    activate_elevation(state, elevation, actor_id).await
+}
+
+/// Any member admin can reject, including the requester to withdraw it
+#[instrument(level = "debug", skip_all)]
+pub async fn reject_elevation_svc(
+    state: &AppState,
+    actor_id: &str,
+    org_id: &str,
+    elevation_id: &str,
+) -> Result<RoleElevationDto> {
+    let elevation = get_elevation(state, org_id, elevation_id).await?;
+
+    let rejected = state
+        .db
+        .role_elevations
+        .end(
+            elevation.id.clone(),
+            ElevationStatus::Pending,
+            ElevationStatus::Rejected,
+            Some(actor_id.to_string()),
+        )
+        .await?;
+
+    ensure!(
+        rejected,
+        ValidationSnafu {
+            msg: format!("Elevation is already {}", elevation.status),
+        }
+    );
+
+    info!(
+        elevation_id = elevation.id.as_str(),
+        org_id = elevation.org_id.as_str(),
+        user_id = elevation.user_id.as_str(),
+        role = elevation.role.to_string(),
+        decided_by = actor_id,
+        "elevation.rejected"
+    );
+
+    get_elevation(state, org_id, elevation_id).await
+}
+
+/// Ends an active elevation before it expires
+#[instrument(level = "debug", skip_all)]
+pub async fn revert_elevation_svc(
+    state: &AppState,
+    actor_id: &str,
+    org_id: &str,
+    elevation_id: &str,
+) -> Result<RoleElevationDto> {
+    let elevation = get_elevation(state, org_id, elevation_id).await?;
+
+    let reverted = revert_elevation(state, &elevation, Some(actor_id)).await?;
+
+    ensure!(
+        reverted,
+        ValidationSnafu {
+            msg: format!("Elevation is already {}", elevation.status),
+        }
+    );
+
+    get_elevation(state, org_id, elevation_id).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn request_elevation_web_svc(
+    state: &AppState,
+    actor_id: &str,
+    org_id: &str,
+    user_id: &str,
+    form: RoleElevationFormData,
+) -> Result<RoleElevationDto> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == user_id, CsrfTokenSnafu);
+
+    request_elevation_svc(
+        state,
+        actor_id,
+        org_id,
+        user_id,
+        NewRoleElevationDto {
+            role: form.role,
+            reason: form.reason,
+            duration_mins: form.duration_mins,
+        },
+    )
+    .await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn approve_elevation_web_svc(
+    state: &AppState,
+    actor_id: &str,
+    member: &OrgMemberDto,
+    elevation_id: &str,
+    csrf_token: &str,
+) -> Result<RoleElevationDto> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == member.user_id, CsrfTokenSnafu);
+
+    approve_elevation_svc(state, actor_id, &member.org_id, elevation_id).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn reject_elevation_web_svc(
+    state: &AppState,
+    actor_id: &str,
+    member: &OrgMemberDto,
+    elevation_id: &str,
+    csrf_token: &str,
+) -> Result<RoleElevationDto> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == member.user_id, CsrfTokenSnafu);
+
+    reject_elevation_svc(state, actor_id, &member.org_id, elevation_id).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn revert_elevation_web_svc(
+    state: &AppState,
+    actor_id: &str,
+    member: &OrgMemberDto,
+    elevation_id: &str,
+    csrf_token: &str,
+) -> Result<RoleElevationDto> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == member.user_id, CsrfTokenSnafu);
+
+    revert_elevation_svc(state, actor_id, &member.org_id, elevation_id).await
+}
+
+/// Job handler reverting an elevation once it expires, elevations already
+/// reverted by an admin are skipped
+pub async fn run_elevation_revert(
+    state: &AppState,
+    job: ElevationRevertJobDto,
+    now: i64,
+) -> Result<()> {
+    let Some(elevation) = state.db.role_elevations.get(job.elevation_id).await? else {
+        return Ok(());
+    };
+    if elevation.status != ElevationStatus::Active {
+        return Ok(());
+    }
+
+    ensure!(
+        elevation.is_expired(now),
+        ValidationSnafu {
+            msg: "Elevation has not expired yet".to_string(),
+        }
+    );
+
+    revert_elevation(state, &elevation, None).await?;
+    Ok(())
+}
+
+async fn get_elevation(
+    state: &AppState,
+    org_id: &str,
+    elevation_id: &str,
+) -> Result<RoleElevationDto> {
+    let elevation = state
+        .db
+        .role_elevations
+        .get(elevation_id.to_string())
+        .await?
+        .filter(|elevation| elevation.org_id == org_id);
+
+    elevation.context(NotFoundSnafu {
+        msg: "Elevation not found".to_string(),
+    })
+}
+
+/// Active member of the org that does not hold the role yet
+async fn find_elevatable_member(
+    state: &AppState,
+    org_id: &str,
+    user_id: &str,
+    role: &Role,
+) -> Result<OrgMemberDto> {
+    let member = state
+        .db
+        .org_members
+        .find_member(org_id.to_string(), user_id.to_string())
+        .await?
+        .context(NotFoundSnafu {
+            msg: "Org member not found".to_string(),
+        })?;
+
+    ensure!(
+        member.status == "active",
+        ValidationSnafu {
+            msg: "Only active members can be elevated".to_string(),
+        }
+    );
+
+    ensure!(
+        !member.roles.contains(role),
+        ValidationSnafu {
+            msg: format!("Member already has the {} role", role),
+        }
+    );
+
+    Ok(member)
+}
+
+async fn activate_elevation(
+    state: &AppState,
+    elevation: RoleElevationDto,
+    actor_id: &str,
+) -> Result<RoleElevationDto> {
+    let expires_at = Utc::now().timestamp_millis() + elevation.duration_mins * 60 * 1000;
+
+    let activated = state
+        .db
+        .role_elevations
+        .activate(elevation.id.clone(), actor_id.to_string(), expires_at)
+        .await?;
+
+    ensure!(
+        activated,
+        ValidationSnafu {
+            msg: "Elevation was already decided".to_string()
+        }
+    );
+
+    let member = state
+        .db
+        .org_members
+        .find_member(elevation.org_id.clone(), elevation.user_id.clone())
+        .await?
+        .context(NotFoundSnafu {
+            msg: "Org member not found".to_string(),
+        })?;
+
+    let mut roles = member.roles.clone();
+    roles.push(elevation.role.clone());
+    set_member_roles(state, &member, roles).await?;
+
+    schedule_job_svc(
+        &state.db,
+        JobKind::ElevationRevert,
+        &ElevationRevertJobDto {
+            elevation_id: elevation.id.clone(),
+        },
+        expires_at,
+    )
+    .await?;
+
+    warn!(
+        elevation_id = elevation.id.as_str(),
+        org_id = elevation.org_id.as_str(),
+        user_id = elevation.user_id.as_str(),
+        role = elevation.role.to_string(),
+        expires_at = expires_at,
+        requested_by = elevation.requested_by.as_str(),
+        decided_by = actor_id,
+        reason = elevation.reason.as_str(),
+        "elevation.granted"
+    );
+
+    get_elevation(state, &elevation.org_id, &elevation.id).await
+}
+
+/// Takes the role back and signs the member out, so tokens carrying the
+/// elevated role stop working. Returns false when it was not active.
+async fn revert_elevation(
+    state: &AppState,
+    elevation: &RoleElevationDto,
+    actor_id: Option<&str>,
+) -> Result<bool> {
+    let reverted = state
+        .db
+        .role_elevations
+        .end(
+            elevation.id.clone(),
+            ElevationStatus::Active,
+            ElevationStatus::Reverted,
+            actor_id.map(|id| id.to_string()),
+        )
+        .await?;
+
+    if !reverted {
+        return Ok(false);
+    }
+
+    // The member may have left the org in the meantime
+    if let Some(member) = state
+        .db
+        .org_members
+        .find_member(elevation.org_id.clone(), elevation.user_id.clone())
+        .await?
+        && member.roles.contains(&elevation.role)
+    {
+        let roles: Vec<Role> = member
+            .roles
+            .iter()
+            .filter(|role| **role != elevation.role)
+            .cloned()
+            .collect();
+        set_member_roles(state, &member, roles).await?;
+    }
+
+    revoke_user_tokens_svc(state, actor_id, &elevation.user_id).await?;
+
+    warn!(
+        elevation_id = elevation.id.as_str(),
+        org_id = elevation.org_id.as_str(),
+        user_id = elevation.user_id.as_str(),
+        role = elevation.role.to_string(),
+        reverted_by = actor_id,
+        expired = actor_id.is_none(),
+        "elevation.reverted"
+    );
+
+    Ok(true)
+}
+
+async fn set_member_roles(state: &AppState, member: &OrgMemberDto, roles: Vec<Role>) -> Result<()> {
+    let roles: Vec<String> = roles.iter().map(|role| role.to_string()).collect();
+
+    state
+        .db
+        .org_members
+        .update(
+            &AuditCtx::current(),
+            member.id.clone(),
+            UpdateOrgMemberDto {
+                roles: Some(roles),
+                status: None,
+            },
+        )
+        .await?;
+
+    invalidate_auth_cache(state, &member.user_id).await;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::Utc;
+
+    use crate::ctx::AuditCtx;
+    use crate::dto::{
+        CredentialsDto, ElevationStatus, JobStatus, NewOrgMemberDto, NewRoleElevationDto,
+        Permission, Role, SessionClientDto,
+    };
+    use crate::services::auth::{authenticate, authenticate_token_svc};
+    use crate::services::jobs::run_next_job_svc;
+    use crate::test::TestCtx;
+
+    use super::{approve_elevation_svc, elevated_until_svc, request_elevation_svc};
+
+    fn elevate_to_admin() -> NewRoleElevationDto {
+        NewRoleElevationDto {
+            role: "OrgAdmin".to_string(),
+            reason: "Incident 42".to_string(),
+            duration_mins: 60,
+        }
+    }
+
+    #[tokio::test]
+    async fn elevation_waits_for_approval_and_reverts_on_expiry() {
+        let mut ctx = TestCtx::new("elevations").await.expect("test ctx");
+        Arc::make_mut(&mut ctx.state.config)
+            .approvals
+            .elevation_approval = true;
+
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Elevation Owner",
+                "elevation.owner@example.com",
+                "password123",
+                "Elevation Org",
+            )
+            .await
+            .expect("auth fixture");
+        let approver = ctx
+            .seed_user_with_password(
+                "Elevation Approver",
+                "elevation.approver@example.com",
+                "password123",
+            )
+            .await
+            .expect("approver");
+        let member = ctx
+            .seed_user_with_password(
+                "Elevation Member",
+                "elevation.member@example.com",
+                "password123",
+            )
+            .await
+            .expect("member");
+
+        ctx.state
+            .db
+            .org_members
+            .create(
+                &AuditCtx::default(),
+                fixture.org.id.clone(),
+                NewOrgMemberDto {
+                    user_id: member.id.clone(),
+                    roles: vec!["OrgViewer".to_string()],
+                    status: "active".to_string(),
+                },
+            )
+            .await
+            .expect("member should be created");
+
+        let superuser_role = request_elevation_svc(
+            &ctx.state,
+            &fixture.user.id,
+            &fixture.org.id,
+            &member.id,
+            NewRoleElevationDto {
+                role: "Superuser".to_string(),
+                ..elevate_to_admin()
+            },
+        )
+        .await;
+        assert!(superuser_role.is_err(), "superuser is never elevated");
+
+        let pending = request_elevation_svc(
+            &ctx.state,
+            &fixture.user.id,
+            &fixture.org.id,
+            &member.id,
+            elevate_to_admin(),
+        )
+        .await
+        .expect("request");
+        assert_eq!(pending.status, ElevationStatus::Pending);
+
+        let duplicate = request_elevation_svc(
+            &ctx.state,
+            &fixture.user.id,
+            &fixture.org.id,
+            &member.id,
+            elevate_to_admin(),
+        )
+        .await;
+        assert!(duplicate.is_err(), "one open elevation per role");
+
+        let self_approved =
+            approve_elevation_svc(&ctx.state, &fixture.user.id, &fixture.org.id, &pending.id).await;
+        assert!(self_approved.is_err(), "the requester cannot approve");
+
+        let active = approve_elevation_svc(&ctx.state, &approver.id, &fixture.org.id, &pending.id)
+            .await
+            .expect("approve");
+        assert_eq!(active.status, ElevationStatus::Active);
+        assert_eq!(active.decided_by.as_deref(), Some(approver.id.as_str()));
+        let expires_at = active.expires_at.expect("expiry");
+
+        let membership = ctx
+            .state
+            .db
+            .org_members
+            .find_member(fixture.org.id.clone(), member.id.clone())
+            .await
+            .expect("find")
+            .expect("member");
+        assert!(membership.roles.contains(&Role::OrgAdmin));
+
+        let until = elevated_until_svc(&ctx.state, &fixture.org.id)
+            .await
+            .expect("elevated until");
+        assert_eq!(until.get(&member.id), Some(&expires_at));
+
+        let session = authenticate(
+            &ctx.state,
+            &CredentialsDto {
+                email: "elevation.member@example.com".to_string(),
+                password: "password123".to_string(),
+            },
+            SessionClientDto::default(),
+        )
+        .await
+        .expect("login");
+        let actor = authenticate_token_svc(&ctx.state, &session.token)
+            .await
+            .expect("elevated session");
+        assert!(actor.has_permissions(&[Permission::OrgMembersEdit]));
+
+        // The revert job is not due before the expiry
+        let status = run_next_job_svc(&ctx.state, Utc::now().timestamp_millis())
+            .await
+            .expect("job run");
+        assert_eq!(status, None);
+
+        let status = run_next_job_svc(&ctx.state, expires_at)
+            .await
+            .expect("job run");
+        assert_eq!(status, Some(JobStatus::Succeeded));
+
+        let membership = ctx
+            .state
+            .db
+            .org_members
+            .find_member(fixture.org.id.clone(), member.id.clone())
+            .await
+            .expect("find")
+            .expect("member");
+        assert_eq!(membership.roles, vec![Role::OrgViewer]);
+
+        assert!(
+            authenticate_token_svc(&ctx.state, &session.token)
+                .await
+                .is_err(),
+            "tokens carrying the elevated role are revoked"
+        );
+
+        let until = elevated_until_svc(&ctx.state, &fixture.org.id)
+            .await
+            .expect("elevated until");
+        assert!(until.is_empty());
+    }
+}
@@ -0,0 +1,224 @@
+use askama::Template;
+use snafu::ResultExt;
+use tracing::instrument;
+
+use crate::Result;
+use crate::db::DbMapper;
+use crate::dto::NewEmailDto;
+use crate::error::TemplateSnafu;
+
+/// Name and logo shown at the top of an email
+#[derive(Clone)]
+pub struct EmailBranding {
+    pub name: String,
+    pub logo_url: Option<String>,
+}
+
+impl EmailBranding {
+    /// Emails about the account itself and not about one org
+    pub fn yaas() -> Self {
+        Self {
+            name: "Yaas".to_string(),
+            logo_url: None,
+        }
+    }
+}
+
+/// Org name and logo from its settings, Yaas when the org is gone
+#[instrument(level = "debug", skip_all)]
+pub async fn org_branding_svc(db: &DbMapper, org_id: &str) -> Result<EmailBranding> {
+    let Some(org) = db.orgs.get(org_id.to_string()).await? else {
+        return Ok(EmailBranding::yaas());
+    };
+    let settings = db.org_settings.get(org_id.to_string()).await?;
+
+    Ok(EmailBranding {
+        name: org.name,
+        logo_url: settings.and_then(|settings| settings.logo_url),
+    })
+}
+
+/// Link button of an email
+pub struct EmailAction {
+    pub label: String,
+    pub url: String,
+}
+
+/// What an email says, rendered into both the text and the HTML body
+pub struct EmailContent {
+    pub subject: String,
+    pub paragraphs: Vec<String>,
+    pub action: Option<EmailAction>,
+    /// One-time code shown on its own line
+    pub code: Option<String>,
+    pub footer: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "emails/message.html")]
+struct HtmlEmailTemplate<'a> {
+    branding: &'a EmailBranding,
+    content: &'a EmailContent,
+}
+
+#[derive(Template)]
+#[template(path = "emails/message.txt", whitespace = "preserve")]
+struct TextEmailTemplate<'a> {
+    branding: &'a EmailBranding,
+    content: &'a EmailContent,
+}
+
+/// Renders the content with the branding into an email for the recipient
+pub fn render_email(
+    branding: &EmailBranding,
+    org_id: Option<String>,
+    recipient: &str,
+    content: EmailContent,
+) -> Result<NewEmailDto> {
+    let html_body = HtmlEmailTemplate {
+        branding,
+        content: &content,
+    }
+    .render()
+    .context(TemplateSnafu)?;
+
+    let text_body = TextEmailTemplate {
+        branding,
+        content: &content,
+    }
+    .render()
+    .context(TemplateSnafu)?;
+
+    Ok(NewEmailDto {
+        org_id,
+        recipient: recipient.to_string(),
+        subject: format!("{}: {}", branding.name, content.subject),
+        text_body,
+        html_body,
+    })
+}
+
+pub fn password_reset_email(url: String, ttl_mins: i64) -> EmailContent {
+    EmailContent {
+        subject: "Reset your password".to_string(),
+        paragraphs: vec![
+            "Someone asked to reset the password of your account.".to_string(),
+            format!(
+                "The link below works once and expires in {} minutes.",
+                ttl_mins
+            ),
+        ],
+        action: Some(EmailAction {
+            label: "Reset password".to_string(),
+            url,
+        }),
+        code: None,
+        footer: Some("If this was not you, ignore this email.".to_string()),
+    }
+}
+
+pub fn org_invitation_email(org_name: &str, url: String, ttl_hours: i64) -> EmailContent {
+    EmailContent {
+        subject: format!("Join {}", org_name),
+        paragraphs: vec![
+            format!("You are invited to join {}.", org_name),
+            format!("The invitation expires in {} hours.", ttl_hours),
+        ],
+        action: Some(EmailAction {
+            label: "Accept invitation".to_string(),
+            url,
+        }),
+        code: None,
+        footer: Some("If you were not expecting this, ignore this email.".to_string()),
+    }
+}
+
+pub fn org_owner_transfer_email(org_name: &str, url: String, ttl_hours: i64) -> EmailContent {
+    EmailContent {
+        subject: format!("Take over {}", org_name),
+        paragraphs: vec![
+            format!("You were asked to become the owner of {}.", org_name),
+            format!(
+                "Ownership only changes once you accept, the request expires in {} hours.",
+                ttl_hours
+            ),
+        ],
+        action: Some(EmailAction {
+            label: "Review transfer".to_string(),
+            url,
+        }),
+        code: None,
+        footer: Some("If you were not expecting this, ignore this email.".to_string()),
+    }
+}
+
+pub fn email_verification_email(code: String) -> EmailContent {
+    EmailContent {
+        subject: "Verify your email".to_string(),
+        paragraphs: vec![
+            "Enter this code on your profile to verify the email address.".to_string(),
+        ],
+        action: None,
+        code: Some(code),
+        footer: Some("If you did not add this address, ignore this email.".to_string()),
+    }
+}
+
+/// Plain notification, one paragraph per line of the body
+pub fn notification_email(subject: &str, body: &str) -> EmailContent {
+    EmailContent {
+        subject: subject.to_string(),
+        paragraphs: body.lines().map(|line| line.to_string()).collect(),
+        action: None,
+        code: None,
+        footer: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EmailBranding, password_reset_email, render_email};
+
+    #[test]
+    fn render_email_applies_the_branding_to_both_bodies() {
+        let branding = EmailBranding {
+            name: "Acme <Corp>".to_string(),
+            logo_url: Some("https://acme.example.com/logo.png".to_string()),
+        };
+        let content = password_reset_email(
+            "https://yaas.example.com/reset-password?token=abc".to_string(),
+            30,
+        );
+
+        let email = render_email(
+            &branding,
+            Some("org_1".to_string()),
+            "user@example.com",
+            content,
+        )
+        .expect("render");
+
+        assert_eq!(email.subject, "Acme <Corp>: Reset your password");
+        assert_eq!(email.recipient, "user@example.com");
+
+        assert!(email.html_body.contains("Acme &#60;Corp&#62;"));
+        assert!(
+            email
+                .html_body
+                .contains("https://acme.example.com/logo.png")
+        );
+        assert!(
+            email
+                .html_body
+                .contains("https://yaas.example.com/reset-password?token=abc")
+        );
+
+        assert!(email.text_body.starts_with("Acme <Corp>\n"));
+        assert!(
+            email
+                .text_body
+                .contains("Reset password: https://yaas.example.com/reset-password?token=abc\n")
+        );
+        assert!(email.text_body.contains("expires in 30 minutes"));
+    }
+}
@@ -0,0 +1,216 @@
+use serde_json::json;
+
+use crate::dto::{
+    EventSchemaDto, EventSchemasDto, LIFECYCLE_SCHEMA_VERSION, LIFECYCLE_TOPICS, LifecycleTopic,
+};
+
+/// Schemas of every event posted to subscribers.
+///
+/// Written by hand next to `LifecycleEventDto`, the tests fail when the
+/// payload and its schema drift apart. Adding optional fields is fine,
+/// anything else needs a `LIFECYCLE_SCHEMA_VERSION` bump.
+pub fn list_event_schemas_svc() -> EventSchemasDto {
+    EventSchemasDto {
+        schemas: LIFECYCLE_TOPICS
+            .iter()
+            .map(|topic| lifecycle_event_schema(*topic))
+            .collect(),
+    }
+}
+
+pub fn lifecycle_event_schema(topic: LifecycleTopic) -> EventSchemaDto {
+    let membership = topic.is_membership();
+
+    let mut required = vec![
+        "id",
+        "topic",
+        "schema_version",
+        "occurred_at",
+        "external_ids",
+        "user",
+    ];
+    let mut external_ids_required = vec!["yaas_user_id"];
+    if membership {
+        required.push("membership");
+        external_ids_required.push("yaas_org_id");
+    }
+
+    let mut properties = json!({
+        "id": {
+            "type": "string",
+            "description": "Event id, also sent as the X-Yaas-Event-Id header"
+        },
+        "topic": { "const": topic.to_string() },
+        "schema_version": { "const": LIFECYCLE_SCHEMA_VERSION },
+        "occurred_at": {
+            "type": "integer",
+            "description": "Unix timestamp in milliseconds"
+        },
+        "external_ids": {
+            "type": "object",
+            "required": external_ids_required,
+            "properties": {
+                "yaas_user_id": { "type": "string" },
+                "yaas_org_id": { "type": "string" }
+            }
+        },
+        "user": {
+            "type": "object",
+            "required": ["email", "name", "status"],
+            "properties": {
+                "email": { "type": "string" },
+                "name": { "type": "string" },
+                "status": { "type": "string" }
+            }
+        },
+        "changed_fields": {
+            "type": "array",
+            "items": { "type": "string" },
+            "description": "Fields changed by the update that triggered the event, omitted when empty"
+        }
+    });
+
+    if membership {
+        properties["membership"] = json!({
+            "type": "object",
+            "required": ["org_id", "roles", "status"],
+            "properties": {
+                "org_id": { "type": "string" },
+                "roles": { "type": "array", "items": { "type": "string" } },
+                "status": { "type": "string" }
+            }
+        });
+    }
+
+    EventSchemaDto {
+        event: topic.to_string(),
+        version: LIFECYCLE_SCHEMA_VERSION,
+        schema: json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": format!("urn:yaas:events:{}:v{}", topic, LIFECYCLE_SCHEMA_VERSION),
+            "title": topic.to_string(),
+            "description": "Posted as JSON, signed with HMAC-SHA256 of the body in the X-Yaas-Signature header",
+            "type": "object",
+            "required": required,
+            "properties": properties
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::{
+        LIFECYCLE_SCHEMA_VERSION, LIFECYCLE_TOPICS, LifecycleEventDto, LifecycleExternalIdsDto,
+        LifecycleMembershipDto, LifecycleUserDto,
+    };
+
+    use serde_json::Value;
+
+    use super::{lifecycle_event_schema, list_event_schemas_svc};
+
+    /// Schema violations of a payload, only the parts the event schemas use
+    fn schema_violations(schema: &Value, value: &Value, path: &str) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        if let Some(expected) = schema.get("const") {
+            if expected != value {
+                violations.push(format!("{}: expected {}", path, expected));
+            }
+            return violations;
+        }
+
+        match schema["type"].as_str() {
+            Some("object") => {
+                let Some(object) = value.as_object() else {
+                    return vec![format!("{}: expected an object", path)];
+                };
+                for key in schema["required"].as_array().into_iter().flatten() {
+                    let key = key.as_str().unwrap_or_default();
+                    if !object.contains_key(key) {
+                        violations.push(format!("{}.{}: missing", path, key));
+                    }
+                }
+                for (key, item) in object.iter() {
+                    match schema["properties"].get(key) {
+                        Some(item_schema) => violations.extend(schema_violations(
+                            item_schema,
+                            item,
+                            &format!("{}.{}", path, key),
+                        )),
+                        None => violations.push(format!("{}.{}: not in the schema", path, key)),
+                    }
+                }
+            }
+            Some("array") => match value.as_array() {
+                Some(items) => {
+                    for (index, item) in items.iter().enumerate() {
+                        violations.extend(schema_violations(
+                            &schema["items"],
+                            item,
+                            &format!("{}[{}]", path, index),
+                        ));
+                    }
+                }
+                None => violations.push(format!("{}: expected an array", path)),
+            },
+            Some("string") if !value.is_string() => {
+                violations.push(format!("{}: expected a string", path))
+            }
+            Some("integer") if !value.is_i64() => {
+                violations.push(format!("{}: expected an integer", path))
+            }
+            _ => {}
+        }
+
+        violations
+    }
+
+    fn sample_event(topic: crate::dto::LifecycleTopic) -> LifecycleEventDto {
+        let membership = topic.is_membership();
+        LifecycleEventDto {
+            id: "lce_sample".to_string(),
+            topic,
+            schema_version: LIFECYCLE_SCHEMA_VERSION,
+            occurred_at: 1_700_000_000_000,
+            external_ids: LifecycleExternalIdsDto {
+                yaas_user_id: "usr_sample".to_string(),
+                yaas_org_id: membership.then(|| "org_sample".to_string()),
+            },
+            user: LifecycleUserDto {
+                email: "sample@example.com".to_string(),
+                name: "Sample".to_string(),
+                status: "active".to_string(),
+            },
+            membership: membership.then(|| LifecycleMembershipDto {
+                org_id: "org_sample".to_string(),
+                roles: vec!["OrgViewer".to_string()],
+                status: "active".to_string(),
+            }),
+            changed_fields: vec!["status".to_string()],
+        }
+    }
+
+    #[test]
+    fn payloads_match_their_published_schema() {
+        for topic in LIFECYCLE_TOPICS {
+            let schema = lifecycle_event_schema(topic);
+            let payload = serde_json::to_value(sample_event(topic)).unwrap();
+            let violations = schema_violations(&schema.schema, &payload, "$");
+            assert!(violations.is_empty(), "{}: {:?}", topic, violations);
+        }
+
+        // Required fields are really required
+        let schema = lifecycle_event_schema(LIFECYCLE_TOPICS[2]);
+        let mut payload = serde_json::to_value(sample_event(LIFECYCLE_TOPICS[2])).unwrap();
+        payload.as_object_mut().unwrap().remove("membership");
+        assert_eq!(
+            schema_violations(&schema.schema, &payload, "$"),
+            vec!["$.membership: missing".to_string()]
+        );
+
+        assert_eq!(
+            list_event_schemas_svc().schemas.len(),
+            LIFECYCLE_TOPICS.len()
+        );
+    }
+}
@@ -1,9 +1,10 @@
 use serde::Serialize;
 use std::sync::Arc;
-use tracing::error;
+use tracing::{error, instrument};
 
 use crate::Result;
-use crate::db::DbMapper;
+use crate::db::{DbMapper, MIGRATIONS, Migration};
+use crate::dto::MigrationPhase;
 
 #[derive(Serialize)]
 pub struct LiveStatus {
@@ -15,11 +16,16 @@ pub struct HealthStatus {
     pub status: String,
     pub message: String,
     pub checks: HealthChecks,
+
+    /// Migrations not yet recorded in the database
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pending_migrations: Vec<String>,
 }
 
 #[derive(Serialize)]
 pub struct HealthChecks {
     pub database: String,
+    pub migrations: String,
 }
 
 impl HealthStatus {
@@ -32,14 +38,16 @@ impl HealthChecks {
     pub fn new() -> Self {
         Self {
             database: "DOWN".to_string(),
+            migrations: "DOWN".to_string(),
         }
     }
 
     pub fn is_healthy(&self) -> bool {
-        self.database == "UP"
+        self.database == "UP" && self.migrations == "UP"
     }
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn check_liveness() -> Result<LiveStatus> {
     // Nothing much to check, if it hits this function, it's alive
     Ok(LiveStatus {
@@ -47,8 +55,24 @@ pub async fn check_liveness() -> Result<LiveStatus> {
     })
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn check_readiness(db: Arc<DbMapper>) -> Result<HealthStatus> {
-    let checks = perform_checks(db).await?;
+    perform_checks(db, MIGRATIONS).await
+}
+
+async fn perform_checks(db: Arc<DbMapper>, migrations: &[Migration]) -> Result<HealthStatus> {
+    let mut checks = HealthChecks::new();
+    let mut pending_migrations: Vec<String> = Vec::new();
+
+    checks.database = check_database(db.clone()).await?;
+
+    // Migrations can only be checked once the database answers
+    if checks.database == "UP" {
+        let (status, pending) = check_migrations(db, migrations).await?;
+        checks.migrations = status;
+        pending_migrations = pending;
+    }
+
     let mut status = "DOWN".to_string();
     let mut message = "One or more health checks are failing".to_string();
 
@@ -61,17 +85,10 @@ pub async fn check_readiness(db: Arc<DbMapper>) -> Result<HealthStatus> {
         status,
         message,
         checks,
+        pending_migrations,
     })
 }
 
-async fn perform_checks(db: Arc<DbMapper>) -> Result<HealthChecks> {
-    let mut checks = HealthChecks::new();
-
-    checks.database = check_database(db).await?;
-
-    Ok(checks)
-}
-
 async fn check_database(db: Arc<DbMapper>) -> Result<String> {
     match db.orgs.test_read().await {
         Ok(_) => Ok("UP".to_string()),
@@ -83,9 +100,42 @@ async fn check_database(db: Arc<DbMapper>) -> Result<String> {
     }
 }
 
+/// Pending contract migrations are expected while older servers still run,
+/// only pending expand migrations mean this release's schema is missing.
+async fn check_migrations(
+    db: Arc<DbMapper>,
+    migrations: &[Migration],
+) -> Result<(String, Vec<String>)> {
+    // A database never migrated has no history table yet
+    let tables = db.schema.list_tables().await?;
+    let applied = match tables.iter().any(|table| table == "schema_migrations") {
+        true => db.schema.list_applied_migrations().await?,
+        false => Vec::new(),
+    };
+
+    let pending: Vec<String> = migrations
+        .iter()
+        .filter(|migration| !applied.iter().any(|name| name == migration.name))
+        .map(|migration| migration.name.to_string())
+        .collect();
+
+    let status = match pending
+        .iter()
+        .any(|name| MigrationPhase::from_name(name) == MigrationPhase::Expand)
+    {
+        true => "DOWN".to_string(),
+        false => "UP".to_string(),
+    };
+
+    Ok((status, pending))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{check_liveness, check_readiness};
+    use super::{check_liveness, check_readiness, perform_checks};
+    use crate::db::{MIGRATIONS, Migration};
+    use crate::dto::MigrateOptionsDto;
+    use crate::services::migrations::migrate_svc;
     use crate::test::TestCtx;
 
     #[tokio::test]
@@ -101,6 +151,14 @@ mod tests {
             .await
             .expect("test context should initialize");
 
+        let options = MigrateOptionsDto {
+            baseline: true,
+            ..Default::default()
+        };
+        migrate_svc(&ctx.state.db, MIGRATIONS, options)
+            .await
+            .expect("baseline should succeed");
+
         let status = check_readiness(ctx.state.db.clone())
             .await
             .expect("readiness should succeed");
@@ -108,6 +166,66 @@ mod tests {
         assert_eq!(status.status, "UP");
         assert_eq!(status.message, "All health checks are passing");
         assert_eq!(status.checks.database, "UP");
+        assert_eq!(status.checks.migrations, "UP");
+        assert!(status.pending_migrations.is_empty());
         assert!(status.is_healthy());
     }
+
+    #[tokio::test]
+    async fn check_readiness_returns_down_with_pending_expand_migrations() {
+        let ctx = TestCtx::new("health_readiness_pending")
+            .await
+            .expect("test context should initialize");
+
+        // No migration history at all
+        let status = check_readiness(ctx.state.db.clone())
+            .await
+            .expect("readiness should succeed");
+        assert_eq!(status.checks.database, "UP");
+        assert_eq!(status.checks.migrations, "DOWN");
+        assert_eq!(status.pending_migrations.len(), MIGRATIONS.len());
+        assert!(!status.is_healthy());
+
+        let options = MigrateOptionsDto {
+            baseline: true,
+            ..Default::default()
+        };
+        migrate_svc(&ctx.state.db, MIGRATIONS, options)
+            .await
+            .expect("baseline should succeed");
+
+        let contract = Migration {
+            name: "99-contract-drop-legacy.sql",
+            sql: "DROP TABLE legacy;",
+        };
+        let all: Vec<Migration> = MIGRATIONS
+            .iter()
+            .copied()
+            .chain(std::iter::once(contract))
+            .collect();
+        let status = perform_checks(ctx.state.db.clone(), &all)
+            .await
+            .expect("readiness should succeed");
+        assert_eq!(status.checks.migrations, "UP");
+        assert_eq!(
+            status.pending_migrations,
+            vec!["99-contract-drop-legacy.sql"]
+        );
+
+        let expand = Migration {
+            name: "99-add-users-nickname.sql",
+            sql: "ALTER TABLE users ADD COLUMN nickname TEXT DEFAULT NULL;",
+        };
+        let all: Vec<Migration> = MIGRATIONS
+            .iter()
+            .copied()
+            .chain(std::iter::once(expand))
+            .collect();
+        let status = perform_checks(ctx.state.db.clone(), &all)
+            .await
+            .expect("readiness should succeed");
+        assert_eq!(status.status, "DOWN");
+        assert_eq!(status.checks.migrations, "DOWN");
+        assert!(!status.is_healthy());
+    }
 }
@@ -0,0 +1,165 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use ring::digest::{SHA256, digest};
+use snafu::ensure;
+use tracing::{error, info, instrument};
+
+use crate::Result;
+use crate::db::DbMapper;
+use crate::dto::{IdempotencyKeyDto, IdempotentResponseDto};
+use crate::error::{
+    IdempotencyKeyInProgressSnafu, IdempotencyKeyNotReplayableSnafu, IdempotencyKeyReusedSnafu,
+};
+use crate::run::AppState;
+
+/// Responses are replayed for a day after the first request
+const IDEMPOTENCY_KEY_TTL_HOURS: i64 = 24;
+
+/// A key still in progress after this time was left by a request that never
+/// finished, a retry may claim it again
+const IDEMPOTENCY_KEY_LEASE_SECS: i64 = 2 * 60;
+
+/// Keys are scoped to the actor and the route, clients pick the key itself
+#[derive(Clone, Debug)]
+pub struct IdempotencyScope {
+    pub user_id: String,
+    pub route: String,
+    pub key: String,
+}
+
+pub enum IdempotencyOutcome {
+    /// First time the key is seen, the request must run
+    Reserved,
+    /// Retry of a finished request
+    Replay(IdempotentResponseDto),
+}
+
+/// SHA-256 of a request or response body
+pub fn idempotency_body_hash(body: &[u8]) -> String {
+    hex::encode(digest(&SHA256, body))
+}
+
+/// Claims the key for the request or returns the stored response.
+///
+/// Fails when the first request is still running, the key was used with
+/// another body or the first response held a secret that is not kept.
+#[instrument(level = "debug", skip_all)]
+pub async fn reserve_idempotency_key_svc(
+    state: &AppState,
+    scope: &IdempotencyScope,
+    request_hash: String,
+) -> Result<IdempotencyOutcome> {
+    let now = Utc::now().timestamp_millis();
+    let existing = state
+        .db
+        .idempotency_keys
+        .get(
+            scope.user_id.clone(),
+            scope.route.clone(),
+            scope.key.clone(),
+            now,
+        )
+        .await?;
+
+    if let Some(existing) = existing {
+        ensure!(
+            existing.request_hash == request_hash,
+            IdempotencyKeyReusedSnafu
+        );
+
+        return match (existing.status_code, existing.response_hash) {
+            (Some(status_code), Some(response_hash)) => {
+                ensure!(existing.body.is_some(), IdempotencyKeyNotReplayableSnafu);
+
+                Ok(IdempotencyOutcome::Replay(IdempotentResponseDto {
+                    status_code,
+                    content_type: existing.content_type,
+                    body: existing.body,
+                    response_hash,
+                }))
+            }
+            _ => IdempotencyKeyInProgressSnafu.fail(),
+        };
+    }
+
+    let reserved = state
+        .db
+        .idempotency_keys
+        .reserve(IdempotencyKeyDto {
+            user_id: scope.user_id.clone(),
+            route: scope.route.clone(),
+            idempotency_key: scope.key.clone(),
+            request_hash,
+            status_code: None,
+            content_type: None,
+            body: None,
+            response_hash: None,
+            created_at: now,
+            expires_at: now + IDEMPOTENCY_KEY_LEASE_SECS * 1000,
+        })
+        .await?;
+
+    // Another retry got the key first
+    ensure!(reserved, IdempotencyKeyInProgressSnafu);
+
+    Ok(IdempotencyOutcome::Reserved)
+}
+
+/// Stores the response replayed to retries for the next 24 hours
+#[instrument(level = "debug", skip_all)]
+pub async fn complete_idempotency_key_svc(
+    state: &AppState,
+    scope: &IdempotencyScope,
+    response: IdempotentResponseDto,
+) -> Result<()> {
+    let expires_at = Utc::now().timestamp_millis() + IDEMPOTENCY_KEY_TTL_HOURS * 60 * 60 * 1000;
+
+    state
+        .db
+        .idempotency_keys
+        .complete(
+            scope.user_id.clone(),
+            scope.route.clone(),
+            scope.key.clone(),
+            response,
+            expires_at,
+        )
+        .await
+}
+
+/// Forgets the key of a failed request so it can be retried
+#[instrument(level = "debug", skip_all)]
+pub async fn release_idempotency_key_svc(state: &AppState, scope: &IdempotencyScope) -> Result<()> {
+    state
+        .db
+        .idempotency_keys
+        .delete(
+            scope.user_id.clone(),
+            scope.route.clone(),
+            scope.key.clone(),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Removes expired idempotency keys every hour
+pub async fn idempotency_cleanup_job(db: Arc<DbMapper>) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(60 * 60));
+
+    loop {
+        ticker.tick().await;
+
+        match db
+            .idempotency_keys
+            .delete_expired(Utc::now().timestamp_millis())
+            .await
+        {
+            Ok(0) => {}
+            Ok(removed) => info!(removed = removed, "Expired idempotency keys removed"),
+            Err(e) => error!("Idempotency key cleanup failed: {}", e),
+        }
+    }
+}
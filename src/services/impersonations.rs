@@ -0,0 +1,361 @@
+use chrono::Utc;
+use snafu::{OptionExt, ensure};
+use tracing::{instrument, warn};
+
+use crate::Result;
+use crate::dto::{
+    ActorDto, ActorPayloadDto, ImpersonationDto, ImpersonationLogDto, ImpersonationTokenDto,
+    ListingParamsDto, Scope,
+};
+use crate::error::{
+    CsrfTokenSnafu, ForbiddenSnafu, InactiveUserSnafu, InvalidAuthTokenSnafu, UserNoOrgSnafu,
+    UserNotFoundSnafu, ValidationSnafu,
+};
+use crate::run::AppState;
+use crate::services::token::{create_auth_token, verify_csrf_token};
+
+/// How long an impersonation lasts unless stopped earlier
+pub const IMPERSONATION_MINS: i64 = 60;
+
+/// Impersonations listed per user, newest first
+const IMPERSONATIONS_LIST_LIMIT: i64 = 20;
+
+/// Requests listed per impersonation
+const IMPERSONATION_ACTIONS_LIMIT: i64 = 200;
+
+/// Issues a token acting as the user on behalf of a superuser.
+///
+/// The token carries the impersonator and only works while the impersonation
+/// runs. Superusers cannot be impersonated, so the token never reaches the
+/// admin API.
+#[instrument(level = "debug", skip_all)]
+pub async fn start_impersonation_svc(
+    state: &AppState,
+    impersonator_id: &str,
+    user_id: &str,
+) -> Result<ImpersonationTokenDto> {
+    let impersonator = state.db.superusers.get(impersonator_id.to_string()).await?;
+    ensure!(
+        impersonator.is_some(),
+        ForbiddenSnafu {
+            msg: "Only superusers can impersonate users.".to_string(),
+        }
+    );
+    ensure!(
+        impersonator_id != user_id,
+        ValidationSnafu {
+            msg: "You cannot impersonate yourself".to_string(),
+        }
+    );
+
+    let user = state
+        .db
+        .users
+        .get(user_id.to_string())
+        .await?
+        .context(UserNotFoundSnafu)?;
+    ensure!(&user.status == "active", InactiveUserSnafu);
+
+    let superuser = state.db.superusers.get(user.id.clone()).await?;
+    ensure!(
+        superuser.is_none(),
+        ForbiddenSnafu {
+            msg: "Superusers cannot be impersonated.".to_string(),
+        }
+    );
+
+    // Same org the user would land on after signing in
+    let memberships = state
+        .db
+        .org_members
+        .list_memberships(
+            user.id.clone(),
+            ListingParamsDto {
+                page: Some(1),
+                per_page: Some(1),
+            },
+        )
+        .await?;
+    let membership = memberships.data.first().context(UserNoOrgSnafu)?;
+
+    let expires_at = Utc::now().timestamp_millis() + IMPERSONATION_MINS * 60 * 1000;
+    let impersonation = state
+        .db
+        .impersonations
+        .create(
+            impersonator_id.to_string(),
+            user.id.clone(),
+            membership.org_id.clone(),
+            expires_at,
+        )
+        .await?;
+
+    let actor = ActorPayloadDto {
+        id: user.id.clone(),
+        org_id: membership.org_id.clone(),
+        org_count: memberships.meta.total_records as i32,
+        roles: membership.roles.clone(),
+        scopes: vec![Scope::Auth],
+        grant_id: None,
+        proof_key_id: None,
+        permission_mask: None,
+        issued_at: 0,
+        session_id: None,
+        region: state.config.region.name.clone(),
+        home_region: state.config.region.name.clone(),
+        token_id: None,
+        org_app_id: None,
+        impersonator_id: Some(impersonator_id.to_string()),
+        impersonation_id: Some(impersonation.id.clone()),
+    };
+
+    let token = create_auth_token(&actor, &state.token_keys)?;
+
+    warn!(
+        impersonation_id = impersonation.id,
+        impersonator_id = impersonator_id,
+        user_id = user.id,
+        "user.impersonation_started"
+    );
+
+    Ok(ImpersonationTokenDto {
+        token,
+        impersonation,
+    })
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn start_impersonation_web_svc(
+    state: &AppState,
+    impersonator_id: &str,
+    user_id: &str,
+    csrf_token: &str,
+) -> Result<ImpersonationTokenDto> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == user_id, CsrfTokenSnafu);
+
+    start_impersonation_svc(state, impersonator_id, user_id).await
+}
+
+/// Rejects impersonation tokens once the impersonation ended or expired
+#[instrument(level = "debug", skip_all)]
+pub async fn verify_impersonation_svc(state: &AppState, payload: &ActorPayloadDto) -> Result<()> {
+    let Some(impersonation_id) = payload.impersonation_id.clone() else {
+        return Ok(());
+    };
+
+    let impersonation = state
+        .db
+        .impersonations
+        .get(impersonation_id)
+        .await?
+        .context(InvalidAuthTokenSnafu)?;
+
+    ensure!(
+        impersonation.is_active(Utc::now().timestamp_millis())
+            && impersonation.user_id == payload.id
+            && payload.impersonator_id.as_deref() == Some(impersonation.impersonator_id.as_str()),
+        InvalidAuthTokenSnafu
+    );
+
+    Ok(())
+}
+
+/// Ends the impersonation the actor's token was issued for
+#[instrument(level = "debug", skip_all)]
+pub async fn stop_impersonation_svc(
+    state: &AppState,
+    actor: &ActorDto,
+) -> Result<ImpersonationDto> {
+    let impersonation_id = actor.impersonation_id.clone().context(ValidationSnafu {
+        msg: "You are not impersonating anyone".to_string(),
+    })?;
+
+    state
+        .db
+        .impersonations
+        .end(impersonation_id.clone(), Utc::now().timestamp_millis())
+        .await?;
+
+    let impersonation = state
+        .db
+        .impersonations
+        .get(impersonation_id)
+        .await?
+        .context(InvalidAuthTokenSnafu)?;
+
+    warn!(
+        impersonation_id = impersonation.id,
+        impersonator_id = impersonation.impersonator_id,
+        user_id = impersonation.user_id,
+        "user.impersonation_stopped"
+    );
+
+    Ok(impersonation)
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn stop_impersonation_web_svc(
+    state: &AppState,
+    actor: &ActorDto,
+    csrf_token: &str,
+) -> Result<ImpersonationDto> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == "stop_impersonating", CsrfTokenSnafu);
+
+    stop_impersonation_svc(state, actor).await
+}
+
+/// Adds a request to the audit trail, failures are logged and do not fail the request
+pub async fn record_impersonation_action(
+    state: &AppState,
+    impersonation_id: &str,
+    method: &str,
+    path: &str,
+    status: u16,
+) {
+    let result = state
+        .db
+        .impersonations
+        .record_action(
+            impersonation_id.to_string(),
+            method.to_string(),
+            path.to_string(),
+            status as i64,
+        )
+        .await;
+
+    if let Err(err) = result {
+        warn!(
+            impersonation_id = impersonation_id,
+            error = %err,
+            "impersonation.record_action_failed"
+        );
+    }
+}
+
+/// Latest impersonations of the user with the requests made during each
+#[instrument(level = "debug", skip_all)]
+pub async fn list_user_impersonations_svc(
+    state: &AppState,
+    user_id: &str,
+) -> Result<Vec<ImpersonationLogDto>> {
+    let impersonations = state
+        .db
+        .impersonations
+        .list_by_user(user_id.to_string(), IMPERSONATIONS_LIST_LIMIT)
+        .await?;
+
+    let mut items = Vec::with_capacity(impersonations.len());
+    for impersonation in impersonations.into_iter() {
+        let actions = state
+            .db
+            .impersonations
+            .list_actions(impersonation.id.clone(), IMPERSONATION_ACTIONS_LIMIT)
+            .await?;
+        items.push(ImpersonationLogDto {
+            impersonation,
+            actions,
+        });
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+    use crate::services::auth::authenticate_token_svc;
+    use crate::test::TestCtx;
+
+    use super::{
+        list_user_impersonations_svc, record_impersonation_action, start_impersonation_svc,
+        stop_impersonation_svc,
+    };
+
+    #[tokio::test]
+    async fn impersonation_token_works_until_stopped() {
+        let ctx = TestCtx::new("impersonation_stop").await.expect("test ctx");
+        let superuser = ctx
+            .seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Customer",
+                "customer@example.com",
+                "password123",
+                "Customer Org",
+            )
+            .await
+            .expect("auth fixture");
+
+        let started = start_impersonation_svc(&ctx.state, &superuser.id, &fixture.user.id)
+            .await
+            .expect("start");
+        let actor = authenticate_token_svc(&ctx.state, &started.token)
+            .await
+            .expect("impersonation token authenticates");
+        assert_eq!(actor.impersonator_id(), Some(superuser.id.as_str()));
+        let actor = actor.actor.expect("user actor");
+        assert_eq!(actor.id, fixture.user.id);
+        assert_eq!(actor.org_id, fixture.org.id);
+
+        record_impersonation_action(
+            &ctx.state,
+            &started.impersonation.id,
+            "POST",
+            "/profile/update",
+            200,
+        )
+        .await;
+
+        let stopped = stop_impersonation_svc(&ctx.state, &actor)
+            .await
+            .expect("stop");
+        assert!(stopped.ended_at.is_some());
+        assert!(
+            authenticate_token_svc(&ctx.state, &started.token)
+                .await
+                .is_err()
+        );
+
+        let logs = list_user_impersonations_svc(&ctx.state, &fixture.user.id)
+            .await
+            .expect("logs");
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].impersonation.impersonator_id, superuser.id);
+        assert_eq!(logs[0].actions.len(), 1);
+        assert_eq!(logs[0].actions[0].path, "/profile/update");
+    }
+
+    #[tokio::test]
+    async fn only_superusers_impersonate_and_never_each_other() {
+        let ctx = TestCtx::new("impersonation_rules").await.expect("test ctx");
+        let superuser = ctx
+            .seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+        let other = ctx
+            .seed_superuser("other.root@example.com")
+            .await
+            .expect("other superuser");
+        let fixture = ctx
+            .seed_auth_fixture("Member", "member@example.com", "password123", "Member Org")
+            .await
+            .expect("auth fixture");
+        let peer = ctx
+            .seed_auth_fixture("Peer", "peer@example.com", "password123", "Peer Org")
+            .await
+            .expect("peer fixture");
+
+        let result = start_impersonation_svc(&ctx.state, &fixture.user.id, &peer.user.id).await;
+        assert!(matches!(result, Err(Error::Forbidden { .. })));
+
+        let result = start_impersonation_svc(&ctx.state, &superuser.id, &other.id).await;
+        assert!(matches!(result, Err(Error::Forbidden { .. })));
+
+        let result = start_impersonation_svc(&ctx.state, &superuser.id, &superuser.id).await;
+        assert!(matches!(result, Err(Error::Validation { .. })));
+    }
+}
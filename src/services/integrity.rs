@@ -0,0 +1,165 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tracing::{error, info, instrument, warn};
+
+use crate::db::DbMapper;
+use crate::dto::IntegrityReportDto;
+use crate::{Error, Result};
+
+/// Prevents overlapping scans within the same process (scheduled job vs manual runs)
+static SCAN_RUNNING: AtomicBool = AtomicBool::new(false);
+
+struct ScanGuard;
+
+impl ScanGuard {
+    fn acquire() -> Result<Self> {
+        if SCAN_RUNNING
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return Err(Error::Service {
+                msg: "An integrity scan is already running".to_string(),
+            });
+        }
+        Ok(Self)
+    }
+}
+
+impl Drop for ScanGuard {
+    fn drop(&mut self) {
+        SCAN_RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Scans for orphaned records and optionally removes them
+#[instrument(level = "debug", skip_all)]
+pub async fn integrity_scan_svc(db: &DbMapper, repair: bool) -> Result<IntegrityReportDto> {
+    let _guard = ScanGuard::acquire()?;
+
+    let items = match repair {
+        true => db.integrity.delete_orphans().await?,
+        false => db.integrity.count_orphans().await?,
+    };
+
+    let report = IntegrityReportDto {
+        items,
+        repaired: repair,
+    };
+
+    for item in report.items.iter().filter(|item| item.count > 0) {
+        warn!(
+            kind = item.kind.as_str(),
+            count = item.count,
+            repaired = repair,
+            "integrity_scan.orphans"
+        );
+    }
+    info!(
+        total = report.total(),
+        repaired = repair,
+        "integrity_scan.completed"
+    );
+
+    Ok(report)
+}
+
+/// Periodically scans for orphans without repairing them
+pub async fn integrity_scan_job(db: Arc<DbMapper>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    // Skip the immediate first tick so startup is not slowed down
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+
+        if let Err(e) = integrity_scan_svc(&db, false).await {
+            error!("Integrity scan failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::integrity_scan_svc;
+    use crate::ctx::AuditCtx;
+    use crate::dto::NewOrgMemberDto;
+    use crate::test::TestCtx;
+
+    #[tokio::test]
+    async fn integrity_scan_svc_reports_and_repairs_orphans() {
+        let ctx = TestCtx::new("integrity_scan").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Integrity Owner",
+                "integrity.owner@example.com",
+                "password123",
+                "Integrity Org",
+            )
+            .await
+            .expect("auth fixture");
+        let member = ctx
+            .seed_user_with_password(
+                "Integrity Member",
+                "integrity.member@example.com",
+                "password123",
+            )
+            .await
+            .expect("member user");
+
+        ctx.state
+            .db
+            .org_members
+            .create(
+                &AuditCtx::default(),
+                fixture.org.id.clone(),
+                NewOrgMemberDto {
+                    user_id: member.id.clone(),
+                    roles: vec!["OrgViewer".to_string()],
+                    status: "active".to_string(),
+                },
+            )
+            .await
+            .expect("member should be created");
+
+        let report = integrity_scan_svc(&ctx.state.db, false)
+            .await
+            .expect("scan should pass");
+        assert_eq!(report.total(), 0);
+
+        // Soft delete the user directly, leaving the membership behind
+        ctx.state
+            .db
+            .users
+            .delete(&AuditCtx::default(), member.id.clone())
+            .await
+            .expect("user should be deleted");
+
+        let report = integrity_scan_svc(&ctx.state.db, false)
+            .await
+            .expect("scan should pass");
+        assert_eq!(report.total(), 1);
+        assert!(!report.repaired);
+
+        let report = integrity_scan_svc(&ctx.state.db, true)
+            .await
+            .expect("repair should pass");
+        assert_eq!(report.total(), 1);
+        assert!(report.repaired);
+
+        let member = ctx
+            .state
+            .db
+            .org_members
+            .find_member(fixture.org.id.clone(), member.id)
+            .await
+            .expect("query should pass");
+        assert!(member.is_none());
+
+        let report = integrity_scan_svc(&ctx.state.db, false)
+            .await
+            .expect("scan should pass");
+        assert_eq!(report.total(), 0);
+    }
+}
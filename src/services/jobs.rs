@@ -0,0 +1,335 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use snafu::{ResultExt, ensure};
+use tokio::sync::watch;
+use tracing::{error, info, instrument, warn};
+
+use crate::db::DbMapper;
+use crate::dto::{
+    AppUriCheckDto, ElevationRevertJobDto, EmailDeliveryJobDto, JobDto, JobKind, JobStatus,
+    LifecycleDeliveryJobDto, ListJobsParamsDto, UserExportJobDto,
+};
+use crate::error::{JsonSerializeSnafu, NotFoundSnafu};
+use crate::run::AppState;
+use crate::services::apps::run_redirect_uri_probe;
+use crate::services::elevations::run_elevation_revert;
+use crate::services::lifecycle::{LIFECYCLE_RETRY, run_lifecycle_delivery};
+use crate::services::mailer::{EMAIL_RETRY, run_email_delivery};
+use crate::services::user_exports::run_user_export;
+use crate::utils::RetryPolicy;
+use crate::{Error, Result};
+
+/// Attempts before a job is moved to the dead letters
+pub const JOB_MAX_ATTEMPTS: i64 = 5;
+/// Retries double from 10 seconds up to 1 hour
+const JOB_RETRY: RetryPolicy = RetryPolicy {
+    max_attempts: JOB_MAX_ATTEMPTS,
+    base_secs: 10,
+    max_secs: 60 * 60,
+};
+/// A running job not finished within this time is claimed again
+const JOB_LOCK_SECS: i64 = 5 * 60;
+const JOB_POLL_SECS: u64 = 5;
+const JOB_LIST_LIMIT: i64 = 100;
+
+/// Retry policy of the kind, deliveries keep their own longer schedule
+pub fn job_retry_policy(kind: JobKind) -> RetryPolicy {
+    match kind {
+        JobKind::LifecycleDelivery => LIFECYCLE_RETRY,
+        JobKind::EmailDelivery => EMAIL_RETRY,
+        JobKind::RedirectUriProbe | JobKind::UserExport | JobKind::ElevationRevert => JOB_RETRY,
+    }
+}
+
+/// Queues a job to run as soon as the worker picks it up
+#[instrument(level = "debug", skip_all)]
+pub async fn enqueue_job_svc<T: Serialize>(
+    db: &DbMapper,
+    kind: JobKind,
+    payload: &T,
+) -> Result<JobDto> {
+    let now = Utc::now().timestamp_millis();
+    schedule_job_svc(db, kind, payload, now).await
+}
+
+/// Queues a job that is not due before `run_at`
+#[instrument(level = "debug", skip_all)]
+pub async fn schedule_job_svc<T: Serialize>(
+    db: &DbMapper,
+    kind: JobKind,
+    payload: &T,
+    run_at: i64,
+) -> Result<JobDto> {
+    let payload = serde_json::to_string(payload).context(JsonSerializeSnafu)?;
+    let max_attempts = job_retry_policy(kind).max_attempts;
+
+    db.jobs.enqueue(kind, payload, max_attempts, run_at).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn list_jobs_svc(state: &AppState, params: ListJobsParamsDto) -> Result<Vec<JobDto>> {
+    let status = match params.status {
+        Some(status) => Some(JobStatus::try_from(status.as_str())?),
+        None => None,
+    };
+
+    state.db.jobs.list(status, JOB_LIST_LIMIT).await
+}
+
+/// Puts a dead job back in the queue with a fresh set of attempts
+#[instrument(level = "debug", skip_all)]
+pub async fn retry_job_svc(state: &AppState, id: &str) -> Result<JobDto> {
+    let now = Utc::now().timestamp_millis();
+    let revived = state.db.jobs.revive(id.to_string(), now).await?;
+
+    ensure!(
+        revived,
+        NotFoundSnafu {
+            msg: "Dead job not found".to_string()
+        }
+    );
+
+    let job = state.db.jobs.get(id.to_string()).await?;
+    Ok(job.expect("Revived job must exist"))
+}
+
+fn parse_payload<T: DeserializeOwned>(job: &JobDto) -> Result<T> {
+    serde_json::from_str(&job.payload).map_err(|e| Error::Validation {
+        msg: format!("Invalid {} job payload: {}", job.kind, e),
+    })
+}
+
+async fn execute_job(state: &AppState, job: &JobDto, now: i64) -> Result<()> {
+    match job.kind {
+        JobKind::RedirectUriProbe => {
+            let check: AppUriCheckDto = parse_payload(job)?;
+            run_redirect_uri_probe(state, check).await
+        }
+        JobKind::UserExport => {
+            let export: UserExportJobDto = parse_payload(job)?;
+            run_user_export(state, export).await
+        }
+        JobKind::LifecycleDelivery => {
+            let delivery: LifecycleDeliveryJobDto = parse_payload(job)?;
+            run_lifecycle_delivery(state, delivery, now).await
+        }
+        JobKind::EmailDelivery => {
+            let email: EmailDeliveryJobDto = parse_payload(job)?;
+            run_email_delivery(state, email, now).await
+        }
+        JobKind::ElevationRevert => {
+            let elevation: ElevationRevertJobDto = parse_payload(job)?;
+            run_elevation_revert(state, elevation, now).await
+        }
+    }
+}
+
+/// Claims and runs the next due job, if any.
+///
+/// Returns the status the job ended with.
+#[instrument(level = "debug", skip_all)]
+pub async fn run_next_job_svc(state: &AppState, now: i64) -> Result<Option<JobStatus>> {
+    let locked_until = now + JOB_LOCK_SECS * 1000;
+    let Some(job) = state.db.jobs.claim_next(now, locked_until).await? else {
+        return Ok(None);
+    };
+
+    let kind = job.kind.to_string();
+
+    let Err(e) = execute_job(state, &job, now).await else {
+        state.db.jobs.succeed(job.id, now).await?;
+        return Ok(Some(JobStatus::Succeeded));
+    };
+
+    if job.attempts >= job.max_attempts {
+        error!(
+            job_id = job.id.as_str(),
+            kind = kind.as_str(),
+            attempts = job.attempts,
+            "job.dead: {}",
+            e
+        );
+
+        state.db.jobs.bury(job.id, Some(e.to_string()), now).await?;
+        return Ok(Some(JobStatus::Dead));
+    }
+
+    warn!(
+        job_id = job.id.as_str(),
+        kind = kind.as_str(),
+        attempts = job.attempts,
+        "job.retry: {}",
+        e
+    );
+
+    let run_at = now + job_retry_policy(job.kind).delay_secs(job.attempts) * 1000;
+    state
+        .db
+        .jobs
+        .retry_later(job.id, run_at, e.to_string(), now)
+        .await?;
+
+    Ok(Some(JobStatus::Queued))
+}
+
+/// Polls for due jobs until `shutdown` turns true.
+///
+/// A job already started is finished before the worker stops.
+#[instrument(level = "debug", skip_all)]
+pub async fn job_worker(state: AppState, mut shutdown: watch::Receiver<bool>) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(JOB_POLL_SECS));
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            changed = shutdown.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+            }
+        }
+
+        // Drain the due jobs, checking for shutdown between each
+        while !*shutdown.borrow() {
+            let now = Utc::now().timestamp_millis();
+            match run_next_job_svc(&state, now).await {
+                Ok(Some(_)) => {}
+                Ok(None) => break,
+                Err(e) => {
+                    error!("Job worker failed: {}", e);
+                    break;
+                }
+            }
+        }
+
+        if *shutdown.borrow() {
+            break;
+        }
+    }
+
+    info!("Job worker stopped");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::{JobKind, JobStatus, ListJobsParamsDto};
+    use crate::test::TestCtx;
+
+    use super::{job_retry_policy, list_jobs_svc, retry_job_svc, run_next_job_svc};
+
+    #[test]
+    fn job_retry_delay_doubles_up_to_the_cap() {
+        let policy = job_retry_policy(JobKind::UserExport);
+        assert_eq!(policy.delay_secs(1), 10);
+        assert_eq!(policy.delay_secs(3), 40);
+        assert_eq!(policy.delay_secs(20), 60 * 60);
+
+        let policy = job_retry_policy(JobKind::LifecycleDelivery);
+        assert_eq!(policy.max_attempts, 10);
+        assert_eq!(policy.delay_secs(1), 30);
+        assert_eq!(policy.delay_secs(5), 480);
+        assert_eq!(policy.delay_secs(30), 6 * 60 * 60);
+    }
+
+    #[tokio::test]
+    async fn failing_job_is_retried_then_dead_lettered() {
+        let ctx = TestCtx::new("jobs_dead_letter").await.expect("test ctx");
+        let db = &ctx.state.db;
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let job = db
+            .jobs
+            .enqueue(JobKind::RedirectUriProbe, "not json".to_string(), 2, now)
+            .await
+            .expect("job should be queued");
+
+        let status = run_next_job_svc(&ctx.state, now).await.expect("job run");
+        assert_eq!(status, Some(JobStatus::Queued));
+
+        let queued = db
+            .jobs
+            .get(job.id.clone())
+            .await
+            .expect("get job")
+            .expect("job exists");
+        assert_eq!(queued.attempts, 1);
+        assert_eq!(queued.run_at, now + 10_000);
+        assert!(queued.last_error.is_some());
+
+        // Not due until the backoff passes
+        let status = run_next_job_svc(&ctx.state, now + 1_000)
+            .await
+            .expect("job run");
+        assert_eq!(status, None);
+
+        let status = run_next_job_svc(&ctx.state, now + 10_000)
+            .await
+            .expect("job run");
+        assert_eq!(status, Some(JobStatus::Dead));
+
+        let dead = list_jobs_svc(
+            &ctx.state,
+            ListJobsParamsDto {
+                status: Some("dead".to_string()),
+            },
+        )
+        .await
+        .expect("list jobs");
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].attempts, 2);
+        assert!(dead[0].finished_at.is_some());
+
+        // Dead letters stay put until retried
+        let status = run_next_job_svc(&ctx.state, now + 3_600_000)
+            .await
+            .expect("job run");
+        assert_eq!(status, None);
+
+        let revived = retry_job_svc(&ctx.state, &job.id)
+            .await
+            .expect("dead job should be revived");
+        assert_eq!(revived.status, JobStatus::Queued);
+        assert_eq!(revived.attempts, 0);
+
+        let missing = retry_job_svc(&ctx.state, &job.id).await;
+        assert!(missing.is_err());
+    }
+
+    #[tokio::test]
+    async fn abandoned_running_job_is_claimed_again() {
+        let ctx = TestCtx::new("jobs_abandoned").await.expect("test ctx");
+        let db = &ctx.state.db;
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let job = db
+            .jobs
+            .enqueue(JobKind::RedirectUriProbe, "{}".to_string(), 5, now)
+            .await
+            .expect("job should be queued");
+
+        let claimed = db
+            .jobs
+            .claim_next(now, now + 60_000)
+            .await
+            .expect("claim")
+            .expect("job is due");
+        assert_eq!(claimed.id, job.id);
+        assert_eq!(claimed.status, JobStatus::Running);
+
+        // Locked while its worker runs it
+        let none = db.jobs.claim_next(now + 1_000, now + 61_000).await;
+        assert!(none.expect("claim").is_none());
+
+        let reclaimed = db
+            .jobs
+            .claim_next(now + 60_000, now + 120_000)
+            .await
+            .expect("claim")
+            .expect("lock expired");
+        assert_eq!(reclaimed.id, job.id);
+        assert_eq!(reclaimed.attempts, 2);
+    }
+}
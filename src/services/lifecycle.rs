@@ -0,0 +1,713 @@
+use chrono::Utc;
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, ensure};
+use tracing::{Span, info, instrument, warn};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::db::DbMapper;
+use crate::dto::{
+    DELIVERY_FAILED, DELIVERY_PENDING, JobKind, LIFECYCLE_SCHEMA_VERSION, LifecycleDeliveryDto,
+    LifecycleDeliveryJobDto, LifecycleEventDto, LifecycleExternalIdsDto, LifecycleMembershipDto,
+    LifecycleSubscriptionDto, LifecycleTopic, LifecycleUserDto, NewLifecycleSubscriptionDto,
+    OrgMemberDto, UserDto,
+};
+use crate::error::{CsrfTokenSnafu, JsonSerializeSnafu, NotFoundSnafu};
+use crate::run::AppState;
+use crate::services::jobs::enqueue_job_svc;
+use crate::services::token::verify_csrf_token;
+use crate::utils::{IdPrefix, RetryPolicy, generate_id, trace_context_headers};
+use crate::validators::validate_payload;
+use crate::{Error, Result};
+
+/// Attempts before a delivery is marked as failed
+pub const LIFECYCLE_MAX_ATTEMPTS: i64 = 10;
+/// Retries double from 30 seconds up to 6 hours
+pub const LIFECYCLE_RETRY: RetryPolicy = RetryPolicy {
+    max_attempts: LIFECYCLE_MAX_ATTEMPTS,
+    base_secs: 30,
+    max_secs: 6 * 60 * 60,
+};
+const LIFECYCLE_DELIVERY_LIST_LIMIT: i64 = 50;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct LifecycleSubscriptionFormData {
+    pub token: String,
+    pub topic: String,
+    pub url: String,
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn list_lifecycle_subscriptions_svc(
+    state: &AppState,
+    app_id: &str,
+) -> Result<Vec<LifecycleSubscriptionDto>> {
+    state.db.lifecycle.list_by_app(app_id.to_string()).await
+}
+
+/// Latest deliveries of the app with their retry state
+#[instrument(level = "debug", skip_all)]
+pub async fn list_lifecycle_deliveries_svc(
+    state: &AppState,
+    app_id: &str,
+) -> Result<Vec<LifecycleDeliveryDto>> {
+    state
+        .db
+        .lifecycle_deliveries
+        .list_by_app(app_id.to_string(), LIFECYCLE_DELIVERY_LIST_LIMIT)
+        .await
+}
+
+/// Subscribes the app to the topic, or moves an existing subscription to a new url
+#[instrument(level = "debug", skip_all)]
+pub async fn subscribe_lifecycle_svc(
+    state: &AppState,
+    app_id: &str,
+    data: NewLifecycleSubscriptionDto,
+) -> Result<LifecycleSubscriptionDto> {
+    validate_payload(&data)?;
+
+    let topic = LifecycleTopic::try_from(data.topic.as_str())?;
+
+    state
+        .db
+        .lifecycle
+        .upsert(app_id.to_string(), topic, data.url)
+        .await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn subscribe_lifecycle_web_svc(
+    state: &AppState,
+    app_id: &str,
+    form: LifecycleSubscriptionFormData,
+) -> Result<LifecycleSubscriptionDto> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == app_id, CsrfTokenSnafu);
+
+    subscribe_lifecycle_svc(
+        state,
+        app_id,
+        NewLifecycleSubscriptionDto {
+            topic: form.topic,
+            url: form.url,
+        },
+    )
+    .await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn unsubscribe_lifecycle_svc(state: &AppState, app_id: &str, id: &str) -> Result<()> {
+    let deleted = state
+        .db
+        .lifecycle
+        .delete(app_id.to_string(), id.to_string())
+        .await?;
+
+    ensure!(
+        deleted,
+        NotFoundSnafu {
+            msg: "Lifecycle subscription not found".to_string()
+        }
+    );
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn unsubscribe_lifecycle_web_svc(
+    state: &AppState,
+    app_id: &str,
+    id: &str,
+    csrf_token: &str,
+) -> Result<()> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == id, CsrfTokenSnafu);
+
+    unsubscribe_lifecycle_svc(state, app_id, id).await
+}
+
+/// Hex encoded HMAC-SHA256 of the body, sent as `X-Yaas-Signature: sha256=<hex>`
+pub fn sign_lifecycle_payload(secret: &str, body: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = hmac::sign(&key, body.as_bytes());
+    hex::encode(tag.as_ref())
+}
+
+/// Publishes a user topic to every subscribed app.
+///
+/// Returns the number of deliveries scheduled.
+#[instrument(level = "debug", skip_all)]
+pub async fn publish_user_event_svc(
+    state: &AppState,
+    topic: LifecycleTopic,
+    user: &UserDto,
+    changed_fields: Vec<String>,
+) -> Result<usize> {
+    publish_event(state, user_event(topic, user, changed_fields), None).await
+}
+
+/// Queues a user topic for the job worker of the running server.
+///
+/// Used by the CLI, which only has the database at hand.
+#[instrument(level = "debug", skip_all)]
+pub async fn queue_user_event_svc(
+    db: &DbMapper,
+    topic: LifecycleTopic,
+    user: &UserDto,
+    changed_fields: Vec<String>,
+) -> Result<usize> {
+    let deliveries = queue_event(db, &user_event(topic, user, changed_fields), None).await?;
+    Ok(deliveries.len())
+}
+
+fn user_event(
+    topic: LifecycleTopic,
+    user: &UserDto,
+    changed_fields: Vec<String>,
+) -> LifecycleEventDto {
+    LifecycleEventDto {
+        id: generate_id(IdPrefix::LifecycleEvent),
+        topic,
+        schema_version: LIFECYCLE_SCHEMA_VERSION,
+        occurred_at: chrono::Utc::now().timestamp_millis(),
+        external_ids: LifecycleExternalIdsDto {
+            yaas_user_id: user.id.clone(),
+            yaas_org_id: None,
+        },
+        user: LifecycleUserDto {
+            email: user.email.clone(),
+            name: user.name.clone(),
+            status: user.status.clone(),
+        },
+        membership: None,
+        changed_fields,
+    }
+}
+
+/// Publishes a membership topic to the apps linked to the member's org
+#[instrument(level = "debug", skip_all)]
+pub async fn publish_membership_event_svc(
+    state: &AppState,
+    topic: LifecycleTopic,
+    member: &OrgMemberDto,
+    changed_fields: Vec<String>,
+) -> Result<usize> {
+    let Some(user) = state.db.users.get(member.user_id.clone()).await? else {
+        return Err(Error::UserNotFound);
+    };
+
+    let event = LifecycleEventDto {
+        id: generate_id(IdPrefix::LifecycleEvent),
+        topic,
+        schema_version: LIFECYCLE_SCHEMA_VERSION,
+        occurred_at: chrono::Utc::now().timestamp_millis(),
+        external_ids: LifecycleExternalIdsDto {
+            yaas_user_id: user.id.clone(),
+            yaas_org_id: Some(member.org_id.clone()),
+        },
+        user: LifecycleUserDto {
+            email: user.email,
+            name: user.name,
+            status: user.status,
+        },
+        membership: Some(LifecycleMembershipDto {
+            org_id: member.org_id.clone(),
+            roles: member.roles.iter().map(|r| r.to_string()).collect(),
+            status: member.status.clone(),
+        }),
+        changed_fields,
+    };
+
+    publish_event(state, event, Some(member.org_id.clone())).await
+}
+
+async fn publish_event(
+    state: &AppState,
+    event: LifecycleEventDto,
+    org_id: Option<String>,
+) -> Result<usize> {
+    let deliveries = queue_event(&state.db, &event, org_id).await?;
+    Ok(deliveries.len())
+}
+
+/// Adds a delivery to the outbox for each subscriber of the event, each sent
+/// and retried by its own job
+async fn queue_event(
+    db: &DbMapper,
+    event: &LifecycleEventDto,
+    org_id: Option<String>,
+) -> Result<Vec<LifecycleDeliveryDto>> {
+    let subscribers = db.lifecycle.list_subscribers(event.topic, org_id).await?;
+
+    if subscribers.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let body = serde_json::to_string(event).context(JsonSerializeSnafu)?;
+    let now = Utc::now().timestamp_millis();
+    let mut deliveries = Vec::with_capacity(subscribers.len());
+
+    for subscription in subscribers.iter() {
+        let delivery = db
+            .lifecycle_deliveries
+            .create(subscription, event.id.clone(), body.clone(), now)
+            .await?;
+        enqueue_job_svc(
+            db,
+            JobKind::LifecycleDelivery,
+            &LifecycleDeliveryJobDto {
+                delivery_id: delivery.id.clone(),
+            },
+        )
+        .await?;
+        deliveries.push(delivery);
+    }
+
+    Ok(deliveries)
+}
+
+/// Job handler sending one queued delivery.
+///
+/// A rejected attempt fails the job so that the worker retries it on the
+/// delivery schedule. Deliveries no longer pending are skipped.
+#[instrument(level = "debug", skip_all, fields(delivery_id = %job.delivery_id))]
+pub async fn run_lifecycle_delivery(
+    state: &AppState,
+    job: LifecycleDeliveryJobDto,
+    now: i64,
+) -> Result<()> {
+    // Delivered, given up or cancelled by an erasure in the meantime
+    let Some(delivery) = state.db.lifecycle_deliveries.get(job.delivery_id).await? else {
+        return Ok(());
+    };
+    if delivery.status != DELIVERY_PENDING {
+        return Ok(());
+    }
+
+    let attempts = delivery.attempts + 1;
+    let subscription = state
+        .db
+        .lifecycle
+        .get(delivery.app_id.clone(), delivery.subscription_id.clone())
+        .await?;
+
+    let Some(subscription) = subscription else {
+        state
+            .db
+            .lifecycle_deliveries
+            .record_failure(
+                delivery.id,
+                DELIVERY_FAILED,
+                attempts,
+                now,
+                None,
+                Some("Subscription removed".to_string()),
+                now,
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let topic = delivery.topic.to_string();
+    let signature = sign_lifecycle_payload(&subscription.secret, &delivery.payload);
+
+    let result = state
+        .client
+        .post(&subscription.url)
+        .header("Content-Type", "application/json")
+        .header("X-Yaas-Event-Id", delivery.event_id.as_str())
+        .header("X-Yaas-Topic", topic.as_str())
+        .header("X-Yaas-Signature", format!("sha256={}", signature))
+        .headers(trace_context_headers(&Span::current().context()))
+        .body(delivery.payload.clone())
+        .send()
+        .await;
+
+    let (last_status, last_error) = match result {
+        Ok(res) if res.status().is_success() => {
+            info!(
+                event_id = delivery.event_id.as_str(),
+                topic = topic.as_str(),
+                app_id = delivery.app_id.as_str(),
+                attempts = attempts,
+                "lifecycle.delivered"
+            );
+
+            state
+                .db
+                .lifecycle_deliveries
+                .mark_delivered(delivery.id, attempts, res.status().as_u16() as i64, now)
+                .await?;
+            return Ok(());
+        }
+        Ok(res) => (
+            Some(res.status().as_u16() as i64),
+            format!("Rejected with status {}", res.status().as_u16()),
+        ),
+        Err(e) => (None, e.to_string()),
+    };
+
+    let (status, next_attempt_at) = if LIFECYCLE_RETRY.exhausted(attempts) {
+        (DELIVERY_FAILED, now)
+    } else {
+        (
+            DELIVERY_PENDING,
+            now + LIFECYCLE_RETRY.delay_secs(attempts) * 1000,
+        )
+    };
+
+    warn!(
+        event_id = delivery.event_id.as_str(),
+        topic = topic.as_str(),
+        app_id = delivery.app_id.as_str(),
+        attempts = attempts,
+        status = status,
+        "lifecycle.rejected: {}",
+        last_error
+    );
+
+    state
+        .db
+        .lifecycle_deliveries
+        .record_failure(
+            delivery.id,
+            status,
+            attempts,
+            next_attempt_at,
+            last_status,
+            Some(last_error.clone()),
+            now,
+        )
+        .await?;
+
+    Err(Error::Service { msg: last_error })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::Router;
+    use axum::http::HeaderMap;
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use tokio::sync::mpsc;
+
+    use crate::dto::{
+        JobKind, JobStatus, LifecycleDeliveryJobDto, LifecycleEventDto, LifecycleTopic,
+        NewLifecycleSubscriptionDto, NewOrgMemberDto,
+    };
+    use crate::services::jobs::{enqueue_job_svc, run_next_job_svc};
+    use crate::services::org_members::create_org_member_svc;
+    use crate::test::TestCtx;
+
+    use super::{sign_lifecycle_payload, subscribe_lifecycle_svc, unsubscribe_lifecycle_svc};
+
+    #[test]
+    fn sign_lifecycle_payload_matches_hmac_sha256() {
+        let signature =
+            sign_lifecycle_payload("key", "The quick brown fox jumps over the lazy dog");
+        assert_eq!(
+            signature,
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    /// Local endpoint that rejects every webhook
+    async fn spawn_failing_receiver() -> String {
+        let app = Router::new().route(
+            "/hook",
+            post(|| async { (StatusCode::INTERNAL_SERVER_ERROR, "down") }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener should bind");
+        let addr = listener.local_addr().expect("local addr");
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        format!("http://{}/hook", addr)
+    }
+
+    /// Local endpoint that hands every received webhook to the test
+    async fn spawn_receiver() -> (String, mpsc::UnboundedReceiver<(HeaderMap, String)>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let app = Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: String| {
+                let tx = tx.clone();
+                async move {
+                    let _ = tx.send((headers, body));
+                    "ok"
+                }
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener should bind");
+        let addr = listener.local_addr().expect("local addr");
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        (format!("http://{}/hook", addr), rx)
+    }
+
+    #[tokio::test]
+    async fn membership_granted_is_signed_and_delivered_to_linked_apps() {
+        let ctx = TestCtx::new("lifecycle_deliver").await.expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "Owner User",
+                "lifecycle.owner@example.com",
+                "password123",
+                "Mirror Org",
+                "Mirror App",
+                "https://mirror.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+
+        let (url, mut rx) = spawn_receiver().await;
+
+        let subscription = subscribe_lifecycle_svc(
+            &ctx.state,
+            &fixture.app.id,
+            NewLifecycleSubscriptionDto {
+                topic: "membership.granted".to_string(),
+                url: url.clone(),
+            },
+        )
+        .await
+        .expect("subscription should be saved");
+        assert_eq!(subscription.topic, LifecycleTopic::MembershipGranted);
+
+        // Saving the topic again only moves the url
+        let resubscribed = subscribe_lifecycle_svc(
+            &ctx.state,
+            &fixture.app.id,
+            NewLifecycleSubscriptionDto {
+                topic: "membership.granted".to_string(),
+                url,
+            },
+        )
+        .await
+        .expect("subscription should be updated");
+        assert_eq!(resubscribed.id, subscription.id);
+        assert_eq!(resubscribed.secret, subscription.secret);
+
+        let member_user = ctx
+            .seed_user_with_password("Member", "lifecycle.member@example.com", "password123")
+            .await
+            .expect("member user");
+
+        create_org_member_svc(
+            &ctx.state,
+            &fixture.auth.org.id,
+            NewOrgMemberDto {
+                user_id: member_user.id.clone(),
+                roles: vec!["OrgViewer".to_string()],
+                status: "active".to_string(),
+            },
+        )
+        .await
+        .expect("member should be created");
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let status = run_next_job_svc(&ctx.state, now).await.expect("job run");
+        assert_eq!(status, Some(JobStatus::Succeeded));
+
+        let (headers, body) = tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+            .await
+            .expect("webhook should arrive")
+            .expect("channel open");
+
+        let expected = format!(
+            "sha256={}",
+            sign_lifecycle_payload(&subscription.secret, &body)
+        );
+        assert_eq!(
+            headers
+                .get("X-Yaas-Signature")
+                .and_then(|v| v.to_str().ok()),
+            Some(expected.as_str())
+        );
+        assert_eq!(
+            headers.get("X-Yaas-Topic").and_then(|v| v.to_str().ok()),
+            Some("membership.granted")
+        );
+
+        let event: LifecycleEventDto = serde_json::from_str(&body).expect("payload json");
+        assert_eq!(event.topic, LifecycleTopic::MembershipGranted);
+        assert_eq!(event.schema_version, 1);
+        assert_eq!(event.external_ids.yaas_user_id, member_user.id);
+        assert_eq!(
+            event.external_ids.yaas_org_id.as_deref(),
+            Some(fixture.auth.org.id.as_str())
+        );
+        let membership = event.membership.expect("membership section");
+        assert_eq!(membership.roles, vec!["OrgViewer".to_string()]);
+
+        unsubscribe_lifecycle_svc(&ctx.state, &fixture.app.id, &subscription.id)
+            .await
+            .expect("unsubscribe should pass");
+        let missing =
+            unsubscribe_lifecycle_svc(&ctx.state, &fixture.app.id, &subscription.id).await;
+        assert!(missing.is_err());
+    }
+
+    #[tokio::test]
+    async fn failed_deliveries_are_retried_with_backoff() {
+        let ctx = TestCtx::new("lifecycle_retry").await.expect("test ctx");
+        let app = ctx
+            .seed_app("Mirror App", "https://mirror.example.com/callback")
+            .await
+            .expect("app");
+
+        let subscription = subscribe_lifecycle_svc(
+            &ctx.state,
+            &app.id,
+            NewLifecycleSubscriptionDto {
+                topic: "user.provisioned".to_string(),
+                url: spawn_failing_receiver().await,
+            },
+        )
+        .await
+        .expect("subscription should be saved");
+
+        let created_at = chrono::Utc::now().timestamp_millis();
+        let delivery = ctx
+            .state
+            .db
+            .lifecycle_deliveries
+            .create(
+                &subscription,
+                "lce_retry".to_string(),
+                "{}".to_string(),
+                created_at,
+            )
+            .await
+            .expect("delivery should be queued");
+        let job = enqueue_job_svc(
+            &ctx.state.db,
+            JobKind::LifecycleDelivery,
+            &LifecycleDeliveryJobDto {
+                delivery_id: delivery.id.clone(),
+            },
+        )
+        .await
+        .expect("job should be queued");
+        assert_eq!(job.max_attempts, 10);
+        let now = job.run_at;
+
+        let status = run_next_job_svc(&ctx.state, now).await.expect("job run");
+        assert_eq!(status, Some(JobStatus::Queued));
+
+        let queued = ctx
+            .state
+            .db
+            .lifecycle_deliveries
+            .get(delivery.id.clone())
+            .await
+            .expect("get delivery")
+            .expect("delivery exists");
+        assert_eq!(queued.status, "pending");
+        assert_eq!(queued.attempts, 1);
+        assert_eq!(queued.last_status, Some(500));
+        assert_eq!(queued.next_attempt_at, now + 30_000);
+
+        let retried = ctx
+            .state
+            .db
+            .jobs
+            .get(job.id.clone())
+            .await
+            .expect("get job")
+            .expect("job exists");
+        assert_eq!(retried.run_at, now + 30_000);
+
+        // Not due yet
+        let status = run_next_job_svc(&ctx.state, now + 1_000)
+            .await
+            .expect("job run");
+        assert_eq!(status, None);
+
+        // Second failure doubles the delay
+        let status = run_next_job_svc(&ctx.state, now + 30_000)
+            .await
+            .expect("job run");
+        assert_eq!(status, Some(JobStatus::Queued));
+        let queued = ctx
+            .state
+            .db
+            .lifecycle_deliveries
+            .get(delivery.id.clone())
+            .await
+            .expect("get delivery")
+            .expect("delivery exists");
+        assert_eq!(queued.attempts, 2);
+        assert_eq!(queued.next_attempt_at, now + 90_000);
+
+        // The app fixes its endpoint, the next retry goes through
+        let (url, mut rx) = spawn_receiver().await;
+        subscribe_lifecycle_svc(
+            &ctx.state,
+            &app.id,
+            NewLifecycleSubscriptionDto {
+                topic: "user.provisioned".to_string(),
+                url,
+            },
+        )
+        .await
+        .expect("subscription should be updated");
+
+        let status = run_next_job_svc(&ctx.state, now + 90_000)
+            .await
+            .expect("job run");
+        assert_eq!(status, Some(JobStatus::Succeeded));
+
+        let (headers, body) = rx.recv().await.expect("webhook should arrive");
+        assert_eq!(body, "{}");
+        assert_eq!(
+            headers.get("X-Yaas-Event-Id").and_then(|v| v.to_str().ok()),
+            Some("lce_retry")
+        );
+
+        let sent = ctx
+            .state
+            .db
+            .lifecycle_deliveries
+            .get(delivery.id)
+            .await
+            .expect("get delivery")
+            .expect("delivery exists");
+        assert_eq!(sent.status, "delivered");
+        assert_eq!(sent.attempts, 3);
+        assert_eq!(sent.delivered_at, Some(now + 90_000));
+    }
+
+    #[tokio::test]
+    async fn subscribe_lifecycle_svc_rejects_unknown_topic() {
+        let ctx = TestCtx::new("lifecycle_bad_topic").await.expect("test ctx");
+        let app = ctx
+            .seed_app("Mirror App", "https://mirror.example.com/callback")
+            .await
+            .expect("app");
+
+        let result = subscribe_lifecycle_svc(
+            &ctx.state,
+            &app.id,
+            NewLifecycleSubscriptionDto {
+                topic: "user.renamed".to_string(),
+                url: "https://mirror.example.com/hooks".to_string(),
+            },
+        )
+        .await;
+
+        let err = result.err().expect("unknown topic should fail");
+        assert_eq!(err.to_string(), "Invalid lifecycle topic: user.renamed");
+    }
+}
@@ -0,0 +1,411 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use snafu::ensure;
+use tracing::{info, instrument, warn};
+
+use crate::config::{MailTransport, MailerConfig, SmtpConfig, SmtpTls};
+use crate::db::DbMapper;
+use crate::dto::{
+    DELIVERY_DELIVERED, DELIVERY_FAILED, DELIVERY_PENDING, EmailDeliveryJobDto, EmailDto, JobKind,
+    ListEmailsParamsDto, NewEmailDto,
+};
+use crate::error::ValidationSnafu;
+use crate::run::AppState;
+use crate::services::jobs::enqueue_job_svc;
+use crate::utils::RetryPolicy;
+use crate::{Error, Result};
+
+/// Attempts before an email is marked as failed
+pub const EMAIL_MAX_ATTEMPTS: i64 = 8;
+/// Retries double from 1 minute up to 1 hour
+pub const EMAIL_RETRY: RetryPolicy = RetryPolicy {
+    max_attempts: EMAIL_MAX_ATTEMPTS,
+    base_secs: 60,
+    max_secs: 60 * 60,
+};
+const SMTP_TIMEOUT_SECS: u64 = 30;
+const EMAIL_LIST_LIMIT: i64 = 100;
+
+/// Sends one email, failed sends are retried from the outbox
+pub trait MailSender {
+    fn send(&self, email: &EmailDto) -> impl Future<Output = Result<()>> + Send;
+}
+
+/// Writes emails to the log for operators to relay
+pub struct LogSender;
+
+impl MailSender for LogSender {
+    async fn send(&self, email: &EmailDto) -> Result<()> {
+        info!(
+            email_id = email.id.as_str(),
+            recipient = email.recipient.as_str(),
+            subject = email.subject.as_str(),
+            body = email.text_body.as_str(),
+            "email.logged"
+        );
+        Ok(())
+    }
+}
+
+pub struct SmtpSender {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpSender {
+    pub fn new(config: &SmtpConfig, from: Mailbox) -> Result<Self> {
+        let builder = match config.tls {
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host),
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host),
+            SmtpTls::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                &config.host,
+            )),
+        }
+        .map_err(|e| Error::Config {
+            msg: format!("Unable to create the SMTP transport: {}", e),
+        })?;
+
+        let mut builder = builder
+            .port(config.port)
+            .timeout(Some(Duration::from_secs(SMTP_TIMEOUT_SECS)));
+
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+impl MailSender for SmtpSender {
+    async fn send(&self, email: &EmailDto) -> Result<()> {
+        let to: Mailbox = email.recipient.parse().map_err(|e| Error::Mail {
+            msg: format!("Invalid recipient {}: {}", email.recipient, e),
+        })?;
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(email.subject.as_str())
+            .multipart(MultiPart::alternative_plain_html(
+                email.text_body.clone(),
+                email.html_body.clone(),
+            ))
+            .map_err(|e| Error::Mail { msg: e.to_string() })?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| Error::Mail { msg: e.to_string() })?;
+
+        Ok(())
+    }
+}
+
+/// Sender selected with the `MAILER` setting
+pub enum Mailer {
+    Log(LogSender),
+    Smtp(SmtpSender),
+}
+
+impl Mailer {
+    pub fn new(config: &MailerConfig) -> Result<Self> {
+        let from: Mailbox = config.from.parse().map_err(|e| Error::Config {
+            msg: format!("MAIL_FROM must be a valid address: {}", e),
+        })?;
+
+        match &config.transport {
+            MailTransport::Log => Ok(Self::Log(LogSender)),
+            MailTransport::Smtp(smtp) => Ok(Self::Smtp(SmtpSender::new(smtp, from)?)),
+        }
+    }
+}
+
+impl MailSender for Mailer {
+    async fn send(&self, email: &EmailDto) -> Result<()> {
+        match self {
+            Self::Log(sender) => sender.send(email).await,
+            Self::Smtp(sender) => sender.send(email).await,
+        }
+    }
+}
+
+/// Queues a rendered email with the `email.delivery` job that sends it
+#[instrument(level = "debug", skip_all)]
+pub async fn queue_email_svc(db: &DbMapper, email: NewEmailDto) -> Result<EmailDto> {
+    let now = Utc::now().timestamp_millis();
+    let queued = db.email_outbox.create(email, now).await?;
+    enqueue_job_svc(
+        db,
+        JobKind::EmailDelivery,
+        &EmailDeliveryJobDto {
+            email_id: queued.id.clone(),
+        },
+    )
+    .await?;
+
+    info!(
+        email_id = queued.id.as_str(),
+        recipient = queued.recipient.as_str(),
+        subject = queued.subject.as_str(),
+        "email.queued"
+    );
+
+    Ok(queued)
+}
+
+/// Latest queued emails without their bodies, for operators to spot failed deliveries
+#[instrument(level = "debug", skip_all)]
+pub async fn list_emails_svc(
+    state: &AppState,
+    params: ListEmailsParamsDto,
+) -> Result<Vec<EmailDto>> {
+    if let Some(status) = &params.status {
+        ensure!(
+            [DELIVERY_PENDING, DELIVERY_DELIVERED, DELIVERY_FAILED].contains(&status.as_str()),
+            ValidationSnafu {
+                msg: "Status must be pending, delivered or failed".to_string(),
+            }
+        );
+    }
+
+    state
+        .db
+        .email_outbox
+        .list(params.status, EMAIL_LIST_LIMIT)
+        .await
+}
+
+/// Job handler sending one queued email with the configured mailer
+pub async fn run_email_delivery(
+    state: &AppState,
+    job: EmailDeliveryJobDto,
+    now: i64,
+) -> Result<()> {
+    send_queued_email(&state.db, state.mailer.as_ref(), job, now).await
+}
+
+/// Sends one queued email.
+///
+/// A rejected send fails the job so that the worker retries it on the email
+/// schedule. Emails no longer pending are skipped.
+#[instrument(level = "debug", skip_all, fields(email_id = %job.email_id))]
+async fn send_queued_email<S: MailSender>(
+    db: &DbMapper,
+    sender: &S,
+    job: EmailDeliveryJobDto,
+    now: i64,
+) -> Result<()> {
+    // Sent, given up or removed by an erasure in the meantime
+    let Some(email) = db.email_outbox.get(job.email_id).await? else {
+        return Ok(());
+    };
+    if email.status != DELIVERY_PENDING {
+        return Ok(());
+    }
+
+    let attempts = email.attempts + 1;
+
+    let Err(e) = sender.send(&email).await else {
+        info!(
+            email_id = email.id.as_str(),
+            attempts = attempts,
+            "email.sent"
+        );
+        db.email_outbox.mark_sent(email.id, attempts, now).await?;
+        return Ok(());
+    };
+
+    let (status, next_attempt_at) = if EMAIL_RETRY.exhausted(attempts) {
+        (DELIVERY_FAILED, now)
+    } else {
+        (
+            DELIVERY_PENDING,
+            now + EMAIL_RETRY.delay_secs(attempts) * 1000,
+        )
+    };
+
+    warn!(
+        email_id = email.id.as_str(),
+        attempts = attempts,
+        status = status,
+        "email.rejected: {}",
+        e
+    );
+
+    db.email_outbox
+        .record_failure(
+            email.id,
+            status,
+            attempts,
+            next_attempt_at,
+            e.to_string(),
+            now,
+        )
+        .await?;
+
+    Err(e)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use crate::dto::{EmailDeliveryJobDto, EmailDto, JobKind, JobStatus, NewEmailDto};
+    use crate::services::jobs::run_next_job_svc;
+    use crate::test::TestCtx;
+    use crate::{Error, Result};
+
+    use super::{EMAIL_MAX_ATTEMPTS, EMAIL_RETRY, MailSender, queue_email_svc, send_queued_email};
+
+    /// Rejects every email until told to accept them
+    struct FlakySender {
+        accept: AtomicBool,
+    }
+
+    impl MailSender for FlakySender {
+        async fn send(&self, _email: &EmailDto) -> Result<()> {
+            match self.accept.load(Ordering::Relaxed) {
+                true => Ok(()),
+                false => Err(Error::Mail {
+                    msg: "Connection refused".to_string(),
+                }),
+            }
+        }
+    }
+
+    fn new_email() -> NewEmailDto {
+        NewEmailDto {
+            org_id: None,
+            recipient: "jane@example.com".to_string(),
+            subject: "Yaas: Hello".to_string(),
+            text_body: "Hello".to_string(),
+            html_body: "<p>Hello</p>".to_string(),
+        }
+    }
+
+    #[test]
+    fn email_retry_delay_doubles_up_to_the_cap() {
+        assert_eq!(EMAIL_RETRY.delay_secs(1), 60);
+        assert_eq!(EMAIL_RETRY.delay_secs(2), 120);
+        assert_eq!(EMAIL_RETRY.delay_secs(3), 240);
+        assert_eq!(EMAIL_RETRY.delay_secs(7), 3600);
+        assert_eq!(EMAIL_RETRY.delay_secs(20), 3600);
+    }
+
+    #[tokio::test]
+    async fn failed_emails_are_retried_then_sent() {
+        let ctx = TestCtx::new("mailer_retry").await.expect("test ctx");
+        let db = &ctx.state.db;
+        let sender = FlakySender {
+            accept: AtomicBool::new(false),
+        };
+
+        let queued = queue_email_svc(db, new_email()).await.expect("queue");
+        let now = queued.next_attempt_at;
+        let job = EmailDeliveryJobDto {
+            email_id: queued.id.clone(),
+        };
+
+        let result = send_queued_email(db, &sender, job.clone(), now).await;
+        assert!(result.is_err(), "a rejected send fails the job");
+
+        let email = db
+            .email_outbox
+            .get(queued.id.clone())
+            .await
+            .expect("get")
+            .expect("email exists");
+        assert_eq!(email.status, "pending");
+        assert_eq!(email.attempts, 1);
+        assert_eq!(email.next_attempt_at, now + 60_000);
+        assert!(email.last_error.unwrap().contains("Connection refused"));
+
+        sender.accept.store(true, Ordering::Relaxed);
+        send_queued_email(db, &sender, job.clone(), now + 60_000)
+            .await
+            .expect("send");
+
+        let email = db
+            .email_outbox
+            .get(queued.id)
+            .await
+            .expect("get")
+            .expect("email exists");
+        assert_eq!(email.status, "delivered");
+        assert_eq!(email.attempts, 2);
+        assert_eq!(email.sent_at, Some(now + 60_000));
+        assert!(email.last_error.is_none());
+        assert!(email.text_body.is_empty() && email.html_body.is_empty());
+
+        // A sent email is not sent again
+        send_queued_email(db, &sender, job, now + 120_000)
+            .await
+            .expect("send");
+    }
+
+    #[tokio::test]
+    async fn emails_are_given_up_after_the_last_attempt() {
+        let ctx = TestCtx::new("mailer_give_up").await.expect("test ctx");
+        let db = &ctx.state.db;
+        let sender = FlakySender {
+            accept: AtomicBool::new(false),
+        };
+
+        let queued = queue_email_svc(db, new_email()).await.expect("queue");
+
+        for attempt in 0..EMAIL_MAX_ATTEMPTS {
+            let now = queued.next_attempt_at + attempt * 2 * 60 * 60 * 1000;
+            let job = EmailDeliveryJobDto {
+                email_id: queued.id.clone(),
+            };
+            let _ = send_queued_email(db, &sender, job, now).await;
+        }
+
+        let email = db
+            .email_outbox
+            .get(queued.id)
+            .await
+            .expect("get")
+            .expect("email exists");
+        assert_eq!(email.status, "failed");
+        assert_eq!(email.attempts, EMAIL_MAX_ATTEMPTS);
+        assert!(email.text_body.is_empty() && email.html_body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn queued_emails_are_sent_by_the_job_worker() {
+        let ctx = TestCtx::new("mailer_job").await.expect("test ctx");
+        let db = &ctx.state.db;
+
+        let queued = queue_email_svc(db, new_email()).await.expect("queue");
+
+        let jobs = db
+            .jobs
+            .list(Some(JobStatus::Queued), 10)
+            .await
+            .expect("list jobs");
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].kind, JobKind::EmailDelivery);
+        assert_eq!(jobs[0].max_attempts, EMAIL_MAX_ATTEMPTS);
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let status = run_next_job_svc(&ctx.state, now).await.expect("job run");
+        assert_eq!(status, Some(JobStatus::Succeeded));
+
+        let email = db
+            .email_outbox
+            .get(queued.id)
+            .await
+            .expect("get")
+            .expect("email exists");
+        assert_eq!(email.status, "delivered");
+    }
+}
@@ -0,0 +1,145 @@
+use std::fmt::Write;
+use std::time::Duration;
+use tracing::info;
+
+use crate::dto::MemoryStatsDto;
+use crate::utils::alloc_counters;
+
+/// Current RSS and allocator counters
+pub fn memory_stats_svc() -> MemoryStatsDto {
+    let status = std::fs::read_to_string("/proc/self/status").ok();
+
+    MemoryStatsDto {
+        rss_bytes: status.as_deref().and_then(parse_vm_rss),
+        allocator: alloc_counters().map(Into::into),
+    }
+}
+
+/// Renders the snapshot in the Prometheus text exposition format
+pub fn render_memory_metrics(stats: &MemoryStatsDto) -> String {
+    let mut out = String::new();
+
+    if let Some(rss) = stats.rss_bytes {
+        push_metric(
+            &mut out,
+            "yaas_process_resident_memory_bytes",
+            "gauge",
+            "Resident set size of the server process.",
+            rss,
+        );
+    }
+
+    if let Some(alloc) = &stats.allocator {
+        push_metric(
+            &mut out,
+            "yaas_alloc_allocations_total",
+            "counter",
+            "Number of heap allocations.",
+            alloc.allocations,
+        );
+        push_metric(
+            &mut out,
+            "yaas_alloc_deallocations_total",
+            "counter",
+            "Number of heap deallocations.",
+            alloc.deallocations,
+        );
+        push_metric(
+            &mut out,
+            "yaas_alloc_allocated_bytes_total",
+            "counter",
+            "Bytes requested from the allocator.",
+            alloc.allocated_bytes,
+        );
+        push_metric(
+            &mut out,
+            "yaas_alloc_live_bytes",
+            "gauge",
+            "Heap bytes currently in use.",
+            alloc.live_bytes,
+        );
+        push_metric(
+            &mut out,
+            "yaas_alloc_peak_bytes",
+            "gauge",
+            "Highest heap bytes in use since startup.",
+            alloc.peak_bytes,
+        );
+    }
+
+    out
+}
+
+/// Periodically logs RSS and allocator counters
+pub async fn memory_sample_job(interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    loop {
+        ticker.tick().await;
+
+        let stats = memory_stats_svc();
+        let alloc = stats.allocator.unwrap_or_default();
+        info!(
+            rss_bytes = stats.rss_bytes.unwrap_or_default(),
+            live_bytes = alloc.live_bytes,
+            peak_bytes = alloc.peak_bytes,
+            allocations = alloc.allocations,
+            "memory.sample"
+        );
+    }
+}
+
+fn push_metric(out: &mut String, name: &str, kind: &str, help: &str, value: u64) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+/// Reads `VmRSS` from `/proc/self/status`, reported in kB
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|value| value.trim().strip_suffix("kB"))
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::{AllocatorStatsDto, MemoryStatsDto};
+
+    use super::{parse_vm_rss, render_memory_metrics};
+
+    #[test]
+    fn test_parse_vm_rss() {
+        let status = "Name:\tyaas\nVmPeak:\t  20480 kB\nVmRSS:\t   1024 kB\nThreads:\t4\n";
+        assert_eq!(parse_vm_rss(status), Some(1024 * 1024));
+        assert_eq!(parse_vm_rss("Name:\tyaas\n"), None);
+    }
+
+    #[test]
+    fn test_render_memory_metrics() {
+        let stats = MemoryStatsDto {
+            rss_bytes: Some(4096),
+            allocator: None,
+        };
+        let text = render_memory_metrics(&stats);
+        assert!(text.contains("# TYPE yaas_process_resident_memory_bytes gauge\n"));
+        assert!(text.contains("yaas_process_resident_memory_bytes 4096\n"));
+        assert!(!text.contains("yaas_alloc_"));
+
+        let stats = MemoryStatsDto {
+            rss_bytes: None,
+            allocator: Some(AllocatorStatsDto {
+                allocations: 3,
+                live_bytes: 10,
+                ..Default::default()
+            }),
+        };
+        let text = render_memory_metrics(&stats);
+        assert!(text.contains("yaas_alloc_allocations_total 3\n"));
+        assert!(text.contains("yaas_alloc_live_bytes 10\n"));
+        assert!(!text.contains("resident_memory"));
+    }
+}
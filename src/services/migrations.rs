@@ -0,0 +1,278 @@
+use snafu::ensure;
+use std::collections::HashSet;
+use tracing::instrument;
+
+use crate::Result;
+use crate::db::{DbMapper, Migration};
+use crate::dto::{MigrateOptionsDto, MigrateReportDto, MigrationLintDto, MigrationPhase};
+use crate::error::ValidationSnafu;
+
+/// Flags statements that break servers still running the previous release.
+///
+/// Drops and renames are only allowed in contract migrations. Type changes
+/// and indexes built on existing tables lock writes and are always flagged.
+pub fn lint_migration(migration: &Migration) -> Vec<MigrationLintDto> {
+    let phase = MigrationPhase::from_name(migration.name);
+    let statements: Vec<String> = migration
+        .sql
+        .split(';')
+        .map(normalize_statement)
+        .filter(|stmt| !stmt.is_empty())
+        .collect();
+
+    // Indexes on tables created by the same migration build on empty tables
+    let created: HashSet<&str> = statements
+        .iter()
+        .filter_map(|stmt| {
+            let rest = stmt.strip_prefix("CREATE TABLE ")?;
+            let rest = rest.strip_prefix("IF NOT EXISTS ").unwrap_or(rest);
+            rest.split([' ', '(']).next()
+        })
+        .collect();
+
+    let mut lints = Vec::new();
+    for stmt in statements.iter() {
+        let rule = match stmt.as_str() {
+            s if s.starts_with("DROP TABLE ") => Some("drop_table"),
+            s if s.starts_with("ALTER TABLE ") && s.contains(" DROP ") => Some("drop_column"),
+            s if s.starts_with("ALTER TABLE ") && s.contains(" RENAME ") => Some("rename"),
+            s if s.starts_with("ALTER TABLE ") && s.contains(" ALTER COLUMN ") => {
+                Some("type_change")
+            }
+            s if s.starts_with("CREATE INDEX ") || s.starts_with("CREATE UNIQUE INDEX ") => s
+                .split(" ON ")
+                .nth(1)
+                .and_then(|rest| rest.split([' ', '(']).next())
+                .filter(|table| !created.contains(table))
+                .map(|_| "blocking_index"),
+            _ => None,
+        };
+
+        let allowed = phase == MigrationPhase::Contract
+            && matches!(rule, Some("drop_table" | "drop_column" | "rename"));
+
+        if let Some(rule) = rule
+            && !allowed
+        {
+            lints.push(MigrationLintDto {
+                migration: migration.name.to_string(),
+                rule: rule.to_string(),
+                statement: stmt.clone(),
+            });
+        }
+    }
+
+    lints
+}
+
+/// Applies pending migrations in order, stopping at the first one that is
+/// flagged by the linter or belongs to a phase that was not requested.
+///
+/// Fresh databases skip the linter since there is no data or running server
+/// to protect.
+#[instrument(level = "debug", skip_all)]
+pub async fn migrate_svc(
+    db: &DbMapper,
+    migrations: &[Migration],
+    options: MigrateOptionsDto,
+) -> Result<MigrateReportDto> {
+    let tables = db.schema.list_tables().await?;
+    db.schema.ensure_migrations_table().await?;
+
+    let applied: HashSet<String> = db
+        .schema
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .collect();
+
+    let has_schema = tables
+        .iter()
+        .any(|table| table != "schema_migrations" && !table.starts_with("sqlite_"));
+
+    let pending: Vec<&Migration> = migrations
+        .iter()
+        .filter(|migration| !applied.contains(migration.name))
+        .collect();
+
+    let mut report = MigrateReportDto::default();
+
+    if options.baseline {
+        for migration in pending {
+            if !options.check {
+                let phase = MigrationPhase::from_name(migration.name);
+                db.schema
+                    .apply_migration(migration.name, phase, None)
+                    .await?;
+            }
+            report.baselined.push(migration.name.to_string());
+        }
+        return Ok(report);
+    }
+
+    ensure!(
+        !(applied.is_empty() && has_schema),
+        ValidationSnafu {
+            msg: "The database has tables but no migration history. Run `yaas migrate --baseline` once to record the current schema.".to_string(),
+        }
+    );
+
+    let fresh = applied.is_empty() && !has_schema;
+
+    for (index, migration) in pending.iter().enumerate() {
+        let phase = MigrationPhase::from_name(migration.name);
+
+        if phase == MigrationPhase::Contract && !options.contract {
+            report.blocked = Some(format!(
+                "{} is a contract migration. Run with --contract once every server runs the new release.",
+                migration.name
+            ));
+        }
+
+        if report.blocked.is_none() && !fresh {
+            let lints = lint_migration(migration);
+            if !lints.is_empty() && !options.allow_unsafe {
+                report.blocked = Some(format!(
+                    "{} has unsafe statements. Review them and run with --allow-unsafe.",
+                    migration.name
+                ));
+            }
+            report.lints.extend(lints);
+        }
+
+        if report.blocked.is_some() || options.check {
+            report.pending.extend(
+                pending[index..]
+                    .iter()
+                    .map(|migration| migration.name.to_string()),
+            );
+            break;
+        }
+
+        db.schema
+            .apply_migration(migration.name, phase, Some(migration.sql))
+            .await?;
+        report.applied.push(migration.name.to_string());
+    }
+
+    Ok(report)
+}
+
+/// Uppercases keywords and collapses whitespace for matching
+fn normalize_statement(stmt: &str) -> String {
+    let stmt: String = stmt
+        .lines()
+        .map(|line| line.split("--").next().unwrap_or_default())
+        .collect::<Vec<&str>>()
+        .join(" ");
+
+    stmt.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::{MIGRATIONS, Migration};
+    use crate::dto::MigrateOptionsDto;
+    use crate::test::TestCtx;
+
+    use super::{lint_migration, migrate_svc};
+
+    fn rules(migration: &Migration) -> Vec<String> {
+        lint_migration(migration)
+            .into_iter()
+            .map(|lint| lint.rule)
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_migration() {
+        let expand = Migration {
+            name: "21-drop-legacy.sql",
+            sql: "ALTER TABLE users DROP COLUMN legacy;\nDROP TABLE old_things;\nALTER TABLE orgs RENAME COLUMN name TO title;\nCREATE INDEX idx_users_name ON users(name);",
+        };
+        assert_eq!(
+            rules(&expand),
+            vec!["drop_column", "drop_table", "rename", "blocking_index"]
+        );
+
+        let contract = Migration {
+            name: "21-contract-drop-legacy.sql",
+            sql: expand.sql,
+        };
+        assert_eq!(rules(&contract), vec!["blocking_index"]);
+
+        let new_table = Migration {
+            name: "21-create-things.sql",
+            sql: "CREATE TABLE things (\n    id TEXT PRIMARY KEY,\n    name TEXT\n);\nCREATE INDEX idx_things_name ON things(name);\nALTER TABLE users ADD COLUMN nickname TEXT DEFAULT NULL;",
+        };
+        assert!(rules(&new_table).is_empty());
+    }
+
+    #[tokio::test]
+    async fn migrate_requires_baseline_then_gates_unsafe_and_contract() {
+        let ctx = TestCtx::new("migrate_gates").await.expect("test ctx");
+        let db = &ctx.state.db;
+
+        let err = migrate_svc(db, MIGRATIONS, MigrateOptionsDto::default())
+            .await
+            .expect_err("history is required");
+        assert!(err.to_string().contains("--baseline"));
+
+        let options = MigrateOptionsDto {
+            baseline: true,
+            ..Default::default()
+        };
+        let report = migrate_svc(db, MIGRATIONS, options)
+            .await
+            .expect("baseline");
+        assert_eq!(report.baselined.len(), MIGRATIONS.len());
+
+        let report = migrate_svc(db, MIGRATIONS, MigrateOptionsDto::default())
+            .await
+            .expect("up to date");
+        assert!(report.applied.is_empty() && report.pending.is_empty());
+
+        let next: Vec<Migration> = vec![
+            Migration {
+                name: "21-add-users-nickname.sql",
+                sql: "ALTER TABLE users ADD COLUMN nickname TEXT DEFAULT NULL;",
+            },
+            Migration {
+                name: "22-index-users-nickname.sql",
+                sql: "CREATE INDEX idx_users_nickname ON users(nickname);",
+            },
+            Migration {
+                name: "23-contract-drop-org-apps-index.sql",
+                sql: "DROP INDEX idx_org_apps_deleted_at;",
+            },
+        ];
+        let all: Vec<Migration> = MIGRATIONS.iter().chain(next.iter()).copied().collect();
+
+        let report = migrate_svc(db, &all, MigrateOptionsDto::default())
+            .await
+            .expect("migrate");
+        assert_eq!(report.applied, vec!["21-add-users-nickname.sql"]);
+        assert_eq!(report.lints.len(), 1);
+        assert_eq!(report.lints[0].rule, "blocking_index");
+        assert!(report.blocked.is_some());
+
+        let options = MigrateOptionsDto {
+            allow_unsafe: true,
+            ..Default::default()
+        };
+        let report = migrate_svc(db, &all, options).await.expect("migrate");
+        assert_eq!(report.applied, vec!["22-index-users-nickname.sql"]);
+        assert_eq!(report.pending, vec!["23-contract-drop-org-apps-index.sql"]);
+
+        let options = MigrateOptionsDto {
+            contract: true,
+            ..Default::default()
+        };
+        let report = migrate_svc(db, &all, options).await.expect("migrate");
+        assert_eq!(report.applied, vec!["23-contract-drop-org-apps-index.sql"]);
+        assert!(report.blocked.is_none());
+    }
+}
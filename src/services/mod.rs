@@ -1,13 +1,59 @@
+pub mod admin_events;
+pub mod app_environments;
+pub mod approvals;
 pub mod apps;
 pub mod auth;
+pub mod auth_rate_limits;
+pub mod backfills;
 pub mod captcha;
+pub mod counters;
+pub mod drafts;
+pub mod elevations;
+pub mod emails;
+pub mod event_schemas;
 pub mod health;
+pub mod idempotency;
+pub mod impersonations;
+pub mod integrity;
+pub mod jobs;
+pub mod lifecycle;
+pub mod mailer;
+pub mod memory;
+pub mod migrations;
+pub mod notifications;
 pub mod oauth;
 pub mod oauth_code;
+pub mod oauth_grants;
+pub mod oidc;
+pub mod openapi;
+pub mod org_access;
 pub mod org_apps;
+pub mod org_invitations;
 pub mod org_members;
+pub mod org_owner_transfers;
+pub mod org_rate_limits;
+pub mod org_roles;
+pub mod org_settings;
+pub mod org_transfer;
 pub mod orgs;
+pub mod palette;
 pub mod password;
+pub mod password_reset;
+pub mod permissions;
+pub mod proof_keys;
+pub mod recovery;
+pub mod revocations;
+pub mod rollouts;
+pub mod sessions;
 pub mod setup;
+pub mod shared_cache;
+pub mod suggestions;
+pub mod teams;
 pub mod token;
+pub mod user_emails;
+pub mod user_erasure;
+pub mod user_exports;
+pub mod user_import;
+pub mod user_notifications;
+pub mod user_profiles;
 pub mod users;
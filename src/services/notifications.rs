@@ -0,0 +1,345 @@
+use serde::{Deserialize, Serialize};
+use snafu::ensure;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, instrument};
+
+use crate::Result;
+use crate::db::DbMapper;
+use crate::dto::{
+    AdminDigestDto, NotificationDelivery, NotificationPrefDto, OrgMemberDto, OrgPendingSummaryDto,
+    PendingMembersRowDto,
+};
+use crate::error::CsrfTokenSnafu;
+use crate::run::AppState;
+use crate::services::emails::{EmailBranding, notification_email, org_branding_svc, render_email};
+use crate::services::mailer::queue_email_svc;
+use crate::services::token::verify_csrf_token;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct NotificationPrefFormData {
+    pub token: String,
+    pub delivery: String,
+}
+
+/// Queues a notification email to the recipient
+async fn deliver_notification(
+    db: &DbMapper,
+    branding: &EmailBranding,
+    org_id: Option<String>,
+    recipient: &str,
+    subject: &str,
+    body: &str,
+) -> Result<()> {
+    let message = render_email(
+        branding,
+        org_id,
+        recipient,
+        notification_email(subject, body),
+    )?;
+    queue_email_svc(db, message).await?;
+    Ok(())
+}
+
+/// Sends the same notification to every superuser.
+///
+/// Returns the number of superusers notified.
+#[instrument(level = "debug", skip_all)]
+pub async fn notify_superusers_svc(db: &DbMapper, subject: &str, body: &str) -> Result<usize> {
+    let superusers = db.superusers.list().await?;
+    let mut count: usize = 0;
+
+    let branding = EmailBranding::yaas();
+
+    for superuser in superusers.into_iter() {
+        if let Some(user) = db.users.get(superuser.id).await? {
+            deliver_notification(db, &branding, None, &user.email, subject, body).await?;
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+/// Notification preference of the user, digest when never set
+#[instrument(level = "debug", skip_all)]
+pub async fn get_notification_pref_svc(
+    state: &AppState,
+    user_id: &str,
+) -> Result<NotificationPrefDto> {
+    let pref = state.db.notifications.get_pref(user_id.to_string()).await?;
+
+    Ok(pref.unwrap_or(NotificationPrefDto {
+        user_id: user_id.to_string(),
+        delivery: NotificationDelivery::default(),
+    }))
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn update_notification_pref_svc(
+    state: &AppState,
+    user_id: &str,
+    delivery: NotificationDelivery,
+) -> Result<NotificationPrefDto> {
+    state
+        .db
+        .notifications
+        .upsert_pref(NotificationPrefDto {
+            user_id: user_id.to_string(),
+            delivery,
+        })
+        .await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn update_notification_pref_web_svc(
+    state: &AppState,
+    user_id: &str,
+    form: NotificationPrefFormData,
+) -> Result<NotificationPrefDto> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == "notification_prefs", CsrfTokenSnafu);
+
+    let delivery = NotificationDelivery::try_from(form.delivery.as_str())?;
+    update_notification_pref_svc(state, user_id, delivery).await
+}
+
+/// Groups pending memberships per admin, keeping only digest subscribers
+/// with something to report. Rows must be ordered by admin.
+pub fn build_admin_digests(rows: Vec<PendingMembersRowDto>) -> Vec<AdminDigestDto> {
+    let mut digests: Vec<AdminDigestDto> = Vec::new();
+
+    for row in rows.into_iter() {
+        if row.delivery != NotificationDelivery::Digest || row.pending_count == 0 {
+            continue;
+        }
+
+        let summary = OrgPendingSummaryDto {
+            org_id: row.org_id,
+            org_name: row.org_name,
+            pending_count: row.pending_count,
+        };
+
+        match digests.last_mut() {
+            Some(digest) if digest.admin_id == row.admin_id => digest.orgs.push(summary),
+            _ => digests.push(AdminDigestDto {
+                admin_id: row.admin_id,
+                admin_email: row.admin_email,
+                orgs: vec![summary],
+            }),
+        }
+    }
+
+    digests
+}
+
+fn digest_body(digest: &AdminDigestDto) -> String {
+    let lines: Vec<String> = digest
+        .orgs
+        .iter()
+        .map(|org| format!("{}: {} pending member(s)", org.org_name, org.pending_count))
+        .collect();
+
+    lines.join("\n")
+}
+
+/// Sends the pending membership digest to every subscribed org admin
+#[instrument(level = "debug", skip_all)]
+pub async fn send_notification_digests_svc(db: &DbMapper) -> Result<usize> {
+    let rows = db.notifications.list_pending_members().await?;
+    let digests = build_admin_digests(rows);
+
+    let branding = EmailBranding::yaas();
+
+    for digest in digests.iter() {
+        deliver_notification(
+            db,
+            &branding,
+            None,
+            &digest.admin_email,
+            "Pending org memberships",
+            &digest_body(digest),
+        )
+        .await?;
+    }
+
+    info!(sent = digests.len(), "notification_digest.completed");
+
+    Ok(digests.len())
+}
+
+/// Periodically sends the pending membership digest
+pub async fn notification_digest_job(db: Arc<DbMapper>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+
+    // Skip the immediate first tick so restarts do not resend the digest
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+
+        if let Err(e) = send_notification_digests_svc(&db).await {
+            error!("Notification digest failed: {}", e);
+        }
+    }
+}
+
+/// Tells admins who opted for instant delivery about a new pending member
+#[instrument(level = "debug", skip_all)]
+pub async fn notify_pending_member_svc(state: &AppState, member: &OrgMemberDto) -> Result<usize> {
+    let rows = state
+        .db
+        .notifications
+        .list_org_pending_members(member.org_id.clone())
+        .await?;
+
+    let member_label = member
+        .member_email
+        .clone()
+        .unwrap_or_else(|| member.user_id.clone());
+
+    let recipients: Vec<PendingMembersRowDto> = rows
+        .into_iter()
+        .filter(|row| row.delivery == NotificationDelivery::Instant)
+        .filter(|row| row.admin_id != member.user_id)
+        .collect();
+
+    let branding = org_branding_svc(&state.db, &member.org_id).await?;
+
+    for row in recipients.iter() {
+        deliver_notification(
+            &state.db,
+            &branding,
+            Some(member.org_id.clone()),
+            &row.admin_email,
+            "New pending org member",
+            &format!(
+                "{} is waiting to be activated in {}. {} pending member(s) in total.",
+                member_label, row.org_name, row.pending_count
+            ),
+        )
+        .await?;
+    }
+
+    Ok(recipients.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::{NewOrgMemberDto, NotificationDelivery, PendingMembersRowDto};
+    use crate::services::org_members::create_org_member_svc;
+    use crate::test::TestCtx;
+
+    use super::{
+        build_admin_digests, get_notification_pref_svc, notify_pending_member_svc,
+        send_notification_digests_svc, update_notification_pref_svc,
+    };
+
+    fn row(
+        admin_id: &str,
+        delivery: NotificationDelivery,
+        org: &str,
+        count: i64,
+    ) -> PendingMembersRowDto {
+        PendingMembersRowDto {
+            admin_id: admin_id.to_string(),
+            admin_email: format!("{}@example.com", admin_id),
+            delivery,
+            org_id: format!("org_{}", org),
+            org_name: org.to_string(),
+            pending_count: count,
+        }
+    }
+
+    #[test]
+    fn build_admin_digests_groups_by_admin() {
+        let digests = build_admin_digests(vec![
+            row("a", NotificationDelivery::Digest, "Alpha", 2),
+            row("a", NotificationDelivery::Digest, "Beta", 0),
+            row("a", NotificationDelivery::Digest, "Gamma", 1),
+            row("b", NotificationDelivery::Instant, "Alpha", 2),
+            row("c", NotificationDelivery::Off, "Alpha", 2),
+            row("d", NotificationDelivery::Digest, "Beta", 0),
+        ]);
+
+        assert_eq!(digests.len(), 1);
+        assert_eq!(digests[0].admin_id, "a");
+        let orgs: Vec<&str> = digests[0]
+            .orgs
+            .iter()
+            .map(|o| o.org_name.as_str())
+            .collect();
+        assert_eq!(orgs, vec!["Alpha", "Gamma"]);
+    }
+
+    #[tokio::test]
+    async fn pending_members_are_notified_per_preference() {
+        let ctx = TestCtx::new("notifications_pending")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Digest Admin",
+                "digest.admin@example.com",
+                "password123",
+                "Digest Org",
+            )
+            .await
+            .expect("auth fixture");
+        let pending = ctx
+            .seed_user_with_password("Pending", "pending@example.com", "password123")
+            .await
+            .expect("pending user");
+
+        let pref = get_notification_pref_svc(&ctx.state, &fixture.user.id)
+            .await
+            .expect("pref should load");
+        assert_eq!(pref.delivery, NotificationDelivery::Digest);
+
+        let member = create_org_member_svc(
+            &ctx.state,
+            &fixture.org.id,
+            NewOrgMemberDto {
+                user_id: pending.id.clone(),
+                roles: vec!["OrgViewer".to_string()],
+                status: "inactive".to_string(),
+            },
+        )
+        .await
+        .expect("member should be created");
+
+        let sent = send_notification_digests_svc(&ctx.state.db)
+            .await
+            .expect("digest should be sent");
+        assert_eq!(sent, 1);
+
+        // Digest subscribers are not notified instantly
+        let notified = notify_pending_member_svc(&ctx.state, &member)
+            .await
+            .expect("notify should pass");
+        assert_eq!(notified, 0);
+
+        update_notification_pref_svc(&ctx.state, &fixture.user.id, NotificationDelivery::Instant)
+            .await
+            .expect("pref should be saved");
+
+        let notified = notify_pending_member_svc(&ctx.state, &member)
+            .await
+            .expect("notify should pass");
+        assert_eq!(notified, 1);
+
+        let sent = send_notification_digests_svc(&ctx.state.db)
+            .await
+            .expect("digest should be sent");
+        assert_eq!(sent, 0);
+
+        update_notification_pref_svc(&ctx.state, &fixture.user.id, NotificationDelivery::Off)
+            .await
+            .expect("pref should be saved");
+
+        let notified = notify_pending_member_svc(&ctx.state, &member)
+            .await
+            .expect("notify should pass");
+        assert_eq!(notified, 0);
+    }
+}
@@ -1,104 +1,231 @@
-use snafu::{OptionExt, ensure};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, ensure};
+use tracing::instrument;
 
 use crate::ctx::Ctx;
 use crate::dto::{
-    ActorPayloadDto, NewOauthCodeDto, OauthAuthorizationCodeDto, OauthAuthorizeDto,
-    OauthClientAppDto, OauthClientLookupDto, OauthTokenRequestDto, OauthTokenResponseDto, Scope,
-    to_scopes,
+    APP_SCOPES, ActorDto, ActorPayloadDto, CLIENT_CREDENTIALS_SCOPES, NewOauthCodeDto,
+    NewOauthGrantDto, ORG_APP_ROLES, OauthAuthorizationCodeDto, OauthAuthorizeDto,
+    OauthClientAppDto, OauthClientCredentialsRequestDto, OauthClientDto, OauthClientLookupDto,
+    OauthConsentDto, OauthIntrospectRequestDto, OauthIntrospectionDto, OauthRevokeRequestDto,
+    OauthTokenRequestDto, OauthTokenResponseDto, Scope, join_scopes, parse_scopes,
 };
 use crate::error::{
-    AppNotRegisteredSnafu, ForbiddenSnafu, InvalidClientSnafu, OauthCodeInvalidSnafu,
-    OauthInvalidScopesSnafu, OauthStateMismatchSnafu, RedirectUriMistmatchSnafu,
+    AppNotRegisteredSnafu, CsrfTokenSnafu, ForbiddenSnafu, InvalidAuthTokenSnafu,
+    InvalidClientSnafu, OauthCodeInvalidSnafu, OauthInvalidScopesSnafu, OauthStateMismatchSnafu,
+    OrgNotFoundSnafu, RedirectUriMistmatchSnafu, UnsupportedGrantTypeSnafu, UnverifiedClientSnafu,
+    UserNotFoundSnafu, ValidationSnafu,
 };
 use crate::run::AppState;
-use crate::services::oauth_code::{create_oauth_code_svc, delete_oauth_code_svc};
-use crate::services::token::create_auth_token;
-use crate::utils::{IdPrefix, generate_id, validate_redirect_uri};
+use crate::services::app_environments::resolve_oauth_client_svc;
+use crate::services::oauth_code::{consume_oauth_code_svc, create_oauth_code_svc};
+use crate::services::oauth_grants::{
+    grant_covers_scopes_svc, upsert_oauth_grant_svc, verify_oauth_grant_svc,
+};
+use crate::services::oidc::create_id_token_svc;
+use crate::services::org_apps::{granted_app_scopes, verify_org_app_link_svc};
+use crate::services::revocations::{
+    revoke_token_svc, verify_not_denied_svc, verify_not_revoked_svc,
+};
+use crate::services::sessions::verify_session_svc;
+use crate::services::token::{
+    EXP_DURATION, create_access_token, create_auth_token, verify_auth_token, verify_csrf_token,
+};
+use crate::utils::{IdPrefix, generate_id, hash_secret, validate_redirect_uri};
 use crate::{Error, Result};
 
+#[derive(Clone, Deserialize, Serialize)]
+pub struct OauthConsentFormData {
+    pub token: String,
+    pub client_id: String,
+    pub redirect_uri: String,
+    pub scope: String,
+    pub state: String,
+    pub nonce: Option<String>,
+
+    /// Either `approve` or `deny`
+    pub decision: String,
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn create_authorization_code_svc(
     state: &AppState,
     ctx: &Ctx,
     query: &OauthAuthorizeDto,
 ) -> Result<OauthAuthorizationCodeDto> {
-    // Validate scopes
-    let scope_list: Vec<String> = query
-        .scope
-        .split(' ')
-        .filter(|scope| !scope.is_empty())
-        .map(|scope| scope.to_string())
-        .collect();
+    let actor = ctx.actor().context(InvalidClientSnafu)?;
+    let (client, scopes) = verify_authorize_request(state, actor, query).await?;
 
-    // Convert it to scope enums
-    let scopes = to_scopes(&scope_list)?;
+    // Generate oauth_code object to be finalized later at token generation
+    let code = generate_id(IdPrefix::OauthCode);
 
-    // Allowed scopes
-    let allowed_scopes = [Scope::Auth, Scope::Oauth];
+    let new_code = NewOauthCodeDto {
+        code: code.clone(),
+        state: query.state.clone(),
+        redirect_uri: query.redirect_uri.clone(),
+        scope: join_scopes(&scopes),
+        app_id: client.app.id,
+        org_id: actor.org_id.clone(),
+        user_id: actor.id.clone(),
+        nonce: query.nonce.clone(),
+    };
 
-    // Ensure all requested scopes are allowed
-    let invalid_scopes = scopes
-        .iter()
-        .filter(|s| !allowed_scopes.contains(s))
-        .count();
+    create_oauth_code_svc(state, new_code).await?;
 
-    ensure!(invalid_scopes == 0, InvalidClientSnafu);
+    let auth_code = OauthAuthorizationCodeDto {
+        code: code.clone(),
+        state: query.state.clone(),
+    };
+
+    Ok(auth_code)
+}
 
+/// What the user is asked to approve for the authorize request.
+///
+/// The prompt is skipped when an earlier approval for the app within the
+/// org already covers every requested scope.
+#[instrument(level = "debug", skip_all)]
+pub async fn oauth_consent_svc(
+    state: &AppState,
+    ctx: &Ctx,
+    query: &OauthAuthorizeDto,
+) -> Result<OauthConsentDto> {
     let actor = ctx.actor().context(InvalidClientSnafu)?;
+    let (client, scopes) = verify_authorize_request(state, actor, query).await?;
 
-    // Validate client_id from the apps table
-    let app = state
-        .db
-        .apps
-        .find_by_client_id(query.client_id.clone())
-        .await?;
+    let org = state.db.orgs.get(actor.org_id.clone()).await?;
+    let org = org.context(OrgNotFoundSnafu)?;
+
+    let granted =
+        grant_covers_scopes_svc(state, &actor.id, &actor.org_id, &client.app.id, &scopes).await?;
 
-    let app = app.context(InvalidClientSnafu)?;
-    let app_id = app.id.clone();
-    let actor_org_id = actor.org_id.clone();
-    let actor_user_id = actor.id.clone();
+    Ok(OauthConsentDto {
+        app_name: client.app.name,
+        org_name: org.name,
+        scopes,
+        required: !granted,
+    })
+}
+
+/// Records the user's approval of the requested scopes and issues the code
+#[instrument(level = "debug", skip_all)]
+pub async fn approve_oauth_consent_svc(
+    state: &AppState,
+    ctx: &Ctx,
+    query: &OauthAuthorizeDto,
+) -> Result<OauthAuthorizationCodeDto> {
+    let actor = ctx.actor().context(InvalidClientSnafu)?;
+    let (client, scopes) = verify_authorize_request(state, actor, query).await?;
+
+    upsert_oauth_grant_svc(
+        state,
+        NewOauthGrantDto {
+            user_id: actor.id.clone(),
+            org_id: actor.org_id.clone(),
+            app_id: client.app.id,
+            scope: join_scopes(&scopes),
+        },
+    )
+    .await?;
+
+    create_authorization_code_svc(state, ctx, query).await
+}
+
+/// Approves or denies the consent screen, denials reach the app as `access_denied`
+#[instrument(level = "debug", skip_all)]
+pub async fn decide_oauth_consent_web_svc(
+    state: &AppState,
+    ctx: &Ctx,
+    query: &OauthAuthorizeDto,
+    approved: bool,
+    csrf_token: &str,
+) -> Result<OauthAuthorizationCodeDto> {
+    let actor = ctx.actor().context(InvalidClientSnafu)?;
+
+    // Errors are only reported back once the client and redirect_uri are trusted
+    verify_authorize_request(state, actor, query).await?;
+
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == query.client_id, CsrfTokenSnafu);
+
+    ensure!(
+        approved,
+        ForbiddenSnafu {
+            msg: "The user denied the request".to_string(),
+        }
+    );
+
+    approve_oauth_consent_svc(state, ctx, query).await
+}
+
+/// Client and scopes of an authorize request the user may approve
+async fn verify_authorize_request(
+    state: &AppState,
+    actor: &ActorDto,
+    query: &OauthAuthorizeDto,
+) -> Result<(OauthClientDto, Vec<Scope>)> {
+    // Validate client_id and redirect_uri first so that later errors
+    // can be safely reported back to the client via redirect, lookup
+    // failures are not as the redirect_uri is not trusted yet
+    let client = resolve_oauth_client_svc(state, &query.client_id)
+        .await
+        .context(UnverifiedClientSnafu)?;
+    let client = client.context(InvalidClientSnafu)?;
 
     // Ensure redirect_uri is valid and matches the registered one
     ensure!(
-        validate_redirect_uri(&app.redirect_uri, &query.redirect_uri),
+        validate_redirect_uri(&client.redirect_uris, &query.redirect_uri),
         RedirectUriMistmatchSnafu
     );
 
+    // Validate scopes, they limit what the token can do with the user's roles
+    let scopes = parse_scopes(&query.scope)?;
+    ensure!(
+        scopes.iter().all(|s| APP_SCOPES.contains(s)),
+        OauthInvalidScopesSnafu
+    );
+
+    // Support staff may look around as the user but not hand out tokens for them
+    ensure!(
+        actor.impersonator_id.is_none(),
+        ForbiddenSnafu {
+            msg: "Apps cannot be authorized while impersonating".to_string(),
+        }
+    );
+
     // Ensure that the app is registered to the user's current org
     let org_app = state
         .db
         .org_apps
-        .find_app(actor_org_id.clone(), app_id.clone())
+        .find_app(actor.org_id.clone(), client.app.id.clone())
         .await?;
 
-    ensure!(org_app.is_some(), AppNotRegisteredSnafu);
+    let org_app = org_app.context(AppNotRegisteredSnafu)?;
 
-    // Generate oauth_code object to be finalized later at token generation
-    let code = generate_id(IdPrefix::OauthCode);
-
-    let new_code = NewOauthCodeDto {
-        code: code.clone(),
-        state: query.state.clone(),
-        redirect_uri: query.redirect_uri.clone(),
-        scope: query.scope.clone(),
-        app_id,
-        org_id: actor_org_id,
-        user_id: actor_user_id,
-    };
-
-    create_oauth_code_svc(&state, new_code).await?;
-
-    let auth_code = OauthAuthorizationCodeDto {
-        code: code.clone(),
-        state: query.state.clone(),
-    };
+    // Only scopes the app declares and the org allows can be requested
+    let allowed = granted_app_scopes(&client.app, &org_app)?;
+    ensure!(
+        scopes.iter().all(|s| allowed.contains(s)),
+        OauthInvalidScopesSnafu
+    );
 
-    Ok(auth_code)
+    Ok((client, scopes))
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn exchange_code_for_access_token_svc(
     state: &AppState,
     payload: &OauthTokenRequestDto,
 ) -> Result<OauthTokenResponseDto> {
+    // RFC 6749 section 4.1.3 requires the grant_type
+    let grant_type = payload.grant_type.as_deref().context(ValidationSnafu {
+        msg: "grant_type is required",
+    })?;
+    ensure!(
+        grant_type == "authorization_code",
+        UnsupportedGrantTypeSnafu
+    );
+
     // Find the authorization code
     let oauth_code = state.db.oauth_codes.find_by_code(&payload.code).await?;
 
@@ -114,39 +241,47 @@ pub async fn exchange_code_for_access_token_svc(
     );
 
     // Validate client_id and client_secret
-    let app = state
-        .db
-        .apps
-        .find_by_client_id(payload.client_id.clone())
-        .await?;
-
-    let app = app.context(InvalidClientSnafu)?;
+    let client = resolve_oauth_client_svc(state, &payload.client_id).await?;
 
+    let client = client.context(InvalidClientSnafu)?;
     ensure!(
-        app.client_secret == payload.client_secret,
+        client.accepts_secret_hash(&hash_secret(&payload.client_secret)),
         InvalidClientSnafu
     );
 
-    // Parse scopes
-    let scope_list: Vec<String> = oauth_code
-        .scope
-        .split(' ')
-        .filter(|scope| !scope.is_empty())
-        .map(|scope| scope.to_string())
-        .collect();
+    let app = client.app;
 
-    let scopes = to_scopes(&scope_list)?;
+    // The code must be redeemed by the app and environment it was issued to
+    ensure!(
+        app.id == oauth_code.app_id
+            && validate_redirect_uri(&client.redirect_uris, &oauth_code.redirect_uri),
+        InvalidClientSnafu
+    );
 
-    // Allowed scopes
-    let allowed_scopes = [Scope::Auth, Scope::Oauth];
+    // Burn the code before issuing, concurrent exchanges of the same code
+    // race on the delete and only one of them wins
+    let consumed = consume_oauth_code_svc(state, &oauth_code.id).await?;
+    ensure!(consumed, OauthCodeInvalidSnafu);
 
-    // Ensure all requested scopes are allowed
-    let invalid_scopes: Vec<&Scope> = scopes
-        .iter()
-        .filter(|s| !allowed_scopes.contains(s))
-        .collect();
+    // Scopes granted at authorization, checked again in case they were narrowed since
+    let scopes = parse_scopes(&oauth_code.scope)?;
+    ensure!(
+        scopes.iter().all(|s| APP_SCOPES.contains(s)),
+        OauthInvalidScopesSnafu
+    );
 
-    ensure!(invalid_scopes.is_empty(), OauthInvalidScopesSnafu);
+    let org_app = state
+        .db
+        .org_apps
+        .find_app(oauth_org_id.clone(), app.id.clone())
+        .await?;
+    let org_app = org_app.context(AppNotRegisteredSnafu)?;
+    let allowed = granted_app_scopes(&app, &org_app)?;
+    ensure!(
+        scopes.iter().all(|s| allowed.contains(s)),
+        OauthInvalidScopesSnafu
+    );
+    let granted_scope = join_scopes(&scopes);
 
     // Fetch roles for the user in the org
     let membership = state
@@ -166,6 +301,36 @@ pub async fn exchange_code_for_access_token_svc(
         .list_memberships_count(oauth_user_id.clone())
         .await?;
 
+    let user = state.db.users.get(oauth_user_id.clone()).await?;
+    let user = user.context(UserNotFoundSnafu)?;
+
+    // Record the authorization so the user can review and revoke it later
+    let grant = upsert_oauth_grant_svc(
+        state,
+        NewOauthGrantDto {
+            user_id: oauth_user_id.clone(),
+            org_id: oauth_org_id.clone(),
+            app_id: oauth_code.app_id.clone(),
+            scope: granted_scope.clone(),
+        },
+    )
+    .await?;
+
+    // Bind the token when the app registered a proof key
+    let proof_key_id = state
+        .db
+        .app_proof_keys
+        .find(app.id.clone())
+        .await?
+        .map(|key| key.key_id);
+
+    // ID token for OpenID Connect clients, addressed to the requesting client
+    let id_token = if scopes.contains(&Scope::Openid) {
+        create_id_token_svc(state, &user, &payload.client_id, oauth_code.nonce.clone())?
+    } else {
+        None
+    };
+
     // Create a token
     let payload = ActorPayloadDto {
         id: oauth_user_id,
@@ -173,52 +338,249 @@ pub async fn exchange_code_for_access_token_svc(
         org_count: org_count as i32,
         roles: membership.roles.clone(),
         scopes,
+        grant_id: Some(grant.id),
+        proof_key_id: proof_key_id.clone(),
+        permission_mask: None,
+        issued_at: 0,
+        session_id: None,
+        region: state.config.region.name.clone(),
+        home_region: state.config.region.name.clone(),
+        token_id: None,
+        org_app_id: None,
+        impersonator_id: None,
+        impersonation_id: None,
     };
 
-    let token = create_auth_token(&payload, &state.config.jwt_secret)?;
-
-    // Cleanup oauth code so it cannot be used again
-    delete_oauth_code_svc(&state, &oauth_code.id).await?;
+    let token = create_access_token(
+        &payload,
+        &user,
+        &state.config.token_claims,
+        &state.token_keys,
+    )?;
 
     let response = OauthTokenResponseDto {
         access_token: token,
-        scope: oauth_code.scope,
+        scope: granted_scope,
         token_type: "app".to_string(),
+        key_id: proof_key_id,
+        id_token,
     };
 
     Ok(response)
 }
 
+/// Issues a token to a server-to-server app for an org it is linked to.
+///
+/// The token has no user, its permissions derive from the org app link
+/// and it stops working once the app is unlinked.
+#[instrument(level = "debug", skip_all)]
+pub async fn issue_client_credentials_token_svc(
+    state: &AppState,
+    payload: &OauthClientCredentialsRequestDto,
+) -> Result<OauthTokenResponseDto> {
+    ensure!(
+        payload.grant_type == "client_credentials",
+        UnsupportedGrantTypeSnafu
+    );
+
+    let client = resolve_oauth_client_svc(state, &payload.client_id).await?;
+    let client = client.context(InvalidClientSnafu)?;
+    ensure!(
+        client.accepts_secret_hash(&hash_secret(&payload.client_secret)),
+        InvalidClientSnafu
+    );
+
+    let app = client.app;
+
+    let scopes = parse_scopes(payload.scope.as_deref().unwrap_or("oauth"))?;
+
+    // There is no user to act for, only app level scopes are granted
+    ensure!(
+        !scopes.is_empty() && scopes.iter().all(|s| CLIENT_CREDENTIALS_SCOPES.contains(s)),
+        OauthInvalidScopesSnafu
+    );
+    let granted_scope = join_scopes(&scopes);
+
+    let org_app = state
+        .db
+        .org_apps
+        .find_app(payload.org_id.clone(), app.id.clone())
+        .await?;
+    let org_app = org_app.context(AppNotRegisteredSnafu)?;
+
+    let allowed = granted_app_scopes(&app, &org_app)?;
+    ensure!(
+        scopes.iter().all(|s| allowed.contains(s)),
+        OauthInvalidScopesSnafu
+    );
+
+    let org = state.db.orgs.get(org_app.org_id.clone()).await?;
+    let _ = org.context(OrgNotFoundSnafu)?;
+
+    let proof_key_id = state
+        .db
+        .app_proof_keys
+        .find(app.id.clone())
+        .await?
+        .map(|key| key.key_id);
+
+    let actor = ActorPayloadDto {
+        id: app.id,
+        org_id: org_app.org_id,
+        org_count: 1,
+        roles: ORG_APP_ROLES.to_vec(),
+        scopes,
+        grant_id: None,
+        proof_key_id: proof_key_id.clone(),
+        permission_mask: None,
+        issued_at: 0,
+        session_id: None,
+        region: state.config.region.name.clone(),
+        home_region: state.config.region.name.clone(),
+        token_id: None,
+        org_app_id: Some(org_app.id),
+        impersonator_id: None,
+        impersonation_id: None,
+    };
+
+    let token = create_auth_token(&actor, &state.token_keys)?;
+
+    Ok(OauthTokenResponseDto {
+        access_token: token,
+        scope: granted_scope,
+        token_type: "app".to_string(),
+        key_id: proof_key_id,
+        id_token: None,
+    })
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn lookup_oauth_client_app_svc(
     state: &AppState,
     payload: &OauthClientLookupDto,
 ) -> Result<OauthClientAppDto> {
     // Find app by client_id
-    let Some(app) = state
-        .db
-        .apps
-        .find_by_client_id(payload.client_id.to_owned())
-        .await?
-    else {
+    let Some(client) = resolve_oauth_client_svc(state, &payload.client_id).await? else {
         return Err(Error::InvalidClient);
     };
 
     // Validate if redirect_uri is valid
     ensure!(
-        validate_redirect_uri(&app.redirect_uri, &payload.redirect_uri),
+        validate_redirect_uri(&client.redirect_uris, &payload.redirect_uri),
+        InvalidClientSnafu
+    );
+
+    Ok(OauthClientAppDto {
+        name: client.app.name,
+        environment: client.environment,
+    })
+}
+
+/// Reports whether a token is active for the requesting app, see RFC 7662.
+///
+/// Only tokens the app received from the token exchange are active for it,
+/// session tokens and tokens of other apps are reported inactive.
+#[instrument(level = "debug", skip_all)]
+pub async fn introspect_token_svc(
+    state: &AppState,
+    payload: &OauthIntrospectRequestDto,
+) -> Result<OauthIntrospectionDto> {
+    let client = resolve_oauth_client_svc(state, &payload.client_id).await?;
+    let client = client.context(InvalidClientSnafu)?;
+    ensure!(
+        client.accepts_secret_hash(&hash_secret(&payload.client_secret)),
+        InvalidClientSnafu
+    );
+
+    let actor = match verify_app_token(state, &client.app.id, &payload.token).await {
+        Ok(actor) => actor,
+        Err(Error::InvalidAuthToken) => return Ok(OauthIntrospectionDto::default()),
+        Err(e) => return Err(e),
+    };
+
+    let scopes: Vec<String> = actor.scopes.iter().map(|s| s.to_string()).collect();
+
+    Ok(OauthIntrospectionDto {
+        active: true,
+        scope: Some(scopes.join(" ")),
+        client_id: Some(payload.client_id.clone()),
+        token_type: Some("app".to_string()),
+        sub: Some(actor.id),
+        org_id: Some(actor.org_id),
+        exp: Some(actor.issued_at + EXP_DURATION),
+        iat: Some(actor.issued_at),
+    })
+}
+
+/// Revokes a token the app received, see RFC 7009.
+///
+/// Invalid tokens and tokens of other apps are ignored, the app cannot tell them apart.
+#[instrument(level = "debug", skip_all)]
+pub async fn revoke_token_for_app_svc(
+    state: &AppState,
+    payload: &OauthRevokeRequestDto,
+) -> Result<()> {
+    let client = resolve_oauth_client_svc(state, &payload.client_id).await?;
+    let client = client.context(InvalidClientSnafu)?;
+    ensure!(
+        client.accepts_secret_hash(&hash_secret(&payload.client_secret)),
         InvalidClientSnafu
     );
 
-    Ok(OauthClientAppDto { name: app.name })
+    match verify_app_token(state, &client.app.id, &payload.token).await {
+        Ok(actor) => revoke_token_svc(state, &actor).await,
+        Err(Error::InvalidAuthToken) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Same checks as authenticating the token, plus the grant must belong to the app
+async fn verify_app_token(state: &AppState, app_id: &str, token: &str) -> Result<ActorPayloadDto> {
+    let actor = verify_auth_token(token, &state.token_keys)?;
+
+    verify_not_revoked_svc(state, &actor).await?;
+    verify_not_denied_svc(state, &actor).await?;
+
+    if let Some(session_id) = actor.session_id.as_deref() {
+        verify_session_svc(state, session_id, &actor.id).await?;
+    }
+
+    // Client credentials tokens are tied to the org app link instead of a grant
+    if let Some(org_app_id) = actor.org_app_id.as_deref() {
+        verify_org_app_link_svc(state, org_app_id, app_id, &actor.org_id).await?;
+        return Ok(actor);
+    }
+
+    let grant_id = actor.grant_id.as_deref().context(InvalidAuthTokenSnafu)?;
+    let grant = verify_oauth_grant_svc(state, grant_id).await?;
+    ensure!(grant.app_id == app_id, InvalidAuthTokenSnafu);
+
+    Ok(actor)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::dto::{NewOauthCodeDto, OauthAuthorizeDto, OauthTokenRequestDto, Scope};
+    use axum::http::StatusCode;
+
+    use crate::Error;
+    use crate::dto::{
+        ActorPayloadDto, AppRedirectUriDto, NewOauthCodeDto, OauthAuthorizeDto,
+        OauthClientCredentialsRequestDto, OauthIntrospectRequestDto, OauthRevokeRequestDto,
+        OauthTokenRequestDto, Permission, Role, Scope, UpdateOrgAppDto,
+    };
+    use crate::services::apps::{add_app_redirect_uri_svc, rotate_app_secret_svc};
+    use crate::services::auth::authenticate_token_svc;
+    use crate::services::oauth_grants::revoke_authorized_app_svc;
+    use crate::services::org_apps::{delete_org_app_svc, update_org_app_svc};
+    use crate::services::token::{create_auth_token, create_csrf_token_svc};
     use crate::test::TestCtx;
     use crate::utils::{IdPrefix, generate_id};
 
-    use super::{create_authorization_code_svc, exchange_code_for_access_token_svc};
+    use super::{
+        approve_oauth_consent_svc, create_authorization_code_svc, decide_oauth_consent_web_svc,
+        exchange_code_for_access_token_svc, introspect_token_svc,
+        issue_client_credentials_token_svc, oauth_consent_svc, revoke_token_for_app_svc,
+    };
 
     fn build_authorize(client_id: String, redirect_uri: &str, scope: &str) -> OauthAuthorizeDto {
         OauthAuthorizeDto {
@@ -226,6 +588,7 @@ mod tests {
             redirect_uri: redirect_uri.to_string(),
             scope: scope.to_string(),
             state: "state-1".to_string(),
+            nonce: None,
         }
     }
 
@@ -237,6 +600,7 @@ mod tests {
         redirect_uri: &str,
     ) -> OauthTokenRequestDto {
         OauthTokenRequestDto {
+            grant_type: Some("authorization_code".to_string()),
             client_id,
             client_secret,
             code,
@@ -278,6 +642,164 @@ mod tests {
         assert_eq!(code.state, "state-1");
     }
 
+    #[tokio::test]
+    async fn create_authorization_code_svc_rejects_scopes_the_org_restricts() {
+        let ctx = TestCtx::new("oauth_create_code_org_scopes")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "OAuth User",
+                "oauth.org.scopes@example.com",
+                "password123",
+                "OAuth Org",
+                "OAuth App",
+                "https://oauth.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+
+        update_org_app_svc(
+            &ctx.state,
+            &fixture.auth.org.id,
+            &fixture.app.id,
+            UpdateOrgAppDto {
+                scopes: Some(vec!["oauth".to_string()]),
+            },
+        )
+        .await
+        .expect("org app scopes should update");
+
+        let actor_ctx = fixture.auth.to_ctx(vec![Scope::Auth]);
+        let query = build_authorize(
+            fixture.app.client_id.clone(),
+            "https://oauth.example.com/callback",
+            "auth oauth",
+        );
+        let result = create_authorization_code_svc(&ctx.state, &actor_ctx, &query).await;
+        assert!(matches!(result, Err(Error::OauthInvalidScopes)));
+
+        let query = build_authorize(
+            fixture.app.client_id.clone(),
+            "https://oauth.example.com/callback",
+            "oauth",
+        );
+        create_authorization_code_svc(&ctx.state, &actor_ctx, &query)
+            .await
+            .expect("allowed scope should be authorized");
+    }
+
+    #[tokio::test]
+    async fn oauth_consent_is_remembered_until_revoked() {
+        let ctx = TestCtx::new("oauth_consent_remembered")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "OAuth User",
+                "oauth.consent@example.com",
+                "password123",
+                "OAuth Org",
+                "OAuth App",
+                "https://oauth.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+
+        let actor_ctx = fixture.auth.to_ctx(vec![Scope::Auth]);
+        let query = build_authorize(
+            fixture.app.client_id.clone(),
+            "https://oauth.example.com/callback",
+            "auth oauth",
+        );
+
+        let consent = oauth_consent_svc(&ctx.state, &actor_ctx, &query)
+            .await
+            .expect("consent should load");
+        assert!(consent.required, "first authorization should ask");
+        assert_eq!(consent.app_name, "OAuth App");
+        assert_eq!(consent.org_name, "OAuth Org");
+        assert_eq!(consent.scopes, vec![Scope::Auth, Scope::Oauth]);
+
+        approve_oauth_consent_svc(&ctx.state, &actor_ctx, &query)
+            .await
+            .expect("approval should create a code");
+
+        let consent = oauth_consent_svc(&ctx.state, &actor_ctx, &query)
+            .await
+            .expect("consent should load");
+        assert!(!consent.required, "approved scopes should skip the prompt");
+
+        // Asking for more than what was approved prompts again
+        let broader = build_authorize(
+            fixture.app.client_id.clone(),
+            "https://oauth.example.com/callback",
+            "auth oauth openid",
+        );
+        let consent = oauth_consent_svc(&ctx.state, &actor_ctx, &broader)
+            .await
+            .expect("consent should load");
+        assert!(consent.required, "new scopes should ask again");
+
+        revoke_authorized_app_svc(&ctx.state, &fixture.auth.user.id, &fixture.app.id)
+            .await
+            .expect("revoke should succeed");
+
+        let consent = oauth_consent_svc(&ctx.state, &actor_ctx, &query)
+            .await
+            .expect("consent should load");
+        assert!(consent.required, "revoked apps should ask again");
+    }
+
+    #[tokio::test]
+    async fn decide_oauth_consent_web_svc_denial_is_access_denied() {
+        let ctx = TestCtx::new("oauth_consent_denied")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "OAuth User",
+                "oauth.consent.deny@example.com",
+                "password123",
+                "OAuth Org",
+                "OAuth App",
+                "https://oauth.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+
+        let actor_ctx = fixture.auth.to_ctx(vec![Scope::Auth]);
+        let query = build_authorize(
+            fixture.app.client_id.clone(),
+            "https://oauth.example.com/callback",
+            "auth",
+        );
+
+        let result =
+            decide_oauth_consent_web_svc(&ctx.state, &actor_ctx, &query, true, "bogus").await;
+        let err = result.err().expect("invalid csrf should fail");
+        assert_eq!(err.oauth_error_code(), None);
+
+        let token = create_csrf_token_svc(&query.client_id, &ctx.state.config.jwt_secret)
+            .expect("csrf token");
+        let result =
+            decide_oauth_consent_web_svc(&ctx.state, &actor_ctx, &query, false, &token).await;
+        let err = result.err().expect("denial should fail");
+        assert_eq!(err.oauth_error_code(), Some("access_denied"));
+
+        let consent = oauth_consent_svc(&ctx.state, &actor_ctx, &query)
+            .await
+            .expect("consent should load");
+        assert!(consent.required, "denials should not be remembered");
+
+        decide_oauth_consent_web_svc(&ctx.state, &actor_ctx, &query, true, &token)
+            .await
+            .expect("approval should create a code");
+    }
+
     #[tokio::test]
     async fn create_authorization_code_svc_rejects_invalid_scope() {
         let ctx = TestCtx::new("oauth_create_code_invalid_scope")
@@ -307,6 +829,7 @@ mod tests {
         assert!(result.is_err(), "should reject invalid scope");
         let err = result.err().expect("error should exist");
         assert_eq!(err.to_string(), "Invalid scopes: invalid_scope");
+        assert_eq!(err.oauth_error_code(), Some("invalid_scope"));
     }
 
     #[tokio::test]
@@ -335,6 +858,7 @@ mod tests {
         assert!(result.is_err(), "should reject missing app");
         let err = result.err().expect("error should exist");
         assert_eq!(err.to_string(), "Invalid client");
+        assert_eq!(err.oauth_error_code(), None);
     }
 
     #[tokio::test]
@@ -366,6 +890,18 @@ mod tests {
         assert!(result.is_err(), "should reject redirect mismatch");
         let err = result.err().expect("error should exist");
         assert_eq!(err.to_string(), "OAuth redirect_uri mismatch");
+        assert_eq!(err.oauth_error_code(), None);
+    }
+
+    #[test]
+    fn unverified_client_errors_are_not_redirected() {
+        let err = Error::UnverifiedClient {
+            source: Box::new(Error::Service {
+                msg: "database is locked".to_string(),
+            }),
+        };
+        assert_eq!(err.oauth_error_code(), None);
+        assert!(StatusCode::from(&err).is_server_error());
     }
 
     #[tokio::test]
@@ -397,6 +933,7 @@ mod tests {
         assert!(result.is_err(), "should reject unregistered app");
         let err = result.err().expect("error should exist");
         assert_eq!(err.to_string(), "OAuth app not registered in the org");
+        assert_eq!(err.oauth_error_code(), Some("unauthorized_client"));
     }
 
     #[tokio::test]
@@ -443,6 +980,66 @@ mod tests {
         assert!(!token.access_token.is_empty());
         assert_eq!(token.scope, "auth oauth");
         assert_eq!(token.token_type, "app");
+
+        let reused = exchange_code_for_access_token_svc(&ctx.state, &payload).await;
+        let err = reused.expect_err("code is single use");
+        assert_eq!(err.to_string(), "OAuth code invalid");
+    }
+
+    #[tokio::test]
+    async fn exchange_code_for_access_token_svc_rejects_unsupported_grant_type() {
+        let ctx = TestCtx::new("oauth_exchange_grant_type")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "OAuth User",
+                "oauth.grant.type@example.com",
+                "password123",
+                "OAuth Org",
+                "OAuth App",
+                "https://oauth.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+
+        let actor_ctx = fixture.auth.to_ctx(vec![Scope::Auth]);
+        let authorize = build_authorize(
+            fixture.app.client_id.clone(),
+            "https://oauth.example.com/callback",
+            "auth",
+        );
+        let code = create_authorization_code_svc(&ctx.state, &actor_ctx, &authorize)
+            .await
+            .expect("authorization code should be created");
+
+        let mut payload = build_token_request(
+            fixture.app.client_id,
+            fixture.app.client_secret,
+            code.code,
+            &code.state,
+            "https://oauth.example.com/callback",
+        );
+        payload.grant_type = Some("client_credentials".to_string());
+
+        let result = exchange_code_for_access_token_svc(&ctx.state, &payload).await;
+        let err = result.expect_err("should reject grant_type");
+        assert_eq!(err.to_string(), "Unsupported grant_type");
+        assert_eq!(err.oauth_error_code(), Some("unsupported_grant_type"));
+
+        // A missing grant_type is an invalid request
+        payload.grant_type = None;
+        let result = exchange_code_for_access_token_svc(&ctx.state, &payload).await;
+        let err = result.expect_err("should require grant_type");
+        assert_eq!(err.to_string(), "grant_type is required");
+        assert_eq!(err.oauth_error_code(), Some("invalid_request"));
+
+        // The rejected requests do not burn the code
+        payload.grant_type = Some("authorization_code".to_string());
+        exchange_code_for_access_token_svc(&ctx.state, &payload)
+            .await
+            .expect("token exchange should succeed");
     }
 
     #[tokio::test]
@@ -473,7 +1070,7 @@ mod tests {
 
         let result = exchange_code_for_access_token_svc(&ctx.state, &payload).await;
         assert!(result.is_err(), "should reject missing code");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "OAuth code invalid");
     }
 
@@ -515,7 +1112,7 @@ mod tests {
 
         let result = exchange_code_for_access_token_svc(&ctx.state, &payload).await;
         assert!(result.is_err(), "should reject state mismatch");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "OAuth state mismatch");
     }
 
@@ -557,7 +1154,7 @@ mod tests {
 
         let result = exchange_code_for_access_token_svc(&ctx.state, &payload).await;
         assert!(result.is_err(), "should reject redirect mismatch");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "OAuth redirect_uri mismatch");
     }
 
@@ -599,7 +1196,7 @@ mod tests {
 
         let result = exchange_code_for_access_token_svc(&ctx.state, &payload).await;
         assert!(result.is_err(), "should reject missing client app");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "Invalid client");
     }
 
@@ -641,8 +1238,138 @@ mod tests {
 
         let result = exchange_code_for_access_token_svc(&ctx.state, &payload).await;
         assert!(result.is_err(), "should reject invalid client secret");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
+        assert_eq!(err.to_string(), "Invalid client");
+    }
+
+    #[tokio::test]
+    async fn exchange_code_for_access_token_svc_accepts_secrets_within_rotation_grace() {
+        let ctx = TestCtx::new("oauth_exchange_rotated_secret")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "OAuth User",
+                "oauth.exchange.rotated@example.com",
+                "password123",
+                "OAuth Org",
+                "OAuth App",
+                "https://oauth.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+
+        let actor_ctx = fixture.auth.to_ctx(vec![Scope::Auth]);
+        let exchange = |client_secret: String| {
+            let ctx = &ctx;
+            let actor_ctx = &actor_ctx;
+            let client_id = fixture.app.client_id.clone();
+            async move {
+                let authorize = build_authorize(
+                    client_id.clone(),
+                    "https://oauth.example.com/callback",
+                    "auth",
+                );
+                let code = create_authorization_code_svc(&ctx.state, actor_ctx, &authorize)
+                    .await
+                    .expect("authorization code should be created");
+                let payload = build_token_request(
+                    client_id,
+                    client_secret,
+                    code.code,
+                    &code.state,
+                    "https://oauth.example.com/callback",
+                );
+                exchange_code_for_access_token_svc(&ctx.state, &payload).await
+            }
+        };
+
+        let rotated = rotate_app_secret_svc(&ctx.state, &fixture.app.id)
+            .await
+            .expect("rotate should pass");
+
+        // Both the old and the new secret work during the grace period
+        exchange(fixture.app.client_secret.clone())
+            .await
+            .expect("previous secret should still work");
+        exchange(rotated.client_secret.clone())
+            .await
+            .expect("new secret should work");
+
+        // Rotating again drops the original secret
+        rotate_app_secret_svc(&ctx.state, &fixture.app.id)
+            .await
+            .expect("rotate should pass");
+
+        let err = exchange(fixture.app.client_secret.clone())
+            .await
+            .expect_err("original secret should be rejected");
         assert_eq!(err.to_string(), "Invalid client");
+
+        exchange(rotated.client_secret)
+            .await
+            .expect("previous secret should still work");
+    }
+
+    #[tokio::test]
+    async fn create_authorization_code_svc_accepts_any_registered_redirect_uri() {
+        let ctx = TestCtx::new("oauth_authorize_redirect_uris")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "OAuth User",
+                "oauth.authorize.uris@example.com",
+                "password123",
+                "OAuth Org",
+                "OAuth App",
+                "https://oauth.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+
+        add_app_redirect_uri_svc(
+            &ctx.state,
+            &fixture.app.id,
+            AppRedirectUriDto {
+                redirect_uri: "http://localhost/callback".to_string(),
+            },
+        )
+        .await
+        .expect("add should pass");
+
+        let actor_ctx = fixture.auth.to_ctx(vec![Scope::Auth]);
+        let authorize = |redirect_uri: &str| {
+            build_authorize(fixture.app.client_id.clone(), redirect_uri, "auth")
+        };
+
+        create_authorization_code_svc(
+            &ctx.state,
+            &actor_ctx,
+            &authorize("https://oauth.example.com/callback"),
+        )
+        .await
+        .expect("main redirect URI should work");
+        create_authorization_code_svc(
+            &ctx.state,
+            &actor_ctx,
+            &authorize("http://localhost:49152/callback"),
+        )
+        .await
+        .expect("loopback redirect URI should work on any port");
+
+        let result = create_authorization_code_svc(
+            &ctx.state,
+            &actor_ctx,
+            &authorize("https://oauth.example.com/callback/extra"),
+        )
+        .await;
+        assert!(
+            matches!(result, Err(Error::RedirectUriMistmatch)),
+            "unregistered redirect URI should be rejected"
+        );
     }
 
     #[tokio::test]
@@ -675,6 +1402,7 @@ mod tests {
                 app_id: fixture.app.id.clone(),
                 org_id: fixture.auth.org.id.clone(),
                 user_id: fixture.auth.user.id.clone(),
+                nonce: None,
             })
             .await
             .expect("oauth code should be inserted");
@@ -689,7 +1417,7 @@ mod tests {
 
         let result = exchange_code_for_access_token_svc(&ctx.state, &payload).await;
         assert!(result.is_err(), "should reject invalid scope");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "OAuth scopes invalid");
     }
 
@@ -746,7 +1474,415 @@ mod tests {
 
         let result = exchange_code_for_access_token_svc(&ctx.state, &payload).await;
         assert!(result.is_err(), "should reject non-member user");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "User must be a member of the org");
     }
+
+    #[tokio::test]
+    async fn introspect_token_svc_reports_tokens_of_the_app() {
+        let ctx = TestCtx::new("oauth_introspect").await.expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "OAuth User",
+                "oauth.introspect@example.com",
+                "password123",
+                "OAuth Org",
+                "OAuth App",
+                "https://oauth.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+        let other = ctx
+            .seed_oauth_fixture(
+                "Other User",
+                "other.introspect@example.com",
+                "password123",
+                "Other Org",
+                "Other App",
+                "https://other.example.com/callback",
+                true,
+            )
+            .await
+            .expect("other oauth fixture");
+
+        let actor_ctx = fixture.auth.to_ctx(vec![Scope::Auth]);
+        let authorize = build_authorize(
+            fixture.app.client_id.clone(),
+            "https://oauth.example.com/callback",
+            "oauth",
+        );
+        let code = create_authorization_code_svc(&ctx.state, &actor_ctx, &authorize)
+            .await
+            .expect("authorization code should be created");
+        let token = exchange_code_for_access_token_svc(
+            &ctx.state,
+            &build_token_request(
+                fixture.app.client_id.clone(),
+                fixture.app.client_secret.clone(),
+                code.code,
+                &code.state,
+                "https://oauth.example.com/callback",
+            ),
+        )
+        .await
+        .expect("token exchange should succeed");
+
+        let request =
+            |client_id: &str, client_secret: &str, token: &str| OauthIntrospectRequestDto {
+                client_id: client_id.to_string(),
+                client_secret: client_secret.to_string(),
+                token: token.to_string(),
+                token_type_hint: None,
+            };
+
+        let active = introspect_token_svc(
+            &ctx.state,
+            &request(
+                &fixture.app.client_id,
+                &fixture.app.client_secret,
+                &token.access_token,
+            ),
+        )
+        .await
+        .expect("introspection");
+        assert!(active.active);
+        assert_eq!(active.sub.as_deref(), Some(fixture.auth.user.id.as_str()));
+        assert_eq!(active.org_id.as_deref(), Some(fixture.auth.org.id.as_str()));
+        assert_eq!(active.scope.as_deref(), Some("oauth"));
+        assert!(active.exp > active.iat);
+
+        // Other apps and session tokens learn nothing
+        let other_app = introspect_token_svc(
+            &ctx.state,
+            &request(
+                &other.app.client_id,
+                &other.app.client_secret,
+                &token.access_token,
+            ),
+        )
+        .await
+        .expect("introspection");
+        assert!(!other_app.active);
+        assert!(other_app.sub.is_none());
+
+        let session_token = create_auth_token(
+            &ActorPayloadDto {
+                id: fixture.auth.user.id.clone(),
+                org_id: fixture.auth.org.id.clone(),
+                org_count: 1,
+                roles: vec![Role::OrgAdmin],
+                scopes: vec![Scope::Auth],
+                grant_id: None,
+                proof_key_id: None,
+                permission_mask: None,
+                issued_at: 0,
+                session_id: None,
+                region: None,
+                home_region: None,
+                token_id: None,
+                org_app_id: None,
+                impersonator_id: None,
+                impersonation_id: None,
+            },
+            &ctx.state.token_keys,
+        )
+        .expect("session token");
+        let session = introspect_token_svc(
+            &ctx.state,
+            &request(
+                &fixture.app.client_id,
+                &fixture.app.client_secret,
+                &session_token,
+            ),
+        )
+        .await
+        .expect("introspection");
+        assert!(!session.active);
+
+        let garbage = introspect_token_svc(
+            &ctx.state,
+            &request(&fixture.app.client_id, &fixture.app.client_secret, "nope"),
+        )
+        .await
+        .expect("introspection");
+        assert!(!garbage.active);
+
+        let wrong_secret = introspect_token_svc(
+            &ctx.state,
+            &request(
+                &fixture.app.client_id,
+                &other.app.client_secret,
+                &token.access_token,
+            ),
+        )
+        .await;
+        assert!(wrong_secret.is_err());
+    }
+
+    #[tokio::test]
+    async fn token_scopes_narrow_the_role_permissions() {
+        let ctx = TestCtx::new("oauth_scope_permissions")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "OAuth User",
+                "oauth.scopes@example.com",
+                "password123",
+                "OAuth Org",
+                "OAuth App",
+                "https://oauth.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+
+        let actor_ctx = fixture.auth.to_ctx(vec![Scope::Auth]);
+        let authorize = build_authorize(
+            fixture.app.client_id.clone(),
+            "https://oauth.example.com/callback",
+            "org.read  openid org.read",
+        );
+        let code = create_authorization_code_svc(&ctx.state, &actor_ctx, &authorize)
+            .await
+            .expect("authorization code should be created");
+        let token = exchange_code_for_access_token_svc(
+            &ctx.state,
+            &build_token_request(
+                fixture.app.client_id.clone(),
+                fixture.app.client_secret.clone(),
+                code.code,
+                &code.state,
+                "https://oauth.example.com/callback",
+            ),
+        )
+        .await
+        .expect("token exchange should succeed");
+        assert_eq!(token.scope, "org.read openid");
+
+        let actor = authenticate_token_svc(&ctx.state, &token.access_token)
+            .await
+            .expect("token works");
+        assert!(actor.has_permissions(&[Permission::OrgMembersView]));
+        assert!(!actor.has_permissions(&[Permission::OrgMembersEdit]));
+        assert!(!actor.has_auth_scope());
+
+        // Write scopes do not exist
+        let write = build_authorize(
+            fixture.app.client_id.clone(),
+            "https://oauth.example.com/callback",
+            "org.write",
+        );
+        assert!(
+            create_authorization_code_svc(&ctx.state, &actor_ctx, &write)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn client_credentials_token_acts_for_the_linked_org() {
+        let ctx = TestCtx::new("oauth_client_credentials")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "OAuth User",
+                "oauth.client@example.com",
+                "password123",
+                "OAuth Org",
+                "OAuth App",
+                "https://oauth.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+        let unlinked = ctx
+            .seed_oauth_fixture(
+                "Other User",
+                "other.client@example.com",
+                "password123",
+                "Other Org",
+                "Other App",
+                "https://other.example.com/callback",
+                false,
+            )
+            .await
+            .expect("other oauth fixture");
+
+        let request = |org_id: &str, client_secret: &str, scope: Option<&str>| {
+            OauthClientCredentialsRequestDto {
+                grant_type: "client_credentials".to_string(),
+                client_id: fixture.app.client_id.clone(),
+                client_secret: client_secret.to_string(),
+                org_id: org_id.to_string(),
+                scope: scope.map(|s| s.to_string()),
+            }
+        };
+
+        let token = issue_client_credentials_token_svc(
+            &ctx.state,
+            &request(&fixture.auth.org.id, &fixture.app.client_secret, None),
+        )
+        .await
+        .expect("client credentials token");
+        assert_eq!(token.scope, "oauth");
+        assert!(token.id_token.is_none());
+
+        let actor = authenticate_token_svc(&ctx.state, &token.access_token)
+            .await
+            .expect("token works");
+        assert!(actor.actor.is_none());
+        assert!(actor.member_of(&fixture.auth.org.id));
+        let app = actor.app.expect("app actor");
+        assert_eq!(app.app_id, fixture.app.id);
+        assert_eq!(app.roles, vec![Role::OrgViewer]);
+
+        let introspection = introspect_token_svc(
+            &ctx.state,
+            &OauthIntrospectRequestDto {
+                client_id: fixture.app.client_id.clone(),
+                client_secret: fixture.app.client_secret.clone(),
+                token: token.access_token.clone(),
+                token_type_hint: None,
+            },
+        )
+        .await
+        .expect("introspection");
+        assert!(introspection.active);
+        assert_eq!(introspection.sub.as_deref(), Some(fixture.app.id.as_str()));
+
+        // Only orgs the app is linked to, only app level scopes
+        let other_org = issue_client_credentials_token_svc(
+            &ctx.state,
+            &request(&unlinked.auth.org.id, &fixture.app.client_secret, None),
+        )
+        .await;
+        assert!(other_org.is_err());
+
+        let user_scope = issue_client_credentials_token_svc(
+            &ctx.state,
+            &request(
+                &fixture.auth.org.id,
+                &fixture.app.client_secret,
+                Some("auth"),
+            ),
+        )
+        .await;
+        assert!(user_scope.is_err());
+
+        let wrong_secret = issue_client_credentials_token_svc(
+            &ctx.state,
+            &request(&fixture.auth.org.id, &unlinked.app.client_secret, None),
+        )
+        .await;
+        assert!(wrong_secret.is_err());
+
+        // Unlinking the app ends its tokens
+        delete_org_app_svc(&ctx.state, &app.org_app_id)
+            .await
+            .expect("unlink");
+        assert!(
+            authenticate_token_svc(&ctx.state, &token.access_token)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn revoke_token_for_app_svc_only_revokes_tokens_of_the_app() {
+        let ctx = TestCtx::new("oauth_revoke").await.expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "OAuth User",
+                "oauth.revoke@example.com",
+                "password123",
+                "OAuth Org",
+                "OAuth App",
+                "https://oauth.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+        let other = ctx
+            .seed_oauth_fixture(
+                "Other User",
+                "other.revoke@example.com",
+                "password123",
+                "Other Org",
+                "Other App",
+                "https://other.example.com/callback",
+                true,
+            )
+            .await
+            .expect("other oauth fixture");
+
+        let actor_ctx = fixture.auth.to_ctx(vec![Scope::Auth]);
+        let authorize = build_authorize(
+            fixture.app.client_id.clone(),
+            "https://oauth.example.com/callback",
+            "oauth",
+        );
+        let code = create_authorization_code_svc(&ctx.state, &actor_ctx, &authorize)
+            .await
+            .expect("authorization code should be created");
+        let token = exchange_code_for_access_token_svc(
+            &ctx.state,
+            &build_token_request(
+                fixture.app.client_id.clone(),
+                fixture.app.client_secret.clone(),
+                code.code,
+                &code.state,
+                "https://oauth.example.com/callback",
+            ),
+        )
+        .await
+        .expect("token exchange should succeed");
+
+        let revoke = |client_id: &str, client_secret: &str| OauthRevokeRequestDto {
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+            token: token.access_token.clone(),
+            token_type_hint: None,
+        };
+
+        // Other apps cannot revoke it, nor tell it exists
+        revoke_token_for_app_svc(
+            &ctx.state,
+            &revoke(&other.app.client_id, &other.app.client_secret),
+        )
+        .await
+        .expect("revoke is a no-op");
+        authenticate_token_svc(&ctx.state, &token.access_token)
+            .await
+            .expect("token still works");
+
+        let wrong_secret = revoke_token_for_app_svc(
+            &ctx.state,
+            &revoke(&fixture.app.client_id, &other.app.client_secret),
+        )
+        .await;
+        assert!(wrong_secret.is_err());
+
+        revoke_token_for_app_svc(
+            &ctx.state,
+            &revoke(&fixture.app.client_id, &fixture.app.client_secret),
+        )
+        .await
+        .expect("revoke");
+        assert!(
+            authenticate_token_svc(&ctx.state, &token.access_token)
+                .await
+                .is_err()
+        );
+
+        // Revoking twice is fine
+        revoke_token_for_app_svc(
+            &ctx.state,
+            &revoke(&fixture.app.client_id, &fixture.app.client_secret),
+        )
+        .await
+        .expect("revoke again");
+    }
 }
@@ -1,7 +1,10 @@
+use tracing::instrument;
+
 use crate::Result;
 use crate::dto::{NewOauthCodeDto, OauthCodeDto};
 use crate::run::AppState;
 
+#[instrument(level = "debug", skip_all)]
 pub async fn create_oauth_code_svc(
     state: &AppState,
     data: NewOauthCodeDto,
@@ -9,6 +12,8 @@ pub async fn create_oauth_code_svc(
     state.db.oauth_codes.create(data).await
 }
 
-pub async fn delete_oauth_code_svc(state: &AppState, id: &str) -> Result<()> {
-    state.db.oauth_codes.delete(id.to_string()).await
+/// Marks the code as used, only the first caller gets true
+#[instrument(level = "debug", skip_all)]
+pub async fn consume_oauth_code_svc(state: &AppState, id: &str) -> Result<bool> {
+    state.db.oauth_codes.consume(id.to_string()).await
 }
@@ -0,0 +1,215 @@
+use snafu::{OptionExt, ensure};
+use tracing::instrument;
+
+use crate::Result;
+use crate::dto::{
+    AuthorizedAppDto, NewOauthGrantDto, OauthGrantDto, Scope, join_scopes, parse_scopes,
+};
+use crate::error::{CsrfTokenSnafu, InvalidAuthTokenSnafu, NotFoundSnafu};
+use crate::run::AppState;
+use crate::services::token::verify_csrf_token;
+
+#[instrument(level = "debug", skip_all)]
+pub async fn list_authorized_apps_svc(
+    state: &AppState,
+    user_id: &str,
+) -> Result<Vec<AuthorizedAppDto>> {
+    state
+        .db
+        .oauth_grants
+        .list_authorized_apps(user_id.to_string())
+        .await
+}
+
+/// Records the user's approval, scopes approved before are kept
+#[instrument(level = "debug", skip_all)]
+pub async fn upsert_oauth_grant_svc(
+    state: &AppState,
+    mut data: NewOauthGrantDto,
+) -> Result<OauthGrantDto> {
+    let existing = state
+        .db
+        .oauth_grants
+        .find_grant(
+            data.user_id.clone(),
+            data.org_id.clone(),
+            data.app_id.clone(),
+        )
+        .await?;
+
+    if let Some(existing) = existing {
+        let mut scopes = parse_scopes(&existing.scope)?;
+        scopes.extend(parse_scopes(&data.scope)?);
+        data.scope = join_scopes(&scopes);
+    }
+
+    state.db.oauth_grants.upsert(data).await
+}
+
+/// Whether the user already approved every scope for the app within the org
+#[instrument(level = "debug", skip_all)]
+pub async fn grant_covers_scopes_svc(
+    state: &AppState,
+    user_id: &str,
+    org_id: &str,
+    app_id: &str,
+    scopes: &[Scope],
+) -> Result<bool> {
+    let grant = state
+        .db
+        .oauth_grants
+        .find_grant(user_id.to_string(), org_id.to_string(), app_id.to_string())
+        .await?;
+
+    let Some(grant) = grant else {
+        return Ok(false);
+    };
+
+    let granted = parse_scopes(&grant.scope)?;
+    Ok(scopes.iter().all(|scope| granted.contains(scope)))
+}
+
+/// Ensures the grant behind an OAuth token has not been revoked
+#[instrument(level = "debug", skip_all)]
+pub async fn verify_oauth_grant_svc(state: &AppState, grant_id: &str) -> Result<OauthGrantDto> {
+    let grant = state.db.oauth_grants.get(grant_id.to_string()).await?;
+    let grant = grant.context(InvalidAuthTokenSnafu)?;
+
+    state.db.oauth_grants.touch(grant.id.clone()).await?;
+
+    Ok(grant)
+}
+
+/// Revokes the user's authorization for the app.
+/// Tokens issued under the revoked grants are rejected from then on.
+#[instrument(level = "debug", skip_all)]
+pub async fn revoke_authorized_app_svc(
+    state: &AppState,
+    user_id: &str,
+    app_id: &str,
+) -> Result<()> {
+    let deleted = state
+        .db
+        .oauth_grants
+        .delete_by_app(user_id.to_string(), app_id.to_string())
+        .await?;
+
+    ensure!(
+        deleted,
+        NotFoundSnafu {
+            msg: "Authorized app not found".to_string()
+        }
+    );
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn revoke_authorized_app_web_svc(
+    state: &AppState,
+    user_id: &str,
+    app_id: &str,
+    csrf_token: &str,
+) -> Result<()> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == app_id, CsrfTokenSnafu);
+
+    revoke_authorized_app_svc(state, user_id, app_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::{OauthAuthorizeDto, OauthTokenRequestDto, Scope};
+    use crate::services::auth::authenticate_token_svc;
+    use crate::services::oauth::{
+        create_authorization_code_svc, exchange_code_for_access_token_svc,
+    };
+    use crate::services::token::create_csrf_token_svc;
+    use crate::test::TestCtx;
+
+    use super::{list_authorized_apps_svc, revoke_authorized_app_web_svc};
+
+    #[tokio::test]
+    async fn revoke_authorized_app_invalidates_issued_tokens() {
+        let ctx = TestCtx::new("oauth_grants_revoke").await.expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "Grant User",
+                "oauth.grant@example.com",
+                "password123",
+                "Grant Org",
+                "Grant App",
+                "https://grant.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+
+        let actor_ctx = fixture.auth.to_ctx(vec![Scope::Auth]);
+        let code = create_authorization_code_svc(
+            &ctx.state,
+            &actor_ctx,
+            &OauthAuthorizeDto {
+                client_id: fixture.app.client_id.clone(),
+                redirect_uri: "https://grant.example.com/callback".to_string(),
+                scope: "oauth".to_string(),
+                state: "state-1".to_string(),
+                nonce: None,
+            },
+        )
+        .await
+        .expect("authorization code should be created");
+
+        let token = exchange_code_for_access_token_svc(
+            &ctx.state,
+            &OauthTokenRequestDto {
+                grant_type: Some("authorization_code".to_string()),
+                client_id: fixture.app.client_id.clone(),
+                client_secret: fixture.app.client_secret.clone(),
+                code: code.code,
+                state: code.state,
+                redirect_uri: "https://grant.example.com/callback".to_string(),
+            },
+        )
+        .await
+        .expect("token exchange should succeed");
+
+        let apps = list_authorized_apps_svc(&ctx.state, &fixture.auth.user.id)
+            .await
+            .expect("listing should pass");
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].app_name, "Grant App");
+        assert_eq!(apps[0].org_name, "Grant Org");
+        assert_eq!(apps[0].scope, "oauth");
+
+        authenticate_token_svc(&ctx.state, &token.access_token)
+            .await
+            .expect("token should be valid before revoking");
+
+        let csrf = create_csrf_token_svc(&fixture.app.id, &ctx.state.config.jwt_secret)
+            .expect("csrf token should be generated");
+        revoke_authorized_app_web_svc(&ctx.state, &fixture.auth.user.id, &fixture.app.id, &csrf)
+            .await
+            .expect("revoke should pass");
+
+        let result = authenticate_token_svc(&ctx.state, &token.access_token).await;
+        assert!(result.is_err(), "revoked token should be rejected");
+
+        let apps = list_authorized_apps_svc(&ctx.state, &fixture.auth.user.id)
+            .await
+            .expect("listing should pass");
+        assert!(apps.is_empty());
+
+        // Revoking again reports a missing grant
+        let csrf = create_csrf_token_svc(&fixture.app.id, &ctx.state.config.jwt_secret)
+            .expect("csrf token should be generated");
+        let result = revoke_authorized_app_web_svc(
+            &ctx.state,
+            &fixture.auth.user.id,
+            &fixture.app.id,
+            &csrf,
+        )
+        .await;
+        assert!(result.is_err(), "missing grant should fail");
+    }
+}
@@ -0,0 +1,2284 @@
+use serde_json::{Value, json};
+
+use crate::dto::{
+    APP_SORT, LIFECYCLE_TOPICS, MAX_ALLOWED_EMAIL_DOMAINS, MAX_BATCH_GET_IDS, ORG_MEMBER_SORT,
+    ORG_SORT, SortSpec, USER_PROFILE_METADATA_MAX_BYTES, USER_PROFILE_METADATA_MAX_KEYS, USER_SORT,
+};
+
+/// OpenAPI 3 document of the JSON APIs, the OAuth API and the admin API.
+///
+/// Written by hand like the event schemas, the tests fail when a route is
+/// added without documenting it or a documented DTO gains a field.
+pub fn openapi_spec_svc() -> Value {
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Yaas API",
+            "description": "OAuth API for apps and the superuser admin API",
+            "version": env!("CARGO_PKG_VERSION")
+        },
+        "tags": [
+            { "name": "oauth", "description": "Token exchange and the signed in user" },
+            { "name": "admin", "description": "Superuser bearer token required" },
+            { "name": "public", "description": "No authentication" }
+        ],
+        "paths": paths(),
+        "components": {
+            "securitySchemes": {
+                "bearer": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" }
+            },
+            "schemas": schemas()
+        }
+    })
+}
+
+fn paths() -> Value {
+    let mut paths = json!({
+        "/oauth/token": {
+            "post": public_op(
+                "oauth",
+                "Exchange an authorization code, or client credentials of an org app, for an access token",
+                Some("OauthTokenRequest"),
+                "200",
+                Some(schema_ref("OauthTokenResponse"))
+            )
+        },
+        "/oauth/profile": {
+            "get": op(
+                "oauth",
+                "Signed in user, org, roles and permissions",
+                vec![fields_param()],
+                None,
+                "200",
+                Some(schema_ref("Actor"))
+            )
+        },
+        "/user/authorized-apps": {
+            "get": op(
+                "oauth",
+                "Apps the user has authorized",
+                vec![],
+                None,
+                "200",
+                Some(list_of("AuthorizedApp"))
+            )
+        },
+        "/user/authorized-apps/{app_id}": {
+            "delete": op(
+                "oauth",
+                "Revoke an authorized app",
+                vec![path_param("app_id")],
+                None,
+                "204",
+                None
+            )
+        },
+        "/user/sessions": {
+            "get": op(
+                "oauth",
+                "Sessions of the user",
+                vec![],
+                None,
+                "200",
+                Some(list_of("UserSession"))
+            )
+        },
+        "/user/sessions/{session_id}": {
+            "delete": op(
+                "oauth",
+                "Revoke a session",
+                vec![path_param("session_id")],
+                None,
+                "204",
+                None
+            )
+        },
+        "/org/usage": {
+            "get": op(
+                "oauth",
+                "Rate limit budget of the token's org",
+                vec![],
+                None,
+                "200",
+                Some(schema_ref("OrgRateUsage"))
+            )
+        },
+        "/admin/api/users": {
+            "get": admin_op(
+                "List users",
+                {
+                    let mut params = listing_params(true, &USER_SORT);
+                    params.extend(cursor_params());
+                    params
+                },
+                None,
+                "200",
+                Some(paginated_or_cursor("User"))
+            ),
+            "post": admin_op("Create a user", vec![], Some("NewUser"), "201", Some(schema_ref("User")))
+        },
+        "/admin/api/users/{user_id}": {
+            "get": admin_op(
+                "Get a user",
+                vec![path_param("user_id"), include_deleted_param(), fields_param()],
+                None,
+                "200",
+                Some(schema_ref("User"))
+            ),
+            "patch": admin_op(
+                "Update a user, 409 with the user as it is now when the version is stale",
+                vec![path_param("user_id"), if_match_param()],
+                Some("UpdateUser"),
+                "200",
+                Some(updated("User"))
+            )
+        },
+        "/admin/api/users/import": {
+            "post": import_op()
+        },
+        "/admin/api/users/{user_id}/restore": {
+            "post": admin_op(
+                "Restore a deleted user",
+                vec![path_param("user_id")],
+                None,
+                "200",
+                Some(schema_ref("User"))
+            )
+        },
+        "/admin/api/users/{user_id}/force-logout": {
+            "post": admin_op(
+                "Revoke every token of a user",
+                vec![path_param("user_id")],
+                None,
+                "200",
+                Some(schema_ref("TokenRevocation"))
+            )
+        },
+        "/admin/api/orgs": {
+            "get": admin_op(
+                "List orgs",
+                {
+                    let mut params = listing_params(true, &ORG_SORT);
+                    params.extend(cursor_params());
+                    params
+                },
+                None,
+                "200",
+                Some(paginated_or_cursor("Org"))
+            ),
+            "post": admin_op("Create an org", vec![], Some("NewOrg"), "201", Some(schema_ref("Org")))
+        },
+        "/admin/api/orgs/{org_id}": {
+            "get": admin_op(
+                "Get an org",
+                vec![path_param("org_id"), include_deleted_param(), fields_param()],
+                None,
+                "200",
+                Some(schema_ref("Org"))
+            ),
+            "patch": admin_op(
+                "Update an org, 409 with the org as it is now when the version is stale",
+                vec![path_param("org_id"), if_match_param()],
+                Some("UpdateOrg"),
+                "200",
+                Some(updated("Org"))
+            )
+        },
+        "/admin/api/orgs/{org_id}/restore": {
+            "post": admin_op(
+                "Restore a deleted org",
+                vec![path_param("org_id")],
+                None,
+                "200",
+                Some(schema_ref("Org"))
+            )
+        },
+        "/admin/api/orgs/{org_id}/access-log": {
+            "get": csv_op(
+                "Export the org access log as CSV",
+                vec![
+                    path_param("org_id"),
+                    query_param("from", "string", "First day, YYYY-MM-DD", true),
+                    query_param("to", "string", "Last day, YYYY-MM-DD", true)
+                ]
+            )
+        },
+        "/admin/api/orgs/{org_id}/rate-limit": {
+            "get": admin_op(
+                "Rate limit budget of an org",
+                vec![path_param("org_id")],
+                None,
+                "200",
+                Some(schema_ref("OrgRateUsage"))
+            ),
+            "put": admin_op(
+                "Override the rate limit budget of an org",
+                vec![path_param("org_id")],
+                Some("UpdateOrgRateLimit"),
+                "200",
+                Some(schema_ref("OrgRateUsage"))
+            ),
+            "delete": admin_op(
+                "Go back to the default rate limit budget",
+                vec![path_param("org_id")],
+                None,
+                "200",
+                Some(schema_ref("OrgRateUsage"))
+            )
+        },
+        "/admin/api/orgs/{org_id}/members": {
+            "get": admin_op(
+                "List org members",
+                {
+                    let mut params = vec![path_param("org_id")];
+                    params.extend(listing_params(false, &ORG_MEMBER_SORT));
+                    params.extend(cursor_params());
+                    params
+                },
+                None,
+                "200",
+                Some(paginated_or_cursor("OrgMember"))
+            ),
+            "post": admin_op(
+                "Add a member to an org",
+                vec![path_param("org_id")],
+                Some("NewOrgMember"),
+                "201",
+                Some(schema_ref("OrgMember"))
+            )
+        },
+        "/admin/api/orgs/{org_id}/members/bulk": {
+            "post": admin_op(
+                "Add or update several members in one transaction",
+                vec![path_param("org_id")],
+                Some("BulkOrgMembers"),
+                "200",
+                Some(schema_ref("BulkResult"))
+            )
+        },
+        "/admin/api/apps": {
+            "get": admin_op(
+                "List apps",
+                listing_params(true, &APP_SORT),
+                None,
+                "200",
+                Some(paginated("App"))
+            ),
+            "post": admin_op("Create an app", vec![], Some("NewApp"), "201", Some(schema_ref("App")))
+        },
+        "/admin/api/apps/{app_id}": {
+            "get": admin_op(
+                "Get an app",
+                vec![path_param("app_id"), include_deleted_param(), fields_param()],
+                None,
+                "200",
+                Some(schema_ref("App"))
+            ),
+            "patch": admin_op(
+                "Update an app, 409 with the app as it is now when the version is stale",
+                vec![path_param("app_id"), if_match_param()],
+                Some("UpdateApp"),
+                "200",
+                Some(updated("App"))
+            )
+        },
+        "/admin/api/apps/{app_id}/restore": {
+            "post": admin_op(
+                "Restore a deleted app",
+                vec![path_param("app_id")],
+                None,
+                "200",
+                Some(schema_ref("App"))
+            )
+        },
+        "/admin/api/apps/{app_id}/environments": {
+            "get": admin_op(
+                "List app environments",
+                vec![path_param("app_id"), fields_param()],
+                None,
+                "200",
+                Some(list_of("AppEnvironment"))
+            ),
+            "post": admin_op(
+                "Create an app environment",
+                vec![path_param("app_id")],
+                Some("NewAppEnvironment"),
+                "201",
+                Some(schema_ref("AppEnvironment"))
+            )
+        },
+        "/admin/api/apps/{app_id}/environments/{environment_id}": {
+            "delete": admin_op(
+                "Delete an app environment",
+                vec![path_param("app_id"), path_param("environment_id")],
+                None,
+                "204",
+                None
+            )
+        },
+        "/admin/api/memory": {
+            "get": admin_op("Process memory usage", vec![], None, "200", Some(json!({ "type": "object" })))
+        },
+        "/admin/api/stats": {
+            "get": admin_op(
+                "Deployment totals as of the last counter reconcile",
+                vec![],
+                None,
+                "200",
+                Some(schema_ref("Stats"))
+            )
+        },
+        "/openapi.json": {
+            "get": public_op("public", "This document", None, "200", Some(json!({ "type": "object" })))
+        },
+        "/admin/api/metrics": {
+            "get": {
+                "tags": ["admin"],
+                "summary": "Memory usage in the Prometheus text format",
+                "security": [{ "bearer": [] }],
+                "responses": {
+                    "200": {
+                        "description": "OK",
+                        "content": { "text/plain": { "schema": { "type": "string" } } }
+                    }
+                }
+            }
+        }
+    });
+
+    // Split out to stay under the json! macro recursion limit
+    if let (Some(paths), Value::Object(org_roles)) = (paths.as_object_mut(), org_role_paths()) {
+        paths.extend(org_roles);
+    }
+    if let (Some(paths), Value::Object(lifecycle)) = (paths.as_object_mut(), lifecycle_paths()) {
+        paths.extend(lifecycle);
+    }
+    if let (Some(paths), Value::Object(tokens)) = (paths.as_object_mut(), token_paths()) {
+        paths.extend(tokens);
+    }
+    if let (Some(paths), Value::Object(profile)) = (paths.as_object_mut(), profile_paths()) {
+        paths.extend(profile);
+    }
+    if let (Some(paths), Value::Object(teams)) = (paths.as_object_mut(), team_paths()) {
+        paths.extend(teams);
+    }
+    if let (Some(paths), Value::Object(notifications)) =
+        (paths.as_object_mut(), notification_paths())
+    {
+        paths.extend(notifications);
+    }
+    if let (Some(paths), Value::Object(batch)) = (paths.as_object_mut(), batch_get_paths()) {
+        paths.extend(batch);
+    }
+    if let (Some(paths), Value::Object(transfers)) = (paths.as_object_mut(), owner_transfer_paths())
+    {
+        paths.extend(transfers);
+    }
+    if let (Some(paths), Value::Object(credentials)) =
+        (paths.as_object_mut(), app_credential_paths())
+    {
+        paths.extend(credentials);
+    }
+    if let (Some(paths), Value::Object(org_apps)) = (paths.as_object_mut(), org_app_paths()) {
+        paths.extend(org_apps);
+    }
+    if let (Some(paths), Value::Object(impersonations)) =
+        (paths.as_object_mut(), impersonation_paths())
+    {
+        paths.extend(impersonations);
+    }
+    if let (Some(paths), Value::Object(exports)) = (paths.as_object_mut(), user_export_paths()) {
+        paths.extend(exports);
+    }
+    if let (Some(paths), Value::Object(erasure)) = (paths.as_object_mut(), user_erasure_paths()) {
+        paths.extend(erasure);
+    }
+    if let (Some(paths), Value::Object(invitations)) = (paths.as_object_mut(), invitation_paths()) {
+        paths.extend(invitations);
+    }
+    if let (Some(paths), Value::Object(transfers)) = (paths.as_object_mut(), org_transfer_paths()) {
+        paths.extend(transfers);
+    }
+
+    // Every admin POST accepts an idempotency key
+    for (path, item) in paths.as_object_mut().into_iter().flatten() {
+        if !path.starts_with("/admin/api/") {
+            continue;
+        }
+        if let Some(operation) = item.get_mut("post") {
+            match operation["parameters"].as_array_mut() {
+                Some(params) => params.push(idempotency_key_param()),
+                None => operation["parameters"] = json!([idempotency_key_param()]),
+            }
+        }
+    }
+
+    paths
+}
+
+fn org_role_paths() -> Value {
+    json!({
+        "/admin/api/orgs/{org_id}/members/{user_id}/roles": {
+            "put": admin_op(
+                "Replace the custom roles of an org member",
+                vec![path_param("org_id"), path_param("user_id")],
+                Some("OrgMemberRoles"),
+                "200",
+                Some(list_of("OrgRole"))
+            )
+        },
+        "/admin/api/orgs/{org_id}/roles": {
+            "get": admin_op(
+                "List the custom roles of an org",
+                vec![path_param("org_id"), fields_param()],
+                None,
+                "200",
+                Some(list_of("OrgRole"))
+            ),
+            "post": admin_op(
+                "Create a custom role",
+                vec![path_param("org_id")],
+                Some("NewOrgRole"),
+                "201",
+                Some(schema_ref("OrgRole"))
+            )
+        },
+        "/admin/api/orgs/{org_id}/settings": {
+            "get": admin_op(
+                "Get the settings of an org, the defaults when never saved",
+                vec![path_param("org_id")],
+                None,
+                "200",
+                Some(schema_ref("OrgSettings"))
+            ),
+            "patch": admin_op(
+                "Update the settings of an org, fields left out are kept",
+                vec![path_param("org_id")],
+                Some("UpdateOrgSettings"),
+                "200",
+                Some(schema_ref("OrgSettings"))
+            )
+        },
+        "/admin/api/orgs/{org_id}/roles/{role_id}": {
+            "get": admin_op(
+                "Get a custom role",
+                vec![path_param("org_id"), path_param("role_id")],
+                None,
+                "200",
+                Some(schema_ref("OrgRole"))
+            ),
+            "patch": admin_op(
+                "Update a custom role",
+                vec![path_param("org_id"), path_param("role_id")],
+                Some("UpdateOrgRole"),
+                "200",
+                Some(schema_ref("OrgRole"))
+            ),
+            "delete": admin_op(
+                "Delete a custom role and its assignments",
+                vec![path_param("org_id"), path_param("role_id")],
+                None,
+                "204",
+                None
+            )
+        }
+    })
+}
+
+fn team_paths() -> Value {
+    json!({
+        "/admin/api/orgs/{org_id}/teams": {
+            "get": admin_op(
+                "List the teams of an org",
+                vec![path_param("org_id"), fields_param()],
+                None,
+                "200",
+                Some(list_of("Team"))
+            ),
+            "post": admin_op(
+                "Create a team",
+                vec![path_param("org_id")],
+                Some("NewTeam"),
+                "201",
+                Some(schema_ref("Team"))
+            )
+        },
+        "/admin/api/orgs/{org_id}/teams/{team_id}": {
+            "get": admin_op(
+                "Get a team",
+                vec![path_param("org_id"), path_param("team_id")],
+                None,
+                "200",
+                Some(schema_ref("Team"))
+            ),
+            "patch": admin_op(
+                "Update a team, its members get the new roles on their next request",
+                vec![path_param("org_id"), path_param("team_id")],
+                Some("UpdateTeam"),
+                "200",
+                Some(schema_ref("Team"))
+            ),
+            "delete": admin_op(
+                "Delete a team and its memberships",
+                vec![path_param("org_id"), path_param("team_id")],
+                None,
+                "204",
+                None
+            )
+        },
+        "/admin/api/orgs/{org_id}/teams/{team_id}/members": {
+            "get": admin_op(
+                "List the members of a team",
+                vec![path_param("org_id"), path_param("team_id")],
+                None,
+                "200",
+                Some(list_of("TeamMember"))
+            ),
+            "post": admin_op(
+                "Add an org member to a team",
+                vec![path_param("org_id"), path_param("team_id")],
+                Some("NewTeamMember"),
+                "200",
+                Some(list_of("TeamMember"))
+            )
+        },
+        "/admin/api/orgs/{org_id}/teams/{team_id}/members/{user_id}": {
+            "delete": admin_op(
+                "Remove a member from a team",
+                vec![path_param("org_id"), path_param("team_id"), path_param("user_id")],
+                None,
+                "204",
+                None
+            )
+        }
+    })
+}
+
+fn profile_paths() -> Value {
+    json!({
+        "/user/profile": {
+            "get": op(
+                "oauth",
+                "Profile attributes and metadata of the user",
+                vec![],
+                None,
+                "200",
+                Some(schema_ref("UserProfile"))
+            ),
+            "patch": op(
+                "oauth",
+                "Update profile attributes, empty strings clear them and null metadata keys are removed",
+                vec![],
+                Some("UpdateUserProfile"),
+                "200",
+                Some(schema_ref("UserProfile"))
+            )
+        }
+    })
+}
+
+fn batch_get_paths() -> Value {
+    json!({
+        "/admin/api/users/batch-get": {
+            "post": admin_op(
+                "Users by id, up to 100, with the ids not found",
+                vec![fields_param()],
+                Some("BatchGet"),
+                "200",
+                Some(batch_of("User"))
+            )
+        },
+        "/admin/api/orgs/batch-get": {
+            "post": admin_op(
+                "Orgs by id, up to 100, with the ids not found",
+                vec![fields_param()],
+                Some("BatchGet"),
+                "200",
+                Some(batch_of("Org"))
+            )
+        }
+    })
+}
+
+fn owner_transfer_paths() -> Value {
+    json!({
+        "/admin/api/orgs/{org_id}/owner-transfer": {
+            "get": admin_op(
+                "Ownership transfer waiting for the new owner, 404 when there is none",
+                vec![path_param("org_id")],
+                None,
+                "200",
+                Some(schema_ref("OrgOwnerTransfer"))
+            ),
+            "post": admin_op(
+                "Ask a member to take over the org, the accept link is emailed to them",
+                vec![path_param("org_id")],
+                Some("NewOrgOwnerTransfer"),
+                "201",
+                Some(schema_ref("OrgOwnerTransfer"))
+            ),
+            "delete": admin_op(
+                "Cancel the pending ownership transfer",
+                vec![path_param("org_id")],
+                None,
+                "204",
+                None
+            )
+        }
+    })
+}
+
+fn app_credential_paths() -> Value {
+    json!({
+        "/admin/api/apps/{app_id}/rotate-secret": {
+            "post": admin_op(
+                "Issue a new client secret, returned only once, the previous one keeps working for the grace period",
+                vec![path_param("app_id")],
+                None,
+                "200",
+                Some(schema_ref("App"))
+            )
+        },
+        "/admin/api/apps/{app_id}/environments/{environment_id}/rotate-secret": {
+            "post": admin_op(
+                "Issue a new environment client secret, returned only once, the previous one keeps working for the grace period",
+                vec![path_param("app_id"), path_param("environment_id")],
+                None,
+                "200",
+                Some(schema_ref("AppEnvironment"))
+            )
+        },
+        "/admin/api/apps/{app_id}/redirect-uris": {
+            "post": admin_op(
+                "Register an additional redirect URI",
+                vec![path_param("app_id")],
+                Some("AppRedirectUri"),
+                "200",
+                Some(schema_ref("App"))
+            ),
+            "delete": admin_op(
+                "Remove an additional redirect URI, the main one can only be changed",
+                vec![path_param("app_id"), query_param("redirect_uri", "string", "Redirect URI to remove", true)],
+                None,
+                "200",
+                Some(schema_ref("App"))
+            )
+        }
+    })
+}
+
+fn org_app_paths() -> Value {
+    json!({
+        "/admin/api/orgs/{org_id}/apps/{app_id}": {
+            "patch": admin_op(
+                "Restrict the scopes the org grants the app, null allows every scope the app declares",
+                vec![path_param("org_id"), path_param("app_id")],
+                Some("UpdateOrgApp"),
+                "200",
+                Some(schema_ref("OrgApp"))
+            )
+        }
+    })
+}
+
+fn impersonation_paths() -> Value {
+    json!({
+        "/admin/api/users/{user_id}/impersonate": {
+            "post": admin_op(
+                "Sign in as the user for an hour, requests made with the token are recorded",
+                vec![path_param("user_id")],
+                None,
+                "201",
+                Some(schema_ref("ImpersonationToken"))
+            )
+        },
+        "/admin/api/users/{user_id}/impersonations": {
+            "get": admin_op(
+                "Latest impersonations of the user with the requests made during each",
+                vec![path_param("user_id")],
+                None,
+                "200",
+                Some(json!({ "type": "array", "items": schema_ref("ImpersonationLog") }))
+            )
+        },
+        "/auth/stop-impersonating": {
+            "post": op(
+                "oauth",
+                "End the impersonation the token was issued for, the token stops working",
+                vec![],
+                None,
+                "200",
+                Some(schema_ref("Impersonation"))
+            )
+        }
+    })
+}
+
+fn user_export_paths() -> Value {
+    let format = query_param("format", "string", "json (default) or protobuf", false);
+
+    json!({
+        "/admin/api/users/{user_id}/export": {
+            "get": export_op(admin_op(
+                "Data export of the user, queued when there is none to download",
+                vec![path_param("user_id"), format.clone()],
+                None,
+                "202",
+                Some(schema_ref("UserExport"))
+            ))
+        },
+        "/user/export": {
+            "get": export_op(op(
+                "oauth",
+                "Data export of the user, queued when there is none to download",
+                vec![format],
+                None,
+                "202",
+                Some(schema_ref("UserExport"))
+            ))
+        },
+        "/user/export/download": {
+            "get": download_op()
+        }
+    })
+}
+
+fn user_erasure_paths() -> Value {
+    json!({
+        "/admin/api/users/{user_id}/anonymize/confirmation": {
+            "post": admin_op(
+                "Confirmation token for erasing the user, valid for 10 minutes. Requires users.erase",
+                vec![path_param("user_id")],
+                None,
+                "200",
+                Some(schema_ref("ErasureConfirmation"))
+            )
+        },
+        "/admin/api/users/{user_id}/anonymize": {
+            "post": admin_op(
+                "Irreversibly scrub the personal data of the user, memberships and audit logs are kept. Requires users.erase",
+                vec![path_param("user_id")],
+                Some("AnonymizeUser"),
+                "200",
+                Some(schema_ref("AnonymizedUser"))
+            )
+        }
+    })
+}
+
+fn invitation_paths() -> Value {
+    json!({
+        "/admin/api/orgs/{org_id}/invitations": {
+            "get": admin_op(
+                "List the invitations of an org with the delivery status of their latest email",
+                vec![path_param("org_id"), fields_param()],
+                None,
+                "200",
+                Some(list_of("OrgInvitation"))
+            )
+        },
+        "/admin/api/orgs/{org_id}/invitations/{invitation_id}/resend": {
+            "post": admin_op(
+                "Email a new link with a fresh expiry, the previous link stops working",
+                vec![path_param("org_id"), path_param("invitation_id")],
+                None,
+                "200",
+                Some(schema_ref("OrgInvitation"))
+            )
+        },
+        "/admin/api/emails": {
+            "get": admin_op(
+                "List the latest queued emails, without their bodies",
+                vec![
+                    query_param("status", "string", "Only emails of this status: pending, delivered or failed", false),
+                    fields_param()
+                ],
+                None,
+                "200",
+                Some(list_of("Email"))
+            )
+        },
+        "/admin/api/users/{user_id}/password-reset": {
+            "get": admin_op(
+                "Newest password reset of a user with the delivery status of its email",
+                vec![path_param("user_id")],
+                None,
+                "200",
+                Some(schema_ref("PasswordReset"))
+            )
+        },
+        "/admin/api/users/{user_id}/password-reset/resend": {
+            "post": admin_op(
+                "Email a new reset link to the primary email of the user, the previous link stops working",
+                vec![path_param("user_id")],
+                None,
+                "200",
+                Some(schema_ref("PasswordReset"))
+            )
+        }
+    })
+}
+
+fn org_transfer_paths() -> Value {
+    let mut operation = admin_op(
+        "Import an org archive exported from another environment",
+        vec![
+            query_param(
+                "strategy",
+                "string",
+                "skip, merge or rename, defaults to skip",
+                false,
+            ),
+            query_param("dry_run", "boolean", "Only resolve the import plan", false),
+        ],
+        Some("OrgArchive"),
+        "201",
+        Some(schema_ref("OrgImportReport")),
+    );
+    operation["responses"]["200"] = json!({
+        "description": "Dry run or an existing org skipped, nothing was written",
+        "content": { "application/json": { "schema": schema_ref("OrgImportReport") } }
+    });
+
+    json!({
+        "/admin/api/orgs/import": {
+            "post": operation
+        }
+    })
+}
+
+fn notification_paths() -> Value {
+    json!({
+        "/user/notifications": {
+            "get": op(
+                "oauth",
+                "Latest notifications of the user with the unread count",
+                vec![],
+                None,
+                "200",
+                Some(schema_ref("UserNotifications"))
+            )
+        },
+        "/user/notifications/read-all": {
+            "post": op(
+                "oauth",
+                "Mark every notification as read",
+                vec![],
+                None,
+                "204",
+                None
+            )
+        },
+        "/user/notifications/{notification_id}/read": {
+            "post": op(
+                "oauth",
+                "Mark a notification as read",
+                vec![path_param("notification_id")],
+                None,
+                "204",
+                None
+            )
+        }
+    })
+}
+
+/// Endpoints apps verify tokens with: introspection, OpenID Connect and the JWKS
+fn token_paths() -> Value {
+    json!({
+        "/oauth/introspect": {
+            "post": public_op(
+                "oauth",
+                "Check a token the app received, authenticated with its client credentials",
+                Some("OauthIntrospectRequest"),
+                "200",
+                Some(schema_ref("OauthIntrospection"))
+            )
+        },
+        "/oauth/revoke": {
+            "post": public_op(
+                "oauth",
+                "Revoke a token the app received, unknown tokens are ignored",
+                Some("OauthRevokeRequest"),
+                "200",
+                None
+            )
+        },
+        "/auth/logout-all": {
+            "post": op(
+                "oauth",
+                "Sign the user out everywhere, including this token",
+                vec![],
+                None,
+                "204",
+                None
+            )
+        },
+        "/oauth/userinfo": {
+            "get": op(
+                "oauth",
+                "Standard claims of the signed in user, needs the openid scope",
+                vec![],
+                None,
+                "200",
+                Some(schema_ref("Userinfo"))
+            )
+        },
+        "/.well-known/openid-configuration": {
+            "get": public_op(
+                "public",
+                "OpenID Connect discovery document",
+                None,
+                "200",
+                Some(schema_ref("OpenidConfiguration"))
+            )
+        },
+        "/.well-known/jwks.json": {
+            "get": public_op(
+                "public",
+                "Public keys that verify ID tokens and signed access tokens",
+                None,
+                "200",
+                Some(schema_ref("Jwks"))
+            )
+        }
+    })
+}
+
+fn lifecycle_paths() -> Value {
+    json!({
+        "/admin/api/apps/{app_id}/lifecycle": {
+            "get": admin_op(
+                "List the lifecycle subscriptions of an app",
+                vec![path_param("app_id"), fields_param()],
+                None,
+                "200",
+                Some(list_of("LifecycleSubscription"))
+            ),
+            "post": admin_op(
+                "Subscribe an app to a lifecycle topic, or move the topic to a new url",
+                vec![path_param("app_id")],
+                Some("NewLifecycleSubscription"),
+                "201",
+                Some(schema_ref("LifecycleSubscriptionSecret"))
+            )
+        },
+        "/admin/api/apps/{app_id}/lifecycle/deliveries": {
+            "get": admin_op(
+                "List the latest lifecycle deliveries of an app",
+                vec![path_param("app_id"), fields_param()],
+                None,
+                "200",
+                Some(list_of("LifecycleDelivery"))
+            )
+        },
+        "/admin/api/jobs": {
+            "get": admin_op(
+                "List the latest background jobs",
+                vec![
+                    query_param("status", "string", "Only jobs of this status: queued, running, succeeded or dead", false),
+                    fields_param()
+                ],
+                None,
+                "200",
+                Some(list_of("Job"))
+            )
+        },
+        "/admin/api/jobs/{job_id}/retry": {
+            "post": admin_op(
+                "Queue a dead job again with a fresh set of attempts",
+                vec![path_param("job_id")],
+                None,
+                "200",
+                Some(schema_ref("Job"))
+            )
+        },
+        "/admin/api/apps/{app_id}/lifecycle/{subscription_id}": {
+            "delete": admin_op(
+                "Remove a lifecycle subscription",
+                vec![path_param("app_id"), path_param("subscription_id")],
+                None,
+                "204",
+                None
+            )
+        }
+    })
+}
+
+fn schemas() -> Value {
+    let timestamp = json!({ "type": "integer", "format": "int64", "description": "Unix timestamp in milliseconds" });
+    let opt_timestamp = json!({ "type": "integer", "format": "int64", "nullable": true });
+    let string = json!({ "type": "string" });
+    let opt_string = json!({ "type": "string", "nullable": true });
+    let strings = json!({ "type": "array", "items": { "type": "string" } });
+    let integer = json!({ "type": "integer", "format": "int64" });
+    let version = json!({ "type": "integer", "format": "int64", "description": "updated_at of the record when it was read, alternative to If-Match" });
+    let topic = json!({ "type": "string", "enum": LIFECYCLE_TOPICS.iter().map(|t| t.to_string()).collect::<Vec<_>>() });
+
+    let mut schemas = json!({
+        "Error": object(&["status_code", "message", "error"], json!({
+            "status_code": integer,
+            "message": string,
+            "error": string,
+            "error_code": opt_string,
+            "request_id": opt_string
+        })),
+        "User": object(&["id", "email", "name", "status", "created_at", "updated_at"], json!({
+            "id": string,
+            "email": string,
+            "name": string,
+            "status": string,
+            "created_at": timestamp,
+            "updated_at": timestamp,
+            "deleted_at": opt_timestamp
+        })),
+        "Org": object(&["id", "name", "status", "updated_at", "created_at"], json!({
+            "id": string,
+            "name": string,
+            "status": string,
+            "owner_id": opt_string,
+            "owner_email": opt_string,
+            "owner_name": opt_string,
+            "member_count": integer,
+            "app_count": integer,
+            "updated_at": timestamp,
+            "created_at": timestamp,
+            "deleted_at": opt_timestamp,
+            "created_by": opt_string,
+            "updated_by": opt_string,
+            "pending_transfer": schema_ref("OrgOwnerTransfer")
+        })),
+        "App": object(&["id", "name", "client_id", "redirect_uri", "created_at", "updated_at"], json!({
+            "id": string,
+            "name": string,
+            "client_id": string,
+            "client_secret": { "type": "string", "description": "Only present when the secret is issued" },
+            "redirect_uri": string,
+            "redirect_uris": { "type": "array", "items": string, "description": "Every registered redirect URI, the main one first" },
+            "scopes": { "type": "array", "items": string, "description": "Scopes the app may request" },
+            "created_at": timestamp,
+            "updated_at": timestamp,
+            "deleted_at": opt_timestamp,
+            "previous_secret_expires_at": opt_timestamp,
+            "created_by": opt_string,
+            "updated_by": opt_string
+        })),
+        "AppEnvironment": object(&["id", "app_id", "label", "client_id", "redirect_uri", "created_at"], json!({
+            "id": string,
+            "app_id": string,
+            "label": string,
+            "client_id": string,
+            "client_secret": { "type": "string", "description": "Only present when the secret is issued" },
+            "redirect_uri": string,
+            "created_at": timestamp,
+            "previous_secret_expires_at": opt_timestamp
+        })),
+        "OrgMember": object(&["id", "org_id", "user_id", "roles", "status", "created_at", "updated_at"], json!({
+            "id": string,
+            "org_id": string,
+            "user_id": string,
+            "member_email": opt_string,
+            "member_name": opt_string,
+            "roles": strings,
+            "status": string,
+            "created_at": timestamp,
+            "updated_at": timestamp
+        })),
+        "OrgRole": object(&["id", "org_id", "name", "description", "permissions", "created_at", "updated_at"], json!({
+            "id": string,
+            "org_id": string,
+            "name": string,
+            "description": string,
+            "permissions": strings,
+            "created_at": timestamp,
+            "updated_at": timestamp
+        })),
+        "LifecycleSubscription": object(&["id", "app_id", "topic", "url", "created_at", "updated_at"], json!({
+            "id": string,
+            "app_id": string,
+            "topic": topic,
+            "url": string,
+            "created_at": timestamp,
+            "updated_at": timestamp
+        })),
+        "LifecycleSubscriptionSecret": object(&["id", "app_id", "topic", "url", "secret", "created_at", "updated_at"], json!({
+            "id": string,
+            "app_id": string,
+            "topic": topic,
+            "url": string,
+            "secret": string,
+            "created_at": timestamp,
+            "updated_at": timestamp
+        })),
+        "LifecycleDelivery": object(&["id", "event_id", "subscription_id", "app_id", "topic", "status", "attempts", "next_attempt_at", "created_at", "updated_at"], json!({
+            "id": string,
+            "event_id": string,
+            "subscription_id": string,
+            "app_id": string,
+            "topic": topic,
+            "status": { "type": "string", "enum": ["pending", "delivered", "failed"] },
+            "attempts": integer,
+            "next_attempt_at": timestamp,
+            "last_status": { "type": "integer", "nullable": true },
+            "last_error": opt_string,
+            "created_at": timestamp,
+            "updated_at": timestamp,
+            "delivered_at": opt_timestamp
+        })),
+        "Job": object(&["id", "kind", "payload", "status", "attempts", "max_attempts", "run_at", "created_at", "updated_at"], json!({
+            "id": string,
+            "kind": { "type": "string", "enum": ["app.redirect_uri_probe"] },
+            "payload": string,
+            "status": { "type": "string", "enum": ["queued", "running", "succeeded", "dead"] },
+            "attempts": integer,
+            "max_attempts": integer,
+            "run_at": timestamp,
+            "locked_until": opt_timestamp,
+            "last_error": opt_string,
+            "created_at": timestamp,
+            "updated_at": timestamp,
+            "finished_at": opt_timestamp
+        })),
+        "Actor": object(&["id", "org_id", "org_count", "scopes", "user", "roles", "permissions"], json!({
+            "id": string,
+            "org_id": string,
+            "org_count": integer,
+            "scopes": strings,
+            "user": schema_ref("User"),
+            "roles": strings,
+            "permissions": strings,
+            "region": opt_string,
+            "home_region": opt_string,
+            "impersonator_id": { "type": "string", "description": "Superuser acting as the user, only present while impersonating" }
+        })),
+        "AuthorizedApp": object(&["app_id", "app_name", "org_id", "org_name", "scope", "created_at", "last_used_at"], json!({
+            "app_id": string,
+            "app_name": string,
+            "org_id": string,
+            "org_name": string,
+            "scope": string,
+            "created_at": timestamp,
+            "last_used_at": timestamp
+        })),
+        "UserSession": object(&["id", "user_id", "created_at", "last_used_at", "expires_at", "current"], json!({
+            "id": string,
+            "user_id": string,
+            "user_agent": opt_string,
+            "ip_address": opt_string,
+            "created_at": timestamp,
+            "last_used_at": timestamp,
+            "expires_at": timestamp,
+            "revoked_at": opt_timestamp,
+            "current": { "type": "boolean" }
+        })),
+        "OrgRateUsage": object(&["org_id", "enabled", "requests_per_min", "burst", "source", "remaining"], json!({
+            "org_id": string,
+            "enabled": { "type": "boolean" },
+            "requests_per_min": integer,
+            "burst": integer,
+            "source": { "type": "string", "enum": ["org", "default"] },
+            "remaining": integer
+        })),
+        "TokenRevocation": object(&["user_id", "revoked_at"], json!({
+            "user_id": string,
+            "revoked_at": timestamp,
+            "revoked_by": opt_string
+        })),
+        "Stats": object(&["users", "active_users", "orgs", "active_orgs", "apps", "org_members"], json!({
+            "users": integer,
+            "active_users": integer,
+            "orgs": integer,
+            "active_orgs": integer,
+            "apps": integer,
+            "org_members": integer,
+            "updated_at": opt_timestamp
+        })),
+        "UserImportRowResult": object(&["row", "email", "user_id", "errors"], json!({
+            "row": integer,
+            "email": string,
+            "user_id": opt_string,
+            "errors": strings
+        })),
+        "UserImportResult": object(&["imported", "total", "created", "failed", "rows"], json!({
+            "imported": { "type": "boolean" },
+            "total": integer,
+            "created": integer,
+            "failed": integer,
+            "rows": list_of("UserImportRowResult")
+        })),
+        "PaginatedMeta": object(&["page", "per_page", "total_records", "total_pages"], json!({
+            "page": integer,
+            "per_page": integer,
+            "total_records": integer,
+            "total_pages": integer
+        })),
+        "OauthTokenRequest": object(&["grant_type", "client_id", "client_secret"], json!({
+            "grant_type": { "type": "string", "enum": ["authorization_code", "client_credentials"] },
+            "client_id": string,
+            "client_secret": string,
+            "code": { "type": "string", "description": "Required for authorization_code" },
+            "state": { "type": "string", "description": "Required for authorization_code" },
+            "redirect_uri": { "type": "string", "description": "Required for authorization_code" },
+            "org_id": { "type": "string", "description": "Required for client_credentials, an org the app is linked to" },
+            "scope": { "type": "string", "description": "client_credentials only, oauth (default) or org.read" }
+        })),
+        "OauthTokenResponse": object(&["access_token", "scope", "token_type"], json!({
+            "access_token": string,
+            "scope": string,
+            "token_type": string,
+            "key_id": string,
+            "id_token": { "type": "string", "description": "Signed OpenID Connect ID token, only for the openid scope" }
+        })),
+        "NewUser": object(&["email", "name", "password"], json!({
+            "email": string,
+            "name": string,
+            "password": string
+        })),
+        "UpdateUser": object(&[], json!({
+            "name": string,
+            "status": string,
+            "version": version
+        })),
+        "NewOrg": object(&["name", "owner_id"], json!({
+            "name": string,
+            "owner_id": string
+        })),
+        "UpdateOrg": object(&[], json!({
+            "name": string,
+            "status": string,
+            "owner_id": string,
+            "version": version
+        })),
+        "NewApp": object(&["name", "redirect_uri"], json!({
+            "name": string,
+            "redirect_uri": string,
+            "scopes": { "type": "array", "items": string, "description": "Defaults to every app scope" }
+        })),
+        "UpdateApp": object(&[], json!({
+            "name": string,
+            "redirect_uri": string,
+            "scopes": { "type": "array", "items": string },
+            "version": version
+        })),
+        "NewAppEnvironment": object(&["label", "redirect_uri"], json!({
+            "label": string,
+            "redirect_uri": string
+        })),
+        "NewOrgMember": object(&["user_id", "status"], json!({
+            "user_id": string,
+            "roles": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "The org's default member role when left out"
+            },
+            "status": string
+        })),
+        "BulkOrgMembers": object(&["members"], json!({
+            "members": list_of("NewOrgMember")
+        })),
+        "BulkResult": object(&["items"], json!({
+            "items": {
+                "type": "array",
+                "items": object(&["id", "label", "success", "message"], json!({
+                    "id": string,
+                    "label": string,
+                    "success": { "type": "boolean" },
+                    "message": opt_string
+                }))
+            }
+        })),
+        "NewOrgRole": object(&["name", "permissions"], json!({
+            "name": string,
+            "description": string,
+            "permissions": strings
+        })),
+        "UpdateOrgRole": object(&[], json!({
+            "name": string,
+            "description": string,
+            "permissions": strings
+        })),
+        "OrgMemberRoles": object(&["role_ids"], json!({
+            "role_ids": strings
+        })),
+        "NewLifecycleSubscription": object(&["topic", "url"], json!({
+            "topic": topic,
+            "url": string
+        })),
+        "UpdateOrgRateLimit": object(&["requests_per_min", "burst"], json!({
+            "requests_per_min": integer,
+            "burst": integer
+        }))
+    });
+
+    // Split out to stay under the json! macro recursion limit
+    if let (Some(schemas), Value::Object(tokens)) = (schemas.as_object_mut(), token_schemas()) {
+        schemas.extend(tokens);
+    }
+    if let (Some(schemas), Value::Object(profile)) = (schemas.as_object_mut(), profile_schemas()) {
+        schemas.extend(profile);
+    }
+    if let (Some(schemas), Value::Object(teams)) = (schemas.as_object_mut(), team_schemas()) {
+        schemas.extend(teams);
+    }
+    if let (Some(schemas), Value::Object(notifications)) =
+        (schemas.as_object_mut(), notification_schemas())
+    {
+        schemas.extend(notifications);
+    }
+    if let (Some(schemas), Value::Object(batch)) = (schemas.as_object_mut(), batch_get_schemas()) {
+        schemas.extend(batch);
+    }
+    if let (Some(schemas), Value::Object(transfers)) =
+        (schemas.as_object_mut(), owner_transfer_schemas())
+    {
+        schemas.extend(transfers);
+    }
+    if let (Some(schemas), Value::Object(credentials)) =
+        (schemas.as_object_mut(), app_credential_schemas())
+    {
+        schemas.extend(credentials);
+    }
+    if let (Some(schemas), Value::Object(org_apps)) = (schemas.as_object_mut(), org_app_schemas()) {
+        schemas.extend(org_apps);
+    }
+    if let (Some(schemas), Value::Object(impersonations)) =
+        (schemas.as_object_mut(), impersonation_schemas())
+    {
+        schemas.extend(impersonations);
+    }
+    if let (Some(schemas), Value::Object(exports)) =
+        (schemas.as_object_mut(), user_export_schemas())
+    {
+        schemas.extend(exports);
+    }
+    if let (Some(schemas), Value::Object(erasure)) =
+        (schemas.as_object_mut(), user_erasure_schemas())
+    {
+        schemas.extend(erasure);
+    }
+    if let (Some(schemas), Value::Object(invitations)) =
+        (schemas.as_object_mut(), invitation_schemas())
+    {
+        schemas.extend(invitations);
+    }
+    if let (Some(schemas), Value::Object(transfers)) =
+        (schemas.as_object_mut(), org_transfer_schemas())
+    {
+        schemas.extend(transfers);
+    }
+
+    schemas
+}
+
+fn profile_schemas() -> Value {
+    let string = json!({ "type": "string" });
+    let opt_string = json!({ "type": "string", "nullable": true });
+
+    json!({
+        "UserProfile": object(&["user_id", "metadata"], json!({
+            "user_id": string,
+            "avatar_url": opt_string,
+            "locale": opt_string,
+            "timezone": opt_string,
+            "phone": opt_string,
+            "metadata": { "type": "object", "additionalProperties": true }
+        })),
+        "UpdateUserProfile": object(&[], json!({
+            "avatar_url": string,
+            "locale": { "type": "string", "description": "BCP 47 language tag" },
+            "timezone": { "type": "string", "description": "IANA time zone name" },
+            "phone": { "type": "string", "description": "E.164 phone number" },
+            "metadata": {
+                "type": "object",
+                "additionalProperties": true,
+                "description": format!("Merged into the stored object, at most {} keys and {} bytes", USER_PROFILE_METADATA_MAX_KEYS, USER_PROFILE_METADATA_MAX_BYTES)
+            }
+        })),
+        "OrgSettings": object(&["org_id", "default_member_role", "allowed_email_domains"], json!({
+            "org_id": string,
+            "logo_url": opt_string,
+            "default_member_role": string,
+            "allowed_email_domains": { "type": "array", "items": { "type": "string" } }
+        })),
+        "UpdateOrgSettings": object(&[], json!({
+            "logo_url": { "type": "string", "description": "HTTPS URL, empty to remove the logo" },
+            "default_member_role": { "type": "string", "description": "OrgAdmin, OrgEditor or OrgViewer" },
+            "allowed_email_domains": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": format!("Exact domains members' emails must be at, at most {}, empty to allow any", MAX_ALLOWED_EMAIL_DOMAINS)
+            }
+        }))
+    })
+}
+
+fn team_schemas() -> Value {
+    let string = json!({ "type": "string" });
+    let opt_string = json!({ "type": "string", "nullable": true });
+    let integer = json!({ "type": "integer", "format": "int64" });
+    let timestamp = json!({ "type": "integer", "format": "int64", "description": "Unix timestamp in milliseconds" });
+    let roles = json!({
+        "type": "array",
+        "items": { "type": "string" },
+        "description": "OrgAdmin, OrgEditor or OrgViewer, granted to every member of the team"
+    });
+
+    json!({
+        "Team": object(&["id", "org_id", "name", "description", "roles", "member_count", "created_at", "updated_at"], json!({
+            "id": string,
+            "org_id": string,
+            "name": string,
+            "description": string,
+            "roles": roles,
+            "member_count": integer,
+            "created_at": timestamp,
+            "updated_at": timestamp
+        })),
+        "NewTeam": object(&["name"], json!({
+            "name": string,
+            "description": string,
+            "roles": roles
+        })),
+        "UpdateTeam": object(&[], json!({
+            "name": string,
+            "description": string,
+            "roles": roles
+        })),
+        "TeamMember": object(&["team_id", "user_id", "created_at"], json!({
+            "team_id": string,
+            "user_id": string,
+            "member_name": opt_string,
+            "member_email": opt_string,
+            "created_at": timestamp
+        })),
+        "NewTeamMember": object(&["user_id"], json!({
+            "user_id": string
+        }))
+    })
+}
+
+fn batch_get_schemas() -> Value {
+    json!({
+        "BatchGet": object(&["ids"], json!({
+            "ids": { "type": "array", "items": { "type": "string" }, "minItems": 1, "maxItems": MAX_BATCH_GET_IDS }
+        }))
+    })
+}
+
+fn app_credential_schemas() -> Value {
+    let string = json!({ "type": "string" });
+
+    json!({
+        "AppRedirectUri": object(&["redirect_uri"], json!({
+            "redirect_uri": string
+        }))
+    })
+}
+
+fn org_app_schemas() -> Value {
+    let string = json!({ "type": "string" });
+    let opt_string = json!({ "type": "string", "nullable": true });
+    let opt_scopes = json!({ "type": "array", "items": string, "nullable": true });
+    let timestamp = json!({ "type": "integer", "format": "int64", "description": "Unix timestamp in milliseconds" });
+
+    json!({
+        "OrgApp": object(&["id", "org_id", "app_id", "created_at"], json!({
+            "id": string,
+            "org_id": string,
+            "app_id": string,
+            "app_name": opt_string,
+            "scopes": opt_scopes,
+            "created_at": timestamp
+        })),
+        "UpdateOrgApp": object(&[], json!({
+            "scopes": opt_scopes
+        }))
+    })
+}
+
+fn impersonation_schemas() -> Value {
+    let string = json!({ "type": "string" });
+    let timestamp = json!({ "type": "integer", "format": "int64", "description": "Unix timestamp in milliseconds" });
+    let opt_timestamp = json!({ "type": "integer", "format": "int64", "nullable": true });
+    let impersonation = json!({
+        "id": string,
+        "impersonator_id": string,
+        "user_id": string,
+        "org_id": string,
+        "created_at": timestamp,
+        "expires_at": timestamp,
+        "ended_at": opt_timestamp
+    });
+    let required = [
+        "id",
+        "impersonator_id",
+        "user_id",
+        "org_id",
+        "created_at",
+        "expires_at",
+    ];
+
+    let mut log = impersonation.clone();
+    log["actions"] = json!({ "type": "array", "items": schema_ref("ImpersonationAction") });
+
+    json!({
+        "Impersonation": object(&required, impersonation),
+        "ImpersonationLog": object(&[&required[..], &["actions"]].concat(), log),
+        "ImpersonationAction": object(&["id", "impersonation_id", "method", "path", "status", "created_at"], json!({
+            "id": string,
+            "impersonation_id": string,
+            "method": string,
+            "path": string,
+            "status": { "type": "integer", "description": "Response status code" },
+            "created_at": timestamp
+        })),
+        "ImpersonationToken": object(&["token", "impersonation"], json!({
+            "token": { "type": "string", "description": "Bearer token acting as the user, only returned once" },
+            "impersonation": schema_ref("Impersonation")
+        }))
+    })
+}
+
+fn user_export_schemas() -> Value {
+    let string = json!({ "type": "string" });
+    let opt_string = json!({ "type": "string", "nullable": true });
+    let timestamp = json!({ "type": "integer", "format": "int64", "description": "Unix timestamp in milliseconds" });
+    let opt_timestamp = json!({ "type": "integer", "format": "int64", "nullable": true });
+
+    json!({
+        "UserExport": object(&["id", "user_id", "format", "status", "created_at"], json!({
+            "id": string,
+            "user_id": string,
+            "requested_by": opt_string,
+            "format": { "type": "string", "enum": ["json", "protobuf"] },
+            "status": { "type": "string", "enum": ["pending", "ready"] },
+            "size": { "type": "integer", "format": "int64", "nullable": true, "description": "Archive size in bytes" },
+            "created_at": timestamp,
+            "completed_at": opt_timestamp,
+            "expires_at": opt_timestamp,
+            "download_url": { "type": "string", "description": "Signed link valid for an hour, only once ready" }
+        }))
+    })
+}
+
+fn user_erasure_schemas() -> Value {
+    let string = json!({ "type": "string" });
+    let timestamp = json!({ "type": "integer", "format": "int64", "description": "Unix timestamp in milliseconds" });
+
+    json!({
+        "ErasureConfirmation": object(&["user_id", "confirmation_token", "expires_at"], json!({
+            "user_id": string,
+            "confirmation_token": string,
+            "expires_at": timestamp
+        })),
+        "AnonymizeUser": object(&["confirmation_token"], json!({
+            "confirmation_token": { "type": "string", "description": "Token from the confirmation endpoint" }
+        })),
+        "AnonymizedUser": object(&["user", "erased_at", "rows_removed"], json!({
+            "user": schema_ref("User"),
+            "erased_at": timestamp,
+            "rows_removed": { "type": "integer", "format": "int64", "description": "Credential and personal data rows removed" }
+        }))
+    })
+}
+
+fn invitation_schemas() -> Value {
+    let string = json!({ "type": "string" });
+    let opt_string = json!({ "type": "string", "nullable": true });
+    let integer = json!({ "type": "integer", "format": "int64" });
+    let timestamp = json!({ "type": "integer", "format": "int64", "description": "Unix timestamp in milliseconds" });
+    let opt_timestamp = json!({ "type": "integer", "format": "int64", "nullable": true });
+    let delivery = json!({ "type": "string", "enum": ["pending", "delivered", "failed"] });
+
+    json!({
+        "OrgInvitation": object(&["id", "org_id", "email", "roles", "created_at", "updated_at", "expires_at"], json!({
+            "id": string,
+            "org_id": string,
+            "email": string,
+            "roles": { "type": "array", "items": { "type": "string" } },
+            "created_at": timestamp,
+            "updated_at": timestamp,
+            "expires_at": timestamp,
+            "accepted_at": opt_timestamp,
+            "accepted_by": opt_string,
+            "created_by": opt_string,
+            "email_id": { "type": "string", "nullable": true, "description": "Outbox email carrying the latest link" },
+            "delivery_status": { "type": "string", "nullable": true, "enum": ["pending", "delivered", "failed"] }
+        })),
+        "PasswordReset": object(&["id", "user_id", "created_at", "expires_at"], json!({
+            "id": string,
+            "user_id": string,
+            "created_at": timestamp,
+            "expires_at": timestamp,
+            "used_at": opt_timestamp,
+            "email_id": { "type": "string", "nullable": true, "description": "Outbox email carrying the link" },
+            "delivery_status": { "type": "string", "nullable": true, "enum": ["pending", "delivered", "failed"] }
+        })),
+        "Email": object(&["id", "recipient", "subject", "status", "attempts", "next_attempt_at", "created_at", "updated_at"], json!({
+            "id": string,
+            "org_id": opt_string,
+            "recipient": string,
+            "subject": string,
+            "status": delivery,
+            "attempts": integer,
+            "next_attempt_at": timestamp,
+            "last_error": opt_string,
+            "created_at": timestamp,
+            "updated_at": timestamp,
+            "sent_at": opt_timestamp
+        }))
+    })
+}
+
+fn org_transfer_schemas() -> Value {
+    let string = json!({ "type": "string" });
+    let opt_string = json!({ "type": "string", "nullable": true });
+    let strings = json!({ "type": "array", "items": string });
+    let opt_strings = json!({ "type": "array", "items": string, "nullable": true });
+    let timestamp = json!({ "type": "integer", "format": "int64", "description": "Unix timestamp in milliseconds" });
+
+    json!({
+        "OrgArchive": object(&["format_version", "exported_at", "org", "members", "apps"], json!({
+            "format_version": { "type": "integer" },
+            "exported_at": timestamp,
+            "org": object(&["id", "name", "status"], json!({
+                "id": string,
+                "name": string,
+                "status": string,
+                "owner_email": opt_string
+            })),
+            "members": { "type": "array", "items": object(&["email", "roles", "status"], json!({
+                "email": string,
+                "roles": strings,
+                "status": string,
+                "custom_roles": strings
+            })) },
+            "apps": { "type": "array", "items": object(&["id", "name", "redirect_uri"], json!({
+                "id": string,
+                "name": string,
+                "redirect_uri": string,
+                "extra_redirect_uris": strings,
+                "scopes": opt_strings,
+                "org_scopes": opt_strings
+            })) },
+            "settings": {
+                "type": "object",
+                "nullable": true,
+                "properties": {
+                    "logo_url": opt_string,
+                    "default_member_role": string,
+                    "allowed_email_domains": strings
+                }
+            },
+            "roles": { "type": "array", "items": object(&["name", "description", "permissions"], json!({
+                "name": string,
+                "description": string,
+                "permissions": strings
+            })) }
+        })),
+        "OrgImportReport": object(&["dry_run", "plan", "result"], json!({
+            "dry_run": { "type": "boolean" },
+            "plan": {
+                "type": "object",
+                "description": "What the import does with the org, its settings, roles, apps and members"
+            },
+            "result": {
+                "type": "object",
+                "nullable": true,
+                "description": "Target org id, app id mappings and counts, null on a dry run or a skipped org"
+            }
+        }))
+    })
+}
+
+fn owner_transfer_schemas() -> Value {
+    let string = json!({ "type": "string" });
+    let opt_string = json!({ "type": "string", "nullable": true });
+    let timestamp = json!({ "type": "integer", "format": "int64", "description": "Unix timestamp in milliseconds" });
+    let opt_timestamp = json!({ "type": "integer", "format": "int64", "nullable": true });
+
+    json!({
+        "OrgOwnerTransfer": object(&["id", "org_id", "from_user_id", "to_user_id", "created_at", "updated_at", "expires_at"], json!({
+            "id": string,
+            "org_id": string,
+            "from_user_id": string,
+            "to_user_id": string,
+            "to_email": opt_string,
+            "created_at": timestamp,
+            "updated_at": timestamp,
+            "expires_at": timestamp,
+            "accepted_at": opt_timestamp,
+            "cancelled_at": opt_timestamp,
+            "created_by": opt_string
+        })),
+        "NewOrgOwnerTransfer": object(&["user_id"], json!({
+            "user_id": string
+        }))
+    })
+}
+
+fn notification_schemas() -> Value {
+    let string = json!({ "type": "string" });
+    let opt_string = json!({ "type": "string", "nullable": true });
+    let integer = json!({ "type": "integer", "format": "int64" });
+    let timestamp = json!({ "type": "integer", "format": "int64", "description": "Unix timestamp in milliseconds" });
+    let opt_timestamp = json!({ "type": "integer", "format": "int64", "nullable": true });
+
+    json!({
+        "UserNotification": object(&["id", "user_id", "kind", "title", "body", "created_at"], json!({
+            "id": string,
+            "user_id": string,
+            "kind": { "type": "string", "enum": ["membership_added", "role_changed", "password_changed"] },
+            "title": string,
+            "body": string,
+            "link": opt_string,
+            "read_at": opt_timestamp,
+            "created_at": timestamp
+        })),
+        "UserNotifications": object(&["unread_count", "notifications"], json!({
+            "unread_count": integer,
+            "notifications": list_of("UserNotification")
+        }))
+    })
+}
+
+fn token_schemas() -> Value {
+    let string = json!({ "type": "string" });
+    let strings = json!({ "type": "array", "items": { "type": "string" } });
+    let seconds =
+        json!({ "type": "integer", "format": "int64", "description": "Unix timestamp in seconds" });
+
+    json!({
+        "OauthIntrospectRequest": object(&["client_id", "client_secret", "token"], json!({
+            "client_id": string,
+            "client_secret": string,
+            "token": string,
+            "token_type_hint": string
+        })),
+        "OauthIntrospection": object(&["active"], json!({
+            "active": { "type": "boolean" },
+            "scope": string,
+            "client_id": string,
+            "token_type": string,
+            "sub": string,
+            "org_id": string,
+            "exp": seconds,
+            "iat": seconds
+        })),
+        "OauthRevokeRequest": object(&["client_id", "client_secret", "token"], json!({
+            "client_id": string,
+            "client_secret": string,
+            "token": string,
+            "token_type_hint": string
+        })),
+        "OpenidConfiguration": object(&["issuer", "authorization_endpoint", "token_endpoint", "userinfo_endpoint", "jwks_uri"], json!({
+            "issuer": string,
+            "authorization_endpoint": string,
+            "token_endpoint": string,
+            "userinfo_endpoint": string,
+            "jwks_uri": string,
+            "response_types_supported": strings,
+            "grant_types_supported": strings,
+            "subject_types_supported": strings,
+            "id_token_signing_alg_values_supported": strings,
+            "scopes_supported": strings,
+            "token_endpoint_auth_methods_supported": strings,
+            "claims_supported": strings
+        })),
+        "Jwks": object(&["keys"], json!({
+            "keys": {
+                "type": "array",
+                "items": object(&["kty", "use", "alg", "kid"], json!({
+                    "kty": string,
+                    "use": string,
+                    "alg": string,
+                    "kid": string,
+                    "n": string,
+                    "e": string,
+                    "crv": string,
+                    "x": string
+                }))
+            }
+        })),
+        "Userinfo": object(&["sub", "name", "email", "updated_at"], json!({
+            "sub": string,
+            "name": string,
+            "email": string,
+            "updated_at": seconds
+        }))
+    })
+}
+
+fn object(required: &[&str], properties: Value) -> Value {
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = json!(required);
+    }
+    schema
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+fn list_of(name: &str) -> Value {
+    json!({ "type": "array", "items": schema_ref(name) })
+}
+
+fn paginated(name: &str) -> Value {
+    object(
+        &["meta", "data"],
+        json!({ "meta": schema_ref("PaginatedMeta"), "data": list_of(name) }),
+    )
+}
+
+/// Records of a batch get in the requested order and the ids not found
+fn batch_of(name: &str) -> Value {
+    object(
+        &["items", "missing_ids"],
+        json!({
+            "items": list_of(name),
+            "missing_ids": { "type": "array", "items": { "type": "string" } }
+        }),
+    )
+}
+
+/// Offset page by default, keyset page once `cursor` or `limit` is passed
+fn paginated_or_cursor(name: &str) -> Value {
+    json!({
+        "oneOf": [
+            paginated(name),
+            object(
+                &["data", "next_cursor"],
+                json!({
+                    "data": list_of(name),
+                    "next_cursor": { "type": "string", "nullable": true }
+                }),
+            )
+        ]
+    })
+}
+
+/// Updated record with the names of the fields that changed
+fn updated(name: &str) -> Value {
+    json!({
+        "allOf": [
+            schema_ref(name),
+            object(&["changed_fields"], json!({ "changed_fields": { "type": "array", "items": { "type": "string" } } }))
+        ]
+    })
+}
+
+fn path_param(name: &str) -> Value {
+    json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } })
+}
+
+fn query_param(name: &str, kind: &str, description: &str, required: bool) -> Value {
+    json!({
+        "name": name,
+        "in": "query",
+        "required": required,
+        "description": description,
+        "schema": { "type": kind }
+    })
+}
+
+fn if_match_param() -> Value {
+    json!({
+        "name": "If-Match",
+        "in": "header",
+        "required": false,
+        "description": "ETag of the record, required unless the body has the version",
+        "schema": { "type": "string" }
+    })
+}
+
+fn idempotency_key_param() -> Value {
+    json!({
+        "name": "Idempotency-Key",
+        "in": "header",
+        "required": false,
+        "description": "Retries with the same key and body within 24 hours get the first successful response back",
+        "schema": { "type": "string", "maxLength": 255 }
+    })
+}
+
+fn fields_param() -> Value {
+    query_param(
+        "fields",
+        "string",
+        "Comma separated fields to return, all fields when left out",
+        false,
+    )
+}
+
+fn include_deleted_param() -> Value {
+    query_param(
+        "include_deleted",
+        "boolean",
+        "Include soft deleted records",
+        false,
+    )
+}
+
+fn cursor_params() -> Vec<Value> {
+    vec![
+        query_param(
+            "cursor",
+            "string",
+            "`next_cursor` of the previous page, switches to keyset pagination",
+            false,
+        ),
+        query_param(
+            "limit",
+            "integer",
+            "Records per keyset page, also switches to keyset pagination",
+            false,
+        ),
+    ]
+}
+
+fn sort_params(sort: &SortSpec) -> Vec<Value> {
+    let fields: Vec<&str> = sort.columns.iter().map(|(field, _)| *field).collect();
+    vec![
+        json!({
+            "name": "sort_by",
+            "in": "query",
+            "required": false,
+            "description": "Field to sort by, ignored on cursor pages",
+            "schema": { "type": "string", "enum": fields, "default": sort.default_field() }
+        }),
+        json!({
+            "name": "sort_dir",
+            "in": "query",
+            "required": false,
+            "description": "Sort direction, ignored on cursor pages",
+            "schema": { "type": "string", "enum": ["asc", "desc"], "default": "asc" }
+        }),
+    ]
+}
+
+fn listing_params(include_deleted: bool, sort: &SortSpec) -> Vec<Value> {
+    let mut params = vec![
+        query_param("page", "integer", "Page number, starts at 1", false),
+        query_param("per_page", "integer", "Records per page", false),
+        query_param("keyword", "string", "Search keyword", false),
+        fields_param(),
+    ];
+    params.extend(sort_params(sort));
+    if include_deleted {
+        params.push(include_deleted_param());
+    }
+    params
+}
+
+/// Operation with JSON in and out, errors use the `Error` schema
+fn op(
+    tag: &str,
+    summary: &str,
+    params: Vec<Value>,
+    body: Option<&str>,
+    status: &str,
+    response: Option<Value>,
+) -> Value {
+    let mut operation = public_op(tag, summary, body, status, response);
+    operation["security"] = json!([{ "bearer": [] }]);
+    if !params.is_empty() {
+        operation["parameters"] = json!(params);
+    }
+    operation
+}
+
+fn admin_op(
+    summary: &str,
+    params: Vec<Value>,
+    body: Option<&str>,
+    status: &str,
+    response: Option<Value>,
+) -> Value {
+    op("admin", summary, params, body, status, response)
+}
+
+fn public_op(
+    tag: &str,
+    summary: &str,
+    body: Option<&str>,
+    status: &str,
+    response: Option<Value>,
+) -> Value {
+    let success = match response {
+        Some(schema) => json!({
+            "description": "OK",
+            "content": { "application/json": { "schema": schema } }
+        }),
+        None => json!({ "description": "No content" }),
+    };
+
+    let mut operation = json!({
+        "tags": [tag],
+        "summary": summary,
+        "responses": {
+            status: success,
+            "default": {
+                "description": "Error",
+                "content": { "application/json": { "schema": schema_ref("Error") } }
+            }
+        }
+    });
+
+    if let Some(body) = body {
+        operation["requestBody"] = json!({
+            "required": true,
+            "content": { "application/json": { "schema": schema_ref(body) } }
+        });
+    }
+
+    operation
+}
+
+fn csv_op(summary: &str, params: Vec<Value>) -> Value {
+    let mut operation = admin_op(summary, params, None, "200", None);
+    operation["responses"]["200"] = json!({
+        "description": "OK",
+        "content": { "text/csv": { "schema": { "type": "string" } } }
+    });
+    operation
+}
+
+/// Exports answer 202 while pending and 200 with the download link once ready
+fn export_op(mut operation: Value) -> Value {
+    operation["responses"]["200"] = operation["responses"]["202"].clone();
+    operation
+}
+
+/// Export archive download, the signed token in the link is the only credential
+fn download_op() -> Value {
+    let mut operation = public_op(
+        "oauth",
+        "Download a data export archive with its signed link",
+        None,
+        "200",
+        None,
+    );
+    operation["parameters"] = json!([query_param(
+        "token",
+        "string",
+        "Signed token of the download link",
+        true
+    )]);
+    operation["responses"]["200"] = json!({
+        "description": "OK",
+        "content": {
+            "application/json": { "schema": { "type": "object" } },
+            "application/x-protobuf": { "schema": { "type": "string", "format": "binary" } }
+        }
+    });
+    operation
+}
+
+/// Bulk user import, the body is a CSV or NDJSON file of `email`, `name` and `password`
+fn import_op() -> Value {
+    let mut operation = admin_op(
+        "Import users from CSV or NDJSON",
+        vec![],
+        None,
+        "201",
+        Some(schema_ref("UserImportResult")),
+    );
+    operation["requestBody"] = json!({
+        "required": true,
+        "content": {
+            "text/csv": { "schema": { "type": "string" } },
+            "application/x-ndjson": { "schema": { "type": "string" } }
+        }
+    });
+    operation["responses"]["422"] = json!({
+        "description": "Nothing was imported, see the errors of each row",
+        "content": { "application/json": { "schema": schema_ref("UserImportResult") } }
+    });
+    operation
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde_json::Value;
+
+    use crate::dto::{
+        ImportConflictStrategy, ListEmailsParamsDto, NewOrgInvitationDto, NewPasswordDto,
+        NewTeamDto, Scope,
+    };
+    use crate::services::mailer::list_emails_svc;
+    use crate::services::org_invitations::create_org_invitation_svc;
+    use crate::services::org_settings::get_org_settings_svc;
+    use crate::services::org_transfer::{export_org_svc, import_org_svc};
+    use crate::services::password::update_password_svc;
+    use crate::services::password_reset::request_password_reset_svc;
+    use crate::services::teams::create_team_svc;
+    use crate::services::user_notifications::list_user_notifications_svc;
+    use crate::services::user_profiles::get_user_profile_svc;
+    use crate::test::TestCtx;
+
+    use super::openapi_spec_svc;
+
+    /// Paths registered with `.route(` in a router source file
+    fn routes_in(source: &str, prefix: &str) -> Vec<String> {
+        source
+            .split(".route(")
+            .skip(1)
+            .filter_map(|rest| {
+                let start = rest.find('"')? + 1;
+                let end = start + rest[start..].find('"')?;
+                Some(format!("{}{}", prefix, &rest[start..end]))
+            })
+            .collect()
+    }
+
+    /// Fields of the value missing from the schema, or required and absent
+    fn schema_drift<T: Serialize>(spec: &Value, name: &str, value: &T) -> Vec<String> {
+        let schema = &spec["components"]["schemas"][name];
+        let value = serde_json::to_value(value).expect("serializable");
+        let object = value.as_object().expect("an object");
+        let mut drift = Vec::new();
+
+        for key in object.keys() {
+            if schema["properties"].get(key).is_none() {
+                drift.push(format!("{}.{}: not in the schema", name, key));
+            }
+        }
+        for key in schema["required"].as_array().into_iter().flatten() {
+            let key = key.as_str().unwrap_or_default();
+            if !object.contains_key(key) {
+                drift.push(format!("{}.{}: required but missing", name, key));
+            }
+        }
+
+        drift
+    }
+
+    #[test]
+    fn every_api_route_is_documented() {
+        let spec = openapi_spec_svc();
+        let paths = spec["paths"].as_object().expect("paths");
+
+        let mut routes = routes_in(include_str!("../web/oauth.rs"), "");
+        routes.extend(routes_in(include_str!("../web/admin_api.rs"), "/admin/api"));
+        assert!(routes.len() > 20);
+
+        for route in routes {
+            assert!(paths.contains_key(&route), "{} is not documented", route);
+        }
+
+        // Every referenced schema exists
+        let text = spec.to_string();
+        for reference in text.split("#/components/schemas/").skip(1) {
+            let name = &reference[..reference.find('"').expect("closing quote")];
+            assert!(
+                spec["components"]["schemas"].get(name).is_some(),
+                "{} is not defined",
+                name
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn dtos_match_their_documented_schema() {
+        let ctx = TestCtx::new("openapi_schemas").await.expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "OpenAPI User",
+                "openapi@example.com",
+                "password123",
+                "OpenAPI Org",
+                "OpenAPI App",
+                "https://openapi.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+        let spec = openapi_spec_svc();
+
+        let mut drift = schema_drift(&spec, "User", &fixture.auth.user);
+        drift.extend(schema_drift(&spec, "Org", &fixture.auth.org));
+        drift.extend(schema_drift(&spec, "App", &fixture.app));
+
+        let actor_ctx = fixture.auth.to_ctx(vec![Scope::Auth]);
+        let actor = actor_ctx.actor().expect("actor");
+        drift.extend(schema_drift(&spec, "Actor", actor));
+
+        let profile = get_user_profile_svc(&ctx.state, &fixture.auth.user.id)
+            .await
+            .expect("profile");
+        drift.extend(schema_drift(&spec, "UserProfile", &profile));
+
+        let settings = get_org_settings_svc(&ctx.state, &fixture.auth.org.id)
+            .await
+            .expect("org settings");
+        drift.extend(schema_drift(&spec, "OrgSettings", &settings));
+
+        let team = create_team_svc(
+            &ctx.state,
+            &fixture.auth.org.id,
+            NewTeamDto {
+                name: "OpenAPI Team".to_string(),
+                description: String::new(),
+                roles: vec!["OrgViewer".to_string()],
+            },
+        )
+        .await
+        .expect("team");
+        drift.extend(schema_drift(&spec, "Team", &team));
+
+        update_password_svc(
+            &ctx.state,
+            &fixture.auth.user.id,
+            NewPasswordDto {
+                password: "new-password123".to_string(),
+            },
+        )
+        .await
+        .expect("update password");
+        let notifications = list_user_notifications_svc(&ctx.state, &fixture.auth.user.id)
+            .await
+            .expect("notifications");
+        drift.extend(schema_drift(&spec, "UserNotifications", &notifications));
+        drift.extend(schema_drift(
+            &spec,
+            "UserNotification",
+            &notifications.notifications[0],
+        ));
+
+        let issued = create_org_invitation_svc(
+            &ctx.state,
+            &fixture.auth.org.id,
+            NewOrgInvitationDto {
+                email: "openapi.invitee@example.com".to_string(),
+                roles: vec!["OrgViewer".to_string()],
+            },
+        )
+        .await
+        .expect("invitation");
+        drift.extend(schema_drift(&spec, "OrgInvitation", &issued.invitation));
+
+        let emails = list_emails_svc(&ctx.state, ListEmailsParamsDto { status: None })
+            .await
+            .expect("emails");
+        drift.extend(schema_drift(&spec, "Email", &emails[0]));
+
+        let reset = request_password_reset_svc(&ctx.state, &fixture.auth.user.email)
+            .await
+            .expect("password reset")
+            .expect("issued");
+        drift.extend(schema_drift(&spec, "PasswordReset", &reset.reset));
+
+        let archive = export_org_svc(&ctx.state.db, &fixture.auth.org.id)
+            .await
+            .expect("org archive");
+        drift.extend(schema_drift(&spec, "OrgArchive", &archive));
+        let report = import_org_svc(&ctx.state.db, &archive, ImportConflictStrategy::Merge, true)
+            .await
+            .expect("import report");
+        drift.extend(schema_drift(&spec, "OrgImportReport", &report));
+
+        assert!(drift.is_empty(), "{:?}", drift);
+    }
+}
@@ -0,0 +1,208 @@
+use chrono::{DateTime, Utc};
+use snafu::OptionExt;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, instrument};
+
+use crate::Result;
+use crate::db::{DbMapper, DeletedScope};
+use crate::dto::{OrgAccessDto, OrgAccessExportParamsDto};
+use crate::error::OrgNotFoundSnafu;
+use crate::run::AppState;
+
+/// Repeated requests within this window are recorded once
+pub const ORG_ACCESS_SAMPLE_MINS: u64 = 5;
+
+const ORG_ACCESS_CSV_HEADER: &str = "day,user_id,email,name,first_seen_at,last_seen_at,samples";
+
+/// Records that the user accessed the org today, sampled per window.
+///
+/// Failures are logged and never fail the request being authenticated.
+#[instrument(level = "debug", skip_all)]
+pub async fn record_org_access(state: &AppState, org_id: &str, user_id: &str) {
+    let now = Utc::now();
+    let day = now.format("%Y-%m-%d").to_string();
+    let key = format!("{}:{}:{}", org_id, user_id, day);
+
+    if state.access_log_cache.contains_key(&key) {
+        return;
+    }
+    state.access_log_cache.insert(key, ());
+
+    let result = state
+        .db
+        .org_access
+        .touch(
+            org_id.to_string(),
+            user_id.to_string(),
+            day,
+            now.timestamp_millis(),
+        )
+        .await;
+
+    if let Err(e) = result {
+        error!("Failed to record org access: {}", e);
+    }
+}
+
+/// Users that accessed the org between two days as CSV, deleted orgs included
+#[instrument(level = "debug", skip_all)]
+pub async fn export_org_access_csv_svc(
+    state: &AppState,
+    org_id: &str,
+    params: OrgAccessExportParamsDto,
+) -> Result<String> {
+    let (from, to) = params.range()?;
+
+    let _ = state
+        .db
+        .orgs
+        .get_scoped(org_id.to_string(), DeletedScope::include_deleted(true))
+        .await?
+        .context(OrgNotFoundSnafu)?;
+
+    let items = state
+        .db
+        .org_access
+        .list_range(org_id.to_string(), from.to_string(), to.to_string())
+        .await?;
+
+    Ok(render_org_access_csv(&items))
+}
+
+/// Removes access rows older than `retention_days`, returns the count
+#[instrument(level = "debug", skip_all)]
+pub async fn prune_org_access_svc(db: &DbMapper, retention_days: u64) -> Result<u64> {
+    let cutoff = Utc::now() - chrono::Duration::days(retention_days as i64);
+    let removed = db
+        .org_access
+        .delete_before(cutoff.format("%Y-%m-%d").to_string())
+        .await?;
+
+    info!(
+        removed = removed,
+        retention_days = retention_days,
+        "org_access.pruned"
+    );
+
+    Ok(removed)
+}
+
+/// Applies the access log retention once a day
+pub async fn org_access_retention_job(db: Arc<DbMapper>, retention_days: u64) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(24 * 60 * 60));
+
+    loop {
+        ticker.tick().await;
+
+        if let Err(e) = prune_org_access_svc(&db, retention_days).await {
+            error!("Org access retention failed: {}", e);
+        }
+    }
+}
+
+fn render_org_access_csv(items: &[OrgAccessDto]) -> String {
+    let mut out = String::from(ORG_ACCESS_CSV_HEADER);
+    out.push('\n');
+
+    for item in items.iter() {
+        let fields = [
+            item.day.clone(),
+            item.user_id.clone(),
+            item.email.clone().unwrap_or_default(),
+            item.name.clone().unwrap_or_default(),
+            format_millis(item.first_seen_at),
+            format_millis(item.last_seen_at),
+            item.samples.to_string(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+
+    out
+}
+
+fn format_millis(millis: i64) -> String {
+    DateTime::from_timestamp_millis(millis)
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_default()
+}
+
+/// Quotes fields with separators, quotes or line breaks.
+/// Leading formula characters are escaped so spreadsheets show them as text.
+fn csv_field(value: &str) -> String {
+    let value = match value.starts_with(['=', '+', '-', '@']) {
+        true => format!("'{}", value),
+        false => value.to_string(),
+    };
+
+    match value.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::OrgAccessExportParamsDto;
+    use crate::test::TestCtx;
+
+    use super::{csv_field, export_org_access_csv_svc, prune_org_access_svc, record_org_access};
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("Doe, Jane"), "\"Doe, Jane\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("=SUM(A1)"), "'=SUM(A1)");
+    }
+
+    #[tokio::test]
+    async fn access_is_sampled_exported_and_pruned() {
+        let ctx = TestCtx::new("org_access_log").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture("Owner", "owner@example.com", "password", "Acme")
+            .await
+            .expect("seed fixture");
+
+        record_org_access(&ctx.state, &fixture.org.id, &fixture.user.id).await;
+        record_org_access(&ctx.state, &fixture.org.id, &fixture.user.id).await;
+
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        let params = OrgAccessExportParamsDto {
+            from: today.clone(),
+            to: today.clone(),
+        };
+        let csv = export_org_access_csv_svc(&ctx.state, &fixture.org.id, params.clone())
+            .await
+            .expect("export");
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with(&format!("{},{},owner@example.com", today, fixture.user.id)));
+        assert!(lines[1].ends_with(",1"));
+
+        // An old row falls outside the retention window
+        ctx.state
+            .db
+            .org_access
+            .touch(
+                fixture.org.id.clone(),
+                fixture.user.id.clone(),
+                "2020-01-01".to_string(),
+                0,
+            )
+            .await
+            .expect("old access");
+
+        let removed = prune_org_access_svc(&ctx.state.db, 30)
+            .await
+            .expect("prune");
+        assert_eq!(removed, 1);
+
+        let csv = export_org_access_csv_svc(&ctx.state, &fixture.org.id, params)
+            .await
+            .expect("export");
+        assert_eq!(csv.lines().count(), 2);
+    }
+}
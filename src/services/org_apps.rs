@@ -1,12 +1,21 @@
 use serde::{Deserialize, Serialize};
-use snafu::ensure;
+use snafu::{OptionExt, ensure};
+use tracing::instrument;
 
-use crate::Result;
+use crate::ctx::AuditCtx;
 use crate::dto::Paginated;
-use crate::dto::{ListOrgAppsParamsDto, NewOrgAppDto, OrgAppDto, OrgAppSuggestionDto};
-use crate::error::{CsrfTokenSnafu, ValidationSnafu};
+use crate::dto::{
+    AppDto, ListOrgAppsParamsDto, NewOrgAppDto, OrgAppDto, OrgAppSuggestionDto, Scope,
+    UpdateOrgAppDto, to_scopes,
+};
+use crate::error::{
+    AppNotFoundSnafu, CsrfTokenSnafu, InvalidAuthTokenSnafu, OrgAppNotFoundSnafu, ValidationSnafu,
+};
 use crate::run::AppState;
+use crate::services::counters::refresh_org_counters;
 use crate::services::token::verify_csrf_token;
+use crate::validators::validate_payload;
+use crate::{Error, Result};
 
 #[derive(Clone, Deserialize, Serialize)]
 pub struct NewOrgAppFormData {
@@ -15,6 +24,44 @@ pub struct NewOrgAppFormData {
     pub app_name: String,
 }
 
+/// Org app scopes form, scopes are submitted as repeated `scopes` fields
+#[derive(Clone, Deserialize, Serialize)]
+pub struct OrgAppScopesFormData {
+    pub token: String,
+    pub restrict: Option<String>,
+    pub scopes: Vec<String>,
+}
+
+impl TryFrom<Vec<(String, String)>> for OrgAppScopesFormData {
+    type Error = Error;
+
+    fn try_from(pairs: Vec<(String, String)>) -> Result<Self> {
+        let mut token: Option<String> = None;
+        let mut restrict: Option<String> = None;
+        let mut scopes: Vec<String> = Vec::new();
+
+        for (key, value) in pairs.into_iter() {
+            match key.as_str() {
+                "token" => token = Some(value),
+                "restrict" => restrict = Some(value),
+                "scopes" if !value.is_empty() && !scopes.contains(&value) => scopes.push(value),
+                _ => {}
+            }
+        }
+
+        let Some(token) = token else {
+            return Err(Error::CsrfToken);
+        };
+
+        Ok(OrgAppScopesFormData {
+            token,
+            restrict,
+            scopes,
+        })
+    }
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn list_org_apps_svc(
     state: &AppState,
     org_id: &str,
@@ -23,6 +70,7 @@ pub async fn list_org_apps_svc(
     state.db.org_apps.list(org_id.to_string(), params).await
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn list_org_app_suggestions_svc(
     state: &AppState,
     org_id: &str,
@@ -35,11 +83,14 @@ pub async fn list_org_app_suggestions_svc(
         .await
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn create_org_app_svc(
     state: &AppState,
     org_id: &str,
     data: NewOrgAppDto,
 ) -> Result<OrgAppDto> {
+    validate_payload(&data)?;
+
     // Ensure that the app exists
     let app_id = data.app_id.clone();
     let existing_app = state.db.apps.get(app_id.clone()).await?;
@@ -65,9 +116,17 @@ pub async fn create_org_app_svc(
         }
     );
 
-    state.db.org_apps.create(org_id.to_string(), data).await
+    let org_app = state
+        .db
+        .org_apps
+        .create(&AuditCtx::current(), org_id.to_string(), data)
+        .await?;
+
+    refresh_org_counters(&state.db, org_id).await;
+    Ok(org_app)
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn create_org_app_web_svc(
     state: &AppState,
     org_id: &str,
@@ -86,6 +145,7 @@ pub async fn create_org_app_web_svc(
     .await
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn get_org_app_svc(
     state: &AppState,
     org_id: &str,
@@ -98,10 +158,118 @@ pub async fn get_org_app_svc(
         .await
 }
 
+/// Client credentials tokens are only valid while the app stays linked to the org
+#[instrument(level = "debug", skip_all)]
+pub async fn verify_org_app_link_svc(
+    state: &AppState,
+    org_app_id: &str,
+    app_id: &str,
+    org_id: &str,
+) -> Result<OrgAppDto> {
+    let link = state.db.org_apps.get(org_app_id.to_string()).await?;
+    let link = link.context(InvalidAuthTokenSnafu)?;
+    ensure!(
+        link.app_id == app_id && link.org_id == org_id,
+        InvalidAuthTokenSnafu
+    );
+
+    let org = state.db.orgs.get(org_id.to_string()).await?;
+    ensure!(org.is_some(), InvalidAuthTokenSnafu);
+
+    Ok(link)
+}
+
+/// Scopes the app can be granted in the org.
+///
+/// The scopes the app declares, narrowed by the org's restriction on the link when it has one.
+pub fn granted_app_scopes(app: &AppDto, org_app: &OrgAppDto) -> Result<Vec<Scope>> {
+    let declared = to_scopes(&app.scopes)?;
+
+    let Some(restriction) = &org_app.scopes else {
+        return Ok(declared);
+    };
+
+    let allowed = to_scopes(restriction)?;
+    Ok(declared
+        .into_iter()
+        .filter(|scope| allowed.contains(scope))
+        .collect())
+}
+
+/// Restricts the scopes the org grants the app, only scopes the app declares can be kept
+#[instrument(level = "debug", skip_all)]
+pub async fn update_org_app_svc(
+    state: &AppState,
+    org_id: &str,
+    app_id: &str,
+    data: UpdateOrgAppDto,
+) -> Result<OrgAppDto> {
+    validate_payload(&data)?;
+
+    let org_app = get_org_app_svc(state, org_id, app_id)
+        .await?
+        .context(OrgAppNotFoundSnafu)?;
+    let app = state
+        .db
+        .apps
+        .get(app_id.to_string())
+        .await?
+        .context(AppNotFoundSnafu)?;
+
+    if let Some(scopes) = &data.scopes {
+        ensure!(
+            scopes.iter().all(|scope| app.scopes.contains(scope)),
+            ValidationSnafu {
+                msg: "Only scopes the app declares can be allowed".to_string(),
+            }
+        );
+    }
+
+    state
+        .db
+        .org_apps
+        .set_scopes(&AuditCtx::current(), org_app.id.clone(), data.scopes)
+        .await?;
+
+    get_org_app_svc(state, org_id, app_id)
+        .await?
+        .context(OrgAppNotFoundSnafu)
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn update_org_app_scopes_web_svc(
+    state: &AppState,
+    org_id: &str,
+    app_id: &str,
+    form: OrgAppScopesFormData,
+) -> Result<OrgAppDto> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == app_id, CsrfTokenSnafu);
+
+    // Unchecking the restriction lets the link grant every scope the app declares
+    let scopes = form.restrict.map(|_| form.scopes);
+
+    update_org_app_svc(state, org_id, app_id, UpdateOrgAppDto { scopes }).await
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn delete_org_app_svc(state: &AppState, id: &str) -> Result<()> {
-    state.db.org_apps.delete(id.to_string()).await
+    let existing = state.db.org_apps.get(id.to_string()).await?;
+
+    state
+        .db
+        .org_apps
+        .delete(&AuditCtx::current(), id.to_string())
+        .await?;
+
+    if let Some(existing) = existing {
+        refresh_org_counters(&state.db, &existing.org_id).await;
+    }
+
+    Ok(())
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn delete_org_app_web_svc(
     state: &AppState,
     org_id: &str,
@@ -129,11 +297,14 @@ pub async fn delete_org_app_web_svc(
 
 #[cfg(test)]
 mod tests {
+    use crate::dto::{Scope, UpdateAppDto, UpdateOrgAppDto};
+    use crate::services::apps::{get_app_svc, update_app_svc};
     use crate::services::token::create_csrf_token_svc;
     use crate::test::TestCtx;
 
     use super::{
         NewOrgAppFormData, create_org_app_web_svc, delete_org_app_web_svc, get_org_app_svc,
+        granted_app_scopes, update_org_app_svc,
     };
 
     #[tokio::test]
@@ -228,13 +399,13 @@ mod tests {
         let create_csrf = create_csrf_token_svc("new_org_app", &ctx.state.config.jwt_secret)
             .expect("csrf token should be generated");
 
-        let _created = create_org_app_web_svc(
+        let created = create_org_app_web_svc(
             &ctx.state,
             &fixture.auth.org.id,
             NewOrgAppFormData {
-                token: create_csrf,
+                token: create_csrf.clone(),
                 app_id: fixture.app.id.clone(),
-                app_name: fixture.app.name,
+                app_name: fixture.app.name.clone(),
             },
         )
         .await
@@ -256,6 +427,20 @@ mod tests {
             .await
             .expect("query should pass");
         assert!(fetched.is_none());
+
+        // Links are soft deleted, linking again restores the same row
+        let relinked = create_org_app_web_svc(
+            &ctx.state,
+            &fixture.auth.org.id,
+            NewOrgAppFormData {
+                token: create_csrf,
+                app_id: fixture.app.id.clone(),
+                app_name: fixture.app.name,
+            },
+        )
+        .await
+        .expect("org app should be linked again");
+        assert_eq!(relinked.id, created.id);
     }
 
     #[tokio::test]
@@ -306,4 +491,85 @@ mod tests {
             .expect("query should pass");
         assert!(fetched.is_some());
     }
+
+    #[tokio::test]
+    async fn update_org_app_svc_narrows_scopes_the_app_declares() {
+        let ctx = TestCtx::new("org_apps_update_scopes")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "Org Apps User",
+                "org.apps.scopes@example.com",
+                "password123",
+                "Org Apps Org",
+                "Org Apps App",
+                "https://org-apps.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+        let org_id = fixture.auth.org.id.clone();
+        let app_id = fixture.app.id.clone();
+
+        update_app_svc(
+            &ctx.state,
+            &app_id,
+            UpdateAppDto {
+                name: None,
+                redirect_uri: None,
+                scopes: Some(vec!["auth".to_string(), "oauth".to_string()]),
+            },
+        )
+        .await
+        .expect("app scopes should update");
+
+        let org_app = update_org_app_svc(
+            &ctx.state,
+            &org_id,
+            &app_id,
+            UpdateOrgAppDto {
+                scopes: Some(vec!["oauth".to_string()]),
+            },
+        )
+        .await
+        .expect("org app scopes should update");
+        assert_eq!(org_app.scopes, Some(vec!["oauth".to_string()]));
+
+        let app = get_app_svc(&ctx.state, &app_id)
+            .await
+            .expect("query should pass")
+            .expect("app should exist");
+        assert_eq!(
+            granted_app_scopes(&app, &org_app).expect("granted scopes"),
+            vec![Scope::Oauth]
+        );
+
+        // Scopes the app does not declare cannot be allowed by the org
+        let result = update_org_app_svc(
+            &ctx.state,
+            &org_id,
+            &app_id,
+            UpdateOrgAppDto {
+                scopes: Some(vec!["openid".to_string()]),
+            },
+        )
+        .await;
+        assert!(result.is_err(), "undeclared scope should be rejected");
+
+        // Lifting the restriction grants every declared scope again
+        let org_app = update_org_app_svc(
+            &ctx.state,
+            &org_id,
+            &app_id,
+            UpdateOrgAppDto { scopes: None },
+        )
+        .await
+        .expect("restriction should be lifted");
+        assert_eq!(org_app.scopes, None);
+        assert_eq!(
+            granted_app_scopes(&app, &org_app).expect("granted scopes"),
+            vec![Scope::Auth, Scope::Oauth]
+        );
+    }
 }
@@ -0,0 +1,855 @@
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ensure};
+use tracing::{error, info, instrument};
+
+use crate::ctx::AuditCtx;
+use crate::dto::{
+    ChangeAction, EntityChangeDto, InvitationAccount, LifecycleTopic, NewOrgInvitationDto,
+    NewUserWithPasswordDto, OrgInvitationDto, OrgMemberDto,
+};
+use crate::error::{CsrfTokenSnafu, NotFoundSnafu, OrgNotFoundSnafu, ValidationSnafu};
+use crate::run::AppState;
+use crate::services::admin_events::publish_change;
+use crate::services::counters::refresh_org_counters;
+use crate::services::emails::{org_branding_svc, org_invitation_email, render_email};
+use crate::services::lifecycle::{publish_membership_event_svc, publish_user_event_svc};
+use crate::services::mailer::queue_email_svc;
+use crate::services::org_members::{form_roles, push_role};
+use crate::services::org_settings::{
+    default_member_roles, ensure_member_email_allowed, get_org_settings_svc,
+};
+use crate::services::password::hash_password;
+use crate::services::recovery::generate_recovery_token;
+use crate::services::suggestions::invalidate_suggestions;
+use crate::services::token::verify_csrf_token;
+use crate::services::user_emails::{email_in_use_svc, find_user_by_login_email_svc};
+use crate::utils::hash_secret;
+use crate::validators::validate_payload;
+use crate::{Error, Result};
+
+pub const INVITATION_TTL_HOURS: i64 = 72;
+
+/// Invite form, roles are submitted as repeated `roles` fields
+#[derive(Clone, Deserialize, Serialize)]
+pub struct OrgInvitationFormData {
+    pub token: String,
+    pub email: String,
+    pub roles: Vec<String>,
+}
+
+impl TryFrom<Vec<(String, String)>> for OrgInvitationFormData {
+    type Error = Error;
+
+    fn try_from(pairs: Vec<(String, String)>) -> Result<Self> {
+        let mut token: Option<String> = None;
+        let mut email = String::new();
+        let mut roles: Vec<String> = Vec::new();
+
+        for (key, value) in pairs.into_iter() {
+            match key.as_str() {
+                "token" => token = Some(value),
+                "email" => email = value,
+                "roles" => push_role(&mut roles, value),
+                _ => {}
+            }
+        }
+
+        let Some(token) = token else {
+            return Err(Error::CsrfToken);
+        };
+
+        Ok(OrgInvitationFormData {
+            token,
+            email,
+            roles,
+        })
+    }
+}
+
+/// Acceptance form, name and password are only used when the invitee has no account yet
+#[derive(Clone, Deserialize, Serialize)]
+pub struct AcceptInvitationFormData {
+    pub token: String,
+
+    #[serde(default)]
+    pub name: String,
+
+    #[serde(default)]
+    pub password: String,
+
+    #[serde(default)]
+    pub confirm_password: String,
+}
+
+/// Freshly issued invitation, the plain token is never stored
+pub struct IssuedOrgInvitation {
+    pub token: String,
+    pub invitation: OrgInvitationDto,
+}
+
+/// What the acceptance page shows before the invitee confirms
+pub struct InvitationPreview {
+    pub invitation: OrgInvitationDto,
+    pub org_name: String,
+    pub has_account: bool,
+}
+
+const INVALID_INVITATION_MSG: &str = "Invitation link is invalid or has expired.";
+
+fn accept_url(token: &str) -> String {
+    format!("/invitations/accept?token={}", token)
+}
+
+/// Emails the acceptance link to the invitee with the org branding, the
+/// invitation tracks the delivery of that email
+async fn send_issued(
+    state: &AppState,
+    issued: &mut IssuedOrgInvitation,
+    event: &str,
+) -> Result<()> {
+    let org_id = &issued.invitation.org_id;
+    let branding = org_branding_svc(&state.db, org_id).await?;
+    let url = format!(
+        "{}{}",
+        state.config.mailer.base_url,
+        accept_url(&issued.token)
+    );
+
+    let message = render_email(
+        &branding,
+        Some(org_id.clone()),
+        &issued.invitation.email,
+        org_invitation_email(&branding.name, url, INVITATION_TTL_HOURS),
+    )?;
+    let email = queue_email_svc(&state.db, message).await?;
+    state
+        .db
+        .org_invitations
+        .set_email(issued.invitation.id.clone(), email.id.clone())
+        .await?;
+    issued.invitation.email_id = Some(email.id);
+    issued.invitation.delivery_status = Some(email.status);
+
+    info!(
+        invitation_id = issued.invitation.id,
+        org_id = issued.invitation.org_id,
+        email = issued.invitation.email,
+        expires_at = issued.invitation.expires_at,
+        "{}",
+        event
+    );
+
+    Ok(())
+}
+
+fn invitation_expiry(now: i64) -> i64 {
+    now + INVITATION_TTL_HOURS * 60 * 60 * 1000
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn list_org_invitations_svc(
+    state: &AppState,
+    org_id: &str,
+) -> Result<Vec<OrgInvitationDto>> {
+    state.db.org_invitations.list(org_id.to_string()).await
+}
+
+async fn get_org_invitation_svc(
+    state: &AppState,
+    org_id: &str,
+    id: &str,
+) -> Result<OrgInvitationDto> {
+    state
+        .db
+        .org_invitations
+        .get(org_id.to_string(), id.to_string())
+        .await?
+        .context(NotFoundSnafu {
+            msg: "Invitation not found".to_string(),
+        })
+}
+
+/// Invites the email to the org with the given roles.
+///
+/// Fails when the email already belongs to a member or has a pending invitation,
+/// resend the pending one instead.
+#[instrument(level = "debug", skip_all)]
+pub async fn create_org_invitation_svc(
+    state: &AppState,
+    org_id: &str,
+    mut data: NewOrgInvitationDto,
+) -> Result<IssuedOrgInvitation> {
+    let _ = state
+        .db
+        .orgs
+        .get(org_id.to_string())
+        .await?
+        .context(OrgNotFoundSnafu)?;
+
+    let settings = get_org_settings_svc(state, org_id).await?;
+    if data.roles.is_empty() {
+        data.roles = default_member_roles(&settings);
+    }
+
+    data.email = data.email.trim().to_lowercase();
+    validate_payload(&data)?;
+    ensure_member_email_allowed(&settings, &data.email)?;
+
+    if let Some(user) = find_user_by_login_email_svc(state, &data.email).await? {
+        let member = state
+            .db
+            .org_members
+            .find_member(org_id.to_string(), user.id.clone())
+            .await?;
+        ensure!(
+            member.is_none(),
+            ValidationSnafu {
+                msg: "User is already a member of the organization".to_string(),
+            }
+        );
+
+        let superuser = state.db.superusers.get(user.id).await?;
+        ensure!(
+            superuser.is_none(),
+            ValidationSnafu {
+                msg: "Cannot add superuser as organization member".to_string(),
+            }
+        );
+    }
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let pending = state
+        .db
+        .org_invitations
+        .find_pending(org_id.to_string(), data.email.clone(), now)
+        .await?;
+    ensure!(
+        pending.is_none(),
+        ValidationSnafu {
+            msg: "Email already has a pending invitation, resend it instead".to_string(),
+        }
+    );
+
+    let token = generate_recovery_token()?;
+    let invitation = state
+        .db
+        .org_invitations
+        .create(
+            &AuditCtx::current(),
+            org_id.to_string(),
+            data,
+            hash_secret(&token),
+            invitation_expiry(now),
+        )
+        .await?;
+
+    let mut issued = IssuedOrgInvitation { token, invitation };
+    send_issued(state, &mut issued, "org_invitation.issued").await?;
+
+    Ok(issued)
+}
+
+/// Sends a new link with a fresh expiry, the previous link stops working
+#[instrument(level = "debug", skip_all)]
+pub async fn resend_org_invitation_svc(
+    state: &AppState,
+    org_id: &str,
+    id: &str,
+) -> Result<IssuedOrgInvitation> {
+    let invitation = get_org_invitation_svc(state, org_id, id).await?;
+    ensure!(
+        invitation.accepted_at.is_none(),
+        ValidationSnafu {
+            msg: "Invitation was already accepted".to_string(),
+        }
+    );
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let token = generate_recovery_token()?;
+    let token_hash = hash_secret(&token);
+    let expires_at = invitation_expiry(now);
+
+    let reissued = state
+        .db
+        .org_invitations
+        .reissue(invitation.id.clone(), token_hash.clone(), expires_at)
+        .await?;
+    ensure!(
+        reissued,
+        ValidationSnafu {
+            msg: "Invitation was already accepted".to_string(),
+        }
+    );
+
+    let mut issued = IssuedOrgInvitation {
+        token,
+        invitation: OrgInvitationDto {
+            token_hash,
+            expires_at,
+            updated_at: now,
+            ..invitation
+        },
+    };
+    send_issued(state, &mut issued, "org_invitation.resent").await?;
+
+    Ok(issued)
+}
+
+/// Expires a pending invitation so its link can no longer be used
+#[instrument(level = "debug", skip_all)]
+pub async fn expire_org_invitation_svc(state: &AppState, org_id: &str, id: &str) -> Result<()> {
+    let invitation = get_org_invitation_svc(state, org_id, id).await?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let expired = state.db.org_invitations.expire(invitation.id, now).await?;
+    ensure!(
+        expired,
+        ValidationSnafu {
+            msg: "Only pending invitations can be expired".to_string(),
+        }
+    );
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn create_org_invitation_web_svc(
+    state: &AppState,
+    org_id: &str,
+    form: OrgInvitationFormData,
+) -> Result<IssuedOrgInvitation> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+
+    let roles = form_roles(&form.roles)?;
+
+    create_org_invitation_svc(
+        state,
+        org_id,
+        NewOrgInvitationDto {
+            email: form.email,
+            roles,
+        },
+    )
+    .await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn resend_org_invitation_web_svc(
+    state: &AppState,
+    org_id: &str,
+    id: &str,
+    csrf_token: &str,
+) -> Result<IssuedOrgInvitation> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+
+    resend_org_invitation_svc(state, org_id, id).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn expire_org_invitation_web_svc(
+    state: &AppState,
+    org_id: &str,
+    id: &str,
+    csrf_token: &str,
+) -> Result<()> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+
+    expire_org_invitation_svc(state, org_id, id).await
+}
+
+async fn find_usable_invitation(state: &AppState, token: &str) -> Result<OrgInvitationDto> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let invitation = state
+        .db
+        .org_invitations
+        .find_by_hash(hash_secret(token))
+        .await?
+        .context(ValidationSnafu {
+            msg: INVALID_INVITATION_MSG,
+        })?;
+
+    ensure!(
+        invitation.is_usable(now),
+        ValidationSnafu {
+            msg: INVALID_INVITATION_MSG
+        }
+    );
+
+    Ok(invitation)
+}
+
+/// Looks up the invitation behind an acceptance link
+#[instrument(level = "debug", skip_all)]
+pub async fn preview_org_invitation_svc(
+    state: &AppState,
+    token: &str,
+) -> Result<InvitationPreview> {
+    let invitation = find_usable_invitation(state, token).await?;
+    let org = state
+        .db
+        .orgs
+        .get(invitation.org_id.clone())
+        .await?
+        .context(ValidationSnafu {
+            msg: INVALID_INVITATION_MSG,
+        })?;
+    let has_account = find_user_by_login_email_svc(state, &invitation.email)
+        .await?
+        .is_some();
+
+    Ok(InvitationPreview {
+        invitation,
+        org_name: org.name,
+        has_account,
+    })
+}
+
+/// Accepts the invitation behind the link.
+///
+/// Invitees without an account get one with the submitted name and password.
+/// The account, when new, and the active membership are created together, the
+/// link can only be used once.
+#[instrument(level = "debug", skip_all)]
+pub async fn accept_org_invitation_svc(
+    state: &AppState,
+    form: AcceptInvitationFormData,
+) -> Result<OrgMemberDto> {
+    let invitation = find_usable_invitation(state, &form.token).await?;
+
+    // The allowed domains may have changed since the invitation was sent
+    let settings = get_org_settings_svc(state, &invitation.org_id).await?;
+    ensure_member_email_allowed(&settings, &invitation.email)?;
+
+    let account = match find_user_by_login_email_svc(state, &invitation.email).await? {
+        Some(user) => {
+            ensure!(
+                user.status == "active",
+                ValidationSnafu {
+                    msg: INVALID_INVITATION_MSG
+                }
+            );
+
+            let member = state
+                .db
+                .org_members
+                .find_member(invitation.org_id.clone(), user.id.clone())
+                .await?;
+            ensure!(
+                member.is_none(),
+                ValidationSnafu {
+                    msg: "You are already a member of the organization".to_string(),
+                }
+            );
+
+            let superuser = state.db.superusers.get(user.id.clone()).await?;
+            ensure!(
+                superuser.is_none(),
+                ValidationSnafu {
+                    msg: "Cannot add superuser as organization member".to_string(),
+                }
+            );
+
+            InvitationAccount::Existing(user)
+        }
+        None => {
+            ensure!(
+                form.password == form.confirm_password,
+                ValidationSnafu {
+                    msg: "Passwords must match".to_string()
+                }
+            );
+
+            let mut new_user = NewUserWithPasswordDto {
+                email: invitation.email.clone(),
+                name: form.name.trim().to_string(),
+                password: form.password,
+            };
+            validate_payload(&new_user)?;
+
+            // An unverified secondary email can still hold the address
+            let in_use = email_in_use_svc(state, &new_user.email).await?;
+            ensure!(
+                !in_use,
+                ValidationSnafu {
+                    msg: "Email already exists".to_string(),
+                }
+            );
+
+            new_user.password = hash_password(&new_user.password)?;
+            InvitationAccount::New(new_user)
+        }
+    };
+    let new_account = matches!(account, InvitationAccount::New(_));
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let (user, member) = state
+        .db
+        .org_invitations
+        .accept(&invitation, account, now)
+        .await?
+        .context(ValidationSnafu {
+            msg: INVALID_INVITATION_MSG,
+        })?;
+
+    invalidate_suggestions(state);
+    refresh_org_counters(&state.db, &member.org_id).await;
+    if new_account {
+        publish_change(
+            state,
+            EntityChangeDto::user(ChangeAction::Created, &user.id),
+        );
+    }
+    publish_change(
+        state,
+        EntityChangeDto::org_member(ChangeAction::Created, &member.org_id, &user.id),
+    );
+
+    info!(
+        invitation_id = invitation.id,
+        org_id = member.org_id,
+        user_id = user.id,
+        new_account = new_account,
+        "org_invitation.accepted"
+    );
+
+    if new_account
+        && let Err(e) =
+            publish_user_event_svc(state, LifecycleTopic::UserProvisioned, &user, Vec::new()).await
+    {
+        error!("Failed to publish lifecycle event: {}", e);
+    }
+
+    if let Err(e) = publish_membership_event_svc(
+        state,
+        LifecycleTopic::MembershipGranted,
+        &member,
+        Vec::new(),
+    )
+    .await
+    {
+        error!("Failed to publish lifecycle event: {}", e);
+    }
+
+    Ok(member)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+    use crate::dto::{
+        DELIVERY_FAILED, DELIVERY_PENDING, ListEmailsParamsDto, NewOrgInvitationDto, Role,
+        UpdateOrgSettingsDto,
+    };
+    use crate::services::mailer::{EMAIL_MAX_ATTEMPTS, list_emails_svc};
+    use crate::services::org_settings::update_org_settings_svc;
+    use crate::services::password::verify_password;
+    use crate::test::TestCtx;
+
+    use super::{
+        AcceptInvitationFormData, accept_org_invitation_svc, create_org_invitation_svc,
+        expire_org_invitation_svc, list_org_invitations_svc, preview_org_invitation_svc,
+        resend_org_invitation_svc,
+    };
+
+    fn accept_form(token: &str) -> AcceptInvitationFormData {
+        AcceptInvitationFormData {
+            token: token.to_string(),
+            name: "Invited Person".to_string(),
+            password: "invited-password".to_string(),
+            confirm_password: "invited-password".to_string(),
+        }
+    }
+
+    fn invitation(email: &str) -> NewOrgInvitationDto {
+        NewOrgInvitationDto {
+            email: email.to_string(),
+            roles: vec!["OrgEditor".to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn accepting_creates_user_and_membership_once() {
+        let ctx = TestCtx::new("org_invitations_accept")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Invite Owner",
+                "invite.owner@example.com",
+                "password123",
+                "Invite Org",
+            )
+            .await
+            .expect("auth fixture");
+
+        let first = create_org_invitation_svc(
+            &ctx.state,
+            &fixture.org.id,
+            invitation(" New.Person@Example.com "),
+        )
+        .await
+        .expect("invitation should be created");
+        assert_eq!(first.invitation.email, "new.person@example.com");
+        assert_ne!(first.token, first.invitation.token_hash);
+
+        let duplicate = create_org_invitation_svc(
+            &ctx.state,
+            &fixture.org.id,
+            invitation("new.person@example.com"),
+        )
+        .await;
+        assert!(duplicate.is_err(), "pending invitations are not duplicated");
+
+        // Resending replaces the link
+        let resent = resend_org_invitation_svc(&ctx.state, &fixture.org.id, &first.invitation.id)
+            .await
+            .expect("invitation should be resent");
+        assert!(
+            preview_org_invitation_svc(&ctx.state, &first.token)
+                .await
+                .is_err()
+        );
+
+        let preview = preview_org_invitation_svc(&ctx.state, &resent.token)
+            .await
+            .expect("preview");
+        assert_eq!(preview.org_name, "Invite Org");
+        assert!(!preview.has_account);
+
+        let mut mismatch = accept_form(&resent.token);
+        mismatch.confirm_password = "something-else".to_string();
+        assert!(
+            accept_org_invitation_svc(&ctx.state, mismatch)
+                .await
+                .is_err()
+        );
+
+        let member = accept_org_invitation_svc(&ctx.state, accept_form(&resent.token))
+            .await
+            .expect("invitation should be accepted");
+        assert_eq!(member.status, "active");
+        assert_eq!(member.roles, vec![Role::OrgEditor]);
+
+        let user = ctx
+            .state
+            .db
+            .users
+            .find_by_email("new.person@example.com".to_string())
+            .await
+            .expect("user lookup")
+            .expect("user created");
+        assert_eq!(user.name, "Invited Person");
+        let password = ctx
+            .state
+            .db
+            .passwords
+            .get(user.id.clone())
+            .await
+            .expect("password lookup")
+            .expect("password row");
+        assert!(verify_password("invited-password", &password.password).expect("verify"));
+
+        let reused = accept_org_invitation_svc(&ctx.state, accept_form(&resent.token)).await;
+        assert!(reused.is_err(), "links work once");
+
+        let member_again = create_org_invitation_svc(
+            &ctx.state,
+            &fixture.org.id,
+            invitation("new.person@example.com"),
+        )
+        .await;
+        assert!(member_again.is_err(), "members cannot be invited again");
+    }
+
+    #[tokio::test]
+    async fn expired_invitations_cannot_be_accepted() {
+        let ctx = TestCtx::new("org_invitations_expire")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Expire Owner",
+                "expire.owner@example.com",
+                "password123",
+                "Expire Org",
+            )
+            .await
+            .expect("auth fixture");
+        let existing = ctx
+            .seed_user_with_password("Existing", "existing@example.com", "password123")
+            .await
+            .expect("existing user");
+
+        let issued = create_org_invitation_svc(
+            &ctx.state,
+            &fixture.org.id,
+            invitation("existing@example.com"),
+        )
+        .await
+        .expect("invitation should be created");
+
+        expire_org_invitation_svc(&ctx.state, &fixture.org.id, &issued.invitation.id)
+            .await
+            .expect("invitation should expire");
+        assert!(
+            accept_org_invitation_svc(&ctx.state, accept_form(&issued.token))
+                .await
+                .is_err()
+        );
+
+        // Existing accounts only get the membership
+        let resent = resend_org_invitation_svc(&ctx.state, &fixture.org.id, &issued.invitation.id)
+            .await
+            .expect("expired invitations can be resent");
+        let member = accept_org_invitation_svc(
+            &ctx.state,
+            AcceptInvitationFormData {
+                token: resent.token,
+                name: String::new(),
+                password: String::new(),
+                confirm_password: String::new(),
+            },
+        )
+        .await
+        .expect("existing user accepts");
+        assert_eq!(member.user_id, existing.id);
+    }
+
+    #[tokio::test]
+    async fn failed_invitation_emails_show_until_resent() {
+        let ctx = TestCtx::new("org_invitations_delivery")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Delivery Owner",
+                "delivery.owner@example.com",
+                "password123",
+                "Delivery Org",
+            )
+            .await
+            .expect("auth fixture");
+
+        let issued = create_org_invitation_svc(
+            &ctx.state,
+            &fixture.org.id,
+            invitation("undelivered@example.com"),
+        )
+        .await
+        .expect("invitation should be created");
+        assert_eq!(
+            issued.invitation.delivery_status.as_deref(),
+            Some(DELIVERY_PENDING)
+        );
+        let first_email = issued.invitation.email_id.clone().expect("email queued");
+
+        // The outbox gives up on the email
+        ctx.state
+            .db
+            .email_outbox
+            .record_failure(
+                first_email.clone(),
+                DELIVERY_FAILED,
+                EMAIL_MAX_ATTEMPTS,
+                0,
+                "Connection refused".to_string(),
+                0,
+            )
+            .await
+            .expect("record failure");
+
+        let listed = list_org_invitations_svc(&ctx.state, &fixture.org.id)
+            .await
+            .expect("list invitations");
+        assert_eq!(listed[0].delivery_status.as_deref(), Some(DELIVERY_FAILED));
+
+        let failed = list_emails_svc(
+            &ctx.state,
+            ListEmailsParamsDto {
+                status: Some(DELIVERY_FAILED.to_string()),
+            },
+        )
+        .await
+        .expect("list emails");
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].id, first_email);
+
+        let resent = resend_org_invitation_svc(&ctx.state, &fixture.org.id, &issued.invitation.id)
+            .await
+            .expect("invitation should be resent");
+        assert_ne!(
+            resent.invitation.email_id.as_deref(),
+            Some(first_email.as_str())
+        );
+
+        let listed = list_org_invitations_svc(&ctx.state, &fixture.org.id)
+            .await
+            .expect("list invitations");
+        assert_eq!(listed[0].email_id, resent.invitation.email_id);
+        assert_eq!(listed[0].delivery_status.as_deref(), Some(DELIVERY_PENDING));
+
+        let unknown = list_emails_svc(
+            &ctx.state,
+            ListEmailsParamsDto {
+                status: Some("bounced".to_string()),
+            },
+        )
+        .await;
+        assert!(unknown.is_err(), "unknown statuses are rejected");
+    }
+
+    #[tokio::test]
+    async fn invitations_follow_org_settings() {
+        let ctx = TestCtx::new("org_invitations_settings")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Settings Owner",
+                "settings.owner@example.com",
+                "password123",
+                "Settings Org",
+            )
+            .await
+            .expect("auth fixture");
+
+        let data = UpdateOrgSettingsDto {
+            logo_url: None,
+            default_member_role: Some("OrgEditor".to_string()),
+            allowed_email_domains: Some(vec!["example.com".to_string()]),
+        };
+        update_org_settings_svc(&ctx.state, &fixture.org.id, data)
+            .await
+            .expect("settings should be saved");
+
+        let issued = create_org_invitation_svc(
+            &ctx.state,
+            &fixture.org.id,
+            NewOrgInvitationDto {
+                email: "default.role@example.com".to_string(),
+                roles: Vec::new(),
+            },
+        )
+        .await
+        .expect("invitation should be created");
+        assert_eq!(issued.invitation.roles, vec![Role::OrgEditor]);
+
+        // Subdomains are not included
+        let result = create_org_invitation_svc(
+            &ctx.state,
+            &fixture.org.id,
+            invitation("someone@mail.example.com"),
+        )
+        .await;
+        let Err(Error::Validation { msg }) = result else {
+            panic!("other domains cannot be invited");
+        };
+        assert_eq!(
+            msg,
+            "Only emails from example.com can join the organization"
+        );
+    }
+}
@@ -1,56 +1,210 @@
 use serde::{Deserialize, Serialize};
 use snafu::ensure;
+use tracing::{error, instrument};
 
+use crate::ctx::AuditCtx;
 use crate::dto::ListingParamsDto;
 use crate::dto::OrgMembershipDto;
-use crate::dto::Paginated;
-use crate::dto::to_roles;
+use crate::dto::{BulkResultDto, MAX_BULK_ITEMS};
+use crate::dto::{ChangeAction, EntityChangeDto, changed_fields, to_roles};
+use crate::dto::{CursorPage, Paginated};
 use crate::dto::{
-    ListOrgMembersParamsDto, NewOrgMemberDto, OrgMemberDto, OrgMemberSuggestionDto,
-    UpdateOrgMemberDto,
+    LifecycleTopic, ListOrgMembersParamsDto, NewOrgMemberDto, OrgMemberDto, UpdateOrgMemberDto,
 };
 use crate::error::CsrfTokenSnafu;
 use crate::error::ValidationSnafu;
+use crate::models::BulkStatusFormData;
 use crate::run::AppState;
+use crate::services::admin_events::publish_change;
+use crate::services::auth::invalidate_auth_cache;
+use crate::services::counters::refresh_org_counters;
+use crate::services::lifecycle::publish_membership_event_svc;
+use crate::services::notifications::notify_pending_member_svc;
+use crate::services::org_settings::{
+    default_member_roles, ensure_member_email_allowed, get_org_settings_svc,
+};
+use crate::services::suggestions::invalidate_suggestions;
 use crate::services::token::verify_csrf_token;
+use crate::services::user_notifications::{notify_membership_added_svc, notify_role_changed_svc};
+use crate::utils::ListingAllocGuard;
+use crate::validators;
+use crate::validators::validate_payload;
 use crate::{Error, Result};
 
+/// New member form, roles are submitted as repeated `roles` fields
 #[derive(Clone, Deserialize, Serialize)]
 pub struct NewOrgMemberFormData {
     pub token: String,
     pub user_id: String,
     pub user_email: String,
-    pub role: String,
+    pub roles: Vec<String>,
     pub active: Option<String>,
 }
 
+impl TryFrom<Vec<(String, String)>> for NewOrgMemberFormData {
+    type Error = Error;
+
+    fn try_from(pairs: Vec<(String, String)>) -> Result<Self> {
+        let mut token: Option<String> = None;
+        let mut user_id = String::new();
+        let mut user_email = String::new();
+        let mut active: Option<String> = None;
+        let mut roles: Vec<String> = Vec::new();
+
+        for (key, value) in pairs.into_iter() {
+            match key.as_str() {
+                "token" => token = Some(value),
+                "user_id" => user_id = value,
+                "user_email" => user_email = value,
+                "active" => active = Some(value),
+                "roles" => push_role(&mut roles, value),
+                _ => {}
+            }
+        }
+
+        let Some(token) = token else {
+            return Err(Error::CsrfToken);
+        };
+
+        Ok(NewOrgMemberFormData {
+            token,
+            user_id,
+            user_email,
+            roles,
+            active,
+        })
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct UpdateOrgMemberFormData {
     pub token: String,
-    pub role: String,
+    pub roles: Vec<String>,
+    pub active: Option<String>,
+}
+
+impl TryFrom<Vec<(String, String)>> for UpdateOrgMemberFormData {
+    type Error = Error;
+
+    fn try_from(pairs: Vec<(String, String)>) -> Result<Self> {
+        let mut token: Option<String> = None;
+        let mut active: Option<String> = None;
+        let mut roles: Vec<String> = Vec::new();
+
+        for (key, value) in pairs.into_iter() {
+            match key.as_str() {
+                "token" => token = Some(value),
+                "active" => active = Some(value),
+                "roles" => push_role(&mut roles, value),
+                _ => {}
+            }
+        }
+
+        let Some(token) = token else {
+            return Err(Error::CsrfToken);
+        };
+
+        Ok(UpdateOrgMemberFormData {
+            token,
+            roles,
+            active,
+        })
+    }
+}
+
+/// Add members form, users are picked as repeated `user_ids` and share the roles
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BulkOrgMembersFormData {
+    pub token: String,
+    pub user_ids: Vec<String>,
+    pub roles: Vec<String>,
     pub active: Option<String>,
 }
 
+impl TryFrom<Vec<(String, String)>> for BulkOrgMembersFormData {
+    type Error = Error;
+
+    fn try_from(pairs: Vec<(String, String)>) -> Result<Self> {
+        let mut token: Option<String> = None;
+        let mut user_ids: Vec<String> = Vec::new();
+        let mut active: Option<String> = None;
+        let mut roles: Vec<String> = Vec::new();
+
+        for (key, value) in pairs.into_iter() {
+            match key.as_str() {
+                "token" => token = Some(value),
+                "user_ids" if !value.is_empty() && !user_ids.contains(&value) => {
+                    user_ids.push(value)
+                }
+                "active" => active = Some(value),
+                "roles" => push_role(&mut roles, value),
+                _ => {}
+            }
+        }
+
+        let Some(token) = token else {
+            return Err(Error::CsrfToken);
+        };
+
+        Ok(BulkOrgMembersFormData {
+            token,
+            user_ids,
+            roles,
+            active,
+        })
+    }
+}
+
+pub fn push_role(roles: &mut Vec<String>, role: String) {
+    if !role.is_empty() && !roles.contains(&role) {
+        roles.push(role);
+    }
+}
+
+/// Converts the submitted role names, at least one is required
+pub fn form_roles(roles: &[String]) -> Result<Vec<String>> {
+    ensure!(
+        !roles.is_empty(),
+        ValidationSnafu {
+            msg: "Select at least one role".to_string(),
+        }
+    );
+
+    let Ok(roles) = to_roles(roles) else {
+        return Err(Error::Validation {
+            msg: "Role is invalid".to_string(),
+        });
+    };
+
+    Ok(roles.into_iter().map(|r| r.to_string()).collect())
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn list_org_members_svc(
     state: &AppState,
     org_id: &str,
     params: ListOrgMembersParamsDto,
 ) -> Result<Paginated<OrgMemberDto>> {
+    let _guard = ListingAllocGuard::new("org_members");
     state.db.org_members.list(org_id.to_string(), params).await
 }
 
-pub async fn list_org_member_suggestions_svc(
+/// Keyset listing for large orgs, see `ListOrgMembersParamsDto::uses_cursor`
+#[instrument(level = "debug", skip_all)]
+pub async fn list_org_members_cursor_svc(
     state: &AppState,
     org_id: &str,
     params: ListOrgMembersParamsDto,
-) -> Result<Paginated<OrgMemberSuggestionDto>> {
+) -> Result<CursorPage<OrgMemberDto>> {
+    let _guard = ListingAllocGuard::new("org_members");
     state
         .db
         .org_members
-        .list_member_suggestions(org_id.to_string(), params)
+        .list_cursor(org_id.to_string(), params)
         .await
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn list_org_memberships_svc(
     state: &AppState,
     user_id: &str,
@@ -63,21 +217,31 @@ pub async fn list_org_memberships_svc(
         .await
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn create_org_member_svc(
     state: &AppState,
     org_id: &str,
-    data: NewOrgMemberDto,
+    mut data: NewOrgMemberDto,
 ) -> Result<OrgMemberDto> {
+    let settings = get_org_settings_svc(state, org_id).await?;
+    if data.roles.is_empty() {
+        data.roles = default_member_roles(&settings);
+    }
+
+    validate_payload(&data)?;
+
     // Ensure that the user exists
     let user_id = data.user_id.clone();
     let existing_user = state.db.users.get(user_id.clone()).await?;
 
-    ensure!(
-        existing_user.is_some(),
-        ValidationSnafu {
+    let Some(existing_user) = existing_user else {
+        return ValidationSnafu {
             msg: "User does not exist".to_string(),
         }
-    );
+        .fail();
+    };
+
+    ensure_member_email_allowed(&settings, &existing_user.email)?;
 
     // Ensure user is not already a member of the org
     let existing_member = state
@@ -103,9 +267,47 @@ pub async fn create_org_member_svc(
         }
     );
 
-    state.db.org_members.create(org_id.to_string(), data).await
+    let member = state
+        .db
+        .org_members
+        .create(&AuditCtx::current(), org_id.to_string(), data)
+        .await?;
+
+    invalidate_auth_cache(state, &member.user_id).await;
+    invalidate_suggestions(state);
+    refresh_org_counters(&state.db, org_id).await;
+    publish_change(
+        state,
+        EntityChangeDto::org_member(ChangeAction::Created, org_id, &member.user_id),
+    );
+
+    if let Err(e) = notify_membership_added_svc(&state.db, &member).await {
+        error!("Failed to notify the new member: {}", e);
+    }
+
+    // Inactive members wait for an admin to activate them
+    if member.status == "inactive"
+        && let Err(e) = notify_pending_member_svc(state, &member).await
+    {
+        error!("Failed to notify org admins: {}", e);
+    }
+
+    if member.status == "active"
+        && let Err(e) = publish_membership_event_svc(
+            state,
+            LifecycleTopic::MembershipGranted,
+            &member,
+            Vec::new(),
+        )
+        .await
+    {
+        error!("Failed to publish lifecycle event: {}", e);
+    }
+
+    Ok(member)
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn create_org_member_web_svc(
     state: &AppState,
     org_id: &str,
@@ -114,19 +316,14 @@ pub async fn create_org_member_web_svc(
     let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
     ensure!(csrf_result == "new_org_member", CsrfTokenSnafu);
 
-    // Convert role to enum
-    let Ok(roles) = to_roles(&[form.role]) else {
-        return Err(Error::Validation {
-            msg: "Role is invalid".to_string(),
-        });
-    };
+    let roles = form_roles(&form.roles)?;
 
     create_org_member_svc(
         state,
         org_id,
         NewOrgMemberDto {
             user_id: form.user_id,
-            roles: roles.into_iter().map(|r| r.to_string()).collect(),
+            roles,
             status: match form.active {
                 Some(_) => "active".to_string(),
                 None => "inactive".to_string(),
@@ -136,6 +333,7 @@ pub async fn create_org_member_web_svc(
     .await
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn get_org_member_svc(
     state: &AppState,
     org_id: &str,
@@ -148,14 +346,60 @@ pub async fn get_org_member_svc(
         .await
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn update_org_member_svc(
     state: &AppState,
     id: &str,
     data: UpdateOrgMemberDto,
 ) -> Result<bool> {
-    state.db.org_members.update(id.to_string(), data).await
+    validate_payload(&data)?;
+
+    let existing = match data.status.is_some() || data.roles.is_some() {
+        true => state.db.org_members.get(id.to_string()).await?,
+        false => None,
+    };
+
+    let updated = state
+        .db
+        .org_members
+        .update(&AuditCtx::current(), id.to_string(), data)
+        .await?;
+
+    let (Some(existing), true) = (existing, updated) else {
+        return Ok(updated);
+    };
+    let Some(member) = state.db.org_members.get(id.to_string()).await? else {
+        return Ok(updated);
+    };
+
+    invalidate_auth_cache(state, &member.user_id).await;
+    publish_change(
+        state,
+        EntityChangeDto::org_member(ChangeAction::Updated, &member.org_id, &member.user_id),
+    );
+
+    if let Err(e) = notify_role_changed_svc(&state.db, &existing, &member).await {
+        error!("Failed to notify the member: {}", e);
+    }
+
+    // Activation grants access downstream, deactivation revokes it
+    if member.status != existing.status {
+        let topic = match member.status.as_str() {
+            "active" => LifecycleTopic::MembershipGranted,
+            _ => LifecycleTopic::MembershipRevoked,
+        };
+
+        let changed = changed_fields(&existing, &member);
+
+        if let Err(e) = publish_membership_event_svc(state, topic, &member, changed).await {
+            error!("Failed to publish lifecycle event: {}", e);
+        }
+    }
+
+    Ok(updated)
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn update_org_member_web_svc(
     state: &AppState,
     org_id: &str,
@@ -165,12 +409,7 @@ pub async fn update_org_member_web_svc(
     let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
     ensure!(csrf_result == user_id, CsrfTokenSnafu);
 
-    // Convert role to enum
-    let Ok(roles) = to_roles(&[form.role]) else {
-        return Err(Error::Validation {
-            msg: "Role is invalid".to_string(),
-        });
-    };
+    let roles = form_roles(&form.roles)?;
 
     // Find member entry
     let Some(member) = get_org_member_svc(state, org_id, user_id).await? else {
@@ -181,7 +420,7 @@ pub async fn update_org_member_web_svc(
         state,
         &member.id,
         UpdateOrgMemberDto {
-            roles: Some(roles.into_iter().map(|r| r.to_string()).collect()),
+            roles: Some(roles),
             status: match form.active {
                 Some(_) => Some("active".to_string()),
                 None => Some("inactive".to_string()),
@@ -198,10 +437,373 @@ pub async fn update_org_member_web_svc(
     Ok(updated_member)
 }
 
+#[instrument(level = "debug", skip_all)]
+pub async fn bulk_update_org_member_status_svc(
+    state: &AppState,
+    org_id: &str,
+    user_ids: &[String],
+    status: &str,
+) -> Result<BulkResultDto> {
+    ensure!(
+        validators::status(status).is_ok(),
+        ValidationSnafu {
+            msg: "Status is invalid".to_string(),
+        }
+    );
+
+    ensure!(
+        !user_ids.is_empty(),
+        ValidationSnafu {
+            msg: "Select at least one member".to_string(),
+        }
+    );
+
+    ensure!(
+        user_ids.len() <= MAX_BULK_ITEMS,
+        ValidationSnafu {
+            msg: format!("Select at most {} members", MAX_BULK_ITEMS),
+        }
+    );
+
+    let Some(org) = state.db.orgs.get(org_id.to_string()).await? else {
+        return Err(Error::OrgNotFound);
+    };
+
+    let mut result = BulkResultDto::default();
+
+    for user_id in user_ids.iter() {
+        let member = match get_org_member_svc(state, org_id, user_id).await {
+            Ok(Some(member)) => member,
+            Ok(None) => {
+                result.push_failure(user_id, user_id, Error::OrgMemberNotFound.to_string());
+                continue;
+            }
+            Err(err) => {
+                result.push_failure(user_id, user_id, err.to_string());
+                continue;
+            }
+        };
+
+        let label = member.member_email.clone().unwrap_or(user_id.clone());
+
+        if org.owner_id.as_deref() == Some(user_id.as_str()) {
+            result.push_failure(
+                user_id,
+                &label,
+                "Cannot change the status of the org owner".to_string(),
+            );
+            continue;
+        }
+
+        let data = UpdateOrgMemberDto {
+            roles: None,
+            status: Some(status.to_string()),
+        };
+
+        match update_org_member_svc(state, &member.id, data).await {
+            Ok(_) => result.push_success(user_id, &label),
+            Err(err) => result.push_failure(user_id, &label, err.to_string()),
+        }
+    }
+
+    Ok(result)
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn bulk_update_org_member_status_web_svc(
+    state: &AppState,
+    org_id: &str,
+    form: BulkStatusFormData,
+) -> Result<BulkResultDto> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+
+    bulk_update_org_member_status_svc(state, org_id, &form.ids, &form.status).await
+}
+
+/// Where a bulk item ended up after validation
+enum BulkAssignment {
+    Failed(String),
+    Insert,
+    Update(OrgMemberDto),
+}
+
+/// Creates or updates several memberships in one transaction.
+///
+/// Items failing validation are reported and skipped, the rest are written
+/// together and fail together when the transaction does.
+#[instrument(level = "debug", skip_all)]
+pub async fn bulk_assign_org_members_svc(
+    state: &AppState,
+    org_id: &str,
+    members: Vec<NewOrgMemberDto>,
+) -> Result<BulkResultDto> {
+    ensure!(
+        !members.is_empty(),
+        ValidationSnafu {
+            msg: "Select at least one user".to_string(),
+        }
+    );
+
+    ensure!(
+        members.len() <= MAX_BULK_ITEMS,
+        ValidationSnafu {
+            msg: format!("Select at most {} users", MAX_BULK_ITEMS),
+        }
+    );
+
+    let Some(org) = state.db.orgs.get(org_id.to_string()).await? else {
+        return Err(Error::OrgNotFound);
+    };
+    let settings = get_org_settings_svc(state, org_id).await?;
+
+    let mut planned: Vec<(NewOrgMemberDto, String, BulkAssignment)> = Vec::new();
+
+    for mut data in members.into_iter() {
+        let label = data.user_id.clone();
+        if data.roles.is_empty() {
+            data.roles = default_member_roles(&settings);
+        }
+
+        if planned
+            .iter()
+            .any(|(item, _, _)| item.user_id == data.user_id)
+        {
+            let reason = BulkAssignment::Failed("User is listed more than once".to_string());
+            planned.push((data, label, reason));
+            continue;
+        }
+
+        if let Err(err) = validate_payload(&data) {
+            planned.push((data, label, BulkAssignment::Failed(err.to_string())));
+            continue;
+        }
+
+        let Some(user) = state.db.users.get(data.user_id.clone()).await? else {
+            let reason = BulkAssignment::Failed("User does not exist".to_string());
+            planned.push((data, label, reason));
+            continue;
+        };
+        let label = user.email;
+
+        if state
+            .db
+            .superusers
+            .get(data.user_id.clone())
+            .await?
+            .is_some()
+        {
+            let reason =
+                BulkAssignment::Failed("Cannot add superuser as organization member".to_string());
+            planned.push((data, label, reason));
+            continue;
+        }
+
+        let existing = state
+            .db
+            .org_members
+            .find_member(org_id.to_string(), data.user_id.clone())
+            .await?;
+
+        let assignment = match existing {
+            None => match ensure_member_email_allowed(&settings, &label) {
+                Ok(_) => BulkAssignment::Insert,
+                Err(err) => BulkAssignment::Failed(err.to_string()),
+            },
+            Some(existing)
+                if org.owner_id.as_deref() == Some(data.user_id.as_str())
+                    && existing.status != data.status =>
+            {
+                BulkAssignment::Failed("Cannot change the status of the org owner".to_string())
+            }
+            Some(existing) => BulkAssignment::Update(existing),
+        };
+        planned.push((data, label, assignment));
+    }
+
+    let mut inserts: Vec<NewOrgMemberDto> = Vec::new();
+    let mut updates: Vec<(String, NewOrgMemberDto)> = Vec::new();
+    for (data, _, assignment) in planned.iter() {
+        match assignment {
+            BulkAssignment::Insert => inserts.push(data.clone()),
+            BulkAssignment::Update(existing) => updates.push((existing.id.clone(), data.clone())),
+            BulkAssignment::Failed(_) => {}
+        }
+    }
+
+    let written = if inserts.is_empty() && updates.is_empty() {
+        Ok(())
+    } else {
+        state
+            .db
+            .org_members
+            .bulk_assign(&AuditCtx::current(), org_id.to_string(), inserts, updates)
+            .await
+    };
+
+    let mut result = BulkResultDto::default();
+    let mut changed = false;
+
+    for (data, label, assignment) in planned.into_iter() {
+        let existing = match (assignment, &written) {
+            (BulkAssignment::Failed(message), _) => {
+                result.push_failure(&data.user_id, &label, message);
+                continue;
+            }
+            (_, Err(err)) => {
+                result.push_failure(&data.user_id, &label, err.to_string());
+                continue;
+            }
+            (BulkAssignment::Insert, Ok(_)) => None,
+            (BulkAssignment::Update(existing), Ok(_)) => Some(existing),
+        };
+
+        result.push_success(&data.user_id, &label);
+        invalidate_auth_cache(state, &data.user_id).await;
+        changed = true;
+
+        let Some(member) = get_org_member_svc(state, org_id, &data.user_id).await? else {
+            continue;
+        };
+        publish_bulk_assignment(state, existing, &member).await;
+    }
+
+    if changed {
+        invalidate_suggestions(state);
+        refresh_org_counters(&state.db, org_id).await;
+    }
+
+    Ok(result)
+}
+
+/// Same notifications and lifecycle events as adding or updating one member
+async fn publish_bulk_assignment(
+    state: &AppState,
+    existing: Option<OrgMemberDto>,
+    member: &OrgMemberDto,
+) {
+    let action = match existing {
+        Some(_) => ChangeAction::Updated,
+        None => ChangeAction::Created,
+    };
+    publish_change(
+        state,
+        EntityChangeDto::org_member(action, &member.org_id, &member.user_id),
+    );
+
+    let Some(existing) = existing else {
+        if let Err(e) = notify_membership_added_svc(&state.db, member).await {
+            error!("Failed to notify the new member: {}", e);
+        }
+
+        if member.status == "inactive"
+            && let Err(e) = notify_pending_member_svc(state, member).await
+        {
+            error!("Failed to notify org admins: {}", e);
+        }
+
+        if member.status == "active"
+            && let Err(e) = publish_membership_event_svc(
+                state,
+                LifecycleTopic::MembershipGranted,
+                member,
+                Vec::new(),
+            )
+            .await
+        {
+            error!("Failed to publish lifecycle event: {}", e);
+        }
+        return;
+    };
+
+    if let Err(e) = notify_role_changed_svc(&state.db, &existing, member).await {
+        error!("Failed to notify the member: {}", e);
+    }
+
+    if member.status == existing.status {
+        return;
+    }
+
+    let topic = match member.status.as_str() {
+        "active" => LifecycleTopic::MembershipGranted,
+        _ => LifecycleTopic::MembershipRevoked,
+    };
+    let changed = changed_fields(&existing, member);
+
+    if let Err(e) = publish_membership_event_svc(state, topic, member, changed).await {
+        error!("Failed to publish lifecycle event: {}", e);
+    }
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn bulk_assign_org_members_web_svc(
+    state: &AppState,
+    org_id: &str,
+    form: BulkOrgMembersFormData,
+) -> Result<BulkResultDto> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+
+    let roles = form_roles(&form.roles)?;
+    let status = match form.active {
+        Some(_) => "active".to_string(),
+        None => "inactive".to_string(),
+    };
+
+    let members = form
+        .user_ids
+        .into_iter()
+        .map(|user_id| NewOrgMemberDto {
+            user_id,
+            roles: roles.clone(),
+            status: status.clone(),
+        })
+        .collect();
+
+    bulk_assign_org_members_svc(state, org_id, members).await
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn delete_org_member_svc(state: &AppState, id: &str) -> Result<()> {
-    state.db.org_members.delete(id.to_string()).await
+    let existing = state.db.org_members.get(id.to_string()).await?;
+
+    state.db.org_members.delete(id.to_string()).await?;
+    invalidate_suggestions(state);
+
+    if let Some(existing) = &existing {
+        invalidate_auth_cache(state, &existing.user_id).await;
+        refresh_org_counters(&state.db, &existing.org_id).await;
+        publish_change(
+            state,
+            EntityChangeDto::org_member(ChangeAction::Deleted, &existing.org_id, &existing.user_id),
+        );
+    }
+
+    // Inactive members never had access, so there is nothing to revoke
+    if let Some(existing) = existing
+        && existing.status == "active"
+    {
+        let member = OrgMemberDto {
+            status: "removed".to_string(),
+            ..existing
+        };
+
+        if let Err(e) = publish_membership_event_svc(
+            state,
+            LifecycleTopic::MembershipRevoked,
+            &member,
+            Vec::new(),
+        )
+        .await
+        {
+            error!("Failed to publish lifecycle event: {}", e);
+        }
+    }
+
+    Ok(())
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn delete_org_member_web_svc(
     state: &AppState,
     org_id: &str,
@@ -223,14 +825,18 @@ pub async fn delete_org_member_web_svc(
 
 #[cfg(test)]
 mod tests {
+    use crate::dto::Role;
     use crate::services::token::create_csrf_token_svc;
     use crate::test::TestCtx;
     use crate::utils::{IdPrefix, generate_id};
 
     use super::{
-        NewOrgMemberFormData, UpdateOrgMemberFormData, create_org_member_web_svc,
-        delete_org_member_web_svc, get_org_member_svc, update_org_member_web_svc,
+        BulkOrgMembersFormData, NewOrgMemberFormData, UpdateOrgMemberFormData,
+        bulk_assign_org_members_web_svc, bulk_update_org_member_status_web_svc,
+        create_org_member_web_svc, delete_org_member_web_svc, get_org_member_svc,
+        update_org_member_web_svc,
     };
+    use crate::models::BulkStatusFormData;
 
     #[tokio::test]
     async fn create_org_member_web_svc_creates_member_and_get_returns_it() {
@@ -261,7 +867,7 @@ mod tests {
                 token: csrf,
                 user_id: member_user.id.clone(),
                 user_email: member_user.email.clone(),
-                role: "OrgEditor".to_string(),
+                roles: vec!["OrgEditor".to_string()],
                 active: Some("1".to_string()),
             },
         )
@@ -278,6 +884,84 @@ mod tests {
         assert_eq!(fetched.status, "active");
     }
 
+    #[test]
+    fn test_new_org_member_form_from_pairs() {
+        let pairs = vec![
+            ("token".to_string(), "abc".to_string()),
+            ("user_id".to_string(), "usr_1".to_string()),
+            ("roles".to_string(), "OrgEditor".to_string()),
+            ("roles".to_string(), "OrgViewer".to_string()),
+            ("roles".to_string(), "OrgEditor".to_string()),
+            ("active".to_string(), "1".to_string()),
+        ];
+
+        let form = NewOrgMemberFormData::try_from(pairs).expect("form should parse");
+        assert_eq!(form.user_id, "usr_1");
+        assert_eq!(
+            form.roles,
+            vec!["OrgEditor".to_string(), "OrgViewer".to_string()]
+        );
+        assert!(form.active.is_some());
+
+        let pairs = vec![("roles".to_string(), "OrgEditor".to_string())];
+        assert!(UpdateOrgMemberFormData::try_from(pairs).is_err());
+    }
+
+    #[tokio::test]
+    async fn create_org_member_web_svc_assigns_multiple_roles() {
+        let ctx = TestCtx::new("org_members_create_multi_role")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Owner User",
+                "org.members.owner.multi@example.com",
+                "password123",
+                "Org Members Org",
+            )
+            .await
+            .expect("auth fixture");
+        let member_user = ctx
+            .seed_user_with_password("Member User", "org.member.multi@example.com", "password123")
+            .await
+            .expect("member user");
+
+        let csrf = create_csrf_token_svc("new_org_member", &ctx.state.config.jwt_secret)
+            .expect("csrf token should be generated");
+
+        let created = create_org_member_web_svc(
+            &ctx.state,
+            &fixture.org.id,
+            NewOrgMemberFormData {
+                token: csrf.clone(),
+                user_id: member_user.id.clone(),
+                user_email: member_user.email.clone(),
+                roles: vec!["OrgEditor".to_string(), "OrgViewer".to_string()],
+                active: Some("1".to_string()),
+            },
+        )
+        .await
+        .expect("member should be created");
+
+        assert_eq!(created.roles, vec![Role::OrgEditor, Role::OrgViewer]);
+
+        let result = create_org_member_web_svc(
+            &ctx.state,
+            &fixture.org.id,
+            NewOrgMemberFormData {
+                token: csrf,
+                user_id: member_user.id,
+                user_email: member_user.email,
+                roles: Vec::new(),
+                active: None,
+            },
+        )
+        .await;
+
+        let err = result.expect_err("roles are required");
+        assert_eq!(err.to_string(), "Select at least one role");
+    }
+
     #[tokio::test]
     async fn create_org_member_web_svc_rejects_invalid_csrf_token() {
         let ctx = TestCtx::new("org_members_create_invalid_csrf")
@@ -304,7 +988,7 @@ mod tests {
                 token: "invalid.token".to_string(),
                 user_id: member_user.id,
                 user_email: member_user.email,
-                role: "OrgEditor".to_string(),
+                roles: vec!["OrgEditor".to_string()],
                 active: Some("1".to_string()),
             },
         )
@@ -342,14 +1026,14 @@ mod tests {
                 token: csrf,
                 user_id: member_user.id,
                 user_email: member_user.email,
-                role: "InvalidRole".to_string(),
+                roles: vec!["InvalidRole".to_string()],
                 active: Some("1".to_string()),
             },
         )
         .await;
 
         assert!(result.is_err(), "invalid role should fail");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "Role is invalid");
     }
 
@@ -385,7 +1069,7 @@ mod tests {
                 token: create_csrf,
                 user_id: member_user.id.clone(),
                 user_email: member_user.email,
-                role: "OrgViewer".to_string(),
+                roles: vec!["OrgViewer".to_string()],
                 active: Some("1".to_string()),
             },
         )
@@ -400,7 +1084,7 @@ mod tests {
             &member_user.id,
             UpdateOrgMemberFormData {
                 token: update_csrf,
-                role: "OrgAdmin".to_string(),
+                roles: vec!["OrgAdmin".to_string()],
                 active: None,
             },
         )
@@ -451,7 +1135,7 @@ mod tests {
                 token: create_csrf,
                 user_id: member_user.id.clone(),
                 user_email: member_user.email,
-                role: "OrgViewer".to_string(),
+                roles: vec!["OrgViewer".to_string()],
                 active: Some("1".to_string()),
             },
         )
@@ -464,7 +1148,7 @@ mod tests {
             &member_user.id,
             UpdateOrgMemberFormData {
                 token: "invalid.token".to_string(),
-                role: "OrgAdmin".to_string(),
+                roles: vec!["OrgAdmin".to_string()],
                 active: None,
             },
         )
@@ -505,7 +1189,7 @@ mod tests {
                 token: create_csrf,
                 user_id: member_user.id.clone(),
                 user_email: member_user.email,
-                role: "OrgViewer".to_string(),
+                roles: vec!["OrgViewer".to_string()],
                 active: Some("1".to_string()),
             },
         )
@@ -521,14 +1205,14 @@ mod tests {
             &member_user.id,
             UpdateOrgMemberFormData {
                 token: update_csrf,
-                role: "InvalidRole".to_string(),
+                roles: vec!["InvalidRole".to_string()],
                 active: None,
             },
         )
         .await;
 
         assert!(result.is_err(), "invalid role should fail");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "Role is invalid");
     }
 
@@ -557,14 +1241,14 @@ mod tests {
             &missing_user_id,
             UpdateOrgMemberFormData {
                 token: csrf,
-                role: "OrgAdmin".to_string(),
+                roles: vec!["OrgAdmin".to_string()],
                 active: None,
             },
         )
         .await;
 
         assert!(result.is_err(), "missing member should fail");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "Org member not found");
     }
 
@@ -600,7 +1284,7 @@ mod tests {
                 token: create_csrf,
                 user_id: member_user.id.clone(),
                 user_email: member_user.email,
-                role: "OrgViewer".to_string(),
+                roles: vec!["OrgViewer".to_string()],
                 active: Some("1".to_string()),
             },
         )
@@ -651,7 +1335,7 @@ mod tests {
                 token: create_csrf,
                 user_id: member_user.id.clone(),
                 user_email: member_user.email,
-                role: "OrgViewer".to_string(),
+                roles: vec!["OrgViewer".to_string()],
                 active: Some("1".to_string()),
             },
         )
@@ -692,7 +1376,160 @@ mod tests {
             delete_org_member_web_svc(&ctx.state, &fixture.org.id, &missing_user_id, &csrf).await;
 
         assert!(result.is_err(), "missing member should fail");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "Org member not found");
     }
+
+    #[tokio::test]
+    async fn bulk_update_org_member_status_web_svc_skips_owner() {
+        let ctx = TestCtx::new("org_members_bulk_status")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Owner User",
+                "org.members.bulk.owner@example.com",
+                "password123",
+                "Bulk Org",
+            )
+            .await
+            .expect("auth fixture");
+        let member_user = ctx
+            .seed_user_with_password("Member User", "org.member.bulk@example.com", "password123")
+            .await
+            .expect("member user");
+
+        let csrf = create_csrf_token_svc("new_org_member", &ctx.state.config.jwt_secret)
+            .expect("csrf token should be generated");
+
+        create_org_member_web_svc(
+            &ctx.state,
+            &fixture.org.id,
+            NewOrgMemberFormData {
+                token: csrf,
+                user_id: member_user.id.clone(),
+                user_email: member_user.email.clone(),
+                roles: vec!["OrgViewer".to_string()],
+                active: Some("1".to_string()),
+            },
+        )
+        .await
+        .expect("member should be created");
+
+        let csrf = create_csrf_token_svc(&fixture.org.id, &ctx.state.config.jwt_secret)
+            .expect("csrf token should be generated");
+
+        let result = bulk_update_org_member_status_web_svc(
+            &ctx.state,
+            &fixture.org.id,
+            BulkStatusFormData {
+                token: csrf,
+                status: "inactive".to_string(),
+                ids: vec![member_user.id.clone(), fixture.user.id.clone()],
+            },
+        )
+        .await
+        .expect("bulk update should pass");
+
+        assert_eq!(result.succeeded(), 1);
+        assert_eq!(result.failed(), 1);
+
+        let member = get_org_member_svc(&ctx.state, &fixture.org.id, &member_user.id)
+            .await
+            .expect("query should pass")
+            .expect("member should exist");
+        assert_eq!(member.status, "inactive");
+
+        let owner = get_org_member_svc(&ctx.state, &fixture.org.id, &fixture.user.id)
+            .await
+            .expect("query should pass")
+            .expect("owner should exist");
+        assert_eq!(owner.status, "active");
+    }
+
+    #[tokio::test]
+    async fn bulk_assign_org_members_web_svc_adds_and_updates_members() {
+        let ctx = TestCtx::new("org_members_bulk_assign")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Owner User",
+                "org.members.owner.bulk@example.com",
+                "password123",
+                "Org Members Org",
+            )
+            .await
+            .expect("auth fixture");
+        let existing_user = ctx
+            .seed_user_with_password("Existing", "org.member.bulk1@example.com", "password123")
+            .await
+            .expect("existing user");
+        let new_user = ctx
+            .seed_user_with_password("New", "org.member.bulk2@example.com", "password123")
+            .await
+            .expect("new user");
+
+        let create_csrf = create_csrf_token_svc("new_org_member", &ctx.state.config.jwt_secret)
+            .expect("csrf token should be generated");
+        create_org_member_web_svc(
+            &ctx.state,
+            &fixture.org.id,
+            NewOrgMemberFormData {
+                token: create_csrf,
+                user_id: existing_user.id.clone(),
+                user_email: existing_user.email.clone(),
+                roles: vec!["OrgViewer".to_string()],
+                active: Some("1".to_string()),
+            },
+        )
+        .await
+        .expect("member should be created");
+
+        let csrf = create_csrf_token_svc(&fixture.org.id, &ctx.state.config.jwt_secret)
+            .expect("csrf token should be generated");
+        let pairs = vec![
+            ("token".to_string(), csrf),
+            ("user_ids".to_string(), existing_user.id.clone()),
+            ("user_ids".to_string(), new_user.id.clone()),
+            ("user_ids".to_string(), generate_id(IdPrefix::User)),
+            ("user_ids".to_string(), fixture.user.id.clone()),
+            ("roles".to_string(), "OrgEditor".to_string()),
+        ];
+        let form = BulkOrgMembersFormData::try_from(pairs).expect("form should parse");
+
+        let result = bulk_assign_org_members_web_svc(&ctx.state, &fixture.org.id, form)
+            .await
+            .expect("bulk assign should pass");
+
+        assert_eq!(result.succeeded(), 2);
+        assert_eq!(result.failed(), 2);
+        assert_eq!(
+            result.items[2].message.as_deref(),
+            Some("User does not exist")
+        );
+        assert_eq!(
+            result.items[3].message.as_deref(),
+            Some("Cannot change the status of the org owner")
+        );
+
+        for user_id in [&existing_user.id, &new_user.id] {
+            let member = get_org_member_svc(&ctx.state, &fixture.org.id, user_id)
+                .await
+                .expect("query should pass")
+                .expect("member should exist");
+            assert_eq!(member.status, "inactive");
+            assert_eq!(member.roles, vec![Role::OrgEditor]);
+        }
+
+        let org = ctx
+            .state
+            .db
+            .orgs
+            .get(fixture.org.id.clone())
+            .await
+            .expect("query should pass")
+            .expect("org should exist");
+        assert_eq!(org.member_count, 3);
+    }
 }
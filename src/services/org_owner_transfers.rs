@@ -0,0 +1,588 @@
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ensure};
+use tracing::{info, instrument};
+
+use crate::ctx::AuditCtx;
+use crate::dto::{
+    ChangeAction, EntityChangeDto, NewOrgOwnerTransferDto, OrgDto, OrgOwnerTransferDto, Role,
+    UpdateOrgMemberDto,
+};
+use crate::error::{CsrfTokenSnafu, ForbiddenSnafu, OrgNotFoundSnafu, ValidationSnafu};
+use crate::run::AppState;
+use crate::services::admin_events::publish_change;
+use crate::services::auth::invalidate_auth_cache;
+use crate::services::counters::refresh_org_counters;
+use crate::services::emails::{org_branding_svc, org_owner_transfer_email, render_email};
+use crate::services::mailer::queue_email_svc;
+use crate::services::org_members::push_role;
+use crate::services::recovery::generate_recovery_token;
+use crate::services::suggestions::invalidate_suggestions;
+use crate::services::token::verify_csrf_token;
+use crate::utils::hash_secret;
+use crate::validators::validate_payload;
+use crate::{Error, Result};
+
+pub const TRANSFER_TTL_HOURS: i64 = 72;
+
+/// Acceptance form, the token is the one from the emailed link
+#[derive(Clone, Deserialize, Serialize)]
+pub struct AcceptOwnerTransferFormData {
+    pub token: String,
+}
+
+/// Freshly issued transfer, the plain token is never stored
+pub struct IssuedOwnerTransfer {
+    pub token: String,
+    pub transfer: OrgOwnerTransferDto,
+}
+
+/// What the acceptance page shows before the new owner confirms
+pub struct OwnerTransferPreview {
+    pub transfer: OrgOwnerTransferDto,
+    pub org_name: String,
+}
+
+const INVALID_TRANSFER_MSG: &str = "Ownership transfer link is invalid or has expired.";
+
+fn accept_url(token: &str) -> String {
+    format!("/owner-transfers/accept?token={}", token)
+}
+
+fn transfer_expiry(now: i64) -> i64 {
+    now + TRANSFER_TTL_HOURS * 60 * 60 * 1000
+}
+
+/// Emails the acceptance link to the new owner with the org branding
+async fn send_issued(state: &AppState, issued: &IssuedOwnerTransfer, org_name: &str) -> Result<()> {
+    let transfer = &issued.transfer;
+
+    if let Some(email) = transfer.to_email.as_deref() {
+        let branding = org_branding_svc(&state.db, &transfer.org_id).await?;
+        let url = format!(
+            "{}{}",
+            state.config.mailer.base_url,
+            accept_url(&issued.token)
+        );
+        let message = render_email(
+            &branding,
+            Some(transfer.org_id.clone()),
+            email,
+            org_owner_transfer_email(org_name, url, TRANSFER_TTL_HOURS),
+        )?;
+        queue_email_svc(&state.db, message).await?;
+    }
+
+    info!(
+        transfer_id = transfer.id,
+        org_id = transfer.org_id,
+        to_user_id = transfer.to_user_id,
+        expires_at = transfer.expires_at,
+        "org_owner_transfer.issued"
+    );
+
+    Ok(())
+}
+
+/// Only the current owner of the org or a superuser can hand it over
+async fn ensure_can_transfer(state: &AppState, actor_id: &str, org: &OrgDto) -> Result<()> {
+    if org.owner_id.as_deref() == Some(actor_id) {
+        return Ok(());
+    }
+
+    let superuser = state.db.superusers.get(actor_id.to_string()).await?;
+    ensure!(
+        superuser.is_some(),
+        ForbiddenSnafu {
+            msg: "Only the org owner can transfer ownership".to_string(),
+        }
+    );
+
+    Ok(())
+}
+
+/// Asks an active member to take over the org.
+///
+/// Ownership stays with the current owner until the member accepts through
+/// the emailed link. Only one transfer can be pending per org, cancel it to
+/// pick someone else.
+#[instrument(level = "debug", skip_all)]
+pub async fn initiate_owner_transfer_svc(
+    state: &AppState,
+    actor_id: &str,
+    org_id: &str,
+    data: NewOrgOwnerTransferDto,
+) -> Result<IssuedOwnerTransfer> {
+    validate_payload(&data)?;
+    let to_user_id = data.user_id.as_str();
+
+    let org = state
+        .db
+        .orgs
+        .get(org_id.to_string())
+        .await?
+        .context(OrgNotFoundSnafu)?;
+    ensure_can_transfer(state, actor_id, &org).await?;
+
+    let from_user_id = org.owner_id.clone().context(ValidationSnafu {
+        msg: "Org has no owner to transfer from".to_string(),
+    })?;
+    ensure!(
+        from_user_id != to_user_id,
+        ValidationSnafu {
+            msg: "User is already the owner".to_string(),
+        }
+    );
+
+    let member = state
+        .db
+        .org_members
+        .find_member(org_id.to_string(), to_user_id.to_string())
+        .await?;
+    let Some(member) = member.filter(|m| m.status == "active") else {
+        return Err(Error::Validation {
+            msg: "New owner must be an active member of the org".to_string(),
+        });
+    };
+
+    let superuser = state.db.superusers.get(to_user_id.to_string()).await?;
+    ensure!(
+        superuser.is_none(),
+        ValidationSnafu {
+            msg: "Owner cannot be a superuser".to_string(),
+        }
+    );
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let pending = state
+        .db
+        .org_owner_transfers
+        .find_pending(org_id.to_string(), now)
+        .await?;
+    ensure!(
+        pending.is_none(),
+        ValidationSnafu {
+            msg: "Org already has a pending ownership transfer, cancel it first".to_string(),
+        }
+    );
+
+    let token = generate_recovery_token()?;
+    let mut transfer = state
+        .db
+        .org_owner_transfers
+        .create(
+            &AuditCtx::current(),
+            org_id.to_string(),
+            from_user_id,
+            to_user_id.to_string(),
+            hash_secret(&token),
+            transfer_expiry(now),
+        )
+        .await?;
+    transfer.to_email = member.member_email;
+
+    let issued = IssuedOwnerTransfer { token, transfer };
+    send_issued(state, &issued, &org.name).await?;
+
+    Ok(issued)
+}
+
+/// Withdraws the pending transfer, its link stops working
+#[instrument(level = "debug", skip_all)]
+pub async fn cancel_owner_transfer_svc(
+    state: &AppState,
+    actor_id: &str,
+    org_id: &str,
+) -> Result<()> {
+    let org = state
+        .db
+        .orgs
+        .get(org_id.to_string())
+        .await?
+        .context(OrgNotFoundSnafu)?;
+    ensure_can_transfer(state, actor_id, &org).await?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let cancelled = state
+        .db
+        .org_owner_transfers
+        .cancel_for_org(org_id.to_string(), now)
+        .await?;
+    ensure!(
+        cancelled > 0,
+        ValidationSnafu {
+            msg: "Org has no pending ownership transfer".to_string(),
+        }
+    );
+
+    info!(org_id = org_id, "org_owner_transfer.cancelled");
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn initiate_owner_transfer_web_svc(
+    state: &AppState,
+    actor_id: &str,
+    org_id: &str,
+    csrf_token: &str,
+    to_user_id: &str,
+) -> Result<IssuedOwnerTransfer> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+
+    let data = NewOrgOwnerTransferDto {
+        user_id: to_user_id.to_string(),
+    };
+    initiate_owner_transfer_svc(state, actor_id, org_id, data).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn cancel_owner_transfer_web_svc(
+    state: &AppState,
+    actor_id: &str,
+    org_id: &str,
+    csrf_token: &str,
+) -> Result<()> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+
+    cancel_owner_transfer_svc(state, actor_id, org_id).await
+}
+
+/// Transfer behind the link along with its org, as long as nothing changed
+/// since it was issued
+async fn find_usable_transfer(
+    state: &AppState,
+    token: &str,
+) -> Result<(OrgOwnerTransferDto, OrgDto)> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let transfer = state
+        .db
+        .org_owner_transfers
+        .find_by_hash(hash_secret(token))
+        .await?
+        .context(ValidationSnafu {
+            msg: INVALID_TRANSFER_MSG,
+        })?;
+    ensure!(
+        transfer.is_usable(now),
+        ValidationSnafu {
+            msg: INVALID_TRANSFER_MSG
+        }
+    );
+
+    let org = state
+        .db
+        .orgs
+        .get(transfer.org_id.clone())
+        .await?
+        .context(ValidationSnafu {
+            msg: INVALID_TRANSFER_MSG,
+        })?;
+    ensure!(
+        org.owner_id.as_deref() == Some(transfer.from_user_id.as_str()),
+        ValidationSnafu {
+            msg: INVALID_TRANSFER_MSG
+        }
+    );
+
+    Ok((transfer, org))
+}
+
+/// Looks up the transfer behind an acceptance link
+#[instrument(level = "debug", skip_all)]
+pub async fn preview_owner_transfer_svc(
+    state: &AppState,
+    token: &str,
+) -> Result<OwnerTransferPreview> {
+    let (transfer, org) = find_usable_transfer(state, token).await?;
+
+    Ok(OwnerTransferPreview {
+        transfer,
+        org_name: org.name,
+    })
+}
+
+/// Makes the member behind the link the owner of the org.
+///
+/// The member must still be active, they are promoted to org admin along with
+/// the ownership change. The link can only be used once.
+#[instrument(level = "debug", skip_all)]
+pub async fn accept_owner_transfer_svc(
+    state: &AppState,
+    form: AcceptOwnerTransferFormData,
+) -> Result<OrgDto> {
+    let (transfer, org) = find_usable_transfer(state, &form.token).await?;
+
+    let member = state
+        .db
+        .org_members
+        .find_member(org.id.clone(), transfer.to_user_id.clone())
+        .await?;
+    let Some(member) = member.filter(|m| m.status == "active") else {
+        return Err(Error::Validation {
+            msg: INVALID_TRANSFER_MSG.to_string(),
+        });
+    };
+
+    let mut roles: Vec<String> = member.roles.iter().map(|r| r.to_string()).collect();
+    push_role(&mut roles, Role::OrgAdmin.to_string());
+
+    let audit = AuditCtx::current();
+    let now = chrono::Utc::now().timestamp_millis();
+
+    // The transfer, the promotion and the owner change land together
+    let uow = state.db.begin().await?;
+    let work = async {
+        let accepted = uow
+            .org_owner_transfers
+            .accept(transfer.id.clone(), now)
+            .await?;
+        ensure!(
+            accepted,
+            ValidationSnafu {
+                msg: INVALID_TRANSFER_MSG
+            }
+        );
+
+        uow.org_members
+            .update(
+                &audit,
+                member.id.clone(),
+                UpdateOrgMemberDto {
+                    roles: Some(roles),
+                    status: None,
+                },
+            )
+            .await?;
+        uow.orgs
+            .set_owner(&audit, org.id.clone(), transfer.to_user_id.clone())
+            .await?;
+        Ok(())
+    };
+    let result = work.await;
+    uow.finish(result).await?;
+
+    invalidate_auth_cache(state, &transfer.to_user_id).await;
+    invalidate_suggestions(state);
+    refresh_org_counters(&state.db, &org.id).await;
+    publish_change(state, EntityChangeDto::org(ChangeAction::Updated, &org.id));
+
+    info!(
+        transfer_id = transfer.id,
+        org_id = org.id,
+        from_user_id = transfer.from_user_id,
+        to_user_id = transfer.to_user_id,
+        "org_owner_transfer.accepted"
+    );
+
+    state.db.orgs.get(org.id).await?.context(OrgNotFoundSnafu)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ctx::AuditCtx;
+    use crate::dto::{NewOrgMemberDto, NewOrgOwnerTransferDto, Role, UpdateOrgDto, UserDto};
+    use crate::services::orgs::{get_org_svc, update_org_svc};
+    use crate::test::TestCtx;
+
+    use super::{
+        AcceptOwnerTransferFormData, accept_owner_transfer_svc, cancel_owner_transfer_svc,
+        initiate_owner_transfer_svc, preview_owner_transfer_svc,
+    };
+
+    async fn seed_member(ctx: &TestCtx, org_id: &str, email: &str) -> UserDto {
+        let user = ctx
+            .seed_user_with_password("Member User", email, "password123")
+            .await
+            .expect("member user");
+        ctx.state
+            .db
+            .org_members
+            .create(
+                &AuditCtx::default(),
+                org_id.to_string(),
+                NewOrgMemberDto {
+                    user_id: user.id.clone(),
+                    roles: vec!["OrgViewer".to_string()],
+                    status: "active".to_string(),
+                },
+            )
+            .await
+            .expect("membership");
+        user
+    }
+
+    fn transfer_to(user: &UserDto) -> NewOrgOwnerTransferDto {
+        NewOrgOwnerTransferDto {
+            user_id: user.id.clone(),
+        }
+    }
+
+    fn accept_form(token: &str) -> AcceptOwnerTransferFormData {
+        AcceptOwnerTransferFormData {
+            token: token.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn accepting_hands_over_the_org_once() {
+        let ctx = TestCtx::new("owner_transfers_accept")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Transfer Owner",
+                "transfer.owner@example.com",
+                "password123",
+                "Transfer Org",
+            )
+            .await
+            .expect("auth fixture");
+        let member = seed_member(&ctx, &fixture.org.id, "transfer.member@example.com").await;
+
+        let not_owner = initiate_owner_transfer_svc(
+            &ctx.state,
+            &member.id,
+            &fixture.org.id,
+            transfer_to(&member),
+        )
+        .await;
+        assert!(not_owner.is_err(), "only the owner can start a transfer");
+
+        let issued = initiate_owner_transfer_svc(
+            &ctx.state,
+            &fixture.user.id,
+            &fixture.org.id,
+            transfer_to(&member),
+        )
+        .await
+        .expect("transfer should be issued");
+        assert_ne!(issued.token, issued.transfer.token_hash);
+
+        let org = get_org_svc(&ctx.state, &fixture.org.id)
+            .await
+            .expect("get org")
+            .expect("org");
+        assert_eq!(org.owner_id, Some(fixture.user.id.clone()));
+        let pending = org.pending_transfer.expect("pending transfer is visible");
+        assert_eq!(pending.to_user_id, member.id);
+        assert_eq!(
+            pending.to_email.as_deref(),
+            Some("transfer.member@example.com")
+        );
+
+        let duplicate = initiate_owner_transfer_svc(
+            &ctx.state,
+            &fixture.user.id,
+            &fixture.org.id,
+            transfer_to(&member),
+        )
+        .await;
+        assert!(duplicate.is_err(), "one pending transfer per org");
+
+        let preview = preview_owner_transfer_svc(&ctx.state, &issued.token)
+            .await
+            .expect("preview");
+        assert_eq!(preview.org_name, "Transfer Org");
+
+        let org = accept_owner_transfer_svc(&ctx.state, accept_form(&issued.token))
+            .await
+            .expect("transfer should be accepted");
+        assert_eq!(org.owner_id, Some(member.id.clone()));
+        assert!(org.pending_transfer.is_none());
+
+        let membership = ctx
+            .state
+            .db
+            .org_members
+            .find_member(fixture.org.id.clone(), member.id.clone())
+            .await
+            .expect("query")
+            .expect("membership");
+        assert_eq!(membership.roles, vec![Role::OrgViewer, Role::OrgAdmin]);
+
+        let again = accept_owner_transfer_svc(&ctx.state, accept_form(&issued.token)).await;
+        assert!(again.is_err(), "the link works once");
+    }
+
+    #[tokio::test]
+    async fn cancelled_or_outdated_links_stop_working() {
+        let ctx = TestCtx::new("owner_transfers_cancel")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Cancel Owner",
+                "cancel.owner@example.com",
+                "password123",
+                "Cancel Org",
+            )
+            .await
+            .expect("auth fixture");
+        let member = seed_member(&ctx, &fixture.org.id, "cancel.member@example.com").await;
+
+        let issued = initiate_owner_transfer_svc(
+            &ctx.state,
+            &fixture.user.id,
+            &fixture.org.id,
+            transfer_to(&member),
+        )
+        .await
+        .expect("transfer should be issued");
+
+        cancel_owner_transfer_svc(&ctx.state, &fixture.user.id, &fixture.org.id)
+            .await
+            .expect("transfer should be cancelled");
+        assert!(
+            preview_owner_transfer_svc(&ctx.state, &issued.token)
+                .await
+                .is_err()
+        );
+        assert!(
+            accept_owner_transfer_svc(&ctx.state, accept_form(&issued.token))
+                .await
+                .is_err()
+        );
+        assert!(
+            cancel_owner_transfer_svc(&ctx.state, &fixture.user.id, &fixture.org.id)
+                .await
+                .is_err(),
+            "nothing left to cancel"
+        );
+
+        // Changing the owner directly drops the request
+        let reissued = initiate_owner_transfer_svc(
+            &ctx.state,
+            &fixture.user.id,
+            &fixture.org.id,
+            transfer_to(&member),
+        )
+        .await
+        .expect("transfer should be issued again");
+        let other = seed_member(&ctx, &fixture.org.id, "cancel.other@example.com").await;
+        update_org_svc(
+            &ctx.state,
+            &fixture.org.id,
+            UpdateOrgDto {
+                name: None,
+                owner_id: Some(other.id.clone()),
+                status: None,
+            },
+        )
+        .await
+        .expect("owner should be updated");
+
+        assert!(
+            accept_owner_transfer_svc(&ctx.state, accept_form(&reissued.token))
+                .await
+                .is_err()
+        );
+        let org = get_org_svc(&ctx.state, &fixture.org.id)
+            .await
+            .expect("get org")
+            .expect("org");
+        assert_eq!(org.owner_id, Some(other.id));
+        assert!(org.pending_transfer.is_none());
+    }
+}
@@ -0,0 +1,359 @@
+use chrono::Utc;
+use moka::sync::Cache;
+use snafu::OptionExt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, instrument};
+
+use crate::Result;
+use crate::config::OrgRateLimitConfig;
+use crate::dto::{OrgRateLimitDto, OrgRateUsageDto, UpdateOrgRateLimitDto};
+use crate::error::OrgNotFoundSnafu;
+use crate::run::AppState;
+use crate::validators::validate_payload;
+
+/// Budget in effect for an org
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OrgBudget {
+    pub requests_per_min: i64,
+    pub burst: i64,
+    /// Set by the org instead of the deployment defaults
+    pub custom: bool,
+}
+
+impl OrgBudget {
+    fn from_config(config: &OrgRateLimitConfig) -> Self {
+        Self {
+            requests_per_min: config.requests_per_min,
+            burst: config.burst,
+            custom: false,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.requests_per_min > 0
+    }
+}
+
+/// Outcome of taking a request from an org's budget
+#[derive(Clone, Copy, Debug)]
+pub struct OrgRateDecision {
+    pub allowed: bool,
+    pub budget: OrgBudget,
+    pub remaining: i64,
+    /// Seconds until the next request fits, 0 when allowed
+    pub retry_after_secs: u64,
+}
+
+/// Token bucket of one org, holds up to `burst` requests and refills at
+/// the per minute rate
+#[derive(Clone, Copy, Debug)]
+struct OrgBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl OrgBucket {
+    fn new(budget: &OrgBudget, now: Instant) -> Self {
+        Self {
+            tokens: budget.burst as f64,
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, budget: &OrgBudget, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        let refilled = self.tokens + elapsed * budget.requests_per_min as f64 / 60.0;
+        self.tokens = refilled.min(budget.burst as f64);
+        self.updated_at = now;
+    }
+
+    fn take(&mut self, budget: &OrgBudget, now: Instant) -> OrgRateDecision {
+        self.refill(budget, now);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return OrgRateDecision {
+                allowed: true,
+                budget: *budget,
+                remaining: self.tokens.floor() as i64,
+                retry_after_secs: 0,
+            };
+        }
+
+        let wait = (1.0 - self.tokens) * 60.0 / budget.requests_per_min as f64;
+        OrgRateDecision {
+            allowed: false,
+            budget: *budget,
+            remaining: 0,
+            retry_after_secs: (wait.ceil() as u64).max(1),
+        }
+    }
+}
+
+/// Per org budgets and buckets, kept in memory on each instance
+#[derive(Clone)]
+pub struct OrgRateLimiter {
+    budgets: Cache<String, OrgBudget>,
+    buckets: Cache<String, Arc<Mutex<OrgBucket>>>,
+}
+
+impl Default for OrgRateLimiter {
+    fn default() -> Self {
+        Self {
+            // Budget changes invalidate the org directly, the TTL picks up
+            // changes made by other instances
+            budgets: Cache::builder()
+                .time_to_live(Duration::from_secs(60))
+                .max_capacity(10_000)
+                .build(),
+            // An idle bucket is full again long before it is evicted
+            buckets: Cache::builder()
+                .time_to_idle(Duration::from_secs(30 * 60))
+                .max_capacity(10_000)
+                .build(),
+        }
+    }
+}
+
+impl OrgRateLimiter {
+    fn invalidate(&self, org_id: &str) {
+        self.budgets.invalidate(org_id);
+        self.buckets.invalidate(org_id);
+    }
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn org_budget_svc(state: &AppState, org_id: &str) -> Result<OrgBudget> {
+    if let Some(budget) = state.org_rate_limiter.budgets.get(org_id) {
+        return Ok(budget);
+    }
+
+    let budget = match state.db.org_rate_limits.get(org_id.to_string()).await? {
+        Some(limit) => OrgBudget {
+            requests_per_min: limit.requests_per_min,
+            burst: limit.burst,
+            custom: true,
+        },
+        None => OrgBudget::from_config(&state.config.org_rate_limit),
+    };
+
+    state
+        .org_rate_limiter
+        .budgets
+        .insert(org_id.to_string(), budget);
+    Ok(budget)
+}
+
+/// Takes one request from the org's budget, returns None when it has no budget
+#[instrument(level = "debug", skip_all)]
+pub async fn take_org_request_svc(
+    state: &AppState,
+    org_id: &str,
+) -> Result<Option<OrgRateDecision>> {
+    let budget = org_budget_svc(state, org_id).await?;
+    if !budget.enabled() {
+        return Ok(None);
+    }
+
+    let now = Instant::now();
+    let bucket = state
+        .org_rate_limiter
+        .buckets
+        .get_with(org_id.to_string(), || {
+            Arc::new(Mutex::new(OrgBucket::new(&budget, now)))
+        });
+
+    let decision = bucket
+        .lock()
+        .expect("Org rate bucket lock must not be poisoned")
+        .take(&budget, now);
+    Ok(Some(decision))
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn org_rate_usage_svc(state: &AppState, org_id: &str) -> Result<OrgRateUsageDto> {
+    let budget = org_budget_svc(state, org_id).await?;
+
+    let remaining = match state.org_rate_limiter.buckets.get(org_id) {
+        Some(bucket) => {
+            let mut bucket = bucket
+                .lock()
+                .expect("Org rate bucket lock must not be poisoned");
+            bucket.refill(&budget, Instant::now());
+            bucket.tokens.floor() as i64
+        }
+        None => budget.burst,
+    };
+
+    Ok(OrgRateUsageDto {
+        org_id: org_id.to_string(),
+        enabled: budget.enabled(),
+        requests_per_min: budget.requests_per_min,
+        burst: budget.burst,
+        source: match budget.custom {
+            true => "org".to_string(),
+            false => "default".to_string(),
+        },
+        remaining: match budget.enabled() {
+            true => remaining,
+            false => 0,
+        },
+    })
+}
+
+/// Overrides the deployment defaults for the org, its bucket starts full
+#[instrument(level = "debug", skip_all)]
+pub async fn update_org_rate_limit_svc(
+    state: &AppState,
+    actor_id: Option<&str>,
+    org_id: &str,
+    data: UpdateOrgRateLimitDto,
+) -> Result<OrgRateUsageDto> {
+    validate_payload(&data)?;
+
+    let org = state
+        .db
+        .orgs
+        .get(org_id.to_string())
+        .await?
+        .context(OrgNotFoundSnafu)?;
+
+    state
+        .db
+        .org_rate_limits
+        .upsert(OrgRateLimitDto {
+            org_id: org.id.clone(),
+            requests_per_min: data.requests_per_min,
+            burst: data.burst,
+            updated_at: Utc::now().timestamp_millis(),
+            updated_by: actor_id.map(|id| id.to_string()),
+        })
+        .await?;
+
+    state.org_rate_limiter.invalidate(&org.id);
+
+    info!(
+        org_id = org.id,
+        requests_per_min = data.requests_per_min,
+        burst = data.burst,
+        updated_by = actor_id,
+        "org_rate_limit.updated"
+    );
+
+    org_rate_usage_svc(state, &org.id).await
+}
+
+/// Puts the org back on the deployment defaults
+#[instrument(level = "debug", skip_all)]
+pub async fn reset_org_rate_limit_svc(state: &AppState, org_id: &str) -> Result<OrgRateUsageDto> {
+    let org = state
+        .db
+        .orgs
+        .get(org_id.to_string())
+        .await?
+        .context(OrgNotFoundSnafu)?;
+
+    state.db.org_rate_limits.delete(org.id.clone()).await?;
+    state.org_rate_limiter.invalidate(&org.id);
+
+    info!(org_id = org.id, "org_rate_limit.reset");
+
+    org_rate_usage_svc(state, &org.id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::dto::UpdateOrgRateLimitDto;
+    use crate::test::TestCtx;
+
+    use super::{
+        OrgBucket, OrgBudget, org_rate_usage_svc, reset_org_rate_limit_svc, take_org_request_svc,
+        update_org_rate_limit_svc,
+    };
+
+    #[test]
+    fn bucket_allows_burst_then_refills_at_the_rate() {
+        let budget = OrgBudget {
+            requests_per_min: 60,
+            burst: 3,
+            custom: true,
+        };
+        let start = Instant::now();
+        let mut bucket = OrgBucket::new(&budget, start);
+
+        for remaining in [2, 1, 0] {
+            let decision = bucket.take(&budget, start);
+            assert!(decision.allowed);
+            assert_eq!(decision.remaining, remaining);
+        }
+
+        let denied = bucket.take(&budget, start);
+        assert!(!denied.allowed);
+        assert_eq!(denied.retry_after_secs, 1);
+
+        // One request per second comes back
+        let later = start + Duration::from_secs(1);
+        assert!(bucket.take(&budget, later).allowed);
+        assert!(!bucket.take(&budget, later).allowed);
+
+        // Never more than the burst
+        let idle = start + Duration::from_secs(600);
+        bucket.refill(&budget, idle);
+        assert_eq!(bucket.tokens, 3.0);
+    }
+
+    #[tokio::test]
+    async fn org_budget_overrides_the_defaults() {
+        let ctx = TestCtx::new("org_rate_limits").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture("Member", "member@example.com", "password", "Acme")
+            .await
+            .expect("fixture");
+        let org_id = fixture.org.id.clone();
+
+        // The test config has no default budget
+        let decision = take_org_request_svc(&ctx.state, &org_id)
+            .await
+            .expect("take");
+        assert!(decision.is_none());
+
+        let usage = update_org_rate_limit_svc(
+            &ctx.state,
+            None,
+            &org_id,
+            UpdateOrgRateLimitDto {
+                requests_per_min: 1,
+                burst: 2,
+            },
+        )
+        .await
+        .expect("update");
+        assert_eq!((usage.source.as_str(), usage.remaining), ("org", 2));
+
+        for _ in 0..2 {
+            let decision = take_org_request_svc(&ctx.state, &org_id)
+                .await
+                .expect("take")
+                .expect("budget");
+            assert!(decision.allowed);
+        }
+        let denied = take_org_request_svc(&ctx.state, &org_id)
+            .await
+            .expect("take")
+            .expect("budget");
+        assert!(!denied.allowed);
+
+        let usage = org_rate_usage_svc(&ctx.state, &org_id)
+            .await
+            .expect("usage");
+        assert_eq!(usage.remaining, 0);
+
+        let usage = reset_org_rate_limit_svc(&ctx.state, &org_id)
+            .await
+            .expect("reset");
+        assert_eq!((usage.source.as_str(), usage.enabled), ("default", false));
+    }
+}
@@ -0,0 +1,436 @@
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ensure};
+use tracing::instrument;
+
+use crate::ctx::AuditCtx;
+use crate::dto::{
+    ALL_ROLES, NewOrgRoleDto, OrgMemberDto, OrgRoleDto, Permission, UpdateOrgRoleDto,
+};
+use crate::error::{CsrfTokenSnafu, NotFoundSnafu, OrgNotFoundSnafu, ValidationSnafu};
+use crate::run::AppState;
+use crate::services::auth::{invalidate_all_auth_cache, invalidate_auth_cache};
+use crate::services::token::verify_csrf_token;
+use crate::validators::validate_payload;
+use crate::{Error, Result};
+
+/// Custom role form, permissions are submitted as repeated `permissions` fields
+#[derive(Clone, Deserialize, Serialize)]
+pub struct OrgRoleFormData {
+    pub token: String,
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<String>,
+}
+
+impl TryFrom<Vec<(String, String)>> for OrgRoleFormData {
+    type Error = Error;
+
+    fn try_from(pairs: Vec<(String, String)>) -> Result<Self> {
+        let mut token: Option<String> = None;
+        let mut name = String::new();
+        let mut description = String::new();
+        let mut permissions: Vec<String> = Vec::new();
+
+        for (key, value) in pairs.into_iter() {
+            match key.as_str() {
+                "token" => token = Some(value),
+                "name" => name = value.trim().to_string(),
+                "description" => description = value.trim().to_string(),
+                "permissions" if !value.is_empty() && !permissions.contains(&value) => {
+                    permissions.push(value)
+                }
+                _ => {}
+            }
+        }
+
+        let Some(token) = token else {
+            return Err(Error::CsrfToken);
+        };
+
+        Ok(OrgRoleFormData {
+            token,
+            name,
+            description,
+            permissions,
+        })
+    }
+}
+
+/// Member custom roles form, roles are submitted as repeated `role_ids` fields
+#[derive(Clone, Deserialize, Serialize)]
+pub struct OrgMemberRolesFormData {
+    pub token: String,
+    pub role_ids: Vec<String>,
+}
+
+impl TryFrom<Vec<(String, String)>> for OrgMemberRolesFormData {
+    type Error = Error;
+
+    fn try_from(pairs: Vec<(String, String)>) -> Result<Self> {
+        let mut token: Option<String> = None;
+        let mut role_ids: Vec<String> = Vec::new();
+
+        for (key, value) in pairs.into_iter() {
+            match key.as_str() {
+                "token" => token = Some(value),
+                "role_ids" if !value.is_empty() && !role_ids.contains(&value) => {
+                    role_ids.push(value)
+                }
+                _ => {}
+            }
+        }
+
+        let Some(token) = token else {
+            return Err(Error::CsrfToken);
+        };
+
+        Ok(OrgMemberRolesFormData { token, role_ids })
+    }
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn list_org_roles_svc(state: &AppState, org_id: &str) -> Result<Vec<OrgRoleDto>> {
+    state.db.org_roles.list(org_id.to_string()).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn get_org_role_svc(state: &AppState, org_id: &str, role_id: &str) -> Result<OrgRoleDto> {
+    state
+        .db
+        .org_roles
+        .get(org_id.to_string(), role_id.to_string())
+        .await?
+        .context(NotFoundSnafu {
+            msg: "Org role not found".to_string(),
+        })
+}
+
+/// Custom role names must not shadow a built-in role or another role of the org
+async fn ensure_role_name_available(
+    state: &AppState,
+    org_id: &str,
+    name: &str,
+    role_id: Option<&str>,
+) -> Result<()> {
+    let lowered = name.to_lowercase();
+    let builtin = ALL_ROLES.iter().any(|role| {
+        role.to_string().to_lowercase() == lowered || role.label().to_lowercase() == lowered
+    });
+    ensure!(
+        !builtin,
+        ValidationSnafu {
+            msg: "Role name is reserved for a built-in role".to_string(),
+        }
+    );
+
+    let existing = state
+        .db
+        .org_roles
+        .find_by_name(org_id.to_string(), name.to_string())
+        .await?;
+
+    ensure!(
+        existing.is_none_or(|existing| Some(existing.id.as_str()) == role_id),
+        ValidationSnafu {
+            msg: "Role name already exists".to_string(),
+        }
+    );
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn create_org_role_svc(
+    state: &AppState,
+    org_id: &str,
+    data: NewOrgRoleDto,
+) -> Result<OrgRoleDto> {
+    validate_payload(&data)?;
+
+    let _ = state
+        .db
+        .orgs
+        .get(org_id.to_string())
+        .await?
+        .context(OrgNotFoundSnafu)?;
+
+    ensure_role_name_available(state, org_id, &data.name, None).await?;
+
+    state
+        .db
+        .org_roles
+        .create(&AuditCtx::current(), org_id.to_string(), data)
+        .await
+}
+
+/// Updates the role, members holding it get the new permissions on their next request
+#[instrument(level = "debug", skip_all)]
+pub async fn update_org_role_svc(
+    state: &AppState,
+    org_id: &str,
+    role_id: &str,
+    data: UpdateOrgRoleDto,
+) -> Result<OrgRoleDto> {
+    validate_payload(&data)?;
+
+    let role = get_org_role_svc(state, org_id, role_id).await?;
+
+    if let Some(name) = data.name.as_deref() {
+        ensure_role_name_available(state, org_id, name, Some(&role.id)).await?;
+    }
+
+    let updated = state
+        .db
+        .org_roles
+        .update(&AuditCtx::current(), role.id.clone(), data)
+        .await?;
+
+    if updated {
+        invalidate_all_auth_cache(state).await;
+    }
+
+    get_org_role_svc(state, org_id, role_id).await
+}
+
+/// Removes the role, members holding it lose its permissions right away
+#[instrument(level = "debug", skip_all)]
+pub async fn delete_org_role_svc(state: &AppState, org_id: &str, role_id: &str) -> Result<()> {
+    let role = get_org_role_svc(state, org_id, role_id).await?;
+
+    state.db.org_roles.delete(role.id).await?;
+    invalidate_all_auth_cache(state).await;
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn create_org_role_web_svc(
+    state: &AppState,
+    org_id: &str,
+    form: OrgRoleFormData,
+) -> Result<OrgRoleDto> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+
+    create_org_role_svc(
+        state,
+        org_id,
+        NewOrgRoleDto {
+            name: form.name,
+            description: form.description,
+            permissions: form.permissions,
+        },
+    )
+    .await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn update_org_role_web_svc(
+    state: &AppState,
+    org_id: &str,
+    role_id: &str,
+    form: OrgRoleFormData,
+) -> Result<OrgRoleDto> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+
+    update_org_role_svc(
+        state,
+        org_id,
+        role_id,
+        UpdateOrgRoleDto {
+            name: Some(form.name),
+            description: Some(form.description),
+            permissions: Some(form.permissions),
+        },
+    )
+    .await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn delete_org_role_web_svc(
+    state: &AppState,
+    org_id: &str,
+    role_id: &str,
+    csrf_token: &str,
+) -> Result<()> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+
+    delete_org_role_svc(state, org_id, role_id).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn list_member_roles_svc(
+    state: &AppState,
+    org_id: &str,
+    user_id: &str,
+) -> Result<Vec<OrgRoleDto>> {
+    state
+        .db
+        .org_roles
+        .list_by_member(org_id.to_string(), user_id.to_string())
+        .await
+}
+
+/// Permissions granted to the member by custom roles, merged into the actor on authentication
+#[instrument(level = "debug", skip_all)]
+pub async fn custom_permissions_svc(
+    state: &AppState,
+    org_id: &str,
+    user_id: &str,
+) -> Result<Vec<Permission>> {
+    let roles = list_member_roles_svc(state, org_id, user_id).await?;
+
+    let mut permissions: Vec<Permission> = Vec::new();
+    for permission in roles.into_iter().flat_map(|role| role.permissions) {
+        if !permissions.contains(&permission) {
+            permissions.push(permission);
+        }
+    }
+
+    Ok(permissions)
+}
+
+/// Replaces the member's custom roles, every role must belong to the member's org
+#[instrument(level = "debug", skip_all)]
+pub async fn assign_member_roles_svc(
+    state: &AppState,
+    member: &OrgMemberDto,
+    role_ids: Vec<String>,
+) -> Result<Vec<OrgRoleDto>> {
+    let mut unique: Vec<String> = Vec::with_capacity(role_ids.len());
+    for role_id in role_ids.into_iter() {
+        if !unique.contains(&role_id) {
+            get_org_role_svc(state, &member.org_id, &role_id).await?;
+            unique.push(role_id);
+        }
+    }
+
+    state
+        .db
+        .org_roles
+        .set_member_roles(member.id.clone(), unique)
+        .await?;
+    invalidate_auth_cache(state, &member.user_id).await;
+
+    list_member_roles_svc(state, &member.org_id, &member.user_id).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn assign_member_roles_web_svc(
+    state: &AppState,
+    member: &OrgMemberDto,
+    form: OrgMemberRolesFormData,
+) -> Result<Vec<OrgRoleDto>> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == member.user_id, CsrfTokenSnafu);
+
+    assign_member_roles_svc(state, member, form.role_ids).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ctx::AuditCtx;
+    use crate::dto::{NewOrgMemberDto, NewOrgRoleDto, Permission};
+    use crate::test::TestCtx;
+
+    use super::{
+        assign_member_roles_svc, create_org_role_svc, custom_permissions_svc, delete_org_role_svc,
+    };
+
+    #[tokio::test]
+    async fn custom_roles_grant_permissions_to_members() {
+        let ctx = TestCtx::new("org_roles").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Role Owner",
+                "role.owner@example.com",
+                "password123",
+                "Role Org",
+            )
+            .await
+            .expect("auth fixture");
+        let user = ctx
+            .seed_user_with_password("Role Member", "role.member@example.com", "password123")
+            .await
+            .expect("member user");
+
+        ctx.state
+            .db
+            .org_members
+            .create(
+                &AuditCtx::default(),
+                fixture.org.id.clone(),
+                NewOrgMemberDto {
+                    user_id: user.id.clone(),
+                    roles: vec!["OrgViewer".to_string()],
+                    status: "active".to_string(),
+                },
+            )
+            .await
+            .expect("member should be created");
+        let member = ctx
+            .state
+            .db
+            .org_members
+            .find_member(fixture.org.id.clone(), user.id.clone())
+            .await
+            .expect("find member")
+            .expect("member exists");
+
+        let reserved = create_org_role_svc(
+            &ctx.state,
+            &fixture.org.id,
+            NewOrgRoleDto {
+                name: "OrgAdmin".to_string(),
+                description: String::new(),
+                permissions: vec!["org_members.edit".to_string()],
+            },
+        )
+        .await;
+        assert!(reserved.is_err(), "built-in role names are reserved");
+
+        let escalated = create_org_role_svc(
+            &ctx.state,
+            &fixture.org.id,
+            NewOrgRoleDto {
+                name: "Creators".to_string(),
+                description: String::new(),
+                permissions: vec!["users.create".to_string()],
+            },
+        )
+        .await;
+        assert!(escalated.is_err(), "system permissions are not allowed");
+
+        let role = create_org_role_svc(
+            &ctx.state,
+            &fixture.org.id,
+            NewOrgRoleDto {
+                name: "Member Editors".to_string(),
+                description: "Edits members".to_string(),
+                permissions: vec!["org_members.edit".to_string()],
+            },
+        )
+        .await
+        .expect("role should be created");
+
+        let assigned = assign_member_roles_svc(&ctx.state, &member, vec![role.id.clone()])
+            .await
+            .expect("role should be assigned");
+        assert_eq!(assigned.len(), 1);
+
+        let permissions = custom_permissions_svc(&ctx.state, &fixture.org.id, &user.id)
+            .await
+            .expect("custom permissions");
+        assert_eq!(permissions, vec![Permission::OrgMembersEdit]);
+
+        delete_org_role_svc(&ctx.state, &fixture.org.id, &role.id)
+            .await
+            .expect("role should be deleted");
+        let permissions = custom_permissions_svc(&ctx.state, &fixture.org.id, &user.id)
+            .await
+            .expect("custom permissions");
+        assert!(permissions.is_empty(), "deleting a role drops its grants");
+    }
+}
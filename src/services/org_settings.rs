@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ensure};
+use tracing::{info, instrument};
+
+use crate::Result;
+use crate::dto::{OrgSettingsDto, UpdateOrgSettingsDto};
+use crate::error::{CsrfTokenSnafu, OrgNotFoundSnafu, ValidationSnafu};
+use crate::run::AppState;
+use crate::services::token::verify_csrf_token;
+use crate::validators::validate_payload;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct OrgSettingsFormData {
+    pub token: String,
+    pub logo_url: String,
+    pub default_member_role: String,
+
+    /// Separated by commas or new lines
+    pub allowed_email_domains: String,
+}
+
+/// Settings of the org, the defaults when never saved
+#[instrument(level = "debug", skip_all)]
+pub async fn get_org_settings_svc(state: &AppState, org_id: &str) -> Result<OrgSettingsDto> {
+    let settings = state.db.org_settings.get(org_id.to_string()).await?;
+    Ok(settings.unwrap_or_else(|| OrgSettingsDto::defaults(org_id)))
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn update_org_settings_svc(
+    state: &AppState,
+    org_id: &str,
+    mut data: UpdateOrgSettingsDto,
+) -> Result<OrgSettingsDto> {
+    // Accept `@example.com` and mixed case, domains are compared in lowercase
+    if let Some(domains) = data.allowed_email_domains.take() {
+        let mut normalized: Vec<String> = Vec::with_capacity(domains.len());
+        for domain in domains.into_iter() {
+            let domain = domain.trim().trim_start_matches('@').to_lowercase();
+            if !domain.is_empty() && !normalized.contains(&domain) {
+                normalized.push(domain);
+            }
+        }
+        data.allowed_email_domains = Some(normalized);
+    }
+
+    validate_payload(&data)?;
+
+    let _ = state
+        .db
+        .orgs
+        .get(org_id.to_string())
+        .await?
+        .context(OrgNotFoundSnafu)?;
+
+    let mut settings = get_org_settings_svc(state, org_id).await?;
+
+    if let Some(logo_url) = data.logo_url {
+        settings.logo_url = Some(logo_url).filter(|url| !url.is_empty());
+    }
+    if let Some(role) = data.default_member_role {
+        settings.default_member_role = role;
+    }
+    if let Some(domains) = data.allowed_email_domains {
+        settings.allowed_email_domains = domains;
+    }
+
+    let settings = state.db.org_settings.upsert(settings).await?;
+
+    info!(
+        org_id = org_id,
+        default_member_role = settings.default_member_role,
+        allowed_email_domains = settings.allowed_email_domains.join(","),
+        "org_settings.updated"
+    );
+
+    Ok(settings)
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn update_org_settings_web_svc(
+    state: &AppState,
+    org_id: &str,
+    form: OrgSettingsFormData,
+) -> Result<OrgSettingsDto> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+
+    let domains: Vec<String> = form
+        .allowed_email_domains
+        .split([',', '\n'])
+        .map(|domain| domain.trim().to_string())
+        .filter(|domain| !domain.is_empty())
+        .collect();
+
+    let data = UpdateOrgSettingsDto {
+        logo_url: Some(form.logo_url.trim().to_string()),
+        default_member_role: Some(form.default_member_role),
+        allowed_email_domains: Some(domains),
+    };
+
+    update_org_settings_svc(state, org_id, data).await
+}
+
+/// Rejects emails outside of the org's allowed domains
+pub fn ensure_member_email_allowed(settings: &OrgSettingsDto, email: &str) -> Result<()> {
+    ensure!(
+        settings.allows_email(email),
+        ValidationSnafu {
+            msg: format!(
+                "Only emails from {} can join the organization",
+                settings.allowed_email_domains.join(", ")
+            ),
+        }
+    );
+    Ok(())
+}
+
+/// Roles of members added without picking any
+pub fn default_member_roles(settings: &OrgSettingsDto) -> Vec<String> {
+    vec![settings.default_member_role.clone()]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+    use crate::dto::UpdateOrgSettingsDto;
+    use crate::test::TestCtx;
+
+    use super::{get_org_settings_svc, update_org_settings_svc};
+
+    #[tokio::test]
+    async fn org_settings_updates_are_partial() {
+        let ctx = TestCtx::new("org_settings_partial")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Settings User",
+                "settings@example.com",
+                "password123",
+                "Settings Org",
+            )
+            .await
+            .expect("auth fixture");
+        let org_id = fixture.org.id.as_str();
+
+        let settings = get_org_settings_svc(&ctx.state, org_id)
+            .await
+            .expect("default settings");
+        assert_eq!(settings.default_member_role, "OrgViewer");
+        assert!(settings.allowed_email_domains.is_empty());
+
+        let data = UpdateOrgSettingsDto {
+            logo_url: Some("https://cdn.example.com/logo.png".to_string()),
+            default_member_role: None,
+            allowed_email_domains: Some(vec![
+                "@Example.com".to_string(),
+                "example.com".to_string(),
+                "corp.example.org".to_string(),
+            ]),
+        };
+        update_org_settings_svc(&ctx.state, org_id, data)
+            .await
+            .expect("settings should be saved");
+
+        let data = UpdateOrgSettingsDto {
+            logo_url: None,
+            default_member_role: Some("OrgEditor".to_string()),
+            allowed_email_domains: None,
+        };
+        let settings = update_org_settings_svc(&ctx.state, org_id, data)
+            .await
+            .expect("settings should be saved");
+        assert_eq!(
+            settings.logo_url.as_deref(),
+            Some("https://cdn.example.com/logo.png")
+        );
+        assert_eq!(settings.default_member_role, "OrgEditor");
+        assert_eq!(
+            settings.allowed_email_domains,
+            vec!["example.com".to_string(), "corp.example.org".to_string()]
+        );
+
+        let data = UpdateOrgSettingsDto {
+            logo_url: None,
+            default_member_role: Some("Superuser".to_string()),
+            allowed_email_domains: None,
+        };
+        let result = update_org_settings_svc(&ctx.state, org_id, data).await;
+        let Err(Error::Validation { msg }) = result else {
+            panic!("superuser is not a member role");
+        };
+        assert_eq!(
+            msg,
+            "default_member_role: must be OrgAdmin, OrgEditor or OrgViewer"
+        );
+    }
+}
@@ -0,0 +1,822 @@
+use snafu::{OptionExt, ensure};
+use tracing::{info, instrument};
+
+use crate::ctx::AuditCtx;
+use crate::db::DbMapper;
+use crate::dto::{
+    ImportAction, ImportConflictStrategy, NewOrgRoleDto, ORG_ARCHIVE_FORMAT_VERSION, OrgArchiveDto,
+    OrgArchiveOrgDto, OrgArchiveRoleDto, OrgArchiveSettingsDto, OrgImportAppPlanDto,
+    OrgImportMemberPlanDto, OrgImportOrgPlanDto, OrgImportPlanDto, OrgImportReportDto,
+    OrgImportRolePlanDto, OrgImportSettingsPlanDto, Role, UpdateOrgSettingsDto, to_roles,
+};
+use crate::error::{ForbiddenSnafu, ValidationSnafu};
+use crate::services::apps::check_extra_redirect_uris;
+use crate::services::counters::refresh_org_counters;
+use crate::validators::{app_scopes, validate_payload};
+use crate::{Error, Result};
+
+/// Renamed copies get a numbered suffix, give up after this many attempts
+const MAX_RENAME_ATTEMPTS: usize = 100;
+
+/// Exports the org with its members and apps as a portable archive
+#[instrument(level = "debug", skip_all)]
+pub async fn export_org_svc(db: &DbMapper, org_id: &str) -> Result<OrgArchiveDto> {
+    let org = db
+        .orgs
+        .get(org_id.to_string())
+        .await?
+        .context(ValidationSnafu {
+            msg: format!("Org not found: {}", org_id),
+        })?;
+
+    // The org created during setup holds the superuser memberships
+    if let Some(owner_id) = org.owner_id.clone() {
+        let superuser = db.superusers.get(owner_id).await?;
+        ensure!(
+            superuser.is_none(),
+            ForbiddenSnafu {
+                msg: "Cannot export the superuser org".to_string()
+            }
+        );
+    }
+
+    let members = db.org_transfers.export_members(org.id.clone()).await?;
+    let apps = db.org_transfers.export_apps(org.id.clone()).await?;
+    let settings =
+        db.org_settings
+            .get(org.id.clone())
+            .await?
+            .map(|settings| OrgArchiveSettingsDto {
+                logo_url: settings.logo_url,
+                default_member_role: settings.default_member_role,
+                allowed_email_domains: settings.allowed_email_domains,
+            });
+    let roles = db
+        .org_roles
+        .list(org.id.clone())
+        .await?
+        .into_iter()
+        .map(|role| OrgArchiveRoleDto {
+            name: role.name,
+            description: role.description,
+            permissions: role.permissions.iter().map(|p| p.to_string()).collect(),
+        })
+        .collect();
+
+    Ok(OrgArchiveDto {
+        format_version: ORG_ARCHIVE_FORMAT_VERSION,
+        exported_at: chrono::Utc::now().timestamp_millis(),
+        org: OrgArchiveOrgDto {
+            id: org.id,
+            name: org.name,
+            status: org.status,
+            owner_email: org.owner_email,
+        },
+        members,
+        apps,
+        settings,
+        roles,
+    })
+}
+
+pub fn parse_org_archive(contents: &str) -> Result<OrgArchiveDto> {
+    let archive: OrgArchiveDto = serde_json::from_str(contents).map_err(|e| Error::Validation {
+        msg: format!("Invalid org archive: {}", e),
+    })?;
+
+    ensure!(
+        archive.format_version == ORG_ARCHIVE_FORMAT_VERSION,
+        ValidationSnafu {
+            msg: format!(
+                "Unsupported org archive version: {}",
+                archive.format_version
+            ),
+        }
+    );
+
+    ensure!(
+        !archive.org.name.trim().is_empty(),
+        ValidationSnafu {
+            msg: "Org archive must have an org name".to_string(),
+        }
+    );
+
+    validate_org_archive(&archive)?;
+
+    Ok(archive)
+}
+
+/// Applies the rules of the admin screens to the settings, roles and apps of the archive
+fn validate_org_archive(archive: &OrgArchiveDto) -> Result<()> {
+    if let Some(settings) = archive.settings.as_ref() {
+        validate_payload(&UpdateOrgSettingsDto {
+            logo_url: settings.logo_url.clone(),
+            default_member_role: Some(settings.default_member_role.clone()),
+            allowed_email_domains: Some(settings.allowed_email_domains.clone()),
+        })?;
+    }
+
+    for (index, role) in archive.roles.iter().enumerate() {
+        validate_payload(&NewOrgRoleDto {
+            name: role.name.clone(),
+            description: role.description.clone(),
+            permissions: role.permissions.clone(),
+        })?;
+
+        ensure!(
+            !archive.roles[..index].iter().any(|r| r.name == role.name),
+            ValidationSnafu {
+                msg: format!("Duplicate org role in archive: {}", role.name),
+            }
+        );
+    }
+
+    for member in archive.members.iter() {
+        for name in member.custom_roles.iter() {
+            ensure!(
+                archive.roles.iter().any(|role| role.name == *name),
+                ValidationSnafu {
+                    msg: format!("Member {} has an unknown org role: {}", member.email, name),
+                }
+            );
+        }
+    }
+
+    for app in archive.apps.iter() {
+        check_extra_redirect_uris(&app.redirect_uri, app.extra_redirect_uris.iter().cloned())?;
+
+        for scopes in [app.scopes.as_ref(), app.org_scopes.as_ref()]
+            .into_iter()
+            .flatten()
+        {
+            ensure!(
+                app_scopes(scopes).is_ok(),
+                ValidationSnafu {
+                    msg: format!("App {} has invalid scopes: {}", app.name, scopes.join(" ")),
+                }
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolves the archive against the target environment without writing anything
+#[instrument(level = "debug", skip_all)]
+pub async fn plan_org_import_svc(
+    db: &DbMapper,
+    archive: &OrgArchiveDto,
+    strategy: ImportConflictStrategy,
+) -> Result<OrgImportPlanDto> {
+    let org = plan_org(db, archive, strategy).await?;
+    let skip_all = org.action == ImportAction::Skip;
+
+    let mut settings: Option<OrgImportSettingsPlanDto> = None;
+    if let Some(archived) = archive.settings.as_ref() {
+        let mut item = OrgImportSettingsPlanDto {
+            action: ImportAction::Create,
+            logo_url: archived.logo_url.clone(),
+            default_member_role: archived.default_member_role.clone(),
+            allowed_email_domains: archived.allowed_email_domains.clone(),
+        };
+
+        // Settings already chosen in the target org are kept
+        if skip_all {
+            item.action = ImportAction::Skip;
+        } else if let Some(org_id) = org.target_id.clone()
+            && db.org_settings.get(org_id).await?.is_some()
+        {
+            item.action = ImportAction::Reuse;
+        }
+
+        settings = Some(item);
+    }
+
+    let mut roles: Vec<OrgImportRolePlanDto> = Vec::with_capacity(archive.roles.len());
+    for role in archive.roles.iter() {
+        let mut item = OrgImportRolePlanDto {
+            action: ImportAction::Create,
+            target_id: None,
+            name: role.name.clone(),
+            description: role.description.clone(),
+            permissions: role.permissions.clone(),
+        };
+
+        if skip_all {
+            item.action = ImportAction::Skip;
+        } else if let Some(org_id) = org.target_id.clone()
+            && let Some(existing) = db.org_roles.find_by_name(org_id, role.name.clone()).await?
+        {
+            item.action = ImportAction::Reuse;
+            item.target_id = Some(existing.id);
+        }
+
+        roles.push(item);
+    }
+
+    let mut apps: Vec<OrgImportAppPlanDto> = Vec::with_capacity(archive.apps.len());
+    for app in archive.apps.iter() {
+        let mut item = OrgImportAppPlanDto {
+            source_id: app.id.clone(),
+            action: ImportAction::Create,
+            target_id: None,
+            name: app.name.clone(),
+            redirect_uri: app.redirect_uri.clone(),
+            extra_redirect_uris: app.extra_redirect_uris.clone(),
+            scopes: app.scopes.clone(),
+            org_scopes: app.org_scopes.clone(),
+            link: !skip_all,
+        };
+
+        if skip_all {
+            item.action = ImportAction::Skip;
+            apps.push(item);
+            continue;
+        }
+
+        if let Some(existing) = db.org_transfers.find_app_by_name(app.name.clone()).await? {
+            match strategy {
+                ImportConflictStrategy::Skip => {
+                    item.action = ImportAction::Reuse;
+                    item.target_id = Some(existing.id);
+                }
+                ImportConflictStrategy::Merge => {
+                    let mut changed = false;
+                    if let Some(current) = db.apps.get(existing.id.clone()).await? {
+                        // Redirect URIs registered in either environment keep working
+                        let current_extras = current.extra_redirect_uris();
+                        let extras = check_extra_redirect_uris(
+                            &app.redirect_uri,
+                            current
+                                .redirect_uris
+                                .into_iter()
+                                .chain(app.extra_redirect_uris.iter().cloned()),
+                        )?;
+                        changed =
+                            current.redirect_uri != app.redirect_uri || extras != current_extras;
+                        item.extra_redirect_uris = extras;
+                    }
+                    item.action = if changed {
+                        ImportAction::Update
+                    } else {
+                        ImportAction::Reuse
+                    };
+                    item.target_id = Some(existing.id);
+                }
+                ImportConflictStrategy::Rename => {
+                    item.name = available_app_name(db, &app.name).await?;
+                }
+            }
+        }
+
+        // Only an existing org can already have the app linked
+        if let (Some(org_id), Some(app_id)) = (org.target_id.clone(), item.target_id.clone()) {
+            let linked = db.org_apps.find_app(org_id, app_id).await?;
+            item.link = linked.is_none();
+        }
+
+        apps.push(item);
+    }
+
+    let mut members: Vec<OrgImportMemberPlanDto> = Vec::with_capacity(archive.members.len());
+    for member in archive.members.iter() {
+        let mut item = OrgImportMemberPlanDto {
+            email: member.email.clone(),
+            action: ImportAction::Skip,
+            user_id: None,
+            member_id: None,
+            roles: Vec::new(),
+            custom_roles: member.custom_roles.clone(),
+            status: member.status.clone(),
+            note: None,
+        };
+
+        if skip_all {
+            members.push(item);
+            continue;
+        }
+
+        let Ok(roles) = to_roles(&member.roles) else {
+            item.note = Some("Invalid roles".to_string());
+            members.push(item);
+            continue;
+        };
+
+        // Superuser status never travels through an archive
+        item.roles = roles
+            .into_iter()
+            .filter(|role| *role != Role::Superuser)
+            .map(|role| role.to_string())
+            .collect();
+
+        if item.roles.is_empty() {
+            item.note = Some("No importable roles".to_string());
+            members.push(item);
+            continue;
+        }
+
+        if member.status != "active" && member.status != "inactive" {
+            item.note = Some(format!("Invalid status: {}", member.status));
+            members.push(item);
+            continue;
+        }
+
+        let Some(user) = db.users.find_by_email(member.email.clone()).await? else {
+            item.note = Some("User not found".to_string());
+            members.push(item);
+            continue;
+        };
+
+        if db.superusers.get(user.id.clone()).await?.is_some() {
+            item.note = Some("User is a superuser".to_string());
+            members.push(item);
+            continue;
+        }
+
+        item.user_id = Some(user.id.clone());
+        item.action = ImportAction::Create;
+
+        if let Some(org_id) = org.target_id.clone()
+            && let Some(existing) = db
+                .org_members
+                .find_member(org_id.clone(), user.id.clone())
+                .await?
+        {
+            let held: Vec<String> = db
+                .org_roles
+                .list_by_member(org_id, user.id)
+                .await?
+                .into_iter()
+                .map(|role| role.name)
+                .collect();
+            let missing_custom_roles = item.custom_roles.iter().any(|name| !held.contains(name));
+
+            let mut merged: Vec<String> =
+                existing.roles.iter().map(|role| role.to_string()).collect();
+            for role in item.roles.iter() {
+                if !merged.contains(role) {
+                    merged.push(role.clone());
+                }
+            }
+
+            item.member_id = Some(existing.id);
+            item.status = existing.status;
+            if merged.len() > existing.roles.len() || missing_custom_roles {
+                item.action = ImportAction::Update;
+            } else {
+                item.action = ImportAction::Skip;
+                item.note = Some("Already a member".to_string());
+            }
+            item.roles = merged;
+        }
+
+        members.push(item);
+    }
+
+    Ok(OrgImportPlanDto {
+        strategy,
+        org,
+        settings,
+        roles,
+        apps,
+        members,
+    })
+}
+
+/// Imports the archive, or only previews the plan on a dry run
+#[instrument(level = "debug", skip_all)]
+pub async fn import_org_svc(
+    db: &DbMapper,
+    archive: &OrgArchiveDto,
+    strategy: ImportConflictStrategy,
+    dry_run: bool,
+) -> Result<OrgImportReportDto> {
+    let plan = plan_org_import_svc(db, archive, strategy).await?;
+
+    if dry_run || plan.org.action == ImportAction::Skip {
+        return Ok(OrgImportReportDto {
+            dry_run,
+            plan,
+            result: None,
+        });
+    }
+
+    let result = db.org_transfers.import(&AuditCtx::current(), &plan).await?;
+    refresh_org_counters(db, &result.org_id).await;
+
+    info!(
+        source_org_id = archive.org.id,
+        org_id = result.org_id,
+        strategy = strategy.to_string(),
+        apps = result.apps.len(),
+        roles_created = result.roles_created,
+        members_created = result.members_created,
+        members_updated = result.members_updated,
+        "org.imported"
+    );
+
+    Ok(OrgImportReportDto {
+        dry_run,
+        plan,
+        result: Some(result),
+    })
+}
+
+async fn plan_org(
+    db: &DbMapper,
+    archive: &OrgArchiveDto,
+    strategy: ImportConflictStrategy,
+) -> Result<OrgImportOrgPlanDto> {
+    let mut owner_id: Option<String> = None;
+    if let Some(email) = archive.org.owner_email.clone()
+        && let Some(owner) = db.users.find_by_email(email).await?
+        && db.superusers.get(owner.id.clone()).await?.is_none()
+    {
+        owner_id = Some(owner.id);
+    }
+
+    let mut org = OrgImportOrgPlanDto {
+        action: ImportAction::Create,
+        target_id: None,
+        name: archive.org.name.clone(),
+        status: archive.org.status.clone(),
+        owner_id,
+    };
+
+    let Some(existing) = db
+        .org_transfers
+        .find_org_by_name(archive.org.name.clone())
+        .await?
+    else {
+        return Ok(org);
+    };
+
+    match strategy {
+        ImportConflictStrategy::Skip => {
+            org.action = ImportAction::Skip;
+            org.target_id = Some(existing.id);
+        }
+        ImportConflictStrategy::Merge => {
+            org.action = ImportAction::Reuse;
+            org.target_id = Some(existing.id);
+        }
+        ImportConflictStrategy::Rename => {
+            org.name = available_org_name(db, &archive.org.name).await?;
+        }
+    }
+
+    Ok(org)
+}
+
+async fn available_org_name(db: &DbMapper, name: &str) -> Result<String> {
+    for n in 2..(MAX_RENAME_ATTEMPTS + 2) {
+        let candidate = format!("{} ({})", name, n);
+        if db
+            .org_transfers
+            .find_org_by_name(candidate.clone())
+            .await?
+            .is_none()
+        {
+            return Ok(candidate);
+        }
+    }
+
+    Err(Error::Validation {
+        msg: format!("Unable to find a free name for org {}", name),
+    })
+}
+
+async fn available_app_name(db: &DbMapper, name: &str) -> Result<String> {
+    for n in 2..(MAX_RENAME_ATTEMPTS + 2) {
+        let candidate = format!("{} ({})", name, n);
+        if db
+            .org_transfers
+            .find_app_by_name(candidate.clone())
+            .await?
+            .is_none()
+        {
+            return Ok(candidate);
+        }
+    }
+
+    Err(Error::Validation {
+        msg: format!("Unable to find a free name for app {}", name),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::{
+        AppRedirectUriDto, ImportAction, ImportConflictStrategy, NewOrgRoleDto, UpdateOrgAppDto,
+        UpdateOrgSettingsDto,
+    };
+    use crate::services::apps::add_app_redirect_uri_svc;
+    use crate::services::org_apps::update_org_app_svc;
+    use crate::services::org_roles::{assign_member_roles_svc, create_org_role_svc};
+    use crate::services::org_settings::update_org_settings_svc;
+    use crate::test::TestCtx;
+
+    use super::{export_org_svc, import_org_svc, parse_org_archive};
+
+    #[tokio::test]
+    async fn export_then_import_remaps_ids() {
+        let ctx = TestCtx::new("org_transfer_roundtrip")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "Owner User",
+                "transfer.owner@example.com",
+                "password123",
+                "Transfer Org",
+                "Transfer App",
+                "https://transfer.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+        let db = ctx.state.db.as_ref();
+
+        let archive = export_org_svc(db, &fixture.auth.org.id)
+            .await
+            .expect("export");
+        let contents = serde_json::to_string(&archive).expect("serialize");
+        let archive = parse_org_archive(&contents).expect("parse");
+        assert_eq!(archive.apps.len(), 1);
+        assert!(
+            archive
+                .members
+                .iter()
+                .any(|member| member.email == "transfer.owner@example.com")
+        );
+
+        // Same environment, so the org already exists
+        let skipped = import_org_svc(db, &archive, ImportConflictStrategy::Skip, false)
+            .await
+            .expect("skip import");
+        assert_eq!(skipped.plan.org.action, ImportAction::Skip);
+        assert!(skipped.result.is_none());
+
+        let preview = import_org_svc(db, &archive, ImportConflictStrategy::Rename, true)
+            .await
+            .expect("dry run");
+        assert_eq!(preview.plan.org.name, "Transfer Org (2)");
+        assert!(preview.result.is_none());
+        assert!(
+            db.org_transfers
+                .find_org_by_name("Transfer Org (2)".to_string())
+                .await
+                .expect("lookup")
+                .is_none()
+        );
+
+        let renamed = import_org_svc(db, &archive, ImportConflictStrategy::Rename, false)
+            .await
+            .expect("rename import");
+        let result = renamed.result.expect("import result");
+        assert_ne!(result.org_id, fixture.auth.org.id);
+        assert_eq!(result.apps.len(), 1);
+        assert_eq!(result.apps[0].source_id, fixture.app.id);
+        assert_ne!(result.apps[0].target_id, fixture.app.id);
+        assert!(result.members_created >= 1);
+
+        let member = db
+            .org_members
+            .find_member(result.org_id.clone(), fixture.auth.user.id.clone())
+            .await
+            .expect("member lookup");
+        assert!(member.is_some());
+    }
+
+    #[tokio::test]
+    async fn archive_carries_roles_settings_and_app_options() {
+        let ctx = TestCtx::new("org_transfer_sections")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "Owner User",
+                "sections.owner@example.com",
+                "password123",
+                "Sections Org",
+                "Sections App",
+                "https://sections.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+        let org_id = fixture.auth.org.id.clone();
+        let db = ctx.state.db.as_ref();
+
+        let role = create_org_role_svc(
+            &ctx.state,
+            &org_id,
+            NewOrgRoleDto {
+                name: "Auditors".to_string(),
+                description: "Reads files".to_string(),
+                permissions: vec!["files.view".to_string()],
+            },
+        )
+        .await
+        .expect("role");
+        let member = db
+            .org_members
+            .find_member(org_id.clone(), fixture.auth.user.id.clone())
+            .await
+            .expect("member lookup")
+            .expect("member");
+        assign_member_roles_svc(&ctx.state, &member, vec![role.id])
+            .await
+            .expect("assign role");
+        update_org_settings_svc(
+            &ctx.state,
+            &org_id,
+            UpdateOrgSettingsDto {
+                logo_url: None,
+                default_member_role: Some("OrgEditor".to_string()),
+                allowed_email_domains: Some(vec!["example.com".to_string()]),
+            },
+        )
+        .await
+        .expect("settings");
+        add_app_redirect_uri_svc(
+            &ctx.state,
+            &fixture.app.id,
+            AppRedirectUriDto {
+                redirect_uri: "https://sections.example.com/extra".to_string(),
+            },
+        )
+        .await
+        .expect("extra redirect uri");
+        update_org_app_svc(
+            &ctx.state,
+            &org_id,
+            &fixture.app.id,
+            UpdateOrgAppDto {
+                scopes: Some(vec!["openid".to_string()]),
+            },
+        )
+        .await
+        .expect("org app scopes");
+
+        let archive = export_org_svc(db, &org_id).await.expect("export");
+        let contents = serde_json::to_string(&archive).expect("serialize");
+        let archive = parse_org_archive(&contents).expect("parse");
+        assert_eq!(archive.roles.len(), 1);
+        assert_eq!(
+            archive
+                .settings
+                .as_ref()
+                .map(|s| s.default_member_role.as_str()),
+            Some("OrgEditor")
+        );
+        assert_eq!(
+            archive.apps[0].extra_redirect_uris,
+            vec!["https://sections.example.com/extra".to_string()]
+        );
+        assert_eq!(archive.apps[0].org_scopes, Some(vec!["openid".to_string()]));
+
+        let report = import_org_svc(db, &archive, ImportConflictStrategy::Rename, false)
+            .await
+            .expect("rename import");
+        let result = report.result.expect("import result");
+        assert_eq!(result.roles_created, 1);
+
+        let settings = db
+            .org_settings
+            .get(result.org_id.clone())
+            .await
+            .expect("settings lookup")
+            .expect("settings row");
+        assert_eq!(settings.allowed_email_domains, vec!["example.com"]);
+
+        let held = db
+            .org_roles
+            .list_by_member(result.org_id.clone(), fixture.auth.user.id.clone())
+            .await
+            .expect("member roles");
+        assert_eq!(held.len(), 1);
+        assert_eq!(held[0].name, "Auditors");
+
+        let app_id = result.apps[0].target_id.clone();
+        let app = db
+            .apps
+            .get(app_id.clone())
+            .await
+            .expect("app lookup")
+            .expect("app row");
+        assert_eq!(
+            app.extra_redirect_uris(),
+            vec!["https://sections.example.com/extra".to_string()]
+        );
+        let link = db
+            .org_apps
+            .find_app(result.org_id.clone(), app_id)
+            .await
+            .expect("link lookup")
+            .expect("link row");
+        assert_eq!(link.scopes, Some(vec!["openid".to_string()]));
+
+        // Merging into the source org finds everything already in place
+        let merged = import_org_svc(db, &archive, ImportConflictStrategy::Merge, true)
+            .await
+            .expect("merge dry run");
+        assert_eq!(merged.plan.roles[0].action, ImportAction::Reuse);
+        assert_eq!(
+            merged.plan.settings.map(|settings| settings.action),
+            Some(ImportAction::Reuse)
+        );
+        assert_eq!(merged.plan.apps[0].action, ImportAction::Reuse);
+    }
+
+    #[tokio::test]
+    async fn archive_with_invalid_sections_is_rejected() {
+        let ctx = TestCtx::new("org_transfer_invalid")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "Owner User",
+                "invalid.owner@example.com",
+                "password123",
+                "Invalid Org",
+                "Invalid App",
+                "https://invalid.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+        let db = ctx.state.db.as_ref();
+
+        let archive = export_org_svc(db, &fixture.auth.org.id)
+            .await
+            .expect("export");
+
+        let mut scoped = archive.clone();
+        scoped.apps[0].scopes = Some(vec!["vault".to_string()]);
+        let contents = serde_json::to_string(&scoped).expect("serialize");
+        assert!(parse_org_archive(&contents).is_err());
+
+        let mut linked = archive.clone();
+        linked.members[0].custom_roles = vec!["Ghosts".to_string()];
+        let contents = serde_json::to_string(&linked).expect("serialize");
+        assert!(parse_org_archive(&contents).is_err());
+
+        // Archives exported before these sections existed still parse
+        let mut legacy = serde_json::to_value(&archive).expect("to value");
+        let legacy_object = legacy.as_object_mut().expect("object");
+        legacy_object.remove("roles");
+        legacy_object.remove("settings");
+        assert!(parse_org_archive(&legacy.to_string()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn merge_import_only_fills_in_missing_records() {
+        let ctx = TestCtx::new("org_transfer_merge").await.expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "Owner User",
+                "merge.owner@example.com",
+                "password123",
+                "Merge Org",
+                "Merge App",
+                "https://merge.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+        let db = ctx.state.db.as_ref();
+
+        let mut archive = export_org_svc(db, &fixture.auth.org.id)
+            .await
+            .expect("export");
+        archive.apps[0].redirect_uri = "https://merge.example.com/new-callback".to_string();
+        archive.apps[0].extra_redirect_uris = vec!["https://merge.example.com/extra".to_string()];
+
+        let report = import_org_svc(db, &archive, ImportConflictStrategy::Merge, false)
+            .await
+            .expect("merge import");
+        assert_eq!(report.plan.org.action, ImportAction::Reuse);
+        assert_eq!(report.plan.apps[0].action, ImportAction::Update);
+        assert!(!report.plan.apps[0].link);
+
+        let result = report.result.expect("import result");
+        assert_eq!(result.org_id, fixture.auth.org.id);
+        assert_eq!(result.members_created, 0);
+
+        let app = db
+            .apps
+            .get(fixture.app.id.clone())
+            .await
+            .expect("app lookup")
+            .expect("app row");
+        assert_eq!(app.redirect_uri, "https://merge.example.com/new-callback");
+        assert_eq!(
+            app.extra_redirect_uris(),
+            vec![
+                "https://merge.example.com/callback".to_string(),
+                "https://merge.example.com/extra".to_string()
+            ]
+        );
+    }
+}
@@ -1,14 +1,29 @@
 use serde::{Deserialize, Serialize};
-use snafu::ensure;
+use snafu::{OptionExt, ensure};
+use tracing::{info, instrument};
 
-use crate::dto::{ListOrgAppsParamsDto, ListOrgMembersParamsDto, Paginated};
+use crate::ctx::AuditCtx;
+use crate::db::DbMapper;
+use crate::db::{DeletedScope, SoftDelete, Versioned};
 use crate::dto::{
-    ListOrgOwnerSuggestionsParamsDto, ListOrgsParamsDto, NewOrgDto, OrgDto, OrgOwnerSuggestionDto,
-    UpdateOrgDto,
+    ApprovalAction, BatchGetDto, BatchGetResultDto, CursorPage, ListOrgMembersParamsDto, Paginated,
+};
+use crate::dto::{
+    ChangeAction, EntityChangeDto, ListOrgsParamsDto, NewOrgDto, NewOrgMemberDto, OrgDto,
+    UpdateOrgDto, UpdatedDto,
+};
+use crate::error::{
+    CsrfTokenSnafu, ForbiddenSnafu, OrgNotFoundSnafu, ValidationSnafu, VersionMismatchSnafu,
 };
-use crate::error::{CsrfTokenSnafu, ForbiddenSnafu, ValidationSnafu};
 use crate::run::AppState;
+use crate::services::admin_events::publish_change;
+use crate::services::approvals::{approval_required_error, request_approval_svc};
+use crate::services::auth::invalidate_auth_cache;
+use crate::services::counters::refresh_org_counters;
+use crate::services::suggestions::invalidate_suggestions;
 use crate::services::token::verify_csrf_token;
+use crate::utils::ListingAllocGuard;
+use crate::validators::validate_payload;
 use crate::{Error, Result};
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -39,21 +54,41 @@ pub struct SelectOrgOwnerParams {
     pub owner_email: String,
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn list_orgs_svc(
     state: &AppState,
     params: ListOrgsParamsDto,
 ) -> Result<Paginated<OrgDto>> {
+    let _guard = ListingAllocGuard::new("orgs");
     state.db.orgs.list(params).await
 }
 
-pub async fn list_org_owner_suggestions_svc(
+/// Admin listing that can also see soft deleted orgs
+#[instrument(level = "debug", skip_all)]
+pub async fn list_orgs_scoped_svc(
     state: &AppState,
-    params: ListOrgOwnerSuggestionsParamsDto,
-) -> Result<Paginated<OrgOwnerSuggestionDto>> {
-    state.db.orgs.list_owner_suggestions(params).await
+    params: ListOrgsParamsDto,
+    scope: DeletedScope,
+) -> Result<Paginated<OrgDto>> {
+    let _guard = ListingAllocGuard::new("orgs");
+    state.db.orgs.list_scoped(params, scope).await
+}
+
+/// Keyset listing for large tables, see `ListOrgsParamsDto::uses_cursor`
+#[instrument(level = "debug", skip_all)]
+pub async fn list_orgs_cursor_svc(
+    state: &AppState,
+    params: ListOrgsParamsDto,
+    scope: DeletedScope,
+) -> Result<CursorPage<OrgDto>> {
+    let _guard = ListingAllocGuard::new("orgs");
+    state.db.orgs.list_cursor(params, scope).await
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn create_org_svc(state: &AppState, data: NewOrgDto) -> Result<OrgDto> {
+    validate_payload(&data)?;
+
     let owner_id = data.owner_id.clone();
 
     // Owner must exists
@@ -67,7 +102,7 @@ pub async fn create_org_svc(state: &AppState, data: NewOrgDto) -> Result<OrgDto>
     );
 
     // Owner must not be a superuser
-    let superuser = state.db.superusers.get(owner_id).await?;
+    let superuser = state.db.superusers.get(owner_id.clone()).await?;
 
     ensure!(
         superuser.is_none(),
@@ -76,9 +111,32 @@ pub async fn create_org_svc(state: &AppState, data: NewOrgDto) -> Result<OrgDto>
         }
     );
 
-    state.db.orgs.create(data).await
+    // The org and its owner membership are created together
+    let audit = AuditCtx::current();
+    let uow = state.db.begin().await?;
+    let created = async {
+        let org = uow.orgs.create(&audit, data).await?;
+        uow.org_members
+            .create(
+                &audit,
+                org.id.clone(),
+                NewOrgMemberDto {
+                    user_id: owner_id,
+                    roles: vec!["OrgAdmin".to_string()],
+                    status: "active".to_string(),
+                },
+            )
+            .await?;
+        Ok(org)
+    };
+    let result = created.await;
+    let org = uow.finish(result).await?;
+    publish_change(state, EntityChangeDto::org(ChangeAction::Created, &org.id));
+
+    Ok(org)
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn create_org_web_svc(state: &AppState, form: NewOrgFormData) -> Result<OrgDto> {
     let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
     ensure!(csrf_result == "new_org", CsrfTokenSnafu);
@@ -93,13 +151,88 @@ pub async fn create_org_web_svc(state: &AppState, form: NewOrgFormData) -> Resul
     .await
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn get_org_svc(state: &AppState, id: &str) -> Result<Option<OrgDto>> {
-    state.db.orgs.get(id.to_string()).await
+    let org = state.db.orgs.get(id.to_string()).await?;
+    with_pending_transfer(state, org).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn get_org_scoped_svc(
+    state: &AppState,
+    id: &str,
+    scope: DeletedScope,
+) -> Result<Option<OrgDto>> {
+    let org = state.db.orgs.get_scoped(id.to_string(), scope).await?;
+    with_pending_transfer(state, org).await
+}
+
+/// Attaches the ownership transfer waiting for the new owner, if any
+async fn with_pending_transfer(state: &AppState, org: Option<OrgDto>) -> Result<Option<OrgDto>> {
+    let Some(mut org) = org else {
+        return Ok(None);
+    };
+
+    let now = chrono::Utc::now().timestamp_millis();
+    org.pending_transfer = state
+        .db
+        .org_owner_transfers
+        .find_pending(org.id.clone(), now)
+        .await?;
+
+    Ok(Some(org))
 }
 
+/// Brings back a soft deleted org.
+///
+/// Memberships are removed on delete, the owner is added back as org admin
+/// when still active so the org is manageable again.
+#[instrument(level = "debug", skip_all)]
+pub async fn restore_org_svc(state: &AppState, id: &str) -> Result<OrgDto> {
+    let Some(org) = get_org_scoped_svc(state, id, DeletedScope::OnlyDeleted).await? else {
+        return Err(Error::OrgNotFound);
+    };
+
+    let audit = AuditCtx::current();
+    state.db.orgs.restore(&audit, id.to_string()).await?;
+
+    if let Some(owner_id) = org.owner_id
+        && state.db.users.get(owner_id.clone()).await?.is_some()
+    {
+        state
+            .db
+            .orgs
+            .update_owner(&audit, id.to_string(), owner_id)
+            .await?;
+        // A transfer asked for earlier no longer matches the owner
+        state
+            .db
+            .org_owner_transfers
+            .cancel_for_org(id.to_string(), chrono::Utc::now().timestamp_millis())
+            .await?;
+        invalidate_suggestions(state);
+        refresh_org_counters(&state.db, id).await;
+    }
+
+    publish_change(state, EntityChangeDto::org(ChangeAction::Updated, id));
+    info!(org_id = id, "org.restored");
+
+    let Some(restored) = get_org_svc(state, id).await? else {
+        return Err(Error::OrgNotFound);
+    };
+
+    Ok(restored)
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn update_org_svc(state: &AppState, id: &str, data: UpdateOrgDto) -> Result<bool> {
-    // Owner must exists and must be a member of the org
-    if let Some(owner_id) = data.owner_id.clone() {
+    validate_payload(&data)?;
+
+    let audit = AuditCtx::current();
+    let mut data = data;
+    let mut owner_updated = false;
+
+    if let Some(owner_id) = data.owner_id.take() {
         // User must exists
         let owner = state.db.users.get(owner_id.clone()).await?;
 
@@ -110,22 +243,8 @@ pub async fn update_org_svc(state: &AppState, id: &str, data: UpdateOrgDto) -> R
             }
         );
 
-        // Owner must be an existing member of the org
-        let member = state
-            .db
-            .org_members
-            .find_member(id.to_string(), owner_id.clone())
-            .await?;
-
-        ensure!(
-            member.is_some(),
-            ValidationSnafu {
-                msg: "Owner must be a member of the org".to_string()
-            }
-        );
-
         // Owner must not be a superuser
-        let superuser = state.db.superusers.get(owner_id).await?;
+        let superuser = state.db.superusers.get(owner_id.clone()).await?;
 
         ensure!(
             superuser.is_none(),
@@ -133,11 +252,75 @@ pub async fn update_org_svc(state: &AppState, id: &str, data: UpdateOrgDto) -> R
                 msg: "Owner cannot be a superuser".to_string()
             }
         );
+
+        // Owner membership is created or promoted to OrgAdmin along with the transfer
+        owner_updated = state
+            .db
+            .orgs
+            .update_owner(&audit, id.to_string(), owner_id)
+            .await?;
+        // A transfer asked for earlier no longer matches the owner
+        state
+            .db
+            .org_owner_transfers
+            .cancel_for_org(id.to_string(), chrono::Utc::now().timestamp_millis())
+            .await?;
+        invalidate_suggestions(state);
+        refresh_org_counters(&state.db, id).await;
     }
 
-    state.db.orgs.update(id.to_string(), data).await
+    let updated = state.db.orgs.update(&audit, id.to_string(), data).await?;
+
+    if owner_updated || updated {
+        publish_change(state, EntityChangeDto::org(ChangeAction::Updated, id));
+    }
+
+    Ok(owner_updated || updated)
 }
 
+/// Active orgs with the requested ids, ids not found are listed apart
+#[instrument(level = "debug", skip_all)]
+pub async fn batch_get_orgs_svc(
+    state: &AppState,
+    data: BatchGetDto,
+) -> Result<BatchGetResultDto<OrgDto>> {
+    validate_payload(&data)?;
+
+    let found = state.db.orgs.list_by_ids(&data.ids).await?;
+    Ok(BatchGetResultDto::new(&data.ids, found, |org| {
+        org.id.as_str()
+    }))
+}
+
+/// Updates the org and reports which fields actually changed.
+///
+/// With a version, the update only goes through while the org is still at it.
+#[instrument(level = "debug", skip_all)]
+pub async fn update_org_tracked_svc(
+    state: &AppState,
+    id: &str,
+    data: UpdateOrgDto,
+    version: Option<i64>,
+) -> Result<UpdatedDto<OrgDto>> {
+    let before = get_org_svc(state, id).await?.context(OrgNotFoundSnafu)?;
+
+    if let Some(version) = version {
+        validate_payload(&data)?;
+        let claimed = state
+            .db
+            .orgs
+            .claim_version(&AuditCtx::current(), id.to_string(), version)
+            .await?;
+        ensure!(claimed, VersionMismatchSnafu);
+    }
+
+    update_org_svc(state, id, data).await?;
+
+    let after = get_org_svc(state, id).await?.context(OrgNotFoundSnafu)?;
+    Ok(UpdatedDto::new(&before, after))
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn update_org_web_svc(
     state: &AppState,
     org_id: &str,
@@ -165,66 +348,160 @@ pub async fn update_org_web_svc(
     Ok(updated_org)
 }
 
-pub async fn update_org_owner_web_svc(
-    state: &AppState,
-    org_id: &str,
-    form: UpdateOrgOwnerFormData,
-) -> Result<OrgDto> {
-    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
-    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+/// What happened to the orgs of a deleted owner
+#[derive(Default)]
+pub struct OwnedOrgsCleanup {
+    /// Org id and the admin who took it over
+    pub reassigned: Vec<(String, String)>,
+    /// Org id and the users who were its members
+    pub deleted: Vec<(String, Vec<String>)>,
+}
 
-    let body = UpdateOrgDto {
-        name: None,
-        owner_id: Some(form.owner_id),
-        status: None,
-    };
+/// Soft deletes the org with its memberships, app links and pending oauth
+/// codes. Returns the users who were members, None when the org is gone.
+///
+/// Runs inside the unit of work of the caller.
+pub async fn delete_org_rows(
+    db: &DbMapper,
+    audit: &AuditCtx,
+    id: &str,
+) -> Result<Option<Vec<String>>> {
+    if !db.orgs.delete(audit, id.to_string()).await? {
+        return Ok(None);
+    }
 
-    update_org_svc(state, org_id, body).await?;
+    let user_ids = db.org_members.delete_for_org(id.to_string()).await?;
+    db.org_apps.delete_for_org(audit, id.to_string()).await?;
+    db.oauth_codes.delete_for_org(id.to_string()).await?;
+    db.org_owner_transfers
+        .cancel_for_org(id.to_string(), chrono::Utc::now().timestamp_millis())
+        .await?;
+    db.counters.refresh_org(id.to_string()).await?;
 
-    // Fetch the updated org to return
-    let Some(updated_org) = get_org_svc(state, org_id).await? else {
-        return Err(Error::OrgNotFound);
-    };
+    Ok(Some(user_ids))
+}
 
-    Ok(updated_org)
+/// Hands each org owned by the user to its longest standing active admin.
+///
+/// Orgs without another admin are deleted along with everything under
+/// them, except for the superuser org which is left as is.
+/// Runs inside the unit of work of the caller.
+pub async fn release_owned_orgs(
+    db: &DbMapper,
+    audit: &AuditCtx,
+    owner_id: &str,
+) -> Result<OwnedOrgsCleanup> {
+    let mut cleanup = OwnedOrgsCleanup::default();
+    let superuser = db.superusers.get(owner_id.to_string()).await?.is_some();
+
+    for org_id in db.orgs.list_owned_ids(owner_id.to_string()).await? {
+        let successor = db
+            .org_members
+            .find_successor(org_id.clone(), owner_id.to_string())
+            .await?;
+
+        match successor {
+            Some(successor) => {
+                db.orgs
+                    .set_owner(audit, org_id.clone(), successor.clone())
+                    .await?;
+                db.org_owner_transfers
+                    .cancel_for_org(org_id.clone(), chrono::Utc::now().timestamp_millis())
+                    .await?;
+                cleanup.reassigned.push((org_id, successor));
+            }
+            None if superuser => {}
+            None => {
+                if let Some(user_ids) = delete_org_rows(db, audit, &org_id).await? {
+                    cleanup.deleted.push((org_id, user_ids));
+                }
+            }
+        }
+    }
+
+    Ok(cleanup)
+}
+
+/// Drops the cached state of a deleted org and its former members
+pub async fn publish_org_deleted(state: &AppState, id: &str, user_ids: &[String]) {
+    for user_id in user_ids.iter() {
+        invalidate_auth_cache(state, user_id).await;
+        publish_change(
+            state,
+            EntityChangeDto::org_member(ChangeAction::Deleted, id, user_id),
+        );
+    }
+    invalidate_suggestions(state);
+    publish_change(state, EntityChangeDto::org(ChangeAction::Deleted, id));
+    info!(org_id = id, members = user_ids.len(), "org.deleted");
 }
 
+/// Deletes the org with its memberships, app links and pending oauth codes
+#[instrument(level = "debug", skip_all)]
 pub async fn delete_org_svc(state: &AppState, id: &str) -> Result<bool> {
-    // Ensure no members under the org
-    let member_count = state
-        .db
-        .org_members
-        .listing_count(id.to_string(), ListOrgMembersParamsDto::default())
-        .await?;
+    ensure_org_deletable_svc(state, id).await?;
 
-    ensure!(
-        member_count == 0,
-        ForbiddenSnafu {
-            msg: "Cannot delete org with existing members".to_string()
-        }
-    );
+    let audit = AuditCtx::current();
+    let uow = state.db.begin().await?;
+    let work = delete_org_rows(&uow, &audit, id).await;
+    let Some(user_ids) = uow.finish(work).await? else {
+        return Ok(false);
+    };
 
-    // Ensure no apps under the org
-    let app_count = state
-        .db
-        .org_apps
-        .listing_count(id.to_string(), ListOrgAppsParamsDto::default())
-        .await?;
+    publish_org_deleted(state, id, &user_ids).await;
 
-    ensure!(
-        app_count == 0,
-        ForbiddenSnafu {
-            msg: "Cannot delete org with existing apps".to_string()
-        }
-    );
+    Ok(true)
+}
+
+/// Keeps the superuser org from being deleted
+#[instrument(level = "debug", skip_all)]
+pub async fn ensure_org_deletable_svc(state: &AppState, id: &str) -> Result<()> {
+    let Some(org) = get_org_svc(state, id).await? else {
+        return Err(Error::OrgNotFound);
+    };
+
+    // The org created during setup holds the superuser memberships
+    if let Some(owner_id) = org.owner_id {
+        let superuser = state.db.superusers.get(owner_id).await?;
+
+        ensure!(
+            superuser.is_none(),
+            ForbiddenSnafu {
+                msg: "Cannot delete the superuser org".to_string()
+            }
+        );
+    }
 
-    state.db.orgs.delete(id.to_string()).await
+    Ok(())
 }
 
-pub async fn delete_org_web_svc(state: &AppState, org_id: &str, csrf_token: &str) -> Result<()> {
+#[instrument(level = "debug", skip_all)]
+pub async fn delete_org_web_svc(
+    state: &AppState,
+    actor_id: &str,
+    org_id: &str,
+    csrf_token: &str,
+) -> Result<()> {
     let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
     ensure!(csrf_result == org_id, CsrfTokenSnafu);
 
+    if state.config.approvals.two_person_rule {
+        let member_count = state
+            .db
+            .org_members
+            .listing_count(org_id.to_string(), ListOrgMembersParamsDto::default())
+            .await?;
+
+        if member_count > 0 {
+            ensure_org_deletable_svc(state, org_id).await?;
+
+            let approval =
+                request_approval_svc(state, ApprovalAction::DeleteOrg, org_id, actor_id).await?;
+
+            return Err(approval_required_error(&approval));
+        }
+    }
+
     delete_org_svc(state, org_id).await?;
 
     Ok(())
@@ -232,15 +509,15 @@ pub async fn delete_org_web_svc(state: &AppState, org_id: &str, csrf_token: &str
 
 #[cfg(test)]
 mod tests {
-    use crate::dto::NewOrgAppDto;
-    use crate::services::org_apps::create_org_app_svc;
+    use crate::ctx::AuditCtx;
+    use crate::dto::{NewOauthCodeDto, NewOrgMemberDto, Role, UpdateOrgDto};
     use crate::services::token::create_csrf_token_svc;
     use crate::test::TestCtx;
     use crate::utils::{IdPrefix, generate_id};
 
     use super::{
-        NewOrgFormData, UpdateOrgFormData, UpdateOrgOwnerFormData, create_org_web_svc,
-        delete_org_web_svc, get_org_svc, update_org_owner_web_svc, update_org_web_svc,
+        NewOrgFormData, UpdateOrgFormData, create_org_web_svc, delete_org_web_svc, get_org_svc,
+        update_org_svc, update_org_web_svc,
     };
 
     #[tokio::test]
@@ -273,7 +550,18 @@ mod tests {
 
         assert_eq!(fetched.id, created.id);
         assert_eq!(fetched.name, "Platform Org");
-        assert_eq!(fetched.owner_id, Some(owner.id));
+        assert_eq!(fetched.owner_id, Some(owner.id.clone()));
+
+        let membership = ctx
+            .state
+            .db
+            .org_members
+            .find_member(created.id.clone(), owner.id)
+            .await
+            .expect("query should pass")
+            .expect("owner should be a member");
+        assert_eq!(membership.roles, vec![Role::OrgAdmin]);
+        assert_eq!(membership.status, "active");
     }
 
     #[tokio::test]
@@ -434,7 +722,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn update_org_owner_web_svc_rejects_new_owner_not_found() {
+    async fn update_org_svc_owner_rejects_new_owner_not_found() {
         let ctx = TestCtx::new("orgs_update_owner_missing")
             .await
             .expect("test ctx");
@@ -449,27 +737,24 @@ mod tests {
             .expect("auth fixture");
         let missing_owner = generate_id(IdPrefix::User);
 
-        let csrf = create_csrf_token_svc(&fixture.org.id, &ctx.state.config.jwt_secret)
-            .expect("csrf token should be generated");
-
-        let result = update_org_owner_web_svc(
+        let result = update_org_svc(
             &ctx.state,
             &fixture.org.id,
-            UpdateOrgOwnerFormData {
-                token: csrf,
-                owner_id: missing_owner,
-                owner_email: "missing@example.com".to_string(),
+            UpdateOrgDto {
+                name: None,
+                owner_id: Some(missing_owner),
+                status: None,
             },
         )
         .await;
 
         assert!(result.is_err(), "missing owner should fail");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "Owner does not exists");
     }
 
     #[tokio::test]
-    async fn update_org_owner_web_svc_rejects_new_owner_not_member() {
+    async fn update_org_svc_owner_adds_new_owner_as_member() {
         let ctx = TestCtx::new("orgs_update_owner_not_member")
             .await
             .expect("test ctx");
@@ -491,27 +776,100 @@ mod tests {
             .await
             .expect("external user");
 
-        let csrf = create_csrf_token_svc(&fixture.org.id, &ctx.state.config.jwt_secret)
-            .expect("csrf token should be generated");
+        update_org_svc(
+            &ctx.state,
+            &fixture.org.id,
+            UpdateOrgDto {
+                name: None,
+                owner_id: Some(external_user.id.clone()),
+                status: None,
+            },
+        )
+        .await
+        .expect("owner should be updated");
+
+        let updated = get_org_svc(&ctx.state, &fixture.org.id)
+            .await
+            .expect("org should load")
+            .expect("org should exist");
+        assert_eq!(updated.owner_id, Some(external_user.id.clone()));
+
+        let membership = ctx
+            .state
+            .db
+            .org_members
+            .find_member(fixture.org.id.clone(), external_user.id)
+            .await
+            .expect("query should pass")
+            .expect("new owner should be a member");
+        assert_eq!(membership.roles, vec![Role::OrgAdmin]);
+        assert_eq!(membership.status, "active");
+    }
+
+    #[tokio::test]
+    async fn update_org_svc_owner_promotes_existing_member() {
+        let ctx = TestCtx::new("orgs_update_owner_promote")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Owner User",
+                "org.owner.promote.current@example.com",
+                "password123",
+                "Original Org",
+            )
+            .await
+            .expect("auth fixture");
+        let viewer = ctx
+            .seed_user_with_password(
+                "Viewer User",
+                "org.owner.promote@example.com",
+                "password123",
+            )
+            .await
+            .expect("viewer user");
+
+        ctx.state
+            .db
+            .org_members
+            .create(
+                &AuditCtx::default(),
+                fixture.org.id.clone(),
+                NewOrgMemberDto {
+                    user_id: viewer.id.clone(),
+                    roles: vec!["OrgViewer".to_string()],
+                    status: "inactive".to_string(),
+                },
+            )
+            .await
+            .expect("viewer should be member");
 
-        let result = update_org_owner_web_svc(
+        update_org_svc(
             &ctx.state,
             &fixture.org.id,
-            UpdateOrgOwnerFormData {
-                token: csrf,
-                owner_id: external_user.id,
-                owner_email: external_user.email,
+            UpdateOrgDto {
+                name: None,
+                owner_id: Some(viewer.id.clone()),
+                status: None,
             },
         )
-        .await;
+        .await
+        .expect("owner should be updated");
 
-        assert!(result.is_err(), "non-member owner should fail");
-        let err = result.err().expect("error should exist");
-        assert_eq!(err.to_string(), "Owner must be a member of the org");
+        let membership = ctx
+            .state
+            .db
+            .org_members
+            .find_member(fixture.org.id.clone(), viewer.id)
+            .await
+            .expect("query should pass")
+            .expect("new owner should be a member");
+        assert_eq!(membership.roles, vec![Role::OrgViewer, Role::OrgAdmin]);
+        assert_eq!(membership.status, "active");
     }
 
     #[tokio::test]
-    async fn update_org_owner_web_svc_rejects_new_owner_superuser() {
+    async fn update_org_svc_owner_rejects_new_owner_superuser() {
         let ctx = TestCtx::new("orgs_update_owner_superuser")
             .await
             .expect("test ctx");
@@ -538,8 +896,9 @@ mod tests {
             .db
             .org_members
             .create(
+                &AuditCtx::default(),
                 fixture.org.id.clone(),
-                crate::dto::NewOrgMemberDto {
+                NewOrgMemberDto {
                     user_id: candidate_owner.id.clone(),
                     roles: vec!["OrgAdmin".to_string()],
                     status: "active".to_string(),
@@ -557,22 +916,19 @@ mod tests {
             .await
             .expect("should create superuser");
 
-        let csrf = create_csrf_token_svc(&fixture.org.id, &ctx.state.config.jwt_secret)
-            .expect("csrf token should be generated");
-
-        let result = update_org_owner_web_svc(
+        let result = update_org_svc(
             &ctx.state,
             &fixture.org.id,
-            UpdateOrgOwnerFormData {
-                token: csrf,
-                owner_id: candidate_owner.id,
-                owner_email: candidate_owner.email,
+            UpdateOrgDto {
+                name: None,
+                owner_id: Some(candidate_owner.id),
+                status: None,
             },
         )
         .await;
 
         assert!(result.is_err(), "superuser owner should fail");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "Owner cannot be a superuser");
     }
 
@@ -589,24 +945,9 @@ mod tests {
             .await
             .expect("auth fixture");
 
-        let owner_member = ctx
-            .state
-            .db
-            .org_members
-            .find_member(fixture.org.id.clone(), fixture.user.id.clone())
-            .await
-            .expect("membership query should pass")
-            .expect("owner membership should exist");
-        ctx.state
-            .db
-            .org_members
-            .delete(owner_member.id)
-            .await
-            .expect("owner membership should be removed");
-
         let csrf = create_csrf_token_svc(&fixture.org.id, &ctx.state.config.jwt_secret)
             .expect("csrf token should be generated");
-        delete_org_web_svc(&ctx.state, &fixture.org.id, &csrf)
+        delete_org_web_svc(&ctx.state, &fixture.user.id, &fixture.org.id, &csrf)
             .await
             .expect("org should be deleted");
 
@@ -617,82 +958,69 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn delete_org_web_svc_rejects_when_org_has_members() {
-        let ctx = TestCtx::new("orgs_delete_has_members")
-            .await
-            .expect("test ctx");
+    async fn delete_org_web_svc_removes_members_app_links_and_codes() {
+        let ctx = TestCtx::new("orgs_delete_cascade").await.expect("test ctx");
         let fixture = ctx
-            .seed_auth_fixture(
+            .seed_oauth_fixture(
                 "Owner User",
-                "org.delete.members.owner@example.com",
+                "org.delete.cascade@example.com",
                 "password123",
                 "Disposable Org",
+                "Linked App",
+                "https://linked.example.com/callback",
+                true,
             )
             .await
-            .expect("auth fixture");
-
-        let csrf = create_csrf_token_svc(&fixture.org.id, &ctx.state.config.jwt_secret)
-            .expect("csrf token should be generated");
-        let result = delete_org_web_svc(&ctx.state, &fixture.org.id, &csrf).await;
-
-        assert!(result.is_err(), "org with members should fail to delete");
-        let err = result.err().expect("error should exist");
-        assert_eq!(err.to_string(), "Cannot delete org with existing members");
-    }
+            .expect("oauth fixture");
+        let org_id = fixture.auth.org.id.clone();
 
-    #[tokio::test]
-    async fn delete_org_web_svc_rejects_when_org_has_linked_apps() {
-        let ctx = TestCtx::new("orgs_delete_has_apps")
+        ctx.state
+            .db
+            .oauth_codes
+            .create(NewOauthCodeDto {
+                code: generate_id(IdPrefix::OauthCode),
+                state: "state-1".to_string(),
+                redirect_uri: "https://linked.example.com/callback".to_string(),
+                scope: "vault".to_string(),
+                app_id: fixture.app.id.clone(),
+                org_id: org_id.clone(),
+                user_id: fixture.auth.user.id.clone(),
+                nonce: None,
+            })
             .await
-            .expect("test ctx");
-        let fixture = ctx
-            .seed_auth_fixture(
-                "Owner User",
-                "org.delete.apps.owner@example.com",
-                "password123",
-                "Disposable Org",
-            )
+            .expect("oauth code should be inserted");
+
+        let csrf = create_csrf_token_svc(&org_id, &ctx.state.config.jwt_secret)
+            .expect("csrf token should be generated");
+        delete_org_web_svc(&ctx.state, &fixture.auth.user.id, &org_id, &csrf)
             .await
-            .expect("auth fixture");
+            .expect("org should be deleted");
 
-        let owner_member = ctx
+        let member = ctx
             .state
             .db
             .org_members
-            .find_member(fixture.org.id.clone(), fixture.user.id.clone())
+            .find_member(org_id.clone(), fixture.auth.user.id.clone())
             .await
-            .expect("membership query should pass")
-            .expect("owner membership should exist");
-        ctx.state
+            .expect("membership query should pass");
+        assert!(member.is_none(), "memberships are removed");
+
+        let link = ctx
+            .state
             .db
-            .org_members
-            .delete(owner_member.id)
+            .org_apps
+            .find_app(org_id.clone(), fixture.app.id.clone())
             .await
-            .expect("owner membership should be removed");
+            .expect("org app query should pass");
+        assert!(link.is_none(), "app links are removed");
 
-        let app = ctx
-            .seed_app("Linked App", "https://linked.example.com/callback")
+        let codes = ctx
+            .state
+            .db
+            .oauth_codes
+            .list_by_user(fixture.auth.user.id.clone())
             .await
-            .expect("app should be created");
-        create_org_app_svc(
-            &ctx.state,
-            &fixture.org.id,
-            NewOrgAppDto {
-                app_id: app.id.clone(),
-            },
-        )
-        .await
-        .expect("org app should be created");
-
-        let csrf = create_csrf_token_svc(&fixture.org.id, &ctx.state.config.jwt_secret)
-            .expect("csrf token should be generated");
-        let result = delete_org_web_svc(&ctx.state, &fixture.org.id, &csrf).await;
-
-        assert!(
-            result.is_err(),
-            "org with linked apps should fail to delete"
-        );
-        let err = result.err().expect("error should exist");
-        assert_eq!(err.to_string(), "Cannot delete org with existing apps");
+            .expect("oauth code query should pass");
+        assert!(codes.is_empty(), "pending codes are dropped");
     }
 }
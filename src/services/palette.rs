@@ -0,0 +1,224 @@
+use tracing::instrument;
+
+use crate::Result;
+use crate::dto::{
+    Actor, ListAppsParamsDto, ListOrgsParamsDto, ListUsersParamsDto, PaletteItemDto, Permission,
+};
+use crate::run::AppState;
+use crate::services::apps::list_apps_svc;
+use crate::services::orgs::list_orgs_svc;
+use crate::services::users::list_users_svc;
+
+const PALETTE_ENTITY_LIMIT: i32 = 5;
+
+struct QuickAction {
+    label: &'static str,
+    description: &'static str,
+    url: &'static str,
+    permission: Permission,
+}
+
+const QUICK_ACTIONS: &[QuickAction] = &[
+    QuickAction {
+        label: "New user",
+        description: "Create a new user account",
+        url: "/users/new",
+        permission: Permission::UsersCreate,
+    },
+    QuickAction {
+        label: "Users",
+        description: "Browse all users",
+        url: "/users",
+        permission: Permission::UsersList,
+    },
+    QuickAction {
+        label: "New org",
+        description: "Create a new org",
+        url: "/orgs/new",
+        permission: Permission::OrgsCreate,
+    },
+    QuickAction {
+        label: "Orgs",
+        description: "Browse all orgs",
+        url: "/orgs",
+        permission: Permission::OrgsList,
+    },
+    QuickAction {
+        label: "New app",
+        description: "Register a new OAuth app",
+        url: "/apps/new",
+        permission: Permission::AppsCreate,
+    },
+    QuickAction {
+        label: "Apps",
+        description: "Browse all apps",
+        url: "/apps",
+        permission: Permission::AppsList,
+    },
+    QuickAction {
+        label: "Profile",
+        description: "View your profile",
+        url: "/profile",
+        permission: Permission::UsersView,
+    },
+];
+
+/// Quick actions available to the actor that match the keyword
+pub fn palette_actions(actor: &Actor, keyword: &str) -> Vec<PaletteItemDto> {
+    let keyword = keyword.trim().to_lowercase();
+
+    QUICK_ACTIONS
+        .iter()
+        .filter(|action| actor.has_permissions(std::slice::from_ref(&action.permission)))
+        .filter(|action| {
+            keyword.is_empty()
+                || action.label.to_lowercase().contains(&keyword)
+                || action.description.to_lowercase().contains(&keyword)
+        })
+        .map(|action| PaletteItemDto {
+            kind: "action".to_string(),
+            label: action.label.to_string(),
+            description: action.description.to_string(),
+            url: action.url.to_string(),
+        })
+        .collect()
+}
+
+/// Aggregates quick actions and entity jumps for the command palette
+#[instrument(level = "debug", skip_all)]
+pub async fn search_palette_svc(
+    state: &AppState,
+    actor: &Actor,
+    keyword: &str,
+) -> Result<Vec<PaletteItemDto>> {
+    let mut items = palette_actions(actor, keyword);
+
+    let keyword = keyword.trim();
+    if keyword.is_empty() {
+        return Ok(items);
+    }
+
+    if actor.has_permissions(&[Permission::UsersList, Permission::UsersView]) {
+        let users = list_users_svc(
+            state,
+            ListUsersParamsDto {
+                page: Some(1),
+                per_page: Some(PALETTE_ENTITY_LIMIT),
+                keyword: Some(keyword.to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        items.extend(users.data.into_iter().map(|user| PaletteItemDto {
+            kind: "user".to_string(),
+            label: user.email,
+            description: user.name,
+            url: format!("/users/{}", user.id),
+        }));
+    }
+
+    if actor.has_permissions(&[Permission::OrgsList, Permission::OrgsView]) {
+        let orgs = list_orgs_svc(
+            state,
+            ListOrgsParamsDto {
+                page: Some(1),
+                per_page: Some(PALETTE_ENTITY_LIMIT),
+                keyword: Some(keyword.to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        items.extend(orgs.data.into_iter().map(|org| PaletteItemDto {
+            kind: "org".to_string(),
+            label: org.name,
+            description: org.owner_email.unwrap_or_default(),
+            url: format!("/orgs/{}", org.id),
+        }));
+    }
+
+    if actor.has_permissions(&[Permission::AppsList, Permission::AppsView]) {
+        let apps = list_apps_svc(
+            state,
+            ListAppsParamsDto {
+                page: Some(1),
+                per_page: Some(PALETTE_ENTITY_LIMIT),
+                keyword: Some(keyword.to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        items.extend(apps.data.into_iter().map(|app| PaletteItemDto {
+            kind: "app".to_string(),
+            label: app.name,
+            description: app.redirect_uri,
+            url: format!("/apps/{}", app.id),
+        }));
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::{Actor, ActorPayloadDto, Role, Scope};
+    use crate::test::TestCtx;
+
+    use super::{palette_actions, search_palette_svc};
+
+    #[tokio::test]
+    async fn search_palette_svc_returns_actions_and_entities() {
+        let ctx = TestCtx::new("palette_search").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Palette User",
+                "palette.user@example.com",
+                "password123",
+                "Palette Org",
+            )
+            .await
+            .expect("auth fixture");
+
+        let actor = Actor::new(
+            ActorPayloadDto {
+                id: fixture.user.id.clone(),
+                org_id: fixture.org.id.clone(),
+                org_count: 1,
+                roles: vec![Role::Superuser],
+                scopes: vec![Scope::Auth],
+                grant_id: None,
+                proof_key_id: None,
+                permission_mask: None,
+                issued_at: 0,
+                session_id: None,
+                region: None,
+                home_region: None,
+                token_id: None,
+                org_app_id: None,
+                impersonator_id: None,
+                impersonation_id: None,
+            },
+            fixture.user.clone(),
+        );
+
+        let items = search_palette_svc(&ctx.state, &actor, "palette")
+            .await
+            .expect("search should pass");
+
+        assert!(items.iter().any(|i| i.kind == "user"));
+        assert!(items.iter().any(|i| i.kind == "org"));
+
+        let items = search_palette_svc(&ctx.state, &actor, "")
+            .await
+            .expect("search should pass");
+        assert!(items.iter().all(|i| i.kind == "action"));
+    }
+
+    #[test]
+    fn palette_actions_are_filtered_by_permission() {
+        let actor = Actor::default();
+        assert!(palette_actions(&actor, "").is_empty());
+    }
+}
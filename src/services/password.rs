@@ -2,9 +2,16 @@ use argon2::{
     Argon2,
     password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
 };
+use ring::rand::{SecureRandom, SystemRandom};
 use snafu::{OptionExt, ensure};
+use tracing::{error, info, instrument};
 
+use crate::db::DbMapper;
+use crate::dto::{TokenRevocationDto, UserDto};
+use crate::error::UserNotFoundSnafu;
 use crate::run::AppState;
+use crate::services::user_notifications::notify_password_changed_svc;
+use crate::validators::validate_payload;
 use crate::{Result, services::users::ChangeCurrentPasswordFormData};
 use crate::{
     dto::{ChangeCurrentPasswordDto, NewPasswordDto},
@@ -17,23 +24,40 @@ use crate::{
     services::users::ChangePasswordFormData,
 };
 
+/// The password is already changed, a missing notification must not fail it
+async fn notify_password_changed(db: &DbMapper, user_id: &str) {
+    if let Err(e) = notify_password_changed_svc(db, user_id).await {
+        error!("Failed to notify the user: {}", e);
+    }
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn update_password_svc(
     state: &AppState,
     user_id: &str,
     data: NewPasswordDto,
 ) -> Result<bool> {
+    validate_payload(&data)?;
+
     let hashed_password = hash_password(&data.password)?;
     let updated_data = NewPasswordDto {
         password: hashed_password,
     };
 
-    state
+    let updated = state
         .db
         .passwords
         .update(user_id.to_string(), updated_data)
-        .await
+        .await?;
+
+    if updated {
+        notify_password_changed(&state.db, user_id).await;
+    }
+
+    Ok(updated)
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn change_user_password_web_svc(
     state: &AppState,
     user_id: &str,
@@ -58,11 +82,14 @@ pub async fn change_user_password_web_svc(
     Ok(())
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn change_current_password_svc(
     state: &AppState,
     user_id: &str,
     data: ChangeCurrentPasswordDto,
 ) -> Result<bool> {
+    validate_payload(&data)?;
+
     // Validate current password
     let password = state
         .db
@@ -89,13 +116,20 @@ pub async fn change_current_password_svc(
         password: hashed_password,
     };
 
-    state
+    let updated = state
         .db
         .passwords
         .update(user_id.to_string(), update_data)
-        .await
+        .await?;
+
+    if updated {
+        notify_password_changed(&state.db, user_id).await;
+    }
+
+    Ok(updated)
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn change_user_current_password_web_svc(
     state: &AppState,
     user_id: &str,
@@ -121,6 +155,63 @@ pub async fn change_user_current_password_web_svc(
     Ok(())
 }
 
+/// Sets a new password from the command line and signs the user out everywhere.
+///
+/// Users who never had a password, such as imported ones, get one. Deactivated
+/// users stay inactive.
+#[instrument(level = "debug", skip_all)]
+pub async fn reset_user_password_svc(
+    db: &DbMapper,
+    email: &str,
+    password: &str,
+) -> Result<UserDto> {
+    let data = NewPasswordDto {
+        password: password.to_string(),
+    };
+    validate_payload(&data)?;
+
+    let user = db
+        .users
+        .find_by_email(email.trim().to_string())
+        .await?
+        .context(UserNotFoundSnafu)?;
+
+    let hashed = NewPasswordDto {
+        password: hash_password(&data.password)?,
+    };
+
+    let updated = db.passwords.update(user.id.clone(), hashed.clone()).await?;
+    if !updated {
+        db.passwords.create(user.id.clone(), hashed).await?;
+    }
+
+    db.token_revocations
+        .upsert(TokenRevocationDto {
+            user_id: user.id.clone(),
+            revoked_at: chrono::Utc::now().timestamp_millis(),
+            revoked_by: None,
+        })
+        .await?;
+
+    notify_password_changed(db, &user.id).await;
+
+    info!(user_id = user.id.as_str(), "user.password_reset_from_cli");
+
+    Ok(user)
+}
+
+/// Random password for accounts set up from the command line
+pub fn generate_password() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .ok()
+        .context(WhateverSnafu {
+            msg: "Unable to generate password".to_string(),
+        })?;
+    Ok(hex::encode(bytes))
+}
+
 pub fn hash_password(password: &str) -> Result<String> {
     let pwd = password.as_bytes();
     let salt = SaltString::generate(&mut OsRng);
@@ -151,6 +242,7 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ctx::AuditCtx;
     use crate::services::token::create_csrf_token_svc;
     use crate::test::TestCtx;
 
@@ -158,7 +250,7 @@ mod tests {
     fn test_hash_password() {
         let password = "password";
         let hash = hash_password(password).unwrap();
-        assert!(hash.len() > 0);
+        assert!(!hash.is_empty());
     }
 
     #[test]
@@ -166,11 +258,11 @@ mod tests {
         let password = "password";
         let stored_hash = "$argon2id$v=19$m=19456,t=2,p=1$NxAcor94oNDtRqstYqRvmA$EtLJjVFPFz0hE5QLZ/ydx4Td4slp9GaXuwQX3vQU9Dc";
 
-        let result = verify_password(password, &stored_hash).unwrap();
+        let result = verify_password(password, stored_hash).unwrap();
         assert!(result);
 
         // Try again
-        let result = verify_password(password, &stored_hash).unwrap();
+        let result = verify_password(password, stored_hash).unwrap();
         assert!(result);
     }
 
@@ -269,7 +361,7 @@ mod tests {
         .await;
 
         assert!(result.is_err(), "password mismatch should fail");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "Passwords must match.");
     }
 
@@ -375,7 +467,7 @@ mod tests {
         .await;
 
         assert!(result.is_err(), "password mismatch should fail");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "Passwords must match.");
     }
 
@@ -388,10 +480,13 @@ mod tests {
             .state
             .db
             .users
-            .create(crate::dto::NewUserDto {
-                name: "No Password User".to_string(),
-                email: "password.current.none@example.com".to_string(),
-            })
+            .create(
+                &AuditCtx::default(),
+                crate::dto::NewUserDto {
+                    name: "No Password User".to_string(),
+                    email: "password.current.none@example.com".to_string(),
+                },
+            )
             .await
             .expect("user should be created");
 
@@ -411,7 +506,7 @@ mod tests {
         .await;
 
         assert!(result.is_err(), "missing password should fail");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "User has no password set");
     }
 
@@ -445,7 +540,38 @@ mod tests {
         .await;
 
         assert!(result.is_err(), "wrong current password should fail");
-        let err = result.err().expect("error should exist");
+        let err = result.expect_err("error should exist");
         assert_eq!(err.to_string(), "Current password is incorrect");
     }
+
+    #[tokio::test]
+    async fn reset_user_password_replaces_hash_and_revokes_tokens() {
+        let ctx = TestCtx::new("reset_user_password_cli")
+            .await
+            .expect("test ctx");
+        let db = &ctx.state.db;
+        let fixture = ctx
+            .seed_auth_fixture("Reset", "reset@example.com", "password123", "Reset Org")
+            .await
+            .expect("fixture");
+
+        let user = reset_user_password_svc(db, "reset@example.com", "newpassword456")
+            .await
+            .expect("reset password");
+        assert_eq!(user.id, fixture.user.id);
+
+        let password = db
+            .passwords
+            .get(user.id.clone())
+            .await
+            .expect("get password")
+            .expect("password should exist");
+        assert!(verify_password("newpassword456", &password.password).unwrap());
+
+        let revocation = db.token_revocations.find(user.id).await.expect("find");
+        assert!(revocation.is_some());
+
+        let missing = reset_user_password_svc(db, "nobody@example.com", "newpassword456").await;
+        assert!(missing.is_err());
+    }
 }
@@ -0,0 +1,383 @@
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ensure};
+use tracing::{info, instrument};
+
+use crate::dto::{NewPasswordDto, NewPasswordResetDto, PasswordResetDto, UserDto};
+use crate::error::{NotFoundSnafu, ValidationSnafu};
+use crate::run::AppState;
+use crate::services::emails::{EmailBranding, password_reset_email, render_email};
+use crate::services::mailer::queue_email_svc;
+use crate::services::password::{hash_password, update_password_svc};
+use crate::services::recovery::generate_recovery_token;
+use crate::services::revocations::revoke_user_tokens_svc;
+use crate::services::user_emails::find_user_by_login_email_svc;
+use crate::utils::hash_secret;
+use crate::validators::validate_payload;
+use crate::{Error, Result};
+
+pub const PASSWORD_RESET_TTL_MINS: i64 = 30;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ForgotPasswordFormData {
+    pub email: String,
+
+    #[serde(rename = "g-recaptcha-response")]
+    pub g_recaptcha_response: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ResetPasswordFormData {
+    pub token: String,
+    pub password: String,
+    pub confirm_password: String,
+}
+
+/// Freshly issued reset token, the plain secret is never stored
+pub struct IssuedPasswordReset {
+    pub token: String,
+    pub reset: PasswordResetDto,
+}
+
+/// Issues a reset token when the email belongs to an active user.
+///
+/// Returns nothing for unknown or inactive users, callers must respond the
+/// same way either way so the form cannot be used to probe for accounts.
+/// Any older unused token for the same user is burned.
+#[instrument(level = "debug", skip_all)]
+pub async fn request_password_reset_svc(
+    state: &AppState,
+    email: &str,
+) -> Result<Option<IssuedPasswordReset>> {
+    let email = email.trim();
+    ensure!(
+        !email.is_empty(),
+        ValidationSnafu {
+            msg: "Email is required."
+        }
+    );
+
+    let Some(user) = find_user_by_login_email_svc(state, email).await? else {
+        return Ok(None);
+    };
+    if user.status != "active" {
+        return Ok(None);
+    }
+
+    let issued = issue_password_reset(state, &user, email).await?;
+    Ok(Some(issued))
+}
+
+/// Newest reset of the user with the delivery status of its email, for admins
+/// to spot links that never arrived
+#[instrument(level = "debug", skip_all)]
+pub async fn latest_password_reset_svc(
+    state: &AppState,
+    user_id: &str,
+) -> Result<PasswordResetDto> {
+    let reset = state
+        .db
+        .password_resets
+        .find_latest(user_id.to_string())
+        .await?;
+
+    reset.context(NotFoundSnafu {
+        msg: "Password reset not found",
+    })
+}
+
+/// Emails a new reset link to the primary email of a user whose previous link
+/// did not arrive, the previous link stops working
+#[instrument(level = "debug", skip_all)]
+pub async fn resend_password_reset_svc(
+    state: &AppState,
+    user_id: &str,
+) -> Result<PasswordResetDto> {
+    let Some(user) = state.db.users.get(user_id.to_string()).await? else {
+        return Err(Error::UserNotFound);
+    };
+    ensure!(
+        user.status == "active",
+        ValidationSnafu {
+            msg: "Password resets are only sent to active users."
+        }
+    );
+
+    // Only resends, admins do not start resets on behalf of users
+    latest_password_reset_svc(state, &user.id).await?;
+
+    let issued = issue_password_reset(state, &user, &user.email).await?;
+    Ok(issued.reset)
+}
+
+/// Burns the older tokens of the user and emails a new link to `email`
+async fn issue_password_reset(
+    state: &AppState,
+    user: &UserDto,
+    email: &str,
+) -> Result<IssuedPasswordReset> {
+    let now = chrono::Utc::now().timestamp_millis();
+    state
+        .db
+        .password_resets
+        .revoke_for_user(user.id.clone(), now)
+        .await?;
+
+    let token = generate_recovery_token()?;
+    let reset = state
+        .db
+        .password_resets
+        .create(NewPasswordResetDto {
+            user_id: user.id.clone(),
+            token_hash: hash_secret(&token),
+            expires_at: now + PASSWORD_RESET_TTL_MINS * 60 * 1000,
+        })
+        .await?;
+
+    let mut issued = IssuedPasswordReset { token, reset };
+
+    let url = format!(
+        "{}/reset-password?token={}",
+        state.config.mailer.base_url, issued.token
+    );
+    let message = render_email(
+        &EmailBranding::yaas(),
+        None,
+        email,
+        password_reset_email(url, PASSWORD_RESET_TTL_MINS),
+    )?;
+    let queued = queue_email_svc(&state.db, message).await?;
+    state
+        .db
+        .password_resets
+        .set_email(issued.reset.id.clone(), queued.id.clone())
+        .await?;
+    issued.reset.email_id = Some(queued.id);
+    issued.reset.delivery_status = Some(queued.status);
+
+    info!(
+        reset_id = issued.reset.id,
+        user_id = user.id,
+        email = email,
+        expires_at = issued.reset.expires_at,
+        "password_reset.issued"
+    );
+
+    Ok(issued)
+}
+
+/// Sets a new password with a reset token.
+///
+/// Every session and token issued to the user before the reset stops working.
+#[instrument(level = "debug", skip_all)]
+pub async fn reset_password_svc(state: &AppState, form: ResetPasswordFormData) -> Result<()> {
+    ensure!(
+        form.password == form.confirm_password,
+        ValidationSnafu {
+            msg: "Passwords must match."
+        }
+    );
+
+    let new_password = NewPasswordDto {
+        password: form.password,
+    };
+    validate_payload(&new_password)?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let invalid_msg = "Password reset link is invalid or has expired.";
+
+    let reset = state
+        .db
+        .password_resets
+        .find_by_hash(hash_secret(&form.token))
+        .await?
+        .context(ValidationSnafu { msg: invalid_msg })?;
+
+    ensure!(reset.is_usable(now), ValidationSnafu { msg: invalid_msg });
+
+    let user = state
+        .db
+        .users
+        .get(reset.user_id.clone())
+        .await?
+        .context(ValidationSnafu { msg: invalid_msg })?;
+
+    ensure!(
+        user.status == "active",
+        ValidationSnafu { msg: invalid_msg }
+    );
+
+    // Consume first so a racing request cannot use the same token twice
+    let consumed = state
+        .db
+        .password_resets
+        .consume(reset.id.clone(), now)
+        .await?;
+    ensure!(consumed, ValidationSnafu { msg: invalid_msg });
+
+    let updated = update_password_svc(state, &user.id, new_password.clone()).await?;
+    if !updated {
+        let hashed = NewPasswordDto {
+            password: hash_password(&new_password.password)?,
+        };
+        state.db.passwords.create(user.id.clone(), hashed).await?;
+    }
+
+    revoke_user_tokens_svc(state, None, &user.id).await?;
+
+    info!(
+        reset_id = reset.id,
+        user_id = user.id,
+        email = user.email,
+        "password_reset.completed"
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::{DELIVERY_FAILED, DELIVERY_PENDING};
+    use crate::services::mailer::EMAIL_MAX_ATTEMPTS;
+    use crate::services::password::verify_password;
+    use crate::test::TestCtx;
+
+    use super::{
+        ResetPasswordFormData, latest_password_reset_svc, request_password_reset_svc,
+        resend_password_reset_svc, reset_password_svc,
+    };
+
+    fn reset_form(token: &str) -> ResetPasswordFormData {
+        ResetPasswordFormData {
+            token: token.to_string(),
+            password: "brand-new-password".to_string(),
+            confirm_password: "brand-new-password".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn reset_token_sets_password_once() {
+        let ctx = TestCtx::new("password_reset_once").await.expect("test ctx");
+        let user = ctx
+            .seed_user_with_password("Jane", "jane@example.com", "old-password")
+            .await
+            .expect("user");
+
+        let unknown = request_password_reset_svc(&ctx.state, "nobody@example.com")
+            .await
+            .expect("unknown email is not an error");
+        assert!(unknown.is_none());
+
+        let first = request_password_reset_svc(&ctx.state, "jane@example.com")
+            .await
+            .expect("first reset")
+            .expect("issued");
+        let issued = request_password_reset_svc(&ctx.state, "jane@example.com")
+            .await
+            .expect("second reset")
+            .expect("issued");
+        assert_ne!(issued.token, issued.reset.token_hash);
+
+        // Only the newest token works
+        let stale = reset_password_svc(&ctx.state, reset_form(&first.token)).await;
+        assert!(stale.is_err());
+
+        reset_password_svc(&ctx.state, reset_form(&issued.token))
+            .await
+            .expect("reset should succeed");
+
+        let password = ctx
+            .state
+            .db
+            .passwords
+            .get(user.id.clone())
+            .await
+            .expect("password lookup")
+            .expect("password row");
+        assert!(verify_password("brand-new-password", &password.password).expect("verify"));
+
+        let reused = reset_password_svc(&ctx.state, reset_form(&issued.token)).await;
+        assert!(reused.is_err());
+    }
+
+    #[tokio::test]
+    async fn reset_link_is_queued_for_the_requested_email() {
+        let ctx = TestCtx::new("password_reset_email")
+            .await
+            .expect("test ctx");
+        ctx.seed_user_with_password("Jane", "jane@example.com", "old-password")
+            .await
+            .expect("user");
+
+        let issued = request_password_reset_svc(&ctx.state, " jane@example.com ")
+            .await
+            .expect("reset")
+            .expect("issued");
+
+        let email_id = issued.reset.email_id.clone().expect("email tracked");
+        let queued = ctx
+            .state
+            .db
+            .email_outbox
+            .get(email_id)
+            .await
+            .expect("get email")
+            .expect("email queued");
+        assert_eq!(queued.recipient, "jane@example.com");
+        assert!(queued.text_body.contains(&format!(
+            "http://localhost:4000/reset-password?token={}",
+            issued.token
+        )));
+    }
+
+    #[tokio::test]
+    async fn failed_reset_emails_show_until_resent() {
+        let ctx = TestCtx::new("password_reset_resend")
+            .await
+            .expect("test ctx");
+        let user = ctx
+            .seed_user_with_password("Jane", "jane@example.com", "old-password")
+            .await
+            .expect("user");
+
+        let missing = resend_password_reset_svc(&ctx.state, &user.id).await;
+        assert!(missing.is_err(), "nothing to resend yet");
+
+        let issued = request_password_reset_svc(&ctx.state, "jane@example.com")
+            .await
+            .expect("reset")
+            .expect("issued");
+        let first_email = issued.reset.email_id.clone().expect("email tracked");
+
+        // The outbox gives up on the email
+        ctx.state
+            .db
+            .email_outbox
+            .record_failure(
+                first_email.clone(),
+                DELIVERY_FAILED,
+                EMAIL_MAX_ATTEMPTS,
+                0,
+                "Connection refused".to_string(),
+                0,
+            )
+            .await
+            .expect("record failure");
+
+        let latest = latest_password_reset_svc(&ctx.state, &user.id)
+            .await
+            .expect("latest reset");
+        assert_eq!(latest.id, issued.reset.id);
+        assert_eq!(latest.delivery_status.as_deref(), Some(DELIVERY_FAILED));
+
+        let resent = resend_password_reset_svc(&ctx.state, &user.id)
+            .await
+            .expect("reset should be resent");
+        assert_ne!(resent.id, issued.reset.id);
+        assert_ne!(resent.email_id.as_deref(), Some(first_email.as_str()));
+        assert_eq!(resent.delivery_status.as_deref(), Some(DELIVERY_PENDING));
+
+        // The link that never arrived stops working
+        let stale = reset_password_svc(&ctx.state, reset_form(&issued.token)).await;
+        assert!(stale.is_err());
+    }
+}
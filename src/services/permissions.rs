@@ -0,0 +1,502 @@
+use snafu::OptionExt;
+use tracing::instrument;
+
+use crate::db::DbMapper;
+use crate::dto::{
+    ALL_PERMISSIONS, ALL_ROLES, EffectivePermissionDto, MemberPermissionImpactDto,
+    MemberPermissionsDto, OrgDto, OrgMemberDto, OrgRoleDto, Permission, PermissionHelpDto,
+    PermissionImpactDto, PermissionInfoDto, Role, RoleInfoDto, RolePermissionsDto, TeamDto,
+    role_permissions, roles_permissions, to_roles,
+};
+use crate::error::OrgNotFoundSnafu;
+use crate::{Error, Result};
+
+/// Role to permission mapping, optionally limited to roles assigned within an org.
+///
+/// The org variant also lists the custom roles defined in the org, after the
+/// built-in ones.
+#[instrument(level = "debug", skip_all)]
+pub async fn permission_matrix_svc(
+    db: &DbMapper,
+    org_id: Option<&str>,
+) -> Result<Vec<RolePermissionsDto>> {
+    let (roles, custom_roles): (Vec<Role>, Vec<OrgRoleDto>) = match org_id {
+        Some(org_id) => {
+            let org = db.orgs.get(org_id.to_string()).await?;
+            let _ = org.context(OrgNotFoundSnafu)?;
+
+            let assigned = db
+                .org_members
+                .list_assigned_roles(org_id.to_string())
+                .await?;
+
+            let custom_roles = db.org_roles.list(org_id.to_string()).await?;
+
+            // Keep the canonical role ordering
+            let roles = ALL_ROLES
+                .iter()
+                .filter(|role| assigned.contains(role))
+                .cloned()
+                .collect();
+
+            (roles, custom_roles)
+        }
+        None => (ALL_ROLES.to_vec(), Vec::new()),
+    };
+
+    let built_in = roles.into_iter().map(|role| RolePermissionsDto {
+        permissions: role_permissions(&role),
+        role: role.to_string(),
+        custom: false,
+    });
+    let custom = custom_roles.into_iter().map(|role| RolePermissionsDto {
+        role: role.name,
+        custom: true,
+        permissions: role.permissions,
+    });
+
+    Ok(built_in.chain(custom).collect())
+}
+
+/// Effective permissions granted by a combination of roles, sorted by name
+pub fn preview_role_permissions(roles: &[String]) -> Result<Vec<String>> {
+    let Ok(roles) = to_roles(roles) else {
+        return Err(Error::Validation {
+            msg: "Role is invalid".to_string(),
+        });
+    };
+
+    let mut permissions: Vec<String> = roles_permissions(&roles)
+        .into_iter()
+        .map(|permission| permission.to_string())
+        .collect();
+    permissions.sort();
+
+    Ok(permissions)
+}
+
+/// Effective permissions of an org member, each with the roles granting it.
+///
+/// Built-in roles are listed as `role:<name>`, custom org roles as `custom_role:<name>`
+/// and teams as `team:<name>`.
+pub fn member_permissions(
+    org: &OrgDto,
+    member: &OrgMemberDto,
+    custom_roles: &[OrgRoleDto],
+    teams: &[TeamDto],
+) -> MemberPermissionsDto {
+    let mut permissions: Vec<EffectivePermissionDto> = Vec::new();
+
+    let sources = member
+        .roles
+        .iter()
+        .map(|role| (format!("role:{}", role), role_permissions(role)))
+        .chain(custom_roles.iter().map(|role| {
+            (
+                format!("custom_role:{}", role.name),
+                role.permissions.clone(),
+            )
+        }))
+        .chain(teams.iter().map(|team| {
+            (
+                format!("team:{}", team.name),
+                roles_permissions(&team.roles),
+            )
+        }));
+
+    for (source, granted) in sources {
+        for permission in granted.into_iter() {
+            match permissions
+                .iter_mut()
+                .find(|entry| entry.permission == permission)
+            {
+                Some(entry) => entry.sources.push(source.clone()),
+                None => permissions.push(EffectivePermissionDto {
+                    permission,
+                    sources: vec![source.clone()],
+                }),
+            }
+        }
+    }
+
+    permissions.sort_by_key(|entry| entry.permission.to_string());
+
+    MemberPermissionsDto {
+        org_id: member.org_id.clone(),
+        user_id: member.user_id.clone(),
+        roles: member.roles.clone(),
+        custom_roles: custom_roles.iter().map(|role| role.name.clone()).collect(),
+        teams: teams.iter().map(|team| team.name.clone()).collect(),
+        active: member.status == "active" && org.status == "active",
+        permissions,
+    }
+}
+
+/// Permissions granted by the roles, none when the membership or org is inactive
+fn granted_permissions(roles: &[Role], active: bool) -> Vec<Permission> {
+    if !active {
+        return Vec::new();
+    }
+
+    let mut permissions = roles_permissions(roles);
+    permissions.sort_by_key(|permission| permission.to_string());
+    permissions
+}
+
+/// Diffs the member's effective permissions against the proposed roles and
+/// status, nothing is saved
+pub fn role_change_impact(
+    org: &OrgDto,
+    member: &OrgMemberDto,
+    roles: &[String],
+    active: bool,
+) -> Result<PermissionImpactDto> {
+    let Ok(roles) = to_roles(roles) else {
+        return Err(Error::Validation {
+            msg: "Role is invalid".to_string(),
+        });
+    };
+
+    let org_active = org.status == "active";
+    let before = granted_permissions(&member.roles, member.status == "active" && org_active);
+    let after = granted_permissions(&roles, active && org_active);
+
+    let gained: Vec<Permission> = after
+        .iter()
+        .filter(|permission| !before.contains(permission))
+        .cloned()
+        .collect();
+    let lost: Vec<Permission> = before
+        .iter()
+        .filter(|permission| !after.contains(permission))
+        .cloned()
+        .collect();
+
+    Ok(PermissionImpactDto {
+        org_id: org.id.clone(),
+        members: vec![MemberPermissionImpactDto {
+            user_id: member.user_id.clone(),
+            member_email: member.member_email.clone(),
+            before,
+            after,
+            gained,
+            lost,
+        }],
+    })
+}
+
+/// Labels and descriptions of every permission and role, in canonical order
+pub fn permission_help_svc() -> PermissionHelpDto {
+    PermissionHelpDto {
+        permissions: ALL_PERMISSIONS
+            .iter()
+            .map(|permission| PermissionInfoDto {
+                permission: permission.clone(),
+                label: permission.label().to_string(),
+                description: permission.description().to_string(),
+            })
+            .collect(),
+        roles: ALL_ROLES
+            .iter()
+            .map(|role| RoleInfoDto {
+                role: role.clone(),
+                label: role.label().to_string(),
+                description: role.description().to_string(),
+                permissions: role_permissions(role),
+            })
+            .collect(),
+    }
+}
+
+/// Flattens the matrix into `role,permission` CSV rows.
+///
+/// Custom roles are listed as `custom_role:<name>`, like the member permission sources.
+pub fn permission_matrix_to_csv(matrix: &[RolePermissionsDto]) -> String {
+    let mut csv = String::from("role,permission\n");
+
+    for entry in matrix.iter() {
+        let role = match entry.custom {
+            true => format!("custom_role:{}", entry.role),
+            false => entry.role.clone(),
+        };
+        for permission in entry.permissions.iter() {
+            csv.push_str(&format!("{},{}\n", role, permission));
+        }
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ctx::AuditCtx;
+    use crate::dto::{
+        ALL_PERMISSIONS, ALL_ROLES, NewOrgMemberDto, NewOrgRoleDto, Permission, Role,
+    };
+    use crate::test::TestCtx;
+
+    use super::{
+        member_permissions, permission_help_svc, permission_matrix_svc, permission_matrix_to_csv,
+        preview_role_permissions, role_change_impact,
+    };
+
+    #[test]
+    fn test_preview_role_permissions_is_the_union() {
+        let viewer = preview_role_permissions(&["OrgViewer".to_string()]).expect("preview");
+        let editor = preview_role_permissions(&["OrgEditor".to_string()]).expect("preview");
+        let both = preview_role_permissions(&["OrgViewer".to_string(), "OrgEditor".to_string()])
+            .expect("preview");
+
+        assert!(viewer.iter().all(|p| both.contains(p)));
+        assert!(editor.iter().all(|p| both.contains(p)));
+        assert!(both.windows(2).all(|pair| pair[0] < pair[1]));
+
+        assert!(preview_role_permissions(&[]).expect("preview").is_empty());
+        assert!(preview_role_permissions(&["Nope".to_string()]).is_err());
+    }
+
+    #[test]
+    fn permission_help_svc_covers_every_permission() {
+        let help = permission_help_svc();
+
+        assert_eq!(help.permissions.len(), ALL_PERMISSIONS.len());
+        assert_eq!(help.roles.len(), ALL_ROLES.len());
+
+        let mut labels: Vec<&str> = help.permissions.iter().map(|p| p.label.as_str()).collect();
+        labels.sort();
+        labels.dedup();
+        assert_eq!(labels.len(), ALL_PERMISSIONS.len(), "labels must be unique");
+        assert!(
+            help.permissions
+                .iter()
+                .all(|p| !p.description.is_empty() && p.description.ends_with('.'))
+        );
+    }
+
+    #[tokio::test]
+    async fn permission_matrix_svc_filters_by_org() {
+        let ctx = TestCtx::new("permission_matrix").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Matrix Owner",
+                "matrix.owner@example.com",
+                "password123",
+                "Matrix Org",
+            )
+            .await
+            .expect("auth fixture");
+        let viewer = ctx
+            .seed_user_with_password("Matrix Viewer", "matrix.viewer@example.com", "password123")
+            .await
+            .expect("viewer user");
+
+        ctx.state
+            .db
+            .org_members
+            .create(
+                &AuditCtx::default(),
+                fixture.org.id.clone(),
+                NewOrgMemberDto {
+                    user_id: viewer.id,
+                    roles: vec!["OrgViewer".to_string()],
+                    status: "active".to_string(),
+                },
+            )
+            .await
+            .expect("viewer should be member");
+
+        let all = permission_matrix_svc(&ctx.state.db, None)
+            .await
+            .expect("matrix should build");
+        assert_eq!(all.len(), 4);
+
+        let org_matrix = permission_matrix_svc(&ctx.state.db, Some(&fixture.org.id))
+            .await
+            .expect("matrix should build");
+        let roles: Vec<String> = org_matrix.iter().map(|e| e.role.clone()).collect();
+        assert_eq!(
+            roles,
+            vec![Role::OrgAdmin.to_string(), Role::OrgViewer.to_string()]
+        );
+        assert!(org_matrix.iter().all(|e| !e.custom));
+
+        let csv = permission_matrix_to_csv(&org_matrix);
+        assert!(csv.starts_with("role,permission\n"));
+        assert!(csv.contains("OrgViewer,users.view\n"));
+
+        let missing = permission_matrix_svc(&ctx.state.db, Some("org_missing")).await;
+        assert!(missing.is_err(), "unknown org should fail");
+    }
+
+    #[tokio::test]
+    async fn permission_matrix_svc_includes_custom_org_roles() {
+        let ctx = TestCtx::new("permission_matrix_custom")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Custom Owner",
+                "custom.owner@example.com",
+                "password123",
+                "Custom Org",
+            )
+            .await
+            .expect("auth fixture");
+
+        ctx.state
+            .db
+            .org_roles
+            .create(
+                &AuditCtx::default(),
+                fixture.org.id.clone(),
+                NewOrgRoleDto {
+                    name: "Auditor".to_string(),
+                    description: "Reads users".to_string(),
+                    permissions: vec!["users.view".to_string()],
+                },
+            )
+            .await
+            .expect("custom role should be created");
+
+        let org_matrix = permission_matrix_svc(&ctx.state.db, Some(&fixture.org.id))
+            .await
+            .expect("matrix should build");
+        let auditor = org_matrix
+            .iter()
+            .find(|e| e.custom && e.role == "Auditor")
+            .expect("custom role is listed");
+        assert_eq!(auditor.permissions, vec![Permission::UsersView]);
+
+        let csv = permission_matrix_to_csv(&org_matrix);
+        assert!(csv.contains("custom_role:Auditor,users.view\n"));
+
+        // Custom roles belong to their org
+        let all = permission_matrix_svc(&ctx.state.db, None)
+            .await
+            .expect("matrix should build");
+        assert!(all.iter().all(|e| !e.custom));
+    }
+
+    #[tokio::test]
+    async fn member_permissions_lists_contributing_roles() {
+        let ctx = TestCtx::new("member_permissions").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Perm Owner",
+                "perm.owner@example.com",
+                "password123",
+                "Perm Org",
+            )
+            .await
+            .expect("auth fixture");
+        let user = ctx
+            .seed_user_with_password("Perm Member", "perm.member@example.com", "password123")
+            .await
+            .expect("member user");
+
+        ctx.state
+            .db
+            .org_members
+            .create(
+                &AuditCtx::default(),
+                fixture.org.id.clone(),
+                NewOrgMemberDto {
+                    user_id: user.id.clone(),
+                    roles: vec!["OrgEditor".to_string(), "OrgViewer".to_string()],
+                    status: "inactive".to_string(),
+                },
+            )
+            .await
+            .expect("member should be created");
+
+        let member = ctx
+            .state
+            .db
+            .org_members
+            .find_member(fixture.org.id.clone(), user.id.clone())
+            .await
+            .expect("find member")
+            .expect("member exists");
+
+        let result = member_permissions(&fixture.org, &member, &[], &[]);
+        assert!(!result.active, "inactive members are not granted anything");
+
+        let names: Vec<String> = result
+            .permissions
+            .iter()
+            .map(|entry| entry.permission.to_string())
+            .collect();
+        assert!(names.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let users_view = result
+            .permissions
+            .iter()
+            .find(|entry| entry.permission.to_string() == "users.view")
+            .expect("users.view is granted");
+        assert_eq!(
+            users_view.sources,
+            vec!["role:OrgEditor".to_string(), "role:OrgViewer".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn role_change_impact_lists_gained_and_lost() {
+        let ctx = TestCtx::new("role_change_impact").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Impact Owner",
+                "impact.owner@example.com",
+                "password123",
+                "Impact Org",
+            )
+            .await
+            .expect("auth fixture");
+        let user = ctx
+            .seed_user_with_password("Impact Member", "impact.member@example.com", "password123")
+            .await
+            .expect("member user");
+
+        ctx.state
+            .db
+            .org_members
+            .create(
+                &AuditCtx::default(),
+                fixture.org.id.clone(),
+                NewOrgMemberDto {
+                    user_id: user.id.clone(),
+                    roles: vec!["OrgEditor".to_string()],
+                    status: "active".to_string(),
+                },
+            )
+            .await
+            .expect("member should be created");
+
+        let member = ctx
+            .state
+            .db
+            .org_members
+            .find_member(fixture.org.id.clone(), user.id.clone())
+            .await
+            .expect("find member")
+            .expect("member exists");
+
+        let same = role_change_impact(&fixture.org, &member, &["OrgEditor".to_string()], true)
+            .expect("impact");
+        assert!(!same.has_changes());
+
+        let viewer = role_change_impact(&fixture.org, &member, &["OrgViewer".to_string()], true)
+            .expect("impact");
+        let impact = &viewer.members[0];
+        assert!(impact.gained.is_empty());
+        assert!(!impact.lost.is_empty());
+        assert!(impact.lost.iter().all(|p| !impact.after.contains(p)));
+        assert!(impact.after.iter().all(|p| impact.before.contains(p)));
+
+        let inactive = role_change_impact(&fixture.org, &member, &["OrgAdmin".to_string()], false)
+            .expect("impact");
+        assert!(inactive.members[0].after.is_empty());
+        assert_eq!(inactive.members[0].lost, inactive.members[0].before);
+
+        assert!(role_change_impact(&fixture.org, &member, &["Nope".to_string()], true).is_err());
+    }
+}
@@ -0,0 +1,262 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ensure};
+use tracing::instrument;
+
+use crate::dto::{AppProofKeyDto, NewAppProofKeyDto, TokenProofDto};
+use crate::error::{CsrfTokenSnafu, InvalidTokenProofSnafu};
+use crate::run::AppState;
+use crate::services::token::verify_csrf_token;
+use crate::utils::{decode_proof_public_key, proof_key_id, verify_token_proof};
+use crate::validators::validate_payload;
+use crate::{Error, Result};
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct AppProofKeyFormData {
+    pub token: String,
+    pub public_key: String,
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn get_app_proof_key_svc(
+    state: &AppState,
+    app_id: &str,
+) -> Result<Option<AppProofKeyDto>> {
+    state.db.app_proof_keys.find(app_id.to_string()).await
+}
+
+/// Registers or replaces the app's key, tokens issued from now on are bound to it
+#[instrument(level = "debug", skip_all)]
+pub async fn register_app_proof_key_svc(
+    state: &AppState,
+    app_id: &str,
+    data: NewAppProofKeyDto,
+) -> Result<AppProofKeyDto> {
+    validate_payload(&data)?;
+
+    let public_key =
+        decode_proof_public_key(&data.public_key).map_err(|msg| Error::Validation { msg })?;
+
+    state
+        .db
+        .app_proof_keys
+        .upsert(AppProofKeyDto {
+            app_id: app_id.to_string(),
+            key_id: proof_key_id(&public_key),
+            public_key: data.public_key.trim().trim_end_matches('=').to_string(),
+            created_at: Utc::now().timestamp_millis(),
+        })
+        .await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn register_app_proof_key_web_svc(
+    state: &AppState,
+    app_id: &str,
+    form: AppProofKeyFormData,
+) -> Result<AppProofKeyDto> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == app_id, CsrfTokenSnafu);
+
+    register_app_proof_key_svc(
+        state,
+        app_id,
+        NewAppProofKeyDto {
+            public_key: form.public_key,
+        },
+    )
+    .await
+}
+
+/// Turns proof of possession off, tokens bound to the old key stop working
+#[instrument(level = "debug", skip_all)]
+pub async fn remove_app_proof_key_web_svc(
+    state: &AppState,
+    app_id: &str,
+    csrf_token: &str,
+) -> Result<()> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == app_id, CsrfTokenSnafu);
+
+    state.db.app_proof_keys.delete(app_id.to_string()).await
+}
+
+/// Checks the request proof of a token bound to `key_id`
+#[instrument(level = "debug", skip_all)]
+pub async fn verify_token_proof_svc(
+    state: &AppState,
+    key_id: &str,
+    token: &str,
+    proof: Option<&TokenProofDto>,
+) -> Result<()> {
+    let proof = proof.context(InvalidTokenProofSnafu {
+        msg: "token is bound to a key".to_string(),
+    })?;
+    let header = proof.header.as_deref().context(InvalidTokenProofSnafu {
+        msg: "missing proof header".to_string(),
+    })?;
+
+    let key = state
+        .db
+        .app_proof_keys
+        .find_by_key_id(key_id.to_string())
+        .await?
+        .context(InvalidTokenProofSnafu {
+            msg: "key is no longer registered".to_string(),
+        })?;
+
+    let public_key =
+        decode_proof_public_key(&key.public_key).map_err(|msg| Error::InvalidTokenProof { msg })?;
+
+    verify_token_proof(
+        &public_key,
+        header,
+        &proof.method,
+        &proof.path,
+        token,
+        Utc::now().timestamp(),
+    )
+    .map_err(|msg| Error::InvalidTokenProof { msg })
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+    use chrono::Utc;
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    use crate::dto::TokenProofDto;
+    use crate::dto::{NewAppProofKeyDto, OauthAuthorizeDto, OauthTokenRequestDto, Scope};
+    use crate::services::auth::{authenticate_bound_token_svc, authenticate_token_svc};
+    use crate::services::oauth::{
+        create_authorization_code_svc, exchange_code_for_access_token_svc,
+    };
+    use crate::test::TestCtx;
+    use crate::utils::token_proof_message;
+
+    use super::register_app_proof_key_svc;
+
+    #[tokio::test]
+    async fn bound_tokens_require_a_valid_proof() {
+        let ctx = TestCtx::new("proof_keys_bound").await.expect("test ctx");
+        let fixture = ctx
+            .seed_oauth_fixture(
+                "Proof User",
+                "proof.user@example.com",
+                "password123",
+                "Proof Org",
+                "Proof App",
+                "https://proof.example.com/callback",
+                true,
+            )
+            .await
+            .expect("oauth fixture");
+
+        let invalid = register_app_proof_key_svc(
+            &ctx.state,
+            &fixture.app.id,
+            NewAppProofKeyDto {
+                public_key: "!".repeat(43),
+            },
+        )
+        .await;
+        assert!(invalid.is_err(), "only base64url keys are accepted");
+
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let key = register_app_proof_key_svc(
+            &ctx.state,
+            &fixture.app.id,
+            NewAppProofKeyDto {
+                public_key: URL_SAFE_NO_PAD.encode(pair.public_key().as_ref()),
+            },
+        )
+        .await
+        .expect("key should register");
+
+        let code = create_authorization_code_svc(
+            &ctx.state,
+            &fixture.auth.to_ctx(vec![Scope::Auth]),
+            &OauthAuthorizeDto {
+                client_id: fixture.app.client_id.clone(),
+                redirect_uri: "https://proof.example.com/callback".to_string(),
+                scope: "auth".to_string(),
+                state: "state-1".to_string(),
+                nonce: None,
+            },
+        )
+        .await
+        .expect("authorization code");
+
+        let token = exchange_code_for_access_token_svc(
+            &ctx.state,
+            &OauthTokenRequestDto {
+                grant_type: Some("authorization_code".to_string()),
+                client_id: fixture.app.client_id.clone(),
+                client_secret: fixture.app.client_secret.clone(),
+                code: code.code,
+                state: code.state,
+                redirect_uri: "https://proof.example.com/callback".to_string(),
+            },
+        )
+        .await
+        .expect("token exchange");
+        assert_eq!(token.key_id.as_deref(), Some(key.key_id.as_str()));
+
+        let access_token = token.access_token;
+        let mut proof = TokenProofDto {
+            header: None,
+            method: "GET".to_string(),
+            path: "/oauth/profile".to_string(),
+        };
+
+        // Cookie style auth and missing proofs are rejected
+        assert!(
+            authenticate_token_svc(&ctx.state, &access_token)
+                .await
+                .is_err()
+        );
+        assert!(
+            authenticate_bound_token_svc(&ctx.state, &access_token, Some(&proof))
+                .await
+                .is_err()
+        );
+
+        let now = Utc::now().timestamp();
+        let message = token_proof_message("GET", "/oauth/profile", now, &access_token);
+        let signature = pair.sign(message.as_bytes());
+        proof.header = Some(format!(
+            "{}.{}",
+            now,
+            URL_SAFE_NO_PAD.encode(signature.as_ref())
+        ));
+
+        let actor = authenticate_bound_token_svc(&ctx.state, &access_token, Some(&proof))
+            .await
+            .expect("valid proof should pass");
+        assert!(actor.actor.is_some());
+
+        // The same proof does not cover another endpoint
+        proof.path = "/user/authorized-apps".to_string();
+        assert!(
+            authenticate_bound_token_svc(&ctx.state, &access_token, Some(&proof))
+                .await
+                .is_err()
+        );
+
+        // Removing the key invalidates the bound token
+        proof.path = "/oauth/profile".to_string();
+        ctx.state
+            .db
+            .app_proof_keys
+            .delete(fixture.app.id.clone())
+            .await
+            .expect("remove key");
+        assert!(
+            authenticate_bound_token_svc(&ctx.state, &access_token, Some(&proof))
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ensure};
+use tracing::{instrument, warn};
+
+use crate::Result;
+use crate::ctx::AuditCtx;
+use crate::db::DbMapper;
+use crate::dto::{NewPasswordDto, NewRecoveryTokenDto, RecoveryTokenDto, UpdateUserDto};
+use crate::error::{ValidationSnafu, WhateverSnafu};
+use crate::run::AppState;
+use crate::services::auth::invalidate_auth_cache;
+use crate::services::notifications::notify_superusers_svc;
+use crate::services::password::{hash_password, update_password_svc};
+use crate::utils::hash_secret;
+use crate::validators::validate_payload;
+
+pub const RECOVERY_TOKEN_TTL_MINS: i64 = 15;
+pub const RECOVERY_TOKEN_MAX_TTL_MINS: i64 = 60;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct RecoverAccountFormData {
+    pub token: String,
+    pub password: String,
+    pub confirm_password: String,
+}
+
+/// Freshly issued token, the plain secret is never stored
+pub struct IssuedRecoveryToken {
+    pub token: String,
+    pub recovery: RecoveryTokenDto,
+}
+
+pub fn generate_recovery_token() -> Result<String> {
+    let mut bytes = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .ok()
+        .context(WhateverSnafu {
+            msg: "Unable to generate recovery token".to_string(),
+        })?;
+    Ok(hex::encode(bytes))
+}
+
+/// Issues a one-time recovery token for the user.
+///
+/// Only callable from the CLI since it needs direct access to the database.
+/// Any older unused token for the same user is burned.
+#[instrument(level = "debug", skip_all)]
+pub async fn issue_recovery_token_svc(
+    db: &DbMapper,
+    email: &str,
+    ttl_mins: i64,
+) -> Result<IssuedRecoveryToken> {
+    ensure!(
+        (1..=RECOVERY_TOKEN_MAX_TTL_MINS).contains(&ttl_mins),
+        ValidationSnafu {
+            msg: format!(
+                "Recovery token TTL must be between 1 and {} minutes.",
+                RECOVERY_TOKEN_MAX_TTL_MINS
+            ),
+        }
+    );
+
+    let user = db
+        .users
+        .find_by_email(email.trim().to_string())
+        .await?
+        .context(ValidationSnafu {
+            msg: format!("No user found with email {}.", email.trim()),
+        })?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let revoked = db
+        .recovery_tokens
+        .revoke_for_user(user.id.clone(), now)
+        .await?;
+
+    let token = generate_recovery_token()?;
+    let recovery = db
+        .recovery_tokens
+        .create(NewRecoveryTokenDto {
+            user_id: user.id.clone(),
+            token_hash: hash_secret(&token),
+            expires_at: now + ttl_mins * 60 * 1000,
+        })
+        .await?;
+
+    warn!(
+        recovery_id = recovery.id,
+        user_id = user.id,
+        email = user.email,
+        expires_at = recovery.expires_at,
+        revoked = revoked,
+        "break_glass.issued"
+    );
+
+    let body = format!(
+        "A break-glass recovery token was issued from the command line for {}. It expires in {} minute(s). If this was not expected, treat the host as compromised.",
+        user.email, ttl_mins
+    );
+    notify_superusers_svc(db, "Break-glass recovery token issued", &body).await?;
+
+    Ok(IssuedRecoveryToken { token, recovery })
+}
+
+/// Redeems a recovery token by setting a new password for its user.
+///
+/// Reactivates the account when it was deactivated.
+#[instrument(level = "debug", skip_all)]
+pub async fn recover_account_svc(state: &AppState, form: RecoverAccountFormData) -> Result<()> {
+    ensure!(
+        form.password == form.confirm_password,
+        ValidationSnafu {
+            msg: "Passwords must match."
+        }
+    );
+
+    let new_password = NewPasswordDto {
+        password: form.password,
+    };
+    validate_payload(&new_password)?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let invalid_msg = "Recovery token is invalid or has expired.";
+
+    let recovery = state
+        .db
+        .recovery_tokens
+        .find_by_hash(hash_secret(&form.token))
+        .await?
+        .context(ValidationSnafu { msg: invalid_msg })?;
+
+    ensure!(
+        recovery.is_usable(now),
+        ValidationSnafu { msg: invalid_msg }
+    );
+
+    let user = state
+        .db
+        .users
+        .get(recovery.user_id.clone())
+        .await?
+        .context(ValidationSnafu { msg: invalid_msg })?;
+
+    // Consume first so a racing request cannot redeem the same token twice
+    let consumed = state
+        .db
+        .recovery_tokens
+        .consume(recovery.id.clone(), now)
+        .await?;
+    ensure!(consumed, ValidationSnafu { msg: invalid_msg });
+
+    let updated = update_password_svc(state, &user.id, new_password.clone()).await?;
+    if !updated {
+        let hashed = NewPasswordDto {
+            password: hash_password(&new_password.password)?,
+        };
+        state.db.passwords.create(user.id.clone(), hashed).await?;
+    }
+
+    let reactivated = user.status != "active";
+    if reactivated {
+        let data = UpdateUserDto {
+            name: None,
+            status: Some("active".to_string()),
+        };
+        state
+            .db
+            .users
+            .update(&AuditCtx::current(), user.id.clone(), data)
+            .await?;
+    }
+
+    invalidate_auth_cache(state, &user.id).await;
+
+    warn!(
+        recovery_id = recovery.id,
+        user_id = user.id,
+        email = user.email,
+        reactivated = reactivated,
+        "break_glass.redeemed"
+    );
+
+    let body = format!(
+        "The break-glass recovery token for {} was used to reset the password{}.",
+        user.email,
+        if reactivated {
+            " and reactivate the account"
+        } else {
+            ""
+        }
+    );
+    notify_superusers_svc(&state.db, "Break-glass recovery token used", &body).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::services::password::verify_password;
+    use crate::test::TestCtx;
+
+    use super::{RecoverAccountFormData, issue_recovery_token_svc, recover_account_svc};
+
+    fn recover_form(token: &str) -> RecoverAccountFormData {
+        RecoverAccountFormData {
+            token: token.to_string(),
+            password: "recovered-password".to_string(),
+            confirm_password: "recovered-password".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn recovery_token_resets_password_once() {
+        let ctx = TestCtx::new("recovery_reset_once").await.expect("test ctx");
+        let superuser = ctx
+            .seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+
+        let issued = issue_recovery_token_svc(&ctx.state.db, "root@example.com", 15)
+            .await
+            .expect("recovery token");
+        assert_ne!(issued.token, issued.recovery.token_hash);
+
+        recover_account_svc(&ctx.state, recover_form(&issued.token))
+            .await
+            .expect("recovery should succeed");
+
+        let password = ctx
+            .state
+            .db
+            .passwords
+            .get(superuser.id.clone())
+            .await
+            .expect("password lookup")
+            .expect("password row");
+        assert!(verify_password("recovered-password", &password.password).expect("verify"));
+
+        let reused = recover_account_svc(&ctx.state, recover_form(&issued.token)).await;
+        assert!(reused.is_err());
+    }
+
+    #[tokio::test]
+    async fn newer_recovery_token_burns_older_ones() {
+        let ctx = TestCtx::new("recovery_burns_older")
+            .await
+            .expect("test ctx");
+        ctx.seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+
+        let first = issue_recovery_token_svc(&ctx.state.db, "root@example.com", 15)
+            .await
+            .expect("first token");
+        issue_recovery_token_svc(&ctx.state.db, "root@example.com", 15)
+            .await
+            .expect("second token");
+
+        let result = recover_account_svc(&ctx.state, recover_form(&first.token)).await;
+        assert!(result.is_err());
+
+        let too_long = issue_recovery_token_svc(&ctx.state.db, "root@example.com", 24 * 60).await;
+        assert!(too_long.is_err());
+    }
+}
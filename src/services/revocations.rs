@@ -0,0 +1,327 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use moka::sync::Cache;
+use snafu::{OptionExt, ensure};
+use tracing::{instrument, warn};
+
+use crate::Result;
+use crate::config::TokenDenylistStore;
+use crate::dto::{ActorPayloadDto, RevokedTokenDto, TokenRevocationDto};
+use crate::error::{CsrfTokenSnafu, InvalidAuthTokenSnafu, UserNotFoundSnafu};
+use crate::run::AppState;
+use crate::services::auth::invalidate_auth_cache;
+use crate::services::notifications::notify_superusers_svc;
+use crate::services::token::{EXP_DURATION, verify_csrf_token};
+
+/// Ids of individually revoked tokens, see `TokenDenylistStore`
+#[derive(Clone)]
+pub enum TokenDenylist {
+    Database,
+    /// Entries outlive the tokens they deny, tokens expire within `EXP_DURATION`
+    Memory(Cache<String, ()>),
+    /// Entries in the shared cache expire with the tokens they deny
+    Redis,
+}
+
+impl TokenDenylist {
+    pub fn new(store: TokenDenylistStore) -> Self {
+        match store {
+            TokenDenylistStore::Database => Self::Database,
+            TokenDenylistStore::Memory => Self::Memory(
+                Cache::builder()
+                    .time_to_live(Duration::from_secs(EXP_DURATION as u64))
+                    .max_capacity(100_000)
+                    .build(),
+            ),
+            TokenDenylistStore::Redis => Self::Redis,
+        }
+    }
+}
+
+/// Signs the user out everywhere.
+///
+/// Every token issued to the user so far is rejected, including org switch
+/// and OAuth app tokens. Signing in again issues tokens that are accepted.
+#[instrument(level = "debug", skip_all)]
+pub async fn force_logout_svc(
+    state: &AppState,
+    actor_id: Option<&str>,
+    user_id: &str,
+) -> Result<TokenRevocationDto> {
+    let user = state
+        .db
+        .users
+        .get(user_id.to_string())
+        .await?
+        .context(UserNotFoundSnafu)?;
+
+    let revocation = revoke_user_tokens_svc(state, actor_id, &user.id).await?;
+
+    warn!(
+        user_id = user.id,
+        email = user.email,
+        revoked_by = revocation.revoked_by,
+        "user.force_logout"
+    );
+
+    let body = format!(
+        "All sessions and tokens of {} were revoked. They need to sign in again.",
+        user.email
+    );
+    notify_superusers_svc(&state.db, "User signed out everywhere", &body).await?;
+
+    Ok(revocation)
+}
+
+/// Rejects every token issued to the user so far, without notifying anyone
+#[instrument(level = "debug", skip_all)]
+pub async fn revoke_user_tokens_svc(
+    state: &AppState,
+    actor_id: Option<&str>,
+    user_id: &str,
+) -> Result<TokenRevocationDto> {
+    let revocation = state
+        .db
+        .token_revocations
+        .upsert(TokenRevocationDto {
+            user_id: user_id.to_string(),
+            revoked_at: Utc::now().timestamp_millis(),
+            revoked_by: actor_id.map(|id| id.to_string()),
+        })
+        .await?;
+
+    state
+        .revocation_cache
+        .insert(user_id.to_string(), revocation.revoked_at);
+    invalidate_auth_cache(state, user_id).await;
+
+    Ok(revocation)
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn force_logout_web_svc(
+    state: &AppState,
+    actor_id: &str,
+    user_id: &str,
+    csrf_token: &str,
+) -> Result<TokenRevocationDto> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == user_id, CsrfTokenSnafu);
+
+    force_logout_svc(state, Some(actor_id), user_id).await
+}
+
+/// Rejects tokens issued before the user's last forced logout
+#[instrument(level = "debug", skip_all)]
+pub async fn verify_not_revoked_svc(state: &AppState, payload: &ActorPayloadDto) -> Result<()> {
+    let revoked_at = match state.revocation_cache.get(&payload.id) {
+        Some(revoked_at) => revoked_at,
+        None => {
+            let revoked_at = state
+                .db
+                .token_revocations
+                .find(payload.id.clone())
+                .await?
+                .map(|revocation| revocation.revoked_at)
+                .unwrap_or(0);
+
+            state
+                .revocation_cache
+                .insert(payload.id.clone(), revoked_at);
+            revoked_at
+        }
+    };
+
+    // Token timestamps are in seconds, tokens issued within the same
+    // second as the revocation are rejected too
+    ensure!(
+        revoked_at == 0 || payload.issued_at > revoked_at / 1000,
+        InvalidAuthTokenSnafu
+    );
+
+    Ok(())
+}
+
+/// Rejects a single token until it expires, other tokens of the user keep working.
+///
+/// Tokens issued before token ids were introduced carry none and cannot be
+/// revoked one by one, revoking them is a no-op.
+#[instrument(level = "debug", skip_all)]
+pub async fn revoke_token_svc(state: &AppState, payload: &ActorPayloadDto) -> Result<()> {
+    let Some(jti) = payload.token_id.clone() else {
+        return Ok(());
+    };
+
+    match &state.token_denylist {
+        TokenDenylist::Memory(cache) => cache.insert(jti, ()),
+        TokenDenylist::Redis => {
+            let expires_at = payload.issued_at + EXP_DURATION;
+            let ttl_secs = (expires_at - Utc::now().timestamp()).max(1) as u64;
+            state
+                .shared_cache
+                .set(
+                    &denylist_key(&jti),
+                    "1".to_string(),
+                    Duration::from_secs(ttl_secs),
+                )
+                .await;
+        }
+        TokenDenylist::Database => {
+            let now = Utc::now().timestamp_millis();
+            state
+                .db
+                .revoked_tokens
+                .insert(RevokedTokenDto {
+                    jti,
+                    user_id: payload.id.clone(),
+                    expires_at: (payload.issued_at + EXP_DURATION) * 1000,
+                    created_at: now,
+                })
+                .await?;
+
+            // Piggyback the cleanup, revocations are rare
+            state.db.revoked_tokens.delete_expired(now).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Rejects tokens revoked one by one
+#[instrument(level = "debug", skip_all)]
+pub async fn verify_not_denied_svc(state: &AppState, payload: &ActorPayloadDto) -> Result<()> {
+    let Some(jti) = payload.token_id.as_ref() else {
+        return Ok(());
+    };
+
+    let denied = match &state.token_denylist {
+        TokenDenylist::Memory(cache) => cache.contains_key(jti),
+        TokenDenylist::Redis => state.shared_cache.get(&denylist_key(jti)).await.is_some(),
+        TokenDenylist::Database => state.db.revoked_tokens.find(jti.clone()).await?.is_some(),
+    };
+    ensure!(!denied, InvalidAuthTokenSnafu);
+
+    Ok(())
+}
+
+fn denylist_key(jti: &str) -> String {
+    format!("denylist:{}", jti)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::config::TokenDenylistStore;
+    use crate::dto::{CredentialsDto, SessionClientDto};
+    use crate::services::auth::{authenticate, authenticate_token_svc};
+    use crate::services::token::verify_auth_token;
+    use crate::test::TestCtx;
+
+    use super::{TokenDenylist, force_logout_svc, revoke_token_svc};
+
+    #[tokio::test]
+    async fn revoke_token_rejects_only_that_token() {
+        let ctx = TestCtx::new("revoke_token").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Revoked",
+                "revoked@example.com",
+                "password123",
+                "Revoke Org",
+            )
+            .await
+            .expect("auth fixture");
+        let credentials = CredentialsDto {
+            email: fixture.email.clone(),
+            password: fixture.password.clone(),
+        };
+
+        // Without REDIS_URL the redis store falls back to memory
+        for store in [
+            TokenDenylistStore::Database,
+            TokenDenylistStore::Memory,
+            TokenDenylistStore::Redis,
+        ] {
+            let mut state = ctx.state.clone();
+            state.token_denylist = TokenDenylist::new(store);
+
+            let revoked = authenticate(&state, &credentials, SessionClientDto::default())
+                .await
+                .expect("login");
+            let kept = authenticate(&state, &credentials, SessionClientDto::default())
+                .await
+                .expect("login again");
+
+            let payload = verify_auth_token(&revoked.token, &state.token_keys).expect("payload");
+            assert!(payload.token_id.is_some());
+            revoke_token_svc(&state, &payload).await.expect("revoke");
+
+            assert!(
+                authenticate_token_svc(&state, &revoked.token)
+                    .await
+                    .is_err(),
+                "{:?} store denies the token",
+                store
+            );
+            authenticate_token_svc(&state, &kept.token)
+                .await
+                .expect("other tokens keep working");
+        }
+    }
+
+    #[tokio::test]
+    async fn force_logout_rejects_earlier_tokens() {
+        let ctx = TestCtx::new("force_logout").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture("Leaked", "leaked@example.com", "password123", "Leak Org")
+            .await
+            .expect("auth fixture");
+        let credentials = CredentialsDto {
+            email: fixture.email.clone(),
+            password: fixture.password.clone(),
+        };
+
+        let before = authenticate(&ctx.state, &credentials, SessionClientDto::default())
+            .await
+            .expect("login");
+        authenticate_token_svc(&ctx.state, &before.token)
+            .await
+            .expect("token works before the logout");
+
+        let revocation = force_logout_svc(&ctx.state, None, &fixture.user.id)
+            .await
+            .expect("force logout");
+        assert!(revocation.revoked_by.is_none());
+
+        assert!(
+            authenticate_token_svc(&ctx.state, &before.token)
+                .await
+                .is_err()
+        );
+
+        // Survives a restart, when the cache is empty
+        ctx.state.revocation_cache.invalidate_all();
+        assert!(
+            authenticate_token_svc(&ctx.state, &before.token)
+                .await
+                .is_err()
+        );
+
+        // Tokens carry seconds, sign in on the next one
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        let after = authenticate(&ctx.state, &credentials, SessionClientDto::default())
+            .await
+            .expect("login again");
+        authenticate_token_svc(&ctx.state, &after.token)
+            .await
+            .expect("new token works");
+        assert!(
+            authenticate_token_svc(&ctx.state, &before.token)
+                .await
+                .is_err(),
+            "the cached actor does not revive old tokens"
+        );
+    }
+}
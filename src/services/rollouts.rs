@@ -0,0 +1,88 @@
+use ring::digest::{SHA256, digest};
+
+use crate::config::{RolloutKey, RouteRollout};
+use crate::dto::ActorPayloadDto;
+
+/// Implementation of a canaried route a request is served
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteVariant {
+    Stable,
+    Canary,
+}
+
+impl RouteVariant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteVariant::Stable => "stable",
+            RouteVariant::Canary => "canary",
+        }
+    }
+}
+
+/// Stable bucket from 0 to 99 for the subject on the route.
+///
+/// The route is part of the hash so each rollout picks its own cohort.
+pub fn rollout_bucket(route: &str, subject: &str) -> u8 {
+    let hash = digest(&SHA256, format!("{}:{}", route, subject).as_bytes());
+    let bytes = hash.as_ref();
+    let value = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    (value % 100) as u8
+}
+
+/// Picks the variant for the actor, anonymous requests get the stable one
+pub fn route_variant(
+    route: &str,
+    rollout: &RouteRollout,
+    actor: Option<&ActorPayloadDto>,
+) -> RouteVariant {
+    let subject = actor.map(|actor| match rollout.key {
+        RolloutKey::Org => actor.org_id.as_str(),
+        RolloutKey::User => actor.id.as_str(),
+    });
+
+    match subject {
+        Some(subject) if !subject.is_empty() => {
+            match rollout_bucket(route, subject) < rollout.percent {
+                true => RouteVariant::Canary,
+                false => RouteVariant::Stable,
+            }
+        }
+        _ => RouteVariant::Stable,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{RolloutKey, RouteRollout};
+
+    use super::{RouteVariant, rollout_bucket, route_variant};
+
+    #[test]
+    fn rollout_covers_the_configured_share() {
+        let rollout = |percent: u8| RouteRollout {
+            percent,
+            key: RolloutKey::Org,
+            gated: false,
+        };
+
+        // Same subject always lands in the same bucket
+        assert_eq!(
+            rollout_bucket("/oauth/profile", "org_1"),
+            rollout_bucket("/oauth/profile", "org_1")
+        );
+
+        let canaries = |percent: u8| {
+            (0..1000)
+                .filter(|i| rollout_bucket("/oauth/profile", &format!("org_{}", i)) < percent)
+                .count()
+        };
+        assert_eq!(canaries(0), 0);
+        assert_eq!(canaries(100), 1000);
+        assert!((150..350).contains(&canaries(25)));
+
+        assert_eq!(
+            route_variant("/oauth/profile", &rollout(100), None),
+            RouteVariant::Stable
+        );
+    }
+}
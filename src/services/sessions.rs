@@ -0,0 +1,193 @@
+use chrono::Utc;
+use snafu::{OptionExt, ensure};
+use tracing::{info, instrument};
+
+use crate::Result;
+use crate::db::DbMapper;
+use crate::dto::{SessionClientDto, UserSessionDto};
+use crate::error::{CsrfTokenSnafu, InvalidAuthTokenSnafu, NotFoundSnafu};
+use crate::run::AppState;
+use crate::services::token::{EXP_DURATION, verify_csrf_token};
+use crate::utils::{IdPrefix, generate_id};
+
+/// Longest user agent kept for a session
+const MAX_USER_AGENT_LEN: usize = 250;
+
+/// Starts a session for a sign in, it lasts as long as the login token
+#[instrument(level = "debug", skip_all)]
+pub async fn start_session_svc(
+    db: &DbMapper,
+    user_id: &str,
+    client: SessionClientDto,
+) -> Result<UserSessionDto> {
+    let now = Utc::now().timestamp_millis();
+    let user_agent = client
+        .user_agent
+        .map(|value| value.trim().chars().take(MAX_USER_AGENT_LEN).collect())
+        .filter(|value: &String| !value.is_empty());
+
+    db.user_sessions
+        .create(UserSessionDto {
+            id: generate_id(IdPrefix::UserSession),
+            user_id: user_id.to_string(),
+            user_agent,
+            ip_address: client.ip_address,
+            created_at: now,
+            last_used_at: now,
+            expires_at: now + EXP_DURATION * 1000,
+            revoked_at: None,
+            current: false,
+        })
+        .await
+}
+
+/// Ensures the session behind a token is still active and records its use
+#[instrument(level = "debug", skip_all)]
+pub async fn verify_session_svc(state: &AppState, session_id: &str, user_id: &str) -> Result<()> {
+    let now = Utc::now().timestamp_millis();
+    let session = state.db.user_sessions.get(session_id.to_string()).await?;
+    let session = session.context(InvalidAuthTokenSnafu)?;
+
+    ensure!(
+        session.user_id == user_id && session.is_active(now),
+        InvalidAuthTokenSnafu
+    );
+
+    state.db.user_sessions.touch(session.id, now).await
+}
+
+/// Active sessions of the user, `current_id` marks the one making the request
+#[instrument(level = "debug", skip_all)]
+pub async fn list_user_sessions_svc(
+    state: &AppState,
+    user_id: &str,
+    current_id: Option<&str>,
+) -> Result<Vec<UserSessionDto>> {
+    let now = Utc::now().timestamp_millis();
+    let mut sessions = state
+        .db
+        .user_sessions
+        .list_active(user_id.to_string(), now)
+        .await?;
+
+    for session in sessions.iter_mut() {
+        session.current = current_id == Some(session.id.as_str());
+    }
+
+    Ok(sessions)
+}
+
+/// Signs the user out of one session, its tokens are rejected from then on
+#[instrument(level = "debug", skip_all)]
+pub async fn revoke_user_session_svc(
+    state: &AppState,
+    user_id: &str,
+    session_id: &str,
+) -> Result<()> {
+    let now = Utc::now().timestamp_millis();
+    let revoked = state
+        .db
+        .user_sessions
+        .revoke(user_id.to_string(), session_id.to_string(), now)
+        .await?;
+
+    ensure!(
+        revoked,
+        NotFoundSnafu {
+            msg: "Session not found".to_string()
+        }
+    );
+
+    info!(
+        user_id = user_id,
+        session_id = session_id,
+        "session.revoked"
+    );
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn revoke_user_session_web_svc(
+    state: &AppState,
+    user_id: &str,
+    session_id: &str,
+    csrf_token: &str,
+) -> Result<()> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == session_id, CsrfTokenSnafu);
+
+    revoke_user_session_svc(state, user_id, session_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::{CredentialsDto, SessionClientDto};
+    use crate::services::auth::{authenticate, authenticate_token_svc};
+    use crate::services::token::verify_auth_token;
+    use crate::test::TestCtx;
+
+    use super::{list_user_sessions_svc, revoke_user_session_svc};
+
+    #[tokio::test]
+    async fn revoked_session_rejects_its_tokens_only() {
+        let ctx = TestCtx::new("user_sessions").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Session User",
+                "session@example.com",
+                "password123",
+                "Session Org",
+            )
+            .await
+            .expect("auth fixture");
+        let credentials = CredentialsDto {
+            email: fixture.email.clone(),
+            password: fixture.password.clone(),
+        };
+        let client = SessionClientDto {
+            user_agent: Some("Firefox".to_string()),
+            ip_address: Some("203.0.113.7".to_string()),
+        };
+
+        let laptop = authenticate(&ctx.state, &credentials, client.clone())
+            .await
+            .expect("login");
+        let phone = authenticate(&ctx.state, &credentials, SessionClientDto::default())
+            .await
+            .expect("login");
+        let laptop_id = verify_auth_token(&laptop.token, &ctx.state.token_keys)
+            .expect("token")
+            .session_id
+            .expect("session id");
+
+        let sessions = list_user_sessions_svc(&ctx.state, &fixture.user.id, Some(&laptop_id))
+            .await
+            .expect("sessions");
+        assert_eq!(sessions.len(), 2);
+        let current = sessions.iter().find(|s| s.current).expect("current");
+        assert_eq!(current.id, laptop_id);
+        assert_eq!(current.user_agent.as_deref(), Some("Firefox"));
+        assert_eq!(current.ip_address.as_deref(), Some("203.0.113.7"));
+
+        revoke_user_session_svc(&ctx.state, &fixture.user.id, &laptop_id)
+            .await
+            .expect("revoke");
+
+        assert!(
+            authenticate_token_svc(&ctx.state, &laptop.token)
+                .await
+                .is_err()
+        );
+        authenticate_token_svc(&ctx.state, &phone.token)
+            .await
+            .expect("other sessions keep working");
+
+        // Already revoked
+        assert!(
+            revoke_user_session_svc(&ctx.state, &fixture.user.id, &laptop_id)
+                .await
+                .is_err()
+        );
+    }
+}
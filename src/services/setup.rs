@@ -1,55 +1,284 @@
-use snafu::ensure;
+use snafu::{OptionExt, ensure};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{info, instrument, warn};
 
-use crate::dto::{NewPasswordDto, NewUserDto, SetupBodyDto, SuperuserDto};
-use crate::error::ValidationSnafu;
-use crate::services::password::hash_password;
+use crate::config::Config;
+use crate::ctx::AuditCtx;
+use crate::db::DbMapper;
+use crate::dto::{
+    NewPasswordDto, NewUserDto, NewUserWithPasswordDto, SetupBodyDto, SuperuserDto,
+    TokenRevocationDto, UpdateUserDto, UserDto,
+};
+use crate::error::{ConflictSnafu, ServiceSnafu, ValidationSnafu};
+use crate::services::password::{hash_password, verify_password};
+use crate::validators::validate_payload;
 use crate::{Result, run::AppState};
 
+/// Set once this process has printed the setup key
+static SETUP_KEY_LOGGED: AtomicBool = AtomicBool::new(false);
+
+/// Prints the setup key to the logs, once per process.
+pub fn log_setup_key(config: &Config) {
+    if let Some(setup_key) = &config.superuser.setup_key
+        && !SETUP_KEY_LOGGED.swap(true, Ordering::Relaxed)
+    {
+        warn!("No active superuser, setup key: {}", setup_key);
+    }
+}
+
+/// Setup is open while no superuser can sign in, either before the first
+/// setup or after every superuser account was deactivated or deleted.
+#[instrument(level = "debug", skip_all)]
+pub async fn setup_required_svc(db: &DbMapper) -> Result<bool> {
+    let superusers = db.superusers.list_active().await?;
+    Ok(superusers.is_empty())
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn setup_superuser_svc(state: &AppState, payload: SetupBodyDto) -> Result<SuperuserDto> {
+    validate_payload(&payload)?;
+
     // Validate setup key
     ensure!(
-        Some(payload.setup_key) == state.config.superuser.setup_key,
+        state.config.superuser.setup_key.as_deref() == Some(payload.setup_key.as_str()),
         ValidationSnafu {
             msg: "Invalid setup key".to_string(),
         }
     );
 
-    // Make sure there are no superusers yet
-    let superusers = state.db.superusers.list().await?;
+    let db = &state.db;
+    let email = payload.email.trim().to_string();
+
+    if !setup_required_svc(db).await? {
+        // Repeating a completed setup with the same credentials is not an error
+        return completed_setup(db, &email, &payload.password)
+            .await?
+            .context(ValidationSnafu {
+                msg: "Superuser already exists".to_string(),
+            });
+    }
+
+    let hashed = hash_password(&payload.password)?;
+
+    let superuser = match db.users.find_by_email(email.clone()).await? {
+        Some(user) => {
+            // Only former superusers are recovered, other accounts keep their access
+            let former = db.superusers.get(user.id.clone()).await?;
+            let former = former.context(ConflictSnafu {
+                msg: "Email belongs to an account that is not a superuser, use another email"
+                    .to_string(),
+            })?;
+            restore_superuser(db, user, former, hashed).await?
+        }
+        None => {
+            ensure_email_available(db, &email).await?;
+            create_superuser_account(
+                db,
+                NewUserWithPasswordDto {
+                    email: email.clone(),
+                    name: "Superuser".to_string(),
+                    password: hashed,
+                },
+            )
+            .await?
+        }
+    };
+
+    info!(
+        user_id = superuser.id.as_str(),
+        email = email.as_str(),
+        "superuser.setup_completed"
+    );
+
+    Ok(superuser)
+}
+
+/// The superuser behind a completed setup, when the credentials match
+async fn completed_setup(
+    db: &DbMapper,
+    email: &str,
+    password: &str,
+) -> Result<Option<SuperuserDto>> {
+    let Some(user) = db.users.find_by_email(email.to_string()).await? else {
+        return Ok(None);
+    };
+    let Some(superuser) = db.superusers.get(user.id.clone()).await? else {
+        return Ok(None);
+    };
+    let Some(hash) = db.passwords.get(user.id.clone()).await? else {
+        return Ok(None);
+    };
+
+    let matched = user.status == "active" && verify_password(password, &hash.password)?;
+    Ok(matched.then_some(superuser))
+}
+
+/// Recovers the account of a former superuser.
+///
+/// The account gets the new password and is reactivated, tokens issued
+/// before the recovery are revoked.
+async fn restore_superuser(
+    db: &DbMapper,
+    user: UserDto,
+    superuser: SuperuserDto,
+    hashed_password: String,
+) -> Result<SuperuserDto> {
+    if user.status != "active" {
+        let data = UpdateUserDto {
+            name: None,
+            status: Some("active".to_string()),
+        };
+        db.users
+            .update(&AuditCtx::current(), user.id.clone(), data)
+            .await?;
+    }
+
+    let password = NewPasswordDto {
+        password: hashed_password,
+    };
+    let updated = db
+        .passwords
+        .update(user.id.clone(), password.clone())
+        .await?;
+    if !updated {
+        db.passwords.create(user.id.clone(), password).await?;
+    }
+
+    db.token_revocations
+        .upsert(TokenRevocationDto {
+            user_id: user.id.clone(),
+            revoked_at: chrono::Utc::now().timestamp_millis(),
+            revoked_by: None,
+        })
+        .await?;
+
+    info!(user_id = user.id.as_str(), "superuser.restored");
+
+    Ok(superuser)
+}
+
+/// Email must be unique across primary and secondary emails
+async fn ensure_email_available(db: &DbMapper, email: &str) -> Result<()> {
+    let primary = db.users.find_by_email(email.to_string()).await?;
+    let secondary = db.user_emails.find_by_email(email.to_string()).await?;
     ensure!(
-        superusers.is_empty(),
+        primary.is_none() && secondary.is_none(),
         ValidationSnafu {
-            msg: "Superuser already exists".to_string(),
+            msg: "Email already exists".to_string(),
         }
     );
+    Ok(())
+}
 
-    let new_user = NewUserDto {
-        email: payload.email,
+/// New superuser account, the first one runs the setup and later ones join
+/// the superuser org it created. The password must be hashed already.
+async fn create_superuser_account(
+    db: &DbMapper,
+    data: NewUserWithPasswordDto,
+) -> Result<SuperuserDto> {
+    let superusers = db.superusers.list().await?;
+
+    match superusers.is_empty() {
+        true => {
+            let new_user = NewUserDto {
+                email: data.email,
+                name: data.name,
+            };
+            let new_password = NewPasswordDto {
+                password: data.password,
+            };
+            db.superusers.setup(new_user, new_password).await
+        }
+        false => {
+            let user = db
+                .users
+                .create_with_password(&AuditCtx::current(), data)
+                .await?;
+            db.superusers.grant(user.id).await?.context(ServiceSnafu {
+                msg: "Superuser org not found, run the setup first".to_string(),
+            })
+        }
+    }
+}
+
+/// Creates a superuser account from the command line, no setup key needed.
+///
+/// The first one runs the setup, later ones join the superuser org it created.
+#[instrument(level = "debug", skip_all)]
+pub async fn create_superuser_svc(
+    db: &DbMapper,
+    email: &str,
+    password: &str,
+) -> Result<SuperuserDto> {
+    let data = NewUserWithPasswordDto {
+        email: email.trim().to_string(),
         name: "Superuser".to_string(),
+        password: password.to_string(),
     };
+    validate_payload(&data)?;
+    ensure_email_available(db, &data.email).await?;
 
-    let new_password = NewPasswordDto {
-        password: hash_password(&payload.password)?,
-    };
+    let email = data.email.clone();
+    let superuser = create_superuser_account(
+        db,
+        NewUserWithPasswordDto {
+            password: hash_password(&data.password)?,
+            ..data
+        },
+    )
+    .await?;
 
-    let superuser = state.db.superusers.setup(new_user, new_password).await?;
+    info!(
+        user_id = superuser.id.as_str(),
+        email = email.as_str(),
+        "superuser.created_from_cli"
+    );
 
     Ok(superuser)
 }
 
+/// Whether setup is done, prints the setup key when it is not
+#[instrument(level = "debug", skip_all)]
 pub async fn setup_status_svc(state: &AppState) -> Result<bool> {
-    let superusers = state.db.superusers.list().await?;
-    Ok(!superusers.is_empty())
+    let required = setup_required_svc(&state.db).await?;
+    if required {
+        log_setup_key(&state.config);
+    }
+    Ok(!required)
 }
 
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
-    use crate::dto::SetupBodyDto;
+    use crate::Error;
+    use crate::ctx::AuditCtx;
+    use crate::dto::{SetupBodyDto, UpdateUserDto};
+    use crate::services::password::verify_password;
     use crate::test::TestCtx;
 
-    use super::{setup_status_svc, setup_superuser_svc};
+    use super::{create_superuser_svc, setup_status_svc, setup_superuser_svc};
+
+    fn setup_body(email: &str, password: &str) -> SetupBodyDto {
+        SetupBodyDto {
+            setup_key: TEST_SETUP_KEY.to_string(),
+            email: email.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    async fn deactivate(ctx: &TestCtx, user_id: &str) {
+        let data = UpdateUserDto {
+            name: None,
+            status: Some("inactive".to_string()),
+        };
+        ctx.state
+            .db
+            .users
+            .update(&AuditCtx::current(), user_id.to_string(), data)
+            .await
+            .expect("deactivate user");
+    }
 
     const TEST_SETUP_KEY: &str = "12345678-1234-1234-1234-123456789012";
 
@@ -172,4 +401,157 @@ mod tests {
 
         assert!(status);
     }
+
+    #[tokio::test]
+    async fn create_superuser_svc_runs_setup_then_grants() {
+        let ctx = TestCtx::new("create_superuser_cli")
+            .await
+            .expect("test ctx");
+        let db = &ctx.state.db;
+
+        let first = create_superuser_svc(db, "root@example.com", "password123")
+            .await
+            .expect("first superuser");
+        let second = create_superuser_svc(db, "second@example.com", "password123")
+            .await
+            .expect("second superuser");
+        assert_ne!(first.id, second.id);
+
+        let superusers = db.superusers.list().await.expect("list superusers");
+        assert_eq!(superusers.len(), 2);
+
+        let duplicate = create_superuser_svc(db, "second@example.com", "password123").await;
+        let Err(err) = duplicate else {
+            panic!("duplicate email should fail");
+        };
+        assert_eq!(err.to_string(), "Email already exists");
+    }
+
+    #[tokio::test]
+    async fn setup_superuser_svc_repeats_completed_setup_with_same_credentials() {
+        let mut ctx = TestCtx::new("setup_superuser_idempotent")
+            .await
+            .expect("test ctx");
+        set_setup_key(&mut ctx, TEST_SETUP_KEY);
+
+        let first = setup_superuser_svc(&ctx.state, setup_body("root@example.com", "password123"))
+            .await
+            .expect("first setup should succeed");
+        let again = setup_superuser_svc(&ctx.state, setup_body("root@example.com", "password123"))
+            .await
+            .expect("same setup should succeed again");
+        assert_eq!(first.id, again.id);
+
+        let wrong =
+            setup_superuser_svc(&ctx.state, setup_body("root@example.com", "password456")).await;
+        assert!(wrong.is_err(), "other credentials should fail");
+
+        let superusers = ctx.state.db.superusers.list().await.expect("list");
+        assert_eq!(superusers.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn setup_superuser_svc_reruns_when_superusers_are_inactive() {
+        let mut ctx = TestCtx::new("setup_superuser_rerun")
+            .await
+            .expect("test ctx");
+        set_setup_key(&mut ctx, TEST_SETUP_KEY);
+
+        let first = setup_superuser_svc(&ctx.state, setup_body("root@example.com", "password123"))
+            .await
+            .expect("first setup should succeed");
+        deactivate(&ctx, &first.id).await;
+
+        let status = setup_status_svc(&ctx.state).await.expect("status");
+        assert!(!status, "setup reopens without an active superuser");
+
+        let second =
+            setup_superuser_svc(&ctx.state, setup_body("backup@example.com", "password123"))
+                .await
+                .expect("setup should run again");
+        assert_ne!(first.id, second.id);
+
+        let status = setup_status_svc(&ctx.state).await.expect("status");
+        assert!(status);
+
+        let superusers = ctx.state.db.superusers.list().await.expect("list");
+        assert_eq!(superusers.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn setup_superuser_svc_restores_existing_superuser_account() {
+        let mut ctx = TestCtx::new("setup_superuser_restore")
+            .await
+            .expect("test ctx");
+        set_setup_key(&mut ctx, TEST_SETUP_KEY);
+        let db = &ctx.state.db;
+
+        let first = setup_superuser_svc(&ctx.state, setup_body("root@example.com", "password123"))
+            .await
+            .expect("first setup should succeed");
+        deactivate(&ctx, &first.id).await;
+
+        let restored =
+            setup_superuser_svc(&ctx.state, setup_body("root@example.com", "newpassword456"))
+                .await
+                .expect("setup should restore the account");
+        assert_eq!(first.id, restored.id);
+
+        let user = db
+            .users
+            .get(first.id.clone())
+            .await
+            .expect("get user")
+            .expect("user exists");
+        assert_eq!(user.status, "active");
+
+        let password = db
+            .passwords
+            .get(first.id.clone())
+            .await
+            .expect("get password")
+            .expect("password exists");
+        assert!(verify_password("newpassword456", &password.password).unwrap());
+
+        let revocation = db.token_revocations.find(first.id).await.expect("find");
+        assert!(revocation.is_some());
+    }
+
+    #[tokio::test]
+    async fn setup_superuser_svc_does_not_take_over_other_accounts() {
+        let mut ctx = TestCtx::new("setup_superuser_takeover")
+            .await
+            .expect("test ctx");
+        set_setup_key(&mut ctx, TEST_SETUP_KEY);
+        let db = &ctx.state.db;
+
+        let user = ctx
+            .seed_user_with_password("Regular", "regular@example.com", "password123")
+            .await
+            .expect("seed user");
+
+        let result = setup_superuser_svc(
+            &ctx.state,
+            setup_body("regular@example.com", "newpassword456"),
+        )
+        .await;
+        let Err(err) = result else {
+            panic!("regular accounts should not be restored");
+        };
+        assert!(matches!(err, Error::Conflict { .. }));
+
+        let superuser = db.superusers.get(user.id.clone()).await.expect("get");
+        assert!(superuser.is_none());
+
+        let password = db
+            .passwords
+            .get(user.id.clone())
+            .await
+            .expect("get password")
+            .expect("password exists");
+        assert!(verify_password("password123", &password.password).unwrap());
+
+        let status = setup_status_svc(&ctx.state).await.expect("status");
+        assert!(!status, "setup stays open");
+    }
 }
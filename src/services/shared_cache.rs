@@ -0,0 +1,439 @@
+use std::time::{Duration, Instant};
+
+use moka::Expiry;
+use moka::sync::Cache;
+use redis::AsyncCommands;
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use snafu::ResultExt;
+use tracing::{error, info, warn};
+
+use crate::config::RedisConfig;
+use crate::error::RedisSnafu;
+use crate::{Error, Result};
+
+/// Redis calls give up after this long so a stalled server falls back quickly
+const REDIS_TIMEOUT_SECS: u64 = 2;
+
+/// Every key written to Redis starts with this
+const REDIS_KEY_PREFIX: &str = "yaas:";
+
+/// Entries kept in memory when Redis is not used or unavailable
+const MEMORY_MAX_CAPACITY: u64 = 100_000;
+
+/// Key value store with expiring entries
+pub trait CacheStore {
+    fn get(&self, key: &str) -> impl Future<Output = Result<Option<String>>> + Send;
+
+    fn set(
+        &self,
+        key: &str,
+        value: String,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    fn delete(&self, key: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Deletes every key starting with the prefix
+    fn delete_prefix(&self, prefix: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Counts a hit on the key, the count resets `window` after its first hit.
+    ///
+    /// Returns the count and the time left until it resets.
+    fn incr(
+        &self,
+        key: &str,
+        window: Duration,
+    ) -> impl Future<Output = Result<(u64, Duration)>> + Send;
+}
+
+#[derive(Clone)]
+struct MemoryEntry {
+    value: String,
+    expires_at: Instant,
+}
+
+/// Expires each entry at its own `expires_at`
+struct MemoryEntryExpiry;
+
+impl Expiry<String, MemoryEntry> for MemoryEntryExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &String,
+        value: &MemoryEntry,
+        created_at: Instant,
+    ) -> Option<Duration> {
+        Some(value.expires_at.saturating_duration_since(created_at))
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &String,
+        value: &MemoryEntry,
+        updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        Some(value.expires_at.saturating_duration_since(updated_at))
+    }
+}
+
+/// Entries kept on this instance only
+pub struct MemoryStore {
+    entries: Cache<String, MemoryEntry>,
+}
+
+impl MemoryStore {
+    pub fn new(max_capacity: u64) -> Self {
+        Self {
+            entries: Cache::builder()
+                .max_capacity(max_capacity)
+                .expire_after(MemoryEntryExpiry)
+                .build(),
+        }
+    }
+}
+
+impl CacheStore for MemoryStore {
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        let now = Instant::now();
+        Ok(self
+            .entries
+            .get(key)
+            .filter(|entry| entry.expires_at > now)
+            .map(|entry| entry.value))
+    }
+
+    async fn set(&self, key: &str, value: String, ttl: Duration) -> Result<()> {
+        let entry = MemoryEntry {
+            value,
+            expires_at: Instant::now() + ttl,
+        };
+        self.entries.insert(key.to_string(), entry);
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.entries.invalidate(key);
+        Ok(())
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        for (key, _) in self.entries.iter() {
+            if key.starts_with(prefix) {
+                self.entries.invalidate(key.as_str());
+            }
+        }
+        Ok(())
+    }
+
+    async fn incr(&self, key: &str, window: Duration) -> Result<(u64, Duration)> {
+        let now = Instant::now();
+
+        // Upserts on the same key run one at a time
+        let entry = self
+            .entries
+            .entry(key.to_string())
+            .and_upsert_with(|current| {
+                let current = current
+                    .map(|entry| entry.into_value())
+                    .filter(|entry| entry.expires_at > now);
+
+                match current {
+                    Some(entry) => MemoryEntry {
+                        value: (entry.value.parse::<u64>().unwrap_or(0) + 1).to_string(),
+                        expires_at: entry.expires_at,
+                    },
+                    None => MemoryEntry {
+                        value: "1".to_string(),
+                        expires_at: now + window,
+                    },
+                }
+            })
+            .into_value();
+
+        Ok((
+            entry.value.parse::<u64>().unwrap_or(1),
+            entry.expires_at.saturating_duration_since(now),
+        ))
+    }
+}
+
+/// Entries shared by every instance connected to the same Redis
+pub struct RedisStore {
+    conn: ConnectionManager,
+}
+
+impl RedisStore {
+    pub async fn connect(config: &RedisConfig) -> Result<Self> {
+        let client = redis::Client::open(config.url.as_str()).map_err(|e| Error::Config {
+            msg: format!("REDIS_URL must be a valid Redis URL: {}", e),
+        })?;
+
+        let manager_config = ConnectionManagerConfig::new()
+            .set_connection_timeout(Duration::from_secs(REDIS_TIMEOUT_SECS))
+            .set_response_timeout(Duration::from_secs(REDIS_TIMEOUT_SECS))
+            .set_number_of_retries(1);
+
+        let conn = ConnectionManager::new_with_config(client, manager_config)
+            .await
+            .context(RedisSnafu)?;
+
+        Ok(Self { conn })
+    }
+
+    fn key(key: &str) -> String {
+        format!("{}{}", REDIS_KEY_PREFIX, key)
+    }
+}
+
+impl CacheStore for RedisStore {
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        let mut conn = self.conn.clone();
+        conn.get(Self::key(key)).await.context(RedisSnafu)
+    }
+
+    async fn set(&self, key: &str, value: String, ttl: Duration) -> Result<()> {
+        let mut conn = self.conn.clone();
+        let ttl_ms = (ttl.as_millis() as u64).max(1);
+        conn.pset_ex::<_, _, ()>(Self::key(key), value, ttl_ms)
+            .await
+            .context(RedisSnafu)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let mut conn = self.conn.clone();
+        conn.del::<_, ()>(Self::key(key)).await.context(RedisSnafu)
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        let mut conn = self.conn.clone();
+        let pattern = format!("{}*", Self::key(prefix));
+
+        let mut keys: Vec<String> = Vec::new();
+        {
+            let mut iter = conn
+                .scan_match::<_, String>(pattern)
+                .await
+                .context(RedisSnafu)?;
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
+            }
+        }
+
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        conn.del::<_, ()>(keys).await.context(RedisSnafu)
+    }
+
+    async fn incr(&self, key: &str, window: Duration) -> Result<(u64, Duration)> {
+        let mut conn = self.conn.clone();
+        let key = Self::key(key);
+        let window_ms = (window.as_millis() as u64).max(1);
+
+        // The window starts with the first hit and is not extended by later ones
+        let (count, ttl_ms): (u64, i64) = redis::pipe()
+            .atomic()
+            .cmd("SET")
+            .arg(&key)
+            .arg(0)
+            .arg("PX")
+            .arg(window_ms)
+            .arg("NX")
+            .ignore()
+            .cmd("INCR")
+            .arg(&key)
+            .cmd("PTTL")
+            .arg(&key)
+            .query_async(&mut conn)
+            .await
+            .context(RedisSnafu)?;
+
+        Ok((count, Duration::from_millis(ttl_ms.max(0) as u64)))
+    }
+}
+
+/// Cache shared by the instances of a deployment, selected with `REDIS_URL`.
+///
+/// Calls fall back to memory on this instance while Redis fails, deletes
+/// are applied to both so entries written during an outage are dropped too.
+pub struct SharedCache {
+    redis: Option<RedisStore>,
+    memory: MemoryStore,
+}
+
+impl SharedCache {
+    /// In-memory only, for single instance deployments
+    pub fn memory() -> Self {
+        Self {
+            redis: None,
+            memory: MemoryStore::new(MEMORY_MAX_CAPACITY),
+        }
+    }
+
+    /// Connects to Redis when configured, an unreachable Redis leaves the
+    /// cache in memory
+    pub async fn connect(config: Option<&RedisConfig>) -> Result<Self> {
+        let Some(config) = config else {
+            return Ok(Self::memory());
+        };
+
+        let redis = match RedisStore::connect(config).await {
+            Ok(redis) => {
+                info!("shared_cache.redis_connected");
+                Some(redis)
+            }
+            Err(Error::Config { msg }) => return Err(Error::Config { msg }),
+            Err(e) => {
+                error!("Redis is unavailable, caching in memory instead: {}", e);
+                None
+            }
+        };
+
+        Ok(Self {
+            redis,
+            memory: MemoryStore::new(MEMORY_MAX_CAPACITY),
+        })
+    }
+
+    /// Whether entries are shared with other instances
+    pub fn is_redis(&self) -> bool {
+        self.redis.is_some()
+    }
+
+    pub async fn get(&self, key: &str) -> Option<String> {
+        if let Some(redis) = &self.redis {
+            match redis.get(key).await {
+                Ok(value) => return value,
+                Err(e) => redis_failed("get", &e),
+            }
+        }
+        self.memory.get(key).await.unwrap_or_default()
+    }
+
+    pub async fn set(&self, key: &str, value: String, ttl: Duration) {
+        if let Some(redis) = &self.redis {
+            match redis.set(key, value.clone(), ttl).await {
+                Ok(()) => return,
+                Err(e) => redis_failed("set", &e),
+            }
+        }
+        let _ = self.memory.set(key, value, ttl).await;
+    }
+
+    pub async fn delete(&self, key: &str) {
+        if let Some(redis) = &self.redis
+            && let Err(e) = redis.delete(key).await
+        {
+            redis_failed("delete", &e);
+        }
+        let _ = self.memory.delete(key).await;
+    }
+
+    pub async fn delete_prefix(&self, prefix: &str) {
+        if let Some(redis) = &self.redis
+            && let Err(e) = redis.delete_prefix(prefix).await
+        {
+            redis_failed("delete_prefix", &e);
+        }
+        let _ = self.memory.delete_prefix(prefix).await;
+    }
+
+    /// See `CacheStore::incr`
+    pub async fn incr(&self, key: &str, window: Duration) -> (u64, Duration) {
+        if let Some(redis) = &self.redis {
+            match redis.incr(key, window).await {
+                Ok(counted) => return counted,
+                Err(e) => redis_failed("incr", &e),
+            }
+        }
+        self.memory
+            .incr(key, window)
+            .await
+            .unwrap_or((1, Duration::ZERO))
+    }
+}
+
+fn redis_failed(op: &str, e: &Error) {
+    warn!(op = op, "shared_cache.redis_failed: {}", e);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::config::RedisConfig;
+
+    use super::{CacheStore, MemoryStore, SharedCache};
+
+    #[tokio::test]
+    async fn memory_store_counts_hits_within_the_window() {
+        let store = MemoryStore::new(100);
+        let window = Duration::from_millis(200);
+
+        let (count, left) = store.incr("ip:1", window).await.expect("incr");
+        assert_eq!(count, 1);
+        assert!(left <= window);
+
+        let (count, _) = store.incr("ip:1", window).await.expect("incr");
+        assert_eq!(count, 2);
+
+        let (count, _) = store.incr("ip:2", window).await.expect("incr");
+        assert_eq!(count, 1);
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+
+        let (count, _) = store.incr("ip:1", window).await.expect("incr");
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn memory_store_deletes_keys_by_prefix() {
+        let store = MemoryStore::new(100);
+        let ttl = Duration::from_secs(60);
+
+        store
+            .set("auth:1", "a".to_string(), ttl)
+            .await
+            .expect("set");
+        store
+            .set("auth:2", "b".to_string(), ttl)
+            .await
+            .expect("set");
+        store
+            .set("denylist:1", "c".to_string(), ttl)
+            .await
+            .expect("set");
+
+        store.delete_prefix("auth:").await.expect("delete");
+
+        assert_eq!(store.get("auth:1").await.expect("get"), None);
+        assert_eq!(store.get("auth:2").await.expect("get"), None);
+        assert_eq!(
+            store.get("denylist:1").await.expect("get"),
+            Some("c".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn unreachable_redis_falls_back_to_memory() {
+        let config = RedisConfig {
+            url: "redis://127.0.0.1:1".to_string(),
+        };
+        let cache = SharedCache::connect(Some(&config)).await.expect("cache");
+        assert!(!cache.is_redis());
+
+        cache
+            .set("auth:1", "a".to_string(), Duration::from_secs(60))
+            .await;
+        assert_eq!(cache.get("auth:1").await, Some("a".to_string()));
+
+        cache.delete("auth:1").await;
+        assert_eq!(cache.get("auth:1").await, None);
+
+        let invalid = RedisConfig {
+            url: "not a url".to_string(),
+        };
+        assert!(SharedCache::connect(Some(&invalid)).await.is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::Result;
+use crate::dto::{SUGGESTION_LIMIT, SuggestionBufDto, SuggestionParamsDto};
+use crate::run::AppState;
+use crate::validators::validate_payload;
+
+/// Users that can become the owner of an org
+#[instrument(level = "debug", skip_all)]
+pub async fn suggest_org_owners_svc(
+    state: &AppState,
+    params: SuggestionParamsDto,
+) -> Result<Arc<SuggestionBufDto>> {
+    validate_payload(&params)?;
+
+    let key = cache_key("owners", None, &params);
+    if let Some(buf) = state.suggestion_cache.get(&key) {
+        return Ok(buf);
+    }
+
+    let buf = state
+        .db
+        .suggestions
+        .users(None, params, SUGGESTION_LIMIT)
+        .await?;
+    let buf = Arc::new(buf);
+    state.suggestion_cache.insert(key, buf.clone());
+
+    Ok(buf)
+}
+
+/// Users that are not yet members of the org
+#[instrument(level = "debug", skip_all)]
+pub async fn suggest_org_members_svc(
+    state: &AppState,
+    org_id: &str,
+    params: SuggestionParamsDto,
+) -> Result<Arc<SuggestionBufDto>> {
+    validate_payload(&params)?;
+
+    let key = cache_key("members", Some(org_id), &params);
+    if let Some(buf) = state.suggestion_cache.get(&key) {
+        return Ok(buf);
+    }
+
+    let buf = state
+        .db
+        .suggestions
+        .users(Some(org_id.to_string()), params, SUGGESTION_LIMIT)
+        .await?;
+    let buf = Arc::new(buf);
+    state.suggestion_cache.insert(key, buf.clone());
+
+    Ok(buf)
+}
+
+/// Drops cached suggestions after users or memberships change
+pub fn invalidate_suggestions(state: &AppState) {
+    state.suggestion_cache.invalidate_all();
+}
+
+fn cache_key(kind: &str, org_id: Option<&str>, params: &SuggestionParamsDto) -> String {
+    format!(
+        "{}:{}:{}:{}",
+        kind,
+        org_id.unwrap_or_default(),
+        params.exclude_id.as_deref().unwrap_or_default(),
+        params.normalized_keyword().unwrap_or_default()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ctx::AuditCtx;
+    use crate::dto::{NewOrgMemberDto, SuggestionParamsDto, UpdateUserDto};
+    use crate::services::users::update_user_svc;
+    use crate::test::TestCtx;
+
+    use super::{invalidate_suggestions, suggest_org_members_svc, suggest_org_owners_svc};
+
+    fn keyword(value: &str) -> SuggestionParamsDto {
+        SuggestionParamsDto {
+            keyword: Some(value.to_string()),
+            exclude_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn owner_suggestions_rank_prefix_and_active_users_first() {
+        let ctx = TestCtx::new("suggestions_ranked").await.expect("test ctx");
+        let infix = ctx
+            .seed_user_with_password("Infix", "aaa.ann@example.com", "password")
+            .await
+            .expect("seed infix");
+        let inactive = ctx
+            .seed_user_with_password("Inactive", "ann.inactive@example.com", "password")
+            .await
+            .expect("seed inactive");
+        let prefix = ctx
+            .seed_user_with_password("Prefix", "ann.zed@example.com", "password")
+            .await
+            .expect("seed prefix");
+
+        update_user_svc(
+            &ctx.state,
+            &inactive.id,
+            UpdateUserDto {
+                name: None,
+                status: Some("inactive".to_string()),
+            },
+        )
+        .await
+        .expect("deactivate user");
+
+        let buf = suggest_org_owners_svc(&ctx.state, keyword("ANN"))
+            .await
+            .expect("suggestions");
+        let ids: Vec<&str> = buf.items.iter().map(|item| item.id.as_str()).collect();
+
+        assert_eq!(
+            ids,
+            vec![prefix.id.as_str(), inactive.id.as_str(), infix.id.as_str()]
+        );
+        assert!(!buf.items[1].active);
+        assert!(!buf.has_more);
+    }
+
+    #[tokio::test]
+    async fn member_suggestions_exclude_members_and_hint_memberships() {
+        let ctx = TestCtx::new("suggestions_members").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture("Owner", "owner@example.com", "password", "Acme")
+            .await
+            .expect("seed fixture");
+        let candidate = ctx
+            .seed_user_with_password("Candidate", "candidate@example.com", "password")
+            .await
+            .expect("seed candidate");
+
+        let buf = suggest_org_members_svc(&ctx.state, &fixture.org.id, keyword("candidate"))
+            .await
+            .expect("suggestions");
+        assert_eq!(buf.items.len(), 1);
+        assert_eq!(buf.items[0].memberships, 0);
+
+        ctx.state
+            .db
+            .org_members
+            .create(
+                &AuditCtx::default(),
+                fixture.org.id.clone(),
+                NewOrgMemberDto {
+                    user_id: candidate.id.clone(),
+                    roles: vec!["OrgViewer".to_string()],
+                    status: "active".to_string(),
+                },
+            )
+            .await
+            .expect("add member");
+
+        // Cached until invalidated
+        let cached = suggest_org_members_svc(&ctx.state, &fixture.org.id, keyword("candidate"))
+            .await
+            .expect("suggestions");
+        assert_eq!(cached.items.len(), 1);
+
+        invalidate_suggestions(&ctx.state);
+
+        let buf = suggest_org_members_svc(&ctx.state, &fixture.org.id, keyword("candidate"))
+            .await
+            .expect("suggestions");
+        assert!(buf.items.is_empty());
+
+        let buf = suggest_org_owners_svc(&ctx.state, keyword("candidate"))
+            .await
+            .expect("suggestions");
+        assert_eq!(buf.items[0].memberships, 1);
+    }
+}
@@ -0,0 +1,501 @@
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ensure};
+use tracing::instrument;
+
+use crate::ctx::AuditCtx;
+use crate::dto::{
+    NewTeamDto, OrgMemberDto, Permission, TeamDto, TeamMemberDto, UpdateTeamDto, role_permissions,
+};
+use crate::error::{CsrfTokenSnafu, NotFoundSnafu, OrgNotFoundSnafu, ValidationSnafu};
+use crate::run::AppState;
+use crate::services::auth::{invalidate_all_auth_cache, invalidate_auth_cache};
+use crate::services::token::verify_csrf_token;
+use crate::validators::validate_payload;
+use crate::{Error, Result};
+
+/// Team form, roles are submitted as repeated `roles` fields
+#[derive(Clone, Deserialize, Serialize)]
+pub struct TeamFormData {
+    pub token: String,
+    pub name: String,
+    pub description: String,
+    pub roles: Vec<String>,
+}
+
+impl TryFrom<Vec<(String, String)>> for TeamFormData {
+    type Error = Error;
+
+    fn try_from(pairs: Vec<(String, String)>) -> Result<Self> {
+        let mut token: Option<String> = None;
+        let mut name = String::new();
+        let mut description = String::new();
+        let mut roles: Vec<String> = Vec::new();
+
+        for (key, value) in pairs.into_iter() {
+            match key.as_str() {
+                "token" => token = Some(value),
+                "name" => name = value.trim().to_string(),
+                "description" => description = value.trim().to_string(),
+                "roles" if !value.is_empty() && !roles.contains(&value) => roles.push(value),
+                _ => {}
+            }
+        }
+
+        let Some(token) = token else {
+            return Err(Error::CsrfToken);
+        };
+
+        Ok(TeamFormData {
+            token,
+            name,
+            description,
+            roles,
+        })
+    }
+}
+
+/// Adds an org member to the team by their primary email
+#[derive(Clone, Deserialize, Serialize)]
+pub struct TeamMemberFormData {
+    pub token: String,
+    pub email: String,
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn list_teams_svc(state: &AppState, org_id: &str) -> Result<Vec<TeamDto>> {
+    state.db.teams.list(org_id.to_string()).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn get_team_svc(state: &AppState, org_id: &str, team_id: &str) -> Result<TeamDto> {
+    state
+        .db
+        .teams
+        .get(org_id.to_string(), team_id.to_string())
+        .await?
+        .context(NotFoundSnafu {
+            msg: "Team not found".to_string(),
+        })
+}
+
+async fn ensure_team_name_available(
+    state: &AppState,
+    org_id: &str,
+    name: &str,
+    team_id: Option<&str>,
+) -> Result<()> {
+    let existing = state
+        .db
+        .teams
+        .find_by_name(org_id.to_string(), name.to_string())
+        .await?;
+
+    ensure!(
+        existing.is_none_or(|existing| Some(existing.id.as_str()) == team_id),
+        ValidationSnafu {
+            msg: "Team name already exists".to_string(),
+        }
+    );
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn create_team_svc(state: &AppState, org_id: &str, data: NewTeamDto) -> Result<TeamDto> {
+    validate_payload(&data)?;
+
+    let _ = state
+        .db
+        .orgs
+        .get(org_id.to_string())
+        .await?
+        .context(OrgNotFoundSnafu)?;
+
+    ensure_team_name_available(state, org_id, &data.name, None).await?;
+
+    state
+        .db
+        .teams
+        .create(&AuditCtx::current(), org_id.to_string(), data)
+        .await
+}
+
+/// Updates the team, its members get the new role permissions on their next request
+#[instrument(level = "debug", skip_all)]
+pub async fn update_team_svc(
+    state: &AppState,
+    org_id: &str,
+    team_id: &str,
+    data: UpdateTeamDto,
+) -> Result<TeamDto> {
+    validate_payload(&data)?;
+
+    let team = get_team_svc(state, org_id, team_id).await?;
+
+    if let Some(name) = data.name.as_deref() {
+        ensure_team_name_available(state, org_id, name, Some(&team.id)).await?;
+    }
+
+    let updated = state
+        .db
+        .teams
+        .update(&AuditCtx::current(), team.id.clone(), data)
+        .await?;
+
+    if updated {
+        invalidate_all_auth_cache(state).await;
+    }
+
+    get_team_svc(state, org_id, team_id).await
+}
+
+/// Removes the team, its members lose the team's permissions right away
+#[instrument(level = "debug", skip_all)]
+pub async fn delete_team_svc(state: &AppState, org_id: &str, team_id: &str) -> Result<()> {
+    let team = get_team_svc(state, org_id, team_id).await?;
+
+    state.db.teams.delete(team.id).await?;
+    invalidate_all_auth_cache(state).await;
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn list_team_members_svc(
+    state: &AppState,
+    org_id: &str,
+    team_id: &str,
+) -> Result<Vec<TeamMemberDto>> {
+    let team = get_team_svc(state, org_id, team_id).await?;
+    state.db.teams.list_members(team.id).await
+}
+
+/// Membership of the user in the team's org, only org members can join its teams
+async fn find_team_org_member(
+    state: &AppState,
+    org_id: &str,
+    user_id: &str,
+) -> Result<OrgMemberDto> {
+    state
+        .db
+        .org_members
+        .find_member(org_id.to_string(), user_id.to_string())
+        .await?
+        .context(NotFoundSnafu {
+            msg: "Org member not found".to_string(),
+        })
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn add_team_member_svc(
+    state: &AppState,
+    org_id: &str,
+    team_id: &str,
+    user_id: &str,
+) -> Result<Vec<TeamMemberDto>> {
+    let team = get_team_svc(state, org_id, team_id).await?;
+    let member = find_team_org_member(state, org_id, user_id).await?;
+
+    state
+        .db
+        .teams
+        .add_member(team.id.clone(), member.id)
+        .await?;
+    invalidate_auth_cache(state, &member.user_id).await;
+
+    state.db.teams.list_members(team.id).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn remove_team_member_svc(
+    state: &AppState,
+    org_id: &str,
+    team_id: &str,
+    user_id: &str,
+) -> Result<()> {
+    let team = get_team_svc(state, org_id, team_id).await?;
+    let member = find_team_org_member(state, org_id, user_id).await?;
+
+    let removed = state.db.teams.remove_member(team.id, member.id).await?;
+    ensure!(
+        removed,
+        NotFoundSnafu {
+            msg: "Team member not found".to_string(),
+        }
+    );
+    invalidate_auth_cache(state, &member.user_id).await;
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn create_team_web_svc(
+    state: &AppState,
+    org_id: &str,
+    form: TeamFormData,
+) -> Result<TeamDto> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+
+    create_team_svc(
+        state,
+        org_id,
+        NewTeamDto {
+            name: form.name,
+            description: form.description,
+            roles: form.roles,
+        },
+    )
+    .await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn update_team_web_svc(
+    state: &AppState,
+    org_id: &str,
+    team_id: &str,
+    form: TeamFormData,
+) -> Result<TeamDto> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+
+    update_team_svc(
+        state,
+        org_id,
+        team_id,
+        UpdateTeamDto {
+            name: Some(form.name),
+            description: Some(form.description),
+            roles: Some(form.roles),
+        },
+    )
+    .await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn delete_team_web_svc(
+    state: &AppState,
+    org_id: &str,
+    team_id: &str,
+    csrf_token: &str,
+) -> Result<()> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+
+    delete_team_svc(state, org_id, team_id).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn add_team_member_web_svc(
+    state: &AppState,
+    org_id: &str,
+    team_id: &str,
+    form: TeamMemberFormData,
+) -> Result<Vec<TeamMemberDto>> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+
+    let user = state
+        .db
+        .users
+        .find_by_email(form.email.trim().to_lowercase())
+        .await?
+        .context(NotFoundSnafu {
+            msg: "Org member not found".to_string(),
+        })?;
+
+    add_team_member_svc(state, org_id, team_id, &user.id).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn remove_team_member_web_svc(
+    state: &AppState,
+    org_id: &str,
+    team_id: &str,
+    user_id: &str,
+    csrf_token: &str,
+) -> Result<()> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == org_id, CsrfTokenSnafu);
+
+    remove_team_member_svc(state, org_id, team_id, user_id).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn list_member_teams_svc(
+    state: &AppState,
+    org_id: &str,
+    user_id: &str,
+) -> Result<Vec<TeamDto>> {
+    state
+        .db
+        .teams
+        .list_by_member(org_id.to_string(), user_id.to_string())
+        .await
+}
+
+/// Permissions granted to the member by the roles of their teams, merged into the actor on authentication
+#[instrument(level = "debug", skip_all)]
+pub async fn team_permissions_svc(
+    state: &AppState,
+    org_id: &str,
+    user_id: &str,
+) -> Result<Vec<Permission>> {
+    let teams = list_member_teams_svc(state, org_id, user_id).await?;
+
+    let mut permissions: Vec<Permission> = Vec::new();
+    for permission in teams
+        .iter()
+        .flat_map(|team| team.roles.iter())
+        .flat_map(role_permissions)
+    {
+        if !permissions.contains(&permission) {
+            permissions.push(permission);
+        }
+    }
+
+    Ok(permissions)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+    use crate::ctx::AuditCtx;
+    use crate::dto::{NewOrgMemberDto, NewTeamDto, Permission, UpdateTeamDto};
+    use crate::test::TestCtx;
+
+    use super::{
+        add_team_member_svc, create_team_svc, delete_team_svc, list_team_members_svc,
+        remove_team_member_svc, team_permissions_svc, update_team_svc,
+    };
+
+    #[tokio::test]
+    async fn team_roles_grant_permissions_to_members() {
+        let ctx = TestCtx::new("teams").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Team Owner",
+                "team.owner@example.com",
+                "password123",
+                "Team Org",
+            )
+            .await
+            .expect("auth fixture");
+        let user = ctx
+            .seed_user_with_password("Team Member", "team.member@example.com", "password123")
+            .await
+            .expect("member user");
+        let outsider = ctx
+            .seed_user_with_password("Outsider", "team.outsider@example.com", "password123")
+            .await
+            .expect("outsider user");
+
+        ctx.state
+            .db
+            .org_members
+            .create(
+                &AuditCtx::default(),
+                fixture.org.id.clone(),
+                NewOrgMemberDto {
+                    user_id: user.id.clone(),
+                    roles: vec!["OrgViewer".to_string()],
+                    status: "active".to_string(),
+                },
+            )
+            .await
+            .expect("member should be created");
+
+        let superusers = create_team_svc(
+            &ctx.state,
+            &fixture.org.id,
+            NewTeamDto {
+                name: "Root".to_string(),
+                description: String::new(),
+                roles: vec!["Superuser".to_string()],
+            },
+        )
+        .await;
+        assert!(
+            matches!(superusers, Err(Error::Validation { .. })),
+            "teams only hold org roles"
+        );
+
+        let team = create_team_svc(
+            &ctx.state,
+            &fixture.org.id,
+            NewTeamDto {
+                name: "Editors".to_string(),
+                description: "Content team".to_string(),
+                roles: vec!["OrgEditor".to_string()],
+            },
+        )
+        .await
+        .expect("team should be created");
+
+        let duplicate = create_team_svc(
+            &ctx.state,
+            &fixture.org.id,
+            NewTeamDto {
+                name: "editors".to_string(),
+                description: String::new(),
+                roles: Vec::new(),
+            },
+        )
+        .await;
+        assert!(duplicate.is_err(), "team names are unique ignoring case");
+
+        let outside =
+            add_team_member_svc(&ctx.state, &fixture.org.id, &team.id, &outsider.id).await;
+        assert!(outside.is_err(), "only org members can join");
+
+        let members = add_team_member_svc(&ctx.state, &fixture.org.id, &team.id, &user.id)
+            .await
+            .expect("member should be added");
+        assert_eq!(members.len(), 1);
+        assert_eq!(
+            members[0].member_email.as_deref(),
+            Some("team.member@example.com")
+        );
+
+        let permissions = team_permissions_svc(&ctx.state, &fixture.org.id, &user.id)
+            .await
+            .expect("team permissions");
+        assert!(permissions.contains(&Permission::FilesCreate));
+        assert!(!permissions.contains(&Permission::OrgMembersDelete));
+
+        let team = update_team_svc(
+            &ctx.state,
+            &fixture.org.id,
+            &team.id,
+            UpdateTeamDto {
+                name: None,
+                description: None,
+                roles: Some(Vec::new()),
+            },
+        )
+        .await
+        .expect("team should be updated");
+        assert_eq!(team.member_count, 1);
+        let permissions = team_permissions_svc(&ctx.state, &fixture.org.id, &user.id)
+            .await
+            .expect("team permissions");
+        assert!(permissions.is_empty(), "teams without roles only group");
+
+        remove_team_member_svc(&ctx.state, &fixture.org.id, &team.id, &user.id)
+            .await
+            .expect("member should be removed");
+        let members = list_team_members_svc(&ctx.state, &fixture.org.id, &team.id)
+            .await
+            .expect("team members");
+        assert!(members.is_empty());
+
+        delete_team_svc(&ctx.state, &fixture.org.id, &team.id)
+            .await
+            .expect("team should be deleted");
+        assert!(
+            list_team_members_svc(&ctx.state, &fixture.org.id, &team.id)
+                .await
+                .is_err()
+        );
+    }
+}
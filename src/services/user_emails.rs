@@ -0,0 +1,448 @@
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ensure};
+use tracing::{info, instrument};
+
+use crate::Result;
+use crate::dto::{NewUserEmailDto, UserDto, UserEmailDto, VerifyUserEmailDto};
+use crate::error::{CsrfTokenSnafu, NotFoundSnafu, UserNotFoundSnafu, ValidationSnafu};
+use crate::run::AppState;
+use crate::services::auth::invalidate_auth_cache;
+use crate::services::emails::{EmailBranding, email_verification_email, render_email};
+use crate::services::mailer::queue_email_svc;
+use crate::services::token::verify_csrf_token;
+use crate::utils::{IdPrefix, generate_id};
+use crate::validators::validate_payload;
+
+/// Maximum number of secondary emails per user
+const MAX_USER_EMAILS: i64 = 5;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct NewUserEmailFormData {
+    pub token: String,
+    pub email: String,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct VerifyUserEmailFormData {
+    pub token: String,
+    pub code: String,
+}
+
+/// Whether the email is taken by any user, as a primary or secondary email
+#[instrument(level = "debug", skip_all)]
+pub async fn email_in_use_svc(state: &AppState, email: &str) -> Result<bool> {
+    if state
+        .db
+        .users
+        .find_by_email(email.to_string())
+        .await?
+        .is_some()
+    {
+        return Ok(true);
+    }
+
+    let secondary = state
+        .db
+        .user_emails
+        .find_by_email(email.to_string())
+        .await?;
+
+    Ok(secondary.is_some())
+}
+
+/// Finds the user by primary email or by a verified secondary email
+#[instrument(level = "debug", skip_all)]
+pub async fn find_user_by_login_email_svc(
+    state: &AppState,
+    email: &str,
+) -> Result<Option<UserDto>> {
+    if let Some(user) = state.db.users.find_by_email(email.to_string()).await? {
+        return Ok(Some(user));
+    }
+
+    let secondary = state
+        .db
+        .user_emails
+        .find_by_email(email.to_string())
+        .await?;
+
+    match secondary {
+        Some(secondary) if secondary.is_verified() => state.db.users.get(secondary.user_id).await,
+        _ => Ok(None),
+    }
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn list_user_emails_svc(state: &AppState, user_id: &str) -> Result<Vec<UserEmailDto>> {
+    state.db.user_emails.list_by_user(user_id.to_string()).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn add_user_email_svc(
+    state: &AppState,
+    user_id: &str,
+    data: NewUserEmailDto,
+) -> Result<UserEmailDto> {
+    validate_payload(&data)?;
+
+    let in_use = email_in_use_svc(state, &data.email).await?;
+    ensure!(
+        !in_use,
+        ValidationSnafu {
+            msg: "Email already exists".to_string(),
+        }
+    );
+
+    let count = state
+        .db
+        .user_emails
+        .count_by_user(user_id.to_string())
+        .await?;
+    ensure!(
+        count < MAX_USER_EMAILS,
+        ValidationSnafu {
+            msg: format!("A user can only have up to {} emails", MAX_USER_EMAILS),
+        }
+    );
+
+    let code = generate_id(IdPrefix::EmailVerification);
+    let email = state
+        .db
+        .user_emails
+        .create(user_id.to_string(), data, code.clone())
+        .await?;
+
+    let message = render_email(
+        &EmailBranding::yaas(),
+        None,
+        &email.email,
+        email_verification_email(code),
+    )?;
+    queue_email_svc(&state.db, message).await?;
+
+    info!(
+        user_id = user_id,
+        email_id = email.id.as_str(),
+        "Email verification code issued"
+    );
+
+    Ok(email)
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn add_user_email_web_svc(
+    state: &AppState,
+    user_id: &str,
+    form: NewUserEmailFormData,
+) -> Result<UserEmailDto> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == "new_user_email", CsrfTokenSnafu);
+
+    add_user_email_svc(state, user_id, NewUserEmailDto { email: form.email }).await
+}
+
+async fn get_user_email(state: &AppState, user_id: &str, id: &str) -> Result<UserEmailDto> {
+    state
+        .db
+        .user_emails
+        .get(user_id.to_string(), id.to_string())
+        .await?
+        .context(NotFoundSnafu {
+            msg: "Email not found".to_string(),
+        })
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn verify_user_email_svc(
+    state: &AppState,
+    user_id: &str,
+    id: &str,
+    data: VerifyUserEmailDto,
+) -> Result<()> {
+    validate_payload(&data)?;
+
+    let email = get_user_email(state, user_id, id).await?;
+
+    ensure!(
+        !email.is_verified(),
+        ValidationSnafu {
+            msg: "Email is already verified".to_string(),
+        }
+    );
+
+    ensure!(
+        email.verification_code.as_deref() == Some(data.code.trim()),
+        ValidationSnafu {
+            msg: "Invalid verification code".to_string(),
+        }
+    );
+
+    state.db.user_emails.mark_verified(email.id).await?;
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn verify_user_email_web_svc(
+    state: &AppState,
+    user_id: &str,
+    id: &str,
+    form: VerifyUserEmailFormData,
+) -> Result<()> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == id, CsrfTokenSnafu);
+
+    verify_user_email_svc(state, user_id, id, VerifyUserEmailDto { code: form.code }).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn remove_user_email_svc(state: &AppState, user_id: &str, id: &str) -> Result<()> {
+    let deleted = state
+        .db
+        .user_emails
+        .delete(user_id.to_string(), id.to_string())
+        .await?;
+
+    ensure!(
+        deleted,
+        NotFoundSnafu {
+            msg: "Email not found".to_string()
+        }
+    );
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn remove_user_email_web_svc(
+    state: &AppState,
+    user_id: &str,
+    id: &str,
+    csrf_token: &str,
+) -> Result<()> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == id, CsrfTokenSnafu);
+
+    remove_user_email_svc(state, user_id, id).await
+}
+
+/// Makes a verified secondary email the primary one.
+/// The previous primary email is kept as a verified secondary email.
+#[instrument(level = "debug", skip_all)]
+pub async fn promote_user_email_svc(state: &AppState, user_id: &str, id: &str) -> Result<()> {
+    let email = get_user_email(state, user_id, id).await?;
+
+    ensure!(
+        email.is_verified(),
+        ValidationSnafu {
+            msg: "Email must be verified before it can be made primary".to_string(),
+        }
+    );
+
+    let user = state.db.users.get(user_id.to_string()).await?;
+    let user = user.context(UserNotFoundSnafu)?;
+
+    let promoted = state
+        .db
+        .user_emails
+        .promote(user.id.clone(), email.id, user.email, email.email)
+        .await?;
+
+    ensure!(
+        promoted,
+        ValidationSnafu {
+            msg: "Email was changed by another request, please try again".to_string(),
+        }
+    );
+
+    // Cached actors still carry the old primary email
+    invalidate_auth_cache(state, &user.id).await;
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn promote_user_email_web_svc(
+    state: &AppState,
+    user_id: &str,
+    id: &str,
+    csrf_token: &str,
+) -> Result<()> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == id, CsrfTokenSnafu);
+
+    promote_user_email_svc(state, user_id, id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::{CredentialsDto, NewUserEmailDto, SessionClientDto, VerifyUserEmailDto};
+    use crate::services::auth::authenticate;
+    use crate::services::users::create_user_svc;
+    use crate::test::TestCtx;
+
+    use super::{
+        add_user_email_svc, list_user_emails_svc, promote_user_email_svc, remove_user_email_svc,
+        verify_user_email_svc,
+    };
+
+    #[tokio::test]
+    async fn user_emails_lifecycle() {
+        let ctx = TestCtx::new("user_emails_lifecycle")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Email User",
+                "primary@example.com",
+                "password123",
+                "Email Org",
+            )
+            .await
+            .expect("auth fixture");
+        let user_id = fixture.user.id.clone();
+
+        let added = add_user_email_svc(
+            &ctx.state,
+            &user_id,
+            NewUserEmailDto {
+                email: "work@example.com".to_string(),
+            },
+        )
+        .await
+        .expect("email should be added");
+        assert!(!added.is_verified());
+
+        // Unverified emails cannot be used to sign in
+        let login = authenticate(
+            &ctx.state,
+            &CredentialsDto {
+                email: "work@example.com".to_string(),
+                password: "password123".to_string(),
+            },
+            SessionClientDto::default(),
+        )
+        .await;
+        assert!(login.is_err());
+
+        let wrong_code = verify_user_email_svc(
+            &ctx.state,
+            &user_id,
+            &added.id,
+            VerifyUserEmailDto {
+                code: "wrong".to_string(),
+            },
+        )
+        .await;
+        assert!(wrong_code.is_err());
+
+        verify_user_email_svc(
+            &ctx.state,
+            &user_id,
+            &added.id,
+            VerifyUserEmailDto {
+                code: added.verification_code.clone().expect("code"),
+            },
+        )
+        .await
+        .expect("email should be verified");
+
+        let login = authenticate(
+            &ctx.state,
+            &CredentialsDto {
+                email: "work@example.com".to_string(),
+                password: "password123".to_string(),
+            },
+            SessionClientDto::default(),
+        )
+        .await
+        .expect("verified email should sign in");
+        assert_eq!(login.user.id, user_id);
+
+        promote_user_email_svc(&ctx.state, &user_id, &added.id)
+            .await
+            .expect("email should be promoted");
+
+        let user = ctx
+            .state
+            .db
+            .users
+            .get(user_id.clone())
+            .await
+            .expect("query should pass")
+            .expect("user should exist");
+        assert_eq!(user.email, "work@example.com");
+
+        let emails = list_user_emails_svc(&ctx.state, &user_id)
+            .await
+            .expect("listing should pass");
+        assert_eq!(emails.len(), 1);
+        assert_eq!(emails[0].email, "primary@example.com");
+        assert!(emails[0].is_verified());
+
+        remove_user_email_svc(&ctx.state, &user_id, &added.id)
+            .await
+            .expect("email should be removed");
+        let emails = list_user_emails_svc(&ctx.state, &user_id)
+            .await
+            .expect("listing should pass");
+        assert!(emails.is_empty());
+    }
+
+    #[tokio::test]
+    async fn user_emails_are_unique_across_accounts() {
+        let ctx = TestCtx::new("user_emails_unique").await.expect("test ctx");
+        let first = ctx
+            .seed_user_with_password("First", "first@example.com", "password123")
+            .await
+            .expect("first user");
+        let second = ctx
+            .seed_user_with_password("Second", "second@example.com", "password123")
+            .await
+            .expect("second user");
+
+        // Primary email of another user
+        let result = add_user_email_svc(
+            &ctx.state,
+            &second.id,
+            NewUserEmailDto {
+                email: "first@example.com".to_string(),
+            },
+        )
+        .await;
+        assert!(result.is_err());
+
+        add_user_email_svc(
+            &ctx.state,
+            &first.id,
+            NewUserEmailDto {
+                email: "shared@example.com".to_string(),
+            },
+        )
+        .await
+        .expect("email should be added");
+
+        // Secondary email of another user
+        let result = add_user_email_svc(
+            &ctx.state,
+            &second.id,
+            NewUserEmailDto {
+                email: "shared@example.com".to_string(),
+            },
+        )
+        .await;
+        assert!(result.is_err());
+
+        // New accounts cannot claim secondary emails either
+        let result = create_user_svc(
+            &ctx.state,
+            crate::dto::NewUserWithPasswordDto {
+                name: "Third".to_string(),
+                email: "shared@example.com".to_string(),
+                password: "password123".to_string(),
+            },
+        )
+        .await;
+        assert!(result.is_err());
+    }
+}
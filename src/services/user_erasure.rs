@@ -0,0 +1,354 @@
+use chrono::Utc;
+use snafu::{OptionExt, ensure};
+use tracing::{error, instrument, warn};
+
+use crate::Result;
+use crate::ctx::{AuditCtx, Ctx};
+use crate::db::DeletedScope;
+use crate::dto::{
+    AnonymizedUserDto, ChangeAction, EntityChangeDto, ErasureConfirmationDto, LifecycleTopic,
+    Permission, UserDto, changed_fields,
+};
+use crate::error::{ForbiddenSnafu, UserNotFoundSnafu, ValidationSnafu};
+use crate::run::AppState;
+use crate::services::admin_events::publish_change;
+use crate::services::lifecycle::publish_user_event_svc;
+use crate::services::revocations::revoke_user_tokens_svc;
+use crate::services::suggestions::invalidate_suggestions;
+use crate::services::token::{ERASURE_TOKEN_MINS, create_erasure_token, verify_erasure_token};
+
+/// Name left in place of an erased user's name
+pub const ERASED_USER_NAME: &str = "Erased User";
+
+/// Reserved domain of tombstone emails, it can never receive mail
+const ERASED_EMAIL_DOMAIN: &str = "erased.invalid";
+
+/// Unique per user so the email index keeps holding
+fn tombstone_email(user_id: &str) -> String {
+    format!("{}@{}", user_id, ERASED_EMAIL_DOMAIN)
+}
+
+/// Erasure is irreversible, so it has a permission of its own
+pub fn ensure_can_erase(ctx: &Ctx) -> Result<()> {
+    ensure!(
+        ctx.actor.has_permissions(&[Permission::UsersErase]),
+        ForbiddenSnafu {
+            msg: "You do not have permission to erase users."
+        }
+    );
+    Ok(())
+}
+
+/// Issues the token `anonymize_user_svc` asks for, after checking the user can be erased
+#[instrument(level = "debug", skip_all)]
+pub async fn erasure_confirmation_svc(
+    state: &AppState,
+    actor_id: &str,
+    user_id: &str,
+) -> Result<ErasureConfirmationDto> {
+    let user = erasable_user(state, actor_id, user_id).await?;
+    let confirmation_token = create_erasure_token(&user.id, &state.config.jwt_secret)?;
+
+    Ok(ErasureConfirmationDto {
+        user_id: user.id,
+        confirmation_token,
+        expires_at: Utc::now().timestamp_millis() + ERASURE_TOKEN_MINS * 60 * 1000,
+    })
+}
+
+/// Irreversibly scrubs the personal data of the user.
+///
+/// The email and name are replaced with tombstones and the user is
+/// deactivated. Credentials, sessions, grants, profile, notifications and
+/// exports are removed and every token issued so far is rejected. The user
+/// row stays, so memberships, owned orgs and audit logs keep pointing to it.
+#[instrument(level = "debug", skip_all)]
+pub async fn anonymize_user_svc(
+    state: &AppState,
+    actor_id: &str,
+    user_id: &str,
+    confirmation_token: &str,
+) -> Result<AnonymizedUserDto> {
+    let confirmed_id = verify_erasure_token(confirmation_token, &state.config.jwt_secret)?;
+    ensure!(
+        confirmed_id == user_id,
+        ForbiddenSnafu {
+            msg: "The confirmation token was issued for another user.".to_string(),
+        }
+    );
+
+    let existing = erasable_user(state, actor_id, user_id).await?;
+
+    let mut emails = vec![existing.email.clone()];
+    let secondary = state
+        .db
+        .user_emails
+        .list_by_user(existing.id.clone())
+        .await?;
+    emails.extend(secondary.into_iter().map(|email| email.email));
+
+    let tombstone = tombstone_email(&existing.id);
+    let erased_at = Utc::now().timestamp_millis();
+    let audit = AuditCtx {
+        actor_id: Some(actor_id.to_string()),
+    };
+
+    let uow = state.db.begin().await?;
+    let work = async {
+        uow.users
+            .anonymize(
+                &audit,
+                existing.id.clone(),
+                tombstone.clone(),
+                ERASED_USER_NAME.to_string(),
+            )
+            .await?;
+
+        let removed = uow
+            .user_erasures
+            .purge_user_data(existing.id.clone())
+            .await?;
+
+        for email in emails.into_iter() {
+            uow.user_erasures
+                .scrub_email(email, tombstone.clone(), erased_at)
+                .await?;
+        }
+
+        // Payloads embed the email and name, pending ones are never sent
+        uow.user_erasures
+            .scrub_lifecycle_deliveries(
+                existing.id.clone(),
+                tombstone.clone(),
+                ERASED_USER_NAME.to_string(),
+                erased_at,
+            )
+            .await?;
+        Ok(removed)
+    };
+    let result = work.await;
+    let rows_removed = uow.finish(result).await?;
+
+    revoke_user_tokens_svc(state, Some(actor_id), &existing.id).await?;
+    invalidate_suggestions(state);
+
+    let user = state
+        .db
+        .users
+        .get_scoped(existing.id.clone(), DeletedScope::WithDeleted)
+        .await?
+        .context(UserNotFoundSnafu)?;
+
+    publish_change(
+        state,
+        EntityChangeDto::user(ChangeAction::Updated, &user.id),
+    );
+
+    // Deleted users were already reported as deactivated
+    if existing.status == "active" && existing.deleted_at.is_none() {
+        let changed = changed_fields(&existing, &user);
+        if let Err(e) =
+            publish_user_event_svc(state, LifecycleTopic::UserDeactivated, &user, changed).await
+        {
+            error!("Failed to publish lifecycle event: {}", e);
+        }
+    }
+
+    warn!(
+        user_id = user.id,
+        erased_by = actor_id,
+        rows_removed = rows_removed,
+        "user.erased"
+    );
+
+    Ok(AnonymizedUserDto {
+        user,
+        erased_at,
+        rows_removed,
+    })
+}
+
+/// Deleted users can be erased too, superusers and the actor cannot
+async fn erasable_user(state: &AppState, actor_id: &str, user_id: &str) -> Result<UserDto> {
+    ensure!(
+        actor_id != user_id,
+        ValidationSnafu {
+            msg: "You cannot erase yourself".to_string(),
+        }
+    );
+
+    let user = state
+        .db
+        .users
+        .get_scoped(user_id.to_string(), DeletedScope::WithDeleted)
+        .await?
+        .context(UserNotFoundSnafu)?;
+
+    ensure!(
+        user.email != tombstone_email(&user.id),
+        ValidationSnafu {
+            msg: "The user was already erased".to_string(),
+        }
+    );
+
+    let superuser = state.db.superusers.get(user.id.clone()).await?;
+    ensure!(
+        superuser.is_none(),
+        ForbiddenSnafu {
+            msg: "Superusers cannot be erased.".to_string(),
+        }
+    );
+
+    Ok(user)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::DeletedScope;
+    use crate::dto::{CredentialsDto, SessionClientDto};
+    use crate::services::auth::{authenticate, authenticate_token_svc};
+    use crate::services::token::{create_csrf_token_svc, create_erasure_token};
+    use crate::test::TestCtx;
+
+    use super::{ERASED_USER_NAME, anonymize_user_svc, erasure_confirmation_svc};
+
+    #[tokio::test]
+    async fn anonymize_scrubs_the_user_and_keeps_memberships() {
+        let ctx = TestCtx::new("user_erasure").await.expect("test ctx");
+        let admin = ctx
+            .seed_superuser("eraser@example.com")
+            .await
+            .expect("superuser");
+        let fixture = ctx
+            .seed_auth_fixture("Erased", "erased@example.com", "password123", "Erase Org")
+            .await
+            .expect("auth fixture");
+        let credentials = CredentialsDto {
+            email: fixture.email.clone(),
+            password: fixture.password.clone(),
+        };
+        let login = authenticate(&ctx.state, &credentials, SessionClientDto::default())
+            .await
+            .expect("login");
+
+        let confirmation = erasure_confirmation_svc(&ctx.state, &admin.id, &fixture.user.id)
+            .await
+            .expect("confirmation");
+        let erased = anonymize_user_svc(
+            &ctx.state,
+            &admin.id,
+            &fixture.user.id,
+            &confirmation.confirmation_token,
+        )
+        .await
+        .expect("anonymize");
+
+        assert_eq!(erased.user.name, ERASED_USER_NAME);
+        assert_eq!(erased.user.status, "inactive");
+        assert!(!erased.user.email.contains("erased@example.com"));
+        assert!(erased.rows_removed > 0);
+
+        let db = &ctx.state.db;
+        assert!(
+            db.users
+                .find_by_email("erased@example.com".to_string())
+                .await
+                .expect("find")
+                .is_none()
+        );
+        assert!(
+            db.passwords
+                .get(fixture.user.id.clone())
+                .await
+                .expect("password")
+                .is_none()
+        );
+        assert!(
+            db.user_sessions
+                .list_by_user(fixture.user.id.clone())
+                .await
+                .expect("sessions")
+                .is_empty()
+        );
+        assert!(
+            authenticate_token_svc(&ctx.state, &login.token)
+                .await
+                .is_err()
+        );
+        assert!(
+            authenticate(&ctx.state, &credentials, SessionClientDto::default())
+                .await
+                .is_err()
+        );
+
+        // The row and its memberships stay for org history
+        let memberships = db
+            .org_members
+            .list_memberships_count(fixture.user.id.clone())
+            .await
+            .expect("memberships");
+        assert_eq!(memberships, 1);
+        let user = db
+            .users
+            .get_scoped(fixture.user.id.clone(), DeletedScope::WithDeleted)
+            .await
+            .expect("user")
+            .expect("tombstone");
+        assert_eq!(user.email, erased.user.email);
+
+        // Erasing twice is refused
+        assert!(
+            erasure_confirmation_svc(&ctx.state, &admin.id, &fixture.user.id)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn anonymize_requires_a_confirmation_for_the_user() {
+        let ctx = TestCtx::new("user_erasure_confirm")
+            .await
+            .expect("test ctx");
+        let admin = ctx
+            .seed_superuser("confirmer@example.com")
+            .await
+            .expect("superuser");
+        let user = ctx
+            .seed_user_with_password("Kept", "kept@example.com", "password123")
+            .await
+            .expect("user");
+        let other = ctx
+            .seed_user_with_password("Other", "other@example.com", "password123")
+            .await
+            .expect("other user");
+        let secret = &ctx.state.config.jwt_secret;
+
+        let csrf = create_csrf_token_svc(&user.id, secret).unwrap();
+        let for_other = create_erasure_token(&other.id, secret).unwrap();
+        for token in [csrf.as_str(), for_other.as_str(), "not-a-token"] {
+            assert!(
+                anonymize_user_svc(&ctx.state, &admin.id, &user.id, token)
+                    .await
+                    .is_err()
+            );
+        }
+
+        // Superusers cannot be erased, not even by themselves
+        let own = create_erasure_token(&admin.id, secret).unwrap();
+        assert!(
+            anonymize_user_svc(&ctx.state, &admin.id, &admin.id, &own)
+                .await
+                .is_err()
+        );
+
+        let kept = ctx
+            .state
+            .db
+            .users
+            .get(user.id.clone())
+            .await
+            .expect("user")
+            .expect("untouched");
+        assert_eq!(kept.email, "kept@example.com");
+    }
+}
@@ -0,0 +1,345 @@
+use chrono::Utc;
+use prost::Message;
+use snafu::{OptionExt, ResultExt, ensure};
+use tracing::{info, instrument};
+
+use crate::Result;
+use crate::dto::{
+    JobKind, ListingParamsDto, UserExportArchiveDto, UserExportDto, UserExportFileDto,
+    UserExportFormat, UserExportJobDto, UserExportParamsDto, UserExportStatus, proto,
+};
+use crate::error::{JsonSerializeSnafu, NotFoundSnafu, UserNotFoundSnafu};
+use crate::run::AppState;
+use crate::services::impersonations::list_user_impersonations_svc;
+use crate::services::jobs::enqueue_job_svc;
+use crate::services::token::{create_download_token, verify_download_token};
+use crate::services::user_profiles::get_user_profile_svc;
+
+/// Days a ready export can be downloaded, a new one is assembled afterwards
+pub const USER_EXPORT_DAYS: i64 = 7;
+
+/// A pending export older than this was given up by the job worker and is queued again
+const USER_EXPORT_PENDING_MINS: i64 = 60;
+
+/// Memberships read per page while assembling the archive
+const USER_EXPORT_MEMBERSHIPS_PER_PAGE: i32 = 50;
+
+/// Latest data export of the user, queued when there is none to download.
+///
+/// Polling is safe: a pending or downloadable export is returned as is, with a
+/// fresh download link once ready.
+#[instrument(level = "debug", skip_all)]
+pub async fn export_user_data_svc(
+    state: &AppState,
+    requested_by: Option<&str>,
+    user_id: &str,
+    params: UserExportParamsDto,
+) -> Result<UserExportDto> {
+    let format = params.format()?;
+    let user = state
+        .db
+        .users
+        .get(user_id.to_string())
+        .await?
+        .context(UserNotFoundSnafu)?;
+
+    let now = Utc::now().timestamp_millis();
+    let latest = state
+        .db
+        .user_exports
+        .find_latest(user.id.clone(), format)
+        .await?;
+
+    if let Some(latest) = latest {
+        let pending_since = now - USER_EXPORT_PENDING_MINS * 60 * 1000;
+        if latest.status == UserExportStatus::Pending && latest.created_at > pending_since {
+            return Ok(latest);
+        }
+        if latest.is_downloadable(now) {
+            return with_download_url(state, latest);
+        }
+    }
+
+    let export = state
+        .db
+        .user_exports
+        .create(
+            user.id.clone(),
+            requested_by.map(|id| id.to_string()),
+            format,
+        )
+        .await?;
+
+    state
+        .db
+        .user_exports
+        .delete_others(user.id.clone(), format, export.id.clone())
+        .await?;
+
+    enqueue_job_svc(
+        &state.db,
+        JobKind::UserExport,
+        &UserExportJobDto {
+            export_id: export.id.clone(),
+        },
+    )
+    .await?;
+
+    info!(
+        export_id = export.id,
+        user_id = user.id,
+        requested_by = requested_by,
+        format = format.to_string(),
+        "user.export_requested"
+    );
+
+    Ok(export)
+}
+
+/// Job handler assembling the archive of a pending export
+pub async fn run_user_export(state: &AppState, job: UserExportJobDto) -> Result<()> {
+    // Replaced by a newer export in the meantime
+    let Some(export) = state.db.user_exports.get(job.export_id).await? else {
+        return Ok(());
+    };
+    if export.status == UserExportStatus::Ready {
+        return Ok(());
+    }
+
+    let archive = build_user_archive(state, &export.user_id).await?;
+    let content = encode_user_archive(&archive, export.format)?;
+    let size = content.len();
+
+    let now = Utc::now().timestamp_millis();
+    let expires_at = now + USER_EXPORT_DAYS * 24 * 60 * 60 * 1000;
+    state
+        .db
+        .user_exports
+        .complete(export.id.clone(), content, now, expires_at)
+        .await?;
+
+    info!(
+        export_id = export.id,
+        user_id = export.user_id,
+        size = size,
+        "user.export_ready"
+    );
+
+    Ok(())
+}
+
+/// Archive file the signed link points to
+#[instrument(level = "debug", skip_all)]
+pub async fn download_user_export_svc(state: &AppState, token: &str) -> Result<UserExportFileDto> {
+    let export_id = verify_download_token(token, &state.config.jwt_secret)?;
+
+    let export = state
+        .db
+        .user_exports
+        .get(export_id.clone())
+        .await?
+        .context(NotFoundSnafu {
+            msg: "Export not found".to_string(),
+        })?;
+    ensure!(
+        export.is_downloadable(Utc::now().timestamp_millis()),
+        NotFoundSnafu {
+            msg: "Export is not ready or has expired".to_string(),
+        }
+    );
+
+    let content = state
+        .db
+        .user_exports
+        .get_content(export_id)
+        .await?
+        .context(NotFoundSnafu {
+            msg: "Export not found".to_string(),
+        })?;
+
+    Ok(UserExportFileDto {
+        filename: format!(
+            "yaas-export-{}.{}",
+            export.user_id,
+            export.format.extension()
+        ),
+        content_type: export.format.content_type(),
+        content,
+    })
+}
+
+fn with_download_url(state: &AppState, mut export: UserExportDto) -> Result<UserExportDto> {
+    let token = create_download_token(&export.id, &state.config.jwt_secret)?;
+    export.download_url = Some(format!(
+        "{}/user/export/download?token={}",
+        state.config.mailer.base_url, token
+    ));
+    Ok(export)
+}
+
+async fn build_user_archive(state: &AppState, user_id: &str) -> Result<UserExportArchiveDto> {
+    let user = state
+        .db
+        .users
+        .get(user_id.to_string())
+        .await?
+        .context(UserNotFoundSnafu)?;
+    let profile = get_user_profile_svc(state, &user.id).await?;
+
+    let mut memberships = Vec::new();
+    let mut page = 1;
+    loop {
+        let listing = state
+            .db
+            .org_members
+            .list_memberships(
+                user.id.clone(),
+                ListingParamsDto {
+                    page: Some(page),
+                    per_page: Some(USER_EXPORT_MEMBERSHIPS_PER_PAGE),
+                },
+            )
+            .await?;
+        memberships.extend(listing.data);
+
+        if page as i64 >= listing.meta.total_pages {
+            break;
+        }
+        page += 1;
+    }
+
+    let sessions = state.db.user_sessions.list_by_user(user.id.clone()).await?;
+    let access_log = state.db.org_access.list_by_user(user.id.clone()).await?;
+    let impersonations = list_user_impersonations_svc(state, &user.id).await?;
+
+    Ok(UserExportArchiveDto {
+        exported_at: Utc::now().timestamp_millis(),
+        user,
+        profile,
+        memberships,
+        sessions,
+        access_log,
+        impersonations,
+    })
+}
+
+fn encode_user_archive(
+    archive: &UserExportArchiveDto,
+    format: UserExportFormat,
+) -> Result<Vec<u8>> {
+    match format {
+        UserExportFormat::Json => serde_json::to_vec_pretty(archive).context(JsonSerializeSnafu),
+        UserExportFormat::Protobuf => Ok(proto::UserExport::from(archive).encode_to_vec()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use prost::Message;
+
+    use crate::dto::{UserExportParamsDto, UserExportStatus, proto};
+    use crate::services::jobs::run_next_job_svc;
+    use crate::services::token::create_csrf_token_svc;
+    use crate::test::TestCtx;
+
+    use super::{download_user_export_svc, export_user_data_svc};
+
+    fn link_token(url: &str) -> String {
+        url.split("token=").nth(1).expect("token").to_string()
+    }
+
+    #[tokio::test]
+    async fn export_is_assembled_by_the_job_and_downloaded() {
+        let ctx = TestCtx::new("user_export").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Exported",
+                "exported@example.com",
+                "password123",
+                "Export Org",
+            )
+            .await
+            .expect("auth fixture");
+
+        for format in ["json", "protobuf"] {
+            let params = UserExportParamsDto {
+                format: Some(format.to_string()),
+            };
+            let pending = export_user_data_svc(&ctx.state, None, &fixture.user.id, params.clone())
+                .await
+                .expect("request export");
+            assert_eq!(pending.status, UserExportStatus::Pending);
+            assert!(pending.download_url.is_none());
+
+            // Polling while pending does not queue another export
+            let polled = export_user_data_svc(&ctx.state, None, &fixture.user.id, params.clone())
+                .await
+                .expect("poll export");
+            assert_eq!(polled.id, pending.id);
+
+            run_next_job_svc(&ctx.state, Utc::now().timestamp_millis())
+                .await
+                .expect("run job")
+                .expect("export job");
+
+            let ready = export_user_data_svc(&ctx.state, None, &fixture.user.id, params)
+                .await
+                .expect("ready export");
+            assert_eq!(ready.id, pending.id);
+            assert_eq!(ready.status, UserExportStatus::Ready);
+
+            let url = ready.download_url.expect("download url");
+            let file = download_user_export_svc(&ctx.state, &link_token(&url))
+                .await
+                .expect("download");
+            assert_eq!(Some(file.content.len() as i64), ready.size);
+
+            match format {
+                "json" => {
+                    let archive: serde_json::Value =
+                        serde_json::from_slice(&file.content).expect("json archive");
+                    assert_eq!(archive["user"]["email"], "exported@example.com");
+                    assert_eq!(archive["memberships"][0]["org_id"], fixture.org.id.as_str());
+                    assert_eq!(file.content_type, "application/json");
+                }
+                _ => {
+                    let archive =
+                        proto::UserExport::decode(file.content.as_slice()).expect("protobuf");
+                    assert_eq!(archive.user.expect("user").email, "exported@example.com");
+                    assert_eq!(archive.memberships[0].org_id, fixture.org.id);
+                    assert_eq!(file.content_type, "application/x-protobuf");
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn download_requires_a_download_link_token() {
+        let ctx = TestCtx::new("user_export_link").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture("Linked", "linked@example.com", "password123", "Link Org")
+            .await
+            .expect("auth fixture");
+
+        let export = export_user_data_svc(
+            &ctx.state,
+            None,
+            &fixture.user.id,
+            UserExportParamsDto::default(),
+        )
+        .await
+        .expect("request export");
+        run_next_job_svc(&ctx.state, Utc::now().timestamp_millis())
+            .await
+            .expect("run job");
+
+        let csrf = create_csrf_token_svc(&export.id, &ctx.state.config.jwt_secret).unwrap();
+        assert!(download_user_export_svc(&ctx.state, &csrf).await.is_err());
+        assert!(
+            download_user_export_svc(&ctx.state, "not-a-token")
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,387 @@
+use std::collections::HashSet;
+use tracing::{error, info, instrument};
+
+use crate::ctx::AuditCtx;
+use crate::dto::{
+    ChangeAction, EntityChangeDto, LifecycleTopic, MAX_IMPORT_USERS, NewPasswordDto, NewUserDto,
+    NewUserWithPasswordDto, UserImportResultDto, UserImportRowDto, UserImportRowResultDto,
+};
+use crate::run::AppState;
+use crate::services::admin_events::publish_change;
+use crate::services::lifecycle::publish_user_event_svc;
+use crate::services::password::hash_password;
+use crate::services::suggestions::invalidate_suggestions;
+use crate::services::user_emails::email_in_use_svc;
+use crate::validators::validate_payload;
+use crate::{Error, Result};
+
+/// Import file formats, picked from the request content type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserImportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl UserImportFormat {
+    pub fn from_content_type(content_type: &str) -> Result<Self> {
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase();
+
+        match mime.as_str() {
+            "text/csv" => Ok(UserImportFormat::Csv),
+            "application/x-ndjson" | "application/ndjson" => Ok(UserImportFormat::Ndjson),
+            _ => Err(Error::Validation {
+                msg: "Content type must be text/csv or application/x-ndjson".to_string(),
+            }),
+        }
+    }
+}
+
+/// A parsed row, or why it could not be read
+type ParsedRow = std::result::Result<UserImportRowDto, String>;
+
+/// Splits the file into rows, a malformed row is reported instead of failing the file
+pub fn parse_user_import(format: UserImportFormat, body: &str) -> Result<Vec<ParsedRow>> {
+    let rows = match format {
+        UserImportFormat::Csv => parse_csv_rows(body)?,
+        UserImportFormat::Ndjson => body
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str::<UserImportRowDto>(line)
+                    .map_err(|e| format!("Invalid JSON: {}", e))
+            })
+            .collect(),
+    };
+
+    if rows.is_empty() {
+        return Err(Error::Validation {
+            msg: "Import file has no rows".to_string(),
+        });
+    }
+    if rows.len() > MAX_IMPORT_USERS {
+        return Err(Error::Validation {
+            msg: format!("Import is limited to {} rows", MAX_IMPORT_USERS),
+        });
+    }
+
+    Ok(rows)
+}
+
+/// Reads CSV with an `email,name[,password]` header, columns in any order
+fn parse_csv_rows(body: &str) -> Result<Vec<ParsedRow>> {
+    let mut records = split_csv_records(body).into_iter();
+
+    let Some(header) = records.next() else {
+        return Ok(Vec::new());
+    };
+    let header: Vec<String> = header.iter().map(|h| h.trim().to_lowercase()).collect();
+    let column = |name: &str| header.iter().position(|h| h == name);
+
+    let (Some(email_col), Some(name_col)) = (column("email"), column("name")) else {
+        return Err(Error::Validation {
+            msg: "CSV header must include email and name".to_string(),
+        });
+    };
+    let password_col = column("password");
+
+    Ok(records
+        .map(|record| {
+            if record.len() != header.len() {
+                return Err(format!(
+                    "Expected {} columns, found {}",
+                    header.len(),
+                    record.len()
+                ));
+            }
+
+            Ok(UserImportRowDto {
+                email: record[email_col].trim().to_string(),
+                name: record[name_col].trim().to_string(),
+                password: password_col
+                    .map(|col| record[col].clone())
+                    .filter(|password| !password.is_empty()),
+            })
+        })
+        .collect())
+}
+
+/// Splits CSV text into records, handling quoted fields and `""` escapes
+fn split_csv_records(body: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = body.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|f| !f.trim().is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                }
+                record.clear();
+            }
+            (false, c) => field.push(c),
+        }
+    }
+
+    record.push(field);
+    if record.iter().any(|f| !f.trim().is_empty()) {
+        records.push(record);
+    }
+
+    records
+}
+
+/// Validates every row and creates the users in a single transaction.
+///
+/// Nothing is created when any row fails, the result lists the errors of each row.
+#[instrument(level = "debug", skip_all)]
+pub async fn import_users_svc(
+    state: &AppState,
+    rows: Vec<ParsedRow>,
+) -> Result<UserImportResultDto> {
+    let mut results: Vec<UserImportRowResultDto> = Vec::with_capacity(rows.len());
+    let mut valid: Vec<UserImportRowDto> = Vec::with_capacity(rows.len());
+    let mut seen: HashSet<String> = HashSet::new();
+
+    for (index, parsed) in rows.into_iter().enumerate() {
+        let mut result = UserImportRowResultDto {
+            row: index + 1,
+            email: String::new(),
+            user_id: None,
+            errors: Vec::new(),
+        };
+
+        let row = match parsed {
+            Ok(row) => row,
+            Err(message) => {
+                result.errors.push(message);
+                results.push(result);
+                continue;
+            }
+        };
+        result.email = row.email.clone();
+
+        let validated = match &row.password {
+            Some(password) => validate_payload(&NewUserWithPasswordDto {
+                email: row.email.clone(),
+                name: row.name.clone(),
+                password: password.clone(),
+            }),
+            None => validate_payload(&NewUserDto {
+                email: row.email.clone(),
+                name: row.name.clone(),
+            }),
+        };
+        if let Err(err) = validated {
+            result.errors.push(err.to_string());
+        }
+
+        if !seen.insert(row.email.to_lowercase()) {
+            result
+                .errors
+                .push("Email appears more than once in the file".to_string());
+        } else if email_in_use_svc(state, &row.email).await? {
+            result.errors.push("Email already exists".to_string());
+        }
+
+        if result.errors.is_empty() {
+            valid.push(row);
+        }
+        results.push(result);
+    }
+
+    let total = results.len();
+    let failed = results.iter().filter(|r| !r.errors.is_empty()).count();
+
+    if failed > 0 {
+        return Ok(UserImportResultDto {
+            imported: false,
+            total,
+            created: 0,
+            failed,
+            rows: results,
+        });
+    }
+
+    let mut hashed = Vec::with_capacity(valid.len());
+    for row in valid.into_iter() {
+        let password = match row.password {
+            Some(password) => Some(hash_password(&password)?),
+            None => None,
+        };
+        hashed.push(UserImportRowDto { password, ..row });
+    }
+
+    let audit = AuditCtx::current();
+    let uow = state.db.begin().await?;
+    let work = async {
+        let mut users = Vec::with_capacity(hashed.len());
+        for row in hashed.into_iter() {
+            let user = uow
+                .users
+                .create(
+                    &audit,
+                    NewUserDto {
+                        email: row.email,
+                        name: row.name,
+                    },
+                )
+                .await?;
+
+            // Users without a password set one with the forgot password flow
+            if let Some(password) = row.password {
+                uow.passwords
+                    .create(user.id.clone(), NewPasswordDto { password })
+                    .await?;
+            }
+            users.push(user);
+        }
+        Ok(users)
+    };
+    let result = work.await;
+    let users = uow.finish(result).await?;
+    invalidate_suggestions(state);
+
+    for (result, user) in results.iter_mut().zip(users.iter()) {
+        result.user_id = Some(user.id.clone());
+        publish_change(
+            state,
+            EntityChangeDto::user(ChangeAction::Created, &user.id),
+        );
+    }
+
+    info!(created = users.len(), "users.imported");
+
+    for user in users.iter() {
+        if let Err(e) =
+            publish_user_event_svc(state, LifecycleTopic::UserProvisioned, user, Vec::new()).await
+        {
+            error!("Failed to publish lifecycle event: {}", e);
+        }
+    }
+
+    Ok(UserImportResultDto {
+        imported: true,
+        total,
+        created: users.len(),
+        failed: 0,
+        rows: results,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::TestCtx;
+
+    use super::{UserImportFormat, import_users_svc, parse_user_import};
+
+    #[test]
+    fn csv_rows_follow_the_header() {
+        let body = "name,email,password\r\n\
+                    \"Doe, Jane\",jane@example.com,\"pa\"\"ss1234\"\n\
+                    John,john@example.com,\n\
+                    broken\n";
+        let rows = parse_user_import(UserImportFormat::Csv, body).expect("parsed");
+
+        assert_eq!(rows.len(), 3);
+        let jane = rows[0].as_ref().expect("jane");
+        assert_eq!(jane.name, "Doe, Jane");
+        assert_eq!(jane.password.as_deref(), Some("pa\"ss1234"));
+        assert!(rows[1].as_ref().expect("john").password.is_none());
+        assert!(rows[2].is_err());
+
+        assert!(parse_user_import(UserImportFormat::Csv, "email\nx@example.com").is_err());
+        assert_eq!(
+            UserImportFormat::from_content_type("application/x-ndjson; charset=utf-8")
+                .expect("ndjson"),
+            UserImportFormat::Ndjson
+        );
+        assert!(UserImportFormat::from_content_type("application/json").is_err());
+    }
+
+    #[tokio::test]
+    async fn import_is_all_or_nothing() {
+        let ctx = TestCtx::new("user_import").await.expect("test ctx");
+        ctx.seed_user_with_password("Taken", "taken@example.com", "password123")
+            .await
+            .expect("existing user");
+
+        let failing = parse_user_import(
+            UserImportFormat::Ndjson,
+            r#"{"email":"new@example.com","name":"New","password":"password123"}
+{"email":"taken@example.com","name":"Taken Again"}
+{"email":"NEW@example.com","name":"Twice"}
+{"email":"not-an-email","name":""}
+not json"#,
+        )
+        .expect("parsed");
+        let result = import_users_svc(&ctx.state, failing).await.expect("import");
+
+        assert!(!result.imported);
+        assert_eq!((result.total, result.created, result.failed), (5, 0, 4));
+        assert!(result.rows[0].errors.is_empty());
+        assert_eq!(result.rows[1].errors, vec!["Email already exists"]);
+        assert_eq!(
+            result.rows[2].errors,
+            vec!["Email appears more than once in the file"]
+        );
+        assert!(!result.rows[3].errors.is_empty());
+        assert!(result.rows[4].errors[0].starts_with("Invalid JSON"));
+        assert!(
+            ctx.state
+                .db
+                .users
+                .find_by_email("new@example.com".to_string())
+                .await
+                .expect("query")
+                .is_none()
+        );
+
+        let passing = parse_user_import(
+            UserImportFormat::Csv,
+            "email,name,password\nnew@example.com,New,password123\nplain@example.com,Plain,\n",
+        )
+        .expect("parsed");
+        let result = import_users_svc(&ctx.state, passing).await.expect("import");
+
+        assert!(result.imported);
+        assert_eq!(result.created, 2);
+        let user_id = result.rows[0].user_id.clone().expect("user id");
+        assert!(
+            ctx.state
+                .db
+                .passwords
+                .get(user_id)
+                .await
+                .expect("query")
+                .is_some()
+        );
+        let plain_id = result.rows[1].user_id.clone().expect("user id");
+        assert!(
+            ctx.state
+                .db
+                .passwords
+                .get(plain_id)
+                .await
+                .expect("query")
+                .is_none()
+        );
+    }
+}
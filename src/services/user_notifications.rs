@@ -0,0 +1,309 @@
+use chrono::Utc;
+use tracing::{info, instrument};
+
+use crate::Result;
+use crate::db::DbMapper;
+use crate::dto::{
+    NewUserNotificationDto, OrgMemberDto, UserNotificationDto, UserNotificationKind,
+    UserNotificationsDto,
+};
+use crate::run::AppState;
+
+/// Notifications shown in the bell, older ones are still counted as unread
+const NOTIFICATION_LIST_LIMIT: i64 = 20;
+
+#[instrument(level = "debug", skip_all)]
+pub async fn notify_user_svc(
+    db: &DbMapper,
+    data: NewUserNotificationDto,
+) -> Result<UserNotificationDto> {
+    let now = Utc::now().timestamp_millis();
+    let notification = db.user_notifications.create(data, now).await?;
+
+    info!(
+        notification_id = notification.id.as_str(),
+        user_id = notification.user_id.as_str(),
+        kind = notification.kind.to_string(),
+        "user_notification.created"
+    );
+
+    Ok(notification)
+}
+
+async fn org_name(db: &DbMapper, org_id: &str) -> Result<String> {
+    let org = db.orgs.get(org_id.to_string()).await?;
+    Ok(org
+        .map(|org| org.name)
+        .unwrap_or_else(|| "an organization".to_string()))
+}
+
+fn role_names(member: &OrgMemberDto) -> String {
+    member
+        .roles
+        .iter()
+        .map(|role| role.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Tells the user they were added to an org
+#[instrument(level = "debug", skip_all)]
+pub async fn notify_membership_added_svc(db: &DbMapper, member: &OrgMemberDto) -> Result<()> {
+    let org_name = org_name(db, &member.org_id).await?;
+
+    let body = match member.status.as_str() {
+        "active" => format!("Your roles: {}.", role_names(member)),
+        _ => "An org admin still has to activate your membership.".to_string(),
+    };
+
+    notify_user_svc(
+        db,
+        NewUserNotificationDto {
+            user_id: member.user_id.clone(),
+            kind: UserNotificationKind::MembershipAdded,
+            title: format!("You were added to {}", org_name),
+            body,
+            link: None,
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Tells the member about new roles, nothing is sent when they did not change
+#[instrument(level = "debug", skip_all)]
+pub async fn notify_role_changed_svc(
+    db: &DbMapper,
+    existing: &OrgMemberDto,
+    member: &OrgMemberDto,
+) -> Result<()> {
+    if existing.roles == member.roles {
+        return Ok(());
+    }
+
+    let org_name = org_name(db, &member.org_id).await?;
+
+    notify_user_svc(
+        db,
+        NewUserNotificationDto {
+            user_id: member.user_id.clone(),
+            kind: UserNotificationKind::RoleChanged,
+            title: format!("Your roles in {} changed", org_name),
+            body: format!("Your roles: {}.", role_names(member)),
+            link: None,
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn notify_password_changed_svc(db: &DbMapper, user_id: &str) -> Result<()> {
+    notify_user_svc(
+        db,
+        NewUserNotificationDto {
+            user_id: user_id.to_string(),
+            kind: UserNotificationKind::PasswordChanged,
+            title: "Your password was changed".to_string(),
+            body: "If this was not you, reset your password and review your sessions.".to_string(),
+            link: Some("/profile".to_string()),
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Latest notifications of the user with the unread count
+#[instrument(level = "debug", skip_all)]
+pub async fn list_user_notifications_svc(
+    state: &AppState,
+    user_id: &str,
+) -> Result<UserNotificationsDto> {
+    let notifications = state
+        .db
+        .user_notifications
+        .list(user_id.to_string(), NOTIFICATION_LIST_LIMIT)
+        .await?;
+    let unread_count = state
+        .db
+        .user_notifications
+        .count_unread(user_id.to_string())
+        .await?;
+
+    Ok(UserNotificationsDto {
+        unread_count,
+        notifications,
+    })
+}
+
+/// Marks one notification as read, notifications of other users are ignored
+#[instrument(level = "debug", skip_all)]
+pub async fn mark_notification_read_svc(state: &AppState, user_id: &str, id: &str) -> Result<bool> {
+    let now = Utc::now().timestamp_millis();
+    state
+        .db
+        .user_notifications
+        .mark_read(user_id.to_string(), id.to_string(), now)
+        .await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn mark_all_notifications_read_svc(state: &AppState, user_id: &str) -> Result<u64> {
+    let now = Utc::now().timestamp_millis();
+    state
+        .db
+        .user_notifications
+        .mark_all_read(user_id.to_string(), now)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dto::{NewOrgMemberDto, NewPasswordDto, UpdateOrgMemberDto, UserNotificationKind};
+    use crate::services::org_members::{create_org_member_svc, update_org_member_svc};
+    use crate::services::password::update_password_svc;
+    use crate::test::TestCtx;
+
+    use super::{
+        list_user_notifications_svc, mark_all_notifications_read_svc, mark_notification_read_svc,
+    };
+
+    #[tokio::test]
+    async fn member_changes_notify_the_member() {
+        let ctx = TestCtx::new("user_notifications_member")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Owner User",
+                "notify.owner@example.com",
+                "password123",
+                "Notify Org",
+            )
+            .await
+            .expect("auth fixture");
+        let user = ctx
+            .seed_user_with_password("Member User", "notify.member@example.com", "password123")
+            .await
+            .expect("member user");
+
+        let member = create_org_member_svc(
+            &ctx.state,
+            &fixture.org.id,
+            NewOrgMemberDto {
+                user_id: user.id.clone(),
+                roles: vec!["OrgViewer".to_string()],
+                status: "active".to_string(),
+            },
+        )
+        .await
+        .expect("create member");
+
+        // Same roles, nothing to tell
+        update_org_member_svc(
+            &ctx.state,
+            &member.id,
+            UpdateOrgMemberDto {
+                roles: Some(vec!["OrgViewer".to_string()]),
+                status: None,
+            },
+        )
+        .await
+        .expect("update member");
+
+        update_org_member_svc(
+            &ctx.state,
+            &member.id,
+            UpdateOrgMemberDto {
+                roles: Some(vec!["OrgEditor".to_string()]),
+                status: None,
+            },
+        )
+        .await
+        .expect("update member");
+
+        let data = list_user_notifications_svc(&ctx.state, &user.id)
+            .await
+            .expect("list");
+        assert_eq!(data.unread_count, 2);
+
+        let kinds: Vec<UserNotificationKind> =
+            data.notifications.iter().map(|item| item.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                UserNotificationKind::RoleChanged,
+                UserNotificationKind::MembershipAdded
+            ]
+        );
+        assert_eq!(data.notifications[1].title, "You were added to Notify Org");
+        assert_eq!(data.notifications[0].body, "Your roles: OrgEditor.");
+    }
+
+    #[tokio::test]
+    async fn notifications_are_marked_as_read_by_their_owner_only() {
+        let ctx = TestCtx::new("user_notifications_read")
+            .await
+            .expect("test ctx");
+        let user = ctx
+            .seed_user_with_password("Jane", "notify.jane@example.com", "password123")
+            .await
+            .expect("user");
+        let other = ctx
+            .seed_user_with_password("John", "notify.john@example.com", "password123")
+            .await
+            .expect("other user");
+
+        for _ in 0..2 {
+            let updated = update_password_svc(
+                &ctx.state,
+                &user.id,
+                NewPasswordDto {
+                    password: "new-password123".to_string(),
+                },
+            )
+            .await
+            .expect("update password");
+            assert!(updated);
+        }
+
+        let data = list_user_notifications_svc(&ctx.state, &user.id)
+            .await
+            .expect("list");
+        assert_eq!(data.unread_count, 2);
+        assert_eq!(
+            data.notifications[0].kind,
+            UserNotificationKind::PasswordChanged
+        );
+        let id = data.notifications[0].id.clone();
+
+        let marked = mark_notification_read_svc(&ctx.state, &other.id, &id)
+            .await
+            .expect("mark read");
+        assert!(!marked);
+
+        let marked = mark_notification_read_svc(&ctx.state, &user.id, &id)
+            .await
+            .expect("mark read");
+        assert!(marked);
+
+        let data = list_user_notifications_svc(&ctx.state, &user.id)
+            .await
+            .expect("list");
+        assert_eq!(data.unread_count, 1);
+        assert!(data.notifications[0].read_at.is_some());
+
+        let count = mark_all_notifications_read_svc(&ctx.state, &user.id)
+            .await
+            .expect("mark all read");
+        assert_eq!(count, 1);
+
+        let data = list_user_notifications_svc(&ctx.state, &user.id)
+            .await
+            .expect("list");
+        assert_eq!(data.unread_count, 0);
+    }
+}
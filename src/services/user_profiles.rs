@@ -0,0 +1,303 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use snafu::ensure;
+use tracing::instrument;
+
+use crate::Result;
+use crate::dto::{UpdateUserProfileDto, UserProfileDto};
+use crate::error::{CsrfTokenSnafu, ValidationSnafu};
+use crate::run::AppState;
+use crate::services::token::verify_csrf_token;
+use crate::validators::validate_payload;
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct UserProfileFormData {
+    pub token: String,
+    pub avatar_url: String,
+    pub locale: String,
+    pub timezone: String,
+    pub phone: String,
+
+    /// JSON object replacing the stored metadata, empty for none
+    pub metadata: String,
+}
+
+/// Profile of the user, empty when never set
+#[instrument(level = "debug", skip_all)]
+pub async fn get_user_profile_svc(state: &AppState, user_id: &str) -> Result<UserProfileDto> {
+    let profile = state.db.user_profiles.get(user_id.to_string()).await?;
+
+    Ok(profile.unwrap_or(UserProfileDto {
+        user_id: user_id.to_string(),
+        ..Default::default()
+    }))
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn update_user_profile_svc(
+    state: &AppState,
+    user_id: &str,
+    data: UpdateUserProfileDto,
+) -> Result<UserProfileDto> {
+    validate_payload(&data)?;
+
+    let mut profile = get_user_profile_svc(state, user_id).await?;
+
+    let non_empty = |value: String| Some(value).filter(|value| !value.is_empty());
+    if let Some(avatar_url) = data.avatar_url {
+        profile.avatar_url = non_empty(avatar_url);
+    }
+    if let Some(locale) = data.locale {
+        profile.locale = non_empty(locale);
+    }
+    if let Some(timezone) = data.timezone {
+        profile.timezone = non_empty(timezone);
+    }
+    if let Some(phone) = data.phone {
+        profile.phone = non_empty(phone);
+    }
+
+    if let Some(metadata) = data.metadata {
+        for (key, value) in metadata.into_iter() {
+            if value.is_null() {
+                profile.metadata.remove(&key);
+            } else {
+                profile.metadata.insert(key, value);
+            }
+        }
+
+        // The merged object has to fit the limits as well
+        validate_payload(&UpdateUserProfileDto {
+            avatar_url: None,
+            locale: None,
+            timezone: None,
+            phone: None,
+            metadata: Some(profile.metadata.clone()),
+        })?;
+    }
+
+    state.db.user_profiles.upsert(profile).await
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn update_user_profile_web_svc(
+    state: &AppState,
+    user_id: &str,
+    form: UserProfileFormData,
+) -> Result<UserProfileDto> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == "user_profile", CsrfTokenSnafu);
+
+    let metadata_text = form.metadata.trim();
+    let mut metadata: Map<String, Value> = Map::new();
+    if !metadata_text.is_empty() {
+        let Ok(Value::Object(object)) = serde_json::from_str::<Value>(metadata_text) else {
+            return ValidationSnafu {
+                msg: "metadata: must be a JSON object",
+            }
+            .fail();
+        };
+        metadata = object;
+    }
+
+    // The form edits the whole object, keys left out are removed
+    let current = get_user_profile_svc(state, user_id).await?;
+    for key in current.metadata.keys() {
+        if !metadata.contains_key(key) {
+            metadata.insert(key.clone(), Value::Null);
+        }
+    }
+
+    let data = UpdateUserProfileDto {
+        avatar_url: Some(form.avatar_url.trim().to_string()),
+        locale: Some(form.locale.trim().to_string()),
+        timezone: Some(form.timezone.trim().to_string()),
+        phone: Some(form.phone.trim().to_string()),
+        metadata: Some(metadata),
+    };
+
+    update_user_profile_svc(state, user_id, data).await
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::Error;
+    use crate::dto::{USER_PROFILE_METADATA_MAX_BYTES, UpdateUserProfileDto};
+    use crate::services::token::create_csrf_token_svc;
+    use crate::test::TestCtx;
+
+    use super::{
+        UserProfileFormData, get_user_profile_svc, update_user_profile_svc,
+        update_user_profile_web_svc,
+    };
+
+    fn metadata_patch(value: serde_json::Value) -> UpdateUserProfileDto {
+        UpdateUserProfileDto {
+            avatar_url: None,
+            locale: None,
+            timezone: None,
+            phone: None,
+            metadata: value.as_object().cloned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn user_profile_updates_are_partial() {
+        let ctx = TestCtx::new("user_profile_partial")
+            .await
+            .expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Profile User",
+                "profile@example.com",
+                "password123",
+                "Profile Org",
+            )
+            .await
+            .expect("auth fixture");
+        let user_id = fixture.user.id.as_str();
+
+        let profile = get_user_profile_svc(&ctx.state, user_id)
+            .await
+            .expect("empty profile");
+        assert!(profile.locale.is_none());
+        assert!(profile.metadata.is_empty());
+
+        let data = UpdateUserProfileDto {
+            avatar_url: Some("https://cdn.example.com/me.png".to_string()),
+            locale: Some("en-PH".to_string()),
+            timezone: Some("Asia/Manila".to_string()),
+            phone: None,
+            metadata: json!({ "theme": "dark", "beta": true })
+                .as_object()
+                .cloned(),
+        };
+        update_user_profile_svc(&ctx.state, user_id, data)
+            .await
+            .expect("profile should be saved");
+
+        // Left out fields are kept, empty strings clear and null metadata keys are removed
+        let data = UpdateUserProfileDto {
+            avatar_url: Some("".to_string()),
+            locale: None,
+            timezone: None,
+            phone: Some("+639171234567".to_string()),
+            metadata: json!({ "beta": null, "team": "blue" }).as_object().cloned(),
+        };
+        update_user_profile_svc(&ctx.state, user_id, data)
+            .await
+            .expect("profile should be saved");
+
+        let profile = get_user_profile_svc(&ctx.state, user_id)
+            .await
+            .expect("profile");
+        assert!(profile.avatar_url.is_none());
+        assert_eq!(profile.locale.as_deref(), Some("en-PH"));
+        assert_eq!(profile.timezone.as_deref(), Some("Asia/Manila"));
+        assert_eq!(profile.phone.as_deref(), Some("+639171234567"));
+        assert_eq!(
+            serde_json::Value::Object(profile.metadata),
+            json!({ "theme": "dark", "team": "blue" })
+        );
+    }
+
+    #[tokio::test]
+    async fn user_profile_metadata_limits_apply_after_merging() {
+        let ctx = TestCtx::new("user_profile_limits").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Profile User",
+                "profile.limits@example.com",
+                "password123",
+                "Profile Org",
+            )
+            .await
+            .expect("auth fixture");
+        let user_id = fixture.user.id.as_str();
+        let half = "x".repeat(USER_PROFILE_METADATA_MAX_BYTES / 2);
+
+        update_user_profile_svc(&ctx.state, user_id, metadata_patch(json!({ "a": half })))
+            .await
+            .expect("first half fits");
+
+        let result =
+            update_user_profile_svc(&ctx.state, user_id, metadata_patch(json!({ "b": half })))
+                .await;
+        let Err(Error::Validation { msg }) = result else {
+            panic!("merged metadata should be too large");
+        };
+        assert_eq!(msg, "metadata: must be at most 4096 bytes");
+
+        let result = update_user_profile_svc(
+            &ctx.state,
+            user_id,
+            UpdateUserProfileDto {
+                avatar_url: None,
+                locale: Some("english".to_string()),
+                timezone: None,
+                phone: None,
+                metadata: None,
+            },
+        )
+        .await;
+        assert!(matches!(result, Err(Error::Validation { .. })));
+    }
+
+    #[tokio::test]
+    async fn user_profile_form_replaces_metadata() {
+        let ctx = TestCtx::new("user_profile_form").await.expect("test ctx");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Profile User",
+                "profile.form@example.com",
+                "password123",
+                "Profile Org",
+            )
+            .await
+            .expect("auth fixture");
+        let user_id = fixture.user.id.as_str();
+
+        update_user_profile_svc(
+            &ctx.state,
+            user_id,
+            metadata_patch(json!({ "theme": "dark", "beta": true })),
+        )
+        .await
+        .expect("profile should be saved");
+
+        let token = create_csrf_token_svc("user_profile", &ctx.state.config.jwt_secret)
+            .expect("csrf token");
+        let form = UserProfileFormData {
+            token: token.clone(),
+            avatar_url: "".to_string(),
+            locale: "fil".to_string(),
+            timezone: "UTC".to_string(),
+            phone: "".to_string(),
+            metadata: r#"{ "theme": "light" }"#.to_string(),
+        };
+        let profile = update_user_profile_web_svc(&ctx.state, user_id, form)
+            .await
+            .expect("form should save");
+        assert_eq!(profile.locale.as_deref(), Some("fil"));
+        assert_eq!(
+            serde_json::Value::Object(profile.metadata),
+            json!({ "theme": "light" })
+        );
+
+        let form = UserProfileFormData {
+            token,
+            avatar_url: "".to_string(),
+            locale: "".to_string(),
+            timezone: "".to_string(),
+            phone: "".to_string(),
+            metadata: "[1, 2]".to_string(),
+        };
+        let result = update_user_profile_web_svc(&ctx.state, user_id, form).await;
+        let Err(Error::Validation { msg }) = result else {
+            panic!("arrays are not accepted as metadata");
+        };
+        assert_eq!(msg, "metadata: must be a JSON object");
+    }
+}
@@ -1,12 +1,37 @@
 use serde::{Deserialize, Serialize};
-use snafu::ensure;
+use snafu::{OptionExt, ensure};
+use tracing::{error, info, instrument};
 
-use crate::dto::Paginated;
-use crate::dto::{ListUsersParamsDto, NewUserWithPasswordDto, UpdateUserDto, UserDto};
-use crate::error::{CsrfTokenSnafu, ValidationSnafu};
+use crate::ctx::AuditCtx;
+use crate::db::DbMapper;
+use crate::db::DeletedScope;
+use crate::db::SoftDelete;
+use crate::db::Versioned;
+use crate::dto::{
+    ApprovalAction, ChangeAction, EntityChangeDto, LifecycleTopic, ListUsersParamsDto,
+    NewUserWithPasswordDto, SuperuserDto, TokenRevocationDto, UpdateUserDto, UpdatedDto, UserDto,
+    changed_fields,
+};
+use crate::dto::{
+    BatchGetDto, BatchGetResultDto, BulkResultDto, CursorPage, MAX_BULK_ITEMS, Paginated,
+};
+use crate::error::{
+    CsrfTokenSnafu, ServiceSnafu, UserNotFoundSnafu, ValidationSnafu, VersionMismatchSnafu,
+};
+use crate::models::BulkStatusFormData;
 use crate::run::AppState;
+use crate::services::admin_events::publish_change;
+use crate::services::approvals::{approval_required_error, request_approval_svc};
+use crate::services::auth::invalidate_auth_cache;
+use crate::services::lifecycle::{publish_user_event_svc, queue_user_event_svc};
+use crate::services::orgs::{publish_org_deleted, release_owned_orgs};
 use crate::services::password::hash_password;
+use crate::services::suggestions::invalidate_suggestions;
 use crate::services::token::verify_csrf_token;
+use crate::services::user_emails::email_in_use_svc;
+use crate::utils::ListingAllocGuard;
+use crate::validators;
+use crate::validators::validate_payload;
 use crate::{Error, Result};
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -39,22 +64,49 @@ pub struct ChangePasswordFormData {
     pub confirm_password: String,
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn list_users_svc(
     state: &AppState,
     params: ListUsersParamsDto,
 ) -> Result<Paginated<UserDto>> {
+    let _guard = ListingAllocGuard::new("users");
     state.db.users.list(params).await
 }
 
+/// Admin listing that can also see soft deleted users
+#[instrument(level = "debug", skip_all)]
+pub async fn list_users_scoped_svc(
+    state: &AppState,
+    params: ListUsersParamsDto,
+    scope: DeletedScope,
+) -> Result<Paginated<UserDto>> {
+    let _guard = ListingAllocGuard::new("users");
+    state.db.users.list_scoped(params, scope).await
+}
+
+/// Keyset listing for large tables, see `ListUsersParamsDto::uses_cursor`
+#[instrument(level = "debug", skip_all)]
+pub async fn list_users_cursor_svc(
+    state: &AppState,
+    params: ListUsersParamsDto,
+    scope: DeletedScope,
+) -> Result<CursorPage<UserDto>> {
+    let _guard = ListingAllocGuard::new("users");
+    state.db.users.list_cursor(params, scope).await
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn create_user_svc(
     state: &AppState,
     mut data: NewUserWithPasswordDto,
 ) -> Result<UserDto> {
-    // Email must be unique
-    let existing = state.db.users.find_by_email(data.email.clone()).await?;
+    validate_payload(&data)?;
+
+    // Email must be unique across primary and secondary emails
+    let in_use = email_in_use_svc(state, &data.email).await?;
 
     ensure!(
-        existing.is_none(),
+        !in_use,
         ValidationSnafu {
             msg: "Email already exists".to_string(),
         }
@@ -63,9 +115,28 @@ pub async fn create_user_svc(
     // Hash password before sending to DB
     data.password = hash_password(&data.password)?;
 
-    state.db.users.create_with_password(data).await
+    let user = state
+        .db
+        .users
+        .create_with_password(&AuditCtx::current(), data)
+        .await?;
+
+    invalidate_suggestions(state);
+    publish_change(
+        state,
+        EntityChangeDto::user(ChangeAction::Created, &user.id),
+    );
+
+    if let Err(e) =
+        publish_user_event_svc(state, LifecycleTopic::UserProvisioned, &user, Vec::new()).await
+    {
+        error!("Failed to publish lifecycle event: {}", e);
+    }
+
+    Ok(user)
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn create_user_web_svc(state: &AppState, form: NewUserFormData) -> Result<UserDto> {
     let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
     ensure!(csrf_result == "new_user", CsrfTokenSnafu);
@@ -86,14 +157,115 @@ pub async fn create_user_web_svc(state: &AppState, form: NewUserFormData) -> Res
     create_user_svc(state, body).await
 }
 
+#[instrument(level = "debug", skip_all)]
 pub async fn get_user_svc(state: &AppState, id: &str) -> Result<Option<UserDto>> {
     state.db.users.get(id.to_string()).await
 }
 
+#[instrument(level = "debug", skip_all)]
+pub async fn get_user_scoped_svc(
+    state: &AppState,
+    id: &str,
+    scope: DeletedScope,
+) -> Result<Option<UserDto>> {
+    state.db.users.get_scoped(id.to_string(), scope).await
+}
+
+/// Email of a principal stamped in `created_by` or `updated_by`.
+///
+/// Deleted users still resolve so the audit trail keeps a readable name.
+#[instrument(level = "debug", skip_all)]
+pub async fn get_actor_email_svc(state: &AppState, id: Option<&str>) -> Result<Option<String>> {
+    let Some(id) = id else {
+        return Ok(None);
+    };
+
+    let user = get_user_scoped_svc(state, id, DeletedScope::WithDeleted).await?;
+    Ok(user.map(|user| user.email))
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn update_user_svc(state: &AppState, id: &str, data: UpdateUserDto) -> Result<bool> {
-    state.db.users.update(id.to_string(), data).await
+    validate_payload(&data)?;
+
+    let deactivating = data.status.as_deref() == Some("inactive");
+    let existing = match deactivating {
+        true => get_user_svc(state, id).await?,
+        false => None,
+    };
+
+    let updated = state
+        .db
+        .users
+        .update(&AuditCtx::current(), id.to_string(), data)
+        .await?;
+
+    if updated {
+        invalidate_auth_cache(state, id).await;
+        invalidate_suggestions(state);
+        publish_change(state, EntityChangeDto::user(ChangeAction::Updated, id));
+    }
+
+    if updated
+        && let Some(existing) = existing
+        && existing.status == "active"
+        && let Some(user) = get_user_svc(state, id).await?
+    {
+        let changed = changed_fields(&existing, &user);
+
+        if let Err(e) =
+            publish_user_event_svc(state, LifecycleTopic::UserDeactivated, &user, changed).await
+        {
+            error!("Failed to publish lifecycle event: {}", e);
+        }
+    }
+
+    Ok(updated)
+}
+
+/// Active users with the requested ids, ids not found are listed apart
+#[instrument(level = "debug", skip_all)]
+pub async fn batch_get_users_svc(
+    state: &AppState,
+    data: BatchGetDto,
+) -> Result<BatchGetResultDto<UserDto>> {
+    validate_payload(&data)?;
+
+    let found = state.db.users.list_by_ids(&data.ids).await?;
+    Ok(BatchGetResultDto::new(&data.ids, found, |user| {
+        user.id.as_str()
+    }))
 }
 
+/// Updates the user and reports which fields actually changed.
+///
+/// With a version, the update only goes through while the user is still at it.
+#[instrument(level = "debug", skip_all)]
+pub async fn update_user_tracked_svc(
+    state: &AppState,
+    id: &str,
+    data: UpdateUserDto,
+    version: Option<i64>,
+) -> Result<UpdatedDto<UserDto>> {
+    let before = get_user_svc(state, id).await?.context(UserNotFoundSnafu)?;
+
+    if let Some(version) = version {
+        validate_payload(&data)?;
+        let claimed = state
+            .db
+            .users
+            .claim_version(&AuditCtx::current(), id.to_string(), version)
+            .await?;
+        ensure!(claimed, VersionMismatchSnafu);
+    }
+
+    update_user_svc(state, id, data).await?;
+
+    let after = get_user_svc(state, id).await?.context(UserNotFoundSnafu)?;
+    Ok(UpdatedDto::new(&before, after))
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn update_user_status_web_svc(
     state: &AppState,
     user_id: &str,
@@ -120,16 +292,172 @@ pub async fn update_user_status_web_svc(
     Ok(updated_user)
 }
 
+#[instrument(level = "debug", skip_all)]
+pub async fn bulk_update_user_status_svc(
+    state: &AppState,
+    actor_id: &str,
+    ids: &[String],
+    status: &str,
+) -> Result<BulkResultDto> {
+    ensure!(
+        validators::status(status).is_ok(),
+        ValidationSnafu {
+            msg: "Status is invalid".to_string(),
+        }
+    );
+
+    ensure!(
+        !ids.is_empty(),
+        ValidationSnafu {
+            msg: "Select at least one user".to_string(),
+        }
+    );
+
+    ensure!(
+        ids.len() <= MAX_BULK_ITEMS,
+        ValidationSnafu {
+            msg: format!("Select at most {} users", MAX_BULK_ITEMS),
+        }
+    );
+
+    let mut result = BulkResultDto::default();
+
+    for id in ids.iter() {
+        if id == actor_id {
+            result.push_failure(id, id, "Cannot change your own status".to_string());
+            continue;
+        }
+
+        let user = match get_user_svc(state, id).await {
+            Ok(Some(user)) => user,
+            Ok(None) => {
+                result.push_failure(id, id, Error::UserNotFound.to_string());
+                continue;
+            }
+            Err(err) => {
+                result.push_failure(id, id, err.to_string());
+                continue;
+            }
+        };
+
+        let data = UpdateUserDto {
+            name: None,
+            status: Some(status.to_string()),
+        };
+
+        match update_user_svc(state, id, data).await {
+            Ok(_) => result.push_success(id, &user.email),
+            Err(err) => result.push_failure(id, &user.email, err.to_string()),
+        }
+    }
+
+    Ok(result)
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn bulk_update_user_status_web_svc(
+    state: &AppState,
+    actor_id: &str,
+    form: BulkStatusFormData,
+) -> Result<BulkResultDto> {
+    let csrf_result = verify_csrf_token(&form.token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == "bulk_users", CsrfTokenSnafu);
+
+    bulk_update_user_status_svc(state, actor_id, &form.ids, &form.status).await
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn delete_user_svc(state: &AppState, id: &str) -> Result<bool> {
-    // Delete user and password
-    let deleted = state.db.users.delete(id.to_string()).await?;
+    let existing = get_user_svc(state, id).await?;
+
+    // The user, its password, its memberships and its owned orgs go away together
+    let audit = AuditCtx::current();
+    let uow = state.db.begin().await?;
+    let work = async {
+        let owned = release_owned_orgs(&uow, &audit, id).await?;
+        let deleted = uow.users.delete(&audit, id.to_string()).await?;
+        uow.passwords.delete(id.to_string()).await?;
+
+        let org_ids = uow.org_members.delete_for_user(id.to_string()).await?;
+        for org_id in org_ids.iter() {
+            uow.counters.refresh_org(org_id.clone()).await?;
+        }
+        Ok((deleted, org_ids, owned))
+    };
+    let result = work.await;
+    let (deleted, org_ids, owned) = uow.finish(result).await?;
+
+    invalidate_auth_cache(state, id).await;
+    invalidate_suggestions(state);
+    if deleted {
+        publish_change(state, EntityChangeDto::user(ChangeAction::Deleted, id));
+    }
+    for org_id in org_ids.iter() {
+        publish_change(
+            state,
+            EntityChangeDto::org_member(ChangeAction::Deleted, org_id, id),
+        );
+    }
+    for (org_id, owner_id) in owned.reassigned.iter() {
+        publish_change(state, EntityChangeDto::org(ChangeAction::Updated, org_id));
+        info!(
+            org_id = org_id.as_str(),
+            owner_id = owner_id.as_str(),
+            "org.owner_reassigned"
+        );
+    }
+    for (org_id, user_ids) in owned.deleted.iter() {
+        publish_org_deleted(state, org_id, user_ids).await;
+    }
 
-    // No need to wrap in a transaction, who cares if delete of password fails
-    state.db.passwords.delete(id.to_string()).await?;
+    // Deleted users are reported as deactivated so downstream apps drop access
+    if deleted && let Some(existing) = existing {
+        let user = UserDto {
+            status: "deleted".to_string(),
+            ..existing
+        };
+
+        if let Err(e) =
+            publish_user_event_svc(state, LifecycleTopic::UserDeactivated, &user, Vec::new()).await
+        {
+            error!("Failed to publish lifecycle event: {}", e);
+        }
+    }
 
     Ok(deleted)
 }
 
+/// Brings back a soft deleted user.
+///
+/// The password and memberships were removed on delete, so the user signs in
+/// again through a recovery token or a password set by an admin and has to be
+/// added back to its orgs.
+#[instrument(level = "debug", skip_all)]
+pub async fn restore_user_svc(state: &AppState, id: &str) -> Result<UserDto> {
+    let Some(user) = get_user_scoped_svc(state, id, DeletedScope::OnlyDeleted).await? else {
+        return Err(Error::UserNotFound);
+    };
+
+    ensure!(
+        !email_in_use_svc(state, &user.email).await?,
+        ValidationSnafu {
+            msg: "Another user already uses this email.".to_string()
+        }
+    );
+
+    state
+        .db
+        .users
+        .restore(&AuditCtx::current(), id.to_string())
+        .await?;
+    invalidate_suggestions(state);
+    publish_change(state, EntityChangeDto::user(ChangeAction::Updated, id));
+    info!(user_id = id, "user.restored");
+
+    get_user_svc(state, id).await?.context(UserNotFoundSnafu)
+}
+
+#[instrument(level = "debug", skip_all)]
 pub async fn delete_user_web_svc(state: &AppState, user_id: &str, csrf_token: &str) -> Result<()> {
     let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
     ensure!(csrf_result == user_id, CsrfTokenSnafu);
@@ -139,17 +467,161 @@ pub async fn delete_user_web_svc(state: &AppState, user_id: &str, csrf_token: &s
     Ok(())
 }
 
+/// Deactivates the user from the command line and signs them out everywhere.
+///
+/// Running servers drop cached tokens within the 10 minute revocation cache TTL.
+/// The `user.deactivated` event is left to their delivery job.
+#[instrument(level = "debug", skip_all)]
+pub async fn deactivate_user_svc(db: &DbMapper, email: &str) -> Result<UserDto> {
+    let existing = db
+        .users
+        .find_by_email(email.trim().to_string())
+        .await?
+        .context(UserNotFoundSnafu)?;
+
+    ensure!(
+        existing.status == "active",
+        ValidationSnafu {
+            msg: format!("{} is already inactive", existing.email),
+        }
+    );
+
+    let data = UpdateUserDto {
+        name: None,
+        status: Some("inactive".to_string()),
+    };
+    db.users
+        .update(&AuditCtx::current(), existing.id.clone(), data)
+        .await?;
+
+    db.token_revocations
+        .upsert(TokenRevocationDto {
+            user_id: existing.id.clone(),
+            revoked_at: chrono::Utc::now().timestamp_millis(),
+            revoked_by: None,
+        })
+        .await?;
+
+    let user = db
+        .users
+        .get(existing.id.clone())
+        .await?
+        .context(UserNotFoundSnafu)?;
+
+    let changed = changed_fields(&existing, &user);
+    if let Err(e) = queue_user_event_svc(db, LifecycleTopic::UserDeactivated, &user, changed).await
+    {
+        error!("Failed to queue lifecycle event: {}", e);
+    }
+
+    info!(user_id = user.id.as_str(), "user.deactivated_from_cli");
+
+    Ok(user)
+}
+
+/// Checks that the user can become a superuser.
+///
+/// Superusers cannot belong to regular orgs, so members are refused.
+#[instrument(level = "debug", skip_all)]
+pub async fn ensure_can_grant_superuser_svc(state: &AppState, user_id: &str) -> Result<()> {
+    let Some(user) = get_user_svc(state, user_id).await? else {
+        return Err(Error::UserNotFound);
+    };
+
+    ensure!(
+        user.status == "active",
+        ValidationSnafu {
+            msg: "Only active users can become superusers".to_string()
+        }
+    );
+
+    let superuser = state.db.superusers.get(user_id.to_string()).await?;
+
+    ensure!(
+        superuser.is_none(),
+        ValidationSnafu {
+            msg: "User is already a superuser".to_string()
+        }
+    );
+
+    let memberships = state
+        .db
+        .org_members
+        .list_memberships_count(user_id.to_string())
+        .await?;
+
+    ensure!(
+        memberships == 0,
+        ValidationSnafu {
+            msg: "Remove the user from all orgs before granting superuser".to_string()
+        }
+    );
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all)]
+pub async fn grant_superuser_svc(state: &AppState, user_id: &str) -> Result<SuperuserDto> {
+    ensure_can_grant_superuser_svc(state, user_id).await?;
+
+    let superuser = state
+        .db
+        .superusers
+        .grant(user_id.to_string())
+        .await?
+        .context(ServiceSnafu {
+            msg: "Superuser org not found, run the setup first".to_string(),
+        })?;
+
+    invalidate_auth_cache(state, &superuser.id).await;
+    invalidate_suggestions(state);
+    publish_change(state, EntityChangeDto::user(ChangeAction::Updated, user_id));
+
+    info!(user_id = superuser.id.as_str(), "superuser.granted");
+
+    Ok(superuser)
+}
+
+/// Grants superuser right away, or files an approval under the two-person rule
+#[instrument(level = "debug", skip_all)]
+pub async fn grant_superuser_web_svc(
+    state: &AppState,
+    actor_id: &str,
+    user_id: &str,
+    csrf_token: &str,
+) -> Result<SuperuserDto> {
+    let csrf_result = verify_csrf_token(csrf_token, &state.config.jwt_secret)?;
+    ensure!(csrf_result == user_id, CsrfTokenSnafu);
+
+    if state.config.approvals.two_person_rule {
+        ensure_can_grant_superuser_svc(state, user_id).await?;
+
+        let approval =
+            request_approval_svc(state, ApprovalAction::GrantSuperuser, user_id, actor_id).await?;
+
+        return Err(approval_required_error(&approval));
+    }
+
+    grant_superuser_svc(state, user_id).await
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::dto::NewUserWithPasswordDto;
+    use crate::dto::{ListUsersParamsDto, NewOrgDto, NewOrgMemberDto, NewUserWithPasswordDto};
+    use crate::services::org_members::create_org_member_svc;
+    use crate::services::orgs::{create_org_svc, get_org_svc};
     use crate::services::password::verify_password;
     use crate::services::token::create_csrf_token_svc;
     use crate::test::TestCtx;
 
     use super::{
-        UserActiveFormData, create_user_svc, delete_user_web_svc, get_user_svc,
+        UserActiveFormData, bulk_update_user_status_web_svc, create_user_svc, deactivate_user_svc,
+        delete_user_svc, delete_user_web_svc, get_user_scoped_svc, get_user_svc,
+        list_users_cursor_svc, list_users_scoped_svc, list_users_svc, restore_user_svc,
         update_user_status_web_svc,
     };
+    use crate::db::DeletedScope;
+    use crate::models::BulkStatusFormData;
 
     #[tokio::test]
     async fn create_user_saves_and_hashes_password() {
@@ -307,4 +779,321 @@ mod tests {
 
         assert!(result.is_err(), "invalid csrf should fail");
     }
+
+    #[tokio::test]
+    async fn bulk_update_user_status_web_svc_reports_partial_failures() {
+        let ctx = TestCtx::new("users_bulk_status").await.expect("test ctx");
+        let actor = ctx
+            .seed_user_with_password("Actor", "bulk.actor@example.com", "password123")
+            .await
+            .expect("actor user");
+        let user = ctx
+            .seed_user_with_password("Target", "bulk.target@example.com", "password123")
+            .await
+            .expect("target user");
+
+        let csrf = create_csrf_token_svc("bulk_users", &ctx.state.config.jwt_secret)
+            .expect("csrf token should be generated");
+
+        let result = bulk_update_user_status_web_svc(
+            &ctx.state,
+            &actor.id,
+            BulkStatusFormData {
+                token: csrf,
+                status: "inactive".to_string(),
+                ids: vec![user.id.clone(), actor.id.clone(), "usr_missing".to_string()],
+            },
+        )
+        .await
+        .expect("bulk update should pass");
+
+        assert_eq!(result.succeeded(), 1);
+        assert_eq!(result.failed(), 2);
+
+        let fetched = get_user_svc(&ctx.state, &user.id)
+            .await
+            .expect("query should pass")
+            .expect("user should exist");
+        assert_eq!(fetched.status, "inactive");
+
+        let fetched_actor = get_user_svc(&ctx.state, &actor.id)
+            .await
+            .expect("query should pass")
+            .expect("actor should exist");
+        assert_eq!(fetched_actor.status, "active");
+    }
+
+    #[tokio::test]
+    async fn bulk_update_user_status_web_svc_rejects_invalid_status() {
+        let ctx = TestCtx::new("users_bulk_status_invalid")
+            .await
+            .expect("test ctx");
+        let user = ctx
+            .seed_user_with_password("Target", "bulk.invalid@example.com", "password123")
+            .await
+            .expect("target user");
+
+        let csrf = create_csrf_token_svc("bulk_users", &ctx.state.config.jwt_secret)
+            .expect("csrf token should be generated");
+
+        let result = bulk_update_user_status_web_svc(
+            &ctx.state,
+            "usr_actor",
+            BulkStatusFormData {
+                token: csrf,
+                status: "banned".to_string(),
+                ids: vec![user.id],
+            },
+        )
+        .await;
+
+        assert!(result.is_err(), "invalid status should fail");
+    }
+
+    #[tokio::test]
+    async fn list_users_cursor_walks_every_page_once() {
+        let ctx = TestCtx::new("users_list_cursor").await.expect("test ctx");
+
+        for i in 0..5 {
+            ctx.seed_user_with_password(
+                "Cursor User",
+                &format!("cursor{}@example.com", i),
+                "password123",
+            )
+            .await
+            .expect("user should be seeded");
+        }
+
+        let mut emails: Vec<String> = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = list_users_cursor_svc(
+                &ctx.state,
+                ListUsersParamsDto {
+                    keyword: Some("cursor".to_string()),
+                    cursor: cursor.clone(),
+                    limit: Some(2),
+                    ..Default::default()
+                },
+                DeletedScope::Active,
+            )
+            .await
+            .expect("listing should pass");
+
+            assert!(page.data.len() <= 2);
+            emails.extend(page.data.into_iter().map(|user| user.email));
+
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        let expected: Vec<String> = (0..5).map(|i| format!("cursor{}@example.com", i)).collect();
+        assert_eq!(emails, expected);
+
+        let result = list_users_cursor_svc(
+            &ctx.state,
+            ListUsersParamsDto {
+                cursor: Some("bogus".to_string()),
+                ..Default::default()
+            },
+            DeletedScope::Active,
+        )
+        .await;
+        assert!(result.is_err(), "tampered cursor should fail");
+    }
+
+    #[tokio::test]
+    async fn list_users_clamps_pagination_to_limits() {
+        let ctx = TestCtx::new("users_list_limits").await.expect("test ctx");
+
+        for i in 0..3 {
+            ctx.seed_user_with_password(
+                "Paged User",
+                &format!("paged{}@example.com", i),
+                "password123",
+            )
+            .await
+            .expect("user should be seeded");
+        }
+
+        let listing = list_users_svc(
+            &ctx.state,
+            ListUsersParamsDto {
+                page: Some(1),
+                per_page: Some(100000),
+                keyword: None,
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("listing should pass");
+        assert_eq!(listing.meta.per_page, 50);
+        assert_eq!(listing.data.len(), 3);
+
+        let listing = list_users_svc(
+            &ctx.state,
+            ListUsersParamsDto {
+                page: Some(100000),
+                per_page: Some(2),
+                keyword: None,
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("listing should pass");
+        assert_eq!(listing.meta.page, 1);
+        assert_eq!(listing.data.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn restore_user_svc_restores_soft_deleted_user() {
+        let ctx = TestCtx::new("users_restore").await.expect("test ctx");
+        let user = ctx
+            .seed_user_with_password("Restore Me", "restore@example.com", "password123")
+            .await
+            .expect("seed user");
+
+        delete_user_svc(&ctx.state, &user.id)
+            .await
+            .expect("delete should pass");
+        assert!(
+            get_user_svc(&ctx.state, &user.id)
+                .await
+                .expect("get should pass")
+                .is_none()
+        );
+
+        let deleted = get_user_scoped_svc(&ctx.state, &user.id, DeletedScope::WithDeleted)
+            .await
+            .expect("get should pass")
+            .expect("deleted user is visible with the escape hatch");
+        assert!(deleted.deleted_at.is_some());
+
+        let listed = list_users_scoped_svc(
+            &ctx.state,
+            ListUsersParamsDto::default(),
+            DeletedScope::OnlyDeleted,
+        )
+        .await
+        .expect("list should pass");
+        assert_eq!(listed.meta.total_records, 1);
+
+        let restored = restore_user_svc(&ctx.state, &user.id)
+            .await
+            .expect("restore should pass");
+        assert_eq!(restored.id, user.id);
+        assert!(restored.deleted_at.is_none());
+
+        let again = restore_user_svc(&ctx.state, &user.id).await;
+        assert!(again.is_err(), "active users cannot be restored");
+    }
+
+    #[tokio::test]
+    async fn restore_user_svc_rejects_taken_email() {
+        let ctx = TestCtx::new("users_restore_taken").await.expect("test ctx");
+        let user = ctx
+            .seed_user_with_password("First", "taken@example.com", "password123")
+            .await
+            .expect("seed user");
+
+        delete_user_svc(&ctx.state, &user.id)
+            .await
+            .expect("delete should pass");
+        ctx.seed_user_with_password("Second", "taken@example.com", "password123")
+            .await
+            .expect("email is free after delete");
+
+        let result = restore_user_svc(&ctx.state, &user.id).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn deactivate_user_marks_inactive_and_revokes_tokens() {
+        let ctx = TestCtx::new("deactivate_user_cli").await.expect("test ctx");
+        let db = &ctx.state.db;
+        let fixture = ctx
+            .seed_auth_fixture("Leaver", "leaver@example.com", "password123", "Leaver Org")
+            .await
+            .expect("fixture");
+
+        let user = deactivate_user_svc(db, "leaver@example.com")
+            .await
+            .expect("deactivate user");
+        assert_eq!(user.id, fixture.user.id);
+        assert_eq!(user.status, "inactive");
+
+        let revocation = db.token_revocations.find(user.id).await.expect("find");
+        assert!(revocation.is_some());
+
+        let again = deactivate_user_svc(db, "leaver@example.com").await;
+        let err = again.expect_err("already inactive");
+        assert_eq!(err.to_string(), "leaver@example.com is already inactive");
+    }
+
+    #[tokio::test]
+    async fn delete_user_svc_hands_owned_orgs_to_an_admin_or_deletes_them() {
+        let ctx = TestCtx::new("users_delete_owner").await.expect("test ctx");
+        let kept = ctx
+            .seed_auth_fixture(
+                "Owner User",
+                "leaving.owner@example.com",
+                "password123",
+                "Kept Org",
+            )
+            .await
+            .expect("auth fixture");
+        let owner = kept.user.clone();
+        let admin = ctx
+            .seed_user_with_password("Next Admin", "next.admin@example.com", "password123")
+            .await
+            .expect("admin user");
+        create_org_member_svc(
+            &ctx.state,
+            &kept.org.id,
+            NewOrgMemberDto {
+                user_id: admin.id.clone(),
+                roles: vec!["OrgAdmin".to_string()],
+                status: "active".to_string(),
+            },
+        )
+        .await
+        .expect("admin member");
+
+        let dropped = create_org_svc(
+            &ctx.state,
+            NewOrgDto {
+                name: "Dropped Org".to_string(),
+                owner_id: owner.id.clone(),
+            },
+        )
+        .await
+        .expect("second org");
+
+        delete_user_svc(&ctx.state, &owner.id)
+            .await
+            .expect("user deleted");
+
+        let org = get_org_svc(&ctx.state, &kept.org.id)
+            .await
+            .expect("org query")
+            .expect("org with another admin is kept");
+        assert_eq!(org.owner_id, Some(admin.id.clone()));
+        assert_eq!(org.member_count, 1);
+
+        let org = get_org_svc(&ctx.state, &dropped.id)
+            .await
+            .expect("org query");
+        assert!(org.is_none(), "org without another admin is deleted");
+
+        let membership = ctx
+            .state
+            .db
+            .org_members
+            .find_member(kept.org.id.clone(), owner.id.clone())
+            .await
+            .expect("membership query");
+        assert!(membership.is_none());
+    }
 }
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -9,32 +10,33 @@ use snafu::ResultExt;
 use turso::{Builder, Connection, Value};
 
 use crate::Result;
-use crate::config::{AssetManifest, Config, DbConfig, ServerConfig, SuperuserConfig};
+use crate::config::{
+    ApprovalConfig, AssetManifest, AuthCacheConfig, AuthRateLimitConfig, Config, DbConfig,
+    MailTransport, MailerConfig, OrgRateLimitConfig, RegionConfig, RegionRouting, ServerConfig,
+    SuperuserConfig, TokenClaimsConfig, TokenDenylistStore,
+};
 use crate::ctx::Ctx;
-use crate::db::create_db_mapper;
+use crate::db::{MIGRATIONS, create_db_mapper};
 use crate::dto::{
-    Actor, ActorPayloadDto, AppDto, NewAppDto, NewOrgAppDto, NewOrgDto, NewUserWithPasswordDto,
-    OrgDto, Role, Scope, UserDto,
+    Actor, ActorPayloadDto, AppDto, NewAppDto, NewOrgAppDto, NewOrgDto, NewPasswordDto, NewUserDto,
+    NewUserWithPasswordDto, OrgDto, PaginationLimits, Role, Scope, SuperuserDto, UserDto,
 };
 use crate::error::{DbBuilderSnafu, DbConnectSnafu, DbPrepareSnafu, DbStatementSnafu, IoSnafu};
 use crate::run::AppState;
+use crate::services::admin_events::AdminEvents;
 use crate::services::apps::create_app_svc;
+use crate::services::auth::new_auth_cache;
+use crate::services::auth_rate_limits::AuthRateLimiter;
+use crate::services::mailer::Mailer;
 use crate::services::org_apps::create_org_app_svc;
+use crate::services::org_rate_limits::OrgRateLimiter;
 use crate::services::orgs::create_org_svc;
+use crate::services::revocations::TokenDenylist;
+use crate::services::shared_cache::SharedCache;
+use crate::services::token::TokenKeys;
 use crate::services::users::create_user_svc;
 use crate::utils::{IdPrefix, generate_id};
 
-const MIGRATIONS: &[&str] = &[
-    include_str!("../db/migrations/02-create-users.sql"),
-    include_str!("../db/migrations/03-create-passwords.sql"),
-    include_str!("../db/migrations/04-create-orgs.sql"),
-    include_str!("../db/migrations/05-create-org-members.sql"),
-    include_str!("../db/migrations/06-create-apps.sql"),
-    include_str!("../db/migrations/07-create-org-apps.sql"),
-    include_str!("../db/migrations/08-create-oauth-codes.sql"),
-    include_str!("../db/migrations/09-create-superusers.sql"),
-];
-
 pub struct TestCtx {
     pub state: AppState,
     pub db_dir: PathBuf,
@@ -61,6 +63,17 @@ impl AuthFixture {
                 org_count: 1,
                 roles: vec![Role::OrgAdmin],
                 scopes,
+                grant_id: None,
+                proof_key_id: None,
+                permission_mask: None,
+                issued_at: 0,
+                session_id: None,
+                region: None,
+                home_region: None,
+                token_id: None,
+                org_app_id: None,
+                impersonator_id: None,
+                impersonation_id: None,
             },
             self.user.clone(),
         );
@@ -87,7 +100,7 @@ impl TestCtx {
         let conn = create_connection(&db_file).await?;
         run_migrations(&conn).await?;
 
-        let mapper = create_db_mapper(db_file.as_path()).await?;
+        let mapper = create_db_mapper(db_file.as_path(), &PaginationLimits::default()).await?;
 
         let config = Config {
             server: ServerConfig {
@@ -96,13 +109,56 @@ impl TestCtx {
             },
             db: DbConfig {
                 dir: db_dir.clone(),
+                read_path: None,
             },
             superuser: SuperuserConfig { setup_key: None },
             jwt_secret: "test-jwt-secret".to_string(),
+            jwt_signing: None,
+            token_denylist: TokenDenylistStore::Database,
             frontend_dir: db_dir.clone(),
             captcha_site_key: None,
             captcha_api_key: None,
             ga_tag_id: None,
+            token_claims: TokenClaimsConfig::default(),
+            integrity_scan_mins: 0,
+            notification_digest_hours: 0,
+            app_secret_grace_hours: 24,
+            memory_sample_mins: 0,
+            counter_reconcile_mins: 0,
+            org_access_retention_days: 0,
+            pagination: PaginationLimits::default(),
+            approvals: ApprovalConfig {
+                two_person_rule: false,
+                window_mins: 60,
+                elevation_approval: false,
+            },
+            org_rate_limit: OrgRateLimitConfig {
+                requests_per_min: 0,
+                burst: 0,
+            },
+            auth_rate_limit: AuthRateLimitConfig {
+                per_ip: 0,
+                per_email: 0,
+                window_secs: 60,
+            },
+            auth_cache: AuthCacheConfig {
+                ttl_secs: 10 * 60,
+                max_capacity: 100,
+            },
+            redis: None,
+            region: RegionConfig {
+                name: None,
+                routing: RegionRouting::Off,
+                urls: HashMap::new(),
+            },
+            route_rollouts: HashMap::new(),
+            redirect_uri_probe: false,
+            oidc: None,
+            mailer: MailerConfig {
+                from: "Yaas <noreply@localhost>".to_string(),
+                base_url: "http://localhost:4000".to_string(),
+                transport: MailTransport::Log,
+            },
             assets: AssetManifest {
                 main_css: "".to_string(),
                 main_js: "".to_string(),
@@ -114,18 +170,44 @@ impl TestCtx {
             .build()
             .expect("HTTP Client is required");
 
-        let auth_cache = Cache::builder()
-            .time_to_live(Duration::from_secs(10 * 60))
-            .time_to_idle(Duration::from_secs(60))
+        let auth_cache = new_auth_cache(&config.auth_cache);
+
+        let suggestion_cache = Cache::builder()
+            .time_to_live(Duration::from_secs(30))
+            .max_capacity(500)
+            .build();
+
+        let access_log_cache = Cache::builder()
+            .time_to_live(Duration::from_secs(60))
             .max_capacity(100)
             .build();
 
+        let revocation_cache = Cache::builder()
+            .time_to_live(Duration::from_secs(60))
+            .max_capacity(100)
+            .build();
+
+        let token_keys = Arc::new(TokenKeys::new(&config)?);
+        let token_denylist = TokenDenylist::new(config.token_denylist);
+        let mailer = Arc::new(Mailer::new(&config.mailer)?);
+
         Ok(Self {
             state: AppState {
                 config: Arc::new(config),
                 db: Arc::new(mapper),
                 client,
                 auth_cache,
+                suggestion_cache,
+                access_log_cache,
+                revocation_cache,
+                token_denylist,
+                shared_cache: Arc::new(SharedCache::memory()),
+                org_rate_limiter: OrgRateLimiter::default(),
+                auth_rate_limiter: AuthRateLimiter::default(),
+                oidc: None,
+                token_keys,
+                mailer,
+                admin_events: AdminEvents::default(),
             },
             db_dir,
         })
@@ -148,6 +230,35 @@ impl TestCtx {
         .await
     }
 
+    /// Seeds a superuser, the first one goes through the setup flow
+    pub async fn seed_superuser(&self, email: &str) -> Result<SuperuserDto> {
+        let superusers = self.state.db.superusers.list().await?;
+
+        if superusers.is_empty() {
+            return self
+                .state
+                .db
+                .superusers
+                .setup(
+                    NewUserDto {
+                        email: email.to_string(),
+                        name: "Superuser".to_string(),
+                    },
+                    NewPasswordDto {
+                        password: "not-a-real-hash".to_string(),
+                    },
+                )
+                .await;
+        }
+
+        let user = self
+            .seed_user_with_password("Superuser", email, "password123")
+            .await?;
+
+        let superuser = self.state.db.superusers.grant(user.id).await?;
+        Ok(superuser.expect("superuser org should exist"))
+    }
+
     pub async fn seed_auth_fixture(
         &self,
         name: &str,
@@ -180,11 +291,13 @@ impl TestCtx {
             NewAppDto {
                 name: name.to_string(),
                 redirect_uri: redirect_uri.to_string(),
+                scopes: None,
             },
         )
         .await
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn seed_oauth_fixture(
         &self,
         name: &str,
@@ -237,7 +350,7 @@ async fn create_connection(filename: &Path) -> Result<Connection> {
 
 async fn run_migrations(conn: &Connection) -> Result<()> {
     for migration in MIGRATIONS {
-        for stmt in migration.split(';') {
+        for stmt in migration.sql.split(';') {
             let sql = stmt.trim();
             if sql.is_empty() {
                 continue;
@@ -0,0 +1,144 @@
+//! Allocation counters for the optional `alloc-stats` feature.
+//!
+//! The counting allocator wraps mimalloc, the allocator used otherwise, and
+//! keeps process wide totals, so deltas taken around a request also include
+//! whatever other requests allocated at the same time.
+
+use tracing::warn;
+
+/// Listings that allocate more than this are logged as possible regressions
+pub const LISTING_ALLOC_WARN_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Point-in-time allocator counters
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocCounters {
+    pub allocations: u64,
+    pub deallocations: u64,
+    pub allocated_bytes: u64,
+    pub live_bytes: u64,
+    pub peak_bytes: u64,
+}
+
+#[cfg(feature = "alloc-stats")]
+mod counting {
+    use mimalloc::MiMalloc;
+    use std::alloc::{GlobalAlloc, Layout};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::AllocCounters;
+
+    static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+    static DEALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+    static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+    static LIVE_BYTES: AtomicU64 = AtomicU64::new(0);
+    static PEAK_BYTES: AtomicU64 = AtomicU64::new(0);
+
+    /// mimalloc wrapper that counts allocations and live bytes
+    pub struct CountingAlloc;
+
+    fn record_alloc(size: u64) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(size, Ordering::Relaxed);
+        let live = LIVE_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK_BYTES.fetch_max(live, Ordering::Relaxed);
+    }
+
+    fn record_dealloc(size: u64) {
+        DEALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        LIVE_BYTES.fetch_sub(size, Ordering::Relaxed);
+    }
+
+    unsafe impl GlobalAlloc for CountingAlloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = unsafe { MiMalloc.alloc(layout) };
+            if !ptr.is_null() {
+                record_alloc(layout.size() as u64);
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = unsafe { MiMalloc.alloc_zeroed(layout) };
+            if !ptr.is_null() {
+                record_alloc(layout.size() as u64);
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { MiMalloc.dealloc(ptr, layout) };
+            record_dealloc(layout.size() as u64);
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = unsafe { MiMalloc.realloc(ptr, layout, new_size) };
+            if !new_ptr.is_null() {
+                record_dealloc(layout.size() as u64);
+                record_alloc(new_size as u64);
+            }
+            new_ptr
+        }
+    }
+
+    pub fn snapshot() -> AllocCounters {
+        AllocCounters {
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            deallocations: DEALLOCATIONS.load(Ordering::Relaxed),
+            allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+            live_bytes: LIVE_BYTES.load(Ordering::Relaxed),
+            peak_bytes: PEAK_BYTES.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(feature = "alloc-stats")]
+pub use counting::CountingAlloc;
+
+/// Current allocator counters, `None` unless built with `alloc-stats`
+pub fn alloc_counters() -> Option<AllocCounters> {
+    #[cfg(feature = "alloc-stats")]
+    {
+        Some(counting::snapshot())
+    }
+
+    #[cfg(not(feature = "alloc-stats"))]
+    {
+        None
+    }
+}
+
+/// Logs a warning when a listing allocates more than expected.
+///
+/// Hold it for the duration of a listing service call. Does nothing unless
+/// built with `alloc-stats`.
+pub struct ListingAllocGuard {
+    listing: &'static str,
+    start: Option<u64>,
+}
+
+impl ListingAllocGuard {
+    pub fn new(listing: &'static str) -> Self {
+        Self {
+            listing,
+            start: alloc_counters().map(|counters| counters.allocated_bytes),
+        }
+    }
+}
+
+impl Drop for ListingAllocGuard {
+    fn drop(&mut self) {
+        let (Some(start), Some(end)) = (self.start, alloc_counters()) else {
+            return;
+        };
+
+        let allocated = end.allocated_bytes.saturating_sub(start);
+        if allocated > LISTING_ALLOC_WARN_BYTES {
+            warn!(
+                listing = self.listing,
+                allocated_bytes = allocated,
+                threshold_bytes = LISTING_ALLOC_WARN_BYTES,
+                "listing.alloc_exceeded"
+            );
+        }
+    }
+}
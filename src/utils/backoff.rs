@@ -0,0 +1,46 @@
+/// Exponential backoff of a delivery or job retried after failing
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    /// Attempts before giving up
+    pub max_attempts: i64,
+    pub base_secs: i64,
+    pub max_secs: i64,
+}
+
+impl RetryPolicy {
+    /// Delay in seconds before the next attempt after `attempts` failed ones.
+    ///
+    /// Doubles from `base_secs` and is capped at `max_secs`.
+    pub fn delay_secs(&self, attempts: i64) -> i64 {
+        let exponent = (attempts - 1).clamp(0, 20) as u32;
+        (self.base_secs * 2_i64.pow(exponent)).min(self.max_secs)
+    }
+
+    /// True when the attempt was the last one allowed
+    pub fn exhausted(&self, attempts: i64) -> bool {
+        attempts >= self.max_attempts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_doubles_up_to_the_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 8,
+            base_secs: 60,
+            max_secs: 60 * 60,
+        };
+
+        assert_eq!(policy.delay_secs(0), 60);
+        assert_eq!(policy.delay_secs(1), 60);
+        assert_eq!(policy.delay_secs(2), 120);
+        assert_eq!(policy.delay_secs(3), 240);
+        assert_eq!(policy.delay_secs(7), 3600);
+        assert_eq!(policy.delay_secs(100), 3600);
+        assert!(!policy.exhausted(7));
+        assert!(policy.exhausted(8));
+    }
+}
@@ -41,7 +41,7 @@ mod tests {
     #[test]
     fn test_str_to_datetime_invalid() {
         let date_str = "2025-01-01";
-        let date = str_to_datetime(&date_str);
+        let date = str_to_datetime(date_str);
         assert!(date.is_err());
     }
 
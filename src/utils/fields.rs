@@ -0,0 +1,87 @@
+//! Sparse fieldsets: clients pick the fields they need with `?fields=`.
+
+use serde::Serialize;
+use serde_json::Value;
+use snafu::ensure;
+
+use crate::Result;
+use crate::error::ValidationSnafu;
+
+/// Most fields a client can ask for at once
+const MAX_FIELDS: usize = 30;
+
+/// DTOs returned by the JSON APIs declare the fields a client may select
+pub trait SparseFields: Serialize {
+    const FIELDS: &'static [&'static str];
+}
+
+/// Parses a comma separated `fields` value, each field must be allowed
+pub fn parse_fields(raw: &str, allowed: &[&str]) -> Result<Vec<String>> {
+    let mut fields: Vec<String> = Vec::new();
+
+    for field in raw.split(',').map(|field| field.trim()) {
+        ensure!(
+            !field.is_empty(),
+            ValidationSnafu {
+                msg: "fields must not contain empty names".to_string(),
+            }
+        );
+
+        ensure!(
+            allowed.contains(&field),
+            ValidationSnafu {
+                msg: format!(
+                    "Unknown field: {}. Allowed fields: {}",
+                    field,
+                    allowed.join(", ")
+                ),
+            }
+        );
+
+        if !fields.iter().any(|existing| existing == field) {
+            fields.push(field.to_string());
+        }
+    }
+
+    ensure!(
+        fields.len() <= MAX_FIELDS,
+        ValidationSnafu {
+            msg: format!("Select at most {} fields", MAX_FIELDS),
+        }
+    );
+
+    Ok(fields)
+}
+
+/// Drops the keys of a JSON object that were not selected
+pub fn retain_fields(value: &mut Value, fields: &[String]) {
+    if let Value::Object(map) = value {
+        map.retain(|key, _| fields.iter().any(|field| field == key));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{parse_fields, retain_fields};
+
+    const ALLOWED: &[&str] = &["id", "email", "name"];
+
+    #[test]
+    fn test_parse_fields() {
+        let fields = parse_fields(" id,email ,id", ALLOWED).expect("valid fields");
+        assert_eq!(fields, vec!["id".to_string(), "email".to_string()]);
+
+        assert!(parse_fields("id,password", ALLOWED).is_err());
+        assert!(parse_fields("id,,email", ALLOWED).is_err());
+        assert!(parse_fields("", ALLOWED).is_err());
+    }
+
+    #[test]
+    fn test_retain_fields() {
+        let mut value = json!({ "id": "usr_1", "email": "a@example.com", "status": "active" });
+        retain_fields(&mut value, &["id".to_string(), "name".to_string()]);
+        assert_eq!(value, json!({ "id": "usr_1" }));
+    }
+}
@@ -10,9 +10,34 @@ pub enum IdPrefix {
     Org,
     OrgApp,
     OauthCode,
+    OauthGrant,
     Password,
     Superuser,
     SuperuserKey,
+    UserEmail,
+    EmailVerification,
+    NotificationPref,
+    Approval,
+    LifecycleSubscription,
+    LifecycleEvent,
+    LifecycleDelivery,
+    SigningSecret,
+    RecoveryToken,
+    AppEnvironment,
+    RoleElevation,
+    UserSession,
+    PasswordReset,
+    OrgRole,
+    OrgInvitation,
+    Team,
+    Job,
+    TokenId,
+    Email,
+    UserNotification,
+    OwnerTransfer,
+    Impersonation,
+    ImpersonationAction,
+    UserExport,
 }
 
 impl TryFrom<&str> for IdPrefix {
@@ -28,9 +53,34 @@ impl TryFrom<&str> for IdPrefix {
             "org" => Ok(Self::Org),
             "oap" => Ok(Self::OrgApp),
             "oac" => Ok(Self::OauthCode),
+            "ogr" => Ok(Self::OauthGrant),
             "pas" => Ok(Self::Password),
             "sup" => Ok(Self::Superuser),
             "suk" => Ok(Self::SuperuserKey),
+            "uem" => Ok(Self::UserEmail),
+            "emv" => Ok(Self::EmailVerification),
+            "ntp" => Ok(Self::NotificationPref),
+            "apv" => Ok(Self::Approval),
+            "lcs" => Ok(Self::LifecycleSubscription),
+            "lce" => Ok(Self::LifecycleEvent),
+            "lcd" => Ok(Self::LifecycleDelivery),
+            "sgs" => Ok(Self::SigningSecret),
+            "rct" => Ok(Self::RecoveryToken),
+            "aen" => Ok(Self::AppEnvironment),
+            "rel" => Ok(Self::RoleElevation),
+            "ses" => Ok(Self::UserSession),
+            "pwr" => Ok(Self::PasswordReset),
+            "orl" => Ok(Self::OrgRole),
+            "inv" => Ok(Self::OrgInvitation),
+            "tem" => Ok(Self::Team),
+            "job" => Ok(Self::Job),
+            "jti" => Ok(Self::TokenId),
+            "eml" => Ok(Self::Email),
+            "unt" => Ok(Self::UserNotification),
+            "otr" => Ok(Self::OwnerTransfer),
+            "imp" => Ok(Self::Impersonation),
+            "ima" => Ok(Self::ImpersonationAction),
+            "uex" => Ok(Self::UserExport),
             _ => Err(format!("Invalid ID Prefix: {value}")),
         }
     }
@@ -47,9 +97,34 @@ impl core::fmt::Display for IdPrefix {
             Self::Org => write!(f, "org"),
             Self::OrgApp => write!(f, "oap"),
             Self::OauthCode => write!(f, "oac"),
+            Self::OauthGrant => write!(f, "ogr"),
             Self::Password => write!(f, "pas"),
             Self::Superuser => write!(f, "sup"),
             Self::SuperuserKey => write!(f, "suk"),
+            Self::UserEmail => write!(f, "uem"),
+            Self::EmailVerification => write!(f, "emv"),
+            Self::NotificationPref => write!(f, "ntp"),
+            Self::Approval => write!(f, "apv"),
+            Self::LifecycleSubscription => write!(f, "lcs"),
+            Self::LifecycleEvent => write!(f, "lce"),
+            Self::LifecycleDelivery => write!(f, "lcd"),
+            Self::SigningSecret => write!(f, "sgs"),
+            Self::RecoveryToken => write!(f, "rct"),
+            Self::AppEnvironment => write!(f, "aen"),
+            Self::RoleElevation => write!(f, "rel"),
+            Self::UserSession => write!(f, "ses"),
+            Self::PasswordReset => write!(f, "pwr"),
+            Self::OrgRole => write!(f, "orl"),
+            Self::OrgInvitation => write!(f, "inv"),
+            Self::Team => write!(f, "tem"),
+            Self::Job => write!(f, "job"),
+            Self::TokenId => write!(f, "jti"),
+            Self::Email => write!(f, "eml"),
+            Self::UserNotification => write!(f, "unt"),
+            Self::OwnerTransfer => write!(f, "otr"),
+            Self::Impersonation => write!(f, "imp"),
+            Self::ImpersonationAction => write!(f, "ima"),
+            Self::UserExport => write!(f, "uex"),
         }
     }
 }
@@ -90,7 +165,7 @@ mod tests {
         assert!(id.starts_with("usr_"));
 
         // Can be parsed back as uuid
-        assert_eq!(valid_id(id.as_str()), true);
+        assert!(valid_id(id.as_str()));
     }
 
     #[test]
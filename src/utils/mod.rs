@@ -1,14 +1,28 @@
+mod alloc;
+mod backoff;
 mod datetime;
+mod fields;
 mod id;
 mod oauth;
+mod proof;
+mod redact;
+mod secret;
 mod slug;
+mod telemetry;
 mod truncate;
 
+pub use alloc::*;
+pub use backoff::*;
 #[allow(unused)]
 pub use datetime::*;
+pub use fields::*;
 pub use id::*;
 pub use oauth::*;
+pub use proof::*;
+pub use redact::*;
+pub use secret::*;
 #[allow(unused)]
 pub use slug::*;
+pub use telemetry::*;
 #[allow(unused)]
 pub use truncate::*;
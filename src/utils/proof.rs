@@ -0,0 +1,174 @@
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use ring::digest;
+use ring::signature::{ED25519, UnparsedPublicKey};
+
+/// Proofs signed further than this from the server clock are rejected
+pub const TOKEN_PROOF_MAX_SKEW_SECS: i64 = 60;
+
+/// Decodes a base64url Ed25519 public key, padding is tolerated
+pub fn decode_proof_public_key(value: &str) -> Result<Vec<u8>, String> {
+    let Ok(bytes) = URL_SAFE_NO_PAD.decode(value.trim().trim_end_matches('=')) else {
+        return Err("Public key must be base64url encoded.".to_string());
+    };
+
+    if bytes.len() != 32 {
+        return Err("Public key must be a raw 32 byte Ed25519 key.".to_string());
+    }
+
+    Ok(bytes)
+}
+
+/// Stable key id, the base64url SHA-256 thumbprint of the raw public key
+pub fn proof_key_id(public_key: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(digest::digest(&digest::SHA256, public_key))
+}
+
+/// Message the client signs for each request.
+///
+/// Binds the proof to the request line, the time it was made and the
+/// access token it accompanies.
+pub fn token_proof_message(method: &str, path: &str, timestamp: i64, token: &str) -> String {
+    let token_hash = digest::digest(&digest::SHA256, token.as_bytes());
+
+    format!(
+        "{}\n{}\n{}\n{}",
+        method.to_uppercase(),
+        path,
+        timestamp,
+        URL_SAFE_NO_PAD.encode(token_hash)
+    )
+}
+
+/// Verifies a `<unix timestamp>.<base64url signature>` proof header
+pub fn verify_token_proof(
+    public_key: &[u8],
+    header: &str,
+    method: &str,
+    path: &str,
+    token: &str,
+    now: i64,
+) -> Result<(), String> {
+    let Some((timestamp, signature)) = header.trim().split_once('.') else {
+        return Err("malformed proof header".to_string());
+    };
+
+    let Ok(timestamp) = timestamp.parse::<i64>() else {
+        return Err("malformed proof timestamp".to_string());
+    };
+
+    if (now - timestamp).abs() > TOKEN_PROOF_MAX_SKEW_SECS {
+        return Err("proof has expired".to_string());
+    }
+
+    let Ok(signature) = URL_SAFE_NO_PAD.decode(signature) else {
+        return Err("malformed proof signature".to_string());
+    };
+
+    let message = token_proof_message(method, path, timestamp, token);
+    let key = UnparsedPublicKey::new(&ED25519, public_key);
+
+    key.verify(message.as_bytes(), &signature)
+        .map_err(|_| "signature does not match".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    use super::*;
+
+    fn key_pair() -> Ed25519KeyPair {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
+
+    fn sign(pair: &Ed25519KeyPair, method: &str, path: &str, ts: i64, token: &str) -> String {
+        let message = token_proof_message(method, path, ts, token);
+        let signature = pair.sign(message.as_bytes());
+        format!("{}.{}", ts, URL_SAFE_NO_PAD.encode(signature.as_ref()))
+    }
+
+    #[test]
+    fn test_decode_proof_public_key() {
+        let pair = key_pair();
+        let encoded = URL_SAFE_NO_PAD.encode(pair.public_key().as_ref());
+
+        let decoded = decode_proof_public_key(&encoded).unwrap();
+        assert_eq!(decoded, pair.public_key().as_ref());
+        assert_eq!(proof_key_id(&decoded).len(), 43);
+
+        assert!(decode_proof_public_key("not base64!").is_err());
+        assert!(decode_proof_public_key(&URL_SAFE_NO_PAD.encode([1u8; 16])).is_err());
+    }
+
+    #[test]
+    fn test_verify_token_proof() {
+        let pair = key_pair();
+        let public_key = pair.public_key().as_ref();
+        let header = sign(&pair, "get", "/oauth/profile", 1_000, "token-1");
+
+        assert!(
+            verify_token_proof(
+                public_key,
+                &header,
+                "GET",
+                "/oauth/profile",
+                "token-1",
+                1_030
+            )
+            .is_ok()
+        );
+
+        // Another request, token or key does not match
+        assert!(
+            verify_token_proof(
+                public_key,
+                &header,
+                "DELETE",
+                "/oauth/profile",
+                "token-1",
+                1_000
+            )
+            .is_err()
+        );
+        assert!(
+            verify_token_proof(
+                public_key,
+                &header,
+                "GET",
+                "/oauth/profile",
+                "token-2",
+                1_000
+            )
+            .is_err()
+        );
+        let other = key_pair();
+        assert!(
+            verify_token_proof(
+                other.public_key().as_ref(),
+                &header,
+                "GET",
+                "/oauth/profile",
+                "token-1",
+                1_000
+            )
+            .is_err()
+        );
+
+        // Stale or malformed
+        assert_eq!(
+            verify_token_proof(
+                public_key,
+                &header,
+                "GET",
+                "/oauth/profile",
+                "token-1",
+                1_061
+            ),
+            Err("proof has expired".to_string())
+        );
+        assert!(verify_token_proof(public_key, "garbage", "GET", "/", "token-1", 1_000).is_err());
+    }
+}
@@ -0,0 +1,188 @@
+//! Masks secrets before request data or DTOs reach the logs.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Replacement for masked values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Exact field names that are always masked
+const SENSITIVE_NAMES: &[&str] = &["code", "code_verifier", "setup_key", "authorization"];
+
+/// Whether a field or query param name carries a secret.
+///
+/// Covers passwords, secrets, tokens and keys by name so request bodies can
+/// be masked without knowing which DTO they belong to.
+pub fn is_sensitive_field(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SENSITIVE_NAMES.contains(&name.as_str())
+        || name.contains("password")
+        || name.contains("secret")
+        || name == "token"
+        || name.ends_with("_token")
+        || name.ends_with("_key")
+}
+
+/// DTOs carrying secrets declare them so their debug output stays masked.
+///
+/// Declared fields must also match [`is_sensitive_field`], the request log
+/// only sees raw bodies and relies on the name rules.
+pub trait Redact: Serialize {
+    const SENSITIVE_FIELDS: &'static [&'static str];
+
+    /// JSON form of the value with the declared fields masked
+    fn redacted(&self) -> Value {
+        let mut value = serde_json::to_value(self).unwrap_or(Value::Null);
+        if let Value::Object(map) = &mut value {
+            for field in Self::SENSITIVE_FIELDS.iter() {
+                if let Some(entry) = map.get_mut(*field) {
+                    *entry = Value::String(REDACTED.to_string());
+                }
+            }
+        }
+        value
+    }
+}
+
+/// Implements `Debug` through [`Redact::redacted`]
+macro_rules! redacted_debug {
+    ($($ty:ident),+ $(,)?) => {
+        $(
+            impl core::fmt::Debug for $ty {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    let value = $crate::utils::Redact::redacted(self);
+                    write!(f, "{} {}", stringify!($ty), value)
+                }
+            }
+        )+
+    };
+}
+
+pub(crate) use redacted_debug;
+
+/// Masks sensitive keys at any depth
+pub fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, entry) in map.iter_mut() {
+                if is_sensitive_field(key) {
+                    *entry = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(entry);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Masks sensitive params of a query string or urlencoded form body
+pub fn redact_query(query: &str) -> String {
+    query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, _)) if is_sensitive_field(&decode_key(key)) => {
+                format!("{}={}", key, REDACTED)
+            }
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+fn decode_key(key: &str) -> String {
+    urlencoding::decode(&key.replace('+', " "))
+        .map(|key| key.into_owned())
+        .unwrap_or_else(|_| key.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::dto::{
+        AppDto, AppEnvironmentDto, AuthResponseDto, ChangeCurrentPasswordDto, CredentialsDto,
+        NewPasswordDto, NewUserWithPasswordDto, OauthTokenRequestDto, OauthTokenResponseDto,
+        PasswordDto, SetupBodyDto,
+    };
+
+    use super::{REDACTED, Redact, is_sensitive_field, redact_json, redact_query};
+
+    #[test]
+    fn test_is_sensitive_field() {
+        for name in [
+            "password",
+            "new_password",
+            "client_secret",
+            "token",
+            "access_token",
+            "setup_key",
+            "code",
+        ] {
+            assert!(is_sensitive_field(name), "{} should be sensitive", name);
+        }
+        for name in ["email", "name", "state", "redirect_uri", "client_id"] {
+            assert!(!is_sensitive_field(name), "{} should be kept", name);
+        }
+    }
+
+    #[test]
+    fn test_declared_fields_match_log_rules() {
+        let declared = [
+            AppDto::SENSITIVE_FIELDS,
+            AppEnvironmentDto::SENSITIVE_FIELDS,
+            AuthResponseDto::SENSITIVE_FIELDS,
+            ChangeCurrentPasswordDto::SENSITIVE_FIELDS,
+            CredentialsDto::SENSITIVE_FIELDS,
+            NewPasswordDto::SENSITIVE_FIELDS,
+            NewUserWithPasswordDto::SENSITIVE_FIELDS,
+            OauthTokenRequestDto::SENSITIVE_FIELDS,
+            OauthTokenResponseDto::SENSITIVE_FIELDS,
+            PasswordDto::SENSITIVE_FIELDS,
+            SetupBodyDto::SENSITIVE_FIELDS,
+        ];
+        for field in declared.iter().flat_map(|fields| fields.iter()) {
+            assert!(is_sensitive_field(field), "{} is not masked in logs", field);
+        }
+    }
+
+    #[test]
+    fn test_debug_output_is_redacted() {
+        let credentials = CredentialsDto {
+            email: "jane@example.com".to_string(),
+            password: "hunter22".to_string(),
+        };
+        let output = format!("{:?}", credentials);
+        assert!(output.starts_with("CredentialsDto {"));
+        assert!(output.contains("jane@example.com"));
+        assert!(!output.contains("hunter22"));
+    }
+
+    #[test]
+    fn test_redact_json() {
+        let mut value = json!({
+            "email": "jane@example.com",
+            "password": "hunter22",
+            "items": [{ "client_secret": "s3cret", "name": "App" }],
+        });
+        redact_json(&mut value);
+
+        assert_eq!(value["email"], "jane@example.com");
+        assert_eq!(value["password"], REDACTED);
+        assert_eq!(value["items"][0]["client_secret"], REDACTED);
+        assert_eq!(value["items"][0]["name"], "App");
+    }
+
+    #[test]
+    fn test_redact_query() {
+        assert_eq!(
+            redact_query("token=abc&next=%2Fhome&new%5Fpassword=x"),
+            format!(
+                "token={}&next=%2Fhome&new%5Fpassword={}",
+                REDACTED, REDACTED
+            )
+        );
+        assert_eq!(redact_query("page=2"), "page=2");
+    }
+}
@@ -0,0 +1,27 @@
+use ring::digest;
+
+/// Hex SHA-256 of a high entropy secret, so only the hash is stored.
+///
+/// Surrounding whitespace is ignored since secrets are often pasted.
+pub fn hash_secret(secret: &str) -> String {
+    let hash = digest::digest(&digest::SHA256, secret.trim().as_bytes());
+    hex::encode(hash.as_ref())
+}
+
+/// Hashes accepted for a client, the rotated out one included while in grace
+pub fn accepted_secret_hashes(
+    current: Option<&String>,
+    previous: Option<&String>,
+    previous_expires_at: Option<i64>,
+    now: i64,
+) -> Vec<String> {
+    let mut hashes: Vec<String> = current.into_iter().cloned().collect();
+
+    if let (Some(hash), Some(expires_at)) = (previous, previous_expires_at)
+        && expires_at > now
+    {
+        hashes.push(hash.clone());
+    }
+
+    hashes
+}
@@ -0,0 +1,131 @@
+use axum::http::HeaderMap;
+use opentelemetry::Context;
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_http::{HeaderExtractor, HeaderInjector};
+use opentelemetry_otlp::SpanExporter;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use std::env;
+use tracing::{Level, Metadata};
+use tracing_subscriber::filter::{LevelFilter, filter_fn};
+use tracing_subscriber::prelude::*;
+
+use crate::{Error, Result};
+
+/// Service and db spans are recorded at this level, below the default log level
+const TRACE_SPAN_LEVEL: Level = Level::DEBUG;
+
+/// Installs the log output and, when an OTLP endpoint is set, the trace exporter.
+///
+/// The exporter reads the standard `OTEL_EXPORTER_OTLP_ENDPOINT` or
+/// `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` and `OTEL_EXPORTER_OTLP_HEADERS`
+/// variables. The returned provider must be shut down to flush the last spans.
+pub fn init_tracing(max_log: Level) -> Result<Option<SdkTracerProvider>> {
+    let fmt_layer = tracing_subscriber::fmt::layer()
+        .with_target(false)
+        .compact()
+        .with_filter(LevelFilter::from_level(max_log));
+
+    let provider = build_tracer_provider()?;
+
+    // Spans go down to the service and db layers, events follow the log level
+    // so request bodies logged at debug level are not exported
+    let otel_layer = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer()
+            .with_tracer(provider.tracer("yaas"))
+            .with_filter(filter_fn(move |meta: &Metadata| match meta.is_span() {
+                true => *meta.level() <= TRACE_SPAN_LEVEL,
+                false => *meta.level() <= max_log,
+            }))
+    });
+
+    tracing_subscriber::registry()
+        .with(fmt_layer)
+        .with(otel_layer)
+        .init();
+
+    Ok(provider)
+}
+
+fn build_tracer_provider() -> Result<Option<SdkTracerProvider>> {
+    let configured = [
+        "OTEL_EXPORTER_OTLP_ENDPOINT",
+        "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+    ]
+    .iter()
+    .any(|name| env::var(name).is_ok_and(|value| !value.trim().is_empty()));
+
+    if !configured {
+        return Ok(None);
+    }
+
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .build()
+        .map_err(|e| Error::Config {
+            msg: format!("Unable to create the OTLP exporter: {}", e),
+        })?;
+
+    let service_name = env::var("OTEL_SERVICE_NAME")
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| "yaas".to_string());
+
+    let provider = SdkTracerProvider::builder()
+        .with_resource(Resource::builder().with_service_name(service_name).build())
+        .with_batch_exporter(exporter)
+        .build();
+
+    Ok(Some(provider))
+}
+
+/// Remote parent from the W3C `traceparent` and `tracestate` headers
+pub fn extract_trace_context(headers: &HeaderMap) -> Context {
+    TraceContextPropagator::new().extract(&HeaderExtractor(headers))
+}
+
+/// W3C trace headers continuing the given context, empty when not traced
+pub fn trace_context_headers(cx: &Context) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    TraceContextPropagator::new().inject_context(cx, &mut HeaderInjector(&mut headers));
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, HeaderValue};
+    use opentelemetry::Context;
+    use opentelemetry::trace::TraceContextExt;
+
+    use super::{extract_trace_context, trace_context_headers};
+
+    #[test]
+    fn trace_context_round_trips_through_headers() {
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+        let mut headers = HeaderMap::new();
+        headers.insert("traceparent", HeaderValue::from_static(traceparent));
+
+        let cx = extract_trace_context(&headers);
+        let span_context = cx.span().span_context().clone();
+        assert!(span_context.is_remote());
+        assert_eq!(
+            span_context.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+
+        let headers = trace_context_headers(&cx);
+        assert_eq!(headers["traceparent"], traceparent);
+
+        // Nothing to continue outside of a trace
+        assert!(trace_context_headers(&Context::new()).is_empty());
+        assert!(
+            !extract_trace_context(&HeaderMap::new())
+                .span()
+                .span_context()
+                .is_valid()
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use core::result::Result;
+use validator::ValidationError;
+
+/// Email domain like `example.com`, without the `@`
+pub fn email_domain(value: &str) -> Result<(), ValidationError> {
+    let labels: Vec<&str> = value.split('.').collect();
+    let valid = value.len() <= 253
+        && labels.len() >= 2
+        && labels.iter().all(|label| {
+            (1..=63).contains(&label.len())
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        });
+
+    match valid {
+        true => Ok(()),
+        false => Err(ValidationError::new("email_domain")),
+    }
+}
+
+pub fn email_domains(items: &[String]) -> Result<(), ValidationError> {
+    items.iter().try_for_each(|item| email_domain(item))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_email_domain() {
+        assert!(email_domain("example.com").is_ok());
+        assert!(email_domain("mail.example.co.uk").is_ok());
+        assert!(email_domain("my-company.io").is_ok());
+        assert!(email_domain("localhost").is_err());
+        assert!(email_domain("@example.com").is_err());
+        assert!(email_domain("Example.com").is_err());
+        assert!(email_domain("-bad.com").is_err());
+        assert!(email_domain("example..com").is_err());
+        assert!(email_domains(&["example.com".to_string(), "bad".to_string()]).is_err());
+    }
+}
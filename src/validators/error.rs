@@ -1,5 +1,7 @@
 use validator::{ValidationError, ValidationErrors};
 
+use crate::dto::{USER_PROFILE_METADATA_MAX_BYTES, USER_PROFILE_METADATA_MAX_KEYS};
+
 pub fn flatten_errors(errors: &ValidationErrors) -> String {
     // Collect field keys first
     let mut fields: Vec<String> = errors
@@ -51,7 +53,18 @@ fn error_to_string(error: &ValidationError) -> String {
             _ => "invalid".to_string(),
         },
         "required" => "required".to_string(),
+        "uuid" => "must be a valid id".to_string(),
         "sluggable" => "must be composed of alpha-numeric characters or dashes".to_string(),
+        "email_domain" => "must be domain names like example.com".to_string(),
+        "member_role" => "must be OrgAdmin, OrgEditor or OrgViewer".to_string(),
+        "locale" => "must be a language tag like en-US".to_string(),
+        "timezone" => "must be a time zone name like Asia/Manila".to_string(),
+        "phone" => "must be an international number like +639171234567".to_string(),
+        "metadata_keys" => format!(
+            "must have at most {} keys of 1 to 64 characters",
+            USER_PROFILE_METADATA_MAX_KEYS
+        ),
+        "metadata_size" => format!("must be at most {} bytes", USER_PROFILE_METADATA_MAX_BYTES),
         _ => "invalid".to_string(),
     }
 }
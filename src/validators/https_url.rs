@@ -0,0 +1,29 @@
+use core::result::Result;
+use url::Url;
+use validator::ValidationError;
+
+/// Images like avatars and logos are loaded by browsers, only https URLs are accepted.
+/// Empty strings are accepted, they clear the URL.
+pub fn https_url(value: &str) -> Result<(), ValidationError> {
+    if value.is_empty() {
+        return Ok(());
+    }
+
+    match Url::parse(value) {
+        Ok(url) if url.scheme() == "https" && url.host_str().is_some() => Ok(()),
+        _ => Err(ValidationError::new("url")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_https_url() {
+        assert!(https_url("https://cdn.example.com/me.png").is_ok());
+        assert!(https_url("").is_ok());
+        assert!(https_url("http://cdn.example.com/me.png").is_err());
+        assert!(https_url("javascript:alert(1)").is_err());
+    }
+}
@@ -2,23 +2,33 @@ mod alphanumeric;
 mod anyname;
 mod csvname;
 mod datetime;
+mod email_domain;
 mod error;
+mod https_url;
+mod payload;
 mod prefixed_uuid;
+mod profile;
 mod roles;
+mod scopes;
 mod sluggable;
+mod sort;
 mod status;
 
 #[allow(unused)]
 pub use alphanumeric::*;
-#[allow(unused)]
 pub use anyname::*;
 #[allow(unused)]
 pub use csvname::*;
 #[allow(unused)]
 pub use datetime::*;
+pub use email_domain::*;
 pub use error::*;
-#[allow(unused)]
+pub use https_url::*;
+pub use payload::*;
 pub use prefixed_uuid::*;
+pub use profile::*;
 pub use roles::*;
+pub use scopes::*;
 pub use sluggable::*;
+pub use sort::*;
 pub use status::*;
@@ -0,0 +1,50 @@
+use validator::Validate;
+
+use super::flatten_errors;
+use crate::{Error, Result};
+
+/// Rejects decoded payloads with empty or malformed required fields.
+///
+/// Form and JSON extractors only check that fields are present, so this runs
+/// before any lookup or write and reports errors per field.
+pub fn validate_payload<T: Validate>(data: &T) -> Result<()> {
+    match data.validate() {
+        Ok(_) => Ok(()),
+        Err(errors) => Err(Error::Validation {
+            msg: flatten_errors(&errors),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto::{NewOrgDto, NewOrgMemberDto};
+    use crate::utils::{IdPrefix, generate_id};
+
+    #[test]
+    fn test_empty_payload_is_rejected_per_field() {
+        let data = NewOrgDto {
+            name: "".to_string(),
+            owner_id: "".to_string(),
+        };
+
+        let Err(Error::Validation { msg }) = validate_payload(&data) else {
+            panic!("empty payload should be rejected");
+        };
+        assert_eq!(
+            msg,
+            "name: must be between 1 and 100 characters, owner_id: must be a valid id"
+        );
+    }
+
+    #[test]
+    fn test_valid_payload_is_accepted() {
+        let data = NewOrgMemberDto {
+            user_id: generate_id(IdPrefix::User),
+            roles: vec!["OrgViewer".to_string()],
+            status: "active".to_string(),
+        };
+        assert!(validate_payload(&data).is_ok());
+    }
+}
@@ -0,0 +1,129 @@
+use core::result::Result;
+use serde_json::{Map, Value};
+use validator::ValidationError;
+
+use crate::dto::{USER_PROFILE_METADATA_MAX_BYTES, USER_PROFILE_METADATA_MAX_KEYS};
+
+// Empty strings are accepted by the profile validators, they clear the attribute
+
+/// BCP 47 language tag like `en`, `en-US` or `zh-Hant-TW`
+pub fn locale(value: &str) -> Result<(), ValidationError> {
+    if value.is_empty() {
+        return Ok(());
+    }
+
+    let mut parts = value.split('-');
+    let language = parts.next().unwrap_or_default();
+    let valid_language =
+        (2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_alphabetic());
+    let valid_subtags = parts.all(|part| {
+        (2..=8).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric())
+    });
+
+    match valid_language && valid_subtags && value.len() <= 35 {
+        true => Ok(()),
+        false => Err(ValidationError::new("locale")),
+    }
+}
+
+/// IANA time zone name like `UTC` or `Asia/Manila`, the name itself is not looked up
+pub fn timezone(value: &str) -> Result<(), ValidationError> {
+    if value.is_empty() {
+        return Ok(());
+    }
+
+    let valid = value.len() <= 64
+        && value.split('/').all(|part| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '+')
+        });
+
+    match valid {
+        true => Ok(()),
+        false => Err(ValidationError::new("timezone")),
+    }
+}
+
+/// E.164 phone number like `+639171234567`
+pub fn phone(value: &str) -> Result<(), ValidationError> {
+    if value.is_empty() {
+        return Ok(());
+    }
+
+    let Some(digits) = value.strip_prefix('+') else {
+        return Err(ValidationError::new("phone"));
+    };
+
+    let valid = (8..=15).contains(&digits.len())
+        && !digits.starts_with('0')
+        && digits.chars().all(|c| c.is_ascii_digit());
+
+    match valid {
+        true => Ok(()),
+        false => Err(ValidationError::new("phone")),
+    }
+}
+
+/// Metadata stays a small JSON object with short keys
+pub fn profile_metadata(value: &Map<String, Value>) -> Result<(), ValidationError> {
+    if value.len() > USER_PROFILE_METADATA_MAX_KEYS {
+        return Err(ValidationError::new("metadata_keys"));
+    }
+
+    if value.keys().any(|key| key.is_empty() || key.len() > 64) {
+        return Err(ValidationError::new("metadata_keys"));
+    }
+
+    let size = serde_json::to_string(value).map(|json| json.len());
+    match size {
+        Ok(size) if size <= USER_PROFILE_METADATA_MAX_BYTES => Ok(()),
+        _ => Err(ValidationError::new("metadata_size")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_profile_attributes() {
+        assert!(locale("en").is_ok());
+        assert!(locale("en-US").is_ok());
+        assert!(locale("zh-Hant-TW").is_ok());
+        assert!(locale("english").is_err());
+        assert!(locale("en_US").is_err());
+
+        assert!(timezone("UTC").is_ok());
+        assert!(timezone("Asia/Manila").is_ok());
+        assert!(timezone("America/Argentina/Buenos_Aires").is_ok());
+        assert!(timezone("Etc/GMT+8").is_ok());
+        assert!(timezone("Asia//Manila").is_err());
+        assert!(timezone("Asia/Manila City").is_err());
+
+        assert!(phone("+639171234567").is_ok());
+        assert!(phone("09171234567").is_err());
+        assert!(phone("+0123456789").is_err());
+        assert!(phone("+63 917 123 4567").is_err());
+    }
+
+    #[test]
+    fn test_profile_metadata() {
+        let metadata = json!({ "theme": "dark", "beta": true });
+        assert!(profile_metadata(metadata.as_object().expect("object")).is_ok());
+
+        let metadata = json!({ "": 1 });
+        assert!(profile_metadata(metadata.as_object().expect("object")).is_err());
+
+        let metadata = json!({ "notes": "x".repeat(USER_PROFILE_METADATA_MAX_BYTES) });
+        assert!(profile_metadata(metadata.as_object().expect("object")).is_err());
+
+        let many: Map<String, Value> = (0..=USER_PROFILE_METADATA_MAX_KEYS)
+            .map(|k| (format!("key{}", k), json!(k)))
+            .collect();
+        assert!(profile_metadata(&many).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 use core::result::Result;
 use validator::ValidationError;
 
-use crate::dto::to_roles;
+use crate::dto::{Role, custom_role_permissions as allowed_permissions, to_permissions, to_roles};
 
 pub fn roles(items: &[String]) -> Result<(), ValidationError> {
     match to_roles(items) {
@@ -10,10 +10,45 @@ pub fn roles(items: &[String]) -> Result<(), ValidationError> {
     }
 }
 
+/// Built-in role an org member can hold, superusers are not org members
+pub fn member_role(value: &str) -> Result<(), ValidationError> {
+    match Role::try_from(value) {
+        Ok(Role::Superuser) | Err(_) => Err(ValidationError::new("member_role")),
+        Ok(_) => Ok(()),
+    }
+}
+
+/// Built-in roles given to org members, ie: by a team
+pub fn member_roles(items: &[String]) -> Result<(), ValidationError> {
+    items.iter().try_for_each(|item| member_role(item))
+}
+
+/// Permissions of a custom org role, only org level permissions are allowed
+pub fn custom_role_permissions(items: &[String]) -> Result<(), ValidationError> {
+    let allowed = allowed_permissions();
+    match to_permissions(items) {
+        Ok(permissions) if permissions.iter().all(|p| allowed.contains(p)) => Ok(()),
+        _ => Err(ValidationError::new("custom_role_permissions")),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_member_role() {
+        assert!(member_role("OrgViewer").is_ok());
+        assert!(member_role("OrgAdmin").is_ok());
+        assert!(member_role("Superuser").is_err());
+        assert!(member_role("Owner").is_err());
+
+        let items = vec!["OrgEditor".to_string(), "OrgViewer".to_string()];
+        assert!(member_roles(&items).is_ok());
+        let items = vec!["OrgViewer".to_string(), "Superuser".to_string()];
+        assert!(member_roles(&items).is_err());
+    }
+
     #[test]
     fn test_roles_valid() {
         let items = vec!["OrgAdmin".to_string(), "OrgEditor".to_string()];
@@ -25,4 +60,16 @@ mod tests {
         let items = vec!["OrgAdmin".to_string(), "CEO".to_string()];
         assert!(roles(&items).is_err());
     }
+
+    #[test]
+    fn test_custom_role_permissions() {
+        let items = vec!["org_members.edit".to_string(), "files.view".to_string()];
+        assert!(custom_role_permissions(&items).is_ok());
+
+        let items = vec!["org_members.edit".to_string(), "users.create".to_string()];
+        assert!(custom_role_permissions(&items).is_err());
+
+        let items = vec!["files.binge".to_string()];
+        assert!(custom_role_permissions(&items).is_err());
+    }
 }
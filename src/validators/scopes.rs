@@ -0,0 +1,27 @@
+use core::result::Result;
+use validator::ValidationError;
+
+use crate::dto::{APP_SCOPES, to_scopes};
+
+/// Scopes an app may request with the authorization code flow, at least one is required
+pub fn app_scopes(items: &[String]) -> Result<(), ValidationError> {
+    match to_scopes(items) {
+        Ok(scopes) if !scopes.is_empty() && scopes.iter().all(|s| APP_SCOPES.contains(s)) => Ok(()),
+        _ => Err(ValidationError::new("app_scopes")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_scopes() {
+        let items = vec!["openid".to_string(), "org.read".to_string()];
+        assert!(app_scopes(&items).is_ok());
+
+        assert!(app_scopes(&[]).is_err());
+        assert!(app_scopes(&["vault".to_string()]).is_err());
+        assert!(app_scopes(&["org.write".to_string()]).is_err());
+    }
+}
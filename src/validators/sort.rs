@@ -0,0 +1,55 @@
+use core::result::Result;
+use validator::ValidationError;
+
+use crate::dto::{APP_SORT, ORG_APP_SORT, ORG_MEMBER_SORT, ORG_SORT, SortSpec, USER_SORT};
+
+pub fn sort_dir(value: &str) -> Result<(), ValidationError> {
+    match value {
+        "asc" | "desc" => Ok(()),
+        _ => Err(ValidationError::new("sort_dir")),
+    }
+}
+
+fn sort_field(spec: &SortSpec, value: &str) -> Result<(), ValidationError> {
+    match spec.column(value) {
+        Some(_) => Ok(()),
+        None => Err(ValidationError::new("sort_by")),
+    }
+}
+
+pub fn user_sort(value: &str) -> Result<(), ValidationError> {
+    sort_field(&USER_SORT, value)
+}
+
+pub fn org_sort(value: &str) -> Result<(), ValidationError> {
+    sort_field(&ORG_SORT, value)
+}
+
+pub fn org_member_sort(value: &str) -> Result<(), ValidationError> {
+    sort_field(&ORG_MEMBER_SORT, value)
+}
+
+pub fn app_sort(value: &str) -> Result<(), ValidationError> {
+    sort_field(&APP_SORT, value)
+}
+
+pub fn org_app_sort(value: &str) -> Result<(), ValidationError> {
+    sort_field(&ORG_APP_SORT, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_fields() {
+        assert!(user_sort("email").is_ok());
+        assert!(user_sort("password").is_err());
+        assert!(org_sort("member_count").is_ok());
+        assert!(org_member_sort("owner_email").is_err());
+        assert!(app_sort("").is_err());
+        assert!(org_app_sort("created_at").is_ok());
+        assert!(sort_dir("desc").is_ok());
+        assert!(sort_dir("DESC").is_err());
+    }
+}
@@ -0,0 +1,1999 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query, Request, State, rejection::JsonRejection},
+    http::{HeaderMap, StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{delete, get, patch, post, put},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use snafu::{OptionExt, ResultExt, ensure};
+use std::sync::Arc;
+use tower_governor::{
+    GovernorLayer, governor::GovernorConfigBuilder, key_extractor::SmartIpKeyExtractor,
+};
+use validator::Validate;
+
+use crate::db::DeletedScope;
+use crate::dto::{
+    Actor, AnonymizeUserDto, AnonymizedUserDto, AppDto, AppEnvironmentDto, AppRedirectUriDto,
+    BatchGetDto, BulkOrgMembersDto, BulkResultDto, ErasureConfirmationDto, HasVersion,
+    ImpersonationLogDto, ImpersonationTokenDto, ImportConflictStrategy, JobDto,
+    LifecycleSubscriptionSecretDto, ListAppsParamsDto, ListEmailsParamsDto, ListJobsParamsDto,
+    ListOrgMembersParamsDto, ListOrgsParamsDto, ListUsersParamsDto, MemoryStatsDto, NewAppDto,
+    NewAppEnvironmentDto, NewLifecycleSubscriptionDto, NewOrgDto, NewOrgMemberDto,
+    NewOrgOwnerTransferDto, NewOrgRoleDto, NewTeamDto, NewTeamMemberDto, NewUserWithPasswordDto,
+    OrgAccessExportParamsDto, OrgAppDto, OrgDto, OrgImportReportDto, OrgInvitationDto,
+    OrgMemberDto, OrgMemberRolesDto, OrgOwnerTransferDto, OrgRateUsageDto, OrgRoleDto,
+    OrgSettingsDto, PasswordResetDto, StatsDto, TeamDto, TeamMemberDto, TokenRevocationDto,
+    UpdateAppDto, UpdateOrgAppDto, UpdateOrgDto, UpdateOrgRateLimitDto, UpdateOrgRoleDto,
+    UpdateOrgSettingsDto, UpdateTeamDto, UpdateUserDto, UpdatedDto, UserDto, UserExportDto,
+    UserExportParamsDto, UserImportResultDto, VersionConflictDto,
+};
+use crate::error::{
+    AppNotFoundSnafu, BadRequestSnafu, ForbiddenSnafu, JsonRejectionSnafu, NotFoundSnafu,
+    OrgNotFoundSnafu, UserNotFoundSnafu, ValidationSnafu, VersionRequiredSnafu,
+};
+use crate::services::app_environments::{
+    create_app_environment_svc, delete_app_environment_svc, list_app_environments_svc,
+    rotate_app_environment_secret_svc,
+};
+use crate::services::apps::{
+    add_app_redirect_uri_svc, create_app_svc, get_app_scoped_svc, get_app_svc,
+    list_apps_scoped_svc, remove_app_redirect_uri_svc, restore_app_svc, rotate_app_secret_svc,
+    update_app_tracked_svc,
+};
+use crate::services::auth::authenticate_token_svc;
+use crate::services::counters::stats_svc;
+use crate::services::impersonations::{list_user_impersonations_svc, start_impersonation_svc};
+use crate::services::jobs::{list_jobs_svc, retry_job_svc};
+use crate::services::lifecycle::{
+    list_lifecycle_deliveries_svc, list_lifecycle_subscriptions_svc, subscribe_lifecycle_svc,
+    unsubscribe_lifecycle_svc,
+};
+use crate::services::mailer::list_emails_svc;
+use crate::services::memory::{memory_stats_svc, render_memory_metrics};
+use crate::services::org_access::export_org_access_csv_svc;
+use crate::services::org_apps::update_org_app_svc;
+use crate::services::org_invitations::{list_org_invitations_svc, resend_org_invitation_svc};
+use crate::services::org_members::{
+    bulk_assign_org_members_svc, create_org_member_svc, list_org_members_cursor_svc,
+    list_org_members_svc,
+};
+use crate::services::org_owner_transfers::{
+    cancel_owner_transfer_svc, initiate_owner_transfer_svc,
+};
+use crate::services::org_rate_limits::{
+    org_rate_usage_svc, reset_org_rate_limit_svc, update_org_rate_limit_svc,
+};
+use crate::services::org_roles::{
+    assign_member_roles_svc, create_org_role_svc, delete_org_role_svc, get_org_role_svc,
+    list_org_roles_svc, update_org_role_svc,
+};
+use crate::services::org_settings::{get_org_settings_svc, update_org_settings_svc};
+use crate::services::org_transfer::{import_org_svc, parse_org_archive};
+use crate::services::orgs::{
+    batch_get_orgs_svc, create_org_svc, get_org_scoped_svc, get_org_svc, list_orgs_cursor_svc,
+    list_orgs_scoped_svc, restore_org_svc, update_org_tracked_svc,
+};
+use crate::services::password_reset::{latest_password_reset_svc, resend_password_reset_svc};
+use crate::services::revocations::force_logout_svc;
+use crate::services::teams::{
+    add_team_member_svc, create_team_svc, delete_team_svc, get_team_svc, list_team_members_svc,
+    list_teams_svc, remove_team_member_svc, update_team_svc,
+};
+use crate::services::token::verify_auth_token;
+use crate::services::user_erasure::{
+    anonymize_user_svc, ensure_can_erase, erasure_confirmation_svc,
+};
+use crate::services::user_exports::export_user_data_svc;
+use crate::services::user_import::{UserImportFormat, import_users_svc, parse_user_import};
+use crate::services::users::{
+    batch_get_users_svc, create_user_svc, get_user_scoped_svc, get_user_svc, list_users_cursor_svc,
+    list_users_scoped_svc, restore_user_svc, update_user_tracked_svc,
+};
+use crate::validators::flatten_errors;
+use crate::{Error, Result, ctx::Ctx, run::AppState};
+
+use super::oauth::{api_response_mapper, user_export_status};
+use super::{FieldsQuery, SecretResponse, idempotency_middleware, request_id};
+
+pub fn admin_api_routes(state: AppState) -> Router {
+    // Rate limiter: 120 requests per minute per IP, same as the web UI
+    let governor_config = Arc::new(
+        GovernorConfigBuilder::default()
+            .per_second(2)
+            .burst_size(120)
+            .key_extractor(SmartIpKeyExtractor)
+            .finish()
+            .expect("Failed to create admin API rate limiter config"),
+    );
+
+    let inner = Router::new()
+        .route("/users", get(list_users_handler).post(create_user_handler))
+        .route(
+            "/users/{user_id}",
+            get(get_user_handler).patch(update_user_handler),
+        )
+        .route("/users/import", post(import_users_handler))
+        .route("/users/batch-get", post(batch_get_users_handler))
+        .route("/users/{user_id}/restore", post(restore_user_handler))
+        .route("/users/{user_id}/force-logout", post(force_logout_handler))
+        .route(
+            "/users/{user_id}/impersonate",
+            post(impersonate_user_handler),
+        )
+        .route(
+            "/users/{user_id}/impersonations",
+            get(list_user_impersonations_handler),
+        )
+        .route("/users/{user_id}/export", get(export_user_handler))
+        .route(
+            "/users/{user_id}/anonymize/confirmation",
+            post(erasure_confirmation_handler),
+        )
+        .route("/users/{user_id}/anonymize", post(anonymize_user_handler))
+        .route(
+            "/users/{user_id}/password-reset",
+            get(latest_password_reset_handler),
+        )
+        .route(
+            "/users/{user_id}/password-reset/resend",
+            post(resend_password_reset_handler),
+        )
+        .route("/orgs", get(list_orgs_handler).post(create_org_handler))
+        .route(
+            "/orgs/{org_id}",
+            get(get_org_handler).patch(update_org_handler),
+        )
+        .route("/orgs/batch-get", post(batch_get_orgs_handler))
+        .route("/orgs/import", post(import_org_handler))
+        .route("/orgs/{org_id}/restore", post(restore_org_handler))
+        .route("/orgs/{org_id}/access-log", get(org_access_log_handler))
+        .route(
+            "/orgs/{org_id}/owner-transfer",
+            get(get_owner_transfer_handler)
+                .post(initiate_owner_transfer_handler)
+                .delete(cancel_owner_transfer_handler),
+        )
+        .route(
+            "/orgs/{org_id}/settings",
+            get(get_org_settings_handler).patch(update_org_settings_handler),
+        )
+        .route(
+            "/orgs/{org_id}/rate-limit",
+            get(get_org_rate_limit_handler)
+                .put(update_org_rate_limit_handler)
+                .delete(reset_org_rate_limit_handler),
+        )
+        .route(
+            "/orgs/{org_id}/members",
+            get(list_org_members_handler).post(create_org_member_handler),
+        )
+        .route(
+            "/orgs/{org_id}/members/bulk",
+            post(bulk_org_members_handler),
+        )
+        .route(
+            "/orgs/{org_id}/members/{user_id}/roles",
+            put(update_org_member_roles_handler),
+        )
+        .route(
+            "/orgs/{org_id}/apps/{app_id}",
+            patch(update_org_app_handler),
+        )
+        .route(
+            "/orgs/{org_id}/roles",
+            get(list_org_roles_handler).post(create_org_role_handler),
+        )
+        .route(
+            "/orgs/{org_id}/roles/{role_id}",
+            get(get_org_role_handler)
+                .patch(update_org_role_handler)
+                .delete(delete_org_role_handler),
+        )
+        .route(
+            "/orgs/{org_id}/teams",
+            get(list_teams_handler).post(create_team_handler),
+        )
+        .route(
+            "/orgs/{org_id}/teams/{team_id}",
+            get(get_team_handler)
+                .patch(update_team_handler)
+                .delete(delete_team_handler),
+        )
+        .route(
+            "/orgs/{org_id}/teams/{team_id}/members",
+            get(list_team_members_handler).post(add_team_member_handler),
+        )
+        .route(
+            "/orgs/{org_id}/teams/{team_id}/members/{user_id}",
+            delete(remove_team_member_handler),
+        )
+        .route("/apps", get(list_apps_handler).post(create_app_handler))
+        .route(
+            "/apps/{app_id}",
+            get(get_app_handler).patch(update_app_handler),
+        )
+        .route("/apps/{app_id}/restore", post(restore_app_handler))
+        .route(
+            "/apps/{app_id}/rotate-secret",
+            post(rotate_app_secret_handler),
+        )
+        .route(
+            "/apps/{app_id}/redirect-uris",
+            post(add_app_redirect_uri_handler).delete(remove_app_redirect_uri_handler),
+        )
+        .route(
+            "/apps/{app_id}/environments",
+            get(list_app_environments_handler).post(create_app_environment_handler),
+        )
+        .route(
+            "/apps/{app_id}/environments/{environment_id}",
+            delete(delete_app_environment_handler),
+        )
+        .route(
+            "/apps/{app_id}/environments/{environment_id}/rotate-secret",
+            post(rotate_app_environment_secret_handler),
+        )
+        .route(
+            "/apps/{app_id}/lifecycle",
+            get(list_lifecycle_subscriptions_handler).post(subscribe_lifecycle_handler),
+        )
+        .route(
+            "/apps/{app_id}/lifecycle/deliveries",
+            get(list_lifecycle_deliveries_handler),
+        )
+        .route(
+            "/apps/{app_id}/lifecycle/{subscription_id}",
+            delete(unsubscribe_lifecycle_handler),
+        )
+        .route(
+            "/orgs/{org_id}/invitations",
+            get(list_org_invitations_handler),
+        )
+        .route(
+            "/orgs/{org_id}/invitations/{invitation_id}/resend",
+            post(resend_org_invitation_handler),
+        )
+        .route("/emails", get(list_emails_handler))
+        .route("/jobs", get(list_jobs_handler))
+        .route("/jobs/{job_id}/retry", post(retry_job_handler))
+        .route("/memory", get(memory_stats_handler))
+        .route("/stats", get(stats_handler))
+        .route("/metrics", get(metrics_handler))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            idempotency_middleware,
+        ))
+        .layer(GovernorLayer::new(governor_config))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            admin_api_auth_middleware,
+        ))
+        .layer(middleware::map_response_with_state(
+            state.clone(),
+            api_response_mapper,
+        ))
+        .with_state(state);
+
+    Router::new().nest("/admin/api", inner)
+}
+
+/// PATCH body, `version` is the `updated_at` of the record the client last read
+#[derive(Deserialize)]
+struct VersionedPayload<T> {
+    version: Option<i64>,
+    #[serde(flatten)]
+    data: T,
+}
+
+/// Version the client expects the record to be at.
+///
+/// Read from `If-Match`, which takes the `ETag` of an earlier response, or
+/// from the `version` field. `If-Match: *` updates whatever version is stored.
+fn expected_version(headers: &HeaderMap, body_version: Option<i64>) -> Result<Option<i64>> {
+    let Some(value) = headers.get(header::IF_MATCH) else {
+        return body_version.context(VersionRequiredSnafu).map(Some);
+    };
+
+    let value = value.to_str().unwrap_or_default().trim();
+    if value == "*" {
+        return Ok(None);
+    }
+
+    let version = value
+        .trim_start_matches("W/")
+        .trim_matches('"')
+        .parse::<i64>()
+        .ok()
+        .context(BadRequestSnafu {
+            msg: "If-Match must be the ETag of the record.",
+        })?;
+
+    Ok(Some(version))
+}
+
+fn version_etag<T: HasVersion>(record: &T) -> [(header::HeaderName, String); 1] {
+    [(header::ETAG, format!("\"{}\"", record.version()))]
+}
+
+/// Response of a versioned PATCH, a conflict comes with the record as it is now
+async fn versioned_update<T, F>(
+    headers: &HeaderMap,
+    result: Result<UpdatedDto<T>>,
+    current: F,
+) -> Result<Response>
+where
+    T: HasVersion + Serialize,
+    F: Future<Output = Result<Option<T>>>,
+{
+    match result {
+        Ok(updated) => Ok((version_etag(&updated.data), Json(updated)).into_response()),
+        Err(Error::VersionMismatch) => {
+            // Deleted since, nothing left to merge with
+            let Some(current) = current.await? else {
+                return Err(Error::VersionMismatch);
+            };
+
+            let status_code = StatusCode::CONFLICT;
+            let body = VersionConflictDto {
+                status_code: status_code.as_u16(),
+                message: Error::VersionMismatch.to_string(),
+                error: status_code.canonical_reason().unwrap().to_string(),
+                error_code: Some("version_mismatch".to_string()),
+                request_id: request_id(headers),
+                current,
+            };
+            Ok((status_code, version_etag(&body.current), Json(body)).into_response())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn admin_api_auth_middleware(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    mut req: Request,
+    next: Next,
+) -> Result<Response> {
+    let ctx = Ctx::new(admin_actor(&state, &headers).await?);
+    let audit = ctx.audit();
+    req.extensions_mut().insert(ctx);
+    Ok(audit.scope(next.run(req)).await)
+}
+
+/// Only superuser session tokens are accepted, tokens issued to OAuth apps are not
+pub(crate) async fn admin_actor(state: &AppState, headers: &HeaderMap) -> Result<Actor> {
+    let token = headers
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|value| value.trim().to_string())
+        .ok_or(Error::LoginRequired)?;
+
+    let payload = verify_auth_token(&token, &state.token_keys)?;
+    ensure!(
+        payload.grant_id.is_none(),
+        ForbiddenSnafu {
+            msg: "OAuth app tokens cannot use the admin API."
+        }
+    );
+
+    let actor = authenticate_token_svc(state, &token).await?;
+    ensure!(
+        actor.is_system_admin(),
+        ForbiddenSnafu {
+            msg: "The admin API requires a superuser token."
+        }
+    );
+
+    Ok(actor)
+}
+
+/// Soft deleted records are hidden unless `?include_deleted=true` is passed
+#[derive(Deserialize)]
+struct DeletedQuery {
+    include_deleted: Option<bool>,
+}
+
+/// Same options as the `org-import` command, a dry run only returns the plan
+#[derive(Deserialize)]
+struct OrgImportQuery {
+    strategy: Option<String>,
+    dry_run: Option<bool>,
+}
+
+impl DeletedQuery {
+    fn scope(&self) -> DeletedScope {
+        DeletedScope::include_deleted(self.include_deleted.unwrap_or(false))
+    }
+}
+
+fn validate_query<T: Validate>(query: &T) -> Result<()> {
+    let errors = query.validate();
+    ensure!(
+        errors.is_ok(),
+        ValidationSnafu {
+            msg: flatten_errors(&errors.unwrap_err()),
+        }
+    );
+    Ok(())
+}
+
+async fn list_users_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ListUsersParamsDto>,
+    Query(deleted): Query<DeletedQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>> {
+    validate_query(&query)?;
+    if query.uses_cursor() {
+        return fields.cursor_page(list_users_cursor_svc(&state, query, deleted.scope()).await?);
+    }
+    fields.page(list_users_scoped_svc(&state, query, deleted.scope()).await?)
+}
+
+async fn create_user_handler(
+    State(state): State<AppState>,
+    payload: core::result::Result<Json<NewUserWithPasswordDto>, JsonRejection>,
+) -> Result<(StatusCode, Json<UserDto>)> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    let user = create_user_svc(&state, data).await?;
+    Ok((StatusCode::CREATED, Json(user)))
+}
+
+/// Creates users from a CSV or NDJSON body, nothing is created when any row fails
+async fn import_users_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<(StatusCode, Json<UserImportResultDto>)> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let format = UserImportFormat::from_content_type(content_type)?;
+    let rows = parse_user_import(format, &body)?;
+
+    let result = import_users_svc(&state, rows).await?;
+    let status = if result.imported {
+        StatusCode::CREATED
+    } else {
+        StatusCode::UNPROCESSABLE_ENTITY
+    };
+    Ok((status, Json(result)))
+}
+
+/// Up to 100 users by id in one call, ids not found are listed in `missing_ids`
+async fn batch_get_users_handler(
+    State(state): State<AppState>,
+    Query(fields): Query<FieldsQuery>,
+    payload: core::result::Result<Json<BatchGetDto>, JsonRejection>,
+) -> Result<Json<Value>> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    fields.batch(batch_get_users_svc(&state, data).await?)
+}
+
+async fn get_user_handler(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+    Query(deleted): Query<DeletedQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<impl IntoResponse> {
+    let user = get_user_scoped_svc(&state, &user_id, deleted.scope())
+        .await?
+        .context(UserNotFoundSnafu)?;
+    Ok((version_etag(&user), fields.item(user)?))
+}
+
+async fn restore_user_handler(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+) -> Result<Json<UserDto>> {
+    Ok(Json(restore_user_svc(&state, &user_id).await?))
+}
+
+async fn update_user_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+    headers: HeaderMap,
+    payload: core::result::Result<Json<VersionedPayload<UpdateUserDto>>, JsonRejection>,
+) -> Result<Response> {
+    let Json(payload) = payload.context(JsonRejectionSnafu)?;
+    let version = expected_version(&headers, payload.version)?;
+    let data = payload.data;
+    let actor = ctx.actor().expect("actor is required");
+    ensure!(
+        actor.user.id != user_id || data.status.is_none(),
+        ValidationSnafu {
+            msg: "Cannot change your own status."
+        }
+    );
+
+    let result = update_user_tracked_svc(&state, &user_id, data, version).await;
+    versioned_update(&headers, result, get_user_svc(&state, &user_id)).await
+}
+
+async fn force_logout_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+) -> Result<Json<TokenRevocationDto>> {
+    let actor_id = ctx.actor().map(|actor| actor.id.clone());
+    let revocation = force_logout_svc(&state, actor_id.as_deref(), &user_id).await?;
+    Ok(Json(revocation))
+}
+
+async fn impersonate_user_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+) -> Result<(
+    StatusCode,
+    Extension<SecretResponse>,
+    Json<ImpersonationTokenDto>,
+)> {
+    let impersonator_id = ctx
+        .actor()
+        .map(|actor| actor.id.clone())
+        .unwrap_or_default();
+    let impersonation = start_impersonation_svc(&state, &impersonator_id, &user_id).await?;
+    Ok((
+        StatusCode::CREATED,
+        Extension(SecretResponse),
+        Json(impersonation),
+    ))
+}
+
+async fn list_user_impersonations_handler(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+) -> Result<Json<Vec<ImpersonationLogDto>>> {
+    Ok(Json(list_user_impersonations_svc(&state, &user_id).await?))
+}
+
+async fn export_user_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+    Query(query): Query<UserExportParamsDto>,
+) -> Result<(StatusCode, Json<UserExportDto>)> {
+    let actor_id = ctx.actor().map(|actor| actor.id.clone());
+    let export = export_user_data_svc(&state, actor_id.as_deref(), &user_id, query).await?;
+    Ok((user_export_status(&export), Json(export)))
+}
+
+async fn erasure_confirmation_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+) -> Result<Json<ErasureConfirmationDto>> {
+    ensure_can_erase(&ctx)?;
+    let actor = ctx.actor().expect("actor is required");
+    Ok(Json(
+        erasure_confirmation_svc(&state, &actor.id, &user_id).await?,
+    ))
+}
+
+async fn anonymize_user_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+    payload: core::result::Result<Json<AnonymizeUserDto>, JsonRejection>,
+) -> Result<Json<AnonymizedUserDto>> {
+    ensure_can_erase(&ctx)?;
+    let Json(payload) = payload.context(JsonRejectionSnafu)?;
+    let actor = ctx.actor().expect("actor is required");
+    let erased =
+        anonymize_user_svc(&state, &actor.id, &user_id, &payload.confirmation_token).await?;
+    Ok(Json(erased))
+}
+
+async fn list_orgs_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ListOrgsParamsDto>,
+    Query(deleted): Query<DeletedQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>> {
+    validate_query(&query)?;
+    if query.uses_cursor() {
+        return fields.cursor_page(list_orgs_cursor_svc(&state, query, deleted.scope()).await?);
+    }
+    fields.page(list_orgs_scoped_svc(&state, query, deleted.scope()).await?)
+}
+
+async fn create_org_handler(
+    State(state): State<AppState>,
+    payload: core::result::Result<Json<NewOrgDto>, JsonRejection>,
+) -> Result<(StatusCode, Json<OrgDto>)> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    let org = create_org_svc(&state, data).await?;
+    Ok((StatusCode::CREATED, Json(org)))
+}
+
+/// Up to 100 orgs by id in one call, ids not found are listed in `missing_ids`
+async fn batch_get_orgs_handler(
+    State(state): State<AppState>,
+    Query(fields): Query<FieldsQuery>,
+    payload: core::result::Result<Json<BatchGetDto>, JsonRejection>,
+) -> Result<Json<Value>> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    fields.batch(batch_get_orgs_svc(&state, data).await?)
+}
+
+/// Imports an archive made by the org export page or the `org-export` command
+async fn import_org_handler(
+    State(state): State<AppState>,
+    Query(query): Query<OrgImportQuery>,
+    body: String,
+) -> Result<(StatusCode, Json<OrgImportReportDto>)> {
+    let strategy = match query.strategy.as_deref() {
+        Some(strategy) => ImportConflictStrategy::try_from(strategy)?,
+        None => ImportConflictStrategy::Skip,
+    };
+    let archive = parse_org_archive(&body)?;
+
+    let report = import_org_svc(
+        &state.db,
+        &archive,
+        strategy,
+        query.dry_run.unwrap_or(false),
+    )
+    .await?;
+    let status = if report.result.is_some() {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    Ok((status, Json(report)))
+}
+
+async fn get_org_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+    Query(deleted): Query<DeletedQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<impl IntoResponse> {
+    let org = get_org_scoped_svc(&state, &org_id, deleted.scope())
+        .await?
+        .context(OrgNotFoundSnafu)?;
+    Ok((version_etag(&org), fields.item(org)?))
+}
+
+async fn restore_org_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+) -> Result<Json<OrgDto>> {
+    Ok(Json(restore_org_svc(&state, &org_id).await?))
+}
+
+async fn update_org_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+    headers: HeaderMap,
+    payload: core::result::Result<Json<VersionedPayload<UpdateOrgDto>>, JsonRejection>,
+) -> Result<Response> {
+    let Json(payload) = payload.context(JsonRejectionSnafu)?;
+    let version = expected_version(&headers, payload.version)?;
+    let data = payload.data;
+    let result = update_org_tracked_svc(&state, &org_id, data, version).await;
+    versioned_update(&headers, result, get_org_svc(&state, &org_id)).await
+}
+
+/// Transfer of the org waiting for the new owner to accept
+async fn get_owner_transfer_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+) -> Result<Json<OrgOwnerTransferDto>> {
+    let org = get_org_svc(&state, &org_id)
+        .await?
+        .context(OrgNotFoundSnafu)?;
+    let transfer = org.pending_transfer.context(NotFoundSnafu {
+        msg: "Org has no pending ownership transfer".to_string(),
+    })?;
+    Ok(Json(transfer))
+}
+
+/// Emails the member a link to accept the org, the owner stays until they do
+async fn initiate_owner_transfer_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+    payload: core::result::Result<Json<NewOrgOwnerTransferDto>, JsonRejection>,
+) -> Result<(StatusCode, Json<OrgOwnerTransferDto>)> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    let actor = ctx.actor().context(ForbiddenSnafu {
+        msg: "Only users can transfer org ownership".to_string(),
+    })?;
+    let issued = initiate_owner_transfer_svc(&state, &actor.user.id, &org_id, data).await?;
+    Ok((StatusCode::CREATED, Json(issued.transfer)))
+}
+
+async fn cancel_owner_transfer_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+) -> Result<StatusCode> {
+    let actor = ctx.actor().context(ForbiddenSnafu {
+        msg: "Only users can transfer org ownership".to_string(),
+    })?;
+    cancel_owner_transfer_svc(&state, &actor.user.id, &org_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Distinct users that accessed the org per day, as CSV
+async fn org_access_log_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+    Query(query): Query<OrgAccessExportParamsDto>,
+) -> Result<impl IntoResponse> {
+    let body = export_org_access_csv_svc(&state, &org_id, query.clone()).await?;
+    let disposition = format!(
+        "attachment; filename=\"org-access-{}-{}.csv\"",
+        query.from.trim(),
+        query.to.trim()
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    ))
+}
+
+async fn get_org_settings_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+) -> Result<Json<OrgSettingsDto>> {
+    get_org_svc(&state, &org_id)
+        .await?
+        .context(OrgNotFoundSnafu)?;
+    Ok(Json(get_org_settings_svc(&state, &org_id).await?))
+}
+
+async fn update_org_settings_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+    payload: core::result::Result<Json<UpdateOrgSettingsDto>, JsonRejection>,
+) -> Result<Json<OrgSettingsDto>> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    Ok(Json(update_org_settings_svc(&state, &org_id, data).await?))
+}
+
+async fn get_org_rate_limit_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+) -> Result<Json<OrgRateUsageDto>> {
+    get_org_svc(&state, &org_id)
+        .await?
+        .context(OrgNotFoundSnafu)?;
+    Ok(Json(org_rate_usage_svc(&state, &org_id).await?))
+}
+
+async fn update_org_rate_limit_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+    payload: core::result::Result<Json<UpdateOrgRateLimitDto>, JsonRejection>,
+) -> Result<Json<OrgRateUsageDto>> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    let actor_id = ctx.actor().map(|actor| actor.id.clone());
+    let usage = update_org_rate_limit_svc(&state, actor_id.as_deref(), &org_id, data).await?;
+    Ok(Json(usage))
+}
+
+async fn reset_org_rate_limit_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+) -> Result<Json<OrgRateUsageDto>> {
+    Ok(Json(reset_org_rate_limit_svc(&state, &org_id).await?))
+}
+
+async fn list_org_members_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+    Query(query): Query<ListOrgMembersParamsDto>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>> {
+    validate_query(&query)?;
+    get_org_svc(&state, &org_id)
+        .await?
+        .context(OrgNotFoundSnafu)?;
+    if query.uses_cursor() {
+        return fields.cursor_page(list_org_members_cursor_svc(&state, &org_id, query).await?);
+    }
+    fields.page(list_org_members_svc(&state, &org_id, query).await?)
+}
+
+async fn create_org_member_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+    payload: core::result::Result<Json<NewOrgMemberDto>, JsonRejection>,
+) -> Result<(StatusCode, Json<OrgMemberDto>)> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    get_org_svc(&state, &org_id)
+        .await?
+        .context(OrgNotFoundSnafu)?;
+    let member = create_org_member_svc(&state, &org_id, data).await?;
+    Ok((StatusCode::CREATED, Json(member)))
+}
+
+async fn bulk_org_members_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+    payload: core::result::Result<Json<BulkOrgMembersDto>, JsonRejection>,
+) -> Result<Json<BulkResultDto>> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    Ok(Json(
+        bulk_assign_org_members_svc(&state, &org_id, data.members).await?,
+    ))
+}
+
+async fn update_org_member_roles_handler(
+    State(state): State<AppState>,
+    Path((org_id, user_id)): Path<(String, String)>,
+    payload: core::result::Result<Json<OrgMemberRolesDto>, JsonRejection>,
+) -> Result<Json<Vec<OrgRoleDto>>> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    let member = state
+        .db
+        .org_members
+        .find_member(org_id, user_id)
+        .await?
+        .context(NotFoundSnafu {
+            msg: "Org member not found".to_string(),
+        })?;
+    Ok(Json(
+        assign_member_roles_svc(&state, &member, data.role_ids).await?,
+    ))
+}
+
+async fn list_org_roles_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>> {
+    get_org_svc(&state, &org_id)
+        .await?
+        .context(OrgNotFoundSnafu)?;
+    fields.list(list_org_roles_svc(&state, &org_id).await?)
+}
+
+async fn create_org_role_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+    payload: core::result::Result<Json<NewOrgRoleDto>, JsonRejection>,
+) -> Result<(StatusCode, Json<OrgRoleDto>)> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    let role = create_org_role_svc(&state, &org_id, data).await?;
+    Ok((StatusCode::CREATED, Json(role)))
+}
+
+async fn get_org_role_handler(
+    State(state): State<AppState>,
+    Path((org_id, role_id)): Path<(String, String)>,
+) -> Result<Json<OrgRoleDto>> {
+    Ok(Json(get_org_role_svc(&state, &org_id, &role_id).await?))
+}
+
+async fn update_org_app_handler(
+    State(state): State<AppState>,
+    Path((org_id, app_id)): Path<(String, String)>,
+    payload: core::result::Result<Json<UpdateOrgAppDto>, JsonRejection>,
+) -> Result<Json<OrgAppDto>> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    Ok(Json(
+        update_org_app_svc(&state, &org_id, &app_id, data).await?,
+    ))
+}
+
+async fn update_org_role_handler(
+    State(state): State<AppState>,
+    Path((org_id, role_id)): Path<(String, String)>,
+    payload: core::result::Result<Json<UpdateOrgRoleDto>, JsonRejection>,
+) -> Result<Json<OrgRoleDto>> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    Ok(Json(
+        update_org_role_svc(&state, &org_id, &role_id, data).await?,
+    ))
+}
+
+async fn delete_org_role_handler(
+    State(state): State<AppState>,
+    Path((org_id, role_id)): Path<(String, String)>,
+) -> Result<StatusCode> {
+    delete_org_role_svc(&state, &org_id, &role_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_teams_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>> {
+    get_org_svc(&state, &org_id)
+        .await?
+        .context(OrgNotFoundSnafu)?;
+    fields.list(list_teams_svc(&state, &org_id).await?)
+}
+
+async fn create_team_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+    payload: core::result::Result<Json<NewTeamDto>, JsonRejection>,
+) -> Result<(StatusCode, Json<TeamDto>)> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    let team = create_team_svc(&state, &org_id, data).await?;
+    Ok((StatusCode::CREATED, Json(team)))
+}
+
+async fn get_team_handler(
+    State(state): State<AppState>,
+    Path((org_id, team_id)): Path<(String, String)>,
+) -> Result<Json<TeamDto>> {
+    Ok(Json(get_team_svc(&state, &org_id, &team_id).await?))
+}
+
+async fn update_team_handler(
+    State(state): State<AppState>,
+    Path((org_id, team_id)): Path<(String, String)>,
+    payload: core::result::Result<Json<UpdateTeamDto>, JsonRejection>,
+) -> Result<Json<TeamDto>> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    Ok(Json(
+        update_team_svc(&state, &org_id, &team_id, data).await?,
+    ))
+}
+
+async fn delete_team_handler(
+    State(state): State<AppState>,
+    Path((org_id, team_id)): Path<(String, String)>,
+) -> Result<StatusCode> {
+    delete_team_svc(&state, &org_id, &team_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_team_members_handler(
+    State(state): State<AppState>,
+    Path((org_id, team_id)): Path<(String, String)>,
+) -> Result<Json<Vec<TeamMemberDto>>> {
+    Ok(Json(
+        list_team_members_svc(&state, &org_id, &team_id).await?,
+    ))
+}
+
+async fn add_team_member_handler(
+    State(state): State<AppState>,
+    Path((org_id, team_id)): Path<(String, String)>,
+    payload: core::result::Result<Json<NewTeamMemberDto>, JsonRejection>,
+) -> Result<Json<Vec<TeamMemberDto>>> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    Ok(Json(
+        add_team_member_svc(&state, &org_id, &team_id, &data.user_id).await?,
+    ))
+}
+
+async fn remove_team_member_handler(
+    State(state): State<AppState>,
+    Path((org_id, team_id, user_id)): Path<(String, String, String)>,
+) -> Result<StatusCode> {
+    remove_team_member_svc(&state, &org_id, &team_id, &user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_apps_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ListAppsParamsDto>,
+    Query(deleted): Query<DeletedQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>> {
+    validate_query(&query)?;
+    fields.page(list_apps_scoped_svc(&state, query, deleted.scope()).await?)
+}
+
+async fn create_app_handler(
+    State(state): State<AppState>,
+    payload: core::result::Result<Json<NewAppDto>, JsonRejection>,
+) -> Result<(StatusCode, Extension<SecretResponse>, Json<AppDto>)> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    let app = create_app_svc(&state, data).await?;
+    Ok((StatusCode::CREATED, Extension(SecretResponse), Json(app)))
+}
+
+async fn get_app_handler(
+    State(state): State<AppState>,
+    Path(app_id): Path<String>,
+    Query(deleted): Query<DeletedQuery>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<impl IntoResponse> {
+    let app = get_app_scoped_svc(&state, &app_id, deleted.scope())
+        .await?
+        .context(AppNotFoundSnafu)?;
+    Ok((version_etag(&app), fields.item(app)?))
+}
+
+async fn restore_app_handler(
+    State(state): State<AppState>,
+    Path(app_id): Path<String>,
+) -> Result<Json<AppDto>> {
+    Ok(Json(restore_app_svc(&state, &app_id).await?))
+}
+
+async fn rotate_app_secret_handler(
+    State(state): State<AppState>,
+    Path(app_id): Path<String>,
+) -> Result<(Extension<SecretResponse>, Json<AppDto>)> {
+    let app = rotate_app_secret_svc(&state, &app_id).await?;
+    Ok((Extension(SecretResponse), Json(app)))
+}
+
+async fn add_app_redirect_uri_handler(
+    State(state): State<AppState>,
+    Path(app_id): Path<String>,
+    payload: core::result::Result<Json<AppRedirectUriDto>, JsonRejection>,
+) -> Result<Json<AppDto>> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    Ok(Json(add_app_redirect_uri_svc(&state, &app_id, data).await?))
+}
+
+async fn remove_app_redirect_uri_handler(
+    State(state): State<AppState>,
+    Path(app_id): Path<String>,
+    Query(query): Query<AppRedirectUriDto>,
+) -> Result<Json<AppDto>> {
+    Ok(Json(
+        remove_app_redirect_uri_svc(&state, &app_id, &query.redirect_uri).await?,
+    ))
+}
+
+async fn update_app_handler(
+    State(state): State<AppState>,
+    Path(app_id): Path<String>,
+    headers: HeaderMap,
+    payload: core::result::Result<Json<VersionedPayload<UpdateAppDto>>, JsonRejection>,
+) -> Result<Response> {
+    let Json(payload) = payload.context(JsonRejectionSnafu)?;
+    let version = expected_version(&headers, payload.version)?;
+    let data = payload.data;
+    let result = update_app_tracked_svc(&state, &app_id, data, version).await;
+    versioned_update(&headers, result, get_app_svc(&state, &app_id)).await
+}
+
+async fn list_app_environments_handler(
+    State(state): State<AppState>,
+    Path(app_id): Path<String>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>> {
+    let _ = get_app_scoped_svc(&state, &app_id, DeletedScope::Active)
+        .await?
+        .context(AppNotFoundSnafu)?;
+    fields.list(list_app_environments_svc(&state, &app_id).await?)
+}
+
+async fn create_app_environment_handler(
+    State(state): State<AppState>,
+    Path(app_id): Path<String>,
+    payload: core::result::Result<Json<NewAppEnvironmentDto>, JsonRejection>,
+) -> Result<(
+    StatusCode,
+    Extension<SecretResponse>,
+    Json<AppEnvironmentDto>,
+)> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    let environment = create_app_environment_svc(&state, &app_id, data).await?;
+    Ok((
+        StatusCode::CREATED,
+        Extension(SecretResponse),
+        Json(environment),
+    ))
+}
+
+async fn rotate_app_environment_secret_handler(
+    State(state): State<AppState>,
+    Path((app_id, environment_id)): Path<(String, String)>,
+) -> Result<(Extension<SecretResponse>, Json<AppEnvironmentDto>)> {
+    let environment = rotate_app_environment_secret_svc(&state, &app_id, &environment_id).await?;
+    Ok((Extension(SecretResponse), Json(environment)))
+}
+
+async fn delete_app_environment_handler(
+    State(state): State<AppState>,
+    Path((app_id, environment_id)): Path<(String, String)>,
+) -> Result<StatusCode> {
+    delete_app_environment_svc(&state, &app_id, &environment_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_lifecycle_subscriptions_handler(
+    State(state): State<AppState>,
+    Path(app_id): Path<String>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>> {
+    let _ = get_app_scoped_svc(&state, &app_id, DeletedScope::Active)
+        .await?
+        .context(AppNotFoundSnafu)?;
+    fields.list(list_lifecycle_subscriptions_svc(&state, &app_id).await?)
+}
+
+/// Also moves an existing subscription of the topic to the new url
+async fn subscribe_lifecycle_handler(
+    State(state): State<AppState>,
+    Path(app_id): Path<String>,
+    payload: core::result::Result<Json<NewLifecycleSubscriptionDto>, JsonRejection>,
+) -> Result<(
+    StatusCode,
+    Extension<SecretResponse>,
+    Json<LifecycleSubscriptionSecretDto>,
+)> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    let _ = get_app_scoped_svc(&state, &app_id, DeletedScope::Active)
+        .await?
+        .context(AppNotFoundSnafu)?;
+    let subscription = subscribe_lifecycle_svc(&state, &app_id, data).await?;
+    Ok((
+        StatusCode::CREATED,
+        Extension(SecretResponse),
+        Json(subscription.into()),
+    ))
+}
+
+async fn list_lifecycle_deliveries_handler(
+    State(state): State<AppState>,
+    Path(app_id): Path<String>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>> {
+    let _ = get_app_scoped_svc(&state, &app_id, DeletedScope::Active)
+        .await?
+        .context(AppNotFoundSnafu)?;
+    fields.list(list_lifecycle_deliveries_svc(&state, &app_id).await?)
+}
+
+async fn unsubscribe_lifecycle_handler(
+    State(state): State<AppState>,
+    Path((app_id, subscription_id)): Path<(String, String)>,
+) -> Result<StatusCode> {
+    unsubscribe_lifecycle_svc(&state, &app_id, &subscription_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Invitations of the org with the delivery status of their latest email
+async fn list_org_invitations_handler(
+    State(state): State<AppState>,
+    Path(org_id): Path<String>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>> {
+    get_org_svc(&state, &org_id)
+        .await?
+        .context(OrgNotFoundSnafu)?;
+    fields.list(list_org_invitations_svc(&state, &org_id).await?)
+}
+
+/// Emails a new link to the invitee, the previous link stops working
+async fn resend_org_invitation_handler(
+    State(state): State<AppState>,
+    Path((org_id, invitation_id)): Path<(String, String)>,
+) -> Result<Json<OrgInvitationDto>> {
+    let issued = resend_org_invitation_svc(&state, &org_id, &invitation_id).await?;
+    Ok(Json(issued.invitation))
+}
+
+async fn latest_password_reset_handler(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+) -> Result<Json<PasswordResetDto>> {
+    let reset = latest_password_reset_svc(&state, &user_id).await?;
+    Ok(Json(reset))
+}
+
+async fn resend_password_reset_handler(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+) -> Result<Json<PasswordResetDto>> {
+    let reset = resend_password_reset_svc(&state, &user_id).await?;
+    Ok(Json(reset))
+}
+
+async fn list_emails_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ListEmailsParamsDto>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>> {
+    fields.list(list_emails_svc(&state, query).await?)
+}
+
+async fn list_jobs_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ListJobsParamsDto>,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<Json<Value>> {
+    fields.list(list_jobs_svc(&state, query).await?)
+}
+
+async fn retry_job_handler(
+    State(state): State<AppState>,
+    Path(job_id): Path<String>,
+) -> Result<Json<JobDto>> {
+    Ok(Json(retry_job_svc(&state, &job_id).await?))
+}
+
+async fn memory_stats_handler() -> Json<MemoryStatsDto> {
+    Json(memory_stats_svc())
+}
+
+/// Deployment totals as of the last counter reconcile
+async fn stats_handler(State(state): State<AppState>) -> Result<Json<StatsDto>> {
+    let stats = stats_svc(&state).await?;
+    Ok(Json(stats))
+}
+
+/// Prometheus scrape target, same bearer token as the rest of the admin API
+async fn metrics_handler() -> impl IntoResponse {
+    let body = render_memory_metrics(&memory_stats_svc());
+    (
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        body,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use serde_json::{Value, json};
+
+    use crate::dto::{CredentialsDto, SessionClientDto};
+    use crate::services::apps::delete_app_svc;
+    use crate::services::auth::{authenticate, issue_superuser_token_svc};
+    use crate::services::org_access::record_org_access;
+    use crate::services::org_transfer::export_org_svc;
+    use crate::test::TestCtx;
+    use crate::web::oauth_api_routes;
+
+    use super::admin_api_routes;
+
+    /// Serves the admin API on a local port, returns its base URL
+    async fn spawn_admin_api(ctx: &TestCtx) -> String {
+        let app = admin_api_routes(ctx.state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener should bind");
+        let addr = listener.local_addr().expect("local addr");
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        format!("http://{}/admin/api", addr)
+    }
+
+    #[tokio::test]
+    async fn admin_api_requires_superuser_token() {
+        let ctx = TestCtx::new("admin_api_guard").await.expect("test ctx");
+        ctx.seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Regular User",
+                "regular@example.com",
+                "password123",
+                "Regular Org",
+            )
+            .await
+            .expect("auth fixture");
+        let base_url = spawn_admin_api(&ctx).await;
+        let client = reqwest::Client::new();
+
+        let anonymous = client
+            .get(format!("{}/users", base_url))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+
+        let auth = authenticate(
+            &ctx.state,
+            &CredentialsDto {
+                email: fixture.email.clone(),
+                password: fixture.password.clone(),
+            },
+            SessionClientDto::default(),
+        )
+        .await
+        .expect("login");
+        let regular = client
+            .get(format!("{}/users", base_url))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(auth.token)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(regular.status(), StatusCode::FORBIDDEN);
+        let body: Value = regular.json().await.expect("json error body");
+        assert_eq!(body["status_code"], 403);
+    }
+
+    #[tokio::test]
+    async fn admin_api_reads_and_creates_with_superuser_token() {
+        let ctx = TestCtx::new("admin_api_crud").await.expect("test ctx");
+        ctx.seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+        let owner = ctx
+            .seed_user_with_password("Owner User", "owner@example.com", "password123")
+            .await
+            .expect("owner");
+        let token =
+            issue_superuser_token_svc(&ctx.state.db, &ctx.state.token_keys, "root@example.com")
+                .await
+                .expect("admin token");
+        let base_url = spawn_admin_api(&ctx).await;
+        let client = reqwest::Client::new();
+
+        let users = client
+            .get(format!("{}/users?keyword=owner", base_url))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(users.status(), StatusCode::OK);
+        let users: Value = users.json().await.expect("json");
+        assert_eq!(users["data"][0]["email"], "owner@example.com");
+
+        let created = client
+            .post(format!("{}/orgs", base_url))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .json(&json!({ "name": "Scripted Org", "owner_id": owner.id }))
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(created.status(), StatusCode::CREATED);
+        let org: Value = created.json().await.expect("json");
+        assert_eq!(org["name"], "Scripted Org");
+
+        let org_url = format!("{}/orgs/{}", base_url, org["id"].as_str().unwrap());
+        let mut etag = format!("\"{}\"", org["updated_at"]);
+        for (name, changed) in [("Renamed Org", json!(["name"])), ("Renamed Org", json!([]))] {
+            let updated = client
+                .patch(&org_url)
+                .header("X-Forwarded-For", "127.0.0.1")
+                .header("If-Match", &etag)
+                .bearer_auth(&token)
+                .json(&json!({ "name": name }))
+                .send()
+                .await
+                .expect("request");
+            assert_eq!(updated.status(), StatusCode::OK);
+            etag = updated.headers()["etag"].to_str().unwrap().to_string();
+            let updated: Value = updated.json().await.expect("json");
+            assert_eq!(updated["name"], "Renamed Org");
+            assert_eq!(updated["changed_fields"], changed);
+            assert_eq!(etag, format!("\"{}\"", updated["updated_at"]));
+        }
+
+        let unversioned = client
+            .patch(&org_url)
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .json(&json!({ "name": "Unversioned Org" }))
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(unversioned.status(), StatusCode::PRECONDITION_REQUIRED);
+
+        // Edits made against the version read at creation lose to the renames
+        let stale = client
+            .patch(&org_url)
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .json(&json!({ "name": "Stale Org", "version": org["updated_at"] }))
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(stale.status(), StatusCode::CONFLICT);
+        let stale: Value = stale.json().await.expect("json");
+        assert_eq!(stale["error_code"], "version_mismatch");
+        assert_eq!(stale["current"]["name"], "Renamed Org");
+
+        let missing = client
+            .get(format!("{}/apps/app_missing", base_url))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn admin_api_returns_only_selected_fields() {
+        let ctx = TestCtx::new("admin_api_fields").await.expect("test ctx");
+        ctx.seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+        let owner = ctx
+            .seed_user_with_password("Owner User", "owner@example.com", "password123")
+            .await
+            .expect("owner");
+        let token =
+            issue_superuser_token_svc(&ctx.state.db, &ctx.state.token_keys, "root@example.com")
+                .await
+                .expect("admin token");
+        let base_url = spawn_admin_api(&ctx).await;
+        let client = reqwest::Client::new();
+
+        let users = client
+            .get(format!("{}/users?keyword=owner&fields=id,email", base_url))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(users.status(), StatusCode::OK);
+        let users: Value = users.json().await.expect("json");
+        assert_eq!(users["meta"]["total_records"], 1);
+        assert_eq!(
+            users["data"][0],
+            json!({ "id": owner.id, "email": "owner@example.com" })
+        );
+
+        let user = client
+            .get(format!("{}/users/{}?fields=name", base_url, owner.id))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(user.status(), StatusCode::OK);
+        let user: Value = user.json().await.expect("json");
+        assert_eq!(user, json!({ "name": "Owner User" }));
+
+        let unknown = client
+            .get(format!(
+                "{}/users/{}?fields=id,password",
+                base_url, owner.id
+            ))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(unknown.status(), StatusCode::BAD_REQUEST);
+        let unknown: Value = unknown.json().await.expect("json");
+        assert!(
+            unknown["message"]
+                .as_str()
+                .unwrap_or_default()
+                .contains("Unknown field: password")
+        );
+    }
+
+    #[tokio::test]
+    async fn admin_api_shows_and_restores_deleted_records() {
+        let ctx = TestCtx::new("admin_api_restore").await.expect("test ctx");
+        ctx.seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+        let app = ctx
+            .seed_app("Gone App", "https://gone.example.com/callback")
+            .await
+            .expect("seed app");
+        delete_app_svc(&ctx.state, &app.id)
+            .await
+            .expect("delete app");
+        let token =
+            issue_superuser_token_svc(&ctx.state.db, &ctx.state.token_keys, "root@example.com")
+                .await
+                .expect("admin token");
+        let base_url = spawn_admin_api(&ctx).await;
+        let client = reqwest::Client::new();
+
+        let hidden = client
+            .get(format!("{}/apps/{}", base_url, app.id))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(hidden.status(), StatusCode::NOT_FOUND);
+
+        let listed = client
+            .get(format!("{}/apps?include_deleted=true", base_url))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(listed.status(), StatusCode::OK);
+        let listed: Value = listed.json().await.expect("json");
+        assert_eq!(listed["data"][0]["id"], app.id.as_str());
+        assert!(listed["data"][0]["deleted_at"].is_i64());
+
+        let restored = client
+            .post(format!("{}/apps/{}/restore", base_url, app.id))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(restored.status(), StatusCode::OK);
+        let restored: Value = restored.json().await.expect("json");
+        assert!(restored.get("deleted_at").is_none());
+    }
+
+    #[tokio::test]
+    async fn admin_api_exports_org_access_log_as_csv() {
+        let ctx = TestCtx::new("admin_api_access_log")
+            .await
+            .expect("test ctx");
+        ctx.seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+        let fixture = ctx
+            .seed_auth_fixture("Member", "member@example.com", "password", "Acme")
+            .await
+            .expect("fixture");
+        record_org_access(&ctx.state, &fixture.org.id, &fixture.user.id).await;
+
+        let token =
+            issue_superuser_token_svc(&ctx.state.db, &ctx.state.token_keys, "root@example.com")
+                .await
+                .expect("admin token");
+        let base_url = spawn_admin_api(&ctx).await;
+        let client = reqwest::Client::new();
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+
+        let res = client
+            .get(format!(
+                "{}/orgs/{}/access-log?from={}&to={}",
+                base_url, fixture.org.id, today, today
+            ))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["content-type"], "text/csv; charset=utf-8");
+        let csv = res.text().await.expect("body");
+        assert_eq!(csv.lines().count(), 2);
+        assert!(csv.contains("member@example.com"));
+
+        let res = client
+            .get(format!(
+                "{}/orgs/{}/access-log?from={}&to=2020-01-01",
+                base_url, fixture.org.id, today
+            ))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn admin_api_imports_org_archives() {
+        let ctx = TestCtx::new("admin_api_org_import")
+            .await
+            .expect("test ctx");
+        ctx.seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+        let fixture = ctx
+            .seed_auth_fixture("Member", "member@example.com", "password", "Acme")
+            .await
+            .expect("fixture");
+        let archive = export_org_svc(&ctx.state.db, &fixture.org.id)
+            .await
+            .expect("archive");
+        let body = serde_json::to_string(&archive).expect("serialize");
+
+        let token =
+            issue_superuser_token_svc(&ctx.state.db, &ctx.state.token_keys, "root@example.com")
+                .await
+                .expect("admin token");
+        let base_url = spawn_admin_api(&ctx).await;
+        let client = reqwest::Client::new();
+
+        let res = client
+            .post(format!(
+                "{}/orgs/import?strategy=rename&dry_run=true",
+                base_url
+            ))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .body(body.clone())
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(res.status(), StatusCode::OK);
+        let report: Value = res.json().await.expect("json");
+        assert_eq!(report["plan"]["org"]["name"], "Acme (2)");
+        assert!(report["result"].is_null());
+
+        let res = client
+            .post(format!("{}/orgs/import?strategy=rename", base_url))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .body(body)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let report: Value = res.json().await.expect("json");
+        assert_ne!(report["result"]["org_id"], json!(fixture.org.id));
+
+        let res = client
+            .post(format!("{}/orgs/import?strategy=overwrite", base_url))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .body("{}")
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn admin_api_manages_app_environments() {
+        let ctx = TestCtx::new("admin_api_app_envs").await.expect("test ctx");
+        ctx.seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+        let app = ctx
+            .seed_app("Env App", "https://prod.example.com/callback")
+            .await
+            .expect("seed app");
+        let token =
+            issue_superuser_token_svc(&ctx.state.db, &ctx.state.token_keys, "root@example.com")
+                .await
+                .expect("admin token");
+        let base_url = spawn_admin_api(&ctx).await;
+        let client = reqwest::Client::new();
+        let url = format!("{}/apps/{}/environments", base_url, app.id);
+
+        let created = client
+            .post(&url)
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .json(&json!({
+                "label": "staging",
+                "redirect_uri": "https://staging.example.com/callback",
+            }))
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(created.status(), StatusCode::CREATED);
+        let created: Value = created.json().await.expect("json");
+        assert_eq!(created["label"], "staging");
+        assert_eq!(created["client_secret"].as_str().unwrap().len(), 36);
+
+        let invalid = client
+            .post(&url)
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .json(&json!({ "label": "Not a slug", "redirect_uri": "https://x.example.com" }))
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+
+        let listed = client
+            .get(&url)
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .send()
+            .await
+            .expect("request");
+        let listed: Value = listed.json().await.expect("json");
+        assert_eq!(listed[0]["client_id"], created["client_id"]);
+        assert!(listed[0].get("client_secret").is_none());
+
+        let rotated = client
+            .post(format!(
+                "{}/{}/rotate-secret",
+                url,
+                created["id"].as_str().unwrap()
+            ))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(rotated.status(), StatusCode::OK);
+        let rotated: Value = rotated.json().await.expect("json");
+        assert_eq!(rotated["client_id"], created["client_id"]);
+        assert_ne!(rotated["client_secret"], created["client_secret"]);
+        assert!(rotated["previous_secret_expires_at"].as_i64().is_some());
+
+        let deleted = client
+            .delete(format!("{}/{}", url, created["id"].as_str().unwrap()))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(deleted.status(), StatusCode::NO_CONTENT);
+
+        let missing = client
+            .delete(format!("{}/{}", url, created["id"].as_str().unwrap()))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn org_rate_limit_applies_to_org_tokens() {
+        let ctx = TestCtx::new("admin_api_org_rate_limit")
+            .await
+            .expect("test ctx");
+        ctx.seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+        let fixture = ctx
+            .seed_auth_fixture("Member", "member@example.com", "password123", "Acme")
+            .await
+            .expect("fixture");
+
+        let admin_token =
+            issue_superuser_token_svc(&ctx.state.db, &ctx.state.token_keys, "root@example.com")
+                .await
+                .expect("admin token");
+        let base_url = spawn_admin_api(&ctx).await;
+        let client = reqwest::Client::new();
+
+        let updated = client
+            .put(format!("{}/orgs/{}/rate-limit", base_url, fixture.org.id))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&admin_token)
+            .json(&json!({ "requests_per_min": 1, "burst": 1 }))
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(updated.status(), StatusCode::OK);
+        let updated: Value = updated.json().await.expect("json");
+        assert_eq!(updated["source"], "org");
+
+        // The org's scripts share one budget across the OAuth API
+        let app = oauth_api_routes(ctx.state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener should bind");
+        let api_url = format!("http://{}", listener.local_addr().expect("local addr"));
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        let auth = authenticate(
+            &ctx.state,
+            &CredentialsDto {
+                email: fixture.email.clone(),
+                password: fixture.password.clone(),
+            },
+            SessionClientDto::default(),
+        )
+        .await
+        .expect("login");
+
+        let first = client
+            .get(format!("{}/org/usage", api_url))
+            .bearer_auth(&auth.token)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(first.headers()["x-ratelimit-limit"], "1");
+        assert_eq!(first.headers()["x-ratelimit-remaining"], "0");
+
+        let limited = client
+            .get(format!("{}/org/usage", api_url))
+            .bearer_auth(&auth.token)
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(limited.headers().contains_key("retry-after"));
+        let body: Value = limited.json().await.expect("json error body");
+        assert_eq!(body["status_code"], 429);
+
+        let reset = client
+            .delete(format!("{}/orgs/{}/rate-limit", base_url, fixture.org.id))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&admin_token)
+            .send()
+            .await
+            .expect("request");
+        let reset: Value = reset.json().await.expect("json");
+        assert_eq!(reset["source"], "default");
+    }
+
+    #[tokio::test]
+    async fn admin_api_replays_requests_with_the_same_idempotency_key() {
+        let ctx = TestCtx::new("admin_api_idempotency")
+            .await
+            .expect("test ctx");
+        ctx.seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+        let owner = ctx
+            .seed_user_with_password("Owner User", "owner@example.com", "password123")
+            .await
+            .expect("owner");
+        let token =
+            issue_superuser_token_svc(&ctx.state.db, &ctx.state.token_keys, "root@example.com")
+                .await
+                .expect("admin token");
+        let base_url = spawn_admin_api(&ctx).await;
+        let client = reqwest::Client::new();
+
+        let create_org = |name: &str| {
+            client
+                .post(format!("{}/orgs", base_url))
+                .header("X-Forwarded-For", "127.0.0.1")
+                .header("Idempotency-Key", "create-org-1")
+                .bearer_auth(&token)
+                .json(&json!({ "name": name, "owner_id": owner.id }))
+                .send()
+        };
+
+        let first = create_org("Retried Org").await.expect("request");
+        assert_eq!(first.status(), StatusCode::CREATED);
+        assert!(first.headers().get("idempotent-replayed").is_none());
+        let first: Value = first.json().await.expect("json");
+
+        let retry = create_org("Retried Org").await.expect("request");
+        assert_eq!(retry.status(), StatusCode::CREATED);
+        assert_eq!(retry.headers()["idempotent-replayed"], "true");
+        let retry: Value = retry.json().await.expect("json");
+        assert_eq!(retry["id"], first["id"]);
+
+        let reused = create_org("Another Org").await.expect("request");
+        assert_eq!(reused.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let orgs = client
+            .get(format!("{}/orgs?keyword=Org", base_url))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .send()
+            .await
+            .expect("request");
+        let orgs: Value = orgs.json().await.expect("json");
+        assert_eq!(orgs["meta"]["total_records"], 1);
+    }
+
+    #[tokio::test]
+    async fn admin_api_does_not_replay_secrets() {
+        let ctx = TestCtx::new("admin_api_idempotency_secret")
+            .await
+            .expect("test ctx");
+        let root = ctx
+            .seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+        let app = ctx
+            .seed_app("Secret App", "https://secret.example.com/callback")
+            .await
+            .expect("seed app");
+        let token =
+            issue_superuser_token_svc(&ctx.state.db, &ctx.state.token_keys, "root@example.com")
+                .await
+                .expect("admin token");
+        let base_url = spawn_admin_api(&ctx).await;
+        let client = reqwest::Client::new();
+
+        let rotate = || {
+            client
+                .post(format!("{}/apps/{}/rotate-secret", base_url, app.id))
+                .header("X-Forwarded-For", "127.0.0.1")
+                .header("Idempotency-Key", "rotate-1")
+                .bearer_auth(&token)
+                .send()
+        };
+
+        let first = rotate().await.expect("request");
+        assert_eq!(first.status(), StatusCode::OK);
+        let first: Value = first.json().await.expect("json");
+        assert!(first["client_secret"].as_str().is_some());
+
+        let retry = rotate().await.expect("request");
+        assert_eq!(retry.status(), StatusCode::CONFLICT);
+        let retry = retry.text().await.expect("body");
+        assert!(!retry.contains(first["client_secret"].as_str().unwrap()));
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let stored = ctx
+            .state
+            .db
+            .idempotency_keys
+            .get(
+                root.id.clone(),
+                format!("/admin/api/apps/{}/rotate-secret", app.id),
+                "rotate-1".to_string(),
+                now,
+            )
+            .await
+            .expect("get key")
+            .expect("key is kept");
+        assert_eq!(stored.status_code, Some(200));
+        assert!(stored.body.is_none());
+        assert!(stored.response_hash.is_some());
+    }
+
+    #[tokio::test]
+    async fn admin_api_batch_gets_users_and_orgs() {
+        let ctx = TestCtx::new("admin_api_batch_get").await.expect("test ctx");
+        ctx.seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Batch User",
+                "batch@example.com",
+                "password123",
+                "Batch Org",
+            )
+            .await
+            .expect("auth fixture");
+        let token =
+            issue_superuser_token_svc(&ctx.state.db, &ctx.state.token_keys, "root@example.com")
+                .await
+                .expect("admin token");
+        let base_url = spawn_admin_api(&ctx).await;
+        let client = reqwest::Client::new();
+
+        let users = client
+            .post(format!("{}/users/batch-get?fields=id,email", base_url))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .json(&json!({ "ids": ["usr_missing", fixture.user.id, fixture.user.id] }))
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(users.status(), StatusCode::OK);
+        let users: Value = users.json().await.expect("json");
+        assert_eq!(
+            users,
+            json!({
+                "items": [{ "id": fixture.user.id, "email": "batch@example.com" }],
+                "missing_ids": ["usr_missing"]
+            })
+        );
+
+        let orgs = client
+            .post(format!("{}/orgs/batch-get", base_url))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .json(&json!({ "ids": [fixture.org.id] }))
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(orgs.status(), StatusCode::OK);
+        let orgs: Value = orgs.json().await.expect("json");
+        assert_eq!(orgs["items"][0]["name"], "Batch Org");
+        assert_eq!(orgs["missing_ids"], json!([]));
+
+        let ids: Vec<String> = (0..101).map(|i| format!("org_{}", i)).collect();
+        let too_many = client
+            .post(format!("{}/orgs/batch-get", base_url))
+            .header("X-Forwarded-For", "127.0.0.1")
+            .bearer_auth(&token)
+            .json(&json!({ "ids": ids }))
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(too_many.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,56 @@
+use std::convert::Infallible;
+
+use axum::{
+    Extension,
+    extract::State,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use snafu::ensure;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::{Stream, StreamExt};
+use tracing::warn;
+
+use crate::dto::EntityChangeDto;
+use crate::error::ForbiddenSnafu;
+use crate::{Result, ctx::Ctx, run::AppState};
+
+/// Event sent when changes were missed, pages reload everything they show
+const RESYNC_EVENT: &str = "resync";
+
+fn change_event(
+    change: std::result::Result<EntityChangeDto, BroadcastStreamRecvError>,
+) -> Option<Event> {
+    match change {
+        Ok(change) => Event::default()
+            .event(change.entity.to_string())
+            .json_data(&change)
+            .ok(),
+        Err(BroadcastStreamRecvError::Lagged(missed)) => {
+            warn!(missed = missed, "admin_events.lagged");
+            Some(Event::default().event(RESYNC_EVENT).data("{}"))
+        }
+    }
+}
+
+/// Streams user, org and membership changes to superusers as Server-Sent Events.
+///
+/// Each event is named after the entity, `user`, `org` or `org_member`, with
+/// the change as JSON data.
+pub async fn admin_events_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+) -> Result<Sse<impl Stream<Item = std::result::Result<Event, Infallible>>>> {
+    ensure!(
+        ctx.actor.is_system_admin(),
+        ForbiddenSnafu {
+            msg: "Only superusers can watch admin events."
+        }
+    );
+
+    let stream = BroadcastStream::new(state.admin_events.subscribe())
+        .filter_map(change_event)
+        .map(Ok);
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
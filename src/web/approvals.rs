@@ -0,0 +1,149 @@
+use askama::Template;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::{Extension, Form, body::Body, extract::State, response::Response};
+use axum::{
+    Router,
+    routing::{get, post},
+};
+use snafu::{ResultExt, ensure};
+
+use crate::Result;
+use crate::ctx::Ctx;
+use crate::error::{ErrorInfo, ForbiddenSnafu, ResponseBuilderSnafu, TemplateSnafu};
+use crate::models::{ApprovalView, CspNonce, EmptyState, Pref, TemplateData, TokenFormData};
+use crate::run::AppState;
+use crate::services::approvals::{approve_web_svc, list_approvals_svc, reject_web_svc};
+use crate::services::token::create_csrf_token_svc;
+
+pub fn approvals_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", get(approvals_handler))
+        .route("/{approval_id}/approve", post(post_approve_handler))
+        .route("/{approval_id}/reject", post(post_reject_handler))
+        .with_state(state)
+}
+
+#[derive(Clone)]
+struct ApprovalItem {
+    approval: ApprovalView,
+    token: String,
+}
+
+#[derive(Template)]
+#[template(path = "pages/approvals/index.html")]
+struct ApprovalsPageTemplate {
+    t: TemplateData,
+    approvals: Vec<ApprovalItem>,
+    empty_state: EmptyState,
+    error_message: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "widgets/approvals/list.html")]
+struct ApprovalsTemplate {
+    approvals: Vec<ApprovalItem>,
+    empty_state: EmptyState,
+    error_message: Option<String>,
+}
+
+fn ensure_system_admin(ctx: &Ctx) -> Result<()> {
+    ensure!(
+        ctx.actor.is_system_admin(),
+        ForbiddenSnafu {
+            msg: "Only superusers can view approvals."
+        }
+    );
+
+    Ok(())
+}
+
+async fn build_approvals(state: &AppState) -> Result<Vec<ApprovalItem>> {
+    let approvals = list_approvals_svc(state).await?;
+
+    approvals
+        .into_iter()
+        .map(|approval| {
+            let token = create_csrf_token_svc(&approval.id, &state.config.jwt_secret)?;
+            Ok(ApprovalItem {
+                approval: approval.into(),
+                token,
+            })
+        })
+        .collect()
+}
+
+async fn approvals_handler(
+    Extension(csp_nonce): Extension<CspNonce>,
+    Extension(ctx): Extension<Ctx>,
+    Extension(pref): Extension<Pref>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    ensure_system_admin(&ctx)?;
+
+    let mut t = TemplateData::new(&state, ctx.actor.clone(), &pref, csp_nonce.nonce);
+    t.title = String::from("Approvals");
+
+    let tpl = ApprovalsPageTemplate {
+        t,
+        approvals: build_approvals(&state).await?,
+        empty_state: EmptyState::suggestions("approval requests", None),
+        error_message: None,
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn post_approve_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(approval_id): Path<String>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    ensure_system_admin(&ctx)?;
+
+    let actor = ctx.actor().expect("actor is required");
+    let result = approve_web_svc(&state, &approval_id, &actor.user.id, &payload.token).await;
+
+    approvals_response(&state, result.map(|_| ())).await
+}
+
+async fn post_reject_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(approval_id): Path<String>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    ensure_system_admin(&ctx)?;
+
+    let actor = ctx.actor().expect("actor is required");
+    let result = reject_web_svc(&state, &approval_id, &actor.user.id, &payload.token).await;
+
+    approvals_response(&state, result.map(|_| ())).await
+}
+
+/// Renders the refreshed approvals list with the outcome of the decision
+async fn approvals_response(state: &AppState, result: Result<()>) -> Result<Response<Body>> {
+    let mut status = StatusCode::OK;
+    let mut error_message = None;
+
+    if let Err(err) = result {
+        let error_info = ErrorInfo::from(&err);
+        status = error_info.status_code;
+        error_message = Some(error_info.message);
+    }
+
+    let tpl = ApprovalsTemplate {
+        approvals: build_approvals(state).await?,
+        empty_state: EmptyState::suggestions("approval requests", None),
+        error_message,
+    };
+
+    Response::builder()
+        .status(status)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
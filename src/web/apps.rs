@@ -1,23 +1,43 @@
 use askama::Template;
-use axum::extract::Query;
+use axum::extract::{Path, Query};
 use axum::http::StatusCode;
 use axum::{Extension, Form, body::Body, extract::State, response::Response};
-use axum::{Router, middleware, routing::get};
+use axum::{
+    Router, middleware,
+    routing::{get, post},
+};
 use snafu::{ResultExt, ensure};
+use tracing::warn;
 use urlencoding::encode;
 use validator::Validate;
 
 use crate::dto::AppDto;
+use crate::dto::AppEnvironmentDto;
+use crate::dto::AppProofKeyDto;
+use crate::dto::AppUriCheckDto;
 use crate::dto::ListAppsParamsDto;
 use crate::dto::Permission;
+use crate::dto::{APP_SORT, sort_query};
 use crate::error::ValidationSnafu;
-use crate::models::{AppView, CspNonce, PaginationLinks, TokenFormData};
+use crate::models::{AppView, CspNonce, EmptyState, PaginationLinks, SortLinks, TokenFormData};
+use crate::services::app_environments::{
+    AppEnvironmentFormData, create_app_environment_web_svc, delete_app_environment_web_svc,
+    list_app_environments_svc, rotate_app_environment_secret_web_svc,
+};
 use crate::services::apps::{
-    NewAppFormData, UpdateAppFormData, create_app_web_svc, delete_app_web_svc, get_app_svc,
-    list_apps_svc, regenerate_app_secret_web_svc, update_app_web_svc,
+    MAX_REDIRECT_URIS, NewAppFormData, UpdateAppFormData, create_app_web_svc, delete_app_web_svc,
+    get_app_uri_check_svc, list_apps_svc, regenerate_app_secret_web_svc, rotate_app_secret_web_svc,
+    update_app_web_svc,
 };
+use crate::services::drafts::discard_draft_svc;
+use crate::services::proof_keys::{
+    AppProofKeyFormData, get_app_proof_key_svc, register_app_proof_key_web_svc,
+    remove_app_proof_key_web_svc,
+};
+use crate::services::users::get_actor_email_svc;
 use crate::validators::flatten_errors;
 use crate::web::middleware::app_middleware;
+use crate::web::{SecretResponse, lifecycle_routes};
 use crate::{
     Error, Result,
     ctx::Ctx,
@@ -49,10 +69,32 @@ fn app_inner_routes(state: AppState) -> Router<AppState> {
             "/regenerate-secret",
             get(regenerate_app_secret_handler).post(post_regenerate_app_secret_handler),
         )
+        .route(
+            "/rotate-secret",
+            get(rotate_app_secret_handler).post(post_rotate_app_secret_handler),
+        )
         .route(
             "/delete",
             get(delete_app_handler).post(post_delete_app_handler),
         )
+        .route(
+            "/proof-key",
+            get(app_proof_key_handler).post(post_app_proof_key_handler),
+        )
+        .route("/proof-key/delete", post(post_delete_app_proof_key_handler))
+        .route(
+            "/environments",
+            get(app_environments_handler).post(post_app_environment_handler),
+        )
+        .route(
+            "/environments/{environment_id}/rotate-secret",
+            post(post_rotate_app_environment_secret_handler),
+        )
+        .route(
+            "/environments/{environment_id}/delete",
+            post(post_delete_app_environment_handler),
+        )
+        .nest("/lifecycle", lifecycle_routes(state.clone()))
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             app_middleware,
@@ -104,6 +146,8 @@ struct SearchAppsTemplate {
     apps: Vec<AppView>,
     pagination: Option<PaginationLinks>,
     error_message: Option<String>,
+    empty_state: EmptyState,
+    sorting: SortLinks,
 }
 async fn search_apps_handler(
     Extension(ctx): Extension<Ctx>,
@@ -116,9 +160,20 @@ async fn search_apps_handler(
         apps: Vec::new(),
         pagination: None,
         error_message: None,
+        empty_state: EmptyState::apps(&ctx.actor, query.keyword.as_deref()),
+        sorting: SortLinks::new(
+            &APP_SORT,
+            query.sort_by.as_deref(),
+            query.sort_dir.as_deref(),
+            "/apps/search",
+            "/apps",
+            query.keyword.as_deref(),
+            ".album-items",
+        ),
     };
 
     let keyword = query.keyword.clone();
+    let sort_param = sort_query(query.sort_by.as_deref(), query.sort_dir.as_deref());
 
     match list_apps_svc(&state, query).await {
         Ok(apps) => {
@@ -131,7 +186,7 @@ async fn search_apps_handler(
                 &apps.meta,
                 "/apps/search",
                 "/apps",
-                &keyword_param,
+                &format!("{}{}", keyword_param, sort_param),
                 ".album-items",
             ));
 
@@ -191,6 +246,7 @@ async fn new_app_handler(
             name: "".to_string(),
             redirect_uri: "".to_string(),
             token,
+            verify: None,
         },
         error_message: None,
     };
@@ -201,6 +257,12 @@ async fn new_app_handler(
         .context(ResponseBuilderSnafu)
 }
 
+#[derive(Template)]
+#[template(path = "widgets/apps/secret_issued.html")]
+struct AppSecretIssuedTemplate {
+    app: AppDto,
+}
+
 async fn post_new_app_handler(
     Extension(ctx): Extension<Ctx>,
     State(state): State<AppState>,
@@ -218,6 +280,7 @@ async fn post_new_app_handler(
             name: "".to_string(),
             redirect_uri: "".to_string(),
             token,
+            verify: None,
         },
         error_message: None,
     };
@@ -228,18 +291,20 @@ async fn post_new_app_handler(
         name: payload.name.clone(),
         redirect_uri: payload.redirect_uri.clone(),
         token: payload.token.clone(),
+        verify: payload.verify.clone(),
     };
 
     let result = create_app_web_svc(&state, app).await;
 
     match result {
-        Ok(_) => {
-            let next_url = "/apps".to_string();
-            // Weird but can't do a redirect here, let htmx handle it
+        Ok(app) => {
+            // The plain secret is only available right now, show it instead of redirecting
+            let tpl = AppSecretIssuedTemplate { app };
             return Response::builder()
                 .status(200)
-                .header("HX-Redirect", next_url)
-                .body(Body::from("".to_string()))
+                .header("Content-Type", "text/html")
+                .extension(SecretResponse)
+                .body(Body::from(tpl.render().context(TemplateSnafu)?))
                 .context(ResponseBuilderSnafu);
         }
         Err(err) => {
@@ -251,6 +316,7 @@ async fn post_new_app_handler(
 
     tpl.payload.name = payload.name.clone();
     tpl.payload.redirect_uri = payload.redirect_uri.clone();
+    tpl.payload.verify = payload.verify.clone();
 
     // Will only arrive here on error
     Response::builder()
@@ -264,7 +330,10 @@ async fn post_new_app_handler(
 struct AppPageTemplate {
     t: TemplateData,
     app: AppDto,
+    created_by_email: Option<String>,
+    uri_check: Option<AppUriCheckDto>,
     updated: bool,
+    secret_notice: Option<String>,
     can_edit: bool,
     can_delete: bool,
 }
@@ -280,10 +349,16 @@ async fn app_page_handler(
 
     t.title = format!("App - {}", &app.name);
 
+    let uri_check = get_app_uri_check_svc(&state, &app).await?;
+    let created_by_email = get_actor_email_svc(&state, app.created_by.as_deref()).await?;
+
     let tpl = AppPageTemplate {
         t,
         app,
+        created_by_email,
+        uri_check,
         updated: false,
+        secret_notice: None,
         can_edit: ctx.actor.has_permissions(&[Permission::AppsEdit]),
         can_delete: ctx.actor.has_permissions(&[Permission::AppsDelete]),
     };
@@ -299,6 +374,7 @@ async fn app_page_handler(
 struct AppControlsTemplate {
     app: AppDto,
     updated: bool,
+    secret_notice: Option<String>,
     can_edit: bool,
     can_delete: bool,
 }
@@ -312,6 +388,7 @@ async fn app_controls_handler(
     let tpl = AppControlsTemplate {
         app,
         updated: false,
+        secret_notice: None,
         can_edit: ctx.actor.has_permissions(&[Permission::AppsEdit]),
         can_delete: ctx.actor.has_permissions(&[Permission::AppsDelete]),
     };
@@ -323,11 +400,18 @@ async fn app_controls_handler(
         .context(ResponseBuilderSnafu)
 }
 
+/// Autosaved drafts of the edit form are kept per record
+fn edit_app_draft_key(id: &str) -> String {
+    format!("app-edit-{}", id)
+}
+
 #[derive(Template)]
 #[template(path = "widgets/apps/update_form.html")]
 struct UpdateAppTemplate {
+    draft_key: String,
     app: AppDto,
     payload: UpdateAppFormData,
+    max_redirect_uris: usize,
     error_message: Option<String>,
 }
 
@@ -343,14 +427,21 @@ async fn update_app_handler(
 
     let name = app.name.clone();
     let redirect_uri = app.redirect_uri.clone();
+    let extra_redirect_uris = app.extra_redirect_uris().join("\n");
+    let scopes = app.scopes.join(" ");
 
     let tpl = UpdateAppTemplate {
+        draft_key: edit_app_draft_key(&app.id),
         app,
         payload: UpdateAppFormData {
             token,
             name,
             redirect_uri,
+            extra_redirect_uris,
+            scopes,
+            verify: None,
         },
+        max_redirect_uris: MAX_REDIRECT_URIS,
         error_message: None,
     };
 
@@ -375,12 +466,17 @@ async fn post_update_app_handler(
     let app_id = app.id.clone();
 
     let mut tpl = UpdateAppTemplate {
+        draft_key: edit_app_draft_key(&app_id),
         app,
         payload: UpdateAppFormData {
             token,
             name: payload.name.clone(),
             redirect_uri: payload.redirect_uri.clone(),
+            extra_redirect_uris: payload.extra_redirect_uris.clone(),
+            scopes: payload.scopes.clone(),
+            verify: payload.verify.clone(),
         },
+        max_redirect_uris: MAX_REDIRECT_URIS,
         error_message: None,
     };
 
@@ -388,16 +484,28 @@ async fn post_update_app_handler(
         token: payload.token.clone(),
         name: payload.name.clone(),
         redirect_uri: payload.redirect_uri.clone(),
+        extra_redirect_uris: payload.extra_redirect_uris.clone(),
+        scopes: payload.scopes.clone(),
+        verify: payload.verify.clone(),
     };
 
     let result = update_app_web_svc(&state, &app_id, data).await;
 
     match result {
         Ok(updated_app) => {
+            // The form went through, its autosaved draft is no longer needed
+            let actor = ctx.actor().expect("actor is required");
+            if let Err(err) =
+                discard_draft_svc(&state, &actor.id, &edit_app_draft_key(&app_id)).await
+            {
+                warn!("Unable to discard form draft: {}", err);
+            }
+
             // Render back the controls but with updated data
             let tpl = AppControlsTemplate {
                 app: updated_app,
                 updated: true,
+                secret_notice: None,
                 can_edit: ctx.actor.has_permissions(&[Permission::AppsEdit]),
                 can_delete: ctx.actor.has_permissions(&[Permission::AppsDelete]),
             };
@@ -486,15 +594,100 @@ async fn post_regenerate_app_secret_handler(
     let result = regenerate_app_secret_web_svc(&state, &app.id, &payload.token).await;
 
     match result {
-        Ok(_) => {
-            let Some(updated_app) = get_app_svc(&state, &app.id).await? else {
-                return Err(Error::AppNotFound);
+        Ok(updated_app) => {
+            // Just render back the controls, the new secret is shown once
+            let tpl = AppControlsTemplate {
+                app: updated_app,
+                updated: true,
+                secret_notice: Some(
+                    "Copy the new client secret now, it will not be shown again.".to_string(),
+                ),
+                can_edit: ctx.actor.has_permissions(&[Permission::AppsEdit]),
+                can_delete: ctx.actor.has_permissions(&[Permission::AppsDelete]),
             };
 
-            // Just render back the controls
+            Ok(Response::builder()
+                .status(200)
+                .header("Content-Type", "text/html")
+                .extension(SecretResponse)
+                .body(Body::from(tpl.render().context(TemplateSnafu)?))
+                .context(ResponseBuilderSnafu)?)
+        }
+        Err(err) => {
+            let error_info = ErrorInfo::from(&err);
+            tpl.error_message = Some(error_info.message);
+
+            Ok(Response::builder()
+                .status(error_info.status_code)
+                .body(Body::from(tpl.render().context(TemplateSnafu)?))
+                .context(ResponseBuilderSnafu)?)
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "widgets/apps/rotate_secret_form.html")]
+struct RotateAppSecretFormTemplate {
+    app: AppDto,
+    payload: TokenFormData,
+    grace_hours: u64,
+    error_message: Option<String>,
+}
+
+async fn rotate_app_secret_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(app): Extension<AppDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    let config = state.config.clone();
+
+    enforce_policy(&ctx.actor, Resource::App, Action::Update)?;
+
+    let token = create_csrf_token_svc(&app.id.to_string(), &config.jwt_secret)?;
+
+    let tpl = RotateAppSecretFormTemplate {
+        app,
+        payload: TokenFormData { token },
+        grace_hours: config.app_secret_grace_hours,
+        error_message: None,
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn post_rotate_app_secret_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(app): Extension<AppDto>,
+    State(state): State<AppState>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    let config = state.config.clone();
+
+    enforce_policy(&ctx.actor, Resource::App, Action::Update)?;
+
+    let token = create_csrf_token_svc(&app.id.to_string(), &config.jwt_secret)?;
+
+    let mut tpl = RotateAppSecretFormTemplate {
+        app: app.clone(),
+        payload: TokenFormData { token },
+        grace_hours: config.app_secret_grace_hours,
+        error_message: None,
+    };
+
+    let result = rotate_app_secret_web_svc(&state, &app.id, &payload.token).await;
+
+    match result {
+        Ok(updated_app) => {
             let tpl = AppControlsTemplate {
                 app: updated_app,
                 updated: true,
+                secret_notice: Some(format!(
+                    "Copy the new client secret now, it will not be shown again. The previous secret keeps working for {} hours.",
+                    config.app_secret_grace_hours
+                )),
                 can_edit: ctx.actor.has_permissions(&[Permission::AppsEdit]),
                 can_delete: ctx.actor.has_permissions(&[Permission::AppsDelete]),
             };
@@ -502,6 +695,7 @@ async fn post_regenerate_app_secret_handler(
             Ok(Response::builder()
                 .status(200)
                 .header("Content-Type", "text/html")
+                .extension(SecretResponse)
                 .body(Body::from(tpl.render().context(TemplateSnafu)?))
                 .context(ResponseBuilderSnafu)?)
         }
@@ -517,6 +711,197 @@ async fn post_regenerate_app_secret_handler(
     }
 }
 
+#[derive(Template)]
+#[template(path = "widgets/apps/proof_key.html")]
+struct AppProofKeyTemplate {
+    app: AppDto,
+    proof_key: Option<AppProofKeyDto>,
+    token: String,
+    error_message: Option<String>,
+}
+
+impl AppProofKeyTemplate {
+    async fn load(state: &AppState, app: AppDto) -> Result<Self> {
+        let token = create_csrf_token_svc(&app.id, &state.config.jwt_secret)?;
+        let proof_key = get_app_proof_key_svc(state, &app.id).await?;
+
+        Ok(Self {
+            app,
+            proof_key,
+            token,
+            error_message: None,
+        })
+    }
+
+    fn into_response(self, result: Result<()>) -> Result<Response<Body>> {
+        let mut tpl = self;
+        let mut status = StatusCode::OK;
+
+        if let Err(err) = result {
+            let error_info = ErrorInfo::from(&err);
+            status = error_info.status_code;
+            tpl.error_message = Some(error_info.message);
+        }
+
+        Response::builder()
+            .status(status)
+            .body(Body::from(tpl.render().context(TemplateSnafu)?))
+            .context(ResponseBuilderSnafu)
+    }
+}
+
+async fn app_proof_key_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(app): Extension<AppDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    enforce_policy(&ctx.actor, Resource::App, Action::Update)?;
+
+    AppProofKeyTemplate::load(&state, app)
+        .await?
+        .into_response(Ok(()))
+}
+
+async fn post_app_proof_key_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(app): Extension<AppDto>,
+    State(state): State<AppState>,
+    Form(payload): Form<AppProofKeyFormData>,
+) -> Result<Response<Body>> {
+    enforce_policy(&ctx.actor, Resource::App, Action::Update)?;
+
+    let result = register_app_proof_key_web_svc(&state, &app.id, payload)
+        .await
+        .map(|_| ());
+
+    AppProofKeyTemplate::load(&state, app)
+        .await?
+        .into_response(result)
+}
+
+async fn post_delete_app_proof_key_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(app): Extension<AppDto>,
+    State(state): State<AppState>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    enforce_policy(&ctx.actor, Resource::App, Action::Update)?;
+
+    let result = remove_app_proof_key_web_svc(&state, &app.id, &payload.token).await;
+
+    AppProofKeyTemplate::load(&state, app)
+        .await?
+        .into_response(result)
+}
+
+#[derive(Template)]
+#[template(path = "widgets/apps/environments.html")]
+struct AppEnvironmentsTemplate {
+    app: AppDto,
+    environments: Vec<AppEnvironmentDto>,
+    /// Environment whose secret was just issued, shown this once
+    issued: Option<AppEnvironmentDto>,
+    token: String,
+    error_message: Option<String>,
+}
+
+impl AppEnvironmentsTemplate {
+    async fn load(state: &AppState, app: AppDto) -> Result<Self> {
+        let token = create_csrf_token_svc(&app.id, &state.config.jwt_secret)?;
+        let environments = list_app_environments_svc(state, &app.id).await?;
+
+        Ok(Self {
+            app,
+            environments,
+            issued: None,
+            token,
+            error_message: None,
+        })
+    }
+
+    fn into_response(self, result: Result<()>) -> Result<Response<Body>> {
+        let mut tpl = self;
+        let mut status = StatusCode::OK;
+
+        if let Err(err) = result {
+            let error_info = ErrorInfo::from(&err);
+            status = error_info.status_code;
+            tpl.error_message = Some(error_info.message);
+        }
+
+        let mut builder = Response::builder().status(status);
+        if tpl.issued.is_some() {
+            builder = builder.extension(SecretResponse);
+        }
+
+        builder
+            .body(Body::from(tpl.render().context(TemplateSnafu)?))
+            .context(ResponseBuilderSnafu)
+    }
+}
+
+async fn app_environments_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(app): Extension<AppDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    enforce_policy(&ctx.actor, Resource::App, Action::Update)?;
+
+    AppEnvironmentsTemplate::load(&state, app)
+        .await?
+        .into_response(Ok(()))
+}
+
+async fn post_app_environment_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(app): Extension<AppDto>,
+    State(state): State<AppState>,
+    Form(payload): Form<AppEnvironmentFormData>,
+) -> Result<Response<Body>> {
+    enforce_policy(&ctx.actor, Resource::App, Action::Update)?;
+
+    let result = create_app_environment_web_svc(&state, &app.id, payload).await;
+
+    let mut tpl = AppEnvironmentsTemplate::load(&state, app).await?;
+    tpl.issued = result.as_ref().ok().cloned();
+    tpl.into_response(result.map(|_| ()))
+}
+
+async fn post_rotate_app_environment_secret_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(app): Extension<AppDto>,
+    State(state): State<AppState>,
+    Path((_app_id, environment_id)): Path<(String, String)>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    enforce_policy(&ctx.actor, Resource::App, Action::Update)?;
+
+    let result =
+        rotate_app_environment_secret_web_svc(&state, &app.id, &environment_id, &payload.token)
+            .await;
+
+    let mut tpl = AppEnvironmentsTemplate::load(&state, app).await?;
+    tpl.issued = result.as_ref().ok().cloned();
+    tpl.into_response(result.map(|_| ()))
+}
+
+async fn post_delete_app_environment_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(app): Extension<AppDto>,
+    State(state): State<AppState>,
+    Path((_app_id, environment_id)): Path<(String, String)>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    enforce_policy(&ctx.actor, Resource::App, Action::Update)?;
+
+    let result =
+        delete_app_environment_web_svc(&state, &app.id, &environment_id, &payload.token).await;
+
+    AppEnvironmentsTemplate::load(&state, app)
+        .await?
+        .into_response(result)
+}
+
 #[derive(Template)]
 #[template(path = "widgets/apps/delete_form.html")]
 struct DeleteAppFormTemplate {
@@ -0,0 +1,152 @@
+use axum::{
+    body::{Body, to_bytes},
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderValue, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::net::SocketAddr;
+use url::form_urlencoded;
+
+use crate::Error;
+use crate::run::AppState;
+use crate::services::auth_rate_limits::take_auth_attempt_svc;
+
+use super::login::client_ip;
+
+/// Forms that take credentials or recovery secrets
+const AUTH_RATE_LIMITED_PATHS: &[&str] = &[
+    "/login",
+    "/setup",
+    "/recover",
+    "/auth/forgot-password",
+    "/auth/reset-password",
+    "/invitations/accept",
+];
+
+/// These forms are a few fields, anything larger is not a real attempt
+const MAX_AUTH_FORM_BYTES: usize = 16 * 1024;
+
+/// Email submitted with the form, the login form calls it `username`
+fn form_email(body: &[u8]) -> Option<String> {
+    form_urlencoded::parse(body)
+        .find(|(key, _)| key == "username" || key == "email")
+        .map(|(_, value)| value.trim().to_lowercase())
+        .filter(|value| !value.is_empty())
+}
+
+/// Limits attempts on the sign in and recovery forms per client IP and per
+/// email, rejected attempts get a 429 with `Retry-After`
+pub async fn auth_rate_limit_middleware(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if req.method() != Method::POST || !AUTH_RATE_LIMITED_PATHS.contains(&req.uri().path()) {
+        return next.run(req).await;
+    }
+
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.0);
+    let ip_address = client_ip(req.headers(), peer);
+
+    // The body is read here to find the email, then handed back to the handler
+    let (parts, body) = req.into_parts();
+    let Ok(bytes) = to_bytes(body, MAX_AUTH_FORM_BYTES).await else {
+        return Error::Validation {
+            msg: "Form is too large.".to_string(),
+        }
+        .into_response();
+    };
+    let email = form_email(&bytes);
+
+    let decision = take_auth_attempt_svc(&state, ip_address.as_deref(), email.as_deref()).await;
+    if !decision.allowed {
+        let mut res = Error::RateLimitExceeded.into_response();
+        res.headers_mut()
+            .insert("retry-after", HeaderValue::from(decision.retry_after_secs));
+        return res;
+    }
+
+    next.run(Request::from_parts(parts, Body::from(bytes)))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, middleware, response::Response, routing::post};
+    use reqwest::StatusCode;
+    use std::sync::Arc;
+
+    use crate::error::ErrorInfo;
+    use crate::test::TestCtx;
+
+    use super::{auth_rate_limit_middleware, form_email};
+
+    #[tokio::test]
+    async fn login_attempts_are_limited_per_email() {
+        let ctx = TestCtx::new("auth_rate_limit").await.expect("test ctx");
+        let mut state = ctx.state.clone();
+        let mut config = (*state.config).clone();
+        config.auth_rate_limit.per_email = 2;
+        state.config = Arc::new(config);
+
+        let app = Router::new()
+            .route("/login", post(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                auth_rate_limit_middleware,
+            ))
+            // Stands in for the response mappers that render errors
+            .layer(middleware::map_response(|mut res: Response| async move {
+                if let Some(info) = res.extensions().get::<ErrorInfo>() {
+                    *res.status_mut() = info.status_code;
+                }
+                res
+            }))
+            .with_state(state);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener should bind");
+        let base_url = format!("http://{}", listener.local_addr().expect("local addr"));
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        let client = reqwest::Client::new();
+        let attempt = |email: &str| {
+            client
+                .post(format!("{}/login", base_url))
+                .form(&[("username", email), ("password", "wrong")])
+                .send()
+        };
+
+        for _ in 0..2 {
+            let res = attempt("jane@example.com").await.expect("request");
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(res.text().await.expect("body"), "ok");
+        }
+
+        let limited = attempt("JANE@example.com").await.expect("request");
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(limited.headers().contains_key("retry-after"));
+
+        let other = attempt("john@example.com").await.expect("request");
+        assert_eq!(other.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn form_email_reads_username_or_email() {
+        assert_eq!(
+            form_email(b"username=Jane%40Example.com&password=x"),
+            Some("jane@example.com".to_string())
+        );
+        assert_eq!(
+            form_email(b"email=jane%40example.com"),
+            Some("jane@example.com".to_string())
+        );
+        assert_eq!(form_email(b"token=abc&password=x"), None);
+    }
+}
@@ -0,0 +1,45 @@
+use askama::Template;
+use axum::{body::Body, response::Response};
+use snafu::ResultExt;
+
+use crate::Result;
+use crate::dto::BulkResultDto;
+use crate::error::{ErrorInfo, ResponseBuilderSnafu, TemplateSnafu};
+
+#[derive(Template)]
+#[template(path = "widgets/bulk_results.html")]
+struct BulkResultsTemplate {
+    result: BulkResultDto,
+    error_message: Option<String>,
+}
+
+/// Render per-item results of a bulk action, including partial failures.
+///
+/// On success, listing widgets are told to reload via the `bulkStatusUpdated` event.
+pub fn render_bulk_results(result: Result<BulkResultDto>) -> Result<Response<Body>> {
+    let mut tpl = BulkResultsTemplate {
+        result: BulkResultDto::default(),
+        error_message: None,
+    };
+
+    match result {
+        Ok(result) => {
+            tpl.result = result;
+
+            Response::builder()
+                .status(200)
+                .header("HX-Trigger", "bulkStatusUpdated")
+                .body(Body::from(tpl.render().context(TemplateSnafu)?))
+                .context(ResponseBuilderSnafu)
+        }
+        Err(err) => {
+            let error_info = ErrorInfo::from(&err);
+            tpl.error_message = Some(error_info.message);
+
+            Response::builder()
+                .status(error_info.status_code)
+                .body(Body::from(tpl.render().context(TemplateSnafu)?))
+                .context(ResponseBuilderSnafu)
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{StatusCode, header},
+    middleware::Next,
+    response::Response,
+};
+
+/// Fingerprinted assets: Vite bundles carry a content hash and vendor
+/// assets carry their version in the path, so their URLs never change content.
+const IMMUTABLE_PREFIXES: &[&str] = &["/assets/bundles/", "/assets/vendors/"];
+
+const IMMUTABLE_CACHE: &str = "public, max-age=31536000, immutable";
+
+/// Other static files keep stable URLs, browsers revalidate them with Last-Modified
+const REVALIDATE_CACHE: &str = "public, no-cache";
+
+pub fn asset_cache_control(path: &str) -> &'static str {
+    // The Vite manifest keeps its name across builds
+    if path.contains("/.vite/") {
+        return REVALIDATE_CACHE;
+    }
+
+    if IMMUTABLE_PREFIXES
+        .iter()
+        .any(|prefix| path.starts_with(prefix))
+    {
+        return IMMUTABLE_CACHE;
+    }
+
+    REVALIDATE_CACHE
+}
+
+/// Middleware to add Cache-Control headers to static asset responses
+pub async fn add_asset_cache_headers(req: Request, next: Next) -> Response<Body> {
+    let cache_control = asset_cache_control(req.uri().path());
+
+    let mut response = next.run(req).await;
+
+    // Never let caches hold on to missing files
+    let cacheable = response.status().is_success() || response.status() == StatusCode::NOT_MODIFIED;
+    if cacheable {
+        response.headers_mut().insert(
+            header::CACHE_CONTROL,
+            header::HeaderValue::from_static(cache_control),
+        );
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asset_cache_control() {
+        assert_eq!(
+            asset_cache_control("/assets/bundles/main-abc123.js"),
+            IMMUTABLE_CACHE
+        );
+        assert_eq!(
+            asset_cache_control("/assets/vendors/htmx/2.0.2/js/htmx.min.js"),
+            IMMUTABLE_CACHE
+        );
+        assert_eq!(
+            asset_cache_control("/assets/bundles/.vite/manifest.json"),
+            REVALIDATE_CACHE
+        );
+        assert_eq!(
+            asset_cache_control("/assets/images/logos/memories-logo-50x50.png"),
+            REVALIDATE_CACHE
+        );
+        assert_eq!(asset_cache_control("/favicon.ico"), REVALIDATE_CACHE);
+    }
+}
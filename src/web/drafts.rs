@@ -0,0 +1,110 @@
+use askama::Template;
+use axum::{
+    Extension, Router,
+    body::Body,
+    extract::{Form, Path, State},
+    http::{HeaderMap, Response, StatusCode},
+    routing::{get, post},
+};
+use snafu::{ResultExt, ensure};
+
+use crate::error::{ForbiddenSnafu, ResponseBuilderSnafu, TemplateSnafu};
+use crate::models::FormDraftView;
+use crate::services::drafts::{discard_draft_svc, get_draft_svc, save_draft_svc};
+use crate::{Result, ctx::Ctx, run::AppState};
+
+pub fn drafts_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route(
+            "/{form_key}",
+            post(save_draft_handler).delete(discard_draft_handler),
+        )
+        .route("/{form_key}/prompt", get(draft_prompt_handler))
+        .with_state(state)
+}
+
+/// Drafts are only written by the autosave requests, which cross site
+/// forms cannot send
+fn ensure_htmx_request(headers: &HeaderMap) -> Result<()> {
+    ensure!(
+        headers.contains_key("HX-Request"),
+        ForbiddenSnafu {
+            msg: "Drafts are saved by the page itself."
+        }
+    );
+    Ok(())
+}
+
+#[derive(Template)]
+#[template(path = "widgets/drafts/saved.html")]
+struct DraftSavedTemplate {
+    draft: FormDraftView,
+}
+
+async fn save_draft_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(form_key): Path<String>,
+    headers: HeaderMap,
+    Form(fields): Form<Vec<(String, String)>>,
+) -> Result<Response<Body>> {
+    ensure_htmx_request(&headers)?;
+    let actor = ctx.actor().expect("actor is required");
+
+    let draft = save_draft_svc(&state, &actor.id, &form_key, fields).await?;
+    let tpl = DraftSavedTemplate {
+        draft: FormDraftView::from(draft),
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+#[derive(Template)]
+#[template(path = "widgets/drafts/prompt.html")]
+struct DraftPromptTemplate {
+    draft: FormDraftView,
+}
+
+/// Offers to restore a draft when the user comes back to the form
+async fn draft_prompt_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(form_key): Path<String>,
+) -> Result<Response<Body>> {
+    let actor = ctx.actor().expect("actor is required");
+
+    let body = match get_draft_svc(&state, &actor.id, &form_key).await? {
+        Some(draft) => {
+            let tpl = DraftPromptTemplate {
+                draft: FormDraftView::from(draft),
+            };
+            tpl.render().context(TemplateSnafu)?
+        }
+        None => String::new(),
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(body))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn discard_draft_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(form_key): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response<Body>> {
+    ensure_htmx_request(&headers)?;
+    let actor = ctx.actor().expect("actor is required");
+
+    discard_draft_svc(&state, &actor.id, &form_key).await?;
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .body(Body::empty())
+        .context(ResponseBuilderSnafu)
+}
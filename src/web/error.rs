@@ -45,6 +45,7 @@ pub async fn error_handler(
             status_code: StatusCode::NOT_FOUND,
             title: String::from("Not Found"),
             message: String::from("The page you are looking for cannot be found."),
+            request_id: None,
         },
         true,
     )
@@ -0,0 +1,16 @@
+use axum::{Json, Router, http::StatusCode, routing::get};
+
+use crate::dto::EventSchemasDto;
+use crate::run::AppState;
+use crate::services::event_schemas::list_event_schemas_svc;
+
+pub fn event_schema_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/events/schemas", get(event_schemas_handler))
+        .with_state(state)
+}
+
+/// Publishes the payload schemas of the lifecycle events
+pub async fn event_schemas_handler() -> (StatusCode, Json<EventSchemasDto>) {
+    (StatusCode::OK, Json(list_event_schemas_svc()))
+}
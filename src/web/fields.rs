@@ -0,0 +1,72 @@
+use axum::Json;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::Result;
+use crate::dto::{BatchGetResultDto, CursorPage, Paginated};
+use crate::utils::{SparseFields, parse_fields, retain_fields};
+
+/// Optional `?fields=id,email` selection shared by the JSON endpoints
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct FieldsQuery {
+    pub fields: Option<String>,
+}
+
+impl FieldsQuery {
+    /// Validated selection for the DTO, `None` means all fields
+    fn selection<T: SparseFields>(&self) -> Result<Option<Vec<String>>> {
+        match self.fields.as_deref() {
+            Some(raw) => Ok(Some(parse_fields(raw, T::FIELDS)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn item<T: SparseFields>(&self, item: T) -> Result<Json<Value>> {
+        let selection = self.selection::<T>()?;
+        let mut value = serde_json::to_value(item).expect("DTO should serialize");
+        if let Some(fields) = selection {
+            retain_fields(&mut value, &fields);
+        }
+        Ok(Json(value))
+    }
+
+    pub fn list<T: SparseFields>(&self, items: Vec<T>) -> Result<Json<Value>> {
+        let selection = self.selection::<T>()?;
+        let mut value = serde_json::to_value(items).expect("DTOs should serialize");
+        if let (Some(fields), Value::Array(items)) = (selection, &mut value) {
+            items
+                .iter_mut()
+                .for_each(|item| retain_fields(item, &fields));
+        }
+        Ok(Json(value))
+    }
+
+    /// Filters the records of a page, the pagination meta is always kept
+    pub fn page<T: SparseFields>(&self, page: Paginated<T>) -> Result<Json<Value>> {
+        let value = serde_json::to_value(page).expect("page should serialize");
+        self.records::<T>(value, "data")
+    }
+
+    /// Filters the records of a keyset page, `next_cursor` is always kept
+    pub fn cursor_page<T: SparseFields>(&self, page: CursorPage<T>) -> Result<Json<Value>> {
+        let value = serde_json::to_value(page).expect("page should serialize");
+        self.records::<T>(value, "data")
+    }
+
+    /// Filters the records of a batch get, `missing_ids` is always kept
+    pub fn batch<T: SparseFields>(&self, result: BatchGetResultDto<T>) -> Result<Json<Value>> {
+        let value = serde_json::to_value(result).expect("batch should serialize");
+        self.records::<T>(value, "items")
+    }
+
+    /// Filters the array of records under `key`, other keys are kept
+    fn records<T: SparseFields>(&self, mut value: Value, key: &str) -> Result<Json<Value>> {
+        let selection = self.selection::<T>()?;
+        if let (Some(fields), Some(Value::Array(items))) = (selection, value.get_mut(key)) {
+            items
+                .iter_mut()
+                .for_each(|item| retain_fields(item, &fields));
+        }
+        Ok(Json(value))
+    }
+}
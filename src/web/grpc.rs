@@ -0,0 +1,759 @@
+use axum::Router;
+use snafu::{OptionExt, ensure};
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tonic::body::Body;
+use tonic::codegen::{Service, http};
+use tonic::server::{Grpc, UnaryService};
+use tonic::{Request, Response, Status};
+use tonic_prost::ProstCodec;
+
+use crate::Result;
+use crate::ctx::Ctx;
+use crate::db::DeletedScope;
+use crate::dto::{
+    ListAppsParamsDto, ListOrgMembersParamsDto, ListOrgsParamsDto, ListUsersParamsDto, rpc,
+};
+use crate::error::{
+    AppNotFoundSnafu, OrgMemberNotFoundSnafu, OrgNotFoundSnafu, UserNotFoundSnafu, ValidationSnafu,
+    VersionRequiredSnafu,
+};
+use crate::run::AppState;
+use crate::services::apps::{
+    create_app_svc, get_app_scoped_svc, list_apps_scoped_svc, update_app_tracked_svc,
+};
+use crate::services::org_members::{
+    create_org_member_svc, delete_org_member_svc, get_org_member_svc, list_org_members_svc,
+    update_org_member_svc,
+};
+use crate::services::orgs::{
+    create_org_svc, get_org_scoped_svc, get_org_svc, list_orgs_scoped_svc, update_org_tracked_svc,
+};
+use crate::services::users::{
+    create_user_svc, get_user_scoped_svc, list_users_scoped_svc, update_user_tracked_svc,
+};
+use crate::validators::validate_payload;
+
+use super::admin_api::admin_actor;
+
+/// gRPC services of proto/admin.proto, served next to the HTTP routes.
+///
+/// Calls take the same superuser bearer token as the admin API, passed in the
+/// `authorization` metadata.
+pub fn grpc_routes(state: AppState) -> Router {
+    Router::new()
+        .route_service(UserService::PATH, UserService::new(state.clone()))
+        .route_service(OrgService::PATH, OrgService::new(state.clone()))
+        .route_service(AppService::PATH, AppService::new(state.clone()))
+        .route_service(OrgMemberService::PATH, OrgMemberService::new(state))
+}
+
+/// Declares a gRPC service dispatching its methods to the given handlers
+macro_rules! grpc_service {
+    ($service:ident, $name:literal, { $($method:literal => $handler:ident),+ $(,)? }) => {
+        #[derive(Clone)]
+        struct $service {
+            state: AppState,
+        }
+
+        impl $service {
+            const PATH: &'static str = concat!("/", $name, "/{*method}");
+
+            fn new(state: AppState) -> Self {
+                Self { state }
+            }
+        }
+
+        impl Service<http::Request<axum::body::Body>> for $service {
+            type Response = http::Response<Body>;
+            type Error = Infallible;
+            type Future = Pin<
+                Box<dyn Future<Output = core::result::Result<Self::Response, Infallible>> + Send>,
+            >;
+
+            fn poll_ready(
+                &mut self,
+                _cx: &mut Context<'_>,
+            ) -> Poll<core::result::Result<(), Infallible>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn call(&mut self, req: http::Request<axum::body::Body>) -> Self::Future {
+                let state = self.state.clone();
+                match req.uri().path().strip_prefix(concat!("/", $name, "/")) {
+                    $(Some($method) => Box::pin(async move { Ok(unary(state, req, $handler).await) }),)+
+                    _ => Box::pin(async { Ok(Status::unimplemented("Unknown method").into_http()) }),
+                }
+            }
+        }
+    };
+}
+
+grpc_service!(UserService, "yaas.admin.v1.UserService", {
+    "ListUsers" => list_users,
+    "GetUser" => get_user,
+    "CreateUser" => create_user,
+    "UpdateUser" => update_user,
+});
+
+grpc_service!(OrgService, "yaas.admin.v1.OrgService", {
+    "ListOrgs" => list_orgs,
+    "GetOrg" => get_org,
+    "CreateOrg" => create_org,
+    "UpdateOrg" => update_org,
+});
+
+grpc_service!(AppService, "yaas.admin.v1.AppService", {
+    "ListApps" => list_apps,
+    "GetApp" => get_app,
+    "CreateApp" => create_app,
+    "UpdateApp" => update_app,
+});
+
+grpc_service!(OrgMemberService, "yaas.admin.v1.OrgMemberService", {
+    "ListOrgMembers" => list_org_members,
+    "GetOrgMember" => get_org_member,
+    "CreateOrgMember" => create_org_member,
+    "UpdateOrgMember" => update_org_member,
+    "DeleteOrgMember" => delete_org_member,
+});
+
+/// Handles one call, authenticated and audited like the admin API
+async fn unary<Req, Res, F, Fut>(
+    state: AppState,
+    req: http::Request<axum::body::Body>,
+    handler: F,
+) -> http::Response<Body>
+where
+    Req: prost::Message + Default + Send + 'static,
+    Res: prost::Message + Send + 'static,
+    F: FnOnce(AppState, Ctx, Req) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Res>> + Send,
+{
+    let method = Method(Some(move |request: Request<Req>| async move {
+        let headers = request.metadata().clone().into_headers();
+        let ctx = Ctx::new(admin_actor(&state, &headers).await?);
+        let audit = ctx.audit();
+        let res = audit
+            .scope(handler(state, ctx, request.into_inner()))
+            .await?;
+        Ok::<_, Status>(Response::new(res))
+    }));
+
+    let mut grpc = Grpc::new(ProstCodec::<Res, Req>::default());
+    grpc.unary(method, req).await
+}
+
+/// Single use [`UnaryService`] wrapping the closure of a call
+struct Method<F>(Option<F>);
+
+impl<Req, Res, F, Fut> UnaryService<Req> for Method<F>
+where
+    F: FnOnce(Request<Req>) -> Fut,
+    Fut: Future<Output = core::result::Result<Response<Res>, Status>>,
+{
+    type Response = Res;
+    type Future = Fut;
+
+    fn call(&mut self, request: Request<Req>) -> Self::Future {
+        let handler = self.0.take().expect("Method must be called once");
+        handler(request)
+    }
+}
+
+/// Same rule as the PATCH routes, `any_version` stands in for `If-Match: *`
+fn expected_version(version: Option<i64>, any_version: bool) -> Result<Option<i64>> {
+    if any_version {
+        return Ok(None);
+    }
+    version.context(VersionRequiredSnafu).map(Some)
+}
+
+async fn list_users(
+    state: AppState,
+    _ctx: Ctx,
+    req: rpc::ListRequest,
+) -> Result<rpc::ListUsersResponse> {
+    let scope = DeletedScope::include_deleted(req.include_deleted);
+    let params = ListUsersParamsDto::from(req);
+    validate_payload(&params)?;
+    let page = list_users_scoped_svc(&state, params, scope).await?;
+    Ok(rpc::ListUsersResponse {
+        meta: Some(page.meta.into()),
+        users: page.data.into_iter().map(Into::into).collect(),
+    })
+}
+
+async fn get_user(state: AppState, _ctx: Ctx, req: rpc::GetRequest) -> Result<rpc::User> {
+    let scope = DeletedScope::include_deleted(req.include_deleted);
+    let user = get_user_scoped_svc(&state, &req.id, scope)
+        .await?
+        .context(UserNotFoundSnafu)?;
+    Ok(user.into())
+}
+
+async fn create_user(state: AppState, _ctx: Ctx, req: rpc::CreateUserRequest) -> Result<rpc::User> {
+    Ok(create_user_svc(&state, req.into()).await?.into())
+}
+
+async fn update_user(
+    state: AppState,
+    ctx: Ctx,
+    req: rpc::UpdateUserRequest,
+) -> Result<rpc::UpdateUserResponse> {
+    let version = expected_version(req.version, req.any_version)?;
+    let actor = ctx.actor().expect("actor is required");
+    ensure!(
+        actor.user.id != req.id || req.status.is_none(),
+        ValidationSnafu {
+            msg: "Cannot change your own status."
+        }
+    );
+
+    let id = req.id.clone();
+    let updated = update_user_tracked_svc(&state, &id, req.into(), version).await?;
+    Ok(rpc::UpdateUserResponse {
+        user: Some(updated.data.into()),
+        changed_fields: updated.changed_fields,
+    })
+}
+
+async fn list_orgs(
+    state: AppState,
+    _ctx: Ctx,
+    req: rpc::ListRequest,
+) -> Result<rpc::ListOrgsResponse> {
+    let scope = DeletedScope::include_deleted(req.include_deleted);
+    let params = ListOrgsParamsDto::from(req);
+    validate_payload(&params)?;
+    let page = list_orgs_scoped_svc(&state, params, scope).await?;
+    Ok(rpc::ListOrgsResponse {
+        meta: Some(page.meta.into()),
+        orgs: page.data.into_iter().map(Into::into).collect(),
+    })
+}
+
+async fn get_org(state: AppState, _ctx: Ctx, req: rpc::GetRequest) -> Result<rpc::Org> {
+    let scope = DeletedScope::include_deleted(req.include_deleted);
+    let org = get_org_scoped_svc(&state, &req.id, scope)
+        .await?
+        .context(OrgNotFoundSnafu)?;
+    Ok(org.into())
+}
+
+async fn create_org(state: AppState, _ctx: Ctx, req: rpc::CreateOrgRequest) -> Result<rpc::Org> {
+    Ok(create_org_svc(&state, req.into()).await?.into())
+}
+
+async fn update_org(
+    state: AppState,
+    _ctx: Ctx,
+    req: rpc::UpdateOrgRequest,
+) -> Result<rpc::UpdateOrgResponse> {
+    let version = expected_version(req.version, req.any_version)?;
+    let id = req.id.clone();
+    let updated = update_org_tracked_svc(&state, &id, req.into(), version).await?;
+    Ok(rpc::UpdateOrgResponse {
+        org: Some(updated.data.into()),
+        changed_fields: updated.changed_fields,
+    })
+}
+
+async fn list_apps(
+    state: AppState,
+    _ctx: Ctx,
+    req: rpc::ListRequest,
+) -> Result<rpc::ListAppsResponse> {
+    let scope = DeletedScope::include_deleted(req.include_deleted);
+    let params = ListAppsParamsDto::from(req);
+    validate_payload(&params)?;
+    let page = list_apps_scoped_svc(&state, params, scope).await?;
+    Ok(rpc::ListAppsResponse {
+        meta: Some(page.meta.into()),
+        apps: page.data.into_iter().map(Into::into).collect(),
+    })
+}
+
+async fn get_app(state: AppState, _ctx: Ctx, req: rpc::GetRequest) -> Result<rpc::App> {
+    let scope = DeletedScope::include_deleted(req.include_deleted);
+    let app = get_app_scoped_svc(&state, &req.id, scope)
+        .await?
+        .context(AppNotFoundSnafu)?;
+    Ok(app.into())
+}
+
+async fn create_app(state: AppState, _ctx: Ctx, req: rpc::CreateAppRequest) -> Result<rpc::App> {
+    Ok(create_app_svc(&state, req.into()).await?.into())
+}
+
+async fn update_app(
+    state: AppState,
+    _ctx: Ctx,
+    req: rpc::UpdateAppRequest,
+) -> Result<rpc::UpdateAppResponse> {
+    let version = expected_version(req.version, req.any_version)?;
+    let id = req.id.clone();
+    let updated = update_app_tracked_svc(&state, &id, req.into(), version).await?;
+    Ok(rpc::UpdateAppResponse {
+        app: Some(updated.data.into()),
+        changed_fields: updated.changed_fields,
+    })
+}
+
+async fn list_org_members(
+    state: AppState,
+    _ctx: Ctx,
+    req: rpc::ListOrgMembersRequest,
+) -> Result<rpc::ListOrgMembersResponse> {
+    get_org_svc(&state, &req.org_id)
+        .await?
+        .context(OrgNotFoundSnafu)?;
+    let org_id = req.org_id.clone();
+    let params = ListOrgMembersParamsDto::from(req);
+    validate_payload(&params)?;
+    let page = list_org_members_svc(&state, &org_id, params).await?;
+    Ok(rpc::ListOrgMembersResponse {
+        meta: Some(page.meta.into()),
+        members: page.data.into_iter().map(Into::into).collect(),
+    })
+}
+
+async fn get_org_member(
+    state: AppState,
+    _ctx: Ctx,
+    req: rpc::OrgMemberRequest,
+) -> Result<rpc::OrgMember> {
+    let member = get_org_member_svc(&state, &req.org_id, &req.user_id)
+        .await?
+        .context(OrgMemberNotFoundSnafu)?;
+    Ok(member.into())
+}
+
+async fn create_org_member(
+    state: AppState,
+    _ctx: Ctx,
+    req: rpc::CreateOrgMemberRequest,
+) -> Result<rpc::OrgMember> {
+    get_org_svc(&state, &req.org_id)
+        .await?
+        .context(OrgNotFoundSnafu)?;
+    let org_id = req.org_id.clone();
+    Ok(create_org_member_svc(&state, &org_id, req.into())
+        .await?
+        .into())
+}
+
+async fn update_org_member(
+    state: AppState,
+    _ctx: Ctx,
+    req: rpc::UpdateOrgMemberRequest,
+) -> Result<rpc::OrgMember> {
+    let (org_id, user_id) = (req.org_id.clone(), req.user_id.clone());
+    let member = get_org_member_svc(&state, &org_id, &user_id)
+        .await?
+        .context(OrgMemberNotFoundSnafu)?;
+    update_org_member_svc(&state, &member.id, req.into()).await?;
+
+    let member = get_org_member_svc(&state, &org_id, &user_id)
+        .await?
+        .context(OrgMemberNotFoundSnafu)?;
+    Ok(member.into())
+}
+
+async fn delete_org_member(
+    state: AppState,
+    _ctx: Ctx,
+    req: rpc::OrgMemberRequest,
+) -> Result<rpc::Empty> {
+    let member = get_org_member_svc(&state, &req.org_id, &req.user_id)
+        .await?
+        .context(OrgMemberNotFoundSnafu)?;
+    delete_org_member_svc(&state, &member.id).await?;
+    Ok(rpc::Empty {})
+}
+
+#[cfg(test)]
+mod tests {
+    use tonic::codegen::http::uri::PathAndQuery;
+    use tonic::transport::Channel;
+    use tonic::{Code, Request, Status};
+    use tonic_prost::ProstCodec;
+
+    use crate::dto::{CredentialsDto, SessionClientDto, rpc};
+    use crate::services::auth::{authenticate, issue_superuser_token_svc};
+    use crate::test::TestCtx;
+
+    use super::grpc_routes;
+
+    /// Serves the gRPC services on a local port, returns a channel to it
+    async fn spawn_grpc(ctx: &TestCtx) -> Channel {
+        let app = grpc_routes(ctx.state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener should bind");
+        let addr = listener.local_addr().expect("local addr");
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        Channel::from_shared(format!("http://{}", addr))
+            .expect("channel uri")
+            .connect()
+            .await
+            .expect("channel should connect")
+    }
+
+    async fn call<Req, Res>(
+        channel: &Channel,
+        token: Option<&str>,
+        path: &'static str,
+        message: Req,
+    ) -> Result<Res, Status>
+    where
+        Req: prost::Message + Send + Sync + 'static,
+        Res: prost::Message + Default + Send + Sync + 'static,
+    {
+        let mut client = tonic::client::Grpc::new(channel.clone());
+        client.ready().await.expect("client ready");
+
+        let mut request = Request::new(message);
+        if let Some(token) = token {
+            let value = format!("Bearer {}", token).parse().expect("metadata value");
+            request.metadata_mut().insert("authorization", value);
+        }
+
+        let codec = ProstCodec::<Req, Res>::default();
+        let response = client
+            .unary(request, PathAndQuery::from_static(path), codec)
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    #[tokio::test]
+    async fn grpc_requires_superuser_token() {
+        let ctx = TestCtx::new("grpc_guard").await.expect("test ctx");
+        ctx.seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Regular User",
+                "regular@example.com",
+                "password123",
+                "Regular Org",
+            )
+            .await
+            .expect("auth fixture");
+        let channel = spawn_grpc(&ctx).await;
+        let path = "/yaas.admin.v1.UserService/ListUsers";
+
+        let anonymous =
+            call::<_, rpc::ListUsersResponse>(&channel, None, path, rpc::ListRequest::default())
+                .await
+                .expect_err("anonymous call");
+        assert_eq!(anonymous.code(), Code::Unauthenticated);
+
+        let auth = authenticate(
+            &ctx.state,
+            &CredentialsDto {
+                email: fixture.email.clone(),
+                password: fixture.password.clone(),
+            },
+            SessionClientDto::default(),
+        )
+        .await
+        .expect("login");
+        let regular = call::<_, rpc::ListUsersResponse>(
+            &channel,
+            Some(&auth.token),
+            path,
+            rpc::ListRequest::default(),
+        )
+        .await
+        .expect_err("regular user call");
+        assert_eq!(regular.code(), Code::PermissionDenied);
+
+        let token =
+            issue_superuser_token_svc(&ctx.state.db, &ctx.state.token_keys, "root@example.com")
+                .await
+                .expect("admin token");
+        let unknown = call::<_, rpc::Empty>(
+            &channel,
+            Some(&token),
+            "/yaas.admin.v1.UserService/DeleteUser",
+            rpc::Empty {},
+        )
+        .await
+        .expect_err("unknown method");
+        assert_eq!(unknown.code(), Code::Unimplemented);
+    }
+
+    #[tokio::test]
+    async fn grpc_manages_users_and_orgs() {
+        let ctx = TestCtx::new("grpc_users_orgs").await.expect("test ctx");
+        ctx.seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+        let token =
+            issue_superuser_token_svc(&ctx.state.db, &ctx.state.token_keys, "root@example.com")
+                .await
+                .expect("admin token");
+        let token = Some(token.as_str());
+        let channel = spawn_grpc(&ctx).await;
+
+        let user: rpc::User = call(
+            &channel,
+            token,
+            "/yaas.admin.v1.UserService/CreateUser",
+            rpc::CreateUserRequest {
+                email: "owner@example.com".to_string(),
+                name: "Owner User".to_string(),
+                password: "password123".to_string(),
+            },
+        )
+        .await
+        .expect("create user");
+        assert_eq!(user.email, "owner@example.com");
+
+        let invalid = call::<_, rpc::User>(
+            &channel,
+            token,
+            "/yaas.admin.v1.UserService/CreateUser",
+            rpc::CreateUserRequest {
+                email: "not an email".to_string(),
+                name: "Invalid".to_string(),
+                password: "password123".to_string(),
+            },
+        )
+        .await
+        .expect_err("invalid email");
+        assert_eq!(invalid.code(), Code::InvalidArgument);
+
+        let found: rpc::User = call(
+            &channel,
+            token,
+            "/yaas.admin.v1.UserService/GetUser",
+            rpc::GetRequest {
+                id: user.id.clone(),
+                include_deleted: false,
+            },
+        )
+        .await
+        .expect("get user");
+        assert_eq!(found, user);
+
+        let unversioned = call::<_, rpc::UpdateUserResponse>(
+            &channel,
+            token,
+            "/yaas.admin.v1.UserService/UpdateUser",
+            rpc::UpdateUserRequest {
+                id: user.id.clone(),
+                name: Some("Renamed".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect_err("version is required");
+        assert_eq!(unversioned.code(), Code::FailedPrecondition);
+
+        let updated: rpc::UpdateUserResponse = call(
+            &channel,
+            token,
+            "/yaas.admin.v1.UserService/UpdateUser",
+            rpc::UpdateUserRequest {
+                id: user.id.clone(),
+                version: Some(user.updated_at),
+                name: Some("Renamed".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("update user");
+        assert_eq!(updated.user.expect("user").name, "Renamed");
+        assert!(updated.changed_fields.contains(&"name".to_string()));
+
+        let org: rpc::Org = call(
+            &channel,
+            token,
+            "/yaas.admin.v1.OrgService/CreateOrg",
+            rpc::CreateOrgRequest {
+                name: "Grpc Org".to_string(),
+                owner_id: user.id.clone(),
+            },
+        )
+        .await
+        .expect("create org");
+        assert_eq!(org.owner_id.as_deref(), Some(user.id.as_str()));
+
+        let orgs: rpc::ListOrgsResponse = call(
+            &channel,
+            token,
+            "/yaas.admin.v1.OrgService/ListOrgs",
+            rpc::ListRequest {
+                keyword: Some("grpc".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("list orgs");
+        assert_eq!(orgs.meta.expect("meta").total_records, 1);
+        assert_eq!(orgs.orgs[0].id, org.id);
+
+        let missing = call::<_, rpc::Org>(
+            &channel,
+            token,
+            "/yaas.admin.v1.OrgService/GetOrg",
+            rpc::GetRequest {
+                id: "missing".to_string(),
+                include_deleted: false,
+            },
+        )
+        .await
+        .expect_err("missing org");
+        assert_eq!(missing.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn grpc_manages_apps_and_members() {
+        let ctx = TestCtx::new("grpc_apps_members").await.expect("test ctx");
+        ctx.seed_superuser("root@example.com")
+            .await
+            .expect("superuser");
+        let fixture = ctx
+            .seed_auth_fixture(
+                "Org Owner",
+                "org-owner@example.com",
+                "password123",
+                "Member Org",
+            )
+            .await
+            .expect("auth fixture");
+        let member_user = ctx
+            .seed_user_with_password("Member User", "member@example.com", "password123")
+            .await
+            .expect("member user");
+        let token =
+            issue_superuser_token_svc(&ctx.state.db, &ctx.state.token_keys, "root@example.com")
+                .await
+                .expect("admin token");
+        let token = Some(token.as_str());
+        let channel = spawn_grpc(&ctx).await;
+
+        let app: rpc::App = call(
+            &channel,
+            token,
+            "/yaas.admin.v1.AppService/CreateApp",
+            rpc::CreateAppRequest {
+                name: "Grpc App".to_string(),
+                redirect_uri: "https://app.example.com/callback".to_string(),
+                scopes: None,
+            },
+        )
+        .await
+        .expect("create app");
+        assert!(!app.client_secret.is_empty());
+
+        let found: rpc::App = call(
+            &channel,
+            token,
+            "/yaas.admin.v1.AppService/GetApp",
+            rpc::GetRequest {
+                id: app.id.clone(),
+                include_deleted: false,
+            },
+        )
+        .await
+        .expect("get app");
+        assert!(found.client_secret.is_empty());
+
+        let updated: rpc::UpdateAppResponse = call(
+            &channel,
+            token,
+            "/yaas.admin.v1.AppService/UpdateApp",
+            rpc::UpdateAppRequest {
+                id: app.id.clone(),
+                any_version: true,
+                name: Some("Renamed App".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("update app");
+        assert_eq!(updated.app.expect("app").name, "Renamed App");
+
+        let org_id = fixture.org.id.clone();
+        let member: rpc::OrgMember = call(
+            &channel,
+            token,
+            "/yaas.admin.v1.OrgMemberService/CreateOrgMember",
+            rpc::CreateOrgMemberRequest {
+                org_id: org_id.clone(),
+                user_id: member_user.id.clone(),
+                roles: vec!["OrgViewer".to_string()],
+                status: "active".to_string(),
+            },
+        )
+        .await
+        .expect("create member");
+        assert_eq!(member.roles, vec!["OrgViewer".to_string()]);
+
+        let member: rpc::OrgMember = call(
+            &channel,
+            token,
+            "/yaas.admin.v1.OrgMemberService/UpdateOrgMember",
+            rpc::UpdateOrgMemberRequest {
+                org_id: org_id.clone(),
+                user_id: member_user.id.clone(),
+                roles: Some(rpc::Roles {
+                    roles: vec!["OrgEditor".to_string()],
+                }),
+                status: None,
+            },
+        )
+        .await
+        .expect("update member");
+        assert_eq!(member.roles, vec!["OrgEditor".to_string()]);
+
+        let members: rpc::ListOrgMembersResponse = call(
+            &channel,
+            token,
+            "/yaas.admin.v1.OrgMemberService/ListOrgMembers",
+            rpc::ListOrgMembersRequest {
+                org_id: org_id.clone(),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("list members");
+        assert!(
+            members
+                .members
+                .iter()
+                .any(|listed| listed.user_id == member_user.id)
+        );
+
+        let request = rpc::OrgMemberRequest {
+            org_id: org_id.clone(),
+            user_id: member_user.id.clone(),
+        };
+        let _: rpc::Empty = call(
+            &channel,
+            token,
+            "/yaas.admin.v1.OrgMemberService/DeleteOrgMember",
+            request.clone(),
+        )
+        .await
+        .expect("delete member");
+        let deleted = call::<_, rpc::OrgMember>(
+            &channel,
+            token,
+            "/yaas.admin.v1.OrgMemberService/GetOrgMember",
+            request,
+        )
+        .await
+        .expect_err("deleted member");
+        assert_eq!(deleted.code(), Code::NotFound);
+    }
+}
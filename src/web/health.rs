@@ -7,6 +7,8 @@ use crate::{
 
 pub fn health_api_routes(state: AppState) -> Router {
     Router::new()
+        .route("/healthz", get(health_liveness_handler))
+        .route("/readyz", get(health_readiness_handler))
         .route("/health/live", get(health_liveness_handler))
         .route("/health/ready", get(health_readiness_handler))
         .with_state(state)
@@ -39,6 +41,7 @@ pub async fn health_readiness_handler(State(state): State<AppState>) -> impl Int
                 status: "DOWN".to_string(),
                 message: format!("Readiness check failed: {}", err),
                 checks: HealthChecks::new(),
+                pending_migrations: Vec::new(),
             }),
         ),
     }
@@ -0,0 +1,166 @@
+use axum::{
+    Extension,
+    body::{Body, to_bytes},
+    extract::{OriginalUri, Request, State},
+    http::{HeaderValue, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use snafu::ensure;
+use tracing::warn;
+
+use crate::dto::IdempotentResponseDto;
+use crate::error::{BadRequestSnafu, ErrorInfo};
+use crate::services::idempotency::{
+    IdempotencyOutcome, IdempotencyScope, complete_idempotency_key_svc, idempotency_body_hash,
+    release_idempotency_key_svc, reserve_idempotency_key_svc,
+};
+use crate::{Result, ctx::Ctx, run::AppState};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Set on responses replayed from a previous request with the same key
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+const IDEMPOTENCY_KEY_MAX_LEN: usize = 255;
+
+/// Same as the default body limit of the JSON extractor
+const IDEMPOTENT_REQUEST_MAX_BYTES: usize = 2 * 1024 * 1024;
+
+/// Response extension of handlers returning a secret shown once.
+///
+/// Only the status and a hash of such a response are stored, a retry with
+/// the same key gets a conflict instead of the secret.
+#[derive(Clone, Copy, Debug)]
+pub struct SecretResponse;
+
+/// Replays the first response of a POST sent with an `Idempotency-Key` header.
+///
+/// Must run after authentication, keys are scoped to the actor and the route.
+/// Only successful responses are kept, a failed request frees its key.
+/// Responses marked with [`SecretResponse`] are never replayed.
+pub async fn idempotency_middleware(
+    State(state): State<AppState>,
+    ctx: Option<Extension<Ctx>>,
+    req: Request,
+    next: Next,
+) -> Result<Response> {
+    if req.method() != Method::POST {
+        return Ok(next.run(req).await);
+    }
+
+    let key = req
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .map(|value| value.to_str().unwrap_or_default().trim().to_string());
+    let user_id = ctx.and_then(|Extension(ctx)| ctx.actor.actor.map(|actor| actor.user.id));
+
+    let (Some(key), Some(user_id)) = (key, user_id) else {
+        return Ok(next.run(req).await);
+    };
+
+    ensure!(
+        !key.is_empty() && key.len() <= IDEMPOTENCY_KEY_MAX_LEN,
+        BadRequestSnafu {
+            msg: "Idempotency-Key must be 1 to 255 visible ASCII characters."
+        }
+    );
+
+    let route = match req.extensions().get::<OriginalUri>() {
+        Some(uri) => uri.path().to_string(),
+        None => req.uri().path().to_string(),
+    };
+    let scope = IdempotencyScope {
+        user_id,
+        route,
+        key,
+    };
+
+    let (parts, body) = req.into_parts();
+    let Ok(bytes) = to_bytes(body, IDEMPOTENT_REQUEST_MAX_BYTES).await else {
+        return BadRequestSnafu {
+            msg: "Request body is too large.",
+        }
+        .fail();
+    };
+
+    let request_hash = idempotency_body_hash(&bytes);
+    if let IdempotencyOutcome::Replay(response) =
+        reserve_idempotency_key_svc(&state, &scope, request_hash).await?
+    {
+        return Ok(replay_response(response));
+    }
+
+    let res = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+
+    // Errors are only turned into JSON by the response mapper, nothing to keep
+    let stored = res.status().is_success() && res.extensions().get::<ErrorInfo>().is_none();
+    if !stored {
+        release_idempotency_key_svc(&state, &scope).await?;
+        return Ok(res);
+    }
+
+    let secret = res.extensions().get::<SecretResponse>().is_some();
+    let (parts, body) = res.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            release_idempotency_key_svc(&state, &scope).await?;
+            warn!(
+                "Unable to read the response of an idempotent request: {}",
+                e
+            );
+            return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+        }
+    };
+
+    let body = match secret {
+        true => Ok(None),
+        false => String::from_utf8(bytes.to_vec()).map(Some),
+    };
+
+    match body {
+        Ok(body) => {
+            let content_type = parts
+                .headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string());
+
+            complete_idempotency_key_svc(
+                &state,
+                &scope,
+                IdempotentResponseDto {
+                    status_code: parts.status.as_u16() as i64,
+                    content_type,
+                    body,
+                    response_hash: idempotency_body_hash(&bytes),
+                },
+            )
+            .await?;
+        }
+        Err(_) => release_idempotency_key_svc(&state, &scope).await?,
+    }
+
+    Ok(Response::from_parts(parts, Body::from(bytes)))
+}
+
+fn replay_response(response: IdempotentResponseDto) -> Response {
+    let status = u16::try_from(response.status_code)
+        .ok()
+        .and_then(|code| StatusCode::from_u16(code).ok())
+        .unwrap_or(StatusCode::OK);
+
+    let mut res = (status, response.body.unwrap_or_default()).into_response();
+    if let Some(value) = response
+        .content_type
+        .and_then(|value| HeaderValue::from_str(&value).ok())
+    {
+        res.headers_mut().insert(header::CONTENT_TYPE, value);
+    }
+    res.headers_mut()
+        .insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    res
+}
@@ -0,0 +1,129 @@
+use axum::{
+    Extension, Form,
+    extract::{OriginalUri, Request, State},
+    http::{HeaderValue, Method},
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+};
+use snafu::OptionExt;
+use tower_cookies::{Cookie, Cookies, cookie::time::Duration};
+
+use crate::{
+    Result,
+    ctx::Ctx,
+    error::LoginRequiredSnafu,
+    models::TokenFormData,
+    run::AppState,
+    services::{
+        impersonations::{record_impersonation_action, stop_impersonation_web_svc},
+        token::verify_auth_token,
+    },
+    web::bearer_token,
+};
+
+use super::{AUTH_TOKEN_COOKIE, IMPERSONATOR_TOKEN_COOKIE};
+
+/// Response header naming the superuser behind an impersonated request
+pub const IMPERSONATED_BY_HEADER: &str = "X-Impersonated-By";
+
+/// Marks the response and records the request in the audit trail.
+///
+/// Only requests that may change something are recorded, page views are not.
+pub(crate) async fn audit_impersonated_response(
+    state: &AppState,
+    impersonation_id: &str,
+    impersonator_id: &str,
+    method: &Method,
+    path: &str,
+    mut response: Response,
+) -> Response {
+    if !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        record_impersonation_action(
+            state,
+            impersonation_id,
+            method.as_str(),
+            path,
+            response.status().as_u16(),
+        )
+        .await;
+    }
+
+    if let Ok(value) = HeaderValue::from_str(impersonator_id) {
+        response.headers_mut().insert(IMPERSONATED_BY_HEADER, value);
+    }
+    response
+}
+
+/// Path of the request as sent, nested routers strip their prefix
+pub(crate) fn request_path(req: &Request) -> String {
+    req.extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string())
+}
+
+/// Audits JSON API requests made with an impersonation token.
+///
+/// API handlers authenticate themselves, the token is only peeked here to tell
+/// whether it was issued for an impersonation.
+pub async fn impersonation_api_middleware(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let impersonation = bearer_token(req.headers())
+        .and_then(|token| verify_auth_token(&token, &state.token_keys).ok())
+        .and_then(|payload| payload.impersonation_id.zip(payload.impersonator_id));
+
+    let Some((impersonation_id, impersonator_id)) = impersonation else {
+        return next.run(req).await;
+    };
+
+    let method = req.method().clone();
+    let path = request_path(&req);
+    let response = next.run(req).await;
+
+    audit_impersonated_response(
+        &state,
+        &impersonation_id,
+        &impersonator_id,
+        &method,
+        &path,
+        response,
+    )
+    .await
+}
+
+/// Ends the impersonation and signs the superuser back in
+pub async fn post_stop_impersonating_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    cookies: Cookies,
+    payload: Form<TokenFormData>,
+) -> Result<Response> {
+    let actor = ctx.actor().context(LoginRequiredSnafu)?;
+    let impersonation = stop_impersonation_web_svc(&state, actor, &payload.token).await?;
+
+    let Some(impersonator_token) = cookies.get(IMPERSONATOR_TOKEN_COOKIE) else {
+        // Started from the API, there is no session to go back to
+        cookies.remove(Cookie::new(AUTH_TOKEN_COOKIE, ""));
+        return Ok(Redirect::to("/login").into_response());
+    };
+
+    let auth_cookie = Cookie::build((AUTH_TOKEN_COOKIE, impersonator_token.value().to_string()))
+        .http_only(true)
+        .max_age(Duration::weeks(1))
+        .secure(state.config.server.https)
+        .same_site(tower_cookies::cookie::SameSite::Strict)
+        .path("/")
+        .build();
+
+    cookies.add(auth_cookie);
+    cookies.remove(
+        Cookie::build((IMPERSONATOR_TOKEN_COOKIE, ""))
+            .path("/")
+            .build(),
+    );
+
+    Ok(Redirect::to(&format!("/users/{}", impersonation.user_id)).into_response())
+}
@@ -0,0 +1,171 @@
+use askama::Template;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::{Extension, Form, body::Body, extract::State, response::Response};
+use axum::{
+    Router,
+    routing::{get, post},
+};
+use snafu::ResultExt;
+
+use crate::ctx::Ctx;
+use crate::dto::{AppDto, LIFECYCLE_TOPICS, Permission};
+use crate::error::{ErrorInfo, ResponseBuilderSnafu, TemplateSnafu};
+use crate::models::{CspNonce, LifecycleSubscriptionView, Pref, TemplateData, TokenFormData};
+use crate::run::AppState;
+use crate::services::lifecycle::{
+    LifecycleSubscriptionFormData, list_lifecycle_subscriptions_svc, subscribe_lifecycle_web_svc,
+    unsubscribe_lifecycle_web_svc,
+};
+use crate::services::token::create_csrf_token_svc;
+use crate::web::{Action, Resource, SecretResponse, enforce_policy};
+use crate::{Result, models::AppView};
+
+pub fn lifecycle_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route(
+            "/",
+            get(lifecycle_handler).post(post_subscribe_lifecycle_handler),
+        )
+        .route(
+            "/{subscription_id}/delete",
+            post(post_unsubscribe_lifecycle_handler),
+        )
+        .with_state(state)
+}
+
+#[derive(Clone)]
+struct LifecycleTopicItem {
+    topic: String,
+    subscription: Option<LifecycleSubscriptionView>,
+    delete_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "pages/apps/lifecycle.html")]
+struct LifecyclePageTemplate {
+    t: TemplateData,
+    app: AppView,
+    topics: Vec<LifecycleTopicItem>,
+    token: String,
+    can_edit: bool,
+    error_message: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "widgets/apps/lifecycle.html")]
+struct LifecycleTemplate {
+    app: AppView,
+    topics: Vec<LifecycleTopicItem>,
+    token: String,
+    can_edit: bool,
+    error_message: Option<String>,
+}
+
+/// One row per topic, with the app's subscription when there is one
+async fn build_topics(state: &AppState, app_id: &str) -> Result<Vec<LifecycleTopicItem>> {
+    let subscriptions = list_lifecycle_subscriptions_svc(state, app_id).await?;
+
+    LIFECYCLE_TOPICS
+        .iter()
+        .map(|topic| {
+            let subscription = subscriptions.iter().find(|s| s.topic == *topic).cloned();
+            let delete_token = match &subscription {
+                Some(s) => create_csrf_token_svc(&s.id, &state.config.jwt_secret)?,
+                None => String::new(),
+            };
+
+            Ok(LifecycleTopicItem {
+                topic: topic.to_string(),
+                subscription: subscription.map(|s| s.into()),
+                delete_token,
+            })
+        })
+        .collect()
+}
+
+async fn lifecycle_handler(
+    Extension(csp_nonce): Extension<CspNonce>,
+    Extension(ctx): Extension<Ctx>,
+    Extension(pref): Extension<Pref>,
+    Extension(app): Extension<AppDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    enforce_policy(&ctx.actor, Resource::App, Action::Read)?;
+
+    let mut t = TemplateData::new(&state, ctx.actor.clone(), &pref, csp_nonce.nonce);
+    t.title = format!("App - {} - Lifecycle Webhooks", &app.name);
+
+    let tpl = LifecyclePageTemplate {
+        t,
+        topics: build_topics(&state, &app.id).await?,
+        token: create_csrf_token_svc(&app.id, &state.config.jwt_secret)?,
+        can_edit: ctx.actor.has_permissions(&[Permission::AppsEdit]),
+        app: app.into(),
+        error_message: None,
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn post_subscribe_lifecycle_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(app): Extension<AppDto>,
+    State(state): State<AppState>,
+    Form(payload): Form<LifecycleSubscriptionFormData>,
+) -> Result<Response<Body>> {
+    enforce_policy(&ctx.actor, Resource::App, Action::Update)?;
+
+    let result = subscribe_lifecycle_web_svc(&state, &app.id, payload).await;
+
+    lifecycle_response(&state, &ctx, app, result.map(|_| ())).await
+}
+
+async fn post_unsubscribe_lifecycle_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(app): Extension<AppDto>,
+    State(state): State<AppState>,
+    Path((_app_id, subscription_id)): Path<(String, String)>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    enforce_policy(&ctx.actor, Resource::App, Action::Update)?;
+
+    let result =
+        unsubscribe_lifecycle_web_svc(&state, &app.id, &subscription_id, &payload.token).await;
+
+    lifecycle_response(&state, &ctx, app, result).await
+}
+
+async fn lifecycle_response(
+    state: &AppState,
+    ctx: &Ctx,
+    app: AppDto,
+    result: Result<()>,
+) -> Result<Response<Body>> {
+    let mut status = StatusCode::OK;
+    let mut error_message = None;
+
+    if let Err(err) = result {
+        let error_info = ErrorInfo::from(&err);
+        status = error_info.status_code;
+        error_message = Some(error_info.message);
+    }
+
+    let tpl = LifecycleTemplate {
+        topics: build_topics(state, &app.id).await?,
+        token: create_csrf_token_svc(&app.id, &state.config.jwt_secret)?,
+        can_edit: ctx.actor.has_permissions(&[Permission::AppsEdit]),
+        app: app.into(),
+        error_message,
+    };
+
+    // The widget lists the signing secrets
+    Response::builder()
+        .status(status)
+        .extension(SecretResponse)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
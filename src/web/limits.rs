@@ -0,0 +1,20 @@
+use axum::{Json, Router, extract::State, http::StatusCode, routing::get};
+
+use crate::dto::LimitsDto;
+use crate::run::AppState;
+
+pub fn limits_api_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/limits", get(limits_handler))
+        .with_state(state)
+}
+
+/// Publishes the caps applied to listing requests
+pub async fn limits_handler(State(state): State<AppState>) -> (StatusCode, Json<LimitsDto>) {
+    (
+        StatusCode::OK,
+        Json(LimitsDto {
+            pagination: state.config.pagination.clone(),
+        }),
+    )
+}
@@ -2,12 +2,13 @@ use askama::Template;
 use axum::{
     Extension,
     body::Body,
-    extract::{Form, Query, State},
-    http::Response,
+    extract::{ConnectInfo, Form, Query, State},
+    http::{HeaderMap, Response, header},
     response::{IntoResponse, Redirect},
 };
 use snafu::ResultExt;
 use std::collections::HashMap;
+use std::net::SocketAddr;
 use tower_cookies::{Cookie, Cookies, cookie::time::Duration};
 use url::{Url, form_urlencoded};
 use validator::Validate;
@@ -19,7 +20,7 @@ use crate::{
     services::{auth::authenticate, captcha::validate_catpcha},
 };
 use crate::{
-    dto::{Actor, CredentialsDto, OauthClientLookupDto},
+    dto::{Actor, CredentialsDto, OauthClientLookupDto, SessionClientDto},
     services::oauth::lookup_oauth_client_app_svc,
 };
 use crate::{error::ErrorInfo, models::Pref, run::AppState};
@@ -86,6 +87,8 @@ pub async fn login_handler(
 
 pub async fn post_login_handler(
     cookies: Cookies,
+    headers: HeaderMap,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
     State(state): State<AppState>,
     Form(login_payload): Form<LoginFormPayload>,
 ) -> impl IntoResponse {
@@ -135,7 +138,8 @@ pub async fn post_login_handler(
         email: login_payload.username,
         password: login_payload.password,
     };
-    let login_result = authenticate(&state, &auth_payload).await;
+    let client = session_client(&headers, connect_info.map(|Extension(info)| info.0));
+    let login_result = authenticate(&state, &auth_payload, client).await;
     let auth = match login_result {
         Ok(val) => val,
         Err(err) => {
@@ -170,6 +174,35 @@ pub async fn post_login_handler(
     Redirect::to(&redirect_url).into_response()
 }
 
+/// Device details shown in the user's active sessions.
+///
+/// The forwarded address is preferred since the app usually runs behind a proxy.
+fn session_client(headers: &HeaderMap, peer: Option<SocketAddr>) -> SessionClientDto {
+    SessionClientDto {
+        user_agent: header_value(headers, header::USER_AGENT.as_str()),
+        ip_address: client_ip(headers, peer),
+    }
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Client IP from the proxy headers, falling back to the peer address
+pub(crate) fn client_ip(headers: &HeaderMap, peer: Option<SocketAddr>) -> Option<String> {
+    let forwarded = header_value(headers, "X-Forwarded-For")
+        .and_then(|value| value.split(',').next().map(|ip| ip.trim().to_string()))
+        .filter(|ip| !ip.is_empty());
+
+    forwarded
+        .or_else(|| header_value(headers, "X-Real-IP"))
+        .or_else(|| peer.map(|addr| addr.ip().to_string()))
+}
+
 fn handle_error(error: Error, next: Option<&str>) -> Response<Body> {
     let error_info = ErrorInfo::from(&error);
 
@@ -187,7 +220,10 @@ async fn resolve_login_title(state: &AppState, next: Option<&str>) -> String {
     };
 
     match lookup_oauth_client_app_svc(state, &payload).await {
-        Ok(app) => format!("Login to {}", app.name),
+        Ok(app) => match app.environment {
+            Some(environment) => format!("Login to {} ({})", app.name, environment),
+            None => format!("Login to {}", app.name),
+        },
         Err(_) => "Login to YAAS".to_string(),
     }
 }
@@ -1,10 +1,30 @@
-use axum::{http::Response, response::IntoResponse};
+use axum::{extract::State, http::Response, response::IntoResponse};
 use tower_cookies::{Cookie, Cookies};
+use tracing::warn;
 
-use super::AUTH_TOKEN_COOKIE;
+use crate::run::AppState;
+use crate::services::sessions::revoke_user_session_svc;
+use crate::services::token::verify_auth_token;
+
+use super::{AUTH_TOKEN_COOKIE, IMPERSONATOR_TOKEN_COOKIE};
+
+pub async fn logout_handler(State(state): State<AppState>, cookies: Cookies) -> impl IntoResponse {
+    // End the session too so a copy of the cookie stops working
+    if let Some((user_id, session_id)) = cookie_session(&state, &cookies)
+        && let Err(err) = revoke_user_session_svc(&state, &user_id, &session_id).await
+    {
+        warn!(
+            session_id = session_id,
+            "Unable to end session on logout: {}", err
+        );
+    }
 
-pub async fn logout_handler(cookies: Cookies) -> impl IntoResponse {
     cookies.remove(Cookie::new(AUTH_TOKEN_COOKIE, ""));
+    cookies.remove(
+        Cookie::build((IMPERSONATOR_TOKEN_COOKIE, ""))
+            .path("/")
+            .build(),
+    );
 
     Response::builder()
         .status(200)
@@ -12,3 +32,12 @@ pub async fn logout_handler(cookies: Cookies) -> impl IntoResponse {
         .body("Log in".to_string())
         .expect("Response builder must succeed")
 }
+
+/// User and session of the auth cookie, if the token carries one
+pub(crate) fn cookie_session(state: &AppState, cookies: &Cookies) -> Option<(String, String)> {
+    let token = cookies.get(AUTH_TOKEN_COOKIE)?;
+    let payload = verify_auth_token(token.value(), &state.token_keys).ok()?;
+    payload
+        .session_id
+        .map(|session_id| (payload.id, session_id))
+}
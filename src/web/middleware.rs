@@ -16,7 +16,10 @@ use crate::{
         auth::authenticate_token_svc, org_apps::get_org_app_svc, org_members::get_org_member_svc,
         orgs::get_org_svc, users::get_user_svc,
     },
-    web::{Action, Resource, enforce_policy, handle_error},
+    web::{
+        Action, Resource, audit_impersonated_response, enforce_org_policy, enforce_policy,
+        handle_error, request_id, request_path,
+    },
 };
 use crate::{dto::Actor, services::apps::get_app_svc};
 
@@ -68,7 +71,10 @@ pub async fn auth_middleware(
                         Actor::default(),
                         &pref,
                         csp_nonce.nonce.clone(),
-                        ErrorInfo::from(&err),
+                        ErrorInfo {
+                            request_id: request_id(req.headers()),
+                            ..ErrorInfo::from(&err)
+                        },
                         full_page,
                     );
                 }
@@ -76,8 +82,33 @@ pub async fn auth_middleware(
         };
     }
 
+    let impersonation = ctx.actor().and_then(|actor| {
+        actor
+            .impersonation_id
+            .clone()
+            .zip(actor.impersonator_id.clone())
+    });
+    let method = req.method().clone();
+    let path = request_path(&req);
+
+    let audit = ctx.audit();
     req.extensions_mut().insert(ctx);
-    next.run(req).await
+    let response = audit.scope(next.run(req)).await;
+
+    match impersonation {
+        Some((impersonation_id, impersonator_id)) => {
+            audit_impersonated_response(
+                &state,
+                &impersonation_id,
+                &impersonator_id,
+                &method,
+                &path,
+                response,
+            )
+            .await
+        }
+        None => response,
+    }
 }
 
 pub async fn require_auth_middleware(
@@ -139,7 +170,7 @@ pub async fn org_middleware(
     mut req: Request,
     next: Next,
 ) -> Result<Response> {
-    enforce_policy(&ctx.actor, Resource::Org, Action::Read)?;
+    enforce_org_policy(&ctx.actor, &params.org_id, Resource::Org, Action::Read)?;
 
     let Some(org) = get_org_svc(&state, &params.org_id).await? else {
         return Err(Error::OrgNotFound);
@@ -156,7 +187,12 @@ pub async fn org_member_middleware(
     mut req: Request,
     next: Next,
 ) -> Result<Response> {
-    enforce_policy(&ctx.actor, Resource::OrgMember, Action::Read)?;
+    enforce_org_policy(
+        &ctx.actor,
+        &params.org_id,
+        Resource::OrgMember,
+        Action::Read,
+    )?;
 
     let Some(org_member) = get_org_member_svc(&state, &params.org_id, &params.user_id).await?
     else {
@@ -174,7 +210,7 @@ pub async fn org_app_middleware(
     mut req: Request,
     next: Next,
 ) -> Result<Response> {
-    enforce_policy(&ctx.actor, Resource::OrgApp, Action::Read)?;
+    enforce_org_policy(&ctx.actor, &params.org_id, Resource::OrgApp, Action::Read)?;
 
     let Some(org_app) = get_org_app_svc(&state, &params.org_id, &params.app_id).await? else {
         return Err(Error::OrgAppNotFound);
@@ -1,38 +1,93 @@
+mod admin_api;
+mod admin_events;
+mod approvals;
 mod apps;
+mod auth_rate_limit;
+mod bulk;
+mod cache_headers;
+mod drafts;
 mod error;
+mod events;
+mod fields;
+mod grpc;
 mod health;
+mod idempotency;
+mod impersonation;
 mod index;
+mod lifecycle;
+mod limits;
 mod login;
 mod logout;
 mod middleware;
+mod notifications;
 mod oauth;
+mod openapi;
 mod org_apps;
+mod org_invitations;
 mod org_members;
+mod org_rate_limit;
+mod org_roles;
+mod org_settings;
 mod orgs;
+mod palette;
+mod password_reset;
+mod permissions;
 mod policies;
 mod pref;
 mod profile;
+mod recover;
+mod region;
+mod request_log;
+mod rollout;
 mod routes;
 mod security_headers;
 mod setup;
+mod teams;
 mod users;
 
 pub const AUTH_TOKEN_COOKIE: &str = "auth_token";
+pub const IMPERSONATOR_TOKEN_COOKIE: &str = "impersonator_token";
 pub const THEME_COOKIE: &str = "theme";
 
+pub use admin_api::*;
+pub use admin_events::*;
+pub use approvals::*;
 pub use apps::*;
+pub use auth_rate_limit::*;
+pub use drafts::*;
 pub use error::*;
+pub use events::*;
+pub use fields::*;
+pub use grpc::*;
 pub use health::*;
+pub use idempotency::*;
+pub use impersonation::*;
 pub use index::*;
+pub use lifecycle::*;
+pub use limits::*;
 pub use login::*;
 pub use logout::*;
+pub use notifications::*;
 pub use oauth::*;
+pub use openapi::*;
 pub use org_apps::*;
+pub use org_invitations::*;
 pub use org_members::*;
+pub use org_rate_limit::*;
+pub use org_roles::*;
+pub use org_settings::*;
 pub use orgs::*;
+pub use palette::*;
+pub use password_reset::*;
+pub use permissions::*;
 pub use policies::*;
 pub use pref::*;
 pub use profile::*;
+pub use recover::*;
+pub use region::*;
+pub use request_log::*;
+pub use rollout::*;
 pub use routes::*;
 pub use setup::*;
+pub use teams::*;
 pub use users::*;
@@ -0,0 +1,93 @@
+use askama::Template;
+use axum::{
+    Extension, Router,
+    body::Body,
+    extract::{Path, State},
+    http::{HeaderMap, Response},
+    routing::{get, post},
+};
+use snafu::{ResultExt, ensure};
+
+use crate::error::{ForbiddenSnafu, ResponseBuilderSnafu, TemplateSnafu};
+use crate::models::UserNotificationView;
+use crate::services::user_notifications::{
+    list_user_notifications_svc, mark_all_notifications_read_svc, mark_notification_read_svc,
+};
+use crate::{Result, ctx::Ctx, run::AppState};
+
+pub fn notifications_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/bell", get(notification_bell_handler))
+        .route("/read-all", post(mark_all_read_handler))
+        .route("/{id}/read", post(mark_read_handler))
+        .with_state(state)
+}
+
+/// Notifications are only marked as read by the bell, which cross site
+/// forms cannot imitate
+fn ensure_htmx_request(headers: &HeaderMap) -> Result<()> {
+    ensure!(
+        headers.contains_key("HX-Request"),
+        ForbiddenSnafu {
+            msg: "Notifications are marked as read by the page itself."
+        }
+    );
+    Ok(())
+}
+
+#[derive(Template)]
+#[template(path = "widgets/user/notification_bell.html")]
+struct NotificationBellTemplate {
+    unread_count: i64,
+    notifications: Vec<UserNotificationView>,
+}
+
+async fn render_bell(state: &AppState, user_id: &str) -> Result<Response<Body>> {
+    let data = list_user_notifications_svc(state, user_id).await?;
+    let tpl = NotificationBellTemplate {
+        unread_count: data.unread_count,
+        notifications: data
+            .notifications
+            .into_iter()
+            .map(UserNotificationView::from)
+            .collect(),
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn notification_bell_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    let actor = ctx.actor().expect("actor is required");
+    render_bell(&state, &actor.id).await
+}
+
+async fn mark_read_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response<Body>> {
+    ensure_htmx_request(&headers)?;
+    let actor = ctx.actor().expect("actor is required");
+
+    mark_notification_read_svc(&state, &actor.id, &id).await?;
+    render_bell(&state, &actor.id).await
+}
+
+async fn mark_all_read_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response<Body>> {
+    ensure_htmx_request(&headers)?;
+    let actor = ctx.actor().expect("actor is required");
+
+    mark_all_notifications_read_svc(&state, &actor.id).await?;
+    render_bell(&state, &actor.id).await
+}
@@ -1,32 +1,66 @@
 use askama::Template;
 use axum::{
-    Extension, Json, Router,
+    Extension, Form, Json, Router,
     body::Body,
-    extract::{Query, State, rejection::JsonRejection},
-    http::{HeaderMap, StatusCode},
+    extract::{OriginalUri, Path, Query, State, rejection::JsonRejection},
+    http::{HeaderMap, Method, StatusCode, header},
     middleware,
     response::{IntoResponse, Redirect, Response},
-    routing::{get, post},
+    routing::{delete, get, post},
 };
-use snafu::ResultExt;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use snafu::{ResultExt, ensure};
 use tracing::error;
 use url::Url;
-use validator::Validate;
+use validator::{Validate, ValidationErrors};
 
 use crate::{
     Error, Result,
     ctx::Ctx,
-    error::{ErrorInfo, JsonRejectionSnafu, ResponseBuilderSnafu, TemplateSnafu},
+    error::{
+        ErrorInfo, InsufficientAuthScopeSnafu, JsonRejectionSnafu, ResponseBuilderSnafu,
+        TemplateSnafu,
+    },
     models::{CspNonce, Pref, TemplateData},
     run::AppState,
     services::{
-        auth::authenticate_token_svc,
-        oauth::{create_authorization_code_svc, exchange_code_for_access_token_svc},
+        auth::authenticate_bound_token_svc,
+        impersonations::stop_impersonation_svc,
+        oauth::{
+            OauthConsentFormData, create_authorization_code_svc, decide_oauth_consent_web_svc,
+            exchange_code_for_access_token_svc, introspect_token_svc,
+            issue_client_credentials_token_svc, oauth_consent_svc, revoke_token_for_app_svc,
+        },
+        oauth_grants::{list_authorized_apps_svc, revoke_authorized_app_svc},
+        oidc::openid_configuration_svc,
+        org_rate_limits::org_rate_usage_svc,
+        revocations::revoke_user_tokens_svc,
+        sessions::{list_user_sessions_svc, revoke_user_session_svc},
+        token::{create_csrf_token_svc, jwks_svc, verify_auth_token},
+        user_exports::{download_user_export_svc, export_user_data_svc},
+        user_notifications::{
+            list_user_notifications_svc, mark_all_notifications_read_svc,
+            mark_notification_read_svc,
+        },
+        user_profiles::{get_user_profile_svc, update_user_profile_svc},
+    },
+    utils::build_redirect_url,
+    web::{
+        FieldsQuery, copy_preserved_headers, handle_error, impersonation_api_middleware,
+        org_rate_limit_middleware, region_routing_middleware, request_id, route_rollout_middleware,
     },
-    web::handle_error,
 };
 use crate::{
-    dto::{ErrorMessageDto, OauthAuthorizeDto, OauthTokenRequestDto},
+    dto::{
+        Actor, ActorDto, AuthorizedAppDto, ErrorMessageDto, ImpersonationDto, JwksDto,
+        OauthAuthorizationCodeDto, OauthAuthorizeDto, OauthClientCredentialsRequestDto,
+        OauthConsentDto, OauthIntrospectRequestDto, OauthIntrospectionDto, OauthRevokeRequestDto,
+        OauthTokenRequestDto, OauthTokenResponseDto, OpenidConfigurationDto, OrgRateUsageDto,
+        Scope, TOKEN_PROOF_HEADER, TokenProofDto, UpdateUserProfileDto,
+        UserExportDownloadParamsDto, UserExportDto, UserExportParamsDto, UserExportStatus,
+        UserNotificationsDto, UserProfileDto, UserSessionDto, UserinfoDto, scope_description,
+    },
     validators::flatten_errors,
 };
 
@@ -35,7 +69,59 @@ use crate::{
 pub fn oauth_api_routes(state: AppState) -> Router {
     Router::new()
         .route("/oauth/token", post(oauth_token_handler))
+        .route("/oauth/introspect", post(oauth_introspect_handler))
+        .route("/oauth/revoke", post(oauth_revoke_handler))
         .route("/oauth/profile", get(oauth_profile_handler))
+        .route("/oauth/userinfo", get(oauth_userinfo_handler))
+        .route(
+            "/.well-known/openid-configuration",
+            get(openid_configuration_handler),
+        )
+        .route("/.well-known/jwks.json", get(jwks_handler))
+        .route("/user/authorized-apps", get(authorized_apps_handler))
+        .route(
+            "/user/authorized-apps/{app_id}",
+            delete(revoke_authorized_app_handler),
+        )
+        .route(
+            "/user/profile",
+            get(user_profile_handler).patch(update_user_profile_handler),
+        )
+        .route("/user/sessions", get(user_sessions_handler))
+        .route("/user/export", get(user_export_handler))
+        .route("/user/export/download", get(download_user_export_handler))
+        .route("/auth/logout-all", post(logout_all_handler))
+        .route("/auth/stop-impersonating", post(stop_impersonating_handler))
+        .route(
+            "/user/sessions/{session_id}",
+            delete(revoke_user_session_handler),
+        )
+        .route("/user/notifications", get(user_notifications_handler))
+        .route(
+            "/user/notifications/read-all",
+            post(read_all_user_notifications_handler),
+        )
+        .route(
+            "/user/notifications/{notification_id}/read",
+            post(read_user_notification_handler),
+        )
+        .route("/org/usage", get(org_usage_handler))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            route_rollout_middleware,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            impersonation_api_middleware,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            org_rate_limit_middleware,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            region_routing_middleware,
+        ))
         .layer(middleware::map_response_with_state(
             state.clone(),
             api_response_mapper,
@@ -53,87 +139,226 @@ pub async fn oauth_authorize_handler(
 ) -> Result<Response<Body>> {
     // Validate query parameters
     if let Err(err) = query.validate() {
-        let msg = flatten_errors(&err);
-        let error_info = ErrorInfo {
-            status_code: StatusCode::BAD_REQUEST,
-            title: "Invalid Request".to_string(),
-            message: msg,
-        };
-
-        return Ok(handle_error(
-            &state,
-            ctx.actor,
-            &pref,
-            csp_nonce.nonce,
-            error_info,
-            true,
+        return Ok(invalid_authorize_request(
+            &state, ctx, &pref, csp_nonce, &err,
         ));
     }
 
     // Check if user is logged in
     if !ctx.actor.has_auth_scope() {
-        let current_path = format!(
-            "/oauth/authorize?client_id={}&redirect_uri={}&scope={}&state={}",
-            urlencoding::encode(&query.client_id),
-            urlencoding::encode(&query.redirect_uri),
-            urlencoding::encode(&query.scope),
-            urlencoding::encode(&query.state),
-        );
+        let current_path = format!("/oauth/authorize?{}", authorize_query(&query));
         let login_url = format!("/login?next={}", urlencoding::encode(&current_path));
         return Ok(Redirect::to(&login_url).into_response());
     }
 
-    let result = create_authorization_code_svc(&state, &ctx, &query).await;
+    // Ask for consent unless an earlier approval covers the requested scopes
+    let result = match oauth_consent_svc(&state, &ctx, &query).await {
+        Ok(consent) if consent.required => {
+            let consent_url = format!("/oauth/consent?{}", authorize_query(&query));
+            return Ok(Redirect::to(&consent_url).into_response());
+        }
+        Ok(_) => create_authorization_code_svc(&state, &ctx, &query).await,
+        Err(err) => Err(err),
+    };
+
+    Ok(authorize_response(
+        &state, ctx, &pref, csp_nonce, &query, result,
+    ))
+}
+
+#[derive(Template)]
+#[template(path = "pages/oauth_consent.html")]
+struct OauthConsentTemplate {
+    t: TemplateData,
+    consent: OauthConsentDto,
+    scopes: Vec<(String, &'static str)>,
+    query: OauthAuthorizeDto,
+    token: String,
+}
+
+/// Consent screen listing what the app requests, the user approves or denies it
+pub async fn oauth_consent_handler(
+    State(state): State<AppState>,
+    Extension(csp_nonce): Extension<CspNonce>,
+    Extension(ctx): Extension<Ctx>,
+    Extension(pref): Extension<Pref>,
+    Query(query): Query<OauthAuthorizeDto>,
+) -> Result<Response<Body>> {
+    if let Err(err) = query.validate() {
+        return Ok(invalid_authorize_request(
+            &state, ctx, &pref, csp_nonce, &err,
+        ));
+    }
+
+    // The authorize endpoint takes care of the login and comes back here
+    if !ctx.actor.has_auth_scope() {
+        let authorize_url = format!("/oauth/authorize?{}", authorize_query(&query));
+        return Ok(Redirect::to(&authorize_url).into_response());
+    }
+
+    let consent = match oauth_consent_svc(&state, &ctx, &query).await {
+        Ok(consent) => consent,
+        Err(err) => {
+            return Ok(authorize_response(
+                &state,
+                ctx,
+                &pref,
+                csp_nonce,
+                &query,
+                Err(err),
+            ));
+        }
+    };
+
+    let mut t = TemplateData::new(&state, ctx.actor.clone(), &pref, csp_nonce.nonce);
+    t.title = format!("Authorize {}", consent.app_name);
+
+    let tpl = OauthConsentTemplate {
+        t,
+        scopes: consent
+            .scopes
+            .iter()
+            .map(|scope| (scope.to_string(), scope_description(scope)))
+            .collect(),
+        consent,
+        token: create_csrf_token_svc(&query.client_id, &state.config.jwt_secret)?,
+        query,
+    };
+
+    Response::builder()
+        .status(200)
+        .header("Cache-Control", "no-store")
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+/// Approves or denies the consent screen, both end with a redirect to the app
+pub async fn post_oauth_consent_handler(
+    State(state): State<AppState>,
+    Extension(csp_nonce): Extension<CspNonce>,
+    Extension(ctx): Extension<Ctx>,
+    Extension(pref): Extension<Pref>,
+    Form(form): Form<OauthConsentFormData>,
+) -> Result<Response<Body>> {
+    let query = OauthAuthorizeDto {
+        client_id: form.client_id,
+        redirect_uri: form.redirect_uri,
+        scope: form.scope,
+        state: form.state,
+        nonce: form.nonce.filter(|nonce| !nonce.is_empty()),
+    };
+
+    if let Err(err) = query.validate() {
+        return Ok(invalid_authorize_request(
+            &state, ctx, &pref, csp_nonce, &err,
+        ));
+    }
+
+    if !ctx.actor.has_auth_scope() {
+        let authorize_url = format!("/oauth/authorize?{}", authorize_query(&query));
+        return Ok(Redirect::to(&authorize_url).into_response());
+    }
+
+    let approved = form.decision == "approve";
+    let result = decide_oauth_consent_web_svc(&state, &ctx, &query, approved, &form.token).await;
+
+    Ok(authorize_response(
+        &state, ctx, &pref, csp_nonce, &query, result,
+    ))
+}
+
+/// Query string of the authorize request, to come back to it after login or consent
+fn authorize_query(query: &OauthAuthorizeDto) -> String {
+    let mut params = format!(
+        "client_id={}&redirect_uri={}&scope={}&state={}",
+        urlencoding::encode(&query.client_id),
+        urlencoding::encode(&query.redirect_uri),
+        urlencoding::encode(&query.scope),
+        urlencoding::encode(&query.state),
+    );
+    if let Some(nonce) = &query.nonce {
+        params.push_str(&format!("&nonce={}", urlencoding::encode(nonce)));
+    }
+    params
+}
+
+fn invalid_authorize_request(
+    state: &AppState,
+    ctx: Ctx,
+    pref: &Pref,
+    csp_nonce: CspNonce,
+    err: &ValidationErrors,
+) -> Response<Body> {
+    let error_info = ErrorInfo {
+        status_code: StatusCode::BAD_REQUEST,
+        title: "Invalid Request".to_string(),
+        message: flatten_errors(err),
+        request_id: None,
+    };
+
+    handle_error(state, ctx.actor, pref, csp_nonce.nonce, error_info, true)
+}
 
+/// Redirects back to the app with the code or the error
+fn authorize_response(
+    state: &AppState,
+    ctx: Ctx,
+    pref: &Pref,
+    csp_nonce: CspNonce,
+    query: &OauthAuthorizeDto,
+    result: Result<OauthAuthorizationCodeDto>,
+) -> Response<Body> {
     match result {
         Ok(auth_code) => {
             // Success: redirect to resume page before leaving this origin
-            let redirect_url = format!(
-                "{}?code={}&state={}",
-                query.redirect_uri,
-                urlencoding::encode(&auth_code.code),
-                urlencoding::encode(&auth_code.state)
+            let redirect_url = build_redirect_url(
+                &query.redirect_uri,
+                &[("code", &auth_code.code), ("state", &auth_code.state)],
             );
-            let resume_url = format!(
-                "/oauth/authorize/resume?next={}",
-                urlencoding::encode(&redirect_url)
-            );
-            Ok(Redirect::to(&resume_url).into_response())
+            Redirect::to(&resume_url(&redirect_url)).into_response()
         }
         Err(err) => {
-            // Error: redirect to redirect_uri with error details if possible
-            let error_info = ErrorInfo::from(&err);
-
-            // Only redirect to redirect_uri if it's a valid URL
-            // Otherwise, render error page
-            if query.redirect_uri.starts_with("http://")
-                || query.redirect_uri.starts_with("https://")
-            {
-                let redirect_url = format!(
-                    "{}?error=access_denied&error_description={}&state={}",
-                    query.redirect_uri,
-                    urlencoding::encode(&error_info.message),
-                    urlencoding::encode(&query.state)
-                );
-                let resume_url = format!(
-                    "/oauth/authorize/resume?next={}",
-                    urlencoding::encode(&redirect_url)
-                );
-                Ok(Redirect::to(&resume_url).into_response())
-            } else {
-                Ok(handle_error(
-                    &state,
+            // Only redirect back to the client when both client_id and redirect_uri
+            // have been verified, otherwise render the error page (RFC 6749 4.1.2.1)
+            let Some(error_code) = err.oauth_error_code() else {
+                return handle_error(
+                    state,
                     ctx.actor,
-                    &pref,
+                    pref,
                     csp_nonce.nonce,
-                    error_info,
+                    ErrorInfo::from(&err),
                     true,
-                ))
-            }
+                );
+            };
+
+            // Do not leak internal error details to the client
+            let description = if StatusCode::from(&err).is_server_error() {
+                error!("{}", err);
+                "The server encountered an unexpected error".to_string()
+            } else {
+                err.to_string()
+            };
+
+            let redirect_url = build_redirect_url(
+                &query.redirect_uri,
+                &[
+                    ("error", error_code),
+                    ("error_description", &description),
+                    ("state", &query.state),
+                ],
+            );
+            Redirect::to(&resume_url(&redirect_url)).into_response()
         }
     }
 }
 
+fn resume_url(redirect_url: &str) -> String {
+    format!(
+        "/oauth/authorize/resume?next={}",
+        urlencoding::encode(redirect_url)
+    )
+}
+
 #[derive(Template)]
 #[template(path = "pages/oauth_authorize_resume.html")]
 struct OauthAuthorizeResumeTemplate {
@@ -188,52 +413,421 @@ fn resolve_app_name_from_next(next: &str) -> String {
 }
 
 /// API handler for OAuth2 Token Endpoint
-/// Exchange authorization code for access token
+/// Exchange authorization code for access token, or issue a client credentials token
 pub async fn oauth_token_handler(
     State(state): State<AppState>,
-    payload: core::result::Result<Json<OauthTokenRequestDto>, JsonRejection>,
-) -> Result<(StatusCode, Json<crate::dto::OauthTokenResponseDto>)> {
+    payload: core::result::Result<Json<Value>, JsonRejection>,
+) -> Result<(StatusCode, Json<OauthTokenResponseDto>)> {
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+
+    // Server-to-server apps send a different payload, see RFC 6749 section 4.4
+    let oauth_token = match data.get("grant_type").and_then(Value::as_str) {
+        Some("client_credentials") => {
+            let data: OauthClientCredentialsRequestDto = parse_token_request(data)?;
+            issue_client_credentials_token_svc(&state, &data).await?
+        }
+        _ => {
+            let data: OauthTokenRequestDto = parse_token_request(data)?;
+            exchange_code_for_access_token_svc(&state, &data).await?
+        }
+    };
+
+    Ok((StatusCode::OK, Json(oauth_token)))
+}
+
+fn parse_token_request<T: DeserializeOwned + Validate>(data: Value) -> Result<T> {
+    let data: T =
+        serde_json::from_value(data).map_err(|e| Error::Validation { msg: e.to_string() })?;
+
+    if let Err(err) = data.validate() {
+        let msg = flatten_errors(&err);
+        return Err(Error::Validation { msg });
+    }
+
+    Ok(data)
+}
+
+/// API handler for OAuth2 Token Introspection Endpoint
+/// Lets the app's backend check a token it received, authenticated with its client credentials
+pub async fn oauth_introspect_handler(
+    State(state): State<AppState>,
+    payload: core::result::Result<Json<OauthIntrospectRequestDto>, JsonRejection>,
+) -> Result<(StatusCode, Json<OauthIntrospectionDto>)> {
     let data = payload.context(JsonRejectionSnafu)?;
 
-    // Validate query parameters
     if let Err(err) = data.validate() {
         let msg = flatten_errors(&err);
         return Err(Error::Validation { msg });
     }
 
-    let oauth_token = exchange_code_for_access_token_svc(&state, &data).await?;
+    let introspection = introspect_token_svc(&state, &data).await?;
 
-    Ok((StatusCode::OK, Json(oauth_token)))
+    Ok((StatusCode::OK, Json(introspection)))
+}
+
+/// API handler for OAuth2 Token Revocation Endpoint
+/// Lets the app's backend revoke a token it received, e.g. when the user signs out of the app
+pub async fn oauth_revoke_handler(
+    State(state): State<AppState>,
+    payload: core::result::Result<Json<OauthRevokeRequestDto>, JsonRejection>,
+) -> Result<StatusCode> {
+    let data = payload.context(JsonRejectionSnafu)?;
+
+    if let Err(err) = data.validate() {
+        let msg = flatten_errors(&err);
+        return Err(Error::Validation { msg });
+    }
+
+    revoke_token_for_app_svc(&state, &data).await?;
+
+    Ok(StatusCode::OK)
 }
 
 /// API handler for OAuth2 User Profile Endpoint
 /// Fetch user profile using access token
 pub async fn oauth_profile_handler(
     State(state): State<AppState>,
+    method: Method,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    Query(fields): Query<FieldsQuery>,
+) -> Result<(StatusCode, Json<Value>)> {
+    let actor = authenticate_bearer(&state, &method, uri.path(), &headers).await?;
+    Ok((StatusCode::OK, fields.item(actor)?))
+}
+
+/// OpenID Connect UserInfo Endpoint
+/// Standard claims of the user, for tokens granted the `openid` scope
+pub async fn oauth_userinfo_handler(
+    State(state): State<AppState>,
+    method: Method,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<UserinfoDto>)> {
+    let actor = authenticate_bearer(&state, &method, uri.path(), &headers).await?;
+    ensure!(
+        actor.scopes.contains(&Scope::Openid),
+        InsufficientAuthScopeSnafu
+    );
+
+    Ok((StatusCode::OK, Json(UserinfoDto::from(actor.user))))
+}
+
+/// OpenID Connect discovery document
+pub async fn openid_configuration_handler(
+    State(state): State<AppState>,
+) -> Result<(StatusCode, Json<OpenidConfigurationDto>)> {
+    let config = openid_configuration_svc(&state)?;
+    Ok((StatusCode::OK, Json(config)))
+}
+
+/// Public keys to verify ID token signatures
+pub async fn jwks_handler(State(state): State<AppState>) -> Result<(StatusCode, Json<JwksDto>)> {
+    let jwks = jwks_svc(&state)?;
+    Ok((StatusCode::OK, Json(jwks)))
+}
+
+/// API handler listing the apps the user has authorized
+pub async fn authorized_apps_handler(
+    State(state): State<AppState>,
+    method: Method,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<Vec<AuthorizedAppDto>>)> {
+    let actor = authenticate_bearer(&state, &method, uri.path(), &headers).await?;
+    ensure!(
+        actor.scopes.contains(&Scope::Auth),
+        InsufficientAuthScopeSnafu
+    );
+
+    let apps = list_authorized_apps_svc(&state, &actor.id).await?;
+    Ok((StatusCode::OK, Json(apps)))
+}
+
+/// API handler revoking the user's authorization for an app
+pub async fn revoke_authorized_app_handler(
+    State(state): State<AppState>,
+    Path(app_id): Path<String>,
+    method: Method,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Result<StatusCode> {
+    let actor = authenticate_bearer(&state, &method, uri.path(), &headers).await?;
+    ensure!(
+        actor.scopes.contains(&Scope::Auth),
+        InsufficientAuthScopeSnafu
+    );
+
+    revoke_authorized_app_svc(&state, &actor.id, &app_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// API handler listing the user's active sessions
+pub async fn user_sessions_handler(
+    State(state): State<AppState>,
+    method: Method,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<Vec<UserSessionDto>>)> {
+    let actor = authenticate_bearer(&state, &method, uri.path(), &headers).await?;
+    ensure!(
+        actor.scopes.contains(&Scope::Auth),
+        InsufficientAuthScopeSnafu
+    );
+
+    // The token was verified above, it only needs decoding for its session
+    let current_id = bearer_token(&headers)
+        .and_then(|token| verify_auth_token(&token, &state.token_keys).ok())
+        .and_then(|payload| payload.session_id);
+
+    let sessions = list_user_sessions_svc(&state, &actor.id, current_id.as_deref()).await?;
+    Ok((StatusCode::OK, Json(sessions)))
+}
+
+/// API handler for the profile attributes and metadata of the user
+pub async fn user_profile_handler(
+    State(state): State<AppState>,
+    method: Method,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<UserProfileDto>)> {
+    let actor = authenticate_bearer(&state, &method, uri.path(), &headers).await?;
+    ensure!(
+        actor.scopes.contains(&Scope::Auth),
+        InsufficientAuthScopeSnafu
+    );
+
+    let profile = get_user_profile_svc(&state, &actor.id).await?;
+    Ok((StatusCode::OK, Json(profile)))
+}
+
+/// API handler updating some of the profile attributes or metadata keys
+pub async fn update_user_profile_handler(
+    State(state): State<AppState>,
+    method: Method,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+    payload: core::result::Result<Json<UpdateUserProfileDto>, JsonRejection>,
+) -> Result<(StatusCode, Json<UserProfileDto>)> {
+    let actor = authenticate_bearer(&state, &method, uri.path(), &headers).await?;
+    ensure!(
+        actor.scopes.contains(&Scope::Auth),
+        InsufficientAuthScopeSnafu
+    );
+
+    let Json(data) = payload.context(JsonRejectionSnafu)?;
+    let profile = update_user_profile_svc(&state, &actor.id, data).await?;
+    Ok((StatusCode::OK, Json(profile)))
+}
+
+/// API handler signing the user out of one session
+pub async fn revoke_user_session_handler(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    method: Method,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Result<StatusCode> {
+    let actor = authenticate_bearer(&state, &method, uri.path(), &headers).await?;
+    ensure!(
+        actor.scopes.contains(&Scope::Auth),
+        InsufficientAuthScopeSnafu
+    );
+
+    revoke_user_session_svc(&state, &actor.id, &session_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// API handler for the latest notifications of the user and the unread count
+pub async fn user_notifications_handler(
+    State(state): State<AppState>,
+    method: Method,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<UserNotificationsDto>)> {
+    let actor = authenticate_bearer(&state, &method, uri.path(), &headers).await?;
+    ensure!(
+        actor.scopes.contains(&Scope::Auth),
+        InsufficientAuthScopeSnafu
+    );
+
+    let notifications = list_user_notifications_svc(&state, &actor.id).await?;
+    Ok((StatusCode::OK, Json(notifications)))
+}
+
+/// API handler marking one notification as read, already read ones are left as is
+pub async fn read_user_notification_handler(
+    State(state): State<AppState>,
+    Path(notification_id): Path<String>,
+    method: Method,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Result<StatusCode> {
+    let actor = authenticate_bearer(&state, &method, uri.path(), &headers).await?;
+    ensure!(
+        actor.scopes.contains(&Scope::Auth),
+        InsufficientAuthScopeSnafu
+    );
+
+    mark_notification_read_svc(&state, &actor.id, &notification_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn read_all_user_notifications_handler(
+    State(state): State<AppState>,
+    method: Method,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Result<StatusCode> {
+    let actor = authenticate_bearer(&state, &method, uri.path(), &headers).await?;
+    ensure!(
+        actor.scopes.contains(&Scope::Auth),
+        InsufficientAuthScopeSnafu
+    );
+
+    mark_all_notifications_read_svc(&state, &actor.id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// API handler signing the user out everywhere, including the calling token
+pub async fn logout_all_handler(
+    State(state): State<AppState>,
+    method: Method,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Result<StatusCode> {
+    let actor = authenticate_bearer(&state, &method, uri.path(), &headers).await?;
+    ensure!(
+        actor.scopes.contains(&Scope::Auth),
+        InsufficientAuthScopeSnafu
+    );
+
+    revoke_user_tokens_svc(&state, Some(&actor.id), &actor.id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// API handler for the user's own data export, queued when there is none to download
+pub async fn user_export_handler(
+    State(state): State<AppState>,
+    method: Method,
+    OriginalUri(uri): OriginalUri,
     headers: HeaderMap,
-) -> Result<(StatusCode, Json<crate::dto::ActorDto>)> {
-    // Manually validate auth token
-    let mut token: Option<String> = None;
-
-    if let Some(auth_header) = headers.get("Authorization")
-        && let Ok(auth_str) = auth_header.to_str()
-        && auth_str.starts_with("Bearer ")
-    {
-        token = Some(auth_str[7..].to_string());
+    Query(query): Query<UserExportParamsDto>,
+) -> Result<(StatusCode, Json<UserExportDto>)> {
+    let actor = authenticate_bearer(&state, &method, uri.path(), &headers).await?;
+    ensure!(
+        actor.scopes.contains(&Scope::Auth),
+        InsufficientAuthScopeSnafu
+    );
+
+    let export = export_user_data_svc(&state, Some(&actor.id), &actor.id, query).await?;
+    Ok((user_export_status(&export), Json(export)))
+}
+
+/// Serves an export archive, the signed link is the only credential
+pub async fn download_user_export_handler(
+    State(state): State<AppState>,
+    Query(query): Query<UserExportDownloadParamsDto>,
+) -> Result<Response> {
+    let file = download_user_export_svc(&state, &query.token).await?;
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, file.content_type)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", file.filename),
+        )
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(Body::from(file.content))
+        .context(ResponseBuilderSnafu)
+}
+
+/// Pending exports are accepted for later, ready ones are served right away
+pub(crate) fn user_export_status(export: &UserExportDto) -> StatusCode {
+    match export.status {
+        UserExportStatus::Pending => StatusCode::ACCEPTED,
+        UserExportStatus::Ready => StatusCode::OK,
     }
+}
+
+/// API handler ending the impersonation the bearer token was issued for
+pub async fn stop_impersonating_handler(
+    State(state): State<AppState>,
+    method: Method,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<ImpersonationDto>)> {
+    let actor = authenticate_bearer(&state, &method, uri.path(), &headers).await?;
+
+    let impersonation = stop_impersonation_svc(&state, &actor).await?;
+    Ok((StatusCode::OK, Json(impersonation)))
+}
+
+/// API handler reporting the request budget of the token's org
+pub async fn org_usage_handler(
+    State(state): State<AppState>,
+    method: Method,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<OrgRateUsageDto>)> {
+    // Client credentials tokens of org apps can check their budget too
+    let actor = authenticate_bearer_actor(&state, &method, uri.path(), &headers).await?;
+    let org_id = actor.org_id().ok_or(Error::LoginRequired)?;
+    let usage = org_rate_usage_svc(&state, org_id).await?;
+    Ok((StatusCode::OK, Json(usage)))
+}
 
-    let Some(token) = token else {
+/// Manually validate the bearer token since API routes are not behind the auth middleware.
+///
+/// Tokens bound to an app's proof key also need a signed proof of this request.
+async fn authenticate_bearer(
+    state: &AppState,
+    method: &Method,
+    path: &str,
+    headers: &HeaderMap,
+) -> Result<ActorDto> {
+    let Some(actor) = authenticate_bearer_actor(state, method, path, headers)
+        .await?
+        .actor
+    else {
         return Err(Error::LoginRequired);
     };
 
-    let Some(actor) = authenticate_token_svc(&state, &token).await?.actor else {
+    Ok(actor)
+}
+
+/// Same as `authenticate_bearer` but app actors of client credentials tokens pass too
+async fn authenticate_bearer_actor(
+    state: &AppState,
+    method: &Method,
+    path: &str,
+    headers: &HeaderMap,
+) -> Result<Actor> {
+    let Some(token) = bearer_token(headers) else {
         return Err(Error::LoginRequired);
     };
 
-    Ok((StatusCode::OK, Json(actor)))
+    let proof = TokenProofDto {
+        header: headers
+            .get(TOKEN_PROOF_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string()),
+        method: method.to_string(),
+        path: path.to_string(),
+    };
+
+    authenticate_bound_token_svc(state, &token, Some(&proof)).await
 }
 
-async fn api_response_mapper(res: Response) -> Response {
+pub(crate) fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    let auth_str = headers.get("Authorization")?.to_str().ok()?;
+    auth_str
+        .strip_prefix("Bearer ")
+        .map(|token| token.to_string())
+}
+
+pub(crate) async fn api_response_mapper(headers: HeaderMap, res: Response) -> Response {
     let error = res.extensions().get::<ErrorInfo>();
     if let Some(e) = error {
         if e.status_code.is_server_error() {
@@ -246,16 +840,44 @@ async fn api_response_mapper(res: Response) -> Response {
             message: e.message.clone(),
             error: e.status_code.canonical_reason().unwrap().to_string(),
             error_code: None,
+            request_id: request_id(&headers),
         };
 
-        return (e.status_code, Json(error_message)).into_response();
+        let mut mapped = (e.status_code, Json(error_message)).into_response();
+        copy_preserved_headers(res.headers(), mapped.headers_mut());
+        return mapped;
     }
     res
 }
 
 #[cfg(test)]
 mod tests {
-    use super::resolve_app_name_from_next;
+    use axum::body::to_bytes;
+    use axum::http::{HeaderMap, HeaderValue, StatusCode};
+    use axum::response::IntoResponse;
+    use serde_json::Value;
+
+    use crate::Error;
+
+    use super::{api_response_mapper, resolve_app_name_from_next};
+
+    #[tokio::test]
+    async fn api_errors_carry_the_request_id() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", HeaderValue::from_static("req-123"));
+
+        let res = Error::NotFound {
+            msg: "App not found".to_string(),
+        }
+        .into_response();
+        let res = api_response_mapper(headers, res).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let body = to_bytes(res.into_body(), usize::MAX).await.expect("body");
+        let body: Value = serde_json::from_slice(&body).expect("json");
+        assert_eq!(body["message"], "App not found");
+        assert_eq!(body["request_id"], "req-123");
+    }
 
     #[test]
     fn resolve_app_name_uses_host() {
@@ -0,0 +1,16 @@
+use axum::{Json, Router, http::StatusCode, routing::get};
+use serde_json::Value;
+
+use crate::run::AppState;
+use crate::services::openapi::openapi_spec_svc;
+
+pub fn openapi_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/openapi.json", get(openapi_handler))
+        .with_state(state)
+}
+
+/// Publishes the OpenAPI document of the JSON APIs
+pub async fn openapi_handler() -> (StatusCode, Json<Value>) {
+    (StatusCode::OK, Json(openapi_spec_svc()))
+}
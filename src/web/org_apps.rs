@@ -9,13 +9,18 @@ use validator::Validate;
 
 use crate::dto::OrgDto;
 use crate::dto::Permission;
-use crate::dto::{ListOrgAppsParamsDto, OrgAppDto, OrgAppSuggestionDto};
+use crate::dto::{ListOrgAppsParamsDto, OrgAppDto, OrgAppSuggestionDto, Scope, scope_description};
+use crate::dto::{ORG_APP_SORT, sort_query};
 use crate::error::ValidationSnafu;
-use crate::models::{CspNonce, OrgAppParams, OrgAppView, PaginationLinks, TokenFormData};
+use crate::models::options::CheckboxOption;
+use crate::models::{
+    CspNonce, EmptyState, OrgAppParams, OrgAppView, PaginationLinks, SortLinks, TokenFormData,
+};
 use crate::services::apps::get_app_svc;
 use crate::services::org_apps::{
-    NewOrgAppFormData, create_org_app_web_svc, delete_org_app_web_svc,
-    list_org_app_suggestions_svc, list_org_apps_svc,
+    NewOrgAppFormData, OrgAppScopesFormData, create_org_app_web_svc, delete_org_app_web_svc,
+    granted_app_scopes, list_org_app_suggestions_svc, list_org_apps_svc,
+    update_org_app_scopes_web_svc,
 };
 use crate::validators::flatten_errors;
 use crate::web::middleware::org_app_middleware;
@@ -26,7 +31,7 @@ use crate::{
     models::{Pref, TemplateData},
     run::AppState,
     services::token::create_csrf_token_svc,
-    web::{Action, Resource, enforce_policy},
+    web::{Action, Resource, enforce_org_policy, enforce_policy, missing_permission_hint},
 };
 
 pub fn org_apps_routes(state: AppState) -> Router<AppState> {
@@ -50,6 +55,10 @@ fn org_app_inner_routes(state: AppState) -> Router<AppState> {
     Router::new()
         .route("/", get(org_app_page_handler))
         .route("/edit-controls", get(org_app_controls_handler))
+        .route(
+            "/scopes",
+            get(org_app_scopes_handler).post(post_org_app_scopes_handler),
+        )
         .route(
             "/delete",
             get(delete_org_app_handler).post(post_delete_org_app_handler),
@@ -68,6 +77,7 @@ struct OrgAppsPageTemplate {
     org: OrgDto,
     query_params: String,
     can_add_app: bool,
+    add_app_hint: Option<String>,
 }
 
 async fn org_apps_handler(
@@ -78,8 +88,9 @@ async fn org_apps_handler(
     State(state): State<AppState>,
     Query(query): Query<ListOrgAppsParamsDto>,
 ) -> Result<Response<Body>> {
-    enforce_policy(&ctx.actor, Resource::OrgApp, Action::Read)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgApp, Action::Read)?;
     let can_add_app = ctx.actor.has_permissions(&[Permission::OrgAppsCreate]);
+    let add_app_hint = missing_permission_hint(&ctx.actor, Resource::OrgApp, Action::Create);
 
     let errors = query.validate();
     ensure!(
@@ -97,6 +108,7 @@ async fn org_apps_handler(
         org,
         query_params: query.to_string(),
         can_add_app,
+        add_app_hint,
     };
 
     Response::builder()
@@ -111,6 +123,8 @@ struct SearchOrgAppsTemplate {
     org_apps: Vec<OrgAppView>,
     pagination: Option<PaginationLinks>,
     error_message: Option<String>,
+    empty_state: EmptyState,
+    sorting: SortLinks,
 }
 async fn search_org_apps_handler(
     Extension(ctx): Extension<Ctx>,
@@ -118,15 +132,26 @@ async fn search_org_apps_handler(
     State(state): State<AppState>,
     Query(query): Query<ListOrgAppsParamsDto>,
 ) -> Result<Response<Body>> {
-    enforce_policy(&ctx.actor, Resource::OrgMember, Action::Read)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Read)?;
 
     let mut tpl = SearchOrgAppsTemplate {
         org_apps: Vec::new(),
         pagination: None,
         error_message: None,
+        empty_state: EmptyState::org_apps(&ctx.actor, &org.id, query.keyword.as_deref()),
+        sorting: SortLinks::new(
+            &ORG_APP_SORT,
+            query.sort_by.as_deref(),
+            query.sort_dir.as_deref(),
+            &format!("/orgs/{}/apps/search", org.id),
+            &format!("/orgs/{}/apps", org.id),
+            query.keyword.as_deref(),
+            ".org-apps",
+        ),
     };
 
     let keyword = query.keyword.clone();
+    let sort_param = sort_query(query.sort_by.as_deref(), query.sort_dir.as_deref());
 
     match list_org_apps_svc(&state, &org.id, query).await {
         Ok(org_apps) => {
@@ -139,7 +164,7 @@ async fn search_org_apps_handler(
                 &org_apps.meta,
                 format!("/orgs/{}/apps/search", org.id).as_str(),
                 format!("/orgs/{}/apps", org.id).as_str(),
-                &keyword_param,
+                &format!("{}{}", keyword_param, sort_param),
                 ".org-apps",
             ));
 
@@ -166,6 +191,7 @@ struct SearchAppSuggestionsTemplate {
     org: OrgDto,
     suggestions: Vec<OrgAppSuggestionDto>,
     error_message: Option<String>,
+    empty_state: EmptyState,
 }
 
 async fn search_app_suggestions_handler(
@@ -181,6 +207,7 @@ async fn search_app_suggestions_handler(
         org,
         suggestions: Vec::new(),
         error_message: None,
+        empty_state: EmptyState::suggestions("apps", query.keyword.as_deref()),
     };
 
     let mut updated_query = query.clone();
@@ -281,7 +308,7 @@ async fn new_org_app_handler(
     Extension(org): Extension<OrgDto>,
     State(state): State<AppState>,
 ) -> Result<Response<Body>> {
-    enforce_policy(&ctx.actor, Resource::OrgApp, Action::Create)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgApp, Action::Create)?;
 
     let mut t = TemplateData::new(&state, ctx.actor.clone(), &pref, csp_nonce.nonce);
     t.title = String::from("Add New Org App");
@@ -305,7 +332,7 @@ async fn post_new_org_app_handler(
     State(state): State<AppState>,
     Form(payload): Form<NewOrgAppFormData>,
 ) -> Result<Response<Body>> {
-    enforce_policy(&ctx.actor, Resource::OrgApp, Action::Create)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgApp, Action::Create)?;
 
     let org_id = org.id.clone();
 
@@ -349,6 +376,8 @@ struct OrgAppPageTemplate {
     t: TemplateData,
     org: OrgDto,
     org_app: OrgAppDto,
+    granted_scopes: Vec<String>,
+    can_edit: bool,
     can_delete: bool,
 }
 
@@ -365,10 +394,20 @@ async fn org_app_page_handler(
 
     t.title = format!("Org App - {}", app_name,);
 
+    let app = get_app_svc(&state, &org_app.app_id)
+        .await?
+        .ok_or(Error::AppNotFound)?;
+    let granted_scopes = granted_app_scopes(&app, &org_app)?
+        .iter()
+        .map(|scope| scope.to_string())
+        .collect();
+
     let tpl = OrgAppPageTemplate {
         t,
         org,
         org_app,
+        granted_scopes,
+        can_edit: ctx.actor.has_permissions(&[Permission::OrgAppsEdit]),
         can_delete: ctx.actor.has_permissions(&[Permission::OrgAppsDelete]),
     };
 
@@ -382,6 +421,7 @@ async fn org_app_page_handler(
 #[template(path = "widgets/org_apps/edit_controls.html")]
 struct OrgAppControlsTemplate {
     org_app: OrgAppDto,
+    can_edit: bool,
     can_delete: bool,
 }
 
@@ -389,10 +429,16 @@ async fn org_app_controls_handler(
     Extension(ctx): Extension<Ctx>,
     Extension(org_app): Extension<OrgAppDto>,
 ) -> Result<Response<Body>> {
-    enforce_policy(&ctx.actor, Resource::OrgApp, Action::Update)?;
+    enforce_org_policy(
+        &ctx.actor,
+        &org_app.org_id,
+        Resource::OrgApp,
+        Action::Update,
+    )?;
 
     let tpl = OrgAppControlsTemplate {
         org_app,
+        can_edit: ctx.actor.has_permissions(&[Permission::OrgAppsEdit]),
         can_delete: ctx.actor.has_permissions(&[Permission::OrgAppsDelete]),
     };
 
@@ -403,6 +449,110 @@ async fn org_app_controls_handler(
         .context(ResponseBuilderSnafu)
 }
 
+#[derive(Template)]
+#[template(path = "widgets/org_apps/scopes_form.html")]
+struct OrgAppScopesFormTemplate {
+    org_app: OrgAppDto,
+    token: String,
+    restricted: bool,
+    scope_options: Vec<CheckboxOption>,
+    error_message: Option<String>,
+}
+
+/// Scope checkboxes of an org app link, only scopes the app declares are offered
+fn scope_options(app_scopes: &[String], selected: Option<&Vec<String>>) -> Vec<CheckboxOption> {
+    app_scopes
+        .iter()
+        .map(|value| CheckboxOption {
+            checked: selected.is_none_or(|selected| selected.contains(value)),
+            value: value.clone(),
+            label: value.clone(),
+            help: Scope::try_from(value.as_str())
+                .ok()
+                .map(|scope| scope_description(&scope).to_string()),
+        })
+        .collect()
+}
+
+async fn org_app_scopes_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org_app): Extension<OrgAppDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(
+        &ctx.actor,
+        &org_app.org_id,
+        Resource::OrgApp,
+        Action::Update,
+    )?;
+
+    let app = get_app_svc(&state, &org_app.app_id)
+        .await?
+        .ok_or(Error::AppNotFound)?;
+    let token = create_csrf_token_svc(&org_app.app_id, &state.config.jwt_secret)?;
+
+    let tpl = OrgAppScopesFormTemplate {
+        token,
+        restricted: org_app.scopes.is_some(),
+        scope_options: scope_options(&app.scopes, org_app.scopes.as_ref()),
+        org_app,
+        error_message: None,
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn post_org_app_scopes_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org_app): Extension<OrgAppDto>,
+    State(state): State<AppState>,
+    Form(pairs): Form<Vec<(String, String)>>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(
+        &ctx.actor,
+        &org_app.org_id,
+        Resource::OrgApp,
+        Action::Update,
+    )?;
+
+    let org_id = org_app.org_id.clone();
+    let app_id = org_app.app_id.clone();
+    let app = get_app_svc(&state, &app_id)
+        .await?
+        .ok_or(Error::AppNotFound)?;
+    let form = OrgAppScopesFormData::try_from(pairs)?;
+
+    let restricted = form.restrict.is_some();
+    let selected = form.scopes.clone();
+    let token = create_csrf_token_svc(&app_id, &state.config.jwt_secret)?;
+
+    match update_org_app_scopes_web_svc(&state, &org_id, &app_id, form).await {
+        Ok(_) => Response::builder()
+            .status(200)
+            .header("HX-Redirect", format!("/orgs/{}/apps/{}", org_id, app_id))
+            .body(Body::from("".to_string()))
+            .context(ResponseBuilderSnafu),
+        Err(err) => {
+            let error_info = ErrorInfo::from(&err);
+            let tpl = OrgAppScopesFormTemplate {
+                org_app,
+                token,
+                restricted,
+                scope_options: scope_options(&app.scopes, Some(&selected)),
+                error_message: Some(error_info.message),
+            };
+
+            Response::builder()
+                .status(error_info.status_code)
+                .body(Body::from(tpl.render().context(TemplateSnafu)?))
+                .context(ResponseBuilderSnafu)
+        }
+    }
+}
+
 #[derive(Template)]
 #[template(path = "widgets/org_apps/delete_form.html")]
 struct DeleteOrgAppFormTemplate {
@@ -418,7 +568,12 @@ async fn delete_org_app_handler(
 ) -> Result<Response<Body>> {
     let config = state.config.clone();
 
-    enforce_policy(&ctx.actor, Resource::OrgApp, Action::Delete)?;
+    enforce_org_policy(
+        &ctx.actor,
+        &org_app.org_id,
+        Resource::OrgApp,
+        Action::Delete,
+    )?;
 
     let token = create_csrf_token_svc(&org_app.app_id.to_string(), &config.jwt_secret)?;
 
@@ -443,7 +598,7 @@ async fn post_delete_org_app_handler(
 ) -> Result<Response<Body>> {
     let config = state.config.clone();
 
-    enforce_policy(&ctx.actor, Resource::OrgApp, Action::Delete)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgApp, Action::Delete)?;
 
     let token = create_csrf_token_svc(&org_app.app_id.to_string(), &config.jwt_secret)?;
     let org_id = org.id.clone();
@@ -0,0 +1,237 @@
+use askama::Template;
+use axum::extract::{Path, Query};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Redirect};
+use axum::{Extension, Form, body::Body, extract::State, response::Response};
+use axum::{
+    Router,
+    routing::{get, post},
+};
+use snafu::ResultExt;
+use std::collections::HashMap;
+use urlencoding::encode;
+
+use crate::dto::{Actor, OrgDto};
+use crate::models::options::CheckboxOption;
+use crate::models::{CspNonce, OrgInvitationView, Pref, TemplateData, TokenFormData};
+use crate::services::org_invitations::{
+    AcceptInvitationFormData, OrgInvitationFormData, accept_org_invitation_svc,
+    create_org_invitation_web_svc, expire_org_invitation_web_svc, list_org_invitations_svc,
+    preview_org_invitation_svc, resend_org_invitation_web_svc,
+};
+use crate::services::org_settings::{default_member_roles, get_org_settings_svc};
+use crate::{
+    Result,
+    ctx::Ctx,
+    error::{ErrorInfo, ResponseBuilderSnafu, TemplateSnafu},
+    run::AppState,
+    services::token::create_csrf_token_svc,
+    web::{Action, Resource, create_role_options, enforce_org_policy},
+};
+
+pub fn org_invitations_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route(
+            "/",
+            get(org_invitations_handler).post(post_org_invitation_handler),
+        )
+        .route(
+            "/{invitation_id}/resend",
+            post(post_resend_org_invitation_handler),
+        )
+        .route(
+            "/{invitation_id}/expire",
+            post(post_expire_org_invitation_handler),
+        )
+        .with_state(state)
+}
+
+#[derive(Template)]
+#[template(path = "widgets/org_invitations/index.html")]
+struct OrgInvitationsTemplate {
+    org: OrgDto,
+    invitations: Vec<OrgInvitationView>,
+    role_options: Vec<CheckboxOption>,
+    token: String,
+    success_message: Option<String>,
+    error_message: Option<String>,
+}
+
+impl OrgInvitationsTemplate {
+    async fn load(state: &AppState, org: OrgDto) -> Result<Self> {
+        let token = create_csrf_token_svc(&org.id, &state.config.jwt_secret)?;
+        let invitations = list_org_invitations_svc(state, &org.id).await?;
+        let settings = get_org_settings_svc(state, &org.id).await?;
+
+        Ok(Self {
+            invitations: invitations.into_iter().map(|item| item.into()).collect(),
+            role_options: create_role_options(&default_member_roles(&settings)),
+            org,
+            token,
+            success_message: None,
+            error_message: None,
+        })
+    }
+
+    fn into_response(self, result: Result<Option<String>>) -> Result<Response<Body>> {
+        let mut tpl = self;
+        let mut status = StatusCode::OK;
+
+        match result {
+            Ok(message) => tpl.success_message = message,
+            Err(err) => {
+                let error_info = ErrorInfo::from(&err);
+                status = error_info.status_code;
+                tpl.error_message = Some(error_info.message);
+            }
+        }
+
+        Response::builder()
+            .status(status)
+            .body(Body::from(tpl.render().context(TemplateSnafu)?))
+            .context(ResponseBuilderSnafu)
+    }
+}
+
+async fn org_invitations_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Create)?;
+
+    OrgInvitationsTemplate::load(&state, org)
+        .await?
+        .into_response(Ok(None))
+}
+
+async fn post_org_invitation_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Form(pairs): Form<Vec<(String, String)>>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Create)?;
+
+    let result = match OrgInvitationFormData::try_from(pairs) {
+        Ok(payload) => create_org_invitation_web_svc(&state, &org.id, payload)
+            .await
+            .map(|issued| Some(format!("Invitation sent to {}.", issued.invitation.email))),
+        Err(err) => Err(err),
+    };
+
+    OrgInvitationsTemplate::load(&state, org)
+        .await?
+        .into_response(result)
+}
+
+async fn post_resend_org_invitation_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Path((_org_id, invitation_id)): Path<(String, String)>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Create)?;
+
+    let result = resend_org_invitation_web_svc(&state, &org.id, &invitation_id, &payload.token)
+        .await
+        .map(|issued| Some(format!("Invitation resent to {}.", issued.invitation.email)));
+
+    OrgInvitationsTemplate::load(&state, org)
+        .await?
+        .into_response(result)
+}
+
+async fn post_expire_org_invitation_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Path((_org_id, invitation_id)): Path<(String, String)>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Create)?;
+
+    let result = expire_org_invitation_web_svc(&state, &org.id, &invitation_id, &payload.token)
+        .await
+        .map(|_| Some("Invitation expired.".to_string()));
+
+    OrgInvitationsTemplate::load(&state, org)
+        .await?
+        .into_response(result)
+}
+
+#[derive(Template)]
+#[template(path = "pages/accept_invitation.html")]
+struct AcceptInvitationTemplate {
+    t: TemplateData,
+    token: String,
+    org_name: String,
+    email: String,
+    has_account: bool,
+    valid: bool,
+    error_message: Option<String>,
+}
+
+pub async fn accept_invitation_handler(
+    Extension(csp_nonce): Extension<CspNonce>,
+    State(state): State<AppState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Response<Body>> {
+    let pref = Pref::new();
+    let actor = Actor::default();
+    let mut t = TemplateData::new(&state, actor, &pref, csp_nonce.nonce);
+    t.title = String::from("Accept Invitation");
+
+    let token = query.get("token").cloned().unwrap_or_default();
+    let mut tpl = AcceptInvitationTemplate {
+        t,
+        token,
+        org_name: String::new(),
+        email: String::new(),
+        has_account: false,
+        valid: false,
+        error_message: query.get("error").cloned(),
+    };
+
+    match preview_org_invitation_svc(&state, &tpl.token).await {
+        Ok(preview) => {
+            tpl.valid = true;
+            tpl.org_name = preview.org_name;
+            tpl.email = preview.invitation.email;
+            tpl.has_account = preview.has_account;
+        }
+        Err(err) => tpl.error_message = Some(err.to_string()),
+    }
+
+    Response::builder()
+        .status(200)
+        .header("Cache-Control", "no-store")
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+pub async fn post_accept_invitation_handler(
+    State(state): State<AppState>,
+    Form(payload): Form<AcceptInvitationFormData>,
+) -> impl IntoResponse {
+    let token = payload.token.clone();
+
+    match accept_org_invitation_svc(&state, payload).await {
+        Ok(_) => {
+            let url = format!(
+                "/login?success={}",
+                encode("Invitation accepted. Login to continue.")
+            );
+            Redirect::to(&url).into_response()
+        }
+        Err(err) => {
+            let url = format!(
+                "/invitations/accept?token={}&error={}",
+                encode(&token),
+                encode(&err.to_string())
+            );
+            Redirect::to(&url).into_response()
+        }
+    }
+}
@@ -1,25 +1,52 @@
 use askama::Template;
 use axum::extract::{Path, Query};
 use axum::http::StatusCode;
-use axum::{Extension, Form, body::Body, extract::State, response::Response};
-use axum::{Router, middleware, routing::get};
+use axum::{Extension, Form, Json, body::Body, extract::State, response::Response};
+use axum::{
+    Router, middleware,
+    routing::{get, post},
+};
 use snafu::{ResultExt, ensure};
+use std::sync::Arc;
 use urlencoding::encode;
 use validator::Validate;
 
-use crate::dto::{ListOrgMembersParamsDto, OrgMemberSuggestionDto};
-use crate::dto::{OrgDto, OrgMemberDto};
+use crate::dto::{
+    ListOrgMembersParamsDto, SuggestionBufDto, SuggestionParamsDto, UserDto, UserSuggestionDto,
+};
+use crate::dto::{MAX_BULK_ITEMS, PermissionImpactDto};
+use crate::dto::{MemberPermissionImpactDto, MemberPermissionsDto, OrgDto, OrgMemberDto};
+use crate::dto::{ORG_MEMBER_SORT, sort_query};
 use crate::dto::{Permission, Role};
 use crate::error::ValidationSnafu;
-use crate::models::options::SelectOption;
-use crate::models::{CspNonce, OrgMemberParams, OrgMemberView, PaginationLinks, TokenFormData};
+use crate::models::options::CheckboxOption;
+use crate::models::{
+    BulkStatusFormData, CspNonce, EmptyState, OrgMemberParams, OrgMemberView, PaginationLinks,
+    RoleElevationView, SortLinks, TokenFormData,
+};
+use crate::services::elevations::{
+    RoleElevationFormData, approve_elevation_web_svc, elevated_until_svc,
+    list_member_elevations_svc, reject_elevation_web_svc, request_elevation_web_svc,
+    revert_elevation_web_svc,
+};
 use crate::services::org_members::{
-    NewOrgMemberFormData, UpdateOrgMemberFormData, create_org_member_web_svc,
-    delete_org_member_web_svc, list_org_member_suggestions_svc, list_org_members_svc,
+    BulkOrgMembersFormData, NewOrgMemberFormData, UpdateOrgMemberFormData,
+    bulk_assign_org_members_web_svc, bulk_update_org_member_status_web_svc,
+    create_org_member_web_svc, delete_org_member_web_svc, list_org_members_svc,
     update_org_member_web_svc,
 };
+use crate::services::org_roles::{
+    OrgMemberRolesFormData, assign_member_roles_web_svc, list_member_roles_svc, list_org_roles_svc,
+};
+use crate::services::org_settings::{default_member_roles, get_org_settings_svc};
+use crate::services::permissions::{
+    member_permissions, preview_role_permissions, role_change_impact,
+};
+use crate::services::suggestions::suggest_org_members_svc;
+use crate::services::teams::list_member_teams_svc;
 use crate::services::users::get_user_svc;
 use crate::validators::flatten_errors;
+use crate::web::bulk::render_bulk_results;
 use crate::web::middleware::org_member_middleware;
 use crate::{
     Error, Result,
@@ -28,13 +55,19 @@ use crate::{
     models::{Pref, TemplateData},
     run::AppState,
     services::token::create_csrf_token_svc,
-    web::{Action, Resource, enforce_policy},
+    web::{Action, Resource, enforce_org_policy, enforce_policy, missing_permission_hint},
 };
 
 pub fn org_members_routes(state: AppState) -> Router<AppState> {
     Router::new()
         .route("/", get(org_members_handler))
         .route("/search", get(search_org_members_handler))
+        .route("/bulk-status", post(post_bulk_org_member_status_handler))
+        .route(
+            "/bulk",
+            get(bulk_org_members_handler).post(post_bulk_org_members_handler),
+        )
+        .route("/bulk-suggestions", get(bulk_member_suggestions_handler))
         .route(
             "/new",
             get(new_org_member_handler).post(post_new_org_member_handler),
@@ -47,6 +80,7 @@ pub fn org_members_routes(state: AppState) -> Router<AppState> {
             "/select-member-suggestion/{user_id}",
             get(select_org_member_suggestion_handler),
         )
+        .route("/role-preview", get(role_preview_handler))
         .nest("/{user_id}", org_member_inner_routes(state.clone()))
         .with_state(state)
 }
@@ -55,6 +89,31 @@ fn org_member_inner_routes(state: AppState) -> Router<AppState> {
     Router::new()
         .route("/", get(org_member_page_handler))
         .route("/edit-controls", get(org_member_controls_handler))
+        .route("/permissions", get(org_member_permissions_handler))
+        .route(
+            "/permission-impact",
+            get(org_member_permission_impact_handler),
+        )
+        .route(
+            "/custom-roles",
+            get(org_member_custom_roles_handler).post(post_org_member_custom_roles_handler),
+        )
+        .route(
+            "/elevations",
+            get(org_member_elevations_handler).post(post_org_member_elevation_handler),
+        )
+        .route(
+            "/elevations/{elevation_id}/approve",
+            post(post_approve_elevation_handler),
+        )
+        .route(
+            "/elevations/{elevation_id}/reject",
+            post(post_reject_elevation_handler),
+        )
+        .route(
+            "/elevations/{elevation_id}/revert",
+            post(post_revert_elevation_handler),
+        )
         .route(
             "/edit",
             get(update_org_member_handler).post(post_update_org_member_handler),
@@ -76,6 +135,7 @@ struct OrgMembersPageTemplate {
     t: TemplateData,
     org: OrgDto,
     query_params: String,
+    can_invite: bool,
 }
 
 async fn org_members_handler(
@@ -86,7 +146,7 @@ async fn org_members_handler(
     State(state): State<AppState>,
     Query(query): Query<ListOrgMembersParamsDto>,
 ) -> Result<Response<Body>> {
-    enforce_policy(&ctx.actor, Resource::OrgMember, Action::Read)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Read)?;
 
     let errors = query.validate();
     ensure!(
@@ -101,6 +161,7 @@ async fn org_members_handler(
 
     let tpl = OrgMembersPageTemplate {
         t,
+        can_invite: enforce_policy(&ctx.actor, Resource::OrgMember, Action::Create).is_ok(),
         org,
         query_params: query.to_string(),
     };
@@ -117,6 +178,10 @@ struct SearchOrgMembersTemplate {
     org_members: Vec<OrgMemberView>,
     pagination: Option<PaginationLinks>,
     error_message: Option<String>,
+    empty_state: EmptyState,
+    sorting: SortLinks,
+    bulk_token: Option<String>,
+    bulk_url: String,
 }
 async fn search_org_members_handler(
     Extension(ctx): Extension<Ctx>,
@@ -124,15 +189,32 @@ async fn search_org_members_handler(
     State(state): State<AppState>,
     Query(query): Query<ListOrgMembersParamsDto>,
 ) -> Result<Response<Body>> {
-    enforce_policy(&ctx.actor, Resource::OrgMember, Action::Read)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Read)?;
 
     let mut tpl = SearchOrgMembersTemplate {
         org_members: Vec::new(),
         pagination: None,
         error_message: None,
+        empty_state: EmptyState::org_members(&ctx.actor, &org.id, query.keyword.as_deref()),
+        sorting: SortLinks::new(
+            &ORG_MEMBER_SORT,
+            query.sort_by.as_deref(),
+            query.sort_dir.as_deref(),
+            &format!("/orgs/{}/members/search", org.id),
+            &format!("/orgs/{}/members", org.id),
+            query.keyword.as_deref(),
+            ".org-members",
+        ),
+        bulk_token: None,
+        bulk_url: format!("/orgs/{}/members/bulk-status", org.id),
     };
 
+    if ctx.actor.has_permissions(&[Permission::OrgMembersEdit]) {
+        tpl.bulk_token = Some(create_csrf_token_svc(&org.id, &state.config.jwt_secret)?);
+    }
+
     let keyword = query.keyword.clone();
+    let sort_param = sort_query(query.sort_by.as_deref(), query.sort_dir.as_deref());
 
     match list_org_members_svc(&state, &org.id, query).await {
         Ok(org_members) => {
@@ -140,16 +222,20 @@ async fn search_org_members_handler(
             if let Some(keyword) = &keyword {
                 keyword_param = format!("&keyword={}", encode(keyword));
             }
+            let elevated = elevated_until_svc(&state, &org.id).await?;
             tpl.org_members = org_members
                 .data
                 .into_iter()
-                .map(OrgMemberView::from)
+                .map(|member| {
+                    let until = elevated.get(&member.user_id).copied();
+                    OrgMemberView::from(member).with_elevated_until(until)
+                })
                 .collect();
             tpl.pagination = Some(PaginationLinks::new(
                 &org_members.meta,
                 format!("/orgs/{}/members/search", org.id).as_str(),
                 format!("/orgs/{}/members", org.id).as_str(),
-                &keyword_param,
+                &format!("{}{}", keyword_param, sort_param),
                 ".org-members",
             ));
 
@@ -170,35 +256,179 @@ async fn search_org_members_handler(
     }
 }
 
+async fn post_bulk_org_member_status_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Form(pairs): Form<Vec<(String, String)>>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Update)?;
+
+    let result = match BulkStatusFormData::try_from(pairs) {
+        Ok(form) => bulk_update_org_member_status_web_svc(&state, &org.id, form).await,
+        Err(err) => Err(err),
+    };
+
+    render_bulk_results(result)
+}
+
+#[derive(Template)]
+#[template(path = "pages/org_members/bulk.html")]
+struct BulkOrgMembersTemplate {
+    t: TemplateData,
+    org: OrgDto,
+    token: String,
+    role_options: Vec<CheckboxOption>,
+    role_preview_url: String,
+}
+
+/// Add members page, several users picked from suggestions share the same roles
+async fn bulk_org_members_handler(
+    Extension(csp_nonce): Extension<CspNonce>,
+    Extension(ctx): Extension<Ctx>,
+    Extension(pref): Extension<Pref>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Create)?;
+
+    let mut t = TemplateData::new(&state, ctx.actor.clone(), &pref, csp_nonce.nonce);
+    t.title = String::from("Add Org Members");
+
+    let settings = get_org_settings_svc(&state, &org.id).await?;
+
+    let tpl = BulkOrgMembersTemplate {
+        t,
+        token: create_csrf_token_svc(&org.id, &state.config.jwt_secret)?,
+        role_options: create_role_options(&default_member_roles(&settings)),
+        role_preview_url: role_preview_url(&org.id),
+        org,
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+#[derive(Template)]
+#[template(path = "widgets/org_members/bulk_member_suggestions.html")]
+struct BulkMemberSuggestionsTemplate {
+    selected: Vec<UserDto>,
+    suggestions: Vec<UserSuggestionDto>,
+    has_more: bool,
+    error_message: Option<String>,
+    empty_state: EmptyState,
+}
+
+/// Suggestions as checkboxes, users already ticked stay on top across searches
+async fn bulk_member_suggestions_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Query(pairs): Query<Vec<(String, String)>>,
+) -> Result<Response<Body>> {
+    enforce_policy(&ctx.actor, Resource::User, Action::Read)?;
+
+    let mut query = SuggestionParamsDto::default();
+    let mut selected_ids: Vec<String> = Vec::new();
+    for (key, value) in pairs.into_iter() {
+        match key.as_str() {
+            "keyword" => query.keyword = Some(value),
+            "user_ids" if !value.is_empty() && !selected_ids.contains(&value) => {
+                selected_ids.push(value)
+            }
+            _ => {}
+        }
+    }
+    selected_ids.truncate(MAX_BULK_ITEMS);
+
+    let mut tpl = BulkMemberSuggestionsTemplate {
+        selected: Vec::new(),
+        suggestions: Vec::new(),
+        has_more: false,
+        error_message: None,
+        empty_state: EmptyState::suggestions("users", query.keyword.as_deref()),
+    };
+
+    for user_id in selected_ids.iter() {
+        if let Some(user) = get_user_svc(&state, user_id).await? {
+            tpl.selected.push(user);
+        }
+    }
+
+    match suggest_org_members_svc(&state, &org.id, query).await {
+        Ok(suggestions) => {
+            tpl.has_more = suggestions.has_more;
+            tpl.suggestions = suggestions
+                .items
+                .iter()
+                .filter(|suggestion| !selected_ids.contains(&suggestion.id))
+                .cloned()
+                .collect();
+
+            Ok(Response::builder()
+                .status(200)
+                .body(Body::from(tpl.render().context(TemplateSnafu)?))
+                .context(ResponseBuilderSnafu)?)
+        }
+        Err(err) => {
+            let error_info = ErrorInfo::from(&err);
+            tpl.error_message = Some(error_info.message);
+
+            Ok(Response::builder()
+                .status(error_info.status_code)
+                .body(Body::from(tpl.render().context(TemplateSnafu)?))
+                .context(ResponseBuilderSnafu)?)
+        }
+    }
+}
+
+async fn post_bulk_org_members_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Form(pairs): Form<Vec<(String, String)>>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Create)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Update)?;
+
+    let result = match BulkOrgMembersFormData::try_from(pairs) {
+        Ok(form) => bulk_assign_org_members_web_svc(&state, &org.id, form).await,
+        Err(err) => Err(err),
+    };
+
+    render_bulk_results(result)
+}
+
 #[derive(Template)]
 #[template(path = "widgets/org_members/search_member_suggestions.html")]
 struct SearchMemberSuggestionsTemplate {
     org: OrgDto,
-    suggestions: Vec<OrgMemberSuggestionDto>,
+    suggestions: Arc<SuggestionBufDto>,
     error_message: Option<String>,
+    empty_state: EmptyState,
 }
 
 async fn search_member_suggestions_handler(
     Extension(ctx): Extension<Ctx>,
     Extension(org): Extension<OrgDto>,
     State(state): State<AppState>,
-    Query(query): Query<ListOrgMembersParamsDto>,
+    Query(query): Query<SuggestionParamsDto>,
 ) -> Result<Response<Body>> {
     enforce_policy(&ctx.actor, Resource::User, Action::Read)?;
 
     let org_id = org.id.clone();
     let mut tpl = SearchMemberSuggestionsTemplate {
         org,
-        suggestions: Vec::new(),
+        suggestions: Arc::default(),
         error_message: None,
+        empty_state: EmptyState::suggestions("users", query.keyword.as_deref()),
     };
 
-    let mut updated_query = query.clone();
-    updated_query.per_page = Some(10);
-
-    match list_org_member_suggestions_svc(&state, &org_id, updated_query).await {
-        Ok(users) => {
-            tpl.suggestions = users.data;
+    match suggest_org_members_svc(&state, &org_id, query).await {
+        Ok(suggestions) => {
+            tpl.suggestions = suggestions;
 
             Ok(Response::builder()
                 .status(200)
@@ -222,25 +452,74 @@ async fn search_member_suggestions_handler(
 struct SelectMemberSuggestionTemplate {
     org: OrgDto,
     payload: NewOrgMemberFormData,
-    role_options: Vec<SelectOption>,
+    role_options: Vec<CheckboxOption>,
+    role_preview_url: String,
     error_message: Option<String>,
 }
 
-fn create_role_options() -> Vec<SelectOption> {
-    vec![
-        SelectOption {
-            value: Role::OrgAdmin.to_string(),
-            label: "Admin".to_string(),
-        },
-        SelectOption {
-            value: Role::OrgEditor.to_string(),
-            label: "Editor".to_string(),
-        },
-        SelectOption {
-            value: Role::OrgViewer.to_string(),
-            label: "Viewer".to_string(),
-        },
-    ]
+pub fn create_role_options(selected: &[String]) -> Vec<CheckboxOption> {
+    [Role::OrgAdmin, Role::OrgEditor, Role::OrgViewer]
+        .into_iter()
+        .map(|role| {
+            let value = role.to_string();
+            CheckboxOption {
+                checked: selected.contains(&value),
+                value,
+                label: role.label().to_string(),
+                help: Some(role.description().to_string()),
+            }
+        })
+        .collect()
+}
+
+fn role_preview_url(org_id: &str) -> String {
+    format!("/orgs/{}/members/role-preview", org_id)
+}
+
+#[derive(Template)]
+#[template(path = "widgets/org_members/role_preview.html")]
+struct RolePreviewTemplate {
+    permissions: Vec<Permission>,
+    error_message: Option<String>,
+}
+
+/// Effective permissions for the roles currently ticked in the member form
+async fn role_preview_handler(
+    Extension(ctx): Extension<Ctx>,
+    Query(pairs): Query<Vec<(String, String)>>,
+) -> Result<Response<Body>> {
+    enforce_policy(&ctx.actor, Resource::OrgMember, Action::Read)?;
+
+    let roles: Vec<String> = pairs
+        .into_iter()
+        .filter(|(key, _)| key == "roles")
+        .map(|(_, value)| value)
+        .collect();
+
+    let mut tpl = RolePreviewTemplate {
+        permissions: Vec::new(),
+        error_message: None,
+    };
+
+    let status = match preview_role_permissions(&roles) {
+        Ok(permissions) => {
+            tpl.permissions = permissions
+                .iter()
+                .filter_map(|permission| Permission::try_from(permission.as_str()).ok())
+                .collect();
+            StatusCode::OK
+        }
+        Err(err) => {
+            let error_info = ErrorInfo::from(&err);
+            tpl.error_message = Some(error_info.message);
+            error_info.status_code
+        }
+    };
+
+    Response::builder()
+        .status(status)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
 }
 
 async fn select_org_member_suggestion_handler(
@@ -251,17 +530,20 @@ async fn select_org_member_suggestion_handler(
 ) -> Result<Response<Body>> {
     enforce_policy(&ctx.actor, Resource::User, Action::Read)?;
     let token = create_csrf_token_svc("new_org_member", &state.config.jwt_secret)?;
+    let settings = get_org_settings_svc(&state, &org.id).await?;
+    let roles = default_member_roles(&settings);
 
     let mut tpl = SelectMemberSuggestionTemplate {
+        role_preview_url: role_preview_url(&org.id),
         org,
         payload: NewOrgMemberFormData {
             token,
             user_id: "".to_string(),
             user_email: "".to_string(),
-            role: "".to_string(),
+            roles: roles.clone(),
             active: Some("1".to_string()),
         },
-        role_options: create_role_options(),
+        role_options: create_role_options(&roles),
         error_message: None,
     };
 
@@ -312,7 +594,7 @@ async fn new_org_member_handler(
     Extension(org): Extension<OrgDto>,
     State(state): State<AppState>,
 ) -> Result<Response<Body>> {
-    enforce_policy(&ctx.actor, Resource::OrgMember, Action::Create)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Create)?;
 
     let mut t = TemplateData::new(&state, ctx.actor.clone(), &pref, csp_nonce.nonce);
     t.title = String::from("Create New Org Member");
@@ -334,9 +616,9 @@ async fn post_new_org_member_handler(
     Extension(ctx): Extension<Ctx>,
     Extension(org): Extension<OrgDto>,
     State(state): State<AppState>,
-    Form(payload): Form<NewOrgMemberFormData>,
+    Form(pairs): Form<Vec<(String, String)>>,
 ) -> Result<Response<Body>> {
-    enforce_policy(&ctx.actor, Resource::OrgMember, Action::Create)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Create)?;
 
     let org_id = org.id.clone();
 
@@ -348,7 +630,10 @@ async fn post_new_org_member_handler(
 
     let status: StatusCode;
 
-    let result = create_org_member_web_svc(&state, &org_id, payload).await;
+    let result = match NewOrgMemberFormData::try_from(pairs) {
+        Ok(payload) => create_org_member_web_svc(&state, &org_id, payload).await,
+        Err(err) => Err(err),
+    };
 
     match result {
         Ok(_) => {
@@ -380,9 +665,12 @@ struct OrgMemberPageTemplate {
     t: TemplateData,
     org: OrgDto,
     org_member: OrgMemberDto,
+    member_permissions: MemberPermissionsDto,
     updated: bool,
     can_edit: bool,
     can_delete: bool,
+    edit_hint: Option<String>,
+    delete_hint: Option<String>,
 }
 
 async fn org_member_page_handler(
@@ -398,13 +686,19 @@ async fn org_member_page_handler(
 
     t.title = format!("Org Member - {}", member_email,);
 
+    let custom_roles = list_member_roles_svc(&state, &org.id, &org_member.user_id).await?;
+    let teams = list_member_teams_svc(&state, &org.id, &org_member.user_id).await?;
+
     let tpl = OrgMemberPageTemplate {
         t,
+        member_permissions: member_permissions(&org, &org_member, &custom_roles, &teams),
         org,
         org_member,
         updated: false,
         can_edit: ctx.actor.has_permissions(&[Permission::OrgMembersEdit]),
         can_delete: ctx.actor.has_permissions(&[Permission::OrgMembersDelete]),
+        edit_hint: missing_permission_hint(&ctx.actor, Resource::OrgMember, Action::Update),
+        delete_hint: missing_permission_hint(&ctx.actor, Resource::OrgMember, Action::Delete),
     };
 
     Response::builder()
@@ -413,6 +707,322 @@ async fn org_member_page_handler(
         .context(ResponseBuilderSnafu)
 }
 
+/// Effective permissions of the member and the roles granting each one
+async fn org_member_permissions_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    Extension(org_member): Extension<OrgMemberDto>,
+    State(state): State<AppState>,
+) -> Result<Json<MemberPermissionsDto>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Read)?;
+
+    let custom_roles = list_member_roles_svc(&state, &org.id, &org_member.user_id).await?;
+    let teams = list_member_teams_svc(&state, &org.id, &org_member.user_id).await?;
+
+    Ok(Json(member_permissions(
+        &org,
+        &org_member,
+        &custom_roles,
+        &teams,
+    )))
+}
+
+/// Permissions the member would gain or lose with the given roles and status,
+/// ie: `?roles=OrgViewer&active=1`
+async fn org_member_permission_impact_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    Extension(org_member): Extension<OrgMemberDto>,
+    Query(pairs): Query<Vec<(String, String)>>,
+) -> Result<Json<PermissionImpactDto>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Update)?;
+
+    let roles: Vec<String> = pairs
+        .iter()
+        .filter(|(key, _)| key == "roles")
+        .map(|(_, value)| value.clone())
+        .collect();
+    let active = pairs.iter().any(|(key, _)| key == "active");
+
+    Ok(Json(role_change_impact(&org, &org_member, &roles, active)?))
+}
+
+#[derive(Template)]
+#[template(path = "widgets/org_members/custom_roles.html")]
+struct OrgMemberCustomRolesTemplate {
+    org_member: OrgMemberDto,
+    role_options: Vec<CheckboxOption>,
+    token: String,
+    error_message: Option<String>,
+}
+
+impl OrgMemberCustomRolesTemplate {
+    async fn load(state: &AppState, org_member: OrgMemberDto) -> Result<Self> {
+        let token = create_csrf_token_svc(&org_member.user_id, &state.config.jwt_secret)?;
+        let roles = list_org_roles_svc(state, &org_member.org_id).await?;
+        let assigned: Vec<String> =
+            list_member_roles_svc(state, &org_member.org_id, &org_member.user_id)
+                .await?
+                .into_iter()
+                .map(|role| role.id)
+                .collect();
+
+        Ok(Self {
+            role_options: roles
+                .into_iter()
+                .map(|role| CheckboxOption {
+                    checked: assigned.contains(&role.id),
+                    value: role.id,
+                    label: role.name,
+                    help: Some(role.description).filter(|help| !help.is_empty()),
+                })
+                .collect(),
+            org_member,
+            token,
+            error_message: None,
+        })
+    }
+
+    fn into_response(self, result: Result<()>) -> Result<Response<Body>> {
+        let mut tpl = self;
+        let mut status = StatusCode::OK;
+
+        if let Err(err) = result {
+            let error_info = ErrorInfo::from(&err);
+            status = error_info.status_code;
+            tpl.error_message = Some(error_info.message);
+        }
+
+        Response::builder()
+            .status(status)
+            .body(Body::from(tpl.render().context(TemplateSnafu)?))
+            .context(ResponseBuilderSnafu)
+    }
+}
+
+async fn org_member_custom_roles_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org_member): Extension<OrgMemberDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(
+        &ctx.actor,
+        &org_member.org_id,
+        Resource::OrgMember,
+        Action::Update,
+    )?;
+
+    OrgMemberCustomRolesTemplate::load(&state, org_member)
+        .await?
+        .into_response(Ok(()))
+}
+
+async fn post_org_member_custom_roles_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org_member): Extension<OrgMemberDto>,
+    State(state): State<AppState>,
+    Form(pairs): Form<Vec<(String, String)>>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(
+        &ctx.actor,
+        &org_member.org_id,
+        Resource::OrgMember,
+        Action::Update,
+    )?;
+
+    let result = match OrgMemberRolesFormData::try_from(pairs) {
+        Ok(payload) => assign_member_roles_web_svc(&state, &org_member, payload)
+            .await
+            .map(|_| ()),
+        Err(err) => Err(err),
+    };
+
+    OrgMemberCustomRolesTemplate::load(&state, org_member)
+        .await?
+        .into_response(result)
+}
+
+#[derive(Template)]
+#[template(path = "widgets/org_members/elevations.html")]
+struct OrgMemberElevationsTemplate {
+    org_member: OrgMemberDto,
+    elevations: Vec<RoleElevationView>,
+    approval_required: bool,
+    token: String,
+    error_message: Option<String>,
+}
+
+impl OrgMemberElevationsTemplate {
+    async fn load(state: &AppState, org_member: OrgMemberDto) -> Result<Self> {
+        let token = create_csrf_token_svc(&org_member.user_id, &state.config.jwt_secret)?;
+        let elevations =
+            list_member_elevations_svc(state, &org_member.org_id, &org_member.user_id).await?;
+
+        Ok(Self {
+            org_member,
+            elevations: elevations
+                .into_iter()
+                .map(RoleElevationView::from)
+                .collect(),
+            approval_required: state.config.approvals.elevation_approval,
+            token,
+            error_message: None,
+        })
+    }
+
+    fn into_response(self, result: Result<()>) -> Result<Response<Body>> {
+        let mut tpl = self;
+        let mut status = StatusCode::OK;
+
+        if let Err(err) = result {
+            let error_info = ErrorInfo::from(&err);
+            status = error_info.status_code;
+            tpl.error_message = Some(error_info.message);
+        }
+
+        Response::builder()
+            .status(status)
+            .body(Body::from(tpl.render().context(TemplateSnafu)?))
+            .context(ResponseBuilderSnafu)
+    }
+}
+
+async fn org_member_elevations_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org_member): Extension<OrgMemberDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(
+        &ctx.actor,
+        &org_member.org_id,
+        Resource::OrgMember,
+        Action::Update,
+    )?;
+
+    OrgMemberElevationsTemplate::load(&state, org_member)
+        .await?
+        .into_response(Ok(()))
+}
+
+async fn post_org_member_elevation_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org_member): Extension<OrgMemberDto>,
+    State(state): State<AppState>,
+    Form(payload): Form<RoleElevationFormData>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(
+        &ctx.actor,
+        &org_member.org_id,
+        Resource::OrgMember,
+        Action::Update,
+    )?;
+    let actor = ctx.actor().expect("actor is required");
+
+    let result = request_elevation_web_svc(
+        &state,
+        &actor.id,
+        &org_member.org_id,
+        &org_member.user_id,
+        payload,
+    )
+    .await
+    .map(|_| ());
+
+    OrgMemberElevationsTemplate::load(&state, org_member)
+        .await?
+        .into_response(result)
+}
+
+async fn post_approve_elevation_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org_member): Extension<OrgMemberDto>,
+    State(state): State<AppState>,
+    Path((_org_id, _user_id, elevation_id)): Path<(String, String, String)>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(
+        &ctx.actor,
+        &org_member.org_id,
+        Resource::OrgMember,
+        Action::Update,
+    )?;
+    let actor = ctx.actor().expect("actor is required");
+
+    let result = approve_elevation_web_svc(
+        &state,
+        &actor.id,
+        &org_member,
+        &elevation_id,
+        &payload.token,
+    )
+    .await
+    .map(|_| ());
+
+    OrgMemberElevationsTemplate::load(&state, org_member)
+        .await?
+        .into_response(result)
+}
+
+async fn post_reject_elevation_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org_member): Extension<OrgMemberDto>,
+    State(state): State<AppState>,
+    Path((_org_id, _user_id, elevation_id)): Path<(String, String, String)>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(
+        &ctx.actor,
+        &org_member.org_id,
+        Resource::OrgMember,
+        Action::Update,
+    )?;
+    let actor = ctx.actor().expect("actor is required");
+
+    let result = reject_elevation_web_svc(
+        &state,
+        &actor.id,
+        &org_member,
+        &elevation_id,
+        &payload.token,
+    )
+    .await
+    .map(|_| ());
+
+    OrgMemberElevationsTemplate::load(&state, org_member)
+        .await?
+        .into_response(result)
+}
+
+async fn post_revert_elevation_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org_member): Extension<OrgMemberDto>,
+    State(state): State<AppState>,
+    Path((_org_id, _user_id, elevation_id)): Path<(String, String, String)>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(
+        &ctx.actor,
+        &org_member.org_id,
+        Resource::OrgMember,
+        Action::Update,
+    )?;
+    let actor = ctx.actor().expect("actor is required");
+
+    let result = revert_elevation_web_svc(
+        &state,
+        &actor.id,
+        &org_member,
+        &elevation_id,
+        &payload.token,
+    )
+    .await
+    .map(|_| ());
+
+    OrgMemberElevationsTemplate::load(&state, org_member)
+        .await?
+        .into_response(result)
+}
+
 #[derive(Template)]
 #[template(path = "widgets/org_members/edit_controls.html")]
 struct OrgMemberControlsTemplate {
@@ -420,19 +1030,28 @@ struct OrgMemberControlsTemplate {
     updated: bool,
     can_edit: bool,
     can_delete: bool,
+    edit_hint: Option<String>,
+    delete_hint: Option<String>,
 }
 
 async fn org_member_controls_handler(
     Extension(ctx): Extension<Ctx>,
     Extension(org_member): Extension<OrgMemberDto>,
 ) -> Result<Response<Body>> {
-    enforce_policy(&ctx.actor, Resource::OrgMember, Action::Update)?;
+    enforce_org_policy(
+        &ctx.actor,
+        &org_member.org_id,
+        Resource::OrgMember,
+        Action::Update,
+    )?;
 
     let tpl = OrgMemberControlsTemplate {
         org_member,
         updated: false,
         can_edit: ctx.actor.has_permissions(&[Permission::OrgMembersEdit]),
         can_delete: ctx.actor.has_permissions(&[Permission::OrgMembersDelete]),
+        edit_hint: missing_permission_hint(&ctx.actor, Resource::OrgMember, Action::Update),
+        delete_hint: missing_permission_hint(&ctx.actor, Resource::OrgMember, Action::Delete),
     };
 
     Response::builder()
@@ -447,7 +1066,8 @@ async fn org_member_controls_handler(
 struct UpdateOrgMemberTemplate {
     org_member: OrgMemberDto,
     payload: UpdateOrgMemberFormData,
-    role_options: Vec<SelectOption>,
+    role_options: Vec<CheckboxOption>,
+    role_preview_url: String,
     error_message: Option<String>,
 }
 
@@ -458,24 +1078,29 @@ async fn update_org_member_handler(
 ) -> Result<Response<Body>> {
     let config = state.config.clone();
 
-    enforce_policy(&ctx.actor, Resource::OrgMember, Action::Update)?;
+    enforce_org_policy(
+        &ctx.actor,
+        &org_member.org_id,
+        Resource::OrgMember,
+        Action::Update,
+    )?;
     let token = create_csrf_token_svc(org_member.user_id.to_string().as_str(), &config.jwt_secret)?;
 
-    // We only expect one role
-    let role = org_member.roles.first().unwrap().to_string();
+    let roles: Vec<String> = org_member.roles.iter().map(|r| r.to_string()).collect();
     let active = match org_member.status.as_str() {
         "active" => Some("1".to_string()),
         _ => None,
     };
 
     let tpl = UpdateOrgMemberTemplate {
+        role_options: create_role_options(&roles),
+        role_preview_url: role_preview_url(&org_member.org_id),
         org_member,
         payload: UpdateOrgMemberFormData {
             token,
-            role,
+            roles,
             active,
         },
-        role_options: create_role_options(),
         error_message: None,
     };
 
@@ -488,37 +1113,68 @@ async fn update_org_member_handler(
 
 async fn post_update_org_member_handler(
     Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
     Extension(org_member): Extension<OrgMemberDto>,
     State(state): State<AppState>,
-    Form(payload): Form<UpdateOrgMemberFormData>,
+    Form(pairs): Form<Vec<(String, String)>>,
 ) -> Result<Response<Body>> {
     let config = state.config.clone();
 
-    enforce_policy(&ctx.actor, Resource::OrgMember, Action::Update)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Update)?;
 
     let token = create_csrf_token_svc(&org_member.user_id.to_string(), &config.jwt_secret)?;
     let org_id = org_member.org_id.clone();
     let user_id = org_member.user_id.clone();
 
-    // We only expect one role
-    let role = org_member.roles.first().unwrap().to_string();
+    let roles: Vec<String> = org_member.roles.iter().map(|r| r.to_string()).collect();
     let active = match org_member.status.as_str() {
         "active" => Some("1".to_string()),
         _ => None,
     };
 
     let mut tpl = UpdateOrgMemberTemplate {
+        role_options: create_role_options(&roles),
+        role_preview_url: role_preview_url(&org_member.org_id),
         org_member,
         payload: UpdateOrgMemberFormData {
             token,
-            role,
+            roles,
             active,
         },
-        role_options: create_role_options(),
         error_message: None,
     };
 
-    let result = update_org_member_web_svc(&state, &org_id, &user_id, payload).await;
+    // Changes to the effective permissions are confirmed first
+    let confirmed = pairs.iter().any(|(key, _)| key == "confirm");
+
+    let result = match UpdateOrgMemberFormData::try_from(pairs) {
+        Ok(payload) if !confirmed && !payload.roles.is_empty() => {
+            match role_change_impact(
+                &org,
+                &tpl.org_member,
+                &payload.roles,
+                payload.active.is_some(),
+            ) {
+                Ok(mut impact) if impact.has_changes() => {
+                    let tpl = ConfirmRoleChangeTemplate {
+                        org_member: tpl.org_member,
+                        payload,
+                        impact: impact.members.remove(0),
+                    };
+
+                    return Response::builder()
+                        .status(200)
+                        .header("Content-Type", "text/html")
+                        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+                        .context(ResponseBuilderSnafu);
+                }
+                Ok(_) => update_org_member_web_svc(&state, &org_id, &user_id, payload).await,
+                Err(err) => Err(err),
+            }
+        }
+        Ok(payload) => update_org_member_web_svc(&state, &org_id, &user_id, payload).await,
+        Err(err) => Err(err),
+    };
 
     match result {
         Ok(updated_member) => {
@@ -528,6 +1184,12 @@ async fn post_update_org_member_handler(
                 updated: true,
                 can_edit: ctx.actor.has_permissions(&[Permission::OrgMembersEdit]),
                 can_delete: ctx.actor.has_permissions(&[Permission::OrgMembersDelete]),
+                edit_hint: missing_permission_hint(&ctx.actor, Resource::OrgMember, Action::Update),
+                delete_hint: missing_permission_hint(
+                    &ctx.actor,
+                    Resource::OrgMember,
+                    Action::Delete,
+                ),
             };
 
             Ok(Response::builder()
@@ -562,6 +1224,14 @@ async fn post_update_org_member_handler(
     }
 }
 
+#[derive(Template)]
+#[template(path = "widgets/org_members/confirm_role_change.html")]
+struct ConfirmRoleChangeTemplate {
+    org_member: OrgMemberDto,
+    payload: UpdateOrgMemberFormData,
+    impact: MemberPermissionImpactDto,
+}
+
 #[derive(Template)]
 #[template(path = "widgets/org_members/delete_form.html")]
 struct DeleteOrgMemberFormTemplate {
@@ -577,7 +1247,12 @@ async fn delete_org_member_handler(
 ) -> Result<Response<Body>> {
     let config = state.config.clone();
 
-    enforce_policy(&ctx.actor, Resource::OrgMember, Action::Delete)?;
+    enforce_org_policy(
+        &ctx.actor,
+        &org_member.org_id,
+        Resource::OrgMember,
+        Action::Delete,
+    )?;
 
     let token = create_csrf_token_svc(&org_member.user_id.to_string(), &config.jwt_secret)?;
 
@@ -602,7 +1277,7 @@ async fn post_delete_org_member_handler(
 ) -> Result<Response<Body>> {
     let config = state.config.clone();
 
-    enforce_policy(&ctx.actor, Resource::OrgMember, Action::Delete)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Delete)?;
 
     let token = create_csrf_token_svc(&org_member.user_id.to_string(), &config.jwt_secret)?;
     let org_id = org.id.clone();
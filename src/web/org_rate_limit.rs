@@ -0,0 +1,92 @@
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::CookieJar;
+
+use crate::Error;
+use crate::run::AppState;
+use crate::services::org_rate_limits::take_org_request_svc;
+use crate::services::token::verify_auth_token;
+
+use super::AUTH_TOKEN_COOKIE;
+use super::oauth::bearer_token;
+use super::{ROUTE_VARIANT_HEADER, SESSION_REGION_HEADER};
+
+/// Headers kept when an error response is rebuilt by the response mappers
+const PRESERVED_HEADERS: &[&str] = &[
+    "x-ratelimit-limit",
+    "x-ratelimit-burst",
+    "x-ratelimit-remaining",
+    "retry-after",
+    SESSION_REGION_HEADER,
+    ROUTE_VARIANT_HEADER,
+];
+
+/// Takes each request from the budget of the token's org.
+///
+/// Requests without a valid token pass through, the handlers reject them.
+/// Usage is reported in the `X-RateLimit-*` headers.
+pub async fn org_rate_limit_middleware(
+    State(state): State<AppState>,
+    cookies: CookieJar,
+    req: Request,
+    next: Next,
+) -> Response {
+    let token = bearer_token(req.headers()).or_else(|| {
+        cookies
+            .get(AUTH_TOKEN_COOKIE)
+            .map(|c| c.value().to_string())
+    });
+
+    // Only the signature is checked, revoked tokens fail in the handlers
+    let org_id = token
+        .and_then(|token| verify_auth_token(&token, &state.token_keys).ok())
+        .map(|payload| payload.org_id)
+        .filter(|org_id| !org_id.is_empty());
+
+    let Some(org_id) = org_id else {
+        return next.run(req).await;
+    };
+
+    let decision = match take_org_request_svc(&state, &org_id).await {
+        Ok(Some(decision)) => decision,
+        Ok(None) => return next.run(req).await,
+        Err(err) => return err.into_response(),
+    };
+
+    let mut res = match decision.allowed {
+        true => next.run(req).await,
+        false => Error::RateLimitExceeded.into_response(),
+    };
+
+    let headers = res.headers_mut();
+    headers.insert(
+        "x-ratelimit-limit",
+        HeaderValue::from(decision.budget.requests_per_min),
+    );
+    headers.insert(
+        "x-ratelimit-burst",
+        HeaderValue::from(decision.budget.burst),
+    );
+    headers.insert(
+        "x-ratelimit-remaining",
+        HeaderValue::from(decision.remaining),
+    );
+    if !decision.allowed {
+        headers.insert("retry-after", HeaderValue::from(decision.retry_after_secs));
+    }
+
+    res
+}
+
+/// Keeps the rate limit and region headers when an error response is rebuilt
+pub(crate) fn copy_preserved_headers(from: &HeaderMap, to: &mut HeaderMap) {
+    for name in PRESERVED_HEADERS.iter() {
+        if let Some(value) = from.get(*name) {
+            to.insert(*name, value.clone());
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use askama::Template;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::{Extension, Form, body::Body, extract::State, response::Response};
+use axum::{
+    Router,
+    routing::{get, post},
+};
+use snafu::ResultExt;
+
+use crate::dto::{OrgDto, OrgRoleDto, Permission, custom_role_permissions};
+use crate::models::TokenFormData;
+use crate::models::options::CheckboxOption;
+use crate::services::org_roles::{
+    OrgRoleFormData, create_org_role_web_svc, delete_org_role_web_svc, list_org_roles_svc,
+    update_org_role_web_svc,
+};
+use crate::{
+    Result,
+    ctx::Ctx,
+    error::{ErrorInfo, ResponseBuilderSnafu, TemplateSnafu},
+    run::AppState,
+    services::token::create_csrf_token_svc,
+    web::{Action, Resource, enforce_org_policy},
+};
+
+pub fn org_roles_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", get(org_roles_handler).post(post_org_role_handler))
+        .route("/{role_id}", post(post_update_org_role_handler))
+        .route("/{role_id}/delete", post(post_delete_org_role_handler))
+        .with_state(state)
+}
+
+/// Permission checkboxes of a custom role, only org level permissions are offered
+fn permission_options(selected: &[Permission]) -> Vec<CheckboxOption> {
+    custom_role_permissions()
+        .into_iter()
+        .map(|permission| CheckboxOption {
+            checked: selected.contains(&permission),
+            value: permission.to_string(),
+            label: permission.label().to_string(),
+            help: Some(permission.description().to_string()),
+        })
+        .collect()
+}
+
+struct OrgRoleView {
+    role: OrgRoleDto,
+    permission_options: Vec<CheckboxOption>,
+}
+
+#[derive(Template)]
+#[template(path = "widgets/org_roles/index.html")]
+struct OrgRolesTemplate {
+    org: OrgDto,
+    roles: Vec<OrgRoleView>,
+    permission_options: Vec<CheckboxOption>,
+    can_manage: bool,
+    token: String,
+    error_message: Option<String>,
+}
+
+impl OrgRolesTemplate {
+    async fn load(state: &AppState, ctx: &Ctx, org: OrgDto) -> Result<Self> {
+        let token = create_csrf_token_svc(&org.id, &state.config.jwt_secret)?;
+        let roles = list_org_roles_svc(state, &org.id).await?;
+
+        Ok(Self {
+            roles: roles
+                .into_iter()
+                .map(|role| OrgRoleView {
+                    permission_options: permission_options(&role.permissions),
+                    role,
+                })
+                .collect(),
+            permission_options: permission_options(&[]),
+            can_manage: ctx.actor.has_permissions(&[Permission::OrgMembersManage]),
+            org,
+            token,
+            error_message: None,
+        })
+    }
+
+    fn into_response(self, result: Result<()>) -> Result<Response<Body>> {
+        let mut tpl = self;
+        let mut status = StatusCode::OK;
+
+        if let Err(err) = result {
+            let error_info = ErrorInfo::from(&err);
+            status = error_info.status_code;
+            tpl.error_message = Some(error_info.message);
+        }
+
+        Response::builder()
+            .status(status)
+            .body(Body::from(tpl.render().context(TemplateSnafu)?))
+            .context(ResponseBuilderSnafu)
+    }
+}
+
+async fn org_roles_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgRole, Action::Read)?;
+
+    OrgRolesTemplate::load(&state, &ctx, org)
+        .await?
+        .into_response(Ok(()))
+}
+
+async fn post_org_role_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Form(pairs): Form<Vec<(String, String)>>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgRole, Action::Create)?;
+
+    let result = match OrgRoleFormData::try_from(pairs) {
+        Ok(payload) => create_org_role_web_svc(&state, &org.id, payload)
+            .await
+            .map(|_| ()),
+        Err(err) => Err(err),
+    };
+
+    OrgRolesTemplate::load(&state, &ctx, org)
+        .await?
+        .into_response(result)
+}
+
+async fn post_update_org_role_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Path((_org_id, role_id)): Path<(String, String)>,
+    Form(pairs): Form<Vec<(String, String)>>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgRole, Action::Update)?;
+
+    let result = match OrgRoleFormData::try_from(pairs) {
+        Ok(payload) => update_org_role_web_svc(&state, &org.id, &role_id, payload)
+            .await
+            .map(|_| ()),
+        Err(err) => Err(err),
+    };
+
+    OrgRolesTemplate::load(&state, &ctx, org)
+        .await?
+        .into_response(result)
+}
+
+async fn post_delete_org_role_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Path((_org_id, role_id)): Path<(String, String)>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgRole, Action::Delete)?;
+
+    let result = delete_org_role_web_svc(&state, &org.id, &role_id, &payload.token).await;
+
+    OrgRolesTemplate::load(&state, &ctx, org)
+        .await?
+        .into_response(result)
+}
@@ -0,0 +1,114 @@
+use askama::Template;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Extension, Form, Router, body::Body, extract::State, response::Response};
+use snafu::ResultExt;
+
+use crate::dto::{OrgDto, OrgSettingsDto};
+use crate::models::options::CheckboxOption;
+use crate::services::org_settings::{
+    OrgSettingsFormData, default_member_roles, get_org_settings_svc, update_org_settings_web_svc,
+};
+use crate::{
+    Result,
+    ctx::Ctx,
+    error::{ErrorInfo, ResponseBuilderSnafu, TemplateSnafu},
+    run::AppState,
+    services::token::create_csrf_token_svc,
+    web::{Action, Resource, create_role_options, enforce_org_policy},
+};
+
+pub fn org_settings_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route(
+            "/",
+            get(org_settings_handler).post(post_org_settings_handler),
+        )
+        .with_state(state)
+}
+
+#[derive(Template)]
+#[template(path = "widgets/org_settings/form.html")]
+struct OrgSettingsTemplate {
+    org: OrgDto,
+    form: OrgSettingsFormData,
+    role_options: Vec<CheckboxOption>,
+    saved: bool,
+    error_message: Option<String>,
+}
+
+/// Form values of the saved settings, one domain per line
+fn org_settings_form(settings: &OrgSettingsDto, token: &str) -> OrgSettingsFormData {
+    OrgSettingsFormData {
+        token: token.to_string(),
+        logo_url: settings.logo_url.clone().unwrap_or_default(),
+        default_member_role: settings.default_member_role.clone(),
+        allowed_email_domains: settings.allowed_email_domains.join("\n"),
+    }
+}
+
+async fn org_settings_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Org, Action::Update)?;
+
+    let token = create_csrf_token_svc(&org.id, &state.config.jwt_secret)?;
+    let settings = get_org_settings_svc(&state, &org.id).await?;
+
+    let tpl = OrgSettingsTemplate {
+        org,
+        form: org_settings_form(&settings, &token),
+        role_options: create_role_options(&default_member_roles(&settings)),
+        saved: false,
+        error_message: None,
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn post_org_settings_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Form(payload): Form<OrgSettingsFormData>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Org, Action::Update)?;
+
+    let token = create_csrf_token_svc(&org.id, &state.config.jwt_secret)?;
+
+    let mut tpl = OrgSettingsTemplate {
+        org: org.clone(),
+        form: OrgSettingsFormData {
+            token: token.clone(),
+            ..payload.clone()
+        },
+        role_options: create_role_options(std::slice::from_ref(&payload.default_member_role)),
+        saved: false,
+        error_message: None,
+    };
+
+    let mut status = StatusCode::OK;
+
+    match update_org_settings_web_svc(&state, &org.id, payload).await {
+        Ok(saved) => {
+            tpl.form = org_settings_form(&saved, &token);
+            tpl.role_options = create_role_options(&default_member_roles(&saved));
+            tpl.saved = true;
+        }
+        Err(err) => {
+            let error_info = ErrorInfo::from(&err);
+            status = error_info.status_code;
+            tpl.error_message = Some(error_info.message);
+        }
+    }
+
+    Response::builder()
+        .status(status)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
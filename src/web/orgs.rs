@@ -1,29 +1,49 @@
 use askama::Template;
 use axum::extract::{Path, Query};
 use axum::http::StatusCode;
+use axum::response::{IntoResponse, Redirect};
 use axum::{Extension, Form, body::Body, extract::State, response::Response};
-use axum::{Router, middleware, routing::get};
+use axum::{
+    Router, middleware,
+    routing::{get, post},
+};
 use snafu::{ResultExt, ensure};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::warn;
 use urlencoding::encode;
 use validator::Validate;
 
-use crate::dto::OrgDto;
 use crate::dto::Permission;
+use crate::dto::{Actor, OrgDto};
 use crate::dto::{
-    ListOrgMembersParamsDto, ListOrgOwnerSuggestionsParamsDto, ListOrgsParamsDto, OrgMemberDto,
-    OrgOwnerSuggestionDto,
+    ListOrgMembersParamsDto, ListOrgsParamsDto, OrgMemberDto, SuggestionBufDto, SuggestionParamsDto,
+};
+use crate::dto::{ORG_SORT, sort_query};
+use crate::error::{ForbiddenSnafu, JsonSerializeSnafu, ValidationSnafu};
+use crate::models::{
+    CspNonce, EmptyState, OrgView, OwnerTransferView, PaginationLinks, SortLinks, TokenFormData,
+    UserParams,
 };
-use crate::error::ValidationSnafu;
-use crate::models::{CspNonce, OrgView, PaginationLinks, TokenFormData, UserParams};
+use crate::services::drafts::discard_draft_svc;
 use crate::services::org_members::{get_org_member_svc, list_org_members_svc};
+use crate::services::org_owner_transfers::{
+    AcceptOwnerTransferFormData, accept_owner_transfer_svc, cancel_owner_transfer_web_svc,
+    initiate_owner_transfer_web_svc, preview_owner_transfer_svc,
+};
+use crate::services::org_transfer::export_org_svc;
 use crate::services::orgs::{
     NewOrgFormData, SelectOrgOwnerParams, UpdateOrgFormData, UpdateOrgOwnerFormData,
-    create_org_web_svc, delete_org_web_svc, list_org_owner_suggestions_svc, list_orgs_svc,
-    update_org_owner_web_svc, update_org_web_svc,
+    create_org_web_svc, delete_org_web_svc, get_org_svc, list_orgs_svc, update_org_web_svc,
 };
+use crate::services::suggestions::suggest_org_owners_svc;
+use crate::services::users::get_actor_email_svc;
 use crate::validators::flatten_errors;
 use crate::web::middleware::org_middleware;
-use crate::web::{org_apps_routes, org_members_routes};
+use crate::web::{
+    org_apps_routes, org_invitations_routes, org_members_routes, org_roles_routes,
+    org_settings_routes, teams_routes,
+};
 use crate::{
     Error, Result,
     ctx::Ctx,
@@ -31,7 +51,7 @@ use crate::{
     models::{Pref, TemplateData},
     run::AppState,
     services::token::create_csrf_token_svc,
-    web::{Action, Resource, enforce_policy},
+    web::{Action, Resource, enforce_org_policy, enforce_policy},
 };
 
 pub fn orgs_routes(state: AppState) -> Router<AppState> {
@@ -54,14 +74,23 @@ fn org_inner_routes(state: AppState) -> Router<AppState> {
             "/change-owner",
             get(change_org_owner_handler).post(post_change_org_owner_handler),
         )
+        .route(
+            "/change-owner/cancel",
+            post(post_cancel_owner_transfer_handler),
+        )
         .route("/search-owner", get(search_new_org_owner_handler))
         .route("/select-owner/{user_id}", get(select_new_org_owner_handler))
         .route(
             "/delete",
             get(delete_org_handler).post(post_delete_org_handler),
         )
+        .route("/export", get(export_org_handler))
         .nest("/members", org_members_routes(state.clone()))
         .nest("/apps", org_apps_routes(state.clone()))
+        .nest("/roles", org_roles_routes(state.clone()))
+        .nest("/invitations", org_invitations_routes(state.clone()))
+        .nest("/settings", org_settings_routes(state.clone()))
+        .nest("/teams", teams_routes(state.clone()))
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             org_middleware,
@@ -113,6 +142,8 @@ struct SearchOrgsTemplate {
     orgs: Vec<OrgView>,
     pagination: Option<PaginationLinks>,
     error_message: Option<String>,
+    empty_state: EmptyState,
+    sorting: SortLinks,
 }
 async fn search_orgs_handler(
     Extension(ctx): Extension<Ctx>,
@@ -125,9 +156,20 @@ async fn search_orgs_handler(
         orgs: Vec::new(),
         pagination: None,
         error_message: None,
+        empty_state: EmptyState::orgs(&ctx.actor, query.keyword.as_deref()),
+        sorting: SortLinks::new(
+            &ORG_SORT,
+            query.sort_by.as_deref(),
+            query.sort_dir.as_deref(),
+            "/orgs/search",
+            "/orgs",
+            query.keyword.as_deref(),
+            ".album-items",
+        ),
     };
 
     let keyword = query.keyword.clone();
+    let sort_param = sort_query(query.sort_by.as_deref(), query.sort_dir.as_deref());
 
     match list_orgs_svc(&state, query).await {
         Ok(orgs) => {
@@ -140,7 +182,7 @@ async fn search_orgs_handler(
                 &orgs.meta,
                 "/orgs/search",
                 "/orgs",
-                &keyword_param,
+                &format!("{}{}", keyword_param, sort_param),
                 ".album-items",
             ));
 
@@ -164,28 +206,27 @@ async fn search_orgs_handler(
 #[derive(Template)]
 #[template(path = "widgets/orgs/search_owner.html")]
 struct SearchOwnerTemplate {
-    users: Vec<OrgOwnerSuggestionDto>,
+    suggestions: Arc<SuggestionBufDto>,
     error_message: Option<String>,
+    empty_state: EmptyState,
 }
 
 async fn search_org_owner_handler(
     Extension(ctx): Extension<Ctx>,
     State(state): State<AppState>,
-    Query(query): Query<ListOrgOwnerSuggestionsParamsDto>,
+    Query(query): Query<SuggestionParamsDto>,
 ) -> Result<Response<Body>> {
     enforce_policy(&ctx.actor, Resource::User, Action::Read)?;
 
     let mut tpl = SearchOwnerTemplate {
-        users: Vec::new(),
+        suggestions: Arc::default(),
         error_message: None,
+        empty_state: EmptyState::suggestions("users", query.keyword.as_deref()),
     };
 
-    let mut updated_query = query.clone();
-    updated_query.per_page = Some(10);
-
-    match list_org_owner_suggestions_svc(&state, updated_query).await {
-        Ok(users) => {
-            tpl.users = users.data;
+    match suggest_org_owners_svc(&state, query).await {
+        Ok(suggestions) => {
+            tpl.suggestions = suggestions;
 
             Ok(Response::builder()
                 .status(200)
@@ -335,9 +376,13 @@ async fn post_new_org_handler(
 struct OrgPageTemplate {
     t: TemplateData,
     org: OrgDto,
+    created_by_email: Option<String>,
     updated: bool,
     can_edit: bool,
     can_delete: bool,
+    can_export: bool,
+    can_view_roles: bool,
+    can_edit_settings: bool,
 }
 
 async fn org_page_handler(
@@ -351,12 +396,20 @@ async fn org_page_handler(
 
     t.title = format!("Org - {}", &org.name);
 
+    let created_by_email = get_actor_email_svc(&state, org.created_by.as_deref()).await?;
+    let can_edit_settings =
+        enforce_org_policy(&ctx.actor, &org.id, Resource::Org, Action::Update).is_ok();
+
     let tpl = OrgPageTemplate {
         t,
         org,
+        created_by_email,
         updated: false,
         can_edit: ctx.actor.has_permissions(&[Permission::OrgsEdit]),
         can_delete: ctx.actor.has_permissions(&[Permission::OrgsDelete]),
+        can_export: ctx.actor.is_system_admin(),
+        can_view_roles: enforce_policy(&ctx.actor, Resource::OrgRole, Action::Read).is_ok(),
+        can_edit_settings,
     };
 
     Response::builder()
@@ -372,19 +425,21 @@ struct OrgControlsTemplate {
     updated: bool,
     can_edit: bool,
     can_delete: bool,
+    can_export: bool,
 }
 
 async fn org_controls_handler(
     Extension(ctx): Extension<Ctx>,
     Extension(org): Extension<OrgDto>,
 ) -> Result<Response<Body>> {
-    enforce_policy(&ctx.actor, Resource::Org, Action::Update)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Org, Action::Update)?;
 
     let tpl = OrgControlsTemplate {
         org,
         updated: false,
         can_edit: ctx.actor.has_permissions(&[Permission::OrgsEdit]),
         can_delete: ctx.actor.has_permissions(&[Permission::OrgsDelete]),
+        can_export: ctx.actor.is_system_admin(),
     };
 
     Response::builder()
@@ -394,9 +449,15 @@ async fn org_controls_handler(
         .context(ResponseBuilderSnafu)
 }
 
+/// Autosaved drafts of the edit form are kept per record
+fn edit_org_draft_key(id: &str) -> String {
+    format!("org-edit-{}", id)
+}
+
 #[derive(Template)]
 #[template(path = "widgets/orgs/edit_form.html")]
 struct EditOrgTemplate {
+    draft_key: String,
     org: OrgDto,
     payload: UpdateOrgFormData,
     error_message: Option<String>,
@@ -409,7 +470,7 @@ async fn edit_org_handler(
 ) -> Result<Response<Body>> {
     let config = state.config.clone();
 
-    enforce_policy(&ctx.actor, Resource::Org, Action::Update)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Org, Action::Update)?;
     let token = create_csrf_token_svc(org.id.to_string().as_str(), &config.jwt_secret)?;
 
     let mut status_opt = None;
@@ -419,6 +480,7 @@ async fn edit_org_handler(
 
     let org_name = org.name.clone();
     let tpl = EditOrgTemplate {
+        draft_key: edit_org_draft_key(&org.id),
         org,
         payload: UpdateOrgFormData {
             token,
@@ -443,12 +505,13 @@ async fn post_edit_org_handler(
 ) -> Result<Response<Body>> {
     let config = state.config.clone();
 
-    enforce_policy(&ctx.actor, Resource::Org, Action::Update)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Org, Action::Update)?;
 
     let token = create_csrf_token_svc(&org.id, &config.jwt_secret)?;
     let org_id = org.id.clone();
 
     let mut tpl = EditOrgTemplate {
+        draft_key: edit_org_draft_key(&org_id),
         org,
         payload: UpdateOrgFormData {
             token,
@@ -462,12 +525,21 @@ async fn post_edit_org_handler(
 
     match result {
         Ok(updated_org) => {
+            // The form went through, its autosaved draft is no longer needed
+            let actor = ctx.actor().expect("actor is required");
+            if let Err(err) =
+                discard_draft_svc(&state, &actor.id, &edit_org_draft_key(&org_id)).await
+            {
+                warn!("Unable to discard form draft: {}", err);
+            }
+
             // Render back the controls but when updated name and status
             let tpl = OrgControlsTemplate {
                 org: updated_org,
                 updated: true,
                 can_edit: ctx.actor.has_permissions(&[Permission::OrgsEdit]),
                 can_delete: ctx.actor.has_permissions(&[Permission::OrgsDelete]),
+                can_export: ctx.actor.is_system_admin(),
             };
 
             Ok(Response::builder()
@@ -508,6 +580,7 @@ struct SearchNewOwnerTemplate {
     org_members: Vec<OrgMemberDto>,
     org_id: String,
     error_message: Option<String>,
+    empty_state: EmptyState,
 }
 
 async fn search_new_org_owner_handler(
@@ -516,12 +589,13 @@ async fn search_new_org_owner_handler(
     State(state): State<AppState>,
     Query(query): Query<ListOrgMembersParamsDto>,
 ) -> Result<Response<Body>> {
-    enforce_policy(&ctx.actor, Resource::OrgMember, Action::Read)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Read)?;
 
     let mut tpl = SearchNewOwnerTemplate {
         org_members: Vec::new(),
         org_id: org.id.clone(),
         error_message: None,
+        empty_state: EmptyState::suggestions("members", query.keyword.as_deref()),
     };
 
     let mut updated_query = query.clone();
@@ -559,19 +633,64 @@ async fn search_new_org_owner_handler(
 #[template(path = "widgets/orgs/change_owner_form.html")]
 struct ChangeOrgOwnerTemplate {
     org: OrgDto,
+    pending_transfer: Option<OwnerTransferView>,
+    token: String,
+    success_message: Option<String>,
     error_message: Option<String>,
 }
 
+impl ChangeOrgOwnerTemplate {
+    fn new(state: &AppState, org: OrgDto) -> Result<Self> {
+        let token = create_csrf_token_svc(&org.id, &state.config.jwt_secret)?;
+
+        Ok(Self {
+            pending_transfer: org.pending_transfer.clone().map(OwnerTransferView::from),
+            org,
+            token,
+            success_message: None,
+            error_message: None,
+        })
+    }
+
+    /// Renders the form again with the org as it is now, along with the outcome
+    async fn into_response(
+        self,
+        state: &AppState,
+        result: Result<String>,
+    ) -> Result<Response<Body>> {
+        let org = get_org_svc(state, &self.org.id)
+            .await?
+            .ok_or(Error::OrgNotFound)?;
+        let mut tpl = Self::new(state, org)?;
+
+        let status = match result {
+            Ok(msg) => {
+                tpl.success_message = Some(msg);
+                StatusCode::OK
+            }
+            Err(err) => {
+                let error_info = ErrorInfo::from(&err);
+                tpl.error_message = Some(error_info.message);
+                error_info.status_code
+            }
+        };
+
+        Response::builder()
+            .status(status)
+            .header("Content-Type", "text/html")
+            .body(Body::from(tpl.render().context(TemplateSnafu)?))
+            .context(ResponseBuilderSnafu)
+    }
+}
+
 async fn change_org_owner_handler(
     Extension(ctx): Extension<Ctx>,
     Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
 ) -> Result<Response<Body>> {
-    enforce_policy(&ctx.actor, Resource::Org, Action::Update)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Org, Action::Update)?;
 
-    let tpl = ChangeOrgOwnerTemplate {
-        org,
-        error_message: None,
-    };
+    let tpl = ChangeOrgOwnerTemplate::new(&state, org)?;
 
     Response::builder()
         .status(200)
@@ -580,62 +699,52 @@ async fn change_org_owner_handler(
         .context(ResponseBuilderSnafu)
 }
 
+/// Sends the selected member a request to take over the org
 async fn post_change_org_owner_handler(
     Extension(ctx): Extension<Ctx>,
     Extension(org): Extension<OrgDto>,
     State(state): State<AppState>,
     Form(payload): Form<UpdateOrgOwnerFormData>,
 ) -> Result<Response<Body>> {
-    enforce_policy(&ctx.actor, Resource::Org, Action::Update)?;
-    let org_id = org.id.clone();
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Org, Action::Update)?;
+    let actor = ctx.actor().expect("actor is required");
+
+    let result = initiate_owner_transfer_web_svc(
+        &state,
+        &actor.user.id,
+        &org.id,
+        &payload.token,
+        &payload.owner_id,
+    )
+    .await
+    .map(|_| {
+        format!(
+            "Transfer request sent to {}, ownership changes once they accept.",
+            payload.owner_email
+        )
+    });
 
-    let mut tpl = ChangeOrgOwnerTemplate {
-        org,
-        error_message: None,
-    };
+    ChangeOrgOwnerTemplate::new(&state, org)?
+        .into_response(&state, result)
+        .await
+}
 
-    let result = update_org_owner_web_svc(&state, &org_id, payload).await;
+async fn post_cancel_owner_transfer_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Form(payload): Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Org, Action::Update)?;
+    let actor = ctx.actor().expect("actor is required");
 
-    match result {
-        Ok(updated_org) => {
-            // Render back the controls but with updated name and status
-            let tpl = OrgControlsTemplate {
-                org: updated_org,
-                updated: true,
-                can_edit: ctx.actor.has_permissions(&[Permission::OrgsEdit]),
-                can_delete: ctx.actor.has_permissions(&[Permission::OrgsDelete]),
-            };
+    let result = cancel_owner_transfer_web_svc(&state, &actor.user.id, &org.id, &payload.token)
+        .await
+        .map(|_| "Ownership transfer cancelled.".to_string());
 
-            Ok(Response::builder()
-                .status(200)
-                .header("Content-Type", "text/html")
-                .body(Body::from(tpl.render().context(TemplateSnafu)?))
-                .context(ResponseBuilderSnafu)?)
-        }
-        Err(err) => {
-            let status;
-            match err {
-                Error::Validation { msg } => {
-                    status = StatusCode::BAD_REQUEST;
-                    tpl.error_message = Some(msg);
-                }
-                Error::LoginRequired => {
-                    status = StatusCode::UNAUTHORIZED;
-                    tpl.error_message = Some("Login required.".to_string());
-                }
-                any_err => {
-                    status = StatusCode::INTERNAL_SERVER_ERROR;
-                    tpl.error_message = Some(any_err.to_string());
-                }
-            };
-
-            Ok(Response::builder()
-                .status(status)
-                .header("Content-Type", "text/html")
-                .body(Body::from(tpl.render().context(TemplateSnafu)?))
-                .context(ResponseBuilderSnafu)?)
-        }
-    }
+    ChangeOrgOwnerTemplate::new(&state, org)?
+        .into_response(&state, result)
+        .await
 }
 
 #[derive(Template)]
@@ -652,7 +761,7 @@ async fn select_new_org_owner_handler(
     State(state): State<AppState>,
     Path(params): Path<UserParams>,
 ) -> Result<Response<Body>> {
-    enforce_policy(&ctx.actor, Resource::OrgMember, Action::Read)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::OrgMember, Action::Read)?;
     let token = create_csrf_token_svc(org.id.to_string().as_str(), &state.config.jwt_secret)?;
 
     let org_id = org.id.clone();
@@ -695,6 +804,7 @@ struct DeleteOrgFormTemplate {
     org: OrgDto,
     payload: TokenFormData,
     error_message: Option<String>,
+    approval_message: Option<String>,
 }
 
 async fn delete_org_handler(
@@ -704,7 +814,7 @@ async fn delete_org_handler(
 ) -> Result<Response<Body>> {
     let config = state.config.clone();
 
-    enforce_policy(&ctx.actor, Resource::Org, Action::Delete)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Org, Action::Delete)?;
 
     let token = create_csrf_token_svc(&org.id.to_string(), &config.jwt_secret)?;
 
@@ -712,6 +822,7 @@ async fn delete_org_handler(
         org,
         payload: TokenFormData { token },
         error_message: None,
+        approval_message: None,
     };
 
     Response::builder()
@@ -728,8 +839,9 @@ async fn post_delete_org_handler(
 ) -> Result<Response<Body>> {
     let config = state.config.clone();
 
-    enforce_policy(&ctx.actor, Resource::Org, Action::Delete)?;
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Org, Action::Delete)?;
 
+    let actor = ctx.actor().expect("actor is required");
     let org_id = org.id.clone();
     let token = create_csrf_token_svc(&org.id.to_string(), &config.jwt_secret)?;
 
@@ -737,9 +849,10 @@ async fn post_delete_org_handler(
         org,
         payload: TokenFormData { token },
         error_message: None,
+        approval_message: None,
     };
 
-    let result = delete_org_web_svc(&state, &org_id, &payload.token).await;
+    let result = delete_org_web_svc(&state, &actor.user.id, &org_id, &payload.token).await;
 
     match result {
         Ok(_) => Response::builder()
@@ -747,6 +860,14 @@ async fn post_delete_org_handler(
             .header("HX-Redirect", "/orgs".to_string())
             .body(Body::from(tpl.render().context(TemplateSnafu)?))
             .context(ResponseBuilderSnafu),
+        Err(Error::ApprovalRequired { msg }) => {
+            tpl.approval_message = Some(msg);
+
+            Response::builder()
+                .status(StatusCode::ACCEPTED)
+                .body(Body::from(tpl.render().context(TemplateSnafu)?))
+                .context(ResponseBuilderSnafu)
+        }
         Err(err) => {
             let error_info = ErrorInfo::from(&err);
             tpl.error_message = Some(error_info.message);
@@ -758,3 +879,102 @@ async fn post_delete_org_handler(
         }
     }
 }
+
+/// Downloads the org as a portable archive for `yaas org-import`
+async fn export_org_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    ensure!(
+        ctx.actor.is_system_admin(),
+        ForbiddenSnafu {
+            msg: "You do not have permission to export orgs."
+        }
+    );
+
+    let archive = export_org_svc(&state.db, &org.id).await?;
+    let contents = serde_json::to_string_pretty(&archive).context(JsonSerializeSnafu)?;
+
+    Response::builder()
+        .status(200)
+        .header("Content-Type", "application/json")
+        .header(
+            "Content-Disposition",
+            format!("attachment; filename=\"org-{}.json\"", org.id),
+        )
+        .body(Body::from(contents))
+        .context(ResponseBuilderSnafu)
+}
+
+#[derive(Template)]
+#[template(path = "pages/accept_owner_transfer.html")]
+struct AcceptOwnerTransferTemplate {
+    t: TemplateData,
+    token: String,
+    org_name: String,
+    email: String,
+    valid: bool,
+    error_message: Option<String>,
+}
+
+pub async fn accept_owner_transfer_handler(
+    Extension(csp_nonce): Extension<CspNonce>,
+    State(state): State<AppState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Response<Body>> {
+    let pref = Pref::new();
+    let actor = Actor::default();
+    let mut t = TemplateData::new(&state, actor, &pref, csp_nonce.nonce);
+    t.title = String::from("Accept Ownership");
+
+    let token = query.get("token").cloned().unwrap_or_default();
+    let mut tpl = AcceptOwnerTransferTemplate {
+        t,
+        token,
+        org_name: String::new(),
+        email: String::new(),
+        valid: false,
+        error_message: query.get("error").cloned(),
+    };
+
+    match preview_owner_transfer_svc(&state, &tpl.token).await {
+        Ok(preview) => {
+            tpl.valid = true;
+            tpl.org_name = preview.org_name;
+            tpl.email = preview.transfer.to_email.unwrap_or_default();
+        }
+        Err(err) => tpl.error_message = Some(err.to_string()),
+    }
+
+    Response::builder()
+        .status(200)
+        .header("Cache-Control", "no-store")
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+pub async fn post_accept_owner_transfer_handler(
+    State(state): State<AppState>,
+    Form(payload): Form<AcceptOwnerTransferFormData>,
+) -> impl IntoResponse {
+    let token = payload.token.clone();
+
+    match accept_owner_transfer_svc(&state, payload).await {
+        Ok(org) => {
+            let url = format!(
+                "/login?success={}",
+                encode(&format!("You now own {}. Login to continue.", org.name))
+            );
+            Redirect::to(&url).into_response()
+        }
+        Err(err) => {
+            let url = format!(
+                "/owner-transfers/accept?token={}&error={}",
+                encode(&token),
+                encode(&err.to_string())
+            );
+            Redirect::to(&url).into_response()
+        }
+    }
+}
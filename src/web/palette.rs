@@ -0,0 +1,85 @@
+use askama::Template;
+use axum::extract::Query;
+use axum::{Extension, body::Body, extract::State, response::Response};
+use axum::{Router, routing::get};
+use snafu::ResultExt;
+use validator::Validate;
+
+use crate::dto::{PaletteItemDto, PaletteParamsDto};
+use crate::services::palette::search_palette_svc;
+use crate::validators::flatten_errors;
+use crate::{
+    Result,
+    ctx::Ctx,
+    error::{ErrorInfo, ResponseBuilderSnafu, TemplateSnafu},
+    run::AppState,
+};
+
+pub fn palette_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", get(palette_handler))
+        .route("/search", get(search_palette_handler))
+        .with_state(state)
+}
+
+#[derive(Template)]
+#[template(path = "widgets/palette/modal.html")]
+struct PaletteTemplate {}
+
+async fn palette_handler() -> Result<Response<Body>> {
+    let tpl = PaletteTemplate {};
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+#[derive(Template)]
+#[template(path = "widgets/palette/results.html")]
+struct PaletteResultsTemplate {
+    items: Vec<PaletteItemDto>,
+    error_message: Option<String>,
+}
+
+async fn search_palette_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Query(query): Query<PaletteParamsDto>,
+) -> Result<Response<Body>> {
+    let mut tpl = PaletteResultsTemplate {
+        items: Vec::new(),
+        error_message: None,
+    };
+
+    if let Err(errors) = query.validate() {
+        tpl.error_message = Some(flatten_errors(&errors));
+
+        return Response::builder()
+            .status(400)
+            .body(Body::from(tpl.render().context(TemplateSnafu)?))
+            .context(ResponseBuilderSnafu);
+    }
+
+    let keyword = query.keyword.unwrap_or_default();
+
+    match search_palette_svc(&state, &ctx.actor, &keyword).await {
+        Ok(items) => {
+            tpl.items = items;
+
+            Ok(Response::builder()
+                .status(200)
+                .body(Body::from(tpl.render().context(TemplateSnafu)?))
+                .context(ResponseBuilderSnafu)?)
+        }
+        Err(err) => {
+            let error_info = ErrorInfo::from(&err);
+            tpl.error_message = Some(error_info.message);
+
+            Ok(Response::builder()
+                .status(error_info.status_code)
+                .body(Body::from(tpl.render().context(TemplateSnafu)?))
+                .context(ResponseBuilderSnafu)?)
+        }
+    }
+}
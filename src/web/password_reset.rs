@@ -0,0 +1,175 @@
+use askama::Template;
+use axum::{
+    Extension,
+    body::Body,
+    extract::{Form, Query, State},
+    http::Response,
+    response::{IntoResponse, Redirect},
+};
+use snafu::ResultExt;
+use std::collections::HashMap;
+use tracing::error;
+use urlencoding::encode;
+
+use crate::{
+    Error, Result,
+    dto::Actor,
+    error::{ResponseBuilderSnafu, TemplateSnafu},
+    models::{CspNonce, Pref, TemplateData},
+    run::AppState,
+    services::{
+        captcha::validate_catpcha,
+        password_reset::{
+            ForgotPasswordFormData, ResetPasswordFormData, request_password_reset_svc,
+            reset_password_svc,
+        },
+    },
+};
+
+#[derive(Template)]
+#[template(path = "pages/forgot_password.html")]
+struct ForgotPasswordTemplate {
+    t: TemplateData,
+    captcha_key: String,
+    captcha_enabled: bool,
+    success_message: Option<String>,
+    error_message: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "pages/reset_password.html")]
+struct ResetPasswordTemplate {
+    t: TemplateData,
+    token: String,
+    error_message: Option<String>,
+}
+
+pub async fn forgot_password_handler(
+    Extension(csp_nonce): Extension<CspNonce>,
+    State(state): State<AppState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Response<Body>> {
+    let pref = Pref::new();
+    let actor = Actor::default();
+    let mut t = TemplateData::new(&state, actor, &pref, csp_nonce.nonce);
+    t.title = String::from("Forgot Password");
+
+    let captcha_enabled = state.config.captcha_enabled();
+    if captcha_enabled {
+        t.async_scripts = vec!["https://www.google.com/recaptcha/enterprise.js".to_string()];
+    }
+    let captcha_key = state.config.captcha_site_key.clone().unwrap_or_default();
+
+    let tpl = ForgotPasswordTemplate {
+        t,
+        captcha_key,
+        captcha_enabled,
+        success_message: query.get("success").cloned(),
+        error_message: query.get("error").cloned(),
+    };
+
+    no_store_response(tpl.render().context(TemplateSnafu)?)
+}
+
+pub async fn post_forgot_password_handler(
+    State(state): State<AppState>,
+    Form(payload): Form<ForgotPasswordFormData>,
+) -> impl IntoResponse {
+    if state.config.captcha_enabled() {
+        let captcha_response = match payload.g_recaptcha_response.as_deref() {
+            Some(value) if !value.trim().is_empty() => value,
+            _ => {
+                return forgot_password_error(Error::Validation {
+                    msg: "Click the I'm not a robot checkbox.".into(),
+                });
+            }
+        };
+
+        if let Err(captcha_err) = validate_catpcha(&state, captcha_response).await {
+            return forgot_password_error(captcha_err);
+        }
+    }
+
+    match request_password_reset_svc(&state, &payload.email).await {
+        Ok(_) => {
+            // Same answer whether or not the email has an account
+            let url = format!(
+                "/forgot-password?success={}",
+                encode(
+                    "If the email belongs to an active account, a password reset link has been sent."
+                )
+            );
+            Redirect::to(&url).into_response()
+        }
+        Err(Error::Validation { msg }) => forgot_password_error(Error::Validation { msg }),
+        Err(err) => {
+            error!("Unable to issue password reset: {}", err);
+            forgot_password_error(Error::Validation {
+                msg: "Unable to send a password reset link, try again later.".into(),
+            })
+        }
+    }
+}
+
+pub async fn reset_password_handler(
+    Extension(csp_nonce): Extension<CspNonce>,
+    State(state): State<AppState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Response<Body>> {
+    let pref = Pref::new();
+    let actor = Actor::default();
+    let mut t = TemplateData::new(&state, actor, &pref, csp_nonce.nonce);
+    t.title = String::from("Reset Password");
+
+    let tpl = ResetPasswordTemplate {
+        t,
+        token: query.get("token").cloned().unwrap_or_default(),
+        error_message: query.get("error").cloned(),
+    };
+
+    no_store_response(tpl.render().context(TemplateSnafu)?)
+}
+
+pub async fn post_reset_password_handler(
+    State(state): State<AppState>,
+    Form(payload): Form<ResetPasswordFormData>,
+) -> impl IntoResponse {
+    let token = payload.token.clone();
+
+    match reset_password_svc(&state, payload).await {
+        Ok(_) => {
+            let url = format!(
+                "/login?success={}",
+                encode("Password updated. Login with your new password.")
+            );
+            Redirect::to(&url).into_response()
+        }
+        Err(err) => {
+            let url = format!(
+                "/reset-password?token={}&error={}",
+                encode(&token),
+                encode(&err.to_string())
+            );
+            Redirect::to(&url).into_response()
+        }
+    }
+}
+
+fn forgot_password_error(error: Error) -> Response<Body> {
+    let url = format!("/forgot-password?error={}", encode(&error.to_string()));
+    Redirect::to(&url).into_response()
+}
+
+fn no_store_response(body: String) -> Result<Response<Body>> {
+    Response::builder()
+        .status(200)
+        .header("Surrogate-Control", "no-store")
+        .header(
+            "Cache-Control",
+            "no-store, no-cache, must-revalidate, proxy-revalidate",
+        )
+        .header("Pragma", "no-cache")
+        .header("Expires", 0)
+        .body(Body::from(body))
+        .context(ResponseBuilderSnafu)
+}
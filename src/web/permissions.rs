@@ -0,0 +1,69 @@
+use axum::extract::Query;
+use axum::{
+    Extension, Json, body::Body, extract::State, response::IntoResponse, response::Response,
+};
+use axum::{Router, routing::get};
+use snafu::{ResultExt, ensure};
+use validator::Validate;
+
+use crate::dto::{PermissionHelpDto, PermissionMatrixParamsDto};
+use crate::services::permissions::{
+    permission_help_svc, permission_matrix_svc, permission_matrix_to_csv,
+};
+use crate::validators::flatten_errors;
+use crate::{
+    Error, Result,
+    ctx::Ctx,
+    error::{ForbiddenSnafu, ResponseBuilderSnafu},
+    run::AppState,
+};
+
+pub fn permissions_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/export", get(export_permission_matrix_handler))
+        .route("/help", get(permission_help_handler))
+        .with_state(state)
+}
+
+/// Exports the role to permission mapping as JSON (default) or CSV
+async fn export_permission_matrix_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Query(query): Query<PermissionMatrixParamsDto>,
+) -> Result<Response<Body>> {
+    ensure!(
+        ctx.actor.is_system_admin(),
+        ForbiddenSnafu {
+            msg: "You do not have permission to export the permission matrix."
+        }
+    );
+
+    if let Err(errors) = query.validate() {
+        return Err(Error::Validation {
+            msg: flatten_errors(&errors),
+        });
+    }
+
+    let matrix = permission_matrix_svc(&state.db, query.org_id.as_deref()).await?;
+
+    match query.format.as_deref() {
+        None | Some("json") => Ok(Json(matrix).into_response()),
+        Some("csv") => Response::builder()
+            .status(200)
+            .header("Content-Type", "text/csv; charset=utf-8")
+            .header(
+                "Content-Disposition",
+                "attachment; filename=\"permission-matrix.csv\"",
+            )
+            .body(Body::from(permission_matrix_to_csv(&matrix)))
+            .context(ResponseBuilderSnafu),
+        Some(_) => Err(Error::Validation {
+            msg: "Format must be either json or csv".to_string(),
+        }),
+    }
+}
+
+/// Labels and descriptions behind the permission tooltips, for any signed in user
+async fn permission_help_handler() -> Json<PermissionHelpDto> {
+    Json(permission_help_svc())
+}
@@ -1,7 +1,5 @@
-use std::result::Result as StdResult;
-
 use crate::dto::Actor;
-use crate::dto::Permission;
+use crate::dto::{ALL_ROLES, Permission, Role, role_permissions};
 use crate::{Error, Result};
 
 pub enum Resource {
@@ -10,6 +8,8 @@ pub enum Resource {
     App,
     OrgMember,
     OrgApp,
+    OrgRole,
+    Team,
 }
 
 pub enum Action {
@@ -20,24 +20,92 @@ pub enum Action {
 }
 
 pub fn enforce_policy(actor: &Actor, resource: Resource, action: Action) -> Result<()> {
-    let result = match resource {
-        Resource::User => enforce_users_permissions(actor, action),
-        Resource::Org => enforce_orgs_permissions(actor, action),
-        Resource::App => enforce_apps_permissions(actor, action),
-        Resource::OrgMember => enforce_org_members_permissions(actor, action),
-        Resource::OrgApp => enforce_org_apps_permissions(actor, action),
-    };
-
-    match result {
-        Ok(_) => Ok(()),
-        Err(message) => Err(Error::Forbidden {
+    let (permissions, message) = policy(resource, action);
+
+    if !actor.has_permissions(&permissions) {
+        return Err(Error::Forbidden {
             msg: message.to_string(),
-        }),
+        });
+    }
+    Ok(())
+}
+
+/// Same as `enforce_policy` for routes of a single org.
+///
+/// The actor's permissions come from its membership in the org it is signed
+/// in to, so they only apply to that org. Superusers pass for every org.
+pub fn enforce_org_policy(
+    actor: &Actor,
+    org_id: &str,
+    resource: Resource,
+    action: Action,
+) -> Result<()> {
+    enforce_policy(actor, resource, action)?;
+
+    if !actor.is_system_admin() && !actor.member_of(org_id) {
+        return Err(Error::Forbidden {
+            msg: "You do not have access to this org, switch to it first.".to_string(),
+        });
     }
+    Ok(())
+}
+
+/// Tooltip for a control the actor cannot use, `None` when the policy allows it.
+///
+/// Built from the same table as `enforce_policy`, naming each missing
+/// permission and the org roles that grant it.
+pub fn missing_permission_hint(
+    actor: &Actor,
+    resource: Resource,
+    action: Action,
+) -> Option<String> {
+    let (permissions, message) = policy(resource, action);
+
+    let missing: Vec<String> = permissions
+        .into_iter()
+        .filter(|permission| !actor.has_permissions(std::slice::from_ref(permission)))
+        .map(|permission| {
+            let roles: Vec<&str> = ALL_ROLES
+                .iter()
+                .filter(|role| **role != Role::Superuser)
+                .filter(|role| role_permissions(role).contains(&permission))
+                .map(|role| role.label())
+                .collect();
+
+            match roles.is_empty() {
+                true => format!("{} ({})", permission.label(), permission),
+                false => format!(
+                    "{} ({}), granted by {}",
+                    permission.label(),
+                    permission,
+                    roles.join(", ")
+                ),
+            }
+        })
+        .collect();
+
+    if missing.is_empty() {
+        return None;
+    }
+
+    Some(format!("{} Requires {}.", message, missing.join("; ")))
 }
 
-fn enforce_orgs_permissions(actor: &Actor, action: Action) -> StdResult<(), &str> {
-    let (permissions, message) = match action {
+/// Permissions required for the action along with the message shown when denied
+fn policy(resource: Resource, action: Action) -> (Vec<Permission>, &'static str) {
+    match resource {
+        Resource::User => users_policy(action),
+        Resource::Org => orgs_policy(action),
+        Resource::App => apps_policy(action),
+        Resource::OrgMember => org_members_policy(action),
+        Resource::OrgApp => org_apps_policy(action),
+        Resource::OrgRole => org_roles_policy(action),
+        Resource::Team => teams_policy(action),
+    }
+}
+
+fn orgs_policy(action: Action) -> (Vec<Permission>, &'static str) {
+    match action {
         Action::Create => (
             vec![Permission::OrgsCreate],
             "You do not have permission to create new orgs.",
@@ -54,16 +122,11 @@ fn enforce_orgs_permissions(actor: &Actor, action: Action) -> StdResult<(), &str
             vec![Permission::OrgsDelete],
             "You do not have permission to delete orgs.",
         ),
-    };
-
-    if !actor.has_permissions(&permissions) {
-        return Err(message);
     }
-    Ok(())
 }
 
-fn enforce_apps_permissions(actor: &Actor, action: Action) -> StdResult<(), &str> {
-    let (permissions, message) = match action {
+fn apps_policy(action: Action) -> (Vec<Permission>, &'static str) {
+    match action {
         Action::Create => (
             vec![Permission::AppsCreate],
             "You do not have permission to create new apps.",
@@ -80,16 +143,11 @@ fn enforce_apps_permissions(actor: &Actor, action: Action) -> StdResult<(), &str
             vec![Permission::AppsDelete],
             "You do not have permission to delete apps.",
         ),
-    };
-
-    if !actor.has_permissions(&permissions) {
-        return Err(message);
     }
-    Ok(())
 }
 
-fn enforce_org_members_permissions(actor: &Actor, action: Action) -> StdResult<(), &str> {
-    let (permissions, message) = match action {
+fn org_members_policy(action: Action) -> (Vec<Permission>, &'static str) {
+    match action {
         Action::Create => (
             vec![Permission::OrgMembersCreate],
             "You do not have permission to create new org members.",
@@ -106,16 +164,11 @@ fn enforce_org_members_permissions(actor: &Actor, action: Action) -> StdResult<(
             vec![Permission::OrgMembersDelete],
             "You do not have permission to delete org members.",
         ),
-    };
-
-    if !actor.has_permissions(&permissions) {
-        return Err(message);
     }
-    Ok(())
 }
 
-fn enforce_org_apps_permissions(actor: &Actor, action: Action) -> StdResult<(), &str> {
-    let (permissions, message) = match action {
+fn org_apps_policy(action: Action) -> (Vec<Permission>, &'static str) {
+    match action {
         Action::Create => (
             vec![Permission::OrgAppsCreate],
             "You do not have permission to create new org apps.",
@@ -132,16 +185,38 @@ fn enforce_org_apps_permissions(actor: &Actor, action: Action) -> StdResult<(),
             vec![Permission::OrgAppsDelete],
             "You do not have permission to delete org apps.",
         ),
-    };
+    }
+}
 
-    if !actor.has_permissions(&permissions) {
-        return Err(message);
+/// Custom roles grant member permissions, so changing them takes full member management
+fn org_roles_policy(action: Action) -> (Vec<Permission>, &'static str) {
+    match action {
+        Action::Read => (
+            vec![Permission::OrgMembersList, Permission::OrgMembersView],
+            "You do not have permission to view org roles.",
+        ),
+        Action::Create | Action::Update | Action::Delete => (
+            vec![Permission::OrgMembersManage],
+            "You do not have permission to manage org roles.",
+        ),
+    }
+}
+
+fn teams_policy(action: Action) -> (Vec<Permission>, &'static str) {
+    match action {
+        Action::Read => (
+            vec![Permission::OrgMembersList, Permission::OrgMembersView],
+            "You do not have permission to view teams.",
+        ),
+        Action::Create | Action::Update | Action::Delete => (
+            vec![Permission::OrgMembersManage],
+            "You do not have permission to manage teams.",
+        ),
     }
-    Ok(())
 }
 
-fn enforce_users_permissions(actor: &Actor, action: Action) -> StdResult<(), &str> {
-    let (permissions, message) = match action {
+fn users_policy(action: Action) -> (Vec<Permission>, &'static str) {
+    match action {
         Action::Create => (
             vec![Permission::UsersCreate],
             "You do not have permission to create new users.",
@@ -158,10 +233,110 @@ fn enforce_users_permissions(actor: &Actor, action: Action) -> StdResult<(), &st
             vec![Permission::UsersDelete],
             "You do not have permission to delete users.",
         ),
-    };
+    }
+}
 
-    if !actor.has_permissions(&permissions) {
-        return Err(message);
+#[cfg(test)]
+mod tests {
+    use crate::dto::{Actor, ActorPayloadDto, Role, Scope, UserDto};
+
+    use super::{Action, Resource, enforce_org_policy, enforce_policy, missing_permission_hint};
+
+    fn actor_with(role: Role) -> Actor {
+        Actor::new(
+            ActorPayloadDto {
+                id: "usr_1".to_string(),
+                org_id: "org_1".to_string(),
+                org_count: 1,
+                roles: vec![role],
+                scopes: vec![Scope::Auth],
+                grant_id: None,
+                proof_key_id: None,
+                permission_mask: None,
+                issued_at: 0,
+                session_id: None,
+                region: None,
+                home_region: None,
+                token_id: None,
+                org_app_id: None,
+                impersonator_id: None,
+                impersonation_id: None,
+            },
+            UserDto {
+                id: "usr_1".to_string(),
+                email: "viewer@example.com".to_string(),
+                name: "Viewer".to_string(),
+                status: "active".to_string(),
+                created_at: 0,
+                updated_at: 0,
+                deleted_at: None,
+            },
+        )
+    }
+
+    #[test]
+    fn hints_follow_the_enforced_policy() {
+        let viewer = actor_with(Role::OrgViewer);
+
+        assert!(enforce_policy(&viewer, Resource::OrgMember, Action::Read).is_ok());
+        assert!(missing_permission_hint(&viewer, Resource::OrgMember, Action::Read).is_none());
+
+        assert!(enforce_policy(&viewer, Resource::OrgMember, Action::Update).is_err());
+        let hint = missing_permission_hint(&viewer, Resource::OrgMember, Action::Update)
+            .expect("viewer cannot edit members");
+        assert_eq!(
+            hint,
+            "You do not have permission to edit org members. \
+             Requires Edit org members (org_members.edit), granted by Admin."
+        );
+
+        let admin = actor_with(Role::OrgAdmin);
+        assert!(missing_permission_hint(&admin, Resource::OrgMember, Action::Update).is_none());
+    }
+
+    #[test]
+    fn org_policy_is_limited_to_the_actor_org() {
+        let admin = actor_with(Role::OrgAdmin);
+
+        assert!(enforce_org_policy(&admin, "org_1", Resource::OrgMember, Action::Update).is_ok());
+        assert!(enforce_org_policy(&admin, "org_2", Resource::OrgMember, Action::Update).is_err());
+        assert!(enforce_org_policy(&admin, "org_2", Resource::Org, Action::Read).is_err());
+
+        let viewer = actor_with(Role::OrgViewer);
+        assert!(enforce_org_policy(&viewer, "org_1", Resource::OrgMember, Action::Update).is_err());
+
+        let superuser = actor_with(Role::Superuser);
+        assert!(enforce_org_policy(&superuser, "org_2", Resource::OrgApp, Action::Delete).is_ok());
+    }
+
+    #[test]
+    fn app_actors_get_the_org_app_link_permissions() {
+        let app = Actor::for_app(
+            ActorPayloadDto {
+                id: "app_1".to_string(),
+                org_id: "org_1".to_string(),
+                org_count: 1,
+                roles: Vec::new(),
+                scopes: vec![Scope::Oauth],
+                grant_id: None,
+                proof_key_id: None,
+                permission_mask: None,
+                issued_at: 0,
+                session_id: None,
+                region: None,
+                home_region: None,
+                token_id: None,
+                org_app_id: Some("oap_1".to_string()),
+                impersonator_id: None,
+                impersonation_id: None,
+            },
+            "oap_1".to_string(),
+        );
+
+        assert!(app.actor.is_none());
+        assert!(!app.has_auth_scope());
+        assert!(enforce_org_policy(&app, "org_1", Resource::Org, Action::Read).is_ok());
+        assert!(enforce_org_policy(&app, "org_1", Resource::OrgMember, Action::Update).is_err());
+        assert!(enforce_org_policy(&app, "org_2", Resource::Org, Action::Read).is_err());
     }
-    Ok(())
 }
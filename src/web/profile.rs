@@ -1,4 +1,5 @@
 use askama::Template;
+use axum::extract::Path;
 use axum::extract::Query;
 use axum::http::StatusCode;
 use axum::{
@@ -7,23 +8,44 @@ use axum::{
     extract::State,
     response::{IntoResponse, Redirect, Response},
 };
-use axum::{Router, routing::get};
-use snafu::ResultExt;
+use axum::{
+    Router,
+    routing::{get, post},
+};
+use snafu::{ResultExt, ensure};
 use tower_cookies::{Cookie, Cookies, cookie::time::Duration};
 use urlencoding::encode;
 
 use crate::dto::{
-    ListOrgMembersParamsDto, ListingParamsDto, OrgMembershipDto, SwitchAuthContextDto, UserDto,
+    ListOrgMembersParamsDto, ListingParamsDto, OrgMembershipDto, SwitchAuthContextDto,
+    USER_PROFILE_METADATA_MAX_BYTES, UserDto, UserProfileDto,
+};
+use crate::error::{ErrorInfo, ForbiddenSnafu};
+use crate::models::{
+    AuthorizedAppView, CspNonce, EmptyState, PaginationLinks, TokenFormData, UserEmailView,
+    UserSessionView,
 };
-use crate::error::ErrorInfo;
-use crate::models::{CspNonce, PaginationLinks};
 use crate::services::auth::{
     SwitchAuthContextFormData, SwitchAuthContextParams, switch_auth_context_svc,
 };
+use crate::services::notifications::{
+    NotificationPrefFormData, get_notification_pref_svc, update_notification_pref_web_svc,
+};
+use crate::services::oauth_grants::{list_authorized_apps_svc, revoke_authorized_app_web_svc};
 use crate::services::org_members::list_org_memberships_svc;
 use crate::services::password::change_user_current_password_web_svc;
+use crate::services::sessions::{list_user_sessions_svc, revoke_user_session_web_svc};
+use crate::services::user_emails::{
+    NewUserEmailFormData, VerifyUserEmailFormData, add_user_email_web_svc, list_user_emails_svc,
+    promote_user_email_web_svc, remove_user_email_web_svc, verify_user_email_web_svc,
+};
+use crate::services::user_profiles::{
+    UserProfileFormData, get_user_profile_svc, update_user_profile_web_svc,
+};
 use crate::services::users::ChangeCurrentPasswordFormData;
+use crate::services::users::get_user_svc;
 use crate::web::AUTH_TOKEN_COOKIE;
+use crate::web::logout::cookie_session;
 use crate::{
     Error, Result,
     ctx::Ctx,
@@ -47,6 +69,40 @@ pub fn profile_routes(state: AppState) -> Router<AppState> {
             "/change-password",
             get(change_current_password_handler).post(post_change_current_password_handler),
         )
+        .route(
+            "/emails",
+            get(user_emails_handler).post(post_add_user_email_handler),
+        )
+        .route(
+            "/emails/{email_id}/verify",
+            post(post_verify_user_email_handler),
+        )
+        .route(
+            "/emails/{email_id}/promote",
+            post(post_promote_user_email_handler),
+        )
+        .route(
+            "/emails/{email_id}/delete",
+            post(post_remove_user_email_handler),
+        )
+        .route(
+            "/notifications",
+            get(notification_prefs_handler).post(post_notification_prefs_handler),
+        )
+        .route(
+            "/details",
+            get(profile_details_handler).post(post_profile_details_handler),
+        )
+        .route("/connected-apps", get(connected_apps_handler))
+        .route(
+            "/connected-apps/{app_id}/revoke",
+            post(post_revoke_connected_app_handler),
+        )
+        .route("/sessions", get(active_sessions_handler))
+        .route(
+            "/sessions/{session_id}/revoke",
+            post(post_revoke_active_session_handler),
+        )
         .with_state(state)
 }
 
@@ -79,6 +135,521 @@ async fn profile_page_handler(
         .context(ResponseBuilderSnafu)
 }
 
+#[derive(Clone)]
+struct UserEmailItem {
+    email: UserEmailView,
+    token: String,
+}
+
+#[derive(Template)]
+#[template(path = "pages/user/emails.html")]
+struct UserEmailsPageTemplate {
+    t: TemplateData,
+    primary_email: String,
+    emails: Vec<UserEmailItem>,
+    new_token: String,
+    error_message: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "widgets/user/emails.html")]
+struct UserEmailsTemplate {
+    primary_email: String,
+    emails: Vec<UserEmailItem>,
+    new_token: String,
+    error_message: Option<String>,
+}
+
+async fn build_user_emails(
+    state: &AppState,
+    user_id: &str,
+    error_message: Option<String>,
+) -> Result<UserEmailsTemplate> {
+    // Primary email may have just been swapped, read it fresh
+    let user = get_user_svc(state, user_id).await?;
+    let user = user.ok_or(Error::UserNotFound)?;
+
+    let emails = list_user_emails_svc(state, user_id)
+        .await?
+        .into_iter()
+        .map(|email| {
+            let token = create_csrf_token_svc(&email.id, &state.config.jwt_secret)?;
+            Ok(UserEmailItem {
+                email: email.into(),
+                token,
+            })
+        })
+        .collect::<Result<Vec<UserEmailItem>>>()?;
+
+    Ok(UserEmailsTemplate {
+        primary_email: user.email,
+        emails,
+        new_token: create_csrf_token_svc("new_user_email", &state.config.jwt_secret)?,
+        error_message,
+    })
+}
+
+/// Renders the emails widget after a mutation, surfacing its error if any
+async fn user_emails_response(
+    state: &AppState,
+    user_id: &str,
+    result: Result<()>,
+) -> Result<Response<Body>> {
+    let mut status = StatusCode::OK;
+    let mut error_message = None;
+
+    if let Err(err) = result {
+        let error_info = ErrorInfo::from(&err);
+        status = error_info.status_code;
+        error_message = Some(error_info.message);
+    }
+
+    let tpl = build_user_emails(state, user_id, error_message).await?;
+
+    Response::builder()
+        .status(status)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn user_emails_handler(
+    Extension(csp_nonce): Extension<CspNonce>,
+    Extension(ctx): Extension<Ctx>,
+    Extension(pref): Extension<Pref>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    let mut t = TemplateData::new(&state, ctx.actor.clone(), &pref, csp_nonce.nonce);
+    t.title = String::from("Emails");
+
+    let actor = ctx.actor().expect("actor is required");
+    let widget = build_user_emails(&state, &actor.user.id, None).await?;
+
+    let tpl = UserEmailsPageTemplate {
+        t,
+        primary_email: widget.primary_email,
+        emails: widget.emails,
+        new_token: widget.new_token,
+        error_message: None,
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn post_add_user_email_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Form(payload): Form<NewUserEmailFormData>,
+) -> Result<Response<Body>> {
+    let actor = ctx.actor().expect("actor is required");
+
+    let result = add_user_email_web_svc(&state, &actor.user.id, payload)
+        .await
+        .map(|_| ());
+
+    user_emails_response(&state, &actor.user.id, result).await
+}
+
+async fn post_verify_user_email_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(email_id): Path<String>,
+    Form(payload): Form<VerifyUserEmailFormData>,
+) -> Result<Response<Body>> {
+    let actor = ctx.actor().expect("actor is required");
+
+    let result = verify_user_email_web_svc(&state, &actor.user.id, &email_id, payload).await;
+
+    user_emails_response(&state, &actor.user.id, result).await
+}
+
+async fn post_promote_user_email_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(email_id): Path<String>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    let actor = ctx.actor().expect("actor is required");
+
+    let result =
+        promote_user_email_web_svc(&state, &actor.user.id, &email_id, &payload.token).await;
+
+    user_emails_response(&state, &actor.user.id, result).await
+}
+
+async fn post_remove_user_email_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(email_id): Path<String>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    let actor = ctx.actor().expect("actor is required");
+
+    let result = remove_user_email_web_svc(&state, &actor.user.id, &email_id, &payload.token).await;
+
+    user_emails_response(&state, &actor.user.id, result).await
+}
+
+#[derive(Template)]
+#[template(path = "pages/user/notifications.html")]
+struct NotificationPrefsPageTemplate {
+    t: TemplateData,
+    token: String,
+    delivery: String,
+    saved: bool,
+    error_message: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "widgets/user/notification_prefs_form.html")]
+struct NotificationPrefsFormTemplate {
+    token: String,
+    delivery: String,
+    saved: bool,
+    error_message: Option<String>,
+}
+
+async fn notification_prefs_handler(
+    Extension(csp_nonce): Extension<CspNonce>,
+    Extension(ctx): Extension<Ctx>,
+    Extension(pref): Extension<Pref>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    let mut t = TemplateData::new(&state, ctx.actor.clone(), &pref, csp_nonce.nonce);
+    t.title = String::from("Notifications");
+
+    let actor = ctx.actor().expect("actor is required");
+    let notification_pref = get_notification_pref_svc(&state, &actor.user.id).await?;
+
+    let tpl = NotificationPrefsPageTemplate {
+        t,
+        token: create_csrf_token_svc("notification_prefs", &state.config.jwt_secret)?,
+        delivery: notification_pref.delivery.to_string(),
+        saved: false,
+        error_message: None,
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn post_notification_prefs_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Form(payload): Form<NotificationPrefFormData>,
+) -> Result<Response<Body>> {
+    let actor = ctx.actor().expect("actor is required");
+
+    let mut tpl = NotificationPrefsFormTemplate {
+        token: create_csrf_token_svc("notification_prefs", &state.config.jwt_secret)?,
+        delivery: payload.delivery.clone(),
+        saved: false,
+        error_message: None,
+    };
+
+    let mut status = StatusCode::OK;
+
+    match update_notification_pref_web_svc(&state, &actor.user.id, payload).await {
+        Ok(saved) => {
+            tpl.delivery = saved.delivery.to_string();
+            tpl.saved = true;
+        }
+        Err(err) => {
+            let error_info = ErrorInfo::from(&err);
+            status = error_info.status_code;
+            tpl.error_message = Some(error_info.message);
+        }
+    }
+
+    Response::builder()
+        .status(status)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+#[derive(Template)]
+#[template(path = "pages/user/profile_details.html")]
+struct ProfileDetailsPageTemplate {
+    t: TemplateData,
+    token: String,
+    form: UserProfileFormData,
+    metadata_max_bytes: usize,
+    saved: bool,
+    error_message: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "widgets/user/profile_details_form.html")]
+struct ProfileDetailsFormTemplate {
+    token: String,
+    form: UserProfileFormData,
+    metadata_max_bytes: usize,
+    saved: bool,
+    error_message: Option<String>,
+}
+
+/// Form values of the saved profile, metadata is pretty printed for editing
+fn profile_details_form(profile: UserProfileDto, token: &str) -> UserProfileFormData {
+    let metadata = match profile.metadata.is_empty() {
+        true => String::new(),
+        false => serde_json::to_string_pretty(&profile.metadata).unwrap_or_default(),
+    };
+
+    UserProfileFormData {
+        token: token.to_string(),
+        avatar_url: profile.avatar_url.unwrap_or_default(),
+        locale: profile.locale.unwrap_or_default(),
+        timezone: profile.timezone.unwrap_or_default(),
+        phone: profile.phone.unwrap_or_default(),
+        metadata,
+    }
+}
+
+async fn profile_details_handler(
+    Extension(csp_nonce): Extension<CspNonce>,
+    Extension(ctx): Extension<Ctx>,
+    Extension(pref): Extension<Pref>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    let mut t = TemplateData::new(&state, ctx.actor.clone(), &pref, csp_nonce.nonce);
+    t.title = String::from("Profile Details");
+
+    let actor = ctx.actor().expect("actor is required");
+    let profile = get_user_profile_svc(&state, &actor.user.id).await?;
+    let token = create_csrf_token_svc("user_profile", &state.config.jwt_secret)?;
+
+    let tpl = ProfileDetailsPageTemplate {
+        t,
+        form: profile_details_form(profile, &token),
+        token,
+        metadata_max_bytes: USER_PROFILE_METADATA_MAX_BYTES,
+        saved: false,
+        error_message: None,
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn post_profile_details_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Form(payload): Form<UserProfileFormData>,
+) -> Result<Response<Body>> {
+    let actor = ctx.actor().expect("actor is required");
+    let token = create_csrf_token_svc("user_profile", &state.config.jwt_secret)?;
+
+    let mut tpl = ProfileDetailsFormTemplate {
+        token: token.clone(),
+        form: payload.clone(),
+        metadata_max_bytes: USER_PROFILE_METADATA_MAX_BYTES,
+        saved: false,
+        error_message: None,
+    };
+
+    let mut status = StatusCode::OK;
+
+    match update_user_profile_web_svc(&state, &actor.user.id, payload).await {
+        Ok(saved) => {
+            tpl.form = profile_details_form(saved, &token);
+            tpl.saved = true;
+        }
+        Err(err) => {
+            let error_info = ErrorInfo::from(&err);
+            status = error_info.status_code;
+            tpl.error_message = Some(error_info.message);
+        }
+    }
+
+    Response::builder()
+        .status(status)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+#[derive(Clone)]
+struct ConnectedAppItem {
+    app: AuthorizedAppView,
+    token: String,
+}
+
+#[derive(Template)]
+#[template(path = "pages/user/connected_apps.html")]
+struct ConnectedAppsPageTemplate {
+    t: TemplateData,
+    apps: Vec<ConnectedAppItem>,
+    empty_state: EmptyState,
+    error_message: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "widgets/user/connected_apps.html")]
+struct ConnectedAppsTemplate {
+    apps: Vec<ConnectedAppItem>,
+    empty_state: EmptyState,
+    error_message: Option<String>,
+}
+
+async fn build_connected_apps(state: &AppState, user_id: &str) -> Result<Vec<ConnectedAppItem>> {
+    let apps = list_authorized_apps_svc(state, user_id).await?;
+
+    apps.into_iter()
+        .map(|app| {
+            let token = create_csrf_token_svc(&app.app_id, &state.config.jwt_secret)?;
+            Ok(ConnectedAppItem {
+                app: app.into(),
+                token,
+            })
+        })
+        .collect()
+}
+
+async fn connected_apps_handler(
+    Extension(csp_nonce): Extension<CspNonce>,
+    Extension(ctx): Extension<Ctx>,
+    Extension(pref): Extension<Pref>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    let mut t = TemplateData::new(&state, ctx.actor.clone(), &pref, csp_nonce.nonce);
+    t.title = String::from("Connected Apps");
+
+    let actor = ctx.actor().expect("actor is required");
+
+    let tpl = ConnectedAppsPageTemplate {
+        t,
+        apps: build_connected_apps(&state, &actor.user.id).await?,
+        empty_state: EmptyState::suggestions("connected apps", None),
+        error_message: None,
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn post_revoke_connected_app_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(app_id): Path<String>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    let actor = ctx.actor().expect("actor is required");
+
+    let result =
+        revoke_authorized_app_web_svc(&state, &actor.user.id, &app_id, &payload.token).await;
+
+    let mut status = StatusCode::OK;
+    let mut error_message = None;
+
+    if let Err(err) = result {
+        let error_info = ErrorInfo::from(&err);
+        status = error_info.status_code;
+        error_message = Some(error_info.message);
+    }
+
+    let tpl = ConnectedAppsTemplate {
+        apps: build_connected_apps(&state, &actor.user.id).await?,
+        empty_state: EmptyState::suggestions("connected apps", None),
+        error_message,
+    };
+
+    Response::builder()
+        .status(status)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+#[derive(Clone)]
+struct UserSessionItem {
+    session: UserSessionView,
+    token: String,
+}
+
+#[derive(Template)]
+#[template(path = "widgets/user/sessions.html")]
+struct UserSessionsTemplate {
+    sessions: Vec<UserSessionItem>,
+    error_message: Option<String>,
+}
+
+async fn build_user_sessions(
+    state: &AppState,
+    user_id: &str,
+    cookies: &Cookies,
+) -> Result<Vec<UserSessionItem>> {
+    let current = cookie_session(state, cookies);
+    let current_id = current.as_ref().map(|(_, session_id)| session_id.as_str());
+    let sessions = list_user_sessions_svc(state, user_id, current_id).await?;
+
+    sessions
+        .into_iter()
+        .map(|session| {
+            let token = create_csrf_token_svc(&session.id, &state.config.jwt_secret)?;
+            Ok(UserSessionItem {
+                session: session.into(),
+                token,
+            })
+        })
+        .collect()
+}
+
+async fn active_sessions_handler(
+    cookies: Cookies,
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    let actor = ctx.actor().expect("actor is required");
+
+    let tpl = UserSessionsTemplate {
+        sessions: build_user_sessions(&state, &actor.user.id, &cookies).await?,
+        error_message: None,
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn post_revoke_active_session_handler(
+    cookies: Cookies,
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    let actor = ctx.actor().expect("actor is required");
+
+    let result =
+        revoke_user_session_web_svc(&state, &actor.user.id, &session_id, &payload.token).await;
+
+    let mut status = StatusCode::OK;
+    let mut error_message = None;
+
+    if let Err(err) = result {
+        let error_info = ErrorInfo::from(&err);
+        status = error_info.status_code;
+        error_message = Some(error_info.message);
+    }
+
+    let tpl = UserSessionsTemplate {
+        sessions: build_user_sessions(&state, &actor.user.id, &cookies).await?,
+        error_message,
+    };
+
+    Response::builder()
+        .status(status)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
 #[derive(Template)]
 #[template(path = "widgets/edit_profile_controls.html")]
 struct ProfileControlsTemplate {}
@@ -267,9 +838,23 @@ async fn post_switch_auth_context_handler(
     let user_id = ctx.actor().as_ref().expect("Actor is required").id.clone();
     let status: StatusCode;
 
+    // A switched token would no longer carry the impersonation
+    ensure!(
+        ctx.actor.impersonator_id().is_none(),
+        ForbiddenSnafu {
+            msg: "Stop impersonating before switching orgs".to_string(),
+        }
+    );
+
+    // Keep the token on the same session and home region
+    let session = cookie_session(&state, &cookies);
     let result = switch_auth_context_svc(
         &state,
         &user_id,
+        session.as_ref().map(|(_, session_id)| session_id.as_str()),
+        ctx.actor()
+            .as_ref()
+            .and_then(|actor| actor.home_region.as_deref()),
         SwitchAuthContextDto {
             org_id: payload.org_id.clone(),
         },
@@ -319,6 +904,7 @@ struct SearchOrgMembershipsTemplate {
     pagination: Option<PaginationLinks>,
     error_message: Option<String>,
     next_url: String,
+    empty_state: EmptyState,
 }
 async fn search_org_memberships_handler(
     Extension(ctx): Extension<Ctx>,
@@ -335,6 +921,7 @@ async fn search_org_memberships_handler(
         pagination: None,
         error_message: None,
         next_url: next_url.to_string(),
+        empty_state: EmptyState::suggestions("org memberships", keyword.as_deref()),
     };
 
     let user_id = ctx.actor().as_ref().expect("Actor is required").id.clone();
@@ -0,0 +1,87 @@
+use askama::Template;
+use axum::{
+    Extension,
+    body::Body,
+    extract::{Form, Query, State},
+    http::Response,
+    response::{IntoResponse, Redirect},
+};
+use snafu::ResultExt;
+use std::collections::HashMap;
+use urlencoding::encode;
+
+use crate::{
+    Error, Result,
+    dto::Actor,
+    error::{ResponseBuilderSnafu, TemplateSnafu},
+    models::{CspNonce, Pref, TemplateData},
+    run::AppState,
+    services::recovery::{RecoverAccountFormData, recover_account_svc},
+};
+
+#[derive(Template)]
+#[template(path = "pages/recover.html")]
+struct RecoverTemplate {
+    t: TemplateData,
+    token: String,
+    error_message: Option<String>,
+}
+
+pub async fn recover_handler(
+    Extension(csp_nonce): Extension<CspNonce>,
+    State(state): State<AppState>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Response<Body>> {
+    let pref = Pref::new();
+    let actor = Actor::default();
+    let mut t = TemplateData::new(&state, actor, &pref, csp_nonce.nonce);
+    t.title = String::from("Account Recovery");
+
+    let token = query.get("token").cloned().unwrap_or_default();
+    let error_message = query.get("error").cloned();
+
+    let tpl = RecoverTemplate {
+        t,
+        token,
+        error_message,
+    };
+
+    Response::builder()
+        .status(200)
+        .header("Surrogate-Control", "no-store")
+        .header(
+            "Cache-Control",
+            "no-store, no-cache, must-revalidate, proxy-revalidate",
+        )
+        .header("Pragma", "no-cache")
+        .header("Expires", 0)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+pub async fn post_recover_handler(
+    State(state): State<AppState>,
+    Form(payload): Form<RecoverAccountFormData>,
+) -> impl IntoResponse {
+    let token = payload.token.clone();
+
+    match recover_account_svc(&state, payload).await {
+        Ok(_) => {
+            let url = format!(
+                "/login?success={}",
+                encode("Account recovered. Login with your new password.")
+            );
+            Redirect::to(&url).into_response()
+        }
+        Err(err) => handle_submit_error(&token, err),
+    }
+}
+
+fn handle_submit_error(token: &str, error: Error) -> Response<Body> {
+    let url = format!(
+        "/recover?token={}&error={}",
+        encode(token),
+        encode(&error.to_string())
+    );
+    Redirect::to(&url).into_response()
+}
@@ -0,0 +1,184 @@
+use axum::{
+    extract::{Request, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::CookieJar;
+
+use crate::Error;
+use crate::config::RegionRouting;
+use crate::run::AppState;
+use crate::services::token::verify_auth_token;
+
+use super::AUTH_TOKEN_COOKIE;
+use super::oauth::bearer_token;
+
+/// Home region of the session, set on misrouted responses
+pub const SESSION_REGION_HEADER: &str = "x-session-region";
+
+/// Sends requests back to the region their session lives in.
+///
+/// Only applies when `REGION_ROUTING` is set. Tokens without a home region,
+/// or with an invalid signature, pass through for the handlers to deal with.
+pub async fn region_routing_middleware(
+    State(state): State<AppState>,
+    cookies: CookieJar,
+    req: Request,
+    next: Next,
+) -> Response {
+    let config = &state.config.region;
+    let Some(region) = config.name.as_deref() else {
+        return next.run(req).await;
+    };
+    if config.routing == RegionRouting::Off {
+        return next.run(req).await;
+    }
+
+    let token = bearer_token(req.headers()).or_else(|| {
+        cookies
+            .get(AUTH_TOKEN_COOKIE)
+            .map(|c| c.value().to_string())
+    });
+
+    let home_region = token
+        .and_then(|token| verify_auth_token(&token, &state.token_keys).ok())
+        .and_then(|payload| payload.home_region)
+        .filter(|home| home != region);
+
+    let Some(home_region) = home_region else {
+        return next.run(req).await;
+    };
+
+    let home_url = match config.routing {
+        RegionRouting::Redirect => config.urls.get(&home_region),
+        _ => None,
+    };
+
+    let mut res = match home_url {
+        Some(base_url) => {
+            let path = req
+                .uri()
+                .path_and_query()
+                .map(|pq| pq.as_str())
+                .unwrap_or("/");
+            Redirect::temporary(&format!("{}{}", base_url, path)).into_response()
+        }
+        None => Error::RegionMismatch {
+            region: home_region.clone(),
+        }
+        .into_response(),
+    };
+
+    if let Ok(value) = HeaderValue::from_str(&home_region) {
+        res.headers_mut().insert(SESSION_REGION_HEADER, value);
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, middleware, response::Response, routing::get};
+    use reqwest::StatusCode;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use crate::config::{RegionConfig, RegionRouting};
+    use crate::dto::{ActorPayloadDto, Scope};
+    use crate::error::ErrorInfo;
+    use crate::services::token::create_auth_token;
+    use crate::test::TestCtx;
+
+    use super::{SESSION_REGION_HEADER, region_routing_middleware};
+
+    #[tokio::test]
+    async fn requests_go_back_to_the_home_region() {
+        let ctx = TestCtx::new("region_routing").await.expect("test ctx");
+        let mut state = ctx.state.clone();
+        let mut config = (*state.config).clone();
+        config.region = RegionConfig {
+            name: Some("ap-southeast-1".to_string()),
+            routing: RegionRouting::Redirect,
+            urls: HashMap::from([(
+                "us-east-1".to_string(),
+                "https://us.example.com".to_string(),
+            )]),
+        };
+        state.config = Arc::new(config);
+
+        let app = Router::new()
+            .route("/oauth/profile", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                region_routing_middleware,
+            ))
+            // Stands in for the response mappers that render errors
+            .layer(middleware::map_response(|mut res: Response| async move {
+                if let Some(info) = res.extensions().get::<ErrorInfo>() {
+                    *res.status_mut() = info.status_code;
+                }
+                res
+            }))
+            .with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener should bind");
+        let base_url = format!("http://{}", listener.local_addr().expect("local addr"));
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        let token = |home: &str| {
+            create_auth_token(
+                &ActorPayloadDto {
+                    id: "usr_1".to_string(),
+                    org_id: "org_1".to_string(),
+                    org_count: 1,
+                    roles: Vec::new(),
+                    scopes: vec![Scope::Auth],
+                    grant_id: None,
+                    proof_key_id: None,
+                    permission_mask: None,
+                    issued_at: 0,
+                    session_id: None,
+                    region: Some(home.to_string()),
+                    home_region: Some(home.to_string()),
+                    token_id: None,
+                    org_app_id: None,
+                    impersonator_id: None,
+                    impersonation_id: None,
+                },
+                &state.token_keys,
+            )
+            .expect("token")
+        };
+
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("client");
+        let call = |home: &str| {
+            client
+                .get(format!("{}/oauth/profile?fields=id", base_url))
+                .bearer_auth(token(home))
+                .send()
+        };
+
+        let local = call("ap-southeast-1").await.expect("request");
+        assert_eq!(local.status(), StatusCode::OK);
+
+        let redirected = call("us-east-1").await.expect("request");
+        assert_eq!(redirected.status(), StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(
+            redirected.headers()["location"],
+            "https://us.example.com/oauth/profile?fields=id"
+        );
+        assert_eq!(redirected.headers()[SESSION_REGION_HEADER], "us-east-1");
+
+        // No URL known for the region
+        let rejected = call("eu-west-1").await.expect("request");
+        assert_eq!(rejected.status(), StatusCode::MISDIRECTED_REQUEST);
+        assert_eq!(rejected.headers()[SESSION_REGION_HEADER], "eu-west-1");
+    }
+}
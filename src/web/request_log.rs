@@ -0,0 +1,263 @@
+use axum::{
+    body::{Body, Bytes, HttpBody, to_bytes},
+    extract::Request,
+    http::{HeaderMap, Uri, header},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::Value;
+use tower_http::trace::MakeSpan;
+use tracing::{Level, Span, debug};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::utils::{extract_trace_context, redact_json, redact_query};
+
+/// Bodies larger than this are summarized instead of logged
+const REQUEST_LOG_MAX_BYTES: u64 = 64 * 1024;
+
+/// Set on every request before it is routed, and echoed in the response
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Id of the request, assigned by the request id layer when the caller sent none
+pub fn request_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+}
+
+/// Request span with sensitive query params masked.
+///
+/// Same fields as `DefaultMakeSpan`, which would log tokens passed in query
+/// strings such as `/recover?token=...`, plus the request id. The span
+/// continues the caller's trace when it sends a `traceparent` header.
+#[derive(Clone, Copy, Default)]
+pub struct RedactedMakeSpan;
+
+impl<B> MakeSpan<B> for RedactedMakeSpan {
+    fn make_span(&mut self, req: &axum::http::Request<B>) -> Span {
+        let request_id = request_id(req.headers()).unwrap_or_default();
+
+        let span = tracing::info_span!(
+            "request",
+            method = %req.method(),
+            uri = %redact_uri(req.uri()),
+            version = ?req.version(),
+            request_id = %request_id,
+        );
+
+        // Fails only when spans are not exported, nothing to link then
+        let _ = span.set_parent(extract_trace_context(req.headers()));
+        span
+    }
+}
+
+/// Logs request and response bodies at debug level with secrets masked.
+///
+/// Does nothing unless debug logging is enabled, bodies are only buffered
+/// when their size is known and small.
+pub async fn request_log_middleware(req: Request, next: Next) -> Response {
+    if !tracing::enabled!(Level::DEBUG) {
+        return next.run(req).await;
+    }
+
+    let (parts, body) = req.into_parts();
+    let (summary, body) = buffer_body(&parts.headers, body).await;
+    debug!(
+        method = %parts.method,
+        uri = %redact_uri(&parts.uri),
+        body = summary,
+        "http.request"
+    );
+
+    let res = next.run(Request::from_parts(parts, body)).await;
+
+    let (parts, body) = res.into_parts();
+    let (summary, body) = buffer_body(&parts.headers, body).await;
+    debug!(
+        status = parts.status.as_u16(),
+        body = summary,
+        "http.response"
+    );
+
+    Response::from_parts(parts, body)
+}
+
+fn redact_uri(uri: &Uri) -> String {
+    match uri.query() {
+        Some(query) => format!("{}?{}", uri.path(), redact_query(query)),
+        None => uri.path().to_string(),
+    }
+}
+
+/// Reads a small body for logging and hands back an equivalent body
+async fn buffer_body(headers: &HeaderMap, body: Body) -> (String, Body) {
+    let size = body.size_hint().exact();
+    let Some(size) = size.filter(|size| *size <= REQUEST_LOG_MAX_BYTES) else {
+        return ("[not buffered]".to_string(), body);
+    };
+
+    match to_bytes(body, size as usize).await {
+        Ok(bytes) => (redact_body(headers, &bytes), Body::from(bytes)),
+        Err(_) => ("[unreadable]".to_string(), Body::empty()),
+    }
+}
+
+fn redact_body(headers: &HeaderMap, bytes: &Bytes) -> String {
+    if bytes.is_empty() {
+        return String::new();
+    }
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    if content_type.starts_with("application/json") {
+        if let Ok(mut value) = serde_json::from_slice::<Value>(bytes) {
+            redact_json(&mut value);
+            return value.to_string();
+        }
+    } else if content_type.starts_with("application/x-www-form-urlencoded") {
+        return redact_query(&String::from_utf8_lossy(bytes));
+    }
+
+    format!("[{} bytes of {}]", bytes.len(), content_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Json, Router, middleware, routing::post};
+    use serde_json::{Value, json};
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+    use tower_http::trace::TraceLayer;
+    use tracing::{Level, info};
+    use tracing_subscriber::fmt::MakeWriter;
+
+    use super::{RedactedMakeSpan, request_log_middleware};
+
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().expect("log buffer").extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for LogBuffer {
+        type Writer = LogBuffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn debug_request_log_masks_secrets() {
+        let logs = LogBuffer::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(Level::DEBUG)
+            .with_ansi(false)
+            .with_writer(logs.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let app = Router::new()
+            .route(
+                "/login",
+                post(|Json(body): Json<Value>| async move {
+                    Json(json!({ "email": body["email"], "token": "issued-jwt" }))
+                }),
+            )
+            .layer(middleware::from_fn(request_log_middleware));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener should bind");
+        let addr = listener.local_addr().expect("local addr");
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        let res = reqwest::Client::new()
+            .post(format!("http://{}/login?token=query-secret", addr))
+            .json(&json!({ "email": "jane@example.com", "password": "hunter22" }))
+            .send()
+            .await
+            .expect("request");
+        let body: Value = res.json().await.expect("json");
+        assert_eq!(body["token"], "issued-jwt");
+
+        let logs = String::from_utf8(logs.0.lock().expect("log buffer").clone()).expect("utf8");
+        assert!(logs.contains("http.request"));
+        assert!(logs.contains("http.response"));
+        assert!(logs.contains("jane@example.com"));
+        for secret in ["hunter22", "issued-jwt", "query-secret"] {
+            assert!(!logs.contains(secret), "{} leaked into logs", secret);
+        }
+    }
+
+    #[tokio::test]
+    async fn request_span_carries_the_request_id() {
+        let logs = LogBuffer::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(Level::INFO)
+            .with_ansi(false)
+            .with_writer(logs.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let app = Router::new()
+            .route(
+                "/ping",
+                post(|| async {
+                    info!("pinged");
+                    "pong"
+                }),
+            )
+            .layer(PropagateRequestIdLayer::x_request_id())
+            .layer(TraceLayer::new_for_http().make_span_with(RedactedMakeSpan))
+            .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener should bind");
+        let addr = listener.local_addr().expect("local addr");
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        let client = reqwest::Client::new();
+        let res = client
+            .post(format!("http://{}/ping", addr))
+            .header("x-request-id", "req-from-website")
+            .send()
+            .await
+            .expect("request");
+        assert_eq!(res.headers()["x-request-id"], "req-from-website");
+
+        // Generated when the caller does not send one
+        let res = client
+            .post(format!("http://{}/ping", addr))
+            .send()
+            .await
+            .expect("request");
+        let generated = res.headers()["x-request-id"]
+            .to_str()
+            .expect("request id")
+            .to_string();
+        assert!(!generated.is_empty());
+
+        let logs = String::from_utf8(logs.0.lock().expect("log buffer").clone()).expect("utf8");
+        assert!(logs.contains("request_id=req-from-website"));
+        assert!(logs.contains(&format!("request_id={}", generated)));
+    }
+}
@@ -0,0 +1,164 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::CookieJar;
+
+use crate::Error;
+use crate::run::AppState;
+use crate::services::rollouts::{RouteVariant, route_variant};
+use crate::services::token::verify_auth_token;
+
+use super::AUTH_TOKEN_COOKIE;
+use super::oauth::bearer_token;
+
+/// Variant of a canaried route the response came from
+pub const ROUTE_VARIANT_HEADER: &str = "x-route-variant";
+
+/// Picks the variant of canaried routes listed in `ROUTE_ROLLOUTS`.
+///
+/// Handlers read the `RouteVariant` extension to serve the new implementation,
+/// every other route sees `RouteVariant::Stable`. Gated routes are hidden
+/// from requests outside the cohort.
+pub async fn route_rollout_middleware(
+    State(state): State<AppState>,
+    cookies: CookieJar,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+    let rollout = route
+        .as_deref()
+        .and_then(|route| state.config.route_rollouts.get(route).map(|r| (route, r)));
+
+    let Some((route, rollout)) = rollout else {
+        req.extensions_mut().insert(RouteVariant::Stable);
+        return next.run(req).await;
+    };
+
+    let token = bearer_token(req.headers()).or_else(|| {
+        cookies
+            .get(AUTH_TOKEN_COOKIE)
+            .map(|c| c.value().to_string())
+    });
+
+    // Only the signature is checked, revoked tokens fail in the handlers
+    let actor = token.and_then(|token| verify_auth_token(&token, &state.token_keys).ok());
+    let variant = route_variant(route, rollout, actor.as_ref());
+
+    if rollout.gated && variant == RouteVariant::Stable {
+        return Error::NotFound {
+            msg: "Not found".to_string(),
+        }
+        .into_response();
+    }
+
+    req.extensions_mut().insert(variant);
+    let mut res = next.run(req).await;
+    res.headers_mut().insert(
+        ROUTE_VARIANT_HEADER,
+        HeaderValue::from_static(variant.as_str()),
+    );
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Extension, Router, middleware, response::Response, routing::get};
+    use reqwest::StatusCode;
+    use std::sync::Arc;
+
+    use crate::config::RouteRollout;
+    use crate::dto::{ActorPayloadDto, Scope};
+    use crate::error::ErrorInfo;
+    use crate::services::rollouts::RouteVariant;
+    use crate::services::token::create_auth_token;
+    use crate::test::TestCtx;
+
+    use super::{ROUTE_VARIANT_HEADER, route_rollout_middleware};
+
+    #[tokio::test]
+    async fn canaried_routes_report_their_variant() {
+        let ctx = TestCtx::new("route_rollout").await.expect("test ctx");
+        let mut state = ctx.state.clone();
+        let mut config = (*state.config).clone();
+        config.route_rollouts =
+            RouteRollout::parse_all("/items/{id}=100,/hidden=0:user:gate").expect("rollouts");
+        state.config = Arc::new(config);
+
+        let variant_handler =
+            |Extension(variant): Extension<RouteVariant>| async move { variant.as_str() };
+        let app = Router::new()
+            .route("/items/{id}", get(variant_handler))
+            .route("/hidden", get(variant_handler))
+            .route("/plain", get(variant_handler))
+            .route_layer(middleware::from_fn_with_state(
+                state.clone(),
+                route_rollout_middleware,
+            ))
+            // Stands in for the response mappers that render errors
+            .layer(middleware::map_response(|mut res: Response| async move {
+                if let Some(info) = res.extensions().get::<ErrorInfo>() {
+                    *res.status_mut() = info.status_code;
+                }
+                res
+            }))
+            .with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("listener should bind");
+        let base_url = format!("http://{}", listener.local_addr().expect("local addr"));
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        let token = create_auth_token(
+            &ActorPayloadDto {
+                id: "usr_1".to_string(),
+                org_id: "org_1".to_string(),
+                org_count: 1,
+                roles: Vec::new(),
+                scopes: vec![Scope::Auth],
+                grant_id: None,
+                proof_key_id: None,
+                permission_mask: None,
+                issued_at: 0,
+                session_id: None,
+                region: None,
+                home_region: None,
+                token_id: None,
+                org_app_id: None,
+                impersonator_id: None,
+                impersonation_id: None,
+            },
+            &state.token_keys,
+        )
+        .expect("token");
+
+        let client = reqwest::Client::new();
+        let call = |path: &str| {
+            client
+                .get(format!("{}{}", base_url, path))
+                .bearer_auth(token.clone())
+                .send()
+        };
+
+        let canary = call("/items/1").await.expect("request");
+        assert_eq!(canary.status(), StatusCode::OK);
+        assert_eq!(canary.headers()[ROUTE_VARIANT_HEADER], "canary");
+        assert_eq!(canary.text().await.expect("body"), "canary");
+
+        let hidden = call("/hidden").await.expect("request");
+        assert_eq!(hidden.status(), StatusCode::NOT_FOUND);
+
+        let plain = call("/plain").await.expect("request");
+        assert!(plain.headers().get(ROUTE_VARIANT_HEADER).is_none());
+        assert_eq!(plain.text().await.expect("body"), "stable");
+    }
+}
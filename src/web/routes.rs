@@ -18,29 +18,49 @@ use crate::error::ErrorInfo;
 use crate::models::{CspNonce, Pref};
 use crate::run::AppState;
 use crate::web::{
-    apps_routes, error_handler, health_api_routes, index_handler, login_handler, logout_handler,
-    oauth_api_routes, oauth_authorize_handler, oauth_authorize_resume_handler, orgs_routes,
-    post_login_handler, post_setup_handler, profile_routes, setup_handler, users_routes,
+    accept_invitation_handler, accept_owner_transfer_handler, admin_api_routes,
+    admin_events_handler, approvals_routes, apps_routes, auth_rate_limit_middleware, drafts_routes,
+    error_handler, event_schema_routes, forgot_password_handler, grpc_routes, health_api_routes,
+    idempotency_middleware, index_handler, limits_api_routes, login_handler, logout_handler,
+    notifications_routes, oauth_api_routes, oauth_authorize_handler,
+    oauth_authorize_resume_handler, oauth_consent_handler, openapi_routes,
+    org_rate_limit_middleware, orgs_routes, palette_routes, permissions_routes,
+    post_accept_invitation_handler, post_accept_owner_transfer_handler,
+    post_forgot_password_handler, post_login_handler, post_oauth_consent_handler,
+    post_recover_handler, post_reset_password_handler, post_setup_handler,
+    post_stop_impersonating_handler, profile_routes, recover_handler, region_routing_middleware,
+    reset_password_handler, route_rollout_middleware, setup_handler, setup_status_handler,
+    users_routes,
 };
 
+use super::cache_headers::add_asset_cache_headers;
 use super::middleware::{
     auth_middleware, csp_nonce_middleware, pref_middleware, require_auth_middleware,
 };
 use super::security_headers::add_security_headers;
-use super::{dark_theme_handler, handle_error, light_theme_handler};
+use super::{
+    copy_preserved_headers, dark_theme_handler, handle_error, light_theme_handler, request_id,
+};
 
 pub fn all_routes(state: AppState, frontend_dir: &Path) -> Router {
+    // gRPC calls skip the browser oriented layers below
+    let grpc_router = grpc_routes(state.clone());
     let app_router = Router::new()
         .merge(public_routes(state.clone()))
         .merge(private_routes(state.clone()))
         .merge(health_api_routes(state.clone()))
+        .merge(limits_api_routes(state.clone()))
+        .merge(event_schema_routes(state.clone()))
+        .merge(openapi_routes(state.clone()))
         .merge(oauth_api_routes(state.clone()))
+        .merge(admin_api_routes(state.clone()))
         .fallback(any(error_handler).with_state(state))
         .layer(middleware::from_fn(add_security_headers))
         .layer(middleware::from_fn(csp_nonce_middleware));
 
     Router::new()
         .merge(assets_routes(frontend_dir))
+        .merge(grpc_router)
         .merge(app_router)
 }
 
@@ -62,6 +82,7 @@ pub fn assets_routes(dir: &Path) -> Router {
                     .not_found_service(file_not_found.into_service()),
             ),
         )
+        .layer(middleware::from_fn(add_asset_cache_headers))
 }
 
 async fn file_not_found() -> impl IntoResponse {
@@ -83,10 +104,33 @@ pub fn private_routes(state: AppState) -> Router {
         .route("/", get(index_handler))
         .route("/prefs/theme/light", post(light_theme_handler))
         .route("/prefs/theme/dark", post(dark_theme_handler))
+        .route("/events", get(admin_events_handler))
+        .route("/stop-impersonating", post(post_stop_impersonating_handler))
         .nest("/profile", profile_routes(state.clone()))
         .nest("/users", users_routes(state.clone()))
         .nest("/apps", apps_routes(state.clone()))
         .nest("/orgs", orgs_routes(state.clone()))
+        .nest("/palette", palette_routes(state.clone()))
+        .nest("/permissions", permissions_routes(state.clone()))
+        .nest("/approvals", approvals_routes(state.clone()))
+        .nest("/drafts", drafts_routes(state.clone()))
+        .nest("/notifications", notifications_routes(state.clone()))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            idempotency_middleware,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            route_rollout_middleware,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            org_rate_limit_middleware,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            region_routing_middleware,
+        ))
         .layer(GovernorLayer::new(governor_config))
         .layer(middleware::map_response_with_state(
             state.clone(),
@@ -118,12 +162,34 @@ pub fn public_routes(state: AppState) -> Router {
     Router::new()
         .route("/login", get(login_handler).post(post_login_handler))
         .route("/setup", get(setup_handler).post(post_setup_handler))
+        .route("/setup/status", get(setup_status_handler))
+        .route("/recover", get(recover_handler).post(post_recover_handler))
+        .route("/forgot-password", get(forgot_password_handler))
+        .route("/reset-password", get(reset_password_handler))
+        .route("/auth/forgot-password", post(post_forgot_password_handler))
+        .route("/auth/reset-password", post(post_reset_password_handler))
+        .route(
+            "/invitations/accept",
+            get(accept_invitation_handler).post(post_accept_invitation_handler),
+        )
+        .route(
+            "/owner-transfers/accept",
+            get(accept_owner_transfer_handler).post(post_accept_owner_transfer_handler),
+        )
         .route("/logout", post(logout_handler))
         .route("/oauth/authorize", get(oauth_authorize_handler))
+        .route(
+            "/oauth/consent",
+            get(oauth_consent_handler).post(post_oauth_consent_handler),
+        )
         .route(
             "/oauth/authorize/resume",
             get(oauth_authorize_resume_handler),
         )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth_rate_limit_middleware,
+        ))
         .layer(GovernorLayer::new(governor_config))
         .layer(middleware::map_response_with_state(
             state.clone(),
@@ -152,17 +218,30 @@ async fn response_mapper(
         }
 
         let full_page = headers.get("HX-Request").is_none();
-        return handle_error(
+        let mut mapped = handle_error(
             &state,
             ctx.actor.clone(),
             &pref,
             csp_nonce.nonce,
-            e.clone(),
+            ErrorInfo {
+                request_id: request_id(&headers),
+                ..e.clone()
+            },
             full_page,
         );
+        copy_preserved_headers(res.headers(), mapped.headers_mut());
+        return mapped;
     }
 
-    res.headers_mut()
-        .insert("Content-Type", "text/html; charset=utf-8".parse().unwrap());
+    // Event streams keep their own content type
+    let event_stream = res
+        .headers()
+        .get("Content-Type")
+        .is_some_and(|value| value.as_bytes().starts_with(b"text/event-stream"));
+
+    if !event_stream {
+        res.headers_mut()
+            .insert("Content-Type", "text/html; charset=utf-8".parse().unwrap());
+    }
     res
 }
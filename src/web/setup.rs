@@ -1,6 +1,6 @@
 use askama::Template;
 use axum::{
-    Extension,
+    Extension, Json,
     body::Body,
     extract::{Form, Query, State},
     http::Response,
@@ -13,7 +13,7 @@ use validator::Validate;
 
 use crate::{
     Error, Result,
-    dto::SetupBodyDto,
+    dto::{SetupBodyDto, SetupStatusDto},
     error::{ErrorInfo, ResponseBuilderSnafu, TemplateSnafu},
     models::{CspNonce, SetupFormPayload, TemplateData},
     run::AppState,
@@ -42,6 +42,7 @@ pub async fn setup_handler(
             status_code: axum::http::StatusCode::NOT_FOUND,
             title: String::from("Not Found"),
             message: String::from("The page you are looking for cannot be found."),
+            request_id: None,
         };
         return Ok(handle_error(
             &state,
@@ -75,32 +76,18 @@ pub async fn setup_handler(
         .context(ResponseBuilderSnafu)
 }
 
+pub async fn setup_status_handler(State(state): State<AppState>) -> Result<Json<SetupStatusDto>> {
+    let setup_done = setup_status_svc(&state).await?;
+
+    Ok(Json(SetupStatusDto {
+        setup_required: !setup_done,
+    }))
+}
+
 pub async fn post_setup_handler(
-    Extension(csp_nonce): Extension<CspNonce>,
     State(state): State<AppState>,
     Form(payload): Form<SetupFormPayload>,
 ) -> impl IntoResponse {
-    let setup_done = match setup_status_svc(&state).await {
-        Ok(done) => done,
-        Err(err) => return handle_submit_error(err),
-    };
-
-    if setup_done {
-        let not_found = ErrorInfo {
-            status_code: axum::http::StatusCode::NOT_FOUND,
-            title: String::from("Not Found"),
-            message: String::from("The page you are looking for cannot be found."),
-        };
-        return handle_error(
-            &state,
-            Actor::default(),
-            &Pref::new(),
-            csp_nonce.nonce,
-            not_found,
-            true,
-        );
-    }
-
     if let Err(err) = payload.validate() {
         let msg = flatten_errors(&err);
         return handle_submit_error(Error::Validation { msg });
@@ -0,0 +1,323 @@
+use askama::Template;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::{Extension, Form, body::Body, extract::State, response::Response};
+use axum::{
+    Router,
+    routing::{get, post},
+};
+use snafu::ResultExt;
+
+use crate::dto::{OrgDto, Permission, Role, TeamDto, TeamMemberDto};
+use crate::models::options::CheckboxOption;
+use crate::models::{CspNonce, Pref, TemplateData, TokenFormData};
+use crate::services::teams::{
+    TeamFormData, TeamMemberFormData, add_team_member_web_svc, create_team_web_svc,
+    delete_team_web_svc, get_team_svc, list_team_members_svc, list_teams_svc,
+    remove_team_member_web_svc, update_team_web_svc,
+};
+use crate::{
+    Result,
+    ctx::Ctx,
+    error::{ErrorInfo, ResponseBuilderSnafu, TemplateSnafu},
+    run::AppState,
+    services::token::create_csrf_token_svc,
+    web::{Action, Resource, create_role_options, enforce_org_policy},
+};
+
+pub fn teams_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", get(teams_page_handler).post(post_team_handler))
+        .route(
+            "/{team_id}",
+            get(team_page_handler).post(post_update_team_handler),
+        )
+        .route("/{team_id}/delete", post(post_delete_team_handler))
+        .route("/{team_id}/members", post(post_add_team_member_handler))
+        .route(
+            "/{team_id}/members/{user_id}/remove",
+            post(post_remove_team_member_handler),
+        )
+        .with_state(state)
+}
+
+fn team_role_options(roles: &[Role]) -> Vec<CheckboxOption> {
+    let selected: Vec<String> = roles.iter().map(|role| role.to_string()).collect();
+    create_role_options(&selected)
+}
+
+#[derive(Template)]
+#[template(path = "widgets/teams/index.html")]
+struct TeamsTemplate {
+    org: OrgDto,
+    teams: Vec<TeamDto>,
+    role_options: Vec<CheckboxOption>,
+    can_manage: bool,
+    token: String,
+    error_message: Option<String>,
+}
+
+impl TeamsTemplate {
+    async fn load(state: &AppState, ctx: &Ctx, org: OrgDto) -> Result<Self> {
+        let token = create_csrf_token_svc(&org.id, &state.config.jwt_secret)?;
+        let teams = list_teams_svc(state, &org.id).await?;
+
+        Ok(Self {
+            teams,
+            role_options: team_role_options(&[]),
+            can_manage: ctx.actor.has_permissions(&[Permission::OrgMembersManage]),
+            org,
+            token,
+            error_message: None,
+        })
+    }
+
+    fn into_response(self, result: Result<()>) -> Result<Response<Body>> {
+        let mut tpl = self;
+        let mut status = StatusCode::OK;
+
+        if let Err(err) = result {
+            let error_info = ErrorInfo::from(&err);
+            status = error_info.status_code;
+            tpl.error_message = Some(error_info.message);
+        }
+
+        Response::builder()
+            .status(status)
+            .body(Body::from(tpl.render().context(TemplateSnafu)?))
+            .context(ResponseBuilderSnafu)
+    }
+}
+
+#[derive(Template)]
+#[template(path = "pages/teams/index.html")]
+struct TeamsPageTemplate {
+    t: TemplateData,
+    org: OrgDto,
+    teams: Vec<TeamDto>,
+    role_options: Vec<CheckboxOption>,
+    can_manage: bool,
+    token: String,
+    error_message: Option<String>,
+}
+
+async fn teams_page_handler(
+    Extension(csp_nonce): Extension<CspNonce>,
+    Extension(ctx): Extension<Ctx>,
+    Extension(pref): Extension<Pref>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Team, Action::Read)?;
+
+    let mut t = TemplateData::new(&state, ctx.actor.clone(), &pref, csp_nonce.nonce);
+    t.title = format!("Teams - {}", &org.name);
+
+    let widget = TeamsTemplate::load(&state, &ctx, org).await?;
+    let tpl = TeamsPageTemplate {
+        t,
+        org: widget.org,
+        teams: widget.teams,
+        role_options: widget.role_options,
+        can_manage: widget.can_manage,
+        token: widget.token,
+        error_message: None,
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn post_team_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Form(pairs): Form<Vec<(String, String)>>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Team, Action::Create)?;
+
+    let result = match TeamFormData::try_from(pairs) {
+        Ok(payload) => create_team_web_svc(&state, &org.id, payload)
+            .await
+            .map(|_| ()),
+        Err(err) => Err(err),
+    };
+
+    TeamsTemplate::load(&state, &ctx, org)
+        .await?
+        .into_response(result)
+}
+
+#[derive(Template)]
+#[template(path = "widgets/teams/view.html")]
+struct TeamTemplate {
+    org: OrgDto,
+    team: TeamDto,
+    members: Vec<TeamMemberDto>,
+    role_options: Vec<CheckboxOption>,
+    can_manage: bool,
+    token: String,
+    error_message: Option<String>,
+}
+
+impl TeamTemplate {
+    async fn load(state: &AppState, ctx: &Ctx, org: OrgDto, team_id: &str) -> Result<Self> {
+        let token = create_csrf_token_svc(&org.id, &state.config.jwt_secret)?;
+        let team = get_team_svc(state, &org.id, team_id).await?;
+        let members = list_team_members_svc(state, &org.id, team_id).await?;
+
+        Ok(Self {
+            role_options: team_role_options(&team.roles),
+            team,
+            members,
+            can_manage: ctx.actor.has_permissions(&[Permission::OrgMembersManage]),
+            org,
+            token,
+            error_message: None,
+        })
+    }
+
+    fn into_response(self, result: Result<()>) -> Result<Response<Body>> {
+        let mut tpl = self;
+        let mut status = StatusCode::OK;
+
+        if let Err(err) = result {
+            let error_info = ErrorInfo::from(&err);
+            status = error_info.status_code;
+            tpl.error_message = Some(error_info.message);
+        }
+
+        Response::builder()
+            .status(status)
+            .body(Body::from(tpl.render().context(TemplateSnafu)?))
+            .context(ResponseBuilderSnafu)
+    }
+}
+
+#[derive(Template)]
+#[template(path = "pages/teams/view.html")]
+struct TeamPageTemplate {
+    t: TemplateData,
+    org: OrgDto,
+    team: TeamDto,
+    members: Vec<TeamMemberDto>,
+    role_options: Vec<CheckboxOption>,
+    can_manage: bool,
+    token: String,
+    error_message: Option<String>,
+}
+
+async fn team_page_handler(
+    Extension(csp_nonce): Extension<CspNonce>,
+    Extension(ctx): Extension<Ctx>,
+    Extension(pref): Extension<Pref>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Path((_org_id, team_id)): Path<(String, String)>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Team, Action::Read)?;
+
+    let widget = TeamTemplate::load(&state, &ctx, org, &team_id).await?;
+
+    let mut t = TemplateData::new(&state, ctx.actor.clone(), &pref, csp_nonce.nonce);
+    t.title = format!("Team - {}", &widget.team.name);
+
+    let tpl = TeamPageTemplate {
+        t,
+        org: widget.org,
+        team: widget.team,
+        members: widget.members,
+        role_options: widget.role_options,
+        can_manage: widget.can_manage,
+        token: widget.token,
+        error_message: None,
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn post_update_team_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Path((_org_id, team_id)): Path<(String, String)>,
+    Form(pairs): Form<Vec<(String, String)>>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Team, Action::Update)?;
+
+    let result = match TeamFormData::try_from(pairs) {
+        Ok(payload) => update_team_web_svc(&state, &org.id, &team_id, payload)
+            .await
+            .map(|_| ()),
+        Err(err) => Err(err),
+    };
+
+    TeamTemplate::load(&state, &ctx, org, &team_id)
+        .await?
+        .into_response(result)
+}
+
+async fn post_delete_team_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Path((_org_id, team_id)): Path<(String, String)>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Team, Action::Delete)?;
+
+    let tpl = TeamTemplate::load(&state, &ctx, org.clone(), &team_id).await?;
+    let result = delete_team_web_svc(&state, &org.id, &team_id, &payload.token).await;
+
+    match result {
+        Ok(_) => {
+            // Render same widget but trigger a redirect to the teams page
+            Response::builder()
+                .status(200)
+                .header("HX-Redirect", format!("/orgs/{}/teams", org.id))
+                .body(Body::from(tpl.render().context(TemplateSnafu)?))
+                .context(ResponseBuilderSnafu)
+        }
+        Err(err) => tpl.into_response(Err(err)),
+    }
+}
+
+async fn post_add_team_member_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Path((_org_id, team_id)): Path<(String, String)>,
+    Form(payload): Form<TeamMemberFormData>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Team, Action::Update)?;
+
+    let result = add_team_member_web_svc(&state, &org.id, &team_id, payload)
+        .await
+        .map(|_| ());
+
+    TeamTemplate::load(&state, &ctx, org, &team_id)
+        .await?
+        .into_response(result)
+}
+
+async fn post_remove_team_member_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(org): Extension<OrgDto>,
+    State(state): State<AppState>,
+    Path((_org_id, team_id, user_id)): Path<(String, String, String)>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    enforce_org_policy(&ctx.actor, &org.id, Resource::Team, Action::Update)?;
+
+    let result =
+        remove_team_member_web_svc(&state, &org.id, &team_id, &user_id, &payload.token).await;
+
+    TeamTemplate::load(&state, &ctx, org, &team_id)
+        .await?
+        .into_response(result)
+}
@@ -2,22 +2,37 @@ use askama::Template;
 use axum::extract::Query;
 use axum::http::StatusCode;
 use axum::{Extension, Form, body::Body, extract::State, response::Response};
-use axum::{Router, middleware, routing::get};
+use axum::{
+    Router, middleware,
+    routing::{get, post},
+};
 use snafu::{ResultExt, ensure};
+use tower_cookies::{Cookie, Cookies, cookie::time::Duration};
 use urlencoding::encode;
 use validator::Validate;
 
 use crate::dto::ListUsersParamsDto;
 use crate::dto::Permission;
 use crate::dto::UserDto;
-use crate::error::ValidationSnafu;
-use crate::models::{CspNonce, PaginationLinks, TokenFormData, UserView};
+use crate::dto::{USER_SORT, sort_query};
+use crate::error::{ForbiddenSnafu, ValidationSnafu};
+use crate::models::{
+    BulkStatusFormData, CspNonce, EmptyState, PaginationLinks, SortLinks, TokenFormData, UserView,
+};
+use crate::services::impersonations::{IMPERSONATION_MINS, start_impersonation_web_svc};
 use crate::services::password::change_user_password_web_svc;
+use crate::services::revocations::force_logout_web_svc;
+use crate::services::user_erasure::{
+    anonymize_user_svc, ensure_can_erase, erasure_confirmation_svc,
+};
 use crate::services::users::{
-    ChangePasswordFormData, create_user_web_svc, delete_user_web_svc, update_user_status_web_svc,
+    ChangePasswordFormData, bulk_update_user_status_web_svc, create_user_web_svc,
+    delete_user_web_svc, grant_superuser_web_svc, update_user_status_web_svc,
 };
 use crate::validators::flatten_errors;
+use crate::web::bulk::render_bulk_results;
 use crate::web::middleware::user_middleware;
+use crate::web::{AUTH_TOKEN_COOKIE, IMPERSONATOR_TOKEN_COOKIE};
 use crate::{
     Error, Result,
     ctx::Ctx,
@@ -35,6 +50,7 @@ pub fn users_routes(state: AppState) -> Router<AppState> {
     Router::new()
         .route("/", get(users_handler))
         .route("/search", get(search_users_handler))
+        .route("/bulk-status", post(post_bulk_user_status_handler))
         .route("/new", get(new_user_handler).post(post_new_user_handler))
         .nest("/{user_id}", user_inner_routes(state.clone()))
         .with_state(state)
@@ -52,10 +68,26 @@ fn user_inner_routes(state: AppState) -> Router<AppState> {
             "/change-password",
             get(change_password_handler).post(post_change_password_handler),
         )
+        .route(
+            "/grant-superuser",
+            get(grant_superuser_handler).post(post_grant_superuser_handler),
+        )
+        .route(
+            "/force-logout",
+            get(force_logout_handler).post(post_force_logout_handler),
+        )
+        .route(
+            "/impersonate",
+            get(impersonate_handler).post(post_impersonate_handler),
+        )
         .route(
             "/delete",
             get(delete_user_handler).post(post_delete_user_handler),
         )
+        .route(
+            "/anonymize",
+            get(anonymize_user_handler).post(post_anonymize_user_handler),
+        )
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
             user_middleware,
@@ -107,6 +139,9 @@ struct SearchUsersTemplate {
     users: Vec<UserView>,
     pagination: Option<PaginationLinks>,
     error_message: Option<String>,
+    empty_state: EmptyState,
+    sorting: SortLinks,
+    bulk_token: Option<String>,
 }
 async fn search_users_handler(
     Extension(ctx): Extension<Ctx>,
@@ -119,9 +154,28 @@ async fn search_users_handler(
         users: Vec::new(),
         pagination: None,
         error_message: None,
+        empty_state: EmptyState::users(&ctx.actor, query.keyword.as_deref()),
+        sorting: SortLinks::new(
+            &USER_SORT,
+            query.sort_by.as_deref(),
+            query.sort_dir.as_deref(),
+            "/users/search",
+            "/users",
+            query.keyword.as_deref(),
+            ".album-items",
+        ),
+        bulk_token: None,
     };
 
+    if ctx.actor.has_permissions(&[Permission::UsersEdit]) {
+        tpl.bulk_token = Some(create_csrf_token_svc(
+            "bulk_users",
+            &state.config.jwt_secret,
+        )?);
+    }
+
     let keyword = query.keyword.clone();
+    let sort_param = sort_query(query.sort_by.as_deref(), query.sort_dir.as_deref());
 
     match list_users_svc(&state, query).await {
         Ok(users) => {
@@ -134,7 +188,7 @@ async fn search_users_handler(
                 &users.meta,
                 "/users/search",
                 "/users",
-                &keyword_param,
+                &format!("{}{}", keyword_param, sort_param),
                 ".album-items",
             ));
 
@@ -155,6 +209,23 @@ async fn search_users_handler(
     }
 }
 
+async fn post_bulk_user_status_handler(
+    Extension(ctx): Extension<Ctx>,
+    State(state): State<AppState>,
+    Form(pairs): Form<Vec<(String, String)>>,
+) -> Result<Response<Body>> {
+    enforce_policy(&ctx.actor, Resource::User, Action::Update)?;
+
+    let actor_id = ctx.actor().expect("Actor is required").id.clone();
+
+    let result = match BulkStatusFormData::try_from(pairs) {
+        Ok(form) => bulk_update_user_status_web_svc(&state, &actor_id, form).await,
+        Err(err) => Err(err),
+    };
+
+    render_bulk_results(result)
+}
+
 #[derive(Template)]
 #[template(path = "pages/users/new.html")]
 struct NewUserTemplate {
@@ -276,6 +347,8 @@ struct UserPageTemplate {
     updated: bool,
     can_edit: bool,
     can_delete: bool,
+    can_erase: bool,
+    can_grant_superuser: bool,
 }
 
 async fn user_page_handler(
@@ -295,6 +368,8 @@ async fn user_page_handler(
         updated: false,
         can_edit: ctx.actor.has_permissions(&[Permission::UsersEdit]),
         can_delete: ctx.actor.has_permissions(&[Permission::UsersDelete]),
+        can_erase: ctx.actor.has_permissions(&[Permission::UsersErase]),
+        can_grant_superuser: ctx.actor.is_system_admin(),
     };
 
     Response::builder()
@@ -310,6 +385,8 @@ struct UserControlsTemplate {
     updated: bool,
     can_edit: bool,
     can_delete: bool,
+    can_erase: bool,
+    can_grant_superuser: bool,
 }
 
 async fn user_controls_handler(
@@ -323,6 +400,8 @@ async fn user_controls_handler(
         updated: false,
         can_edit: ctx.actor.has_permissions(&[Permission::UsersEdit]),
         can_delete: ctx.actor.has_permissions(&[Permission::UsersDelete]),
+        can_erase: ctx.actor.has_permissions(&[Permission::UsersErase]),
+        can_grant_superuser: ctx.actor.is_system_admin(),
     };
 
     Response::builder()
@@ -408,6 +487,8 @@ async fn post_update_user_status_handler(
                 updated: true,
                 can_edit: ctx.actor.has_permissions(&[Permission::UsersEdit]),
                 can_delete: ctx.actor.has_permissions(&[Permission::UsersDelete]),
+                can_erase: ctx.actor.has_permissions(&[Permission::UsersErase]),
+                can_grant_superuser: ctx.actor.is_system_admin(),
             };
 
             Ok(Response::builder()
@@ -515,6 +596,8 @@ async fn post_change_password_handler(
                 updated: false,
                 can_edit: ctx.actor.has_permissions(&[Permission::UsersEdit]),
                 can_delete: ctx.actor.has_permissions(&[Permission::UsersDelete]),
+                can_erase: ctx.actor.has_permissions(&[Permission::UsersErase]),
+                can_grant_superuser: ctx.actor.is_system_admin(),
             };
 
             Ok(Response::builder()
@@ -627,3 +710,329 @@ async fn post_delete_user_handler(
         }
     }
 }
+
+#[derive(Template)]
+#[template(path = "widgets/users/anonymize_form.html")]
+struct AnonymizeUserFormTemplate {
+    user: UserDto,
+    payload: TokenFormData,
+    error_message: Option<String>,
+}
+
+/// Confirmation form, the token it carries is only issued for users that can be erased
+async fn anonymize_user_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(user): Extension<UserDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    ensure_can_erase(&ctx)?;
+
+    let actor = ctx.actor().expect("actor is required");
+    let mut tpl = AnonymizeUserFormTemplate {
+        user,
+        payload: TokenFormData {
+            token: "".to_string(),
+        },
+        error_message: None,
+    };
+
+    match erasure_confirmation_svc(&state, &actor.id, &tpl.user.id).await {
+        Ok(confirmation) => {
+            tpl.payload.token = confirmation.confirmation_token;
+        }
+        Err(err) => {
+            tpl.error_message = Some(ErrorInfo::from(&err).message);
+        }
+    }
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn post_anonymize_user_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(user): Extension<UserDto>,
+    State(state): State<AppState>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    ensure_can_erase(&ctx)?;
+
+    let actor = ctx.actor().expect("actor is required");
+
+    match anonymize_user_svc(&state, &actor.id, &user.id, &payload.token).await {
+        Ok(erased) => Response::builder()
+            .status(200)
+            .header("HX-Redirect", format!("/users/{}", erased.user.id))
+            .body(Body::from("Erased".to_string()))
+            .context(ResponseBuilderSnafu),
+        Err(err) => {
+            let error_info = ErrorInfo::from(&err);
+            let tpl = AnonymizeUserFormTemplate {
+                user,
+                payload: TokenFormData {
+                    token: "".to_string(),
+                },
+                error_message: Some(error_info.message),
+            };
+
+            Response::builder()
+                .status(error_info.status_code)
+                .body(Body::from(tpl.render().context(TemplateSnafu)?))
+                .context(ResponseBuilderSnafu)
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "widgets/users/force_logout_form.html")]
+struct ForceLogoutFormTemplate {
+    user: UserDto,
+    payload: TokenFormData,
+    error_message: Option<String>,
+    revoked: bool,
+}
+
+async fn force_logout_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(user): Extension<UserDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    enforce_policy(&ctx.actor, Resource::User, Action::Update)?;
+
+    let token = create_csrf_token_svc(&user.id, &state.config.jwt_secret)?;
+
+    let tpl = ForceLogoutFormTemplate {
+        user,
+        payload: TokenFormData { token },
+        error_message: None,
+        revoked: false,
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn post_force_logout_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(user): Extension<UserDto>,
+    State(state): State<AppState>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    enforce_policy(&ctx.actor, Resource::User, Action::Update)?;
+
+    let actor = ctx.actor().expect("actor is required");
+    let user_id = user.id.clone();
+    let token = create_csrf_token_svc(&user.id, &state.config.jwt_secret)?;
+
+    let mut tpl = ForceLogoutFormTemplate {
+        user,
+        payload: TokenFormData { token },
+        error_message: None,
+        revoked: false,
+    };
+
+    let mut status = StatusCode::OK;
+
+    match force_logout_web_svc(&state, &actor.user.id, &user_id, &payload.token).await {
+        Ok(_) => {
+            tpl.revoked = true;
+        }
+        Err(err) => {
+            let error_info = ErrorInfo::from(&err);
+            status = error_info.status_code;
+            tpl.error_message = Some(error_info.message);
+        }
+    }
+
+    Response::builder()
+        .status(status)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+#[derive(Template)]
+#[template(path = "widgets/users/impersonate_form.html")]
+struct ImpersonateFormTemplate {
+    user: UserDto,
+    payload: TokenFormData,
+    error_message: Option<String>,
+}
+
+async fn impersonate_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(user): Extension<UserDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    ensure!(
+        ctx.actor.is_system_admin(),
+        ForbiddenSnafu {
+            msg: "Only superusers can impersonate users."
+        }
+    );
+
+    let token = create_csrf_token_svc(&user.id, &state.config.jwt_secret)?;
+
+    let tpl = ImpersonateFormTemplate {
+        user,
+        payload: TokenFormData { token },
+        error_message: None,
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn post_impersonate_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(user): Extension<UserDto>,
+    State(state): State<AppState>,
+    cookies: Cookies,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    ensure!(
+        ctx.actor.is_system_admin(),
+        ForbiddenSnafu {
+            msg: "Only superusers can impersonate users."
+        }
+    );
+
+    let actor = ctx.actor().expect("actor is required");
+
+    match start_impersonation_web_svc(&state, &actor.id, &user.id, &payload.token).await {
+        Ok(impersonation) => {
+            // Keep the superuser's own token to switch back once done
+            if let Some(current) = cookies.get(AUTH_TOKEN_COOKIE) {
+                let impersonator_cookie =
+                    Cookie::build((IMPERSONATOR_TOKEN_COOKIE, current.value().to_string()))
+                        .http_only(true)
+                        .max_age(Duration::weeks(1))
+                        .secure(state.config.server.https)
+                        .same_site(tower_cookies::cookie::SameSite::Strict)
+                        .path("/")
+                        .build();
+                cookies.add(impersonator_cookie);
+            }
+
+            let auth_cookie = Cookie::build((AUTH_TOKEN_COOKIE, impersonation.token))
+                .http_only(true)
+                .max_age(Duration::minutes(IMPERSONATION_MINS))
+                .secure(state.config.server.https)
+                .same_site(tower_cookies::cookie::SameSite::Strict)
+                .path("/")
+                .build();
+            cookies.add(auth_cookie);
+
+            Response::builder()
+                .status(200)
+                .header("HX-Redirect", "/")
+                .body(Body::from("Impersonating".to_string()))
+                .context(ResponseBuilderSnafu)
+        }
+        Err(err) => {
+            let error_info = ErrorInfo::from(&err);
+            let token = create_csrf_token_svc(&user.id, &state.config.jwt_secret)?;
+            let tpl = ImpersonateFormTemplate {
+                user,
+                payload: TokenFormData { token },
+                error_message: Some(error_info.message),
+            };
+
+            Response::builder()
+                .status(error_info.status_code)
+                .body(Body::from(tpl.render().context(TemplateSnafu)?))
+                .context(ResponseBuilderSnafu)
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "widgets/users/grant_superuser_form.html")]
+struct GrantSuperuserFormTemplate {
+    user: UserDto,
+    payload: TokenFormData,
+    error_message: Option<String>,
+    approval_message: Option<String>,
+    granted: bool,
+}
+
+async fn grant_superuser_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(user): Extension<UserDto>,
+    State(state): State<AppState>,
+) -> Result<Response<Body>> {
+    ensure!(
+        ctx.actor.is_system_admin(),
+        ForbiddenSnafu {
+            msg: "Only superusers can grant superuser access."
+        }
+    );
+
+    let token = create_csrf_token_svc(&user.id, &state.config.jwt_secret)?;
+
+    let tpl = GrantSuperuserFormTemplate {
+        user,
+        payload: TokenFormData { token },
+        error_message: None,
+        approval_message: None,
+        granted: false,
+    };
+
+    Response::builder()
+        .status(200)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}
+
+async fn post_grant_superuser_handler(
+    Extension(ctx): Extension<Ctx>,
+    Extension(user): Extension<UserDto>,
+    State(state): State<AppState>,
+    payload: Form<TokenFormData>,
+) -> Result<Response<Body>> {
+    ensure!(
+        ctx.actor.is_system_admin(),
+        ForbiddenSnafu {
+            msg: "Only superusers can grant superuser access."
+        }
+    );
+
+    let actor = ctx.actor().expect("actor is required");
+    let user_id = user.id.clone();
+    let token = create_csrf_token_svc(&user.id, &state.config.jwt_secret)?;
+
+    let mut tpl = GrantSuperuserFormTemplate {
+        user,
+        payload: TokenFormData { token },
+        error_message: None,
+        approval_message: None,
+        granted: false,
+    };
+
+    let mut status = StatusCode::OK;
+
+    match grant_superuser_web_svc(&state, &actor.user.id, &user_id, &payload.token).await {
+        Ok(_) => {
+            tpl.granted = true;
+        }
+        Err(Error::ApprovalRequired { msg }) => {
+            status = StatusCode::ACCEPTED;
+            tpl.approval_message = Some(msg);
+        }
+        Err(err) => {
+            let error_info = ErrorInfo::from(&err);
+            status = error_info.status_code;
+            tpl.error_message = Some(error_info.message);
+        }
+    }
+
+    Response::builder()
+        .status(status)
+        .body(Body::from(tpl.render().context(TemplateSnafu)?))
+        .context(ResponseBuilderSnafu)
+}